@@ -1,17 +1,20 @@
 use crate::act::{Action, ActionVariant, Acts};
 use crate::adapter::PgAdapter;
-use crate::enforcer::{AFEnforcer, NoEnforceGroup};
+use crate::collab::CollabHierarchy;
+use crate::enforcer::AFEnforcer;
+use crate::group::UserGroups;
 use crate::metrics::{tick_metric, AccessControlMetrics};
 
 use anyhow::anyhow;
 use app_error::AppError;
 use casbin::rhai::ImmutableString;
 use casbin::{CoreApi, DefaultModel, Enforcer, MgmtApi};
-use database_entity::dto::{AFAccessLevel, AFRole};
+use database_entity::dto::{AFAccessLevel, AFRole, WorkspaceCapability};
 use lazy_static::lazy_static;
 
 use sqlx::PgPool;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::broadcast;
 use tracing::trace;
@@ -36,10 +39,12 @@ pub enum AccessControlChange {
 /// according to the model defined.
 #[derive(Clone)]
 pub struct AccessControl {
-  enforcer: Arc<AFEnforcer<NoEnforceGroup>>,
+  enforcer: Arc<AFEnforcer<UserGroups>>,
+  user_groups: UserGroups,
   #[allow(dead_code)]
   access_control_metrics: Arc<AccessControlMetrics>,
   change_tx: broadcast::Sender<AccessControlChange>,
+  collab_hierarchy: Option<Arc<dyn CollabHierarchy>>,
 }
 
 impl AccessControl {
@@ -57,7 +62,8 @@ impl AccessControl {
       |r: ImmutableString, p: ImmutableString| cmp_role_or_level(r.as_str(), p.as_str()),
     );
 
-    let enforcer = Arc::new(AFEnforcer::new(enforcer, NoEnforceGroup).await?);
+    let user_groups = UserGroups::load(&pg_pool).await?;
+    let enforcer = Arc::new(AFEnforcer::new(enforcer, user_groups.clone()).await?);
     tick_metric(
       enforcer.metrics_state.clone(),
       access_control_metrics.clone(),
@@ -65,11 +71,27 @@ impl AccessControl {
     let (change_tx, _) = broadcast::channel(1000);
     Ok(Self {
       enforcer,
+      user_groups,
       access_control_metrics,
       change_tx,
+      collab_hierarchy: None,
     })
   }
 
+  /// Let the collabs inherit the access their users hold on the ancestor views, as resolved by
+  /// the given hierarchy. Without a hierarchy, only the policies on the collab itself apply.
+  pub fn with_collab_hierarchy(mut self, collab_hierarchy: Arc<dyn CollabHierarchy>) -> Self {
+    self.collab_hierarchy = Some(collab_hierarchy);
+    self
+  }
+
+  /// Must be called after the folder or the inheritance settings of the workspace changed.
+  pub async fn invalidate_collab_hierarchy(&self, workspace_id: &str) {
+    if let Some(collab_hierarchy) = &self.collab_hierarchy {
+      collab_hierarchy.invalidate(workspace_id).await;
+    }
+  }
+
   pub fn subscribe_change(&self) -> broadcast::Receiver<AccessControlChange> {
     self.change_tx.subscribe()
   }
@@ -93,6 +115,62 @@ impl AccessControl {
     }
   }
 
+  /// Replace the capabilities the user holds in the workspace on top of the default capabilities
+  /// of its role.
+  pub async fn update_workspace_capabilities(
+    &self,
+    uid: &i64,
+    workspace_id: &str,
+    capabilities: &[WorkspaceCapability],
+  ) -> Result<(), AppError> {
+    if enable_access_control() {
+      self
+        .enforcer
+        .remove_capability_policies(uid, &ObjectType::Workspace(workspace_id))
+        .await?;
+      for capability in capabilities {
+        self
+          .enforcer
+          .update_policy(
+            uid,
+            ObjectType::Workspace(workspace_id),
+            ActionVariant::FromCapability(capability),
+          )
+          .await?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Replace the role of the user in the workspace. Unlike [AccessControl::update_policy], the
+  /// role the user held before is removed, along with what it granted.
+  pub async fn replace_workspace_role(
+    &self,
+    uid: &i64,
+    workspace_id: &str,
+    role: &AFRole,
+  ) -> Result<(), AppError> {
+    if enable_access_control() {
+      self
+        .enforcer
+        .remove_role_policies(uid, &ObjectType::Workspace(workspace_id))
+        .await?;
+      self
+        .enforcer
+        .update_policy(
+          uid,
+          ObjectType::Workspace(workspace_id),
+          ActionVariant::FromRole(role),
+        )
+        .await?;
+      let _ = self.change_tx.send(AccessControlChange::UpdatePolicy {
+        uid: *uid,
+        oid: workspace_id.to_string(),
+      });
+    }
+    Ok(())
+  }
+
   pub async fn remove_policy(&self, uid: &i64, obj: &ObjectType<'_>) -> Result<(), AppError> {
     if enable_access_control() {
       self.enforcer.remove_policy(uid, obj).await?;
@@ -106,6 +184,77 @@ impl AccessControl {
     }
   }
 
+  /// Replace the policy of the group on the object. Every member of the group is allowed what
+  /// the group is allowed.
+  pub async fn update_group_policy(
+    &self,
+    guid: &str,
+    obj: ObjectType<'_>,
+    act: ActionVariant<'_>,
+  ) -> Result<(), AppError> {
+    if enable_access_control() {
+      self.enforcer.remove_policy(guid, &obj).await?;
+      self.enforcer.update_policy(guid, obj, act).await?;
+    }
+    Ok(())
+  }
+
+  pub async fn remove_group_policy(
+    &self,
+    guid: &str,
+    obj: &ObjectType<'_>,
+  ) -> Result<(), AppError> {
+    if enable_access_control() {
+      self.enforcer.remove_policy(guid, obj).await?;
+    }
+    Ok(())
+  }
+
+  pub async fn add_group_member(&self, uid: &i64, guid: &str) {
+    self.user_groups.insert(*uid, guid).await;
+  }
+
+  pub async fn remove_group_member(&self, uid: &i64, guid: &str) {
+    self.user_groups.remove(*uid, guid).await;
+  }
+
+  /// Returns the access level of each user that has a policy on the given object, as currently
+  /// held by the enforcer. When a user has more than one access level policy on the object, the
+  /// highest one is returned.
+  pub async fn get_access_levels(
+    &self,
+    obj: &ObjectType<'_>,
+  ) -> Result<Vec<(i64, AFAccessLevel)>, AppError> {
+    if !enable_access_control() {
+      return Ok(vec![]);
+    }
+
+    let mut levels: HashMap<i64, AFAccessLevel> = HashMap::new();
+    for policy in self.enforcer.policies_for_object(obj).await {
+      let (subject, act) = (
+        &policy[POLICY_FIELD_INDEX_SUBJECT],
+        &policy[POLICY_FIELD_INDEX_ACTION],
+      );
+      // Only user access level policies are relevant, e.g. `1, collab::<oid>, l:30`
+      let uid = match subject.parse::<i64>() {
+        Ok(uid) => uid,
+        Err(_) => continue,
+      };
+      if !act.starts_with("l:") {
+        continue;
+      }
+      let level = AFAccessLevel::from_enforce_act(act);
+      levels
+        .entry(uid)
+        .and_modify(|current| *current = (*current).max(level))
+        .or_insert(level);
+    }
+
+    let mut levels: Vec<(i64, AFAccessLevel)> = levels.into_iter().collect();
+    levels.sort_by_key(|(uid, _)| *uid);
+    Ok(levels)
+  }
+
   pub async fn enforce(
     &self,
     workspace_id: &str,
@@ -113,14 +262,64 @@ impl AccessControl {
     obj: ObjectType<'_>,
     act: ActionVariant<'_>,
   ) -> Result<bool, AppError> {
-    if enable_access_control() {
-      self
-        .enforcer
-        .enforce_policy(workspace_id, uid, obj, act)
-        .await
-    } else {
-      Ok(true)
+    if !enable_access_control() {
+      return Ok(true);
+    }
+
+    let oid = match obj {
+      ObjectType::Collab(oid) => oid,
+      ObjectType::Workspace(_) => {
+        return self
+          .enforcer
+          .enforce_policy(workspace_id, uid, obj, act)
+          .await
+      },
+    };
+    if self
+      .enforcer
+      .enforce_policy(workspace_id, uid, ObjectType::Collab(oid), act)
+      .await?
+    {
+      return Ok(true);
+    }
+    self.enforce_inherited(workspace_id, uid, oid, act).await
+  }
+
+  /// The nearest membership wins: a user that holds a policy on the collab is never granted more
+  /// by its ancestors, otherwise the policy on the nearest ancestor the user is a member of
+  /// decides.
+  async fn enforce_inherited(
+    &self,
+    workspace_id: &str,
+    uid: &i64,
+    oid: &str,
+    act: ActionVariant<'_>,
+  ) -> Result<bool, AppError> {
+    let collab_hierarchy = match &self.collab_hierarchy {
+      Some(collab_hierarchy) => collab_hierarchy,
+      None => return Ok(false),
+    };
+    if self
+      .enforcer
+      .has_policy(uid, &ObjectType::Collab(oid))
+      .await
+    {
+      return Ok(false);
+    }
+
+    for ancestor_id in collab_hierarchy
+      .inherited_ancestors(workspace_id, oid)
+      .await?
+    {
+      let ancestor = ObjectType::Collab(&ancestor_id);
+      if self.enforcer.has_policy(uid, &ancestor).await {
+        return self
+          .enforcer
+          .enforce_object_policy(uid, &ancestor, &act)
+          .await;
+      }
     }
+    Ok(false)
   }
 }
 
@@ -135,6 +334,10 @@ impl AccessControl {
 /// - p3 = sub=guid, obj=object_id, act=access_level
 ///   - Defines the access level (`access_level`) a group (`guid`) has for an object (`object_id`).
 ///
+/// - p4 = sub=uid, obj=workspace_id, act=capability
+///   - Grants a capability (`capability`) to a user (`uid`) in a workspace, on top of the
+///     capabilities its role grants.
+///
 /// ## Role Definitions in Database:
 /// Roles and access levels are defined with the following mappings:
 /// - **Role "1" (Owner):** Can `delete`, `write`, and `read`.
@@ -207,7 +410,7 @@ pub async fn casbin_model() -> Result<DefaultModel, AppError> {
 /// it is designed to compare roles or access levels specified in the request and policy.
 /// It supports two prefixes: "r:" for roles and "l:" for access levels. When the prefixes match,
 /// it compares the values to determine if the policy's role or level is greater than or equal to
-/// the request's role or level.
+/// the request's role or level. A capability, prefixed with "c:", is only granted by itself.
 ///
 /// # Arguments
 /// * `r_act` - The role or access level from the request, prefixed with "r:" for roles or "l:" for levels.
@@ -228,6 +431,12 @@ pub fn cmp_role_or_level(r_act: &str, p_act: &str) -> bool {
     return p >= r;
   }
 
+  // A capability is only granted by the same capability, the roles grant their default
+  // capabilities through the grouping policies
+  if r_act.starts_with(CAPABILITY_ACT_PREFIX) {
+    return r_act == p_act;
+  }
+
   if r_act.starts_with("l:") && p_act.starts_with("r:") {
     let r = AFAccessLevel::from_enforce_act(r_act);
     let role = AFRole::from_enforce_act(p_act);
@@ -238,6 +447,12 @@ pub fn cmp_role_or_level(r_act: &str, p_act: &str) -> bool {
   false
 }
 
+/// The prefix of the acts of the [AFRole] policies.
+pub const ROLE_ACT_PREFIX: &str = "r:";
+
+/// The prefix of the acts of the [WorkspaceCapability] policies.
+pub const CAPABILITY_ACT_PREFIX: &str = "c:";
+
 /// Represents the entity stored at the index of the access control policy.
 /// `subject_id, object_id, role/action`
 ///
@@ -333,6 +548,12 @@ pub(crate) async fn load_group_policies(enforcer: &mut Enforcer) -> Result<(), A
     }
   }
 
+  for role in &af_roles {
+    for capability in role.default_capabilities() {
+      grouping_policies.push([role.to_enforce_act(), capability.to_enforce_act()].to_vec());
+    }
+  }
+
   let grouping_policies = grouping_policies
     .into_iter()
     .map(|actions| actions.into_iter().map(|a| a.to_string()).collect())
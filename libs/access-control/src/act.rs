@@ -1,5 +1,5 @@
 use actix_http::Method;
-use database_entity::dto::{AFAccessLevel, AFRole};
+use database_entity::dto::{AFAccessLevel, AFRole, WorkspaceCapability};
 use redis::{ErrorKind, FromRedisValue, RedisError, RedisResult, RedisWrite, ToRedisArgs, Value};
 use std::cmp::Ordering;
 
@@ -11,10 +11,12 @@ pub trait Acts {
   fn from_enforce_act(act: &str) -> Self;
 }
 
+#[derive(Clone, Copy)]
 pub enum ActionVariant<'a> {
   FromRole(&'a AFRole),
   FromAccessLevel(&'a AFAccessLevel),
   FromAction(&'a Action),
+  FromCapability(&'a WorkspaceCapability),
 }
 
 impl<'a> ActionVariant<'a> {
@@ -23,6 +25,7 @@ impl<'a> ActionVariant<'a> {
       ActionVariant::FromRole(role) => role.policy_acts(),
       ActionVariant::FromAccessLevel(level) => level.policy_acts(),
       ActionVariant::FromAction(action) => action.policy_acts(),
+      ActionVariant::FromCapability(capability) => capability.policy_acts(),
     }
   }
 
@@ -31,6 +34,7 @@ impl<'a> ActionVariant<'a> {
       ActionVariant::FromRole(role) => role.to_enforce_act(),
       ActionVariant::FromAccessLevel(level) => level.to_enforce_act(),
       ActionVariant::FromAction(action) => action.to_enforce_act(),
+      ActionVariant::FromCapability(capability) => capability.to_enforce_act(),
     }
   }
 }
@@ -111,6 +115,30 @@ impl Acts for AFRole {
   }
 }
 
+impl Acts for WorkspaceCapability {
+  fn policy_acts(&self) -> Vec<&'static str> {
+    vec![self.to_enforce_act()]
+  }
+
+  fn to_enforce_act(&self) -> &'static str {
+    match self {
+      WorkspaceCapability::Publish => "c:publish",
+      WorkspaceCapability::Invite => "c:invite",
+      WorkspaceCapability::DeleteView => "c:delete_view",
+      WorkspaceCapability::ManageBilling => "c:manage_billing",
+    }
+  }
+
+  fn from_enforce_act(act: &str) -> Self {
+    match act {
+      "c:publish" => WorkspaceCapability::Publish,
+      "c:invite" => WorkspaceCapability::Invite,
+      "c:delete_view" => WorkspaceCapability::DeleteView,
+      _ => WorkspaceCapability::ManageBilling,
+    }
+  }
+}
+
 /// Represents the actions that can be performed on objects.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Action {
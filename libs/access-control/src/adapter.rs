@@ -9,9 +9,14 @@ use casbin::Model;
 use casbin::Result;
 
 use database::collab::select_collab_member_access_level;
+use database::pg_row::AFCollabGroupAccessLevelRow;
 use database::pg_row::AFCollabMemberAccessLevelRow;
+use database::pg_row::AFWorkspaceMemberCapabilityRow;
 use database::pg_row::AFWorkspaceMemberPermRow;
 use database::workspace::select_workspace_member_perm_stream;
+use database::workspace_group::select_collab_group_access_level;
+use database::workspace_role::select_workspace_member_capability_stream;
+use database_entity::dto::WorkspaceCapability;
 
 use crate::act::Acts;
 use futures_util::stream::BoxStream;
@@ -58,6 +63,28 @@ async fn load_collab_policies(
   Ok(policies)
 }
 
+/// Loads the policies granted to groups on collabs. The subject of these policies is the group id,
+/// which is enforced for the members of the group.
+async fn load_collab_group_policies(
+  mut stream: BoxStream<'_, sqlx::Result<AFCollabGroupAccessLevelRow>>,
+) -> Result<Vec<Vec<String>>> {
+  let mut policies: Vec<Vec<String>> = Vec::new();
+
+  while let Some(Ok(group_access_lv)) = stream.next().await {
+    let guid = group_access_lv.group_id.to_string();
+    let object_type = ObjectType::Collab(&group_access_lv.oid);
+    for act in group_access_lv.access_level.policy_acts() {
+      policies.push(vec![
+        guid.clone(),
+        object_type.policy_object(),
+        act.to_string(),
+      ]);
+    }
+  }
+
+  Ok(policies)
+}
+
 /// Loads workspace policies from a given stream of workspace member permissions.
 ///
 /// This function iterates over the stream of member permissions, constructing and accumulating
@@ -119,6 +146,32 @@ async fn load_workspace_policies(
   Ok(policies)
 }
 
+async fn load_workspace_capability_policies(
+  mut stream: BoxStream<'_, sqlx::Result<AFWorkspaceMemberCapabilityRow>>,
+) -> Result<Vec<Vec<String>>> {
+  let mut policies: Vec<Vec<String>> = Vec::new();
+
+  while let Some(Ok(member_capabilities)) = stream.next().await {
+    let uid = member_capabilities.uid;
+    let workspace_id = member_capabilities.workspace_id.to_string();
+    let object_type = ObjectType::Workspace(&workspace_id);
+    // The names of capabilities that no longer exist are skipped
+    for capability in member_capabilities
+      .capabilities
+      .iter()
+      .filter_map(|name| WorkspaceCapability::from_name(name))
+    {
+      policies.push(vec![
+        uid.to_string(),
+        object_type.policy_object(),
+        capability.to_enforce_act().to_string(),
+      ]);
+    }
+  }
+
+  Ok(policies)
+}
+
 #[async_trait]
 impl Adapter for PgAdapter {
   async fn load_policy(&mut self, model: &mut dyn Model) -> Result<()> {
@@ -129,12 +182,24 @@ impl Adapter for PgAdapter {
     // Policy definition `p` of type `p`. See `model.conf`
     model.add_policies("p", "p", workspace_policies);
 
+    let workspace_capability_stream = select_workspace_member_capability_stream(&self.pg_pool);
+    let workspace_capability_policies =
+      load_workspace_capability_policies(workspace_capability_stream).await?;
+
+    model.add_policies("p", "p", workspace_capability_policies);
+
     let collab_member_access_lv_stream = select_collab_member_access_level(&self.pg_pool);
     let collab_policies = load_collab_policies(collab_member_access_lv_stream).await?;
 
     // Policy definition `p` of type `p`. See `model.conf`
     model.add_policies("p", "p", collab_policies);
 
+    let collab_group_access_lv_stream = select_collab_group_access_level(&self.pg_pool);
+    let collab_group_policies = load_collab_group_policies(collab_group_access_lv_stream).await?;
+
+    // Policy definition `p` of type `p`. See `model.conf`
+    model.add_policies("p", "p", collab_group_policies);
+
     self
       .access_control_metrics
       .record_load_all_policies_in_secs(start.elapsed().as_millis() as u64);
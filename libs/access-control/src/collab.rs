@@ -30,6 +30,27 @@ pub trait CollabAccessControl: Sync + Send + 'static {
   ) -> Result<(), AppError>;
 
   async fn remove_access_level(&self, uid: &i64, oid: &str) -> Result<(), AppError>;
+
+  /// Return the access level of every user that has a policy on the collab, as held by the
+  /// access control backend rather than the database.
+  async fn get_access_policies(&self, oid: &str) -> Result<Vec<(i64, AFAccessLevel)>, AppError>;
+
+  /// Grant the group the access level on the collab, replacing the level it held before.
+  /// Every member of the group is enforced with that level.
+  async fn update_group_access_level_policy(
+    &self,
+    guid: &str,
+    oid: &str,
+    level: AFAccessLevel,
+  ) -> Result<(), AppError>;
+
+  async fn remove_group_access_level(&self, guid: &str, oid: &str) -> Result<(), AppError>;
+
+  /// Record that the user belongs to the group, so that the access granted to the group applies
+  /// to the user as well.
+  async fn add_group_member(&self, uid: &i64, guid: &str) -> Result<(), AppError>;
+
+  async fn remove_group_member(&self, uid: &i64, guid: &str) -> Result<(), AppError>;
 }
 
 #[async_trait]
@@ -59,3 +80,20 @@ pub trait RealtimeAccessControl: Sync + Send + 'static {
     oid: &str,
   ) -> Result<bool, AppError>;
 }
+
+/// Resolves where a collab sits in the folder hierarchy of its workspace, so that the access a
+/// user holds on a view can be inherited by the views below it.
+#[async_trait]
+pub trait CollabHierarchy: Sync + Send + 'static {
+  /// Return the ancestors the collab inherits access from, nearest first. The list stops at the
+  /// first ancestor on which inheritance is broken, that ancestor included, and is empty when
+  /// inheritance is disabled for the workspace or broken on the collab itself.
+  async fn inherited_ancestors(
+    &self,
+    workspace_id: &str,
+    oid: &str,
+  ) -> Result<Vec<String>, AppError>;
+
+  /// Forget what is cached for the workspace, so that the next call reads its hierarchy again.
+  async fn invalidate(&self, workspace_id: &str);
+}
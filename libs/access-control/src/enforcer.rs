@@ -1,5 +1,6 @@
 use crate::access::{
-  load_group_policies, ObjectType, POLICY_FIELD_INDEX_OBJECT, POLICY_FIELD_INDEX_SUBJECT,
+  load_group_policies, ObjectType, CAPABILITY_ACT_PREFIX, POLICY_FIELD_INDEX_ACTION,
+  POLICY_FIELD_INDEX_OBJECT, POLICY_FIELD_INDEX_SUBJECT, ROLE_ACT_PREFIX,
 };
 use crate::act::ActionVariant;
 use crate::metrics::MetricsCalState;
@@ -17,9 +18,9 @@ pub const ENFORCER_METRICS_TICK_INTERVAL: Duration = Duration::from_secs(120);
 
 #[async_trait]
 pub trait EnforcerGroup {
-  /// Get the group id of the user.
-  /// User might belong to multiple groups. So return the highest permission group id.
-  async fn get_enforce_group_id(&self, uid: &i64) -> Option<String>;
+  /// Get the ids of the groups the user belongs to.
+  /// The user is allowed to perform the action if any of the groups is allowed to.
+  async fn get_enforce_group_ids(&self, uid: &i64) -> Vec<String>;
 }
 
 pub struct AFEnforcer<T> {
@@ -41,24 +42,25 @@ where
     })
   }
 
-  /// Update policy for a user.
+  /// Update policy for a user, or for a group when the subject is a group id.
   /// If the policy is already exist, then it will return Ok(false).
   ///
   /// [`ObjectType::Workspace`] has to be paired with [`ActionType::Role`],
   /// [`ObjectType::Collab`] has to be paired with [`ActionType::Level`],
   #[instrument(level = "debug", skip_all, err)]
-  pub async fn update_policy(
+  pub async fn update_policy<S: ToString>(
     &self,
-    uid: &i64,
+    subject: S,
     obj: ObjectType<'_>,
     act: ActionVariant<'_>,
   ) -> Result<(), AppError> {
     validate_obj_action(&obj, &act)?;
 
+    let subject = subject.to_string();
     let policies = act
       .policy_acts()
       .into_iter()
-      .map(|act| vec![uid.to_string(), obj.policy_object(), act.to_string()])
+      .map(|act| vec![subject.clone(), obj.policy_object(), act.to_string()])
       .collect::<Vec<Vec<_>>>();
 
     trace!("[access control]: add policy:{:?}", policies);
@@ -74,17 +76,72 @@ where
   }
 
   /// Returns policies that match the filter.
-  pub async fn remove_policy(
+  pub async fn remove_policy<S: ToString>(
     &self,
-    uid: &i64,
+    subject: S,
     object_type: &ObjectType<'_>,
   ) -> Result<(), AppError> {
     let mut enforcer = self.enforcer.write().await;
     self
-      .remove_with_enforcer(uid, object_type, &mut enforcer)
+      .remove_with_enforcer(subject, object_type, &mut enforcer)
       .await
   }
 
+  /// Remove the capability policies of the subject on the object, keeping its other policies.
+  pub async fn remove_capability_policies<S: ToString>(
+    &self,
+    subject: S,
+    object_type: &ObjectType<'_>,
+  ) -> Result<(), AppError> {
+    self
+      .remove_policies_with_act_prefix(subject, object_type, CAPABILITY_ACT_PREFIX)
+      .await
+  }
+
+  /// Remove the role policies of the subject on the object, keeping its other policies.
+  pub async fn remove_role_policies<S: ToString>(
+    &self,
+    subject: S,
+    object_type: &ObjectType<'_>,
+  ) -> Result<(), AppError> {
+    self
+      .remove_policies_with_act_prefix(subject, object_type, ROLE_ACT_PREFIX)
+      .await
+  }
+
+  async fn remove_policies_with_act_prefix<S: ToString>(
+    &self,
+    subject: S,
+    object_type: &ObjectType<'_>,
+    act_prefix: &str,
+  ) -> Result<(), AppError> {
+    let mut enforcer = self.enforcer.write().await;
+    let policies = policies_for_subject_with_given_object(subject, object_type, &enforcer)
+      .await
+      .into_iter()
+      .filter(|p| p[POLICY_FIELD_INDEX_ACTION].starts_with(act_prefix))
+      .collect::<Vec<_>>();
+    if policies.is_empty() {
+      return Ok(());
+    }
+
+    trace!("[access control]: remove policies:{:?}", policies);
+    enforcer
+      .remove_policies(policies)
+      .await
+      .map_err(|e| AppError::Internal(anyhow!("fail to remove policy: {e:?}")))?;
+    Ok(())
+  }
+
+  /// Returns all policies stored for the given object.
+  pub async fn policies_for_object(&self, object_type: &ObjectType<'_>) -> Vec<Vec<String>> {
+    self
+      .enforcer
+      .read()
+      .await
+      .get_filtered_policy(POLICY_FIELD_INDEX_OBJECT, vec![object_type.policy_object()])
+  }
+
   /// 1. **Workspace Policy**: Initially, it checks if the user has permission at the workspace level. If the user
   ///    has permission to perform the action on the workspace, the function returns `true` without further checks.
   ///
@@ -130,7 +187,7 @@ where
 
     // 2. Fallback to group policy if workspace-level check fails.
     if !result {
-      if let Some(guid) = self.enforce_group.get_enforce_group_id(uid).await {
+      for guid in self.enforce_group.get_enforce_group_ids(uid).await {
         let policy_request = GroupPolicyRequest::new(&guid, &obj, &act);
         result = self
           .enforcer
@@ -138,6 +195,9 @@ where
           .await
           .enforce(policy_request.to_policy())
           .map_err(|e| AppError::Internal(anyhow!("enforce: {e:?}")))?;
+        if result {
+          break;
+        }
       }
     }
 
@@ -156,15 +216,41 @@ where
     Ok(result)
   }
 
-  #[inline]
-  async fn remove_with_enforcer(
+  /// Return true if the subject holds any policy on the object, whatever the policy allows.
+  pub async fn has_policy<S: ToString>(&self, subject: S, object_type: &ObjectType<'_>) -> bool {
+    let enforcer = self.enforcer.read().await;
+    !policies_for_subject_with_given_object(subject, object_type, &enforcer)
+      .await
+      .is_empty()
+  }
+
+  /// Enforce the policy the user holds on the object itself, without looking at the workspace
+  /// or group policies.
+  pub async fn enforce_object_policy(
     &self,
     uid: &i64,
+    obj: &ObjectType<'_>,
+    act: &ActionVariant<'_>,
+  ) -> Result<bool, AppError> {
+    let policy_request = PolicyRequest::new(*uid, obj, act);
+    self
+      .enforcer
+      .read()
+      .await
+      .enforce(policy_request.to_policy())
+      .map_err(|e| AppError::Internal(anyhow!("enforce: {e:?}")))
+  }
+
+  #[inline]
+  async fn remove_with_enforcer<S: ToString>(
+    &self,
+    subject: S,
     object_type: &ObjectType<'_>,
     enforcer: &mut Enforcer,
   ) -> Result<(), AppError> {
+    let subject = subject.to_string();
     let policies_for_user_on_object =
-      policies_for_subject_with_given_object(uid, object_type, enforcer).await;
+      policies_for_subject_with_given_object(&subject, object_type, enforcer).await;
 
     // if there are no policies for the user on the object, return early.
     if policies_for_user_on_object.is_empty() {
@@ -173,8 +259,8 @@ where
 
     event!(
       tracing::Level::INFO,
-      "[access control]: remove policy:user={}, object={}, policies={:?}",
-      uid,
+      "[access control]: remove policy:subject={}, object={}, policies={:?}",
+      subject,
       object_type.policy_object(),
       policies_for_user_on_object
     );
@@ -191,6 +277,7 @@ where
 fn validate_obj_action(obj: &ObjectType<'_>, act: &ActionVariant) -> Result<(), AppError> {
   match (obj, act) {
     (ObjectType::Workspace(_), ActionVariant::FromRole(_))
+    | (ObjectType::Workspace(_), ActionVariant::FromCapability(_))
     | (ObjectType::Collab(_), ActionVariant::FromAccessLevel(_)) => Ok(()),
     _ => Err(AppError::Internal(anyhow!(
       "invalid object type and action type combination: object={:?}, action={:?}",
@@ -219,7 +306,7 @@ async fn policies_for_subject_with_given_object<T: ToString>(
 pub struct NoEnforceGroup;
 #[async_trait]
 impl EnforcerGroup for NoEnforceGroup {
-  async fn get_enforce_group_id(&self, _uid: &i64) -> Option<String> {
-    None
+  async fn get_enforce_group_ids(&self, _uid: &i64) -> Vec<String> {
+    vec![]
   }
 }
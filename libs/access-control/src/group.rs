@@ -0,0 +1,70 @@
+use crate::enforcer::EnforcerGroup;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use async_trait::async_trait;
+use database::workspace_group::select_workspace_group_member_stream;
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio_stream::StreamExt;
+
+/// The workspace groups each user belongs to. The policies granted to a group are enforced for
+/// every member of the group.
+#[derive(Clone, Default)]
+pub struct UserGroups {
+  groups_by_uid: Arc<RwLock<HashMap<i64, HashSet<String>>>>,
+}
+
+impl UserGroups {
+  pub async fn load(pg_pool: &PgPool) -> Result<Self, AppError> {
+    let mut groups_by_uid: HashMap<i64, HashSet<String>> = HashMap::new();
+    let mut stream = select_workspace_group_member_stream(pg_pool);
+    while let Some(row) = stream.next().await {
+      let (uid, group_id) =
+        row.map_err(|e| AppError::Internal(anyhow!("Failed to load user groups: {}", e)))?;
+      groups_by_uid
+        .entry(uid)
+        .or_default()
+        .insert(group_id.to_string());
+    }
+
+    Ok(Self {
+      groups_by_uid: Arc::new(RwLock::new(groups_by_uid)),
+    })
+  }
+
+  pub async fn insert(&self, uid: i64, guid: &str) {
+    self
+      .groups_by_uid
+      .write()
+      .await
+      .entry(uid)
+      .or_default()
+      .insert(guid.to_string());
+  }
+
+  pub async fn remove(&self, uid: i64, guid: &str) {
+    let mut groups_by_uid = self.groups_by_uid.write().await;
+    if let Some(groups) = groups_by_uid.get_mut(&uid) {
+      groups.remove(guid);
+      if groups.is_empty() {
+        groups_by_uid.remove(&uid);
+      }
+    }
+  }
+}
+
+#[async_trait]
+impl EnforcerGroup for UserGroups {
+  async fn get_enforce_group_ids(&self, uid: &i64) -> Vec<String> {
+    self
+      .groups_by_uid
+      .read()
+      .await
+      .get(uid)
+      .map(|groups| groups.iter().cloned().collect())
+      .unwrap_or_default()
+  }
+}
@@ -3,6 +3,7 @@ pub mod act;
 mod adapter;
 pub mod collab;
 pub mod enforcer;
+pub mod group;
 pub mod metrics;
 mod request;
 pub mod workspace;
@@ -1,7 +1,7 @@
 use crate::act::Action;
 use app_error::AppError;
 use async_trait::async_trait;
-use database_entity::dto::AFRole;
+use database_entity::dto::{AFRole, WorkspaceCapability};
 use sqlx::types::Uuid;
 
 #[async_trait]
@@ -20,9 +20,34 @@ pub trait WorkspaceAccessControl: Send + Sync + 'static {
     action: Action,
   ) -> Result<bool, AppError>;
 
+  /// Return true if the role of the user, or the custom role assigned to it, grants the capability.
+  async fn enforce_capability(
+    &self,
+    uid: &i64,
+    workspace_id: &str,
+    capability: WorkspaceCapability,
+  ) -> Result<bool, AppError>;
+
+  /// Replace the capabilities granted to the user by its custom role.
+  async fn update_capabilities(
+    &self,
+    uid: &i64,
+    workspace_id: &Uuid,
+    capabilities: &[WorkspaceCapability],
+  ) -> Result<(), AppError>;
+
   async fn insert_role(&self, uid: &i64, workspace_id: &Uuid, role: AFRole)
     -> Result<(), AppError>;
 
+  /// Replace the role of the user, which loses what its previous role granted. [Self::insert_role]
+  /// adds the role to the ones the user already holds.
+  async fn replace_role(
+    &self,
+    uid: &i64,
+    workspace_id: &Uuid,
+    role: AFRole,
+  ) -> Result<(), AppError>;
+
   async fn remove_user_from_workspace(
     &self,
     uid: &i64,
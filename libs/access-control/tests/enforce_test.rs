@@ -4,15 +4,15 @@ use access_control::enforcer::{AFEnforcer, EnforcerGroup, NoEnforceGroup};
 use async_trait::async_trait;
 use casbin::rhai::ImmutableString;
 use casbin::{CoreApi, MemoryAdapter};
-use database_entity::dto::{AFAccessLevel, AFRole};
+use database_entity::dto::{AFAccessLevel, AFRole, WorkspaceCapability};
 
 pub struct TestEnforceGroup {
-  guid: String,
+  guids: Vec<String>,
 }
 #[async_trait]
 impl EnforcerGroup for TestEnforceGroup {
-  async fn get_enforce_group_id(&self, _uid: &i64) -> Option<String> {
-    Some(self.guid.clone())
+  async fn get_enforce_group_ids(&self, _uid: &i64) -> Vec<String> {
+    self.guids.clone()
   }
 }
 
@@ -64,6 +64,67 @@ async fn collab_group_test() {
   }
 }
 
+#[tokio::test]
+async fn collab_access_through_user_group_test() {
+  let enforcer = test_enforcer(TestEnforceGroup {
+    guids: vec!["g1".to_string(), "g2".to_string()],
+  })
+  .await;
+
+  let uid = 1;
+  let workspace_id = "w1";
+  let object_1 = "o1";
+
+  // the second group of the user can read and write the collab
+  enforcer
+    .update_policy(
+      "g2",
+      ObjectType::Collab(object_1),
+      ActionVariant::FromAccessLevel(&AFAccessLevel::ReadAndWrite),
+    )
+    .await
+    .unwrap();
+
+  for action in [Action::Write, Action::Read] {
+    let result = enforcer
+      .enforce_policy(
+        workspace_id,
+        &uid,
+        ObjectType::Collab(object_1),
+        ActionVariant::FromAction(&action),
+      )
+      .await
+      .unwrap();
+    assert!(result);
+  }
+  let result = enforcer
+    .enforce_policy(
+      workspace_id,
+      &uid,
+      ObjectType::Collab(object_1),
+      ActionVariant::FromAction(&Action::Delete),
+    )
+    .await
+    .unwrap();
+  assert!(!result);
+
+  // once the group loses its access, so does the user
+  enforcer
+    .remove_policy("g2", &ObjectType::Collab(object_1))
+    .await
+    .unwrap();
+  let result = enforcer
+    .enforce_policy(
+      workspace_id,
+      &uid,
+      ObjectType::Collab(object_1),
+      ActionVariant::FromAction(&Action::Read),
+    )
+    .await
+    .unwrap();
+  assert!(!result);
+}
+
 #[tokio::test]
 async fn workspace_group_policy_test() {
   let enforcer = test_enforcer(NoEnforceGroup).await;
@@ -583,3 +644,126 @@ async fn cmp_read_only_level_test() {
     }
   }
 }
+
+async fn enforce_capability<T: EnforcerGroup>(
+  enforcer: &AFEnforcer<T>,
+  capability: WorkspaceCapability,
+) -> bool {
+  enforcer
+    .enforce_policy(
+      "w1",
+      &1,
+      ObjectType::Workspace("w1"),
+      ActionVariant::FromCapability(&capability),
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn workspace_capability_test() {
+  let enforcer = test_enforcer(NoEnforceGroup).await;
+  let uid = 1;
+  let workspace_id = "w1";
+  enforcer
+    .update_policy(
+      &uid,
+      ObjectType::Workspace(workspace_id),
+      ActionVariant::FromRole(&AFRole::Member),
+    )
+    .await
+    .unwrap();
+
+  // a member holds the default capabilities of its role only
+  assert!(enforce_capability(&enforcer, WorkspaceCapability::Publish).await);
+  assert!(enforce_capability(&enforcer, WorkspaceCapability::DeleteView).await);
+  assert!(!enforce_capability(&enforcer, WorkspaceCapability::Invite).await);
+
+  enforcer
+    .update_policy(
+      &uid,
+      ObjectType::Workspace(workspace_id),
+      ActionVariant::FromCapability(&WorkspaceCapability::Invite),
+    )
+    .await
+    .unwrap();
+  assert!(enforce_capability(&enforcer, WorkspaceCapability::Invite).await);
+  assert!(!enforce_capability(&enforcer, WorkspaceCapability::ManageBilling).await);
+
+  // removing the capabilities keeps the role of the member
+  enforcer
+    .remove_capability_policies(&uid, &ObjectType::Workspace(workspace_id))
+    .await
+    .unwrap();
+  assert!(!enforce_capability(&enforcer, WorkspaceCapability::Invite).await);
+  assert!(enforce_capability(&enforcer, WorkspaceCapability::Publish).await);
+}
+
+#[tokio::test]
+async fn replace_workspace_role_test() {
+  let enforcer = test_enforcer(NoEnforceGroup).await;
+  let uid = 1;
+  let workspace_id = "w1";
+  enforcer
+    .update_policy(
+      &uid,
+      ObjectType::Workspace(workspace_id),
+      ActionVariant::FromRole(&AFRole::Owner),
+    )
+    .await
+    .unwrap();
+  enforcer
+    .update_policy(
+      &uid,
+      ObjectType::Workspace(workspace_id),
+      ActionVariant::FromCapability(&WorkspaceCapability::Invite),
+    )
+    .await
+    .unwrap();
+
+  // adding a role keeps the one the user held before
+  enforcer
+    .update_policy(
+      &uid,
+      ObjectType::Workspace(workspace_id),
+      ActionVariant::FromRole(&AFRole::Member),
+    )
+    .await
+    .unwrap();
+  assert!(enforce_capability(&enforcer, WorkspaceCapability::ManageBilling).await);
+
+  enforcer
+    .remove_role_policies(&uid, &ObjectType::Workspace(workspace_id))
+    .await
+    .unwrap();
+  enforcer
+    .update_policy(
+      &uid,
+      ObjectType::Workspace(workspace_id),
+      ActionVariant::FromRole(&AFRole::Member),
+    )
+    .await
+    .unwrap();
+  assert!(!enforce_capability(&enforcer, WorkspaceCapability::ManageBilling).await);
+  assert!(enforce_capability(&enforcer, WorkspaceCapability::DeleteView).await);
+  // the capabilities of the custom role are kept
+  assert!(enforce_capability(&enforcer, WorkspaceCapability::Invite).await);
+  assert!(!enforcer
+    .enforce_policy(
+      workspace_id,
+      &uid,
+      ObjectType::Workspace(workspace_id),
+      ActionVariant::FromRole(&AFRole::Owner),
+    )
+    .await
+    .unwrap());
+  assert!(enforcer
+    .enforce_policy(
+      workspace_id,
+      &uid,
+      ObjectType::Workspace(workspace_id),
+      ActionVariant::FromRole(&AFRole::Member),
+    )
+    .await
+    .unwrap());
+}
@@ -145,6 +145,21 @@ pub enum AppError {
 
   #[error("{0}")]
   NotInviteeOfWorkspaceInvitation(String),
+
+  #[error("Resource is locked:{0}")]
+  Locked(String),
+
+  #[error("Too many requests:{0}")]
+  TooManyRequests(String),
+
+  #[error("Single sign-on is required:{0}")]
+  SsoRequired(String),
+
+  #[error("Storage quota exceeded:{0}")]
+  StorageQuotaExceeded(String),
+
+  #[error("AI response limit exceeded:{0}")]
+  AIResponseLimitExceeded(String),
 }
 
 impl AppError {
@@ -212,6 +227,11 @@ impl AppError {
       AppError::InvalidPublishedOutline(_) => ErrorCode::InvalidPublishedOutline,
       AppError::InvalidFolderView(_) => ErrorCode::InvalidFolderView,
       AppError::NotInviteeOfWorkspaceInvitation(_) => ErrorCode::NotInviteeOfWorkspaceInvitation,
+      AppError::Locked(_) => ErrorCode::Locked,
+      AppError::TooManyRequests(_) => ErrorCode::TooManyRequests,
+      AppError::SsoRequired(_) => ErrorCode::SsoRequired,
+      AppError::StorageQuotaExceeded(_) => ErrorCode::FileStorageLimitExceeded,
+      AppError::AIResponseLimitExceeded(_) => ErrorCode::AIResponseLimitExceeded,
     }
   }
 }
@@ -339,6 +359,9 @@ pub enum ErrorCode {
   InvalidPublishedOutline = 1039,
   InvalidFolderView = 1040,
   NotInviteeOfWorkspaceInvitation = 1041,
+  Locked = 1042,
+  TooManyRequests = 1043,
+  SsoRequired = 1044,
 }
 
 impl ErrorCode {
@@ -5,3 +5,6 @@ pub mod client;
 pub mod dto;
 
 pub mod error;
+
+#[cfg(feature = "client-api")]
+pub mod llm;
@@ -0,0 +1,347 @@
+use std::pin::Pin;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use reqwest::{Method, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use tracing::{info, trace};
+
+use crate::error::AIError;
+
+const LLM_REQUEST_TIMEOUT_SECS: u64 = 120;
+
+/// The providers of large language models the server can call without the AppFlowy AI service.
+/// All of them serve the chat completions API of OpenAI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LLMProvider {
+  OpenAI,
+  Ollama,
+}
+
+impl LLMProvider {
+  pub fn default_base_url(&self) -> &'static str {
+    match self {
+      LLMProvider::OpenAI => "https://api.openai.com/v1",
+      LLMProvider::Ollama => "http://localhost:11434/v1",
+    }
+  }
+}
+
+impl FromStr for LLMProvider {
+  type Err = anyhow::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "openai" => Ok(LLMProvider::OpenAI),
+      "ollama" => Ok(LLMProvider::Ollama),
+      _ => Err(anyhow!("Unknown LLM provider: {}", s)),
+    }
+  }
+}
+
+/// The text the model answered with, and the tokens the request consumed.
+#[derive(Debug, Clone)]
+pub struct LLMCompletion {
+  pub text: String,
+  pub prompt_tokens: i64,
+  pub completion_tokens: i64,
+}
+
+impl LLMCompletion {
+  pub fn total_tokens(&self) -> i64 {
+    self.prompt_tokens + self.completion_tokens
+  }
+}
+
+/// A piece of the answer streamed by the model. The tokens consumed by the request come last,
+/// once the answer is complete.
+#[derive(Debug, Clone)]
+pub enum LLMStreamValue {
+  Text(String),
+  Usage {
+    prompt_tokens: i64,
+    completion_tokens: i64,
+  },
+}
+
+#[derive(Clone, Debug)]
+pub struct LLMClient {
+  client: reqwest::Client,
+  provider: LLMProvider,
+  base_url: String,
+  api_key: Option<String>,
+  model: String,
+}
+
+impl LLMClient {
+  /// `base_url` defaults to the one of the provider, and `api_key` is only sent when there is one.
+  pub fn new(
+    provider: LLMProvider,
+    base_url: Option<&str>,
+    api_key: Option<String>,
+    model: &str,
+  ) -> Self {
+    let base_url = base_url
+      .unwrap_or(provider.default_base_url())
+      .trim_end_matches('/')
+      .to_string();
+    info!(
+      "Creating LLMClient for {:?} with url: {}, model: {}",
+      provider, base_url, model
+    );
+    let client = reqwest::Client::builder()
+      .timeout(Duration::from_secs(LLM_REQUEST_TIMEOUT_SECS))
+      .build()
+      .unwrap_or_default();
+    Self {
+      client,
+      provider,
+      base_url,
+      api_key: api_key.filter(|key| !key.is_empty()),
+      model: model.to_string(),
+    }
+  }
+
+  pub fn provider(&self) -> LLMProvider {
+    self.provider
+  }
+
+  pub fn model(&self) -> &str {
+    &self.model
+  }
+
+  /// Ask the model to answer `prompt` following the `instructions` of the system message.
+  pub async fn chat(&self, instructions: &str, prompt: &str) -> Result<LLMCompletion, AIError> {
+    let resp = self
+      .completion_request(instructions, prompt, false)?
+      .send()
+      .await?;
+    let status_code = resp.status();
+    if !status_code.is_success() {
+      let body = resp.text().await?;
+      return Err(AIError::Internal(anyhow!(
+        "error code: {}, {}",
+        status_code,
+        body
+      )));
+    }
+    let resp = resp.json::<ChatCompletionResponse>().await?;
+    let text = resp
+      .choices
+      .into_iter()
+      .next()
+      .map(|choice| choice.message.content)
+      .ok_or_else(|| AIError::Internal(anyhow!("The model answered without any choice")))?;
+    let usage = resp.usage.unwrap_or_default();
+    Ok(LLMCompletion {
+      text,
+      prompt_tokens: usage.prompt_tokens,
+      completion_tokens: usage.completion_tokens,
+    })
+  }
+
+  /// Same as [LLMClient::chat], but the answer is returned piece by piece as the model writes it.
+  pub async fn stream_chat(
+    &self,
+    instructions: &str,
+    prompt: &str,
+  ) -> Result<impl Stream<Item = Result<LLMStreamValue, AIError>> + Send + 'static, AIError> {
+    let resp = self
+      .completion_request(instructions, prompt, true)?
+      .send()
+      .await?;
+    let status_code = resp.status();
+    if !status_code.is_success() {
+      let body = resp.text().await?;
+      return Err(AIError::Internal(anyhow!(
+        "error code: {}, {}",
+        status_code,
+        body
+      )));
+    }
+    Ok(completion_stream(resp.bytes_stream()))
+  }
+
+  fn completion_request(
+    &self,
+    instructions: &str,
+    prompt: &str,
+    stream: bool,
+  ) -> Result<RequestBuilder, AIError> {
+    if prompt.trim().is_empty() {
+      return Err(AIError::InvalidRequest("Empty prompt".to_string()));
+    }
+    let params = ChatCompletionRequest {
+      model: &self.model,
+      messages: vec![
+        ChatMessage {
+          role: "system",
+          content: instructions,
+        },
+        ChatMessage {
+          role: "user",
+          content: prompt,
+        },
+      ],
+      stream,
+      stream_options: stream.then_some(ChatCompletionStreamOptions {
+        include_usage: true,
+      }),
+    };
+    let url = format!("{}/chat/completions", self.base_url);
+    trace!("chat completion url: {}, stream: {}", url, stream);
+    let mut request = self.client.request(Method::POST, &url).json(&params);
+    if let Some(api_key) = &self.api_key {
+      request = request.bearer_auth(api_key);
+    }
+    Ok(request)
+  }
+}
+
+struct CompletionStreamState {
+  stream: Pin<Box<dyn Stream<Item = Result<Bytes, reqwest::Error>> + Send>>,
+  buffer: Vec<u8>,
+  finished: bool,
+}
+
+/// Read the server-sent events of a streamed chat completion. Every event is a `data:` line
+/// holding a chunk of the completion, and the last one is `data: [DONE]`.
+fn completion_stream<S>(stream: S) -> impl Stream<Item = Result<LLMStreamValue, AIError>>
+where
+  S: Stream<Item = Result<Bytes, reqwest::Error>> + Send + 'static,
+{
+  let state = CompletionStreamState {
+    stream: Box::pin(stream),
+    buffer: Vec::new(),
+    finished: false,
+  };
+  futures::stream::unfold(state, |mut state| async move {
+    loop {
+      let line = match state.buffer.iter().position(|b| *b == b'\n') {
+        Some(end) => Some(state.buffer.drain(..=end).collect::<Vec<_>>()),
+        None if state.finished && !state.buffer.is_empty() => {
+          Some(std::mem::take(&mut state.buffer))
+        },
+        None if state.finished => return None,
+        None => None,
+      };
+      if let Some(line) = line {
+        match parse_completion_stream_line(&String::from_utf8_lossy(&line)) {
+          Ok(Some(value)) => return Some((Ok(value), state)),
+          Ok(None) => continue,
+          Err(err) => {
+            state.buffer.clear();
+            state.finished = true;
+            return Some((Err(err), state));
+          },
+        }
+      }
+      match state.stream.next().await {
+        Some(Ok(bytes)) => state.buffer.extend_from_slice(&bytes),
+        Some(Err(err)) => {
+          state.buffer.clear();
+          state.finished = true;
+          return Some((Err(err.into()), state));
+        },
+        None => state.finished = true,
+      }
+    }
+  })
+}
+
+/// Returns `None` for the lines that carry no text nor usage, like the blank lines between the
+/// events and the final `[DONE]`.
+fn parse_completion_stream_line(line: &str) -> Result<Option<LLMStreamValue>, AIError> {
+  let data = match line.trim().strip_prefix("data:") {
+    Some(data) => data.trim(),
+    None => return Ok(None),
+  };
+  if data.is_empty() || data == "[DONE]" {
+    return Ok(None);
+  }
+  let chunk = serde_json::from_str::<ChatCompletionChunk>(data)
+    .map_err(|err| AIError::Internal(anyhow!("Invalid chat completion chunk: {}", err)))?;
+  if let Some(usage) = chunk.usage {
+    return Ok(Some(LLMStreamValue::Usage {
+      prompt_tokens: usage.prompt_tokens,
+      completion_tokens: usage.completion_tokens,
+    }));
+  }
+  let text = chunk
+    .choices
+    .into_iter()
+    .filter_map(|choice| choice.delta.content)
+    .collect::<String>();
+  if text.is_empty() {
+    return Ok(None);
+  }
+  Ok(Some(LLMStreamValue::Text(text)))
+}
+
+#[derive(Serialize)]
+struct ChatCompletionRequest<'a> {
+  model: &'a str,
+  messages: Vec<ChatMessage<'a>>,
+  stream: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  stream_options: Option<ChatCompletionStreamOptions>,
+}
+
+#[derive(Serialize)]
+struct ChatCompletionStreamOptions {
+  include_usage: bool,
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+  role: &'static str,
+  content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ChatCompletionResponse {
+  choices: Vec<ChatCompletionChoice>,
+  #[serde(default)]
+  usage: Option<ChatCompletionUsage>,
+}
+
+#[derive(Deserialize)]
+struct ChatCompletionChoice {
+  message: ChatCompletionMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatCompletionMessage {
+  #[serde(default)]
+  content: String,
+}
+
+#[derive(Deserialize)]
+struct ChatCompletionChunk {
+  #[serde(default)]
+  choices: Vec<ChatCompletionChunkChoice>,
+  #[serde(default)]
+  usage: Option<ChatCompletionUsage>,
+}
+
+#[derive(Deserialize)]
+struct ChatCompletionChunkChoice {
+  delta: ChatCompletionDelta,
+}
+
+#[derive(Deserialize)]
+struct ChatCompletionDelta {
+  #[serde(default)]
+  content: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct ChatCompletionUsage {
+  #[serde(default)]
+  prompt_tokens: i64,
+  #[serde(default)]
+  completion_tokens: i64,
+}
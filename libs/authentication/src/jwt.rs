@@ -1,5 +1,5 @@
 use actix_http::Payload;
-use actix_web::{web::Data, FromRequest, HttpRequest};
+use actix_web::{web::Data, FromRequest, HttpMessage, HttpRequest};
 
 use gotrue_entity::gotrue_jwt::GoTrueJWTClaims;
 use secrecy::{ExposeSecret, Secret};
@@ -41,12 +41,37 @@ impl Display for UserToken {
   }
 }
 
+/// The user a request acts as when it is authenticated with an API key instead of a GoTrue token.
+/// The middleware that verifies the key inserts it into the extensions of the request.
+#[derive(Debug, Clone, Copy)]
+pub struct ApiKeyPrincipal {
+  pub user_uuid: Uuid,
+  pub workspace_id: Uuid,
+}
+
+/// Only the requests authenticated with an API key have a principal.
+impl FromRequest for ApiKeyPrincipal {
+  type Error = actix_web::Error;
+
+  type Future = std::future::Ready<Result<Self, Self::Error>>;
+
+  fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+    let principal = req.extensions().get::<ApiKeyPrincipal>().copied();
+    std::future::ready(principal.ok_or(actix_web::error::ErrorUnauthorized(
+      "The request is not authenticated with an api key",
+    )))
+  }
+}
+
 impl FromRequest for UserUuid {
   type Error = actix_web::Error;
 
   type Future = std::future::Ready<Result<Self, Self::Error>>;
 
   fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+    if let Some(principal) = req.extensions().get::<ApiKeyPrincipal>() {
+      return std::future::ready(Ok(UserUuid(principal.user_uuid)));
+    }
     let auth = get_auth_from_request(req);
     match auth {
       Ok(auth) => match UserUuid::from_auth(auth) {
@@ -68,6 +93,9 @@ impl FromRequest for OptionalUserUuid {
   type Future = std::future::Ready<Result<Self, Self::Error>>;
 
   fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+    if let Some(principal) = req.extensions().get::<ApiKeyPrincipal>() {
+      return std::future::ready(Ok(OptionalUserUuid(Some(UserUuid(principal.user_uuid)))));
+    }
     let auth = get_auth_from_request(req);
     match auth {
       Ok(auth) => match UserUuid::from_auth(auth) {
@@ -381,6 +381,8 @@ impl TestClient {
         workspace_id: workspace_id.to_string(),
         object_id: object_id.to_string(),
         access_level,
+        expires_at: None,
+        group_id: None,
       })
       .await
       .unwrap();
@@ -401,6 +403,8 @@ impl TestClient {
         workspace_id: workspace_id.to_string(),
         object_id: object_id.to_string(),
         access_level,
+        expires_at: None,
+        group_id: None,
       })
       .await
       .unwrap();
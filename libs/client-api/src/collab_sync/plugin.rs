@@ -112,7 +112,7 @@ where
               break;
             }
           },
-          ConnectState::Unauthorized | ConnectState::Lost => {
+          ConnectState::Unauthorized | ConnectState::Lost | ConnectState::UpgradeRequired => {
             if let Some(sync_queue) = weak_sync_queue.upgrade() {
               // Stop sync if the websocket is unauthorized or disconnected
               sync_queue.pause();
@@ -2,9 +2,16 @@ use crate::notify::{ClientToken, TokenStateReceiver};
 use app_error::AppError;
 use client_api_entity::auth_dto::DeleteUserQuery;
 use client_api_entity::workspace_dto::FolderView;
-use client_api_entity::workspace_dto::QueryWorkspaceFolder;
 use client_api_entity::workspace_dto::QueryWorkspaceParam;
 use client_api_entity::workspace_dto::SectionItems;
+use client_api_entity::workspace_dto::{
+  FavoriteViewParams, RecordViewVisitParams, TrashViewsParams,
+};
+use client_api_entity::workspace_dto::{
+  FolderIntegrityReport, FolderViewAncestor, FolderViewConnection, FolderViewSearchResult,
+  QueryFolderChildrenConnection, QueryFolderViewSearch, QueryWorkspaceFolder,
+  QueryWorkspaceFolders, RepairFolderIntegrityParams, UpdateUserViewOrder, WorkspaceFolderResult,
+};
 use client_api_entity::AuthProvider;
 use client_api_entity::CollabType;
 use gotrue::grant::PasswordGrant;
@@ -12,6 +19,7 @@ use gotrue::grant::{Grant, RefreshTokenGrant};
 use gotrue::params::MagicLinkParams;
 use gotrue::params::{AdminUserParams, GenerateLinkParams};
 use shared_entity::dto::workspace_dto::{CreateWorkspaceParam, PatchWorkspaceParam};
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 #[cfg(feature = "enable_brotli")]
 use std::io::Read;
@@ -22,12 +30,15 @@ use reqwest::RequestBuilder;
 
 use client_api_entity::{
   AFSnapshotMeta, AFSnapshotMetas, AFUserProfile, AFUserWorkspaceInfo, AFWorkspace,
-  QuerySnapshotParams, SnapshotData,
+  QuerySnapshotParams, SnapshotData, WorkspaceUsage,
 };
 use semver::Version;
 use shared_entity::dto::auth_dto::SignInTokenResponse;
 use shared_entity::dto::auth_dto::UpdateUserParams;
-use shared_entity::dto::workspace_dto::WorkspaceSpaceUsage;
+use shared_entity::dto::auth_dto::{AccountDeletion, RequestAccountDeletionParams};
+use shared_entity::dto::workspace_dto::{
+  WorkspaceAIUsage, WorkspaceSpaceUsage, WorkspaceStorageFootprint, WorkspaceStorageQuota,
+};
 use shared_entity::response::{AppResponse, AppResponseError};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
@@ -38,11 +49,14 @@ use url::Url;
 use crate::ws::ConnectInfo;
 use client_api_entity::SignUpResponse::{Authenticated, NotAuthenticated};
 use client_api_entity::{GotrueTokenResponse, UpdateGotrueUserParams, User};
+use collab_rt_entity::{RealtimeCompression, RealtimeProtocolVersion};
 use shared_entity::dto::ai_dto::AIModel;
 
 pub const X_COMPRESSION_TYPE: &str = "X-Compression-Type";
 pub const X_COMPRESSION_BUFFER_SIZE: &str = "X-Compression-Buffer-Size";
 pub const X_COMPRESSION_TYPE_BROTLI: &str = "brotli";
+pub use client_api_entity::X_OBJECT_TOKEN;
+pub const X_PUBLISH_ACCESS_TOKEN: &str = "X-Publish-Access-Token";
 
 #[derive(Clone)]
 pub struct ClientConfiguration {
@@ -53,6 +67,9 @@ pub struct ClientConfiguration {
   /// A larger buffer size means more data is compressed in a single operation, which can lead to better compression ratios
   /// since Brotli has more data to analyze for patterns and repetitions.
   pub(crate) compression_buffer_size: usize,
+  /// Whether the client asks the server to compress the realtime messages. The messages are only
+  /// compressed when the server supports it and has compression enabled.
+  pub(crate) ws_compression: bool,
 }
 
 impl ClientConfiguration {
@@ -70,6 +87,11 @@ impl ClientConfiguration {
     };
     self
   }
+
+  pub fn with_ws_compression(mut self, ws_compression: bool) -> Self {
+    self.ws_compression = ws_compression;
+    self
+  }
 }
 
 impl Default for ClientConfiguration {
@@ -77,6 +99,7 @@ impl Default for ClientConfiguration {
     Self {
       compression_quality: 8,
       compression_buffer_size: 10240,
+      ws_compression: true,
     }
   }
 }
@@ -697,6 +720,104 @@ impl Client {
       .query(&QueryWorkspaceFolder {
         depth,
         root_view_id,
+        ..Default::default()
+      })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<FolderView>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the folders of the workspaces, keyed by workspace id. A workspace that can not be read
+  /// fails on its own.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_folders(
+    &self,
+    workspace_ids: Vec<uuid::Uuid>,
+    depth: Option<u32>,
+  ) -> Result<HashMap<String, WorkspaceFolderResult>, AppResponseError> {
+    let url = format!("{}/api/workspace/folder/batch", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&QueryWorkspaceFolders {
+        workspace_ids,
+        depth,
+      })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<HashMap<String, WorkspaceFolderResult>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Scan the folder of the workspace for orphaned views, duplicated children and cycles. Only the
+  /// owner of the workspace can check it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn check_folder_integrity(
+    &self,
+    workspace_id: &str,
+  ) -> Result<FolderIntegrityReport, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/folder/integrity",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<FolderIntegrityReport>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Move the orphaned views under the workspace and break the cycles of the folder. The repair
+  /// is refused unless `confirm` is set. Returns the issues found before the repair.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn repair_folder_integrity(
+    &self,
+    workspace_id: &str,
+    confirm: bool,
+  ) -> Result<FolderIntegrityReport, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/folder/integrity",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&RepairFolderIntegrityParams { confirm })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<FolderIntegrityReport>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Same as [Client::get_workspace_folder], with the permissions of the current user set on
+  /// every view.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_folder_with_permissions(
+    &self,
+    workspace_id: &str,
+    depth: Option<u32>,
+    root_view_id: Option<String>,
+  ) -> Result<FolderView, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/folder", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QueryWorkspaceFolder {
+        depth,
+        root_view_id,
+        include_permissions: Some(true),
+        ..Default::default()
       })
       .send()
       .await?;
@@ -706,6 +827,181 @@ impl Client {
       .into_data()
   }
 
+  /// Same as [Client::get_workspace_folder], with the views restricted by the layouts, the space
+  /// and the private flag of the query.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_folder_with_query(
+    &self,
+    workspace_id: &str,
+    query: &QueryWorkspaceFolder,
+  ) -> Result<FolderView, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/folder", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<FolderView>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Runs a read-only GraphQL query over a workspace, its folder, its members and its published
+  /// views. Returns the GraphQL response, whose `errors` field lists the errors of the query.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn query_workspace_graphql(
+    &self,
+    query: &str,
+    variables: serde_json::Value,
+  ) -> Result<serde_json::Value, AppResponseError> {
+    let url = format!("{}/api/graphql", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&serde_json::json!({ "query": query, "variables": variables }))
+      .send()
+      .await?;
+    log_request_id(&resp);
+    if !resp.status().is_success() {
+      return AppResponse::<serde_json::Value>::from_response(resp)
+        .await?
+        .into_data();
+    }
+    Ok(resp.json().await?)
+  }
+
+  /// Set the user's own order of the children of `parent_view_id`. The order only applies to the
+  /// folder returned by [Client::get_workspace_folder] for this user.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn set_user_view_order(
+    &self,
+    workspace_id: &str,
+    parent_view_id: &str,
+    ordered_child_ids: Vec<String>,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/folder/order",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&UpdateUserViewOrder {
+        parent_view_id: parent_view_id.to_string(),
+        ordered_child_ids,
+      })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Search the workspace folder for views whose name contains `query`, ignoring case.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn search_folder_views(
+    &self,
+    workspace_id: &str,
+    query: &str,
+    include_ancestors: bool,
+  ) -> Result<Vec<FolderViewSearchResult>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/folder/search",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QueryFolderViewSearch {
+        query: query.to_string(),
+        include_ancestors,
+      })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<FolderViewSearchResult>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns a page of the children of the view. Pass the `end_cursor` of a page as `after` to
+  /// get the next one.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_folder_children_connection(
+    &self,
+    workspace_id: &str,
+    parent_view_id: &str,
+    first: Option<u32>,
+    after: Option<String>,
+  ) -> Result<FolderViewConnection, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/folder/{}/children",
+      self.base_url, workspace_id, parent_view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QueryFolderChildrenConnection {
+        first,
+        after,
+        ..Default::default()
+      })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<FolderViewConnection>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the ancestors of the view, from the top-most space down to its direct parent.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_folder_view_ancestors(
+    &self,
+    workspace_id: &str,
+    view_id: &str,
+  ) -> Result<Vec<FolderViewAncestor>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/folder/{}/ancestors",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<FolderViewAncestor>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Same as [Client::get_folder_children_connection], with the children restricted by the
+  /// layouts and the private flag of the query.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_folder_children_connection_with_query(
+    &self,
+    workspace_id: &str,
+    parent_view_id: &str,
+    query: &QueryFolderChildrenConnection,
+  ) -> Result<FolderViewConnection, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/folder/{}/children",
+      self.base_url, workspace_id, parent_view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<FolderViewConnection>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   #[instrument(level = "info", skip_all, err)]
   pub async fn open_workspace(&self, workspace_id: &str) -> Result<AFWorkspace, AppResponseError> {
     let url = format!("{}/api/workspace/{}/open", self.base_url, workspace_id);
@@ -754,6 +1050,47 @@ impl Client {
       .into_data()
   }
 
+  /// Record a visit of the view, which moves it to the top of the recent views of the user.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn record_view_visit(
+    &self,
+    workspace_id: &str,
+    view_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/workspace/{}/recent", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&RecordViewVisitParams {
+        view_id: view_id.to_string(),
+      })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn set_view_favorite(
+    &self,
+    workspace_id: &str,
+    view_id: &str,
+    is_favorite: bool,
+  ) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/workspace/{}/favorite", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&FavoriteViewParams {
+        view_id: view_id.to_string(),
+        is_favorite,
+      })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
   #[instrument(level = "info", skip_all, err)]
   pub async fn get_workspace_trash(
     &self,
@@ -771,6 +1108,43 @@ impl Client {
       .into_data()
   }
 
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn move_views_to_trash(
+    &self,
+    workspace_id: &str,
+    view_ids: Vec<String>,
+  ) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/workspace/{}/trash", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&TrashViewsParams { view_ids })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn restore_views_from_trash(
+    &self,
+    workspace_id: &str,
+    view_ids: Vec<String>,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/trash/restore",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&TrashViewsParams { view_ids })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
   #[instrument(skip_all, err)]
   pub async fn sign_in_password(
     &self,
@@ -869,6 +1243,52 @@ impl Client {
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
 
+  /// Mark the account for deletion. Unlike [Client::delete_user], the account can be restored
+  /// with [Client::cancel_account_deletion] until the grace period ends.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn request_account_deletion(
+    &self,
+    params: RequestAccountDeletionParams,
+  ) -> Result<AccountDeletion, AppResponseError> {
+    let url = format!("{}/api/user/deletion", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AccountDeletion>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_account_deletion(&self) -> Result<AccountDeletion, AppResponseError> {
+    let url = format!("{}/api/user/deletion", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AccountDeletion>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn cancel_account_deletion(&self) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/user/deletion", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
   pub async fn get_snapshot_list(
     &self,
     workspace_id: &str,
@@ -947,6 +1367,12 @@ impl Client {
       access_token: self.access_token()?,
       client_version: self.client_version.clone(),
       device_id: self.device_id.clone(),
+      compression: if self.config.ws_compression {
+        RealtimeCompression::Brotli
+      } else {
+        RealtimeCompression::None
+      },
+      protocol_version: RealtimeProtocolVersion::CURRENT,
     })
   }
 
@@ -967,6 +1393,85 @@ impl Client {
       .into_data()
   }
 
+  /// Returns the activity of the workspace: its documents, active editors, storage and published
+  /// views. Only the owner of the workspace can see it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_usage_metrics(
+    &self,
+    workspace_id: &str,
+  ) -> Result<WorkspaceUsage, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/usage", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceUsage>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_storage_footprint(
+    &self,
+    workspace_id: &str,
+  ) -> Result<WorkspaceStorageFootprint, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/storage-footprint",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceStorageFootprint>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the storage used by the workspace and the quota of its plan.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_storage_quota(
+    &self,
+    workspace_id: &str,
+  ) -> Result<WorkspaceStorageQuota, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/storage-quota",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceStorageQuota>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the AI usage of the workspace this month against the quota of its plan. Only the
+  /// owners of the workspace can see it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_ai_usage(
+    &self,
+    workspace_id: &str,
+  ) -> Result<WorkspaceAIUsage, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/ai-usage", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceAIUsage>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   // Refresh token if given timestamp is close to the token expiration time
   pub async fn refresh_if_expired(&self, ts: i64, reason: &str) -> Result<(), AppResponseError> {
     let expires_at = self.token_expires_at()?;
@@ -0,0 +1,28 @@
+use crate::http::log_request_id;
+use crate::Client;
+use reqwest::Method;
+use shared_entity::dto::admin_dto::{QueryRealtimeHealth, RealtimeHealth};
+use shared_entity::response::{AppResponse, AppResponseError};
+use tracing::instrument;
+
+impl Client {
+  /// Returns the state of the realtime collaboration on the server. Only the administrator of the
+  /// server can call it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_realtime_health(
+    &self,
+    limit: Option<usize>,
+  ) -> Result<RealtimeHealth, AppResponseError> {
+    let url = format!("{}/api/admin/realtime/health", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QueryRealtimeHealth { limit })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<RealtimeHealth>::from_response(resp)
+      .await?
+      .into_data()
+  }
+}
@@ -1,9 +1,12 @@
 use crate::http::log_request_id;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::http_chat::QuestionStream;
 use crate::Client;
 use reqwest::Method;
 use shared_entity::dto::ai_dto::{
-  CompleteTextParams, CompleteTextResponse, LocalAIConfig, SummarizeRowParams,
-  SummarizeRowResponse, TranslateRowParams, TranslateRowResponse,
+  CompleteTextParams, CompleteTextResponse, LocalAIConfig, SummarizeDocumentParams,
+  SummarizeDocumentResponse, SummarizeRowParams, SummarizeRowResponse, TranslateDocumentParams,
+  TranslateDocumentResponse, TranslateRowParams, TranslateRowResponse, WorkspaceChatParams,
 };
 use shared_entity::response::{AppResponse, AppResponseError};
 use std::time::Duration;
@@ -33,6 +36,80 @@ impl Client {
       .into_data()
   }
 
+  /// Summarize the content of a Document collab with the language model of the server.
+  #[instrument(level = "info", skip_all)]
+  pub async fn summarize_document(
+    &self,
+    workspace_id: &str,
+    params: SummarizeDocumentParams,
+  ) -> Result<SummarizeDocumentResponse, AppResponseError> {
+    let url = format!(
+      "{}/api/ai/{}/summarize_document",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<SummarizeDocumentResponse>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Translate the content of a Document collab with the language model of the server, and put
+  /// the translation in a new view next to the document when `params.create_view` is set.
+  #[instrument(level = "info", skip_all)]
+  pub async fn translate_document(
+    &self,
+    workspace_id: &str,
+    params: TranslateDocumentParams,
+  ) -> Result<TranslateDocumentResponse, AppResponseError> {
+    let url = format!(
+      "{}/api/ai/{}/translate_document",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<TranslateDocumentResponse>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Ask a question about the documents of the workspace. The sources of the answer come first in
+  /// the stream, as [QuestionStreamValue::Metadata], followed by the answer.
+  ///
+  /// [QuestionStreamValue::Metadata]: crate::entity::QuestionStreamValue::Metadata
+  #[cfg(not(target_arch = "wasm32"))]
+  #[instrument(level = "info", skip_all)]
+  pub async fn stream_workspace_chat(
+    &self,
+    workspace_id: &str,
+    params: WorkspaceChatParams,
+  ) -> Result<QuestionStream, AppResponseError> {
+    let url = format!(
+      "{}/api/ai/{}/workspace_chat/stream",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&params)
+      .timeout(Duration::from_secs(60))
+      .send()
+      .await?;
+    log_request_id(&resp);
+    let stream = AppResponse::<serde_json::Value>::json_response_stream(resp).await?;
+    Ok(QuestionStream::new(stream))
+  }
+
   #[instrument(level = "info", skip_all)]
   pub async fn translate_row(
     &self,
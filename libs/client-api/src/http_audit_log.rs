@@ -0,0 +1,29 @@
+use crate::http::log_request_id;
+use crate::Client;
+use reqwest::Method;
+use shared_entity::dto::workspace_dto::{AuditLogPage, QueryAuditLog};
+use shared_entity::response::{AppResponse, AppResponseError};
+use tracing::instrument;
+
+impl Client {
+  /// Returns a page of the audit log of the workspace, the most recent entries first. Pass the
+  /// `end_cursor` of a page as `after` to get the next one. Only the owner can.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_audit_log(
+    &self,
+    workspace_id: &str,
+    query: &QueryAuditLog,
+  ) -> Result<AuditLogPage, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/audit-log", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AuditLogPage>::from_response(resp)
+      .await?
+      .into_data()
+  }
+}
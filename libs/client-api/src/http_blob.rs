@@ -76,6 +76,44 @@ impl Client {
     )
   }
 
+  /// The url of the thumbnail of an image uploaded with the v1 api. The thumbnail fits in a square
+  /// of the given size, which the server rounds up to one of the sizes it generates.
+  pub fn get_thumbnail_url_v1(
+    &self,
+    workspace_id: &str,
+    parent_dir: &str,
+    file_id: &str,
+    size: u32,
+  ) -> String {
+    let parent_dir = utf8_percent_encode(parent_dir, NON_ALPHANUMERIC).to_string();
+    format!(
+      "{}/api/file_storage/{workspace_id}/v1/thumbnail/{parent_dir}/{file_id}?size={size}",
+      self.base_url
+    )
+  }
+
+  #[instrument(level = "info", skip_all)]
+  pub async fn get_thumbnail_v1(
+    &self,
+    workspace_id: &str,
+    parent_dir: &str,
+    file_id: &str,
+    size: u32,
+  ) -> Result<(Mime, Vec<u8>), AppResponseError> {
+    let url = self.get_thumbnail_url_v1(workspace_id, parent_dir, file_id, size);
+    let (mime, bytes) = self.get_blob(&url).await?;
+    // The errors are sent back as a json response, the thumbnail as an image.
+    if mime.essence_str() == mime::APPLICATION_JSON.essence_str() {
+      let resp = serde_json::from_slice::<AppResponse<()>>(&bytes)
+        .map_err(|err| AppError::Unhandled(err.to_string()))?;
+      resp.into_error()?;
+      return Err(AppResponseError::from(AppError::Unhandled(
+        "expected the thumbnail as an image".to_string(),
+      )));
+    }
+    Ok((mime, bytes))
+  }
+
   /// Returns the workspace_id, parent_dir, and file_id from the given blob url.
   pub fn parse_blob_url_v1(&self, url: &str) -> Option<(String, String, String)> {
     let parsed_url = Url::parse(url).ok()?;
@@ -1,9 +1,17 @@
-use crate::http::log_request_id;
+use crate::http::{log_request_id, X_OBJECT_TOKEN};
 use crate::{blocking_brotli_compress, Client};
 use app_error::AppError;
+use chrono::{DateTime, Utc};
+use client_api_entity::workspace_dto::{
+  CollabDeltaSyncParams, CollabDeltaSyncResponse, CollabResponse, CollabTypeParam,
+  DeletedObjectSummary,
+};
 use client_api_entity::{
-  BatchQueryCollabParams, BatchQueryCollabResult, CreateCollabParams, DeleteCollabParams,
-  QueryCollab,
+  AFAccessLevel, AFCollabVersion, AFSnapshotMeta, BatchQueryCollabParams, BatchQueryCollabResult,
+  CollabContributors, CollabObjectToken, CollabPresences, CollabShareLink, CollabType,
+  CreateCollabObjectTokenParams, CreateCollabParams, CreateShareLinkParams, DeleteCollabParams,
+  DocumentVersionDiff, EncodedCollab, QueryCollab, QueryCollabVersionDiff, SharePermission,
+  UpdateShareLinkParams,
 };
 use reqwest::Method;
 use shared_entity::response::{AppResponse, AppResponseError};
@@ -118,4 +126,498 @@ impl Client {
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
+
+  /// Delete the object along with its published record, its members and its entry in the folder.
+  /// Its child views take its place under its parent. Only the owner of the workspace can delete
+  /// an object this way.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_object(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<DeletedObjectSummary, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/object/{}",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<DeletedObjectSummary>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Create a token that grants access to the given collab without a user session.
+  /// The access level of the token is capped at the access level of the current user.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_collab_object_token(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    access_level: AFAccessLevel,
+    expires_at: Option<DateTime<Utc>>,
+  ) -> Result<CollabObjectToken, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/token",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&CreateCollabObjectTokenParams {
+        access_level,
+        expires_at,
+      })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabObjectToken>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn revoke_collab_object_token(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    token_id: &uuid::Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/token/{}",
+      self.base_url, workspace_id, object_id, token_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Read a collab with an object-scoped token. No login is required.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_collab_with_object_token(
+    &self,
+    object_token: &str,
+    object_id: &str,
+    collab_type: CollabType,
+  ) -> Result<CollabResponse, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/object-token/collab/{}",
+      self.base_url, object_id
+    );
+    let resp = self
+      .cloud_client
+      .get(&url)
+      .header(X_OBJECT_TOKEN, object_token)
+      .query(&CollabTypeParam { collab_type })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabResponse>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Create a link that grants `permission` on the collab to anyone who opens it, until it
+  /// expires or has been used `max_uses` times. Only the users with full access to the collab can
+  /// manage its share links.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_collab_share_link(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    permission: SharePermission,
+    expires_at: Option<DateTime<Utc>>,
+    max_uses: Option<i32>,
+  ) -> Result<CollabShareLink, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/share-link",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&CreateShareLinkParams {
+        permission,
+        expires_at,
+        max_uses,
+      })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabShareLink>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_collab_share_links(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<Vec<CollabShareLink>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/share-link",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<CollabShareLink>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_collab_share_link(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    link_id: &uuid::Uuid,
+    permission: SharePermission,
+    expires_at: Option<DateTime<Utc>>,
+    max_uses: Option<i32>,
+  ) -> Result<CollabShareLink, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/share-link/{}",
+      self.base_url, workspace_id, object_id, link_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&UpdateShareLinkParams {
+        permission,
+        expires_at,
+        max_uses,
+      })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabShareLink>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_collab_share_link(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    link_id: &uuid::Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/share-link/{}",
+      self.base_url, workspace_id, object_id, link_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Issue a new token for the share link. The old token stops working immediately.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn rotate_collab_share_link(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    link_id: &uuid::Uuid,
+  ) -> Result<CollabShareLink, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/share-link/{}/rotate",
+      self.base_url, workspace_id, object_id, link_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabShareLink>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Revoke every share link of the collab.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_all_collab_share_links(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/share-link",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Returns the share links of the workspace that can still be used, most recently used first.
+  /// Only the owner of the workspace can list them.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_workspace_share_links(
+    &self,
+    workspace_id: &str,
+  ) -> Result<Vec<CollabShareLink>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/share-link",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<CollabShareLink>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Stop the collab, and the views below it, from inheriting the access granted on the views
+  /// above it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn break_collab_inheritance(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/inheritance",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn restore_collab_inheritance(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/inheritance",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Read the collab of a share link. No user session is required.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_collab_with_share_link(
+    &self,
+    share_link_token: &str,
+    collab_type: CollabType,
+  ) -> Result<CollabResponse, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/share-link/{}/collab",
+      self.base_url, share_link_token
+    );
+    let resp = self
+      .cloud_client
+      .get(&url)
+      .query(&CollabTypeParam { collab_type })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabResponse>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the versions of the collab, newest first.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_collab_versions(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<Vec<AFCollabVersion>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/version",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<AFCollabVersion>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_collab_version(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    snapshot_id: i64,
+  ) -> Result<EncodedCollab, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/version/{}",
+      self.base_url, workspace_id, object_id, snapshot_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<EncodedCollab>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Compare two versions of a document, or a version with the current state of the document
+  /// when the query has no `to` side.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn diff_collab_versions(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    query: &QueryCollabVersionDiff,
+  ) -> Result<DocumentVersionDiff, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/version/diff",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<DocumentVersionDiff>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Restore the collab to the version. Returns the version that holds the state of the collab
+  /// before the restore.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn restore_collab_version(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    snapshot_id: i64,
+    collab_type: CollabType,
+  ) -> Result<AFSnapshotMeta, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/version/{}/restore",
+      self.base_url, workspace_id, object_id, snapshot_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&collab_type)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFSnapshotMeta>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the users that currently have the collab open over the realtime connection, most
+  /// recently active first.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_collab_presences(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<CollabPresences, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/presence",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabPresences>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the users who edited the collab, most recent first, along with who edited it last.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_collab_contributors(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<CollabContributors, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/contributors",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabContributors>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Sync the collab without the realtime connection: sends the changes made locally, if any,
+  /// and returns the changes missing from the local copy given its state vector.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delta_sync_collab(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    params: CollabDeltaSyncParams,
+  ) -> Result<CollabDeltaSyncResponse, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/sync",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabDeltaSyncResponse>::from_response(resp)
+      .await?
+      .into_data()
+  }
 }
@@ -0,0 +1,118 @@
+use crate::http::log_request_id;
+use crate::Client;
+use client_api_entity::{
+  CollabComment, CollabComments, CreateCollabCommentParams, QueryCollabComments,
+  ResolveCollabCommentParams,
+};
+use reqwest::Method;
+use shared_entity::response::{AppResponse, AppResponseError};
+use tracing::instrument;
+use uuid::Uuid;
+
+fn collab_comment_resources_url(base_url: &str, workspace_id: &str, object_id: &str) -> String {
+  format!(
+    "{}/api/workspace/{}/collab/{}/comment",
+    base_url, workspace_id, object_id
+  )
+}
+
+fn collab_comment_resource_url(
+  base_url: &str,
+  workspace_id: &str,
+  object_id: &str,
+  comment_id: &Uuid,
+) -> String {
+  format!(
+    "{}/{}",
+    collab_comment_resources_url(base_url, workspace_id, object_id),
+    comment_id
+  )
+}
+
+impl Client {
+  /// Comment on a block of the collab, or reply to a comment when `reply_comment_id` is set.
+  /// Members of the workspace are mentioned with `@` followed by their user uuid.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_collab_comment(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    params: &CreateCollabCommentParams,
+  ) -> Result<CollabComment, AppResponseError> {
+    let url = collab_comment_resources_url(&self.base_url, workspace_id, object_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabComment>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the comments on the collab, oldest first. When `block_id` is given, only the
+  /// comments anchored to that block are returned.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_collab_comments(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    block_id: Option<String>,
+  ) -> Result<CollabComments, AppResponseError> {
+    let url = collab_comment_resources_url(&self.base_url, workspace_id, object_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QueryCollabComments { block_id })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabComments>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Resolve the thread started by the comment, or reopen it when `resolved` is false.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn resolve_collab_comment(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    comment_id: &Uuid,
+    resolved: bool,
+  ) -> Result<CollabComment, AppResponseError> {
+    let url = format!(
+      "{}/resolve",
+      collab_comment_resource_url(&self.base_url, workspace_id, object_id, comment_id)
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&ResolveCollabCommentParams { resolved })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabComment>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_collab_comment(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    comment_id: &Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = collab_comment_resource_url(&self.base_url, workspace_id, object_id, comment_id);
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+}
@@ -0,0 +1,184 @@
+use crate::http::log_request_id;
+use crate::Client;
+use reqwest::Method;
+use shared_entity::dto::workspace_dto::{
+  CreateDatabaseRowParams, DatabaseCalendarFeed, DatabaseRow, DatabaseRows, QueryDatabaseRows,
+  QueryDatabaseViewParams, UpdateDatabaseRowParams,
+};
+use shared_entity::response::{AppResponse, AppResponseError};
+use tracing::instrument;
+
+impl Client {
+  /// List the rows of a database view, in the order of the view, together with its fields.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_database_rows(
+    &self,
+    workspace_id: &str,
+    view_id: &str,
+    query: &QueryDatabaseRows,
+  ) -> Result<DatabaseRows, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/row",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<DatabaseRows>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the rows of a database view that match its filters, in the order of its sorts. The
+  /// server evaluates them, so that only the matching rows are downloaded. The filter and sorts
+  /// of the params replace the ones of the view.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn query_database_view(
+    &self,
+    workspace_id: &str,
+    view_id: &str,
+    params: &QueryDatabaseViewParams,
+  ) -> Result<DatabaseRows, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/query",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<DatabaseRows>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Add a row at the end of the database of the view. The clients editing the database receive
+  /// the new row right away.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_database_row(
+    &self,
+    workspace_id: &str,
+    view_id: &str,
+    params: &CreateDatabaseRowParams,
+  ) -> Result<DatabaseRow, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/row",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<DatabaseRow>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_database_row(
+    &self,
+    workspace_id: &str,
+    view_id: &str,
+    row_id: &str,
+    params: &UpdateDatabaseRowParams,
+  ) -> Result<DatabaseRow, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/row/{}",
+      self.base_url, workspace_id, view_id, row_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<DatabaseRow>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_database_row(
+    &self,
+    workspace_id: &str,
+    view_id: &str,
+    row_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/row/{}",
+      self.base_url, workspace_id, view_id, row_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Returns the iCal feed of the database view, which calendar apps can subscribe to.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_database_calendar_feed(
+    &self,
+    workspace_id: &str,
+    view_id: &str,
+  ) -> Result<DatabaseCalendarFeed, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/calendar-feed",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<DatabaseCalendarFeed>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_database_calendar_feed(
+    &self,
+    workspace_id: &str,
+    view_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/calendar-feed",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Returns the iCalendar file of the feed. No login is required.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_database_calendar_feed(&self, token: &str) -> Result<String, AppResponseError> {
+    let url = format!("{}/api/workspace/calendar-feed/{}", self.base_url, token);
+    let resp = self.cloud_client.get(&url).send().await?;
+    log_request_id(&resp);
+    let txt = resp.text().await?;
+    if let Ok(app_err) = serde_json::from_str::<AppResponseError>(&txt) {
+      return Err(app_err);
+    }
+    Ok(txt)
+  }
+}
@@ -0,0 +1,50 @@
+use crate::http::log_request_id;
+use crate::Client;
+use reqwest::Method;
+use shared_entity::dto::workspace_dto::{DuplicateWorkspaceParams, WorkspaceDuplicateTask};
+use shared_entity::response::{AppResponse, AppResponseError};
+use tracing::instrument;
+
+impl Client {
+  /// Start duplicating the workspace into a new workspace. The workspace is duplicated in the
+  /// background, use [Client::get_workspace_duplicate_task] to follow the progress.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn duplicate_workspace(
+    &self,
+    workspace_id: &str,
+    params: &DuplicateWorkspaceParams,
+  ) -> Result<WorkspaceDuplicateTask, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/duplicate", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceDuplicateTask>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_duplicate_task(
+    &self,
+    workspace_id: &str,
+    task_id: &uuid::Uuid,
+  ) -> Result<WorkspaceDuplicateTask, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/duplicate/{}",
+      self.base_url, workspace_id, task_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceDuplicateTask>::from_response(resp)
+      .await?
+      .into_data()
+  }
+}
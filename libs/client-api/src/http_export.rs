@@ -0,0 +1,160 @@
+use crate::http::log_request_id;
+use crate::Client;
+use bytes::Bytes;
+use reqwest::Method;
+use shared_entity::dto::workspace_dto::{
+  DatabaseExportFormat, QueryDatabaseViewExport, UserDataExportTask, WorkspaceExportTask,
+};
+use shared_entity::response::{AppResponse, AppResponseError};
+use tracing::instrument;
+
+impl Client {
+  /// Export the workspace as a zip archive of Markdown documents and CSV databases. Large
+  /// workspaces have to be exported in the background with [Client::start_workspace_export].
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn export_workspace(&self, workspace_id: &str) -> Result<Bytes, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/export", self.base_url, workspace_id);
+    self.get_workspace_export_archive(&url).await
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn start_workspace_export(
+    &self,
+    workspace_id: &str,
+  ) -> Result<WorkspaceExportTask, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/export", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceExportTask>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_export_task(
+    &self,
+    workspace_id: &str,
+    task_id: &uuid::Uuid,
+  ) -> Result<WorkspaceExportTask, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/export/{}",
+      self.base_url, workspace_id, task_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceExportTask>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Download the archive of a background export once it has completed.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn download_workspace_export(
+    &self,
+    workspace_id: &str,
+    task_id: &uuid::Uuid,
+  ) -> Result<Bytes, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/export/{}/download",
+      self.base_url, workspace_id, task_id
+    );
+    self.get_workspace_export_archive(&url).await
+  }
+
+  /// Start exporting everything tied to the user in the background: the profile, the workspace
+  /// memberships, the collabs the user owns and the comments the user wrote.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn start_user_data_export(&self) -> Result<UserDataExportTask, AppResponseError> {
+    let url = format!("{}/api/user/export", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<UserDataExportTask>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_user_data_export_task(
+    &self,
+    task_id: &uuid::Uuid,
+  ) -> Result<UserDataExportTask, AppResponseError> {
+    let url = format!("{}/api/user/export/{}", self.base_url, task_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<UserDataExportTask>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Download the archive of a user data export from the signed `download_url` of its task. The
+  /// link doesn't require authentication.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn download_user_data_export(
+    &self,
+    download_url: &str,
+  ) -> Result<Bytes, AppResponseError> {
+    let url = format!("{}{}", self.base_url, download_url);
+    let resp = self.cloud_client.get(&url).send().await?;
+    log_request_id(&resp);
+    archive_from_response(resp).await
+  }
+
+  /// Export the rows of the database view that match its filters, with the fields that are not
+  /// hidden in the view, as a CSV file or an Excel workbook.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn export_database_view(
+    &self,
+    workspace_id: &str,
+    view_id: &str,
+    format: DatabaseExportFormat,
+  ) -> Result<Bytes, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/export",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QueryDatabaseViewExport {
+        format: Some(format),
+      })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    archive_from_response(resp).await
+  }
+
+  async fn get_workspace_export_archive(&self, url: &str) -> Result<Bytes, AppResponseError> {
+    let resp = self
+      .http_client_with_auth(Method::GET, url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    archive_from_response(resp).await
+  }
+}
+
+async fn archive_from_response(resp: reqwest::Response) -> Result<Bytes, AppResponseError> {
+  let bytes = resp.error_for_status()?.bytes().await?;
+  if let Ok(app_err) = serde_json::from_slice::<AppResponseError>(&bytes) {
+    return Err(app_err);
+  }
+  Ok(bytes)
+}
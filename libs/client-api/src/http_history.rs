@@ -3,7 +3,9 @@ use crate::Client;
 use client_api_entity::CollabType;
 use reqwest::Method;
 use shared_entity::dto::history_dto::{RepeatedSnapshotMeta, SnapshotInfo};
+use shared_entity::dto::workspace_dto::{SnapshotRetentionPolicy, WorkspaceSnapshotRetention};
 use shared_entity::response::{AppResponse, AppResponseError};
+use tracing::instrument;
 
 impl Client {
   pub async fn get_snapshots(
@@ -49,4 +51,69 @@ impl Client {
       .await?
       .into_data()
   }
+
+  /// Returns the snapshot retention policy of the workspace, or the default of the server if the
+  /// workspace doesn't override it. Only the owner can.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_snapshot_retention(
+    &self,
+    workspace_id: &str,
+  ) -> Result<WorkspaceSnapshotRetention, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/snapshot-retention",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceSnapshotRetention>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Overrides the snapshot retention policy of the workspace. Only the owner can.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_workspace_snapshot_retention(
+    &self,
+    workspace_id: &str,
+    policy: SnapshotRetentionPolicy,
+  ) -> Result<WorkspaceSnapshotRetention, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/snapshot-retention",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&policy)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceSnapshotRetention>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Removes the snapshot retention policy of the workspace, which falls back to the default of
+  /// the server. Only the owner can.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_workspace_snapshot_retention(
+    &self,
+    workspace_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/snapshot-retention",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
 }
@@ -0,0 +1,106 @@
+use crate::http::log_request_id;
+use crate::Client;
+use reqwest::Method;
+use shared_entity::dto::workspace_dto::{
+  DatabaseImportTask, QueryWorkspaceImport, WorkspaceImportTask,
+};
+use shared_entity::response::{AppResponse, AppResponseError};
+use tracing::instrument;
+
+impl Client {
+  /// Start importing a zip archive exported from Notion into the workspace. The pages are
+  /// imported in the background, use [Client::get_workspace_import_task] to follow the progress.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn import_notion_archive(
+    &self,
+    workspace_id: &str,
+    parent_view_id: Option<String>,
+    archive: Vec<u8>,
+  ) -> Result<WorkspaceImportTask, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/import/notion",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .query(&QueryWorkspaceImport { parent_view_id })
+      .header(reqwest::header::CONTENT_TYPE, "application/zip")
+      .body(archive)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceImportTask>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_import_task(
+    &self,
+    workspace_id: &str,
+    task_id: &uuid::Uuid,
+  ) -> Result<WorkspaceImportTask, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/import/{}",
+      self.base_url, workspace_id, task_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceImportTask>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Start importing a CSV file into the database of the view. The rows are imported in the
+  /// background, use [Client::get_database_import_task] to follow the progress.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn import_database_csv(
+    &self,
+    workspace_id: &str,
+    view_id: &str,
+    csv: Vec<u8>,
+  ) -> Result<DatabaseImportTask, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/import/csv",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .header(reqwest::header::CONTENT_TYPE, "text/csv")
+      .body(csv)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<DatabaseImportTask>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_database_import_task(
+    &self,
+    workspace_id: &str,
+    view_id: &str,
+    task_id: &uuid::Uuid,
+  ) -> Result<DatabaseImportTask, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/import/{}",
+      self.base_url, workspace_id, view_id, task_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<DatabaseImportTask>::from_response(resp)
+      .await?
+      .into_data()
+  }
+}
@@ -1,15 +1,25 @@
-use crate::http::log_request_id;
+use crate::http::{log_request_id, X_OBJECT_TOKEN};
 use crate::Client;
 use client_api_entity::{
-  AFCollabMember, AFCollabMembers, AFWorkspaceInvitation, AFWorkspaceInvitationStatus,
-  AFWorkspaceMember, CollabMemberIdentify, InsertCollabMemberParams, QueryCollabMembers,
-  QueryWorkspaceMember, UpdateCollabMemberParams,
+  AFAccessLevel, AFCollabMember, AFCollabMemberPage, AFWorkspaceInvitation,
+  AFWorkspaceInvitationStatus, AFWorkspaceMember, BatchCreateCollabMemberResult,
+  CheckMembershipParams, CollabAccessPolicy, CollabMemberIdentify, CollabMembersExport,
+  EffectiveAccess, EnsureCollabMemberOutcome, InsertCollabMemberParams, MergeCollabMembersParams,
+  MergedCollabMembers, QueryCollabMembers, QueryCollabMembersExport, QueryEffectiveAccess,
+  QuerySharePreview, QueryWorkspaceMember, ShareImpact, SharingState, UpdateCollabMemberParams,
+  WorkspaceCapability,
 };
 use reqwest::Method;
 use shared_entity::dto::workspace_dto::{
-  CreateWorkspaceMembers, WorkspaceMemberChangeset, WorkspaceMemberInvitation, WorkspaceMembers,
+  AddWorkspaceGroupMemberParams, AssignWorkspaceRoleParams, CreateWorkspaceGroupParams,
+  CreateWorkspaceInviteLinkParams, CreateWorkspaceMembers, CreateWorkspaceRoleParams,
+  JoinedWorkspace, PendingWorkspaceInvitation, TransferWorkspaceOwnershipParams,
+  UpdateWorkspaceInviteLinkParams, UpdateWorkspaceRoleParams, WorkspaceGroup, WorkspaceInviteLink,
+  WorkspaceMemberChangeset, WorkspaceMemberInvitation, WorkspaceMembers,
+  WorkspaceOwnershipTransferRequest, WorkspaceRole,
 };
 use shared_entity::response::{AppResponse, AppResponseError};
+use std::collections::HashMap;
 use tracing::instrument;
 
 impl Client {
@@ -114,6 +124,198 @@ impl Client {
     Ok(())
   }
 
+  /// Returns the invitations to the workspace that are still pending. Only the owner of the
+  /// workspace can list them.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_pending_workspace_invitations(
+    &self,
+    workspace_id: &str,
+  ) -> Result<Vec<PendingWorkspaceInvitation>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/invitation",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<PendingWorkspaceInvitation>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Send the email of the pending invitation again.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn resend_workspace_invitation(
+    &self,
+    workspace_id: &str,
+    invite_id: &str,
+  ) -> Result<PendingWorkspaceInvitation, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/invitation/{}/resend",
+      self.base_url, workspace_id, invite_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&())
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<PendingWorkspaceInvitation>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Revoke the pending invitation, so that the invitee can no longer accept it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn revoke_workspace_invitation(
+    &self,
+    workspace_id: &str,
+    invite_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/invitation/{}",
+      self.base_url, workspace_id, invite_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_workspace_invite_links(
+    &self,
+    workspace_id: &str,
+  ) -> Result<Vec<WorkspaceInviteLink>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/invite-link",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<WorkspaceInviteLink>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Create a link that lets users join the workspace by themselves, with the role of the link.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_workspace_invite_link(
+    &self,
+    workspace_id: &str,
+    params: &CreateWorkspaceInviteLinkParams,
+  ) -> Result<WorkspaceInviteLink, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/invite-link",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceInviteLink>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_workspace_invite_link(
+    &self,
+    workspace_id: &str,
+    link_id: &str,
+    params: &UpdateWorkspaceInviteLinkParams,
+  ) -> Result<WorkspaceInviteLink, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/invite-link/{}",
+      self.base_url, workspace_id, link_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceInviteLink>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Give the link a new token, the previous token no longer lets anyone join the workspace.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn rotate_workspace_invite_link(
+    &self,
+    workspace_id: &str,
+    link_id: &str,
+  ) -> Result<WorkspaceInviteLink, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/invite-link/{}/rotate",
+      self.base_url, workspace_id, link_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&())
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceInviteLink>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_workspace_invite_link(
+    &self,
+    workspace_id: &str,
+    link_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/invite-link/{}",
+      self.base_url, workspace_id, link_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Join the workspace of the invite link with the token.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn join_workspace_with_invite_link(
+    &self,
+    token: &str,
+  ) -> Result<JoinedWorkspace, AppResponseError> {
+    let url = format!("{}/api/workspace/invite-link/{}/join", self.base_url, token);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&())
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<JoinedWorkspace>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   #[deprecated(note = "use invite_workspace_members instead")]
   #[instrument(level = "info", skip_all, err)]
   pub async fn add_workspace_members<T: Into<CreateWorkspaceMembers>, W: AsRef<str>>(
@@ -202,6 +404,104 @@ impl Client {
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
 
+  /// Add several members to the same collab at once. Members that could not be added are reported
+  /// in the result, the others are added.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn add_collab_members_batch(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    params: Vec<InsertCollabMemberParams>,
+  ) -> Result<BatchCreateCollabMemberResult, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/member/batch",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<BatchCreateCollabMemberResult>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Make sure the user is a member of the collab with exactly the given access level. Unlike
+  /// [Client::add_collab_member], sending the same params again is not an error.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn ensure_collab_member(
+    &self,
+    params: InsertCollabMemberParams,
+  ) -> Result<EnsureCollabMemberOutcome, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/member/ensure",
+      self.base_url, params.workspace_id, params.object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<EnsureCollabMemberOutcome>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Merge the members of `from_object_id` into `object_id`. When a user is a member of both, the
+  /// higher access level wins.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn merge_collab_members(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    from_object_id: &str,
+  ) -> Result<MergedCollabMembers, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/member/merge",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&MergeCollabMembersParams {
+        from_object_id: from_object_id.to_string(),
+      })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<MergedCollabMembers>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the access policies of the collab held by the access control backend. Only the owner
+  /// of the workspace can list them.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_collab_access_policies(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<Vec<CollabAccessPolicy>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/access-policy",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<CollabAccessPolicy>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   #[instrument(level = "info", skip_all, err)]
   pub async fn get_collab_member(
     &self,
@@ -261,43 +561,560 @@ impl Client {
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
 
+  /// Suspend the membership of the user. The member keeps its access level, but has no access
+  /// to the collab until [Client::unsuspend_collab_member] is called.
   #[instrument(level = "info", skip_all, err)]
-  pub async fn get_collab_members(
+  pub async fn suspend_collab_member(
     &self,
-    params: QueryCollabMembers,
-  ) -> Result<AFCollabMembers, AppResponseError> {
+    params: CollabMemberIdentify,
+  ) -> Result<(), AppResponseError> {
     let url = format!(
-      "{}/api/workspace/{}/collab/{}/member/list",
+      "{}/api/workspace/{}/collab/{}/member/suspension",
       self.base_url, params.workspace_id, &params.object_id
     );
     let resp = self
-      .http_client_with_auth(Method::GET, &url)
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn unsuspend_collab_member(
+    &self,
+    params: CollabMemberIdentify,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/member/suspension",
+      self.base_url, params.workspace_id, &params.object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
       .await?
       .json(&params)
       .send()
       .await?;
     log_request_id(&resp);
-    AppResponse::<AFCollabMembers>::from_response(resp)
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_workspace_group(
+    &self,
+    workspace_id: &str,
+    name: &str,
+  ) -> Result<WorkspaceGroup, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/group", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&CreateWorkspaceGroupParams {
+        name: name.to_string(),
+      })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceGroup>::from_response(resp)
       .await?
       .into_data()
   }
 
   #[instrument(level = "info", skip_all, err)]
-  pub async fn get_workspace_member(
+  pub async fn list_workspace_groups(
     &self,
-    params: QueryWorkspaceMember,
-  ) -> Result<AFWorkspaceMember, AppResponseError> {
+    workspace_id: &str,
+  ) -> Result<Vec<WorkspaceGroup>, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/group", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<WorkspaceGroup>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_workspace_group(
+    &self,
+    workspace_id: &str,
+    group_id: &str,
+  ) -> Result<(), AppResponseError> {
     let url = format!(
-      "{}/api/workspace/{}/member/user/{}",
-      self.base_url, params.workspace_id, params.uid,
+      "{}/api/workspace/{}/group/{}",
+      self.base_url, workspace_id, group_id
     );
     let resp = self
-      .http_client_with_auth(Method::GET, &url)
+      .http_client_with_auth(Method::DELETE, &url)
       .await?
       .send()
       .await?;
     log_request_id(&resp);
-    AppResponse::<AFWorkspaceMember>::from_response(resp)
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn add_workspace_group_member(
+    &self,
+    workspace_id: &str,
+    group_id: &str,
+    uid: i64,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/group/{}/member",
+      self.base_url, workspace_id, group_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&AddWorkspaceGroupMemberParams { uid })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn remove_workspace_group_member(
+    &self,
+    workspace_id: &str,
+    group_id: &str,
+    uid: i64,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/group/{}/member/{}",
+      self.base_url, workspace_id, group_id, uid
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Define a role that grants the capabilities to the members it is assigned to. Only the owner
+  /// of the workspace can manage its roles.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_workspace_role(
+    &self,
+    workspace_id: &str,
+    params: &CreateWorkspaceRoleParams,
+  ) -> Result<WorkspaceRole, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/role", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceRole>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_workspace_roles(
+    &self,
+    workspace_id: &str,
+  ) -> Result<Vec<WorkspaceRole>, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/role", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<WorkspaceRole>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_workspace_role(
+    &self,
+    workspace_id: &str,
+    role_id: &str,
+    params: &UpdateWorkspaceRoleParams,
+  ) -> Result<WorkspaceRole, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/role/{}",
+      self.base_url, workspace_id, role_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceRole>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_workspace_role(
+    &self,
+    workspace_id: &str,
+    role_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/role/{}",
+      self.base_url, workspace_id, role_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Assign the role to the member, replacing the custom role the member held before.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn assign_workspace_role(
+    &self,
+    workspace_id: &str,
+    role_id: &str,
+    uid: i64,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/role/{}/member",
+      self.base_url, workspace_id, role_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&AssignWorkspaceRoleParams { uid })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn unassign_workspace_role(
+    &self,
+    workspace_id: &str,
+    role_id: &str,
+    uid: i64,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/role/{}/member/{}",
+      self.base_url, workspace_id, role_id, uid
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Returns the capabilities the user holds in the workspace.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_capabilities(
+    &self,
+    workspace_id: &str,
+  ) -> Result<Vec<WorkspaceCapability>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/capability",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<WorkspaceCapability>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Ask the member with the given email to become the owner of the workspace. The ownership
+  /// changes once the member calls [Client::accept_workspace_ownership_transfer].
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn request_workspace_ownership_transfer(
+    &self,
+    workspace_id: &str,
+    new_owner_email: &str,
+  ) -> Result<WorkspaceOwnershipTransferRequest, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/ownership-transfer",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&TransferWorkspaceOwnershipParams {
+        new_owner_email: new_owner_email.to_string(),
+      })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceOwnershipTransferRequest>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the pending ownership transfer of the workspace, for the owner that requested it or
+  /// the member asked to accept it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_ownership_transfer(
+    &self,
+    workspace_id: &str,
+  ) -> Result<WorkspaceOwnershipTransferRequest, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/ownership-transfer",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceOwnershipTransferRequest>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Cancel the pending ownership transfer as the owner, or decline it as the member asked to
+  /// accept it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn cancel_workspace_ownership_transfer(
+    &self,
+    workspace_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/ownership-transfer",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn accept_workspace_ownership_transfer(
+    &self,
+    workspace_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/ownership-transfer/accept",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&())
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Remove the access the group was granted on the collab with [Client::add_collab_member].
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn remove_collab_group_access(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    group_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/group/{}",
+      self.base_url, workspace_id, object_id, group_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_collab_members(
+    &self,
+    params: QueryCollabMembers,
+  ) -> Result<AFCollabMemberPage, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/member/list",
+      self.base_url, params.workspace_id, &params.object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .json(&params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFCollabMemberPage>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_member(
+    &self,
+    params: QueryWorkspaceMember,
+  ) -> Result<AFWorkspaceMember, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/member/user/{}",
+      self.base_url, params.workspace_id, params.uid,
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFWorkspaceMember>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the members, inherited members, links and publish status of the collab, together
+  /// with the access level of the current user.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_collab_sharing_state(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<SharingState, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/sharing",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<SharingState>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Resolve the access level of the user on the object, the current user when `uid` is `None`.
+  /// With `explain`, the sources that were considered are included. Only the workspace owner can
+  /// resolve the access of other users.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_effective_access(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    uid: Option<i64>,
+    object_token: Option<&str>,
+    explain: bool,
+  ) -> Result<EffectiveAccess, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/access",
+      self.base_url, workspace_id, object_id
+    );
+    let mut builder = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QueryEffectiveAccess { uid, explain });
+    if let Some(object_token) = object_token {
+      builder = builder.header(X_OBJECT_TOKEN, object_token);
+    }
+    let resp = builder.send().await?;
+    log_request_id(&resp);
+    AppResponse::<EffectiveAccess>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the objects the user would gain access to if the view were shared with them at the
+  /// given access level. Nothing is changed.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn preview_share_impact(
+    &self,
+    workspace_id: &str,
+    view_id: &str,
+    uid: i64,
+    access_level: AFAccessLevel,
+  ) -> Result<Vec<ShareImpact>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/share-preview",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QuerySharePreview { uid, access_level })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<ShareImpact>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Exports the members of every collab in the workspace. Only the workspace owner can export.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn export_workspace_collab_members(
+    &self,
+    workspace_id: &str,
+    access_level_filter: Option<AFAccessLevel>,
+  ) -> Result<CollabMembersExport, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab-members/export",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QueryCollabMembersExport {
+        access_level_filter,
+      })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabMembersExport>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the current user's direct access level for each of the objects, or `None` where the
+  /// user is not a member.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn check_membership(
+    &self,
+    workspace_id: &str,
+    object_ids: Vec<String>,
+  ) -> Result<HashMap<String, Option<AFAccessLevel>>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/membership-check",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&CheckMembershipParams { object_ids })
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<HashMap<String, Option<AFAccessLevel>>>::from_response(resp)
       .await?
       .into_data()
   }
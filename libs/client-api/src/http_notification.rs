@@ -0,0 +1,56 @@
+use crate::http::log_request_id;
+use crate::Client;
+use reqwest::Method;
+use shared_entity::dto::notification_dto::{NotificationSettings, Notifications};
+use shared_entity::response::{AppResponse, AppResponseError};
+use tracing::instrument;
+
+impl Client {
+  /// Returns the latest notifications of the user, newest first.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_notifications(&self) -> Result<Notifications, AppResponseError> {
+    let url = format!("{}/api/notification", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Notifications>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_notification_settings(&self) -> Result<NotificationSettings, AppResponseError> {
+    let url = format!("{}/api/notification/settings", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<NotificationSettings>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Choose whether notifications are emailed immediately, as a daily digest, or not at all.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_notification_settings(
+    &self,
+    settings: &NotificationSettings,
+  ) -> Result<NotificationSettings, AppResponseError> {
+    let url = format!("{}/api/notification/settings", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(settings)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<NotificationSettings>::from_response(resp)
+      .await?
+      .into_data()
+  }
+}
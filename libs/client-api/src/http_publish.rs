@@ -1,5 +1,15 @@
 use bytes::Bytes;
-use client_api_entity::{workspace_dto::PublishedDuplicate, PublishInfo, UpdatePublishNamespace};
+use chrono::{DateTime, Utc};
+use client_api_entity::{
+  workspace_dto::{
+    PublishAnalytics, PublishCustomDomain, PublishedDatabaseForm, PublishedDatabaseFormSettings,
+    PublishedDatabaseFormSubmission, PublishedDuplicate, PublishedView, PublishedViewPreview,
+    QueryPublishAnalytics, RegisterPublishCustomDomainParams, SetPublishedDatabaseFormParams,
+    SubmitPublishedDatabaseForm,
+  },
+  PublishInfo, PublishedViewAccessParams, PublishedViewAccessToken, UpdatePublishNamespace,
+  UpdatePublishedViewExpiry, UpdatePublishedViewNav, UpdatePublishedViewPassword,
+};
 use client_api_entity::{
   CreateGlobalCommentParams, CreateReactionParams, DeleteGlobalCommentParams, DeleteReactionParams,
   GetReactionQueryParams, GlobalComments, Reactions,
@@ -8,6 +18,7 @@ use reqwest::Method;
 use shared_entity::response::{AppResponse, AppResponseError};
 use tracing::instrument;
 
+use crate::http::X_PUBLISH_ACCESS_TOKEN;
 use crate::Client;
 
 // Publisher API
@@ -52,6 +63,107 @@ impl Client {
       .into_data()
   }
 
+  /// Register a domain that serves the publish namespace of the workspace. The domain is served
+  /// once the verification TXT record of the returned domain is added to its DNS, and
+  /// [Client::verify_publish_custom_domain] is called.
+  pub async fn register_publish_custom_domain(
+    &self,
+    workspace_id: &str,
+    domain: &str,
+  ) -> Result<PublishCustomDomain, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-domain",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&RegisterPublishCustomDomainParams {
+        domain: domain.to_string(),
+      })
+      .send()
+      .await?;
+    AppResponse::<PublishCustomDomain>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn list_publish_custom_domains(
+    &self,
+    workspace_id: &str,
+  ) -> Result<Vec<PublishCustomDomain>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-domain",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    AppResponse::<Vec<PublishCustomDomain>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn verify_publish_custom_domain(
+    &self,
+    workspace_id: &str,
+    domain: &str,
+  ) -> Result<PublishCustomDomain, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-domain/{}/verify",
+      self.base_url, workspace_id, domain
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    AppResponse::<PublishCustomDomain>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn unregister_publish_custom_domain(
+    &self,
+    workspace_id: &str,
+    domain: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-domain/{}",
+      self.base_url, workspace_id, domain
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Returns the reads of the views published by the workspace over the last `days` days, which
+  /// defaults to 30 days.
+  pub async fn get_publish_analytics(
+    &self,
+    workspace_id: &str,
+    days: Option<u32>,
+  ) -> Result<PublishAnalytics, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-analytics",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QueryPublishAnalytics { days })
+      .send()
+      .await?;
+    AppResponse::<PublishAnalytics>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   pub async fn unpublish_collabs(
     &self,
     workspace_id: &str,
@@ -67,45 +179,201 @@ impl Client {
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
 
-  pub async fn create_comment_on_published_view(
+  pub async fn set_published_view_show_in_nav(
     &self,
+    workspace_id: &str,
     view_id: &uuid::Uuid,
-    comment_content: &str,
-    reply_comment_id: &Option<uuid::Uuid>,
+    show_in_nav: bool,
   ) -> Result<(), AppResponseError> {
     let url = format!(
-      "{}/api/workspace/published-info/{}/comment",
-      self.base_url, view_id
+      "{}/api/workspace/{}/published-info/{}/nav",
+      self.base_url, workspace_id, view_id
     );
     let resp = self
-      .http_client_with_auth(Method::POST, &url)
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&UpdatePublishedViewNav { show_in_nav })
+      .send()
+      .await?;
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Set the time after which the published view is no longer served. `None` removes the expiry.
+  pub async fn set_published_view_expiry(
+    &self,
+    workspace_id: &str,
+    view_id: &uuid::Uuid,
+    expires_at: Option<DateTime<Utc>>,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/published-info/{}/expiry",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
       .await?
-      .json(&CreateGlobalCommentParams {
-        content: comment_content.to_string(),
-        reply_comment_id: *reply_comment_id,
+      .json(&UpdatePublishedViewExpiry { expires_at })
+      .send()
+      .await?;
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Require a password to read the content of the published view. `None` removes the password.
+  pub async fn set_published_view_password(
+    &self,
+    workspace_id: &str,
+    view_id: &uuid::Uuid,
+    password: Option<&str>,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/published-info/{}/password",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&UpdatePublishedViewPassword {
+        password: password.map(|password| password.to_string()),
       })
       .send()
       .await?;
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
 
+  /// Let the visitors of the published database view add rows to it through a form.
+  pub async fn set_published_database_form(
+    &self,
+    workspace_id: &str,
+    view_id: &uuid::Uuid,
+    params: &SetPublishedDatabaseFormParams,
+  ) -> Result<PublishedDatabaseFormSettings, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/published-info/{}/form",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    AppResponse::<PublishedDatabaseFormSettings>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn get_published_database_form_settings(
+    &self,
+    workspace_id: &str,
+    view_id: &uuid::Uuid,
+  ) -> Result<PublishedDatabaseFormSettings, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/published-info/{}/form",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    AppResponse::<PublishedDatabaseFormSettings>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn delete_published_database_form(
+    &self,
+    workspace_id: &str,
+    view_id: &uuid::Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/published-info/{}/form",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  pub async fn create_comment_on_published_view(
+    &self,
+    view_id: &uuid::Uuid,
+    comment_content: &str,
+    reply_comment_id: &Option<uuid::Uuid>,
+  ) -> Result<(), AppResponseError> {
+    self
+      .create_comment_on_published_view_with_access_token(
+        view_id,
+        comment_content,
+        reply_comment_id,
+        None,
+      )
+      .await
+  }
+
+  /// Same as [Client::create_comment_on_published_view], for views that may be password
+  /// protected.
+  pub async fn create_comment_on_published_view_with_access_token(
+    &self,
+    view_id: &uuid::Uuid,
+    comment_content: &str,
+    reply_comment_id: &Option<uuid::Uuid>,
+    access_token: Option<&str>,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published-info/{}/comment",
+      self.base_url, view_id
+    );
+    let mut request =
+      self
+        .http_client_with_auth(Method::POST, &url)
+        .await?
+        .json(&CreateGlobalCommentParams {
+          content: comment_content.to_string(),
+          reply_comment_id: *reply_comment_id,
+        });
+    if let Some(access_token) = access_token {
+      request = request.header(X_PUBLISH_ACCESS_TOKEN, access_token);
+    }
+    let resp = request.send().await?;
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
   pub async fn delete_comment_on_published_view(
     &self,
     view_id: &uuid::Uuid,
     comment_id: &uuid::Uuid,
+  ) -> Result<(), AppResponseError> {
+    self
+      .delete_comment_on_published_view_with_access_token(view_id, comment_id, None)
+      .await
+  }
+
+  /// Same as [Client::delete_comment_on_published_view], for views that may be password
+  /// protected.
+  pub async fn delete_comment_on_published_view_with_access_token(
+    &self,
+    view_id: &uuid::Uuid,
+    comment_id: &uuid::Uuid,
+    access_token: Option<&str>,
   ) -> Result<(), AppResponseError> {
     let url = format!(
       "{}/api/workspace/published-info/{}/comment",
       self.base_url, view_id
     );
-    let resp = self
+    let mut request = self
       .http_client_with_auth(Method::DELETE, &url)
       .await?
       .json(&DeleteGlobalCommentParams {
         comment_id: *comment_id,
-      })
-      .send()
-      .await?;
+      });
+    if let Some(access_token) = access_token {
+      request = request.header(X_PUBLISH_ACCESS_TOKEN, access_token);
+    }
+    let resp = request.send().await?;
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
 
@@ -114,20 +382,36 @@ impl Client {
     reaction_type: &str,
     view_id: &uuid::Uuid,
     comment_id: &uuid::Uuid,
+  ) -> Result<(), AppResponseError> {
+    self
+      .create_reaction_on_comment_with_access_token(reaction_type, view_id, comment_id, None)
+      .await
+  }
+
+  /// Same as [Client::create_reaction_on_comment], for views that may be password protected.
+  pub async fn create_reaction_on_comment_with_access_token(
+    &self,
+    reaction_type: &str,
+    view_id: &uuid::Uuid,
+    comment_id: &uuid::Uuid,
+    access_token: Option<&str>,
   ) -> Result<(), AppResponseError> {
     let url = format!(
       "{}/api/workspace/published-info/{}/reaction",
       self.base_url, view_id
     );
-    let resp = self
-      .http_client_with_auth(Method::POST, &url)
-      .await?
-      .json(&CreateReactionParams {
-        reaction_type: reaction_type.to_string(),
-        comment_id: *comment_id,
-      })
-      .send()
-      .await?;
+    let mut request =
+      self
+        .http_client_with_auth(Method::POST, &url)
+        .await?
+        .json(&CreateReactionParams {
+          reaction_type: reaction_type.to_string(),
+          comment_id: *comment_id,
+        });
+    if let Some(access_token) = access_token {
+      request = request.header(X_PUBLISH_ACCESS_TOKEN, access_token);
+    }
+    let resp = request.send().await?;
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
 
@@ -136,20 +420,35 @@ impl Client {
     reaction_type: &str,
     view_id: &uuid::Uuid,
     comment_id: &uuid::Uuid,
+  ) -> Result<(), AppResponseError> {
+    self
+      .delete_reaction_on_comment_with_access_token(reaction_type, view_id, comment_id, None)
+      .await
+  }
+
+  /// Same as [Client::delete_reaction_on_comment], for views that may be password protected.
+  pub async fn delete_reaction_on_comment_with_access_token(
+    &self,
+    reaction_type: &str,
+    view_id: &uuid::Uuid,
+    comment_id: &uuid::Uuid,
+    access_token: Option<&str>,
   ) -> Result<(), AppResponseError> {
     let url = format!(
       "{}/api/workspace/published-info/{}/reaction",
       self.base_url, view_id
     );
-    let resp = self
+    let mut request = self
       .http_client_with_auth(Method::DELETE, &url)
       .await?
       .json(&DeleteReactionParams {
         reaction_type: reaction_type.to_string(),
         comment_id: *comment_id,
-      })
-      .send()
-      .await?;
+      });
+    if let Some(access_token) = access_token {
+      request = request.header(X_PUBLISH_ACCESS_TOKEN, access_token);
+    }
+    let resp = request.send().await?;
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
 }
@@ -159,16 +458,30 @@ impl Client {
   pub async fn get_published_view_comments(
     &self,
     view_id: &uuid::Uuid,
+  ) -> Result<GlobalComments, AppResponseError> {
+    self
+      .get_published_view_comments_with_access_token(view_id, None)
+      .await
+  }
+
+  /// Same as [Client::get_published_view_comments], for views that may be password protected.
+  pub async fn get_published_view_comments_with_access_token(
+    &self,
+    view_id: &uuid::Uuid,
+    access_token: Option<&str>,
   ) -> Result<GlobalComments, AppResponseError> {
     let url = format!(
       "{}/api/workspace/published-info/{}/comment",
       self.base_url, view_id
     );
-    let client = if let Ok(client) = self.http_client_with_auth(Method::GET, &url).await {
+    let mut client = if let Ok(client) = self.http_client_with_auth(Method::GET, &url).await {
       client
     } else {
       self.http_client_without_auth(Method::GET, &url).await?
     };
+    if let Some(access_token) = access_token {
+      client = client.header(X_PUBLISH_ACCESS_TOKEN, access_token);
+    }
 
     let resp = client.send().await?;
     AppResponse::<GlobalComments>::from_response(resp)
@@ -179,6 +492,37 @@ impl Client {
 
 // Guest API (no login required)
 impl Client {
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_nav(
+    &self,
+    publish_namespace: &str,
+  ) -> Result<Vec<PublishedView>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published-nav/{}",
+      self.base_url, publish_namespace
+    );
+    let resp = self.cloud_client.get(&url).send().await?;
+    AppResponse::<Vec<PublishedView>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_view_preview(
+    &self,
+    publish_namespace: &str,
+    view_id: &uuid::Uuid,
+  ) -> Result<PublishedViewPreview, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published-preview/{}/{}",
+      self.base_url, publish_namespace, view_id
+    );
+    let resp = self.cloud_client.get(&url).send().await?;
+    AppResponse::<PublishedViewPreview>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   #[instrument(level = "debug", skip_all)]
   pub async fn get_published_collab_info(
     &self,
@@ -192,12 +536,89 @@ impl Client {
       .into_data()
   }
 
+  /// Exchange the password of a password-protected published view for a short-lived access
+  /// token, which is required to read its content.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_view_access_token(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    password: &str,
+  ) -> Result<PublishedViewAccessToken, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published/{}/{}/access",
+      self.base_url, publish_namespace, publish_name
+    );
+    let resp = self
+      .cloud_client
+      .post(&url)
+      .json(&PublishedViewAccessParams {
+        password: password.to_string(),
+      })
+      .send()
+      .await?;
+    AppResponse::<PublishedViewAccessToken>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the fields of the form of the published database view.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_database_form(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+  ) -> Result<PublishedDatabaseForm, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published/{}/{}/form",
+      self.base_url, publish_namespace, publish_name
+    );
+    let resp = self.cloud_client.get(&url).send().await?;
+    AppResponse::<PublishedDatabaseForm>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Add a row to the published database view through its form.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn submit_published_database_form(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    params: &SubmitPublishedDatabaseForm,
+  ) -> Result<PublishedDatabaseFormSubmission, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published/{}/{}/form",
+      self.base_url, publish_namespace, publish_name
+    );
+    let resp = self.cloud_client.post(&url).json(params).send().await?;
+    AppResponse::<PublishedDatabaseFormSubmission>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   #[instrument(level = "debug", skip_all)]
   pub async fn get_published_collab<T>(
     &self,
     publish_namespace: &str,
     publish_name: &str,
   ) -> Result<T, AppResponseError>
+  where
+    T: serde::de::DeserializeOwned,
+  {
+    self
+      .get_published_collab_with_access_token(publish_namespace, publish_name, None)
+      .await
+  }
+
+  /// Same as [Client::get_published_collab], for views that may be password protected.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_collab_with_access_token<T>(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    access_token: Option<&str>,
+  ) -> Result<T, AppResponseError>
   where
     T: serde::de::DeserializeOwned,
   {
@@ -211,12 +632,11 @@ impl Client {
       self.base_url, publish_namespace, publish_name
     );
 
-    let resp = self
-      .cloud_client
-      .get(&url)
-      .send()
-      .await?
-      .error_for_status()?;
+    let mut request = self.cloud_client.get(&url);
+    if let Some(access_token) = access_token {
+      request = request.header(X_PUBLISH_ACCESS_TOKEN, access_token);
+    }
+    let resp = request.send().await?.error_for_status()?;
 
     let txt = resp.text().await?;
 
@@ -234,6 +654,19 @@ impl Client {
     &self,
     publish_namespace: &str,
     publish_name: &str,
+  ) -> Result<Bytes, AppResponseError> {
+    self
+      .get_published_collab_blob_with_access_token(publish_namespace, publish_name, None)
+      .await
+  }
+
+  /// Same as [Client::get_published_collab_blob], for views that may be password protected.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_collab_blob_with_access_token(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    access_token: Option<&str>,
   ) -> Result<Bytes, AppResponseError> {
     tracing::debug!(
       "get_published_collab_blob: {} {}",
@@ -244,20 +677,106 @@ impl Client {
       "{}/api/workspace/published/{}/{}/blob",
       self.base_url, publish_namespace, publish_name
     );
-    let bytes = self
+    let mut request = self.cloud_client.get(&url);
+    if let Some(access_token) = access_token {
+      request = request.header(X_PUBLISH_ACCESS_TOKEN, access_token);
+    }
+    let bytes = request.send().await?.error_for_status()?.bytes().await?;
+
+    if let Ok(app_err) = serde_json::from_slice::<AppResponseError>(&bytes) {
+      return Err(app_err);
+    }
+
+    Ok(bytes)
+  }
+
+  /// Returns the published document rendered as sanitized HTML.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_view_html(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    access_token: Option<&str>,
+  ) -> Result<String, AppResponseError> {
+    self
+      .get_published_view_rendered(publish_namespace, publish_name, "html", access_token)
+      .await
+  }
+
+  /// Returns the published document rendered as Markdown.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_view_markdown(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    access_token: Option<&str>,
+  ) -> Result<String, AppResponseError> {
+    self
+      .get_published_view_rendered(publish_namespace, publish_name, "markdown", access_token)
+      .await
+  }
+
+  async fn get_published_view_rendered(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    format: &str,
+    access_token: Option<&str>,
+  ) -> Result<String, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published/{}/{}/{}",
+      self.base_url, publish_namespace, publish_name, format
+    );
+    let mut request = self.cloud_client.get(&url);
+    if let Some(access_token) = access_token {
+      request = request.header(X_PUBLISH_ACCESS_TOKEN, access_token);
+    }
+    let txt = request.send().await?.error_for_status()?.text().await?;
+    if let Ok(app_err) = serde_json::from_str::<AppResponseError>(&txt) {
+      return Err(app_err);
+    }
+    Ok(txt)
+  }
+
+  /// Returns the sitemap.xml of the published site of the namespace.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_sitemap(
+    &self,
+    publish_namespace: &str,
+  ) -> Result<String, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published/{}/sitemap.xml",
+      self.base_url, publish_namespace
+    );
+    self.get_published_site_xml(&url).await
+  }
+
+  /// Returns the Atom feed of the views most recently published or updated in the namespace.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_feed(
+    &self,
+    publish_namespace: &str,
+  ) -> Result<String, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published/{}/feed.xml",
+      self.base_url, publish_namespace
+    );
+    self.get_published_site_xml(&url).await
+  }
+
+  async fn get_published_site_xml(&self, url: &str) -> Result<String, AppResponseError> {
+    let txt = self
       .cloud_client
-      .get(&url)
+      .get(url)
       .send()
       .await?
       .error_for_status()?
-      .bytes()
+      .text()
       .await?;
-
-    if let Ok(app_err) = serde_json::from_slice::<AppResponseError>(&bytes) {
+    if let Ok(app_err) = serde_json::from_str::<AppResponseError>(&txt) {
       return Err(app_err);
     }
-
-    Ok(bytes)
+    Ok(txt)
   }
 
   pub async fn duplicate_published_to_workspace(
@@ -282,19 +801,30 @@ impl Client {
     &self,
     view_id: &uuid::Uuid,
     comment_id: &Option<uuid::Uuid>,
+  ) -> Result<Reactions, AppResponseError> {
+    self
+      .get_published_view_reactions_with_access_token(view_id, comment_id, None)
+      .await
+  }
+
+  /// Same as [Client::get_published_view_reactions], for views that may be password protected.
+  pub async fn get_published_view_reactions_with_access_token(
+    &self,
+    view_id: &uuid::Uuid,
+    comment_id: &Option<uuid::Uuid>,
+    access_token: Option<&str>,
   ) -> Result<Reactions, AppResponseError> {
     let url = format!(
       "{}/api/workspace/published-info/{}/reaction",
       self.base_url, view_id
     );
-    let resp = self
-      .cloud_client
-      .get(url)
-      .query(&GetReactionQueryParams {
-        comment_id: *comment_id,
-      })
-      .send()
-      .await?;
+    let mut request = self.cloud_client.get(url).query(&GetReactionQueryParams {
+      comment_id: *comment_id,
+    });
+    if let Some(access_token) = access_token {
+      request = request.header(X_PUBLISH_ACCESS_TOKEN, access_token);
+    }
+    let resp = request.send().await?;
     AppResponse::<Reactions>::from_response(resp)
       .await?
       .into_data()
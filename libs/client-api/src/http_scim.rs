@@ -0,0 +1,52 @@
+use crate::http::log_request_id;
+use crate::Client;
+use reqwest::Method;
+use shared_entity::dto::workspace_dto::{ScimGroupRole, UpdateScimGroupRoleParams};
+use shared_entity::response::{AppResponse, AppResponseError};
+use tracing::instrument;
+
+impl Client {
+  /// List the groups the identity provider provisioned into the workspace, with the role each
+  /// group is mapped to.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_scim_groups(
+    &self,
+    workspace_id: &str,
+  ) -> Result<Vec<ScimGroupRole>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/scim/group",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<ScimGroupRole>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Map a provisioned group to a role. The members of the group get the role right away.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_scim_group_role(
+    &self,
+    workspace_id: &str,
+    group_id: &str,
+    params: UpdateScimGroupRoleParams,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/scim/group/{}/role",
+      self.base_url, workspace_id, group_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+}
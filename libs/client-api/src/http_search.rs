@@ -1,6 +1,8 @@
 use app_error::ErrorCode;
 use reqwest::Method;
-use shared_entity::dto::search_dto::SearchDocumentResponseItem;
+use shared_entity::dto::search_dto::{
+  SearchDocumentResponseItem, WorkspaceSearchQuery, WorkspaceSearchResultItem,
+};
 use shared_entity::response::{AppResponse, AppResponseError};
 
 use crate::http::log_request_id;
@@ -31,4 +33,23 @@ impl Client {
       .await?
       .into_data()
   }
+
+  /// Full-text search over the documents and databases of the workspace.
+  pub async fn search_workspace(
+    &self,
+    workspace_id: &str,
+    query: WorkspaceSearchQuery,
+  ) -> Result<Vec<WorkspaceSearchResultItem>, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/search", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&query)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<WorkspaceSearchResultItem>>::from_response(resp)
+      .await?
+      .into_data()
+  }
 }
@@ -0,0 +1,114 @@
+use crate::http::log_request_id;
+use crate::Client;
+use reqwest::Method;
+use shared_entity::dto::workspace_dto::{
+  CreateServiceAccountParams, ServiceAccount, ServiceAccountApiKey,
+};
+use shared_entity::response::{AppResponse, AppResponseError};
+use tracing::instrument;
+
+impl Client {
+  /// Create a service account and its first API key. The key is only returned here.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_service_account(
+    &self,
+    workspace_id: &str,
+    params: CreateServiceAccountParams,
+  ) -> Result<ServiceAccountApiKey, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/service-account",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<ServiceAccountApiKey>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_service_accounts(
+    &self,
+    workspace_id: &str,
+  ) -> Result<Vec<ServiceAccount>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/service-account",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<ServiceAccount>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_service_account(
+    &self,
+    workspace_id: &str,
+    service_account_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/service-account/{}",
+      self.base_url, workspace_id, service_account_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Revoke the active API keys of the service account and issue a new one.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn rotate_service_account_api_key(
+    &self,
+    workspace_id: &str,
+    service_account_id: &str,
+  ) -> Result<ServiceAccountApiKey, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/service-account/{}/rotate",
+      self.base_url, workspace_id, service_account_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<ServiceAccountApiKey>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn revoke_service_account_api_key(
+    &self,
+    workspace_id: &str,
+    service_account_id: &str,
+    api_key_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/service-account/{}/key/{}",
+      self.base_url, workspace_id, service_account_id, api_key_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+}
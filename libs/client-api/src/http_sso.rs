@@ -0,0 +1,73 @@
+use crate::http::log_request_id;
+use crate::Client;
+use reqwest::Method;
+use shared_entity::dto::auth_dto::{SsoSignInParams, SsoSignInUrl};
+use shared_entity::dto::workspace_dto::{UpdateWorkspaceSsoParams, WorkspaceSso};
+use shared_entity::response::{AppResponse, AppResponseError};
+use tracing::instrument;
+
+impl Client {
+  /// Returns the url of the SAML identity provider to sign in with. Once signed in, the user is
+  /// sent to `redirect_to` with its tokens, see [Client::sign_in_with_url].
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn generate_sso_url(
+    &self,
+    params: SsoSignInParams,
+  ) -> Result<SsoSignInUrl, AppResponseError> {
+    let url = format!("{}/api/user/sso", self.base_url);
+    let resp = self.cloud_client.post(&url).json(&params).send().await?;
+    log_request_id(&resp);
+    AppResponse::<SsoSignInUrl>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_sso(
+    &self,
+    workspace_id: &str,
+  ) -> Result<WorkspaceSso, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/sso", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceSso>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Configure the SAML identity provider of the workspace. Only the owner can.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_workspace_sso(
+    &self,
+    workspace_id: &str,
+    params: UpdateWorkspaceSsoParams,
+  ) -> Result<WorkspaceSso, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/sso", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceSso>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_workspace_sso(&self, workspace_id: &str) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/workspace/{}/sso", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+}
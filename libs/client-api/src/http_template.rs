@@ -1,9 +1,9 @@
 use client_api_entity::{
   AccountLink, CreateTemplateCategoryParams, CreateTemplateCreatorParams, CreateTemplateParams,
   GetTemplateCategoriesQueryParams, GetTemplateCreatorsQueryParams, GetTemplatesQueryParams,
-  Template, TemplateCategories, TemplateCategory, TemplateCategoryType, TemplateCreator,
-  TemplateCreators, Templates, UpdateTemplateCategoryParams, UpdateTemplateCreatorParams,
-  UpdateTemplateParams,
+  InstantiateTemplateParams, InstantiatedTemplate, Template, TemplateCategories, TemplateCategory,
+  TemplateCategoryType, TemplateCreator, TemplateCreators, Templates, UpdateTemplateCategoryParams,
+  UpdateTemplateCreatorParams, UpdateTemplateParams,
 };
 use reqwest::Method;
 use shared_entity::response::{AppResponse, AppResponseError};
@@ -297,4 +297,28 @@ impl Client {
 
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
+
+  /// Copy the template into the workspace, under `dest_view_id` or at the top level of the
+  /// workspace. Returns the view the template was copied as.
+  pub async fn instantiate_template(
+    &self,
+    workspace_id: &str,
+    view_id: Uuid,
+    dest_view_id: Option<String>,
+  ) -> Result<InstantiatedTemplate, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/template/{}/instantiate",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&InstantiateTemplateParams { dest_view_id })
+      .send()
+      .await?;
+
+    AppResponse::<InstantiatedTemplate>::from_response(resp)
+      .await?
+      .into_data()
+  }
 }
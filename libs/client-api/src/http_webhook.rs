@@ -0,0 +1,102 @@
+use crate::http::log_request_id;
+use crate::Client;
+use reqwest::Method;
+use shared_entity::dto::workspace_dto::{CreateWebhookParams, Webhook, WebhookDeadLetter};
+use shared_entity::response::{AppResponse, AppResponseError};
+use tracing::instrument;
+
+impl Client {
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_webhook(
+    &self,
+    workspace_id: &str,
+    params: CreateWebhookParams,
+  ) -> Result<Webhook, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/webhook", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&params)
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Webhook>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_webhooks(&self, workspace_id: &str) -> Result<Vec<Webhook>, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/webhook", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<Webhook>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_webhook(
+    &self,
+    workspace_id: &str,
+    webhook_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/webhook/{}",
+      self.base_url, workspace_id, webhook_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Returns the callbacks of the webhook that could not be delivered.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_webhook_dead_letters(
+    &self,
+    workspace_id: &str,
+    webhook_id: &str,
+  ) -> Result<Vec<WebhookDeadLetter>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/webhook/{}/dead-letter",
+      self.base_url, workspace_id, webhook_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<WebhookDeadLetter>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn retry_webhook_dead_letter(
+    &self,
+    workspace_id: &str,
+    webhook_id: &str,
+    delivery_id: i64,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/webhook/{}/dead-letter/{}/retry",
+      self.base_url, workspace_id, webhook_id, delivery_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+}
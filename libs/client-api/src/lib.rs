@@ -1,14 +1,26 @@
 mod http;
+mod http_admin;
 mod http_ai;
+mod http_audit_log;
 mod http_billing;
 
 mod http_blob;
 mod http_collab;
+mod http_comment;
+mod http_database;
+mod http_duplicate;
+mod http_export;
 mod http_history;
+mod http_import;
 mod http_member;
+mod http_notification;
 mod http_publish;
+mod http_scim;
+mod http_service_account;
+mod http_sso;
 mod http_template;
 mod http_view;
+mod http_webhook;
 pub use http::*;
 
 #[cfg(feature = "collab-sync")]
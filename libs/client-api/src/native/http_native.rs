@@ -9,17 +9,18 @@ use async_trait::async_trait;
 use rayon::iter::ParallelIterator;
 
 use bytes::Bytes;
-use client_api_entity::{CollabParams, PublishCollabItem, QueryCollabParams};
+use client_api_entity::{CollabParams, CollabType, PublishCollabItem, QueryCollabParams};
 use client_api_entity::{
-  CompleteUploadRequest, CreateUploadRequest, CreateUploadResponse, UploadPartResponse,
+  CompleteUploadRequest, CreateUploadRequest, CreateUploadResponse, ListUploadPartsResponse,
+  UploadPartResponse, UploadPartUrlResponse,
 };
 use collab_rt_entity::HttpRealtimeMessage;
-use futures::Stream;
+use futures::{Stream, TryStreamExt};
 use futures_util::stream;
 use prost::Message;
 use reqwest::{Body, Method};
 use serde::Serialize;
-use shared_entity::dto::workspace_dto::CollabResponse;
+use shared_entity::dto::workspace_dto::{CollabResponse, CollabTypeParam};
 use shared_entity::response::{AppResponse, AppResponseError};
 use std::future::Future;
 
@@ -36,6 +37,7 @@ use tracing::{debug, event, info, instrument, trace};
 
 pub use infra::file_util::ChunkedBytes;
 use shared_entity::dto::ai_dto::CompleteTextParams;
+use shared_entity::dto::publish_dto::PublishOutcome;
 
 impl Client {
   pub async fn stream_completion_text(
@@ -132,6 +134,120 @@ impl Client {
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
 
+  /// Returns a presigned url the part can be uploaded to with [Client::upload_part_to_url]. The
+  /// part number should be 1-based.
+  pub async fn get_upload_part_url(
+    &self,
+    workspace_id: &str,
+    parent_dir: &str,
+    file_id: &str,
+    upload_id: &str,
+    part_number: i32,
+  ) -> Result<UploadPartUrlResponse, AppResponseError> {
+    let parent_dir = utf8_percent_encode(parent_dir, NON_ALPHANUMERIC).to_string();
+    let url = format!(
+      "{}/api/file_storage/{workspace_id}/upload_part_url/{parent_dir}/{file_id}/{upload_id}/{part_number}",
+      self.base_url
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<UploadPartUrlResponse>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Uploads a part to the presigned url returned by [Client::get_upload_part_url].
+  pub async fn upload_part_to_url(
+    &self,
+    url: &str,
+    part_number: i32,
+    body: Vec<u8>,
+  ) -> Result<UploadPartResponse, AppResponseError> {
+    if body.is_empty() {
+      return Err(AppResponseError::from(AppError::InvalidRequest(
+        "Empty body".to_string(),
+      )));
+    }
+
+    let resp = self.cloud_client.put(url).body(body).send().await?;
+    if !resp.status().is_success() {
+      let status = resp.status();
+      let text = resp.text().await.unwrap_or_default();
+      return Err(AppResponseError::from(AppError::Internal(anyhow!(
+        "Failed to upload part {}: {} {}",
+        part_number,
+        status,
+        text
+      ))));
+    }
+    let e_tag = resp
+      .headers()
+      .get(reqwest::header::ETAG)
+      .and_then(|value| value.to_str().ok())
+      .ok_or_else(|| {
+        AppResponseError::from(AppError::Internal(anyhow!(
+          "The response of part {} has no ETag",
+          part_number
+        )))
+      })?
+      .to_string();
+    Ok(UploadPartResponse {
+      e_tag,
+      part_num: part_number,
+    })
+  }
+
+  /// Lists the parts already uploaded, so that an interrupted upload can be resumed by uploading
+  /// only the missing parts before completing it.
+  pub async fn list_upload_parts(
+    &self,
+    workspace_id: &str,
+    parent_dir: &str,
+    file_id: &str,
+    upload_id: &str,
+  ) -> Result<ListUploadPartsResponse, AppResponseError> {
+    let parent_dir = utf8_percent_encode(parent_dir, NON_ALPHANUMERIC).to_string();
+    let url = format!(
+      "{}/api/file_storage/{workspace_id}/upload_parts/{parent_dir}/{file_id}/{upload_id}",
+      self.base_url
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<ListUploadPartsResponse>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Aborts the upload, which deletes the parts already uploaded.
+  pub async fn abort_upload(
+    &self,
+    workspace_id: &str,
+    parent_dir: &str,
+    file_id: &str,
+    upload_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let parent_dir = utf8_percent_encode(parent_dir, NON_ALPHANUMERIC).to_string();
+    let url = format!(
+      "{}/api/file_storage/{workspace_id}/upload/{parent_dir}/{file_id}/{upload_id}",
+      self.base_url
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
   #[instrument(level = "debug", skip_all)]
   pub async fn get_collab(
     &self,
@@ -144,6 +260,42 @@ impl Client {
     RetryIf::spawn(retry_strategy, action, RetryGetCollabCondition).await
   }
 
+  /// Returns the collab encoded in the v1 format as a stream of chunks, which suits the large
+  /// collabs. The chunks put together can be decoded with [EncodedCollab::decode_from_bytes].
+  ///
+  /// [EncodedCollab::decode_from_bytes]: client_api_entity::EncodedCollab::decode_from_bytes
+  #[instrument(level = "debug", skip_all, err)]
+  pub async fn get_collab_stream(
+    &self,
+    params: QueryCollabParams,
+  ) -> Result<impl Stream<Item = Result<Bytes, AppResponseError>>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/v1/{}/collab/{}",
+      self.base_url, &params.workspace_id, &params.object_id
+    );
+    let collab_type = params.collab_type.clone();
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&CollabTypeParam { collab_type })
+      .header(reqwest::header::ACCEPT, "application/octet-stream")
+      .send()
+      .await?;
+    log_request_id(&resp);
+
+    // The errors are sent back as a json response, the collab as a stream of bytes.
+    let is_json = resp
+      .headers()
+      .get(reqwest::header::CONTENT_TYPE)
+      .and_then(|value| value.to_str().ok())
+      .is_some_and(|value| value.starts_with("application/json"));
+    if is_json {
+      AppResponse::<()>::from_response(resp).await?.into_error()?;
+      return Err(AppError::Internal(anyhow!("expected the collab as a stream")).into());
+    }
+    Ok(resp.bytes_stream().map_err(AppResponseError::from))
+  }
+
   #[instrument(level = "debug", skip_all, err)]
   pub async fn post_realtime_msg(
     &self,
@@ -294,6 +446,35 @@ impl Client {
       .await?;
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
+
+  /// Publish the collab only if it has changed since it was last published. The server compares
+  /// the current version of the collab `item.meta.view_id` against the version last published.
+  pub async fn publish_collab_if_changed<Metadata, Data>(
+    &self,
+    workspace_id: &str,
+    collab_type: CollabType,
+    item: PublishCollabItem<Metadata, Data>,
+  ) -> Result<PublishOutcome, AppResponseError>
+  where
+    Metadata: serde::Serialize + Send + 'static + Unpin,
+    Data: AsRef<[u8]> + Send + 'static + Unpin,
+  {
+    let publish_collab_stream = PublishCollabItemStream::new(vec![item]);
+    let url = format!(
+      "{}/api/workspace/{}/publish/if-changed",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .query(&CollabTypeParam { collab_type })
+      .body(Body::wrap_stream(publish_collab_stream))
+      .send()
+      .await?;
+    AppResponse::<PublishOutcome>::from_response(resp)
+      .await?
+      .into_data()
+  }
 }
 
 #[async_trait]
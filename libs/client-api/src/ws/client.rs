@@ -18,7 +18,10 @@ use client_websocket::{CloseCode, CloseFrame, Message, WebSocketStream};
 use collab_rt_entity::user::UserMessage;
 use collab_rt_entity::ClientCollabMessage;
 use collab_rt_entity::ServerCollabMessage;
-use collab_rt_entity::{RealtimeMessage, SystemMessage};
+use collab_rt_entity::{
+  RealtimeCompression, RealtimeMessage, RealtimeProtocolVersion, SystemMessage, COMPRESSION_HEADER,
+  PROTOCOL_VERSION_HEADER, UPGRADE_REQUIRED_CLOSE_CODE,
+};
 
 use crate::ws::msg_queue::{AggregateMessageQueue, AggregateMessagesReceiver};
 use crate::ws::{ConnectState, ConnectStateNotify, WSError, WebSocketChannel};
@@ -79,6 +82,12 @@ pub struct WSClient {
   ping: Arc<Mutex<Option<ServerFixIntervalPing>>>,
   stop_ws_msg_loop_tx: Mutex<Option<oneshot::Sender<()>>>,
   aggregate_queue: Arc<AggregateMessageQueue>,
+  /// The compression of the messages sent to the server. It stays [RealtimeCompression::None]
+  /// until the server confirms the compression it accepts after connecting.
+  compression: Arc<RwLock<RealtimeCompression>>,
+  /// The version of the protocol confirmed by the server. The servers that don't confirm it speak
+  /// the first version.
+  protocol_version: Arc<RwLock<RealtimeProtocolVersion>>,
 
   #[cfg(debug_assertions)]
   skip_realtime_message: Arc<std::sync::atomic::AtomicBool>,
@@ -98,7 +107,11 @@ impl WSClient {
     let (user_channel, _) = channel(1);
     let (rt_msg_sender, _) = channel(config.buffer_capacity);
     let connect_provider = Arc::new(connect_provider);
-    let aggregate_queue = Arc::new(AggregateMessageQueue::new(MAXIMUM_BATCH_MESSAGE_SIZE));
+    let compression = Arc::new(RwLock::new(RealtimeCompression::None));
+    let aggregate_queue = Arc::new(AggregateMessageQueue::new(
+      MAXIMUM_BATCH_MESSAGE_SIZE,
+      compression.clone(),
+    ));
     WSClient {
       config,
       state_notify,
@@ -110,6 +123,8 @@ impl WSClient {
       ping,
       stop_ws_msg_loop_tx: Mutex::from(None),
       aggregate_queue,
+      compression,
+      protocol_version: Arc::new(RwLock::new(RealtimeProtocolVersion::V1)),
 
       #[cfg(debug_assertions)]
       skip_realtime_message: Default::default(),
@@ -153,6 +168,8 @@ impl WSClient {
     // 4. after the connection is established, the client will start sending ping messages to the server
     // at regular intervals to detect the connection status.
     let (sink, stream) = conn_result?.split();
+    *self.compression.write() = RealtimeCompression::None;
+    *self.protocol_version.write() = RealtimeProtocolVersion::V1;
     self.set_state(ConnectState::Connected).await;
 
     // 5. start pinging
@@ -265,6 +282,10 @@ impl WSClient {
     #[cfg(debug_assertions)]
     let cloned_skip_realtime_message = self.skip_realtime_message.clone();
     let user_message_tx = self.user_channel.as_ref().clone();
+    let compression = self.compression.clone();
+    let protocol_version = self.protocol_version.clone();
+    let weak_state_notify = Arc::downgrade(&self.state_notify);
+    let ping = self.ping.clone();
     af_spawn(async move {
       while let Some(Ok(ws_msg)) = stream.next().await {
         match ws_msg {
@@ -299,6 +320,14 @@ impl WSClient {
                     trace!("detect same ws connect from this device, closing the connection");
                     break;
                   },
+                  SystemMessage::Compression(server_compression) => {
+                    trace!("server accepts {:?} compression", server_compression);
+                    *compression.write() = server_compression;
+                  },
+                  SystemMessage::ProtocolVersion(server_version) => {
+                    trace!("server speaks protocol {:?}", server_version);
+                    *protocol_version.write() = server_version;
+                  },
                 },
                 RealtimeMessage::ServerCollabV1(collab_messages) => {
                   handle_collab_message(&weak_collab_channels, collab_messages);
@@ -326,6 +355,18 @@ impl WSClient {
           },
           Message::Close(close) => {
             info!("websocket close: {:?}", close);
+            let upgrade_required = close.as_ref().map_or(false, |frame| {
+              u16::from(frame.code) == UPGRADE_REQUIRED_CLOSE_CODE
+            });
+            if upgrade_required {
+              // Stop pinging, reconnecting won't help until the app is upgraded
+              if let Some(ping) = ping.lock().await.as_ref() {
+                ping.stop().await;
+              }
+              if let Some(state_notify) = weak_state_notify.upgrade() {
+                state_notify.lock().set_state(ConnectState::UpgradeRequired);
+              }
+            }
             break;
           },
           Message::Pong(_) => {
@@ -405,6 +446,10 @@ impl WSClient {
     Ok(())
   }
 
+  pub fn protocol_version(&self) -> RealtimeProtocolVersion {
+    *self.protocol_version.read()
+  }
+
   pub fn get_state(&self) -> ConnectState {
     self.state_notify.lock().state.clone()
   }
@@ -482,6 +527,10 @@ pub struct ConnectInfo {
   pub access_token: String,
   pub client_version: Version,
   pub device_id: String,
+  /// The compression the client accepts for the realtime messages.
+  pub compression: RealtimeCompression,
+  /// The latest version of the realtime protocol the client supports.
+  pub protocol_version: RealtimeProtocolVersion,
 }
 
 impl Display for ConnectInfo {
@@ -513,6 +562,16 @@ impl From<ConnectInfo> for HeaderMap {
       "connect-at",
       HeaderValue::from(chrono::Utc::now().timestamp()),
     );
+    headers.insert(
+      PROTOCOL_VERSION_HEADER,
+      HeaderValue::from(info.protocol_version as u32),
+    );
+    if info.compression != RealtimeCompression::None {
+      headers.insert(
+        COMPRESSION_HEADER,
+        HeaderValue::from_static(info.compression.as_str()),
+      );
+    }
     headers
   }
 }
@@ -2,14 +2,15 @@ use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::sync::{Arc, Weak};
 use std::time::Duration;
 
+use parking_lot::RwLock;
 use tokio::sync::mpsc;
 use tokio::sync::Mutex;
 use tokio::time::{sleep_until, Instant};
 use tracing::{error, trace};
 
 use client_websocket::Message;
-use collab_rt_entity::RealtimeMessage;
 use collab_rt_entity::{ClientCollabMessage, MsgId};
+use collab_rt_entity::{RealtimeCompression, RealtimeMessage};
 
 pub type AggregateMessagesSender = mpsc::Sender<Message>;
 pub type AggregateMessagesReceiver = mpsc::Receiver<Message>;
@@ -19,15 +20,17 @@ pub struct AggregateMessageQueue {
   queue: Arc<Mutex<BinaryHeap<ClientCollabMessage>>>,
   stop_tx: Mutex<Option<mpsc::Sender<()>>>,
   seen_ids: Arc<Mutex<HashSet<SeenId>>>,
+  compression: Arc<RwLock<RealtimeCompression>>,
 }
 
 impl AggregateMessageQueue {
-  pub fn new(maximum_payload_size: usize) -> Self {
+  pub fn new(maximum_payload_size: usize, compression: Arc<RwLock<RealtimeCompression>>) -> Self {
     Self {
       maximum_payload_size,
       queue: Default::default(),
       stop_tx: Default::default(),
       seen_ids: Arc::new(Default::default()),
+      compression,
     }
   }
 
@@ -56,6 +59,7 @@ impl AggregateMessageQueue {
     let maximum_payload_size = self.maximum_payload_size;
     let weak_queue = Arc::downgrade(&self.queue);
     let weak_seen_ids = Arc::downgrade(&self.seen_ids);
+    let compression = self.compression.clone();
     let interval_duration = Duration::from_millis(1000);
     let mut next_tick = Instant::now() + interval_duration;
     tokio::spawn(async move {
@@ -64,7 +68,14 @@ impl AggregateMessageQueue {
           _ = rx.recv() => break,
           _ = sleep_until(next_tick) => {
             if let Some(queue) = weak_queue.upgrade() {
-              let (num_init_sync, num_messages) = handle_tick(&sender, &queue, maximum_payload_size, weak_seen_ids.clone()).await;
+              let compression = *compression.read();
+              let (num_init_sync, num_messages) = handle_tick(
+                &sender,
+                &queue,
+                maximum_payload_size,
+                weak_seen_ids.clone(),
+                compression,
+              ).await;
               // To determine the next interval dynamically, consider factors such as the number of messages sent,
               // their total size, and the current network type. This approach allows for more nuanced interval
               // adjustments, optimizing for efficiency and responsiveness under varying conditions.
@@ -85,6 +96,7 @@ async fn handle_tick(
   queue: &Arc<Mutex<BinaryHeap<ClientCollabMessage>>>,
   maximum_payload_size: usize,
   weak_seen_ids: Weak<Mutex<HashSet<SeenId>>>,
+  compression: RealtimeCompression,
 ) -> (usize, usize) {
   let (did_sent_seen_ids, messages_map) = next_batch_message(20, maximum_payload_size, queue).await;
   if messages_map.is_empty() {
@@ -94,7 +106,7 @@ async fn handle_tick(
   log_message_map(&messages_map);
 
   // Send messages to server
-  send_batch_message(sender, messages_map).await;
+  send_batch_message(sender, messages_map, compression).await;
 
   // after sending messages, remove seen_ids
   let num_init_sync = did_sent_seen_ids
@@ -114,8 +126,9 @@ async fn handle_tick(
 async fn send_batch_message(
   sender: &AggregateMessagesSender,
   messages_map: HashMap<String, Vec<ClientCollabMessage>>,
+  compression: RealtimeCompression,
 ) {
-  match RealtimeMessage::ClientCollabV2(messages_map).encode() {
+  match RealtimeMessage::ClientCollabV2(messages_map).encode_with(compression) {
     Ok(data) => {
       if let Err(e) = sender.send(Message::Binary(data)).await {
         trace!("websocket channel close:{}, stop sending messages", e);
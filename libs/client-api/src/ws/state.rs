@@ -35,6 +35,9 @@ pub enum ConnectState {
   Connected,
   Unauthorized,
   Lost,
+  /// The server no longer supports the realtime protocol of this client, the app must be upgraded
+  /// before connecting again.
+  UpgradeRequired,
 }
 
 impl ConnectState {
@@ -56,4 +59,8 @@ impl ConnectState {
   pub fn is_lost(&self) -> bool {
     matches!(self, ConnectState::Lost)
   }
+
+  pub fn is_upgrade_required(&self) -> bool {
+    matches!(self, ConnectState::UpgradeRequired)
+  }
 }
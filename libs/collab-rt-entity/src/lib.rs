@@ -8,10 +8,12 @@ mod client_message;
 //  cargo clean
 //  cargo build
 // ```
+mod protocol_version;
 pub mod realtime_proto;
 mod server_message;
 
 pub use client_message::*;
 pub use message::*;
+pub use protocol_version::*;
 pub use realtime_proto::*;
 pub use server_message::*;
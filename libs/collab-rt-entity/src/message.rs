@@ -1,18 +1,18 @@
 use anyhow::{anyhow, Error};
 use bincode::{DefaultOptions, Options};
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 use crate::client_message::ClientCollabMessage;
 use crate::server_message::ServerCollabMessage;
 use crate::user::UserMessage;
+use crate::RealtimeProtocolVersion;
 use crate::{AwarenessSync, BroadcastSync, CollabAck, InitSync, ServerInit, UpdateSync};
-#[cfg(feature = "rt_compress")]
 use brotli::{CompressorReader, Decompressor};
 use bytes::Bytes;
 use collab::core::origin::CollabOrigin;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
-#[cfg(feature = "rt_compress")]
 use std::io::Read;
 
 /// Maximum allowable size for a realtime message.
@@ -24,9 +24,15 @@ use std::io::Read;
 pub const MAXIMUM_REALTIME_MESSAGE_SIZE: u64 = 10 * 1024 * 1024; // 10 MB
 
 /// 1 for using brotli compression
-#[cfg(feature = "rt_compress")]
 const COMPRESSED_PREFIX: &[u8] = b"COMPRESSED:1";
 
+/// Messages smaller than this are sent uncompressed, compressing them saves next to nothing.
+pub const MIN_COMPRESSED_MESSAGE_SIZE: usize = 1024;
+
+/// The header, or query parameter for the clients that can't set headers, listing the
+/// compressions the client accepts, separated by commas.
+pub const COMPRESSION_HEADER: &str = "compression";
+
 pub type MessageByObjectId = HashMap<String, Vec<ClientCollabMessage>>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -85,55 +91,88 @@ impl RealtimeMessage {
     }
   }
 
-  #[cfg(feature = "rt_compress")]
+  /// Encode the message without compression, unless the crate is built with `rt_compress`.
   pub fn encode(&self) -> Result<Vec<u8>, Error> {
+    if cfg!(feature = "rt_compress") {
+      self.encode_with(RealtimeCompression::Brotli)
+    } else {
+      self.encode_with(RealtimeCompression::None)
+    }
+  }
+
+  /// Encode the message, compressing it when it is at least [MIN_COMPRESSED_MESSAGE_SIZE] bytes.
+  pub fn encode_with(&self, compression: RealtimeCompression) -> Result<Vec<u8>, Error> {
     let data = DefaultOptions::new()
       .with_fixint_encoding()
       .allow_trailing_bytes()
       .with_limit(MAXIMUM_REALTIME_MESSAGE_SIZE)
       .serialize(self)?;
 
-    let mut compressor = CompressorReader::new(&*data, 4096, 4, 22);
-    let mut compressed_data = Vec::new();
-    compressor.read_to_end(&mut compressed_data)?;
-    let mut data = Vec::new();
-    data.extend_from_slice(COMPRESSED_PREFIX);
-    data.extend(compressed_data);
-    Ok(data)
+    match compression {
+      RealtimeCompression::Brotli if data.len() >= MIN_COMPRESSED_MESSAGE_SIZE => {
+        let mut compressor = CompressorReader::new(&*data, 4096, 4, 22);
+        let mut compressed_data = COMPRESSED_PREFIX.to_vec();
+        compressor.read_to_end(&mut compressed_data)?;
+        Ok(compressed_data)
+      },
+      _ => Ok(data),
+    }
   }
 
-  #[cfg(not(feature = "rt_compress"))]
-  pub fn encode(&self) -> Result<Vec<u8>, Error> {
-    let data = DefaultOptions::new()
-      .with_fixint_encoding()
-      .allow_trailing_bytes()
-      .with_limit(MAXIMUM_REALTIME_MESSAGE_SIZE)
-      .serialize(self)?;
-    Ok(data)
+  /// Encode the message for a client speaking the given version of the protocol. Returns `None`
+  /// when the message doesn't exist in that version.
+  pub fn encode_for(
+    &self,
+    version: RealtimeProtocolVersion,
+    compression: RealtimeCompression,
+  ) -> Result<Option<Vec<u8>>, Error> {
+    if self.since_version() > version {
+      return Ok(None);
+    }
+    let data = match version {
+      RealtimeProtocolVersion::V1 => self.encode_with(RealtimeCompression::None)?,
+      RealtimeProtocolVersion::V2 => self.encode_with(compression)?,
+    };
+    Ok(Some(data))
   }
 
-  #[cfg(feature = "rt_compress")]
-  pub fn decode(data: &[u8]) -> Result<Self, Error> {
-    if data.starts_with(COMPRESSED_PREFIX) {
-      let data_without_prefix = &data[COMPRESSED_PREFIX.len()..];
-      let mut decompressor = Decompressor::new(data_without_prefix, 4096);
-      let mut decompressed_data = Vec::new();
-      decompressor.read_to_end(&mut decompressed_data)?;
-      decompressed_data
-    } else {
-      data.to_vec()
+  /// Decode a message sent by a client speaking the given version of the protocol.
+  pub fn decode_for(data: &[u8], version: RealtimeProtocolVersion) -> Result<Self, Error> {
+    match version {
+      RealtimeProtocolVersion::V1 => Self::decode_uncompressed(data),
+      RealtimeProtocolVersion::V2 => Self::decode(data),
     }
+  }
 
-    let message = DefaultOptions::new()
-      .with_fixint_encoding()
-      .allow_trailing_bytes()
-      .with_limit(MAXIMUM_REALTIME_MESSAGE_SIZE)
-      .deserialize(&data)?;
-    Ok(message)
+  /// The first version of the protocol that has the message.
+  fn since_version(&self) -> RealtimeProtocolVersion {
+    match self {
+      RealtimeMessage::System(SystemMessage::Compression(_))
+      | RealtimeMessage::System(SystemMessage::ProtocolVersion(_)) => RealtimeProtocolVersion::V2,
+      _ => RealtimeProtocolVersion::V1,
+    }
   }
 
-  #[cfg(not(feature = "rt_compress"))]
+  /// Decode a message, whether it was compressed or not.
   pub fn decode(data: &[u8]) -> Result<Self, Error> {
+    let data = match data.strip_prefix(COMPRESSED_PREFIX) {
+      Some(data_without_prefix) => {
+        // Stop reading past the maximum size, so that a small message can't expand without limit
+        let mut decompressor =
+          Decompressor::new(data_without_prefix, 4096).take(MAXIMUM_REALTIME_MESSAGE_SIZE + 1);
+        let mut decompressed_data = Vec::new();
+        decompressor.read_to_end(&mut decompressed_data)?;
+        if decompressed_data.len() as u64 > MAXIMUM_REALTIME_MESSAGE_SIZE {
+          return Err(anyhow!("Decompressed realtime message is too large"));
+        }
+        Cow::Owned(decompressed_data)
+      },
+      None => Cow::Borrowed(data),
+    };
+    Self::decode_uncompressed(&data)
+  }
+
+  fn decode_uncompressed(data: &[u8]) -> Result<Self, Error> {
     let message = DefaultOptions::new()
       .with_fixint_encoding()
       .allow_trailing_bytes()
@@ -161,6 +200,42 @@ pub enum SystemMessage {
   RateLimit(u32),
   KickOff,
   DuplicateConnection,
+  /// Sent by the server right after the connection is opened, to confirm the compression of the
+  /// messages it sends. Clients that didn't ask for compression don't receive it.
+  Compression(RealtimeCompression),
+  /// Sent by the server right after the connection is opened, to confirm the version of the
+  /// protocol used on the connection. Only the clients that sent their version receive it.
+  ProtocolVersion(RealtimeProtocolVersion),
+}
+
+/// The compression of the realtime messages. Whatever the compression agreed on, a message below
+/// [MIN_COMPRESSED_MESSAGE_SIZE] bytes is sent as is, and both sides can decode either kind.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Hash, Eq, PartialEq)]
+pub enum RealtimeCompression {
+  #[default]
+  None,
+  Brotli,
+}
+
+impl RealtimeCompression {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      RealtimeCompression::None => "none",
+      RealtimeCompression::Brotli => "brotli",
+    }
+  }
+
+  /// Returns the first compression of the comma separated list that is supported, or
+  /// [RealtimeCompression::None] if there is none.
+  pub fn negotiate(accepted: &str) -> Self {
+    accepted
+      .split(',')
+      .find_map(|name| match name.trim() {
+        "brotli" => Some(RealtimeCompression::Brotli),
+        _ => None,
+      })
+      .unwrap_or_default()
+  }
 }
 
 pub type MsgId = u64;
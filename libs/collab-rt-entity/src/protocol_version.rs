@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+/// The header, or query parameter for the clients that can't set headers, holding the latest
+/// version of the realtime protocol the client supports.
+pub const PROTOCOL_VERSION_HEADER: &str = "protocol-version";
+
+/// The close code of the connections whose client only supports versions of the realtime protocol
+/// the server no longer supports. The close reason is an [UpgradeRequired] as JSON.
+///
+/// The codes from 4000 are left to applications, 426 being the HTTP status for the same purpose.
+pub const UPGRADE_REQUIRED_CLOSE_CODE: u16 = 4426;
+
+/// The version of the format of the realtime messages. The client sends the latest version it
+/// supports when connecting, and the server uses the latest version both sides support.
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize_repr, Deserialize_repr,
+)]
+#[repr(u32)]
+pub enum RealtimeProtocolVersion {
+  /// The version of the clients that don't send their version. Their messages are never
+  /// compressed, and they can't decode the system messages added since.
+  V1 = 1,
+  /// The messages can be compressed, and the server confirms the version and the compression of
+  /// the connection with system messages.
+  V2 = 2,
+}
+
+impl RealtimeProtocolVersion {
+  /// The latest version, the one this build speaks.
+  pub const CURRENT: Self = RealtimeProtocolVersion::V2;
+
+  /// Returns the version of a connection given the latest version the client supports, or `None`
+  /// when it is older than `min_version`, the oldest version the server accepts.
+  pub fn negotiate(client_version: u32, min_version: u32) -> Option<Self> {
+    let version = match client_version.min(Self::CURRENT as u32) {
+      1 => RealtimeProtocolVersion::V1,
+      2 => RealtimeProtocolVersion::V2,
+      _ => return None,
+    };
+    (version as u32 >= min_version).then_some(version)
+  }
+}
+
+/// The reason of a connection closed with [UPGRADE_REQUIRED_CLOSE_CODE].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UpgradeRequired {
+  pub client_version: u32,
+  pub min_version: u32,
+  pub current_version: u32,
+}
@@ -7,6 +7,7 @@ use std::hash::Hash;
 pub enum UserMessage {
   ProfileChange(AFUserChange),
   WorkspaceMemberChange(AFWorkspaceMemberChange),
+  CollabPresenceChange(AFCollabPresenceChange),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, Eq, PartialEq)]
@@ -24,6 +25,18 @@ pub struct AFWorkspaceMemberChange {
   removed: Vec<AFWorkspaceMember>,
 }
 
+/// Sent to the users of a collab when the device of another user joins or leaves it.
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, Eq, PartialEq)]
+pub struct AFCollabPresenceChange {
+  pub object_id: String,
+  pub uid: i64,
+  pub device_id: String,
+  /// False when the device left the collab.
+  pub is_present: bool,
+  /// The time of the change, in milliseconds since the Unix epoch.
+  pub timestamp: i64,
+}
+
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
 pub struct UserDevice {
   device_id: String,
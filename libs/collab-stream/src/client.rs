@@ -1,24 +1,24 @@
+use crate::connection::RedisConnectionManager;
 use crate::error::StreamError;
 use crate::pubsub::{CollabStreamPub, CollabStreamSub};
-use crate::stream::CollabStream;
+use crate::stream::{CollabStream, CollabUpdateReader};
 use crate::stream_group::{StreamConfig, StreamGroup};
-use redis::aio::ConnectionManager;
 use tracing::error;
 
 pub const CONTROL_STREAM_KEY: &str = "af_collab_control";
 
 #[derive(Clone)]
 pub struct CollabRedisStream {
-  connection_manager: ConnectionManager,
+  connection_manager: RedisConnectionManager,
 }
 
 impl CollabRedisStream {
   pub async fn new(redis_client: redis::Client) -> Result<Self, redis::RedisError> {
-    let connection_manager = redis_client.get_connection_manager().await?;
+    let connection_manager = RedisConnectionManager::new_standalone(redis_client).await?;
     Ok(Self::new_with_connection_manager(connection_manager))
   }
 
-  pub fn new_with_connection_manager(connection_manager: ConnectionManager) -> Self {
+  pub fn new_with_connection_manager(connection_manager: RedisConnectionManager) -> Self {
     Self { connection_manager }
   }
 
@@ -52,7 +52,7 @@ impl CollabRedisStream {
     oid: &str,
     group_name: &str,
   ) -> Result<StreamGroup, StreamError> {
-    let stream_key = format!("af_collab_update-{}-{}", workspace_id, oid);
+    let stream_key = collab_update_stream_key(workspace_id, oid);
     let mut group = StreamGroup::new_with_config(
       stream_key,
       group_name,
@@ -66,29 +66,39 @@ impl CollabRedisStream {
     group.ensure_consumer_group().await?;
     Ok(group)
   }
+
+  pub fn collab_update_reader(&self, workspace_id: &str, oid: &str) -> CollabUpdateReader {
+    CollabUpdateReader::new(
+      collab_update_stream_key(workspace_id, oid),
+      self.connection_manager.clone(),
+    )
+  }
+}
+
+fn collab_update_stream_key(workspace_id: &str, oid: &str) -> String {
+  format!("af_collab_update-{}-{}", workspace_id, oid)
 }
 
 pub struct PubSubClient {
-  redis_client: redis::Client,
-  connection_manager: ConnectionManager,
+  connection_manager: RedisConnectionManager,
 }
 
 impl PubSubClient {
   pub async fn new(redis_client: redis::Client) -> Result<Self, redis::RedisError> {
-    let connection_manager = redis_client.get_connection_manager().await?;
-    Ok(Self {
-      redis_client,
-      connection_manager,
-    })
+    let connection_manager = RedisConnectionManager::new_standalone(redis_client).await?;
+    Ok(Self::new_with_connection_manager(connection_manager))
+  }
+
+  pub fn new_with_connection_manager(connection_manager: RedisConnectionManager) -> Self {
+    Self { connection_manager }
   }
 
   pub async fn collab_pub(&self) -> CollabStreamPub {
     CollabStreamPub::new(self.connection_manager.clone())
   }
 
-  #[allow(deprecated)]
   pub async fn collab_sub(&self) -> Result<CollabStreamSub, StreamError> {
-    let conn = self.redis_client.get_async_connection().await?;
+    let conn = self.connection_manager.pubsub_connection().await?;
     Ok(CollabStreamSub::new(conn))
   }
 }
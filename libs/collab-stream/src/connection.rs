@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+#[allow(deprecated)]
+use redis::aio::{Connection, ConnectionLike, ConnectionManager};
+use redis::cluster::ClusterClient;
+use redis::cluster_async::ClusterConnection;
+use redis::sentinel::{Sentinel, SentinelNodeConnectionInfo};
+use redis::{Client, Cmd, ErrorKind, Pipeline, RedisError, RedisFuture, RedisResult, Value};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// How long a node may take to answer a PING before it is considered unhealthy.
+const NODE_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedisMode {
+  /// A single Redis server.
+  Standalone,
+  /// A master and its replicas, monitored by Redis Sentinel. The nodes are the sentinels.
+  Sentinel,
+  /// A Redis Cluster. The nodes are used to discover the rest of the cluster.
+  Cluster,
+}
+
+impl FromStr for RedisMode {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "standalone" => Ok(Self::Standalone),
+      "sentinel" => Ok(Self::Sentinel),
+      "cluster" => Ok(Self::Cluster),
+      other => Err(format!(
+        "{} is not a supported redis mode, use either standalone, sentinel or cluster",
+        other
+      )),
+    }
+  }
+}
+
+#[derive(Clone)]
+pub struct RedisSetting {
+  pub mode: RedisMode,
+  /// The urls of the nodes to connect to. See [RedisMode] for what the nodes are in each mode.
+  pub nodes: Vec<String>,
+  /// The name of the master monitored by the sentinels. Only used in [RedisMode::Sentinel].
+  pub sentinel_master: String,
+  pub health_check_interval_secs: u64,
+}
+
+impl Debug for RedisSetting {
+  // The urls of the nodes may contain passwords, so only their number is printed.
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("RedisSetting")
+      .field("mode", &self.mode)
+      .field("nodes", &self.nodes.len())
+      .field("sentinel_master", &self.sentinel_master)
+      .field(
+        "health_check_interval_secs",
+        &self.health_check_interval_secs,
+      )
+      .finish()
+  }
+}
+
+/// A connection to Redis, whatever the deployment of Redis is. It is cheap to clone, and all the
+/// clones share the same underlying connections.
+#[derive(Clone)]
+pub enum RedisConnectionManager {
+  Standalone {
+    client: Client,
+    conn: ConnectionManager,
+  },
+  Sentinel(SentinelConnection),
+  Cluster {
+    nodes: Vec<Client>,
+    conn: ClusterConnection,
+  },
+}
+
+impl RedisConnectionManager {
+  pub async fn connect(setting: &RedisSetting) -> RedisResult<Self> {
+    if setting.nodes.is_empty() {
+      return Err(RedisError::from((
+        ErrorKind::InvalidClientConfig,
+        "No redis node is configured",
+      )));
+    }
+    match setting.mode {
+      RedisMode::Standalone => Self::new_standalone(Client::open(setting.nodes[0].as_str())?).await,
+      RedisMode::Sentinel => {
+        let conn = SentinelConnection::connect(&setting.nodes, &setting.sentinel_master).await?;
+        Ok(Self::Sentinel(conn))
+      },
+      RedisMode::Cluster => {
+        let nodes = setting
+          .nodes
+          .iter()
+          .map(|node| Client::open(node.as_str()))
+          .collect::<RedisResult<Vec<_>>>()?;
+        let conn = ClusterClient::new(setting.nodes.clone())?
+          .get_async_connection()
+          .await?;
+        Ok(Self::Cluster { nodes, conn })
+      },
+    }
+  }
+
+  pub async fn new_standalone(client: Client) -> RedisResult<Self> {
+    let conn = client.get_connection_manager().await?;
+    Ok(Self::Standalone { client, conn })
+  }
+
+  /// Opens a dedicated connection for subscribing to channels. In cluster mode a message
+  /// published on any node is propagated to every node, so subscribing to a single node is enough.
+  #[allow(deprecated)]
+  pub async fn pubsub_connection(&self) -> RedisResult<Connection> {
+    match self {
+      Self::Standalone { client, .. } => client.get_async_connection().await,
+      Self::Sentinel(conn) => conn.master_client().get_async_connection().await,
+      Self::Cluster { nodes, .. } => {
+        let mut last_err = None;
+        for node in nodes {
+          match node.get_async_connection().await {
+            Ok(conn) => return Ok(conn),
+            Err(err) => last_err = Some(err),
+          }
+        }
+        Err(last_err.unwrap_or_else(|| {
+          RedisError::from((
+            ErrorKind::InvalidClientConfig,
+            "No redis node is configured",
+          ))
+        }))
+      },
+    }
+  }
+
+  /// Sends a PING to every node of the deployment. In sentinel mode, the master is looked up
+  /// again when it does not answer, so that the connection follows a failover.
+  pub async fn check_nodes(&self) -> Vec<RedisNodeStatus> {
+    match self {
+      Self::Standalone { client, .. } => vec![check_node(client).await],
+      Self::Sentinel(conn) => {
+        let master = check_node(&conn.master_client()).await;
+        if !master.is_healthy {
+          if let Err(err) = conn.refresh_master().await {
+            warn!(
+              "Failed to look up the redis master from the sentinels: {}",
+              err
+            );
+          }
+        }
+        let mut statuses = vec![master];
+        for sentinel in &conn.sentinels {
+          statuses.push(check_node(sentinel).await);
+        }
+        statuses
+      },
+      Self::Cluster { nodes, .. } => {
+        let mut statuses = Vec::with_capacity(nodes.len());
+        for node in nodes {
+          statuses.push(check_node(node).await);
+        }
+        statuses
+      },
+    }
+  }
+}
+
+impl ConnectionLike for RedisConnectionManager {
+  fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+    match self {
+      Self::Standalone { conn, .. } => conn.req_packed_command(cmd),
+      Self::Sentinel(conn) => {
+        let mut master = conn.master();
+        Box::pin(async move { master.req_packed_command(cmd).await })
+      },
+      Self::Cluster { conn, .. } => conn.req_packed_command(cmd),
+    }
+  }
+
+  fn req_packed_commands<'a>(
+    &'a mut self,
+    cmd: &'a Pipeline,
+    offset: usize,
+    count: usize,
+  ) -> RedisFuture<'a, Vec<Value>> {
+    match self {
+      Self::Standalone { conn, .. } => conn.req_packed_commands(cmd, offset, count),
+      Self::Sentinel(conn) => {
+        let mut master = conn.master();
+        Box::pin(async move { master.req_packed_commands(cmd, offset, count).await })
+      },
+      Self::Cluster { conn, .. } => conn.req_packed_commands(cmd, offset, count),
+    }
+  }
+
+  fn get_db(&self) -> i64 {
+    match self {
+      Self::Standalone { conn, .. } => conn.get_db(),
+      Self::Sentinel(conn) => conn.master().get_db(),
+      Self::Cluster { conn, .. } => conn.get_db(),
+    }
+  }
+}
+
+/// A connection to the master monitored by the sentinels.
+#[derive(Clone)]
+pub struct SentinelConnection {
+  sentinel: Arc<Mutex<Sentinel>>,
+  sentinels: Vec<Client>,
+  master_name: String,
+  master: Arc<RwLock<(Client, ConnectionManager)>>,
+}
+
+impl SentinelConnection {
+  async fn connect(nodes: &[String], master_name: &str) -> RedisResult<Self> {
+    let sentinels = nodes
+      .iter()
+      .map(|node| Client::open(node.as_str()))
+      .collect::<RedisResult<Vec<_>>>()?;
+    let mut sentinel = Sentinel::build(nodes.to_vec())?;
+    let master = Self::lookup_master(&mut sentinel, master_name).await?;
+    Ok(Self {
+      sentinel: Arc::new(Mutex::new(sentinel)),
+      sentinels,
+      master_name: master_name.to_string(),
+      master: Arc::new(RwLock::new(master)),
+    })
+  }
+
+  async fn lookup_master(
+    sentinel: &mut Sentinel,
+    master_name: &str,
+  ) -> RedisResult<(Client, ConnectionManager)> {
+    let client = sentinel
+      .async_master_for(master_name, None::<&SentinelNodeConnectionInfo>)
+      .await?;
+    let conn = client.get_connection_manager().await?;
+    Ok((client, conn))
+  }
+
+  async fn refresh_master(&self) -> RedisResult<()> {
+    let mut sentinel = self.sentinel.lock().await;
+    let master = Self::lookup_master(&mut sentinel, &self.master_name).await?;
+    info!(
+      "Redis master {} is now {}",
+      self.master_name,
+      master.0.get_connection_info().addr
+    );
+    *self.master.write().unwrap() = master;
+    Ok(())
+  }
+
+  fn master(&self) -> ConnectionManager {
+    self.master.read().unwrap().1.clone()
+  }
+
+  fn master_client(&self) -> Client {
+    self.master.read().unwrap().0.clone()
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct RedisNodeStatus {
+  pub address: String,
+  pub is_healthy: bool,
+  pub latency: Duration,
+}
+
+async fn check_node(client: &Client) -> RedisNodeStatus {
+  let start = Instant::now();
+  let result = tokio::time::timeout(NODE_CHECK_TIMEOUT, async {
+    let mut conn = client.get_multiplexed_async_connection().await?;
+    redis::cmd("PING").query_async::<_, String>(&mut conn).await
+  })
+  .await;
+  RedisNodeStatus {
+    address: client.get_connection_info().addr.to_string(),
+    is_healthy: matches!(result, Ok(Ok(_))),
+    latency: start.elapsed(),
+  }
+}
+
+/// Periodically checks every node of the deployment, see [RedisConnectionManager::check_nodes],
+/// and logs the nodes whose health changed.
+pub fn spawn_check_redis_nodes(conn: RedisConnectionManager, period: Duration) {
+  if period.is_zero() {
+    return;
+  }
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(period);
+    let mut healthy_by_node: HashMap<String, bool> = HashMap::new();
+    loop {
+      interval.tick().await;
+      for status in conn.check_nodes().await {
+        let was_healthy = healthy_by_node.insert(status.address.clone(), status.is_healthy);
+        if was_healthy == Some(status.is_healthy) {
+          continue;
+        }
+        if status.is_healthy {
+          info!(
+            "Redis node {} is healthy, latency: {:?}",
+            status.address, status.latency
+          );
+        } else {
+          warn!("Redis node {} is unhealthy", status.address);
+        }
+      }
+    }
+  });
+}
@@ -1,4 +1,5 @@
 pub mod client;
+pub mod connection;
 pub mod error;
 pub mod model;
 pub mod pubsub;
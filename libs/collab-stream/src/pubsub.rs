@@ -1,10 +1,11 @@
+use crate::connection::RedisConnectionManager;
 use crate::error::StreamError;
 use collab_entity::proto;
 use futures::stream::BoxStream;
 use futures::StreamExt;
 use prost::Message;
 #[allow(deprecated)]
-use redis::aio::{Connection, ConnectionManager};
+use redis::aio::Connection;
 use redis::{AsyncCommands, RedisWrite, ToRedisArgs};
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
@@ -40,11 +41,11 @@ impl CollabStreamSub {
 }
 
 pub struct CollabStreamPub {
-  conn: ConnectionManager,
+  conn: RedisConnectionManager,
 }
 
 impl CollabStreamPub {
-  pub fn new(conn: ConnectionManager) -> Self {
+  pub fn new(conn: RedisConnectionManager) -> Self {
     Self { conn }
   }
 
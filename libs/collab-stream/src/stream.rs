@@ -1,16 +1,16 @@
+use crate::connection::RedisConnectionManager;
 use crate::error::StreamError;
 use crate::model::{MessageId, StreamBinary, StreamMessage, StreamMessageByStreamKey};
-use redis::aio::ConnectionManager;
 use redis::streams::{StreamMaxlen, StreamReadOptions};
 use redis::{pipe, AsyncCommands, RedisError};
 
 pub struct CollabStream {
-  connection_manager: ConnectionManager,
+  connection_manager: RedisConnectionManager,
   stream_key: String,
 }
 
 impl CollabStream {
-  pub fn new(workspace_id: &str, oid: &str, connection_manager: ConnectionManager) -> Self {
+  pub fn new(workspace_id: &str, oid: &str, connection_manager: RedisConnectionManager) -> Self {
     let stream_key = format!("af_collab-{}-{}", workspace_id, oid);
     Self {
       connection_manager,
@@ -97,3 +97,45 @@ impl CollabStream {
     Ok(())
   }
 }
+
+/// Reads the updates appended to the update stream of a collab, see
+/// [crate::client::CollabRedisStream::collab_update_stream], without joining its consumer group.
+pub struct CollabUpdateReader {
+  connection_manager: RedisConnectionManager,
+  stream_key: String,
+  last_message_id: MessageId,
+}
+
+impl CollabUpdateReader {
+  /// Only the updates appended after the reader is created are read.
+  pub fn new(stream_key: String, connection_manager: RedisConnectionManager) -> Self {
+    let last_message_id = MessageId {
+      timestamp_ms: chrono::Utc::now().timestamp_millis() as u64,
+      sequence_number: 0,
+    };
+    Self {
+      connection_manager,
+      stream_key,
+      last_message_id,
+    }
+  }
+
+  /// Returns the updates appended since the previous read, if any. It does not block, as the
+  /// connection is shared with the other users of Redis.
+  pub async fn read_updates(&mut self) -> Result<Vec<StreamMessage>, StreamError> {
+    let map: StreamMessageByStreamKey = self
+      .connection_manager
+      .xread(&[&self.stream_key], &[self.last_message_id.to_string()])
+      .await?;
+    let messages = map
+      .0
+      .into_iter()
+      .next()
+      .map(|(_, messages)| messages)
+      .unwrap_or_default();
+    if let Some(message) = messages.last() {
+      self.last_message_id = message.id.clone();
+    }
+    Ok(messages)
+  }
+}
@@ -2,7 +2,6 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
-use redis::aio::ConnectionManager;
 use redis::streams::{
   StreamClaimOptions, StreamClaimReply, StreamMaxlen, StreamPendingData, StreamPendingReply,
   StreamReadOptions,
@@ -11,12 +10,13 @@ use redis::{pipe, AsyncCommands, ErrorKind, RedisResult};
 use tokio_util::sync::CancellationToken;
 use tracing::{error, info, trace, warn};
 
+use crate::connection::RedisConnectionManager;
 use crate::error::StreamError;
 use crate::model::{MessageId, StreamBinary, StreamMessage, StreamMessageByStreamKey};
 
 #[derive(Clone)]
 pub struct StreamGroup {
-  connection_manager: ConnectionManager,
+  connection_manager: RedisConnectionManager,
   stream_key: String,
   group_name: String,
   config: StreamConfig,
@@ -29,7 +29,11 @@ impl Drop for StreamGroup {
   }
 }
 impl StreamGroup {
-  pub fn new(stream_key: String, group_name: &str, connection_manager: ConnectionManager) -> Self {
+  pub fn new(
+    stream_key: String,
+    group_name: &str,
+    connection_manager: RedisConnectionManager,
+  ) -> Self {
     let config = StreamConfig {
       max_len: Some(1000),
       expire_time_in_secs: None,
@@ -40,7 +44,7 @@ impl StreamGroup {
   pub fn new_with_config(
     stream_key: String,
     group_name: &str,
-    connection_manager: ConnectionManager,
+    connection_manager: RedisConnectionManager,
     config: StreamConfig,
   ) -> Self {
     let cancel_token = Arc::new(CancellationToken::new());
@@ -435,7 +439,7 @@ impl StreamGroup {
 
 /// Checks if the stream length exceeds the maximum length.
 async fn get_stream_length(
-  connection_manager: &mut ConnectionManager,
+  connection_manager: &mut RedisConnectionManager,
   stream_key: &str,
 ) -> Result<usize, StreamError> {
   let current_len: usize = connection_manager.xlen(stream_key).await?;
@@ -253,6 +253,80 @@ pub struct AFSnapshotMeta {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AFSnapshotMetas(pub Vec<AFSnapshotMeta>);
 
+/// A version of a collab that can be browsed and restored. Every snapshot of the collab is a
+/// version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AFCollabVersion {
+  pub snapshot_id: i64,
+  pub object_id: String,
+  pub created_at: DateTime<Utc>,
+  /// The user who created the version. `None` for versions taken periodically by the server.
+  pub created_by: Option<i64>,
+}
+
+/// The two versions of a document to compare. Each side is either a version, or the latest
+/// version created at or before a time. Without a `to` side, the current state of the document
+/// is compared.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryCollabVersionDiff {
+  pub from_snapshot_id: Option<i64>,
+  pub from_time: Option<DateTime<Utc>>,
+  pub to_snapshot_id: Option<i64>,
+  pub to_time: Option<DateTime<Utc>>,
+}
+
+/// The changes made to a document between two versions, in the order of the blocks in the
+/// document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentVersionDiff {
+  pub object_id: String,
+  pub from_snapshot_id: i64,
+  /// `None` when compared with the current state of the document.
+  pub to_snapshot_id: Option<i64>,
+  pub inserted_blocks: Vec<DocumentBlockDiff>,
+  pub removed_blocks: Vec<DocumentBlockDiff>,
+  pub changed_blocks: Vec<DocumentBlockChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentBlockDiff {
+  pub block_id: String,
+  pub ty: String,
+  pub parent_id: String,
+  pub text: String,
+}
+
+/// A block that is in both versions, but changed between them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentBlockChange {
+  pub block_id: String,
+  pub ty: String,
+  /// The type of the block in the older version, when the block was turned into another type.
+  pub previous_ty: Option<String>,
+  /// Whether the block was moved under another parent.
+  pub moved: bool,
+  /// Whether the data of the block, other than its text, changed. Such as the level of a heading
+  /// or whether a todo is checked.
+  pub data_changed: bool,
+  /// The text of the block, split into the parts that were kept, inserted and deleted. Empty
+  /// when the text didn't change.
+  pub text_changes: Vec<TextChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextChange {
+  pub kind: TextChangeKind,
+  pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextChangeKind {
+  Equal,
+  Insert,
+  Delete,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct QueryObjectSnapshotParams {
   pub object_id: String,
@@ -278,23 +352,66 @@ pub enum QueryCollabResult {
 #[derive(Serialize, Deserialize)]
 pub struct BatchQueryCollabResult(pub HashMap<String, QueryCollabResult>);
 
-#[derive(Serialize, Deserialize)]
+/// The activity of a workspace, for its owner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct WorkspaceUsage {
+  /// The size of all the collabs of the workspace.
   pub total_document_size: i64,
+  #[serde(default)]
+  pub document_count: i64,
+  /// The members who edited a collab of the workspace in the last 30 days.
+  #[serde(default)]
+  pub active_editor_count: i64,
+  /// The collabs and the blobs together, as counted against the storage quota.
+  #[serde(default)]
+  pub storage_bytes: i64,
+  /// The views that are currently published.
+  #[serde(default)]
+  pub publish_count: i64,
 }
 
 #[derive(Debug, Clone, Validate, Serialize, Deserialize)]
 pub struct InsertCollabMemberParams {
+  /// Ignored when `group_id` is set.
+  #[serde(default)]
   pub uid: i64,
   #[validate(custom = "validate_not_empty_str")]
   pub workspace_id: String,
   #[validate(custom = "validate_not_empty_str")]
   pub object_id: String,
   pub access_level: AFAccessLevel,
+  /// The time after which the membership is revoked. `None` means the membership never expires.
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
+  /// Grant the access level to every member of this workspace group instead of a single user.
+  #[serde(default)]
+  pub group_id: Option<Uuid>,
 }
 
 pub type UpdateCollabMemberParams = InsertCollabMemberParams;
 
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub enum CreateCollabMemberResult {
+  Created,
+  Failed { error: String },
+}
+
+/// The result of making sure a user is a member of a collab with a given access level.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnsureCollabMemberOutcome {
+  /// The user was not a member and has been added.
+  Created,
+  /// The user was a member with another access level, which has been replaced.
+  Updated,
+  /// The user was already a member with the requested access level.
+  Unchanged,
+}
+
+/// The result of adding several members to a collab at once, keyed by uid.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchCreateCollabMemberResult(pub HashMap<i64, CreateCollabMemberResult>);
+
 #[derive(Debug, Clone, Validate, Serialize, Deserialize)]
 pub struct CollabMemberIdentify {
   pub uid: i64,
@@ -304,17 +421,78 @@ pub struct CollabMemberIdentify {
   pub object_id: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishNamespaceInfo {
+  pub namespace: String,
+  pub workspace_id: Uuid,
+  /// The creation time of the workspace that owns the namespace.
+  pub created_at: Option<DateTime<Utc>>,
+}
+
+/// The result of moving a publish namespace to another workspace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReassignedPublishNamespace {
+  pub namespace: String,
+  pub previous_workspace_id: Uuid,
+  pub workspace_id: Uuid,
+  /// The views published under the namespace before the move whose publish name is not published
+  /// in the new workspace, so their public URL no longer resolves.
+  pub unresolved_views: Vec<PublishInfo>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct UpdatePublishNamespace {
   pub new_namespace: String,
 }
 
-#[derive(Debug, Clone, Validate, Serialize, Deserialize)]
+#[derive(Serialize, Deserialize)]
+pub struct UpdatePublishedViewNav {
+  pub show_in_nav: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdatePublishedViewExpiry {
+  /// The time after which the view is no longer published. `None` removes the expiry.
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdatePublishedViewPassword {
+  /// The password required to read the view. `None` removes the password.
+  pub password: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PublishedViewAccessParams {
+  pub password: String,
+}
+
+/// Grants access to the content of a password-protected published view, until it expires.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedViewAccessToken {
+  pub access_token: String,
+  pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Validate, Serialize, Deserialize)]
 pub struct QueryCollabMembers {
   #[validate(custom = "validate_not_empty_str")]
   pub workspace_id: String,
   #[validate(custom = "validate_not_empty_str")]
   pub object_id: String,
+  /// Only return the members with exactly this access level.
+  #[serde(default)]
+  pub access_level: Option<AFAccessLevel>,
+  /// Only return the members whose name or email contains this text, ignoring case.
+  #[serde(default)]
+  pub search: Option<String>,
+  /// The `next_cursor` of the previous page. The members are ordered by the time they joined,
+  /// so the next page starts after this member.
+  #[serde(default)]
+  pub after: Option<i64>,
+  /// The maximum number of members to return. `None` returns all of them.
+  #[serde(default)]
+  pub limit: Option<u32>,
 }
 
 #[derive(Debug, Clone, Validate, Serialize, Deserialize)]
@@ -330,9 +508,237 @@ pub struct AFCollabMember {
   pub uid: i64,
   pub oid: String,
   pub permission: AFPermission,
+  /// The time after which the membership is no longer valid. `None` means it never expires.
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
+  /// A suspended member keeps its access level, but has no access until it is unsuspended.
+  #[serde(default)]
+  pub suspended: bool,
+}
+
+/// Header that carries a [CollabObjectToken].
+pub const X_OBJECT_TOKEN: &str = "X-Object-Token";
+
+/// A token that grants access to a single collab object without a user session.
+/// The `token` itself is only returned when it is created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabObjectToken {
+  pub id: Uuid,
+  pub token: String,
+  pub object_id: String,
+  pub access_level: AFAccessLevel,
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCollabObjectTokenParams {
+  pub access_level: AFAccessLevel,
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// What anyone holding a share link can do with the object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SharePermission {
+  View,
+  Comment,
+  Edit,
+}
+
+impl From<SharePermission> for AFAccessLevel {
+  fn from(permission: SharePermission) -> Self {
+    match permission {
+      SharePermission::View => AFAccessLevel::ReadOnly,
+      SharePermission::Comment => AFAccessLevel::ReadAndComment,
+      SharePermission::Edit => AFAccessLevel::ReadAndWrite,
+    }
+  }
+}
+
+impl From<AFAccessLevel> for SharePermission {
+  fn from(access_level: AFAccessLevel) -> Self {
+    match access_level {
+      AFAccessLevel::ReadOnly => SharePermission::View,
+      AFAccessLevel::ReadAndComment => SharePermission::Comment,
+      AFAccessLevel::ReadAndWrite | AFAccessLevel::FullAccess => SharePermission::Edit,
+    }
+  }
+}
+
+/// A link that grants access to a single collab object to anyone who opens it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabShareLink {
+  pub id: Uuid,
+  pub token: String,
+  pub object_id: String,
+  pub permission: SharePermission,
+  pub created_by: i64,
+  pub created_at: DateTime<Utc>,
+  pub expires_at: Option<DateTime<Utc>>,
+  /// The number of times the link can be used. `None` means unlimited.
+  pub max_uses: Option<i32>,
+  pub use_count: i32,
+  pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateShareLinkParams {
+  pub permission: SharePermission,
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
+  #[serde(default)]
+  pub max_uses: Option<i32>,
+}
+
+/// Replaces the permission, the expiration time and the maximum number of uses of a share link.
+pub type UpdateShareLinkParams = CreateShareLinkParams;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharingMember {
+  pub uid: i64,
+  pub user: Option<AFWebUser>,
+  pub access_level: AFAccessLevel,
+  pub expires_at: Option<DateTime<Utc>>,
+  /// The id of the ancestor view the access is inherited from. `None` for direct members.
+  pub inherited_from: Option<String>,
+}
+
+/// An active object-scoped token of the object. The token itself is never included.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharingLink {
+  pub id: Uuid,
+  pub access_level: AFAccessLevel,
+  pub created_by: i64,
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Everything the sharing dialog of an object needs, assembled in one go.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharingState {
+  pub object_id: String,
+  pub members: Vec<SharingMember>,
+  pub inherited_members: Vec<SharingMember>,
+  pub links: Vec<SharingLink>,
+  pub publish_info: Option<PublishInfo>,
+  /// The access level of the caller, either direct or inherited. `None` if the caller has no access.
+  pub caller_access_level: Option<AFAccessLevel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuerySharePreview {
+  pub uid: i64,
+  pub access_level: AFAccessLevel,
 }
 
+/// An object that a user would gain access to when a view is shared with them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareImpact {
+  pub object_id: String,
+  pub name: String,
+  /// The access level the user would hold after the share.
+  pub access_level: AFAccessLevel,
+  /// The access level the user holds now, either direct or inherited.
+  pub current_access_level: Option<AFAccessLevel>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryEffectiveAccess {
+  /// The user to resolve the access for. Defaults to the caller.
+  #[serde(default)]
+  pub uid: Option<i64>,
+  /// Include the list of sources that were considered.
+  #[serde(default)]
+  pub explain: bool,
+}
+
+/// A source that can grant a user access to an object.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AccessSource {
+  /// A membership on the object itself.
+  Direct,
+  /// A membership on an ancestor view of the object.
+  Inherited { from_object_id: String },
+  /// The role of the user in the workspace of the object.
+  WorkspaceRole { role: AFRole },
+  /// A grant on the object to a workspace group the user belongs to.
+  Group { group_id: Uuid },
+  /// An object-scoped token. `token_id` is `None` when the token could not be resolved.
+  ObjectToken { token_id: Option<Uuid> },
+}
+
+/// One step of the resolution of an [EffectiveAccess].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessDecisionStep {
+  pub source: AccessSource,
+  /// The access level the source grants. `None` if it grants nothing.
+  pub access_level: Option<AFAccessLevel>,
+  /// Whether this source determined the effective access level.
+  pub won: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveAccess {
+  pub object_id: String,
+  pub uid: i64,
+  /// `None` if the user has no access to the object.
+  pub access_level: Option<AFAccessLevel>,
+  /// The sources considered, in the order they were evaluated. Only set in explain mode.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub explanation: Option<Vec<AccessDecisionStep>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeCollabMembersParams {
+  /// The collab whose members are merged into the collab of the path.
+  pub from_object_id: String,
+}
+
+/// The outcome of merging the members of one collab into another.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MergedCollabMembers {
+  /// Members that did not exist on the target collab and were added.
+  pub added: Vec<i64>,
+  /// Members that existed on the target collab with a lower access level and were upgraded.
+  pub upgraded: Vec<i64>,
+}
+
+/// The access level a user holds on a collab, as held by the access control backend.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CollabAccessPolicy {
+  pub uid: i64,
+  pub access_level: AFAccessLevel,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CheckMembershipParams {
+  pub object_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryCollabMembersExport {
+  /// Only members at or above this access level are exported. `None` exports every member.
+  #[serde(default)]
+  pub access_level_filter: Option<AFAccessLevel>,
+}
+
+/// The members of the collabs in a workspace, grouped per collab.
 #[derive(Serialize, Deserialize)]
+pub struct CollabMembersExport {
+  pub workspace_id: Uuid,
+  pub access_level_filter: Option<AFAccessLevel>,
+  /// Collabs without any matching member are omitted.
+  pub objects: Vec<CollabMembersExportItem>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CollabMembersExportItem {
+  pub object_id: String,
+  pub members: Vec<AFCollabMember>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PublishInfo {
   pub namespace: Option<String>,
   pub publish_name: String,
@@ -352,6 +758,54 @@ impl AFRole {
   pub fn can_create_collab(&self) -> bool {
     matches!(self, AFRole::Owner | AFRole::Member)
   }
+
+  /// The capabilities every member with the role holds, whether a custom role is assigned to the
+  /// member or not.
+  pub fn default_capabilities(&self) -> &'static [WorkspaceCapability] {
+    match self {
+      AFRole::Owner => &WorkspaceCapability::ALL,
+      AFRole::Member => &[
+        WorkspaceCapability::Publish,
+        WorkspaceCapability::DeleteView,
+      ],
+      AFRole::Guest => &[],
+    }
+  }
+}
+
+/// What a member of a workspace may do besides reading and writing its collabs. The capabilities
+/// of a custom role are granted on top of the [AFRole::default_capabilities] of the member.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceCapability {
+  Publish,
+  Invite,
+  DeleteView,
+  ManageBilling,
+}
+
+impl WorkspaceCapability {
+  pub const ALL: [WorkspaceCapability; 4] = [
+    WorkspaceCapability::Publish,
+    WorkspaceCapability::Invite,
+    WorkspaceCapability::DeleteView,
+    WorkspaceCapability::ManageBilling,
+  ];
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      WorkspaceCapability::Publish => "publish",
+      WorkspaceCapability::Invite => "invite",
+      WorkspaceCapability::DeleteView => "delete_view",
+      WorkspaceCapability::ManageBilling => "manage_billing",
+    }
+  }
+
+  pub fn from_name(name: &str) -> Option<Self> {
+    Self::ALL
+      .into_iter()
+      .find(|capability| capability.as_str() == name)
+  }
 }
 
 impl From<i32> for AFRole {
@@ -440,6 +894,25 @@ impl AFAccessLevel {
       AFAccessLevel::FullAccess => true,
     }
   }
+
+  pub fn can_comment(&self) -> bool {
+    match self {
+      AFAccessLevel::ReadOnly => false,
+      AFAccessLevel::ReadAndComment | AFAccessLevel::ReadAndWrite | AFAccessLevel::FullAccess => {
+        true
+      },
+    }
+  }
+
+  /// Whether the holder can manage the members of the object.
+  pub fn can_share(&self) -> bool {
+    match self {
+      AFAccessLevel::ReadOnly | AFAccessLevel::ReadAndComment | AFAccessLevel::ReadAndWrite => {
+        false
+      },
+      AFAccessLevel::FullAccess => true,
+    }
+  }
 }
 
 impl From<&AFRole> for AFAccessLevel {
@@ -504,7 +977,11 @@ impl Ord for AFAccessLevel {
 }
 
 #[derive(Serialize, Deserialize)]
-pub struct AFCollabMembers(pub Vec<AFCollabMember>);
+pub struct AFCollabMemberPage {
+  pub members: Vec<AFCollabMember>,
+  /// The uid to pass as `after` to get the next page. `None` when this is the last page.
+  pub next_cursor: Option<i64>,
+}
 
 pub type RawData = Vec<u8>;
 
@@ -522,6 +999,7 @@ pub struct AFUserProfile {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct AFWorkspace {
   pub workspace_id: Uuid,
   pub database_storage_id: Uuid,
@@ -535,12 +1013,18 @@ pub struct AFWorkspace {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct AFWorkspaceSettings {
   #[serde(default)]
   pub disable_search_indexing: bool,
 
   #[serde(default)]
   pub ai_model: String,
+
+  /// When set, the members of a view are granted the same access to the views below it, unless
+  /// the inheritance is broken on one of them.
+  #[serde(default)]
+  pub inherit_view_permissions: bool,
 }
 
 impl Default for AFWorkspaceSettings {
@@ -548,16 +1032,20 @@ impl Default for AFWorkspaceSettings {
     Self {
       disable_search_indexing: false,
       ai_model: "".to_string(),
+      inherit_view_permissions: false,
     }
   }
 }
 
 #[derive(Default, Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct AFWorkspaceSettingsChange {
   #[serde(skip_serializing_if = "Option::is_none")]
   pub disable_search_indexing: Option<bool>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub ai_model: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub inherit_view_permissions: Option<bool>,
 }
 
 impl AFWorkspaceSettingsChange {
@@ -565,6 +1053,7 @@ impl AFWorkspaceSettingsChange {
     Self {
       disable_search_indexing: None,
       ai_model: None,
+      inherit_view_permissions: None,
     }
   }
   pub fn disable_search_indexing(mut self, disable_search_indexing: bool) -> Self {
@@ -575,6 +1064,10 @@ impl AFWorkspaceSettingsChange {
     self.ai_model = Some(ai_model);
     self
   }
+  pub fn inherit_view_permissions(mut self, inherit_view_permissions: bool) -> Self {
+    self.inherit_view_permissions = Some(inherit_view_permissions);
+    self
+  }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -585,9 +1078,11 @@ pub struct AFUserWorkspaceInfo {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct AFWorkspaceMember {
   pub name: String,
   pub email: String,
+  #[cfg_attr(feature = "openapi", schema(value_type = String, example = "Member"))]
   pub role: AFRole,
   pub avatar_url: Option<String>,
 }
@@ -1002,6 +1497,92 @@ pub struct DeleteGlobalCommentParams {
   pub comment_id: Uuid,
 }
 
+/// A comment anchored to a block of a collab. Replies belong to the thread of the comment they
+/// reply to, whose resolution applies to the whole thread.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CollabComment {
+  pub comment_id: Uuid,
+  pub object_id: String,
+  pub block_id: String,
+  pub reply_comment_id: Option<Uuid>,
+  /// Empty once the comment is deleted.
+  pub content: String,
+  pub created_by: Option<AFWebUser>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+  pub resolved_by: Option<AFWebUser>,
+  pub resolved_at: Option<DateTime<Utc>>,
+  pub is_deleted: bool,
+  /// The workspace members mentioned in the content.
+  pub mentions: Vec<AFWebUser>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CollabComments {
+  pub comments: Vec<CollabComment>,
+}
+
+/// A user that currently has the collab open over the realtime connection, on any of its
+/// devices.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CollabPresence {
+  pub uid: i64,
+  /// The last time the user sent a change, or its awareness, to the collab.
+  pub last_active_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CollabPresences {
+  pub presences: Vec<CollabPresence>,
+}
+
+/// The updates a user made to a collab that are not recorded yet.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CollabContribution {
+  pub uid: i64,
+  pub edit_count: i64,
+  pub first_edited_at: DateTime<Utc>,
+  pub last_edited_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CollabContributor {
+  pub uid: i64,
+  pub name: String,
+  pub email: String,
+  /// The number of updates the user made to the collab.
+  pub edit_count: i64,
+  pub first_edited_at: DateTime<Utc>,
+  pub last_edited_at: DateTime<Utc>,
+}
+
+/// The users who edited the collab, most recent first.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CollabContributors {
+  pub last_edited_by: Option<CollabContributor>,
+  pub contributors: Vec<CollabContributor>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateCollabCommentParams {
+  pub block_id: String,
+  /// Members of the workspace are mentioned with `@` followed by their user uuid.
+  pub content: String,
+  /// The comment to reply to, which must be anchored to the same block.
+  pub reply_comment_id: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QueryCollabComments {
+  pub block_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResolveCollabCommentParams {
+  /// `false` reopens the thread.
+  pub resolved: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Reactions {
   pub reactions: Vec<Reaction>,
@@ -1233,6 +1814,20 @@ pub struct TemplateHomePageQueryParams {
   pub per_count: Option<i64>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct InstantiateTemplateParams {
+  /// The view to create the copy of the template under. Defaults to the top level of the
+  /// workspace.
+  #[serde(default)]
+  pub dest_view_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InstantiatedTemplate {
+  /// The view the template was copied as.
+  pub view_id: String,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AvatarImageSource {
   pub file_id: String,
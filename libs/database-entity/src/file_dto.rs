@@ -83,3 +83,27 @@ pub struct CompleteUploadResponse {
   pub upload_id: String,
   pub parts: Vec<CompletedPartRequest>,
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UploadPartUrlResponse {
+  pub part_num: i32,
+  /// The presigned url the part is uploaded to with a PUT request. The `ETag` header of the
+  /// response is the e_tag of the part.
+  pub url: String,
+  pub expires_in_secs: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UploadedPart {
+  pub part_num: i32,
+  pub e_tag: String,
+  pub size: i64,
+}
+
+/// The parts already uploaded, which lets a client resume an interrupted upload by uploading only
+/// the missing parts.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListUploadPartsResponse {
+  pub upload_id: String,
+  pub parts: Vec<UploadedPart>,
+}
@@ -0,0 +1,93 @@
+use app_error::AppError;
+use chrono::NaiveDate;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::{AFWorkspaceAIDailyUsageRow, AFWorkspaceAIQuotaRow};
+
+/// Add AI requests of the workspace, and the tokens they consumed, to the usage of the day.
+pub async fn upsert_workspace_ai_request_usage<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  requests: i32,
+  tokens_consumed: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_workspace_ai_usage (created_at, workspace_id, ai_requests, ai_tokens_consumed)
+      VALUES (now()::date, $1, $2, $3)
+      ON CONFLICT (created_at, workspace_id) DO UPDATE
+      SET ai_requests = af_workspace_ai_usage.ai_requests + $2,
+          ai_tokens_consumed = af_workspace_ai_usage.ai_tokens_consumed + $3
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(requests)
+  .bind(tokens_consumed)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the AI requests and tokens the workspace used since the day `since`, included, together
+/// with its plan.
+pub async fn select_workspace_ai_quota<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  since: NaiveDate,
+) -> Result<AFWorkspaceAIQuotaRow, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceAIQuotaRow>(
+    r#"
+      SELECT
+        COALESCE(u.ai_requests, 0) AS ai_requests,
+        COALESCE(u.ai_tokens_consumed, 0) AS ai_tokens_consumed,
+        p.plan,
+        p.ai_monthly_requests,
+        p.ai_monthly_tokens
+      FROM (SELECT $1::uuid AS workspace_id) w
+      LEFT JOIN (
+        SELECT
+          workspace_id,
+          SUM(ai_requests)::BIGINT AS ai_requests,
+          SUM(ai_tokens_consumed)::BIGINT AS ai_tokens_consumed
+        FROM af_workspace_ai_usage
+        WHERE workspace_id = $1 AND created_at >= $2
+        GROUP BY workspace_id
+      ) u ON u.workspace_id = w.workspace_id
+      LEFT JOIN af_workspace_plan p ON p.workspace_id = w.workspace_id
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(since)
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the AI usage of the workspace for each day since `since`, included, the oldest first.
+/// The days without any usage are left out.
+pub async fn select_workspace_ai_daily_usage<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  since: NaiveDate,
+) -> Result<Vec<AFWorkspaceAIDailyUsageRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFWorkspaceAIDailyUsageRow>(
+    r#"
+      SELECT
+        created_at AS day,
+        ai_requests::BIGINT AS ai_requests,
+        ai_tokens_consumed,
+        COALESCE(search_requests, 0)::BIGINT AS search_requests,
+        COALESCE(search_tokens_consumed, 0) AS search_tokens_consumed,
+        COALESCE(index_tokens_consumed, 0) AS index_tokens_consumed
+      FROM af_workspace_ai_usage
+      WHERE workspace_id = $1 AND created_at >= $2
+      ORDER BY created_at
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(since)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
@@ -0,0 +1,85 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFAuditLogRow;
+
+pub async fn insert_audit_log<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: Option<&Uuid>,
+  actor_uid: Option<i64>,
+  action: &str,
+  target: Option<&str>,
+  metadata: &serde_json::Value,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_audit_log (workspace_id, actor_uid, action, target, metadata)
+      VALUES ($1, $2, $3, $4, $5)
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(actor_uid)
+  .bind(action)
+  .bind(target)
+  .bind(metadata)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the entries of the audit log of the workspace, the most recent first. The entries
+/// that are not tied to a workspace, such as the logins, are included for the current members of
+/// the workspace. Only the entries older than `before_id` are returned when it is given.
+#[allow(clippy::too_many_arguments)]
+pub async fn select_audit_logs<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  actor_uid: Option<i64>,
+  action: Option<&str>,
+  since: Option<DateTime<Utc>>,
+  until: Option<DateTime<Utc>>,
+  before_id: Option<i64>,
+  limit: i64,
+) -> Result<Vec<AFAuditLogRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFAuditLogRow>(
+    r#"
+      SELECT
+        l.id,
+        l.workspace_id,
+        l.actor_uid,
+        u.email AS actor_email,
+        l.action,
+        l.target,
+        l.metadata,
+        l.created_at
+      FROM af_audit_log l
+      LEFT JOIN af_user u ON u.uid = l.actor_uid
+      WHERE (
+          l.workspace_id = $1
+          OR (
+            l.workspace_id IS NULL
+            AND l.actor_uid IN (SELECT uid FROM af_workspace_member WHERE workspace_id = $1)
+          )
+        )
+        AND ($2::bigint IS NULL OR l.actor_uid = $2)
+        AND ($3::text IS NULL OR l.action = $3)
+        AND ($4::timestamptz IS NULL OR l.created_at >= $4)
+        AND ($5::timestamptz IS NULL OR l.created_at < $5)
+        AND ($6::bigint IS NULL OR l.id < $6)
+      ORDER BY l.id DESC
+      LIMIT $7
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(actor_uid)
+  .bind(action)
+  .bind(since)
+  .bind(until)
+  .bind(before_id)
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
@@ -0,0 +1,68 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFDatabaseCalendarFeedRow;
+
+/// Creates the feed of the database view for the user, or returns the one the user already has.
+pub async fn insert_database_calendar_feed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &str,
+  token: &str,
+  created_by: i64,
+) -> Result<AFDatabaseCalendarFeedRow, AppError> {
+  let row = sqlx::query_as::<_, AFDatabaseCalendarFeedRow>(
+    r#"
+      INSERT INTO af_database_calendar_feed (token, workspace_id, view_id, created_by)
+      VALUES ($1, $2, $3, $4)
+      ON CONFLICT (view_id, created_by) DO UPDATE SET view_id = EXCLUDED.view_id
+      RETURNING token, workspace_id, view_id, created_by, created_at
+    "#,
+  )
+  .bind(token)
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(created_by)
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn select_database_calendar_feed_by_token<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  token: &str,
+) -> Result<Option<AFDatabaseCalendarFeedRow>, AppError> {
+  let row = sqlx::query_as::<_, AFDatabaseCalendarFeedRow>(
+    r#"
+      SELECT token, workspace_id, view_id, created_by, created_at
+      FROM af_database_calendar_feed
+      WHERE token = $1
+    "#,
+  )
+  .bind(token)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns false if the user has no feed of the database view.
+pub async fn delete_database_calendar_feed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &str,
+  created_by: i64,
+) -> Result<bool, AppError> {
+  let res = sqlx::query(
+    r#"
+      DELETE FROM af_database_calendar_feed
+      WHERE workspace_id = $1 AND view_id = $2 AND created_by = $3
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(created_by)
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
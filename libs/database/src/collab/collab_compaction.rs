@@ -0,0 +1,80 @@
+use app_error::AppError;
+use collab_entity::CollabType;
+use sqlx::{Executor, Postgres, Transaction};
+use std::ops::DerefMut;
+
+use crate::collab::{collab_type_from_partition_key, partition_key_from_collab_type};
+
+/// Returns the collabs larger than `min_len` bytes that have been written since they were last
+/// compacted, the largest first.
+pub async fn select_collabs_to_compact<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  min_len: i32,
+  limit: i64,
+) -> Result<Vec<(String, CollabType)>, AppError> {
+  let rows: Vec<(String, i32)> = sqlx::query_as(
+    r#"
+      SELECT oid, partition_key
+      FROM af_collab
+      WHERE deleted_at IS NULL
+        AND len >= $1
+        AND compacted_len IS DISTINCT FROM len
+      ORDER BY len DESC
+      LIMIT $2
+    "#,
+  )
+  .bind(min_len)
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|(oid, partition_key)| (oid, collab_type_from_partition_key(partition_key)))
+      .collect(),
+  )
+}
+
+/// Replaces the blob of the collab with its compacted version.
+pub async fn update_compacted_blob(
+  txn: &mut Transaction<'_, Postgres>,
+  oid: &str,
+  collab_type: &CollabType,
+  blob: &[u8],
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_collab
+      SET blob = $3, len = $4, compacted_len = $4
+      WHERE oid = $1 AND partition_key = $2
+    "#,
+  )
+  .bind(oid)
+  .bind(partition_key_from_collab_type(collab_type))
+  .bind(blob)
+  .bind(blob.len() as i32)
+  .execute(txn.deref_mut())
+  .await?;
+  Ok(())
+}
+
+/// Records that the collab has been compacted without changing its blob, when compacting it did
+/// not make it smaller.
+pub async fn update_compacted_len(
+  txn: &mut Transaction<'_, Postgres>,
+  oid: &str,
+  collab_type: &CollabType,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_collab
+      SET compacted_len = len
+      WHERE oid = $1 AND partition_key = $2
+    "#,
+  )
+  .bind(oid)
+  .bind(partition_key_from_collab_type(collab_type))
+  .execute(txn.deref_mut())
+  .await?;
+  Ok(())
+}
@@ -0,0 +1,65 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use database_entity::dto::CollabContribution;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFCollabContributorRow;
+
+/// Add the contributions to the ones recorded for the collab. The edit counts are added up, and
+/// the first and last edit times are widened to include the new edits.
+pub async fn upsert_collab_contributions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  oid: &str,
+  contributions: &[CollabContribution],
+) -> Result<(), AppError> {
+  let uids: Vec<i64> = contributions.iter().map(|c| c.uid).collect();
+  let edit_counts: Vec<i64> = contributions.iter().map(|c| c.edit_count).collect();
+  let first_edited_ats: Vec<DateTime<Utc>> =
+    contributions.iter().map(|c| c.first_edited_at).collect();
+  let last_edited_ats: Vec<DateTime<Utc>> =
+    contributions.iter().map(|c| c.last_edited_at).collect();
+  sqlx::query(
+    r#"
+      INSERT INTO af_collab_contributor
+        (oid, workspace_id, uid, edit_count, first_edited_at, last_edited_at)
+      SELECT $1, $2, c.uid, c.edit_count, c.first_edited_at, c.last_edited_at
+      FROM UNNEST($3::bigint[], $4::bigint[], $5::timestamptz[], $6::timestamptz[])
+        AS c(uid, edit_count, first_edited_at, last_edited_at)
+      ON CONFLICT (oid, uid) DO UPDATE SET
+        edit_count = af_collab_contributor.edit_count + EXCLUDED.edit_count,
+        first_edited_at = LEAST(af_collab_contributor.first_edited_at, EXCLUDED.first_edited_at),
+        last_edited_at = GREATEST(af_collab_contributor.last_edited_at, EXCLUDED.last_edited_at)
+    "#,
+  )
+  .bind(oid)
+  .bind(workspace_id)
+  .bind(uids)
+  .bind(edit_counts)
+  .bind(first_edited_ats)
+  .bind(last_edited_ats)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the users who edited the collab, most recent first.
+pub async fn select_collab_contributors<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+) -> Result<Vec<AFCollabContributorRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFCollabContributorRow>(
+    r#"
+      SELECT c.uid, u.name, u.email, c.edit_count, c.first_edited_at, c.last_edited_at
+      FROM af_collab_contributor c
+      JOIN af_user u ON u.uid = c.uid
+      WHERE c.oid = $1
+      ORDER BY c.last_edited_at DESC, c.uid
+    "#,
+  )
+  .bind(oid)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
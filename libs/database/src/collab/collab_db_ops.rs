@@ -1,15 +1,18 @@
 use anyhow::{anyhow, Context};
 use collab_entity::CollabType;
 use database_entity::dto::{
-  AFAccessLevel, AFCollabMember, AFPermission, AFSnapshotMeta, AFSnapshotMetas, CollabParams,
-  QueryCollab, QueryCollabResult, RawData,
+  AFAccessLevel, AFCollabMember, AFCollabVersion, AFPermission, AFSnapshotMeta, AFSnapshotMetas,
+  CollabParams, QueryCollab, QueryCollabResult, RawData,
 };
 
-use crate::collab::{partition_key_from_collab_type, SNAPSHOT_PER_HOUR};
+use crate::collab::{
+  collab_type_from_partition_key, partition_key_from_collab_type, SNAPSHOT_PER_HOUR,
+};
 use crate::pg_row::AFSnapshotRow;
 use crate::pg_row::{AFCollabMemberAccessLevelRow, AFCollabRowMeta};
+use crate::workspace_usage::upsert_workspace_editor_activity;
 use app_error::AppError;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use futures_util::stream::BoxStream;
 
 use sqlx::postgres::PgRow;
@@ -158,6 +161,7 @@ pub async fn insert_into_af_collab(
     },
   }
 
+  upsert_workspace_editor_activity(tx.deref_mut(), &workspace_id, *uid).await?;
   Ok(())
 }
 
@@ -184,6 +188,28 @@ where
   .await
 }
 
+/// Returns the blob of the collab, locking its row until the end of the transaction so that it is
+/// not written meanwhile. Returns `None` if the collab doesn't exist.
+pub async fn select_blob_for_update(
+  txn: &mut Transaction<'_, Postgres>,
+  object_id: &str,
+  collab_type: &CollabType,
+) -> Result<Option<Vec<u8>>, sqlx::Error> {
+  let partition_key = partition_key_from_collab_type(collab_type);
+  sqlx::query_scalar!(
+    r#"
+        SELECT blob
+        FROM af_collab
+        WHERE oid = $1 AND partition_key = $2 AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+    object_id,
+    partition_key,
+  )
+  .fetch_optional(txn.deref_mut())
+  .await
+}
+
 #[inline]
 pub async fn select_collab_meta_from_af_collab<'a, E>(
   conn: E,
@@ -339,6 +365,9 @@ pub async fn should_create_snapshot2<'a, E: Executor<'a, Database = Postgres>>(
 /// of snapshots stored for the specified `oid` does not exceed the provided `snapshot_limit`. If the limit
 /// is exceeded, the oldest snapshots are deleted to maintain the limit.
 ///
+/// The snapshots of a workspace that overrides the snapshot retention policy are left to the
+/// cleanup of that policy instead.
+///
 pub async fn create_snapshot_and_maintain_limit<'a>(
   mut transaction: Transaction<'a, Postgres>,
   workspace_id: &str,
@@ -368,10 +397,12 @@ pub async fn create_snapshot_and_maintain_limit<'a>(
     r#"
        DELETE FROM af_collab_snapshot
        WHERE oid = $1 AND sid NOT IN ( SELECT sid FROM af_collab_snapshot WHERE oid = $1 ORDER BY created_at DESC LIMIT $2)
+         AND NOT EXISTS (SELECT 1 FROM af_workspace_snapshot_retention WHERE workspace_id = $3)
       "#,
     )
     .bind(oid)
     .bind(snapshot_limit)
+    .bind(workspace_id)
     .execute(transaction.deref_mut())
     .await?;
 
@@ -391,7 +422,8 @@ pub async fn select_snapshot(
   let row = sqlx::query_as!(
     AFSnapshotRow,
     r#"
-      SELECT * FROM af_collab_snapshot
+      SELECT sid, oid, blob, len, encrypt, deleted_at, workspace_id, created_at
+      FROM af_collab_snapshot
       WHERE sid = $1 AND deleted_at IS NULL;
     "#,
     snapshot_id,
@@ -421,6 +453,75 @@ pub async fn get_all_collab_snapshot_meta(
   Ok(AFSnapshotMetas(snapshots))
 }
 
+/// Records the user who created the snapshot.
+pub async fn update_collab_snapshot_created_by<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  snapshot_id: i64,
+  uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    "UPDATE af_collab_snapshot SET created_by = $2 WHERE sid = $1",
+    snapshot_id,
+    uid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the versions of the collab in descending order of creation time.
+pub async fn select_collab_versions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+) -> Result<Vec<AFCollabVersion>, AppError> {
+  let rows = sqlx::query_as::<_, (i64, String, DateTime<Utc>, Option<i64>)>(
+    r#"
+      SELECT sid, oid, created_at, created_by
+      FROM af_collab_snapshot
+      WHERE oid = $1 AND deleted_at IS NULL
+      ORDER BY created_at DESC, sid DESC
+    "#,
+  )
+  .bind(oid)
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(
+        |(snapshot_id, object_id, created_at, created_by)| AFCollabVersion {
+          snapshot_id,
+          object_id,
+          created_at,
+          created_by,
+        },
+      )
+      .collect(),
+  )
+}
+
+/// Returns the id of the latest version of the collab created at or before the given time.
+pub async fn select_collab_version_at<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+  time: DateTime<Utc>,
+) -> Result<Option<i64>, AppError> {
+  let snapshot_id = sqlx::query_scalar!(
+    r#"
+      SELECT sid
+      FROM af_collab_snapshot
+      WHERE oid = $1 AND created_at <= $2 AND deleted_at IS NULL
+      ORDER BY created_at DESC, sid DESC
+      LIMIT 1
+    "#,
+    oid,
+    time,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(snapshot_id)
+}
+
 #[inline]
 #[instrument(level = "trace", skip(txn), err)]
 pub async fn upsert_collab_member_with_txn<T: AsRef<str> + Debug>(
@@ -449,7 +550,11 @@ pub async fn upsert_collab_member_with_txn<T: AsRef<str> + Debug>(
     VALUES ($1, $2, $3)
     ON CONFLICT (uid, oid)
     DO UPDATE
-      SET permission_id = excluded.permission_id;
+      SET permission_id = excluded.permission_id,
+        access_level_updated_at = CASE
+          WHEN af_collab_member.permission_id <> excluded.permission_id THEN CURRENT_TIMESTAMP
+          ELSE af_collab_member.access_level_updated_at
+        END;
     "#,
     uid,
     oid,
@@ -477,6 +582,40 @@ pub async fn insert_collab_member(
   Ok(())
 }
 
+/// Set the time after which the membership is revoked. `None` keeps the current expiry.
+pub async fn update_collab_member_expires_at(
+  uid: i64,
+  oid: &str,
+  expires_at: Option<DateTime<Utc>>,
+  txn: &mut Transaction<'_, sqlx::Postgres>,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    "UPDATE af_collab_member SET expires_at = COALESCE($3, expires_at) WHERE uid = $1 AND oid = $2",
+    uid,
+    oid,
+    expires_at,
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  Ok(())
+}
+
+/// Deletes the collab members whose membership has expired and returns their (uid, oid) pairs.
+pub async fn delete_expired_collab_members<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+) -> Result<Vec<(i64, String)>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      DELETE FROM af_collab_member
+      WHERE expires_at <= NOW()
+      RETURNING uid, oid
+    "#,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows.into_iter().map(|row| (row.uid, row.oid)).collect())
+}
+
 pub async fn delete_collab_member(
   uid: i64,
   oid: &str,
@@ -490,9 +629,24 @@ pub async fn delete_collab_member(
   Ok(())
 }
 
+/// Delete all the members of the collab and return their uids.
+pub async fn delete_collab_members(
+  oid: &str,
+  txn: &mut Transaction<'_, sqlx::Postgres>,
+) -> Result<Vec<i64>, AppError> {
+  let uids = sqlx::query_scalar!(
+    "DELETE FROM af_collab_member WHERE oid = $1 RETURNING uid",
+    oid,
+  )
+  .fetch_all(txn.deref_mut())
+  .await?;
+  Ok(uids)
+}
+
 pub fn select_collab_member_access_level(
   pg_pool: &PgPool,
 ) -> BoxStream<'_, sqlx::Result<AFCollabMemberAccessLevelRow>> {
+  // Suspended members keep their level in the database, but get no policy
   sqlx::query_as!(
     AFCollabMemberAccessLevelRow,
     r#"
@@ -500,15 +654,16 @@ pub fn select_collab_member_access_level(
       FROM af_collab_member
       INNER JOIN af_permissions
         ON af_collab_member.permission_id = af_permissions.id
+      WHERE NOT af_collab_member.suspended
     "#
   )
   .fetch(pg_pool)
 }
 
 #[inline]
-pub async fn select_collab_members(
+pub async fn select_collab_members<'a, E: Executor<'a, Database = Postgres>>(
   oid: &str,
-  pg_pool: &PgPool,
+  executor: E,
 ) -> Result<Vec<AFCollabMember>, AppError> {
   let members = sqlx::query(
     r#"
@@ -517,7 +672,9 @@ pub async fn select_collab_members(
         af_permissions.id,
         af_permissions.name,
         af_permissions.access_level,
-        af_permissions.description
+        af_permissions.description,
+        af_collab_member.expires_at,
+        af_collab_member.suspended
       FROM af_collab_member
       JOIN af_permissions ON af_collab_member.permission_id = af_permissions.id
       WHERE af_collab_member.oid = $1
@@ -526,12 +683,133 @@ pub async fn select_collab_members(
   )
   .bind(oid)
   .try_map(collab_member_try_from_row)
-  .fetch_all(pg_pool)
+  .fetch_all(executor)
   .await?;
 
   Ok(members)
 }
 
+/// Returns a page of the members of the collab, ordered by the time they joined.
+/// `search` matches the name or the email of the member, ignoring case. The page starts after
+/// the member `after_uid`, and is empty if that user is not a member. A `limit` of `None` returns
+/// all the remaining members.
+pub async fn select_collab_members_page<'a, E: Executor<'a, Database = Postgres>>(
+  oid: &str,
+  access_level: Option<AFAccessLevel>,
+  search: Option<&str>,
+  after_uid: Option<i64>,
+  limit: Option<i64>,
+  executor: E,
+) -> Result<Vec<AFCollabMember>, AppError> {
+  let search_pattern = search.map(|search| {
+    let escaped = search
+      .replace('\\', "\\\\")
+      .replace('%', "\\%")
+      .replace('_', "\\_");
+    format!("%{}%", escaped)
+  });
+  let rows = sqlx::query!(
+    r#"
+      SELECT af_collab_member.uid,
+        af_collab_member.oid,
+        af_permissions.id,
+        af_permissions.name,
+        af_permissions.access_level,
+        af_permissions.description,
+        af_collab_member.expires_at,
+        af_collab_member.suspended
+      FROM af_collab_member
+      JOIN af_permissions ON af_collab_member.permission_id = af_permissions.id
+      LEFT JOIN af_user ON af_user.uid = af_collab_member.uid
+      WHERE af_collab_member.oid = $1
+        AND ($2::INT IS NULL OR af_permissions.access_level = $2)
+        AND ($3::TEXT IS NULL OR af_user.name ILIKE $3 OR af_user.email ILIKE $3)
+        AND (
+          $4::BIGINT IS NULL
+          OR (af_collab_member.created_at, af_collab_member.uid) > (
+            SELECT created_at, uid FROM af_collab_member WHERE oid = $1 AND uid = $4
+          )
+        )
+      ORDER BY af_collab_member.created_at ASC, af_collab_member.uid ASC
+      LIMIT $5
+    "#,
+    oid,
+    access_level.map(|level| level as i32),
+    search_pattern,
+    after_uid,
+    limit,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  let members = rows
+    .into_iter()
+    .map(|row| AFCollabMember {
+      uid: row.uid,
+      oid: row.oid,
+      permission: AFPermission {
+        id: row.id,
+        name: row.name,
+        access_level: AFAccessLevel::from(row.access_level),
+        description: row.description.unwrap_or_default(),
+      },
+      expires_at: row.expires_at,
+      suspended: row.suspended,
+    })
+    .collect();
+  Ok(members)
+}
+
+/// Returns the members of every non-deleted collab in the workspace, ordered by collab and then by
+/// membership creation time. When `min_access_level` is set, only members at or above that level
+/// are returned.
+pub async fn select_workspace_collab_members<'a, E: Executor<'a, Database = Postgres>>(
+  workspace_id: &Uuid,
+  min_access_level: Option<AFAccessLevel>,
+  executor: E,
+) -> Result<Vec<AFCollabMember>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT af_collab_member.uid,
+        af_collab_member.oid,
+        af_permissions.id,
+        af_permissions.name,
+        af_permissions.access_level,
+        af_permissions.description,
+        af_collab_member.expires_at,
+        af_collab_member.suspended
+      FROM af_collab_member
+      JOIN af_permissions ON af_collab_member.permission_id = af_permissions.id
+      JOIN af_collab ON af_collab.oid = af_collab_member.oid
+      WHERE af_collab.workspace_id = $1
+        AND af_collab.deleted_at IS NULL
+        AND ($2::INT IS NULL OR af_permissions.access_level >= $2)
+      ORDER BY af_collab_member.oid, af_collab_member.created_at ASC
+    "#,
+    workspace_id,
+    min_access_level.map(|level| level as i32),
+  )
+  .fetch_all(executor)
+  .await?;
+
+  let members = rows
+    .into_iter()
+    .map(|row| AFCollabMember {
+      uid: row.uid,
+      oid: row.oid,
+      permission: AFPermission {
+        id: row.id,
+        name: row.name,
+        access_level: AFAccessLevel::from(row.access_level),
+        description: row.description.unwrap_or_default(),
+      },
+      expires_at: row.expires_at,
+      suspended: row.suspended,
+    })
+    .collect();
+  Ok(members)
+}
+
 #[inline]
 pub async fn select_collab_member<'a, E: Executor<'a, Database = Postgres>>(
   uid: &i64,
@@ -540,7 +818,7 @@ pub async fn select_collab_member<'a, E: Executor<'a, Database = Postgres>>(
 ) -> Result<AFCollabMember, AppError> {
   let row = sqlx::query(
   r#"
-    SELECT af_collab_member.uid, af_collab_member.oid, af_permissions.id, af_permissions.name, af_permissions.access_level, af_permissions.description
+    SELECT af_collab_member.uid, af_collab_member.oid, af_permissions.id, af_permissions.name, af_permissions.access_level, af_permissions.description, af_collab_member.expires_at, af_collab_member.suspended
     FROM af_collab_member
     JOIN af_permissions ON af_collab_member.permission_id = af_permissions.id
     WHERE af_collab_member.uid = $1 AND af_collab_member.oid = $2
@@ -568,9 +846,168 @@ fn collab_member_try_from_row(row: PgRow) -> Result<AFCollabMember, sqlx::Error>
     uid: row.try_get(0)?,
     oid: row.try_get(1)?,
     permission,
+    expires_at: row.try_get(6)?,
+    suspended: row.try_get(7)?,
   })
 }
 
+/// Returns the members of all the given collabs, ordered by collab and then by the time they joined.
+pub async fn select_collab_members_for_objects<'a, E: Executor<'a, Database = Postgres>>(
+  oids: &[String],
+  executor: E,
+) -> Result<Vec<AFCollabMember>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT af_collab_member.uid,
+        af_collab_member.oid,
+        af_permissions.id,
+        af_permissions.name,
+        af_permissions.access_level,
+        af_permissions.description,
+        af_collab_member.expires_at,
+        af_collab_member.suspended
+      FROM af_collab_member
+      JOIN af_permissions ON af_collab_member.permission_id = af_permissions.id
+      WHERE af_collab_member.oid = ANY($1)
+      ORDER BY af_collab_member.oid, af_collab_member.created_at ASC
+    "#,
+    oids,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  let members = rows
+    .into_iter()
+    .map(|row| AFCollabMember {
+      uid: row.uid,
+      oid: row.oid,
+      permission: AFPermission {
+        id: row.id,
+        name: row.name,
+        access_level: AFAccessLevel::from(row.access_level),
+        description: row.description.unwrap_or_default(),
+      },
+      expires_at: row.expires_at,
+      suspended: row.suspended,
+    })
+    .collect();
+  Ok(members)
+}
+
+/// Returns the access level the user holds on each of the given collabs, keyed by collab id.
+/// Collabs the user is not a member of, or is a suspended member of, are not included.
+pub async fn select_access_levels_for_user<'a, E: Executor<'a, Database = Postgres>>(
+  uid: i64,
+  oids: &[String],
+  executor: E,
+) -> Result<HashMap<String, AFAccessLevel>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT af_collab_member.oid, af_permissions.access_level
+      FROM af_collab_member
+      JOIN af_permissions ON af_collab_member.permission_id = af_permissions.id
+      WHERE af_collab_member.uid = $1
+        AND af_collab_member.oid = ANY($2)
+        AND NOT af_collab_member.suspended
+    "#,
+    uid,
+    oids,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| (row.oid, AFAccessLevel::from(row.access_level)))
+      .collect(),
+  )
+}
+
+/// Returns the number of collabs and their total encoded size in bytes, per collab type, for the
+/// given workspace. Deleted collabs are not counted. The sizes come from the stored `len` column,
+/// so no collab is loaded or decoded.
+pub async fn select_workspace_collab_footprint<'a, E: Executor<'a, Database = Postgres>>(
+  workspace_id: &Uuid,
+  executor: E,
+) -> Result<Vec<(CollabType, i64, i64)>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT partition_key,
+        COUNT(*)::BIGINT AS "count!",
+        COALESCE(SUM(len), 0)::BIGINT AS "bytes!"
+      FROM af_collab
+      WHERE workspace_id = $1 AND deleted_at IS NULL
+      GROUP BY partition_key
+      ORDER BY partition_key
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| {
+        (
+          collab_type_from_partition_key(row.partition_key),
+          row.count,
+          row.bytes,
+        )
+      })
+      .collect(),
+  )
+}
+
+/// Returns the members whose membership expires in the window `(now, now + within]`.
+/// Members that have already expired are not included. The result is ordered by the expiry time,
+/// so the members that are about to lose their access come first.
+pub async fn select_expiring_collab_members(
+  pg_pool: &PgPool,
+  within: Duration,
+) -> Result<Vec<AFCollabMember>, AppError> {
+  let now = Utc::now();
+  let rows = sqlx::query!(
+    r#"
+      SELECT af_collab_member.uid,
+        af_collab_member.oid,
+        af_permissions.id,
+        af_permissions.name,
+        af_permissions.access_level,
+        af_permissions.description,
+        af_collab_member.expires_at,
+        af_collab_member.suspended
+      FROM af_collab_member
+      JOIN af_permissions ON af_collab_member.permission_id = af_permissions.id
+      WHERE af_collab_member.expires_at > $1
+        AND af_collab_member.expires_at <= $2
+      ORDER BY af_collab_member.expires_at ASC
+    "#,
+    now,
+    now + within,
+  )
+  .fetch_all(pg_pool)
+  .await?;
+
+  let members = rows
+    .into_iter()
+    .map(|row| AFCollabMember {
+      uid: row.uid,
+      oid: row.oid,
+      permission: AFPermission {
+        id: row.id,
+        name: row.name,
+        access_level: AFAccessLevel::from(row.access_level),
+        description: row.description.unwrap_or_default(),
+      },
+      expires_at: row.expires_at,
+      suspended: row.suspended,
+    })
+    .collect();
+  Ok(members)
+}
+
 #[inline]
 pub async fn is_collab_member_exists<'a, E: Executor<'a, Database = Postgres>>(
   uid: i64,
@@ -589,6 +1026,139 @@ pub async fn is_collab_member_exists<'a, E: Executor<'a, Database = Postgres>>(
   transform_record_not_found_error(result)
 }
 
+/// Returns the access level of the member and the last time it was changed, or `None` if the user
+/// is not a member of the collab. The time is `None` when the level never changed.
+pub async fn select_collab_member_access_level_updated_at<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  uid: i64,
+  oid: &str,
+  executor: E,
+) -> Result<Option<(AFAccessLevel, Option<DateTime<Utc>>)>, AppError> {
+  let row = sqlx::query!(
+    r#"
+      SELECT af_permissions.access_level, af_collab_member.access_level_updated_at
+      FROM af_collab_member
+      JOIN af_permissions ON af_collab_member.permission_id = af_permissions.id
+      WHERE af_collab_member.uid = $1 AND af_collab_member.oid = $2
+    "#,
+    uid,
+    oid
+  )
+  .fetch_optional(executor)
+  .await?;
+
+  Ok(row.map(|row| {
+    (
+      AFAccessLevel::from(row.access_level),
+      row.access_level_updated_at,
+    )
+  }))
+}
+
+/// Set the suspended flag of the member and return the access level the member holds, which is
+/// kept as is. Return [AppError::RecordNotFound] if the user is not a member of the collab.
+pub async fn update_collab_member_suspended<'a, E: Executor<'a, Database = Postgres>>(
+  uid: i64,
+  oid: &str,
+  suspended: bool,
+  executor: E,
+) -> Result<AFAccessLevel, AppError> {
+  let access_level = sqlx::query_scalar!(
+    r#"
+      UPDATE af_collab_member
+      SET suspended = $3
+      FROM af_permissions
+      WHERE af_collab_member.permission_id = af_permissions.id
+        AND af_collab_member.uid = $1
+        AND af_collab_member.oid = $2
+      RETURNING af_permissions.access_level
+    "#,
+    uid,
+    oid,
+    suspended,
+  )
+  .fetch_optional(executor)
+  .await?;
+
+  access_level.map(AFAccessLevel::from).ok_or_else(|| {
+    AppError::RecordNotFound(format!("user:{} is not a member of collab:{}", uid, oid))
+  })
+}
+
+/// Returns true if the user is a member of the collab and the membership is suspended.
+pub async fn is_collab_member_suspended<'a, E: Executor<'a, Database = Postgres>>(
+  uid: i64,
+  oid: &str,
+  executor: E,
+) -> Result<bool, AppError> {
+  let suspended = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS (
+        SELECT 1 FROM af_collab_member WHERE uid = $1 AND oid = $2 AND suspended
+      )
+    "#,
+    uid,
+    oid,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(suspended.unwrap_or(false))
+}
+
+pub async fn is_collab_membership_locked<'a, E: Executor<'a, Database = Postgres>>(
+  oid: &str,
+  executor: E,
+) -> Result<bool, AppError> {
+  let locked = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS (SELECT 1 FROM af_collab_membership_lock WHERE oid = $1)
+    "#,
+    oid,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(locked.unwrap_or(false))
+}
+
+/// Lock the membership of the collab. Locking an already locked collab keeps the original lock.
+pub async fn insert_collab_membership_lock<'a, E: Executor<'a, Database = Postgres>>(
+  oid: &str,
+  locked_by: Option<i64>,
+  reason: Option<&str>,
+  executor: E,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_collab_membership_lock (oid, locked_by, reason)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (oid) DO NOTHING
+    "#,
+    oid,
+    locked_by,
+    reason,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn delete_collab_membership_lock<'a, E: Executor<'a, Database = Postgres>>(
+  oid: &str,
+  executor: E,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_collab_membership_lock WHERE oid = $1
+    "#,
+    oid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
 #[inline]
 fn transform_record_not_found_error(
   result: Result<Option<bool>, sqlx::Error>,
@@ -642,3 +1212,42 @@ pub async fn select_workspace_database_oid<'a, E: Executor<'a, Database = Postgr
   .fetch_one(executor)
   .await
 }
+
+/// Returns the workspace of each of the collabs, keyed by object id. The collabs that are not
+/// stored are left out.
+pub async fn select_workspace_id_of_collabs<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oids: &[String],
+) -> Result<HashMap<String, Uuid>, AppError> {
+  let rows = sqlx::query!(
+    "SELECT oid, workspace_id FROM af_collab WHERE oid = ANY($1)",
+    oids
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| (row.oid, row.workspace_id))
+      .collect(),
+  )
+}
+
+/// Returns a fingerprint of the stored state of the collabs, which changes whenever one of them
+/// is saved with a different length, or is added or removed.
+pub async fn select_collabs_fingerprint<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oids: &[String],
+) -> Result<String, AppError> {
+  let fingerprint = sqlx::query_scalar!(
+    r#"
+      SELECT md5(string_agg(oid || ':' || COALESCE(len, 0), ',' ORDER BY oid))
+      FROM af_collab
+      WHERE oid = ANY($1)
+    "#,
+    oids,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(fingerprint.unwrap_or_default())
+}
@@ -0,0 +1,64 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+/// Break the inheritance of access on the collab. Breaking it again is a no-op.
+pub async fn insert_collab_inheritance_break<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  oid: &str,
+  created_by: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_collab_inheritance_break (workspace_id, oid, created_by)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (workspace_id, oid) DO NOTHING
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(oid)
+  .bind(created_by)
+  .execute(executor)
+  .await?;
+
+  Ok(())
+}
+
+/// Returns true if the inheritance was broken on the collab.
+pub async fn delete_collab_inheritance_break<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  oid: &str,
+) -> Result<bool, AppError> {
+  let result = sqlx::query(
+    r#"
+      DELETE FROM af_collab_inheritance_break
+      WHERE workspace_id = $1 AND oid = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(oid)
+  .execute(executor)
+  .await?;
+
+  Ok(result.rows_affected() > 0)
+}
+
+/// Returns the ids of the collabs of the workspace on which the inheritance is broken.
+pub async fn select_collab_inheritance_breaks<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<String>, AppError> {
+  let oids = sqlx::query_scalar::<_, String>(
+    r#"
+      SELECT oid FROM af_collab_inheritance_break
+      WHERE workspace_id = $1
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(executor)
+  .await?;
+
+  Ok(oids)
+}
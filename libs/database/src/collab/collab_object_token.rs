@@ -0,0 +1,122 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use database_entity::dto::AFAccessLevel;
+use sha2::{Digest, Sha256};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFCollabObjectTokenRow;
+
+/// Only the hash of an object token is stored, so a leaked table does not leak usable tokens.
+fn hash_object_token(token: &str) -> String {
+  format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+pub async fn insert_collab_object_token<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  token: &str,
+  workspace_id: &Uuid,
+  oid: &str,
+  access_level: AFAccessLevel,
+  created_by: i64,
+  expires_at: Option<DateTime<Utc>>,
+) -> Result<Uuid, AppError> {
+  let id = sqlx::query_scalar!(
+    r#"
+      INSERT INTO af_collab_object_token (token_hash, workspace_id, oid, access_level, created_by, expires_at)
+      VALUES ($1, $2, $3, $4, $5, $6)
+      RETURNING id
+    "#,
+    hash_object_token(token),
+    workspace_id,
+    oid,
+    access_level as i32,
+    created_by,
+    expires_at,
+  )
+  .fetch_one(executor)
+  .await?;
+
+  Ok(id)
+}
+
+/// Returns the token if it exists, regardless of whether it is expired or revoked.
+pub async fn select_collab_object_token<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  token: &str,
+) -> Result<Option<AFCollabObjectTokenRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFCollabObjectTokenRow,
+    r#"
+      SELECT id, workspace_id, oid, access_level, created_by, expires_at, revoked_at
+      FROM af_collab_object_token
+      WHERE token_hash = $1
+    "#,
+    hash_object_token(token),
+  )
+  .fetch_optional(executor)
+  .await?;
+
+  Ok(row)
+}
+
+/// Revoke the token of the given object. Returns false if there is no such token, or if it
+/// was already revoked.
+pub async fn revoke_collab_object_token<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  token_id: &Uuid,
+  oid: &str,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE af_collab_object_token
+      SET revoked_at = CURRENT_TIMESTAMP
+      WHERE id = $1 AND oid = $2 AND revoked_at IS NULL
+    "#,
+    token_id,
+    oid,
+  )
+  .execute(executor)
+  .await?;
+
+  Ok(res.rows_affected() == 1)
+}
+
+/// Returns the tokens of the object that are neither revoked nor expired.
+pub async fn select_active_collab_object_tokens<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+) -> Result<Vec<AFCollabObjectTokenRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCollabObjectTokenRow,
+    r#"
+      SELECT id, workspace_id, oid, access_level, created_by, expires_at, revoked_at
+      FROM af_collab_object_token
+      WHERE oid = $1
+        AND revoked_at IS NULL
+        AND (expires_at IS NULL OR expires_at > NOW())
+      ORDER BY created_at ASC
+    "#,
+    oid,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  Ok(rows)
+}
+
+/// Delete all the tokens of the object, revoked and expired ones included.
+pub async fn delete_collab_object_tokens<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_collab_object_token WHERE oid = $1
+    "#,
+    oid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
@@ -0,0 +1,270 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use database_entity::dto::AFAccessLevel;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFCollabShareLinkRow;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_collab_share_link<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  token: &str,
+  workspace_id: &Uuid,
+  oid: &str,
+  access_level: AFAccessLevel,
+  created_by: i64,
+  expires_at: Option<DateTime<Utc>>,
+  max_uses: Option<i32>,
+) -> Result<AFCollabShareLinkRow, AppError> {
+  let row = sqlx::query_as!(
+    AFCollabShareLinkRow,
+    r#"
+      INSERT INTO af_collab_share_link (token, workspace_id, oid, access_level, created_by, expires_at, max_uses)
+      VALUES ($1, $2, $3, $4, $5, $6, $7)
+      RETURNING id, token, workspace_id, oid, access_level, created_by, expires_at, created_at,
+        max_uses, use_count, last_used_at
+    "#,
+    token,
+    workspace_id,
+    oid,
+    access_level as i32,
+    created_by,
+    expires_at,
+    max_uses,
+  )
+  .fetch_one(executor)
+  .await?;
+
+  Ok(row)
+}
+
+/// Returns the link if it exists, regardless of whether it is expired or used up.
+pub async fn select_collab_share_link_by_token<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  token: &str,
+) -> Result<Option<AFCollabShareLinkRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFCollabShareLinkRow,
+    r#"
+      SELECT id, token, workspace_id, oid, access_level, created_by, expires_at, created_at,
+        max_uses, use_count, last_used_at
+      FROM af_collab_share_link
+      WHERE token = $1
+    "#,
+    token,
+  )
+  .fetch_optional(executor)
+  .await?;
+
+  Ok(row)
+}
+
+/// Count a use of the link if it is neither expired nor used up, and return the link after the
+/// use. Returns `None` otherwise.
+pub async fn use_collab_share_link<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  token: &str,
+) -> Result<Option<AFCollabShareLinkRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFCollabShareLinkRow,
+    r#"
+      UPDATE af_collab_share_link
+      SET use_count = use_count + 1, last_used_at = CURRENT_TIMESTAMP
+      WHERE token = $1
+        AND (expires_at IS NULL OR expires_at > NOW())
+        AND (max_uses IS NULL OR use_count < max_uses)
+      RETURNING id, token, workspace_id, oid, access_level, created_by, expires_at, created_at,
+        max_uses, use_count, last_used_at
+    "#,
+    token,
+  )
+  .fetch_optional(executor)
+  .await?;
+
+  Ok(row)
+}
+
+/// Returns the links of the object, expired and used up ones included, oldest first.
+pub async fn select_collab_share_links<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+) -> Result<Vec<AFCollabShareLinkRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCollabShareLinkRow,
+    r#"
+      SELECT id, token, workspace_id, oid, access_level, created_by, expires_at, created_at,
+        max_uses, use_count, last_used_at
+      FROM af_collab_share_link
+      WHERE oid = $1
+      ORDER BY created_at ASC
+    "#,
+    oid,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  Ok(rows)
+}
+
+/// Returns the links of the workspace that are neither expired nor used up, most recently used
+/// first.
+pub async fn select_live_workspace_collab_share_links<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFCollabShareLinkRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCollabShareLinkRow,
+    r#"
+      SELECT id, token, workspace_id, oid, access_level, created_by, expires_at, created_at,
+        max_uses, use_count, last_used_at
+      FROM af_collab_share_link
+      WHERE workspace_id = $1
+        AND (expires_at IS NULL OR expires_at > NOW())
+        AND (max_uses IS NULL OR use_count < max_uses)
+      ORDER BY last_used_at DESC NULLS LAST, created_at DESC
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  Ok(rows)
+}
+
+/// Change the access level, the expiration time and the maximum number of uses of the link of the
+/// given object. Returns the link as it was before the change, or `None` if there is no such link.
+pub async fn update_collab_share_link<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  link_id: &Uuid,
+  oid: &str,
+  access_level: AFAccessLevel,
+  expires_at: Option<DateTime<Utc>>,
+  max_uses: Option<i32>,
+) -> Result<Option<AFCollabShareLinkRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFCollabShareLinkRow,
+    r#"
+      UPDATE af_collab_share_link AS link
+      SET access_level = $3, expires_at = $4, max_uses = $5
+      FROM af_collab_share_link AS old
+      WHERE link.id = old.id AND link.id = $1 AND link.oid = $2
+      RETURNING old.id, old.token, old.workspace_id, old.oid, old.access_level, old.created_by,
+        old.expires_at, old.created_at, old.max_uses, old.use_count, old.last_used_at
+    "#,
+    link_id,
+    oid,
+    access_level as i32,
+    expires_at,
+    max_uses,
+  )
+  .fetch_optional(executor)
+  .await?;
+
+  Ok(row)
+}
+
+/// Replace the token of the link of the given object. The link keeps its id, so the users that
+/// joined the object with it stay attached to it. Returns the updated link, or `None` if there is
+/// no such link.
+pub async fn update_collab_share_link_token<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  link_id: &Uuid,
+  oid: &str,
+  token: &str,
+) -> Result<Option<AFCollabShareLinkRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFCollabShareLinkRow,
+    r#"
+      UPDATE af_collab_share_link
+      SET token = $3
+      WHERE id = $1 AND oid = $2
+      RETURNING id, token, workspace_id, oid, access_level, created_by, expires_at, created_at,
+        max_uses, use_count, last_used_at
+    "#,
+    link_id,
+    oid,
+    token,
+  )
+  .fetch_optional(executor)
+  .await?;
+
+  Ok(row)
+}
+
+/// Delete the link of the given object, along with the record of the users that joined the object
+/// with it. Returns the deleted link, or `None` if there is no such link.
+pub async fn delete_collab_share_link<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  link_id: &Uuid,
+  oid: &str,
+) -> Result<Option<AFCollabShareLinkRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFCollabShareLinkRow,
+    r#"
+      DELETE FROM af_collab_share_link
+      WHERE id = $1 AND oid = $2
+      RETURNING id, token, workspace_id, oid, access_level, created_by, expires_at, created_at,
+        max_uses, use_count, last_used_at
+    "#,
+    link_id,
+    oid,
+  )
+  .fetch_optional(executor)
+  .await?;
+
+  Ok(row)
+}
+
+/// Delete all the links of the object, along with the record of the users that joined the object
+/// with them.
+pub async fn delete_collab_share_links<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_collab_share_link WHERE oid = $1
+    "#,
+    oid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Record that the user became a member of the object of the link by opening it.
+pub async fn insert_collab_share_link_member<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  link_id: &Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_collab_share_link_member (link_id, uid)
+      VALUES ($1, $2)
+      ON CONFLICT (link_id, uid) DO NOTHING
+    "#,
+    link_id,
+    uid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the uids of the users that became members of the object by opening the link.
+pub async fn select_collab_share_link_member_uids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  link_id: &Uuid,
+) -> Result<Vec<i64>, AppError> {
+  let uids = sqlx::query_scalar!(
+    r#"
+      SELECT uid FROM af_collab_share_link_member WHERE link_id = $1
+    "#,
+    link_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(uids)
+}
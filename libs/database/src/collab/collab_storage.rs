@@ -2,13 +2,16 @@ use app_error::AppError;
 use async_trait::async_trait;
 
 use database_entity::dto::{
-  AFAccessLevel, AFSnapshotMeta, AFSnapshotMetas, CollabParams, InsertSnapshotParams, QueryCollab,
-  QueryCollabParams, QueryCollabResult, SnapshotData,
+  AFAccessLevel, AFSnapshotMeta, AFSnapshotMetas, CollabContribution, CollabParams,
+  InsertSnapshotParams, QueryCollab, QueryCollabParams, QueryCollabResult, SnapshotData,
 };
 
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use collab::entity::EncodedCollab;
 use collab_entity::CollabType;
 use collab_rt_entity::ClientCollabMessage;
+use futures_util::stream::BoxStream;
 use serde::{Deserialize, Serialize};
 use sqlx::Transaction;
 use std::collections::HashMap;
@@ -54,6 +57,7 @@ pub trait CollabStorageAccessControl: Send + Sync + 'static {
   ) -> Result<bool, AppError>;
 }
 
+#[derive(Debug, Clone, Copy)]
 pub enum GetCollabOrigin {
   User { uid: i64 },
   Server,
@@ -124,6 +128,15 @@ pub trait CollabStorage: Send + Sync + 'static {
     from_editing_collab: bool,
   ) -> AppResult<EncodedCollab>;
 
+  /// Same as [CollabStorage::get_encode_collab], but returns the collab encoded in the v1 format
+  /// as a stream of chunks, without decoding it, which suits the large collabs.
+  async fn get_encode_collab_stream(
+    &self,
+    origin: GetCollabOrigin,
+    params: QueryCollabParams,
+    from_editing_collab: bool,
+  ) -> AppResult<BoxStream<'static, AppResult<Bytes>>>;
+
   /// Sends a collab message to all connected clients.
   /// # Arguments
   /// * `object_id` - The ID of the collaboration object.
@@ -173,6 +186,23 @@ pub trait CollabStorage: Send + Sync + 'static {
 
   async fn add_connected_user(&self, uid: i64, device_id: &str);
   async fn remove_connected_user(&self, uid: i64, device_id: &str);
+
+  /// Record which users edited the collab, adding to the contributions already recorded.
+  async fn record_collab_contributions(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    contributions: Vec<CollabContribution>,
+  ) -> AppResult<()>;
+
+  /// Record the views found in the trash of `deleted_by`, along with the time each was moved to
+  /// the trash. Views that are already recorded are left untouched.
+  async fn record_trashed_views(
+    &self,
+    workspace_id: &str,
+    deleted_by: i64,
+    trashed_views: Vec<(String, DateTime<Utc>)>,
+  ) -> AppResult<()>;
 }
 
 #[async_trait]
@@ -234,6 +264,18 @@ where
       .await
   }
 
+  async fn get_encode_collab_stream(
+    &self,
+    origin: GetCollabOrigin,
+    params: QueryCollabParams,
+    from_editing_collab: bool,
+  ) -> AppResult<BoxStream<'static, AppResult<Bytes>>> {
+    self
+      .as_ref()
+      .get_encode_collab_stream(origin, params, from_editing_collab)
+      .await
+  }
+
   async fn broadcast_encode_collab(
     &self,
     object_id: String,
@@ -306,6 +348,30 @@ where
   async fn remove_connected_user(&self, uid: i64, device_id: &str) {
     self.as_ref().remove_connected_user(uid, device_id).await
   }
+
+  async fn record_collab_contributions(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    contributions: Vec<CollabContribution>,
+  ) -> AppResult<()> {
+    self
+      .as_ref()
+      .record_collab_contributions(workspace_id, object_id, contributions)
+      .await
+  }
+
+  async fn record_trashed_views(
+    &self,
+    workspace_id: &str,
+    deleted_by: i64,
+    trashed_views: Vec<(String, DateTime<Utc>)>,
+  ) -> AppResult<()> {
+    self
+      .as_ref()
+      .record_trashed_views(workspace_id, deleted_by, trashed_views)
+      .await
+  }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
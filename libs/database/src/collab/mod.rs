@@ -1,9 +1,19 @@
+mod collab_compaction;
+mod collab_contributor;
 mod collab_db_ops;
+mod collab_inheritance;
+mod collab_object_token;
+mod collab_share_link;
 mod collab_storage;
 // mod recent;
 
+pub use collab_compaction::*;
+pub use collab_contributor::*;
 pub use collab_db_ops::*;
+pub use collab_inheritance::*;
 use collab_entity::CollabType;
+pub use collab_object_token::*;
+pub use collab_share_link::*;
 pub use collab_storage::*;
 
 pub(crate) fn partition_key_from_collab_type(collab_type: &CollabType) -> i32 {
@@ -18,3 +28,17 @@ pub(crate) fn partition_key_from_collab_type(collab_type: &CollabType) -> i32 {
     CollabType::Unknown => 0,
   }
 }
+
+/// The inverse of [partition_key_from_collab_type]. [CollabType::Unknown] shares its partition with
+/// [CollabType::Document], so it is never returned.
+pub(crate) fn collab_type_from_partition_key(partition_key: i32) -> CollabType {
+  match partition_key {
+    0 => CollabType::Document,
+    1 => CollabType::Database,
+    2 => CollabType::WorkspaceDatabase,
+    3 => CollabType::Folder,
+    4 => CollabType::DatabaseRow,
+    5 => CollabType::UserAwareness,
+    _ => CollabType::Unknown,
+  }
+}
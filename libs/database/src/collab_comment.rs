@@ -0,0 +1,194 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFCollabCommentRow;
+
+/// Returns the id of the new comment.
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_collab_comment<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  object_id: &str,
+  block_id: &str,
+  reply_comment_id: Option<Uuid>,
+  content: &str,
+  created_by: i64,
+) -> Result<Uuid, AppError> {
+  let comment_id = sqlx::query_scalar!(
+    r#"
+      INSERT INTO af_collab_comment
+        (workspace_id, object_id, block_id, reply_comment_id, content, created_by)
+      VALUES ($1, $2, $3, $4, $5, $6)
+      RETURNING comment_id
+    "#,
+    workspace_id,
+    object_id,
+    block_id,
+    reply_comment_id,
+    content,
+    created_by,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(comment_id)
+}
+
+pub async fn insert_collab_comment_mentions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  comment_id: &Uuid,
+  uids: &[i64],
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_collab_comment_mention (comment_id, uid)
+      SELECT $1, uid FROM UNNEST($2::bigint[]) AS uid
+      ON CONFLICT (comment_id, uid) DO NOTHING
+    "#,
+    comment_id,
+    uids,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_collab_comment<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  comment_id: &Uuid,
+) -> Result<Option<AFCollabCommentRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFCollabCommentRow,
+    r#"
+      SELECT
+        c.comment_id,
+        c.workspace_id,
+        c.object_id,
+        c.block_id,
+        c.reply_comment_id,
+        c.content,
+        c.created_by,
+        c.created_at,
+        c.updated_at,
+        c.resolved_by,
+        c.resolved_at,
+        c.is_deleted,
+        ARRAY(
+          SELECT m.uid FROM af_collab_comment_mention m WHERE m.comment_id = c.comment_id
+        ) AS "mentions!"
+      FROM af_collab_comment c
+      WHERE c.comment_id = $1
+    "#,
+    comment_id,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the comments on the collab, oldest first. When `block_id` is given, only the comments
+/// anchored to that block are returned.
+pub async fn select_collab_comments<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  object_id: &str,
+  block_id: Option<&str>,
+) -> Result<Vec<AFCollabCommentRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCollabCommentRow,
+    r#"
+      SELECT
+        c.comment_id,
+        c.workspace_id,
+        c.object_id,
+        c.block_id,
+        c.reply_comment_id,
+        c.content,
+        c.created_by,
+        c.created_at,
+        c.updated_at,
+        c.resolved_by,
+        c.resolved_at,
+        c.is_deleted,
+        ARRAY(
+          SELECT m.uid FROM af_collab_comment_mention m WHERE m.comment_id = c.comment_id
+        ) AS "mentions!"
+      FROM af_collab_comment c
+      WHERE c.workspace_id = $1
+        AND c.object_id = $2
+        AND ($3::text IS NULL OR c.block_id = $3)
+      ORDER BY c.created_at
+    "#,
+    workspace_id,
+    object_id,
+    block_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Mark the comment as resolved by `resolved_by`, or as unresolved when `resolved_by` is `None`.
+pub async fn update_collab_comment_resolution<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  comment_id: &Uuid,
+  resolved_by: Option<i64>,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_collab_comment
+      SET resolved_by = $2,
+          resolved_at = CASE WHEN $2::bigint IS NULL THEN NULL ELSE NOW() END,
+          updated_at = NOW()
+      WHERE comment_id = $1
+    "#,
+    comment_id,
+    resolved_by,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Mark the comment as deleted and drop its content and mentions. The comment itself is kept so
+/// that its replies still belong to a thread.
+pub async fn delete_collab_comment<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  comment_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      WITH deleted_mentions AS (
+        DELETE FROM af_collab_comment_mention WHERE comment_id = $1
+      )
+      UPDATE af_collab_comment
+      SET is_deleted = TRUE, content = '', updated_at = NOW()
+      WHERE comment_id = $1
+    "#,
+    comment_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the uid of the users that are members of the workspace, out of the given user uuids.
+pub async fn select_workspace_member_uids_by_uuids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uuids: &[Uuid],
+) -> Result<Vec<i64>, AppError> {
+  let uids = sqlx::query_scalar!(
+    r#"
+      SELECT u.uid
+      FROM af_user u
+      JOIN af_workspace_member m ON m.uid = u.uid
+      WHERE m.workspace_id = $1 AND u.uuid = ANY($2)
+    "#,
+    workspace_id,
+    uuids,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(uids)
+}
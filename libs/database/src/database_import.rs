@@ -0,0 +1,114 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFDatabaseImportTaskRow;
+
+pub async fn insert_database_import_task<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  view_id: &str,
+  total_rows: i32,
+) -> Result<AFDatabaseImportTaskRow, AppError> {
+  let row = sqlx::query_as::<_, AFDatabaseImportTaskRow>(
+    r#"
+      INSERT INTO af_database_import_task (workspace_id, uid, view_id, total_rows)
+      VALUES ($1, $2, $3, $4)
+      RETURNING task_id, workspace_id, uid, view_id, status, total_rows, imported_rows,
+        failed_rows, row_errors, error, created_at, completed_at
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .bind(view_id)
+  .bind(total_rows)
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the import task, if it was started by the user in the workspace.
+pub async fn select_database_import_task<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  task_id: &Uuid,
+) -> Result<Option<AFDatabaseImportTaskRow>, AppError> {
+  let row = sqlx::query_as::<_, AFDatabaseImportTaskRow>(
+    r#"
+      SELECT task_id, workspace_id, uid, view_id, status, total_rows, imported_rows,
+        failed_rows, row_errors, error, created_at, completed_at
+      FROM af_database_import_task
+      WHERE workspace_id = $1 AND uid = $2 AND task_id = $3
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .bind(task_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Counts the rows of a batch, and appends the errors of the rows of the batch that failed.
+pub async fn update_database_import_task_progress<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+  imported_rows: i32,
+  failed_rows: i32,
+  row_errors: serde_json::Value,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_database_import_task
+      SET imported_rows = imported_rows + $2,
+        failed_rows = failed_rows + $3,
+        row_errors = row_errors || $4
+      WHERE task_id = $1
+    "#,
+  )
+  .bind(task_id)
+  .bind(imported_rows)
+  .bind(failed_rows)
+  .bind(row_errors)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn update_database_import_task_completed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_database_import_task
+      SET status = 1, completed_at = NOW()
+      WHERE task_id = $1
+    "#,
+  )
+  .bind(task_id)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn update_database_import_task_failed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+  error: &str,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_database_import_task
+      SET status = 2, error = $2, completed_at = NOW()
+      WHERE task_id = $1
+    "#,
+  )
+  .bind(task_id)
+  .bind(error)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
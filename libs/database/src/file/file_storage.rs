@@ -6,9 +6,10 @@ use app_error::AppError;
 use async_trait::async_trait;
 use database_entity::file_dto::{
   CompleteUploadRequest, CreateUploadRequest, CreateUploadResponse, UploadPartData,
-  UploadPartResponse,
+  UploadPartResponse, UploadedPart,
 };
 use sqlx::PgPool;
+use std::time::Duration;
 use tracing::{info, instrument, warn};
 use uuid::Uuid;
 
@@ -52,6 +53,23 @@ pub trait BucketClient {
     req: CompleteUploadRequest,
   ) -> Result<(usize, String), AppError>;
 
+  /// Returns a url the part can be uploaded to directly, without going through the server.
+  async fn upload_part_url(
+    &self,
+    object_key: &str,
+    upload_id: &str,
+    part_number: i32,
+    expires_in: Duration,
+  ) -> Result<String, AppError>;
+
+  async fn list_upload_parts(
+    &self,
+    object_key: &str,
+    upload_id: &str,
+  ) -> Result<Vec<UploadedPart>, AppError>;
+
+  async fn abort_upload(&self, object_key: &str, upload_id: &str) -> Result<(), AppError>;
+
   async fn remove_dir(&self, dir: &str) -> Result<(), AppError>;
 }
 
@@ -150,12 +168,42 @@ where
     self.client.upload_part(&key.object_key(), req).await
   }
 
+  pub async fn upload_part_url(
+    &self,
+    key: impl BlobKey,
+    upload_id: &str,
+    part_number: i32,
+    expires_in: Duration,
+  ) -> Result<String, AppError> {
+    self
+      .client
+      .upload_part_url(&key.object_key(), upload_id, part_number, expires_in)
+      .await
+  }
+
+  pub async fn list_upload_parts(
+    &self,
+    key: impl BlobKey,
+    upload_id: &str,
+  ) -> Result<Vec<UploadedPart>, AppError> {
+    self
+      .client
+      .list_upload_parts(&key.object_key(), upload_id)
+      .await
+  }
+
+  pub async fn abort_upload(&self, key: impl BlobKey, upload_id: &str) -> Result<(), AppError> {
+    self.client.abort_upload(&key.object_key(), upload_id).await
+  }
+
+  /// Completing an upload that was already completed succeeds, so that a client whose request
+  /// was interrupted can retry it.
   pub async fn complete_upload(
     &self,
     key: impl BlobKey,
     req: CompleteUploadRequest,
   ) -> Result<(), AppError> {
-    if is_blob_metadata_exists(&self.pg_pool, key.workspace_id(), &key.object_key()).await? {
+    if is_blob_metadata_exists(&self.pg_pool, key.workspace_id(), &key.meta_key()).await? {
       warn!(
         "file already exists, workspace_id: {}, request: {}",
         key.workspace_id(),
@@ -6,17 +6,19 @@ use aws_sdk_s3::operation::delete_object::DeleteObjectOutput;
 
 use std::ops::Deref;
 
-use aws_sdk_s3::error::SdkError;
+use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
 
 use aws_sdk_s3::operation::delete_objects::DeleteObjectsOutput;
 use aws_sdk_s3::operation::get_object::GetObjectError;
+use aws_sdk_s3::presigning::PresigningConfig;
 use aws_sdk_s3::primitives::ByteStream;
 use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart, Delete, ObjectIdentifier};
 use aws_sdk_s3::Client;
 use database_entity::file_dto::{
   CompleteUploadRequest, CreateUploadRequest, CreateUploadResponse, UploadPartData,
-  UploadPartResponse,
+  UploadPartResponse, UploadedPart,
 };
+use std::time::Duration;
 
 use tracing::{error, trace};
 
@@ -307,6 +309,95 @@ impl BucketClient for AwsS3BucketClientImpl {
       .await
   }
 
+  async fn upload_part_url(
+    &self,
+    object_key: &str,
+    upload_id: &str,
+    part_number: i32,
+    expires_in: Duration,
+  ) -> Result<String, AppError> {
+    trace!(
+      "Presigning upload part of S3 bucket:{}, key {}, upload_id: {}, part_number: {}",
+      self.bucket,
+      object_key,
+      upload_id,
+      part_number
+    );
+    let config = PresigningConfig::expires_in(expires_in)
+      .map_err(|err| anyhow!("Invalid presigning config: {}", err))?;
+    let presigned = self
+      .client
+      .upload_part()
+      .bucket(&self.bucket)
+      .key(object_key)
+      .upload_id(upload_id)
+      .part_number(part_number)
+      .presigned(config)
+      .await
+      .map_err(|err| anyhow!("Failed to presign upload part: {:?}", err))?;
+    Ok(presigned.uri().to_string())
+  }
+
+  async fn list_upload_parts(
+    &self,
+    object_key: &str,
+    upload_id: &str,
+  ) -> Result<Vec<UploadedPart>, AppError> {
+    let mut parts = vec![];
+    let mut part_number_marker = None;
+    loop {
+      let output = self
+        .client
+        .list_parts()
+        .bucket(&self.bucket)
+        .key(object_key)
+        .upload_id(upload_id)
+        .set_part_number_marker(part_number_marker)
+        .send()
+        .await
+        .map_err(|err| upload_error(err, upload_id))?;
+
+      parts.extend(
+        output
+          .parts
+          .unwrap_or_default()
+          .into_iter()
+          .filter_map(|part| {
+            Some(UploadedPart {
+              part_num: part.part_number?,
+              e_tag: part.e_tag?,
+              size: part.size.unwrap_or_default(),
+            })
+          }),
+      );
+
+      if !output.is_truncated.unwrap_or(false) {
+        break;
+      }
+      part_number_marker = output.next_part_number_marker;
+    }
+    Ok(parts)
+  }
+
+  async fn abort_upload(&self, object_key: &str, upload_id: &str) -> Result<(), AppError> {
+    trace!(
+      "Aborting upload to S3 bucket:{}, key {}, upload_id: {}",
+      self.bucket,
+      object_key,
+      upload_id
+    );
+    self
+      .client
+      .abort_multipart_upload()
+      .bucket(&self.bucket)
+      .key(object_key)
+      .upload_id(upload_id)
+      .send()
+      .await
+      .map_err(|err| upload_error(err, upload_id))?;
+    Ok(())
+  }
+
   async fn remove_dir(&self, parent_dir: &str) -> Result<(), AppError> {
     let mut continuation_token = None;
     loop {
@@ -405,6 +496,19 @@ impl BucketClient for AwsS3BucketClientImpl {
   }
 }
 
+/// Maps the error of a request about an upload, which is not found when it was completed, aborted
+/// or never created for the object key.
+fn upload_error<E, R>(err: SdkError<E, R>, upload_id: &str) -> AppError
+where
+  E: ProvideErrorMetadata + std::fmt::Debug,
+  R: std::fmt::Debug,
+{
+  match err.as_service_error().and_then(|err| err.code()) {
+    Some("NoSuchUpload") => AppError::RecordNotFound(format!("upload {} not found", upload_id)),
+    _ => AppError::Internal(anyhow!("Failed to request upload {}: {:?}", upload_id, err)),
+  }
+}
+
 #[derive(Debug)]
 pub struct S3ResponseData {
   data: Vec<u8>,
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use collab_entity::CollabType;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::collab::partition_key_from_collab_type;
+
+/// Replaces the indexed plain text of the collab. Nothing is indexed if the workspace has search
+/// indexing disabled.
+pub async fn upsert_collab_search_content<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  object_id: &str,
+  collab_type: &CollabType,
+  content: &str,
+) -> Result<(), sqlx::Error> {
+  let partition_key = partition_key_from_collab_type(collab_type);
+  sqlx::query(
+    r#"
+      INSERT INTO af_collab_search (oid, partition_key, workspace_id, content)
+      SELECT $2, $3, workspace_id, $4
+      FROM af_workspace
+      WHERE workspace_id = $1
+        AND NOT COALESCE(settings['disable_search_indexing']::boolean, false)
+      ON CONFLICT (oid, partition_key)
+      DO UPDATE SET content = excluded.content, indexed_at = NOW()
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(object_id)
+  .bind(partition_key)
+  .bind(content)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn delete_workspace_search_content<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<(), sqlx::Error> {
+  sqlx::query("DELETE FROM af_collab_search WHERE workspace_id = $1")
+    .bind(workspace_id)
+    .execute(executor)
+    .await?;
+  Ok(())
+}
+
+/// Marks the start of a matching word in [FullTextSearchItem::highlight]. The control characters
+/// used as markers are removed from the content before highlighting, so they only ever appear as
+/// markers.
+pub const HIGHLIGHT_START_SEL: char = '\u{2}';
+/// Marks the end of a matching word in [FullTextSearchItem::highlight].
+pub const HIGHLIGHT_STOP_SEL: char = '\u{3}';
+
+/// Returns the collabs of the workspace whose content matches the query, best match first.
+/// The query uses the web search syntax: quoted phrases, `or` and `-` to exclude a word.
+pub async fn search_collab_content<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  params: &FullTextSearchParams<'_>,
+) -> Result<Vec<FullTextSearchItem>, sqlx::Error> {
+  let max_words = params.preview_words.max(2);
+  let headline_options = format!(
+    "StartSel=\"{}\", StopSel=\"{}\", MaxFragments=1, MinWords={}, MaxWords={}",
+    HIGHLIGHT_START_SEL,
+    HIGHLIGHT_STOP_SEL,
+    max_words / 2,
+    max_words
+  );
+  let markers = format!("{}{}", HIGHLIGHT_START_SEL, HIGHLIGHT_STOP_SEL);
+  let rows = sqlx::query_as::<_, FullTextSearchItem>(
+    r#"
+      WITH query AS (SELECT websearch_to_tsquery('simple', $2) AS q)
+      SELECT
+        s.oid AS object_id,
+        s.partition_key AS collab_type,
+        ts_rank(s.content_tsv, query.q) AS score,
+        ts_headline('simple', translate(s.content, $6, ''), query.q, $3) AS highlight,
+        s.indexed_at
+      FROM af_collab_search s
+      CROSS JOIN query
+      JOIN af_collab c ON c.oid = s.oid AND c.partition_key = s.partition_key
+      WHERE s.workspace_id = $1 AND s.content_tsv @@ query.q AND c.deleted_at IS NULL
+      ORDER BY score DESC, s.oid ASC
+      LIMIT $4 OFFSET $5
+    "#,
+  )
+  .bind(params.workspace_id)
+  .bind(params.query)
+  .bind(headline_options)
+  .bind(params.limit)
+  .bind(params.offset)
+  .bind(markers)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+#[derive(Debug, Clone)]
+pub struct FullTextSearchParams<'a> {
+  pub workspace_id: &'a Uuid,
+  pub query: &'a str,
+  /// Maximum number of words of the highlighted fragment of each result.
+  pub preview_words: i32,
+  pub limit: i64,
+  pub offset: i64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FullTextSearchItem {
+  pub object_id: String,
+  /// Partition key, which maps directly onto [collab_entity::CollabType].
+  pub collab_type: i32,
+  /// Rank of the result for the query. Higher is better.
+  pub score: f32,
+  /// Fragment of the content around the matching words, which are wrapped in
+  /// [HIGHLIGHT_START_SEL] and [HIGHLIGHT_STOP_SEL]. The text is not escaped.
+  pub highlight: String,
+  pub indexed_at: DateTime<Utc>,
+}
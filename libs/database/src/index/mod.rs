@@ -1,5 +1,7 @@
 mod collab_embeddings_ops;
+mod full_text_ops;
 mod search_ops;
 
 pub use collab_embeddings_ops::*;
+pub use full_text_ops::*;
 pub use search_ops::*;
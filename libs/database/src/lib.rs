@@ -1,12 +1,37 @@
+pub mod ai_usage;
+pub mod audit_log;
+pub mod calendar_feed;
 pub mod chat;
 pub mod collab;
+pub mod collab_comment;
+pub mod database_import;
 pub mod file;
 pub mod history;
 pub mod index;
 pub mod listener;
+pub mod notification;
 pub mod pg_row;
 pub mod publish;
+pub mod publish_form;
+pub mod rate_limit;
 pub mod resource_usage;
+pub mod scim;
+pub mod service_account;
+pub mod snapshot_retention;
+pub mod storage_quota;
 pub mod template;
 pub mod user;
+pub mod user_data_export;
+pub mod user_deletion;
+pub mod view_trash;
+pub mod webhook;
 pub mod workspace;
+pub mod workspace_duplicate;
+pub mod workspace_export;
+pub mod workspace_group;
+pub mod workspace_import;
+pub mod workspace_invite_link;
+pub mod workspace_ownership_transfer;
+pub mod workspace_role;
+pub mod workspace_sso;
+pub mod workspace_usage;
@@ -0,0 +1,219 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::{AFNotificationRecipientRow, AFNotificationRow};
+
+/// Record the same notification for every user. Without `email`, the notifications are only
+/// listed in the app and never emailed.
+pub async fn insert_notifications<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uids: &[i64],
+  workspace_id: Option<&Uuid>,
+  kind: &str,
+  data: &serde_json::Value,
+  email: bool,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_notification (uid, workspace_id, kind, data, email_handled_at)
+      SELECT uid, $2, $3, $4, CASE WHEN $5 THEN NULL ELSE NOW() END
+      FROM UNNEST($1::bigint[]) AS uid
+    "#,
+    uids,
+    workspace_id,
+    kind,
+    data,
+    email,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the latest notifications of the user, newest first.
+pub async fn select_notifications<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  limit: i64,
+) -> Result<Vec<AFNotificationRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFNotificationRow,
+    r#"
+      SELECT id, uid, workspace_id, kind, data, created_at
+      FROM af_notification
+      WHERE uid = $1
+      ORDER BY created_at DESC, id DESC
+      LIMIT $2
+    "#,
+    uid,
+    limit,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the notifications of the user that have not been emailed yet, oldest first.
+pub async fn select_pending_email_notifications<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Vec<AFNotificationRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFNotificationRow,
+    r#"
+      SELECT id, uid, workspace_id, kind, data, created_at
+      FROM af_notification
+      WHERE uid = $1 AND email_handled_at IS NULL
+      ORDER BY created_at, id
+    "#,
+    uid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the users with notifications to email. Users that receive a daily digest are only
+/// returned when they have not been emailed since `digest_cutoff`.
+pub async fn select_pending_email_recipients<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  digest_cutoff: DateTime<Utc>,
+  limit: i64,
+) -> Result<Vec<AFNotificationRecipientRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFNotificationRecipientRow,
+    r#"
+      SELECT u.uid, u.email, u.name, COALESCE(s.email_delivery, 0::SMALLINT) AS "email_delivery!"
+      FROM af_user u
+      LEFT JOIN af_notification_setting s ON s.uid = u.uid
+      WHERE EXISTS (
+          SELECT 1 FROM af_notification n
+          WHERE n.uid = u.uid AND n.email_handled_at IS NULL
+        )
+        AND (
+          COALESCE(s.email_delivery, 0) <> 1
+          OR s.last_emailed_at IS NULL
+          OR s.last_emailed_at < $1
+        )
+      LIMIT $2
+    "#,
+    digest_cutoff,
+    limit,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn update_notifications_email_handled<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  ids: &[i64],
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_notification
+      SET email_handled_at = NOW()
+      WHERE id = ANY($1)
+    "#,
+    ids,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn update_notification_last_emailed_at<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_notification_setting (uid, last_emailed_at)
+      VALUES ($1, NOW())
+      ON CONFLICT (uid) DO UPDATE SET last_emailed_at = NOW()
+    "#,
+    uid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns how the user wants to be emailed, `0` when the user never changed it.
+pub async fn select_notification_email_delivery<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<i16, AppError> {
+  let email_delivery = sqlx::query_scalar!(
+    r#"
+      SELECT email_delivery FROM af_notification_setting WHERE uid = $1
+    "#,
+    uid,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(email_delivery.unwrap_or_default())
+}
+
+pub async fn upsert_notification_email_delivery<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  email_delivery: i16,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_notification_setting (uid, email_delivery)
+      VALUES ($1, $2)
+      ON CONFLICT (uid) DO UPDATE SET email_delivery = $2, updated_at = NOW()
+    "#,
+    uid,
+    email_delivery,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the unsubscribe token of the user, which is created on first use.
+pub async fn select_or_insert_unsubscribe_token<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Uuid, AppError> {
+  let token = sqlx::query_scalar!(
+    r#"
+      INSERT INTO af_notification_setting (uid)
+      VALUES ($1)
+      ON CONFLICT (uid) DO UPDATE SET uid = EXCLUDED.uid
+      RETURNING unsubscribe_token
+    "#,
+    uid,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(token)
+}
+
+/// Returns false if no user has the token.
+pub async fn update_email_delivery_by_unsubscribe_token<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  token: &Uuid,
+  email_delivery: i16,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE af_notification_setting
+      SET email_delivery = $2, updated_at = NOW()
+      WHERE unsubscribe_token = $1
+    "#,
+    token,
+    email_delivery,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
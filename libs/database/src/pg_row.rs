@@ -1,6 +1,6 @@
 use anyhow::anyhow;
 use app_error::AppError;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 
 use database_entity::dto::{
   AFAccessLevel, AFRole, AFUserProfile, AFWebUser, AFWorkspace, AFWorkspaceInvitationStatus,
@@ -502,3 +502,516 @@ impl From<AFTemplateGroupRow> for TemplateGroup {
     }
   }
 }
+
+/// Represent the row of the af_collab_object_token table
+#[derive(Debug, FromRow)]
+pub struct AFCollabObjectTokenRow {
+  pub id: Uuid,
+  pub workspace_id: Uuid,
+  pub oid: String,
+  pub access_level: i32,
+  pub created_by: i64,
+  pub expires_at: Option<DateTime<Utc>>,
+  pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Represent the row of the af_collab_contributor table, with the name and email of the user
+#[derive(Debug, FromRow)]
+pub struct AFCollabContributorRow {
+  pub uid: i64,
+  pub name: String,
+  pub email: String,
+  pub edit_count: i64,
+  pub first_edited_at: DateTime<Utc>,
+  pub last_edited_at: DateTime<Utc>,
+}
+
+/// Represent the row of the af_collab_share_link table
+#[derive(Debug, FromRow)]
+pub struct AFCollabShareLinkRow {
+  pub id: Uuid,
+  pub token: String,
+  pub workspace_id: Uuid,
+  pub oid: String,
+  pub access_level: i32,
+  pub created_by: i64,
+  pub expires_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub max_uses: Option<i32>,
+  pub use_count: i32,
+  pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Represent the row of the af_workspace_group table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspaceGroupRow {
+  pub group_id: Uuid,
+  pub workspace_id: Uuid,
+  pub name: String,
+  pub created_at: DateTime<Utc>,
+}
+
+/// Represent the row of the af_workspace_role table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspaceRoleRow {
+  pub role_id: Uuid,
+  pub workspace_id: Uuid,
+  pub name: String,
+  pub capabilities: Vec<String>,
+  pub created_at: DateTime<Utc>,
+}
+
+/// The capabilities granted to a workspace member by its custom role.
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspaceMemberCapabilityRow {
+  pub uid: i64,
+  pub workspace_id: Uuid,
+  pub capabilities: Vec<String>,
+}
+
+/// An invitation of the af_workspace_invitation table that is still pending
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspacePendingInvitationRow {
+  pub invite_id: Uuid,
+  pub invitee_email: String,
+  pub role_id: i32,
+  pub inviter_uid: i64,
+  pub inviter_email: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub last_sent_at: DateTime<Utc>,
+  pub sent_count: i32,
+}
+
+/// Represent the row of the af_workspace_ownership_transfer table, with the emails of the users
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspaceOwnershipTransferRow {
+  pub workspace_id: Uuid,
+  pub from_uid: i64,
+  pub from_email: String,
+  pub to_uid: i64,
+  pub to_email: String,
+  pub created_at: DateTime<Utc>,
+  pub expires_at: DateTime<Utc>,
+}
+
+/// Represent the row of the af_workspace_invite_link table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspaceInviteLinkRow {
+  pub id: Uuid,
+  pub token: String,
+  pub workspace_id: Uuid,
+  pub role_id: i32,
+  pub allowed_domains: Vec<String>,
+  pub max_joins_per_hour: i32,
+  pub expires_at: Option<DateTime<Utc>>,
+  pub created_by: i64,
+  pub created_at: DateTime<Utc>,
+  pub rotated_at: Option<DateTime<Utc>>,
+}
+
+/// Represent the row of the af_published_database_form table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFPublishedDatabaseFormRow {
+  pub view_id: Uuid,
+  pub workspace_id: Uuid,
+  pub field_ids: Vec<String>,
+  pub max_submissions_per_hour: i32,
+  pub require_captcha: bool,
+  pub created_by: i64,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// Represent the row of the af_database_calendar_feed table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFDatabaseCalendarFeedRow {
+  pub token: String,
+  pub workspace_id: Uuid,
+  pub view_id: String,
+  pub created_by: i64,
+  pub created_at: DateTime<Utc>,
+}
+
+pub struct AFCollabGroupAccessLevelRow {
+  pub group_id: Uuid,
+  pub oid: String,
+  pub access_level: AFAccessLevel,
+}
+
+/// A published view, as listed in the sitemap and feed of the published site
+#[derive(Debug, Clone, FromRow)]
+pub struct AFPublishedSiteEntryRow {
+  pub view_id: Uuid,
+  pub publish_name: String,
+  pub title: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// The number of reads of a published view on a day
+#[derive(Debug, Clone, FromRow)]
+pub struct AFPublishedViewDailyStatRow {
+  pub view_id: Uuid,
+  pub publish_name: Option<String>,
+  pub day: NaiveDate,
+  pub view_count: i64,
+}
+
+/// The number of reads of a published view from the pages of a referring site
+#[derive(Debug, Clone, FromRow)]
+pub struct AFPublishedViewReferrerStatRow {
+  pub view_id: Uuid,
+  pub referrer_host: String,
+  pub view_count: i64,
+}
+
+/// Represent the row of the af_publish_custom_domain table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFPublishCustomDomainRow {
+  pub workspace_id: Uuid,
+  pub domain: String,
+  pub verification_token: String,
+  pub verified_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
+
+/// Represent the row of the af_workspace_export_task table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspaceExportTaskRow {
+  pub task_id: Uuid,
+  pub workspace_id: Uuid,
+  pub uid: i64,
+  pub status: i16,
+  pub object_key: Option<String>,
+  pub file_size: Option<i64>,
+  pub error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Represent the row of the af_user_data_export_task table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFUserDataExportTaskRow {
+  pub task_id: Uuid,
+  pub uid: i64,
+  pub status: i16,
+  pub object_key: Option<String>,
+  pub file_size: Option<i64>,
+  pub error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// A workspace the user is a member of, as part of the export of the data of the user.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AFUserWorkspaceMembershipRow {
+  pub workspace_id: Uuid,
+  pub workspace_name: Option<String>,
+  pub role_id: i32,
+  pub is_owner: bool,
+  pub joined_at: Option<DateTime<Utc>>,
+}
+
+/// A comment the user wrote, on a collab or on a published view, as part of the export of the
+/// data of the user.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AFUserCommentRow {
+  pub comment_id: Uuid,
+  /// `collab` or `published_view`
+  pub source: String,
+  pub workspace_id: Option<Uuid>,
+  pub object_id: String,
+  pub reply_comment_id: Option<Uuid>,
+  pub content: String,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// A collab the user owns, as part of the export of the data of the user.
+#[derive(Debug, Clone, FromRow)]
+pub struct AFUserOwnedCollabRow {
+  pub oid: String,
+  pub workspace_id: Uuid,
+  pub partition_key: i32,
+}
+
+/// Represent the row of the af_user_deletion table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFUserDeletionRow {
+  pub uid: i64,
+  pub uuid: Uuid,
+  pub workspace_transfers: serde_json::Value,
+  pub requested_at: DateTime<Utc>,
+  pub purge_after: DateTime<Utc>,
+  pub attempts: i32,
+  pub last_error: Option<String>,
+}
+
+/// Represent the row of the af_workspace_duplicate_task table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspaceDuplicateTaskRow {
+  pub task_id: Uuid,
+  pub workspace_id: Uuid,
+  pub uid: i64,
+  pub new_workspace_id: Option<Uuid>,
+  pub include_members: bool,
+  pub status: i16,
+  pub total_objects: i32,
+  pub copied_objects: i32,
+  pub error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Represent the row of the af_workspace_import_task table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspaceImportTaskRow {
+  pub task_id: Uuid,
+  pub workspace_id: Uuid,
+  pub uid: i64,
+  pub view_id: String,
+  pub status: i16,
+  pub total_pages: i32,
+  pub imported_pages: i32,
+  pub error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Represent the row of the af_database_import_task table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFDatabaseImportTaskRow {
+  pub task_id: Uuid,
+  pub workspace_id: Uuid,
+  pub uid: i64,
+  pub view_id: String,
+  pub status: i16,
+  pub total_rows: i32,
+  pub imported_rows: i32,
+  pub failed_rows: i32,
+  pub row_errors: serde_json::Value,
+  pub error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Represent the row of the af_collab_comment table, along with the users mentioned in the comment
+#[derive(Debug, Clone, FromRow)]
+pub struct AFCollabCommentRow {
+  pub comment_id: Uuid,
+  pub workspace_id: Uuid,
+  pub object_id: String,
+  pub block_id: String,
+  pub reply_comment_id: Option<Uuid>,
+  pub content: String,
+  pub created_by: Option<i64>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+  pub resolved_by: Option<i64>,
+  pub resolved_at: Option<DateTime<Utc>>,
+  pub is_deleted: bool,
+  pub mentions: Vec<i64>,
+}
+
+/// Represent the row of the af_notification table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFNotificationRow {
+  pub id: i64,
+  pub uid: i64,
+  pub workspace_id: Option<Uuid>,
+  pub kind: String,
+  pub data: serde_json::Value,
+  pub created_at: DateTime<Utc>,
+}
+
+/// A user with notifications that are due to be emailed, along with how the user wants to be
+/// emailed.
+#[derive(Debug, Clone, FromRow)]
+pub struct AFNotificationRecipientRow {
+  pub uid: i64,
+  pub email: Option<String>,
+  pub name: Option<String>,
+  pub email_delivery: i16,
+}
+
+/// Represent the row of the af_view_trash table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFViewTrashRow {
+  pub workspace_id: Uuid,
+  pub view_id: String,
+  pub deleted_by: i64,
+  pub deleted_at: DateTime<Utc>,
+}
+
+/// Represent the row of the af_workspace_webhook table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspaceWebhookRow {
+  pub webhook_id: Uuid,
+  pub workspace_id: Uuid,
+  pub url: String,
+  pub secret: String,
+  pub events: Vec<String>,
+  pub created_at: DateTime<Utc>,
+}
+
+/// Represent the row of the af_webhook_delivery table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWebhookDeliveryRow {
+  pub delivery_id: i64,
+  pub webhook_id: Uuid,
+  pub event: String,
+  pub payload: serde_json::Value,
+  pub attempts: i32,
+  pub last_error: Option<String>,
+  pub dead_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
+
+/// A delivery that is due, together with the endpoint it is delivered to.
+#[derive(Debug, Clone, FromRow)]
+pub struct AFDueWebhookDeliveryRow {
+  pub delivery_id: i64,
+  pub workspace_id: Uuid,
+  pub url: String,
+  pub secret: String,
+  pub event: String,
+  pub payload: serde_json::Value,
+  pub attempts: i32,
+  pub created_at: DateTime<Utc>,
+}
+
+/// Represent the row of the af_service_account table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFServiceAccountRow {
+  pub service_account_id: Uuid,
+  pub workspace_id: Uuid,
+  pub uid: i64,
+  pub name: String,
+  pub scope: String,
+  pub created_by: Option<i64>,
+  pub created_at: DateTime<Utc>,
+}
+
+/// Represent the row of the af_api_key table, without the hash of the key.
+#[derive(Debug, Clone, FromRow)]
+pub struct AFApiKeyRow {
+  pub api_key_id: Uuid,
+  pub service_account_id: Uuid,
+  pub key_prefix: String,
+  pub created_at: DateTime<Utc>,
+  pub last_used_at: Option<DateTime<Utc>>,
+  pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// An active API key, together with the service account it authenticates.
+#[derive(Debug, Clone, FromRow)]
+pub struct AFApiKeyPrincipalRow {
+  pub api_key_id: Uuid,
+  pub workspace_id: Uuid,
+  pub user_uuid: Uuid,
+  pub scope: String,
+  pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// A user provisioned into a workspace through SCIM, together with its user details.
+#[derive(Debug, Clone, FromRow)]
+pub struct AFScimUserRow {
+  pub uid: i64,
+  pub uuid: Uuid,
+  pub email: String,
+  pub name: String,
+  pub external_id: Option<String>,
+  pub active: bool,
+  /// The SCIM groups of the workspace the user is a member of.
+  pub group_ids: Vec<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// Represent the row of the af_scim_group table, together with the uuid of its members.
+#[derive(Debug, Clone, FromRow)]
+pub struct AFScimGroupRow {
+  pub group_id: Uuid,
+  pub workspace_id: Uuid,
+  pub external_id: Option<String>,
+  pub display_name: String,
+  pub role_id: Option<i32>,
+  pub member_uuids: Vec<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// Represent the row of the af_workspace_sso table
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspaceSsoRow {
+  pub workspace_id: Uuid,
+  pub provider_id: Uuid,
+  pub entity_id: String,
+  pub metadata_url: Option<String>,
+  pub domains: Vec<String>,
+  pub default_role_id: i32,
+  pub enforced: bool,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// Represent the row of the af_audit_log table, together with the email of the actor.
+#[derive(Debug, Clone, FromRow)]
+pub struct AFAuditLogRow {
+  pub id: i64,
+  pub workspace_id: Option<Uuid>,
+  pub actor_uid: Option<i64>,
+  pub actor_email: Option<String>,
+  pub action: String,
+  pub target: Option<String>,
+  pub metadata: serde_json::Value,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspaceSnapshotRetentionRow {
+  pub workspace_id: Uuid,
+  pub keep_last: i32,
+  pub keep_days: i32,
+  pub hourly_days: i32,
+  pub daily_days: i32,
+  pub weekly_weeks: i32,
+  pub updated_by: Option<i64>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspaceStorageRow {
+  pub collab_bytes: i64,
+  pub blob_bytes: i64,
+  pub plan: Option<i16>,
+  pub storage_quota_bytes: Option<i64>,
+}
+
+/// The AI usage of a workspace over a period, together with its plan, which sets its AI quota.
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspaceAIQuotaRow {
+  pub ai_requests: i64,
+  pub ai_tokens_consumed: i64,
+  pub plan: Option<i16>,
+  pub ai_monthly_requests: Option<i64>,
+  pub ai_monthly_tokens: Option<i64>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspaceAIDailyUsageRow {
+  pub day: NaiveDate,
+  pub ai_requests: i64,
+  pub ai_tokens_consumed: i64,
+  pub search_requests: i64,
+  pub search_tokens_consumed: i64,
+  pub index_tokens_consumed: i64,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct AFWorkspaceUsageRow {
+  pub document_count: i64,
+  pub total_document_size: i64,
+  pub active_editor_count: i64,
+  pub storage_bytes: i64,
+  pub publish_count: i64,
+}
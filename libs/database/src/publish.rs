@@ -1,8 +1,17 @@
 use app_error::AppError;
-use database_entity::dto::{PublishCollabItem, PublishCollabKey, PublishInfo};
-use sqlx::{Executor, PgPool, Postgres};
+use chrono::{DateTime, NaiveDate, Utc};
+use database_entity::dto::{
+  PublishCollabItem, PublishCollabKey, PublishInfo, PublishNamespaceInfo,
+};
+use sqlx::{Executor, PgPool, Postgres, Transaction};
+use std::ops::DerefMut;
 use uuid::Uuid;
 
+use crate::pg_row::{
+  AFPublishCustomDomainRow, AFPublishedSiteEntryRow, AFPublishedViewDailyStatRow,
+  AFPublishedViewReferrerStatRow,
+};
+
 pub async fn select_user_is_collab_publisher_for_all_views(
   pg_pool: &PgPool,
   user_uuid: &Uuid,
@@ -120,6 +129,7 @@ pub async fn insert_or_replace_publish_collabs<'a, E: Executor<'a, Database = Po
     blobs.push(item.data);
   });
 
+  // A view that is published again after it expired is served again
   let res = sqlx::query!(
     r#"
       INSERT INTO af_published_collab (workspace_id, view_id, publish_name, published_by, metadata, blob)
@@ -135,7 +145,11 @@ pub async fn insert_or_replace_publish_collabs<'a, E: Executor<'a, Database = Po
       SET metadata = EXCLUDED.metadata,
           blob = EXCLUDED.blob,
           published_by = EXCLUDED.published_by,
-          publish_name = EXCLUDED.publish_name
+          publish_name = EXCLUDED.publish_name,
+          expires_at = CASE
+            WHEN af_published_collab.expires_at <= NOW() THEN NULL
+            ELSE af_published_collab.expires_at
+          END
     "#,
     workspace_id,
     &view_ids,
@@ -170,6 +184,7 @@ pub async fn select_publish_collab_meta<'a, E: Executor<'a, Database = Postgres>
     FROM af_published_collab
     WHERE workspace_id = (SELECT workspace_id FROM af_workspace WHERE publish_namespace = $1)
       AND publish_name = $2
+      AND (expires_at IS NULL OR expires_at > NOW())
     "#,
     publish_namespace,
     publish_name,
@@ -180,6 +195,26 @@ pub async fn select_publish_collab_meta<'a, E: Executor<'a, Database = Postgres>
   Ok(metadata)
 }
 
+/// Delete the published record of the view, whether or not it has expired. Returns false if the
+/// view was not published.
+pub async fn delete_published_collab_if_exists<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<bool, AppError> {
+  let res = sqlx::query(
+    r#"
+      DELETE FROM af_published_collab
+      WHERE workspace_id = $1 AND view_id = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
 #[inline]
 pub async fn delete_published_collabs<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
@@ -220,6 +255,7 @@ pub async fn select_published_metadata_for_view_id(
       SELECT workspace_id, metadata
       FROM af_published_collab
       WHERE view_id = $1
+        AND (expires_at IS NULL OR expires_at > NOW())
     "#,
     view_id,
   )
@@ -238,6 +274,7 @@ pub async fn select_published_data_for_view_id(
       SELECT metadata, blob
       FROM af_published_collab
       WHERE view_id = $1
+        AND (expires_at IS NULL OR expires_at > NOW())
     "#,
     view_id,
   )
@@ -259,6 +296,7 @@ pub async fn select_published_collab_workspace_view_id<'a, E: Executor<'a, Datab
       FROM af_published_collab
       WHERE workspace_id = (SELECT workspace_id FROM af_workspace WHERE publish_namespace = $1)
       AND publish_name = $2
+      AND (expires_at IS NULL OR expires_at > NOW())
     "#,
     publish_namespace,
     publish_name,
@@ -280,6 +318,7 @@ pub async fn select_published_collab_blob<'a, E: Executor<'a, Database = Postgre
       FROM af_published_collab
       WHERE workspace_id = (SELECT workspace_id FROM af_workspace WHERE publish_namespace = $1)
       AND publish_name = $2
+      AND (expires_at IS NULL OR expires_at > NOW())
     "#,
     publish_namespace,
     publish_name,
@@ -290,6 +329,30 @@ pub async fn select_published_collab_blob<'a, E: Executor<'a, Database = Postgre
   Ok(res)
 }
 
+/// Returns the view id of the view published under the given name, with its published version
+/// and the time it was last updated, which together identify the content that is published.
+pub async fn select_published_collab_render_version<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<Option<(Uuid, Option<i64>, Option<DateTime<Utc>>)>, AppError> {
+  let res = sqlx::query_as(
+    r#"
+      SELECT view_id, published_version, updated_at
+      FROM af_published_collab
+      WHERE workspace_id = (SELECT workspace_id FROM af_workspace WHERE publish_namespace = $1)
+      AND publish_name = $2
+      AND (expires_at IS NULL OR expires_at > NOW())
+    "#,
+  )
+  .bind(publish_namespace)
+  .bind(publish_name)
+  .fetch_optional(executor)
+  .await?;
+
+  Ok(res)
+}
+
 pub async fn select_published_collab_info<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
   view_id: &Uuid,
@@ -304,7 +367,8 @@ pub async fn select_published_collab_info<'a, E: Executor<'a, Database = Postgre
       FROM af_published_collab apc
       LEFT JOIN af_workspace aw
         ON apc.workspace_id = aw.workspace_id
-      WHERE apc.view_id = $1;
+      WHERE apc.view_id = $1
+        AND (apc.expires_at IS NULL OR apc.expires_at > NOW());
     "#,
     view_id,
   )
@@ -332,6 +396,164 @@ pub async fn select_workspace_id_for_publish_namespace<'a, E: Executor<'a, Datab
   Ok(res.workspace_id)
 }
 
+/// Returns the publish namespace served by the custom domain, or `None` if the domain is not
+/// verified by any workspace.
+pub async fn select_publish_namespace_for_custom_domain<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  domain: &str,
+) -> Result<Option<String>, AppError> {
+  let res = sqlx::query_scalar!(
+    r#"
+      SELECT aw.publish_namespace
+      FROM af_publish_custom_domain d
+      JOIN af_workspace aw ON aw.workspace_id = d.workspace_id
+      WHERE d.domain = $1 AND d.verified_at IS NOT NULL
+    "#,
+    domain,
+  )
+  .fetch_optional(executor)
+  .await?;
+
+  Ok(res)
+}
+
+/// Registers the domain for the workspace, or returns the existing registration.
+pub async fn insert_publish_custom_domain<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  domain: &str,
+  verification_token: &str,
+) -> Result<AFPublishCustomDomainRow, AppError> {
+  let row = sqlx::query_as!(
+    AFPublishCustomDomainRow,
+    r#"
+      INSERT INTO af_publish_custom_domain (workspace_id, domain, verification_token)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (workspace_id, domain) DO UPDATE SET domain = EXCLUDED.domain
+      RETURNING workspace_id, domain, verification_token, verified_at, created_at
+    "#,
+    workspace_id,
+    domain,
+    verification_token,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the custom domains registered by the workspace, ordered by registration time.
+pub async fn select_publish_custom_domains<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFPublishCustomDomainRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFPublishCustomDomainRow,
+    r#"
+      SELECT workspace_id, domain, verification_token, verified_at, created_at
+      FROM af_publish_custom_domain
+      WHERE workspace_id = $1
+      ORDER BY created_at ASC
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn select_publish_custom_domain<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<Option<AFPublishCustomDomainRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFPublishCustomDomainRow,
+    r#"
+      SELECT workspace_id, domain, verification_token, verified_at, created_at
+      FROM af_publish_custom_domain
+      WHERE workspace_id = $1 AND domain = $2
+    "#,
+    workspace_id,
+    domain,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns true if the domain is verified by a workspace other than `workspace_id`.
+pub async fn select_publish_custom_domain_verified_by_other<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<bool, AppError> {
+  let exists = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS(
+        SELECT 1 FROM af_publish_custom_domain
+        WHERE domain = $2 AND workspace_id <> $1 AND verified_at IS NOT NULL
+      )
+    "#,
+    workspace_id,
+    domain,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(exists.unwrap_or(false))
+}
+
+/// Marks the domain as verified for the workspace. Returns `None` if the domain is already
+/// verified by another workspace.
+pub async fn update_publish_custom_domain_verified<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<Option<AFPublishCustomDomainRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFPublishCustomDomainRow,
+    r#"
+      UPDATE af_publish_custom_domain
+      SET verified_at = COALESCE(verified_at, NOW())
+      WHERE workspace_id = $1 AND domain = $2
+        AND NOT EXISTS (
+          SELECT 1 FROM af_publish_custom_domain
+          WHERE domain = $2 AND workspace_id <> $1 AND verified_at IS NOT NULL
+        )
+      RETURNING workspace_id, domain, verification_token, verified_at, created_at
+    "#,
+    workspace_id,
+    domain,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns true if the domain was registered by the workspace.
+pub async fn delete_publish_custom_domain<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      DELETE FROM af_publish_custom_domain
+      WHERE workspace_id = $1 AND domain = $2
+    "#,
+    workspace_id,
+    domain,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
 pub async fn select_published_view_ids_for_workspace<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
   workspace_id: Uuid,
@@ -341,9 +563,500 @@ pub async fn select_published_view_ids_for_workspace<'a, E: Executor<'a, Databas
       SELECT view_id
       FROM af_published_collab
       WHERE workspace_id = $1
+        AND (expires_at IS NULL OR expires_at > NOW())
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  Ok(res)
+}
+
+/// Returns the views of the workspace that are published without a password, most recently
+/// updated first.
+pub async fn select_published_site_entries<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  limit: i64,
+) -> Result<Vec<AFPublishedSiteEntryRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFPublishedSiteEntryRow>(
+    r#"
+      SELECT
+        view_id,
+        publish_name,
+        metadata #>> '{view,name}' AS title,
+        COALESCE(created_at, NOW()) AS created_at,
+        COALESCE(updated_at, created_at, NOW()) AS updated_at
+      FROM af_published_collab
+      WHERE workspace_id = $1
+        AND password_hash IS NULL
+        AND (expires_at IS NULL OR expires_at > NOW())
+      ORDER BY updated_at DESC, view_id ASC
+      LIMIT $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn update_published_collab_show_in_nav<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  show_in_nav: bool,
+) -> Result<(), AppError> {
+  let res = sqlx::query(
+    r#"
+      UPDATE af_published_collab
+      SET show_in_nav = $3
+      WHERE workspace_id = $1 AND view_id = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(show_in_nav)
+  .execute(executor)
+  .await?;
+
+  if res.rows_affected() == 0 {
+    return Err(AppError::RecordNotFound(format!(
+      "published view {} not found in workspace {}",
+      view_id, workspace_id
+    )));
+  }
+
+  Ok(())
+}
+
+pub async fn select_nav_view_ids_for_workspace<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: Uuid,
+) -> Result<Vec<Uuid>, AppError> {
+  let res = sqlx::query_scalar(
+    r#"
+      SELECT view_id
+      FROM af_published_collab
+      WHERE workspace_id = $1 AND show_in_nav
+        AND (expires_at IS NULL OR expires_at > NOW())
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(executor)
+  .await?;
+
+  Ok(res)
+}
+
+pub async fn select_published_collab_version<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Option<i64>, AppError> {
+  let res = sqlx::query_scalar(
+    r#"
+      SELECT published_version
+      FROM af_published_collab
+      WHERE workspace_id = $1 AND view_id = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .fetch_optional(executor)
+  .await?;
+
+  Ok(res.flatten())
+}
+
+pub async fn update_published_collab_version<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  published_version: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_published_collab
+      SET published_version = $3
+      WHERE workspace_id = $1 AND view_id = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(published_version)
+  .execute(executor)
+  .await?;
+
+  Ok(())
+}
+
+/// Returns the publish namespaces of all workspaces, oldest workspace first.
+/// Ties are broken by workspace id so that pages are stable.
+pub async fn select_all_publish_namespaces<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  limit: i64,
+  offset: i64,
+) -> Result<Vec<PublishNamespaceInfo>, AppError> {
+  let rows: Vec<(String, Uuid, Option<DateTime<Utc>>)> = sqlx::query_as(
+    r#"
+      SELECT publish_namespace, workspace_id, created_at
+      FROM af_workspace
+      ORDER BY created_at ASC, workspace_id ASC
+      LIMIT $1 OFFSET $2
+    "#,
+  )
+  .bind(limit)
+  .bind(offset)
+  .fetch_all(executor)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(
+        |(namespace, workspace_id, created_at)| PublishNamespaceInfo {
+          namespace,
+          workspace_id,
+          created_at,
+        },
+      )
+      .collect(),
+  )
+}
+
+pub async fn update_published_collab_expires_at<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  expires_at: Option<DateTime<Utc>>,
+) -> Result<(), AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE af_published_collab
+      SET expires_at = $3
+      WHERE workspace_id = $1 AND view_id = $2
     "#,
     workspace_id,
+    view_id,
+    expires_at,
+  )
+  .execute(executor)
+  .await?;
+
+  if res.rows_affected() == 0 {
+    return Err(AppError::RecordNotFound(format!(
+      "published view {} not found in workspace {}",
+      view_id, workspace_id
+    )));
+  }
+
+  Ok(())
+}
+
+pub async fn update_published_collab_password_hash<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  password_hash: Option<&str>,
+) -> Result<(), AppError> {
+  let res = sqlx::query(
+    r#"
+      UPDATE af_published_collab
+      SET password_hash = $3
+      WHERE workspace_id = $1 AND view_id = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(password_hash)
+  .execute(executor)
+  .await?;
+
+  if res.rows_affected() == 0 {
+    return Err(AppError::RecordNotFound(format!(
+      "published view {} not found in workspace {}",
+      view_id, workspace_id
+    )));
+  }
+
+  Ok(())
+}
+
+/// Returns the view id and password hash of the view published under the given name, or `None`
+/// if no such view is published.
+pub async fn select_published_collab_password_hash_by_name<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<Option<(Uuid, Option<String>)>, AppError> {
+  let res = sqlx::query_as(
+    r#"
+      SELECT view_id, password_hash
+      FROM af_published_collab
+      WHERE workspace_id = (SELECT workspace_id FROM af_workspace WHERE publish_namespace = $1)
+      AND publish_name = $2
+      AND (expires_at IS NULL OR expires_at > NOW())
+    "#,
+  )
+  .bind(publish_namespace)
+  .bind(publish_name)
+  .fetch_optional(executor)
+  .await?;
+
+  Ok(res)
+}
+
+/// Returns the password hash of the published view, or `None` if the view has no password or is
+/// not published.
+pub async fn select_published_collab_password_hash<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+) -> Result<Option<String>, AppError> {
+  let res: Option<Option<String>> = sqlx::query_scalar(
+    r#"
+      SELECT password_hash
+      FROM af_published_collab
+      WHERE view_id = $1
+      AND (expires_at IS NULL OR expires_at > NOW())
+    "#,
+  )
+  .bind(view_id)
+  .fetch_optional(executor)
+  .await?;
+
+  Ok(res.flatten())
+}
+
+/// Deletes the published collabs whose expiry time has passed and returns their keys.
+pub async fn delete_expired_published_collabs<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+) -> Result<Vec<PublishCollabKey>, AppError> {
+  let keys = sqlx::query_as!(
+    PublishCollabKey,
+    r#"
+      DELETE FROM af_published_collab
+      WHERE expires_at <= NOW()
+      RETURNING workspace_id, view_id
+    "#,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  Ok(keys)
+}
+
+/// Moves the namespace from the workspace that owns it to `new_workspace_id`, and returns the
+/// previous owner. The previous owner gets a fresh default namespace, and the namespace the new
+/// workspace had before is released.
+pub async fn update_publish_namespace_workspace(
+  txn: &mut Transaction<'_, Postgres>,
+  namespace: &str,
+  new_workspace_id: &Uuid,
+) -> Result<Uuid, AppError> {
+  let previous_workspace_id: Uuid = sqlx::query_scalar(
+    r#"
+      SELECT workspace_id
+      FROM af_workspace
+      WHERE publish_namespace = $1
+      FOR UPDATE
+    "#,
+  )
+  .bind(namespace)
+  .fetch_one(txn.deref_mut())
+  .await?;
+
+  // Release the namespace first, as it must stay unique
+  sqlx::query(
+    r#"
+      UPDATE af_workspace
+      SET publish_namespace = DEFAULT
+      WHERE workspace_id = $1
+    "#,
+  )
+  .bind(previous_workspace_id)
+  .execute(txn.deref_mut())
+  .await?;
+
+  let res = sqlx::query(
+    r#"
+      UPDATE af_workspace
+      SET publish_namespace = $1
+      WHERE workspace_id = $2
+    "#,
+  )
+  .bind(namespace)
+  .bind(new_workspace_id)
+  .execute(txn.deref_mut())
+  .await?;
+  if res.rows_affected() == 0 {
+    return Err(AppError::RecordNotFound(format!(
+      "workspace {} not found",
+      new_workspace_id
+    )));
+  }
+
+  Ok(previous_workspace_id)
+}
+
+/// Returns the view id and publish name of every published view of the workspace that has not
+/// expired.
+pub async fn select_published_names_for_workspace<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<(Uuid, String)>, AppError> {
+  let res = sqlx::query_as(
+    r#"
+      SELECT view_id, publish_name
+      FROM af_published_collab
+      WHERE workspace_id = $1
+        AND (expires_at IS NULL OR expires_at > NOW())
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(executor)
+  .await?;
+
+  Ok(res)
+}
+
+/// Count the reads of the views published under the given names, and of the sites they were read
+/// from if any. The arrays are zipped together, one element per published name and referring site.
+/// Reads of views that are not published are not counted.
+///
+/// Each view counts at most `max_referrer_hosts` referring sites per day. Once the limit is
+/// reached, the reads from other sites are counted under `other_referrer_host`, so the referrers
+/// of a view do not grow without bound.
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_published_view_hits<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  publish_namespaces: &[String],
+  publish_names: &[String],
+  referrer_hosts: &[Option<String>],
+  view_counts: &[i64],
+  max_referrer_hosts: i64,
+  other_referrer_host: &str,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      WITH hits AS (
+        SELECT *
+        FROM UNNEST($1::text[], $2::text[], $3::text[], $4::bigint[])
+          AS h(publish_namespace, publish_name, referrer_host, view_count)
+      ),
+      published AS (
+        SELECT apc.workspace_id, apc.view_id, hits.referrer_host, hits.view_count
+        FROM hits
+        JOIN af_workspace aw ON aw.publish_namespace = hits.publish_namespace
+        JOIN af_published_collab apc
+          ON apc.workspace_id = aw.workspace_id AND apc.publish_name = hits.publish_name
+        WHERE apc.expires_at IS NULL OR apc.expires_at > NOW()
+      ),
+      daily AS (
+        INSERT INTO af_published_view_daily_stat (workspace_id, view_id, day, view_count)
+        SELECT workspace_id, view_id, CURRENT_DATE, SUM(view_count)::BIGINT
+        FROM published
+        GROUP BY workspace_id, view_id
+        ON CONFLICT (view_id, day)
+        DO UPDATE SET view_count = af_published_view_daily_stat.view_count + excluded.view_count
+      ),
+      referrers AS (
+        SELECT workspace_id, view_id, referrer_host, SUM(view_count)::BIGINT AS view_count
+        FROM published
+        WHERE referrer_host IS NOT NULL
+        GROUP BY workspace_id, view_id, referrer_host
+      ),
+      ranked AS (
+        SELECT r.*,
+          existing.referrer_host IS NOT NULL AS known,
+          (
+            SELECT COUNT(*)
+            FROM af_published_view_referrer_stat counted
+            WHERE counted.view_id = r.view_id
+              AND counted.day = CURRENT_DATE
+              AND counted.referrer_host <> $6
+          ) AS known_count,
+          ROW_NUMBER() OVER (
+            PARTITION BY r.view_id, existing.referrer_host IS NOT NULL
+            ORDER BY r.view_count DESC, r.referrer_host
+          ) AS host_rank
+        FROM referrers r
+        LEFT JOIN af_published_view_referrer_stat existing
+          ON existing.view_id = r.view_id
+          AND existing.day = CURRENT_DATE
+          AND existing.referrer_host = r.referrer_host
+      )
+      INSERT INTO af_published_view_referrer_stat
+        (workspace_id, view_id, day, referrer_host, view_count)
+      SELECT workspace_id, view_id, CURRENT_DATE,
+        CASE WHEN known OR known_count + host_rank <= $5 THEN referrer_host ELSE $6 END,
+        SUM(view_count)::BIGINT
+      FROM ranked
+      GROUP BY 1, 2, 4
+      ON CONFLICT (view_id, day, referrer_host)
+      DO UPDATE SET view_count = af_published_view_referrer_stat.view_count + excluded.view_count
+    "#,
+  )
+  .bind(publish_namespaces)
+  .bind(publish_names)
+  .bind(referrer_hosts)
+  .bind(view_counts)
+  .bind(max_referrer_hosts)
+  .bind(other_referrer_host)
+  .execute(executor)
+  .await?;
+
+  Ok(())
+}
+
+/// Returns the daily read counts of the views of the workspace since the given day, ordered by
+/// view and day. The publish name is `None` for views that are no longer published.
+pub async fn select_published_view_daily_stats<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  since: NaiveDate,
+) -> Result<Vec<AFPublishedViewDailyStatRow>, AppError> {
+  let res = sqlx::query_as(
+    r#"
+      SELECT stat.view_id, apc.publish_name, stat.day, stat.view_count
+      FROM af_published_view_daily_stat stat
+      LEFT JOIN af_published_collab apc
+        ON apc.workspace_id = stat.workspace_id AND apc.view_id = stat.view_id
+      WHERE stat.workspace_id = $1 AND stat.day >= $2
+      ORDER BY stat.view_id, stat.day
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(since)
+  .fetch_all(executor)
+  .await?;
+
+  Ok(res)
+}
+
+/// Returns the read counts of the views of the workspace since the given day, per referring site,
+/// with the top referrers of each view first.
+pub async fn select_published_view_referrer_stats<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  since: NaiveDate,
+) -> Result<Vec<AFPublishedViewReferrerStatRow>, AppError> {
+  let res = sqlx::query_as(
+    r#"
+      SELECT view_id, referrer_host, SUM(view_count)::BIGINT AS view_count
+      FROM af_published_view_referrer_stat
+      WHERE workspace_id = $1 AND day >= $2
+      GROUP BY view_id, referrer_host
+      ORDER BY view_id, view_count DESC, referrer_host
+    "#,
   )
+  .bind(workspace_id)
+  .bind(since)
   .fetch_all(executor)
   .await?;
 
@@ -0,0 +1,148 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFPublishedDatabaseFormRow;
+
+/// Creates the form of the published database view, or replaces its fields and limits. The user
+/// that sets the form up becomes the one the submitted rows are created on behalf of.
+pub async fn upsert_published_database_form<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  field_ids: &[String],
+  max_submissions_per_hour: i32,
+  require_captcha: bool,
+  created_by: i64,
+) -> Result<AFPublishedDatabaseFormRow, AppError> {
+  let row = sqlx::query_as::<_, AFPublishedDatabaseFormRow>(
+    r#"
+      INSERT INTO af_published_database_form
+        (view_id, workspace_id, field_ids, max_submissions_per_hour, require_captcha, created_by)
+      VALUES ($1, $2, $3, $4, $5, $6)
+      ON CONFLICT (view_id) DO UPDATE SET
+        field_ids = EXCLUDED.field_ids,
+        max_submissions_per_hour = EXCLUDED.max_submissions_per_hour,
+        require_captcha = EXCLUDED.require_captcha,
+        created_by = EXCLUDED.created_by,
+        updated_at = CURRENT_TIMESTAMP
+      RETURNING view_id, workspace_id, field_ids, max_submissions_per_hour, require_captcha,
+        created_by, updated_at
+    "#,
+  )
+  .bind(view_id)
+  .bind(workspace_id)
+  .bind(field_ids)
+  .bind(max_submissions_per_hour)
+  .bind(require_captcha)
+  .bind(created_by)
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn select_published_database_form<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Option<AFPublishedDatabaseFormRow>, AppError> {
+  let row = sqlx::query_as::<_, AFPublishedDatabaseFormRow>(
+    r#"
+      SELECT view_id, workspace_id, field_ids, max_submissions_per_hour, require_captcha,
+        created_by, updated_at
+      FROM af_published_database_form
+      WHERE workspace_id = $1 AND view_id = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the form and locks it until the end of the transaction, so that the submissions made
+/// at the same time are counted one after the other.
+pub async fn select_published_database_form_for_update<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Option<AFPublishedDatabaseFormRow>, AppError> {
+  let row = sqlx::query_as::<_, AFPublishedDatabaseFormRow>(
+    r#"
+      SELECT view_id, workspace_id, field_ids, max_submissions_per_hour, require_captcha,
+        created_by, updated_at
+      FROM af_published_database_form
+      WHERE workspace_id = $1 AND view_id = $2
+      FOR UPDATE
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns false if the published view has no form. The rows already submitted stay in the
+/// database.
+pub async fn delete_published_database_form<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<bool, AppError> {
+  let res =
+    sqlx::query("DELETE FROM af_published_database_form WHERE workspace_id = $1 AND view_id = $2")
+      .bind(workspace_id)
+      .bind(view_id)
+      .execute(executor)
+      .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Returns how many rows were submitted through the form since `since`, in total and from the ip
+/// address.
+pub async fn select_published_database_form_submission_count<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  view_id: &Uuid,
+  ip: Option<&str>,
+  since: DateTime<Utc>,
+) -> Result<(i64, i64), AppError> {
+  let counts = sqlx::query_as::<_, (i64, i64)>(
+    r#"
+      SELECT COUNT(*), COUNT(*) FILTER (WHERE ip = $2)
+      FROM af_published_database_form_submission
+      WHERE view_id = $1 AND submitted_at >= $3
+    "#,
+  )
+  .bind(view_id)
+  .bind(ip)
+  .bind(since)
+  .fetch_one(executor)
+  .await?;
+  Ok(counts)
+}
+
+pub async fn insert_published_database_form_submission<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  row_id: &str,
+  ip: Option<&str>,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_published_database_form_submission (view_id, row_id, ip)
+      VALUES ($1, $2, $3)
+    "#,
+  )
+  .bind(view_id)
+  .bind(row_id)
+  .bind(ip)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
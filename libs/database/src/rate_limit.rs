@@ -0,0 +1,40 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+/// Returns the number of requests per minute the workspace is allowed, if it overrides the
+/// default of the server.
+pub async fn select_workspace_requests_per_minute<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Option<i32>, AppError> {
+  let limit = sqlx::query_scalar::<_, Option<i32>>(
+    r#"
+      SELECT requests_per_minute FROM af_workspace_rate_limit WHERE workspace_id = $1
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(limit.flatten())
+}
+
+/// Returns the highest number of concurrent websocket connections granted to the user by the
+/// workspaces the user is a member of, if any of them overrides the default of the server.
+pub async fn select_user_ws_connection_limit<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Option<i32>, AppError> {
+  let limit = sqlx::query_scalar::<_, Option<i32>>(
+    r#"
+      SELECT MAX(l.ws_connections_per_user)
+      FROM af_workspace_rate_limit l
+      JOIN af_workspace_member m ON m.workspace_id = l.workspace_id
+      WHERE m.uid = $1
+    "#,
+  )
+  .bind(uid)
+  .fetch_one(executor)
+  .await?;
+  Ok(limit)
+}
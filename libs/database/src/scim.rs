@@ -0,0 +1,410 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::{AFScimGroupRow, AFScimUserRow};
+
+pub async fn upsert_scim_user<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  external_id: Option<&str>,
+  active: bool,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_scim_user (workspace_id, uid, external_id, active)
+      VALUES ($1, $2, $3, $4)
+      ON CONFLICT (workspace_id, uid)
+      DO UPDATE SET external_id = EXCLUDED.external_id, active = EXCLUDED.active, updated_at = NOW()
+    "#,
+    workspace_id,
+    uid,
+    external_id,
+    active,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_scim_user<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  user_uuid: &Uuid,
+) -> Result<Option<AFScimUserRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFScimUserRow,
+    r#"
+      SELECT
+        u.uid,
+        u.uuid,
+        u.email,
+        u.name,
+        s.external_id,
+        s.active,
+        ARRAY(
+          SELECT g.group_id
+          FROM af_scim_group_member gm
+          JOIN af_scim_group g ON g.group_id = gm.group_id
+          WHERE gm.uid = s.uid AND g.workspace_id = s.workspace_id
+        ) AS "group_ids!",
+        s.created_at,
+        s.updated_at
+      FROM af_scim_user s
+      JOIN af_user u ON u.uid = s.uid
+      WHERE s.workspace_id = $1 AND u.uuid = $2
+    "#,
+    workspace_id,
+    user_uuid,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the users provisioned into the workspace, ordered by provisioning time. When given,
+/// only the users with that email, or that external id, are returned.
+pub async fn select_scim_users<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  email: Option<&str>,
+  external_id: Option<&str>,
+) -> Result<Vec<AFScimUserRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFScimUserRow,
+    r#"
+      SELECT
+        u.uid,
+        u.uuid,
+        u.email,
+        u.name,
+        s.external_id,
+        s.active,
+        ARRAY(
+          SELECT g.group_id
+          FROM af_scim_group_member gm
+          JOIN af_scim_group g ON g.group_id = gm.group_id
+          WHERE gm.uid = s.uid AND g.workspace_id = s.workspace_id
+        ) AS "group_ids!",
+        s.created_at,
+        s.updated_at
+      FROM af_scim_user s
+      JOIN af_user u ON u.uid = s.uid
+      WHERE s.workspace_id = $1
+        AND ($2::text IS NULL OR LOWER(u.email) = LOWER($2))
+        AND ($3::text IS NULL OR s.external_id = $3)
+      ORDER BY s.created_at ASC
+    "#,
+    workspace_id,
+    email,
+    external_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the uid of the users provisioned into the workspace, out of the given user uuids.
+pub async fn select_scim_uids_by_uuids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uuids: &[Uuid],
+) -> Result<Vec<i64>, AppError> {
+  let uids = sqlx::query_scalar!(
+    r#"
+      SELECT s.uid
+      FROM af_scim_user s
+      JOIN af_user u ON u.uid = s.uid
+      WHERE s.workspace_id = $1 AND u.uuid = ANY($2)
+    "#,
+    workspace_id,
+    uuids,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(uids)
+}
+
+/// Delete the user from the SCIM users of the workspace, and from the SCIM groups of the
+/// workspace.
+pub async fn delete_scim_user<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      WITH deleted_group_members AS (
+        DELETE FROM af_scim_group_member
+        WHERE uid = $2
+          AND group_id IN (SELECT group_id FROM af_scim_group WHERE workspace_id = $1)
+      )
+      DELETE FROM af_scim_user WHERE workspace_id = $1 AND uid = $2
+    "#,
+    workspace_id,
+    uid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the roles of the mapped SCIM groups of the workspace the user is a member of.
+pub async fn select_scim_user_group_role_ids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<Vec<i32>, AppError> {
+  let role_ids = sqlx::query_scalar!(
+    r#"
+      SELECT g.role_id AS "role_id!"
+      FROM af_scim_group g
+      JOIN af_scim_group_member gm ON gm.group_id = g.group_id
+      WHERE g.workspace_id = $1 AND gm.uid = $2 AND g.role_id IS NOT NULL
+    "#,
+    workspace_id,
+    uid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(role_ids)
+}
+
+/// Add the user to the workspace with the role, or change its role if it already is a member.
+/// Returns true if the user was added.
+pub async fn upsert_workspace_member_role<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  role_id: i32,
+) -> Result<bool, AppError> {
+  let inserted = sqlx::query_scalar!(
+    r#"
+      INSERT INTO af_workspace_member (workspace_id, uid, role_id)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (workspace_id, uid) DO UPDATE SET role_id = EXCLUDED.role_id
+      RETURNING (xmax = 0) AS "inserted!"
+    "#,
+    workspace_id,
+    uid,
+    role_id,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(inserted)
+}
+
+/// Returns false if the user was not a member of the workspace.
+pub async fn delete_workspace_member_by_uid<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_workspace_member
+      WHERE workspace_id = $1 AND uid = $2
+    "#,
+    workspace_id,
+    uid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+pub async fn insert_scim_group<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  display_name: &str,
+  external_id: Option<&str>,
+) -> Result<Uuid, AppError> {
+  let group_id = sqlx::query_scalar!(
+    r#"
+      INSERT INTO af_scim_group (workspace_id, display_name, external_id)
+      VALUES ($1, $2, $3)
+      RETURNING group_id
+    "#,
+    workspace_id,
+    display_name,
+    external_id,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(group_id)
+}
+
+pub async fn select_scim_group<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+) -> Result<Option<AFScimGroupRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFScimGroupRow,
+    r#"
+      SELECT
+        g.group_id,
+        g.workspace_id,
+        g.external_id,
+        g.display_name,
+        g.role_id,
+        ARRAY(
+          SELECT u.uuid
+          FROM af_scim_group_member gm
+          JOIN af_user u ON u.uid = gm.uid
+          WHERE gm.group_id = g.group_id
+        ) AS "member_uuids!",
+        g.created_at,
+        g.updated_at
+      FROM af_scim_group g
+      WHERE g.workspace_id = $1 AND g.group_id = $2
+    "#,
+    workspace_id,
+    group_id,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the groups of the workspace, ordered by creation time. When given, only the group
+/// with that display name is returned.
+pub async fn select_scim_groups<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  display_name: Option<&str>,
+) -> Result<Vec<AFScimGroupRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFScimGroupRow,
+    r#"
+      SELECT
+        g.group_id,
+        g.workspace_id,
+        g.external_id,
+        g.display_name,
+        g.role_id,
+        ARRAY(
+          SELECT u.uuid
+          FROM af_scim_group_member gm
+          JOIN af_user u ON u.uid = gm.uid
+          WHERE gm.group_id = g.group_id
+        ) AS "member_uuids!",
+        g.created_at,
+        g.updated_at
+      FROM af_scim_group g
+      WHERE g.workspace_id = $1 AND ($2::text IS NULL OR g.display_name = $2)
+      ORDER BY g.created_at ASC
+    "#,
+    workspace_id,
+    display_name,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn update_scim_group<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  group_id: &Uuid,
+  display_name: &str,
+  external_id: Option<&str>,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_scim_group
+      SET display_name = $2, external_id = $3, updated_at = NOW()
+      WHERE group_id = $1
+    "#,
+    group_id,
+    display_name,
+    external_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Map the group to the role, or unmap it when `role_id` is `None`. Returns false if the group
+/// does not exist in the workspace.
+pub async fn update_scim_group_role<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+  role_id: Option<i32>,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      UPDATE af_scim_group
+      SET role_id = $3, updated_at = NOW()
+      WHERE workspace_id = $1 AND group_id = $2
+    "#,
+    workspace_id,
+    group_id,
+    role_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_scim_group<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  group_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!("DELETE FROM af_scim_group WHERE group_id = $1", group_id)
+    .execute(executor)
+    .await?;
+  Ok(())
+}
+
+pub async fn insert_scim_group_members<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  group_id: &Uuid,
+  uids: &[i64],
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_scim_group_member (group_id, uid)
+      SELECT $1, uid FROM UNNEST($2::bigint[]) AS uid
+      ON CONFLICT (group_id, uid) DO NOTHING
+    "#,
+    group_id,
+    uids,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Remove the given members from the group, or every member when `uids` is `None`.
+pub async fn delete_scim_group_members<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  group_id: &Uuid,
+  uids: Option<&[i64]>,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_scim_group_member
+      WHERE group_id = $1 AND ($2::bigint[] IS NULL OR uid = ANY($2))
+    "#,
+    group_id,
+    uids,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_scim_group_member_uids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  group_id: &Uuid,
+) -> Result<Vec<i64>, AppError> {
+  let uids = sqlx::query_scalar!(
+    "SELECT uid FROM af_scim_group_member WHERE group_id = $1",
+    group_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(uids)
+}
@@ -0,0 +1,225 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::{AFApiKeyPrincipalRow, AFApiKeyRow, AFServiceAccountRow};
+
+/// Insert the user that backs the service account, add it to the workspace with `role_id`, and
+/// insert the service account itself.
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_service_account<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  user_uuid: &Uuid,
+  email: &str,
+  name: &str,
+  scope: &str,
+  role_id: i32,
+  created_by: i64,
+) -> Result<AFServiceAccountRow, AppError> {
+  let row = sqlx::query_as!(
+    AFServiceAccountRow,
+    r#"
+      WITH ins_user AS (
+        INSERT INTO af_user (uid, uuid, email, name)
+        VALUES ($2, $3, $4, $5)
+        RETURNING uid
+      ),
+      ins_member AS (
+        INSERT INTO af_workspace_member (workspace_id, uid, role_id)
+        SELECT $1, uid, $7 FROM ins_user
+      )
+      INSERT INTO af_service_account (workspace_id, uid, name, scope, created_by)
+      SELECT $1, uid, $5, $6, $8 FROM ins_user
+      RETURNING service_account_id, workspace_id, uid, name, scope, created_by, created_at
+    "#,
+    workspace_id,
+    uid,
+    user_uuid,
+    email,
+    name,
+    scope,
+    role_id,
+    created_by,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the service accounts of the workspace, ordered by creation time.
+pub async fn select_service_accounts<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFServiceAccountRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFServiceAccountRow,
+    r#"
+      SELECT service_account_id, workspace_id, uid, name, scope, created_by, created_at
+      FROM af_service_account
+      WHERE workspace_id = $1
+      ORDER BY created_at ASC
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn select_service_account<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  service_account_id: &Uuid,
+) -> Result<Option<AFServiceAccountRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFServiceAccountRow,
+    r#"
+      SELECT service_account_id, workspace_id, uid, name, scope, created_by, created_at
+      FROM af_service_account
+      WHERE workspace_id = $1 AND service_account_id = $2
+    "#,
+    workspace_id,
+    service_account_id,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn select_service_account_exists_by_name<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  name: &str,
+) -> Result<bool, AppError> {
+  let exists = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS(
+        SELECT 1 FROM af_service_account WHERE workspace_id = $1 AND name = $2
+      )
+    "#,
+    workspace_id,
+    name,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(exists.unwrap_or(false))
+}
+
+/// Returns the keys, revoked ones included, of every service account of the workspace, ordered by
+/// creation time.
+pub async fn select_workspace_api_keys<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFApiKeyRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFApiKeyRow,
+    r#"
+      SELECT k.api_key_id, k.service_account_id, k.key_prefix, k.created_at, k.last_used_at,
+             k.revoked_at
+      FROM af_api_key k
+      JOIN af_service_account s ON s.service_account_id = k.service_account_id
+      WHERE s.workspace_id = $1
+      ORDER BY k.created_at ASC
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn insert_api_key<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  service_account_id: &Uuid,
+  key_hash: &str,
+  key_prefix: &str,
+) -> Result<AFApiKeyRow, AppError> {
+  let row = sqlx::query_as!(
+    AFApiKeyRow,
+    r#"
+      INSERT INTO af_api_key (service_account_id, key_hash, key_prefix)
+      VALUES ($1, $2, $3)
+      RETURNING api_key_id, service_account_id, key_prefix, created_at, last_used_at, revoked_at
+    "#,
+    service_account_id,
+    key_hash,
+    key_prefix,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Revoke every active key of the service account.
+pub async fn revoke_service_account_api_keys<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  service_account_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_api_key
+      SET revoked_at = NOW()
+      WHERE service_account_id = $1 AND revoked_at IS NULL
+    "#,
+    service_account_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns false if the service account has no such active key.
+pub async fn revoke_api_key<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  service_account_id: &Uuid,
+  api_key_id: &Uuid,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      UPDATE af_api_key
+      SET revoked_at = NOW()
+      WHERE service_account_id = $1 AND api_key_id = $2 AND revoked_at IS NULL
+    "#,
+    service_account_id,
+    api_key_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+/// Returns the service account the key authenticates, if the key exists and is not revoked.
+pub async fn select_api_key_principal<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  key_hash: &str,
+) -> Result<Option<AFApiKeyPrincipalRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFApiKeyPrincipalRow,
+    r#"
+      SELECT k.api_key_id, s.workspace_id, u.uuid AS user_uuid, s.scope, k.last_used_at
+      FROM af_api_key k
+      JOIN af_service_account s ON s.service_account_id = k.service_account_id
+      JOIN af_user u ON u.uid = s.uid
+      WHERE k.key_hash = $1 AND k.revoked_at IS NULL AND u.deleted_at IS NULL
+    "#,
+    key_hash,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn update_api_key_last_used_at<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  api_key_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    "UPDATE af_api_key SET last_used_at = NOW() WHERE api_key_id = $1",
+    api_key_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
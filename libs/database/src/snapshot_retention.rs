@@ -0,0 +1,125 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFWorkspaceSnapshotRetentionRow;
+
+/// Returns the snapshot retention policy of the workspace, if it overrides the default of the
+/// server.
+pub async fn select_workspace_snapshot_retention<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Option<AFWorkspaceSnapshotRetentionRow>, AppError> {
+  let row = sqlx::query_as(
+    r#"
+      SELECT workspace_id, keep_last, keep_days, hourly_days, daily_days, weekly_weeks,
+        updated_by, updated_at
+      FROM af_workspace_snapshot_retention
+      WHERE workspace_id = $1
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the snapshot retention policies of all the workspaces that override the default.
+pub async fn select_all_workspace_snapshot_retentions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+) -> Result<Vec<AFWorkspaceSnapshotRetentionRow>, AppError> {
+  let rows = sqlx::query_as(
+    r#"
+      SELECT workspace_id, keep_last, keep_days, hourly_days, daily_days, weekly_weeks,
+        updated_by, updated_at
+      FROM af_workspace_snapshot_retention
+    "#,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn upsert_workspace_snapshot_retention<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  keep_last: i32,
+  keep_days: i32,
+  hourly_days: i32,
+  daily_days: i32,
+  weekly_weeks: i32,
+  updated_by: i64,
+) -> Result<AFWorkspaceSnapshotRetentionRow, AppError> {
+  let row = sqlx::query_as(
+    r#"
+      INSERT INTO af_workspace_snapshot_retention
+        (workspace_id, keep_last, keep_days, hourly_days, daily_days, weekly_weeks, updated_by)
+      VALUES ($1, $2, $3, $4, $5, $6, $7)
+      ON CONFLICT (workspace_id) DO UPDATE SET
+        keep_last = EXCLUDED.keep_last,
+        keep_days = EXCLUDED.keep_days,
+        hourly_days = EXCLUDED.hourly_days,
+        daily_days = EXCLUDED.daily_days,
+        weekly_weeks = EXCLUDED.weekly_weeks,
+        updated_by = EXCLUDED.updated_by,
+        updated_at = NOW()
+      RETURNING workspace_id, keep_last, keep_days, hourly_days, daily_days, weekly_weeks,
+        updated_by, updated_at
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(keep_last)
+  .bind(keep_days)
+  .bind(hourly_days)
+  .bind(daily_days)
+  .bind(weekly_weeks)
+  .bind(updated_by)
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn delete_workspace_snapshot_retention<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query("DELETE FROM af_workspace_snapshot_retention WHERE workspace_id = $1")
+    .bind(workspace_id)
+    .execute(executor)
+    .await?;
+  Ok(())
+}
+
+/// Returns the id, object id and creation time of the snapshots of the collabs of the workspace,
+/// ordered by object id, the most recent snapshot of each collab first.
+pub async fn select_workspace_collab_snapshot_times<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<(i64, String, DateTime<Utc>)>, AppError> {
+  let rows = sqlx::query_as(
+    r#"
+      SELECT sid, oid, created_at
+      FROM af_collab_snapshot
+      WHERE workspace_id = $1 AND deleted_at IS NULL
+      ORDER BY oid, created_at DESC, sid DESC
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Deletes the snapshots, returns the number of snapshots deleted.
+pub async fn delete_collab_snapshots<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  snapshot_ids: &[i64],
+) -> Result<u64, AppError> {
+  let result = sqlx::query("DELETE FROM af_collab_snapshot WHERE sid = ANY($1)")
+    .bind(snapshot_ids)
+    .execute(executor)
+    .await?;
+  Ok(result.rows_affected())
+}
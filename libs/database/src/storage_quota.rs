@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use crate::pg_row::AFWorkspaceStorageRow;
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+/// Returns the bytes used by the workspace as of the last
+/// [refresh_workspace_storage_usage], together with its plan, which sets its quota.
+pub async fn select_workspace_storage<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<AFWorkspaceStorageRow, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceStorageRow>(
+    r#"
+      SELECT
+        COALESCE(u.collab_bytes, 0) AS collab_bytes,
+        COALESCE(u.blob_bytes, 0) AS blob_bytes,
+        p.plan,
+        p.storage_quota_bytes
+      FROM (SELECT $1::uuid AS workspace_id) w
+      LEFT JOIN af_workspace_storage_usage u ON u.workspace_id = w.workspace_id
+      LEFT JOIN af_workspace_plan p ON p.workspace_id = w.workspace_id
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Same as [select_workspace_storage], but counts the bytes the workspace uses now.
+pub async fn select_current_workspace_storage<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<AFWorkspaceStorageRow, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceStorageRow>(
+    r#"
+      SELECT
+        COALESCE((SELECT SUM(len) FROM af_collab WHERE workspace_id = $1), 0)::BIGINT
+          AS collab_bytes,
+        COALESCE((SELECT SUM(file_size) FROM af_blob_metadata WHERE workspace_id = $1), 0)::BIGINT
+          AS blob_bytes,
+        p.plan,
+        p.storage_quota_bytes
+      FROM (SELECT $1::uuid AS workspace_id) w
+      LEFT JOIN af_workspace_plan p ON p.workspace_id = w.workspace_id
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Counts again the bytes used by the collabs and the blobs of every workspace.
+pub async fn refresh_workspace_storage_usage<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_workspace_storage_usage (workspace_id, collab_bytes, blob_bytes)
+      SELECT
+        w.workspace_id,
+        COALESCE(c.bytes, 0),
+        COALESCE(b.bytes, 0)
+      FROM af_workspace w
+      LEFT JOIN (
+        SELECT workspace_id, SUM(len)::BIGINT AS bytes FROM af_collab GROUP BY workspace_id
+      ) c ON c.workspace_id = w.workspace_id
+      LEFT JOIN (
+        SELECT workspace_id, SUM(file_size)::BIGINT AS bytes
+        FROM af_blob_metadata
+        GROUP BY workspace_id
+      ) b ON b.workspace_id = w.workspace_id
+      ON CONFLICT (workspace_id) DO UPDATE
+      SET collab_bytes = EXCLUDED.collab_bytes,
+          blob_bytes = EXCLUDED.blob_bytes,
+          updated_at = CURRENT_TIMESTAMP
+    "#,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Sets the plan of the workspace. A `storage_quota_bytes` overrides the quota of the plan.
+pub async fn upsert_workspace_plan<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  plan: i16,
+  storage_quota_bytes: Option<i64>,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_workspace_plan (workspace_id, plan, storage_quota_bytes)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (workspace_id) DO UPDATE
+      SET plan = EXCLUDED.plan,
+          storage_quota_bytes = EXCLUDED.storage_quota_bytes,
+          updated_at = CURRENT_TIMESTAMP
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(plan)
+  .bind(storage_quota_bytes)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the length of the stored collabs among the given ones, by object id.
+pub async fn select_collab_lens<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oids: &[String],
+) -> Result<HashMap<String, i64>, AppError> {
+  let rows = sqlx::query_as::<_, (String, i64)>(
+    r#"
+      SELECT oid, COALESCE(len, 0)::BIGINT FROM af_collab WHERE oid = ANY($1)
+    "#,
+  )
+  .bind(oids)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows.into_iter().collect())
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use database_entity::dto::AFWebUser;
 use futures_util::stream::BoxStream;
 use sqlx::postgres::PgArguments;
@@ -229,6 +231,22 @@ pub async fn select_email_from_user_uuid(
   Ok(email)
 }
 
+/// Returns true if the user confirmed its email, so that the email can be trusted to be its own.
+pub async fn select_is_email_confirmed(pool: &PgPool, uid: i64) -> Result<bool, AppError> {
+  let confirmed = sqlx::query_scalar!(
+    r#"
+      SELECT u.email_confirmed_at IS NOT NULL AS "confirmed!"
+      FROM af_user a
+      JOIN auth.users u ON u.id = a.uuid
+      WHERE a.uid = $1
+    "#,
+    uid
+  )
+  .fetch_optional(pool)
+  .await?;
+  Ok(confirmed.unwrap_or(false))
+}
+
 #[inline]
 pub async fn select_name_from_uuid(pool: &PgPool, user_uuid: &Uuid) -> Result<String, AppError> {
   let email = sqlx::query_scalar!(
@@ -242,6 +260,43 @@ pub async fn select_name_from_uuid(pool: &PgPool, user_uuid: &Uuid) -> Result<St
   Ok(email)
 }
 
+/// Returns the web users of the given uids, keyed by uid. Unknown uids are skipped.
+pub async fn select_web_users_from_uids(
+  pool: &PgPool,
+  uids: &[i64],
+) -> Result<HashMap<i64, AFWebUser>, AppError> {
+  let rows: Vec<(i64, Uuid, Option<String>, Option<String>)> = sqlx::query_as(
+    r#"
+    SELECT
+      uid,
+      uuid,
+      name,
+      metadata ->> 'icon_url' AS avatar_url
+    FROM af_user
+    WHERE uid = ANY($1)
+    "#,
+  )
+  .bind(uids)
+  .fetch_all(pool)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|(uid, uuid, name, avatar_url)| {
+        (
+          uid,
+          AFWebUser {
+            uuid,
+            name: name.unwrap_or_default(),
+            avatar_url,
+          },
+        )
+      })
+      .collect(),
+  )
+}
+
 pub async fn select_web_user_from_uid(pool: &PgPool, uid: i64) -> Result<AFWebUser, AppError> {
   let row = sqlx::query_as!(
     AFWebUser,
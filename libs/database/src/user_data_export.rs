@@ -0,0 +1,212 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::{
+  AFUserCommentRow, AFUserDataExportTaskRow, AFUserOwnedCollabRow, AFUserWorkspaceMembershipRow,
+};
+
+pub async fn insert_user_data_export_task<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<AFUserDataExportTaskRow, AppError> {
+  let row = sqlx::query_as!(
+    AFUserDataExportTaskRow,
+    r#"
+      INSERT INTO af_user_data_export_task (uid)
+      VALUES ($1)
+      RETURNING task_id, uid, status, object_key, file_size, error, created_at, completed_at
+    "#,
+    uid,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn select_user_data_export_task<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+) -> Result<Option<AFUserDataExportTaskRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFUserDataExportTaskRow,
+    r#"
+      SELECT task_id, uid, status, object_key, file_size, error, created_at, completed_at
+      FROM af_user_data_export_task
+      WHERE task_id = $1
+    "#,
+    task_id,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the number of export tasks the user created after `created_after`, and how many of
+/// them are still running.
+pub async fn select_user_data_export_task_count<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  created_after: DateTime<Utc>,
+) -> Result<(i64, i64), AppError> {
+  let row = sqlx::query!(
+    r#"
+      SELECT COUNT(*) AS "total!", COUNT(*) FILTER (WHERE status = 0) AS "running!"
+      FROM af_user_data_export_task
+      WHERE uid = $1 AND created_at > $2
+    "#,
+    uid,
+    created_after,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok((row.total, row.running))
+}
+
+pub async fn update_user_data_export_task_completed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+  object_key: &str,
+  file_size: i64,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_user_data_export_task
+      SET status = 1, object_key = $2, file_size = $3, completed_at = NOW()
+      WHERE task_id = $1
+    "#,
+    task_id,
+    object_key,
+    file_size,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn update_user_data_export_task_failed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+  error: &str,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_user_data_export_task
+      SET status = 2, error = $2, completed_at = NOW()
+      WHERE task_id = $1
+    "#,
+    task_id,
+    error,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Deletes the export tasks of the user created before `created_before`, and returns the keys of
+/// the archives of the deleted tasks.
+pub async fn delete_user_data_export_tasks_before<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  created_before: DateTime<Utc>,
+) -> Result<Vec<String>, AppError> {
+  let object_keys: Vec<Option<String>> = sqlx::query_scalar!(
+    r#"
+      DELETE FROM af_user_data_export_task
+      WHERE uid = $1 AND created_at < $2
+      RETURNING object_key
+    "#,
+    uid,
+    created_before,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(object_keys.into_iter().flatten().collect())
+}
+
+pub async fn select_user_workspace_memberships<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Vec<AFUserWorkspaceMembershipRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFUserWorkspaceMembershipRow,
+    r#"
+      SELECT
+        m.workspace_id,
+        w.workspace_name,
+        m.role_id,
+        w.owner_uid = m.uid AS "is_owner!",
+        m.created_at AS joined_at
+      FROM af_workspace_member m
+      JOIN af_workspace w ON w.workspace_id = m.workspace_id
+      WHERE m.uid = $1
+      ORDER BY m.created_at
+    "#,
+    uid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the collabs the user owns that have not been deleted.
+pub async fn select_user_owned_collabs<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Vec<AFUserOwnedCollabRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFUserOwnedCollabRow,
+    r#"
+      SELECT oid, workspace_id, partition_key
+      FROM af_collab
+      WHERE owner_uid = $1 AND deleted_at IS NULL
+      ORDER BY workspace_id, created_at
+    "#,
+    uid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the comments the user wrote, on collabs and on published views, that have not been
+/// deleted.
+pub async fn select_user_comments<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Vec<AFUserCommentRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFUserCommentRow,
+    r#"
+      SELECT
+        comment_id AS "comment_id!",
+        'collab' AS "source!",
+        workspace_id,
+        object_id AS "object_id!",
+        reply_comment_id,
+        content AS "content!",
+        created_at AS "created_at!",
+        updated_at AS "updated_at!"
+      FROM af_collab_comment
+      WHERE created_by = $1 AND NOT is_deleted
+      UNION ALL
+      SELECT
+        comment_id,
+        'published_view' AS source,
+        NULL::UUID AS workspace_id,
+        view_id::TEXT AS object_id,
+        reply_comment_id,
+        content,
+        created_at,
+        updated_at
+      FROM af_published_view_comment
+      WHERE created_by = $1 AND NOT is_deleted
+      ORDER BY "created_at!"
+    "#,
+    uid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
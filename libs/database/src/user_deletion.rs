@@ -0,0 +1,210 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFUserDeletionRow;
+
+/// Mark the account of the user for deletion. Marking it again only replaces the workspace
+/// transfers, the grace period keeps running.
+pub async fn upsert_user_deletion<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  uuid: &Uuid,
+  workspace_transfers: &serde_json::Value,
+  purge_after: DateTime<Utc>,
+) -> Result<AFUserDeletionRow, AppError> {
+  let row = sqlx::query_as!(
+    AFUserDeletionRow,
+    r#"
+      INSERT INTO af_user_deletion (uid, uuid, workspace_transfers, purge_after)
+      VALUES ($1, $2, $3, $4)
+      ON CONFLICT (uid)
+      DO UPDATE SET workspace_transfers = EXCLUDED.workspace_transfers
+      RETURNING uid, uuid, workspace_transfers, requested_at, purge_after, attempts, last_error
+    "#,
+    uid,
+    uuid,
+    workspace_transfers,
+    purge_after,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn select_user_deletion<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Option<AFUserDeletionRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFUserDeletionRow,
+    r#"
+      SELECT uid, uuid, workspace_transfers, requested_at, purge_after, attempts, last_error
+      FROM af_user_deletion
+      WHERE uid = $1
+    "#,
+    uid,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns true if the account was marked for deletion.
+pub async fn delete_user_deletion<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!("DELETE FROM af_user_deletion WHERE uid = $1", uid)
+    .execute(executor)
+    .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+/// Returns the accounts whose grace period has ended, the ones that failed the least often first.
+pub async fn select_due_user_deletions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  now: DateTime<Utc>,
+  limit: i64,
+) -> Result<Vec<AFUserDeletionRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFUserDeletionRow,
+    r#"
+      SELECT uid, uuid, workspace_transfers, requested_at, purge_after, attempts, last_error
+      FROM af_user_deletion
+      WHERE purge_after <= $1
+      ORDER BY attempts, purge_after
+      LIMIT $2
+    "#,
+    now,
+    limit,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn update_user_deletion_failed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  error: &str,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_user_deletion
+      SET attempts = attempts + 1, last_error = $2
+      WHERE uid = $1
+    "#,
+    uid,
+    error,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the member that takes over the workspace of a deleted account when the user did not
+/// choose one: the member that joined first. Guests are never picked.
+pub async fn select_workspace_successor<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<Option<i64>, AppError> {
+  let successor = sqlx::query_scalar!(
+    r#"
+      SELECT m.uid
+      FROM af_workspace_member m
+      JOIN af_roles r ON r.id = m.role_id
+      WHERE m.workspace_id = $1 AND m.uid <> $2 AND r.name IN ('Owner', 'Member')
+      ORDER BY m.created_at, m.uid
+      LIMIT 1
+    "#,
+    workspace_id,
+    uid,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(successor)
+}
+
+/// Removes the user from all the workspaces it is a member of, and returns these workspaces.
+pub async fn delete_workspace_memberships_of_user<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Vec<Uuid>, AppError> {
+  let workspace_ids = sqlx::query_scalar!(
+    "DELETE FROM af_workspace_member WHERE uid = $1 RETURNING workspace_id",
+    uid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(workspace_ids)
+}
+
+/// Removes the user from all the collabs it is a member of, and returns these collabs.
+pub async fn delete_collab_memberships_of_user<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Vec<String>, AppError> {
+  let oids = sqlx::query_scalar!(
+    "DELETE FROM af_collab_member WHERE uid = $1 RETURNING oid",
+    uid
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(oids)
+}
+
+/// Removes the user from all the workspace groups it is a member of, and returns these groups.
+pub async fn delete_group_memberships_of_user<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Vec<Uuid>, AppError> {
+  let group_ids = sqlx::query_scalar!(
+    "DELETE FROM af_workspace_group_member WHERE uid = $1 RETURNING group_id",
+    uid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(group_ids)
+}
+
+/// Deletes the workspace exports the user started, and returns the keys of their archives.
+pub async fn delete_workspace_export_tasks_of_user<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Vec<String>, AppError> {
+  let object_keys = sqlx::query_scalar!(
+    "DELETE FROM af_workspace_export_task WHERE uid = $1 RETURNING object_key",
+    uid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(object_keys.into_iter().flatten().collect())
+}
+
+/// Deletes the user data exports of the user, and returns the keys of their archives.
+pub async fn delete_user_data_export_tasks_of_user<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Vec<String>, AppError> {
+  let object_keys = sqlx::query_scalar!(
+    "DELETE FROM af_user_data_export_task WHERE uid = $1 RETURNING object_key",
+    uid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(object_keys.into_iter().flatten().collect())
+}
+
+/// Deletes the user, in case the removal of the GoTrue user did not cascade to it.
+pub async fn delete_af_user<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query!("DELETE FROM af_user WHERE uid = $1", uid)
+    .execute(executor)
+    .await?;
+  Ok(())
+}
@@ -0,0 +1,114 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFViewTrashRow;
+
+/// Record that the views were moved to the trash of `deleted_by`. Views that are already recorded
+/// keep the time they were first moved to the trash.
+pub async fn insert_view_trash<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_ids: &[String],
+  deleted_by: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_view_trash (workspace_id, view_id, deleted_by)
+      SELECT $1, view_id, $3 FROM UNNEST($2::text[]) AS view_id
+      ON CONFLICT (workspace_id, view_id) DO NOTHING
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_ids)
+  .bind(deleted_by)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Record that the views were moved to the trash of `deleted_by` at the given times. Views that are
+/// already recorded keep the time they were first moved to the trash.
+pub async fn insert_view_trash_at<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  deleted_by: i64,
+  view_ids: &[String],
+  deleted_at: &[DateTime<Utc>],
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_view_trash (workspace_id, view_id, deleted_by, deleted_at)
+      SELECT $1, view_id, $2, deleted_at
+      FROM UNNEST($3::text[], $4::timestamptz[]) AS t(view_id, deleted_at)
+      ON CONFLICT (workspace_id, view_id) DO NOTHING
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(deleted_by)
+  .bind(view_ids)
+  .bind(deleted_at)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_view_trash<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_ids: &[String],
+) -> Result<Vec<AFViewTrashRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFViewTrashRow>(
+    r#"
+      SELECT workspace_id, view_id, deleted_by, deleted_at
+      FROM af_view_trash
+      WHERE workspace_id = $1 AND view_id = ANY($2)
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_ids)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the oldest views, across all workspaces, that were moved to the trash before `cutoff`.
+pub async fn select_expired_view_trash<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  cutoff: DateTime<Utc>,
+  limit: i64,
+) -> Result<Vec<AFViewTrashRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFViewTrashRow>(
+    r#"
+      SELECT workspace_id, view_id, deleted_by, deleted_at
+      FROM af_view_trash
+      WHERE deleted_at < $1
+      ORDER BY deleted_at
+      LIMIT $2
+    "#,
+  )
+  .bind(cutoff)
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn delete_view_trash<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_ids: &[String],
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      DELETE FROM af_view_trash
+      WHERE workspace_id = $1 AND view_id = ANY($2)
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_ids)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
@@ -0,0 +1,244 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::{AFDueWebhookDeliveryRow, AFWebhookDeliveryRow, AFWorkspaceWebhookRow};
+
+pub async fn insert_workspace_webhook<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  url: &str,
+  secret: &str,
+  events: &[String],
+) -> Result<AFWorkspaceWebhookRow, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceWebhookRow,
+    r#"
+      INSERT INTO af_workspace_webhook (workspace_id, url, secret, events)
+      VALUES ($1, $2, $3, $4)
+      RETURNING webhook_id, workspace_id, url, secret, events, created_at
+    "#,
+    workspace_id,
+    url,
+    secret,
+    events,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the webhooks of the workspace, ordered by creation time.
+pub async fn select_workspace_webhooks<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFWorkspaceWebhookRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFWorkspaceWebhookRow,
+    r#"
+      SELECT webhook_id, workspace_id, url, secret, events, created_at
+      FROM af_workspace_webhook
+      WHERE workspace_id = $1
+      ORDER BY created_at ASC
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn select_workspace_webhook_exists<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  webhook_id: &Uuid,
+) -> Result<bool, AppError> {
+  let exists = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS(
+        SELECT 1 FROM af_workspace_webhook WHERE workspace_id = $1 AND webhook_id = $2
+      )
+    "#,
+    workspace_id,
+    webhook_id,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(exists.unwrap_or(false))
+}
+
+/// Deletes the webhook together with its pending and dead deliveries. Returns false if there is
+/// no such webhook in the workspace.
+pub async fn delete_workspace_webhook<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  webhook_id: &Uuid,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    "DELETE FROM af_workspace_webhook WHERE workspace_id = $1 AND webhook_id = $2",
+    workspace_id,
+    webhook_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Queues a delivery of the event for every webhook of the workspace that is subscribed to it.
+/// Returns the number of queued deliveries, not counting the ones merged into a pending delivery
+/// with the same `coalesce_key`.
+pub async fn insert_webhook_deliveries<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  event: &str,
+  payload: &serde_json::Value,
+  coalesce_key: Option<&str>,
+) -> Result<u64, AppError> {
+  // A delivery with the same coalesce key that is neither sent nor being sent takes the payload
+  // instead of a new delivery being queued
+  let res = sqlx::query!(
+    r#"
+      WITH coalesced AS (
+        UPDATE af_webhook_delivery AS d
+        SET payload = $3
+        FROM af_workspace_webhook AS w
+        WHERE w.webhook_id = d.webhook_id
+          AND w.workspace_id = $1
+          AND d.event = $2
+          AND d.coalesce_key = $4
+          AND d.attempts = 0
+          AND d.dead_at IS NULL
+          AND d.next_attempt_at <= NOW()
+        RETURNING d.webhook_id
+      )
+      INSERT INTO af_webhook_delivery (webhook_id, event, payload, coalesce_key)
+      SELECT webhook_id, $2, $3, $4
+      FROM af_workspace_webhook
+      WHERE workspace_id = $1
+        AND (cardinality(events) = 0 OR $2 = ANY(events))
+        AND webhook_id NOT IN (SELECT webhook_id FROM coalesced)
+    "#,
+    workspace_id,
+    event,
+    payload,
+    coalesce_key,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected())
+}
+
+/// Claims up to `limit` deliveries that are due. A claimed delivery is not due again until
+/// `lease_until`, so that it is not delivered twice while it is in flight.
+pub async fn claim_due_webhook_deliveries<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  limit: i64,
+  lease_until: DateTime<Utc>,
+) -> Result<Vec<AFDueWebhookDeliveryRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFDueWebhookDeliveryRow,
+    r#"
+      UPDATE af_webhook_delivery AS d
+      SET next_attempt_at = $2
+      FROM af_workspace_webhook AS w
+      WHERE w.webhook_id = d.webhook_id
+        AND d.delivery_id IN (
+          SELECT delivery_id
+          FROM af_webhook_delivery
+          WHERE dead_at IS NULL AND next_attempt_at <= NOW()
+          ORDER BY next_attempt_at ASC
+          LIMIT $1
+          FOR UPDATE SKIP LOCKED
+        )
+      RETURNING d.delivery_id, w.workspace_id, w.url, w.secret, d.event, d.payload, d.attempts,
+        d.created_at
+    "#,
+    limit,
+    lease_until,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Removes a delivery that the endpoint accepted.
+pub async fn delete_webhook_delivery<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  delivery_id: i64,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    "DELETE FROM af_webhook_delivery WHERE delivery_id = $1",
+    delivery_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Records a failed attempt. The delivery is tried again at `next_attempt_at`, or becomes a dead
+/// letter when `next_attempt_at` is `None`.
+pub async fn update_webhook_delivery_failed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  delivery_id: i64,
+  error: &str,
+  next_attempt_at: Option<DateTime<Utc>>,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_webhook_delivery
+      SET attempts = attempts + 1,
+          last_error = $2,
+          next_attempt_at = COALESCE($3, next_attempt_at),
+          dead_at = CASE WHEN $3 IS NULL THEN NOW() ELSE NULL END
+      WHERE delivery_id = $1
+    "#,
+    delivery_id,
+    error,
+    next_attempt_at,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the dead letters of the webhook, most recent first.
+pub async fn select_webhook_dead_letters<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  webhook_id: &Uuid,
+) -> Result<Vec<AFWebhookDeliveryRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFWebhookDeliveryRow,
+    r#"
+      SELECT delivery_id, webhook_id, event, payload, attempts, last_error, dead_at, created_at
+      FROM af_webhook_delivery
+      WHERE webhook_id = $1 AND dead_at IS NOT NULL
+      ORDER BY dead_at DESC, delivery_id DESC
+    "#,
+    webhook_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Queues the dead letter for delivery again, with a fresh number of attempts. Returns false if
+/// the webhook has no such dead letter.
+pub async fn update_webhook_dead_letter_requeued<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  webhook_id: &Uuid,
+  delivery_id: i64,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE af_webhook_delivery
+      SET attempts = 0, next_attempt_at = NOW(), dead_at = NULL
+      WHERE webhook_id = $1 AND delivery_id = $2 AND dead_at IS NOT NULL
+    "#,
+    webhook_id,
+    delivery_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
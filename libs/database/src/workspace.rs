@@ -8,9 +8,11 @@ use std::{collections::HashMap, ops::DerefMut};
 use tracing::{event, instrument};
 use uuid::Uuid;
 
+use crate::index::delete_workspace_search_content;
 use crate::pg_row::{
   AFGlobalCommentRow, AFPermissionRow, AFReactionRow, AFUserProfileRow, AFWebUserColumn,
-  AFWorkspaceInvitationMinimal, AFWorkspaceMemberPermRow, AFWorkspaceMemberRow, AFWorkspaceRow,
+  AFWorkspaceInvitationMinimal, AFWorkspaceMemberPermRow, AFWorkspaceMemberRow,
+  AFWorkspacePendingInvitationRow, AFWorkspaceRow,
 };
 use crate::user::select_uid_from_email;
 use app_error::AppError;
@@ -823,28 +825,6 @@ pub async fn select_permission_from_role_id(
   Ok(permission)
 }
 
-pub async fn select_workspace_total_collab_bytes(
-  pool: &PgPool,
-  workspace_id: &Uuid,
-) -> Result<i64, AppError> {
-  let sum = sqlx::query_scalar!(
-    r#"
-    SELECT SUM(len) FROM af_collab WHERE workspace_id = $1
-    "#,
-    workspace_id
-  )
-  .fetch_one(pool)
-  .await?;
-
-  match sum {
-    Some(s) => Ok(s),
-    None => Err(AppError::RecordNotFound(format!(
-      "Failed to get total collab bytes for workspace_id: {}",
-      workspace_id
-    ))),
-  }
-}
-
 #[inline]
 pub async fn select_workspace_name_from_workspace_id(
   pool: &PgPool,
@@ -906,6 +886,122 @@ pub async fn select_workspace_pending_invitations(
   Ok(inv_id_by_email)
 }
 
+/// Record that the email of the invitation has been sent again.
+pub async fn update_workspace_invitation_sent<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  invite_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_workspace_invitation
+      SET last_sent_at = NOW(), sent_count = sent_count + 1
+      WHERE id = $1
+    "#,
+    invite_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the invitations of the workspace that have been neither accepted nor rejected, the
+/// most recent first.
+pub async fn select_workspace_pending_invitation_list<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFWorkspacePendingInvitationRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFWorkspacePendingInvitationRow,
+    r#"
+      SELECT
+        i.id AS invite_id,
+        i.invitee_email,
+        i.role_id,
+        i.inviter AS inviter_uid,
+        u.email AS inviter_email,
+        i.created_at,
+        i.last_sent_at,
+        i.sent_count
+      FROM af_workspace_invitation i
+      LEFT JOIN af_user u ON u.uid = i.inviter
+      WHERE i.workspace_id = $1 AND i.status = 0
+      ORDER BY i.created_at DESC
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn select_workspace_pending_invitation<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  invite_id: &Uuid,
+) -> Result<Option<AFWorkspacePendingInvitationRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspacePendingInvitationRow,
+    r#"
+      SELECT
+        i.id AS invite_id,
+        i.invitee_email,
+        i.role_id,
+        i.inviter AS inviter_uid,
+        u.email AS inviter_email,
+        i.created_at,
+        i.last_sent_at,
+        i.sent_count
+      FROM af_workspace_invitation i
+      LEFT JOIN af_user u ON u.uid = i.inviter
+      WHERE i.workspace_id = $1 AND i.id = $2 AND i.status = 0
+    "#,
+    workspace_id,
+    invite_id,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Deletes the invitation if it is still pending, so that its link no longer works. Returns the
+/// email of the invitee, or `None` if there is no such pending invitation in the workspace.
+pub async fn delete_workspace_pending_invitation<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  invite_id: &Uuid,
+) -> Result<Option<String>, AppError> {
+  let email = sqlx::query_scalar!(
+    r#"
+      DELETE FROM af_workspace_invitation
+      WHERE workspace_id = $1 AND id = $2 AND status = 0
+      RETURNING invitee_email
+    "#,
+    workspace_id,
+    invite_id,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(email)
+}
+
+/// Returns the ids of the pending invitations sent to the email, in any workspace.
+pub async fn select_pending_invitation_ids_for_email<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  email: &str,
+) -> Result<Vec<Uuid>, AppError> {
+  let ids = sqlx::query_scalar!(
+    r#"
+      SELECT id FROM af_workspace_invitation
+      WHERE invitee_email = $1 AND status = 0
+      ORDER BY created_at ASC
+    "#,
+    email,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(ids)
+}
+
 #[inline]
 pub async fn is_workspace_exist<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
@@ -977,6 +1073,7 @@ pub async fn upsert_workspace_settings(
     )
     .execute(tx.deref_mut())
     .await?;
+    delete_workspace_search_content(tx.deref_mut(), workspace_id).await?;
   }
 
   Ok(())
@@ -1397,3 +1494,79 @@ pub async fn select_user_is_invitee_for_workspace_invitation(
   .await?;
   res.map_or(Ok(false), Ok)
 }
+
+pub async fn upsert_user_view_order<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  workspace_id: &Uuid,
+  parent_view_id: &str,
+  ordered_child_ids: &[String],
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_user_view_order (uid, workspace_id, parent_view_id, ordered_child_ids)
+      VALUES ($1, $2, $3, $4)
+      ON CONFLICT (uid, workspace_id, parent_view_id) DO UPDATE
+      SET ordered_child_ids = EXCLUDED.ordered_child_ids,
+          updated_at = CURRENT_TIMESTAMP
+    "#,
+    uid,
+    workspace_id,
+    parent_view_id,
+    ordered_child_ids,
+  )
+  .execute(executor)
+  .await?;
+
+  Ok(())
+}
+
+pub async fn delete_user_view_order<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  workspace_id: &Uuid,
+  parent_view_id: &str,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_user_view_order
+      WHERE uid = $1 AND workspace_id = $2 AND parent_view_id = $3
+    "#,
+    uid,
+    workspace_id,
+    parent_view_id,
+  )
+  .execute(executor)
+  .await?;
+
+  Ok(())
+}
+
+/// Returns the custom order of the children of the given parent views, keyed by parent view id.
+/// Parent views without a custom order are not included.
+pub async fn select_user_view_orders<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  workspace_id: &Uuid,
+  parent_view_ids: &[String],
+) -> Result<HashMap<String, Vec<String>>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT parent_view_id, ordered_child_ids
+      FROM af_user_view_order
+      WHERE uid = $1 AND workspace_id = $2 AND parent_view_id = ANY($3)
+    "#,
+    uid,
+    workspace_id,
+    parent_view_ids,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| (row.parent_view_id, row.ordered_child_ids))
+      .collect(),
+  )
+}
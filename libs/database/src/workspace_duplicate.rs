@@ -0,0 +1,109 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFWorkspaceDuplicateTaskRow;
+
+pub async fn insert_workspace_duplicate_task<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  include_members: bool,
+) -> Result<AFWorkspaceDuplicateTaskRow, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceDuplicateTaskRow>(
+    r#"
+      INSERT INTO af_workspace_duplicate_task (workspace_id, uid, include_members)
+      VALUES ($1, $2, $3)
+      RETURNING task_id, workspace_id, uid, new_workspace_id, include_members, status,
+        total_objects, copied_objects, error, created_at, completed_at
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .bind(include_members)
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the duplicate task, if it was started by the user in the workspace.
+pub async fn select_workspace_duplicate_task<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  task_id: &Uuid,
+) -> Result<Option<AFWorkspaceDuplicateTaskRow>, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceDuplicateTaskRow>(
+    r#"
+      SELECT task_id, workspace_id, uid, new_workspace_id, include_members, status,
+        total_objects, copied_objects, error, created_at, completed_at
+      FROM af_workspace_duplicate_task
+      WHERE workspace_id = $1 AND uid = $2 AND task_id = $3
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .bind(task_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn update_workspace_duplicate_task_progress<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+  total_objects: i32,
+  copied_objects: i32,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_workspace_duplicate_task
+      SET total_objects = $2, copied_objects = $3
+      WHERE task_id = $1
+    "#,
+  )
+  .bind(task_id)
+  .bind(total_objects)
+  .bind(copied_objects)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn update_workspace_duplicate_task_completed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+  new_workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_workspace_duplicate_task
+      SET status = 1, new_workspace_id = $2, copied_objects = total_objects, completed_at = NOW()
+      WHERE task_id = $1
+    "#,
+  )
+  .bind(task_id)
+  .bind(new_workspace_id)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn update_workspace_duplicate_task_failed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+  error: &str,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_workspace_duplicate_task
+      SET status = 2, error = $2, completed_at = NOW()
+      WHERE task_id = $1
+    "#,
+  )
+  .bind(task_id)
+  .bind(error)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
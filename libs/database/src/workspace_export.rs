@@ -0,0 +1,110 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFWorkspaceExportTaskRow;
+
+pub async fn insert_workspace_export_task<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<AFWorkspaceExportTaskRow, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceExportTaskRow>(
+    r#"
+      INSERT INTO af_workspace_export_task (workspace_id, uid)
+      VALUES ($1, $2)
+      RETURNING task_id, workspace_id, uid, status, object_key, file_size, error, created_at,
+        completed_at
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the export task, if it was started by the user in the workspace.
+pub async fn select_workspace_export_task<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  task_id: &Uuid,
+) -> Result<Option<AFWorkspaceExportTaskRow>, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceExportTaskRow>(
+    r#"
+      SELECT task_id, workspace_id, uid, status, object_key, file_size, error, created_at,
+        completed_at
+      FROM af_workspace_export_task
+      WHERE workspace_id = $1 AND uid = $2 AND task_id = $3
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .bind(task_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn update_workspace_export_task_completed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+  object_key: &str,
+  file_size: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_workspace_export_task
+      SET status = 1, object_key = $2, file_size = $3, completed_at = NOW()
+      WHERE task_id = $1
+    "#,
+  )
+  .bind(task_id)
+  .bind(object_key)
+  .bind(file_size)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn update_workspace_export_task_failed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+  error: &str,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_workspace_export_task
+      SET status = 2, error = $2, completed_at = NOW()
+      WHERE task_id = $1
+    "#,
+  )
+  .bind(task_id)
+  .bind(error)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Deletes the export tasks of the workspace created before `created_before`, and returns the
+/// keys of the archives of the deleted tasks.
+pub async fn delete_workspace_export_tasks_before<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  created_before: DateTime<Utc>,
+) -> Result<Vec<String>, AppError> {
+  let object_keys: Vec<Option<String>> = sqlx::query_scalar(
+    r#"
+      DELETE FROM af_workspace_export_task
+      WHERE workspace_id = $1 AND created_at < $2
+      RETURNING object_key
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(created_before)
+  .fetch_all(executor)
+  .await?;
+  Ok(object_keys.into_iter().flatten().collect())
+}
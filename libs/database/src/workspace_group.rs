@@ -0,0 +1,302 @@
+use app_error::AppError;
+use database_entity::dto::AFAccessLevel;
+use futures_util::stream::{BoxStream, StreamExt};
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::{AFCollabGroupAccessLevelRow, AFWorkspaceGroupRow};
+
+/// Returns `None` if the workspace already has a group with the same name.
+pub async fn insert_workspace_group<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  name: &str,
+) -> Result<Option<AFWorkspaceGroupRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceGroupRow,
+    r#"
+      INSERT INTO af_workspace_group (workspace_id, name)
+      VALUES ($1, $2)
+      ON CONFLICT (workspace_id, name) DO NOTHING
+      RETURNING group_id, workspace_id, name, created_at
+    "#,
+    workspace_id,
+    name,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn select_workspace_group<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+) -> Result<Option<AFWorkspaceGroupRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceGroupRow,
+    r#"
+      SELECT group_id, workspace_id, name, created_at
+      FROM af_workspace_group
+      WHERE workspace_id = $1 AND group_id = $2
+    "#,
+    workspace_id,
+    group_id,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the groups of the workspace, ordered by creation time.
+pub async fn select_workspace_groups<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFWorkspaceGroupRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFWorkspaceGroupRow,
+    r#"
+      SELECT group_id, workspace_id, name, created_at
+      FROM af_workspace_group
+      WHERE workspace_id = $1
+      ORDER BY created_at ASC
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Deletes the group together with its members and the access it was granted on collabs.
+/// Returns false if there is no such group in the workspace.
+pub async fn delete_workspace_group<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    "DELETE FROM af_workspace_group WHERE workspace_id = $1 AND group_id = $2",
+    workspace_id,
+    group_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Returns false if the user was already a member of the group.
+pub async fn insert_workspace_group_member<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  group_id: &Uuid,
+  uid: i64,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_group_member (group_id, uid)
+      VALUES ($1, $2)
+      ON CONFLICT (group_id, uid) DO NOTHING
+    "#,
+    group_id,
+    uid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Returns false if the user was not a member of the group.
+pub async fn delete_workspace_group_member<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  group_id: &Uuid,
+  uid: i64,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    "DELETE FROM af_workspace_group_member WHERE group_id = $1 AND uid = $2",
+    group_id,
+    uid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Removes the user from every group of the workspace, once it left the workspace. Returns the
+/// groups the user was removed from.
+pub async fn delete_workspace_group_member_of_workspace<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<Vec<Uuid>, AppError> {
+  let group_ids = sqlx::query_scalar!(
+    r#"
+      DELETE FROM af_workspace_group_member m
+      USING af_workspace_group g
+      WHERE m.group_id = g.group_id AND g.workspace_id = $1 AND m.uid = $2
+      RETURNING m.group_id
+    "#,
+    workspace_id,
+    uid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(group_ids)
+}
+
+/// Returns the uids of the members of each of the groups, ordered by the time they joined.
+pub async fn select_workspace_group_member_uids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  group_ids: &[Uuid],
+) -> Result<Vec<(Uuid, i64)>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT group_id, uid
+      FROM af_workspace_group_member
+      WHERE group_id = ANY($1)
+      ORDER BY created_at ASC, uid ASC
+    "#,
+    group_ids,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| (row.group_id, row.uid))
+      .collect(),
+  )
+}
+
+/// Streams the (uid, group id) pair of every group membership.
+pub fn select_workspace_group_member_stream(
+  pg_pool: &PgPool,
+) -> BoxStream<'_, sqlx::Result<(i64, Uuid)>> {
+  sqlx::query!("SELECT uid, group_id FROM af_workspace_group_member")
+    .fetch(pg_pool)
+    .map(|row| row.map(|row| (row.uid, row.group_id)))
+    .boxed()
+}
+
+/// Grant the group the access level on the collab, replacing the level it held before.
+pub async fn upsert_collab_group_member<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  group_id: &Uuid,
+  oid: &str,
+  access_level: AFAccessLevel,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_collab_group_member (group_id, oid, permission_id)
+      SELECT $1, $2, id FROM af_permissions WHERE access_level = $3
+      ON CONFLICT (group_id, oid)
+      DO UPDATE SET permission_id = excluded.permission_id
+    "#,
+    group_id,
+    oid,
+    access_level as i32,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns false if the group held no access on the collab.
+pub async fn delete_collab_group_member<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  group_id: &Uuid,
+  oid: &str,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    "DELETE FROM af_collab_group_member WHERE group_id = $1 AND oid = $2",
+    group_id,
+    oid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Remove the access of all the groups on the collab. Returns the ids of those groups.
+pub async fn delete_collab_group_members<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+) -> Result<Vec<Uuid>, AppError> {
+  let group_ids = sqlx::query_scalar!(
+    r#"
+      DELETE FROM af_collab_group_member WHERE oid = $1
+      RETURNING group_id
+    "#,
+    oid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(group_ids)
+}
+
+/// Returns the collabs the group holds access on.
+pub async fn select_collab_group_member_oids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  group_id: &Uuid,
+) -> Result<Vec<String>, AppError> {
+  let oids = sqlx::query_scalar!(
+    "SELECT oid FROM af_collab_group_member WHERE group_id = $1",
+    group_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(oids)
+}
+
+/// Returns the access level each group of the workspace the user belongs to holds on the collab,
+/// highest level first.
+pub async fn select_collab_group_access_levels_for_user<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  oid: &str,
+) -> Result<Vec<(Uuid, AFAccessLevel)>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT cgm.group_id, p.access_level
+      FROM af_collab_group_member cgm
+      JOIN af_workspace_group g ON g.group_id = cgm.group_id
+      JOIN af_workspace_group_member wgm ON wgm.group_id = cgm.group_id
+      JOIN af_permissions p ON p.id = cgm.permission_id
+      WHERE g.workspace_id = $1 AND wgm.uid = $2 AND cgm.oid = $3
+      ORDER BY p.access_level DESC, cgm.group_id ASC
+    "#,
+    workspace_id,
+    uid,
+    oid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| (row.group_id, AFAccessLevel::from(row.access_level)))
+      .collect(),
+  )
+}
+
+/// Streams the access level every group holds on each collab.
+pub fn select_collab_group_access_level(
+  pg_pool: &PgPool,
+) -> BoxStream<'_, sqlx::Result<AFCollabGroupAccessLevelRow>> {
+  sqlx::query_as!(
+    AFCollabGroupAccessLevelRow,
+    r#"
+      SELECT group_id, oid, access_level
+      FROM af_collab_group_member
+      INNER JOIN af_permissions
+        ON af_collab_group_member.permission_id = af_permissions.id
+    "#
+  )
+  .fetch(pg_pool)
+}
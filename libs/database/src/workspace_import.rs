@@ -0,0 +1,107 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFWorkspaceImportTaskRow;
+
+pub async fn insert_workspace_import_task<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  view_id: &str,
+  total_pages: i32,
+) -> Result<AFWorkspaceImportTaskRow, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceImportTaskRow>(
+    r#"
+      INSERT INTO af_workspace_import_task (workspace_id, uid, view_id, total_pages)
+      VALUES ($1, $2, $3, $4)
+      RETURNING task_id, workspace_id, uid, view_id, status, total_pages, imported_pages, error,
+        created_at, completed_at
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .bind(view_id)
+  .bind(total_pages)
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the import task, if it was started by the user in the workspace.
+pub async fn select_workspace_import_task<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  task_id: &Uuid,
+) -> Result<Option<AFWorkspaceImportTaskRow>, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceImportTaskRow>(
+    r#"
+      SELECT task_id, workspace_id, uid, view_id, status, total_pages, imported_pages, error,
+        created_at, completed_at
+      FROM af_workspace_import_task
+      WHERE workspace_id = $1 AND uid = $2 AND task_id = $3
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .bind(task_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn update_workspace_import_task_progress<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+  imported_pages: i32,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_workspace_import_task
+      SET imported_pages = $2
+      WHERE task_id = $1
+    "#,
+  )
+  .bind(task_id)
+  .bind(imported_pages)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn update_workspace_import_task_completed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_workspace_import_task
+      SET status = 1, imported_pages = total_pages, completed_at = NOW()
+      WHERE task_id = $1
+    "#,
+  )
+  .bind(task_id)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn update_workspace_import_task_failed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+  error: &str,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_workspace_import_task
+      SET status = 2, error = $2, completed_at = NOW()
+      WHERE task_id = $1
+    "#,
+  )
+  .bind(task_id)
+  .bind(error)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
@@ -0,0 +1,198 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFWorkspaceInviteLinkRow;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_workspace_invite_link<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  token: &str,
+  role_id: i32,
+  allowed_domains: &[String],
+  max_joins_per_hour: i32,
+  expires_at: Option<DateTime<Utc>>,
+  created_by: i64,
+) -> Result<AFWorkspaceInviteLinkRow, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceInviteLinkRow,
+    r#"
+      INSERT INTO af_workspace_invite_link
+        (workspace_id, token, role_id, allowed_domains, max_joins_per_hour, expires_at, created_by)
+      VALUES ($1, $2, $3, $4, $5, $6, $7)
+      RETURNING id, token, workspace_id, role_id, allowed_domains, max_joins_per_hour,
+        expires_at, created_by, created_at, rotated_at
+    "#,
+    workspace_id,
+    token,
+    role_id,
+    allowed_domains,
+    max_joins_per_hour,
+    expires_at,
+    created_by,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the invite links of the workspace, expired or not, the most recent first.
+pub async fn select_workspace_invite_links<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFWorkspaceInviteLinkRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFWorkspaceInviteLinkRow,
+    r#"
+      SELECT id, token, workspace_id, role_id, allowed_domains, max_joins_per_hour,
+        expires_at, created_by, created_at, rotated_at
+      FROM af_workspace_invite_link
+      WHERE workspace_id = $1
+      ORDER BY created_at DESC
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the link of the token and locks it until the end of the transaction, so that the users
+/// joining through the link at the same time are counted one after the other.
+pub async fn select_workspace_invite_link_by_token_for_update<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  token: &str,
+) -> Result<Option<AFWorkspaceInviteLinkRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceInviteLinkRow,
+    r#"
+      SELECT id, token, workspace_id, role_id, allowed_domains, max_joins_per_hour,
+        expires_at, created_by, created_at, rotated_at
+      FROM af_workspace_invite_link
+      WHERE token = $1
+      FOR UPDATE
+    "#,
+    token,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Replaces the role, the allowed domains and the limits of the link. Returns `None` if there is
+/// no such link in the workspace.
+pub async fn update_workspace_invite_link<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  link_id: &Uuid,
+  role_id: i32,
+  allowed_domains: &[String],
+  max_joins_per_hour: i32,
+  expires_at: Option<DateTime<Utc>>,
+) -> Result<Option<AFWorkspaceInviteLinkRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceInviteLinkRow,
+    r#"
+      UPDATE af_workspace_invite_link
+      SET role_id = $3, allowed_domains = $4, max_joins_per_hour = $5, expires_at = $6
+      WHERE workspace_id = $1 AND id = $2
+      RETURNING id, token, workspace_id, role_id, allowed_domains, max_joins_per_hour,
+        expires_at, created_by, created_at, rotated_at
+    "#,
+    workspace_id,
+    link_id,
+    role_id,
+    allowed_domains,
+    max_joins_per_hour,
+    expires_at,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Replaces the token of the link, the previous token no longer resolves to the link. Returns
+/// `None` if there is no such link in the workspace.
+pub async fn update_workspace_invite_link_token<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  link_id: &Uuid,
+  token: &str,
+) -> Result<Option<AFWorkspaceInviteLinkRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceInviteLinkRow,
+    r#"
+      UPDATE af_workspace_invite_link
+      SET token = $3, rotated_at = CURRENT_TIMESTAMP
+      WHERE workspace_id = $1 AND id = $2
+      RETURNING id, token, workspace_id, role_id, allowed_domains, max_joins_per_hour,
+        expires_at, created_by, created_at, rotated_at
+    "#,
+    workspace_id,
+    link_id,
+    token,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns false if there is no such link in the workspace. The users that joined through the
+/// link stay in the workspace.
+pub async fn delete_workspace_invite_link<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  link_id: &Uuid,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    "DELETE FROM af_workspace_invite_link WHERE workspace_id = $1 AND id = $2",
+    workspace_id,
+    link_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Returns how many users joined the workspace through the link since `since`.
+pub async fn select_workspace_invite_link_join_count<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  link_id: &Uuid,
+  since: DateTime<Utc>,
+) -> Result<i64, AppError> {
+  let count = sqlx::query_scalar!(
+    r#"
+      SELECT COUNT(*) AS "count!" FROM af_workspace_invite_link_join
+      WHERE link_id = $1 AND joined_at >= $2
+    "#,
+    link_id,
+    since,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(count)
+}
+
+pub async fn insert_workspace_invite_link_join<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  link_id: &Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_invite_link_join (link_id, uid)
+      VALUES ($1, $2)
+      ON CONFLICT (link_id, uid) DO UPDATE SET joined_at = CURRENT_TIMESTAMP
+    "#,
+    link_id,
+    uid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
@@ -0,0 +1,147 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFWorkspaceOwnershipTransferRow;
+
+/// Request the transfer of the workspace from `from_uid` to `to_uid`, replacing the transfer that
+/// was pending.
+pub async fn upsert_workspace_ownership_transfer<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  from_uid: i64,
+  to_uid: i64,
+  expires_at: DateTime<Utc>,
+) -> Result<AFWorkspaceOwnershipTransferRow, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceOwnershipTransferRow>(
+    r#"
+      WITH transfer AS (
+        INSERT INTO af_workspace_ownership_transfer (workspace_id, from_uid, to_uid, expires_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (workspace_id) DO UPDATE
+        SET
+          from_uid = EXCLUDED.from_uid,
+          to_uid = EXCLUDED.to_uid,
+          created_at = CURRENT_TIMESTAMP,
+          expires_at = EXCLUDED.expires_at
+        RETURNING workspace_id, from_uid, to_uid, created_at, expires_at
+      )
+      SELECT
+        t.workspace_id,
+        t.from_uid,
+        f.email AS from_email,
+        t.to_uid,
+        u.email AS to_email,
+        t.created_at,
+        t.expires_at
+      FROM transfer t
+      JOIN af_user f ON f.uid = t.from_uid
+      JOIN af_user u ON u.uid = t.to_uid
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(from_uid)
+  .bind(to_uid)
+  .bind(expires_at)
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the pending transfer of the workspace, expired or not.
+pub async fn select_workspace_ownership_transfer<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Option<AFWorkspaceOwnershipTransferRow>, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceOwnershipTransferRow>(
+    r#"
+      SELECT
+        t.workspace_id,
+        t.from_uid,
+        f.email AS from_email,
+        t.to_uid,
+        u.email AS to_email,
+        t.created_at,
+        t.expires_at
+      FROM af_workspace_ownership_transfer t
+      JOIN af_user f ON f.uid = t.from_uid
+      JOIN af_user u ON u.uid = t.to_uid
+      WHERE t.workspace_id = $1
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Deletes the pending transfer of the workspace and returns it. Run in a transaction, the
+/// transfer stays locked until the transaction ends.
+pub async fn delete_workspace_ownership_transfer<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Option<AFWorkspaceOwnershipTransferRow>, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceOwnershipTransferRow>(
+    r#"
+      WITH transfer AS (
+        DELETE FROM af_workspace_ownership_transfer
+        WHERE workspace_id = $1
+        RETURNING workspace_id, from_uid, to_uid, created_at, expires_at
+      )
+      SELECT
+        t.workspace_id,
+        t.from_uid,
+        f.email AS from_email,
+        t.to_uid,
+        u.email AS to_email,
+        t.created_at,
+        t.expires_at
+      FROM transfer t
+      JOIN af_user f ON f.uid = t.from_uid
+      JOIN af_user u ON u.uid = t.to_uid
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Make `to_uid` the owner of the workspace and `from_uid` one of its members. Returns false, and
+/// changes nothing, if either of them is not a member of the workspace.
+pub async fn update_workspace_owner_and_demote<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  from_uid: i64,
+  to_uid: i64,
+) -> Result<bool, AppError> {
+  let res = sqlx::query(
+    r#"
+      WITH members AS (
+        SELECT COUNT(*) AS count FROM af_workspace_member
+        WHERE workspace_id = $1 AND uid IN ($2, $3)
+      ),
+      owner AS (
+        UPDATE af_workspace SET owner_uid = $3
+        WHERE workspace_id = $1 AND (SELECT count FROM members) = 2
+      )
+      UPDATE af_workspace_member
+      SET
+        role_id = CASE
+          WHEN uid = $3 THEN (SELECT id FROM af_roles WHERE name = 'Owner')
+          ELSE (SELECT id FROM af_roles WHERE name = 'Member')
+        END,
+        updated_at = NOW()
+      WHERE workspace_id = $1
+        AND uid IN ($2, $3)
+        AND (SELECT count FROM members) = 2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(from_uid)
+  .bind(to_uid)
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() == 2)
+}
@@ -0,0 +1,180 @@
+use app_error::AppError;
+use futures_util::stream::BoxStream;
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::{AFWorkspaceMemberCapabilityRow, AFWorkspaceRoleRow};
+
+/// Returns `None` if the workspace already has a role with the same name.
+pub async fn insert_workspace_role<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  name: &str,
+  capabilities: &[String],
+) -> Result<Option<AFWorkspaceRoleRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceRoleRow,
+    r#"
+      INSERT INTO af_workspace_role (workspace_id, name, capabilities)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (workspace_id, name) DO NOTHING
+      RETURNING role_id, workspace_id, name, capabilities, created_at
+    "#,
+    workspace_id,
+    name,
+    capabilities,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn select_workspace_role<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  role_id: &Uuid,
+) -> Result<Option<AFWorkspaceRoleRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceRoleRow,
+    r#"
+      SELECT role_id, workspace_id, name, capabilities, created_at
+      FROM af_workspace_role
+      WHERE workspace_id = $1 AND role_id = $2
+    "#,
+    workspace_id,
+    role_id,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the roles of the workspace, ordered by creation time.
+pub async fn select_workspace_roles<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFWorkspaceRoleRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFWorkspaceRoleRow,
+    r#"
+      SELECT role_id, workspace_id, name, capabilities, created_at
+      FROM af_workspace_role
+      WHERE workspace_id = $1
+      ORDER BY created_at ASC
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns `None` if there is no such role in the workspace, or if another role of the workspace
+/// already has the name.
+pub async fn update_workspace_role<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  role_id: &Uuid,
+  name: &str,
+  capabilities: &[String],
+) -> Result<Option<AFWorkspaceRoleRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceRoleRow,
+    r#"
+      UPDATE af_workspace_role
+      SET name = $3, capabilities = $4
+      WHERE workspace_id = $1 AND role_id = $2
+        AND NOT EXISTS (
+          SELECT 1 FROM af_workspace_role
+          WHERE workspace_id = $1 AND name = $3 AND role_id <> $2
+        )
+      RETURNING role_id, workspace_id, name, capabilities, created_at
+    "#,
+    workspace_id,
+    role_id,
+    name,
+    capabilities,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Deletes the role, the members it was assigned to are left without a custom role.
+/// Returns false if there is no such role in the workspace.
+pub async fn delete_workspace_role<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  role_id: &Uuid,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    "DELETE FROM af_workspace_role WHERE workspace_id = $1 AND role_id = $2",
+    workspace_id,
+    role_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Returns the uids of the members each of the roles is assigned to.
+pub async fn select_workspace_role_member_uids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  role_ids: &[Uuid],
+) -> Result<Vec<(Uuid, i64)>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT custom_role_id AS "custom_role_id!", uid
+      FROM af_workspace_member
+      WHERE custom_role_id = ANY($1)
+      ORDER BY uid ASC
+    "#,
+    role_ids,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| (row.custom_role_id, row.uid))
+      .collect(),
+  )
+}
+
+/// Assign the custom role to the member, replacing the one it held before. `None` removes the
+/// custom role of the member. Returns false if the user is not a member of the workspace.
+pub async fn update_workspace_member_custom_role<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  role_id: Option<&Uuid>,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE af_workspace_member
+      SET custom_role_id = $3
+      WHERE workspace_id = $1 AND uid = $2
+    "#,
+    workspace_id,
+    uid,
+    role_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Streams the capabilities granted to every workspace member that has a custom role.
+pub fn select_workspace_member_capability_stream(
+  pg_pool: &PgPool,
+) -> BoxStream<'_, sqlx::Result<AFWorkspaceMemberCapabilityRow>> {
+  sqlx::query_as!(
+    AFWorkspaceMemberCapabilityRow,
+    r#"
+      SELECT m.uid, m.workspace_id, r.capabilities
+      FROM af_workspace_member m
+      JOIN af_workspace_role r ON r.role_id = m.custom_role_id
+    "#,
+  )
+  .fetch(pg_pool)
+}
@@ -0,0 +1,180 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFWorkspaceSsoRow;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn upsert_workspace_sso<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  provider_id: &Uuid,
+  entity_id: &str,
+  metadata_url: Option<&str>,
+  domains: &[String],
+  default_role_id: i32,
+  enforced: bool,
+) -> Result<AFWorkspaceSsoRow, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceSsoRow,
+    r#"
+      INSERT INTO af_workspace_sso
+        (workspace_id, provider_id, entity_id, metadata_url, domains, default_role_id, enforced)
+      VALUES ($1, $2, $3, $4, $5, $6, $7)
+      ON CONFLICT (workspace_id)
+      DO UPDATE SET
+        provider_id = EXCLUDED.provider_id,
+        entity_id = EXCLUDED.entity_id,
+        metadata_url = EXCLUDED.metadata_url,
+        domains = EXCLUDED.domains,
+        default_role_id = EXCLUDED.default_role_id,
+        enforced = EXCLUDED.enforced,
+        updated_at = NOW()
+      RETURNING *
+    "#,
+    workspace_id,
+    provider_id,
+    entity_id,
+    metadata_url,
+    domains,
+    default_role_id,
+    enforced,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn select_workspace_sso<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Option<AFWorkspaceSsoRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceSsoRow,
+    "SELECT * FROM af_workspace_sso WHERE workspace_id = $1",
+    workspace_id,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn select_workspace_sso_by_provider<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  provider_id: &Uuid,
+) -> Result<Option<AFWorkspaceSsoRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceSsoRow,
+    "SELECT * FROM af_workspace_sso WHERE provider_id = $1",
+    provider_id,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn delete_workspace_sso<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    "DELETE FROM af_workspace_sso WHERE workspace_id = $1",
+    workspace_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the provider the members of the workspace must sign in with, and the uuid of the
+/// owner of the workspace, if the workspace enforces single sign-on.
+pub async fn select_enforced_workspace_sso<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Option<(Uuid, Option<Uuid>)>, AppError> {
+  let row = sqlx::query!(
+    r#"
+      SELECT s.provider_id, u.uuid AS "owner_uuid?"
+      FROM af_workspace_sso s
+      JOIN af_workspace w ON w.workspace_id = s.workspace_id
+      LEFT JOIN af_user u ON u.uid = w.owner_uid
+      WHERE s.workspace_id = $1 AND s.enforced
+    "#,
+    workspace_id,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row.map(|row| (row.provider_id, row.owner_uuid)))
+}
+
+/// Returns the workspaces that enforce single sign-on in which the user is a member other than
+/// the owner, with the provider each of them requires.
+pub async fn select_enforced_workspace_sso_of_member<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  user_uuid: &Uuid,
+) -> Result<Vec<(Uuid, Uuid)>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT s.workspace_id, s.provider_id
+      FROM af_workspace_sso s
+      JOIN af_workspace w ON w.workspace_id = s.workspace_id
+      JOIN af_workspace_member m ON m.workspace_id = s.workspace_id
+      JOIN af_user u ON u.uid = m.uid
+      WHERE s.enforced AND u.uuid = $1 AND w.owner_uid IS DISTINCT FROM m.uid
+    "#,
+    user_uuid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| (row.workspace_id, row.provider_id))
+      .collect(),
+  )
+}
+
+pub async fn select_is_workspace_member_by_uuid<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  user_uuid: &Uuid,
+) -> Result<bool, AppError> {
+  let is_member = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS (
+        SELECT 1
+        FROM af_workspace_member m
+        JOIN af_user u ON u.uid = m.uid
+        WHERE m.workspace_id = $1 AND u.uuid = $2
+      )
+    "#,
+    workspace_id,
+    user_uuid,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(is_member.unwrap_or(false))
+}
+
+/// Add the user to the workspace with the role, unless it already is a member. Returns true if
+/// the user was added.
+pub async fn insert_workspace_member_if_not_exists<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  role_id: i32,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_member (workspace_id, uid, role_id)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (workspace_id, uid) DO NOTHING
+    "#,
+    workspace_id,
+    uid,
+    role_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,65 @@
+use crate::pg_row::AFWorkspaceUsageRow;
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+/// Records that the user has just edited a collab of the workspace.
+pub async fn upsert_workspace_editor_activity<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_workspace_editor_activity (workspace_id, uid, last_edited_at)
+      VALUES ($1, $2, CURRENT_TIMESTAMP)
+      ON CONFLICT (workspace_id, uid) DO UPDATE
+      SET last_edited_at = EXCLUDED.last_edited_at
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Aggregates the activity of the workspace. The editors are counted when they edited a collab of
+/// the workspace since `active_since`.
+pub async fn select_workspace_usage<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  active_since: DateTime<Utc>,
+) -> Result<AFWorkspaceUsageRow, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceUsageRow>(
+    r#"
+      SELECT
+        (
+          SELECT COUNT(*) FROM af_collab
+          WHERE workspace_id = $1 AND partition_key = 0 AND deleted_at IS NULL
+        ) AS document_count,
+        (
+          SELECT COALESCE(SUM(len), 0)::BIGINT FROM af_collab WHERE workspace_id = $1
+        ) AS total_document_size,
+        (
+          SELECT COUNT(*) FROM af_workspace_editor_activity
+          WHERE workspace_id = $1 AND last_edited_at >= $2
+        ) AS active_editor_count,
+        (
+          SELECT COALESCE(SUM(collab_bytes + blob_bytes), 0)::BIGINT
+          FROM af_workspace_storage_usage
+          WHERE workspace_id = $1
+        ) AS storage_bytes,
+        (
+          SELECT COUNT(*) FROM af_published_collab
+          WHERE workspace_id = $1 AND (expires_at IS NULL OR expires_at > NOW())
+        ) AS publish_count
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(active_since)
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
@@ -39,3 +39,8 @@ pub struct SAMLAttribute {
 pub struct SSODomain {
   pub domain: String,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct SSOUrl {
+  pub url: String,
+}
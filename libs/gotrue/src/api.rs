@@ -1,7 +1,7 @@
 use super::grant::Grant;
 use crate::params::{
   AdminDeleteUserParams, AdminUserParams, CreateSSOProviderParams, GenerateLinkParams,
-  GenerateLinkResponse, InviteUserParams, MagicLinkParams,
+  GenerateLinkResponse, InviteUserParams, MagicLinkParams, SSOParams,
 };
 use anyhow::Context;
 use gotrue_entity::dto::{
@@ -9,7 +9,7 @@ use gotrue_entity::dto::{
   UpdateGotrueUserParams, User,
 };
 use gotrue_entity::error::{GoTrueError, GoTrueErrorSerde, GotrueClientError};
-use gotrue_entity::sso::{SSOProvider, SSOProviders};
+use gotrue_entity::sso::{SSOProvider, SSOProviders, SSOUrl};
 use infra::reqwest::{check_response, from_body, from_response};
 use reqwest::{Method, RequestBuilder};
 use tracing::event;
@@ -247,6 +247,20 @@ impl Client {
     check_gotrue_result(resp).await
   }
 
+  /// Returns the url of the identity provider the user signs in with. Once signed in, the
+  /// identity provider posts the SAML assertion back to GoTrue, which validates it against the
+  /// metadata of the provider and redirects to `redirect_to` with the tokens of the user.
+  pub async fn sso(&self, sso_params: &SSOParams) -> Result<SSOUrl, GoTrueError> {
+    let url = format!("{}/sso", self.base_url);
+    let resp = self
+      .client
+      .request(Method::POST, &url)
+      .json(sso_params)
+      .send()
+      .await?;
+    to_gotrue_result(resp).await
+  }
+
   pub async fn admin_list_sso_providers(
     &self,
     access_token: &str,
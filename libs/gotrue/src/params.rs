@@ -121,3 +121,17 @@ pub struct CreateSSOProviderParams {
   pub domains: Vec<String>,
   pub attribute_mapping: serde_json::Value,
 }
+
+/// Starts a single sign-on with the identity provider, found by its id or by the domain of the
+/// email of the user.
+#[derive(Debug, Serialize, Default)]
+pub struct SSOParams {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub provider_id: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub domain: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub redirect_to: Option<String>,
+  /// Return the url of the identity provider instead of redirecting to it.
+  pub skip_http_redirect: bool,
+}
@@ -0,0 +1,52 @@
+//! The endpoints reserved to the administrators of the server.
+
+use collab_entity::CollabType;
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct QueryRealtimeHealth {
+  /// The most collabs to return, the ones with the most sessions first. 100 by default, and at
+  /// most 1000.
+  pub limit: Option<usize>,
+}
+
+/// The state of the realtime collaboration on the server that answered the request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimeHealth {
+  /// The number of websocket connections.
+  pub connected_users: i64,
+  /// The number of connected users with at least one collab open.
+  pub editing_users: i64,
+  pub open_collabs: i64,
+  /// The number of updates applied since the server started.
+  pub applied_updates: i64,
+  /// The number of updates that failed to be applied since the server started.
+  pub failed_updates: i64,
+  /// The updates applied per second, over the last minute.
+  pub updates_per_second: f64,
+  /// How long it took on average for the updates sent over the last minute to reach the other
+  /// subscribers of their collab, in milliseconds.
+  pub avg_broadcast_lag_ms: f64,
+  pub max_broadcast_lag_ms: u64,
+  /// The workspaces with at least one open session, the ones with the most users first.
+  pub workspaces: Vec<WorkspaceRealtimeHealth>,
+  /// The collabs with the most open sessions.
+  pub collabs: Vec<CollabRealtimeHealth>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceRealtimeHealth {
+  pub workspace_id: String,
+  /// The number of users with a collab of the workspace open.
+  pub connected_users: usize,
+  pub sessions: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabRealtimeHealth {
+  pub object_id: String,
+  pub workspace_id: String,
+  pub collab_type: CollabType,
+  /// A user has a session for each device the collab is open on.
+  pub sessions: usize,
+}
@@ -31,6 +31,65 @@ pub struct SummarizeRowResponse {
   pub text: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SummarizeDocumentParams {
+  /// The object id of the Document collab.
+  pub object_id: String,
+  /// The length the summary should not exceed, in words. The server picks a default when `None`.
+  #[serde(default)]
+  pub max_words: Option<u32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SummarizeDocumentResponse {
+  pub summary: String,
+  /// The tokens of the language model the summary consumed, which count towards the AI usage of
+  /// the workspace.
+  pub tokens_used: i64,
+  /// Whether only the beginning of the document was summarized, because the document is too
+  /// long.
+  pub truncated: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TranslateDocumentParams {
+  /// The object id of the Document collab, which is the id of its view.
+  pub object_id: String,
+  /// The language to translate the document into, such as `French` or `pt-BR`.
+  pub target_language: String,
+  /// Whether to create a view holding the translation, next to the view of the document.
+  #[serde(default)]
+  pub create_view: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TranslateDocumentResponse {
+  pub markdown: String,
+  /// The view holding the translation, when one was asked for.
+  pub view_id: Option<String>,
+  /// The tokens of the language model the translation consumed, which count towards the AI usage
+  /// of the workspace.
+  pub tokens_used: i64,
+  /// Whether only the beginning of the document was translated, because the document is too
+  /// long.
+  pub truncated: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorkspaceChatParams {
+  pub question: String,
+}
+
+/// A view of the workspace the answer of the workspace chat is grounded in. The answer cites the
+/// sources with their position in the list, starting at 1, like `[1]`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorkspaceChatSource {
+  pub view_id: String,
+  /// The lower the better, see [crate::dto::search_dto::SearchDocumentResponseItem::score].
+  pub score: f64,
+  pub preview: Option<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CompleteTextParams {
   pub text: String,
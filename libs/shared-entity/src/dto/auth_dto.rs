@@ -71,3 +71,43 @@ pub struct DeleteUserQuery {
   pub provider_access_token: Option<String>,
   pub provider_refresh_token: Option<String>,
 }
+
+/// Marks the account of the user for deletion. The workspaces the user owns are deleted with the
+/// account, unless they are handed over to another of their members.
+#[derive(Debug, Default, Clone, serde::Deserialize, serde::Serialize)]
+pub struct RequestAccountDeletionParams {
+  #[serde(default)]
+  pub workspace_transfers: Vec<WorkspaceOwnershipTransfer>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct WorkspaceOwnershipTransfer {
+  pub workspace_id: uuid::Uuid,
+  /// The email of the member of the workspace that becomes its owner.
+  pub new_owner_email: String,
+}
+
+/// An account marked for deletion. It can be restored until `purge_after`, after which it is
+/// deleted for good with all of its data.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct AccountDeletion {
+  pub workspace_transfers: Vec<WorkspaceOwnershipTransfer>,
+  pub requested_at: chrono::DateTime<chrono::Utc>,
+  pub purge_after: chrono::DateTime<chrono::Utc>,
+}
+
+/// Starts a single sign-on through the SAML identity provider of the workspace, or, without a
+/// workspace, through the identity provider of the domain of the email.
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct SsoSignInParams {
+  pub workspace_id: Option<uuid::Uuid>,
+  pub email: Option<String>,
+  /// Where the user is sent with its tokens once signed in.
+  pub redirect_to: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct SsoSignInUrl {
+  /// The url of the identity provider to open in a browser.
+  pub url: String,
+}
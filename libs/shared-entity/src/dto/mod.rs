@@ -1,7 +1,10 @@
+pub mod admin_dto;
 pub mod ai_dto;
 pub mod auth_dto;
 pub mod billing_dto;
 pub mod history_dto;
+pub mod notification_dto;
 pub mod publish_dto;
+pub mod scim_dto;
 pub mod search_dto;
 pub mod workspace_dto;
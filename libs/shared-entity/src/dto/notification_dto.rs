@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NotificationKind {
+  /// The user was mentioned in a comment.
+  #[serde(rename = "comment.mention")]
+  Mention,
+  /// Someone replied to a thread the user started.
+  #[serde(rename = "comment.reply")]
+  CommentReply,
+  /// The user was added as a member of a collab.
+  #[serde(rename = "collab.shared")]
+  SharedWithYou,
+  /// The user was invited to a workspace.
+  #[serde(rename = "workspace.invite")]
+  WorkspaceInvite,
+}
+
+impl NotificationKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      NotificationKind::Mention => "comment.mention",
+      NotificationKind::CommentReply => "comment.reply",
+      NotificationKind::SharedWithYou => "collab.shared",
+      NotificationKind::WorkspaceInvite => "workspace.invite",
+    }
+  }
+}
+
+impl std::str::FromStr for NotificationKind {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "comment.mention" => Ok(NotificationKind::Mention),
+      "comment.reply" => Ok(NotificationKind::CommentReply),
+      "collab.shared" => Ok(NotificationKind::SharedWithYou),
+      "workspace.invite" => Ok(NotificationKind::WorkspaceInvite),
+      _ => Err(format!("Unknown notification kind: {}", s)),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+  pub id: i64,
+  pub workspace_id: Option<Uuid>,
+  pub kind: NotificationKind,
+  /// The details of the event, such as the name of the user that triggered it and the object
+  /// it happened on.
+  pub data: serde_json::Value,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notifications {
+  pub notifications: Vec<Notification>,
+}
+
+/// How the notifications of a user are emailed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(i16)]
+pub enum EmailDelivery {
+  /// One email per notification, as soon as possible.
+  Immediate = 0,
+  /// One email a day with all the notifications of the day.
+  DailyDigest = 1,
+  Off = 2,
+}
+
+impl From<i16> for EmailDelivery {
+  fn from(value: i16) -> Self {
+    match value {
+      1 => EmailDelivery::DailyDigest,
+      2 => EmailDelivery::Off,
+      _ => EmailDelivery::Immediate,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationSettings {
+  pub email_delivery: EmailDelivery,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsubscribeNotificationQuery {
+  pub token: Uuid,
+}
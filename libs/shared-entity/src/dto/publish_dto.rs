@@ -59,3 +59,11 @@ pub struct PublishDatabaseData {
   /// Relation view id map
   pub database_relations: HashMap<String, String>,
 }
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PublishOutcome {
+  Published,
+  /// The collab has not changed since it was last published, so it was not published again.
+  NoChange,
+}
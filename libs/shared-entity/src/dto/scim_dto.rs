@@ -0,0 +1,147 @@
+//! The resources of the SCIM 2.0 protocol, RFC 7643 and RFC 7644, as far as they are supported.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub const SCIM_USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+pub const SCIM_GROUP_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+pub const SCIM_LIST_RESPONSE_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+pub const SCIM_PATCH_OP_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+pub const SCIM_ERROR_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimUser {
+  #[serde(default)]
+  pub schemas: Vec<String>,
+  /// The uuid of the user.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub id: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub external_id: Option<String>,
+  /// The email of the user.
+  pub user_name: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub name: Option<ScimName>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub display_name: Option<String>,
+  #[serde(default)]
+  pub emails: Vec<ScimEmail>,
+  /// An inactive user is not a member of the workspace.
+  #[serde(default = "default_active")]
+  pub active: bool,
+  /// The groups of the user. Ignored in requests, the membership is managed through the groups.
+  #[serde(default)]
+  pub groups: Vec<ScimReference>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub meta: Option<ScimMeta>,
+}
+
+fn default_active() -> bool {
+  true
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimName {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub formatted: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub given_name: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub family_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScimEmail {
+  pub value: String,
+  #[serde(default)]
+  pub primary: bool,
+  #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+  pub type_: Option<String>,
+}
+
+/// A reference to a user, as a member of a group, or to a group, as a group of a user.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScimReference {
+  pub value: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub display: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimMeta {
+  pub resource_type: String,
+  pub created: DateTime<Utc>,
+  pub last_modified: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimGroup {
+  #[serde(default)]
+  pub schemas: Vec<String>,
+  /// The id of the group.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub id: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub external_id: Option<String>,
+  pub display_name: String,
+  /// The users in the group, referenced by their id.
+  #[serde(default)]
+  pub members: Vec<ScimReference>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub meta: Option<ScimMeta>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimListResponse<T> {
+  pub schemas: Vec<String>,
+  pub total_results: usize,
+  pub start_index: usize,
+  pub items_per_page: usize,
+  #[serde(rename = "Resources")]
+  pub resources: Vec<T>,
+}
+
+/// The query of a request that lists users or groups. Only the `eq` filters on `userName` and
+/// `externalId` for users, and on `displayName` for groups, are supported.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimListQuery {
+  pub filter: Option<String>,
+  /// 1-based, as in SCIM.
+  pub start_index: Option<usize>,
+  pub count: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScimPatchRequest {
+  #[serde(default)]
+  pub schemas: Vec<String>,
+  #[serde(rename = "Operations")]
+  pub operations: Vec<ScimPatchOperation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScimPatchOperation {
+  /// `add`, `remove` or `replace`, in any case.
+  pub op: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub path: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub value: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimError {
+  pub schemas: Vec<String>,
+  /// The http status code, as a string.
+  pub status: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub scim_type: Option<String>,
+  pub detail: String,
+}
@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use collab_entity::CollabType;
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
@@ -57,3 +58,44 @@ impl SearchContentType {
     }
   }
 }
+
+/// Parameters of the full-text search over the documents and databases of a workspace.
+/// In response, a list of [WorkspaceSearchResultItem] is returned.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorkspaceSearchQuery {
+  /// Query statement, in the web search syntax: quoted phrases, `or`, and `-` to exclude a word.
+  pub query: String,
+  /// Maximum number of results to return. Default: 10.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub limit: Option<u32>,
+  /// Maximum number of words of the highlighted fragment of each result. Default: 30.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub preview_words: Option<u32>,
+}
+
+/// Response array element for the full-text search. Only the collabs the caller can read are
+/// returned. See: [WorkspaceSearchQuery].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorkspaceSearchResultItem {
+  pub object_id: String,
+  pub collab_type: CollabType,
+  /// Rank of the result for the query. The higher the better. List of results is sorted by this
+  /// value.
+  pub score: f32,
+  /// Fragment of the content around the matching words, as plain text. It is not escaped, so it
+  /// must not be rendered as markup.
+  pub highlight: String,
+  /// The matching words of [WorkspaceSearchResultItem::highlight], in order.
+  #[serde(default)]
+  pub highlight_ranges: Vec<HighlightRange>,
+  /// When the content of the collab was last indexed.
+  pub indexed_at: DateTime<Utc>,
+}
+
+/// A part of a highlighted fragment, from `start` inclusive to `end` exclusive. The offsets count
+/// Unicode characters, not bytes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HighlightRange {
+  pub start: usize,
+  pub end: usize,
+}
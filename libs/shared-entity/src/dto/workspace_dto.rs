@@ -1,6 +1,9 @@
-use chrono::{DateTime, Utc};
+use crate::dto::billing_dto::SubscriptionPlan;
+use chrono::{DateTime, NaiveDate, Utc};
 use collab_entity::{CollabType, EncodedCollab};
-use database_entity::dto::{AFRole, AFWebUser, AFWorkspaceInvitationStatus};
+use database_entity::dto::{
+  AFAccessLevel, AFRole, AFWebUser, AFWorkspaceInvitationStatus, WorkspaceCapability,
+};
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use std::{collections::HashMap, ops::Deref};
@@ -79,6 +82,22 @@ pub struct WorkspaceSpaceUsage {
   pub consumed_capacity: u64,
 }
 
+/// The space the collabs of a workspace occupy in storage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceStorageFootprint {
+  pub workspace_id: Uuid,
+  pub object_count: u64,
+  pub total_bytes: u64,
+  pub by_collab_type: Vec<CollabTypeFootprint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabTypeFootprint {
+  pub collab_type: CollabType,
+  pub object_count: u64,
+  pub bytes: u64,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct RepeatedBlobMetaData(pub Vec<BlobMetadata>);
 
@@ -92,11 +111,13 @@ pub struct BlobMetadata {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CreateWorkspaceParam {
   pub workspace_name: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct PatchWorkspaceParam {
   pub workspace_id: Uuid,
   pub workspace_name: Option<String>,
@@ -122,6 +143,28 @@ pub struct CollabResponse {
   pub object_id: String,
 }
 
+/// Sync a collab over plain HTTP, without the realtime connection. See
+/// [CollabDeltaSyncResponse].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabDeltaSyncParams {
+  pub collab_type: CollabType,
+  /// The state vector of the client's copy of the collab, encoded with yrs v1 encoding.
+  pub state_vector: Vec<u8>,
+  /// The changes the client made since its last sync, as a yrs update encoded with v1 encoding.
+  #[serde(default)]
+  pub update: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabDeltaSyncResponse {
+  /// The changes missing from the client's copy of the collab, as a yrs update encoded with v1
+  /// encoding.
+  pub missing_update: Vec<u8>,
+  /// The state vector of the collab once the update of the client is applied. The client can send
+  /// it on its next sync, once it has applied the missing update.
+  pub state_vector: Vec<u8>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PageCollabData {
   pub encoded_collab: Vec<u8>,
@@ -136,6 +179,188 @@ pub struct PageCollab {
   pub last_editor: Option<AFWebUser>,
 }
 
+/// A field of a database, in the order of the database view it was read from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseField {
+  pub id: String,
+  pub name: String,
+  /// The `FieldType` of the field, such as 0 for text or 3 for single select.
+  pub field_type: i64,
+  pub is_primary: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseRow {
+  pub id: String,
+  /// The text of the non-empty cells as shown to the user, by field id.
+  pub cells: HashMap<String, String>,
+  pub created_at: i64,
+  pub last_modified: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseRows {
+  pub fields: Vec<DatabaseField>,
+  pub rows: Vec<DatabaseRow>,
+  /// The number of rows of the database view, of which `rows` is a page.
+  pub total: usize,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct QueryDatabaseRows {
+  pub offset: Option<usize>,
+  /// 100 by default, and at most 1000.
+  pub limit: Option<usize>,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct CreateDatabaseRowParams {
+  /// The value of the cells, by field id or field name. Select options are given by name, dates
+  /// as a timestamp in seconds or an RFC 3339 string, and checkboxes as a boolean.
+  #[serde(default)]
+  pub cells: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateDatabaseRowParams {
+  /// The cells to change, like [CreateDatabaseRowParams::cells]. `null` clears the cell, and the
+  /// cells that are left out are not changed.
+  pub cells: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct QueryDatabaseViewParams {
+  /// Keep the rows matching this filter instead of the filters of the view.
+  pub filter: Option<DatabaseRowFilter>,
+  /// Sort the rows this way instead of with the sorts of the view.
+  pub sorts: Option<Vec<DatabaseRowSort>>,
+  pub offset: Option<usize>,
+  /// 100 by default, and at most 1000.
+  pub limit: Option<usize>,
+}
+
+/// A filter of the rows of a database, such as
+/// `{"and": [{"condition": {"field": "Status", "op": "is", "value": "Done"}}]}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatabaseRowFilter {
+  And(Vec<DatabaseRowFilter>),
+  Or(Vec<DatabaseRowFilter>),
+  Condition(DatabaseRowCondition),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseRowCondition {
+  /// The id or the name of the field.
+  pub field: String,
+  pub op: DatabaseFilterOp,
+  /// What the cell is compared with: a text, a number, a boolean for checkboxes, a timestamp in
+  /// seconds or a date for dates, and option names for select fields. Not needed to check whether
+  /// the cell is empty.
+  #[serde(default)]
+  pub value: serde_json::Value,
+}
+
+/// For select fields, `is` and `contains` keep the rows with any of the options, `is_not` and
+/// `does_not_contain` the rows with none of them. Dates are compared by day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatabaseFilterOp {
+  Is,
+  IsNot,
+  Contains,
+  DoesNotContain,
+  StartsWith,
+  EndsWith,
+  IsEmpty,
+  IsNotEmpty,
+  GreaterThan,
+  LessThan,
+  GreaterThanOrEqual,
+  LessThanOrEqual,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseRowSort {
+  /// The id or the name of the field.
+  pub field: String,
+  #[serde(default)]
+  pub descending: bool,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct QueryDatabaseViewExport {
+  /// CSV by default.
+  pub format: Option<DatabaseExportFormat>,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatabaseExportFormat {
+  #[default]
+  Csv,
+  Xlsx,
+}
+
+/// Let anyone who can read the published database view add rows to it through a form.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetPublishedDatabaseFormParams {
+  /// The ids or the names of the fields the form lets the visitors fill in.
+  pub fields: Vec<String>,
+  /// 100 by default.
+  pub max_submissions_per_hour: Option<i32>,
+  #[serde(default)]
+  pub require_captcha: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedDatabaseFormSettings {
+  pub view_id: Uuid,
+  pub field_ids: Vec<String>,
+  pub max_submissions_per_hour: i32,
+  pub require_captcha: bool,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// The form of a published database view, as shown to its visitors.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedDatabaseForm {
+  pub fields: Vec<PublishedDatabaseFormField>,
+  pub require_captcha: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedDatabaseFormField {
+  pub id: String,
+  pub name: String,
+  /// The `FieldType` of the field, such as 0 for text or 3 for single select.
+  pub field_type: i64,
+  /// The names of the options of a select field, empty for the other fields.
+  pub options: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitPublishedDatabaseForm {
+  /// The values of the cells, by field id or name, in the format of [CreateDatabaseRowParams].
+  pub cells: HashMap<String, serde_json::Value>,
+  /// The token the captcha widget gave the visitor, when the form requires a captcha.
+  pub captcha_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedDatabaseFormSubmission {
+  pub row_id: String,
+}
+
+/// An iCal feed of the rows of a database view that have a date, which calendar apps can
+/// subscribe to. Anyone with the url can read the feed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseCalendarFeed {
+  pub token: String,
+  pub url: String,
+  pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PublishedDuplicate {
   pub published_view_id: String,
@@ -143,6 +368,7 @@ pub struct PublishedDuplicate {
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct FolderView {
   pub view_id: String,
   pub name: String,
@@ -156,6 +382,39 @@ pub struct FolderView {
   /// contains fields like `is_space`, and font information
   pub extra: Option<serde_json::Value>,
   pub children: Vec<FolderView>,
+  /// Only set on the root of the returned tree. It is the update counter of the folder collab at the
+  /// time it was read, so the client can tell whether its local folder is behind the server.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub server_version: Option<u64>,
+  /// What the requesting user can do with the view. Only set when requested.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub permissions: Option<ViewPermissions>,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ViewPermissions {
+  pub can_read: bool,
+  pub can_comment: bool,
+  pub can_edit: bool,
+  pub can_share: bool,
+  pub can_delete: bool,
+}
+
+impl ViewPermissions {
+  /// `None` means the user has no access at all.
+  pub fn from_access_level(access_level: Option<AFAccessLevel>) -> Self {
+    match access_level {
+      None => Self::default(),
+      Some(level) => Self {
+        can_read: true,
+        can_comment: level.can_comment(),
+        can_edit: level.can_write(),
+        can_share: level.can_share(),
+        can_delete: level.can_delete(),
+      },
+    }
+  }
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
@@ -163,8 +422,25 @@ pub struct SectionItems {
   pub views: Vec<FolderView>,
 }
 
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct TrashViewsParams {
+  pub view_ids: Vec<String>,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct RecordViewVisitParams {
+  pub view_id: String,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct FavoriteViewParams {
+  pub view_id: String,
+  pub is_favorite: bool,
+}
+
 #[derive(Eq, PartialEq, Debug, Hash, Clone, Serialize_repr, Deserialize_repr)]
 #[repr(u8)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum IconType {
   Emoji = 0,
   Url = 1,
@@ -172,6 +448,7 @@ pub enum IconType {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ViewIcon {
   pub ty: IconType,
   pub value: String,
@@ -179,6 +456,7 @@ pub struct ViewIcon {
 
 #[derive(Eq, PartialEq, Debug, Hash, Clone, Serialize_repr, Deserialize_repr)]
 #[repr(u8)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum ViewLayout {
   Document = 0,
   Grid = 1,
@@ -193,15 +471,212 @@ impl Default for ViewLayout {
   }
 }
 
+impl TryFrom<u8> for ViewLayout {
+  type Error = String;
+
+  fn try_from(value: u8) -> Result<Self, Self::Error> {
+    match value {
+      0 => Ok(ViewLayout::Document),
+      1 => Ok(ViewLayout::Grid),
+      2 => Ok(ViewLayout::Board),
+      3 => Ok(ViewLayout::Calendar),
+      4 => Ok(ViewLayout::Chat),
+      _ => Err(format!("Invalid view layout: {}", value)),
+    }
+  }
+}
+
 #[derive(Default, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
 pub struct QueryWorkspaceParam {
   pub include_member_count: Option<bool>,
 }
 
 #[derive(Default, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
 pub struct QueryWorkspaceFolder {
   pub depth: Option<u32>,
   pub root_view_id: Option<String>,
+  /// Include the permissions of the requesting user on every view.
+  #[serde(default)]
+  pub include_permissions: Option<bool>,
+  /// Comma separated [ViewLayout] values, e.g. `1,2,3` for the database views. Only the views with
+  /// one of these layouts are returned, along with the views leading to them.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub layouts: Option<String>,
+  /// Only return this space among the children of the root view.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub space_id: Option<String>,
+  /// Whether the private spaces of the user are returned. Defaults to true.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub include_private: Option<bool>,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct QueryWorkspaceFolders {
+  pub workspace_ids: Vec<Uuid>,
+  pub depth: Option<u32>,
+}
+
+/// The folder of one of the workspaces of [QueryWorkspaceFolders].
+#[derive(Debug, Deserialize, Serialize)]
+pub enum WorkspaceFolderResult {
+  Folder(FolderView),
+  Failed { error: String },
+}
+
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct UpdateUserViewOrder {
+  pub parent_view_id: String,
+  /// Child view ids in the order the user wants them. An empty list resets to the folder's order.
+  pub ordered_child_ids: Vec<String>,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct QueryFolderViewSearch {
+  pub query: String,
+  /// Also return the ancestors of the matching views, so that results can be grouped by folder.
+  #[serde(default)]
+  pub include_ancestors: bool,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct QueryFolderChildrenConnection {
+  /// The maximum number of children in the page.
+  pub first: Option<u32>,
+  /// The cursor of the last child of the previous page. `None` for the first page.
+  pub after: Option<String>,
+  /// Comma separated [ViewLayout] values, as in [QueryWorkspaceFolder]. Only the children with one
+  /// of these layouts, or with descendants that have one, are returned.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub layouts: Option<String>,
+  /// Whether the private spaces of the user are returned. Defaults to true.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub include_private: Option<bool>,
+}
+
+/// A page of the children of a folder view, shaped as a cursor connection.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct FolderViewConnection {
+  pub edges: Vec<FolderViewEdge>,
+  pub page_info: FolderPageInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderViewEdge {
+  pub cursor: String,
+  /// The child view. Its own children are not included, they are paginated separately.
+  pub node: FolderView,
+  pub has_children: bool,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct FolderPageInfo {
+  pub has_next_page: bool,
+  /// The cursor of the last edge of the page, to pass as `after` for the next page.
+  pub end_cursor: Option<String>,
+}
+
+/// An ancestor of a view, as shown in its breadcrumb.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderViewAncestor {
+  pub view_id: String,
+  pub name: String,
+  pub icon: Option<ViewIcon>,
+  pub is_space: bool,
+  pub layout: ViewLayout,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct FolderViewPathItem {
+  pub view_id: String,
+  pub name: String,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct FolderViewSearchResult {
+  pub view_id: String,
+  pub name: String,
+  pub icon: Option<ViewIcon>,
+  pub is_space: bool,
+  pub is_private: bool,
+  pub layout: ViewLayout,
+  /// The ancestors of the view, from the top-most space down to its direct parent.
+  pub path: Vec<FolderViewPathItem>,
+  /// `false` when the view does not match itself and is only returned as an ancestor.
+  pub is_match: bool,
+}
+
+/// The fields of a published view needed to render a link preview.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedViewPreview {
+  pub view_id: String,
+  pub title: String,
+  /// The beginning of the text of a published document. `None` for other layouts.
+  pub description: Option<String>,
+  pub cover: Option<PublishedViewCover>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublishedViewCover {
+  /// For example `gradient`, `color`, `built_in`, `custom` or `unsplash`.
+  #[serde(rename = "type")]
+  pub cover_type: String,
+  /// A color or gradient name, or the url of the image, depending on the type.
+  pub value: String,
+}
+
+/// A structural problem found in the folder of a workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FolderIntegrityIssue {
+  /// The parent of the view does not exist, so the view can not be reached from the workspace.
+  Orphan {
+    view_id: String,
+    parent_view_id: String,
+  },
+  /// The view is listed more than once as a child, either by several parents or by the same one.
+  DuplicateId {
+    view_id: String,
+    parent_view_ids: Vec<String>,
+  },
+  /// The views are each other's ancestors. The first view is the one a repair moves to the root.
+  Cycle { view_ids: Vec<String> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderIntegrityReport {
+  pub workspace_id: String,
+  pub issues: Vec<FolderIntegrityIssue>,
+}
+
+impl FolderIntegrityReport {
+  pub fn is_healthy(&self) -> bool {
+    self.issues.is_empty()
+  }
+}
+
+/// What deleting an object cleaned up.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedObjectSummary {
+  pub object_id: String,
+  /// Whether the object was published, and is not anymore.
+  pub unpublished: bool,
+  /// Whether the object was a view of the folder, and has been removed from it.
+  pub removed_from_folder: bool,
+  /// The child views of the object, which now belong to the parent the object had.
+  pub reparented_view_ids: Vec<String>,
+  /// The uids of the members that were removed, together with their access policies.
+  pub removed_member_uids: Vec<i64>,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct RepairFolderIntegrityParams {
+  /// The repair rewrites the folder, so it is refused unless this is set.
+  #[serde(default)]
+  pub confirm: bool,
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
@@ -215,3 +690,806 @@ pub struct PublishedView {
   pub extra: Option<serde_json::Value>,
   pub children: Vec<PublishedView>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWorkspaceGroupParams {
+  pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddWorkspaceGroupMemberParams {
+  pub uid: i64,
+}
+
+/// A named set of workspace members. Granting a group access to a collab grants that access to
+/// every member of the group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceGroup {
+  pub group_id: Uuid,
+  pub workspace_id: Uuid,
+  pub name: String,
+  pub member_uids: Vec<i64>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWorkspaceRoleParams {
+  pub name: String,
+  pub capabilities: Vec<WorkspaceCapability>,
+}
+
+pub type UpdateWorkspaceRoleParams = CreateWorkspaceRoleParams;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssignWorkspaceRoleParams {
+  pub uid: i64,
+}
+
+/// A role defined by the owners of the workspace. Each member can be assigned at most one such
+/// role, which grants its capabilities on top of those of the [AFRole] of the member.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceRole {
+  pub role_id: Uuid,
+  pub workspace_id: Uuid,
+  pub name: String,
+  pub capabilities: Vec<WorkspaceCapability>,
+  pub member_uids: Vec<i64>,
+  pub created_at: DateTime<Utc>,
+}
+
+/// An invitation to the workspace that the invitee has neither accepted nor rejected. The invitee
+/// may not have an account yet, the invitation is then accepted when the invitee signs up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingWorkspaceInvitation {
+  pub invite_id: Uuid,
+  pub email: String,
+  pub role: AFRole,
+  pub inviter_uid: i64,
+  pub inviter_email: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub last_sent_at: DateTime<Utc>,
+  /// How many times the email of the invitation has been sent.
+  pub sent_count: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferWorkspaceOwnershipParams {
+  /// The email of the member of the workspace that is asked to become its owner.
+  pub new_owner_email: String,
+}
+
+/// A transfer of the ownership of a workspace, waiting for the new owner to accept it. The
+/// previous owner stays in the workspace as a member once the transfer is accepted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceOwnershipTransferRequest {
+  pub workspace_id: Uuid,
+  pub from_uid: i64,
+  pub from_email: String,
+  pub to_uid: i64,
+  pub to_email: String,
+  pub created_at: DateTime<Utc>,
+  pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWorkspaceInviteLinkParams {
+  /// The role of the users that join through the link, either [AFRole::Member] or
+  /// [AFRole::Guest].
+  pub role: AFRole,
+  /// The email domains, such as `acme.com`, of the users allowed to join through the link. Any
+  /// user can join when empty.
+  #[serde(default)]
+  pub allowed_domains: Vec<String>,
+  /// How many users can join through the link in an hour. The server picks a default when
+  /// `None`.
+  #[serde(default)]
+  pub max_joins_per_hour: Option<i32>,
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Replaces the role, the allowed domains and the limits of an invite link.
+pub type UpdateWorkspaceInviteLinkParams = CreateWorkspaceInviteLinkParams;
+
+/// A reusable link that lets users join the workspace by themselves.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceInviteLink {
+  pub link_id: Uuid,
+  pub workspace_id: Uuid,
+  pub token: String,
+  pub role: AFRole,
+  pub allowed_domains: Vec<String>,
+  pub max_joins_per_hour: i32,
+  pub expires_at: Option<DateTime<Utc>>,
+  pub created_by: i64,
+  pub created_at: DateTime<Utc>,
+  /// When the token of the link was last replaced, if ever.
+  pub rotated_at: Option<DateTime<Utc>>,
+}
+
+/// The workspace a user joined through an invite link.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JoinedWorkspace {
+  pub workspace_id: Uuid,
+  /// The role of the user in the workspace, which is the role it already held if the user was a
+  /// member before opening the link.
+  pub role: AFRole,
+}
+
+/// The events a webhook can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEvent {
+  #[serde(rename = "collab.updated")]
+  CollabUpdated,
+  #[serde(rename = "member.added")]
+  MemberAdded,
+  #[serde(rename = "member.removed")]
+  MemberRemoved,
+  #[serde(rename = "view.published")]
+  ViewPublished,
+  #[serde(rename = "view.unpublished")]
+  ViewUnpublished,
+}
+
+impl WebhookEvent {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      WebhookEvent::CollabUpdated => "collab.updated",
+      WebhookEvent::MemberAdded => "member.added",
+      WebhookEvent::MemberRemoved => "member.removed",
+      WebhookEvent::ViewPublished => "view.published",
+      WebhookEvent::ViewUnpublished => "view.unpublished",
+    }
+  }
+}
+
+impl std::str::FromStr for WebhookEvent {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "collab.updated" => Ok(WebhookEvent::CollabUpdated),
+      "member.added" => Ok(WebhookEvent::MemberAdded),
+      "member.removed" => Ok(WebhookEvent::MemberRemoved),
+      "view.published" => Ok(WebhookEvent::ViewPublished),
+      "view.unpublished" => Ok(WebhookEvent::ViewUnpublished),
+      _ => Err(format!("Unknown webhook event: {}", s)),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWebhookParams {
+  /// The http or https endpoint that is called back.
+  pub url: String,
+  /// The key the body of every callback is signed with.
+  pub secret: String,
+  /// The events the webhook is called back for. Empty means every event.
+  #[serde(default)]
+  pub events: Vec<WebhookEvent>,
+}
+
+/// An endpoint that is called back when events happen in the workspace. The secret is never
+/// returned.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Webhook {
+  pub webhook_id: Uuid,
+  pub workspace_id: Uuid,
+  pub url: String,
+  pub events: Vec<WebhookEvent>,
+  pub created_at: DateTime<Utc>,
+}
+
+/// A callback that could not be delivered after the maximum number of attempts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDeadLetter {
+  pub delivery_id: i64,
+  pub webhook_id: Uuid,
+  pub event: WebhookEvent,
+  pub payload: serde_json::Value,
+  pub attempts: i32,
+  pub last_error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub dead_at: DateTime<Utc>,
+}
+
+/// What the API keys of a service account are allowed to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+  /// Read the content of the workspace.
+  ReadOnly,
+  /// Read the content of the workspace, and publish or unpublish its views.
+  PublishOnly,
+  /// Everything a member of the workspace can do.
+  Full,
+  /// Provision the members of the workspace through the SCIM endpoints, and nothing else.
+  Scim,
+}
+
+impl ApiKeyScope {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ApiKeyScope::ReadOnly => "read_only",
+      ApiKeyScope::PublishOnly => "publish_only",
+      ApiKeyScope::Full => "full",
+      ApiKeyScope::Scim => "scim",
+    }
+  }
+
+  /// The role the service account holds in its workspace.
+  pub fn role(&self) -> AFRole {
+    match self {
+      ApiKeyScope::ReadOnly | ApiKeyScope::Scim => AFRole::Guest,
+      ApiKeyScope::PublishOnly | ApiKeyScope::Full => AFRole::Member,
+    }
+  }
+}
+
+impl std::str::FromStr for ApiKeyScope {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "read_only" => Ok(ApiKeyScope::ReadOnly),
+      "publish_only" => Ok(ApiKeyScope::PublishOnly),
+      "full" => Ok(ApiKeyScope::Full),
+      "scim" => Ok(ApiKeyScope::Scim),
+      _ => Err(format!("Unknown api key scope: {}", s)),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateServiceAccountParams {
+  pub name: String,
+  pub scope: ApiKeyScope,
+}
+
+/// A key of a service account. The key itself is only returned when it is created, see
+/// [ServiceAccountApiKey].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiKey {
+  pub api_key_id: Uuid,
+  /// The first characters of the key, to tell the keys apart.
+  pub key_prefix: String,
+  pub created_at: DateTime<Utc>,
+  pub last_used_at: Option<DateTime<Utc>>,
+  pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// A non-human member of the workspace that authenticates with API keys.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceAccount {
+  pub service_account_id: Uuid,
+  pub workspace_id: Uuid,
+  /// The uid of the user that backs the service account.
+  pub uid: i64,
+  pub name: String,
+  pub scope: ApiKeyScope,
+  pub created_at: DateTime<Utc>,
+  pub api_keys: Vec<ApiKey>,
+}
+
+/// A newly created key. Sent as `Authorization: Bearer <api_key>`, it authenticates the requests
+/// as the service account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceAccountApiKey {
+  pub service_account: ServiceAccount,
+  pub api_key_id: Uuid,
+  pub api_key: String,
+}
+
+/// The security relevant actions recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AuditAction {
+  #[serde(rename = "member.added")]
+  MemberAdded,
+  #[serde(rename = "member.removed")]
+  MemberRemoved,
+  #[serde(rename = "member.role_changed")]
+  MemberRoleChanged,
+  #[serde(rename = "member.invitation_revoked")]
+  MemberInvitationRevoked,
+  #[serde(rename = "collab_member.added")]
+  CollabMemberAdded,
+  #[serde(rename = "collab_member.updated")]
+  CollabMemberUpdated,
+  #[serde(rename = "collab_member.removed")]
+  CollabMemberRemoved,
+  #[serde(rename = "view.published")]
+  ViewPublished,
+  #[serde(rename = "view.unpublished")]
+  ViewUnpublished,
+  #[serde(rename = "workspace.exported")]
+  WorkspaceExported,
+  #[serde(rename = "user.login")]
+  UserLogin,
+  #[serde(rename = "user.data_exported")]
+  UserDataExported,
+  #[serde(rename = "user.deletion_requested")]
+  UserDeletionRequested,
+  #[serde(rename = "user.deletion_cancelled")]
+  UserDeletionCancelled,
+  #[serde(rename = "user.deleted")]
+  UserDeleted,
+  #[serde(rename = "workspace.ownership_transferred")]
+  WorkspaceOwnershipTransferred,
+  #[serde(rename = "workspace.ownership_transfer_requested")]
+  WorkspaceOwnershipTransferRequested,
+  #[serde(rename = "workspace.ownership_transfer_cancelled")]
+  WorkspaceOwnershipTransferCancelled,
+  #[serde(rename = "invite_link.created")]
+  InviteLinkCreated,
+  #[serde(rename = "invite_link.updated")]
+  InviteLinkUpdated,
+  #[serde(rename = "invite_link.rotated")]
+  InviteLinkRotated,
+  #[serde(rename = "invite_link.deleted")]
+  InviteLinkDeleted,
+}
+
+impl AuditAction {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AuditAction::MemberAdded => "member.added",
+      AuditAction::MemberRemoved => "member.removed",
+      AuditAction::MemberRoleChanged => "member.role_changed",
+      AuditAction::MemberInvitationRevoked => "member.invitation_revoked",
+      AuditAction::CollabMemberAdded => "collab_member.added",
+      AuditAction::CollabMemberUpdated => "collab_member.updated",
+      AuditAction::CollabMemberRemoved => "collab_member.removed",
+      AuditAction::ViewPublished => "view.published",
+      AuditAction::ViewUnpublished => "view.unpublished",
+      AuditAction::WorkspaceExported => "workspace.exported",
+      AuditAction::UserLogin => "user.login",
+      AuditAction::UserDataExported => "user.data_exported",
+      AuditAction::UserDeletionRequested => "user.deletion_requested",
+      AuditAction::UserDeletionCancelled => "user.deletion_cancelled",
+      AuditAction::UserDeleted => "user.deleted",
+      AuditAction::WorkspaceOwnershipTransferred => "workspace.ownership_transferred",
+      AuditAction::WorkspaceOwnershipTransferRequested => "workspace.ownership_transfer_requested",
+      AuditAction::WorkspaceOwnershipTransferCancelled => "workspace.ownership_transfer_cancelled",
+      AuditAction::InviteLinkCreated => "invite_link.created",
+      AuditAction::InviteLinkUpdated => "invite_link.updated",
+      AuditAction::InviteLinkRotated => "invite_link.rotated",
+      AuditAction::InviteLinkDeleted => "invite_link.deleted",
+    }
+  }
+}
+
+impl std::str::FromStr for AuditAction {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "member.added" => Ok(AuditAction::MemberAdded),
+      "member.removed" => Ok(AuditAction::MemberRemoved),
+      "member.role_changed" => Ok(AuditAction::MemberRoleChanged),
+      "member.invitation_revoked" => Ok(AuditAction::MemberInvitationRevoked),
+      "collab_member.added" => Ok(AuditAction::CollabMemberAdded),
+      "collab_member.updated" => Ok(AuditAction::CollabMemberUpdated),
+      "collab_member.removed" => Ok(AuditAction::CollabMemberRemoved),
+      "view.published" => Ok(AuditAction::ViewPublished),
+      "view.unpublished" => Ok(AuditAction::ViewUnpublished),
+      "workspace.exported" => Ok(AuditAction::WorkspaceExported),
+      "user.login" => Ok(AuditAction::UserLogin),
+      "user.data_exported" => Ok(AuditAction::UserDataExported),
+      "user.deletion_requested" => Ok(AuditAction::UserDeletionRequested),
+      "user.deletion_cancelled" => Ok(AuditAction::UserDeletionCancelled),
+      "user.deleted" => Ok(AuditAction::UserDeleted),
+      "workspace.ownership_transferred" => Ok(AuditAction::WorkspaceOwnershipTransferred),
+      "workspace.ownership_transfer_requested" => {
+        Ok(AuditAction::WorkspaceOwnershipTransferRequested)
+      },
+      "workspace.ownership_transfer_cancelled" => {
+        Ok(AuditAction::WorkspaceOwnershipTransferCancelled)
+      },
+      "invite_link.created" => Ok(AuditAction::InviteLinkCreated),
+      "invite_link.updated" => Ok(AuditAction::InviteLinkUpdated),
+      "invite_link.rotated" => Ok(AuditAction::InviteLinkRotated),
+      "invite_link.deleted" => Ok(AuditAction::InviteLinkDeleted),
+      _ => Err(format!("Unknown audit action: {}", s)),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogEntry {
+  pub id: i64,
+  /// `None` for the actions that are not tied to a workspace, such as a login.
+  pub workspace_id: Option<Uuid>,
+  /// `None` for the actions of the server itself.
+  pub actor_uid: Option<i64>,
+  pub actor_email: Option<String>,
+  pub action: AuditAction,
+  /// What the action was applied to, such as the object id of a collab.
+  pub target: Option<String>,
+  pub metadata: serde_json::Value,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct QueryAuditLog {
+  pub actor_uid: Option<i64>,
+  pub action: Option<AuditAction>,
+  /// Only the entries recorded at or after this time.
+  pub since: Option<DateTime<Utc>>,
+  /// Only the entries recorded before this time.
+  pub until: Option<DateTime<Utc>>,
+  /// The maximum number of entries in the page.
+  pub first: Option<u32>,
+  /// The cursor of the last entry of the previous page. `None` for the first page.
+  pub after: Option<String>,
+}
+
+/// A page of the audit log, the most recent entries first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogPage {
+  pub entries: Vec<AuditLogEntry>,
+  pub has_next_page: bool,
+  /// The cursor of the last entry of the page, to pass as `after` for the next page.
+  pub end_cursor: Option<String>,
+}
+
+/// A group provisioned through SCIM, and the role its members get in the workspace.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScimGroupRole {
+  pub group_id: Uuid,
+  pub display_name: String,
+  /// `None` until the owner of the workspace maps the group to a role.
+  pub role: Option<AFRole>,
+  pub member_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateScimGroupRoleParams {
+  /// `None` unmaps the group, its members then get the Member role, unless another group of
+  /// theirs is mapped.
+  pub role: Option<AFRole>,
+}
+
+/// The SAML identity provider of a workspace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceSso {
+  pub workspace_id: Uuid,
+  /// The id of the provider in GoTrue.
+  pub provider_id: Uuid,
+  /// The entity id of the identity provider, read from its metadata.
+  pub entity_id: String,
+  pub metadata_url: Option<String>,
+  /// The users whose email is in one of these domains sign in through the provider.
+  pub domains: Vec<String>,
+  /// The role of the users added to the workspace on their first sign-in through the provider.
+  pub default_role: AFRole,
+  /// When enforced, the members, except the owner, must sign in through the provider to access
+  /// the workspace.
+  pub enforced: bool,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// Configure the SAML identity provider of the workspace, with either the url or the content of
+/// its metadata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateWorkspaceSsoParams {
+  #[serde(default)]
+  pub metadata_url: Option<String>,
+  #[serde(default)]
+  pub metadata_xml: Option<String>,
+  #[serde(default)]
+  pub domains: Vec<String>,
+  #[serde(default = "default_sso_role")]
+  pub default_role: AFRole,
+  #[serde(default)]
+  pub enforced: bool,
+}
+
+fn default_sso_role() -> AFRole {
+  AFRole::Member
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterPublishCustomDomainParams {
+  /// The host name, such as `docs.example.com`.
+  pub domain: String,
+}
+
+/// A domain that serves the publish namespace of the workspace, once the workspace proved that
+/// it controls the domain by adding the verification TXT record to its DNS.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublishCustomDomain {
+  pub domain: String,
+  /// The name of the TXT record to add to the DNS of the domain.
+  pub verification_record_name: String,
+  /// The value of the TXT record to add to the DNS of the domain.
+  pub verification_record_value: String,
+  /// `None` until the domain is verified. Only verified domains are served.
+  pub verified_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(i16)]
+pub enum WorkspaceExportStatus {
+  Pending = 0,
+  Completed = 1,
+  Failed = 2,
+}
+
+impl From<i16> for WorkspaceExportStatus {
+  fn from(value: i16) -> Self {
+    match value {
+      1 => WorkspaceExportStatus::Completed,
+      2 => WorkspaceExportStatus::Failed,
+      _ => WorkspaceExportStatus::Pending,
+    }
+  }
+}
+
+/// An export of a workspace that is built in the background.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceExportTask {
+  pub task_id: Uuid,
+  pub status: WorkspaceExportStatus,
+  /// The path, relative to the base url of the server, the archive can be downloaded from once
+  /// the export has completed.
+  pub download_url: Option<String>,
+  /// The size of the archive in bytes, once the export has completed.
+  pub file_size: Option<i64>,
+  /// Why the export failed, if it did.
+  pub error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// An export of everything tied to a user, built in the background: the profile, the workspace
+/// memberships, the collabs the user owns and the comments the user wrote.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserDataExportTask {
+  pub task_id: Uuid,
+  pub status: WorkspaceExportStatus,
+  /// The signed path, relative to the base url of the server, the archive can be downloaded from
+  /// without authentication once the export has completed. The link stops working at
+  /// `download_url_expires_at`; get the task again for a new one.
+  pub download_url: Option<String>,
+  pub download_url_expires_at: Option<DateTime<Utc>>,
+  /// The size of the archive in bytes, once the export has completed.
+  pub file_size: Option<i64>,
+  /// Why the export failed, if it did.
+  pub error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UserDataExportDownloadQuery {
+  pub expires: i64,
+  pub signature: String,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct DuplicateWorkspaceParams {
+  /// The name of the new workspace. Defaults to the name of the duplicated workspace, followed by
+  /// `(copy)`.
+  pub workspace_name: Option<String>,
+  /// Whether the members of the duplicated workspace become members of the new one.
+  #[serde(default)]
+  pub include_members: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(i16)]
+pub enum WorkspaceDuplicateStatus {
+  Pending = 0,
+  Completed = 1,
+  Failed = 2,
+}
+
+impl From<i16> for WorkspaceDuplicateStatus {
+  fn from(value: i16) -> Self {
+    match value {
+      1 => WorkspaceDuplicateStatus::Completed,
+      2 => WorkspaceDuplicateStatus::Failed,
+      _ => WorkspaceDuplicateStatus::Pending,
+    }
+  }
+}
+
+/// A duplication of a workspace that runs in the background.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceDuplicateTask {
+  pub task_id: Uuid,
+  pub status: WorkspaceDuplicateStatus,
+  /// The workspace the duplicate was created as, once the duplication has completed.
+  pub new_workspace_id: Option<Uuid>,
+  pub include_members: bool,
+  pub total_objects: i32,
+  /// The number of objects copied so far.
+  pub copied_objects: i32,
+  /// Why the duplication failed, if it did.
+  pub error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct QueryWorkspaceImport {
+  /// The view to create the imported pages under. Defaults to the top level of the workspace.
+  pub parent_view_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(i16)]
+pub enum WorkspaceImportStatus {
+  Pending = 0,
+  Completed = 1,
+  Failed = 2,
+}
+
+impl From<i16> for WorkspaceImportStatus {
+  fn from(value: i16) -> Self {
+    match value {
+      1 => WorkspaceImportStatus::Completed,
+      2 => WorkspaceImportStatus::Failed,
+      _ => WorkspaceImportStatus::Pending,
+    }
+  }
+}
+
+/// An import into a workspace that runs in the background.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceImportTask {
+  pub task_id: Uuid,
+  pub status: WorkspaceImportStatus,
+  /// The view the imported pages are created under.
+  pub view_id: String,
+  pub total_pages: i32,
+  /// The number of pages imported so far.
+  pub imported_pages: i32,
+  /// Why the import failed, if it did.
+  pub error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// An import of a CSV file into an existing database that runs in the background.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseImportTask {
+  pub task_id: Uuid,
+  pub status: WorkspaceImportStatus,
+  /// The database view the rows are imported through.
+  pub view_id: String,
+  pub total_rows: i32,
+  /// The number of rows inserted so far.
+  pub imported_rows: i32,
+  /// The number of rows that could not be imported so far.
+  pub failed_rows: i32,
+  /// Why the rows that could not be imported failed. Only the first errors are kept.
+  pub row_errors: Vec<DatabaseImportRowError>,
+  /// Why the import failed, if it did.
+  pub error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseImportRowError {
+  /// The position of the row in the CSV file, starting at 1 for the first row after the header.
+  pub row: i32,
+  pub error: String,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct QueryPublishAnalytics {
+  /// The number of days, up to today, to return the reads of. Defaults to 30 days.
+  pub days: Option<u32>,
+}
+
+/// The reads of the views published by a workspace over the last days.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishAnalytics {
+  /// The first day counted.
+  pub since: NaiveDate,
+  /// The views that were read since `since`, the most read first.
+  pub views: Vec<PublishedViewAnalytics>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedViewAnalytics {
+  pub view_id: Uuid,
+  /// `None` if the view is no longer published.
+  pub publish_name: Option<String>,
+  pub total_views: i64,
+  /// The reads per day, oldest first. Days without reads are left out.
+  pub daily_views: Vec<PublishedViewDailyViews>,
+  /// The sites that linked readers to the view, the top referrer first.
+  pub referrers: Vec<PublishedViewReferrer>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublishedViewDailyViews {
+  pub day: NaiveDate,
+  pub views: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublishedViewReferrer {
+  /// The host of the referring page, such as `news.ycombinator.com`.
+  pub host: String,
+  pub views: i64,
+}
+
+/// How long the snapshots of each collab of a workspace are kept. A snapshot is kept if any of
+/// the rules keeps it, the others are deleted by a periodic cleanup. The tiers keep the most
+/// recent snapshot of each hour, day or week within their window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotRetentionPolicy {
+  /// The number of most recent snapshots that are always kept.
+  pub keep_last: i32,
+  /// All the snapshots created in the last `keep_days` days are kept.
+  #[serde(default)]
+  pub keep_days: i32,
+  /// One snapshot per hour is kept for the last `hourly_days` days.
+  #[serde(default)]
+  pub hourly_days: i32,
+  /// One snapshot per day is kept for the last `daily_days` days.
+  #[serde(default)]
+  pub daily_days: i32,
+  /// One snapshot per week is kept for the last `weekly_weeks` weeks.
+  #[serde(default)]
+  pub weekly_weeks: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceSnapshotRetention {
+  pub workspace_id: Uuid,
+  pub policy: SnapshotRetentionPolicy,
+  /// Whether the policy is the default of the server, as the workspace doesn't override it.
+  pub is_default: bool,
+  /// When the policy was last overridden, `None` for the default policy.
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// The storage used by a workspace against the quota of its plan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceStorageQuota {
+  pub workspace_id: Uuid,
+  pub plan: SubscriptionPlan,
+  pub collab_bytes: i64,
+  pub blob_bytes: i64,
+  /// The collabs and the blobs together, which is what the quota applies to.
+  pub used_bytes: i64,
+  /// `None` when the storage of the workspace is unlimited.
+  pub quota_bytes: Option<i64>,
+}
+
+/// The AI usage of a workspace in the current month against the quota of its plan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceAIUsage {
+  pub workspace_id: Uuid,
+  pub plan: SubscriptionPlan,
+  /// The first day of the month, from which the usage is counted.
+  pub period_start: NaiveDate,
+  /// The first day of the next month, when the usage starts over.
+  pub period_end: NaiveDate,
+  pub requests: i64,
+  pub tokens_consumed: i64,
+  /// `None` when the workspace can make unlimited AI requests.
+  pub request_quota: Option<i64>,
+  /// `None` when the workspace can consume unlimited tokens.
+  pub token_quota: Option<i64>,
+  /// The usage of each day of the month that had any. The searches and the indexing of the
+  /// documents are reported too, but they don't count towards the quota.
+  pub daily: Vec<WorkspaceAIDailyUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceAIDailyUsage {
+  pub day: NaiveDate,
+  pub requests: i64,
+  pub tokens_consumed: i64,
+  pub search_requests: i64,
+  pub search_tokens_consumed: i64,
+  pub index_tokens_consumed: i64,
+}
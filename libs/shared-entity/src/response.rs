@@ -133,6 +133,12 @@ where
 {
   pub async fn from_response(resp: reqwest::Response) -> Result<Self, anyhow::Error> {
     let status_code = resp.status();
+    // Rate limited requests carry the error code in their body
+    if status_code == reqwest::StatusCode::TOO_MANY_REQUESTS {
+      let bytes = resp.bytes().await?;
+      let resp = serde_json::from_slice(&bytes)?;
+      return Ok(resp);
+    }
     if !status_code.is_success() {
       let body = resp.text().await?;
       anyhow::bail!("got error code: {}, body: {}", status_code, body)
@@ -10,6 +10,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     //   "history.SnapshotMeta",
     //   "#[derive(serde::Serialize, serde::Deserialize)]",
     // )
-    .compile(&["proto/history.proto"], &["proto"])?;
+    .compile(
+      &["proto/history.proto", "proto/integration.proto"],
+      &["proto"],
+    )?;
   Ok(())
 }
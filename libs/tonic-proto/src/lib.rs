@@ -1,3 +1,7 @@
 pub mod history {
   tonic::include_proto!("history");
 }
+
+pub mod integration {
+  tonic::include_proto!("integration");
+}
@@ -7,7 +7,7 @@ use access_control::access::ObjectType;
 use access_control::act::{Action, ActionVariant};
 use access_control::workspace::WorkspaceAccessControl;
 use app_error::AppError;
-use database_entity::dto::AFRole;
+use database_entity::dto::{AFRole, WorkspaceCapability};
 
 #[derive(Clone)]
 pub struct WorkspaceAccessControlImpl {
@@ -56,6 +56,36 @@ impl WorkspaceAccessControl for WorkspaceAccessControlImpl {
       .await
   }
 
+  async fn enforce_capability(
+    &self,
+    uid: &i64,
+    workspace_id: &str,
+    capability: WorkspaceCapability,
+  ) -> Result<bool, AppError> {
+    self
+      .access_control
+      .enforce(
+        workspace_id,
+        uid,
+        ObjectType::Workspace(workspace_id),
+        ActionVariant::FromCapability(&capability),
+      )
+      .await
+  }
+
+  #[instrument(level = "info", skip_all)]
+  async fn update_capabilities(
+    &self,
+    uid: &i64,
+    workspace_id: &Uuid,
+    capabilities: &[WorkspaceCapability],
+  ) -> Result<(), AppError> {
+    self
+      .access_control
+      .update_workspace_capabilities(uid, &workspace_id.to_string(), capabilities)
+      .await
+  }
+
   #[instrument(level = "info", skip_all)]
   async fn insert_role(
     &self,
@@ -74,6 +104,19 @@ impl WorkspaceAccessControl for WorkspaceAccessControlImpl {
     Ok(())
   }
 
+  #[instrument(level = "info", skip_all)]
+  async fn replace_role(
+    &self,
+    uid: &i64,
+    workspace_id: &Uuid,
+    role: AFRole,
+  ) -> Result<(), AppError> {
+    self
+      .access_control
+      .replace_workspace_role(uid, &workspace_id.to_string(), &role)
+      .await
+  }
+
   #[instrument(level = "info", skip_all)]
   async fn remove_user_from_workspace(
     &self,
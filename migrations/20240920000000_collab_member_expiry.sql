@@ -0,0 +1,8 @@
+-- Time-limited collab membership. A NULL value means the membership never expires.
+ALTER TABLE af_collab_member
+ADD COLUMN IF NOT EXISTS expires_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+
+-- Only a small fraction of members have an expiry, so keep the index partial.
+CREATE INDEX IF NOT EXISTS idx_af_collab_member_expires_at
+  ON af_collab_member (expires_at)
+  WHERE expires_at IS NOT NULL;
@@ -0,0 +1,2 @@
+-- Whether a published view is listed in the navigation menu of the public site
+ALTER TABLE af_published_collab ADD COLUMN show_in_nav BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+-- The version of the collab at the time it was last published, used to skip unchanged republishes
+ALTER TABLE af_published_collab ADD COLUMN published_version BIGINT DEFAULT NULL;
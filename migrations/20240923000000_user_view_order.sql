@@ -0,0 +1,10 @@
+-- Per user ordering of the children of a view. It only affects how the folder is presented to the
+-- user, the order of the views in the shared folder is left untouched.
+CREATE TABLE IF NOT EXISTS af_user_view_order (
+    uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    parent_view_id TEXT NOT NULL,
+    ordered_child_ids TEXT[] NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (uid, workspace_id, parent_view_id)
+);
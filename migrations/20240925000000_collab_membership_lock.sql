@@ -0,0 +1,8 @@
+-- Collabs whose membership is frozen, e.g. during a migration or a legal hold.
+-- While a collab is listed here, its members can not be added, changed or removed.
+CREATE TABLE IF NOT EXISTS af_collab_membership_lock (
+    oid TEXT PRIMARY KEY,
+    locked_by BIGINT REFERENCES af_user(uid) ON DELETE SET NULL,
+    reason TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,4 @@
+-- The time after which a published view is no longer served. NULL means it never expires
+ALTER TABLE af_published_collab ADD COLUMN expires_at TIMESTAMP WITH TIME ZONE;
+CREATE INDEX IF NOT EXISTS idx_af_published_collab_expires_at
+  ON af_published_collab (expires_at) WHERE expires_at IS NOT NULL;
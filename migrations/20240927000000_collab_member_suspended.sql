@@ -0,0 +1,2 @@
+-- A suspended member keeps its access level, but has no access until it is unsuspended
+ALTER TABLE af_collab_member ADD COLUMN suspended BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+-- The last time the access level of the member was changed. NULL when it never changed since the member was added.
+ALTER TABLE af_collab_member ADD COLUMN access_level_updated_at TIMESTAMP WITH TIME ZONE;
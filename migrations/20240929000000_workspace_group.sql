@@ -0,0 +1,80 @@
+-- Groups of workspace members that can be granted access to a collab as a whole.
+CREATE TABLE IF NOT EXISTS af_workspace_group (
+    group_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (workspace_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS af_workspace_group_member (
+    group_id UUID NOT NULL REFERENCES af_workspace_group(group_id) ON DELETE CASCADE,
+    uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (group_id, uid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_workspace_group_member_uid ON af_workspace_group_member (uid);
+
+-- The access level a group holds on a collab. Every member of the group gets that level.
+CREATE TABLE IF NOT EXISTS af_collab_group_member (
+    group_id UUID NOT NULL REFERENCES af_workspace_group(group_id) ON DELETE CASCADE,
+    oid TEXT NOT NULL,
+    permission_id INTEGER NOT NULL REFERENCES af_permissions(id),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (group_id, oid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_collab_group_member_oid ON af_collab_group_member (oid);
+
+-- Notify the realtime servers of the changes of the groups, so that they enforce the access of
+-- the groups the same way the api server does.
+DROP TRIGGER IF EXISTS af_workspace_group_member_change_trigger ON af_workspace_group_member;
+
+CREATE OR REPLACE FUNCTION notify_af_workspace_group_member_change() RETURNS trigger AS $$
+DECLARE
+payload TEXT;
+BEGIN
+    payload := json_build_object(
+            'old', row_to_json(OLD),
+            'new', row_to_json(NEW),
+            'action_type', TG_OP
+            )::text;
+
+    PERFORM pg_notify('af_workspace_group_member_channel', payload);
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+ELSE
+        RETURN NEW;
+END IF;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER af_workspace_group_member_change_trigger
+    AFTER INSERT OR UPDATE OR DELETE ON af_workspace_group_member
+    FOR EACH ROW EXECUTE FUNCTION notify_af_workspace_group_member_change();
+
+DROP TRIGGER IF EXISTS af_collab_group_member_change_trigger ON af_collab_group_member;
+
+CREATE OR REPLACE FUNCTION notify_af_collab_group_member_change() RETURNS trigger AS $$
+DECLARE
+payload TEXT;
+BEGIN
+    payload := json_build_object(
+            'old', row_to_json(OLD),
+            'new', row_to_json(NEW),
+            'action_type', TG_OP
+            )::text;
+
+    PERFORM pg_notify('af_collab_group_member_channel', payload);
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+ELSE
+        RETURN NEW;
+END IF;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER af_collab_group_member_change_trigger
+    AFTER INSERT OR UPDATE OR DELETE ON af_collab_group_member
+    FOR EACH ROW EXECUTE FUNCTION notify_af_collab_group_member_change();
@@ -0,0 +1,2 @@
+-- The user who created the snapshot. Snapshots taken periodically by the server have no author.
+ALTER TABLE af_collab_snapshot ADD COLUMN IF NOT EXISTS created_by BIGINT DEFAULT NULL;
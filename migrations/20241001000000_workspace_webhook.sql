@@ -0,0 +1,35 @@
+-- Endpoints that are called back when events happen in a workspace. An empty event filter
+-- subscribes the endpoint to every event.
+CREATE TABLE IF NOT EXISTS af_workspace_webhook (
+    webhook_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_workspace_webhook_workspace_id ON af_workspace_webhook (workspace_id);
+
+-- Callbacks waiting to be delivered. A delivery is removed once the endpoint accepts it, and is
+-- kept as a dead letter, with `dead_at` set, once it has failed too many times.
+CREATE TABLE IF NOT EXISTS af_webhook_delivery (
+    delivery_id BIGSERIAL PRIMARY KEY,
+    webhook_id UUID NOT NULL REFERENCES af_workspace_webhook(webhook_id) ON DELETE CASCADE,
+    event TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_error TEXT,
+    dead_at TIMESTAMP WITH TIME ZONE,
+    -- Pending deliveries of the same object are merged, see `insert_webhook_deliveries`.
+    coalesce_key TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_webhook_delivery_pending
+    ON af_webhook_delivery (next_attempt_at) WHERE dead_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_af_webhook_delivery_webhook_id ON af_webhook_delivery (webhook_id);
+CREATE INDEX IF NOT EXISTS idx_af_webhook_delivery_coalesce_key
+    ON af_webhook_delivery (webhook_id, coalesce_key)
+    WHERE coalesce_key IS NOT NULL AND dead_at IS NULL;
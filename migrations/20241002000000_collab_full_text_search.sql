@@ -0,0 +1,16 @@
+-- Plain text of the documents and databases of a workspace, kept up to date whenever the collab
+-- is written, for full-text search. The `simple` configuration is used because the content can be
+-- in any language.
+CREATE TABLE IF NOT EXISTS af_collab_search (
+    oid TEXT NOT NULL,
+    partition_key INTEGER NOT NULL,
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    content TEXT NOT NULL,
+    content_tsv TSVECTOR GENERATED ALWAYS AS (to_tsvector('simple', content)) STORED,
+    indexed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (oid, partition_key),
+    FOREIGN KEY (oid, partition_key) REFERENCES af_collab (oid, partition_key) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_collab_search_workspace_id ON af_collab_search (workspace_id);
+CREATE INDEX IF NOT EXISTS idx_af_collab_search_content_tsv ON af_collab_search USING GIN (content_tsv);
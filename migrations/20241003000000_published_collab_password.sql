@@ -0,0 +1,3 @@
+-- Published views with a password only serve their content to readers holding an access token,
+-- which is issued in exchange for the password.
+ALTER TABLE af_published_collab ADD COLUMN IF NOT EXISTS password_hash TEXT DEFAULT NULL;
@@ -0,0 +1,14 @@
+-- Custom domains that serve the publish namespace of a workspace. A domain is only served once
+-- the workspace proved that it controls it, with a DNS TXT record holding the verification token.
+-- Several workspaces can register the same domain, but only one of them can verify it.
+CREATE TABLE IF NOT EXISTS af_publish_custom_domain (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    domain TEXT NOT NULL,
+    verification_token TEXT NOT NULL,
+    verified_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (workspace_id, domain)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_af_publish_custom_domain_verified
+    ON af_publish_custom_domain (domain) WHERE verified_at IS NOT NULL;
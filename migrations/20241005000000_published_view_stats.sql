@@ -0,0 +1,26 @@
+-- Number of times a published view was read, per day. Only counts are kept: no ip address or
+-- any other data identifying the reader is stored.
+CREATE TABLE IF NOT EXISTS af_published_view_daily_stat (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    view_id UUID NOT NULL,
+    day DATE NOT NULL,
+    view_count BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (view_id, day)
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_published_view_daily_stat_workspace_day
+    ON af_published_view_daily_stat (workspace_id, day);
+
+-- Number of times a published view was read from a link on another site, per day. Only the host
+-- of the referring page is kept.
+CREATE TABLE IF NOT EXISTS af_published_view_referrer_stat (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    view_id UUID NOT NULL,
+    day DATE NOT NULL,
+    referrer_host TEXT NOT NULL,
+    view_count BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (view_id, day, referrer_host)
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_published_view_referrer_stat_workspace_day
+    ON af_published_view_referrer_stat (workspace_id, day);
@@ -0,0 +1,16 @@
+-- Exports of a workspace that are built in the background. The archive is stored in the bucket
+-- under `object_key` once the export has completed.
+CREATE TABLE IF NOT EXISTS af_workspace_export_task (
+    task_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    uid BIGINT NOT NULL,
+    status SMALLINT NOT NULL DEFAULT 0, -- 0: pending, 1: completed, 2: failed
+    object_key TEXT,
+    file_size BIGINT,
+    error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_workspace_export_task_workspace_id
+    ON af_workspace_export_task (workspace_id, created_at);
@@ -0,0 +1,17 @@
+-- Imports into a workspace that run in the background. The pages of the import are created
+-- under the view `view_id`, and `imported_pages` counts up to `total_pages` as they are created.
+CREATE TABLE IF NOT EXISTS af_workspace_import_task (
+    task_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    uid BIGINT NOT NULL,
+    view_id TEXT NOT NULL,
+    status SMALLINT NOT NULL DEFAULT 0, -- 0: pending, 1: completed, 2: failed
+    total_pages INTEGER NOT NULL DEFAULT 0,
+    imported_pages INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_workspace_import_task_workspace_id
+    ON af_workspace_import_task (workspace_id, created_at);
@@ -0,0 +1,12 @@
+-- The views that have been moved to the trash of a workspace. `deleted_by` is the user whose trash
+-- section holds the view. Views are deleted for good once they have been in the trash for longer
+-- than the retention window.
+CREATE TABLE IF NOT EXISTS af_view_trash (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    view_id TEXT NOT NULL,
+    deleted_by BIGINT NOT NULL,
+    deleted_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (workspace_id, view_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_view_trash_deleted_at ON af_view_trash (deleted_at);
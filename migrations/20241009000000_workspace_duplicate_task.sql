@@ -0,0 +1,19 @@
+-- Duplications of a workspace that run in the background. `copied_objects` counts up to
+-- `total_objects` as the collabs of the workspace are copied into `new_workspace_id`, which is
+-- only set once the duplication has completed.
+CREATE TABLE IF NOT EXISTS af_workspace_duplicate_task (
+    task_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    uid BIGINT NOT NULL,
+    new_workspace_id UUID REFERENCES af_workspace(workspace_id) ON DELETE SET NULL,
+    include_members BOOLEAN NOT NULL DEFAULT FALSE,
+    status SMALLINT NOT NULL DEFAULT 0, -- 0: pending, 1: completed, 2: failed
+    total_objects INTEGER NOT NULL DEFAULT 0,
+    copied_objects INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_workspace_duplicate_task_workspace_id
+    ON af_workspace_duplicate_task (workspace_id, created_at);
@@ -0,0 +1,31 @@
+-- Threaded comments anchored to a block of a collab. A reply belongs to the thread of the comment
+-- it replies to, and is anchored to the same block. Deleted comments are kept, without their
+-- content, so that their replies still belong to a thread.
+CREATE TABLE IF NOT EXISTS af_collab_comment (
+    comment_id UUID NOT NULL DEFAULT gen_random_uuid() PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    object_id TEXT NOT NULL,
+    block_id TEXT NOT NULL,
+    reply_comment_id UUID REFERENCES af_collab_comment(comment_id) ON DELETE CASCADE,
+    content TEXT NOT NULL,
+    -- preserve the comment when the user is removed
+    created_by BIGINT REFERENCES af_user(uid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- only the first comment of a thread is resolved, which resolves the whole thread
+    resolved_by BIGINT REFERENCES af_user(uid) ON DELETE SET NULL,
+    resolved_at TIMESTAMP WITH TIME ZONE,
+    is_deleted BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_collab_comment_object
+    ON af_collab_comment (workspace_id, object_id, created_at);
+
+-- The workspace members mentioned in a comment
+CREATE TABLE IF NOT EXISTS af_collab_comment_mention (
+    comment_id UUID NOT NULL REFERENCES af_collab_comment(comment_id) ON DELETE CASCADE,
+    uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    PRIMARY KEY (comment_id, uid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_collab_comment_mention_uid ON af_collab_comment_mention (uid);
@@ -0,0 +1,26 @@
+-- The events a user is notified of. `email_handled_at` is set once the notification has been
+-- emailed, or skipped because the user does not want to be emailed.
+CREATE TABLE IF NOT EXISTS af_notification (
+    id BIGSERIAL PRIMARY KEY,
+    uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    workspace_id UUID REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    data JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    email_handled_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_notification_uid ON af_notification (uid, created_at);
+CREATE INDEX IF NOT EXISTS idx_af_notification_email_pending
+    ON af_notification (uid) WHERE email_handled_at IS NULL;
+
+-- How each user wants to be emailed. Users without a row are emailed immediately.
+-- email_delivery: 0 = immediate, 1 = daily digest, 2 = off
+CREATE TABLE IF NOT EXISTS af_notification_setting (
+    uid BIGINT PRIMARY KEY REFERENCES af_user(uid) ON DELETE CASCADE,
+    email_delivery SMALLINT NOT NULL DEFAULT 0,
+    -- carried by the unsubscribe link of every email, so the user can opt out without signing in
+    unsubscribe_token UUID NOT NULL DEFAULT gen_random_uuid() UNIQUE,
+    last_emailed_at TIMESTAMP WITH TIME ZONE,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
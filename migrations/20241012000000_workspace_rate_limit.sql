@@ -0,0 +1,11 @@
+-- Overrides the default rate limits of the server for a workspace, such as the limits granted by
+-- the plan of the workspace. A NULL column keeps the default of the server.
+CREATE TABLE IF NOT EXISTS af_workspace_rate_limit (
+    workspace_id UUID PRIMARY KEY REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    -- the number of requests per minute to the workspace, all members together
+    requests_per_minute INTEGER,
+    -- the number of concurrent websocket connections of each member. A user that is a member of
+    -- several workspaces gets the highest limit of them.
+    ws_connections_per_user INTEGER,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
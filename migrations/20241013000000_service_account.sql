@@ -0,0 +1,27 @@
+-- Non-human members of a workspace that authenticate with API keys. Every service account is
+-- backed by its own user, so it goes through the same access control as the other members.
+CREATE TABLE IF NOT EXISTS af_service_account (
+    service_account_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    uid BIGINT NOT NULL UNIQUE REFERENCES af_user(uid) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    -- One of 'read_only', 'publish_only' or 'full'
+    scope TEXT NOT NULL,
+    created_by BIGINT REFERENCES af_user(uid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (workspace_id, name)
+);
+
+-- Only the SHA-256 of a key is stored, the key itself is returned once when it is created.
+CREATE TABLE IF NOT EXISTS af_api_key (
+    api_key_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    service_account_id UUID NOT NULL REFERENCES af_service_account(service_account_id) ON DELETE CASCADE,
+    key_hash TEXT NOT NULL UNIQUE,
+    -- The first characters of the key, to tell the keys apart
+    key_prefix TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_api_key_service_account_id ON af_api_key (service_account_id);
@@ -0,0 +1,35 @@
+-- The users and groups an identity provider provisions into a workspace through SCIM. The SCIM
+-- clients authenticate with the API key of a service account of scope 'scim'.
+
+-- A provisioned user is a member of the workspace while it is active.
+CREATE TABLE IF NOT EXISTS af_scim_user (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    external_id TEXT,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (workspace_id, uid)
+);
+
+-- The members of a group get the role the group is mapped to by the owner of the workspace. A
+-- member of several groups gets the highest of their roles, and a member of none gets the
+-- Member role.
+CREATE TABLE IF NOT EXISTS af_scim_group (
+    group_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    external_id TEXT,
+    display_name TEXT NOT NULL,
+    role_id INT REFERENCES af_roles(id),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (workspace_id, display_name)
+);
+
+CREATE TABLE IF NOT EXISTS af_scim_group_member (
+    group_id UUID NOT NULL REFERENCES af_scim_group(group_id) ON DELETE CASCADE,
+    uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    PRIMARY KEY (group_id, uid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_scim_group_member_uid ON af_scim_group_member (uid);
@@ -0,0 +1,16 @@
+-- The SAML identity provider of a workspace. The provider itself, its metadata and the
+-- validation of its assertions live in GoTrue, this table binds it to the workspace.
+CREATE TABLE IF NOT EXISTS af_workspace_sso (
+    workspace_id UUID PRIMARY KEY REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    -- The id of the SSO provider in GoTrue
+    provider_id UUID NOT NULL UNIQUE,
+    entity_id TEXT NOT NULL,
+    metadata_url TEXT,
+    domains TEXT[] NOT NULL DEFAULT '{}',
+    -- The role of the users provisioned on their first sign-in through the provider
+    default_role_id INT NOT NULL DEFAULT 2 REFERENCES af_roles(id),
+    -- When enforced, the members must sign in through the provider to access the workspace
+    enforced BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,28 @@
+-- The append-only record of the security relevant actions, for the compliance teams. The rows
+-- outlive the workspaces and the users they refer to, so there is no foreign key.
+CREATE TABLE IF NOT EXISTS af_audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    -- NULL for the actions that are not tied to a workspace, such as a login
+    workspace_id UUID,
+    -- NULL for the actions of the server itself
+    actor_uid BIGINT,
+    action TEXT NOT NULL,
+    -- What the action was applied to, such as the object id of a collab or the email of a member
+    target TEXT,
+    metadata JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_audit_log_workspace_id ON af_audit_log (workspace_id, id DESC);
+CREATE INDEX IF NOT EXISTS idx_af_audit_log_actor_uid ON af_audit_log (actor_uid, id DESC);
+
+CREATE OR REPLACE FUNCTION prevent_af_audit_log_change()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'af_audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER af_audit_log_append_only
+BEFORE UPDATE OR DELETE ON af_audit_log
+FOR EACH ROW EXECUTE FUNCTION prevent_af_audit_log_change();
@@ -0,0 +1,15 @@
+-- Exports of everything tied to a user, built in the background. The archive is stored in the
+-- bucket under `object_key` once the export has completed.
+CREATE TABLE IF NOT EXISTS af_user_data_export_task (
+    task_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    status SMALLINT NOT NULL DEFAULT 0, -- 0: pending, 1: completed, 2: failed
+    object_key TEXT,
+    file_size BIGINT,
+    error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_user_data_export_task_uid
+    ON af_user_data_export_task (uid, created_at);
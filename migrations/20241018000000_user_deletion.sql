@@ -0,0 +1,16 @@
+-- Accounts marked for deletion by their users. The account is deleted for good, with all of its
+-- data, once `purge_after` has passed, unless the user cancels the deletion before. The row is
+-- not tied to af_user so that it outlives the user until the deletion has completed.
+CREATE TABLE IF NOT EXISTS af_user_deletion (
+    uid BIGINT PRIMARY KEY,
+    uuid UUID NOT NULL,
+    -- The workspaces owned by the user to hand over to another member instead of deleting them,
+    -- as a list of `{ "workspace_id", "new_owner_email" }`
+    workspace_transfers JSONB NOT NULL DEFAULT '[]',
+    requested_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    purge_after TIMESTAMP WITH TIME ZONE NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_user_deletion_purge_after ON af_user_deletion (purge_after);
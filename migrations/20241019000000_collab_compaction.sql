@@ -0,0 +1,3 @@
+-- The length of the blob when the collab was last compacted. A collab whose length differs has
+-- been written since, and is compacted again once it grows above the configured size.
+ALTER TABLE af_collab ADD COLUMN IF NOT EXISTS compacted_len INTEGER;
@@ -0,0 +1,13 @@
+-- Overrides how long the snapshots of the collabs of a workspace are kept. Without a row, the
+-- most recent snapshots of each collab are kept up to the limit of the server. See
+-- `SnapshotRetentionPolicy` for the meaning of the columns.
+CREATE TABLE IF NOT EXISTS af_workspace_snapshot_retention (
+    workspace_id UUID PRIMARY KEY REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    keep_last INTEGER NOT NULL,
+    keep_days INTEGER NOT NULL DEFAULT 0,
+    hourly_days INTEGER NOT NULL DEFAULT 0,
+    daily_days INTEGER NOT NULL DEFAULT 0,
+    weekly_weeks INTEGER NOT NULL DEFAULT 0,
+    updated_by BIGINT,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,27 @@
+-- The bytes used by the collabs and the blobs of each workspace, counted again periodically by the
+-- server so that the storage quota can be checked on each write without summing them.
+CREATE TABLE IF NOT EXISTS af_workspace_storage_usage (
+    workspace_id UUID PRIMARY KEY REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    collab_bytes BIGINT NOT NULL DEFAULT 0,
+    blob_bytes BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- The plan of a workspace, which sets the storage quota of the workspace. The workspaces without
+-- a row are on the free plan.
+CREATE TABLE IF NOT EXISTS af_workspace_plan (
+    workspace_id UUID PRIMARY KEY REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    -- see SubscriptionPlan
+    plan SMALLINT NOT NULL DEFAULT 0,
+    -- overrides the storage quota of the plan when not NULL
+    storage_quota_bytes BIGINT,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO af_workspace_storage_usage (workspace_id, collab_bytes, blob_bytes)
+SELECT
+    w.workspace_id,
+    COALESCE((SELECT SUM(c.len) FROM af_collab c WHERE c.workspace_id = w.workspace_id), 0),
+    COALESCE((SELECT SUM(b.file_size) FROM af_blob_metadata b WHERE b.workspace_id = w.workspace_id), 0)
+FROM af_workspace w
+ON CONFLICT (workspace_id) DO NOTHING;
@@ -0,0 +1,12 @@
+-- The last time each user edited a collab of a workspace, for the usage metrics of the workspace.
+-- The collabs written by the server itself are recorded under its uid, so there is no foreign key
+-- on the uid.
+CREATE TABLE IF NOT EXISTS af_workspace_editor_activity (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    uid BIGINT NOT NULL,
+    last_edited_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (workspace_id, uid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_workspace_editor_activity_last_edited_at
+    ON af_workspace_editor_activity (workspace_id, last_edited_at);
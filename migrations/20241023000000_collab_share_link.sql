@@ -0,0 +1,24 @@
+-- Links that grant access to a single collab object to anyone who opens them.
+-- Unlike object tokens, the token is stored as is, so that the members who manage the sharing of
+-- the object can copy the link again.
+CREATE TABLE IF NOT EXISTS af_collab_share_link (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    token TEXT NOT NULL UNIQUE,
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    oid TEXT NOT NULL,
+    access_level INT NOT NULL,
+    created_by BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    expires_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_collab_share_link_oid ON af_collab_share_link (oid);
+
+-- The users that became members of the object by opening a share link, so that their access
+-- follows the changes of the link.
+CREATE TABLE IF NOT EXISTS af_collab_share_link_member (
+    link_id UUID NOT NULL REFERENCES af_collab_share_link(id) ON DELETE CASCADE,
+    uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (link_id, uid)
+);
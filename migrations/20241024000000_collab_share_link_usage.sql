@@ -0,0 +1,7 @@
+-- A share link stops working once it has been used `max_uses` times. NULL means unlimited.
+ALTER TABLE af_collab_share_link
+    ADD COLUMN IF NOT EXISTS max_uses INT DEFAULT NULL,
+    ADD COLUMN IF NOT EXISTS use_count INT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS last_used_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_af_collab_share_link_workspace_id ON af_collab_share_link (workspace_id);
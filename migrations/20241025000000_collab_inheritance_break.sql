@@ -0,0 +1,57 @@
+-- The views on which the inheritance of access from the parent views is broken. The members of
+-- the ancestors of such a view, and of the views above it, are not granted access to it nor to
+-- the views below it.
+CREATE TABLE IF NOT EXISTS af_collab_inheritance_break (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    oid TEXT NOT NULL,
+    created_by BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (workspace_id, oid)
+);
+
+-- Notify the servers when the inheritance of view permissions changes in a workspace, so that
+-- each of them reloads the hierarchy it caches instead of waiting for the cache to expire.
+DROP TRIGGER IF EXISTS af_collab_inheritance_break_change_trigger ON af_collab_inheritance_break;
+
+CREATE OR REPLACE FUNCTION notify_af_collab_inheritance_break_change() RETURNS trigger AS $$
+DECLARE
+payload TEXT;
+BEGIN
+    payload := json_build_object(
+            'workspace_id', COALESCE(NEW.workspace_id, OLD.workspace_id)
+            )::text;
+
+    PERFORM pg_notify('af_collab_inheritance_channel', payload);
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+ELSE
+        RETURN NEW;
+END IF;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER af_collab_inheritance_break_change_trigger
+    AFTER INSERT OR UPDATE OR DELETE ON af_collab_inheritance_break
+    FOR EACH ROW EXECUTE FUNCTION notify_af_collab_inheritance_break_change();
+
+-- The settings of the workspace tell whether its views inherit the permissions of their parents
+DROP TRIGGER IF EXISTS af_workspace_settings_change_trigger ON af_workspace;
+
+CREATE OR REPLACE FUNCTION notify_af_workspace_settings_change() RETURNS trigger AS $$
+DECLARE
+payload TEXT;
+BEGIN
+    payload := json_build_object(
+            'workspace_id', NEW.workspace_id
+            )::text;
+
+    PERFORM pg_notify('af_collab_inheritance_channel', payload);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER af_workspace_settings_change_trigger
+    AFTER UPDATE OF settings ON af_workspace
+    FOR EACH ROW
+    WHEN (OLD.settings IS DISTINCT FROM NEW.settings)
+    EXECUTE FUNCTION notify_af_workspace_settings_change();
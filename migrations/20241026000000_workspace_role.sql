@@ -0,0 +1,17 @@
+-- Roles defined by the owners of a workspace. A role grants its capabilities to the members it is
+-- assigned to, on top of the capabilities of their role_id in af_workspace_member.
+CREATE TABLE IF NOT EXISTS af_workspace_role (
+    role_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    capabilities TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (workspace_id, name)
+);
+
+-- A member holds at most one custom role.
+ALTER TABLE af_workspace_member
+    ADD COLUMN IF NOT EXISTS custom_role_id UUID REFERENCES af_workspace_role(role_id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_af_workspace_member_custom_role_id
+    ON af_workspace_member (custom_role_id);
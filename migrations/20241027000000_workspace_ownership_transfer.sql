@@ -0,0 +1,9 @@
+-- The transfers of the ownership of a workspace that wait for the new owner to accept them. A
+-- workspace has at most one pending transfer, requesting another one replaces it.
+CREATE TABLE IF NOT EXISTS af_workspace_ownership_transfer (
+    workspace_id UUID PRIMARY KEY REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    from_uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    to_uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
@@ -0,0 +1,9 @@
+-- Track the emails sent for each invitation, so that the owners of the workspace can resend them
+-- without flooding the invitee.
+ALTER TABLE af_workspace_invitation
+    ADD COLUMN IF NOT EXISTS last_sent_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    ADD COLUMN IF NOT EXISTS sent_count INT NOT NULL DEFAULT 1;
+
+-- The pending invitations of a user are looked up by email when the user signs up
+CREATE INDEX IF NOT EXISTS idx_af_workspace_invitation_invitee_email
+    ON af_workspace_invitation (invitee_email);
@@ -0,0 +1,30 @@
+-- Reusable links that let users join a workspace by themselves, with the role the link presets.
+-- An empty allowed_domains lets any user join, otherwise only the users whose email belongs to
+-- one of the domains.
+CREATE TABLE IF NOT EXISTS af_workspace_invite_link (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    token TEXT NOT NULL UNIQUE,
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    role_id INT NOT NULL REFERENCES af_roles(id),
+    allowed_domains TEXT[] NOT NULL DEFAULT '{}',
+    max_joins_per_hour INT NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    created_by BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    rotated_at TIMESTAMP WITH TIME ZONE DEFAULT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_workspace_invite_link_workspace_id
+    ON af_workspace_invite_link (workspace_id);
+
+-- The users that joined the workspace through an invite link, which bounds how many users join
+-- through the link in an hour.
+CREATE TABLE IF NOT EXISTS af_workspace_invite_link_join (
+    link_id UUID NOT NULL REFERENCES af_workspace_invite_link(id) ON DELETE CASCADE,
+    uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    joined_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (link_id, uid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_workspace_invite_link_join_joined_at
+    ON af_workspace_invite_link_join (link_id, joined_at);
@@ -0,0 +1,5 @@
+-- The requests made to the AI features the server serves itself, such as the summary of a
+-- document, and the tokens of the model they consumed.
+ALTER TABLE af_workspace_ai_usage
+    ADD COLUMN IF NOT EXISTS ai_requests INT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS ai_tokens_consumed BIGINT NOT NULL DEFAULT 0;
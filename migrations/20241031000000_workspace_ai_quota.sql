@@ -0,0 +1,4 @@
+-- Override the monthly AI quotas of the plan of the workspace when not NULL. 0 is unlimited.
+ALTER TABLE af_workspace_plan
+    ADD COLUMN IF NOT EXISTS ai_monthly_requests BIGINT,
+    ADD COLUMN IF NOT EXISTS ai_monthly_tokens BIGINT;
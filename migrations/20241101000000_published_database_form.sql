@@ -0,0 +1,24 @@
+-- Published database views that accept public submissions as a form. Only the whitelisted fields
+-- can be set by a submission, and the rows are created on behalf of the user that set the form up.
+CREATE TABLE IF NOT EXISTS af_published_database_form (
+    view_id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    field_ids TEXT[] NOT NULL,
+    max_submissions_per_hour INT NOT NULL,
+    require_captcha BOOLEAN NOT NULL DEFAULT FALSE,
+    created_by BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- The submissions of the forms, which bound how many rows a form, and a single ip address,
+-- create in an hour.
+CREATE TABLE IF NOT EXISTS af_published_database_form_submission (
+    id BIGSERIAL PRIMARY KEY,
+    view_id UUID NOT NULL REFERENCES af_published_database_form(view_id) ON DELETE CASCADE,
+    row_id TEXT NOT NULL,
+    ip TEXT,
+    submitted_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_published_database_form_submission_submitted_at
+    ON af_published_database_form_submission (view_id, submitted_at);
@@ -0,0 +1,10 @@
+-- Tokenized iCal feeds of database calendar views, which calendar apps subscribe to without
+-- signing in. A feed shows the rows that the user who created it can read.
+CREATE TABLE IF NOT EXISTS af_database_calendar_feed (
+    token TEXT PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    view_id TEXT NOT NULL,
+    created_by BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (view_id, created_by)
+);
@@ -0,0 +1,20 @@
+-- Imports of CSV files into an existing database, which run in the background. `imported_rows`
+-- and `failed_rows` count up to `total_rows` as the rows are inserted, and `row_errors` holds why
+-- the first rows that could not be imported failed.
+CREATE TABLE IF NOT EXISTS af_database_import_task (
+    task_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    uid BIGINT NOT NULL,
+    view_id TEXT NOT NULL,
+    status SMALLINT NOT NULL DEFAULT 0, -- 0: pending, 1: completed, 2: failed
+    total_rows INTEGER NOT NULL DEFAULT 0,
+    imported_rows INTEGER NOT NULL DEFAULT 0,
+    failed_rows INTEGER NOT NULL DEFAULT 0,
+    row_errors JSONB NOT NULL DEFAULT '[]',
+    error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_database_import_task_workspace_id
+    ON af_database_import_task (workspace_id, created_at);
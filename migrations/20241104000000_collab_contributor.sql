@@ -0,0 +1,15 @@
+-- The users who edited each collab, so that the contributors of a collab and who last edited it
+-- can be shown without going through its updates. `edit_count` counts the updates of the user,
+-- which are recorded when the collab is saved.
+CREATE TABLE IF NOT EXISTS af_collab_contributor (
+    oid TEXT NOT NULL,
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    uid BIGINT NOT NULL,
+    edit_count BIGINT NOT NULL DEFAULT 0,
+    first_edited_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_edited_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (oid, uid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_collab_contributor_last_edited_at
+    ON af_collab_contributor (oid, last_edited_at DESC);
@@ -11,7 +11,10 @@ use anyhow::anyhow;
 use async_trait::async_trait;
 use bytes::Bytes;
 use collab_rt_entity::user::RealtimeUser;
-use collab_rt_entity::SystemMessage;
+use collab_rt_entity::{
+  MessageByObjectId, RealtimeCompression, RealtimeProtocolVersion, SystemMessage, UpgradeRequired,
+  UPGRADE_REQUIRED_CLOSE_CODE,
+};
 use governor::clock::DefaultClock;
 use governor::middleware::NoOpMiddleware;
 use governor::state::{InMemoryState, NotKeyed};
@@ -33,6 +36,14 @@ pub trait RealtimeServer:
 {
 }
 
+/// Restricts the objects a connection syncs further than the access of its user allows, for
+/// instance the objects of the workspaces its session was not signed in for.
+#[async_trait]
+pub trait RealtimeSessionGuard: Send + Sync {
+  /// Returns the messages of the objects the connection may sync.
+  async fn filter(&self, messages: MessageByObjectId) -> MessageByObjectId;
+}
+
 type BinaryRateLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>;
 pub struct RealtimeClient<S: RealtimeServer> {
   user: RealtimeUser,
@@ -48,6 +59,11 @@ pub struct RealtimeClient<S: RealtimeServer> {
   /// mechanism. This limits the number of messages a client can send per second, ensuring the server's
   /// mailbox does not get full from receiving too many messages at the same time.
   binary_rate_limiter: Arc<BinaryRateLimiter>,
+  /// The compression of the messages sent to the client, agreed on when the client connected.
+  compression: RealtimeCompression,
+  /// The version of the protocol agreed on when the client connected.
+  protocol_version: RealtimeProtocolVersion,
+  session_guard: Option<Arc<dyn RealtimeSessionGuard>>,
 }
 
 impl<S> RealtimeClient<S>
@@ -73,9 +89,27 @@ where
       external_source: Some(external_source),
       client_version,
       binary_rate_limiter: Arc::new(rate_limiter),
+      compression: RealtimeCompression::None,
+      protocol_version: RealtimeProtocolVersion::V1,
+      session_guard: None,
     }
   }
 
+  pub fn with_protocol_version(mut self, protocol_version: RealtimeProtocolVersion) -> Self {
+    self.protocol_version = protocol_version;
+    self
+  }
+
+  pub fn with_compression(mut self, compression: RealtimeCompression) -> Self {
+    self.compression = compression;
+    self
+  }
+
+  pub fn with_session_guard(mut self, session_guard: Arc<dyn RealtimeSessionGuard>) -> Self {
+    self.session_guard = Some(session_guard);
+    self
+  }
+
   fn hb(&self, ctx: &mut ws::WebsocketContext<Self>) {
     ctx.run_interval(self.heartbeat_interval, move |act, ctx| {
       if Instant::now().duration_since(act.hb) > act.client_timeout {
@@ -123,10 +157,25 @@ where
     }
     let server = self.server.clone();
     let user = self.user.clone();
+    let protocol_version = self.protocol_version;
+    let session_guard = self.session_guard.clone();
 
     let fut = async move {
-      match tokio::task::spawn_blocking(move || RealtimeMessage::decode(&bytes)).await {
+      match tokio::task::spawn_blocking(move || {
+        RealtimeMessage::decode_for(&bytes, protocol_version)
+      })
+      .await
+      {
         Ok(Ok(decoded_message)) => {
+          let decoded_message = match session_guard {
+            Some(session_guard) => {
+              match guard_message(session_guard.as_ref(), decoded_message).await {
+                Some(message) => message,
+                None => return Ok(()),
+              }
+            },
+            None => decoded_message,
+          };
           let mut client_message = Some(ClientMessage {
             user,
             message: decoded_message,
@@ -170,6 +219,18 @@ where
     }));
   }
 
+  fn send_message(&self, ctx: &mut WebsocketContext<RealtimeClient<S>>, message: &RealtimeMessage) {
+    match message.encode_for(self.protocol_version, self.compression) {
+      Ok(Some(data)) => ctx.binary(Bytes::from(data)),
+      Ok(None) => trace!(
+        "Skip message {} unknown to protocol {:?}",
+        message,
+        self.protocol_version
+      ),
+      Err(err) => error!("Error encoding message: {}", err),
+    }
+  }
+
   fn handle_ping(&mut self, ctx: &mut WebsocketContext<RealtimeClient<S>>, msg: &Bytes) {
     self.hb = Instant::now();
     ctx.pong(msg);
@@ -194,6 +255,15 @@ where
 
   fn started(&mut self, ctx: &mut Self::Context) {
     self.hb(ctx);
+    // Confirm the version and the compression before sending anything else, the client keeps
+    // sending uncompressed messages until it receives the confirmation. The clients of the first
+    // version don't know these messages, so they aren't sent to them.
+    let version = RealtimeMessage::System(SystemMessage::ProtocolVersion(self.protocol_version));
+    self.send_message(ctx, &version);
+    if self.compression != RealtimeCompression::None {
+      let compression = RealtimeMessage::System(SystemMessage::Compression(self.compression));
+      self.send_message(ctx, &compression);
+    }
     let recipient = ctx.address().recipient();
     if let Some(mut external_source) = self.external_source.take() {
       actix::spawn(async move {
@@ -272,10 +342,7 @@ where
   type Result = ();
 
   fn handle(&mut self, message: RealtimeMessage, ctx: &mut Self::Context) {
-    match message.encode() {
-      Ok(data) => ctx.binary(Bytes::from(data)),
-      Err(err) => error!("Error encoding message: {}", err),
-    }
+    self.send_message(ctx, &message);
 
     if let RealtimeMessage::System(SystemMessage::DuplicateConnection) = &message {
       let reason = CloseReason {
@@ -312,6 +379,62 @@ where
   }
 }
 
+/// Closes the connection of a client whose protocol version isn't supported, with
+/// [UPGRADE_REQUIRED_CLOSE_CODE] and the supported versions as the reason.
+pub struct UpgradeRequiredClient {
+  reason: UpgradeRequired,
+}
+
+impl UpgradeRequiredClient {
+  pub fn new(client_version: u32, min_version: u32) -> Self {
+    Self {
+      reason: UpgradeRequired {
+        client_version,
+        min_version,
+        current_version: RealtimeProtocolVersion::CURRENT as u32,
+      },
+    }
+  }
+}
+
+impl Actor for UpgradeRequiredClient {
+  type Context = ws::WebsocketContext<Self>;
+
+  fn started(&mut self, ctx: &mut Self::Context) {
+    let reason = CloseReason {
+      code: CloseCode::Other(UPGRADE_REQUIRED_CLOSE_CODE),
+      description: serde_json::to_string(&self.reason).ok(),
+    };
+    ctx.close(Some(reason));
+    ctx.stop();
+  }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for UpgradeRequiredClient {
+  fn handle(&mut self, _msg: Result<ws::Message, ws::ProtocolError>, _ctx: &mut Self::Context) {}
+}
+
+/// Drops the collab messages of the objects the [RealtimeSessionGuard] keeps the connection
+/// from syncing. Returns None when nothing is left to send.
+async fn guard_message(
+  session_guard: &dyn RealtimeSessionGuard,
+  message: RealtimeMessage,
+) -> Option<RealtimeMessage> {
+  match message {
+    RealtimeMessage::Collab(_)
+    | RealtimeMessage::ClientCollabV1(_)
+    | RealtimeMessage::ClientCollabV2(_) => {
+      let messages = session_guard.filter(message.transform().ok()?).await;
+      if messages.is_empty() {
+        None
+      } else {
+        Some(RealtimeMessage::ClientCollabV2(messages))
+      }
+    },
+    message => Some(message),
+  }
+}
+
 #[derive(Clone)]
 pub struct RealtimeClientWebsocketSinkImpl(pub Recipient<RealtimeMessage>);
 
@@ -9,6 +9,7 @@ use actix_web::{web, HttpRequest, HttpResponse, Result, Scope};
 use actix_web_actors::ws;
 use anyhow::anyhow;
 use bytes::{Bytes, BytesMut};
+use prometheus_client::encoding::text::encode;
 use prost::Message;
 use secrecy::Secret;
 use semver::Version;
@@ -20,10 +21,13 @@ use tracing::{debug, error, event, instrument, trace};
 use app_error::AppError;
 use authentication::jwt::{authorization_from_token, UserUuid};
 use collab_rt_entity::user::{AFUserChange, RealtimeUser, UserMessage};
-use collab_rt_entity::{HttpRealtimeMessage, RealtimeMessage};
+use collab_rt_entity::{
+  HttpRealtimeMessage, RealtimeCompression, RealtimeMessage, RealtimeProtocolVersion,
+  COMPRESSION_HEADER, PROTOCOL_VERSION_HEADER,
+};
 use shared_entity::response::{AppResponse, AppResponseError};
 
-use crate::actix_ws::client::RealtimeClient;
+use crate::actix_ws::client::{RealtimeClient, UpgradeRequiredClient};
 use crate::actix_ws::entities::ClientStreamMessage;
 use crate::actix_ws::server::RealtimeServerActor;
 use crate::collab::access_control::RealtimeCollabAccessControlImpl;
@@ -47,6 +51,23 @@ pub fn collab_scope() -> Scope {
   )
 }
 
+pub fn metrics_scope() -> Scope {
+  web::scope("/metrics").service(web::resource("").route(web::get().to(metrics_handler)))
+}
+
+async fn metrics_handler(state: Data<AppState>) -> Result<HttpResponse> {
+  let mut body = String::new();
+  encode(&mut body, &state.metrics.registry).map_err(|e| {
+    error!("Failed to encode metrics: {:?}", e);
+    actix_web::error::ErrorInternalServerError(e)
+  })?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("application/openmetrics-text; version=1.0.0; charset=utf-8")
+      .body(body),
+  )
+}
+
 const MAX_FRAME_SIZE: usize = 65_536; // 64 KiB
 
 pub type RealtimeServerAddr =
@@ -68,6 +89,8 @@ pub async fn establish_ws_connection_v1(
     client_version,
     device_id,
     connect_at,
+    compression,
+    protocol_version,
   } = match ConnectInfo::parse_from(&request) {
     Ok(info) => info,
     Err(_) => {
@@ -80,6 +103,20 @@ pub async fn establish_ws_connection_v1(
     return Err(AppError::Connect("Client version is too low".to_string()).into());
   }
 
+  let min_protocol_version = state.config.websocket.min_protocol_version;
+  let protocol_version =
+    match RealtimeProtocolVersion::negotiate(protocol_version, min_protocol_version) {
+      Some(protocol_version) => protocol_version,
+      None => {
+        trace!(
+          "Reject websocket connection of protocol version {}",
+          protocol_version
+        );
+        let client = UpgradeRequiredClient::new(protocol_version, min_protocol_version);
+        return ws::start(client, &request, payload);
+      },
+    };
+
   start_connect(
     &request,
     payload,
@@ -90,6 +127,8 @@ pub async fn establish_ws_connection_v1(
     device_id,
     client_version,
     connect_at,
+    compression,
+    protocol_version,
   )
   .await
 }
@@ -247,6 +286,8 @@ async fn start_connect(
   device_id: String,
   client_app_version: Version,
   connect_at: i64,
+  compression: RealtimeCompression,
+  protocol_version: RealtimeProtocolVersion,
 ) -> Result<HttpResponse> {
   let auth = authorization_from_token(access_token.as_str(), jwt_secret)?;
   let user_uuid = UserUuid::from_auth(auth)?;
@@ -276,7 +317,13 @@ async fn start_connect(
         client_app_version,
         external_source,
         10,
-      );
+      )
+      .with_compression(if state.config.websocket.compression {
+        compression
+      } else {
+        RealtimeCompression::None
+      })
+      .with_protocol_version(protocol_version);
 
       // Receive user change notifications and send them to the client.
       listen_on_user_change(state, uid, tx);
@@ -332,6 +379,9 @@ struct ConnectInfo {
   client_version: Version,
   device_id: String,
   connect_at: i64,
+  compression: RealtimeCompression,
+  /// The latest version of the realtime protocol the client supports.
+  protocol_version: u32,
 }
 
 const CLIENT_VERSION: &str = "client-version";
@@ -385,12 +435,25 @@ impl ConnectInfo {
         .unwrap_or_else(|_| chrono::Utc::now().timestamp()),
       Err(_) => chrono::Utc::now().timestamp(),
     };
+    let compression = source
+      .extract_param(COMPRESSION_HEADER)
+      .map(|accepted| RealtimeCompression::negotiate(&accepted))
+      .unwrap_or_default();
+    // The clients that don't send their version speak the first version
+    let protocol_version = match source.extract_param(PROTOCOL_VERSION_HEADER) {
+      Ok(version) => version
+        .parse::<u32>()
+        .map_err(|_| AppError::InvalidRequest(format!("Invalid protocol version:{}", version)))?,
+      Err(_) => 1,
+    };
 
     Ok(Self {
       access_token,
       client_version,
       device_id,
       connect_at,
+      compression,
+      protocol_version,
     })
   }
 }
@@ -7,7 +7,6 @@ use actix_web::dev::Server;
 use actix_web::web::Data;
 use actix_web::{App, HttpServer};
 use anyhow::{Context, Error};
-use secrecy::ExposeSecret;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
 use tracing::{info, warn};
@@ -15,15 +14,20 @@ use tracing::{info, warn};
 use crate::actix_ws::server::RealtimeServerActor;
 use access_control::access::AccessControl;
 use appflowy_ai_client::client::AppFlowyAIClient;
+use collab_stream::connection::{spawn_check_redis_nodes, RedisSetting};
 use workspace_access::notification::spawn_listen_on_workspace_member_change;
 use workspace_access::WorkspaceAccessControlImpl;
 
-use crate::api::{collab_scope, ws_scope};
+use crate::api::{collab_scope, metrics_scope, ws_scope};
 use crate::collab::access_control::{
   CollabAccessControlImpl, CollabStorageAccessControlImpl, RealtimeCollabAccessControlImpl,
 };
 use crate::collab::cache::CollabCache;
-use crate::collab::notification::spawn_listen_on_collab_member_change;
+use crate::collab::hierarchy::FolderCollabHierarchy;
+use crate::collab::notification::{
+  spawn_listen_on_collab_group_member_change, spawn_listen_on_collab_inheritance_change,
+  spawn_listen_on_collab_member_change, spawn_listen_on_workspace_group_member_change,
+};
 use crate::collab::storage::CollabStorageImpl;
 use crate::command::{CLCommandReceiver, CLCommandSender};
 use crate::config::{Config, DatabaseSetting};
@@ -31,7 +35,7 @@ use crate::indexer::IndexerProvider;
 use crate::pg_listener::PgListeners;
 use crate::shared_state::RealtimeSharedState;
 use crate::snapshot::SnapshotControl;
-use crate::state::{AppMetrics, AppState, UserCache};
+use crate::state::{AppMetrics, AppState, RedisConnectionManager, UserCache};
 use crate::CollaborationServer;
 
 pub struct Application {
@@ -90,6 +94,7 @@ pub async fn run_actix_server(
       .app_data(Data::new(realtime_server_actor.clone()))
       .service(ws_scope())
       .service(collab_scope())
+      .service(metrics_scope())
   });
   server = server.listen(listener)?;
 
@@ -106,7 +111,7 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
   let user_cache = UserCache::new(pg_pool.clone()).await;
 
   info!("Connecting to Redis...");
-  let redis_conn_manager = get_redis_client(config.redis_uri.expose_secret()).await?;
+  let redis_conn_manager = get_redis_client(&config.redis).await?;
   let realtime_shared_state = RealtimeSharedState::new(redis_conn_manager.clone());
   if let Err(err) = realtime_shared_state.remove_all_connected_users().await {
     warn!("Failed to remove all connected users: {:?}", err);
@@ -115,8 +120,15 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
   // Pg listeners
   info!("Setting up Pg listeners...");
   let pg_listeners = Arc::new(PgListeners::new(&pg_pool).await?);
-  let access_control =
-    AccessControl::new(pg_pool.clone(), metrics.access_control_metrics.clone()).await?;
+  let collab_cache = CollabCache::new(
+    redis_conn_manager.clone(),
+    pg_pool.clone(),
+    metrics.collab_storage_metrics.clone(),
+  );
+  let collab_hierarchy = FolderCollabHierarchy::new(pg_pool.clone(), collab_cache.clone());
+  let access_control = AccessControl::new(pg_pool.clone(), metrics.access_control_metrics.clone())
+    .await?
+    .with_collab_hierarchy(Arc::new(collab_hierarchy));
   let collab_member_listener = pg_listeners.subscribe_collab_member_change();
   let workspace_member_listener = pg_listeners.subscribe_workspace_member_change();
 
@@ -126,10 +138,22 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
     collab_member_listener,
     access_control.clone(),
   );
+  spawn_listen_on_workspace_group_member_change(
+    pg_listeners.subscribe_workspace_group_member_change(),
+    access_control.clone(),
+  );
+  spawn_listen_on_collab_group_member_change(
+    pg_pool.clone(),
+    pg_listeners.subscribe_collab_group_member_change(),
+    access_control.clone(),
+  );
+  spawn_listen_on_collab_inheritance_change(
+    pg_listeners.subscribe_collab_inheritance_change(),
+    access_control.clone(),
+  );
 
   let collab_access_control = CollabAccessControlImpl::new(access_control.clone());
   let workspace_access_control = WorkspaceAccessControlImpl::new(access_control.clone());
-  let collab_cache = CollabCache::new(redis_conn_manager.clone(), pg_pool.clone());
 
   let collab_storage_access_control = CollabStorageAccessControlImpl {
     collab_access_control: collab_access_control.clone().into(),
@@ -149,6 +173,7 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
     rt_cmd_tx,
     redis_conn_manager.clone(),
     metrics.collab_metrics.clone(),
+    config.storage_quota.clone(),
   ));
   let app_state = AppState {
     config: Arc::new(config.clone()),
@@ -164,13 +189,15 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
   Ok(app_state)
 }
 
-async fn get_redis_client(redis_uri: &str) -> Result<redis::aio::ConnectionManager, Error> {
-  info!("Connecting to redis with uri: {}", redis_uri);
-  let manager = redis::Client::open(redis_uri)
-    .context("failed to connect to redis")?
-    .get_connection_manager()
+async fn get_redis_client(setting: &RedisSetting) -> Result<RedisConnectionManager, Error> {
+  info!("Connecting to redis with setting: {:?}", setting);
+  let manager = RedisConnectionManager::connect(setting)
     .await
     .context("failed to get the connection manager")?;
+  spawn_check_redis_nodes(
+    manager.clone(),
+    Duration::from_secs(setting.health_check_interval_secs),
+  );
   Ok(manager)
 }
 
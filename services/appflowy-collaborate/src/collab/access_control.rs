@@ -88,6 +88,48 @@ impl CollabAccessControl for CollabAccessControlImpl {
       .await?;
     Ok(())
   }
+
+  async fn get_access_policies(&self, oid: &str) -> Result<Vec<(i64, AFAccessLevel)>, AppError> {
+    self
+      .access_control
+      .get_access_levels(&ObjectType::Collab(oid))
+      .await
+  }
+
+  #[instrument(level = "info", skip_all)]
+  async fn update_group_access_level_policy(
+    &self,
+    guid: &str,
+    oid: &str,
+    level: AFAccessLevel,
+  ) -> Result<(), AppError> {
+    self
+      .access_control
+      .update_group_policy(
+        guid,
+        ObjectType::Collab(oid),
+        ActionVariant::FromAccessLevel(&level),
+      )
+      .await
+  }
+
+  #[instrument(level = "info", skip_all)]
+  async fn remove_group_access_level(&self, guid: &str, oid: &str) -> Result<(), AppError> {
+    self
+      .access_control
+      .remove_group_policy(guid, &ObjectType::Collab(oid))
+      .await
+  }
+
+  async fn add_group_member(&self, uid: &i64, guid: &str) -> Result<(), AppError> {
+    self.access_control.add_group_member(uid, guid).await;
+    Ok(())
+  }
+
+  async fn remove_group_member(&self, uid: &i64, guid: &str) -> Result<(), AppError> {
+    self.access_control.remove_group_member(uid, guid).await;
+    Ok(())
+  }
 }
 
 #[derive(Clone)]
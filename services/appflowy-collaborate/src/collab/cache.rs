@@ -1,9 +1,12 @@
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
+use bytes::Bytes;
 use collab::entity::EncodedCollab;
 use collab_entity::CollabType;
+use futures_util::stream::BoxStream;
 use futures_util::{stream, StreamExt};
 use itertools::{Either, Itertools};
 use sqlx::{PgPool, Transaction};
@@ -13,20 +16,30 @@ use app_error::AppError;
 use database::collab::CollabMetadata;
 use database_entity::dto::{CollabParams, QueryCollab, QueryCollabResult};
 
+use crate::collab::decode_util::encode_collab_from_bytes;
 use crate::collab::disk_cache::CollabDiskCache;
 use crate::collab::mem_cache::{cache_exp_secs_from_collab_type, CollabMemCache};
+use crate::metrics::CollabStorageMetrics;
 use crate::state::RedisConnectionManager;
 
+const MEMORY_SOURCE: &str = "memory";
+const DISK_SOURCE: &str = "disk";
+
 #[derive(Clone)]
 pub struct CollabCache {
   disk_cache: CollabDiskCache,
   mem_cache: CollabMemCache,
   success_attempts: Arc<AtomicU64>,
   total_attempts: Arc<AtomicU64>,
+  metrics: Arc<CollabStorageMetrics>,
 }
 
 impl CollabCache {
-  pub fn new(redis_conn_manager: RedisConnectionManager, pg_pool: PgPool) -> Self {
+  pub fn new(
+    redis_conn_manager: RedisConnectionManager,
+    pg_pool: PgPool,
+    metrics: Arc<CollabStorageMetrics>,
+  ) -> Self {
     let mem_cache = CollabMemCache::new(redis_conn_manager.clone());
     let disk_cache = CollabDiskCache::new(pg_pool.clone());
     Self {
@@ -34,6 +47,7 @@ impl CollabCache {
       mem_cache,
       success_attempts: Arc::new(AtomicU64::new(0)),
       total_attempts: Arc::new(AtomicU64::new(0)),
+      metrics,
     }
   }
 
@@ -68,22 +82,33 @@ impl CollabCache {
   }
 
   pub async fn get_encode_collab(&self, query: QueryCollab) -> Result<EncodedCollab, AppError> {
-    self.total_attempts.fetch_add(1, Ordering::Relaxed);
+    let start = Instant::now();
+    self.record_cache_attempt();
     // Attempt to retrieve encoded collab from memory cache, falling back to disk cache if necessary.
-    if let Some(encoded_collab) = self.mem_cache.get_encode_collab(&query.object_id).await {
-      event!(
-        Level::DEBUG,
-        "Did get encode collab:{} from cache",
-        query.object_id
-      );
-      self.success_attempts.fetch_add(1, Ordering::Relaxed);
-      return Ok(encoded_collab);
+    if let Some(data) = self
+      .mem_cache
+      .get_encode_collab_data(&query.object_id)
+      .await
+    {
+      if let Ok(encoded_collab) = self.decode(data).await {
+        event!(
+          Level::DEBUG,
+          "Did get encode collab:{} from cache",
+          query.object_id
+        );
+        self.record_cache_hit();
+        self.metrics.record_read(MEMORY_SOURCE, 1);
+        self.metrics.record_read_time(start.elapsed());
+        return Ok(encoded_collab);
+      }
     }
 
     // Retrieve from disk cache as fallback. After retrieval, the value is inserted into the memory cache.
     let object_id = query.object_id.clone();
     let expiration_secs = cache_exp_secs_from_collab_type(&query.collab_type);
-    let encode_collab = self.disk_cache.get_collab_encoded_from_disk(query).await?;
+    let encode_collab = self.get_encode_collab_from_disk(query).await?;
+    self.metrics.record_read(DISK_SOURCE, 1);
+    self.metrics.record_read_time(start.elapsed());
 
     // spawn a task to insert the encoded collab into the memory cache
     let cloned_encode_collab = encode_collab.clone();
@@ -97,12 +122,41 @@ impl CollabCache {
     Ok(encode_collab)
   }
 
+  /// Returns the encoded collab, in the v1 format, as a stream of chunks. A collab that is not in
+  /// the memory cache is read from the disk, and is not put in the memory cache afterwards.
+  pub async fn get_encode_collab_stream(
+    &self,
+    query: QueryCollab,
+  ) -> Result<BoxStream<'static, Result<Bytes, AppError>>, AppError> {
+    let start = Instant::now();
+    self.record_cache_attempt();
+    if let Some(data) = self
+      .mem_cache
+      .get_encode_collab_data(&query.object_id)
+      .await
+    {
+      self.record_cache_hit();
+      self.metrics.record_read(MEMORY_SOURCE, 1);
+      self.metrics.record_read_time(start.elapsed());
+      return Ok(stream::once(async move { Ok(Bytes::from(data)) }).boxed());
+    }
+    let stream = self
+      .disk_cache
+      .get_collab_stream_from_disk(query)
+      .await
+      .inspect_err(|_| self.metrics.record_read_failure())?;
+    self.metrics.record_read(DISK_SOURCE, 1);
+    self.metrics.record_read_time(start.elapsed());
+    Ok(stream)
+  }
+
   /// Batch get the encoded collab data from the cache.
   /// returns a hashmap of the object_id to the encoded collab data.
   pub async fn batch_get_encode_collab<T: Into<QueryCollab>>(
     &self,
     queries: Vec<T>,
   ) -> HashMap<String, QueryCollabResult> {
+    let start = Instant::now();
     let queries = queries.into_iter().map(Into::into).collect::<Vec<_>>();
     let mut results = HashMap::new();
     // 1. Processes valid queries against the in-memory cache to retrieve cached values.
@@ -127,12 +181,20 @@ impl CollabCache {
       .await
       .into_iter()
       .partition_map(|either| either);
+    let mem_cache_count = values_from_mem_cache.len() as u64;
     results.extend(values_from_mem_cache);
 
     // 2. Retrieves remaining values from the disk cache for queries not satisfied by the memory cache.
     //    - These values are then merged into the final result set.
     let values_from_disk_cache = self.disk_cache.batch_get_collab(disk_queries).await;
+    let disk_cache_count = values_from_disk_cache
+      .values()
+      .filter(|result| matches!(result, QueryCollabResult::Success { .. }))
+      .count() as u64;
     results.extend(values_from_disk_cache);
+    self.metrics.record_read(MEMORY_SOURCE, mem_cache_count);
+    self.metrics.record_read(DISK_SOURCE, disk_cache_count);
+    self.metrics.record_read_time(start.elapsed());
     results
   }
 
@@ -147,10 +209,13 @@ impl CollabCache {
   ) -> Result<(), AppError> {
     let object_id = params.object_id.clone();
     let encode_collab_data = params.encoded_collab_v1.clone();
-    self
+    let start = Instant::now();
+    let result = self
       .disk_cache
       .upsert_collab_with_transaction(workspace_id, uid, params, transaction)
-      .await?;
+      .await;
+    self.metrics.record_write(result.is_ok(), start.elapsed());
+    result?;
 
     // when the data is written to the disk cache but fails to be written to the memory cache
     // we log the error and continue.
@@ -177,8 +242,12 @@ impl CollabCache {
     &self,
     query: QueryCollab,
   ) -> Result<EncodedCollab, AppError> {
-    let encode_collab = self.disk_cache.get_collab_encoded_from_disk(query).await?;
-    Ok(encode_collab)
+    let data = self
+      .disk_cache
+      .get_collab_blob_from_disk(query)
+      .await
+      .inspect_err(|_| self.metrics.record_read_failure())?;
+    self.decode(data).await
   }
 
   pub async fn insert_encode_collab_in_disk(
@@ -188,11 +257,13 @@ impl CollabCache {
     params: CollabParams,
     transaction: &mut Transaction<'_, sqlx::Postgres>,
   ) -> Result<(), AppError> {
-    self
+    let start = Instant::now();
+    let result = self
       .disk_cache
       .upsert_collab_with_transaction(workspace_id, uid, &params, transaction)
-      .await?;
-    Ok(())
+      .await;
+    self.metrics.record_write(result.is_ok(), start.elapsed());
+    result
   }
 
   /// Insert the encoded collab data into the memory cache.
@@ -214,6 +285,33 @@ impl CollabCache {
     Ok(())
   }
 
+  fn record_cache_attempt(&self) {
+    self.total_attempts.fetch_add(1, Ordering::Relaxed);
+    self.record_cache_hit_rate();
+  }
+
+  fn record_cache_hit(&self) {
+    self.success_attempts.fetch_add(1, Ordering::Relaxed);
+    self.record_cache_hit_rate();
+  }
+
+  fn record_cache_hit_rate(&self) {
+    let QueryState {
+      total_attempts,
+      success_attempts,
+    } = self.query_state();
+    self
+      .metrics
+      .record_cache_hit_rate(success_attempts, total_attempts);
+  }
+
+  async fn decode(&self, data: Vec<u8>) -> Result<EncodedCollab, AppError> {
+    let start = Instant::now();
+    let encoded_collab = encode_collab_from_bytes(data).await?;
+    self.metrics.record_decode(start.elapsed());
+    Ok(encoded_collab)
+  }
+
   pub fn query_state(&self) -> QueryState {
     let success_attempts = self.success_attempts.load(Ordering::Relaxed);
     let total_attempts = self.total_attempts.load(Ordering::Relaxed);
@@ -229,6 +327,11 @@ impl CollabCache {
     Ok(())
   }
 
+  /// Removes the encoded collab from the memory cache, so that it is read from the disk next time.
+  pub async fn remove_encode_collab_in_mem(&self, object_id: &str) -> Result<(), AppError> {
+    self.mem_cache.remove_encode_collab(object_id).await
+  }
+
   pub async fn is_exist(&self, oid: &str) -> Result<bool, AppError> {
     if let Ok(value) = self.mem_cache.is_exist(oid).await {
       if value {
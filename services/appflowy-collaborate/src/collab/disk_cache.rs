@@ -1,23 +1,30 @@
 use std::collections::HashMap;
+use std::ops::DerefMut;
 use std::time::Duration;
 
+use bytes::Bytes;
 use collab::entity::EncodedCollab;
 use collab_entity::CollabType;
+use futures_util::stream::{self, BoxStream, StreamExt};
 use sqlx::{Error, PgPool, Transaction};
 use tokio::time::sleep;
 use tracing::{event, instrument, Level};
 use uuid::Uuid;
 
 use crate::collab::decode_util::encode_collab_from_bytes;
+use crate::indexer::{full_text_content, is_full_text_indexed};
 use app_error::AppError;
 use database::collab::{
   batch_select_collab_blob, insert_into_af_collab, is_collab_exists, select_blob_from_af_collab,
   select_collab_meta_from_af_collab, AppResult,
 };
-use database::index::upsert_collab_embeddings;
+use database::index::{upsert_collab_embeddings, upsert_collab_search_content};
 use database::pg_row::AFCollabRowMeta;
 use database_entity::dto::{CollabParams, QueryCollab, QueryCollabResult};
 
+/// Size of the chunks a collab is streamed in.
+const COLLAB_STREAM_CHUNK_SIZE: usize = 512 * 1024;
+
 #[derive(Clone)]
 pub struct CollabDiskCache {
   pub pg_pool: PgPool,
@@ -67,6 +74,50 @@ impl CollabDiskCache {
     } else if params.collab_type == CollabType::Document {
       tracing::info!("no embeddings to save for collab {}", params.object_id);
     }
+    if is_full_text_indexed(&params.collab_type) {
+      self
+        .upsert_full_text_content(workspace_id, params, transaction)
+        .await?;
+    }
+    Ok(())
+  }
+
+  /// Index the plain text of the collab for full-text search. A collab whose text can't be
+  /// extracted is logged and left with its previous content, so that the write still succeeds.
+  async fn upsert_full_text_content(
+    &self,
+    workspace_id: &str,
+    params: &CollabParams,
+    transaction: &mut Transaction<'_, sqlx::Postgres>,
+  ) -> AppResult<()> {
+    let object_id = params.object_id.clone();
+    let collab_type = params.collab_type.clone();
+    let bytes = params.encoded_collab_v1.clone();
+    let result = tokio::task::spawn_blocking(move || {
+      let encoded_collab =
+        EncodedCollab::decode_from_bytes(&bytes).map_err(|err| AppError::Internal(err.into()))?;
+      full_text_content(&object_id, &collab_type, encoded_collab)
+    })
+    .await?;
+    match result {
+      Ok(Some(content)) => {
+        let workspace_id = Uuid::parse_str(workspace_id)?;
+        upsert_collab_search_content(
+          transaction.deref_mut(),
+          &workspace_id,
+          &params.object_id,
+          &params.collab_type,
+          &content,
+        )
+        .await?;
+      },
+      Ok(None) => {},
+      Err(err) => tracing::warn!(
+        "failed to extract the text of collab {} for search: {}",
+        params.object_id,
+        err
+      ),
+    }
     Ok(())
   }
 
@@ -75,6 +126,13 @@ impl CollabDiskCache {
     &self,
     query: QueryCollab,
   ) -> Result<EncodedCollab, AppError> {
+    let data = self.get_collab_blob_from_disk(query).await?;
+    encode_collab_from_bytes(data).await
+  }
+
+  /// Returns the encoded collab, in the v1 format, without decoding it.
+  #[instrument(level = "trace", skip_all)]
+  pub async fn get_collab_blob_from_disk(&self, query: QueryCollab) -> Result<Vec<u8>, AppError> {
     event!(
       Level::DEBUG,
       "try get {}:{} from disk",
@@ -91,7 +149,7 @@ impl CollabDiskCache {
 
       match result {
         Ok(data) => {
-          return encode_collab_from_bytes(data).await;
+          return Ok(data);
         },
         Err(e) => {
           match e {
@@ -115,6 +173,21 @@ impl CollabDiskCache {
     }
   }
 
+  /// Returns the encoded collab, in the v1 format, as a stream of chunks. The blob is read before
+  /// the stream starts, so that the database connection is not held while the client downloads
+  /// it.
+  #[instrument(level = "trace", skip_all)]
+  pub async fn get_collab_stream_from_disk(
+    &self,
+    query: QueryCollab,
+  ) -> Result<BoxStream<'static, Result<Bytes, AppError>>, AppError> {
+    let data = Bytes::from(self.get_collab_blob_from_disk(query).await?);
+    let chunks = (0..data.len())
+      .step_by(COLLAB_STREAM_CHUNK_SIZE)
+      .map(move |start| Ok(data.slice(start..(start + COLLAB_STREAM_CHUNK_SIZE).min(data.len()))));
+    Ok(stream::iter(chunks).boxed())
+  }
+
   pub async fn batch_get_collab(
     &self,
     queries: Vec<QueryCollab>,
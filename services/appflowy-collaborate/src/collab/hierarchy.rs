@@ -0,0 +1,156 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use access_control::collab::CollabHierarchy;
+use app_error::AppError;
+use async_trait::async_trait;
+use collab_entity::CollabType;
+use collab_folder::{CollabOrigin, Folder};
+use dashmap::DashMap;
+use database::collab::select_collab_inheritance_breaks;
+use database::workspace::select_workspace_settings;
+use database_entity::dto::QueryCollab;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::collab::cache::CollabCache;
+
+/// How long the hierarchy of a workspace is kept before it is read again. The views moved in the
+/// meantime keep inheriting from their previous ancestors until then.
+const HIERARCHY_CACHE_TTL: Duration = Duration::from_secs(30);
+
+struct WorkspaceHierarchy {
+  inherit_view_permissions: bool,
+  /// The parent of every view that is not at the root of the workspace.
+  parents: HashMap<String, String>,
+  breaks: HashSet<String>,
+  loaded_at: Instant,
+}
+
+impl WorkspaceHierarchy {
+  fn ancestors(&self, oid: &str) -> Vec<String> {
+    if !self.inherit_view_permissions || self.breaks.contains(oid) {
+      return vec![];
+    }
+
+    let mut ancestors = vec![];
+    let mut visited = HashSet::from([oid]);
+    let mut current = oid;
+    while let Some(parent_id) = self.parents.get(current) {
+      // A cycle means the folder is corrupted, stop instead of looping forever
+      if !visited.insert(parent_id.as_str()) {
+        break;
+      }
+      ancestors.push(parent_id.clone());
+      if self.breaks.contains(parent_id) {
+        break;
+      }
+      current = parent_id;
+    }
+    ancestors
+  }
+}
+
+/// Resolves the ancestors of a collab from the folder of its workspace. The folder, the views on
+/// which the inheritance is broken and the inheritance setting of each workspace are cached.
+#[derive(Clone)]
+pub struct FolderCollabHierarchy {
+  pg_pool: PgPool,
+  collab_cache: CollabCache,
+  workspaces: Arc<DashMap<String, Arc<WorkspaceHierarchy>>>,
+}
+
+impl FolderCollabHierarchy {
+  pub fn new(pg_pool: PgPool, collab_cache: CollabCache) -> Self {
+    Self {
+      pg_pool,
+      collab_cache,
+      workspaces: Arc::new(DashMap::new()),
+    }
+  }
+
+  async fn workspace_hierarchy(
+    &self,
+    workspace_id: &str,
+  ) -> Result<Arc<WorkspaceHierarchy>, AppError> {
+    if let Some(hierarchy) = self.workspaces.get(workspace_id) {
+      if hierarchy.loaded_at.elapsed() < HIERARCHY_CACHE_TTL {
+        return Ok(hierarchy.clone());
+      }
+    }
+
+    let hierarchy = Arc::new(self.load(workspace_id).await?);
+    self
+      .workspaces
+      .insert(workspace_id.to_string(), hierarchy.clone());
+    Ok(hierarchy)
+  }
+
+  async fn load(&self, workspace_id: &str) -> Result<WorkspaceHierarchy, AppError> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)?;
+    let inherit_view_permissions = select_workspace_settings(&self.pg_pool, &workspace_uuid)
+      .await?
+      .map(|settings| settings.inherit_view_permissions)
+      .unwrap_or(false);
+    if !inherit_view_permissions {
+      return Ok(WorkspaceHierarchy {
+        inherit_view_permissions,
+        parents: HashMap::new(),
+        breaks: HashSet::new(),
+        loaded_at: Instant::now(),
+      });
+    }
+
+    let encoded_collab = self
+      .collab_cache
+      .get_encode_collab(QueryCollab::new(workspace_id, CollabType::Folder))
+      .await?;
+    let parents = {
+      let folder = Folder::from_collab_doc_state(
+        0,
+        CollabOrigin::Server,
+        encoded_collab.into(),
+        workspace_id,
+        vec![],
+      )
+      .map_err(|e| AppError::Unhandled(e.to_string()))?;
+      let txn = folder.collab.transact();
+      folder
+        .body
+        .views
+        .get_all_views(&txn)
+        .into_iter()
+        .filter(|view| !view.parent_view_id.is_empty() && view.parent_view_id != workspace_id)
+        .map(|view| (view.id.clone(), view.parent_view_id.clone()))
+        .collect()
+    };
+    let breaks = select_collab_inheritance_breaks(&self.pg_pool, &workspace_uuid)
+      .await?
+      .into_iter()
+      .collect();
+
+    Ok(WorkspaceHierarchy {
+      inherit_view_permissions,
+      parents,
+      breaks,
+      loaded_at: Instant::now(),
+    })
+  }
+}
+
+#[async_trait]
+impl CollabHierarchy for FolderCollabHierarchy {
+  async fn inherited_ancestors(
+    &self,
+    workspace_id: &str,
+    oid: &str,
+  ) -> Result<Vec<String>, AppError> {
+    let hierarchy = self.workspace_hierarchy(workspace_id).await?;
+    Ok(hierarchy.ancestors(oid))
+  }
+
+  async fn invalidate(&self, workspace_id: &str) {
+    self.workspaces.remove(workspace_id);
+  }
+}
@@ -2,11 +2,13 @@ pub mod access_control;
 pub mod cache;
 mod decode_util;
 pub mod disk_cache;
+pub mod hierarchy;
 pub mod mem_cache;
 pub mod notification;
 pub mod queue;
 mod queue_redis_ops;
 pub mod storage;
+pub mod storage_quota;
 pub mod validator;
 
 pub use queue_redis_ops::{PendingWrite, RedisSortedSet, WritePriority};
@@ -59,6 +59,92 @@ pub fn spawn_listen_on_collab_member_change(
   });
 }
 
+/// Reloads the hierarchy of a workspace once the inheritance of its view permissions changes, which
+/// the api server does.
+pub fn spawn_listen_on_collab_inheritance_change(
+  mut listener: broadcast::Receiver<CollabInheritanceNotification>,
+  access_control: AccessControl,
+) {
+  tokio::spawn(async move {
+    while let Ok(change) = listener.recv().await {
+      access_control
+        .invalidate_collab_hierarchy(&change.workspace_id)
+        .await;
+    }
+  });
+}
+
+/// Keeps the groups of the users in sync with the api server, which changes them.
+pub fn spawn_listen_on_workspace_group_member_change(
+  mut listener: broadcast::Receiver<WorkspaceGroupMemberNotification>,
+  access_control: AccessControl,
+) {
+  tokio::spawn(async move {
+    while let Ok(change) = listener.recv().await {
+      if let Some(row) = change.old {
+        access_control
+          .remove_group_member(&row.uid, &row.group_id)
+          .await;
+      }
+      if let Some(row) = change.new {
+        access_control
+          .add_group_member(&row.uid, &row.group_id)
+          .await;
+      }
+    }
+  });
+}
+
+/// Keeps the access granted to the groups in sync with the api server, which changes it.
+pub fn spawn_listen_on_collab_group_member_change(
+  pg_pool: PgPool,
+  mut listener: broadcast::Receiver<CollabGroupMemberNotification>,
+  access_control: AccessControl,
+) {
+  tokio::spawn(async move {
+    while let Ok(change) = listener.recv().await {
+      match change.action_type {
+        CollabMemberAction::INSERT | CollabMemberAction::UPDATE => {
+          let Some(row) = change.new else {
+            error!("The new collab group member is None");
+            continue;
+          };
+          if let Ok(Some(permission)) = select_permission(&pg_pool, &row.permission_id).await {
+            if let Err(err) = access_control
+              .update_group_policy(
+                &row.group_id,
+                ObjectType::Collab(&row.oid),
+                ActionVariant::FromAccessLevel(&permission.access_level),
+              )
+              .await
+            {
+              error!(
+                "Failed to update the group:{} collab{} access control, error: {}",
+                row.group_id, row.oid, err
+              );
+            }
+          }
+        },
+        CollabMemberAction::DELETE => {
+          let Some(row) = change.old else {
+            warn!("The old collab group member is None");
+            continue;
+          };
+          if let Err(err) = access_control
+            .remove_group_policy(&row.group_id, &ObjectType::Collab(&row.oid))
+            .await
+          {
+            warn!(
+              "Failed to remove the group:{} collab{} access control, error: {}",
+              row.group_id, row.oid, err
+            );
+          }
+        },
+      }
+    }
+  });
+}
+
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Deserialize, Clone, Debug)]
 pub enum CollabMemberAction {
@@ -92,3 +178,35 @@ impl CollabMemberNotification {
     self.new.as_ref().map(|n| n.oid.as_str())
   }
 }
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct WorkspaceGroupMemberRow {
+  pub group_id: String,
+  pub uid: i64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct WorkspaceGroupMemberNotification {
+  pub old: Option<WorkspaceGroupMemberRow>,
+  pub new: Option<WorkspaceGroupMemberRow>,
+  pub action_type: CollabMemberAction,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CollabGroupMemberRow {
+  pub group_id: String,
+  pub oid: String,
+  pub permission_id: i64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CollabGroupMemberNotification {
+  pub old: Option<CollabGroupMemberRow>,
+  pub new: Option<CollabGroupMemberRow>,
+  pub action_type: CollabMemberAction,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CollabInheritanceNotification {
+  pub workspace_id: String,
+}
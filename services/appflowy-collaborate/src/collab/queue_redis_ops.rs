@@ -237,12 +237,13 @@ impl PartialOrd for PendingWrite {
 #[cfg(test)]
 mod tests {
   use crate::collab::{PendingWrite, RedisSortedSet, WritePriority};
+  use crate::state::RedisConnectionManager;
   use anyhow::Context;
   use std::time::Duration;
 
   #[tokio::test]
   async fn pending_write_sorted_set_test() {
-    let conn = redis_client().await.get_connection_manager().await.unwrap();
+    let conn = redis_connection_manager().await;
     let set_name = uuid::Uuid::new_v4().to_string();
     let sorted_set = RedisSortedSet::new(conn.clone(), &set_name);
 
@@ -282,7 +283,7 @@ mod tests {
 
   #[tokio::test]
   async fn sorted_set_consume_partial_items_test() {
-    let conn = redis_client().await.get_connection_manager().await.unwrap();
+    let conn = redis_connection_manager().await;
     let set_name = uuid::Uuid::new_v4().to_string();
     let sorted_set_1 = RedisSortedSet::new(conn.clone(), &set_name);
 
@@ -332,7 +333,7 @@ mod tests {
 
   #[tokio::test]
   async fn large_num_set_test() {
-    let conn = redis_client().await.get_connection_manager().await.unwrap();
+    let conn = redis_connection_manager().await;
     let set_name = uuid::Uuid::new_v4().to_string();
     let sorted_set = RedisSortedSet::new(conn.clone(), &set_name);
     assert!(sorted_set.pop(10).await.unwrap().is_empty());
@@ -366,7 +367,7 @@ mod tests {
 
   #[tokio::test]
   async fn multi_threads_sorted_set_test() {
-    let conn = redis_client().await.get_connection_manager().await.unwrap();
+    let conn = redis_connection_manager().await;
     let set_name = uuid::Uuid::new_v4().to_string();
     let sorted_set = RedisSortedSet::new(conn.clone(), &set_name);
 
@@ -409,10 +410,13 @@ mod tests {
     }
   }
 
-  async fn redis_client() -> redis::Client {
+  async fn redis_connection_manager() -> RedisConnectionManager {
     let redis_uri = "redis://localhost:6379";
-    redis::Client::open(redis_uri)
+    let client = redis::Client::open(redis_uri)
       .context("failed to connect to redis")
+      .unwrap();
+    RedisConnectionManager::new_standalone(client)
+      .await
       .unwrap()
   }
 }
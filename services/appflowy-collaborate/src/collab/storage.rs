@@ -3,9 +3,13 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use collab::entity::EncodedCollab;
 use collab_entity::CollabType;
 use collab_rt_entity::ClientCollabMessage;
+use futures::future::join_all;
+use futures::stream::{self, BoxStream, StreamExt};
 use itertools::{Either, Itertools};
 use sqlx::Transaction;
 use tokio::time::timeout;
@@ -19,17 +23,23 @@ use crate::command::{CLCommandSender, CollaborationCommand};
 use crate::shared_state::RealtimeSharedState;
 use app_error::AppError;
 use database::collab::{
-  AppResult, CollabMetadata, CollabStorage, CollabStorageAccessControl, GetCollabOrigin,
+  upsert_collab_contributions, AppResult, CollabMetadata, CollabStorage,
+  CollabStorageAccessControl, GetCollabOrigin,
 };
+use database::view_trash::insert_view_trash_at;
+use database::webhook::insert_webhook_deliveries;
 use database_entity::dto::{
-  AFAccessLevel, AFSnapshotMeta, AFSnapshotMetas, CollabParams, InsertSnapshotParams, QueryCollab,
-  QueryCollabParams, QueryCollabResult, SnapshotData,
+  AFAccessLevel, AFSnapshotMeta, AFSnapshotMetas, CollabContribution, CollabParams,
+  InsertSnapshotParams, QueryCollab, QueryCollabParams, QueryCollabResult, SnapshotData,
 };
+use shared_entity::dto::workspace_dto::WebhookEvent;
+use uuid::Uuid;
 use workspace_access::WorkspaceAccessControlImpl;
 
 use crate::collab::access_control::CollabStorageAccessControlImpl;
 use crate::collab::queue::{StorageQueue, REDIS_PENDING_WRITE_QUEUE};
 use crate::collab::queue_redis_ops::WritePriority;
+use crate::collab::storage_quota::{check_collab_storage_quota, StorageQuotaSetting};
 use crate::collab::validator::CollabValidator;
 use crate::metrics::CollabMetrics;
 use crate::snapshot::SnapshotControl;
@@ -49,6 +59,7 @@ pub struct CollabStorageImpl<AC> {
   rt_cmd_sender: CLCommandSender,
   queue: Arc<StorageQueue>,
   shared_state: RealtimeSharedState,
+  storage_quota: StorageQuotaSetting,
 }
 
 impl<AC> CollabStorageImpl<AC>
@@ -62,6 +73,7 @@ where
     rt_cmd_sender: CLCommandSender,
     redis_conn_manager: RedisConnectionManager,
     metrics: Arc<CollabMetrics>,
+    storage_quota: StorageQuotaSetting,
   ) -> Self {
     let shared_state = RealtimeSharedState::new(redis_conn_manager.clone());
     let queue = Arc::new(StorageQueue::new_with_metrics(
@@ -77,6 +89,7 @@ where
       rt_cmd_sender,
       queue,
       shared_state,
+      storage_quota,
     }
   }
 
@@ -101,6 +114,31 @@ where
     Ok(())
   }
 
+  async fn check_read_collab_permission(
+    &self,
+    origin: &GetCollabOrigin,
+    params: &QueryCollabParams,
+  ) -> Result<(), AppError> {
+    match origin {
+      GetCollabOrigin::User { uid } => {
+        // Check if the user has enough permissions to access the collab
+        let can_read = self
+          .access_control
+          .enforce_read_collab(&params.workspace_id, uid, &params.object_id)
+          .await?;
+
+        if !can_read {
+          return Err(AppError::NotEnoughPermissions {
+            user: uid.to_string(),
+            action: format!("read collab:{}", params.object_id),
+          });
+        }
+      },
+      GetCollabOrigin::Server => {},
+    }
+    Ok(())
+  }
+
   async fn check_write_collab_permission(
     &self,
     workspace_id: &str,
@@ -122,6 +160,56 @@ where
     Ok(())
   }
 
+  /// Every write of a collab goes through the storage, so its growth is checked against the quota
+  /// of the workspace here, whether it comes from the realtime server or from the APIs.
+  async fn check_storage_quota(
+    &self,
+    workspace_id: &str,
+    params: &CollabParams,
+  ) -> Result<(), AppError> {
+    let workspace_id = Uuid::parse_str(workspace_id)?;
+    check_collab_storage_quota(
+      self.cache.pg_pool(),
+      &self.storage_quota,
+      &workspace_id,
+      &[(params.object_id.clone(), params.encoded_collab_v1.len())],
+    )
+    .await
+  }
+
+  /// Queue a `collab.updated` event for the webhooks of the workspace. The collab has already
+  /// been written, so a failure is logged rather than returned.
+  async fn queue_collab_updated_webhook_event(
+    &self,
+    workspace_id: &str,
+    uid: &i64,
+    object_id: &str,
+    collab_type: &CollabType,
+  ) {
+    let workspace_id = match Uuid::parse_str(workspace_id) {
+      Ok(workspace_id) => workspace_id,
+      Err(_) => return,
+    };
+    let data = serde_json::json!({
+      "object_id": object_id,
+      "collab_type": collab_type,
+      "uid": uid,
+    });
+    if let Err(err) = insert_webhook_deliveries(
+      self.cache.pg_pool(),
+      &workspace_id,
+      WebhookEvent::CollabUpdated.as_str(),
+      &data,
+    )
+    .await
+    {
+      warn!(
+        "Failed to queue webhook event for collab {}: {}",
+        object_id, err
+      );
+    }
+  }
+
   async fn get_encode_collab_from_editing(&self, object_id: &str) -> Option<EncodedCollab> {
     let object_id = object_id.to_string();
     let (ret, rx) = tokio::sync::oneshot::channel();
@@ -224,14 +312,22 @@ where
         .update_policy(uid, &params.object_id, AFAccessLevel::FullAccess)
         .await?;
     }
+    self.check_storage_quota(workspace_id, &params).await?;
     let priority = if write_immediately {
       WritePriority::High
     } else {
       WritePriority::Low
     };
+    let object_id = params.object_id.clone();
+    let collab_type = params.collab_type.clone();
     self
       .queue_insert_collab(workspace_id, uid, params, priority)
       .await?;
+    if is_exist {
+      self
+        .queue_collab_updated_webhook_event(workspace_id, uid, &object_id, &collab_type)
+        .await;
+    }
     Ok(())
   }
 
@@ -246,6 +342,7 @@ where
     self
       .check_write_workspace_permission(workspace_id, uid)
       .await?;
+    self.check_storage_quota(workspace_id, &params).await?;
     self
       .access_control
       .update_policy(uid, &params.object_id, AFAccessLevel::FullAccess)
@@ -269,6 +366,7 @@ where
     self
       .check_write_workspace_permission(workspace_id, uid)
       .await?;
+    self.check_storage_quota(workspace_id, &params).await?;
     self
       .access_control
       .update_policy(uid, &params.object_id, AFAccessLevel::FullAccess)
@@ -288,23 +386,7 @@ where
     from_editing_collab: bool,
   ) -> AppResult<EncodedCollab> {
     params.validate()?;
-    match origin {
-      GetCollabOrigin::User { uid } => {
-        // Check if the user has enough permissions to access the collab
-        let can_read = self
-          .access_control
-          .enforce_read_collab(&params.workspace_id, &uid, &params.object_id)
-          .await?;
-
-        if !can_read {
-          return Err(AppError::NotEnoughPermissions {
-            user: uid.to_string(),
-            action: format!("read collab:{}", params.object_id),
-          });
-        }
-      },
-      GetCollabOrigin::Server => {},
-    }
+    self.check_read_collab_permission(&origin, &params).await?;
 
     // Early return if editing collab is initialized, as it indicates no need to query further.
     if from_editing_collab {
@@ -322,6 +404,29 @@ where
     Ok(encode_collab)
   }
 
+  #[instrument(level = "trace", skip_all, fields(oid = %params.object_id, from_editing_collab = %from_editing_collab))]
+  async fn get_encode_collab_stream(
+    &self,
+    origin: GetCollabOrigin,
+    params: QueryCollabParams,
+    from_editing_collab: bool,
+  ) -> AppResult<BoxStream<'static, AppResult<Bytes>>> {
+    params.validate()?;
+    self.check_read_collab_permission(&origin, &params).await?;
+
+    // The editing collab is already in memory, so it is sent in one chunk.
+    if from_editing_collab {
+      if let Some(value) = self.get_encode_collab_from_editing(&params.object_id).await {
+        let data = value
+          .encode_to_bytes()
+          .map_err(|err| AppError::Internal(anyhow::Error::from(err)))?;
+        return Ok(stream::once(async move { Ok(Bytes::from(data)) }).boxed());
+      }
+    }
+
+    self.cache.get_encode_collab_stream(params.inner).await
+  }
+
   async fn batch_get_collab(
     &self,
     _uid: &i64,
@@ -341,7 +446,27 @@ where
           )),
         });
 
-    results.extend(self.cache.batch_get_encode_collab(valid_queries).await);
+    // The collabs being edited may be ahead of the storage, so they are taken from the realtime
+    // server. The others are read from the cache in a single batch.
+    let editing_collabs = join_all(
+      valid_queries
+        .iter()
+        .map(|query| self.get_encode_collab_from_editing(&query.object_id)),
+    )
+    .await;
+    let mut cache_queries = vec![];
+    for (query, editing_collab) in valid_queries.into_iter().zip(editing_collabs) {
+      match editing_collab.map(|encode_collab| encode_collab.encode_to_bytes()) {
+        Some(Ok(encode_collab_v1)) => {
+          results.insert(
+            query.object_id,
+            QueryCollabResult::Success { encode_collab_v1 },
+          );
+        },
+        _ => cache_queries.push(query),
+      }
+    }
+    results.extend(self.cache.batch_get_encode_collab(cache_queries).await);
     results
   }
 
@@ -412,6 +537,47 @@ where
     }
   }
 
+  async fn record_collab_contributions(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    contributions: Vec<CollabContribution>,
+  ) -> AppResult<()> {
+    if contributions.is_empty() {
+      return Ok(());
+    }
+    let workspace_id = Uuid::parse_str(workspace_id)?;
+    upsert_collab_contributions(
+      self.cache.pg_pool(),
+      &workspace_id,
+      object_id,
+      &contributions,
+    )
+    .await
+  }
+
+  async fn record_trashed_views(
+    &self,
+    workspace_id: &str,
+    deleted_by: i64,
+    trashed_views: Vec<(String, DateTime<Utc>)>,
+  ) -> AppResult<()> {
+    if trashed_views.is_empty() {
+      return Ok(());
+    }
+    let workspace_id = Uuid::parse_str(workspace_id)?;
+    let (view_ids, deleted_at): (Vec<String>, Vec<DateTime<Utc>>) =
+      trashed_views.into_iter().unzip();
+    insert_view_trash_at(
+      self.cache.pg_pool(),
+      &workspace_id,
+      deleted_by,
+      &view_ids,
+      &deleted_at,
+    )
+    .await
+  }
+
   async fn broadcast_encode_collab(
     &self,
     object_id: String,
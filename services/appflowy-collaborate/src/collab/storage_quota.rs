@@ -0,0 +1,149 @@
+use app_error::AppError;
+use database::pg_row::AFWorkspaceStorageRow;
+use database::storage_quota::{select_collab_lens, select_workspace_storage};
+use shared_entity::dto::billing_dto::SubscriptionPlan;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// The storage quota of the workspaces of each plan, in bytes, counting the collabs and the blobs.
+/// `0` leaves the workspaces of a plan unlimited. A workspace can override the quota of its plan,
+/// see `af_workspace_plan`.
+#[derive(Clone, Debug)]
+pub struct StorageQuotaSetting {
+  pub free_plan_bytes: i64,
+  pub pro_plan_bytes: i64,
+  pub team_plan_bytes: i64,
+}
+
+/// Returns [AppError::StorageQuotaExceeded] if writing `additional_bytes` more to the workspace
+/// would exceed its quota. The writes that don't grow the workspace are always allowed, so that
+/// the members of a workspace over its quota can still free up space.
+///
+/// The usage of the workspace is the one of its last refresh, see
+/// [database::storage_quota::refresh_workspace_storage_usage], so a workspace can go a little over
+/// its quota in between.
+pub async fn check_workspace_storage_quota(
+  pg_pool: &PgPool,
+  setting: &StorageQuotaSetting,
+  workspace_id: &Uuid,
+  additional_bytes: i64,
+) -> Result<(), AppError> {
+  let row = select_workspace_storage(pg_pool, workspace_id).await?;
+  let (_, quota_bytes) = storage_quota(setting, &row);
+  let used_bytes = row.collab_bytes + row.blob_bytes;
+  if exceeds_quota(used_bytes, additional_bytes, quota_bytes) {
+    return Err(AppError::StorageQuotaExceeded(format!(
+      "workspace {} uses {} of its {} bytes",
+      workspace_id,
+      used_bytes,
+      quota_bytes.unwrap_or_default()
+    )));
+  }
+  Ok(())
+}
+
+/// Checks the quota of the workspace before the given collabs are written, each of them given by
+/// its object id and the length of its encoded state. Only the growth of the collabs that are
+/// already stored counts against the quota.
+pub async fn check_collab_storage_quota(
+  pg_pool: &PgPool,
+  setting: &StorageQuotaSetting,
+  workspace_id: &Uuid,
+  collabs: &[(String, usize)],
+) -> Result<(), AppError> {
+  let oids = collabs
+    .iter()
+    .map(|(oid, _)| oid.clone())
+    .collect::<Vec<_>>();
+  let stored_lens = select_collab_lens(pg_pool, &oids).await?;
+  let additional_bytes = collabs
+    .iter()
+    .map(|(oid, len)| *len as i64 - stored_lens.get(oid).copied().unwrap_or_default())
+    .sum();
+  check_workspace_storage_quota(pg_pool, setting, workspace_id, additional_bytes).await
+}
+
+/// Returns the plan of the workspace and its quota in bytes, `None` if it is unlimited.
+pub fn storage_quota(
+  setting: &StorageQuotaSetting,
+  row: &AFWorkspaceStorageRow,
+) -> (SubscriptionPlan, Option<i64>) {
+  let plan = row
+    .plan
+    .and_then(|plan| SubscriptionPlan::try_from(plan).ok())
+    .unwrap_or(SubscriptionPlan::Free);
+  let quota_bytes = row.storage_quota_bytes.unwrap_or(match plan {
+    SubscriptionPlan::Pro => setting.pro_plan_bytes,
+    SubscriptionPlan::Team => setting.team_plan_bytes,
+    // The AI plans are add-ons that don't change the storage of the workspace
+    SubscriptionPlan::Free | SubscriptionPlan::AiMax | SubscriptionPlan::AiLocal => {
+      setting.free_plan_bytes
+    },
+  });
+  (plan, Some(quota_bytes).filter(|bytes| *bytes > 0))
+}
+
+fn exceeds_quota(used_bytes: i64, additional_bytes: i64, quota_bytes: Option<i64>) -> bool {
+  match quota_bytes {
+    Some(quota_bytes) => additional_bytes > 0 && used_bytes + additional_bytes > quota_bytes,
+    None => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn setting() -> StorageQuotaSetting {
+    StorageQuotaSetting {
+      free_plan_bytes: 100,
+      pro_plan_bytes: 1000,
+      team_plan_bytes: 0,
+    }
+  }
+
+  fn row(plan: Option<i16>, storage_quota_bytes: Option<i64>) -> AFWorkspaceStorageRow {
+    AFWorkspaceStorageRow {
+      collab_bytes: 0,
+      blob_bytes: 0,
+      plan,
+      storage_quota_bytes,
+    }
+  }
+
+  #[test]
+  fn storage_quota_test() {
+    let setting = setting();
+    assert_eq!(
+      storage_quota(&setting, &row(None, None)),
+      (SubscriptionPlan::Free, Some(100))
+    );
+    assert_eq!(
+      storage_quota(&setting, &row(Some(1), None)),
+      (SubscriptionPlan::Pro, Some(1000))
+    );
+    assert_eq!(
+      storage_quota(&setting, &row(Some(2), None)),
+      (SubscriptionPlan::Team, None)
+    );
+    assert_eq!(
+      storage_quota(&setting, &row(Some(3), None)),
+      (SubscriptionPlan::AiMax, Some(100))
+    );
+    // The quota of the workspace overrides the quota of its plan
+    assert_eq!(
+      storage_quota(&setting, &row(Some(2), Some(50))),
+      (SubscriptionPlan::Team, Some(50))
+    );
+  }
+
+  #[test]
+  fn exceeds_quota_test() {
+    assert!(!exceeds_quota(90, 10, Some(100)));
+    assert!(exceeds_quota(90, 11, Some(100)));
+    assert!(!exceeds_quota(1000, 1000, None));
+    // A workspace over its quota can still shrink
+    assert!(!exceeds_quota(150, -10, Some(100)));
+    assert!(!exceeds_quota(150, 0, Some(100)));
+  }
+}
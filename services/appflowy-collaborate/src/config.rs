@@ -1,4 +1,5 @@
 use anyhow::Context;
+use collab_stream::connection::RedisSetting;
 use secrecy::Secret;
 use semver::Version;
 use serde::Deserialize;
@@ -6,6 +7,8 @@ use sqlx::postgres::{PgConnectOptions, PgSslMode};
 use std::fmt::Display;
 use std::str::FromStr;
 
+use crate::collab::storage_quota::StorageQuotaSetting;
+
 #[derive(Clone, Debug)]
 pub struct Config {
   pub app_env: Environment,
@@ -14,8 +17,9 @@ pub struct Config {
   pub db_settings: DatabaseSetting,
   pub gotrue: GoTrueSetting,
   pub collab: CollabSetting,
-  pub redis_uri: Secret<String>,
+  pub redis: RedisSetting,
   pub ai: AISettings,
+  pub storage_quota: StorageQuotaSetting,
 }
 
 #[derive(Clone, Debug)]
@@ -71,6 +75,10 @@ pub struct WebsocketSetting {
   pub heartbeat_interval: u8,
   pub client_timeout: u8,
   pub min_client_version: Version,
+  /// Whether the realtime messages are compressed for the clients that accept it.
+  pub compression: bool,
+  /// The oldest version of the realtime protocol accepted, older clients are asked to upgrade.
+  pub min_protocol_version: u32,
 }
 
 #[derive(Clone, Debug)]
@@ -142,6 +150,12 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
       heartbeat_interval: get_env_var("APPFLOWY_WEBSOCKET_HEARTBEAT_INTERVAL", "6").parse()?,
       client_timeout: get_env_var("APPFLOWY_WEBSOCKET_CLIENT_TIMEOUT", "60").parse()?,
       min_client_version: get_env_var("APPFLOWY_WEBSOCKET_CLIENT_MIN_VERSION", "0.5.0").parse()?,
+      compression: get_env_var("APPFLOWY_WEBSOCKET_COMPRESSION", "true")
+        .parse()
+        .context("fail to get APPFLOWY_WEBSOCKET_COMPRESSION")?,
+      min_protocol_version: get_env_var("APPFLOWY_WEBSOCKET_MIN_PROTOCOL_VERSION", "1")
+        .parse()
+        .context("fail to get APPFLOWY_WEBSOCKET_MIN_PROTOCOL_VERSION")?,
     },
     db_settings: DatabaseSetting {
       pg_conn_opts: PgConnectOptions::from_str(&get_env_var(
@@ -167,11 +181,40 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
       edit_state_max_count: get_env_var("APPFLOWY_COLLAB_EDIT_STATE_MAX_COUNT", "100").parse()?,
       edit_state_max_secs: get_env_var("APPFLOWY_COLLAB_EDIT_STATE_MAX_SECS", "60").parse()?,
     },
-    redis_uri: get_env_var("APPFLOWY_REDIS_URI", "redis://localhost:6379").into(),
+    redis: get_redis_setting()?,
     ai: AISettings {
       port: get_env_var("APPFLOWY_AI_SERVER_PORT", "5001").parse()?,
       host: get_env_var("APPFLOWY_AI_SERVER_HOST", "localhost"),
     },
+    storage_quota: StorageQuotaSetting {
+      free_plan_bytes: get_env_var("APPFLOWY_STORAGE_QUOTA_FREE_PLAN_BYTES", "0").parse()?,
+      pro_plan_bytes: get_env_var("APPFLOWY_STORAGE_QUOTA_PRO_PLAN_BYTES", "0").parse()?,
+      team_plan_bytes: get_env_var("APPFLOWY_STORAGE_QUOTA_TEAM_PLAN_BYTES", "0").parse()?,
+    },
   };
   Ok(config)
 }
+
+fn get_redis_setting() -> Result<RedisSetting, anyhow::Error> {
+  // Without explicit nodes, the single redis uri is used as the only node.
+  let nodes = get_env_var("APPFLOWY_REDIS_NODES", "");
+  let nodes = if nodes.trim().is_empty() {
+    vec![get_env_var("APPFLOWY_REDIS_URI", "redis://localhost:6379")]
+  } else {
+    nodes
+      .split(',')
+      .map(|node| node.trim().to_string())
+      .filter(|node| !node.is_empty())
+      .collect()
+  };
+  Ok(RedisSetting {
+    mode: get_env_var("APPFLOWY_REDIS_MODE", "standalone")
+      .parse()
+      .map_err(|err: String| anyhow::anyhow!(err))
+      .context("fail to get APPFLOWY_REDIS_MODE")?,
+    nodes,
+    sentinel_master: get_env_var("APPFLOWY_REDIS_SENTINEL_MASTER", "mymaster"),
+    health_check_interval_secs: get_env_var("APPFLOWY_REDIS_HEALTH_CHECK_INTERVAL_SECS", "30")
+      .parse()?,
+  })
+}
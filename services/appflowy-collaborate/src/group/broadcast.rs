@@ -26,6 +26,7 @@ use collab_rt_protocol::{CollabSyncProtocol, Message, MessageReader, MSG_SYNC, M
 use collab_rt_protocol::{RTProtocolError, SyncMessage};
 
 use crate::error::RealtimeError;
+use crate::group::fanout::GroupFanout;
 use crate::group::group_init::EditState;
 use crate::group::protocol::ServerSyncProtocol;
 use crate::metrics::CollabRealtimeMetrics;
@@ -39,7 +40,8 @@ pub trait CollabUpdateStreaming: 'static + Send + Sync {
 ///
 pub struct CollabBroadcast {
   object_id: String,
-  broadcast_sender: Sender<CollabMessage>,
+  /// The messages to send to the subscribers, with the time they were broadcast at.
+  broadcast_sender: Sender<(Instant, CollabMessage)>,
   awareness_sub: Option<YrsSubscription>,
   /// Keep the lifetime of the document observer subscription. The subscription will be stopped
   /// when the broadcast is dropped.
@@ -48,6 +50,8 @@ pub struct CollabBroadcast {
   /// The last modified time of the document.
   pub modified_at: Arc<parking_lot::Mutex<Instant>>,
   update_streaming: Arc<dyn CollabUpdateStreaming>,
+  /// Propagates the changes to the other realtime servers, when more than one is running.
+  fanout: Option<GroupFanout>,
 }
 
 unsafe impl Send for CollabBroadcast {}
@@ -72,6 +76,7 @@ impl CollabBroadcast {
     edit_state: Arc<EditState>,
     collab: &Collab,
     update_streaming: impl CollabUpdateStreaming,
+    fanout: Option<GroupFanout>,
   ) -> Self {
     let update_streaming = Arc::new(update_streaming);
     let object_id = object_id.to_owned();
@@ -85,6 +90,7 @@ impl CollabBroadcast {
       edit_state,
       modified_at: Arc::new(parking_lot::Mutex::new(Instant::now())),
       update_streaming,
+      fanout,
     };
     this.observe_collab_changes(collab);
     this
@@ -98,6 +104,7 @@ impl CollabBroadcast {
       let modified_at = self.modified_at.clone();
       let edit_state = self.edit_state.clone();
       let update_streaming = self.update_streaming.clone();
+      let fanout = self.fanout.clone();
 
       // Observer the document's update and broadcast it to all subscribers. When one of the clients
       // sends an update to the document that alters its state, the document observer will trigger
@@ -109,19 +116,32 @@ impl CollabBroadcast {
         .observe_update_v1(move |txn, event| {
           let seq_num = edit_state.increment_edit_count() + 1;
           let origin = CollabOrigin::from(txn);
+          if let CollabOrigin::Client(client) = &origin {
+            edit_state.record_contribution(client.uid);
+          }
           trace!(
             "observe update with len:{}, origin: {}",
             event.update.len(),
             origin
           );
 
-          let stream_update = event.update.clone();
-          if let Err(err) = update_streaming.send_update(stream_update) {
-            warn!("fail to send updates to redis:{}", err)
-          }
           let payload = gen_update_message(&event.update);
+          // The updates published by another server were already streamed and fanned out by it.
+          let is_remote = fanout
+            .as_ref()
+            .map(|fanout| fanout.is_applying_remote())
+            .unwrap_or(false);
+          if !is_remote {
+            let stream_update = event.update.clone();
+            if let Err(err) = update_streaming.send_update(stream_update) {
+              warn!("fail to send updates to redis:{}", err)
+            }
+            if let Some(fanout) = &fanout {
+              fanout.publish(payload.clone());
+            }
+          }
           let msg = BroadcastSync::new(origin, cloned_oid.clone(), payload, seq_num);
-          if let Err(err) = broadcast_sink.send(msg.into()) {
+          if let Err(err) = broadcast_sink.send((Instant::now(), msg.into())) {
             trace!("fail to broadcast updates:{}", err);
           }
           *modified_at.lock() = Instant::now();
@@ -130,6 +150,7 @@ impl CollabBroadcast {
 
       let broadcast_sink = self.broadcast_sender.clone();
       let cloned_oid = self.object_id.clone();
+      let fanout = self.fanout.clone();
 
       // Observer the awareness's update and broadcast it to all subscribers.
       let awareness_sub = collab
@@ -137,8 +158,14 @@ impl CollabBroadcast {
         .on_update(move |awareness, event, _origin| {
           if let Ok(awareness_update) = awareness.update_with_clients(event.all_changes()) {
             let payload = Message::Awareness(awareness_update).encode_v1();
+            if let Some(fanout) = fanout
+              .as_ref()
+              .filter(|fanout| !fanout.is_applying_remote())
+            {
+              fanout.publish(payload.clone());
+            }
             let msg = AwarenessSync::new(cloned_oid.clone(), payload, CollabOrigin::Empty);
-            if let Err(err) = broadcast_sink.send(msg.into()) {
+            if let Err(err) = broadcast_sink.send((Instant::now(), msg.into())) {
               trace!("fail to broadcast awareness:{}", err);
             }
           }
@@ -200,13 +227,14 @@ impl CollabBroadcast {
       // connected subscriber using its Sink. The loop will break if the stop_rx receives a message.
       let mut receiver = self.broadcast_sender.subscribe();
       let cloned_user = user.clone();
+      let metrics_calculate = metrics_calculate.clone();
       tokio::spawn(async move {
         loop {
           select! {
             _ = stop_rx.recv() => break,
             result = receiver.recv() => {
               match result {
-                Ok(message) => {
+                Ok((broadcast_at, message)) => {
 
                   // No need to broadcast the message back to the originator
                   if message.origin() == &subscriber_origin {
@@ -216,6 +244,8 @@ impl CollabBroadcast {
                   trace!("[realtime]: send {} => {}", message, cloned_user.user_device());
                   if let Err(err) = sink.send(message).await {
                     error!("fail to broadcast message:{}", err);
+                  } else {
+                    metrics_calculate.record_broadcast_lag(broadcast_at.elapsed());
                   }
                 }
                 Err(e) => {
@@ -438,7 +468,7 @@ async fn handle_message(
           .await
         {
           Ok(payload) => {
-            metrics_calculate.apply_update_count.inc();
+            metrics_calculate.record_applied_update();
             // One ClientCollabMessage can have multiple Yrs [Message] in it, but we only need to
             // send one ack back to the client.
             if ack_response.is_none() {
@@ -156,6 +156,8 @@ where
         // safety: messages is not empty because we have checked it before
         let first_message = messages.first().unwrap();
         self.subscribe_group(user, first_message).await?;
+      } else {
+        self.group_manager.touch_presence(user, &object_id).await;
       }
       forward_message_to_group(user, object_id, messages, &self.msg_router_by_user).await;
     } else {
@@ -240,10 +242,10 @@ where
   ) -> Result<(), RealtimeError> {
     let object_id = collab_message.object_id();
     let message_origin = collab_message.origin();
-    match self.msg_router_by_user.get_mut(user) {
+    let result = match self.msg_router_by_user.get_mut(user) {
       None => {
         warn!("The client stream: {} is not found", user);
-        Ok(())
+        return Ok(());
       },
       Some(mut client_msg_router) => {
         self
@@ -256,7 +258,12 @@ where
           )
           .await
       },
-    }
+    };
+    // The router of the user is released at this point, as notifying the other users of the group
+    // goes through their routers.
+    result?;
+    self.group_manager.join_presence(user, object_id).await;
+    Ok(())
   }
 
   #[instrument(level = "debug", skip_all)]
@@ -0,0 +1,405 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use anyhow::anyhow;
+use collab::core::collab::TransactionMutExt;
+use collab::core::origin::CollabOrigin;
+use collab::lock::RwLock;
+use collab::preclude::Collab;
+use dashmap::DashMap;
+use futures_util::StreamExt;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::{error, info, trace, warn};
+use yrs::updates::decoder::Decode;
+use yrs::updates::encoder::Encode;
+use yrs::{ReadTxn, StateVector, Transact, Update};
+
+use collab_rt_protocol::{Message, SyncMessage};
+
+use crate::error::RealtimeError;
+use crate::state::RedisConnectionManager;
+
+/// The channel on which every realtime server publishes the changes of the collabs it has open.
+const FANOUT_CHANNEL: &str = "af_collab_fanout";
+/// A v1 update that carries no change, as encoded by yrs.
+const EMPTY_UPDATE_V1: [u8; 2] = [0, 0];
+
+#[derive(Serialize, Deserialize)]
+struct FanoutMessage {
+  /// The server that published the message, so that it can ignore its own messages.
+  instance_id: String,
+  object_id: String,
+  /// A message of the sync protocol: an update, an awareness update, or a sync step 1 asking the
+  /// other servers for the changes the publisher is missing.
+  payload: Vec<u8>,
+}
+
+enum RemoteMessage {
+  Payload(Vec<u8>),
+  /// The subscription was (re)established, so changes may have been missed in between.
+  Resync,
+}
+
+/// Propagates the changes of the collabs across realtime servers, so that the clients editing the
+/// same collab can be connected to different servers.
+///
+/// Every server publishes the document and awareness updates applied to its groups on a single
+/// Redis channel, and applies the updates published by the other servers to its own group of the
+/// same collab, which broadcasts them to its connected clients.
+pub struct CollabFanout {
+  instance_id: String,
+  publish_tx: mpsc::UnboundedSender<FanoutMessage>,
+  receivers: Arc<DashMap<String, mpsc::UnboundedSender<RemoteMessage>>>,
+}
+
+impl CollabFanout {
+  pub fn new(conn: RedisConnectionManager) -> Arc<Self> {
+    let instance_id = uuid::Uuid::new_v4().to_string();
+    let receivers = Arc::new(DashMap::new());
+    let (publish_tx, publish_rx) = mpsc::unbounded_channel();
+    tokio::spawn(run_publisher(conn.clone(), publish_rx));
+    tokio::spawn(run_subscriber(conn, instance_id.clone(), receivers.clone()));
+    info!("Realtime fan-out enabled, instance id: {}", instance_id);
+    Arc::new(Self {
+      instance_id,
+      publish_tx,
+      receivers,
+    })
+  }
+
+  fn publish(&self, object_id: &str, payload: Vec<u8>) {
+    let message = FanoutMessage {
+      instance_id: self.instance_id.clone(),
+      object_id: object_id.to_string(),
+      payload,
+    };
+    if self.publish_tx.send(message).is_err() {
+      warn!("fan-out publisher of {} is stopped", object_id);
+    }
+  }
+}
+
+/// The fan-out of a single group. The changes applied to the group are published with
+/// [GroupFanout::publish], and the changes published by the other servers are applied to the
+/// collab of the group until the [GroupFanout] is dropped.
+#[derive(Clone)]
+pub struct GroupFanout {
+  peer: FanoutPeer,
+  _registration: Arc<Registration>,
+}
+
+impl GroupFanout {
+  pub fn new(fanout: Arc<CollabFanout>, object_id: &str, collab: Weak<RwLock<Collab>>) -> Self {
+    let (tx, rx) = mpsc::unbounded_channel();
+    fanout.receivers.insert(object_id.to_string(), tx.clone());
+    let registration = Registration {
+      object_id: object_id.to_string(),
+      tx,
+      receivers: fanout.receivers.clone(),
+    };
+    let peer = FanoutPeer {
+      object_id: object_id.to_string(),
+      fanout,
+      is_applying_remote: Default::default(),
+    };
+    tokio::spawn(peer.clone().receive_remote_messages(rx, collab));
+    Self {
+      peer,
+      _registration: Arc::new(registration),
+    }
+  }
+
+  /// Whether the change being applied to the collab was published by another server, in which
+  /// case it must not be published again.
+  pub fn is_applying_remote(&self) -> bool {
+    self.peer.is_applying_remote.load(Ordering::SeqCst)
+  }
+
+  pub fn publish(&self, payload: Vec<u8>) {
+    self.peer.publish(payload);
+  }
+}
+
+#[derive(Clone)]
+struct FanoutPeer {
+  object_id: String,
+  fanout: Arc<CollabFanout>,
+  /// Set while a change published by another server is applied.
+  is_applying_remote: Arc<AtomicBool>,
+}
+
+impl FanoutPeer {
+  fn publish(&self, payload: Vec<u8>) {
+    self.fanout.publish(&self.object_id, payload);
+  }
+
+  async fn receive_remote_messages(
+    self,
+    mut rx: mpsc::UnboundedReceiver<RemoteMessage>,
+    collab: Weak<RwLock<Collab>>,
+  ) {
+    // The collab was loaded from the storage, which may not have the latest changes made on the
+    // other servers yet.
+    if let Some(collab) = collab.upgrade() {
+      self.request_missing_updates(&collab).await;
+    }
+    while let Some(message) = rx.recv().await {
+      let collab = match collab.upgrade() {
+        None => break,
+        Some(collab) => collab,
+      };
+      let result = match message {
+        RemoteMessage::Payload(payload) => self.handle_remote_payload(&payload, &collab).await,
+        RemoteMessage::Resync => {
+          self.request_missing_updates(&collab).await;
+          Ok(())
+        },
+      };
+      if let Err(err) = result {
+        error!(
+          "failed to apply the fan-out message of {}: {}",
+          self.object_id, err
+        );
+      }
+    }
+    trace!("stop receiving fan-out messages of {}", self.object_id);
+  }
+
+  async fn request_missing_updates(&self, collab: &RwLock<Collab>) {
+    let state_vector = collab.read().await.transact().state_vector();
+    self.publish(Message::Sync(SyncMessage::SyncStep1(state_vector)).encode_v1());
+  }
+
+  async fn handle_remote_payload(
+    &self,
+    payload: &[u8],
+    collab: &RwLock<Collab>,
+  ) -> Result<(), RealtimeError> {
+    match Message::decode_v1(payload)? {
+      Message::Sync(SyncMessage::SyncStep1(state_vector)) => {
+        self.reply_missing_updates(&state_vector, collab).await
+      },
+      Message::Sync(SyncMessage::SyncStep2(update))
+      | Message::Sync(SyncMessage::Update(update)) => {
+        let update = Update::decode_v1(&update)?;
+        let mut lock = collab.write().await;
+        self.is_applying_remote.store(true, Ordering::SeqCst);
+        let result = lock
+          .get_awareness()
+          .doc()
+          .try_transact_mut_with(CollabOrigin::Server)
+          .map_err(|err| RealtimeError::Internal(anyhow!("{}", err)))
+          .and_then(|mut txn| txn.try_apply_update(update).map_err(RealtimeError::from));
+        self.is_applying_remote.store(false, Ordering::SeqCst);
+        result
+      },
+      Message::Awareness(update) => {
+        let mut lock = collab.write().await;
+        self.is_applying_remote.store(true, Ordering::SeqCst);
+        let result = lock
+          .get_awareness()
+          .apply_update(update)
+          .map_err(RealtimeError::from);
+        self.is_applying_remote.store(false, Ordering::SeqCst);
+        result
+      },
+      _ => Ok(()),
+    }
+  }
+
+  async fn reply_missing_updates(
+    &self,
+    state_vector: &StateVector,
+    collab: &RwLock<Collab>,
+  ) -> Result<(), RealtimeError> {
+    let update = collab
+      .read()
+      .await
+      .transact()
+      .encode_state_as_update_v1(state_vector);
+    if update != EMPTY_UPDATE_V1 {
+      self.publish(Message::Sync(SyncMessage::SyncStep2(update)).encode_v1());
+    }
+    Ok(())
+  }
+}
+
+/// Stops routing the messages of the other servers to the group once dropped.
+struct Registration {
+  object_id: String,
+  tx: mpsc::UnboundedSender<RemoteMessage>,
+  receivers: Arc<DashMap<String, mpsc::UnboundedSender<RemoteMessage>>>,
+}
+
+impl Drop for Registration {
+  fn drop(&mut self) {
+    // A new group of the same collab may have been registered in the meantime.
+    self
+      .receivers
+      .remove_if(&self.object_id, |_, tx| tx.same_channel(&self.tx));
+  }
+}
+
+async fn run_publisher(
+  mut conn: RedisConnectionManager,
+  mut rx: mpsc::UnboundedReceiver<FanoutMessage>,
+) {
+  while let Some(message) = rx.recv().await {
+    let bytes = match bincode::serialize(&message) {
+      Ok(bytes) => bytes,
+      Err(err) => {
+        error!("failed to serialize the fan-out message: {}", err);
+        continue;
+      },
+    };
+    if let Err(err) = conn.publish::<_, _, ()>(FANOUT_CHANNEL, bytes).await {
+      warn!(
+        "failed to publish the fan-out message of {}: {}",
+        message.object_id, err
+      );
+    }
+  }
+}
+
+async fn run_subscriber(
+  conn: RedisConnectionManager,
+  instance_id: String,
+  receivers: Arc<DashMap<String, mpsc::UnboundedSender<RemoteMessage>>>,
+) {
+  loop {
+    match conn.pubsub_connection().await {
+      Ok(pubsub_conn) => {
+        let mut pubsub = pubsub_conn.into_pubsub();
+        match pubsub.subscribe(FANOUT_CHANNEL).await {
+          Ok(_) => {
+            for entry in receivers.iter() {
+              let _ = entry.value().send(RemoteMessage::Resync);
+            }
+            let mut messages = pubsub.on_message();
+            while let Some(msg) = messages.next().await {
+              let message = match bincode::deserialize::<FanoutMessage>(msg.get_payload_bytes()) {
+                Ok(message) => message,
+                Err(err) => {
+                  warn!("failed to deserialize the fan-out message: {}", err);
+                  continue;
+                },
+              };
+              if message.instance_id == instance_id {
+                continue;
+              }
+              if let Some(tx) = receivers.get(&message.object_id) {
+                let _ = tx.send(RemoteMessage::Payload(message.payload));
+              }
+            }
+            warn!("fan-out subscription is closed, subscribing again");
+          },
+          Err(err) => warn!("failed to subscribe to the fan-out channel: {}", err),
+        }
+      },
+      Err(err) => warn!("failed to connect to the fan-out channel: {}", err),
+    }
+    tokio::time::sleep(Duration::from_secs(1)).await;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+  use std::time::Duration;
+
+  use anyhow::Context;
+  use collab::core::origin::CollabOrigin;
+  use collab::lock::RwLock;
+  use collab::preclude::Collab;
+  use collab_rt_protocol::{Message, SyncMessage};
+  use serde_json::json;
+  use yrs::ReadTxn;
+
+  use crate::group::fanout::{CollabFanout, GroupFanout};
+  use crate::state::RedisConnectionManager;
+
+  #[tokio::test]
+  async fn fanout_update_to_other_server_test() {
+    let object_id = uuid::Uuid::new_v4().to_string();
+    let collab_1 = server_collab(&object_id);
+    let collab_2 = server_collab(&object_id);
+    let group_1 = GroupFanout::new(
+      CollabFanout::new(redis_connection_manager().await),
+      &object_id,
+      Arc::downgrade(&collab_1),
+    );
+    let _group_2 = GroupFanout::new(
+      CollabFanout::new(redis_connection_manager().await),
+      &object_id,
+      Arc::downgrade(&collab_2),
+    );
+    // wait for both servers to subscribe
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let update = {
+      let mut lock = collab_1.write().await;
+      let state_vector = lock.transact().state_vector();
+      lock.insert("title", "hello");
+      lock.transact().encode_state_as_update_v1(&state_vector)
+    };
+    group_1.publish(Message::Sync(SyncMessage::Update(update)).encode_v1());
+    wait_for_json(&collab_2, json!({"title": "hello"})).await;
+  }
+
+  #[tokio::test]
+  async fn fanout_late_group_receives_missing_updates_test() {
+    let object_id = uuid::Uuid::new_v4().to_string();
+    let collab_1 = server_collab(&object_id);
+    collab_1.write().await.insert("title", "hello");
+    let _group_1 = GroupFanout::new(
+      CollabFanout::new(redis_connection_manager().await),
+      &object_id,
+      Arc::downgrade(&collab_1),
+    );
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    // the group opened on the second server asks the first one for the changes it is missing
+    let collab_2 = server_collab(&object_id);
+    let _group_2 = GroupFanout::new(
+      CollabFanout::new(redis_connection_manager().await),
+      &object_id,
+      Arc::downgrade(&collab_2),
+    );
+    wait_for_json(&collab_2, json!({"title": "hello"})).await;
+  }
+
+  fn server_collab(object_id: &str) -> Arc<RwLock<Collab>> {
+    Arc::new(RwLock::new(Collab::new_with_origin(
+      CollabOrigin::Server,
+      object_id,
+      vec![],
+      false,
+    )))
+  }
+
+  async fn wait_for_json(collab: &RwLock<Collab>, expected: serde_json::Value) {
+    for _ in 0..50 {
+      if collab.read().await.to_json_value() == expected {
+        return;
+      }
+      tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!(
+      "the collab did not receive the fan-out changes: {}",
+      collab.read().await.to_json_value()
+    );
+  }
+
+  async fn redis_connection_manager() -> RedisConnectionManager {
+    let redis_uri = "redis://localhost:6379";
+    let client = redis::Client::open(redis_uri)
+      .context("failed to connect to redis")
+      .unwrap();
+    RedisConnectionManager::new_standalone(client)
+      .await
+      .unwrap()
+  }
+}
@@ -1,4 +1,4 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Display;
 use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, Ordering};
 use std::sync::Arc;
@@ -25,9 +25,11 @@ use collab_stream::error::StreamError;
 use collab_stream::model::{CollabUpdateEvent, StreamBinary};
 use collab_stream::stream_group::StreamGroup;
 use database::collab::CollabStorage;
+use database_entity::dto::CollabContribution;
 
 use crate::error::RealtimeError;
 use crate::group::broadcast::{CollabBroadcast, CollabUpdateStreaming, Subscription};
+use crate::group::fanout::{CollabFanout, GroupFanout};
 use crate::group::persistence::GroupPersistence;
 use crate::indexer::Indexer;
 use crate::metrics::CollabRealtimeMetrics;
@@ -70,6 +72,7 @@ impl CollabGroup {
     edit_state_max_count: u32,
     edit_state_max_secs: i64,
     indexer: Option<Arc<dyn Indexer>>,
+    fanout: Option<Arc<CollabFanout>>,
   ) -> Result<Self, StreamError>
   where
     S: CollabStorage,
@@ -79,6 +82,7 @@ impl CollabGroup {
       edit_state_max_secs,
       is_new_collab,
     ));
+    let fanout = fanout.map(|fanout| GroupFanout::new(fanout, &object_id, Arc::downgrade(&collab)));
     let broadcast = {
       let lock = collab.read().await;
       CollabBroadcast::new(
@@ -87,6 +91,7 @@ impl CollabGroup {
         edit_state.clone(),
         &lock,
         CollabUpdateStreamingImpl::new(&workspace_id, &object_id, &collab_redis_stream).await?,
+        fanout,
       )
     };
     let (destroy_group_tx, rx) = mpsc::channel(1);
@@ -102,6 +107,7 @@ impl CollabGroup {
         collab_type.clone(),
         persistence_interval,
         indexer,
+        metrics_calculate.clone(),
       )
       .run(rx),
     );
@@ -137,6 +143,7 @@ impl CollabGroup {
     if let Some((_, mut old_sub)) = self.subscribers.remove(user) {
       trace!("{} remove subscriber from group: {}", self.object_id, user);
       old_sub.stop().await;
+      self.record_sessions();
     }
   }
 
@@ -144,6 +151,15 @@ impl CollabGroup {
     self.subscribers.len()
   }
 
+  /// Returns the users subscribed to the group.
+  pub fn users(&self) -> Vec<RealtimeUser> {
+    self
+      .subscribers
+      .iter()
+      .map(|entry| entry.key().clone())
+      .collect()
+  }
+
   /// Subscribes a new connection to the broadcast group for collaborative activities.
   ///
   pub async fn subscribe<Sink, Stream>(
@@ -171,6 +187,7 @@ impl CollabGroup {
       tracing::warn!("{}: remove old subscriber: {}", &self.object_id, user);
       old.stop().await;
     }
+    self.record_sessions();
 
     if cfg!(debug_assertions) {
       event!(
@@ -235,9 +252,27 @@ impl CollabGroup {
     for mut entry in self.subscribers.iter_mut() {
       entry.value_mut().stop().await;
     }
+    self
+      .metrics_calculate
+      .remove_collab_sessions(&self.object_id);
     let _ = self.destroy_group_tx.send(self.collab.clone()).await;
   }
 
+  /// Keep the sessions reported by the realtime health in sync with the subscribers.
+  fn record_sessions(&self) {
+    let uids = self
+      .subscribers
+      .iter()
+      .map(|entry| entry.key().uid)
+      .collect();
+    self.metrics_calculate.set_collab_sessions(
+      &self.workspace_id,
+      &self.object_id,
+      &self.collab_type,
+      uids,
+    );
+  }
+
   /// Returns the timeout duration in seconds for different collaboration types.
   ///
   /// Collaborative entities vary in their activity and interaction patterns, necessitating
@@ -276,6 +311,8 @@ pub(crate) struct EditState {
   /// Indicate the collab is ready to save to disk.
   /// If is_ready_to_save is true, which means the collab contains the requirement data and ready to save to disk.
   is_ready_to_save: AtomicBool,
+  /// The updates made by each user since the collab was last saved.
+  contributions: parking_lot::Mutex<HashMap<i64, CollabContribution>>,
 }
 
 impl Display for EditState {
@@ -303,6 +340,7 @@ impl EditState {
       max_secs,
       is_new: AtomicBool::new(is_new),
       is_ready_to_save: AtomicBool::new(false),
+      contributions: Default::default(),
     }
   }
 
@@ -321,6 +359,32 @@ impl EditState {
       .unwrap()
   }
 
+  /// Count an update made by the user.
+  pub(crate) fn record_contribution(&self, uid: i64) {
+    let now = chrono::Utc::now();
+    self
+      .contributions
+      .lock()
+      .entry(uid)
+      .and_modify(|contribution| {
+        contribution.edit_count += 1;
+        contribution.last_edited_at = now;
+      })
+      .or_insert(CollabContribution {
+        uid,
+        edit_count: 1,
+        first_edited_at: now,
+        last_edited_at: now,
+      });
+  }
+
+  /// Returns the updates counted since the last call.
+  pub(crate) fn take_contributions(&self) -> Vec<CollabContribution> {
+    std::mem::take(&mut *self.contributions.lock())
+      .into_values()
+      .collect()
+  }
+
   pub(crate) fn tick(&self) {
     self
       .prev_edit_count
@@ -7,12 +7,13 @@ use collab::entity::EncodedCollab;
 use collab::lock::{Mutex, RwLock};
 use collab::preclude::Collab;
 use collab_entity::CollabType;
-use tracing::{error, instrument, trace};
+use dashmap::DashMap;
+use tracing::{error, instrument, trace, warn};
 
 use access_control::collab::RealtimeAccessControl;
 use app_error::AppError;
-use collab_rt_entity::user::RealtimeUser;
-use collab_rt_entity::CollabMessage;
+use collab_rt_entity::user::{AFCollabPresenceChange, RealtimeUser, UserMessage};
+use collab_rt_entity::{CollabMessage, RealtimeMessage};
 use collab_stream::client::{CollabRedisStream, CONTROL_STREAM_KEY};
 use collab_stream::model::CollabControlEvent;
 use collab_stream::stream_group::StreamGroup;
@@ -21,10 +22,12 @@ use database_entity::dto::QueryCollabParams;
 
 use crate::client::client_msg_router::ClientMessageRouter;
 use crate::error::{CreateGroupFailedReason, RealtimeError};
+use crate::group::fanout::CollabFanout;
 use crate::group::group_init::CollabGroup;
 use crate::group::state::GroupManagementState;
 use crate::indexer::IndexerProvider;
 use crate::metrics::CollabRealtimeMetrics;
+use crate::shared_state::RealtimeSharedState;
 
 pub struct GroupManager<S, AC> {
   state: GroupManagementState,
@@ -37,6 +40,11 @@ pub struct GroupManager<S, AC> {
   edit_state_max_count: u32,
   edit_state_max_secs: i64,
   indexer_provider: Arc<IndexerProvider>,
+  /// Keeps track of the users present on each collab, see [GroupManager::join_presence].
+  shared_state: RealtimeSharedState,
+  msg_router_by_user: Arc<DashMap<RealtimeUser, ClientMessageRouter>>,
+  /// Propagates the changes of the groups to the other realtime servers, when enabled.
+  fanout: Option<Arc<CollabFanout>>,
 }
 
 impl<S, AC> GroupManager<S, AC>
@@ -54,6 +62,9 @@ where
     edit_state_max_count: u32,
     edit_state_max_secs: i64,
     indexer_provider: Arc<IndexerProvider>,
+    shared_state: RealtimeSharedState,
+    msg_router_by_user: Arc<DashMap<RealtimeUser, ClientMessageRouter>>,
+    fanout: Option<Arc<CollabFanout>>,
   ) -> Result<Self, RealtimeError> {
     let collab_stream = Arc::new(collab_stream);
     let control_event_stream = collab_stream
@@ -72,6 +83,9 @@ where
       edit_state_max_count,
       edit_state_max_secs,
       indexer_provider,
+      shared_state,
+      msg_router_by_user,
+      fanout,
     })
   }
 
@@ -84,7 +98,9 @@ where
   }
 
   pub async fn remove_user(&self, user: &RealtimeUser) {
-    self.state.remove_user(user).await;
+    for object_id in self.state.remove_user(user).await {
+      self.leave_presence(user, &object_id).await;
+    }
   }
 
   pub async fn contains_group(&self, object_id: &str) -> bool {
@@ -131,6 +147,67 @@ where
     Ok(())
   }
 
+  /// Mark the user as present on the collab it subscribed to, and notify the other users of the
+  /// collab. Must not be called while holding the [ClientMessageRouter] of the user.
+  pub async fn join_presence(&self, user: &RealtimeUser, object_id: &str) {
+    self.touch_presence(user, object_id).await;
+    self.notify_presence_change(user, object_id, true).await;
+  }
+
+  /// Set the last activity of the user on the collab to now.
+  pub async fn touch_presence(&self, user: &RealtimeUser, object_id: &str) {
+    if let Err(err) = self
+      .shared_state
+      .update_collab_presence(object_id, user.uid, &user.device_id)
+      .await
+    {
+      warn!(
+        "fail to update the presence of {} on {}: {}",
+        user, object_id, err
+      );
+    }
+  }
+
+  async fn leave_presence(&self, user: &RealtimeUser, object_id: &str) {
+    if let Err(err) = self
+      .shared_state
+      .remove_collab_presence(object_id, user.uid, &user.device_id)
+      .await
+    {
+      warn!(
+        "fail to remove the presence of {} on {}: {}",
+        user, object_id, err
+      );
+    }
+    self.notify_presence_change(user, object_id, false).await;
+  }
+
+  async fn notify_presence_change(&self, user: &RealtimeUser, object_id: &str, is_present: bool) {
+    let group = match self.state.get_group(object_id).await {
+      Some(group) => group,
+      None => return,
+    };
+    let change = AFCollabPresenceChange {
+      object_id: object_id.to_string(),
+      uid: user.uid,
+      device_id: user.device_id.clone(),
+      is_present,
+      timestamp: chrono::Utc::now().timestamp_millis(),
+    };
+    for other in group.users() {
+      if &other == user {
+        continue;
+      }
+      if let Some(client_msg_router) = self.msg_router_by_user.get(&other) {
+        client_msg_router
+          .send_message(RealtimeMessage::User(UserMessage::CollabPresenceChange(
+            change.clone(),
+          )))
+          .await;
+      }
+    }
+  }
+
   pub async fn create_group(
     &self,
     user: &RealtimeUser,
@@ -239,6 +316,7 @@ where
         self.edit_state_max_count,
         self.edit_state_max_secs,
         indexer,
+        self.fanout.clone(),
       )
       .await?,
     );
@@ -1,5 +1,6 @@
 pub(crate) mod broadcast;
 pub(crate) mod cmd;
+pub(crate) mod fanout;
 pub(crate) mod group_init;
 pub(crate) mod manager;
 mod persistence;
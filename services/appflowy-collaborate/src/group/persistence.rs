@@ -1,10 +1,14 @@
 use std::sync::{Arc, Weak};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::anyhow;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use collab::entity::EncodedCollab;
 use collab::lock::RwLock;
 use collab::preclude::Collab;
 use collab_entity::{validate_data_for_folder, CollabType};
+use collab_folder::{CollabOrigin, Folder};
 use tokio::sync::mpsc;
 use tokio::time::interval;
 use tracing::{trace, warn};
@@ -15,6 +19,7 @@ use database_entity::dto::CollabParams;
 
 use crate::group::group_init::EditState;
 use crate::indexer::Indexer;
+use crate::metrics::CollabRealtimeMetrics;
 
 pub(crate) struct GroupPersistence<S> {
   workspace_id: String,
@@ -26,6 +31,7 @@ pub(crate) struct GroupPersistence<S> {
   collab_type: CollabType,
   persistence_interval: Duration,
   indexer: Option<Arc<dyn Indexer>>,
+  metrics: Arc<CollabRealtimeMetrics>,
 }
 
 impl<S> GroupPersistence<S>
@@ -43,6 +49,7 @@ where
     collab_type: CollabType,
     persistence_interval: Duration,
     ai_client: Option<Arc<dyn Indexer>>,
+    metrics: Arc<CollabRealtimeMetrics>,
   ) -> Self {
     Self {
       workspace_id,
@@ -54,6 +61,7 @@ where
       collab_type,
       persistence_interval,
       indexer: ai_client,
+      metrics,
     }
   }
 
@@ -125,12 +133,17 @@ where
 
     let params = {
       let cloned_collab = collab.clone();
-      let (workspace_id, mut params, object_id) = tokio::task::spawn_blocking(move || {
+      let (workspace_id, mut params, object_id, elapsed) = tokio::task::spawn_blocking(move || {
         let collab = cloned_collab.blocking_read();
+        let start = Instant::now();
         let params = get_encode_collab(&workspace_id, &object_id, &collab, &collab_type)?;
-        Ok::<_, AppError>((workspace_id, params, object_id))
+        Ok::<_, AppError>((workspace_id, params, object_id, start.elapsed()))
       })
       .await??;
+      self
+        .metrics
+        .encode_collab_time
+        .observe(elapsed.as_millis() as f64);
 
       let lock = collab.read().await;
       if let Some(indexer) = &self.indexer {
@@ -160,14 +173,84 @@ where
       params
     };
 
+    let encoded_folder =
+      matches!(self.collab_type, CollabType::Folder).then(|| params.encoded_collab_v1.clone());
     self
       .storage
       .insert_or_update_collab(&self.workspace_id, &self.uid, params, write_immediately)
       .await?;
+    let contributions = self.edit_state.take_contributions();
+    let editor_uids: Vec<i64> = contributions.iter().map(|c| c.uid).collect();
+    if let Err(err) = self
+      .storage
+      .record_collab_contributions(&self.workspace_id, &self.object_id, contributions)
+      .await
+    {
+      warn!(
+        "fail to record the contributors of {}: {}",
+        self.object_id, err
+      );
+    }
+    if let Some(encoded_folder) = encoded_folder {
+      self.record_trashed_views(encoded_folder, editor_uids).await;
+    }
     // Update the edit state on successful save
     self.edit_state.tick();
     Ok(())
   }
+
+  /// Record the views the editors of the folder moved to their trash, with the time the client
+  /// stamped on each trash item, so the trash retention starts when the view was trashed.
+  async fn record_trashed_views(&self, encoded_folder: Bytes, editor_uids: Vec<i64>) {
+    let workspace_id = self.workspace_id.clone();
+    let result = tokio::task::spawn_blocking(move || {
+      let encoded_collab = EncodedCollab::decode_from_bytes(&encoded_folder)
+        .map_err(|err| AppError::Internal(err.into()))?;
+      let mut trashed_views = Vec::with_capacity(editor_uids.len());
+      for uid in editor_uids {
+        let folder = Folder::from_collab_doc_state(
+          uid,
+          CollabOrigin::Server,
+          encoded_collab.clone().into(),
+          &workspace_id,
+          vec![],
+        )
+        .map_err(|err| AppError::Unhandled(err.to_string()))?;
+        let views: Vec<(String, DateTime<Utc>)> = folder
+          .get_my_trash_sections()
+          .into_iter()
+          .map(|item| {
+            let deleted_at = DateTime::from_timestamp(item.timestamp, 0).unwrap_or_else(Utc::now);
+            (item.id, deleted_at)
+          })
+          .collect();
+        trashed_views.push((uid, views));
+      }
+      Ok::<_, AppError>(trashed_views)
+    })
+    .await
+    .map_err(|err| AppError::Internal(err.into()));
+
+    let trashed_views = match result {
+      Ok(Ok(trashed_views)) => trashed_views,
+      Ok(Err(err)) | Err(err) => {
+        warn!("fail to read the trash of {}: {}", self.object_id, err);
+        return;
+      },
+    };
+    for (uid, views) in trashed_views {
+      if let Err(err) = self
+        .storage
+        .record_trashed_views(&self.workspace_id, uid, views)
+        .await
+      {
+        warn!(
+          "fail to record the trash of {} in {}: {}",
+          uid, self.object_id, err
+        );
+      }
+    }
+  }
 }
 
 /// Encodes collaboration parameters for a given workspace and object.
@@ -172,13 +172,16 @@ impl GroupManagementState {
     Ok(())
   }
 
-  pub(crate) async fn remove_user(&self, user: &RealtimeUser) {
+  /// Remove the user from all the groups it is subscribed to. Returns the object ids of those groups.
+  pub(crate) async fn remove_user(&self, user: &RealtimeUser) -> Vec<String> {
     let entry = self.editing_by_user.remove(user);
     if entry.is_some() {
       self.metrics_calculate.num_of_editing_users.dec();
     }
+    let mut object_ids = vec![];
     if let Some(editing_objects) = entry.map(|(_, e)| e) {
       for editing in editing_objects {
+        object_ids.push(editing.object_id.clone());
         match self.group_by_object_id.try_get(&editing.object_id) {
           TryResult::Present(group) => {
             group.remove_user(user).await;
@@ -202,6 +205,7 @@ impl GroupManagementState {
         }
       }
     }
+    object_ids
   }
 
   pub async fn contains_user(&self, object_id: &str, user: &RealtimeUser) -> bool {
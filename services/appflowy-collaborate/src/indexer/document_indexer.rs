@@ -9,21 +9,17 @@ use collab_document::error::DocumentError;
 use collab_entity::CollabType;
 
 use app_error::AppError;
-use appflowy_ai_client::client::AppFlowyAIClient;
-use appflowy_ai_client::dto::{
-  EmbeddingEncodingFormat, EmbeddingInput, EmbeddingOutput, EmbeddingRequest, EmbeddingsModel,
-};
 use database_entity::dto::{AFCollabEmbeddingParams, AFCollabEmbeddings, EmbeddingContentType};
 
-use crate::indexer::{DocumentDataExt, Indexer};
+use crate::indexer::{DocumentDataExt, EmbeddingProvider, Indexer};
 
 pub struct DocumentIndexer {
-  ai_client: AppFlowyAIClient,
+  embedder: Arc<dyn EmbeddingProvider>,
 }
 
 impl DocumentIndexer {
-  pub fn new(ai_client: AppFlowyAIClient) -> Arc<Self> {
-    Arc::new(Self { ai_client })
+  pub fn new(embedder: Arc<dyn EmbeddingProvider>) -> Arc<Self> {
+    Arc::new(Self { embedder })
   }
 }
 
@@ -76,27 +72,8 @@ impl Indexer for DocumentIndexer {
       .map(|fragment| fragment.content.clone())
       .collect();
 
-    let resp = self
-      .ai_client
-      .embeddings(EmbeddingRequest {
-        input: EmbeddingInput::StringArray(contents),
-        model: EmbeddingsModel::TextEmbedding3Small.to_string(),
-        chunk_size: 2000,
-        encoding_format: EmbeddingEncodingFormat::Float,
-        dimensions: 1536,
-      })
-      .await?;
-
-    for embedding in resp.data {
-      let param = &mut params[embedding.index as usize];
-      let embedding: Vec<f32> = match embedding.embedding {
-        EmbeddingOutput::Float(embedding) => embedding.into_iter().map(|f| f as f32).collect(),
-        EmbeddingOutput::Base64(_) => {
-          return Err(AppError::OpenError(
-            "Unexpected base64 encoding".to_string(),
-          ))
-        },
-      };
+    let embeddings = self.embedder.embed(contents).await?;
+    for (param, embedding) in params.iter_mut().zip(embeddings.vectors) {
       param.embedding = Some(embedding);
     }
 
@@ -104,10 +81,10 @@ impl Indexer for DocumentIndexer {
       "received {} embeddings for document {} - tokens used: {}",
       params.len(),
       object_id,
-      resp.total_tokens
+      embeddings.total_tokens
     );
     Ok(Some(AFCollabEmbeddings {
-      tokens_consumed: resp.total_tokens as u32,
+      tokens_consumed: embeddings.total_tokens,
       params,
     }))
   }
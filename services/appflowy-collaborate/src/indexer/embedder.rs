@@ -0,0 +1,226 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use app_error::AppError;
+use appflowy_ai_client::client::AppFlowyAIClient;
+use appflowy_ai_client::dto::{
+  EmbeddingEncodingFormat, EmbeddingInput, EmbeddingOutput, EmbeddingRequest, EmbeddingsModel,
+};
+
+use crate::config::get_env_var;
+
+/// Number of dimensions of every embedding, which is the size of the vectors stored in
+/// `af_collab_embeddings`.
+pub const EMBEDDING_DIMENSIONS: i32 = 1536;
+
+/// Generates the embeddings that collabs are indexed with, and that search queries are matched
+/// against.
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+  /// Returns the embedding of each input, in the order of the inputs.
+  async fn embed(&self, inputs: Vec<String>) -> Result<Embeddings, AppError>;
+}
+
+#[derive(Debug, Clone)]
+pub struct Embeddings {
+  pub vectors: Vec<Vec<f32>>,
+  pub total_tokens: u32,
+}
+
+/// Returns the provider selected by `APPFLOWY_EMBEDDING_PROVIDER`: `appflowy_ai` (the default)
+/// or `openai`, for any endpoint compatible with the OpenAI embeddings API.
+pub fn embedding_provider_from_env(ai_client: AppFlowyAIClient) -> Arc<dyn EmbeddingProvider> {
+  let provider = get_env_var("APPFLOWY_EMBEDDING_PROVIDER", "appflowy_ai");
+  info!("Embedding provider: {}", provider);
+  match provider.as_str() {
+    "openai" => Arc::new(OpenAIEmbeddingProvider::new(
+      get_env_var(
+        "APPFLOWY_EMBEDDING_OPENAI_URL",
+        "https://api.openai.com/v1/embeddings",
+      ),
+      get_env_var("APPFLOWY_EMBEDDING_OPENAI_API_KEY", ""),
+      get_env_var(
+        "APPFLOWY_EMBEDDING_OPENAI_MODEL",
+        &EmbeddingsModel::TextEmbedding3Small.to_string(),
+      ),
+    )),
+    _ => Arc::new(AppFlowyAIEmbeddingProvider::new(ai_client)),
+  }
+}
+
+/// Embeddings generated by the AppFlowy AI service.
+pub struct AppFlowyAIEmbeddingProvider {
+  ai_client: AppFlowyAIClient,
+}
+
+impl AppFlowyAIEmbeddingProvider {
+  pub fn new(ai_client: AppFlowyAIClient) -> Self {
+    Self { ai_client }
+  }
+}
+
+#[async_trait]
+impl EmbeddingProvider for AppFlowyAIEmbeddingProvider {
+  async fn embed(&self, inputs: Vec<String>) -> Result<Embeddings, AppError> {
+    let input = match inputs.len() {
+      1 => EmbeddingInput::String(inputs.into_iter().next().unwrap_or_default()),
+      _ => EmbeddingInput::StringArray(inputs),
+    };
+    let resp = self
+      .ai_client
+      .embeddings(EmbeddingRequest {
+        input,
+        model: EmbeddingsModel::TextEmbedding3Small.to_string(),
+        chunk_size: 2000,
+        encoding_format: EmbeddingEncodingFormat::Float,
+        dimensions: EMBEDDING_DIMENSIONS,
+      })
+      .await?;
+    let outputs = resp
+      .data
+      .into_iter()
+      .map(|embedding| (embedding.index, embedding.embedding))
+      .collect();
+    Ok(Embeddings {
+      vectors: vectors_in_input_order(outputs)?,
+      total_tokens: resp.total_tokens as u32,
+    })
+  }
+}
+
+/// Embeddings generated by an endpoint compatible with the OpenAI embeddings API, such as OpenAI
+/// itself or a self-hosted model server. The model must support [EMBEDDING_DIMENSIONS]
+/// dimensions.
+pub struct OpenAIEmbeddingProvider {
+  http_client: reqwest::Client,
+  url: String,
+  api_key: String,
+  model: String,
+}
+
+impl OpenAIEmbeddingProvider {
+  pub fn new(url: String, api_key: String, model: String) -> Self {
+    Self {
+      http_client: reqwest::Client::new(),
+      url,
+      api_key,
+      model,
+    }
+  }
+}
+
+#[derive(Serialize)]
+struct OpenAIEmbeddingRequest<'a> {
+  input: &'a [String],
+  model: &'a str,
+  dimensions: i32,
+  encoding_format: EmbeddingEncodingFormat,
+}
+
+#[derive(Deserialize)]
+struct OpenAIEmbeddingResponse {
+  data: Vec<OpenAIEmbedding>,
+  usage: OpenAIEmbeddingUsage,
+}
+
+#[derive(Deserialize)]
+struct OpenAIEmbedding {
+  index: i32,
+  embedding: EmbeddingOutput,
+}
+
+#[derive(Deserialize)]
+struct OpenAIEmbeddingUsage {
+  total_tokens: u32,
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAIEmbeddingProvider {
+  async fn embed(&self, inputs: Vec<String>) -> Result<Embeddings, AppError> {
+    let mut request = self
+      .http_client
+      .post(&self.url)
+      .json(&OpenAIEmbeddingRequest {
+        input: &inputs,
+        model: &self.model,
+        dimensions: EMBEDDING_DIMENSIONS,
+        encoding_format: EmbeddingEncodingFormat::Float,
+      });
+    if !self.api_key.is_empty() {
+      request = request.bearer_auth(&self.api_key);
+    }
+    let resp = request.send().await?;
+    let status = resp.status();
+    if !status.is_success() {
+      let body = resp.text().await.unwrap_or_default();
+      return Err(AppError::Internal(anyhow!(
+        "embedding request failed with status {}: {}",
+        status,
+        body
+      )));
+    }
+    let resp: OpenAIEmbeddingResponse = resp.json().await?;
+    let outputs = resp
+      .data
+      .into_iter()
+      .map(|embedding| (embedding.index, embedding.embedding))
+      .collect();
+    Ok(Embeddings {
+      vectors: vectors_in_input_order(outputs)?,
+      total_tokens: resp.usage.total_tokens,
+    })
+  }
+}
+
+/// Orders the embeddings by the index of their input, and checks that they have the expected
+/// size.
+fn vectors_in_input_order(
+  mut outputs: Vec<(i32, EmbeddingOutput)>,
+) -> Result<Vec<Vec<f32>>, AppError> {
+  outputs.sort_by_key(|(index, _)| *index);
+  outputs
+    .into_iter()
+    .map(|(_, output)| match output {
+      EmbeddingOutput::Float(vector) if vector.len() == EMBEDDING_DIMENSIONS as usize => {
+        Ok(vector.into_iter().map(|v| v as f32).collect())
+      },
+      EmbeddingOutput::Float(vector) => Err(AppError::Internal(anyhow!(
+        "expected embeddings of {} dimensions, got {}",
+        EMBEDDING_DIMENSIONS,
+        vector.len()
+      ))),
+      EmbeddingOutput::Base64(_) => Err(AppError::OpenError(
+        "Unexpected base64 encoding".to_string(),
+      )),
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn embeddings_are_ordered_by_input_test() {
+    let first = vec![0.1; EMBEDDING_DIMENSIONS as usize];
+    let second = vec![0.2; EMBEDDING_DIMENSIONS as usize];
+    let vectors = vectors_in_input_order(vec![
+      (1, EmbeddingOutput::Float(second)),
+      (0, EmbeddingOutput::Float(first)),
+    ])
+    .unwrap();
+    assert_eq!(vectors.len(), 2);
+    assert_eq!(vectors[0][0], 0.1);
+    assert_eq!(vectors[1][0], 0.2);
+  }
+
+  #[test]
+  fn embeddings_of_unexpected_size_are_rejected_test() {
+    let result = vectors_in_input_order(vec![(0, EmbeddingOutput::Float(vec![0.1; 3]))]);
+    assert!(result.is_err());
+  }
+}
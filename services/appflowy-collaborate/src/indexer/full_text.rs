@@ -0,0 +1,102 @@
+use collab::core::collab::DataSource;
+use collab::core::origin::CollabOrigin;
+use collab::entity::EncodedCollab;
+use collab::preclude::Collab;
+use collab_database::database::DatabaseBody;
+use collab_database::entity::FieldType;
+use collab_database::rows::{DatabaseRowBody, CELL_FIELD_TYPE, ROW_CELLS};
+use collab_database::template::entity::CELL_DATA;
+use collab_document::document::DocumentBody;
+use collab_entity::CollabType;
+use yrs::{Any, Map, MapRef, Out};
+
+use app_error::AppError;
+
+use crate::indexer::DocumentDataExt;
+
+/// Returns true if the content of collabs of this type is indexed for full-text search.
+pub fn is_full_text_indexed(collab_type: &CollabType) -> bool {
+  matches!(
+    collab_type,
+    CollabType::Document | CollabType::Database | CollabType::DatabaseRow
+  )
+}
+
+/// Extract the plain text of the collab that is indexed for full-text search. Returns `None` if
+/// collabs of this type are not indexed, or the collab is missing the data of its type.
+pub fn full_text_content(
+  object_id: &str,
+  collab_type: &CollabType,
+  encoded_collab: EncodedCollab,
+) -> Result<Option<String>, AppError> {
+  if !is_full_text_indexed(collab_type) {
+    return Ok(None);
+  }
+  let mut collab = Collab::new_with_source(
+    CollabOrigin::Empty,
+    object_id,
+    DataSource::DocStateV1(encoded_collab.doc_state.into()),
+    vec![],
+    false,
+  )
+  .map_err(|err| AppError::Internal(err.into()))?;
+  let content = match collab_type {
+    CollabType::Document => document_text(&collab),
+    CollabType::Database => database_text(&collab),
+    CollabType::DatabaseRow => database_row_text(object_id, &mut collab),
+    _ => None,
+  };
+  Ok(content)
+}
+
+fn document_text(collab: &Collab) -> Option<String> {
+  let document = DocumentBody::from_collab(collab)?;
+  let document_data = document.get_document_data(&collab.transact()).ok()?;
+  Some(document_data.to_plain_text())
+}
+
+/// The names of the views and fields of the database. The content of the rows is indexed with
+/// each row.
+fn database_text(collab: &Collab) -> Option<String> {
+  let database = DatabaseBody::from_collab(collab)?;
+  let txn = collab.transact();
+  let mut buf = String::new();
+  for view in database.views.get_all_views(&txn) {
+    push_text(&mut buf, &view.name);
+  }
+  for field in database.fields.get_all_fields(&txn) {
+    push_text(&mut buf, &field.name);
+  }
+  Some(buf)
+}
+
+/// The text held by the text and url cells of the row.
+fn database_row_text(object_id: &str, collab: &mut Collab) -> Option<String> {
+  let row = DatabaseRowBody::open(object_id.to_string().into(), collab).ok()?;
+  let txn = collab.transact();
+  let cells: MapRef = row.get_data().get(&txn, ROW_CELLS)?.cast().ok()?;
+  let mut buf = String::new();
+  for (_, cell) in cells.iter(&txn) {
+    let cell = match cell.cast::<MapRef>() {
+      Ok(cell) => cell,
+      Err(_) => continue,
+    };
+    let is_text = matches!(
+      cell.get(&txn, CELL_FIELD_TYPE),
+      Some(Out::Any(Any::BigInt(n)))
+        if n == FieldType::RichText as i64 || n == FieldType::URL as i64
+    );
+    if let (true, Some(Out::Any(Any::String(text)))) = (is_text, cell.get(&txn, CELL_DATA)) {
+      push_text(&mut buf, &text);
+    }
+  }
+  Some(buf)
+}
+
+fn push_text(buf: &mut String, text: &str) {
+  let trimmed = text.trim();
+  if !trimmed.is_empty() {
+    buf.push_str(trimmed);
+    buf.push(' ');
+  }
+}
@@ -1,7 +1,11 @@
 mod document_indexer;
+mod embedder;
 mod ext;
+mod full_text;
 mod provider;
 
 pub use document_indexer::DocumentIndexer;
+pub use embedder::*;
 pub use ext::DocumentDataExt;
+pub use full_text::*;
 pub use provider::*;
@@ -16,7 +16,7 @@ use tracing::info;
 use uuid::Uuid;
 
 use crate::config::get_env_var;
-use crate::indexer::DocumentIndexer;
+use crate::indexer::{embedding_provider_from_env, DocumentIndexer, EmbeddingProvider};
 use app_error::AppError;
 use appflowy_ai_client::client::AppFlowyAIClient;
 use database::collab::{CollabStorage, GetCollabOrigin};
@@ -56,6 +56,7 @@ pub trait Indexer: Send + Sync {
 pub struct IndexerProvider {
   db: PgPool,
   indexer_cache: HashMap<CollabType, Arc<dyn Indexer>>,
+  embedder: Arc<dyn EmbeddingProvider>,
 }
 
 impl IndexerProvider {
@@ -64,17 +65,25 @@ impl IndexerProvider {
     let enabled = get_env_var("APPFLOWY_INDEXER_ENABLED", "true")
       .parse::<bool>()
       .unwrap_or(true);
+    let embedder = embedding_provider_from_env(ai_client);
 
     info!("Indexer is enabled: {}", enabled);
     if enabled {
-      cache.insert(CollabType::Document, DocumentIndexer::new(ai_client));
+      cache.insert(CollabType::Document, DocumentIndexer::new(embedder.clone()));
     }
     Arc::new(Self {
       db,
       indexer_cache: cache,
+      embedder,
     })
   }
 
+  /// Returns the provider the collabs are embedded with. Search queries must be embedded with the
+  /// same provider to be comparable.
+  pub fn embedder(&self) -> Arc<dyn EmbeddingProvider> {
+    self.embedder.clone()
+  }
+
   pub async fn can_index_workspace(&self, workspace_id: &str) -> Result<bool, AppError> {
     let uuid = Uuid::parse_str(workspace_id)?;
     let settings = select_workspace_settings(&self.db, &uuid).await?;
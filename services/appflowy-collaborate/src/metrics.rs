@@ -1,6 +1,14 @@
+use std::collections::VecDeque;
+use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
 use std::time::Duration;
 
+use collab_entity::CollabType;
+use dashmap::DashMap;
+
+use prometheus_client::encoding::EncodeLabelSet;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
 use prometheus_client::metrics::gauge::Gauge;
 use prometheus_client::metrics::histogram::Histogram;
 use prometheus_client::registry::Registry;
@@ -25,6 +33,17 @@ pub struct CollabRealtimeMetrics {
   pub(crate) apply_update_time: Histogram,
   /// How big the update is in bytes.
   pub(crate) apply_update_size: Histogram,
+  /// How long it takes to encode a collab before it is saved, in milliseconds.
+  pub(crate) encode_collab_time: Histogram,
+  /// How long it takes for an update to be sent to the other subscribers of the collab once it
+  /// is applied, in milliseconds.
+  pub(crate) broadcast_lag: Histogram,
+  /// The updates applied over the last minute, to report the update throughput.
+  pub(crate) recent_updates: Arc<SlidingWindow>,
+  /// The broadcast lag of the updates sent over the last minute, in milliseconds.
+  pub(crate) recent_broadcast_lag: Arc<SlidingWindow>,
+  /// The sessions open on each collab of this server, by object id.
+  pub(crate) collab_sessions: Arc<DashMap<String, CollabSessions>>,
 }
 
 impl CollabRealtimeMetrics {
@@ -54,6 +73,17 @@ impl CollabRealtimeMetrics {
         ]
         .into_iter(),
       ),
+      // time spent on encoding a collab in milliseconds: 1ms, 5ms, 15ms, 30ms, 100ms, 200ms, 500ms, 1s
+      encode_collab_time: Histogram::new(
+        [1.0, 5.0, 15.0, 30.0, 100.0, 200.0, 500.0, 1000.0].into_iter(),
+      ),
+      // time spent on broadcasting an update in milliseconds: 1ms, 5ms, 15ms, 30ms, 100ms, 200ms, 500ms, 1s
+      broadcast_lag: Histogram::new(
+        [1.0, 5.0, 15.0, 30.0, 100.0, 200.0, 500.0, 1000.0].into_iter(),
+      ),
+      recent_updates: Default::default(),
+      recent_broadcast_lag: Default::default(),
+      collab_sessions: Default::default(),
     }
   }
 
@@ -116,9 +146,171 @@ impl CollabRealtimeMetrics {
       "size of updates applied to collab in bytes",
       metrics.apply_update_size.clone(),
     );
+    realtime_registry.register(
+      "encode_collab_time",
+      "time spent on encoding collabs before saving them in milliseconds",
+      metrics.encode_collab_time.clone(),
+    );
+    realtime_registry.register(
+      "broadcast_lag",
+      "time spent on sending applied updates to the other subscribers in milliseconds",
+      metrics.broadcast_lag.clone(),
+    );
 
     metrics
   }
+
+  pub(crate) fn record_applied_update(&self) {
+    self.apply_update_count.inc();
+    self.recent_updates.record(1);
+  }
+
+  pub(crate) fn record_broadcast_lag(&self, elapsed: Duration) {
+    let millis = elapsed.as_millis() as u64;
+    self.broadcast_lag.observe(millis as f64);
+    self.recent_broadcast_lag.record(millis);
+  }
+
+  /// Replace the sessions of the collab with the ones of the users currently subscribed to it.
+  pub(crate) fn set_collab_sessions(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    collab_type: &CollabType,
+    uids: Vec<i64>,
+  ) {
+    self.collab_sessions.insert(
+      object_id.to_string(),
+      CollabSessions {
+        workspace_id: workspace_id.to_string(),
+        collab_type: collab_type.clone(),
+        uids,
+      },
+    );
+  }
+
+  pub(crate) fn remove_collab_sessions(&self, object_id: &str) {
+    self.collab_sessions.remove(object_id);
+  }
+
+  /// The state of the realtime collaboration on this server, as it is right now.
+  pub fn health(&self) -> RealtimeHealth {
+    RealtimeHealth {
+      connected_users: self.connected_users.get(),
+      editing_users: self.num_of_editing_users.get(),
+      open_collabs: self.opening_collab_count.get(),
+      applied_updates: self.apply_update_count.get(),
+      failed_updates: self.apply_update_failed_count.get(),
+      recent_updates: self.recent_updates.summary(),
+      recent_broadcast_lag: self.recent_broadcast_lag.summary(),
+      collab_sessions: self
+        .collab_sessions
+        .iter()
+        .map(|entry| (entry.key().clone(), entry.value().clone()))
+        .collect(),
+    }
+  }
+}
+
+/// The sessions open on a collab. A user has a session for each device the collab is open on.
+#[derive(Debug, Clone)]
+pub struct CollabSessions {
+  pub workspace_id: String,
+  pub collab_type: CollabType,
+  /// The uid of the user of each session.
+  pub uids: Vec<i64>,
+}
+
+/// A snapshot of [CollabRealtimeMetrics].
+#[derive(Debug, Clone)]
+pub struct RealtimeHealth {
+  /// The number of websocket connections.
+  pub connected_users: i64,
+  /// The number of connected users with at least one collab open.
+  pub editing_users: i64,
+  pub open_collabs: i64,
+  /// The number of updates applied since the server started.
+  pub applied_updates: i64,
+  /// The number of updates that failed to be applied since the server started.
+  pub failed_updates: i64,
+  /// The updates applied over the last minute.
+  pub recent_updates: WindowSummary,
+  /// The broadcast lag of the updates sent over the last minute, in milliseconds.
+  pub recent_broadcast_lag: WindowSummary,
+  /// The sessions open on each collab, by object id.
+  pub collab_sessions: Vec<(String, CollabSessions)>,
+}
+
+/// The number of seconds [SlidingWindow] keeps the values for.
+pub const SLIDING_WINDOW_SECS: i64 = 60;
+
+/// Keeps the values recorded over the last minute, summed up a second at a time.
+#[derive(Default)]
+pub struct SlidingWindow {
+  buckets: parking_lot::Mutex<VecDeque<WindowBucket>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct WindowBucket {
+  second: i64,
+  summary: WindowSummary,
+}
+
+/// The values recorded in a [SlidingWindow].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WindowSummary {
+  pub count: u64,
+  pub sum: u64,
+  pub max: u64,
+}
+
+impl WindowSummary {
+  fn add(&mut self, other: &WindowSummary) {
+    self.count += other.count;
+    self.sum += other.sum;
+    self.max = self.max.max(other.max);
+  }
+}
+
+impl SlidingWindow {
+  pub fn record(&self, value: u64) {
+    self.record_at(chrono::Utc::now().timestamp(), value);
+  }
+
+  pub fn summary(&self) -> WindowSummary {
+    self.summary_at(chrono::Utc::now().timestamp())
+  }
+
+  fn record_at(&self, second: i64, value: u64) {
+    let value = WindowSummary {
+      count: 1,
+      sum: value,
+      max: value,
+    };
+    let mut buckets = self.buckets.lock();
+    match buckets.back_mut() {
+      Some(bucket) if bucket.second == second => bucket.summary.add(&value),
+      _ => buckets.push_back(WindowBucket {
+        second,
+        summary: value,
+      }),
+    }
+    while buckets.front().map_or(false, |bucket| {
+      bucket.second + SLIDING_WINDOW_SECS <= second
+    }) {
+      buckets.pop_front();
+    }
+  }
+
+  fn summary_at(&self, second: i64) -> WindowSummary {
+    let mut summary = WindowSummary::default();
+    for bucket in self.buckets.lock().iter() {
+      if bucket.second + SLIDING_WINDOW_SECS > second {
+        summary.add(&bucket.summary);
+      }
+    }
+    summary
+  }
 }
 
 pub(crate) fn spawn_metrics<S>(metrics: Arc<CollabRealtimeMetrics>, storage: Arc<S>)
@@ -216,3 +408,172 @@ impl CollabMetrics {
     self.total_queue_collab_count.set(total_attempt);
   }
 }
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct CollabReadLabel {
+  /// Where the collab was read from, `memory` for the redis cache or `disk` for postgres.
+  pub source: String,
+}
+
+/// Metrics of the collab storage layer, recorded by [crate::collab::cache::CollabCache].
+#[derive(Clone)]
+pub struct CollabStorageMetrics {
+  read_count: Family<CollabReadLabel, Counter>,
+  read_failure_count: Counter,
+  write_count: Counter,
+  write_failure_count: Counter,
+  /// How long it takes to read a collab in milliseconds.
+  read_time: Histogram,
+  /// How long it takes to write a collab in milliseconds.
+  write_time: Histogram,
+  /// How long it takes to decode a collab read from the storage in milliseconds.
+  decode_time: Histogram,
+  /// The share of the reads that are served by the memory cache, between 0 and 1.
+  cache_hit_rate: Gauge<f64, AtomicU64>,
+}
+
+impl Default for CollabStorageMetrics {
+  fn default() -> Self {
+    Self::init()
+  }
+}
+
+impl CollabStorageMetrics {
+  fn init() -> Self {
+    Self {
+      read_count: Family::default(),
+      read_failure_count: Default::default(),
+      write_count: Default::default(),
+      write_failure_count: Default::default(),
+      // time spent on reading or writing a collab in milliseconds: 1ms, 5ms, 15ms, 30ms, 100ms,
+      // 200ms, 500ms, 1s, 5s
+      read_time: Histogram::new(
+        [1.0, 5.0, 15.0, 30.0, 100.0, 200.0, 500.0, 1000.0, 5000.0].into_iter(),
+      ),
+      write_time: Histogram::new(
+        [1.0, 5.0, 15.0, 30.0, 100.0, 200.0, 500.0, 1000.0, 5000.0].into_iter(),
+      ),
+      // time spent on decoding a collab in milliseconds: 1ms, 5ms, 15ms, 30ms, 100ms, 200ms, 500ms, 1s
+      decode_time: Histogram::new([1.0, 5.0, 15.0, 30.0, 100.0, 200.0, 500.0, 1000.0].into_iter()),
+      cache_hit_rate: Default::default(),
+    }
+  }
+
+  pub fn register(registry: &mut Registry) -> Self {
+    let metrics = Self::init();
+    let storage_registry = registry.sub_registry_with_prefix("collab_storage");
+    storage_registry.register(
+      "read_count",
+      "number of collabs read, by source",
+      metrics.read_count.clone(),
+    );
+    storage_registry.register(
+      "read_failure_count",
+      "number of collabs that failed to be read",
+      metrics.read_failure_count.clone(),
+    );
+    storage_registry.register(
+      "write_count",
+      "number of collabs written",
+      metrics.write_count.clone(),
+    );
+    storage_registry.register(
+      "write_failure_count",
+      "number of collabs that failed to be written",
+      metrics.write_failure_count.clone(),
+    );
+    storage_registry.register(
+      "read_time",
+      "time spent on reading collabs in milliseconds",
+      metrics.read_time.clone(),
+    );
+    storage_registry.register(
+      "write_time",
+      "time spent on writing collabs in milliseconds",
+      metrics.write_time.clone(),
+    );
+    storage_registry.register(
+      "decode_time",
+      "time spent on decoding collabs in milliseconds",
+      metrics.decode_time.clone(),
+    );
+    storage_registry.register(
+      "cache_hit_rate",
+      "share of the collab reads served by the memory cache",
+      metrics.cache_hit_rate.clone(),
+    );
+
+    metrics
+  }
+
+  pub fn record_read(&self, source: &str, count: u64) {
+    self
+      .read_count
+      .get_or_create(&CollabReadLabel {
+        source: source.to_string(),
+      })
+      .inc_by(count);
+  }
+
+  pub fn record_read_time(&self, elapsed: Duration) {
+    self.read_time.observe(elapsed.as_millis() as f64);
+  }
+
+  pub fn record_read_failure(&self) {
+    self.read_failure_count.inc();
+  }
+
+  pub fn record_write(&self, success: bool, elapsed: Duration) {
+    if success {
+      self.write_count.inc();
+    } else {
+      self.write_failure_count.inc();
+    }
+    self.write_time.observe(elapsed.as_millis() as f64);
+  }
+
+  pub fn record_decode(&self, elapsed: Duration) {
+    self.decode_time.observe(elapsed.as_millis() as f64);
+  }
+
+  pub fn record_cache_hit_rate(&self, success_attempts: u64, total_attempts: u64) {
+    if total_attempts > 0 {
+      self
+        .cache_hit_rate
+        .set(success_attempts as f64 / total_attempts as f64);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sliding_window_test() {
+    let window = SlidingWindow::default();
+    window.record_at(100, 5);
+    window.record_at(100, 20);
+    window.record_at(130, 10);
+    assert_eq!(
+      window.summary_at(130),
+      WindowSummary {
+        count: 3,
+        sum: 35,
+        max: 20
+      }
+    );
+    // the values recorded a minute ago are left out
+    assert_eq!(
+      window.summary_at(160),
+      WindowSummary {
+        count: 1,
+        sum: 10,
+        max: 10
+      }
+    );
+    window.record_at(200, 1);
+    assert_eq!(window.buckets.lock().len(), 1);
+    assert_eq!(window.summary_at(200).sum, 1);
+  }
+}
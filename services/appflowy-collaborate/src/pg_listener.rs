@@ -1,4 +1,7 @@
-use crate::collab::notification::CollabMemberNotification;
+use crate::collab::notification::{
+  CollabGroupMemberNotification, CollabInheritanceNotification, CollabMemberNotification,
+  WorkspaceGroupMemberNotification,
+};
 use anyhow::Error;
 use database::listener::PostgresDBListener;
 use database::pg_row::AFUserNotification;
@@ -10,6 +13,9 @@ pub struct PgListeners {
   user_listener: UserListener,
   workspace_member_listener: WorkspaceMemberListener,
   collab_member_listener: CollabMemberListener,
+  workspace_group_member_listener: WorkspaceGroupMemberListener,
+  collab_group_member_listener: CollabGroupMemberListener,
+  collab_inheritance_listener: CollabInheritanceListener,
 }
 
 impl PgListeners {
@@ -22,10 +28,22 @@ impl PgListeners {
     let collab_member_listener =
       CollabMemberListener::new(pg_pool, "af_collab_member_channel").await?;
 
+    let workspace_group_member_listener =
+      WorkspaceGroupMemberListener::new(pg_pool, "af_workspace_group_member_channel").await?;
+
+    let collab_group_member_listener =
+      CollabGroupMemberListener::new(pg_pool, "af_collab_group_member_channel").await?;
+
+    let collab_inheritance_listener =
+      CollabInheritanceListener::new(pg_pool, "af_collab_inheritance_channel").await?;
+
     Ok(Self {
       user_listener,
       workspace_member_listener,
       collab_member_listener,
+      workspace_group_member_listener,
+      collab_group_member_listener,
+      collab_inheritance_listener,
     })
   }
 
@@ -39,6 +57,24 @@ impl PgListeners {
     self.collab_member_listener.notify.subscribe()
   }
 
+  pub fn subscribe_workspace_group_member_change(
+    &self,
+  ) -> broadcast::Receiver<WorkspaceGroupMemberNotification> {
+    self.workspace_group_member_listener.notify.subscribe()
+  }
+
+  pub fn subscribe_collab_group_member_change(
+    &self,
+  ) -> broadcast::Receiver<CollabGroupMemberNotification> {
+    self.collab_group_member_listener.notify.subscribe()
+  }
+
+  pub fn subscribe_collab_inheritance_change(
+    &self,
+  ) -> broadcast::Receiver<CollabInheritanceNotification> {
+    self.collab_inheritance_listener.notify.subscribe()
+  }
+
   pub fn subscribe_user_change(&self, uid: i64) -> tokio::sync::mpsc::Receiver<AFUserNotification> {
     let (tx, rx) = tokio::sync::mpsc::channel(100);
     let mut user_notify = self.user_listener.notify.subscribe();
@@ -58,3 +94,6 @@ impl PgListeners {
 pub type CollabMemberListener = PostgresDBListener<CollabMemberNotification>;
 pub type UserListener = PostgresDBListener<AFUserNotification>;
 pub type WorkspaceMemberListener = PostgresDBListener<WorkspaceMemberNotification>;
+pub type WorkspaceGroupMemberListener = PostgresDBListener<WorkspaceGroupMemberNotification>;
+pub type CollabGroupMemberListener = PostgresDBListener<CollabGroupMemberNotification>;
+pub type CollabInheritanceListener = PostgresDBListener<CollabInheritanceNotification>;
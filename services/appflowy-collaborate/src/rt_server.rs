@@ -22,10 +22,12 @@ use crate::config::get_env_var;
 use crate::connect_state::ConnectState;
 use crate::error::{CreateGroupFailedReason, RealtimeError};
 use crate::group::cmd::{GroupCommand, GroupCommandRunner, GroupCommandSender};
+use crate::group::fanout::CollabFanout;
 use crate::group::manager::GroupManager;
 use crate::indexer::IndexerProvider;
 use crate::metrics::spawn_metrics;
 use crate::rt_server::collaboration_runtime::COLLAB_RUNTIME;
+use crate::shared_state::RealtimeSharedState;
 use crate::state::RedisConnectionManager;
 use crate::{CollabRealtimeMetrics, RealtimeClientWebsocketSink};
 
@@ -68,9 +70,17 @@ where
       info!("CollaborationServer with actix-web runtime");
     }
 
+    // Required when more than one realtime server runs behind the load balancer, so that the
+    // clients of the same collab see each other's changes whatever server they are connected to.
+    let enable_fanout = get_env_var("APPFLOWY_COLLABORATE_FANOUT", "false")
+      .parse::<bool>()
+      .unwrap_or(false);
+    let fanout = enable_fanout.then(|| CollabFanout::new(redis_connection_manager.clone()));
+
     let connect_state = ConnectState::new();
     let access_control = Arc::new(access_control);
-    let collab_stream = CollabRedisStream::new_with_connection_manager(redis_connection_manager);
+    let collab_stream =
+      CollabRedisStream::new_with_connection_manager(redis_connection_manager.clone());
     let group_manager = Arc::new(
       GroupManager::new(
         storage.clone(),
@@ -81,6 +91,9 @@ where
         edit_state_max_count,
         edit_state_max_secs,
         indexer_provider.clone(),
+        RealtimeSharedState::new(redis_connection_manager),
+        connect_state.client_message_routers.clone(),
+        fanout,
       )
       .await?,
     );
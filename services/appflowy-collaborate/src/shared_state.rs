@@ -1,14 +1,18 @@
 use crate::error::RealtimeError;
+use crate::state::RedisConnectionManager;
+use chrono::{DateTime, Utc};
+use database_entity::dto::CollabPresence;
 use futures_util::StreamExt;
 use redis::{pipe, AsyncCommands, AsyncIter};
+use std::collections::HashMap;
 
 #[derive(Clone)]
 pub struct RealtimeSharedState {
-  redis_conn_manager: redis::aio::ConnectionManager,
+  redis_conn_manager: RedisConnectionManager,
 }
 
 impl RealtimeSharedState {
-  pub fn new(redis_conn_manager: redis::aio::ConnectionManager) -> Self {
+  pub fn new(redis_conn_manager: RedisConnectionManager) -> Self {
     Self { redis_conn_manager }
   }
   pub async fn add_connected_user(&self, uid: i64, device_id: &str) -> Result<(), RealtimeError> {
@@ -45,6 +49,91 @@ impl RealtimeSharedState {
     Ok(result.is_some())
   }
 
+  /// Mark the device of the user as present on the collab, with now as its last activity.
+  pub async fn update_collab_presence(
+    &self,
+    object_id: &str,
+    uid: i64,
+    device_id: &str,
+  ) -> Result<(), RealtimeError> {
+    let mut conn = self.redis_conn_manager.clone();
+    let key = collab_presence_cache_key(object_id);
+    pipe()
+      .atomic()
+      .zadd(
+        &key,
+        collab_presence_member(uid, device_id),
+        Utc::now().timestamp_millis(),
+      )
+      .ignore()
+      .expire(&key, COLLAB_PRESENCE_EXPIRE_SECS)
+      .ignore()
+      .query_async::<_, ()>(&mut conn)
+      .await
+      .map_err(|err| RealtimeError::Internal(err.into()))?;
+    Ok(())
+  }
+
+  pub async fn remove_collab_presence(
+    &self,
+    object_id: &str,
+    uid: i64,
+    device_id: &str,
+  ) -> Result<(), RealtimeError> {
+    let mut conn = self.redis_conn_manager.clone();
+    let key = collab_presence_cache_key(object_id);
+    let _: () = conn
+      .zrem(key, collab_presence_member(uid, device_id))
+      .await
+      .map_err(|err| RealtimeError::Internal(err.into()))?;
+    Ok(())
+  }
+
+  /// Returns the users present on the collab, most recently active first. The last activity of a
+  /// user is the one of its most recently active device. The devices that have not been active for
+  /// [COLLAB_PRESENCE_EXPIRE_SECS] are dropped, in case the server they were connected to stopped
+  /// without removing them.
+  pub async fn get_collab_presences(
+    &self,
+    object_id: &str,
+  ) -> Result<Vec<CollabPresence>, RealtimeError> {
+    let mut conn = self.redis_conn_manager.clone();
+    let key = collab_presence_cache_key(object_id);
+    let expired_before = Utc::now().timestamp_millis() - COLLAB_PRESENCE_EXPIRE_SECS * 1000;
+    let _: () = conn
+      .zrembyscore(&key, "-inf", expired_before)
+      .await
+      .map_err(|err| RealtimeError::Internal(err.into()))?;
+    let members: Vec<(String, f64)> = conn
+      .zrange_withscores(&key, 0, -1)
+      .await
+      .map_err(|err| RealtimeError::Internal(err.into()))?;
+
+    let mut last_active_by_uid = HashMap::<i64, i64>::new();
+    for (member, last_active) in members {
+      let uid = member
+        .split_once(':')
+        .and_then(|(uid, _)| uid.parse::<i64>().ok());
+      if let Some(uid) = uid {
+        let entry = last_active_by_uid.entry(uid).or_default();
+        *entry = (*entry).max(last_active as i64);
+      }
+    }
+    let mut presences = last_active_by_uid
+      .into_iter()
+      .filter_map(|(uid, last_active)| {
+        Some(CollabPresence {
+          uid,
+          last_active_at: DateTime::from_timestamp_millis(last_active)?,
+        })
+      })
+      .collect::<Vec<_>>();
+    presences.sort_by(|a, b| b.last_active_at.cmp(&a.last_active_at));
+    Ok(presences)
+  }
+
+  /// Remove the connected users, and the presences on the collabs, left by the previous run of
+  /// the server.
   pub async fn remove_all_connected_users(&self) -> Result<(), RealtimeError> {
     let mut conn = self.redis_conn_manager.clone();
     let iter: AsyncIter<String> = conn
@@ -68,7 +157,22 @@ impl RealtimeSharedState {
 
 pub(crate) const REALTIME_SHARE_STATE_PREFIX: &str = "realtime_shared_state_v0";
 
+/// The presences of a collab expire once none of its devices has been active for 3 hours.
+pub(crate) const COLLAB_PRESENCE_EXPIRE_SECS: i64 = 60 * 60 * 3;
+
 #[inline]
 pub(crate) fn realtime_shared_state_cache_key(uid: &i64, device_id: &str) -> String {
   format!("{}:{}:{}", REALTIME_SHARE_STATE_PREFIX, uid, device_id)
 }
+
+/// The presences of a collab are kept under [REALTIME_SHARE_STATE_PREFIX], so that they are removed
+/// along with the connected users by [RealtimeSharedState::remove_all_connected_users].
+#[inline]
+pub(crate) fn collab_presence_cache_key(object_id: &str) -> String {
+  format!("{}:presence:{}", REALTIME_SHARE_STATE_PREFIX, object_id)
+}
+
+#[inline]
+fn collab_presence_member(uid: i64, device_id: &str) -> String {
+  format!("{}:{}", uid, device_id)
+}
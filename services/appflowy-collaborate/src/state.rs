@@ -13,12 +13,12 @@ use database::user::{select_all_uid_uuid, select_uid_from_uuid};
 use crate::collab::storage::CollabAccessControlStorage;
 use crate::config::Config;
 use crate::indexer::IndexerProvider;
-use crate::metrics::CollabMetrics;
+use crate::metrics::{CollabMetrics, CollabStorageMetrics};
 use crate::pg_listener::PgListeners;
 use crate::shared_state::RealtimeSharedState;
 use crate::CollabRealtimeMetrics;
 
-pub type RedisConnectionManager = redis::aio::ConnectionManager;
+pub type RedisConnectionManager = collab_stream::connection::RedisConnectionManager;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -35,11 +35,11 @@ pub struct AppState {
 
 #[derive(Clone)]
 pub struct AppMetrics {
-  #[allow(dead_code)]
   pub registry: Arc<prometheus_client::registry::Registry>,
   pub access_control_metrics: Arc<AccessControlMetrics>,
   pub realtime_metrics: Arc<CollabRealtimeMetrics>,
   pub collab_metrics: Arc<CollabMetrics>,
+  pub collab_storage_metrics: Arc<CollabStorageMetrics>,
 }
 
 impl Default for AppMetrics {
@@ -54,11 +54,13 @@ impl AppMetrics {
     let access_control_metrics = Arc::new(AccessControlMetrics::register(&mut registry));
     let realtime_metrics = Arc::new(CollabRealtimeMetrics::register(&mut registry));
     let collab_metrics = Arc::new(CollabMetrics::register(&mut registry));
+    let collab_storage_metrics = Arc::new(CollabStorageMetrics::register(&mut registry));
     Self {
       registry: Arc::new(registry),
       access_control_metrics,
       realtime_metrics,
       collab_metrics,
+      collab_storage_metrics,
     }
   }
 }
@@ -1,4 +1,7 @@
-use appflowy_collaborate::indexer::DocumentDataExt;
+use appflowy_collaborate::indexer::{full_text_content, DocumentDataExt};
+use collab::preclude::Collab;
+use collab_document::document::Document;
+use collab_entity::CollabType;
 use workspace_template::document::getting_started::{
   get_initial_document_data, getting_started_document_data,
 };
@@ -18,3 +21,25 @@ fn document_plain_text_with_nested_blocks() {
   let expected = "Welcome to AppFlowy! Here are the basics Here is H3 Click anywhere and just start typing. Click Enter to create a new line. Highlight any text, and use the editing menu to style your writing however you like. As soon as you type / a menu will pop up. Select different types of content blocks you can add. Type / followed by /bullet or /num to create a list. Click + New Page button at the bottom of your sidebar to add a new page. Click + next to any page title in the sidebar to quickly add a new subpage, Document , Grid , or Kanban Board . Keyboard shortcuts, markdown, and code block Keyboard shortcuts guide Markdown reference Type /code to insert a code block // This is the main function.\nfn main() {\n    // Print text to the console.\n    println!(\"Hello World!\");\n} This is a paragraph This is a paragraph Have a question❓ Click ? at the bottom right for help and support. This is a paragraph This is a paragraph Click ? at the bottom right for help and support. Like AppFlowy? Follow us: GitHub Twitter : @appflowy Newsletter ";
   assert_eq!(&text, expected);
 }
+
+#[test]
+fn document_full_text_content() {
+  let doc = getting_started_document_data().unwrap();
+  let expected = doc.to_plain_text();
+  let collab = Collab::new(1, "full_text", "device", vec![], false);
+  let document = Document::create_with_data(collab, doc).unwrap();
+  let encoded_collab = document.encode_collab().unwrap();
+
+  let content = full_text_content("full_text", &CollabType::Document, encoded_collab).unwrap();
+  assert_eq!(content, Some(expected));
+}
+
+#[test]
+fn folder_is_not_full_text_indexed() {
+  let collab = Collab::new(1, "full_text", "device", vec![], false);
+  let document = Document::create_with_data(collab, get_initial_document_data().unwrap()).unwrap();
+  let encoded_collab = document.encode_collab().unwrap();
+
+  let content = full_text_content("full_text", &CollabType::Folder, encoded_collab).unwrap();
+  assert_eq!(content, None);
+}
@@ -1,5 +1,6 @@
 use anyhow::Context;
 use appflowy_collaborate::shared_state::RealtimeSharedState;
+use collab_stream::connection::RedisConnectionManager;
 
 async fn redis_client() -> redis::Client {
   let redis_uri = "redis://localhost:6379";
@@ -11,7 +12,11 @@ async fn redis_client() -> redis::Client {
 #[tokio::test]
 async fn connected_user_test() {
   let redis_client = redis_client().await;
-  let shared_state = RealtimeSharedState::new(redis_client.get_connection_manager().await.unwrap());
+  let shared_state = RealtimeSharedState::new(
+    RedisConnectionManager::new_standalone(redis_client)
+      .await
+      .unwrap(),
+  );
 
   let device_id = uuid::Uuid::new_v4().to_string();
   let is_connected = shared_state
@@ -46,7 +51,11 @@ async fn connected_user_test() {
 #[tokio::test]
 async fn remove_all_connected_user_test() {
   let redis_client = redis_client().await;
-  let shared_state = RealtimeSharedState::new(redis_client.get_connection_manager().await.unwrap());
+  let shared_state = RealtimeSharedState::new(
+    RedisConnectionManager::new_standalone(redis_client)
+      .await
+      .unwrap(),
+  );
 
   let device_id = uuid::Uuid::new_v4().to_string();
   shared_state
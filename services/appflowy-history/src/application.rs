@@ -3,7 +3,7 @@ use crate::config::{Config, DatabaseSetting, Environment};
 use crate::core::manager::OpenCollabManager;
 use anyhow::Error;
 use collab_stream::client::CollabRedisStream;
-use redis::aio::ConnectionManager;
+use collab_stream::connection::RedisConnectionManager;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
 
@@ -77,9 +77,8 @@ pub async fn create_app(config: Config) -> Result<Router<Identity>, Error> {
   migrate(&pg_pool).await?;
 
   // Redis
-  let redis_connection_manager = redis::Client::open(config.redis_url)
-    .expect("failed to create redis client")
-    .get_connection_manager()
+  let redis_client = redis::Client::open(config.redis_url).expect("failed to create redis client");
+  let redis_connection_manager = RedisConnectionManager::new_standalone(redis_client)
     .await
     .expect("failed to get redis connection manager");
 
@@ -106,7 +105,7 @@ pub async fn create_app(config: Config) -> Result<Router<Identity>, Error> {
 
 #[derive(Clone)]
 pub struct AppState {
-  pub redis_client: ConnectionManager,
+  pub redis_client: RedisConnectionManager,
   pub open_collab_manager: Arc<OpenCollabManager>,
   pub pg_pool: PgPool,
 }
@@ -0,0 +1,38 @@
+use actix_web::web::Data;
+use actix_web::{web, Result, Scope};
+use app_error::AppError;
+use authentication::jwt::Authorization;
+use shared_entity::dto::admin_dto::{QueryRealtimeHealth, RealtimeHealth};
+use shared_entity::response::{AppResponse, JsonAppResponse};
+
+use crate::biz::collab::realtime_health::get_realtime_health;
+use crate::state::AppState;
+
+/// The role GoTrue gives to the administrator of the server.
+const SERVER_ADMIN_ROLE: &str = "supabase_admin";
+
+/// The endpoints reserved to the administrator of the server.
+pub fn admin_scope() -> Scope {
+  web::scope("/api/admin")
+    .service(web::resource("/realtime/health").route(web::get().to(get_realtime_health_handler)))
+}
+
+async fn get_realtime_health_handler(
+  auth: Authorization,
+  query: web::Query<QueryRealtimeHealth>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<RealtimeHealth>> {
+  ensure_server_admin(&auth)?;
+  let health = get_realtime_health(&state.metrics.realtime_metrics, query.into_inner());
+  Ok(AppResponse::Ok().with_data(health).into())
+}
+
+fn ensure_server_admin(auth: &Authorization) -> Result<(), AppError> {
+  if auth.claims.role != SERVER_ADMIN_ROLE {
+    return Err(AppError::NotEnoughPermissions {
+      user: auth.claims.email.clone(),
+      action: "read the realtime health of the server".to_string(),
+    });
+  }
+  Ok(())
+}
@@ -1,4 +1,8 @@
-use crate::api::util::ai_model_from_header;
+use crate::api::util::{ai_model_from_header, ai_stream_response};
+use crate::biz::ai::quota::consume_workspace_ai_request;
+use crate::biz::ai::summary::summarize_document;
+use crate::biz::ai::translate::translate_document;
+use crate::biz::ai::workspace_chat::stream_workspace_chat_answer;
 use crate::state::AppState;
 
 use actix_web::web::{Data, Json};
@@ -7,31 +11,42 @@ use app_error::AppError;
 use appflowy_ai_client::dto::{
   CompleteTextResponse, LocalAIConfig, TranslateRowParams, TranslateRowResponse,
 };
+use authentication::jwt::UserUuid;
 
 use futures_util::{stream, TryStreamExt};
 
 use serde::Deserialize;
 use shared_entity::dto::ai_dto::{
-  CompleteTextParams, SummarizeRowData, SummarizeRowParams, SummarizeRowResponse,
+  CompleteTextParams, SummarizeDocumentParams, SummarizeDocumentResponse, SummarizeRowData,
+  SummarizeRowParams, SummarizeRowResponse, TranslateDocumentParams, TranslateDocumentResponse,
+  WorkspaceChatParams,
 };
 use shared_entity::response::{AppResponse, JsonAppResponse};
 
 use tracing::{error, instrument, trace};
+use uuid::Uuid;
 
 pub fn ai_completion_scope() -> Scope {
   web::scope("/api/ai/{workspace_id}")
     .service(web::resource("/complete").route(web::post().to(complete_text_handler)))
     .service(web::resource("/complete/stream").route(web::post().to(stream_complete_text_handler)))
     .service(web::resource("/summarize_row").route(web::post().to(summarize_row_handler)))
+    .service(web::resource("/summarize_document").route(web::post().to(summarize_document_handler)))
+    .service(
+      web::resource("/workspace_chat/stream").route(web::post().to(stream_workspace_chat_handler)),
+    )
     .service(web::resource("/translate_row").route(web::post().to(translate_row_handler)))
+    .service(web::resource("/translate_document").route(web::post().to(translate_document_handler)))
     .service(web::resource("/local/config").route(web::get().to(local_ai_config_handler)))
 }
 
 async fn complete_text_handler(
+  workspace_id: web::Path<Uuid>,
   state: Data<AppState>,
   payload: Json<CompleteTextParams>,
   req: HttpRequest,
 ) -> actix_web::Result<JsonAppResponse<CompleteTextResponse>> {
+  consume_workspace_ai_request(&state.pg_pool, &state.config.ai_quota, &workspace_id).await?;
   let ai_model = ai_model_from_header(&req);
   let params = payload.into_inner();
   let resp = state
@@ -43,10 +58,12 @@ async fn complete_text_handler(
 }
 
 async fn stream_complete_text_handler(
+  workspace_id: web::Path<Uuid>,
   state: Data<AppState>,
   payload: Json<CompleteTextParams>,
   req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
+  consume_workspace_ai_request(&state.pg_pool, &state.config.ai_quota, &workspace_id).await?;
   let ai_model = ai_model_from_header(&req);
   let params = payload.into_inner();
   match state
@@ -54,23 +71,17 @@ async fn stream_complete_text_handler(
     .stream_completion_text(&params.text, params.completion_type, ai_model)
     .await
   {
-    Ok(stream) => Ok(
-      HttpResponse::Ok()
-        .content_type("text/event-stream")
-        .streaming(stream.map_err(AppError::from)),
-    ),
-    Err(err) => Ok(
-      HttpResponse::Ok()
-        .content_type("text/event-stream")
-        .streaming(stream::once(async move {
-          Err(AppError::AIServiceUnavailable(err.to_string()))
-        })),
-    ),
+    Ok(stream) => Ok(ai_stream_response(&req, stream.map_err(AppError::from))),
+    Err(err) => Ok(ai_stream_response(
+      &req,
+      stream::once(async move { Err(AppError::AIServiceUnavailable(err.to_string())) }),
+    )),
   }
 }
 
 #[instrument(level = "debug", skip(state, payload), err)]
 async fn summarize_row_handler(
+  workspace_id: web::Path<Uuid>,
   state: Data<AppState>,
   payload: Json<SummarizeRowParams>,
   req: HttpRequest,
@@ -91,6 +102,7 @@ async fn summarize_row_handler(
         );
       }
 
+      consume_workspace_ai_request(&state.pg_pool, &state.config.ai_quota, &workspace_id).await?;
       let ai_model = ai_model_from_header(&req);
       let result = state.ai_client.summarize_row(&content, ai_model).await;
       let resp = match result {
@@ -108,12 +120,53 @@ async fn summarize_row_handler(
   }
 }
 
+#[instrument(level = "debug", skip(state, payload), err)]
+async fn summarize_document_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  payload: Json<SummarizeDocumentParams>,
+) -> actix_web::Result<JsonAppResponse<SummarizeDocumentResponse>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let resp = summarize_document(&state, uid, &workspace_id, payload.into_inner()).await?;
+  Ok(AppResponse::Ok().with_data(resp).into())
+}
+
+#[instrument(level = "debug", skip(state, payload), err)]
+async fn translate_document_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  payload: Json<TranslateDocumentParams>,
+) -> actix_web::Result<JsonAppResponse<TranslateDocumentResponse>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let resp = translate_document(&state, uid, &workspace_id, payload.into_inner()).await?;
+  Ok(AppResponse::Ok().with_data(resp).into())
+}
+
+#[instrument(level = "debug", skip(state, payload, req), err)]
+async fn stream_workspace_chat_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  payload: Json<WorkspaceChatParams>,
+  req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let answer_stream =
+    stream_workspace_chat_answer(&state, uid, workspace_id.into_inner(), payload.into_inner())
+      .await?;
+  Ok(ai_stream_response(&req, answer_stream))
+}
+
 #[instrument(level = "debug", skip(state, payload), err)]
 async fn translate_row_handler(
+  workspace_id: web::Path<Uuid>,
   state: web::Data<AppState>,
   payload: web::Json<TranslateRowParams>,
   req: HttpRequest,
 ) -> actix_web::Result<Json<AppResponse<TranslateRowResponse>>> {
+  consume_workspace_ai_request(&state.pg_pool, &state.config.ai_quota, &workspace_id).await?;
   let params = payload.into_inner();
   let ai_model = ai_model_from_header(&req);
   match state.ai_client.translate_row(params.data, ai_model).await {
@@ -29,10 +29,12 @@ use tokio::task;
 
 use database::chat;
 
-use crate::api::util::ai_model_from_header;
+use crate::api::util::{ai_model_from_header, ai_stream_response};
+use crate::biz::ai::quota::consume_workspace_ai_request;
 
 use database::chat::chat_ops::insert_answer_message;
 use tracing::{instrument, trace, warn};
+use uuid::Uuid;
 use validator::Validate;
 
 pub fn chat_scope() -> Scope {
@@ -238,7 +240,9 @@ async fn answer_handler(
   state: Data<AppState>,
   req: HttpRequest,
 ) -> actix_web::Result<JsonAppResponse<ChatMessage>> {
-  let (_workspace_id, chat_id, message_id) = path.into_inner();
+  let (workspace_id, chat_id, message_id) = path.into_inner();
+  let workspace_id = Uuid::parse_str(&workspace_id).map_err(AppError::from)?;
+  consume_workspace_ai_request(&state.pg_pool, &state.config.ai_quota, &workspace_id).await?;
   let ai_model = ai_model_from_header(&req);
   let message = generate_chat_message_answer(
     &state.pg_pool,
@@ -257,30 +261,25 @@ async fn answer_stream_handler(
   state: Data<AppState>,
   req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
-  let (_workspace_id, chat_id, question_id) = path.into_inner();
+  let (workspace_id, chat_id, question_id) = path.into_inner();
+  let workspace_id = Uuid::parse_str(&workspace_id).map_err(AppError::from)?;
   let (content, metadata) =
     chat::chat_ops::select_chat_message_content(&state.pg_pool, question_id).await?;
+  consume_workspace_ai_request(&state.pg_pool, &state.config.ai_quota, &workspace_id).await?;
   let ai_model = ai_model_from_header(&req);
   match state
     .ai_client
     .stream_question(&chat_id, &content, Some(metadata), &ai_model)
     .await
   {
-    Ok(answer_stream) => {
-      let new_answer_stream = answer_stream.map_err(AppError::from);
-      Ok(
-        HttpResponse::Ok()
-          .content_type("text/event-stream")
-          .streaming(new_answer_stream),
-      )
-    },
-    Err(err) => Ok(
-      HttpResponse::Ok()
-        .content_type("text/event-stream")
-        .streaming(stream::once(async move {
-          Err(AppError::AIServiceUnavailable(err.to_string()))
-        })),
-    ),
+    Ok(answer_stream) => Ok(ai_stream_response(
+      &req,
+      answer_stream.map_err(AppError::from),
+    )),
+    Err(err) => Ok(ai_stream_response(
+      &req,
+      stream::once(async move { Err(AppError::AIServiceUnavailable(err.to_string())) }),
+    )),
   }
 }
 
@@ -290,30 +289,25 @@ async fn answer_stream_v2_handler(
   state: Data<AppState>,
   req: HttpRequest,
 ) -> actix_web::Result<HttpResponse> {
-  let (_workspace_id, chat_id, question_id) = path.into_inner();
+  let (workspace_id, chat_id, question_id) = path.into_inner();
+  let workspace_id = Uuid::parse_str(&workspace_id).map_err(AppError::from)?;
   let (content, metadata) =
     chat::chat_ops::select_chat_message_content(&state.pg_pool, question_id).await?;
+  consume_workspace_ai_request(&state.pg_pool, &state.config.ai_quota, &workspace_id).await?;
   let ai_model = ai_model_from_header(&req);
   match state
     .ai_client
     .stream_question_v2(&chat_id, &content, Some(metadata), &ai_model)
     .await
   {
-    Ok(answer_stream) => {
-      let new_answer_stream = answer_stream.map_err(AppError::from);
-      Ok(
-        HttpResponse::Ok()
-          .content_type("text/event-stream")
-          .streaming(new_answer_stream),
-      )
-    },
-    Err(err) => Ok(
-      HttpResponse::Ok()
-        .content_type("text/event-stream")
-        .streaming(stream::once(async move {
-          Err(AppError::AIServiceUnavailable(err.to_string()))
-        })),
-    ),
+    Ok(answer_stream) => Ok(ai_stream_response(
+      &req,
+      answer_stream.map_err(AppError::from),
+    )),
+    Err(err) => Ok(ai_stream_response(
+      &req,
+      stream::once(async move { Err(AppError::AIServiceUnavailable(err.to_string())) }),
+    )),
   }
 }
 
@@ -1,3 +1,5 @@
+use access_control::act::Action;
+use access_control::workspace::WorkspaceAccessControl;
 use actix_http::body::BoxBody;
 use actix_web::http::header::{
   ContentLength, ContentType, CACHE_CONTROL, CONTENT_LENGTH, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE,
@@ -10,12 +12,14 @@ use actix_web::{
 };
 use actix_web::{HttpResponse, Result};
 use app_error::AppError;
+use appflowy_collaborate::collab::storage_quota::check_workspace_storage_quota;
+use authentication::jwt::UserUuid;
 use chrono::DateTime;
 use database::file::BlobKey;
 use database::resource_usage::{get_all_workspace_blob_metadata, get_workspace_usage_size};
 use database_entity::file_dto::{
-  CompleteUploadRequest, CreateUploadRequest, CreateUploadResponse, UploadPartData,
-  UploadPartResponse,
+  CompleteUploadRequest, CreateUploadRequest, CreateUploadResponse, ListUploadPartsResponse,
+  UploadPartData, UploadPartResponse, UploadPartUrlResponse,
 };
 
 use serde::Deserialize;
@@ -23,11 +27,15 @@ use shared_entity::dto::workspace_dto::{BlobMetadata, RepeatedBlobMetaData, Work
 use shared_entity::response::{AppResponse, AppResponseError, JsonAppResponse};
 use sqlx::types::Uuid;
 use std::pin::Pin;
+use std::time::Duration;
 use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio_stream::StreamExt;
 use tokio_util::io::StreamReader;
-use tracing::{error, event, instrument, trace};
+use tracing::{error, event, instrument, trace, warn};
 
+use crate::biz::file::thumbnail::{
+  delete_thumbnails, get_or_create_thumbnail, thumbnail_size, DEFAULT_THUMBNAIL_SIZE, THUMBNAIL_DIR,
+};
 use crate::state::AppState;
 
 pub fn file_storage_scope() -> Scope {
@@ -54,6 +62,20 @@ pub fn file_storage_scope() -> Scope {
       web::resource("/{workspace_id}/upload_part/{parent_dir}/{file_id}/{upload_id}/{part_num}")
         .route(web::put().to(upload_part_handler)),
     )
+    .service(
+      web::resource(
+        "/{workspace_id}/upload_part_url/{parent_dir}/{file_id}/{upload_id}/{part_num}",
+      )
+      .route(web::get().to(get_upload_part_url_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/upload_parts/{parent_dir}/{file_id}/{upload_id}")
+        .route(web::get().to(list_upload_parts_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/upload/{parent_dir}/{file_id}/{upload_id}")
+        .route(web::delete().to(abort_upload_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/complete_upload")
         .route(web::put().to(complete_upload_handler)),
@@ -67,28 +89,27 @@ pub fn file_storage_scope() -> Scope {
       web::resource("/{workspace_id}/v1/metadata/{parent_dir}/{file_id}")
         .route(web::get().to(get_blob_metadata_v1_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/v1/thumbnail/{parent_dir}/{file_id}")
+        .route(web::get().to(get_thumbnail_v1_handler)),
+    )
 }
 
 #[instrument(skip_all, err)]
 async fn create_upload(
+  user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
   state: web::Data<AppState>,
   req: web::Json<CreateUploadRequest>,
 ) -> Result<JsonAppResponse<CreateUploadResponse>> {
   let req = req.into_inner();
-  if req.parent_dir.is_empty() {
-    return Err(AppError::InvalidRequest("parent_dir is empty".to_string()).into());
-  }
-
-  if req.file_id.is_empty() {
-    return Err(AppError::InvalidRequest("file_id is empty".to_string()).into());
-  }
-
   let key = BlobPathV1 {
     workspace_id: workspace_id.into_inner(),
     parent_dir: req.parent_dir.clone(),
     file_id: req.file_id.clone(),
   };
+  key.validate()?;
+  check_upload_permission(&state, &user_uuid, &key.workspace_id).await?;
   let resp = state
     .bucket_storage
     .create_upload(key, req)
@@ -107,14 +128,52 @@ struct UploadPartPath {
   part_num: i32,
 }
 
+impl UploadPartPath {
+  fn validate(&self) -> Result<(), AppError> {
+    validate_blob_path(&self.parent_dir, &self.file_id)?;
+    // S3 numbers the parts of an upload from 1 to 10000
+    if !(1..=10000).contains(&self.part_num) {
+      return Err(AppError::InvalidRequest(format!(
+        "part number {} is not between 1 and 10000",
+        self.part_num
+      )));
+    }
+    Ok(())
+  }
+}
+
+/// The uploads are only allowed to the users who can write to the workspace, as the objects of the
+/// uploads are stored under the workspace.
+async fn check_upload_permission(
+  state: &AppState,
+  user_uuid: &UserUuid,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  let uid = state.user_cache.get_user_uid(user_uuid).await?;
+  let can_write = state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id.to_string(), Action::Write)
+    .await?;
+  if !can_write {
+    return Err(AppError::NotEnoughPermissions {
+      user: uid.to_string(),
+      action: format!("upload files to workspace:{}", workspace_id),
+    });
+  }
+  Ok(())
+}
+
 #[instrument(level = "debug", skip_all, err)]
 async fn upload_part_handler(
+  user_uuid: UserUuid,
   path: web::Path<UploadPartPath>,
   state: web::Data<AppState>,
   content_length: web::Header<ContentLength>,
   mut payload: Payload,
 ) -> Result<JsonAppResponse<UploadPartResponse>> {
   let path_params = path.into_inner();
+  path_params.validate()?;
+  check_upload_permission(&state, &user_uuid, &path_params.workspace_id).await?;
   trace!(
     "upload part: workspace_id: {}, parent_dir: {}, file_id: {}, upload_id: {}, part_num: {}",
     path_params.workspace_id,
@@ -125,6 +184,13 @@ async fn upload_part_handler(
   );
 
   let content_length = content_length.into_inner().into_inner();
+  check_workspace_storage_quota(
+    &state.pg_pool,
+    &state.config.storage_quota,
+    &path_params.workspace_id,
+    content_length as i64,
+  )
+  .await?;
   let mut content = Vec::with_capacity(content_length);
   while let Some(chunk) = payload.try_next().await? {
     content.extend_from_slice(&chunk);
@@ -161,7 +227,118 @@ async fn upload_part_handler(
   Ok(AppResponse::Ok().with_data(resp).into())
 }
 
+/// Returns a presigned url the part is uploaded to directly, so that the client can upload the
+/// parts of a large file concurrently and retry a single part without going through the server.
+#[instrument(level = "debug", skip_all, err)]
+async fn get_upload_part_url_handler(
+  user_uuid: UserUuid,
+  path: web::Path<UploadPartPath>,
+  state: web::Data<AppState>,
+) -> Result<JsonAppResponse<UploadPartUrlResponse>> {
+  let path_params = path.into_inner();
+  path_params.validate()?;
+  check_upload_permission(&state, &user_uuid, &path_params.workspace_id).await?;
+
+  let expires_in_secs = state.config.s3.presigned_url_expiration_secs;
+  let key = BlobPathV1 {
+    workspace_id: path_params.workspace_id,
+    parent_dir: path_params.parent_dir,
+    file_id: path_params.file_id,
+  };
+  let url = state
+    .bucket_storage
+    .upload_part_url(
+      key,
+      &path_params.upload_id,
+      path_params.part_num,
+      Duration::from_secs(expires_in_secs),
+    )
+    .await
+    .map_err(AppResponseError::from)?;
+
+  Ok(
+    AppResponse::Ok()
+      .with_data(UploadPartUrlResponse {
+        part_num: path_params.part_num,
+        url,
+        expires_in_secs,
+      })
+      .into(),
+  )
+}
+
+#[derive(Deserialize)]
+struct UploadPath {
+  workspace_id: Uuid,
+  parent_dir: String,
+  file_id: String,
+  upload_id: String,
+}
+
+/// Lists the parts already uploaded, so that an interrupted upload can be resumed from the
+/// missing parts.
+#[instrument(level = "debug", skip_all, err)]
+async fn list_upload_parts_handler(
+  user_uuid: UserUuid,
+  path: web::Path<UploadPath>,
+  state: web::Data<AppState>,
+) -> Result<JsonAppResponse<ListUploadPartsResponse>> {
+  let UploadPath {
+    workspace_id,
+    parent_dir,
+    file_id,
+    upload_id,
+  } = path.into_inner();
+  let key = BlobPathV1 {
+    workspace_id,
+    parent_dir,
+    file_id,
+  };
+  key.validate()?;
+  check_upload_permission(&state, &user_uuid, &key.workspace_id).await?;
+
+  let parts = state
+    .bucket_storage
+    .list_upload_parts(key, &upload_id)
+    .await
+    .map_err(AppResponseError::from)?;
+  Ok(
+    AppResponse::Ok()
+      .with_data(ListUploadPartsResponse { upload_id, parts })
+      .into(),
+  )
+}
+
+#[instrument(level = "debug", skip_all, err)]
+async fn abort_upload_handler(
+  user_uuid: UserUuid,
+  path: web::Path<UploadPath>,
+  state: web::Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let UploadPath {
+    workspace_id,
+    parent_dir,
+    file_id,
+    upload_id,
+  } = path.into_inner();
+  let key = BlobPathV1 {
+    workspace_id,
+    parent_dir,
+    file_id,
+  };
+  key.validate()?;
+  check_upload_permission(&state, &user_uuid, &key.workspace_id).await?;
+
+  state
+    .bucket_storage
+    .abort_upload(key, &upload_id)
+    .await
+    .map_err(AppResponseError::from)?;
+  Ok(AppResponse::Ok().into())
+}
+
 async fn complete_upload_handler(
+  user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
   state: web::Data<AppState>,
   req: web::Json<CompleteUploadRequest>,
@@ -173,6 +350,36 @@ async fn complete_upload_handler(
     parent_dir: req.parent_dir.clone(),
     file_id: req.file_id.clone(),
   };
+  key.validate()?;
+  check_upload_permission(&state, &user_uuid, &key.workspace_id).await?;
+  match state
+    .bucket_storage
+    .list_upload_parts(key.clone(), &req.upload_id)
+    .await
+  {
+    Ok(uploaded_parts) => {
+      let upload_size = uploaded_parts
+        .iter()
+        .filter(|uploaded| {
+          req
+            .parts
+            .iter()
+            .any(|part| part.part_number == uploaded.part_num)
+        })
+        .map(|uploaded| uploaded.size)
+        .sum();
+      check_workspace_storage_quota(
+        &state.pg_pool,
+        &state.config.storage_quota,
+        &key.workspace_id,
+        upload_size,
+      )
+      .await?;
+    },
+    // The upload was already completed, which completing it again acknowledges
+    Err(err) if err.is_record_not_found() => {},
+    Err(err) => return Err(AppResponseError::from(err).into()),
+  }
   state
     .bucket_storage
     .complete_upload(key, req)
@@ -193,6 +400,13 @@ async fn put_blob_handler(
   let path = path.into_inner();
   let content_length = content_length.into_inner().into_inner();
   let content_type = content_type.into_inner().to_string();
+  check_workspace_storage_quota(
+    &state.pg_pool,
+    &state.config.storage_quota,
+    &path.workspace_id,
+    content_length as i64,
+  )
+  .await?;
   let content = {
     let mut payload_reader = payload_to_async_read(payload);
     let mut content = vec![0; content_length];
@@ -269,6 +483,9 @@ async fn delete_blob_v1_handler(
   path: web::Path<BlobPathV1>,
 ) -> Result<JsonAppResponse<()>> {
   let path = path.into_inner();
+  if let Err(err) = delete_thumbnails(&state.bucket_client, &path).await {
+    warn!("Failed to delete the thumbnails of {:?}: {}", path, err);
+  }
   state
     .bucket_storage
     .delete_blob(path)
@@ -278,6 +495,58 @@ async fn delete_blob_v1_handler(
   Ok(AppResponse::Ok().into())
 }
 
+#[derive(Deserialize, Debug)]
+struct ThumbnailQuery {
+  size: Option<u32>,
+}
+
+/// Returns a thumbnail of the image that fits in a square of the requested size, so that the
+/// clients don't download the full image for a preview.
+#[instrument(level = "debug", skip(state, req), err)]
+async fn get_thumbnail_v1_handler(
+  state: Data<AppState>,
+  path: web::Path<BlobPathV1>,
+  query: web::Query<ThumbnailQuery>,
+  req: HttpRequest,
+) -> Result<HttpResponse<BoxBody>> {
+  let path = path.into_inner();
+  let size = thumbnail_size(query.into_inner().size.unwrap_or(DEFAULT_THUMBNAIL_SIZE));
+
+  let metadata = match state
+    .bucket_storage
+    .get_blob_metadata(&path.workspace_id, &path.meta_key())
+    .await
+  {
+    Ok(metadata) => metadata,
+    Err(err) if err.is_record_not_found() => return Ok(HttpResponse::NotFound().finish()),
+    Err(err) => return Ok(AppResponseError::from(err).error_response()),
+  };
+  if let Some(modified_since) = req
+    .headers()
+    .get(IF_MODIFIED_SINCE)
+    .and_then(|h| h.to_str().ok())
+    .and_then(|s| DateTime::parse_from_rfc2822(s).ok())
+  {
+    if metadata.modified_at.naive_utc() <= modified_since.naive_utc() {
+      return Ok(HttpResponse::NotModified().finish());
+    }
+  }
+
+  match get_or_create_thumbnail(&state.bucket_storage, &state.bucket_client, &path, size).await {
+    Ok(thumbnail) => Ok(
+      HttpResponse::Ok()
+        .append_header((ETAG, format!("{}_{}", path.e_tag(), size)))
+        .append_header((CONTENT_TYPE, thumbnail.content_type))
+        .append_header((LAST_MODIFIED, metadata.modified_at.to_rfc2822()))
+        .append_header((CONTENT_LENGTH, thumbnail.data.len()))
+        .append_header((CACHE_CONTROL, "public, immutable, max-age=31536000"))
+        .body(thumbnail.data),
+    ),
+    Err(err) if err.is_record_not_found() => Ok(HttpResponse::NotFound().finish()),
+    Err(err) => Ok(AppResponseError::from(err).error_response()),
+  }
+}
+
 async fn get_blob_by_object_key(
   state: Data<AppState>,
   key: &impl BlobKey,
@@ -462,13 +731,46 @@ impl BlobKey for BlobPathV0 {
 }
 
 /// Use [BlobPathV1] when put/get object by multiple upload parts
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct BlobPathV1 {
   pub workspace_id: Uuid,
   pub parent_dir: String,
   pub file_id: String,
 }
 
+impl BlobPathV1 {
+  /// Makes sure that the object key stays under the directory of the workspace.
+  pub fn validate(&self) -> Result<(), AppError> {
+    validate_blob_path(&self.parent_dir, &self.file_id)
+  }
+}
+
+fn validate_blob_path(parent_dir: &str, file_id: &str) -> Result<(), AppError> {
+  if parent_dir.is_empty() {
+    return Err(AppError::InvalidRequest("parent_dir is empty".to_string()));
+  }
+  if file_id.is_empty() {
+    return Err(AppError::InvalidRequest("file_id is empty".to_string()));
+  }
+  let is_invalid_segment = |segment: &str| segment.is_empty() || segment == "." || segment == "..";
+  if parent_dir.contains('\\')
+    || parent_dir.split('/').any(is_invalid_segment)
+    || parent_dir.split('/').next() == Some(THUMBNAIL_DIR)
+  {
+    return Err(AppError::InvalidRequest(format!(
+      "parent_dir {} is not a valid directory",
+      parent_dir
+    )));
+  }
+  if file_id.contains(['/', '\\']) || is_invalid_segment(file_id) {
+    return Err(AppError::InvalidRequest(format!(
+      "file_id {} is not a valid file name",
+      file_id
+    )));
+  }
+  Ok(())
+}
+
 impl BlobKey for BlobPathV1 {
   fn workspace_id(&self) -> &Uuid {
     &self.workspace_id
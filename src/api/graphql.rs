@@ -0,0 +1,33 @@
+use actix_web::web::{Data, Json};
+use actix_web::{web, Scope};
+
+use authentication::jwt::Authorization;
+
+use crate::biz::workspace::graphql::{build_workspace_schema, GraphQLRequester, WorkspaceSchema};
+use crate::state::AppState;
+
+pub fn graphql_scope() -> Scope {
+  web::scope("/api/graphql")
+    .app_data(Data::new(build_workspace_schema()))
+    .service(web::resource("").route(web::post().to(graphql_handler)))
+}
+
+/// Executes a query of the [WorkspaceSchema]. As in any GraphQL server, the errors of the query
+/// are reported in the `errors` field of the response rather than with the status code.
+#[tracing::instrument(skip_all, err)]
+async fn graphql_handler(
+  auth: Authorization,
+  state: Data<AppState>,
+  schema: Data<WorkspaceSchema>,
+  request: Json<async_graphql::Request>,
+) -> actix_web::Result<Json<async_graphql::Response>> {
+  let user_uuid = auth.uuid()?;
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let request = request.into_inner().data(GraphQLRequester {
+    state: state.get_ref().clone(),
+    uid,
+    user_uuid,
+    claims: auth.claims,
+  });
+  Ok(Json(schema.execute(request).await))
+}
@@ -0,0 +1,273 @@
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::time::Duration;
+
+use access_control::act::Action;
+use access_control::collab::CollabAccessControl;
+use app_error::AppError;
+use async_stream::stream;
+use collab_entity::CollabType;
+use collab_stream::client::CollabRedisStream;
+use collab_stream::model::CollabUpdateEvent;
+use database::collab::{CollabStorage, GetCollabOrigin};
+use database_entity::dto::{CollabParams, QueryCollab, QueryCollabParams};
+use futures_util::Stream;
+use shared_entity::dto::workspace_dto::{ApiKeyScope, FolderView};
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+use tonic_proto::integration::integration_server::{Integration, IntegrationServer};
+use tonic_proto::integration::{
+  CollabPb, CollabUpdatePb, FolderViewPb, GetCollabRequestPb, GetFolderRequestPb,
+  SubscribeCollabUpdatesRequestPb, UpdateCollabRequestPb, UpdateCollabResponsePb,
+};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::biz;
+use crate::biz::collab::folder_view::FolderViewFilter;
+use crate::biz::workspace::service_account::authenticate_api_key;
+use crate::state::AppState;
+
+/// How often the update stream of a subscribed collab is read.
+const COLLAB_UPDATE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Serves the [Integration] service, defined in `libs/tonic-proto/proto/integration.proto`, next
+/// to the REST API.
+pub fn spawn_grpc_server(state: AppState, address: SocketAddr) {
+  tokio::spawn(async move {
+    info!("gRPC server started at {}", address);
+    if let Err(err) = Server::builder()
+      .add_service(IntegrationServer::new(IntegrationImpl { state }))
+      .serve(address)
+      .await
+    {
+      error!("gRPC server stopped: {}", err);
+    }
+  });
+}
+
+pub struct IntegrationImpl {
+  state: AppState,
+}
+
+impl IntegrationImpl {
+  /// Authenticates the api key of the request, and returns the uid of its service account. The
+  /// key must belong to the given workspace, and its scope must allow writing when `is_write`.
+  async fn authenticate<T>(
+    &self,
+    request: &Request<T>,
+    workspace_id: &str,
+    is_write: bool,
+  ) -> Result<i64, Status> {
+    let api_key = request
+      .metadata()
+      .get("authorization")
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| value.strip_prefix("Bearer "))
+      .ok_or_else(|| Status::unauthenticated("Missing api key"))?;
+    let (principal, scope) = authenticate_api_key(&self.state.pg_pool, api_key)
+      .await
+      .map_err(status_from_app_error)?;
+    let workspace_id = Uuid::parse_str(workspace_id)
+      .map_err(|err| Status::invalid_argument(format!("Invalid workspace id: {}", err)))?;
+    let is_allowed = match scope {
+      ApiKeyScope::Full => true,
+      ApiKeyScope::ReadOnly | ApiKeyScope::PublishOnly => !is_write,
+      ApiKeyScope::Scim => false,
+    };
+    if principal.workspace_id != workspace_id || !is_allowed {
+      return Err(Status::permission_denied(format!(
+        "The {} api key is not allowed to {} workspace {}",
+        scope.as_str(),
+        if is_write { "write to" } else { "read" },
+        workspace_id
+      )));
+    }
+    self
+      .state
+      .user_cache
+      .get_user_uid(&principal.user_uuid)
+      .await
+      .map_err(status_from_app_error)
+  }
+}
+
+#[tonic::async_trait]
+impl Integration for IntegrationImpl {
+  async fn get_collab(
+    &self,
+    request: Request<GetCollabRequestPb>,
+  ) -> Result<Response<CollabPb>, Status> {
+    let uid = self
+      .authenticate(&request, &request.get_ref().workspace_id, false)
+      .await?;
+    let request = request.into_inner();
+    let params = QueryCollabParams {
+      workspace_id: request.workspace_id,
+      inner: QueryCollab {
+        object_id: request.object_id.clone(),
+        collab_type: CollabType::from(request.collab_type),
+      },
+    };
+    let encoded_collab = self
+      .state
+      .collab_access_control_storage
+      .get_encode_collab(GetCollabOrigin::User { uid }, params, true)
+      .await
+      .map_err(status_from_app_error)?;
+    let encoded_collab_v1 = encoded_collab
+      .encode_to_bytes()
+      .map_err(|err| Status::internal(err.to_string()))?;
+    Ok(Response::new(CollabPb {
+      object_id: request.object_id,
+      encoded_collab_v1,
+    }))
+  }
+
+  async fn update_collab(
+    &self,
+    request: Request<UpdateCollabRequestPb>,
+  ) -> Result<Response<UpdateCollabResponsePb>, Status> {
+    let uid = self
+      .authenticate(&request, &request.get_ref().workspace_id, true)
+      .await?;
+    let request = request.into_inner();
+    let params = CollabParams {
+      object_id: request.object_id,
+      encoded_collab_v1: request.encoded_collab_v1.into(),
+      collab_type: CollabType::from(request.collab_type),
+      embeddings: None,
+    };
+    self
+      .state
+      .collab_access_control_storage
+      .insert_or_update_collab(&request.workspace_id, &uid, params, false)
+      .await
+      .map_err(status_from_app_error)?;
+    Ok(Response::new(UpdateCollabResponsePb {}))
+  }
+
+  async fn get_folder(
+    &self,
+    request: Request<GetFolderRequestPb>,
+  ) -> Result<Response<FolderViewPb>, Status> {
+    let uid = self
+      .authenticate(&request, &request.get_ref().workspace_id, false)
+      .await?;
+    let request = request.into_inner();
+    let workspace_id = Uuid::parse_str(&request.workspace_id)
+      .map_err(|err| Status::invalid_argument(err.to_string()))?;
+    let root_view_id = if request.root_view_id.is_empty() {
+      workspace_id.to_string()
+    } else {
+      request.root_view_id
+    };
+    let depth = if request.depth == 0 { 1 } else { request.depth };
+    let folder_view = biz::collab::ops::get_user_workspace_structure(
+      self.state.collab_access_control_storage.clone(),
+      &self.state.pg_pool,
+      uid,
+      workspace_id,
+      depth,
+      &root_view_id,
+      &FolderViewFilter::default(),
+      Some(&self.state.collab_access_control),
+    )
+    .await
+    .map_err(status_from_app_error)?;
+    Ok(Response::new(folder_view_to_pb(folder_view)))
+  }
+
+  type SubscribeCollabUpdatesStream =
+    Pin<Box<dyn Stream<Item = Result<CollabUpdatePb, Status>> + Send>>;
+
+  async fn subscribe_collab_updates(
+    &self,
+    request: Request<SubscribeCollabUpdatesRequestPb>,
+  ) -> Result<Response<Self::SubscribeCollabUpdatesStream>, Status> {
+    let uid = self
+      .authenticate(&request, &request.get_ref().workspace_id, false)
+      .await?;
+    let request = request.into_inner();
+    let can_read = self
+      .state
+      .collab_access_control
+      .enforce_action(
+        &request.workspace_id,
+        &uid,
+        &request.object_id,
+        Action::Read,
+      )
+      .await
+      .map_err(status_from_app_error)?;
+    if !can_read {
+      return Err(Status::permission_denied(format!(
+        "Not allowed to read collab {}",
+        request.object_id
+      )));
+    }
+
+    let mut reader =
+      CollabRedisStream::new_with_connection_manager(self.state.redis_connection_manager.clone())
+        .collab_update_reader(&request.workspace_id, &request.object_id);
+    let object_id = request.object_id;
+    let updates = stream! {
+      let mut interval = tokio::time::interval(COLLAB_UPDATE_POLL_INTERVAL);
+      loop {
+        interval.tick().await;
+        let messages = match reader.read_updates().await {
+          Ok(messages) => messages,
+          Err(err) => {
+            warn!("failed to read the updates of {}: {}", object_id, err);
+            yield Err(Status::unavailable(format!(
+              "Failed to read the updates of collab {}",
+              object_id
+            )));
+            break;
+          },
+        };
+        for message in messages {
+          match CollabUpdateEvent::decode(&message.data) {
+            Ok(CollabUpdateEvent::UpdateV1 { encode_update }) => {
+              yield Ok(CollabUpdatePb {
+                object_id: object_id.clone(),
+                update_v1: encode_update,
+                message_id: message.id.to_string(),
+              });
+            },
+            Err(err) => warn!("failed to decode an update of {}: {}", object_id, err),
+          }
+        }
+      }
+    };
+    Ok(Response::new(Box::pin(updates)))
+  }
+}
+
+fn folder_view_to_pb(view: FolderView) -> FolderViewPb {
+  FolderViewPb {
+    view_id: view.view_id,
+    name: view.name,
+    icon: view.icon.map(|icon| icon.value).unwrap_or_default(),
+    is_space: view.is_space,
+    is_private: view.is_private,
+    is_published: view.is_published,
+    layout: view.layout as i32,
+    created_at: view.created_at.timestamp(),
+    last_edited_time: view.last_edited_time.timestamp(),
+    children: view.children.into_iter().map(folder_view_to_pb).collect(),
+  }
+}
+
+fn status_from_app_error(err: AppError) -> Status {
+  match err {
+    AppError::UserUnAuthorized(msg) => Status::unauthenticated(msg),
+    AppError::NotEnoughPermissions { .. } => Status::permission_denied(err.to_string()),
+    AppError::RecordNotFound(msg) => Status::not_found(msg),
+    AppError::InvalidRequest(msg) => Status::invalid_argument(msg),
+    AppError::StorageQuotaExceeded(msg)
+    | AppError::AIResponseLimitExceeded(msg)
+    | AppError::TooManyRequests(msg) => Status::resource_exhausted(msg),
+    err => Status::internal(err.to_string()),
+  }
+}
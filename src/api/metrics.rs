@@ -207,3 +207,53 @@ impl PublishedCollabMetrics {
     self.failure_read_published_collab_count.inc_by(count);
   }
 }
+
+#[derive(Clone)]
+pub struct CollabCompactionMetrics {
+  compacted_collab_count: Gauge,
+  reclaimed_bytes: Gauge,
+  failed_compaction_count: Gauge,
+}
+
+impl CollabCompactionMetrics {
+  fn init() -> Self {
+    Self {
+      compacted_collab_count: Default::default(),
+      reclaimed_bytes: Default::default(),
+      failed_compaction_count: Default::default(),
+    }
+  }
+
+  pub fn register(registry: &mut Registry) -> Self {
+    let metrics = Self::init();
+    let compaction_registry = registry.sub_registry_with_prefix("collab_compaction");
+    compaction_registry.register(
+      "compacted_count",
+      "collabs whose encoded state was compacted",
+      metrics.compacted_collab_count.clone(),
+    );
+    compaction_registry.register(
+      "reclaimed_bytes",
+      "bytes reclaimed by compacting the encoded state of collabs",
+      metrics.reclaimed_bytes.clone(),
+    );
+    compaction_registry.register(
+      "failure_count",
+      "failed to compact collab",
+      metrics.failed_compaction_count.clone(),
+    );
+    metrics
+  }
+
+  pub fn incr_compacted_count(&self, count: i64) {
+    self.compacted_collab_count.inc_by(count);
+  }
+
+  pub fn incr_reclaimed_bytes(&self, bytes: i64) {
+    self.reclaimed_bytes.inc_by(bytes);
+  }
+
+  pub fn incr_failure_count(&self, count: i64) {
+    self.failed_compaction_count.inc_by(count);
+  }
+}
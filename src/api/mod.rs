@@ -1,9 +1,15 @@
+pub mod admin;
 pub mod ai;
 pub mod chat;
 pub mod file_storage;
+pub mod graphql;
+pub mod grpc;
 
 pub mod history;
 pub mod metrics;
+pub mod notification;
+pub mod openapi;
+pub mod scim;
 pub mod search;
 pub mod template;
 pub mod user;
@@ -0,0 +1,65 @@
+use actix_web::web::{Data, Json};
+use actix_web::{web, HttpResponse, Result, Scope};
+use authentication::jwt::UserUuid;
+use shared_entity::dto::notification_dto::{
+  NotificationSettings, Notifications, UnsubscribeNotificationQuery,
+};
+use shared_entity::response::{AppResponse, JsonAppResponse};
+
+use crate::biz::notification::ops;
+use crate::state::AppState;
+
+pub fn notification_scope() -> Scope {
+  web::scope("/api/notification")
+    .service(web::resource("").route(web::get().to(list_notifications_handler)))
+    .service(
+      web::resource("/settings")
+        .route(web::get().to(get_notification_settings_handler))
+        .route(web::put().to(update_notification_settings_handler)),
+    )
+    // Reached from the link of the emails, without signing in
+    .service(web::resource("/unsubscribe").route(web::get().to(unsubscribe_handler)))
+}
+
+async fn list_notifications_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<Notifications>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let notifications = ops::get_notifications(&state.pg_pool, uid).await?;
+  Ok(Json(
+    AppResponse::Ok().with_data(Notifications { notifications }),
+  ))
+}
+
+async fn get_notification_settings_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<NotificationSettings>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let settings = ops::get_notification_settings(&state.pg_pool, uid).await?;
+  Ok(Json(AppResponse::Ok().with_data(settings)))
+}
+
+async fn update_notification_settings_handler(
+  user_uuid: UserUuid,
+  payload: Json<NotificationSettings>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<NotificationSettings>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let settings =
+    ops::update_notification_settings(&state.pg_pool, uid, payload.into_inner()).await?;
+  Ok(Json(AppResponse::Ok().with_data(settings)))
+}
+
+async fn unsubscribe_handler(
+  query: web::Query<UnsubscribeNotificationQuery>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  ops::unsubscribe_notification_emails(&state.pg_pool, &query.token).await?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("text/html; charset=utf-8")
+      .body("<p>You will no longer receive notification emails from AppFlowy.</p>"),
+  )
+}
@@ -0,0 +1,124 @@
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::SwaggerUi;
+
+use database_entity::dto::{
+  AFWorkspace, AFWorkspaceMember, AFWorkspaceSettings, AFWorkspaceSettingsChange, WorkspaceUsage,
+};
+use shared_entity::dto::workspace_dto::{
+  CreateWorkspaceParam, FolderView, IconType, PatchWorkspaceParam, ViewIcon, ViewLayout,
+  ViewPermissions,
+};
+
+use crate::api::workspace;
+
+pub const OPENAPI_JSON_PATH: &str = "/api/openapi.json";
+
+/// The OpenAPI 3 document of the REST API, generated from the `#[utoipa::path]` annotations of
+/// the handlers. Every handler annotated with `#[utoipa::path]` must be listed in `paths`.
+///
+/// Successful responses are wrapped in the `data` field of the `AppResponse` envelope, next to
+/// `code` and `message`. The documented bodies are the content of that field.
+#[derive(OpenApi)]
+#[openapi(
+  info(title = "AppFlowy Cloud", description = "The REST API of AppFlowy Cloud"),
+  paths(
+    workspace::list_workspace_handler,
+    workspace::create_workspace_handler,
+    workspace::patch_workspace_handler,
+    workspace::delete_workspace_handler,
+    workspace::get_workspace_settings_handler,
+    workspace::post_workspace_settings_handler,
+    workspace::get_workspace_members_handler,
+    workspace::get_workspace_usage_handler,
+    workspace::get_workspace_folder_handler,
+  ),
+  components(schemas(
+    AFWorkspace,
+    AFWorkspaceMember,
+    AFWorkspaceSettings,
+    AFWorkspaceSettingsChange,
+    WorkspaceUsage,
+    CreateWorkspaceParam,
+    PatchWorkspaceParam,
+    FolderView,
+    ViewIcon,
+    IconType,
+    ViewLayout,
+    ViewPermissions,
+  )),
+  modifiers(&BearerAuth),
+  tags((name = "workspace", description = "Workspaces, their members and their folder"))
+)]
+pub struct ApiDoc;
+
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+  fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+    let components = openapi.components.get_or_insert_with(Default::default);
+    components.add_security_scheme(
+      "bearer_auth",
+      SecurityScheme::Http(
+        HttpBuilder::new()
+          .scheme(HttpAuthScheme::Bearer)
+          .bearer_format("JWT")
+          .description(Some(
+            "The access token of the user, or an `afk_` api key of a service account",
+          ))
+          .build(),
+      ),
+    );
+  }
+}
+
+/// Serves the OpenAPI document at [OPENAPI_JSON_PATH], and the Swagger UI at
+/// `/swagger-ui/index.html`.
+pub fn openapi_service() -> SwaggerUi {
+  SwaggerUi::new("/swagger-ui/{_:.*}").url(OPENAPI_JSON_PATH, ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::http::StatusCode;
+  use actix_web::{test, App};
+  use utoipa::openapi::PathItemType;
+  use utoipa::OpenApi;
+
+  use super::ApiDoc;
+  use crate::api::workspace::workspace_scope;
+
+  /// A documented route that the server does not serve answers with 404, or 405 when only its
+  /// method is wrong. Any other status, such as 401 without credentials, means it is routed.
+  #[actix_rt::test]
+  async fn documented_paths_are_routed() {
+    let app = test::init_service(App::new().service(workspace_scope())).await;
+    let workspace_id = uuid::Uuid::new_v4().to_string();
+    for (path, item) in ApiDoc::openapi().paths.paths {
+      let uri = path.replace("{workspace_id}", &workspace_id);
+      for method in item.operations.keys() {
+        let method = match method {
+          PathItemType::Get => "GET",
+          PathItemType::Post => "POST",
+          PathItemType::Put => "PUT",
+          PathItemType::Patch => "PATCH",
+          PathItemType::Delete => "DELETE",
+          other => panic!("{:?} {} is not expected to be documented", other, path),
+        };
+        let request = test::TestRequest::default()
+          .method(method.parse().unwrap())
+          .uri(&uri)
+          .to_request();
+        let status = test::call_service(&app, request).await.status();
+        assert_ne!(status, StatusCode::NOT_FOUND, "{} {}", method, path);
+        assert_ne!(
+          status,
+          StatusCode::METHOD_NOT_ALLOWED,
+          "{} {}",
+          method,
+          path
+        );
+      }
+    }
+  }
+}
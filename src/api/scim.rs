@@ -0,0 +1,253 @@
+use actix_web::http::StatusCode;
+use actix_web::web::{Data, Json, Query};
+use actix_web::{web, HttpResponse, ResponseError, Scope};
+use app_error::AppError;
+use authentication::jwt::ApiKeyPrincipal;
+use serde::Serialize;
+use shared_entity::dto::scim_dto::{
+  ScimError, ScimGroup, ScimListQuery, ScimPatchRequest, ScimUser, SCIM_ERROR_SCHEMA,
+};
+use uuid::Uuid;
+
+use crate::biz::workspace::scim::{
+  create_scim_group, create_scim_user, delete_scim_group_from_workspace,
+  delete_scim_user_from_workspace, get_scim_group, get_scim_user, list_scim_groups,
+  list_scim_users, patch_scim_group, patch_scim_user, replace_scim_group, replace_scim_user,
+};
+use crate::state::AppState;
+
+const SCIM_CONTENT_TYPE: &str = "application/scim+json";
+
+/// The SCIM 2.0 endpoints an identity provider provisions the users and the groups of a
+/// workspace through. The requests are authenticated with the API key of a service account of
+/// the `scim` scope, and the workspace is the one of the service account.
+pub fn scim_scope() -> Scope {
+  web::scope("/scim/v2")
+    .service(
+      web::resource("/Users")
+        .route(web::get().to(list_users_handler))
+        .route(web::post().to(create_user_handler)),
+    )
+    .service(
+      web::resource("/Users/{user_id}")
+        .route(web::get().to(get_user_handler))
+        .route(web::put().to(replace_user_handler))
+        .route(web::patch().to(patch_user_handler))
+        .route(web::delete().to(delete_user_handler)),
+    )
+    .service(
+      web::resource("/Groups")
+        .route(web::get().to(list_groups_handler))
+        .route(web::post().to(create_group_handler)),
+    )
+    .service(
+      web::resource("/Groups/{group_id}")
+        .route(web::get().to(get_group_handler))
+        .route(web::put().to(replace_group_handler))
+        .route(web::patch().to(patch_group_handler))
+        .route(web::delete().to(delete_group_handler)),
+    )
+}
+
+async fn list_users_handler(
+  principal: ApiKeyPrincipal,
+  query: Query<ScimListQuery>,
+  state: Data<AppState>,
+) -> Result<HttpResponse, ScimApiError> {
+  let query = query.into_inner();
+  let users = list_scim_users(
+    &state,
+    &principal.workspace_id,
+    query.filter.as_deref(),
+    query.start_index,
+    query.count,
+  )
+  .await?;
+  Ok(scim_response(StatusCode::OK, &users))
+}
+
+async fn create_user_handler(
+  principal: ApiKeyPrincipal,
+  payload: Json<ScimUser>,
+  state: Data<AppState>,
+) -> Result<HttpResponse, ScimApiError> {
+  let user = create_scim_user(&state, &principal.workspace_id, payload.into_inner()).await?;
+  Ok(scim_response(StatusCode::CREATED, &user))
+}
+
+async fn get_user_handler(
+  principal: ApiKeyPrincipal,
+  path: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<HttpResponse, ScimApiError> {
+  let user = get_scim_user(&state, &principal.workspace_id, &path.into_inner()).await?;
+  Ok(scim_response(StatusCode::OK, &user))
+}
+
+async fn replace_user_handler(
+  principal: ApiKeyPrincipal,
+  path: web::Path<Uuid>,
+  payload: Json<ScimUser>,
+  state: Data<AppState>,
+) -> Result<HttpResponse, ScimApiError> {
+  let user = replace_scim_user(
+    &state,
+    &principal.workspace_id,
+    &path.into_inner(),
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(scim_response(StatusCode::OK, &user))
+}
+
+async fn patch_user_handler(
+  principal: ApiKeyPrincipal,
+  path: web::Path<Uuid>,
+  payload: Json<ScimPatchRequest>,
+  state: Data<AppState>,
+) -> Result<HttpResponse, ScimApiError> {
+  let user = patch_scim_user(
+    &state,
+    &principal.workspace_id,
+    &path.into_inner(),
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(scim_response(StatusCode::OK, &user))
+}
+
+async fn delete_user_handler(
+  principal: ApiKeyPrincipal,
+  path: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<HttpResponse, ScimApiError> {
+  delete_scim_user_from_workspace(&state, &principal.workspace_id, &path.into_inner()).await?;
+  Ok(HttpResponse::NoContent().finish())
+}
+
+async fn list_groups_handler(
+  principal: ApiKeyPrincipal,
+  query: Query<ScimListQuery>,
+  state: Data<AppState>,
+) -> Result<HttpResponse, ScimApiError> {
+  let query = query.into_inner();
+  let groups = list_scim_groups(
+    &state,
+    &principal.workspace_id,
+    query.filter.as_deref(),
+    query.start_index,
+    query.count,
+  )
+  .await?;
+  Ok(scim_response(StatusCode::OK, &groups))
+}
+
+async fn create_group_handler(
+  principal: ApiKeyPrincipal,
+  payload: Json<ScimGroup>,
+  state: Data<AppState>,
+) -> Result<HttpResponse, ScimApiError> {
+  let group = create_scim_group(&state, &principal.workspace_id, payload.into_inner()).await?;
+  Ok(scim_response(StatusCode::CREATED, &group))
+}
+
+async fn get_group_handler(
+  principal: ApiKeyPrincipal,
+  path: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<HttpResponse, ScimApiError> {
+  let group = get_scim_group(&state, &principal.workspace_id, &path.into_inner()).await?;
+  Ok(scim_response(StatusCode::OK, &group))
+}
+
+async fn replace_group_handler(
+  principal: ApiKeyPrincipal,
+  path: web::Path<Uuid>,
+  payload: Json<ScimGroup>,
+  state: Data<AppState>,
+) -> Result<HttpResponse, ScimApiError> {
+  let group = replace_scim_group(
+    &state,
+    &principal.workspace_id,
+    &path.into_inner(),
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(scim_response(StatusCode::OK, &group))
+}
+
+async fn patch_group_handler(
+  principal: ApiKeyPrincipal,
+  path: web::Path<Uuid>,
+  payload: Json<ScimPatchRequest>,
+  state: Data<AppState>,
+) -> Result<HttpResponse, ScimApiError> {
+  let group = patch_scim_group(
+    &state,
+    &principal.workspace_id,
+    &path.into_inner(),
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(scim_response(StatusCode::OK, &group))
+}
+
+async fn delete_group_handler(
+  principal: ApiKeyPrincipal,
+  path: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<HttpResponse, ScimApiError> {
+  delete_scim_group_from_workspace(&state, &principal.workspace_id, &path.into_inner()).await?;
+  Ok(HttpResponse::NoContent().finish())
+}
+
+fn scim_response<T: Serialize>(status: StatusCode, body: &T) -> HttpResponse {
+  HttpResponse::build(status)
+    .content_type(SCIM_CONTENT_TYPE)
+    .json(body)
+}
+
+/// Unlike [AppError], which is always replied with a 200, the SCIM clients expect the http status
+/// of the error and a SCIM error body.
+#[derive(Debug)]
+pub struct ScimApiError(AppError);
+
+impl From<AppError> for ScimApiError {
+  fn from(err: AppError) -> Self {
+    Self(err)
+  }
+}
+
+impl std::fmt::Display for ScimApiError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    self.0.fmt(f)
+  }
+}
+
+impl ResponseError for ScimApiError {
+  fn status_code(&self) -> StatusCode {
+    match &self.0 {
+      AppError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+      AppError::RecordNotFound(_) => StatusCode::NOT_FOUND,
+      AppError::RecordAlreadyExists(_) => StatusCode::CONFLICT,
+      AppError::NotEnoughPermissions { .. } => StatusCode::FORBIDDEN,
+      _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+  }
+
+  fn error_response(&self) -> HttpResponse {
+    let status = self.status_code();
+    let scim_type = match &self.0 {
+      AppError::InvalidRequest(_) => Some("invalidValue".to_string()),
+      AppError::RecordAlreadyExists(_) => Some("uniqueness".to_string()),
+      _ => None,
+    };
+    let error = ScimError {
+      schemas: vec![SCIM_ERROR_SCHEMA.to_string()],
+      status: status.as_u16().to_string(),
+      scim_type,
+      detail: self.0.to_string(),
+    };
+    scim_response(status, &error)
+  }
+}
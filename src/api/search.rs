@@ -25,9 +25,11 @@ async fn document_search(
   let user_uuid = auth.uuid()?;
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   let metrics = &*state.metrics.request_metrics;
+  let embedder = state.indexer_provider.embedder();
   let resp = search_document(
     &state.pg_pool,
-    &state.ai_client,
+    &state.collab_access_control,
+    embedder.as_ref(),
     uid,
     workspace_id,
     request,
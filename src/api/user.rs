@@ -1,22 +1,50 @@
+use crate::api::workspace::zip_archive_response;
 use crate::biz::user::user_delete::delete_user;
+use crate::biz::user::user_deletion::{
+  cancel_account_deletion, get_account_deletion, request_account_deletion,
+};
+use crate::biz::user::user_export::{
+  get_user_data_export_archive, get_user_data_export_task, start_user_data_export,
+};
 use crate::biz::user::user_info::{get_profile, get_user_workspace_info, update_user};
-use crate::biz::user::user_verify::verify_token;
+use crate::biz::user::user_verify::verify_sign_in;
+use crate::biz::workspace::sso::sso_sign_in_url;
 use crate::state::AppState;
 use actix_web::web::{Data, Json};
-use actix_web::Result;
 use actix_web::{web, Scope};
+use actix_web::{HttpResponse, Result};
 use authentication::jwt::{Authorization, UserUuid};
 use database_entity::dto::{AFUserProfile, AFUserWorkspaceInfo};
-use shared_entity::dto::auth_dto::{DeleteUserQuery, SignInTokenResponse, UpdateUserParams};
+use secrecy::ExposeSecret;
+use shared_entity::dto::auth_dto::{
+  AccountDeletion, DeleteUserQuery, RequestAccountDeletionParams, SignInTokenResponse,
+  SsoSignInParams, SsoSignInUrl, UpdateUserParams,
+};
+use shared_entity::dto::workspace_dto::{UserDataExportDownloadQuery, UserDataExportTask};
 use shared_entity::response::AppResponseError;
 use shared_entity::response::{AppResponse, JsonAppResponse};
 
 pub fn user_scope() -> Scope {
   web::scope("/api/user")
     .service(web::resource("/verify/{access_token}").route(web::get().to(verify_user_handler)))
+    .service(web::resource("/sso").route(web::post().to(sso_sign_in_url_handler)))
     .service(web::resource("/update").route(web::post().to(update_user_handler)))
     .service(web::resource("/profile").route(web::get().to(get_user_profile_handler)))
     .service(web::resource("/workspace").route(web::get().to(get_user_workspace_info_handler)))
+    .service(web::resource("/export").route(web::post().to(post_user_data_export_handler)))
+    .service(
+      web::resource("/export/{task_id}").route(web::get().to(get_user_data_export_task_handler)),
+    )
+    .service(
+      web::resource("/export/{task_id}/download")
+        .route(web::get().to(get_user_data_export_download_handler)),
+    )
+    .service(
+      web::resource("/deletion")
+        .route(web::get().to(get_account_deletion_handler))
+        .route(web::post().to(request_account_deletion_handler))
+        .route(web::delete().to(cancel_account_deletion_handler)),
+    )
     .service(web::resource("").route(web::delete().to(delete_user_handler)))
 }
 
@@ -26,13 +54,24 @@ async fn verify_user_handler(
   state: Data<AppState>,
 ) -> Result<JsonAppResponse<SignInTokenResponse>> {
   let access_token = path.into_inner();
-  let is_new = verify_token(&access_token, state.as_ref())
+  let is_new = verify_sign_in(&access_token, state.as_ref())
     .await
     .map_err(AppResponseError::from)?;
   let resp = SignInTokenResponse { is_new };
   Ok(AppResponse::Ok().with_data(resp).into())
 }
 
+#[tracing::instrument(skip(state, payload), err)]
+async fn sso_sign_in_url_handler(
+  payload: Json<SsoSignInParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<SsoSignInUrl>> {
+  let url = sso_sign_in_url(state.as_ref(), payload.into_inner())
+    .await
+    .map_err(AppResponseError::from)?;
+  Ok(AppResponse::Ok().with_data(url).into())
+}
+
 #[tracing::instrument(skip(state), err)]
 async fn get_user_profile_handler(
   uuid: UserUuid,
@@ -89,3 +128,103 @@ async fn delete_user_handler(
   .await?;
   Ok(AppResponse::Ok().into())
 }
+
+#[tracing::instrument(skip(state), err)]
+async fn post_user_data_export_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<UserDataExportTask>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let task = start_user_data_export(
+    &state.pg_pool,
+    state.collab_access_control_storage.clone(),
+    state.bucket_client.clone(),
+    state
+      .config
+      .application
+      .export_signing_secret
+      .expose_secret(),
+    uid,
+    *uuid,
+  )
+  .await?;
+  Ok(AppResponse::Ok().with_data(task).into())
+}
+
+#[tracing::instrument(skip(state), err)]
+async fn get_user_data_export_task_handler(
+  uuid: UserUuid,
+  task_id: web::Path<uuid::Uuid>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<UserDataExportTask>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let task = get_user_data_export_task(
+    &state.pg_pool,
+    state
+      .config
+      .application
+      .export_signing_secret
+      .expose_secret(),
+    uid,
+    &task_id,
+  )
+  .await?;
+  Ok(AppResponse::Ok().with_data(task).into())
+}
+
+/// The archive is downloaded with the signed link of the task, without authentication, so that
+/// the link can be opened in a browser.
+#[tracing::instrument(skip(state, query), err)]
+async fn get_user_data_export_download_handler(
+  task_id: web::Path<uuid::Uuid>,
+  query: web::Query<UserDataExportDownloadQuery>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let archive = get_user_data_export_archive(
+    &state.pg_pool,
+    &state.bucket_client,
+    state
+      .config
+      .application
+      .export_signing_secret
+      .expose_secret(),
+    &task_id,
+    &query,
+  )
+  .await
+  .map_err(AppResponseError::from)?;
+  Ok(zip_archive_response(archive))
+}
+
+/// Mark the account for deletion. Unlike [delete_user_handler], the account can be restored until
+/// the grace period ends.
+#[tracing::instrument(skip(state, payload), err)]
+async fn request_account_deletion_handler(
+  uuid: UserUuid,
+  payload: Json<RequestAccountDeletionParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<AccountDeletion>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let deletion = request_account_deletion(state.as_ref(), uid, &uuid, payload.into_inner()).await?;
+  Ok(AppResponse::Ok().with_data(deletion).into())
+}
+
+#[tracing::instrument(skip(state), err)]
+async fn get_account_deletion_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<AccountDeletion>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let deletion = get_account_deletion(state.as_ref(), uid).await?;
+  Ok(AppResponse::Ok().with_data(deletion).into())
+}
+
+#[tracing::instrument(skip(state), err)]
+async fn cancel_account_deletion_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  cancel_account_deletion(state.as_ref(), uid).await?;
+  Ok(AppResponse::Ok().into())
+}
@@ -1,14 +1,18 @@
+use crate::biz::ai::stream::{forward_ai_stream, sse_stream};
 use crate::domain::compression::{CompressionType, X_COMPRESSION_BUFFER_SIZE, X_COMPRESSION_TYPE};
-use actix_http::header::HeaderMap;
+use actix_http::header::{HeaderMap, ACCEPT, CACHE_CONTROL};
 use actix_web::web::Payload;
 use app_error::AppError;
 
-use actix_web::HttpRequest;
+use actix_web::{HttpRequest, HttpResponse};
 use appflowy_ai_client::dto::AIModel;
 use async_trait::async_trait;
 use byteorder::{ByteOrder, LittleEndian};
+use bytes::Bytes;
 use collab_rt_protocol::validate_encode_collab;
 use database_entity::dto::CollabParams;
+use futures_util::Stream;
+use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
 use tokio_stream::StreamExt;
 
@@ -166,6 +170,32 @@ fn copy_buffer(src: &[u8], dest: &mut [u8]) -> usize {
   bytes_to_copy
 }
 
+/// Returns the ip address of the client. The `X-Forwarded-For` header is only read when the
+/// request comes from one of the `trusted_proxies`, as anyone else can forge it. Its last address
+/// that is not a trusted proxy is the client, the addresses before it were sent by the client.
+pub(crate) fn client_ip(req: &HttpRequest, trusted_proxies: &[IpAddr]) -> Option<IpAddr> {
+  let peer_ip = req.peer_addr()?.ip();
+  if !trusted_proxies.contains(&peer_ip) {
+    return Some(peer_ip);
+  }
+  let forwarded_ip = req
+    .headers()
+    .get_all("x-forwarded-for")
+    .filter_map(|value| value.to_str().ok())
+    .flat_map(|value| value.split(','))
+    .map(|ip| ip.trim())
+    .filter_map(|ip| {
+      IpAddr::from_str(ip)
+        .ok()
+        .or_else(|| SocketAddr::from_str(ip).ok().map(|addr| addr.ip()))
+    })
+    .collect::<Vec<_>>()
+    .into_iter()
+    .rev()
+    .find(|ip| !trusted_proxies.contains(ip));
+  Some(forwarded_ip.unwrap_or(peer_ip))
+}
+
 #[inline]
 pub(crate) fn ai_model_from_header(req: &HttpRequest) -> AIModel {
   req
@@ -177,3 +207,64 @@ pub(crate) fn ai_model_from_header(req: &HttpRequest) -> AIModel {
     })
     .unwrap_or(AIModel::GPT35)
 }
+
+/// Stream the answer of the AI service to the client. The answer is framed as server-sent events
+/// when the client accepts `text/event-stream`, and sent as it comes from the AI service
+/// otherwise. In both cases, the answer stops being read when the client goes away.
+pub(crate) fn ai_stream_response<S>(req: &HttpRequest, stream: S) -> HttpResponse
+where
+  S: Stream<Item = Result<Bytes, AppError>> + Send + 'static,
+{
+  let accept_sse = req
+    .headers()
+    .get(ACCEPT)
+    .and_then(|header| header.to_str().ok())
+    .map_or(false, |accept| accept.contains("text/event-stream"));
+  let stream = forward_ai_stream(stream);
+  if accept_sse {
+    HttpResponse::Ok()
+      .content_type("text/event-stream")
+      .insert_header((CACHE_CONTROL, "no-cache"))
+      // Ask nginx not to buffer the events
+      .insert_header(("X-Accel-Buffering", "no"))
+      .streaming(sse_stream(stream))
+  } else {
+    HttpResponse::Ok()
+      .content_type("text/event-stream")
+      .streaming(stream)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use actix_web::test::TestRequest;
+
+  #[test]
+  fn client_ip_test() {
+    let proxy = IpAddr::from([10, 0, 0, 1]);
+    let client = IpAddr::from([203, 0, 113, 7]);
+    let request = |peer: IpAddr, forwarded_for: &str| {
+      TestRequest::default()
+        .peer_addr(SocketAddr::new(peer, 443))
+        .insert_header(("x-forwarded-for", forwarded_for))
+        .to_http_request()
+    };
+
+    // The header of a client that is not behind a trusted proxy is ignored
+    assert_eq!(
+      client_ip(&request(client, "198.51.100.1"), &[proxy]),
+      Some(client)
+    );
+    assert_eq!(client_ip(&request(proxy, "198.51.100.1"), &[]), Some(proxy));
+    // The addresses the client prepends to the header are ignored
+    assert_eq!(
+      client_ip(
+        &request(proxy, "198.51.100.1, 203.0.113.7, 10.0.0.1"),
+        &[proxy]
+      ),
+      Some(client)
+    );
+    assert_eq!(client_ip(&request(proxy, "garbage"), &[proxy]), Some(proxy));
+  }
+}
@@ -1,7 +1,7 @@
 use actix_web::web::{Bytes, Payload};
 use actix_web::web::{Data, Json, PayloadConfig};
 use actix_web::{web, Scope};
-use actix_web::{HttpRequest, Result};
+use actix_web::{HttpRequest, HttpResponse, Result};
 use anyhow::{anyhow, Context};
 use bytes::BytesMut;
 use collab::entity::EncodedCollab;
@@ -9,7 +9,9 @@ use collab_entity::CollabType;
 use futures_util::future::try_join_all;
 use prost::Message as ProstMessage;
 use rayon::prelude::*;
+use secrecy::ExposeSecret;
 use sqlx::types::uuid;
+use std::collections::HashMap;
 
 use tokio_stream::StreamExt;
 use tokio_tungstenite::tungstenite::Message;
@@ -20,34 +22,42 @@ use validator::Validate;
 use access_control::collab::CollabAccessControl;
 use app_error::AppError;
 use appflowy_collaborate::actix_ws::entities::ClientStreamMessage;
+use appflowy_collaborate::collab::storage_quota::check_collab_storage_quota;
 use appflowy_collaborate::indexer::IndexerProvider;
 use authentication::jwt::{Authorization, OptionalUserUuid, UserUuid};
 use collab_rt_entity::realtime_proto::HttpRealtimeMessage;
 use collab_rt_entity::RealtimeMessage;
 use collab_rt_protocol::validate_encode_collab;
 use database::collab::{CollabStorage, GetCollabOrigin};
-use database::user::select_uid_from_email;
+use database::user::{select_email_from_user_uuid, select_uid_from_email};
 use database_entity::dto::PublishCollabItem;
 use database_entity::dto::PublishInfo;
 use database_entity::dto::*;
+use shared_entity::dto::publish_dto::PublishOutcome;
+use shared_entity::dto::search_dto::{WorkspaceSearchQuery, WorkspaceSearchResultItem};
 use shared_entity::dto::workspace_dto::*;
 use shared_entity::response::AppResponseError;
 use shared_entity::response::{AppResponse, JsonAppResponse};
 
 use crate::api::util::PayloadReader;
-use crate::api::util::{compress_type_from_header_value, device_id_from_headers, CollabValidator};
+use crate::api::util::{
+  client_ip, compress_type_from_header_value, device_id_from_headers, CollabValidator,
+};
 use crate::api::ws::RealtimeServerAddr;
 use crate::biz;
+use crate::biz::collab::folder_view::FolderViewFilter;
 use crate::biz::collab::ops::{
   get_user_favorite_folder_views, get_user_recent_folder_views, get_user_trash_folder_views,
 };
 use crate::biz::user::user_verify::verify_token;
 use crate::biz::workspace;
+use crate::biz::workspace::audit_log::{list_audit_logs, record_audit_log};
 use crate::biz::workspace::ops::{
   create_comment_on_published_view, create_reaction_on_comment, get_comments_on_published_view,
   get_reactions_on_published_view, remove_comment_on_published_view, remove_reaction_on_comment,
 };
 use crate::biz::workspace::page_view::get_page_view_collab;
+use crate::biz::workspace::publish_render::PublishedRenderFormat;
 use crate::domain::compression::{
   blocking_decompress, decompress, CompressionType, X_COMPRESSION_TYPE,
 };
@@ -55,15 +65,37 @@ use crate::state::AppState;
 
 pub const WORKSPACE_ID_PATH: &str = "workspace_id";
 pub const COLLAB_OBJECT_ID_PATH: &str = "object_id";
+/// Header that carries an object-scoped token, see [biz::collab::object_token].
+pub use database_entity::dto::X_OBJECT_TOKEN;
+/// Header that carries the access token of a password-protected published view, see
+/// [biz::workspace::publish_access].
+pub const X_PUBLISH_ACCESS_TOKEN: &str = "X-Publish-Access-Token";
 
 pub const WORKSPACE_PATTERN: &str = "/api/workspace";
 pub const WORKSPACE_MEMBER_PATTERN: &str = "/api/workspace/{workspace_id}/member";
 pub const WORKSPACE_INVITE_PATTERN: &str = "/api/workspace/{workspace_id}/invite";
 pub const COLLAB_PATTERN: &str = "/api/workspace/{workspace_id}/collab/{object_id}";
 pub const V1_COLLAB_PATTERN: &str = "/api/workspace/v1/{workspace_id}/collab/{object_id}";
+pub const COLLAB_SHARE_LINK_PATTERN: &str =
+  "/api/workspace/{workspace_id}/collab/{object_id}/share-link";
+pub const COLLAB_SHARE_LINK_ITEM_PATTERN: &str =
+  "/api/workspace/{workspace_id}/collab/{object_id}/share-link/{link_id}";
+pub const COLLAB_SHARE_LINK_ROTATE_PATTERN: &str =
+  "/api/workspace/{workspace_id}/collab/{object_id}/share-link/{link_id}/rotate";
+pub const COLLAB_INHERITANCE_PATTERN: &str =
+  "/api/workspace/{workspace_id}/collab/{object_id}/inheritance";
 pub const WORKSPACE_PUBLISH_PATTERN: &str = "/api/workspace/{workspace_id}/publish";
+pub const WORKSPACE_PUBLISH_ANALYTICS_PATTERN: &str =
+  "/api/workspace/{workspace_id}/publish-analytics";
+pub const WORKSPACE_TRASH_PATTERN: &str = "/api/workspace/{workspace_id}/trash";
+pub const WORKSPACE_AI_USAGE_PATTERN: &str = "/api/workspace/{workspace_id}/ai-usage";
+pub const WORKSPACE_OWNERSHIP_TRANSFER_PATTERN: &str =
+  "/api/workspace/{workspace_id}/ownership-transfer";
 pub const WORKSPACE_PUBLISH_NAMESPACE_PATTERN: &str =
   "/api/workspace/{workspace_id}/publish-namespace";
+pub const WORKSPACE_FOLDER_INTEGRITY_PATTERN: &str =
+  "/api/workspace/{workspace_id}/folder/integrity";
+pub const WORKSPACE_OBJECT_PATTERN: &str = "/api/workspace/{workspace_id}/object/{object_id}";
 
 pub fn workspace_scope() -> Scope {
   web::scope("/api/workspace")
@@ -82,6 +114,36 @@ pub fn workspace_scope() -> Scope {
     .service(
       web::resource("/invite/{invite_id}").route(web::get().to(get_workspace_invite_by_id_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/invitation")
+        .route(web::get().to(list_pending_workspace_invitations_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/invitation/{invite_id}")
+        .route(web::delete().to(revoke_workspace_invitation_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/invitation/{invite_id}/resend")
+        .route(web::post().to(resend_workspace_invitation_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/invite-link")
+        .route(web::get().to(list_workspace_invite_links_handler))
+        .route(web::post().to(create_workspace_invite_link_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/invite-link/{link_id}")
+        .route(web::put().to(update_workspace_invite_link_handler))
+        .route(web::delete().to(delete_workspace_invite_link_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/invite-link/{link_id}/rotate")
+        .route(web::post().to(rotate_workspace_invite_link_handler)),
+    )
+    .service(
+      web::resource("/invite-link/{token}/join")
+        .route(web::post().to(join_workspace_with_invite_link_handler)),
+    )
     .service(
       web::resource("/accept-invite/{invite_id}")
         .route(web::post().to(post_accept_workspace_invite_handler)), // accept invitation to workspace
@@ -104,6 +166,11 @@ pub fn workspace_scope() -> Scope {
       web::resource("/{workspace_id}/member/user/{user_id}")
         .route(web::get().to(get_workspace_member_handler)),
     )
+    // registered before `/{workspace_id}/collab/{object_id}`, which would match it otherwise
+    .service(
+      web::resource("/object-token/collab/{object_id}")
+        .route(web::get().to(get_collab_with_object_token_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/collab/{object_id}")
         .app_data(
@@ -122,6 +189,44 @@ pub fn workspace_scope() -> Scope {
       web::resource("/{workspace_id}/page-view/{view_id}")
         .route(web::get().to(get_page_view_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/database/{view_id}/row")
+        .route(web::get().to(list_database_rows_handler))
+        .route(web::post().to(create_database_row_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/database/{view_id}/query")
+        .route(web::post().to(query_database_view_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/database/{view_id}/row/{row_id}")
+        .route(web::put().to(update_database_row_handler))
+        .route(web::delete().to(delete_database_row_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/database/{view_id}/calendar-feed")
+        .route(web::post().to(create_database_calendar_feed_handler))
+        .route(web::delete().to(delete_database_calendar_feed_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/database/{view_id}/export")
+        .route(web::get().to(export_database_view_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/database/{view_id}/import/csv")
+        .app_data(PayloadConfig::new(
+          workspace::database_import::MAX_DATABASE_IMPORT_CSV_SIZE,
+        ))
+        .route(web::post().to(post_database_csv_import_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/database/{view_id}/import/{task_id}")
+        .route(web::get().to(get_database_import_task_handler)),
+    )
+    .service(
+      web::resource("/calendar-feed/{token}")
+        .route(web::get().to(get_database_calendar_feed_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/batch/collab")
         .route(web::post().to(batch_create_collab_handler)),
@@ -129,6 +234,18 @@ pub fn workspace_scope() -> Scope {
     .service(
       web::resource("/{workspace_id}/usage").route(web::get().to(get_workspace_usage_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/storage-footprint")
+        .route(web::get().to(get_workspace_storage_footprint_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/storage-quota")
+        .route(web::get().to(get_workspace_storage_quota_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/ai-usage")
+        .route(web::get().to(get_workspace_ai_usage_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/{object_id}/snapshot")
         .route(web::get().to(get_collab_snapshot_handler))
@@ -138,6 +255,22 @@ pub fn workspace_scope() -> Scope {
       web::resource("/{workspace_id}/{object_id}/snapshot/list")
         .route(web::get().to(get_all_collab_snapshot_list_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/version")
+        .route(web::get().to(list_collab_versions_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/version/diff")
+        .route(web::get().to(diff_collab_versions_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/version/{snapshot_id}")
+        .route(web::get().to(get_collab_version_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/version/{snapshot_id}/restore")
+        .route(web::post().to(restore_collab_version_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/collab/{object_id}/member")
         .route(web::post().to(add_collab_member_handler))
@@ -145,6 +278,16 @@ pub fn workspace_scope() -> Scope {
         .route(web::put().to(update_collab_member_handler))
         .route(web::delete().to(remove_collab_member_handler)),
     )
+    // Registered before the published collabs. They never shadow a published collab, since publish
+    // names only contain alphanumeric characters and hyphens
+    .service(
+      web::resource("/published/{publish_namespace}/sitemap.xml")
+        .route(web::get().to(get_published_sitemap_handler)),
+    )
+    .service(
+      web::resource("/published/{publish_namespace}/feed.xml")
+        .route(web::get().to(get_published_feed_handler)),
+    )
     .service(
       web::resource("/published/{publish_namespace}/{publish_name}")
         .route(web::get().to(get_published_collab_handler)),
@@ -153,10 +296,31 @@ pub fn workspace_scope() -> Scope {
       web::resource("/published/{publish_namespace}/{publish_name}/blob")
         .route(web::get().to(get_published_collab_blob_handler)),
     )
+    .service(
+      web::resource("/published/{publish_namespace}/{publish_name}/html")
+        .route(web::get().to(get_published_html_handler)),
+    )
+    .service(
+      web::resource("/published/{publish_namespace}/{publish_name}/markdown")
+        .route(web::get().to(get_published_markdown_handler)),
+    )
+    .service(
+      web::resource("/published/{publish_namespace}/{publish_name}/access")
+        .route(web::post().to(post_published_view_access_handler)),
+    )
+    .service(
+      web::resource("/published/{publish_namespace}/{publish_name}/form")
+        .route(web::get().to(get_published_database_form_handler))
+        .route(web::post().to(post_published_database_form_handler)),
+    )
     .service(
       web::resource("{workspace_id}/published-duplicate")
         .route(web::post().to(post_published_duplicate_handler)),
     )
+    .service(
+      web::resource("{workspace_id}/template/{view_id}/instantiate")
+        .route(web::post().to(post_instantiate_template_handler)),
+    )
     .service(
       web::resource("/published-info/{view_id}")
         .route(web::get().to(get_published_collab_info_handler)),
@@ -173,32 +337,365 @@ pub fn workspace_scope() -> Scope {
         .route(web::post().to(post_published_collab_reaction_handler))
         .route(web::delete().to(delete_published_collab_reaction_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/published-info/{view_id}/nav")
+        .route(web::put().to(put_published_view_nav_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/published-info/{view_id}/expiry")
+        .route(web::put().to(put_published_view_expiry_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/published-info/{view_id}/password")
+        .route(web::put().to(put_published_view_password_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/published-info/{view_id}/form")
+        .route(web::get().to(get_published_database_form_settings_handler))
+        .route(web::put().to(put_published_database_form_handler))
+        .route(web::delete().to(delete_published_database_form_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/publish-namespace")
         .route(web::put().to(put_publish_namespace_handler))
         .route(web::get().to(get_publish_namespace_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/publish-domain")
+        .route(web::post().to(post_publish_custom_domain_handler))
+        .route(web::get().to(list_publish_custom_domains_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/publish-domain/{domain}")
+        .route(web::delete().to(delete_publish_custom_domain_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/publish-domain/{domain}/verify")
+        .route(web::post().to(verify_publish_custom_domain_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/publish-analytics")
+        .route(web::get().to(get_publish_analytics_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/publish")
         .route(web::post().to(post_publish_collabs_handler))
         .route(web::delete().to(delete_published_collabs_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/publish/if-changed")
+        .route(web::post().to(post_publish_collab_if_changed_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/folder").route(web::get().to(get_workspace_folder_handler)),
     )
-    .service(web::resource("/{workspace_id}/recent").route(web::get().to(get_recent_views_handler)))
     .service(
-      web::resource("/{workspace_id}/favorite").route(web::get().to(get_favorite_views_handler)),
+      web::resource("/folder/batch").route(web::post().to(get_workspace_folders_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/object/{object_id}")
+        .route(web::delete().to(delete_object_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/folder/integrity")
+        .route(web::get().to(get_folder_integrity_handler))
+        .route(web::post().to(repair_folder_integrity_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/folder/search")
+        .route(web::get().to(search_workspace_folder_handler)),
+    )
+    .service(web::resource("/{workspace_id}/search").route(web::get().to(search_workspace_handler)))
+    .service(
+      web::resource("/{workspace_id}/folder/{view_id}/children")
+        .route(web::get().to(get_folder_children_connection_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/folder/{view_id}/ancestors")
+        .route(web::get().to(get_folder_view_ancestors_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/folder/order")
+        .route(web::put().to(put_user_view_order_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/recent")
+        .route(web::get().to(get_recent_views_handler))
+        .route(web::post().to(record_view_visit_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/favorite")
+        .route(web::get().to(get_favorite_views_handler))
+        .route(web::post().to(set_view_favorite_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/trash")
+        .route(web::get().to(get_trash_views_handler))
+        .route(web::post().to(move_views_to_trash_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/trash/restore")
+        .route(web::post().to(restore_views_from_trash_handler)),
     )
-    .service(web::resource("/{workspace_id}/trash").route(web::get().to(get_trash_views_handler)))
     .service(
       web::resource("/published-outline/{publish_namespace}")
         .route(web::get().to(get_workspace_publish_outline_handler)),
     )
+    .service(
+      web::resource("/published-preview/{publish_namespace}/{view_id}")
+        .route(web::get().to(get_published_view_preview_handler)),
+    )
+    .service(
+      web::resource("/published-nav/{publish_namespace}")
+        .route(web::get().to(get_workspace_publish_nav_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/collab/{object_id}/member/list")
         .route(web::get().to(get_collab_member_list_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/member/batch")
+        .route(web::post().to(add_collab_members_batch_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/member/ensure")
+        .route(web::put().to(ensure_collab_member_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/member/merge")
+        .route(web::post().to(merge_collab_members_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/access-policy")
+        .route(web::get().to(get_collab_access_policies_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/member/suspension")
+        .route(web::put().to(suspend_collab_member_handler))
+        .route(web::delete().to(unsuspend_collab_member_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/membership-check")
+        .route(web::post().to(check_membership_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab-members/export")
+        .route(web::get().to(export_workspace_collab_members_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/access")
+        .route(web::get().to(get_effective_access_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/sharing")
+        .route(web::get().to(get_sharing_state_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/comment")
+        .route(web::get().to(get_collab_comments_handler))
+        .route(web::post().to(create_collab_comment_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/comment/{comment_id}")
+        .route(web::delete().to(delete_collab_comment_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/comment/{comment_id}/resolve")
+        .route(web::put().to(resolve_collab_comment_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/sync")
+        .route(web::post().to(delta_sync_collab_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/presence")
+        .route(web::get().to(get_collab_presences_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/contributors")
+        .route(web::get().to(get_collab_contributors_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/share-preview")
+        .route(web::get().to(get_share_preview_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/token")
+        .route(web::post().to(create_object_token_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/token/{token_id}")
+        .route(web::delete().to(revoke_object_token_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/share-link")
+        .route(web::get().to(list_share_links_handler))
+        .route(web::post().to(create_share_link_handler))
+        .route(web::delete().to(delete_all_share_links_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/inheritance")
+        .route(web::put().to(break_collab_inheritance_handler))
+        .route(web::delete().to(restore_collab_inheritance_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/share-link")
+        .route(web::get().to(list_live_workspace_share_links_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/share-link/{link_id}")
+        .route(web::put().to(update_share_link_handler))
+        .route(web::delete().to(delete_share_link_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/share-link/{link_id}/rotate")
+        .route(web::post().to(rotate_share_link_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/group/{group_id}")
+        .route(web::delete().to(remove_collab_group_access_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/group")
+        .route(web::get().to(list_workspace_groups_handler))
+        .route(web::post().to(create_workspace_group_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/group/{group_id}")
+        .route(web::delete().to(delete_workspace_group_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/group/{group_id}/member")
+        .route(web::post().to(add_workspace_group_member_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/group/{group_id}/member/{uid}")
+        .route(web::delete().to(remove_workspace_group_member_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/role")
+        .route(web::get().to(list_workspace_roles_handler))
+        .route(web::post().to(create_workspace_role_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/role/{role_id}")
+        .route(web::put().to(update_workspace_role_handler))
+        .route(web::delete().to(delete_workspace_role_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/role/{role_id}/member")
+        .route(web::post().to(assign_workspace_role_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/role/{role_id}/member/{uid}")
+        .route(web::delete().to(unassign_workspace_role_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/capability")
+        .route(web::get().to(get_workspace_capabilities_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/ownership-transfer")
+        .route(web::get().to(get_workspace_ownership_transfer_handler))
+        .route(web::post().to(request_workspace_ownership_transfer_handler))
+        .route(web::delete().to(cancel_workspace_ownership_transfer_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/ownership-transfer/accept")
+        .route(web::post().to(accept_workspace_ownership_transfer_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/webhook")
+        .route(web::get().to(list_webhooks_handler))
+        .route(web::post().to(create_webhook_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/webhook/{webhook_id}")
+        .route(web::delete().to(delete_webhook_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/webhook/{webhook_id}/dead-letter")
+        .route(web::get().to(list_webhook_dead_letters_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/webhook/{webhook_id}/dead-letter/{delivery_id}/retry")
+        .route(web::post().to(retry_webhook_dead_letter_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/service-account")
+        .route(web::get().to(list_service_accounts_handler))
+        .route(web::post().to(create_service_account_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/service-account/{service_account_id}")
+        .route(web::delete().to(delete_service_account_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/service-account/{service_account_id}/rotate")
+        .route(web::post().to(rotate_service_account_api_key_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/service-account/{service_account_id}/key/{api_key_id}")
+        .route(web::delete().to(revoke_service_account_api_key_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/snapshot-retention")
+        .route(web::get().to(get_workspace_snapshot_retention_handler))
+        .route(web::put().to(update_workspace_snapshot_retention_handler))
+        .route(web::delete().to(delete_workspace_snapshot_retention_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/sso")
+        .route(web::get().to(get_workspace_sso_handler))
+        .route(web::put().to(update_workspace_sso_handler))
+        .route(web::delete().to(delete_workspace_sso_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/scim/group").route(web::get().to(list_scim_groups_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/scim/group/{group_id}/role")
+        .route(web::put().to(update_scim_group_role_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/audit-log").route(web::get().to(list_audit_logs_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/export")
+        .route(web::get().to(get_workspace_export_handler))
+        .route(web::post().to(post_workspace_export_task_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/export/{task_id}")
+        .route(web::get().to(get_workspace_export_task_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/export/{task_id}/download")
+        .route(web::get().to(get_workspace_export_download_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/import/notion")
+        .app_data(PayloadConfig::new(
+          biz::workspace::import::MAX_IMPORT_ARCHIVE_SIZE,
+        ))
+        .route(web::post().to(post_notion_import_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/import/{task_id}")
+        .route(web::get().to(get_workspace_import_task_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/duplicate")
+        .route(web::post().to(post_workspace_duplicate_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/duplicate/{task_id}")
+        .route(web::get().to(get_workspace_duplicate_task_handler)),
+    )
+    .service(
+      web::resource("/share-link/{token}/collab")
+        .route(web::get().to(get_collab_with_share_link_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/collab_list")
       .route(web::get().to(batch_get_collab_handler))
@@ -218,6 +715,14 @@ pub fn collab_scope() -> Scope {
 }
 
 // Adds a workspace for user, if success, return the workspace id
+#[utoipa::path(
+  post,
+  path = "/api/workspace",
+  tag = "workspace",
+  request_body = CreateWorkspaceParam,
+  responses((status = 200, description = "The created workspace", body = AFWorkspace)),
+  security(("bearer_auth" = []))
+)]
 #[instrument(skip_all, err)]
 async fn create_workspace_handler(
   uuid: UserUuid,
@@ -244,6 +749,14 @@ async fn create_workspace_handler(
 }
 
 // Adds a workspace for user, if success, return the workspace id
+#[utoipa::path(
+  patch,
+  path = "/api/workspace",
+  tag = "workspace",
+  request_body = PatchWorkspaceParam,
+  responses((status = 200, description = "The workspace was renamed or its icon was changed")),
+  security(("bearer_auth" = []))
+)]
 #[instrument(skip_all, err)]
 async fn patch_workspace_handler(
   _uuid: UserUuid,
@@ -261,6 +774,14 @@ async fn patch_workspace_handler(
   Ok(AppResponse::Ok().into())
 }
 
+#[utoipa::path(
+  delete,
+  path = "/api/workspace/{workspace_id}",
+  tag = "workspace",
+  params(("workspace_id" = Uuid, Path, description = "The id of the workspace")),
+  responses((status = 200, description = "The workspace was deleted")),
+  security(("bearer_auth" = []))
+)]
 async fn delete_workspace_handler(
   _user_id: UserUuid,
   workspace_id: web::Path<Uuid>,
@@ -277,6 +798,14 @@ async fn delete_workspace_handler(
 }
 
 /// Get all user owned and shared workspaces
+#[utoipa::path(
+  get,
+  path = "/api/workspace",
+  tag = "workspace",
+  params(QueryWorkspaceParam),
+  responses((status = 200, description = "The workspaces the user owns or is a member of", body = Vec<AFWorkspace>)),
+  security(("bearer_auth" = []))
+)]
 #[instrument(skip_all, err)]
 async fn list_workspace_handler(
   uuid: UserUuid,
@@ -300,6 +829,13 @@ async fn post_workspace_invite_handler(
   state: Data<AppState>,
 ) -> Result<JsonAppResponse<()>> {
   let invited_members = payload.into_inner();
+  // The invite capability of a custom role does not extend to making other users owners
+  if invited_members
+    .iter()
+    .any(|invitation| invitation.role == AFRole::Owner)
+  {
+    workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  }
   workspace::ops::invite_workspace_members(
     &state.mailer,
     &state.gotrue_admin,
@@ -314,52 +850,215 @@ async fn post_workspace_invite_handler(
   Ok(AppResponse::Ok().into())
 }
 
-async fn get_workspace_invite_handler(
+async fn list_pending_workspace_invitations_handler(
   user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
   state: Data<AppState>,
-  query: web::Query<WorkspaceInviteQuery>,
-) -> Result<JsonAppResponse<Vec<AFWorkspaceInvitation>>> {
-  let query = query.into_inner();
-  let res =
-    workspace::ops::list_workspace_invitations_for_user(&state.pg_pool, &user_uuid, query.status)
+) -> Result<JsonAppResponse<Vec<PendingWorkspaceInvitation>>> {
+  let workspace_id = workspace_id.into_inner();
+  workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let invitations =
+    workspace::invitation::list_pending_workspace_invitations(&state.pg_pool, &workspace_id)
       .await?;
-  Ok(AppResponse::Ok().with_data(res).into())
+  Ok(AppResponse::Ok().with_data(invitations).into())
 }
 
-async fn get_workspace_invite_by_id_handler(
+async fn resend_workspace_invitation_handler(
   user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
   state: Data<AppState>,
-  invite_id: web::Path<Uuid>,
-) -> Result<JsonAppResponse<AFWorkspaceInvitation>> {
-  let invite_id = invite_id.into_inner();
-  let res =
-    workspace::ops::get_workspace_invitations_for_user(&state.pg_pool, &user_uuid, &invite_id)
-      .await?;
-  Ok(AppResponse::Ok().with_data(res).into())
+) -> Result<JsonAppResponse<PendingWorkspaceInvitation>> {
+  let (workspace_id, invite_id) = path.into_inner();
+  workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let invitation = workspace::invitation::resend_workspace_invitation(
+    &state,
+    &user_uuid,
+    &workspace_id,
+    &invite_id,
+  )
+  .await?;
+  Ok(AppResponse::Ok().with_data(invitation).into())
 }
 
-async fn post_accept_workspace_invite_handler(
-  auth: Authorization,
-  invite_id: web::Path<Uuid>,
+async fn revoke_workspace_invitation_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
   state: Data<AppState>,
 ) -> Result<JsonAppResponse<()>> {
-  let _is_new = verify_token(&auth.token, state.as_ref()).await?;
-  let user_uuid = auth.uuid()?;
-  let user_uid = state.user_cache.get_user_uid(&user_uuid).await?;
-  let invite_id = invite_id.into_inner();
-  // TODO(zack): insert a workspace member in the af_workspace_member by calling  workspace::ops::add_workspace_members.
-  // Currently, when the server get restarted, the policy in access control will be lost.
-  workspace::ops::accept_workspace_invite(
+  let (workspace_id, invite_id) = path.into_inner();
+  workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  workspace::invitation::revoke_workspace_invitation(
     &state.pg_pool,
-    &state.workspace_access_control,
-    user_uid,
-    &user_uuid,
+    uid,
+    &workspace_id,
     &invite_id,
   )
   .await?;
   Ok(AppResponse::Ok().into())
 }
 
+async fn list_workspace_invite_links_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<Vec<WorkspaceInviteLink>>> {
+  let workspace_id = workspace_id.into_inner();
+  workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let links =
+    workspace::invite_link::list_workspace_invite_links(&state.pg_pool, &workspace_id).await?;
+  Ok(AppResponse::Ok().with_data(links).into())
+}
+
+async fn create_workspace_invite_link_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<CreateWorkspaceInviteLinkParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<WorkspaceInviteLink>> {
+  let workspace_id = workspace_id.into_inner();
+  workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let link = workspace::invite_link::create_workspace_invite_link(
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(AppResponse::Ok().with_data(link).into())
+}
+
+async fn update_workspace_invite_link_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<UpdateWorkspaceInviteLinkParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<WorkspaceInviteLink>> {
+  let (workspace_id, link_id) = path.into_inner();
+  workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let link = workspace::invite_link::update_workspace_invite_link(
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &link_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(AppResponse::Ok().with_data(link).into())
+}
+
+async fn rotate_workspace_invite_link_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<WorkspaceInviteLink>> {
+  let (workspace_id, link_id) = path.into_inner();
+  workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let link = workspace::invite_link::rotate_workspace_invite_link(
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &link_id,
+  )
+  .await?;
+  Ok(AppResponse::Ok().with_data(link).into())
+}
+
+async fn delete_workspace_invite_link_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let (workspace_id, link_id) = path.into_inner();
+  workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  workspace::invite_link::delete_workspace_invite_link(
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &link_id,
+  )
+  .await?;
+  Ok(AppResponse::Ok().into())
+}
+
+/// Join the workspace of an invite link, which any signed in user whose email the link allows can
+/// do.
+async fn join_workspace_with_invite_link_handler(
+  user_uuid: UserUuid,
+  token: web::Path<String>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<JoinedWorkspace>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let email = select_email_from_user_uuid(&state.pg_pool, &user_uuid).await?;
+  let joined = workspace::invite_link::join_workspace_with_invite_link(
+    &state.pg_pool,
+    &state.workspace_access_control,
+    uid,
+    &email,
+    &token.into_inner(),
+  )
+  .await?;
+  Ok(AppResponse::Ok().with_data(joined).into())
+}
+
+async fn get_workspace_invite_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  query: web::Query<WorkspaceInviteQuery>,
+) -> Result<JsonAppResponse<Vec<AFWorkspaceInvitation>>> {
+  let query = query.into_inner();
+  let res =
+    workspace::ops::list_workspace_invitations_for_user(&state.pg_pool, &user_uuid, query.status)
+      .await?;
+  Ok(AppResponse::Ok().with_data(res).into())
+}
+
+async fn get_workspace_invite_by_id_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  invite_id: web::Path<Uuid>,
+) -> Result<JsonAppResponse<AFWorkspaceInvitation>> {
+  let invite_id = invite_id.into_inner();
+  let res =
+    workspace::ops::get_workspace_invitations_for_user(&state.pg_pool, &user_uuid, &invite_id)
+      .await?;
+  Ok(AppResponse::Ok().with_data(res).into())
+}
+
+async fn post_accept_workspace_invite_handler(
+  auth: Authorization,
+  invite_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let _is_new = verify_token(&auth.token, state.as_ref()).await?;
+  let user_uuid = auth.uuid()?;
+  let user_uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let invite_id = invite_id.into_inner();
+  // TODO(zack): insert a workspace member in the af_workspace_member by calling  workspace::ops::add_workspace_members.
+  // Currently, when the server get restarted, the policy in access control will be lost.
+  workspace::ops::accept_workspace_invite(
+    &state.pg_pool,
+    &state.workspace_access_control,
+    user_uid,
+    &user_uuid,
+    &invite_id,
+  )
+  .await?;
+  Ok(AppResponse::Ok().into())
+}
+
+#[utoipa::path(
+  get,
+  path = "/api/workspace/{workspace_id}/settings",
+  tag = "workspace",
+  params(("workspace_id" = Uuid, Path, description = "The id of the workspace")),
+  responses((status = 200, description = "The settings of the workspace", body = AFWorkspaceSettings)),
+  security(("bearer_auth" = []))
+)]
 #[instrument(skip_all, err, fields(user_uuid))]
 async fn get_workspace_settings_handler(
   user_uuid: UserUuid,
@@ -377,6 +1076,15 @@ async fn get_workspace_settings_handler(
   Ok(AppResponse::Ok().with_data(settings).into())
 }
 
+#[utoipa::path(
+  post,
+  path = "/api/workspace/{workspace_id}/settings",
+  tag = "workspace",
+  params(("workspace_id" = Uuid, Path, description = "The id of the workspace")),
+  request_body = AFWorkspaceSettingsChange,
+  responses((status = 200, description = "The updated settings of the workspace", body = AFWorkspaceSettings)),
+  security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip_all, err, fields(user_uuid))]
 async fn post_workspace_settings_handler(
   user_uuid: UserUuid,
@@ -395,9 +1103,21 @@ async fn post_workspace_settings_handler(
     data,
   )
   .await?;
+  state
+    .access_control
+    .invalidate_collab_hierarchy(&workspace_id.to_string())
+    .await;
   Ok(AppResponse::Ok().with_data(settings).into())
 }
 
+#[utoipa::path(
+  get,
+  path = "/api/workspace/{workspace_id}/member",
+  tag = "workspace",
+  params(("workspace_id" = Uuid, Path, description = "The id of the workspace")),
+  responses((status = 200, description = "The members of the workspace", body = Vec<AFWorkspaceMember>)),
+  security(("bearer_auth" = []))
+)]
 #[instrument(skip_all, err)]
 async fn get_workspace_members_handler(
   _user_uuid: UserUuid,
@@ -420,7 +1140,7 @@ async fn get_workspace_members_handler(
 
 #[instrument(skip_all, err)]
 async fn remove_workspace_member_handler(
-  _user_uuid: UserUuid,
+  user_uuid: UserUuid,
   payload: Json<WorkspaceMembers>,
   state: Data<AppState>,
   workspace_id: web::Path<Uuid>,
@@ -431,11 +1151,14 @@ async fn remove_workspace_member_handler(
     .into_iter()
     .map(|member| member.0)
     .collect::<Vec<String>>();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   workspace::ops::remove_workspace_members(
     &state.pg_pool,
+    uid,
     &workspace_id,
     &member_emails,
     &state.workspace_access_control,
+    &state.collab_access_control,
   )
   .await?;
 
@@ -483,6 +1206,7 @@ async fn leave_workspace_handler(
     &workspace_id,
     &user_uuid,
     &state.workspace_access_control,
+    &state.collab_access_control,
   )
   .await?;
   Ok(AppResponse::Ok().into())
@@ -490,6 +1214,7 @@ async fn leave_workspace_handler(
 
 #[instrument(level = "debug", skip_all, err)]
 async fn update_workspace_member_handler(
+  user_uuid: UserUuid,
   payload: Json<WorkspaceMemberChangeset>,
   state: Data<AppState>,
   workspace_id: web::Path<Uuid>,
@@ -503,7 +1228,9 @@ async fn update_workspace_member_handler(
     let uid = select_uid_from_email(&state.pg_pool, &changeset.email)
       .await
       .map_err(AppResponseError::from)?;
+    let actor_uid = state.user_cache.get_user_uid(&user_uuid).await?;
     workspace::ops::update_workspace_member(
+      actor_uid,
       &uid,
       &state.pg_pool,
       &workspace_id,
@@ -612,7 +1339,8 @@ async fn batch_create_collab_handler(
   req: HttpRequest,
 ) -> Result<Json<AppResponse<()>>> {
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
-  let workspace_id = workspace_id.into_inner().to_string();
+  let workspace_uuid = workspace_id.into_inner();
+  let workspace_id = workspace_uuid.to_string();
   let compress_type = compress_type_from_header_value(req.headers())?;
   event!(tracing::Level::DEBUG, "start decompressing collab list");
 
@@ -692,6 +1420,18 @@ async fn batch_create_collab_handler(
     }
   }
 
+  let collabs = collab_params_list
+    .iter()
+    .map(|params| (params.object_id.clone(), params.encoded_collab_v1.len()))
+    .collect::<Vec<_>>();
+  check_collab_storage_quota(
+    &state.pg_pool,
+    &state.config.storage_quota,
+    &workspace_uuid,
+    &collabs,
+  )
+  .await?;
+
   // Process each collab params
   for params in collab_params_list {
     let object_id = params.object_id.clone();
@@ -745,12 +1485,16 @@ async fn get_collab_handler(
   Ok(Json(AppResponse::Ok().with_data(resp)))
 }
 
+/// Returns the collab in a json response. A client that accepts `application/octet-stream`
+/// receives instead the encoded collab, in the v1 format, streamed as the body of the response,
+/// which suits the large collabs. The errors are always returned as a json [AppResponse].
 async fn v1_get_collab_handler(
+  req: HttpRequest,
   user_uuid: UserUuid,
   path: web::Path<(String, String)>,
   query: web::Query<CollabTypeParam>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<CollabResponse>>> {
+) -> Result<HttpResponse> {
   let (workspace_id, object_id) = path.into_inner();
   let collab_type = query.into_inner().collab_type;
   let uid = state
@@ -767,6 +1511,24 @@ async fn v1_get_collab_handler(
     },
   };
 
+  let accepts_stream = req
+    .headers()
+    .get(actix_web::http::header::ACCEPT)
+    .and_then(|value| value.to_str().ok())
+    .is_some_and(|value| value.contains(mime::APPLICATION_OCTET_STREAM.essence_str()));
+  if accepts_stream {
+    let stream = state
+      .collab_access_control_storage
+      .get_encode_collab_stream(GetCollabOrigin::User { uid }, param, true)
+      .await
+      .map_err(AppResponseError::from)?;
+    return Ok(
+      HttpResponse::Ok()
+        .content_type(mime::APPLICATION_OCTET_STREAM)
+        .streaming(stream),
+    );
+  }
+
   let encode_collab = state
     .collab_access_control_storage
     .get_encode_collab(GetCollabOrigin::User { uid }, param, true)
@@ -778,7 +1540,7 @@ async fn v1_get_collab_handler(
     object_id,
   };
 
-  Ok(Json(AppResponse::Ok().with_data(resp)))
+  Ok(HttpResponse::Ok().json(AppResponse::Ok().with_data(resp)))
 }
 
 async fn get_page_view_handler(
@@ -803,106 +1565,412 @@ async fn get_page_view_handler(
   Ok(Json(AppResponse::Ok().with_data(page_collab)))
 }
 
-#[instrument(level = "trace", skip_all, err)]
-async fn get_collab_snapshot_handler(
-  payload: Json<QuerySnapshotParams>,
-  path: web::Path<(String, String)>,
+async fn list_database_rows_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  query: web::Query<QueryDatabaseRows>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<SnapshotData>>> {
-  let (workspace_id, object_id) = path.into_inner();
-  let data = state
-    .collab_access_control_storage
-    .get_collab_snapshot(&workspace_id.to_string(), &object_id, &payload.snapshot_id)
-    .await
-    .map_err(AppResponseError::from)?;
-
-  Ok(Json(AppResponse::Ok().with_data(data)))
+) -> Result<Json<AppResponse<DatabaseRows>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let rows = workspace::database_row::list_database_rows(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &view_id,
+    query.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(rows)))
 }
 
-#[instrument(level = "trace", skip_all, err)]
-async fn create_collab_snapshot_handler(
+async fn query_database_view_handler(
   user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<QueryDatabaseViewParams>,
   state: Data<AppState>,
-  path: web::Path<(String, String)>,
-  payload: Json<CollabType>,
-) -> Result<Json<AppResponse<AFSnapshotMeta>>> {
-  let (workspace_id, object_id) = path.into_inner();
-  let collab_type = payload.into_inner();
-  let uid = state
-    .user_cache
-    .get_user_uid(&user_uuid)
-    .await
-    .map_err(AppResponseError::from)?;
-  let encoded_collab_v1 = state
-    .collab_access_control_storage
-    .get_encode_collab(
-      GetCollabOrigin::User { uid },
-      QueryCollabParams::new(&object_id, collab_type.clone(), &workspace_id),
-      true,
-    )
-    .await?
-    .encode_to_bytes()
-    .unwrap();
-
-  let meta = state
-    .collab_access_control_storage
-    .create_snapshot(InsertSnapshotParams {
-      object_id,
-      workspace_id,
-      encoded_collab_v1,
-      collab_type,
-    })
-    .await?;
-
-  Ok(Json(AppResponse::Ok().with_data(meta)))
+) -> Result<Json<AppResponse<DatabaseRows>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let rows = workspace::database_query::query_database_view(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &view_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(rows)))
 }
 
-#[instrument(level = "trace", skip(path, state), err)]
-async fn get_all_collab_snapshot_list_handler(
-  path: web::Path<(String, String)>,
+async fn create_database_calendar_feed_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<AFSnapshotMetas>>> {
-  let (_, object_id) = path.into_inner();
-  let data = state
-    .collab_access_control_storage
-    .get_collab_snapshot_list(&object_id)
-    .await
-    .map_err(AppResponseError::from)?;
-  Ok(Json(AppResponse::Ok().with_data(data)))
+) -> Result<Json<AppResponse<DatabaseCalendarFeed>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let feed = workspace::calendar_feed::create_database_calendar_feed(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    &state.config.notification.public_url,
+    uid,
+    &workspace_id,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(feed)))
 }
 
-#[instrument(level = "debug", skip(payload, state), err)]
-async fn batch_get_collab_handler(
+async fn delete_database_calendar_feed_handler(
   user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
   state: Data<AppState>,
-  payload: Json<BatchQueryCollabParams>,
-) -> Result<Json<AppResponse<BatchQueryCollabResult>>> {
-  let uid = state
-    .user_cache
-    .get_user_uid(&user_uuid)
-    .await
-    .map_err(AppResponseError::from)?;
-  let result = BatchQueryCollabResult(
-    state
-      .collab_access_control_storage
-      .batch_get_collab(&uid, payload.into_inner().0)
-      .await,
-  );
-  Ok(Json(AppResponse::Ok().with_data(result)))
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  workspace::calendar_feed::delete_database_calendar_feed(
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
 }
 
-#[instrument(skip(state, payload), err)]
-async fn update_collab_handler(
+/// The iCal feed of a database view. No login is required, the token of the feed grants access
+/// to it.
+async fn get_database_calendar_feed_handler(
+  token: web::Path<String>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let calendar = workspace::calendar_feed::get_database_calendar_feed(
+    state.collab_access_control_storage.clone(),
+    &state.collab_access_control,
+    &state.pg_pool,
+    &state.redis_connection_manager,
+    &token,
+  )
+  .await?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("text/calendar; charset=utf-8")
+      .body(calendar),
+  )
+}
+
+async fn export_database_view_handler(
   user_uuid: UserUuid,
-  payload: Json<CreateCollabParams>,
+  path: web::Path<(Uuid, String)>,
+  query: web::Query<QueryDatabaseViewExport>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<()>>> {
-  let (params, workspace_id) = payload.into_inner().split();
+) -> Result<HttpResponse> {
+  let (workspace_id, view_id) = path.into_inner();
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
-
-  let create_params = CreateCollabParams::from((workspace_id.to_string(), params));
-  let (mut params, workspace_id) = create_params.split();
-  if let Some(indexer) = state
+  let export = workspace::database_export::export_database_view(
+    state.collab_access_control_storage.clone(),
+    &state.collab_access_control,
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &view_id,
+    query.into_inner().format.unwrap_or_default(),
+  )
+  .await?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type(export.content_type)
+      .insert_header(actix_web::http::header::ContentDisposition::attachment(
+        export.file_name,
+      ))
+      .streaming(export.body),
+  )
+}
+
+async fn post_database_csv_import_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Bytes,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<DatabaseImportTask>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let task = workspace::database_import::start_database_csv_import(
+    state.collab_access_control_storage.clone(),
+    &state.collab_access_control,
+    &state.pg_pool,
+    uid,
+    workspace_id,
+    view_id,
+    payload.to_vec(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(task)))
+}
+
+async fn get_database_import_task_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<DatabaseImportTask>>> {
+  let (workspace_id, view_id, task_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let task = workspace::database_import::get_database_import_task(
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &view_id,
+    &task_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(task)))
+}
+
+async fn create_database_row_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<CreateDatabaseRowParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<DatabaseRow>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let row = workspace::database_row::create_database_row(
+    state.collab_access_control_storage.clone(),
+    &state.collab_access_control,
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &view_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(row)))
+}
+
+async fn update_database_row_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String, String)>,
+  payload: Json<UpdateDatabaseRowParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<DatabaseRow>>> {
+  let (workspace_id, view_id, row_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let row = workspace::database_row::update_database_row(
+    state.collab_access_control_storage.clone(),
+    &state.collab_access_control,
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &view_id,
+    &row_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(row)))
+}
+
+async fn delete_database_row_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, view_id, row_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  workspace::database_row::delete_database_row(
+    state.collab_access_control_storage.clone(),
+    &state.collab_access_control,
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &view_id,
+    &row_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+#[instrument(level = "trace", skip_all, err)]
+async fn get_collab_snapshot_handler(
+  payload: Json<QuerySnapshotParams>,
+  path: web::Path<(String, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<SnapshotData>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let data = state
+    .collab_access_control_storage
+    .get_collab_snapshot(&workspace_id.to_string(), &object_id, &payload.snapshot_id)
+    .await
+    .map_err(AppResponseError::from)?;
+
+  Ok(Json(AppResponse::Ok().with_data(data)))
+}
+
+#[instrument(level = "trace", skip_all, err)]
+async fn create_collab_snapshot_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<(String, String)>,
+  payload: Json<CollabType>,
+) -> Result<Json<AppResponse<AFSnapshotMeta>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let collab_type = payload.into_inner();
+  let uid = state
+    .user_cache
+    .get_user_uid(&user_uuid)
+    .await
+    .map_err(AppResponseError::from)?;
+  let encoded_collab_v1 = state
+    .collab_access_control_storage
+    .get_encode_collab(
+      GetCollabOrigin::User { uid },
+      QueryCollabParams::new(&object_id, collab_type.clone(), &workspace_id),
+      true,
+    )
+    .await?
+    .encode_to_bytes()
+    .unwrap();
+
+  let meta = state
+    .collab_access_control_storage
+    .create_snapshot(InsertSnapshotParams {
+      object_id,
+      workspace_id,
+      encoded_collab_v1,
+      collab_type,
+    })
+    .await?;
+  database::collab::update_collab_snapshot_created_by(&state.pg_pool, meta.snapshot_id, uid)
+    .await?;
+
+  Ok(Json(AppResponse::Ok().with_data(meta)))
+}
+
+#[instrument(level = "trace", skip(path, state), err)]
+async fn get_all_collab_snapshot_list_handler(
+  path: web::Path<(String, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<AFSnapshotMetas>>> {
+  let (_, object_id) = path.into_inner();
+  let data = state
+    .collab_access_control_storage
+    .get_collab_snapshot_list(&object_id)
+    .await
+    .map_err(AppResponseError::from)?;
+  Ok(Json(AppResponse::Ok().with_data(data)))
+}
+
+#[instrument(level = "trace", skip(state), err)]
+async fn list_collab_versions_handler(
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<Vec<AFCollabVersion>>> {
+  let (_, object_id) = path.into_inner();
+  let versions = biz::collab::version::list_collab_versions(&state.pg_pool, &object_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(versions)))
+}
+
+#[instrument(level = "trace", skip(state), err)]
+async fn get_collab_version_handler(
+  path: web::Path<(Uuid, String, i64)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<EncodedCollab>> {
+  let (workspace_id, object_id, snapshot_id) = path.into_inner();
+  let encoded_collab = biz::collab::version::get_collab_version(
+    &state.pg_pool,
+    &workspace_id.to_string(),
+    &object_id,
+    snapshot_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(encoded_collab)))
+}
+
+#[instrument(level = "trace", skip(state), err)]
+async fn diff_collab_versions_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  query: web::Query<QueryCollabVersionDiff>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<DocumentVersionDiff>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let diff = biz::collab::version_diff::diff_document_versions(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    uid,
+    &workspace_id.to_string(),
+    &object_id,
+    query.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(diff)))
+}
+
+#[instrument(level = "debug", skip(state, payload), err)]
+async fn restore_collab_version_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String, i64)>,
+  payload: Json<CollabType>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<AFSnapshotMeta>> {
+  let (workspace_id, object_id, snapshot_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let backup = biz::collab::version::restore_collab_version(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    uid,
+    &workspace_id.to_string(),
+    &object_id,
+    payload.into_inner(),
+    snapshot_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(backup)))
+}
+
+#[instrument(level = "debug", skip(payload, state), err)]
+async fn batch_get_collab_handler(
+  user_uuid: UserUuid,
+  path: web::Path<Uuid>,
+  state: Data<AppState>,
+  payload: Json<BatchQueryCollabParams>,
+) -> Result<Json<AppResponse<BatchQueryCollabResult>>> {
+  let uid = state
+    .user_cache
+    .get_user_uid(&user_uuid)
+    .await
+    .map_err(AppResponseError::from)?;
+  let workspace_id = path.into_inner();
+  let result = BatchQueryCollabResult(
+    biz::collab::ops::batch_get_collab_for_user(
+      &state.collab_access_control_storage,
+      &state.pg_pool,
+      &state.workspace_access_control,
+      &state.collab_access_control,
+      uid,
+      &workspace_id,
+      payload.into_inner().0,
+    )
+    .await?,
+  );
+  Ok(Json(AppResponse::Ok().with_data(result)))
+}
+
+#[instrument(skip(state, payload), err)]
+async fn update_collab_handler(
+  user_uuid: UserUuid,
+  payload: Json<CreateCollabParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (params, workspace_id) = payload.into_inner().split();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+
+  let create_params = CreateCollabParams::from((workspace_id.to_string(), params));
+  let (mut params, workspace_id) = create_params.split();
+  if let Some(indexer) = state
     .indexer_provider
     .indexer_for(params.collab_type.clone())
   {
@@ -932,10 +2000,18 @@ async fn update_collab_handler(
     }
   }
 
+  let object_id = params.object_id.clone();
   state
     .collab_access_control_storage
     .insert_or_update_collab(&workspace_id, &uid, params, false)
     .await?;
+  biz::collab::contributor::record_collab_edit_or_log(
+    &state.collab_access_control_storage,
+    &workspace_id,
+    &object_id,
+    uid,
+  )
+  .await;
   Ok(AppResponse::Ok().into())
 }
 
@@ -965,10 +2041,13 @@ async fn delete_collab_handler(
 
 #[instrument(level = "debug", skip(state, payload), err)]
 async fn add_collab_member_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
   payload: Json<InsertCollabMemberParams>,
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<()>>> {
   let payload = payload.into_inner();
+  check_collab_member_params_match_path(&path, &payload)?;
   if !state.collab_cache.is_exist(&payload.object_id).await? {
     return Err(
       AppError::RecordNotFound(format!(
@@ -979,309 +2058,2399 @@ async fn add_collab_member_handler(
     );
   }
 
-  biz::collab::ops::create_collab_member(&state.pg_pool, &payload, &state.collab_access_control)
-    .await?;
+  biz::collab::ops::create_collab_member(
+    &state.pg_pool,
+    &payload,
+    &state.collab_access_control,
+    false,
+  )
+  .await?;
+  record_collab_member_audit_log(&state, &user_uuid, AuditAction::CollabMemberAdded, &payload)
+    .await;
+  if payload.group_id.is_none() {
+    biz::notification::ops::notify_shared_with_you(
+      &state.pg_pool,
+      &user_uuid,
+      &payload.workspace_id,
+      &payload.object_id,
+      &[payload.uid],
+    )
+    .await;
+  }
   Ok(Json(AppResponse::Ok()))
 }
 
+/// Add the member, or replace its access level. Sending the same request again changes nothing.
 #[instrument(level = "debug", skip(state, payload), err)]
-async fn update_collab_member_handler(
-  user_uuid: UserUuid,
-  payload: Json<UpdateCollabMemberParams>,
+async fn ensure_collab_member_handler(
+  path: web::Path<(Uuid, String)>,
+  payload: Json<InsertCollabMemberParams>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<()>>> {
+) -> Result<Json<AppResponse<EnsureCollabMemberOutcome>>> {
   let payload = payload.into_inner();
-
+  check_collab_member_params_match_path(&path, &payload)?;
   if !state.collab_cache.is_exist(&payload.object_id).await? {
     return Err(
       AppError::RecordNotFound(format!(
-        "Fail to update collab member. The Collab with object_id {} does not exist",
+        "Fail to ensure collab member. The Collab with object_id {} does not exist",
         payload.object_id
       ))
       .into(),
     );
   }
-  biz::collab::ops::upsert_collab_member(
+
+  let outcome = biz::collab::ops::ensure_collab_member(
     &state.pg_pool,
-    &user_uuid,
     &payload,
     &state.collab_access_control,
+    false,
   )
   .await?;
-  Ok(Json(AppResponse::Ok()))
+  Ok(Json(AppResponse::Ok().with_data(outcome)))
 }
-#[instrument(level = "debug", skip(state, payload), err)]
-async fn get_collab_member_handler(
-  payload: Json<CollabMemberIdentify>,
-  state: Data<AppState>,
-) -> Result<Json<AppResponse<AFCollabMember>>> {
-  let payload = payload.into_inner();
-  let member = biz::collab::ops::get_collab_member(&state.pg_pool, &payload).await?;
-  Ok(Json(AppResponse::Ok().with_data(member)))
+
+/// The access of the caller is checked against the workspace and the collab of the path, so the
+/// member must be added to the same ones.
+fn check_collab_member_params_match_path(
+  path: &(Uuid, String),
+  params: &InsertCollabMemberParams,
+) -> Result<(), AppError> {
+  let (workspace_id, object_id) = path;
+  if params.workspace_id != workspace_id.to_string() || &params.object_id != object_id {
+    return Err(AppError::InvalidRequest(format!(
+      "The member must be added to the collab {} of workspace {}",
+      object_id, workspace_id
+    )));
+  }
+  Ok(())
 }
 
-#[instrument(skip(state, payload), err)]
+#[instrument(level = "debug", skip(state, payload), err)]
+async fn merge_collab_members_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<MergeCollabMembersParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<MergedCollabMembers>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let merged = biz::collab::ops::merge_collab_members(
+    &state.pg_pool,
+    &workspace_id.to_string(),
+    uid,
+    &payload.from_object_id,
+    &object_id,
+    &state.collab_access_control,
+    false,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(merged)))
+}
+
+/// The access policies of the collab held by the access control backend, for the owner of the
+/// workspace to audit them against the collab members.
+#[instrument(level = "debug", skip(state), err)]
+async fn get_collab_access_policies_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<CollabAccessPolicy>>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let policies = biz::collab::ops::get_access_policies(&state.collab_access_control, &object_id)
+    .await?
+    .into_iter()
+    .map(|(uid, access_level)| CollabAccessPolicy { uid, access_level })
+    .collect();
+  Ok(Json(AppResponse::Ok().with_data(policies)))
+}
+
+#[instrument(level = "debug", skip(state, payload), err)]
+async fn add_collab_members_batch_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<Vec<InsertCollabMemberParams>>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<BatchCreateCollabMemberResult>>> {
+  let payload = payload.into_inner();
+  for params in &payload {
+    check_collab_member_params_match_path(&path, params)?;
+  }
+  let (workspace_id, object_id) = path.into_inner();
+  if !state.collab_cache.is_exist(&object_id).await? {
+    return Err(
+      AppError::RecordNotFound(format!(
+        "Fail to insert collab members. The Collab with object_id {} does not exist",
+        object_id
+      ))
+      .into(),
+    );
+  }
+
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let result = biz::collab::ops::create_collab_members_batch(
+    &state.pg_pool,
+    uid,
+    &payload,
+    &state.collab_access_control,
+    false,
+  )
+  .await?;
+  let added_uids: Vec<i64> = result
+    .0
+    .iter()
+    .filter(|(_, result)| matches!(result, CreateCollabMemberResult::Created))
+    .map(|(uid, _)| *uid)
+    .collect();
+  for params in payload
+    .iter()
+    .filter(|params| added_uids.contains(&params.uid))
+  {
+    record_collab_member_audit_log(&state, &user_uuid, AuditAction::CollabMemberAdded, params)
+      .await;
+  }
+  biz::notification::ops::notify_shared_with_you(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id.to_string(),
+    &object_id,
+    &added_uids,
+  )
+  .await;
+  Ok(Json(AppResponse::Ok().with_data(result)))
+}
+
+#[instrument(level = "debug", skip(state, payload), err)]
+async fn update_collab_member_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<UpdateCollabMemberParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let payload = payload.into_inner();
+  check_collab_member_params_match_path(&path, &payload)?;
+
+  if !state.collab_cache.is_exist(&payload.object_id).await? {
+    return Err(
+      AppError::RecordNotFound(format!(
+        "Fail to update collab member. The Collab with object_id {} does not exist",
+        payload.object_id
+      ))
+      .into(),
+    );
+  }
+  biz::collab::ops::upsert_collab_member(
+    &state.pg_pool,
+    &user_uuid,
+    &payload,
+    &state.collab_access_control,
+    false,
+    state.config.collab.access_level_change_cooldown(),
+  )
+  .await?;
+  record_collab_member_audit_log(
+    &state,
+    &user_uuid,
+    AuditAction::CollabMemberUpdated,
+    &payload,
+  )
+  .await;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn record_collab_member_audit_log(
+  state: &AppState,
+  user_uuid: &Uuid,
+  action: AuditAction,
+  params: &InsertCollabMemberParams,
+) {
+  let actor_uid = state.user_cache.get_user_uid(user_uuid).await.ok();
+  record_audit_log(
+    &state.pg_pool,
+    Uuid::parse_str(&params.workspace_id).ok().as_ref(),
+    actor_uid,
+    action,
+    Some(&params.object_id),
+    serde_json::json!({
+      "uid": params.group_id.is_none().then_some(params.uid),
+      "group_id": params.group_id,
+      "access_level": params.access_level,
+    }),
+  )
+  .await;
+}
+
+#[instrument(level = "debug", skip(state, payload), err)]
+async fn get_collab_member_handler(
+  payload: Json<CollabMemberIdentify>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<AFCollabMember>>> {
+  let payload = payload.into_inner();
+  let member = biz::collab::ops::get_collab_member(&state.pg_pool, &payload).await?;
+  Ok(Json(AppResponse::Ok().with_data(member)))
+}
+
+#[instrument(skip(state, payload), err)]
 async fn remove_collab_member_handler(
+  user_uuid: UserUuid,
   payload: Json<CollabMemberIdentify>,
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<()>>> {
   let payload = payload.into_inner();
-  biz::collab::ops::delete_collab_member(&state.pg_pool, &payload, &state.collab_access_control)
-    .await?;
+  biz::collab::ops::delete_collab_member(
+    &state.pg_pool,
+    &payload,
+    &state.collab_access_control,
+    false,
+  )
+  .await?;
+  let actor_uid = state.user_cache.get_user_uid(&user_uuid).await.ok();
+  record_audit_log(
+    &state.pg_pool,
+    Uuid::parse_str(&payload.workspace_id).ok().as_ref(),
+    actor_uid,
+    AuditAction::CollabMemberRemoved,
+    Some(&payload.object_id),
+    serde_json::json!({ "uid": payload.uid }),
+  )
+  .await;
+
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn suspend_collab_member_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<CollabMemberIdentify>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  // The access control of the request covers the object of the path, not the one of the body
+  let params = CollabMemberIdentify {
+    uid: payload.uid,
+    workspace_id: workspace_id.to_string(),
+    object_id,
+  };
+  biz::collab::ops::suspend_collab_member(
+    &state.pg_pool,
+    uid,
+    &params,
+    &state.collab_access_control,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn unsuspend_collab_member_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<CollabMemberIdentify>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  // The access control of the request covers the object of the path, not the one of the body
+  let params = CollabMemberIdentify {
+    uid: payload.uid,
+    workspace_id: workspace_id.to_string(),
+    object_id,
+  };
+  biz::collab::ops::unsuspend_collab_member(
+    &state.pg_pool,
+    uid,
+    &params,
+    &state.collab_access_control,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn put_publish_namespace_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<UpdatePublishNamespace>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let workspace_id = workspace_id.into_inner();
+  let new_namespace = payload.into_inner().new_namespace;
+  biz::workspace::publish::set_workspace_namespace(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+    &new_namespace,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn put_published_view_nav_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<UpdatePublishedViewNav>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  biz::workspace::publish::set_published_view_show_in_nav(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+    &view_id,
+    payload.into_inner().show_in_nav,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn put_published_view_expiry_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<UpdatePublishedViewExpiry>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  biz::workspace::publish::set_published_view_expiry(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+    &view_id,
+    payload.into_inner().expires_at,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn put_published_view_password_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<UpdatePublishedViewPassword>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  biz::workspace::publish_access::set_published_view_password(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+    &view_id,
+    payload.into_inner().password,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+/// Exchange the password of a published view for the access token carried in the
+/// [X_PUBLISH_ACCESS_TOKEN] header when reading its content.
+async fn post_published_view_access_handler(
+  req: HttpRequest,
+  path_param: web::Path<(String, String)>,
+  payload: Json<PublishedViewAccessParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishedViewAccessToken>>> {
+  let (publish_namespace, publish_name) = path_param.into_inner();
+  let publish_namespace = resolve_publish_namespace(&req, &state, publish_namespace).await?;
+  let ip = client_ip(&req, &state.config.application.trusted_proxies);
+  if let Some(retry_after) = state
+    .rate_limiter
+    .check_published_view_password(&publish_namespace, &publish_name, ip)
+    .await
+  {
+    return Err(
+      AppError::TooManyRequests(format!(
+        "Too many password attempts, try again in {} seconds",
+        retry_after
+      ))
+      .into(),
+    );
+  }
+  let token = biz::workspace::publish_access::create_published_view_access_token(
+    &state.pg_pool,
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &publish_namespace,
+    &publish_name,
+    &payload.password,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(token)))
+}
+
+async fn get_published_database_form_settings_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishedDatabaseFormSettings>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let settings = biz::workspace::publish_form::get_published_database_form_settings(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(settings)))
+}
+
+async fn put_published_database_form_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<SetPublishedDatabaseFormParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishedDatabaseFormSettings>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let settings = biz::workspace::publish_form::set_published_database_form(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    &state.config.published_collab,
+    &user_uuid,
+    uid,
+    &workspace_id,
+    &view_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(settings)))
+}
+
+async fn delete_published_database_form_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  biz::workspace::publish_form::delete_published_database_form(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn get_published_database_form_handler(
+  req: HttpRequest,
+  path_param: web::Path<(String, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishedDatabaseForm>>> {
+  let (publish_namespace, publish_name) = path_param.into_inner();
+  let publish_namespace = resolve_publish_namespace(&req, &state, publish_namespace).await?;
+  biz::workspace::publish_access::check_published_view_access_by_name(
+    &state.pg_pool,
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &publish_namespace,
+    &publish_name,
+    publish_access_token_from_header(&req),
+  )
+  .await?;
+  let form = biz::workspace::publish_form::get_published_database_form(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    &publish_namespace,
+    &publish_name,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(form)))
+}
+
+/// Add a row to the published database through its form. No login is required, the submissions
+/// are bounded by the captcha and the limits of the form.
+async fn post_published_database_form_handler(
+  req: HttpRequest,
+  path_param: web::Path<(String, String)>,
+  payload: Json<SubmitPublishedDatabaseForm>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishedDatabaseFormSubmission>>> {
+  let (publish_namespace, publish_name) = path_param.into_inner();
+  let publish_namespace = resolve_publish_namespace(&req, &state, publish_namespace).await?;
+  biz::workspace::publish_access::check_published_view_access_by_name(
+    &state.pg_pool,
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &publish_namespace,
+    &publish_name,
+    publish_access_token_from_header(&req),
+  )
+  .await?;
+  let ip = client_ip(&req, &state.config.application.trusted_proxies).map(|ip| ip.to_string());
+  let submission = biz::workspace::publish_form::submit_published_database_form(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    &state.config.published_collab,
+    &publish_namespace,
+    &publish_name,
+    ip.as_deref(),
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(submission)))
+}
+
+async fn post_publish_custom_domain_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<RegisterPublishCustomDomainParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishCustomDomain>>> {
+  let domain = biz::workspace::publish_domain::register_publish_custom_domain(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+    &payload.domain,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(domain)))
+}
+
+async fn list_publish_custom_domains_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<PublishCustomDomain>>>> {
+  let domains = biz::workspace::publish_domain::list_publish_custom_domains(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(domains)))
+}
+
+async fn verify_publish_custom_domain_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishCustomDomain>>> {
+  let (workspace_id, domain) = path.into_inner();
+  let domain = biz::workspace::publish_domain::verify_publish_custom_domain(
+    &state.pg_pool,
+    &state.config.published_collab.dns_over_https_url,
+    &user_uuid,
+    &workspace_id,
+    &domain,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(domain)))
+}
+
+async fn delete_publish_custom_domain_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, domain) = path.into_inner();
+  biz::workspace::publish_domain::unregister_publish_custom_domain(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+    &domain,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+/// The publish namespace a request is served from. Requests made to a verified custom domain are
+/// always served from the namespace the domain is mapped to, whatever the namespace in the path.
+async fn resolve_publish_namespace(
+  req: &HttpRequest,
+  state: &AppState,
+  path_namespace: String,
+) -> Result<String, AppError> {
+  let host = req.connection_info().host().to_string();
+  let namespace =
+    biz::workspace::publish_domain::publish_namespace_for_host(&state.pg_pool, &host).await?;
+  Ok(namespace.unwrap_or(path_namespace))
+}
+
+/// The namespace a request is served from, and the url the pages of the published site are served
+/// under. The pages of a site served on a custom domain are at the root of the domain.
+async fn resolve_published_site(
+  req: &HttpRequest,
+  state: &AppState,
+  path_namespace: String,
+) -> Result<(String, String), AppError> {
+  let (scheme, host) = {
+    let conn = req.connection_info();
+    (conn.scheme().to_string(), conn.host().to_string())
+  };
+  match biz::workspace::publish_domain::publish_namespace_for_host(&state.pg_pool, &host).await? {
+    Some(namespace) => Ok((namespace, format!("{}://{}", scheme, host))),
+    None => {
+      let web_url = match state.config.appflowy_web_url.as_deref() {
+        Some(web_url) => web_url.trim_end_matches('/').to_string(),
+        None => format!("{}://{}", scheme, host),
+      };
+      let site_url = format!("{}/{}", web_url, path_namespace);
+      Ok((path_namespace, site_url))
+    },
+  }
+}
+
+async fn get_published_sitemap_handler(
+  req: HttpRequest,
+  publish_namespace: web::Path<String>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let (publish_namespace, site_url) =
+    resolve_published_site(&req, &state, publish_namespace.into_inner()).await?;
+  let sitemap = biz::workspace::publish_site::get_published_sitemap(
+    &state.pg_pool,
+    &publish_namespace,
+    &site_url,
+  )
+  .await?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("application/xml; charset=utf-8")
+      .body(sitemap),
+  )
+}
+
+async fn get_published_feed_handler(
+  req: HttpRequest,
+  publish_namespace: web::Path<String>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let (publish_namespace, site_url) =
+    resolve_published_site(&req, &state, publish_namespace.into_inner()).await?;
+  let feed =
+    biz::workspace::publish_site::get_published_feed(&state.pg_pool, &publish_namespace, &site_url)
+      .await?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("application/atom+xml; charset=utf-8")
+      .body(feed),
+  )
+}
+
+fn publish_access_token_from_header(req: &HttpRequest) -> Option<&str> {
+  req
+    .headers()
+    .get(X_PUBLISH_ACCESS_TOKEN)
+    .and_then(|value| value.to_str().ok())
+}
+
+async fn get_publish_analytics_handler(
+  workspace_id: web::Path<Uuid>,
+  query: web::Query<QueryPublishAnalytics>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishAnalytics>>> {
+  let analytics = biz::workspace::publish_analytics::get_publish_analytics(
+    &state.pg_pool,
+    &workspace_id,
+    query.into_inner().days,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(analytics)))
+}
+
+async fn get_publish_namespace_handler(
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<String>>> {
+  let workspace_id = workspace_id.into_inner();
+  let namespace =
+    biz::workspace::publish::get_workspace_publish_namespace(&state.pg_pool, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(namespace)))
+}
+
+async fn get_published_collab_handler(
+  req: HttpRequest,
+  path_param: web::Path<(String, String)>,
+  state: Data<AppState>,
+) -> Result<Json<serde_json::Value>> {
+  let (workspace_namespace, publish_name) = path_param.into_inner();
+  let workspace_namespace = resolve_publish_namespace(&req, &state, workspace_namespace).await?;
+  biz::workspace::publish_access::check_published_view_access_by_name(
+    &state.pg_pool,
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &workspace_namespace,
+    &publish_name,
+    publish_access_token_from_header(&req),
+  )
+  .await?;
+  let metadata = state
+    .published_collab_store
+    .get_collab_metadata(&workspace_namespace, &publish_name)
+    .await?;
+  record_published_view_hit(&req, &state, workspace_namespace, publish_name);
+  Ok(Json(metadata))
+}
+
+async fn get_published_collab_blob_handler(
+  req: HttpRequest,
+  path_param: web::Path<(String, String)>,
+  state: Data<AppState>,
+) -> Result<Vec<u8>> {
+  let (publish_namespace, publish_name) = path_param.into_inner();
+  let publish_namespace = resolve_publish_namespace(&req, &state, publish_namespace).await?;
+  biz::workspace::publish_access::check_published_view_access_by_name(
+    &state.pg_pool,
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &publish_namespace,
+    &publish_name,
+    publish_access_token_from_header(&req),
+  )
+  .await?;
+  let collab_data = state
+    .published_collab_store
+    .get_collab_blob_by_publish_namespace(&publish_namespace, &publish_name)
+    .await?;
+  Ok(collab_data)
+}
+
+async fn get_published_html_handler(
+  req: HttpRequest,
+  path_param: web::Path<(String, String)>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let html = render_published_view(
+    &req,
+    path_param.into_inner(),
+    &state,
+    PublishedRenderFormat::Html,
+  )
+  .await?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("text/html; charset=utf-8")
+      .body(html),
+  )
+}
+
+async fn get_published_markdown_handler(
+  req: HttpRequest,
+  path_param: web::Path<(String, String)>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let markdown = render_published_view(
+    &req,
+    path_param.into_inner(),
+    &state,
+    PublishedRenderFormat::Markdown,
+  )
+  .await?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("text/markdown; charset=utf-8")
+      .body(markdown),
+  )
+}
+
+async fn render_published_view(
+  req: &HttpRequest,
+  (publish_namespace, publish_name): (String, String),
+  state: &AppState,
+  format: PublishedRenderFormat,
+) -> Result<String, AppError> {
+  let publish_namespace = resolve_publish_namespace(req, state, publish_namespace).await?;
+  biz::workspace::publish_access::check_published_view_access_by_name(
+    &state.pg_pool,
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &publish_namespace,
+    &publish_name,
+    publish_access_token_from_header(req),
+  )
+  .await?;
+  let rendered = biz::workspace::publish_render::render_published_document(
+    &state.pg_pool,
+    &state.redis_connection_manager,
+    &publish_namespace,
+    &publish_name,
+    format,
+  )
+  .await?;
+  record_published_view_hit(req, state, publish_namespace, publish_name);
+  Ok(rendered)
+}
+
+/// Count the read of the published view in the background, so that counting does not slow down
+/// the read. Links between the pages of the published site are not counted as referrers.
+fn record_published_view_hit(
+  req: &HttpRequest,
+  state: &AppState,
+  publish_namespace: String,
+  publish_name: String,
+) {
+  let request_host = req.connection_info().host().to_string();
+  let web_host = state
+    .config
+    .appflowy_web_url
+    .as_deref()
+    .and_then(|url| url::Url::parse(url).ok())
+    .and_then(|url| url.host_str().map(|host| host.to_string()));
+  let mut own_hosts = vec![request_host.as_str()];
+  own_hosts.extend(web_host.as_deref());
+  let referrer = req
+    .headers()
+    .get(actix_web::http::header::REFERER)
+    .and_then(|referrer| referrer.to_str().ok());
+  let referrer_host = biz::workspace::publish_analytics::referrer_host(referrer, &own_hosts);
+  state
+    .published_view_hits
+    .record(publish_namespace, publish_name, referrer_host);
+}
+
+async fn post_published_duplicate_handler(
+  req: HttpRequest,
+  user_uuid: UserUuid,
+  workspace_id: web::Path<String>,
+  state: Data<AppState>,
+  params: Json<PublishedDuplicate>,
+) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let params = params.into_inner();
+  biz::workspace::publish_access::check_published_view_access(
+    &state.pg_pool,
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &params.published_view_id.parse()?,
+    publish_access_token_from_header(&req),
+  )
+  .await?;
+  biz::workspace::publish_dup::duplicate_published_collab_to_workspace(
+    &state.pg_pool,
+    state.bucket_client.clone(),
+    state.collab_access_control_storage.clone(),
+    uid,
+    params.published_view_id,
+    workspace_id.into_inner(),
+    params.dest_view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn post_instantiate_template_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+  params: Json<InstantiateTemplateParams>,
+) -> Result<Json<AppResponse<InstantiatedTemplate>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let view_id = biz::template::ops::instantiate_template(
+    &state.pg_pool,
+    state.bucket_client.clone(),
+    state.collab_access_control_storage.clone(),
+    uid,
+    workspace_id,
+    view_id,
+    params.into_inner().dest_view_id,
+  )
+  .await?;
+  Ok(Json(
+    AppResponse::Ok().with_data(InstantiatedTemplate { view_id }),
+  ))
+}
+
+async fn get_published_collab_info_handler(
+  view_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishInfo>>> {
+  let view_id = view_id.into_inner();
+  let collab_data = state
+    .published_collab_store
+    .get_collab_publish_info(&view_id)
+    .await?;
+  Ok(Json(AppResponse::Ok().with_data(collab_data)))
+}
+
+async fn get_published_collab_comment_handler(
+  req: HttpRequest,
+  view_id: web::Path<Uuid>,
+  optional_user_uuid: OptionalUserUuid,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<GlobalComments>> {
+  let view_id = view_id.into_inner();
+  biz::workspace::publish_access::check_published_view_access(
+    &state.pg_pool,
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &view_id,
+    publish_access_token_from_header(&req),
+  )
+  .await?;
+  let comments =
+    get_comments_on_published_view(&state.pg_pool, &view_id, &optional_user_uuid).await?;
+  let resp = GlobalComments { comments };
+  Ok(Json(AppResponse::Ok().with_data(resp)))
+}
+
+async fn post_published_collab_comment_handler(
+  req: HttpRequest,
+  user_uuid: UserUuid,
+  view_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  data: Json<CreateGlobalCommentParams>,
+) -> Result<JsonAppResponse<()>> {
+  let view_id = view_id.into_inner();
+  biz::workspace::publish_access::check_published_view_access(
+    &state.pg_pool,
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &view_id,
+    publish_access_token_from_header(&req),
+  )
+  .await?;
+  create_comment_on_published_view(
+    &state.pg_pool,
+    &view_id,
+    &data.reply_comment_id,
+    &data.content,
+    &user_uuid,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn delete_published_collab_comment_handler(
+  req: HttpRequest,
+  user_uuid: UserUuid,
+  view_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  data: Json<DeleteGlobalCommentParams>,
+) -> Result<JsonAppResponse<()>> {
+  let view_id = view_id.into_inner();
+  biz::workspace::publish_access::check_published_view_access(
+    &state.pg_pool,
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &view_id,
+    publish_access_token_from_header(&req),
+  )
+  .await?;
+  remove_comment_on_published_view(&state.pg_pool, &view_id, &data.comment_id, &user_uuid).await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn get_published_collab_reaction_handler(
+  req: HttpRequest,
+  view_id: web::Path<Uuid>,
+  query: web::Query<GetReactionQueryParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<Reactions>> {
+  let view_id = view_id.into_inner();
+  biz::workspace::publish_access::check_published_view_access(
+    &state.pg_pool,
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &view_id,
+    publish_access_token_from_header(&req),
+  )
+  .await?;
+  let reactions =
+    get_reactions_on_published_view(&state.pg_pool, &view_id, &query.comment_id).await?;
+  let resp = Reactions { reactions };
+  Ok(Json(AppResponse::Ok().with_data(resp)))
+}
+
+async fn post_published_collab_reaction_handler(
+  req: HttpRequest,
+  user_uuid: UserUuid,
+  view_id: web::Path<Uuid>,
+  data: Json<CreateReactionParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let view_id = view_id.into_inner();
+  biz::workspace::publish_access::check_published_view_access(
+    &state.pg_pool,
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &view_id,
+    publish_access_token_from_header(&req),
+  )
+  .await?;
+  create_reaction_on_comment(
+    &state.pg_pool,
+    &data.comment_id,
+    &view_id,
+    &data.reaction_type,
+    &user_uuid,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn delete_published_collab_reaction_handler(
+  req: HttpRequest,
+  user_uuid: UserUuid,
+  view_id: web::Path<Uuid>,
+  data: Json<DeleteReactionParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let view_id = view_id.into_inner();
+  biz::workspace::publish_access::check_published_view_access(
+    &state.pg_pool,
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &view_id,
+    publish_access_token_from_header(&req),
+  )
+  .await?;
+  remove_reaction_on_comment(
+    &state.pg_pool,
+    &data.comment_id,
+    &data.reaction_type,
+    &user_uuid,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+/// Reads the collabs to publish from the payload. Each collab is sent as the length of its
+/// metadata, the metadata, the length of its data and the data, and a zero length ends the list.
+async fn read_publish_collab_items(
+  payload: Payload,
+) -> Result<Vec<PublishCollabItem<serde_json::Value, Vec<u8>>>> {
+  let mut accumulator = Vec::<PublishCollabItem<serde_json::Value, Vec<u8>>>::new();
+  let mut payload_reader: PayloadReader = PayloadReader::new(payload);
+
+  loop {
+    let meta: PublishCollabMetadata<serde_json::Value> = {
+      let meta_len = payload_reader.read_u32_little_endian().await?;
+      if meta_len > 4 * 1024 * 1024 {
+        // 4MiB Limit for metadata
+        return Err(AppError::InvalidRequest(String::from("metadata too large")).into());
+      }
+      if meta_len == 0 {
+        break;
+      }
+
+      let mut meta_buffer = vec![0; meta_len as usize];
+      payload_reader.read_exact(&mut meta_buffer).await?;
+      serde_json::from_slice(&meta_buffer)?
+    };
+
+    let data = {
+      let data_len = payload_reader.read_u32_little_endian().await?;
+      if data_len > 32 * 1024 * 1024 {
+        // 32MiB Limit for data
+        return Err(AppError::InvalidRequest(String::from("data too large")).into());
+      }
+      let mut data_buffer = vec![0; data_len as usize];
+      payload_reader.read_exact(&mut data_buffer).await?;
+      data_buffer
+    };
+
+    accumulator.push(PublishCollabItem { meta, data });
+  }
+
+  Ok(accumulator)
+}
+
+async fn post_publish_collabs_handler(
+  workspace_id: web::Path<Uuid>,
+  user_uuid: UserUuid,
+  payload: Payload,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let workspace_id = workspace_id.into_inner();
+  let accumulator = read_publish_collab_items(payload).await?;
+
+  if accumulator.is_empty() {
+    return Err(
+      AppError::InvalidRequest(String::from("did not receive any data to publish")).into(),
+    );
+  }
+  let published: Vec<_> = accumulator
+    .iter()
+    .map(|item| (item.meta.view_id, item.meta.publish_name.clone()))
+    .collect();
+  state
+    .published_collab_store
+    .publish_collabs(accumulator, &workspace_id, &user_uuid)
+    .await?;
+  let uid = state.user_cache.get_user_uid(&user_uuid).await.ok();
+  for (view_id, publish_name) in published {
+    record_audit_log(
+      &state.pg_pool,
+      Some(&workspace_id),
+      uid,
+      AuditAction::ViewPublished,
+      Some(&view_id.to_string()),
+      serde_json::json!({ "publish_name": publish_name }),
+    )
+    .await;
+    biz::workspace::webhook::enqueue_webhook_event_or_log(
+      &state.pg_pool,
+      &workspace_id,
+      WebhookEvent::ViewPublished,
+      serde_json::json!({ "view_id": view_id, "publish_name": publish_name }),
+    )
+    .await;
+  }
+  Ok(Json(AppResponse::Ok()))
+}
+
+/// Publish a single collab, unless it has not changed since it was last published.
+async fn post_publish_collab_if_changed_handler(
+  workspace_id: web::Path<Uuid>,
+  user_uuid: UserUuid,
+  query: web::Query<CollabTypeParam>,
+  payload: Payload,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishOutcome>>> {
+  let workspace_id = workspace_id.into_inner();
+  let mut items = read_publish_collab_items(payload).await?;
+  if items.len() != 1 {
+    return Err(
+      AppError::InvalidRequest(String::from("expected exactly one collab to publish")).into(),
+    );
+  }
+  let item = items.remove(0);
+  let view_id = item.meta.view_id;
+  let publish_name = item.meta.publish_name.clone();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let outcome = biz::workspace::publish::publish_if_changed(
+    state.published_collab_store.as_ref(),
+    state.collab_access_control_storage.clone(),
+    &state.workspace_access_control,
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    &user_uuid,
+    query.into_inner().collab_type,
+    item,
+  )
+  .await?;
+  if outcome == PublishOutcome::Published {
+    record_audit_log(
+      &state.pg_pool,
+      Some(&workspace_id),
+      Some(uid),
+      AuditAction::ViewPublished,
+      Some(&view_id.to_string()),
+      serde_json::json!({ "publish_name": publish_name }),
+    )
+    .await;
+  }
+  Ok(Json(AppResponse::Ok().with_data(outcome)))
+}
+
+async fn delete_published_collabs_handler(
+  workspace_id: web::Path<Uuid>,
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  view_ids: Json<Vec<Uuid>>,
+) -> Result<Json<AppResponse<()>>> {
+  let workspace_id = workspace_id.into_inner();
+  let view_ids = view_ids.into_inner();
+  if view_ids.is_empty() {
+    return Ok(Json(AppResponse::Ok()));
+  }
+  state
+    .published_collab_store
+    .delete_collab(&workspace_id, &view_ids, &user_uuid)
+    .await?;
+  let uid = state.user_cache.get_user_uid(&user_uuid).await.ok();
+  for view_id in view_ids {
+    record_audit_log(
+      &state.pg_pool,
+      Some(&workspace_id),
+      uid,
+      AuditAction::ViewUnpublished,
+      Some(&view_id.to_string()),
+      serde_json::json!({}),
+    )
+    .await;
+    biz::workspace::webhook::enqueue_webhook_event_or_log(
+      &state.pg_pool,
+      &workspace_id,
+      WebhookEvent::ViewUnpublished,
+      serde_json::json!({ "view_id": view_id }),
+    )
+    .await;
+  }
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn get_sharing_state_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<SharingState>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let sharing_state = biz::collab::sharing::get_sharing_state(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    &state.collab_access_control,
+    uid,
+    &workspace_id,
+    &object_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(sharing_state)))
+}
+
+async fn get_collab_comments_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  query: web::Query<QueryCollabComments>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabComments>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let comments = biz::collab::comment::get_collab_comments(
+    &state.pg_pool,
+    &state.collab_access_control,
+    uid,
+    &workspace_id,
+    &object_id,
+    query.block_id.as_deref(),
+  )
+  .await?;
+  Ok(Json(
+    AppResponse::Ok().with_data(CollabComments { comments }),
+  ))
+}
+
+async fn delta_sync_collab_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<CollabDeltaSyncParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabDeltaSyncResponse>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let response = biz::collab::delta_sync::delta_sync_collab(
+    state.collab_access_control_storage.clone(),
+    &state.collab_access_control,
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &object_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(response)))
+}
+
+async fn get_collab_presences_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabPresences>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let presences = biz::collab::presence::get_collab_presences(
+    &state.realtime_shared_state,
+    &state.collab_access_control,
+    uid,
+    &workspace_id,
+    &object_id,
+  )
+  .await?;
+  Ok(Json(
+    AppResponse::Ok().with_data(CollabPresences { presences }),
+  ))
+}
+
+async fn get_collab_contributors_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabContributors>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let contributors = biz::collab::contributor::get_collab_contributors(
+    &state.pg_pool,
+    &state.collab_access_control,
+    uid,
+    &workspace_id,
+    &object_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(contributors)))
+}
+
+async fn create_collab_comment_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<CreateCollabCommentParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabComment>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let comment = biz::collab::comment::create_collab_comment(
+    &state.pg_pool,
+    &state.collab_access_control,
+    uid,
+    &workspace_id,
+    &object_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(comment)))
+}
+
+async fn resolve_collab_comment_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String, Uuid)>,
+  payload: Json<ResolveCollabCommentParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabComment>>> {
+  let (workspace_id, object_id, comment_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let comment = biz::collab::comment::resolve_collab_comment(
+    &state.pg_pool,
+    &state.collab_access_control,
+    uid,
+    &workspace_id,
+    &object_id,
+    &comment_id,
+    payload.resolved,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(comment)))
+}
+
+async fn delete_collab_comment_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, object_id, comment_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  biz::collab::comment::remove_collab_comment(
+    &state.pg_pool,
+    &state.collab_access_control,
+    uid,
+    &workspace_id,
+    &object_id,
+    &comment_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn get_effective_access_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  query: web::Query<QueryEffectiveAccess>,
+  req: HttpRequest,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<EffectiveAccess>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let caller_uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let uid = query.uid.unwrap_or(caller_uid);
+  // Only the workspace owner can look into the access of other users
+  if uid != caller_uid {
+    biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  }
+  let object_token = req
+    .headers()
+    .get(X_OBJECT_TOKEN)
+    .and_then(|value| value.to_str().ok());
+  let access = biz::collab::effective_access::resolve_effective_access(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    &object_id,
+    object_token,
+    query.explain,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(access)))
+}
+
+async fn check_membership_handler(
+  user_uuid: UserUuid,
+  _workspace_id: web::Path<Uuid>,
+  payload: Json<CheckMembershipParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<HashMap<String, Option<AFAccessLevel>>>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let levels =
+    biz::collab::ops::check_membership(&state.pg_pool, uid, &payload.into_inner().object_ids)
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(levels)))
+}
+
+async fn export_workspace_collab_members_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  query: web::Query<QueryCollabMembersExport>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabMembersExport>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let export = biz::collab::ops::export_workspace_collab_members(
+    &state.pg_pool,
+    &workspace_id,
+    query.into_inner().access_level_filter,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(export)))
+}
+
+async fn get_share_preview_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  query: web::Query<QuerySharePreview>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<ShareImpact>>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let query = query.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let impacts = biz::collab::sharing::preview_share_impact(
+    state.collab_access_control_storage.clone(),
+    &state.collab_access_control,
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &object_id,
+    query.uid,
+    query.access_level,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(impacts)))
+}
+
+async fn create_object_token_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<CreateCollabObjectTokenParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabObjectToken>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let params = payload.into_inner();
+  let token = biz::collab::object_token::create_object_token(
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &object_id,
+    params.access_level,
+    params.expires_at,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(token)))
+}
+
+async fn revoke_object_token_handler(
+  path: web::Path<(Uuid, String, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (_workspace_id, object_id, token_id) = path.into_inner();
+  biz::collab::object_token::revoke_object_token(&state.pg_pool, &object_id, &token_id).await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn list_share_links_handler(
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<CollabShareLink>>>> {
+  let (_workspace_id, object_id) = path.into_inner();
+  let links = biz::collab::share_link::list_share_links(&state.pg_pool, &object_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(links)))
+}
+
+async fn create_share_link_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<CreateShareLinkParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabShareLink>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let link = biz::collab::share_link::create_share_link(
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &object_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(link)))
+}
+
+async fn update_share_link_handler(
+  path: web::Path<(Uuid, String, Uuid)>,
+  payload: Json<UpdateShareLinkParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabShareLink>>> {
+  let (_workspace_id, object_id, link_id) = path.into_inner();
+  let link = biz::collab::share_link::update_share_link(
+    &state.pg_pool,
+    &state.collab_access_control,
+    &object_id,
+    &link_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(link)))
+}
+
+async fn delete_share_link_handler(
+  path: web::Path<(Uuid, String, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (_workspace_id, object_id, link_id) = path.into_inner();
+  biz::collab::share_link::delete_share_link(
+    &state.pg_pool,
+    &state.collab_access_control,
+    &object_id,
+    &link_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+/// Issue a new token for the share link. The old token stops working immediately.
+async fn rotate_share_link_handler(
+  path: web::Path<(Uuid, String, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabShareLink>>> {
+  let (_workspace_id, object_id, link_id) = path.into_inner();
+  let link =
+    biz::collab::share_link::rotate_share_link(&state.pg_pool, &object_id, &link_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(link)))
+}
+
+/// Revoke every share link of the collab.
+async fn delete_all_share_links_handler(
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (_workspace_id, object_id) = path.into_inner();
+  biz::collab::share_link::delete_all_share_links(
+    &state.pg_pool,
+    &state.collab_access_control,
+    &object_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+/// The share links of the workspace that can still be used, for the owner of the workspace.
+async fn list_live_workspace_share_links_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<CollabShareLink>>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let links =
+    biz::collab::share_link::list_live_workspace_share_links(&state.pg_pool, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(links)))
+}
+
+/// Stop the collab from inheriting the access granted on its ancestor views.
+async fn break_collab_inheritance_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  biz::collab::inheritance::break_collab_inheritance(
+    &state.pg_pool,
+    &state.access_control,
+    uid,
+    &workspace_id,
+    &object_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn restore_collab_inheritance_handler(
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  biz::collab::inheritance::restore_collab_inheritance(
+    &state.pg_pool,
+    &state.access_control,
+    &workspace_id,
+    &object_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn remove_collab_group_access_handler(
+  path: web::Path<(Uuid, String, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, object_id, group_id) = path.into_inner();
+  biz::workspace::group::remove_collab_group_access(
+    &state.pg_pool,
+    &state.collab_access_control,
+    &workspace_id,
+    &group_id,
+    &object_id,
+    false,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn list_workspace_groups_handler(
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<WorkspaceGroup>>>> {
+  let groups =
+    biz::workspace::group::list_workspace_groups(&state.pg_pool, &workspace_id.into_inner())
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(groups)))
+}
+
+async fn create_workspace_group_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<CreateWorkspaceGroupParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceGroup>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let group = biz::workspace::group::create_workspace_group(
+    &state.pg_pool,
+    &workspace_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(group)))
+}
+
+async fn delete_workspace_group_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, group_id) = path.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  biz::workspace::group::delete_workspace_group(
+    &state.pg_pool,
+    &state.collab_access_control,
+    &workspace_id,
+    &group_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn add_workspace_group_member_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<AddWorkspaceGroupMemberParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, group_id) = path.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  biz::workspace::group::add_workspace_group_member(
+    &state.pg_pool,
+    &state.collab_access_control,
+    &workspace_id,
+    &group_id,
+    payload.uid,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn remove_workspace_group_member_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, i64)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, group_id, uid) = path.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  biz::workspace::group::remove_workspace_group_member(
+    &state.pg_pool,
+    &state.collab_access_control,
+    &workspace_id,
+    &group_id,
+    uid,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn list_workspace_roles_handler(
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<WorkspaceRole>>>> {
+  let roles =
+    biz::workspace::role::list_workspace_roles(&state.pg_pool, &workspace_id.into_inner()).await?;
+  Ok(Json(AppResponse::Ok().with_data(roles)))
+}
+
+async fn create_workspace_role_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<CreateWorkspaceRoleParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceRole>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let role = biz::workspace::role::create_workspace_role(
+    &state.pg_pool,
+    &workspace_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(role)))
+}
+
+async fn update_workspace_role_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<UpdateWorkspaceRoleParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceRole>>> {
+  let (workspace_id, role_id) = path.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let role = biz::workspace::role::update_workspace_role(
+    &state.pg_pool,
+    &state.workspace_access_control,
+    &workspace_id,
+    &role_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(role)))
+}
+
+async fn delete_workspace_role_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, role_id) = path.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  biz::workspace::role::delete_workspace_role(
+    &state.pg_pool,
+    &state.workspace_access_control,
+    &workspace_id,
+    &role_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn assign_workspace_role_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<AssignWorkspaceRoleParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, role_id) = path.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  biz::workspace::role::assign_workspace_role(
+    &state.pg_pool,
+    &state.workspace_access_control,
+    &workspace_id,
+    &role_id,
+    payload.uid,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn unassign_workspace_role_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, i64)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, role_id, uid) = path.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  biz::workspace::role::unassign_workspace_role(
+    &state.pg_pool,
+    &state.workspace_access_control,
+    &workspace_id,
+    &role_id,
+    uid,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+/// The capabilities the caller holds in the workspace.
+async fn get_workspace_capabilities_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<WorkspaceCapability>>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let capabilities = biz::workspace::role::get_member_capabilities(
+    &state.workspace_access_control,
+    &workspace_id.into_inner(),
+    uid,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(capabilities)))
+}
+
+async fn request_workspace_ownership_transfer_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<TransferWorkspaceOwnershipParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceOwnershipTransferRequest>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let transfer = biz::workspace::ownership_transfer::request_workspace_ownership_transfer(
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(transfer)))
+}
+
+async fn get_workspace_ownership_transfer_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceOwnershipTransferRequest>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let transfer = biz::workspace::ownership_transfer::get_workspace_ownership_transfer(
+    &state.pg_pool,
+    uid,
+    &workspace_id.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(transfer)))
+}
+
+async fn cancel_workspace_ownership_transfer_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  biz::workspace::ownership_transfer::cancel_workspace_ownership_transfer(
+    &state.pg_pool,
+    uid,
+    &workspace_id.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn accept_workspace_ownership_transfer_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  biz::workspace::ownership_transfer::accept_workspace_ownership_transfer(
+    &state.pg_pool,
+    &state.workspace_access_control,
+    uid,
+    &workspace_id.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn get_workspace_export_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let archive = biz::workspace::export::export_workspace(
+    &state.pg_pool,
+    state.collab_access_control_storage.clone(),
+    uid,
+    &workspace_id,
+  )
+  .await?;
+  Ok(zip_archive_response(archive))
+}
+
+async fn post_workspace_export_task_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceExportTask>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let workspace_id = workspace_id.into_inner();
+  let task = biz::workspace::export::start_workspace_export(
+    &state.pg_pool,
+    state.collab_access_control_storage.clone(),
+    state.bucket_client.clone(),
+    uid,
+    workspace_id,
+  )
+  .await?;
+  record_audit_log(
+    &state.pg_pool,
+    Some(&workspace_id),
+    Some(uid),
+    AuditAction::WorkspaceExported,
+    Some(&task.task_id.to_string()),
+    serde_json::json!({}),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok().with_data(task)))
+}
+
+async fn get_workspace_export_task_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceExportTask>>> {
+  let (workspace_id, task_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let task =
+    biz::workspace::export::get_workspace_export_task(&state.pg_pool, uid, &workspace_id, &task_id)
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(task)))
+}
+
+async fn get_workspace_export_download_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let (workspace_id, task_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let archive = biz::workspace::export::get_workspace_export_archive(
+    &state.pg_pool,
+    &state.bucket_client,
+    uid,
+    &workspace_id,
+    &task_id,
+  )
+  .await?;
+  Ok(zip_archive_response(archive))
+}
+
+async fn post_notion_import_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  query: web::Query<QueryWorkspaceImport>,
+  payload: Bytes,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceImportTask>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let task = biz::workspace::import::start_notion_import(
+    &state.pg_pool,
+    state.collab_access_control_storage.clone(),
+    uid,
+    workspace_id.into_inner(),
+    query.into_inner().parent_view_id,
+    payload.to_vec(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(task)))
+}
 
-  Ok(Json(AppResponse::Ok()))
+async fn get_workspace_import_task_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceImportTask>>> {
+  let (workspace_id, task_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let task =
+    biz::workspace::import::get_workspace_import_task(&state.pg_pool, uid, &workspace_id, &task_id)
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(task)))
 }
 
-async fn put_publish_namespace_handler(
+async fn post_workspace_duplicate_handler(
   user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
-  payload: Json<UpdatePublishNamespace>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<()>>> {
-  let workspace_id = workspace_id.into_inner();
-  let new_namespace = payload.into_inner().new_namespace;
-  biz::workspace::publish::set_workspace_namespace(
+  payload: Json<DuplicateWorkspaceParams>,
+) -> Result<Json<AppResponse<WorkspaceDuplicateTask>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let task = biz::workspace::duplicate::start_workspace_duplicate(
     &state.pg_pool,
-    &user_uuid,
+    state.collab_access_control_storage.clone(),
+    state.workspace_access_control.clone(),
+    *user_uuid,
+    uid,
+    workspace_id.into_inner(),
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(task)))
+}
+
+async fn get_workspace_duplicate_task_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceDuplicateTask>>> {
+  let (workspace_id, task_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let task = biz::workspace::duplicate::get_workspace_duplicate_task(
+    &state.pg_pool,
+    uid,
     &workspace_id,
-    &new_namespace,
+    &task_id,
   )
   .await?;
-  Ok(Json(AppResponse::Ok()))
+  Ok(Json(AppResponse::Ok().with_data(task)))
 }
 
-async fn get_publish_namespace_handler(
+pub(crate) fn zip_archive_response(
+  archive: biz::workspace::export::WorkspaceArchive,
+) -> HttpResponse {
+  HttpResponse::Ok()
+    .content_type(biz::workspace::export::ZIP_CONTENT_TYPE)
+    .insert_header(actix_web::http::header::ContentDisposition::attachment(
+      archive.file_name,
+    ))
+    .streaming(archive.body)
+}
+
+async fn list_webhooks_handler(
+  user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<String>>> {
+) -> Result<Json<AppResponse<Vec<Webhook>>>> {
   let workspace_id = workspace_id.into_inner();
-  let namespace =
-    biz::workspace::publish::get_workspace_publish_namespace(&state.pg_pool, &workspace_id).await?;
-  Ok(Json(AppResponse::Ok().with_data(namespace)))
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let webhooks = biz::workspace::webhook::list_webhooks(&state.pg_pool, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(webhooks)))
 }
 
-async fn get_published_collab_handler(
-  path_param: web::Path<(String, String)>,
+async fn create_webhook_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<CreateWebhookParams>,
   state: Data<AppState>,
-) -> Result<Json<serde_json::Value>> {
-  let (workspace_namespace, publish_name) = path_param.into_inner();
-  let metadata = state
-    .published_collab_store
-    .get_collab_metadata(&workspace_namespace, &publish_name)
-    .await?;
-  Ok(Json(metadata))
+) -> Result<Json<AppResponse<Webhook>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let webhook =
+    biz::workspace::webhook::create_webhook(&state.pg_pool, &workspace_id, payload.into_inner())
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(webhook)))
 }
 
-async fn get_published_collab_blob_handler(
-  path_param: web::Path<(String, String)>,
+async fn delete_webhook_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
   state: Data<AppState>,
-) -> Result<Vec<u8>> {
-  let (publish_namespace, publish_name) = path_param.into_inner();
-  let collab_data = state
-    .published_collab_store
-    .get_collab_blob_by_publish_namespace(&publish_namespace, &publish_name)
-    .await?;
-  Ok(collab_data)
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, webhook_id) = path.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  biz::workspace::webhook::delete_webhook(&state.pg_pool, &workspace_id, &webhook_id).await?;
+  Ok(Json(AppResponse::Ok()))
 }
 
-async fn post_published_duplicate_handler(
+async fn list_webhook_dead_letters_handler(
   user_uuid: UserUuid,
-  workspace_id: web::Path<String>,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<WebhookDeadLetter>>>> {
+  let (workspace_id, webhook_id) = path.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let dead_letters =
+    biz::workspace::webhook::list_webhook_dead_letters(&state.pg_pool, &workspace_id, &webhook_id)
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(dead_letters)))
+}
+
+async fn retry_webhook_dead_letter_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, i64)>,
   state: Data<AppState>,
-  params: Json<PublishedDuplicate>,
 ) -> Result<Json<AppResponse<()>>> {
-  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
-  let params = params.into_inner();
-  biz::workspace::publish_dup::duplicate_published_collab_to_workspace(
+  let (workspace_id, webhook_id, delivery_id) = path.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  biz::workspace::webhook::retry_webhook_dead_letter(
     &state.pg_pool,
-    state.bucket_client.clone(),
-    state.collab_access_control_storage.clone(),
-    uid,
-    params.published_view_id,
-    workspace_id.into_inner(),
-    params.dest_view_id,
+    &workspace_id,
+    &webhook_id,
+    delivery_id,
   )
   .await?;
   Ok(Json(AppResponse::Ok()))
 }
 
-async fn get_published_collab_info_handler(
-  view_id: web::Path<Uuid>,
+async fn list_service_accounts_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<PublishInfo>>> {
-  let view_id = view_id.into_inner();
-  let collab_data = state
-    .published_collab_store
-    .get_collab_publish_info(&view_id)
-    .await?;
-  Ok(Json(AppResponse::Ok().with_data(collab_data)))
+) -> Result<Json<AppResponse<Vec<ServiceAccount>>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let service_accounts =
+    biz::workspace::service_account::list_service_accounts(&state.pg_pool, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(service_accounts)))
 }
 
-async fn get_published_collab_comment_handler(
-  view_id: web::Path<Uuid>,
-  optional_user_uuid: OptionalUserUuid,
+async fn create_service_account_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<CreateServiceAccountParams>,
   state: Data<AppState>,
-) -> Result<JsonAppResponse<GlobalComments>> {
-  let view_id = view_id.into_inner();
-  let comments =
-    get_comments_on_published_view(&state.pg_pool, &view_id, &optional_user_uuid).await?;
-  let resp = GlobalComments { comments };
-  Ok(Json(AppResponse::Ok().with_data(resp)))
+) -> Result<Json<AppResponse<ServiceAccountApiKey>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let service_account_uid = state.next_user_id().await;
+  let service_account = biz::workspace::service_account::create_service_account(
+    &state.pg_pool,
+    &state.gotrue_client,
+    &state.gotrue_admin,
+    &state.workspace_access_control,
+    uid,
+    service_account_uid,
+    &workspace_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(service_account)))
 }
 
-async fn post_published_collab_comment_handler(
+async fn delete_service_account_handler(
   user_uuid: UserUuid,
-  view_id: web::Path<Uuid>,
+  path: web::Path<(Uuid, Uuid)>,
   state: Data<AppState>,
-  data: Json<CreateGlobalCommentParams>,
-) -> Result<JsonAppResponse<()>> {
-  let view_id = view_id.into_inner();
-  create_comment_on_published_view(
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, service_account_id) = path.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  biz::workspace::service_account::delete_service_account(
     &state.pg_pool,
-    &view_id,
-    &data.reply_comment_id,
-    &data.content,
-    &user_uuid,
+    &state.gotrue_client,
+    &state.gotrue_admin,
+    &state.workspace_access_control,
+    &workspace_id,
+    &service_account_id,
   )
   .await?;
   Ok(Json(AppResponse::Ok()))
 }
 
-async fn delete_published_collab_comment_handler(
+async fn rotate_service_account_api_key_handler(
   user_uuid: UserUuid,
-  view_id: web::Path<Uuid>,
+  path: web::Path<(Uuid, Uuid)>,
   state: Data<AppState>,
-  data: Json<DeleteGlobalCommentParams>,
-) -> Result<JsonAppResponse<()>> {
-  let view_id = view_id.into_inner();
-  remove_comment_on_published_view(&state.pg_pool, &view_id, &data.comment_id, &user_uuid).await?;
-  Ok(Json(AppResponse::Ok()))
+) -> Result<Json<AppResponse<ServiceAccountApiKey>>> {
+  let (workspace_id, service_account_id) = path.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let api_key = biz::workspace::service_account::rotate_service_account_api_key(
+    &state.pg_pool,
+    &workspace_id,
+    &service_account_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(api_key)))
 }
 
-async fn get_published_collab_reaction_handler(
-  view_id: web::Path<Uuid>,
-  query: web::Query<GetReactionQueryParams>,
+async fn revoke_service_account_api_key_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, Uuid)>,
   state: Data<AppState>,
-) -> Result<JsonAppResponse<Reactions>> {
-  let view_id = view_id.into_inner();
-  let reactions =
-    get_reactions_on_published_view(&state.pg_pool, &view_id, &query.comment_id).await?;
-  let resp = Reactions { reactions };
-  Ok(Json(AppResponse::Ok().with_data(resp)))
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, service_account_id, api_key_id) = path.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  biz::workspace::service_account::revoke_service_account_api_key(
+    &state.pg_pool,
+    &workspace_id,
+    &service_account_id,
+    &api_key_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
 }
 
-async fn post_published_collab_reaction_handler(
+async fn get_workspace_snapshot_retention_handler(
   user_uuid: UserUuid,
-  view_id: web::Path<Uuid>,
-  data: Json<CreateReactionParams>,
+  workspace_id: web::Path<Uuid>,
   state: Data<AppState>,
-) -> Result<JsonAppResponse<()>> {
-  let view_id = view_id.into_inner();
-  create_reaction_on_comment(
+) -> Result<Json<AppResponse<WorkspaceSnapshotRetention>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let retention = biz::workspace::snapshot_retention::get_workspace_snapshot_retention(
     &state.pg_pool,
-    &data.comment_id,
-    &view_id,
-    &data.reaction_type,
-    &user_uuid,
+    &workspace_id,
   )
   .await?;
-  Ok(Json(AppResponse::Ok()))
+  Ok(Json(AppResponse::Ok().with_data(retention)))
 }
 
-async fn delete_published_collab_reaction_handler(
+async fn update_workspace_snapshot_retention_handler(
   user_uuid: UserUuid,
-  data: Json<DeleteReactionParams>,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<SnapshotRetentionPolicy>,
   state: Data<AppState>,
-) -> Result<JsonAppResponse<()>> {
-  remove_reaction_on_comment(
+) -> Result<Json<AppResponse<WorkspaceSnapshotRetention>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let retention = biz::workspace::snapshot_retention::update_workspace_snapshot_retention(
     &state.pg_pool,
-    &data.comment_id,
-    &data.reaction_type,
-    &user_uuid,
+    &workspace_id,
+    uid,
+    payload.into_inner(),
   )
   .await?;
-  Ok(Json(AppResponse::Ok()))
+  Ok(Json(AppResponse::Ok().with_data(retention)))
 }
 
-async fn post_publish_collabs_handler(
-  workspace_id: web::Path<Uuid>,
+async fn delete_workspace_snapshot_retention_handler(
   user_uuid: UserUuid,
-  payload: Payload,
+  workspace_id: web::Path<Uuid>,
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<()>>> {
   let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  biz::workspace::snapshot_retention::remove_workspace_snapshot_retention(
+    &state.pg_pool,
+    &workspace_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
 
-  let mut accumulator = Vec::<PublishCollabItem<serde_json::Value, Vec<u8>>>::new();
-  let mut payload_reader: PayloadReader = PayloadReader::new(payload);
-
-  loop {
-    let meta: PublishCollabMetadata<serde_json::Value> = {
-      let meta_len = payload_reader.read_u32_little_endian().await?;
-      if meta_len > 4 * 1024 * 1024 {
-        // 4MiB Limit for metadata
-        return Err(AppError::InvalidRequest(String::from("metadata too large")).into());
-      }
-      if meta_len == 0 {
-        break;
-      }
+async fn get_workspace_sso_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceSso>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let sso = biz::workspace::sso::get_workspace_sso(&state, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(sso)))
+}
 
-      let mut meta_buffer = vec![0; meta_len as usize];
-      payload_reader.read_exact(&mut meta_buffer).await?;
-      serde_json::from_slice(&meta_buffer)?
-    };
+async fn update_workspace_sso_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<UpdateWorkspaceSsoParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceSso>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let sso =
+    biz::workspace::sso::update_workspace_sso(&state, &workspace_id, payload.into_inner()).await?;
+  Ok(Json(AppResponse::Ok().with_data(sso)))
+}
 
-    let data = {
-      let data_len = payload_reader.read_u32_little_endian().await?;
-      if data_len > 32 * 1024 * 1024 {
-        // 32MiB Limit for data
-        return Err(AppError::InvalidRequest(String::from("data too large")).into());
-      }
-      let mut data_buffer = vec![0; data_len as usize];
-      payload_reader.read_exact(&mut data_buffer).await?;
-      data_buffer
-    };
+async fn delete_workspace_sso_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  biz::workspace::sso::remove_workspace_sso(&state, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok()))
+}
 
-    accumulator.push(PublishCollabItem { meta, data });
-  }
+async fn list_scim_groups_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<ScimGroupRole>>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let groups = biz::workspace::scim::list_scim_group_roles(&state, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(groups)))
+}
 
-  if accumulator.is_empty() {
+async fn update_scim_group_role_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<UpdateScimGroupRoleParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, group_id) = path.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  if payload.role == Some(AFRole::Owner) {
     return Err(
-      AppError::InvalidRequest(String::from("did not receive any data to publish")).into(),
+      AppError::InvalidRequest("A group cannot be mapped to the Owner role".to_string()).into(),
     );
   }
-  state
-    .published_collab_store
-    .publish_collabs(accumulator, &workspace_id, &user_uuid)
-    .await?;
+  biz::workspace::scim::update_scim_group_role_of_workspace(
+    &state,
+    &workspace_id,
+    &group_id,
+    payload.into_inner().role,
+  )
+  .await?;
   Ok(Json(AppResponse::Ok()))
 }
 
-async fn delete_published_collabs_handler(
-  workspace_id: web::Path<Uuid>,
+async fn list_audit_logs_handler(
   user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  query: web::Query<QueryAuditLog>,
   state: Data<AppState>,
-  view_ids: Json<Vec<Uuid>>,
-) -> Result<Json<AppResponse<()>>> {
+) -> Result<Json<AppResponse<AuditLogPage>>> {
   let workspace_id = workspace_id.into_inner();
-  let view_ids = view_ids.into_inner();
-  if view_ids.is_empty() {
-    return Ok(Json(AppResponse::Ok()));
-  }
-  state
-    .published_collab_store
-    .delete_collab(&workspace_id, &view_ids, &user_uuid)
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let page = list_audit_logs(&state.pg_pool, &workspace_id, query.into_inner()).await?;
+  Ok(Json(AppResponse::Ok().with_data(page)))
+}
+
+/// Read a collab with an object-scoped token instead of a user session.
+async fn get_collab_with_object_token_handler(
+  req: HttpRequest,
+  object_id: web::Path<String>,
+  query: web::Query<CollabTypeParam>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabResponse>>> {
+  let token = req
+    .headers()
+    .get(X_OBJECT_TOKEN)
+    .and_then(|value| value.to_str().ok())
+    .ok_or_else(|| AppError::UserUnAuthorized("Missing object token".to_string()))?;
+  let object_id = object_id.into_inner();
+  let workspace_id = biz::collab::object_token::enforce_object_token(
+    &state.pg_pool,
+    token,
+    &object_id,
+    AFAccessLevel::ReadOnly,
+  )
+  .await?;
+
+  let param = QueryCollabParams {
+    workspace_id: workspace_id.to_string(),
+    inner: QueryCollab {
+      object_id: object_id.clone(),
+      collab_type: query.into_inner().collab_type,
+    },
+  };
+  let encode_collab = state
+    .collab_access_control_storage
+    .get_encode_collab(GetCollabOrigin::Server, param, true)
     .await?;
-  Ok(Json(AppResponse::Ok()))
+  Ok(Json(AppResponse::Ok().with_data(CollabResponse {
+    encode_collab,
+    object_id,
+  })))
+}
+
+/// Read the collab of a share link, which does not require a user session.
+async fn get_collab_with_share_link_handler(
+  token: web::Path<String>,
+  query: web::Query<CollabTypeParam>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabResponse>>> {
+  let (link, _access_level) =
+    biz::collab::share_link::resolve_share_link(&state.pg_pool, &token.into_inner()).await?;
+  let param = QueryCollabParams {
+    workspace_id: link.workspace_id.to_string(),
+    inner: QueryCollab {
+      object_id: link.oid.clone(),
+      collab_type: query.into_inner().collab_type,
+    },
+  };
+  let encode_collab = state
+    .collab_access_control_storage
+    .get_encode_collab(GetCollabOrigin::Server, param, true)
+    .await?;
+  Ok(Json(AppResponse::Ok().with_data(CollabResponse {
+    encode_collab,
+    object_id: link.oid,
+  })))
 }
 
 #[instrument(level = "debug", skip(state, payload), err)]
 async fn get_collab_member_list_handler(
   payload: Json<QueryCollabMembers>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<AFCollabMembers>>> {
-  let members =
+) -> Result<Json<AppResponse<AFCollabMemberPage>>> {
+  let page =
     biz::collab::ops::get_collab_member_list(&state.pg_pool, &payload.into_inner()).await?;
-  Ok(Json(AppResponse::Ok().with_data(AFCollabMembers(members))))
+  Ok(Json(AppResponse::Ok().with_data(page)))
 }
 
 #[instrument(level = "info", skip_all, err)]
@@ -1338,13 +4507,22 @@ async fn post_realtime_message_stream_handler(
   }
 }
 
+#[utoipa::path(
+  get,
+  path = "/api/workspace/{workspace_id}/usage",
+  tag = "workspace",
+  params(("workspace_id" = Uuid, Path, description = "The id of the workspace")),
+  responses((status = 200, description = "The activity of the workspace", body = WorkspaceUsage)),
+  security(("bearer_auth" = []))
+)]
 async fn get_workspace_usage_handler(
   user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<WorkspaceUsage>>> {
-  let res = biz::workspace::ops::get_workspace_document_total_bytes(
+  let res = biz::workspace::usage::get_workspace_usage(
     &state.pg_pool,
+    &state.redis_connection_manager,
     &user_uuid,
     &workspace_id,
   )
@@ -1352,6 +4530,93 @@ async fn get_workspace_usage_handler(
   Ok(Json(AppResponse::Ok().with_data(res)))
 }
 
+async fn get_workspace_storage_footprint_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceStorageFootprint>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::ops::check_workspace_owner(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  let res =
+    biz::workspace::ops::get_workspace_storage_footprint(&state.pg_pool, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(res)))
+}
+
+async fn get_workspace_storage_quota_handler(
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceStorageQuota>>> {
+  let res = biz::workspace::storage_quota::get_workspace_storage_quota(
+    &state.pg_pool,
+    &state.config.storage_quota,
+    &workspace_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(res)))
+}
+
+async fn get_workspace_ai_usage_handler(
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceAIUsage>>> {
+  let workspace_id = workspace_id.into_inner();
+  let res =
+    biz::ai::quota::get_workspace_ai_usage(&state.pg_pool, &state.config.ai_quota, &workspace_id)
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(res)))
+}
+
+async fn delete_object_handler(
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<DeletedObjectSummary>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let summary = biz::collab::ops::delete_object(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    &state.collab_access_control,
+    &workspace_id,
+    &object_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(summary)))
+}
+
+async fn get_folder_integrity_handler(
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<FolderIntegrityReport>>> {
+  let report = biz::collab::folder_integrity::check_folder_integrity(
+    state.collab_access_control_storage.clone(),
+    &workspace_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(report)))
+}
+
+async fn repair_folder_integrity_handler(
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  payload: Json<RepairFolderIntegrityParams>,
+) -> Result<Json<AppResponse<FolderIntegrityReport>>> {
+  let report = biz::collab::folder_integrity::repair_folder_integrity(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    &workspace_id,
+    payload.confirm,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(report)))
+}
+
+#[utoipa::path(
+  get,
+  path = "/api/workspace/{workspace_id}/folder",
+  tag = "workspace",
+  params(("workspace_id" = Uuid, Path, description = "The id of the workspace"), QueryWorkspaceFolder),
+  responses((status = 200, description = "The folder of the workspace, as a tree of views", body = FolderView)),
+  security(("bearer_auth" = []))
+)]
 async fn get_workspace_folder_handler(
   user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
@@ -1359,6 +4624,7 @@ async fn get_workspace_folder_handler(
   query: web::Query<QueryWorkspaceFolder>,
 ) -> Result<Json<AppResponse<FolderView>>> {
   let depth = query.depth.unwrap_or(1);
+  let filter = FolderViewFilter::from_query(&query)?;
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   let workspace_id = workspace_id.into_inner();
   let root_view_id = if let Some(root_view_id) = query.root_view_id.as_ref() {
@@ -1373,11 +4639,142 @@ async fn get_workspace_folder_handler(
     workspace_id,
     depth,
     &root_view_id,
+    &filter,
+    query
+      .include_permissions
+      .unwrap_or(false)
+      .then_some(&state.collab_access_control),
   )
   .await?;
   Ok(Json(AppResponse::Ok().with_data(folder_view)))
 }
 
+/// The folders of several workspaces at once, keyed by workspace id. A workspace the user can not
+/// read fails on its own, without failing the others.
+async fn get_workspace_folders_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  payload: Json<QueryWorkspaceFolders>,
+) -> Result<Json<AppResponse<HashMap<String, WorkspaceFolderResult>>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let folders = biz::collab::ops::get_workspace_structures(
+    state.collab_access_control_storage.clone(),
+    state.pg_read_pool.get(),
+    uid,
+    &payload.workspace_ids,
+    payload.depth.unwrap_or(1),
+  )
+  .await
+  .into_iter()
+  .map(|(workspace_id, folder)| {
+    let folder = match folder {
+      Ok(folder) => WorkspaceFolderResult::Folder(folder),
+      Err(err) => WorkspaceFolderResult::Failed {
+        error: err.to_string(),
+      },
+    };
+    (workspace_id, folder)
+  })
+  .collect();
+  Ok(Json(AppResponse::Ok().with_data(folders)))
+}
+
+async fn search_workspace_folder_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  query: web::Query<QueryFolderViewSearch>,
+) -> Result<Json<AppResponse<Vec<FolderViewSearchResult>>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let results = biz::collab::ops::search_folder_views(
+    state.collab_access_control_storage.clone(),
+    uid,
+    workspace_id.into_inner(),
+    &query.query,
+    query.include_ancestors,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(results)))
+}
+
+async fn search_workspace_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  query: web::Query<WorkspaceSearchQuery>,
+) -> Result<Json<AppResponse<Vec<WorkspaceSearchResultItem>>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let results = biz::search::search_workspace_content(
+    &state.pg_pool,
+    &state.collab_access_control,
+    uid,
+    workspace_id.into_inner(),
+    query.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(results)))
+}
+
+async fn get_folder_children_connection_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+  query: web::Query<QueryFolderChildrenConnection>,
+) -> Result<Json<AppResponse<FolderViewConnection>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let query = query.into_inner();
+  let filter = FolderViewFilter::new(query.layouts.as_deref(), None, query.include_private)?;
+  let connection = biz::collab::ops::get_folder_children_connection(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    uid,
+    workspace_id,
+    &view_id,
+    query.first.unwrap_or(50),
+    query.after.as_deref(),
+    &filter,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(connection)))
+}
+
+async fn get_folder_view_ancestors_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<FolderViewAncestor>>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let ancestors = biz::collab::ops::get_folder_view_ancestors(
+    state.collab_access_control_storage.clone(),
+    uid,
+    workspace_id,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(ancestors)))
+}
+
+async fn put_user_view_order_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  payload: Json<UpdateUserViewOrder>,
+) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let params = payload.into_inner();
+  biz::collab::ops::set_user_view_order(
+    &state.pg_pool,
+    uid,
+    &workspace_id,
+    &params.parent_view_id,
+    &params.ordered_child_ids,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
 async fn get_recent_views_handler(
   user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
@@ -1418,6 +4815,44 @@ async fn get_favorite_views_handler(
   Ok(Json(AppResponse::Ok().with_data(section_items)))
 }
 
+async fn record_view_visit_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  payload: Json<RecordViewVisitParams>,
+) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  biz::workspace::section::record_view_visit(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    uid,
+    &workspace_id.into_inner(),
+    payload.into_inner().view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn set_view_favorite_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  payload: Json<FavoriteViewParams>,
+) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let params = payload.into_inner();
+  biz::workspace::section::set_view_favorite(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    uid,
+    &workspace_id.into_inner(),
+    params.view_id,
+    params.is_favorite,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
 async fn get_trash_views_handler(
   user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
@@ -1437,19 +4872,98 @@ async fn get_trash_views_handler(
   Ok(Json(AppResponse::Ok().with_data(section_items)))
 }
 
+async fn move_views_to_trash_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  payload: Json<TrashViewsParams>,
+) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  biz::workspace::trash::move_views_to_trash(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    uid,
+    &workspace_id.into_inner(),
+    payload.into_inner().view_ids,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn restore_views_from_trash_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  payload: Json<TrashViewsParams>,
+) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  biz::workspace::trash::restore_views_from_trash(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    uid,
+    &workspace_id.into_inner(),
+    payload.into_inner().view_ids,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
 async fn get_workspace_publish_outline_handler(
+  req: HttpRequest,
   publish_namespace: web::Path<String>,
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<PublishedView>>> {
+  let publish_namespace =
+    resolve_publish_namespace(&req, &state, publish_namespace.into_inner()).await?;
   let published_view = biz::collab::ops::get_published_view(
     state.collab_access_control_storage.clone(),
-    publish_namespace.into_inner(),
+    publish_namespace,
     &state.pg_pool,
   )
   .await?;
   Ok(Json(AppResponse::Ok().with_data(published_view)))
 }
 
+async fn get_published_view_preview_handler(
+  req: HttpRequest,
+  path: web::Path<(String, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishedViewPreview>>> {
+  let (publish_namespace, view_id) = path.into_inner();
+  let publish_namespace = resolve_publish_namespace(&req, &state, publish_namespace).await?;
+  biz::workspace::publish_access::check_published_view_access(
+    &state.pg_pool,
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &view_id,
+    publish_access_token_from_header(&req),
+  )
+  .await?;
+  let preview = biz::collab::ops::get_published_view_preview(
+    state.collab_access_control_storage.clone(),
+    &publish_namespace,
+    &view_id,
+    &state.pg_pool,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(preview)))
+}
+
+async fn get_workspace_publish_nav_handler(
+  req: HttpRequest,
+  publish_namespace: web::Path<String>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<PublishedView>>>> {
+  let publish_namespace =
+    resolve_publish_namespace(&req, &state, publish_namespace.into_inner()).await?;
+  let nav = biz::collab::ops::get_published_nav(
+    state.collab_access_control_storage.clone(),
+    &state.pg_pool,
+    &publish_namespace,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(nav)))
+}
+
 #[inline]
 async fn parser_realtime_msg(
   payload: Bytes,
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 
 use actix::Addr;
@@ -10,18 +11,24 @@ use secrecy::Secret;
 use semver::Version;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::Sender;
-use tracing::{debug, error, instrument, trace};
+use tracing::{debug, error, instrument, trace, warn};
 
 use app_error::AppError;
-use appflowy_collaborate::actix_ws::client::rt_client::RealtimeClient;
+use appflowy_collaborate::actix_ws::client::rt_client::{RealtimeClient, UpgradeRequiredClient};
 use appflowy_collaborate::actix_ws::server::RealtimeServerActor;
 use appflowy_collaborate::collab::access_control::RealtimeCollabAccessControlImpl;
 use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
 use authentication::jwt::{authorization_from_token, UserUuid};
 use collab_rt_entity::user::{AFUserChange, RealtimeUser, UserMessage};
-use collab_rt_entity::RealtimeMessage;
+use collab_rt_entity::{
+  RealtimeCompression, RealtimeMessage, RealtimeProtocolVersion, COMPRESSION_HEADER,
+  PROTOCOL_VERSION_HEADER,
+};
 use shared_entity::response::AppResponseError;
 
+use crate::biz;
+use crate::middleware::rate_limit_mw::too_many_requests_response;
+use crate::middleware::sso_mw::SsoSessionGuard;
 use crate::state::AppState;
 
 pub fn ws_scope() -> Scope {
@@ -48,6 +55,15 @@ pub async fn establish_ws_connection(
   let (access_token, device_id) = path.into_inner();
   let client_version = Version::new(0, 5, 0);
   let connect_at = chrono::Utc::now().timestamp();
+  // These clients don't send their protocol version, they speak the first one
+  let min_protocol_version = state.config.websocket.min_protocol_version;
+  let protocol_version = match RealtimeProtocolVersion::negotiate(1, min_protocol_version) {
+    Some(protocol_version) => protocol_version,
+    None => {
+      let client = UpgradeRequiredClient::new(1, min_protocol_version);
+      return ws::start(client, &request, payload);
+    },
+  };
   start_connect(
     &request,
     payload,
@@ -58,6 +74,9 @@ pub async fn establish_ws_connection(
     device_id,
     client_version,
     connect_at,
+    None,
+    RealtimeCompression::None,
+    protocol_version,
   )
   .await
 }
@@ -78,6 +97,9 @@ pub async fn establish_ws_connection_v1(
     client_version,
     device_id,
     connect_at,
+    share_link,
+    compression,
+    protocol_version,
   } = match ConnectInfo::parse_from(&request) {
     Ok(info) => info,
     Err(_) => {
@@ -90,6 +112,20 @@ pub async fn establish_ws_connection_v1(
     return Err(AppError::Connect("Client version is too low".to_string()).into());
   }
 
+  let min_protocol_version = state.config.websocket.min_protocol_version;
+  let protocol_version =
+    match RealtimeProtocolVersion::negotiate(protocol_version, min_protocol_version) {
+      Some(protocol_version) => protocol_version,
+      None => {
+        trace!(
+          "Reject websocket connection of protocol version {}",
+          protocol_version
+        );
+        let client = UpgradeRequiredClient::new(protocol_version, min_protocol_version);
+        return ws::start(client, &request, payload);
+      },
+    };
+
   start_connect(
     &request,
     payload,
@@ -100,6 +136,9 @@ pub async fn establish_ws_connection_v1(
     device_id,
     client_version,
     connect_at,
+    share_link,
+    compression,
+    protocol_version,
   )
   .await
 }
@@ -116,19 +155,55 @@ async fn start_connect(
   device_id: String,
   client_app_version: Version,
   connect_at: i64,
+  share_link: Option<String>,
+  compression: RealtimeCompression,
+  protocol_version: RealtimeProtocolVersion,
 ) -> Result<HttpResponse> {
   let auth = authorization_from_token(access_token.as_str(), jwt_secret)?;
+  let claims = auth.claims.clone();
   let user_uuid = UserUuid::from_auth(auth)?;
   let result = state.user_cache.get_user_uid(&user_uuid).await;
 
   match result {
     Ok(uid) => {
+      // The user connects to open the object of a share link, so it becomes a member of the
+      // object before joining its group. A stale link does not keep the user from connecting,
+      // the user only syncs the objects it can access.
+      if let Some(share_link) = share_link {
+        if let Err(err) = biz::collab::share_link::redeem_share_link(
+          &state.pg_pool,
+          &state.collab_access_control,
+          uid,
+          &share_link,
+        )
+        .await
+        {
+          warn!("uid {} could not redeem a share link: {}", uid, err);
+        }
+      }
+
       debug!(
         "🚀new websocket connect: uid={}, device_id={}, client_version:{}",
         uid, device_id, client_app_version
       );
 
+      // The session may not be signed in for some of the workspaces of the user
+      let blocked_workspaces = state
+        .sso_enforcer
+        .blocked_workspaces(&user_uuid, &claims)
+        .await?;
       let session_id = uuid::Uuid::new_v4().to_string();
+      let (tx, external_source) = mpsc::channel(100);
+      // The connection is released once the client drops `external_source`
+      let lease = Duration::from_secs(state.config.websocket.client_timeout as u64 * 2);
+      if let Err(retry_after) = state
+        .rate_limiter
+        .acquire_ws_connection(uid, &session_id, tx.clone(), lease)
+        .await
+      {
+        return Ok(too_many_requests_response(retry_after));
+      }
+
       let realtime_user = RealtimeUser::new(
         uid,
         device_id,
@@ -136,8 +211,7 @@ async fn start_connect(
         connect_at,
         client_app_version.to_string(),
       );
-      let (tx, external_source) = mpsc::channel(100);
-      let client = RealtimeClient::new(
+      let mut client = RealtimeClient::new(
         realtime_user,
         server.get_ref().clone(),
         Duration::from_secs(state.config.websocket.heartbeat_interval as u64),
@@ -145,7 +219,19 @@ async fn start_connect(
         client_app_version,
         external_source,
         10,
-      );
+      )
+      .with_compression(if state.config.websocket.compression {
+        compression
+      } else {
+        RealtimeCompression::None
+      })
+      .with_protocol_version(protocol_version);
+      if !blocked_workspaces.is_empty() {
+        client = client.with_session_guard(Arc::new(SsoSessionGuard::new(
+          state.pg_pool.clone(),
+          blocked_workspaces,
+        )));
+      }
 
       // Receive user change notifications and send them to the client.
       listen_on_user_change(state, uid, tx);
@@ -201,11 +287,16 @@ struct ConnectInfo {
   client_version: Version,
   device_id: String,
   connect_at: i64,
+  share_link: Option<String>,
+  compression: RealtimeCompression,
+  /// The latest version of the realtime protocol the client supports.
+  protocol_version: u32,
 }
 
 const CLIENT_VERSION: &str = "client-version";
 const DEVICE_ID: &str = "device-id";
 const CONNECT_AT: &str = "connect-at";
+const SHARE_LINK: &str = "share-link";
 
 // Trait for parameter extraction
 trait ExtractParameter {
@@ -255,12 +346,27 @@ impl ConnectInfo {
         .unwrap_or_else(|_| chrono::Utc::now().timestamp()),
       Err(_) => chrono::Utc::now().timestamp(),
     };
+    let share_link = source.extract_param(SHARE_LINK).ok();
+    let compression = source
+      .extract_param(COMPRESSION_HEADER)
+      .map(|accepted| RealtimeCompression::negotiate(&accepted))
+      .unwrap_or_default();
+    // The clients that don't send their version speak the first version
+    let protocol_version = match source.extract_param(PROTOCOL_VERSION_HEADER) {
+      Ok(version) => version
+        .parse::<u32>()
+        .map_err(|_| AppError::InvalidRequest(format!("Invalid protocol version:{}", version)))?,
+      Err(_) => 1,
+    };
 
     Ok(Self {
       access_token,
       client_version,
       device_id,
       connect_at,
+      share_link,
+      compression,
+      protocol_version,
     })
   }
 }
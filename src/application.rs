@@ -25,48 +25,71 @@ use tracing::{error, info, warn};
 
 use access_control::access::{enable_access_control, AccessControl};
 use appflowy_ai_client::client::AppFlowyAIClient;
+use appflowy_ai_client::llm::LLMClient;
 use appflowy_collaborate::actix_ws::server::RealtimeServerActor;
 use appflowy_collaborate::collab::access_control::{
   CollabAccessControlImpl, CollabStorageAccessControlImpl, RealtimeCollabAccessControlImpl,
 };
 use appflowy_collaborate::collab::cache::CollabCache;
+use appflowy_collaborate::collab::hierarchy::FolderCollabHierarchy;
+use appflowy_collaborate::collab::notification::spawn_listen_on_collab_inheritance_change;
 use appflowy_collaborate::collab::storage::CollabStorageImpl;
 use appflowy_collaborate::command::{CLCommandReceiver, CLCommandSender};
 use appflowy_collaborate::indexer::IndexerProvider;
 use appflowy_collaborate::shared_state::RealtimeSharedState;
 use appflowy_collaborate::snapshot::SnapshotControl;
 use appflowy_collaborate::CollaborationServer;
+use collab_stream::connection::{spawn_check_redis_nodes, RedisSetting};
 use database::file::s3_client_impl::{AwsS3BucketClientImpl, S3BucketStorage};
 use gotrue::grant::{Grant, PasswordGrant};
 use snowflake::Snowflake;
 use tonic_proto::history::history_client::HistoryClient;
 use workspace_access::WorkspaceAccessControlImpl;
 
+use crate::api::admin::admin_scope;
 use crate::api::ai::ai_completion_scope;
 use crate::api::chat::chat_scope;
 use crate::api::file_storage::file_storage_scope;
+use crate::api::graphql::graphql_scope;
+use crate::api::grpc::spawn_grpc_server;
 use crate::api::history::history_scope;
 use crate::api::metrics::metrics_scope;
+use crate::api::notification::notification_scope;
+use crate::api::openapi::openapi_service;
+use crate::api::scim::scim_scope;
 use crate::api::search::search_scope;
 use crate::api::template::template_scope;
 use crate::api::user::user_scope;
 use crate::api::workspace::{collab_scope, workspace_scope};
 use crate::api::ws::ws_scope;
 use crate::biz::collab::access_control::CollabMiddlewareAccessControl;
+use crate::biz::collab::compaction::spawn_compact_collabs;
+use crate::biz::collab::ops::spawn_revoke_expired_collab_members;
+use crate::biz::notification::ops::spawn_notification_mailer;
 use crate::biz::pg_listener::PgListeners;
+use crate::biz::pg_read_pool::{spawn_check_read_replica, PgReadPool};
+use crate::biz::user::user_deletion::spawn_purge_deleted_accounts;
 use crate::biz::workspace::access_control::WorkspaceMiddlewareAccessControl;
 use crate::biz::workspace::publish::{
   PublishedCollabPostgresStore, PublishedCollabS3StoreWithPostgresFallback, PublishedCollabStore,
 };
+use crate::biz::workspace::publish_analytics::PublishedViewHitRecorder;
+use crate::biz::workspace::snapshot_retention::spawn_enforce_snapshot_retention;
+use crate::biz::workspace::storage_quota::spawn_refresh_workspace_storage_usage;
+use crate::biz::workspace::trash::spawn_purge_expired_trash;
+use crate::biz::workspace::webhook::spawn_webhook_dispatcher;
 use crate::config::config::{
   Config, DatabaseSetting, GoTrueSetting, PublishedCollabStorageBackend, S3Setting,
 };
 use crate::mailer::Mailer;
 use crate::middleware::access_control_mw::MiddlewareAccessControlTransform;
+use crate::middleware::api_key_mw::ApiKeyMiddleware;
 use crate::middleware::metrics_mw::MetricsMiddleware;
+use crate::middleware::rate_limit_mw::{RateLimitMiddleware, RateLimiter};
 use crate::middleware::request_id::RequestIdMiddleware;
+use crate::middleware::sso_mw::{SsoEnforcer, SsoMiddleware};
 use crate::self_signed::create_self_signed_certificate;
-use crate::state::{AppMetrics, AppState, GoTrueAdmin, UserCache};
+use crate::state::{AppMetrics, AppState, GoTrueAdmin, RedisConnectionManager, UserCache};
 
 pub struct Application {
   port: u16,
@@ -83,6 +106,10 @@ impl Application {
     let listener = TcpListener::bind(&address)?;
     let port = listener.local_addr().unwrap().port();
     info!("Server started at {}", listener.local_addr().unwrap());
+    if config.grpc.port != 0 {
+      let grpc_address = format!("{}:{}", config.application.host, config.grpc.port).parse()?;
+      spawn_grpc_server(state.clone(), grpc_address);
+    }
     let actix_server = run_actix_server(listener, state, config, rt_cmd_recv).await?;
 
     Ok(Self { port, actix_server })
@@ -156,7 +183,13 @@ pub async fn run_actix_server(
           .build(),
       )
       // .wrap(DecryptPayloadMiddleware)
+      // Runs after the access control, which keeps the non-members off the workspace
+      .wrap(RateLimitMiddleware::Workspace)
       .wrap(access_control.clone())
+      .wrap(SsoMiddleware)
+      .wrap(RateLimitMiddleware::Caller)
+      // Runs before the rate limit and the access control, which see the service account
+      .wrap(ApiKeyMiddleware)
       .wrap(RequestIdMiddleware)
       .service(user_scope())
       .service(workspace_scope())
@@ -167,8 +200,13 @@ pub async fn run_actix_server(
       .service(ai_completion_scope())
       .service(history_scope())
       .service(metrics_scope())
+      .service(admin_scope())
       .service(search_scope())
       .service(template_scope())
+      .service(notification_scope())
+      .service(scim_scope())
+      .service(graphql_scope())
+      .service(openapi_service())
       .app_data(Data::new(state.metrics.registry.clone()))
       .app_data(Data::new(state.metrics.request_metrics.clone()))
       .app_data(Data::new(state.metrics.realtime_metrics.clone()))
@@ -207,6 +245,11 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
   info!("Preparing to run database migrations...");
   let pg_pool = get_connection_pool(&config.db_settings).await?;
   migrate(&pg_pool).await?;
+  let pg_read_pool = get_read_pool(&config.db_settings, &pg_pool).await;
+  spawn_check_read_replica(
+    pg_read_pool.clone(),
+    Duration::from_secs(config.db_settings.read_replica_check_interval_secs),
+  );
 
   // Bucket storage
   info!("Setting up S3 bucket...");
@@ -228,6 +271,7 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
         Arc::new(PublishedCollabPostgresStore::new(
           metrics.published_collab_metrics.clone(),
           pg_pool.clone(),
+          pg_read_pool.clone(),
         ))
       },
       PublishedCollabStorageBackend::S3WithPostgresBackup => {
@@ -235,6 +279,7 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
         Arc::new(PublishedCollabS3StoreWithPostgresFallback::new(
           metrics.published_collab_metrics.clone(),
           pg_pool.clone(),
+          pg_read_pool.clone(),
           s3_client.clone(),
         ))
       },
@@ -247,11 +292,26 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
 
   // Redis
   info!("Connecting to Redis...");
-  let redis_conn_manager = get_redis_client(config.redis_uri.expose_secret()).await?;
+  let redis_conn_manager = get_redis_client(&config.redis).await?;
 
   info!("Setup AppFlowy AI: {}", config.appflowy_ai.url());
   let appflowy_ai_client = AppFlowyAIClient::new(&config.appflowy_ai.url());
   let indexer_provider = IndexerProvider::new(pg_pool.clone(), appflowy_ai_client.clone());
+  let llm_client = config.llm.provider.map(|provider| {
+    LLMClient::new(
+      provider,
+      config.llm.base_url.as_deref(),
+      Some(config.llm.api_key.expose_secret().clone()),
+      &config.llm.model,
+    )
+  });
+  let rate_limiter = RateLimiter::new(
+    redis_conn_manager.clone(),
+    pg_pool.clone(),
+    config.rate_limit.clone(),
+  );
+  let sso_enforcer = SsoEnforcer::new(pg_pool.clone());
+  let published_view_hits = PublishedViewHitRecorder::new(pg_pool.clone());
 
   // Pg listeners
   info!("Setting up Pg listeners...");
@@ -263,8 +323,20 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
     "Setting up access controls, is_enable: {}",
     enable_access_control()
   );
-  let access_control =
-    AccessControl::new(pg_pool.clone(), metrics.access_control_metrics.clone()).await?;
+  let collab_cache = CollabCache::new(
+    redis_conn_manager.clone(),
+    pg_pool.clone(),
+    metrics.collab_storage_metrics.clone(),
+  );
+  let collab_hierarchy = FolderCollabHierarchy::new(pg_pool.clone(), collab_cache.clone());
+  let access_control = AccessControl::new(pg_pool.clone(), metrics.access_control_metrics.clone())
+    .await?
+    .with_collab_hierarchy(Arc::new(collab_hierarchy));
+  // The other api servers change the inheritance too
+  spawn_listen_on_collab_inheritance_change(
+    pg_listeners.subscribe_collab_inheritance_change(),
+    access_control.clone(),
+  );
 
   // spawn_listen_on_workspace_member_change(workspace_member_listener, access_control.clone());
   // spawn_listen_on_collab_member_change(
@@ -275,8 +347,25 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
 
   let user_cache = UserCache::new(pg_pool.clone()).await;
   let collab_access_control = CollabAccessControlImpl::new(access_control.clone());
+  spawn_revoke_expired_collab_members(
+    pg_pool.clone(),
+    collab_access_control.clone(),
+    Duration::from_secs(config.collab.member_expiry_check_interval_secs),
+  );
+  spawn_webhook_dispatcher(pg_pool.clone(), config.webhook.clone());
+  spawn_enforce_snapshot_retention(
+    pg_pool.clone(),
+    Duration::from_secs(config.collab.snapshot_retention_interval_secs),
+  );
   let workspace_access_control = WorkspaceAccessControlImpl::new(access_control.clone());
-  let collab_cache = CollabCache::new(redis_conn_manager.clone(), pg_pool.clone());
+  spawn_compact_collabs(
+    pg_pool.clone(),
+    collab_cache.clone(),
+    metrics.collab_compaction_metrics.clone(),
+    Duration::from_secs(config.collab.compaction_interval_secs),
+    config.collab.compaction_min_len,
+    config.collab.compaction_batch_size,
+  );
 
   let collab_storage_access_control = CollabStorageAccessControlImpl {
     collab_access_control: collab_access_control.clone().into(),
@@ -296,7 +385,16 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
     rt_cmd_tx,
     redis_conn_manager.clone(),
     metrics.collab_metrics.clone(),
+    config.storage_quota.clone(),
   ));
+  spawn_refresh_workspace_storage_usage(pg_pool.clone());
+  spawn_purge_expired_trash(
+    collab_access_control_storage.clone(),
+    pg_pool.clone(),
+    collab_access_control.clone(),
+    Duration::from_secs(config.collab.trash_purge_interval_secs),
+    config.collab.trash_retention(),
+  );
 
   info!(
     "Connecting to history server: {}",
@@ -315,14 +413,21 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
     config.mailer.smtp_port,
   )
   .await?;
+  spawn_notification_mailer(
+    pg_pool.clone(),
+    mailer.clone(),
+    config.notification.clone(),
+    config.appflowy_web_url.clone(),
+  );
   let realtime_shared_state = RealtimeSharedState::new(redis_conn_manager.clone());
   if let Err(err) = realtime_shared_state.remove_all_connected_users().await {
     warn!("Failed to remove all connected users: {:?}", err);
   }
 
   info!("Application state initialized");
-  Ok(AppState {
+  let state = AppState {
     pg_pool,
+    pg_read_pool,
     config: Arc::new(config.clone()),
     user_cache,
     id_gen: Arc::new(RwLock::new(Snowflake::new(1))),
@@ -341,10 +446,19 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
     gotrue_admin,
     mailer,
     ai_client: appflowy_ai_client,
+    llm_client,
     grpc_history_client,
     realtime_shared_state,
     indexer_provider,
-  })
+    rate_limiter,
+    sso_enforcer,
+    published_view_hits,
+  };
+  spawn_purge_deleted_accounts(
+    state.clone(),
+    Duration::from_secs(config.account_deletion.purge_interval_secs),
+  );
+  Ok(state)
 }
 
 async fn setup_admin_account(
@@ -425,13 +539,15 @@ async fn setup_admin_account(
   }
 }
 
-async fn get_redis_client(redis_uri: &str) -> Result<redis::aio::ConnectionManager, Error> {
-  info!("Connecting to redis with uri: {}", redis_uri);
-  let manager = redis::Client::open(redis_uri)
-    .context("failed to connect to redis")?
-    .get_connection_manager()
+async fn get_redis_client(setting: &RedisSetting) -> Result<RedisConnectionManager, Error> {
+  info!("Connecting to redis with setting: {:?}", setting);
+  let manager = RedisConnectionManager::connect(setting)
     .await
     .context("failed to get the connection manager")?;
+  spawn_check_redis_nodes(
+    manager.clone(),
+    Duration::from_secs(setting.health_check_interval_secs),
+  );
   Ok(manager)
 }
 
@@ -522,6 +638,23 @@ async fn get_connection_pool(setting: &DatabaseSetting) -> Result<PgPool, Error>
     .map_err(|e| anyhow::anyhow!("Failed to connect to postgres database: {}", e))
 }
 
+/// The read replica is connected to lazily, so that the server starts even when it is unavailable,
+/// in which case the reads go to the primary until it becomes available.
+async fn get_read_pool(setting: &DatabaseSetting, primary: &PgPool) -> PgReadPool {
+  let replica = setting.read_replica_connect_options().map(|options| {
+    info!("Connecting to postgres read replica: {:?}", options);
+    PgPoolOptions::new()
+      .max_connections(setting.max_connections)
+      .acquire_timeout(Duration::from_secs(10))
+      .max_lifetime(Duration::from_secs(30 * 60))
+      .idle_timeout(Duration::from_secs(30))
+      .connect_lazy_with(options)
+  });
+  let read_pool = PgReadPool::new(primary.clone(), replica);
+  read_pool.check_replica().await;
+  read_pool
+}
+
 async fn migrate(pool: &PgPool) -> Result<(), Error> {
   sqlx::migrate!("./migrations")
     .set_ignore_missing(true)
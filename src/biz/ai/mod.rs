@@ -0,0 +1,5 @@
+pub mod quota;
+pub mod stream;
+pub mod summary;
+pub mod translate;
+pub mod workspace_chat;
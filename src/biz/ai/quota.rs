@@ -0,0 +1,269 @@
+use app_error::AppError;
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use database::ai_usage::{
+  select_workspace_ai_daily_usage, select_workspace_ai_quota, upsert_workspace_ai_request_usage,
+};
+use database::pg_row::AFWorkspaceAIQuotaRow;
+use shared_entity::dto::billing_dto::SubscriptionPlan;
+use shared_entity::dto::workspace_dto::{WorkspaceAIDailyUsage, WorkspaceAIUsage};
+use sqlx::PgPool;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::config::config::AIQuotaSetting;
+
+/// The monthly quotas of a workspace, `None` when unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct AIQuota {
+  requests: Option<i64>,
+  tokens: Option<i64>,
+}
+
+pub async fn get_workspace_ai_usage(
+  pg_pool: &PgPool,
+  setting: &AIQuotaSetting,
+  workspace_id: &Uuid,
+) -> Result<WorkspaceAIUsage, AppError> {
+  let period_start = month_start(Utc::now().date_naive());
+  let row = select_workspace_ai_quota(pg_pool, workspace_id, period_start).await?;
+  let (plan, quota) = ai_quota(setting, &row);
+  let daily = select_workspace_ai_daily_usage(pg_pool, workspace_id, period_start)
+    .await?
+    .into_iter()
+    .map(|row| WorkspaceAIDailyUsage {
+      day: row.day,
+      requests: row.ai_requests,
+      tokens_consumed: row.ai_tokens_consumed,
+      search_requests: row.search_requests,
+      search_tokens_consumed: row.search_tokens_consumed,
+      index_tokens_consumed: row.index_tokens_consumed,
+    })
+    .collect();
+  Ok(WorkspaceAIUsage {
+    workspace_id: *workspace_id,
+    plan,
+    period_start,
+    period_end: next_month_start(period_start),
+    requests: row.ai_requests,
+    tokens_consumed: row.ai_tokens_consumed,
+    request_quota: quota.requests,
+    token_quota: quota.tokens,
+    daily,
+  })
+}
+
+/// Returns [AppError::AIResponseLimitExceeded] if the workspace already made all the AI requests,
+/// or consumed all the tokens, its plan allows this month.
+pub async fn check_workspace_ai_quota(
+  pg_pool: &PgPool,
+  setting: &AIQuotaSetting,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  let period_start = month_start(Utc::now().date_naive());
+  let row = select_workspace_ai_quota(pg_pool, workspace_id, period_start).await?;
+  let (plan, quota) = ai_quota(setting, &row);
+  if let Some(exceeded) = exceeded_quota(&row, &quota) {
+    return Err(AppError::AIResponseLimitExceeded(format!(
+      "workspace {} used its {} of the month on the {} plan, the quota resets on {}",
+      workspace_id,
+      exceeded,
+      plan.as_ref(),
+      next_month_start(period_start)
+    )));
+  }
+  Ok(())
+}
+
+/// Check the AI quota of the workspace, and count the request that is about to be made.
+pub async fn consume_workspace_ai_request(
+  pg_pool: &PgPool,
+  setting: &AIQuotaSetting,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  check_workspace_ai_quota(pg_pool, setting, workspace_id).await?;
+  record_workspace_ai_request(pg_pool, workspace_id).await;
+  Ok(())
+}
+
+/// Count an AI request of the workspace. The request counts towards the quota as soon as it is
+/// made, so that cancelling it does not leave it uncounted.
+pub async fn record_workspace_ai_request(pg_pool: &PgPool, workspace_id: &Uuid) {
+  record_workspace_ai_usage(pg_pool, workspace_id, 1, 0).await;
+}
+
+/// Count the tokens consumed by an AI request of the workspace, which was already counted by
+/// [record_workspace_ai_request].
+pub async fn record_workspace_ai_tokens(pg_pool: &PgPool, workspace_id: &Uuid, tokens: i64) {
+  if tokens > 0 {
+    record_workspace_ai_usage(pg_pool, workspace_id, 0, tokens).await;
+  }
+}
+
+/// The request was already answered, or is being answered, so a failure to record its usage is
+/// logged rather than returned.
+async fn record_workspace_ai_usage(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  requests: i32,
+  tokens: i64,
+) {
+  if let Err(err) = upsert_workspace_ai_request_usage(pg_pool, workspace_id, requests, tokens).await
+  {
+    error!(
+      "Failed to record AI usage of workspace {}: {}",
+      workspace_id, err
+    );
+  }
+}
+
+/// Returns the plan of the workspace and its monthly quotas. The quotas of the workspace, when
+/// set, override the ones of its plan.
+fn ai_quota(setting: &AIQuotaSetting, row: &AFWorkspaceAIQuotaRow) -> (SubscriptionPlan, AIQuota) {
+  let plan = row
+    .plan
+    .and_then(|plan| SubscriptionPlan::try_from(plan).ok())
+    .unwrap_or(SubscriptionPlan::Free);
+  let plan_quota = match plan {
+    SubscriptionPlan::Pro => &setting.pro_plan,
+    SubscriptionPlan::Team => &setting.team_plan,
+    SubscriptionPlan::AiMax => &setting.ai_max_plan,
+    SubscriptionPlan::Free | SubscriptionPlan::AiLocal => &setting.free_plan,
+  };
+  let requests = row
+    .ai_monthly_requests
+    .unwrap_or(plan_quota.monthly_requests);
+  let tokens = row.ai_monthly_tokens.unwrap_or(plan_quota.monthly_tokens);
+  let quota = AIQuota {
+    requests: Some(requests).filter(|requests| *requests > 0),
+    tokens: Some(tokens).filter(|tokens| *tokens > 0),
+  };
+  (plan, quota)
+}
+
+/// Returns what the workspace used up, if anything.
+fn exceeded_quota(row: &AFWorkspaceAIQuotaRow, quota: &AIQuota) -> Option<String> {
+  if let Some(requests) = quota
+    .requests
+    .filter(|requests| row.ai_requests >= *requests)
+  {
+    return Some(format!("{} AI requests", requests));
+  }
+  if let Some(tokens) = quota
+    .tokens
+    .filter(|tokens| row.ai_tokens_consumed >= *tokens)
+  {
+    return Some(format!("{} AI tokens", tokens));
+  }
+  None
+}
+
+fn month_start(today: NaiveDate) -> NaiveDate {
+  today.with_day(1).unwrap_or(today)
+}
+
+fn next_month_start(month_start: NaiveDate) -> NaiveDate {
+  month_start
+    .checked_add_months(Months::new(1))
+    .unwrap_or(month_start)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::config::config::AIPlanQuota;
+
+  fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+  }
+
+  fn setting() -> AIQuotaSetting {
+    let quota = |monthly_requests, monthly_tokens| AIPlanQuota {
+      monthly_requests,
+      monthly_tokens,
+    };
+    AIQuotaSetting {
+      free_plan: quota(10, 1000),
+      pro_plan: quota(100, 0),
+      team_plan: quota(0, 0),
+      ai_max_plan: quota(1000, 100_000),
+    }
+  }
+
+  fn row(
+    plan: Option<i16>,
+    ai_monthly_requests: Option<i64>,
+    ai_monthly_tokens: Option<i64>,
+  ) -> AFWorkspaceAIQuotaRow {
+    AFWorkspaceAIQuotaRow {
+      ai_requests: 0,
+      ai_tokens_consumed: 0,
+      plan,
+      ai_monthly_requests,
+      ai_monthly_tokens,
+    }
+  }
+
+  #[test]
+  fn month_start_test() {
+    assert_eq!(month_start(day(2024, 10, 29)), day(2024, 10, 1));
+    assert_eq!(month_start(day(2024, 2, 1)), day(2024, 2, 1));
+    assert_eq!(month_start(day(2024, 12, 31)), day(2024, 12, 1));
+    assert_eq!(next_month_start(day(2024, 10, 1)), day(2024, 11, 1));
+    assert_eq!(next_month_start(day(2024, 12, 1)), day(2025, 1, 1));
+  }
+
+  #[test]
+  fn ai_quota_test() {
+    let setting = setting();
+    let quota = |requests, tokens| AIQuota { requests, tokens };
+    assert_eq!(
+      ai_quota(&setting, &row(None, None, None)),
+      (SubscriptionPlan::Free, quota(Some(10), Some(1000)))
+    );
+    assert_eq!(
+      ai_quota(&setting, &row(Some(1), None, None)),
+      (SubscriptionPlan::Pro, quota(Some(100), None))
+    );
+    assert_eq!(
+      ai_quota(&setting, &row(Some(2), None, None)),
+      (SubscriptionPlan::Team, quota(None, None))
+    );
+    assert_eq!(
+      ai_quota(&setting, &row(Some(4), None, None)),
+      (SubscriptionPlan::AiLocal, quota(Some(10), Some(1000)))
+    );
+    // The quotas of the workspace override the quotas of its plan
+    assert_eq!(
+      ai_quota(&setting, &row(Some(3), Some(0), Some(50))),
+      (SubscriptionPlan::AiMax, quota(None, Some(50)))
+    );
+  }
+
+  #[test]
+  fn exceeded_quota_test() {
+    let quota = AIQuota {
+      requests: Some(10),
+      tokens: Some(1000),
+    };
+    let mut row = row(None, None, None);
+    row.ai_requests = 9;
+    row.ai_tokens_consumed = 999;
+    assert_eq!(exceeded_quota(&row, &quota), None);
+    row.ai_requests = 10;
+    assert_eq!(
+      exceeded_quota(&row, &quota),
+      Some("10 AI requests".to_string())
+    );
+    row.ai_requests = 0;
+    row.ai_tokens_consumed = 1000;
+    assert_eq!(
+      exceeded_quota(&row, &quota),
+      Some("1000 AI tokens".to_string())
+    );
+    let unlimited = AIQuota {
+      requests: None,
+      tokens: None,
+    };
+    assert_eq!(exceeded_quota(&row, &unlimited), None);
+  }
+}
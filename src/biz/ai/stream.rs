@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use app_error::AppError;
+use async_stream::stream;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use shared_entity::response::AppResponseError;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{trace, warn};
+
+/// How many pieces of an answer wait for a slow client. Once the buffer is full, the answer is
+/// no longer read from the AI service until the client catches up.
+const AI_STREAM_BUFFER: usize = 16;
+/// A client that does not read the answer for that long is considered gone.
+const AI_STREAM_SEND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Read the answer of the AI service in a task of its own, at the pace of the client. The task
+/// stops reading, which cancels the request to the AI service, when the client disconnects or
+/// does not read the answer anymore.
+pub fn forward_ai_stream<S>(stream: S) -> impl Stream<Item = Result<Bytes, AppError>>
+where
+  S: Stream<Item = Result<Bytes, AppError>> + Send + 'static,
+{
+  let (tx, rx) = mpsc::channel(AI_STREAM_BUFFER);
+  tokio::spawn(async move {
+    let mut stream = Box::pin(stream);
+    loop {
+      let item = tokio::select! {
+        _ = tx.closed() => {
+          trace!("The client disconnected, cancel the AI stream");
+          break;
+        },
+        item = stream.next() => item,
+      };
+      let Some(item) = item else {
+        break;
+      };
+      let failed = item.is_err();
+      match tokio::time::timeout(AI_STREAM_SEND_TIMEOUT, tx.send(item)).await {
+        Ok(Ok(())) => {},
+        Ok(Err(_)) => {
+          trace!("The client disconnected, cancel the AI stream");
+          break;
+        },
+        Err(_) => {
+          warn!(
+            "The client did not read the AI stream for {:?}, cancel it",
+            AI_STREAM_SEND_TIMEOUT
+          );
+          break;
+        },
+      }
+      if failed {
+        break;
+      }
+    }
+  });
+  ReceiverStream::new(rx)
+}
+
+/// Frame the pieces of an answer as server-sent events. Every piece is sent as a `message` event,
+/// an error ends the stream with an `error` event holding the [AppResponseError] as JSON, and a
+/// `done` event is sent once the answer is complete.
+pub fn sse_stream<S>(stream: S) -> impl Stream<Item = Result<Bytes, AppError>>
+where
+  S: Stream<Item = Result<Bytes, AppError>> + Send + 'static,
+{
+  stream! {
+    let mut stream = Box::pin(stream);
+    // a piece may end in the middle of a character, which is then sent with the next piece
+    let mut pending = Vec::new();
+    while let Some(item) = stream.next().await {
+      match item {
+        Ok(bytes) => {
+          pending.extend_from_slice(&bytes);
+          let text = take_utf8_prefix(&mut pending);
+          if !text.is_empty() {
+            yield Ok(sse_event(None, &text));
+          }
+        },
+        Err(err) => {
+          let data = serde_json::to_string(&AppResponseError::from(err)).unwrap_or_default();
+          yield Ok(sse_event(Some("error"), &data));
+          return;
+        },
+      }
+    }
+    if !pending.is_empty() {
+      yield Ok(sse_event(None, &String::from_utf8_lossy(&pending)));
+    }
+    yield Ok(sse_event(Some("done"), ""));
+  }
+}
+
+fn sse_event(event: Option<&str>, data: &str) -> Bytes {
+  let mut frame = String::with_capacity(data.len() + 16);
+  if let Some(event) = event {
+    frame.push_str("event: ");
+    frame.push_str(event);
+    frame.push('\n');
+  }
+  for line in data.split('\n') {
+    frame.push_str("data: ");
+    frame.push_str(line);
+    frame.push('\n');
+  }
+  frame.push('\n');
+  Bytes::from(frame)
+}
+
+/// Remove the longest valid UTF-8 prefix of the buffer and return it. An incomplete character at
+/// the end stays in the buffer, while invalid bytes are replaced.
+fn take_utf8_prefix(buffer: &mut Vec<u8>) -> String {
+  let valid_up_to = match std::str::from_utf8(buffer) {
+    Ok(_) => buffer.len(),
+    Err(err) if err.error_len().is_none() => err.valid_up_to(),
+    Err(_) => {
+      let text = String::from_utf8_lossy(buffer).into_owned();
+      buffer.clear();
+      return text;
+    },
+  };
+  let rest = buffer.split_off(valid_up_to);
+  let prefix = std::mem::replace(buffer, rest);
+  String::from_utf8(prefix).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sse_event_test() {
+    assert_eq!(sse_event(None, "hello"), Bytes::from("data: hello\n\n"));
+    assert_eq!(
+      sse_event(None, "hello\nworld\n"),
+      Bytes::from("data: hello\ndata: world\ndata: \n\n")
+    );
+    assert_eq!(
+      sse_event(Some("done"), ""),
+      Bytes::from("event: done\ndata: \n\n")
+    );
+  }
+
+  #[test]
+  fn take_utf8_prefix_test() {
+    let mut buffer = "hé".as_bytes().to_vec();
+    let last = buffer.pop().unwrap();
+    assert_eq!(take_utf8_prefix(&mut buffer), "h");
+    assert_eq!(buffer.len(), 1);
+    buffer.push(last);
+    assert_eq!(take_utf8_prefix(&mut buffer), "é");
+    assert!(buffer.is_empty());
+
+    let mut buffer = vec![b'a', 0xff, b'b'];
+    assert_eq!(take_utf8_prefix(&mut buffer), "a\u{fffd}b");
+    assert!(buffer.is_empty());
+  }
+}
@@ -0,0 +1,90 @@
+use app_error::AppError;
+use collab_entity::CollabType;
+use database::collab::GetCollabOrigin;
+use shared_entity::dto::ai_dto::{SummarizeDocumentParams, SummarizeDocumentResponse};
+use uuid::Uuid;
+
+use crate::biz::ai::quota::{consume_workspace_ai_request, record_workspace_ai_tokens};
+use crate::biz::collab::ops::get_latest_collab_encoded;
+use crate::biz::workspace::export::document_markdown;
+use crate::state::AppState;
+
+/// Only the beginning of the longer documents is summarized, so that the prompt fits in the
+/// context window of the common models.
+const MAX_SUMMARY_INPUT_CHARS: usize = 48_000;
+const DEFAULT_SUMMARY_MAX_WORDS: u32 = 150;
+const MAX_SUMMARY_MAX_WORDS: u32 = 1000;
+
+/// Summarize the Document collab with the language model configured on the server. The user must
+/// be able to read the document, and the summary counts towards the AI quota of the workspace.
+pub async fn summarize_document(
+  state: &AppState,
+  uid: i64,
+  workspace_id: &Uuid,
+  params: SummarizeDocumentParams,
+) -> Result<SummarizeDocumentResponse, AppError> {
+  let llm_client = state.llm_client.as_ref().ok_or_else(|| {
+    AppError::AIServiceUnavailable("No language model is configured on the server".to_string())
+  })?;
+  let max_words = params.max_words.unwrap_or(DEFAULT_SUMMARY_MAX_WORDS);
+  if !(1..=MAX_SUMMARY_MAX_WORDS).contains(&max_words) {
+    return Err(AppError::InvalidRequest(format!(
+      "The summary must be between 1 and {} words",
+      MAX_SUMMARY_MAX_WORDS
+    )));
+  }
+  let encoded = get_latest_collab_encoded(
+    state.collab_access_control_storage.clone(),
+    GetCollabOrigin::User { uid },
+    &workspace_id.to_string(),
+    &params.object_id,
+    CollabType::Document,
+  )
+  .await?;
+  let markdown = document_markdown(encoded.doc_state.to_vec(), &params.object_id)?;
+  let (text, truncated) = truncate_chars(markdown.trim(), MAX_SUMMARY_INPUT_CHARS);
+  if text.is_empty() {
+    return Err(AppError::InvalidRequest(format!(
+      "The document {} has no content to summarize",
+      params.object_id
+    )));
+  }
+
+  let instructions = format!(
+    "You summarize documents written in Markdown. Write a summary of the document of at most {} \
+     words, in the language of the document. Answer with the summary only.",
+    max_words
+  );
+  consume_workspace_ai_request(&state.pg_pool, &state.config.ai_quota, workspace_id).await?;
+  let completion = llm_client
+    .chat(&instructions, text)
+    .await
+    .map_err(|err| AppError::AIServiceUnavailable(err.to_string()))?;
+  record_workspace_ai_tokens(&state.pg_pool, workspace_id, completion.total_tokens()).await;
+  Ok(SummarizeDocumentResponse {
+    summary: completion.text.trim().to_string(),
+    tokens_used: completion.total_tokens(),
+    truncated,
+  })
+}
+
+/// Returns the first `max_chars` characters of the text, and whether the text was longer.
+pub(crate) fn truncate_chars(text: &str, max_chars: usize) -> (&str, bool) {
+  match text.char_indices().nth(max_chars) {
+    Some((end, _)) => (&text[..end], true),
+    None => (text, false),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn truncate_chars_test() {
+    assert_eq!(truncate_chars("hello", 10), ("hello", false));
+    assert_eq!(truncate_chars("hello", 5), ("hello", false));
+    assert_eq!(truncate_chars("hello", 2), ("he", true));
+    assert_eq!(truncate_chars("héllo", 2), ("hé", true));
+  }
+}
@@ -0,0 +1,108 @@
+use access_control::act::Action;
+use access_control::workspace::WorkspaceAccessControl;
+use app_error::AppError;
+use collab_entity::CollabType;
+use database::collab::GetCollabOrigin;
+use shared_entity::dto::ai_dto::{TranslateDocumentParams, TranslateDocumentResponse};
+use uuid::Uuid;
+
+use crate::biz::ai::quota::{consume_workspace_ai_request, record_workspace_ai_tokens};
+use crate::biz::ai::summary::truncate_chars;
+use crate::biz::collab::ops::get_latest_collab_encoded;
+use crate::biz::workspace::export::document_markdown;
+use crate::biz::workspace::import::create_markdown_sibling_view;
+use crate::state::AppState;
+
+/// The translation is as long as the document, so less of the document fits in a request than
+/// for a summary.
+const MAX_TRANSLATION_INPUT_CHARS: usize = 24_000;
+const MAX_TARGET_LANGUAGE_LEN: usize = 64;
+
+/// Translate the Document collab with the language model configured on the server. The user must
+/// be able to read the document, and also to write to the workspace when the translation is put
+/// in a new view.
+pub async fn translate_document(
+  state: &AppState,
+  uid: i64,
+  workspace_id: &Uuid,
+  params: TranslateDocumentParams,
+) -> Result<TranslateDocumentResponse, AppError> {
+  let llm_client = state.llm_client.as_ref().ok_or_else(|| {
+    AppError::AIServiceUnavailable("No language model is configured on the server".to_string())
+  })?;
+  let target_language = params.target_language.trim();
+  if target_language.is_empty()
+    || target_language.len() > MAX_TARGET_LANGUAGE_LEN
+    || target_language.contains(['\n', '\r'])
+  {
+    return Err(AppError::InvalidRequest(format!(
+      "The target language must be a single line of at most {} characters",
+      MAX_TARGET_LANGUAGE_LEN
+    )));
+  }
+  let workspace_id_str = workspace_id.to_string();
+  if params.create_view {
+    let can_write = state
+      .workspace_access_control
+      .enforce_action(&uid, &workspace_id_str, Action::Write)
+      .await?;
+    if !can_write {
+      return Err(AppError::NotEnoughPermissions {
+        user: uid.to_string(),
+        action: format!("create a view in workspace:{}", workspace_id),
+      });
+    }
+  }
+
+  let encoded = get_latest_collab_encoded(
+    state.collab_access_control_storage.clone(),
+    GetCollabOrigin::User { uid },
+    &workspace_id_str,
+    &params.object_id,
+    CollabType::Document,
+  )
+  .await?;
+  let markdown = document_markdown(encoded.doc_state.to_vec(), &params.object_id)?;
+  let (text, truncated) = truncate_chars(markdown.trim(), MAX_TRANSLATION_INPUT_CHARS);
+  if text.is_empty() {
+    return Err(AppError::InvalidRequest(format!(
+      "The document {} has no content to translate",
+      params.object_id
+    )));
+  }
+
+  let instructions = format!(
+    "You translate documents written in Markdown into {}. Keep the Markdown formatting, the \
+     links and the code blocks as they are. Answer with the translated document only.",
+    target_language
+  );
+  consume_workspace_ai_request(&state.pg_pool, &state.config.ai_quota, workspace_id).await?;
+  let completion = llm_client
+    .chat(&instructions, text)
+    .await
+    .map_err(|err| AppError::AIServiceUnavailable(err.to_string()))?;
+  record_workspace_ai_tokens(&state.pg_pool, workspace_id, completion.total_tokens()).await;
+  let markdown = completion.text.trim().to_string();
+
+  let view_id = if params.create_view {
+    let view_id = create_markdown_sibling_view(
+      state.collab_access_control_storage.clone(),
+      &state.pg_pool,
+      uid,
+      &workspace_id_str,
+      &params.object_id,
+      |view| format!("{} ({})", view.name, target_language),
+      &markdown,
+    )
+    .await?;
+    Some(view_id)
+  } else {
+    None
+  };
+  Ok(TranslateDocumentResponse {
+    markdown,
+    view_id,
+    tokens_used: completion.total_tokens(),
+    truncated,
+  })
+}
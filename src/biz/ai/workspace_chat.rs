@@ -0,0 +1,132 @@
+use app_error::AppError;
+use appflowy_ai_client::dto::{STEAM_ANSWER_KEY, STEAM_METADATA_KEY};
+use appflowy_ai_client::llm::LLMStreamValue;
+use async_stream::stream;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use serde_json::json;
+use shared_entity::dto::ai_dto::{WorkspaceChatParams, WorkspaceChatSource};
+use shared_entity::dto::search_dto::SearchDocumentRequest;
+use shared_entity::response::AppResponseError;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::biz::ai::quota::{consume_workspace_ai_request, record_workspace_ai_tokens};
+use crate::biz::search::search_document;
+use crate::state::AppState;
+
+const MAX_WORKSPACE_CHAT_QUESTION_CHARS: usize = 2000;
+/// How many documents of the workspace the answer is grounded in.
+const WORKSPACE_CHAT_SOURCES: u32 = 5;
+const WORKSPACE_CHAT_SOURCE_CHARS: u32 = 2000;
+
+const WORKSPACE_CHAT_INSTRUCTIONS: &str = "You answer the questions of a user about the \
+  documents of their workspace. Use only the numbered sources given with the question, and cite \
+  the sources the answer relies on with their number in square brackets, like [1]. When the \
+  sources do not answer the question, say so. Answer in the language of the question.";
+
+/// Answer the question with the documents of the workspace that are the closest to it. Only the
+/// documents the user is allowed to read are given to the model.
+///
+/// The stream uses the format of the chat answers: the [WorkspaceChatSource]s come first as
+/// `{"0": [...]}`, then the answer as `{"1": "..."}` pieces.
+pub async fn stream_workspace_chat_answer(
+  state: &AppState,
+  uid: i64,
+  workspace_id: Uuid,
+  params: WorkspaceChatParams,
+) -> Result<impl Stream<Item = Result<Bytes, AppError>>, AppResponseError> {
+  let llm_client = state.llm_client.clone().ok_or_else(|| {
+    AppError::AIServiceUnavailable("No language model is configured on the server".to_string())
+  })?;
+  let question = params.question.trim();
+  if question.is_empty() {
+    return Err(AppError::InvalidRequest("The question is empty".to_string()).into());
+  }
+  if question.chars().count() > MAX_WORKSPACE_CHAT_QUESTION_CHARS {
+    return Err(
+      AppError::InvalidRequest(format!(
+        "The question must not exceed {} characters",
+        MAX_WORKSPACE_CHAT_QUESTION_CHARS
+      ))
+      .into(),
+    );
+  }
+  consume_workspace_ai_request(&state.pg_pool, &state.config.ai_quota, &workspace_id).await?;
+
+  let embedder = state.indexer_provider.embedder();
+  let documents = search_document(
+    &state.pg_pool,
+    &state.collab_access_control,
+    embedder.as_ref(),
+    uid,
+    workspace_id,
+    SearchDocumentRequest {
+      query: question.to_string(),
+      limit: Some(WORKSPACE_CHAT_SOURCES),
+      preview_size: Some(WORKSPACE_CHAT_SOURCE_CHARS),
+    },
+    &state.metrics.request_metrics,
+  )
+  .await?;
+  let sources = documents
+    .into_iter()
+    .map(|document| WorkspaceChatSource {
+      view_id: document.object_id,
+      score: document.score,
+      preview: document.preview,
+    })
+    .collect::<Vec<_>>();
+
+  let prompt = workspace_chat_prompt(question, &sources);
+  let mut answer = llm_client
+    .stream_chat(WORKSPACE_CHAT_INSTRUCTIONS, &prompt)
+    .await
+    .map_err(|err| AppError::AIServiceUnavailable(err.to_string()))?
+    .boxed();
+  let metadata = serde_json::to_vec(&json!({ STEAM_METADATA_KEY: sources }))?;
+  let pg_pool = state.pg_pool.clone();
+  Ok(stream! {
+    yield Ok::<Bytes, AppError>(Bytes::from(metadata));
+    let mut tokens = 0;
+    while let Some(value) = answer.next().await {
+      match value {
+        Ok(LLMStreamValue::Text(text)) => {
+          match serde_json::to_vec(&json!({ STEAM_ANSWER_KEY: text })) {
+            Ok(bytes) => yield Ok(Bytes::from(bytes)),
+            Err(err) => {
+              yield Err(AppError::from(err));
+              break;
+            },
+          }
+        },
+        Ok(LLMStreamValue::Usage { prompt_tokens, completion_tokens }) => {
+          tokens = prompt_tokens + completion_tokens;
+        },
+        Err(err) => {
+          error!("Failed to stream the workspace chat answer: {}", err);
+          yield Err(AppError::AIServiceUnavailable(err.to_string()));
+          break;
+        },
+      }
+    }
+    record_workspace_ai_tokens(&pg_pool, &workspace_id, tokens).await;
+  })
+}
+
+fn workspace_chat_prompt(question: &str, sources: &[WorkspaceChatSource]) -> String {
+  let mut prompt = String::from("Sources:\n");
+  if sources.is_empty() {
+    prompt.push_str("None of the documents of the workspace is related to the question.\n");
+  }
+  for (index, source) in sources.iter().enumerate() {
+    prompt.push_str(&format!(
+      "[{}] {}\n\n",
+      index + 1,
+      source.preview.as_deref().unwrap_or_default().trim()
+    ));
+  }
+  prompt.push_str("\nQuestion: ");
+  prompt.push_str(question);
+  prompt
+}
@@ -12,7 +12,10 @@ use app_error::AppError;
 use appflowy_collaborate::collab::cache::CollabCache;
 use database_entity::dto::AFAccessLevel;
 
-use crate::api::workspace::{COLLAB_PATTERN, V1_COLLAB_PATTERN};
+use crate::api::workspace::{
+  COLLAB_INHERITANCE_PATTERN, COLLAB_PATTERN, COLLAB_SHARE_LINK_ITEM_PATTERN,
+  COLLAB_SHARE_LINK_PATTERN, COLLAB_SHARE_LINK_ROTATE_PATTERN, V1_COLLAB_PATTERN,
+};
 use crate::middleware::access_control_mw::{AccessResource, MiddlewareAccessControl};
 
 #[derive(Clone)]
@@ -51,6 +54,37 @@ where
           ]
           .into(),
         ),
+        (
+          // Only the user with FullAccess can manage the share links of the collab
+          ResourceDef::new(COLLAB_SHARE_LINK_PATTERN),
+          [
+            (Method::GET, AFAccessLevel::FullAccess),
+            (Method::POST, AFAccessLevel::FullAccess),
+            (Method::DELETE, AFAccessLevel::FullAccess),
+          ]
+          .into(),
+        ),
+        (
+          ResourceDef::new(COLLAB_SHARE_LINK_ITEM_PATTERN),
+          [
+            (Method::PUT, AFAccessLevel::FullAccess),
+            (Method::DELETE, AFAccessLevel::FullAccess),
+          ]
+          .into(),
+        ),
+        (
+          ResourceDef::new(COLLAB_SHARE_LINK_ROTATE_PATTERN),
+          [(Method::POST, AFAccessLevel::FullAccess)].into(),
+        ),
+        (
+          // Only the user with FullAccess can break or restore the inheritance of the collab
+          ResourceDef::new(COLLAB_INHERITANCE_PATTERN),
+          [
+            (Method::PUT, AFAccessLevel::FullAccess),
+            (Method::DELETE, AFAccessLevel::FullAccess),
+          ]
+          .into(),
+        ),
       ],
       access_control,
       collab_cache,
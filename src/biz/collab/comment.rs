@@ -0,0 +1,368 @@
+use std::collections::HashSet;
+use std::ops::DerefMut;
+
+use access_control::act::Action;
+use access_control::collab::CollabAccessControl;
+use anyhow::Context;
+use app_error::AppError;
+use database::collab_comment::{
+  delete_collab_comment, insert_collab_comment, insert_collab_comment_mentions,
+  select_collab_comment, select_collab_comments, select_workspace_member_uids_by_uuids,
+  update_collab_comment_resolution,
+};
+use database::pg_row::AFCollabCommentRow;
+use database::user::select_web_users_from_uids;
+use database_entity::dto::{AFAccessLevel, CollabComment, CreateCollabCommentParams};
+use serde_json::json;
+use shared_entity::dto::notification_dto::NotificationKind;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::biz::notification::ops::record_notification_or_log;
+
+/// The length of a hyphenated uuid, as written in a mention.
+const MENTION_UUID_LEN: usize = 36;
+
+/// Returns the users mentioned in the content of a comment, in order of first mention. A mention
+/// is written as `@` followed by the hyphenated uuid of the user.
+pub fn extract_mentions(content: &str) -> Vec<Uuid> {
+  let mut mentions = vec![];
+  for (index, _) in content.match_indices('@') {
+    let start = index + 1;
+    let uuid = content
+      .get(start..start + MENTION_UUID_LEN)
+      .and_then(|s| Uuid::parse_str(s).ok());
+    if let Some(uuid) = uuid {
+      if !mentions.contains(&uuid) {
+        mentions.push(uuid);
+      }
+    }
+  }
+  mentions
+}
+
+pub async fn create_collab_comment(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  uid: i64,
+  workspace_id: &Uuid,
+  object_id: &str,
+  params: CreateCollabCommentParams,
+) -> Result<CollabComment, AppError> {
+  enforce_comment_action(
+    collab_access_control,
+    uid,
+    workspace_id,
+    object_id,
+    "comment on",
+  )
+  .await?;
+  if params.block_id.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "A comment must be anchored to a block".to_string(),
+    ));
+  }
+  if params.content.trim().is_empty() {
+    return Err(AppError::InvalidRequest(
+      "The content of a comment cannot be empty".to_string(),
+    ));
+  }
+
+  // Replies always point to the first comment of the thread
+  let mut replied_to_uid = None;
+  let reply_comment_id = match params.reply_comment_id {
+    Some(reply_comment_id) => {
+      let parent = get_comment(pg_pool, workspace_id, object_id, &reply_comment_id).await?;
+      if parent.block_id != params.block_id {
+        return Err(AppError::InvalidRequest(format!(
+          "Comment {} is not anchored to block {}",
+          reply_comment_id, params.block_id
+        )));
+      }
+      replied_to_uid = parent.created_by;
+      Some(parent.reply_comment_id.unwrap_or(parent.comment_id))
+    },
+    None => None,
+  };
+
+  let mentioned_uuids = extract_mentions(&params.content);
+  let mut transaction = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to create collab comment")?;
+  let comment_id = insert_collab_comment(
+    transaction.deref_mut(),
+    workspace_id,
+    object_id,
+    &params.block_id,
+    reply_comment_id,
+    &params.content,
+    uid,
+  )
+  .await?;
+  let mentioned_uids = if mentioned_uuids.is_empty() {
+    vec![]
+  } else {
+    let mentioned_uids = select_workspace_member_uids_by_uuids(
+      transaction.deref_mut(),
+      workspace_id,
+      &mentioned_uuids,
+    )
+    .await?;
+    insert_collab_comment_mentions(transaction.deref_mut(), &comment_id, &mentioned_uids).await?;
+    mentioned_uids
+  };
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to create collab comment")?;
+
+  let comment = get_comment(pg_pool, workspace_id, object_id, &comment_id).await?;
+  let comment = to_collab_comments(pg_pool, vec![comment]).await?.remove(0);
+  notify_comment(
+    pg_pool,
+    uid,
+    workspace_id,
+    &comment,
+    &mentioned_uids,
+    replied_to_uid,
+  )
+  .await;
+  Ok(comment)
+}
+
+/// Notify the mentioned users, and the author of the comment replied to. Nobody is notified of
+/// their own comment, nor twice for the same comment.
+async fn notify_comment(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  comment: &CollabComment,
+  mentioned_uids: &[i64],
+  replied_to_uid: Option<i64>,
+) {
+  let data = json!({
+    "actor_name": comment.created_by.as_ref().map(|user| user.name.clone()),
+    "object_id": comment.object_id,
+    "block_id": comment.block_id,
+    "comment_id": comment.comment_id,
+    "content": comment.content,
+  });
+  let mentioned_uids: Vec<i64> = mentioned_uids
+    .iter()
+    .copied()
+    .filter(|mentioned_uid| *mentioned_uid != uid)
+    .collect();
+  record_notification_or_log(
+    pg_pool,
+    &mentioned_uids,
+    Some(workspace_id),
+    NotificationKind::Mention,
+    data.clone(),
+  )
+  .await;
+  if let Some(replied_to_uid) = replied_to_uid {
+    if replied_to_uid != uid && !mentioned_uids.contains(&replied_to_uid) {
+      record_notification_or_log(
+        pg_pool,
+        &[replied_to_uid],
+        Some(workspace_id),
+        NotificationKind::CommentReply,
+        data,
+      )
+      .await;
+    }
+  }
+}
+
+/// Returns the comments on the collab, oldest first, including the resolved and deleted ones.
+pub async fn get_collab_comments(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  uid: i64,
+  workspace_id: &Uuid,
+  object_id: &str,
+  block_id: Option<&str>,
+) -> Result<Vec<CollabComment>, AppError> {
+  enforce_comment_action(
+    collab_access_control,
+    uid,
+    workspace_id,
+    object_id,
+    "read the comments of",
+  )
+  .await?;
+  let rows = select_collab_comments(pg_pool, workspace_id, object_id, block_id).await?;
+  to_collab_comments(pg_pool, rows).await
+}
+
+/// Resolve, or reopen, the thread started by the comment. The author of the comment and the users
+/// that can edit the collab are allowed to.
+pub async fn resolve_collab_comment(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  uid: i64,
+  workspace_id: &Uuid,
+  object_id: &str,
+  comment_id: &Uuid,
+  resolved: bool,
+) -> Result<CollabComment, AppError> {
+  enforce_comment_action(
+    collab_access_control,
+    uid,
+    workspace_id,
+    object_id,
+    "resolve the comments of",
+  )
+  .await?;
+  let comment = get_comment(pg_pool, workspace_id, object_id, comment_id).await?;
+  if comment.reply_comment_id.is_some() {
+    return Err(AppError::InvalidRequest(format!(
+      "Comment {} is a reply, only the first comment of a thread can be resolved",
+      comment_id
+    )));
+  }
+  if comment.created_by != Some(uid)
+    && !collab_access_control
+      .enforce_action(&workspace_id.to_string(), &uid, object_id, Action::Write)
+      .await?
+  {
+    return Err(AppError::NotEnoughPermissions {
+      user: uid.to_string(),
+      action: format!("resolve comment {} of collab:{}", comment_id, object_id),
+    });
+  }
+
+  update_collab_comment_resolution(pg_pool, comment_id, resolved.then_some(uid)).await?;
+  let comment = get_comment(pg_pool, workspace_id, object_id, comment_id).await?;
+  let mut comments = to_collab_comments(pg_pool, vec![comment]).await?;
+  Ok(comments.remove(0))
+}
+
+/// Delete the comment. The author of the comment and the users with full access to the collab
+/// are allowed to.
+pub async fn remove_collab_comment(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  uid: i64,
+  workspace_id: &Uuid,
+  object_id: &str,
+  comment_id: &Uuid,
+) -> Result<(), AppError> {
+  enforce_comment_action(
+    collab_access_control,
+    uid,
+    workspace_id,
+    object_id,
+    "delete the comments of",
+  )
+  .await?;
+  let comment = get_comment(pg_pool, workspace_id, object_id, comment_id).await?;
+  if comment.created_by != Some(uid)
+    && !collab_access_control
+      .enforce_access_level(
+        &workspace_id.to_string(),
+        &uid,
+        object_id,
+        AFAccessLevel::FullAccess,
+      )
+      .await?
+  {
+    return Err(AppError::NotEnoughPermissions {
+      user: uid.to_string(),
+      action: format!("delete comment {} of collab:{}", comment_id, object_id),
+    });
+  }
+  delete_collab_comment(pg_pool, comment_id).await
+}
+
+/// Only the users that can read the collab can read or post comments on it.
+async fn enforce_comment_action(
+  collab_access_control: &impl CollabAccessControl,
+  uid: i64,
+  workspace_id: &Uuid,
+  object_id: &str,
+  action: &str,
+) -> Result<(), AppError> {
+  if !collab_access_control
+    .enforce_action(&workspace_id.to_string(), &uid, object_id, Action::Read)
+    .await?
+  {
+    return Err(AppError::NotEnoughPermissions {
+      user: uid.to_string(),
+      action: format!("{} collab:{}", action, object_id),
+    });
+  }
+  Ok(())
+}
+
+async fn get_comment(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  object_id: &str,
+  comment_id: &Uuid,
+) -> Result<AFCollabCommentRow, AppError> {
+  select_collab_comment(pg_pool, comment_id)
+    .await?
+    .filter(|row| row.workspace_id == *workspace_id && row.object_id == object_id)
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!(
+        "Comment {} does not exist on collab {}",
+        comment_id, object_id
+      ))
+    })
+}
+
+async fn to_collab_comments(
+  pg_pool: &PgPool,
+  rows: Vec<AFCollabCommentRow>,
+) -> Result<Vec<CollabComment>, AppError> {
+  let mut uids = HashSet::new();
+  for row in &rows {
+    uids.extend(row.created_by);
+    uids.extend(row.resolved_by);
+    uids.extend(row.mentions.iter().copied());
+  }
+  let uids: Vec<i64> = uids.into_iter().collect();
+  let users = select_web_users_from_uids(pg_pool, &uids).await?;
+  let comments = rows
+    .into_iter()
+    .map(|row| CollabComment {
+      comment_id: row.comment_id,
+      object_id: row.object_id,
+      block_id: row.block_id,
+      reply_comment_id: row.reply_comment_id,
+      content: row.content,
+      created_by: row.created_by.and_then(|uid| users.get(&uid).cloned()),
+      created_at: row.created_at,
+      updated_at: row.updated_at,
+      resolved_by: row.resolved_by.and_then(|uid| users.get(&uid).cloned()),
+      resolved_at: row.resolved_at,
+      is_deleted: row.is_deleted,
+      mentions: row
+        .mentions
+        .iter()
+        .filter_map(|uid| users.get(uid).cloned())
+        .collect(),
+    })
+    .collect();
+  Ok(comments)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn extract_mentions_test() {
+    let alice = Uuid::new_v4();
+    let bob = Uuid::new_v4();
+    let content = format!(
+      "@{} can you check this with @{}? cc @{} and @not-a-user, mail me@example.com",
+      alice, bob, alice
+    );
+    assert_eq!(extract_mentions(&content), vec![alice, bob]);
+    assert!(extract_mentions("no mention @").is_empty());
+    assert!(extract_mentions("@ünïcødé").is_empty());
+  }
+}
@@ -0,0 +1,124 @@
+//! Compaction of the encoded state of the collabs.
+//!
+//! A collab is stored as a single encoded state, which keeps the content removed by the edits made
+//! to it. The compaction loads that state in a document with the garbage collection enabled and
+//! encodes it again, which drops the removed content and makes the collab faster to load.
+
+use std::sync::Arc;
+
+use app_error::AppError;
+use appflowy_collaborate::collab::cache::CollabCache;
+use collab::entity::EncodedCollab;
+use collab_entity::CollabType;
+use database::collab::{
+  select_blob_for_update, select_collabs_to_compact, update_compacted_blob, update_compacted_len,
+};
+use sqlx::PgPool;
+use tracing::{trace, warn};
+
+use crate::api::metrics::CollabCompactionMetrics;
+use crate::biz::workspace::publish_dup::collab_from_doc_state;
+
+/// Periodically compact the large collabs, see [compact_collabs].
+/// A zero `period` disables the task.
+pub fn spawn_compact_collabs(
+  pg_pool: PgPool,
+  collab_cache: CollabCache,
+  metrics: Arc<CollabCompactionMetrics>,
+  period: std::time::Duration,
+  min_len: i32,
+  batch_size: i64,
+) {
+  if period.is_zero() {
+    return;
+  }
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(period);
+    loop {
+      interval.tick().await;
+      if let Err(err) =
+        compact_collabs(&pg_pool, &collab_cache, &metrics, min_len, batch_size).await
+      {
+        warn!("Failed to compact collabs: {:?}", err);
+      }
+    }
+  });
+}
+
+/// Compacts up to `batch_size` collabs larger than `min_len` bytes which have been written since
+/// they were last compacted. A collab that fails to be compacted doesn't stop the others.
+pub async fn compact_collabs(
+  pg_pool: &PgPool,
+  collab_cache: &CollabCache,
+  metrics: &CollabCompactionMetrics,
+  min_len: i32,
+  batch_size: i64,
+) -> Result<(), AppError> {
+  let collabs = select_collabs_to_compact(pg_pool, min_len, batch_size).await?;
+  for (object_id, collab_type) in collabs {
+    match compact_collab(pg_pool, &object_id, &collab_type).await {
+      Ok(reclaimed) if reclaimed > 0 => {
+        trace!(
+          "Compacted collab {}: {} bytes reclaimed",
+          object_id,
+          reclaimed
+        );
+        metrics.incr_compacted_count(1);
+        metrics.incr_reclaimed_bytes(reclaimed);
+        // The memory cache still holds the state as it was before the compaction.
+        if let Err(err) = collab_cache.remove_encode_collab_in_mem(&object_id).await {
+          warn!(
+            "Failed to remove compacted collab {} from the memory cache: {:?}",
+            object_id, err
+          );
+        }
+      },
+      Ok(_) => {},
+      Err(err) => {
+        metrics.incr_failure_count(1);
+        warn!("Failed to compact collab {}: {:?}", object_id, err);
+      },
+    }
+  }
+  Ok(())
+}
+
+/// Compacts the encoded state of the collab and returns the number of bytes reclaimed. The state
+/// is only replaced when the compacted one is smaller. The row of the collab stays locked until
+/// then, so that an edit written meanwhile is not lost.
+pub async fn compact_collab(
+  pg_pool: &PgPool,
+  object_id: &str,
+  collab_type: &CollabType,
+) -> Result<i64, AppError> {
+  let mut txn = pg_pool.begin().await?;
+  let blob = match select_blob_for_update(&mut txn, object_id, collab_type).await? {
+    Some(blob) => blob,
+    // deleted since it was selected
+    None => return Ok(0),
+  };
+  let len = blob.len() as i64;
+
+  let cloned_object_id = object_id.to_string();
+  let cloned_collab_type = collab_type.clone();
+  let compacted = tokio::task::spawn_blocking(move || {
+    let encoded_collab = EncodedCollab::decode_from_bytes(&blob)
+      .map_err(|err| AppError::Internal(anyhow::anyhow!("Failed to decode collab: {:?}", err)))?;
+    let collab = collab_from_doc_state(encoded_collab.doc_state.to_vec(), &cloned_object_id)?;
+    let bin = collab
+      .encode_collab_v1(|collab| cloned_collab_type.validate_require_data(collab))
+      .map_err(|e| AppError::Unhandled(e.to_string()))?
+      .encode_to_bytes()?;
+    Ok::<_, AppError>(bin)
+  })
+  .await??;
+
+  let reclaimed = len - compacted.len() as i64;
+  if reclaimed > 0 {
+    update_compacted_blob(&mut txn, object_id, collab_type, &compacted).await?;
+  } else {
+    update_compacted_len(&mut txn, object_id, collab_type).await?;
+  }
+  txn.commit().await?;
+  Ok(reclaimed.max(0))
+}
@@ -0,0 +1,72 @@
+use access_control::act::Action;
+use access_control::collab::CollabAccessControl;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use database::collab::{select_collab_contributors, CollabStorage};
+use database_entity::dto::{CollabContribution, CollabContributor, CollabContributors};
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Returns the users who edited the collab, most recent first. The edits made over the realtime
+/// connection are only counted once the collab is saved. Only the users that can read the collab
+/// can see who edited it.
+pub async fn get_collab_contributors(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  uid: i64,
+  workspace_id: &Uuid,
+  object_id: &str,
+) -> Result<CollabContributors, AppError> {
+  if !collab_access_control
+    .enforce_action(&workspace_id.to_string(), &uid, object_id, Action::Read)
+    .await?
+  {
+    return Err(AppError::NotEnoughPermissions {
+      user: uid.to_string(),
+      action: format!("read the contributors of collab:{}", object_id),
+    });
+  }
+  let contributors: Vec<CollabContributor> = select_collab_contributors(pg_pool, object_id)
+    .await?
+    .into_iter()
+    .map(|row| CollabContributor {
+      uid: row.uid,
+      name: row.name,
+      email: row.email,
+      edit_count: row.edit_count,
+      first_edited_at: row.first_edited_at,
+      last_edited_at: row.last_edited_at,
+    })
+    .collect();
+  Ok(CollabContributors {
+    last_edited_by: contributors.first().cloned(),
+    contributors,
+  })
+}
+
+/// Record an edit the user made to the collab outside of the realtime connection. A failure is
+/// only logged, as the edit itself is already saved.
+pub async fn record_collab_edit_or_log(
+  collab_storage: &CollabAccessControlStorage,
+  workspace_id: &str,
+  object_id: &str,
+  uid: i64,
+) {
+  let now = chrono::Utc::now();
+  let contribution = CollabContribution {
+    uid,
+    edit_count: 1,
+    first_edited_at: now,
+    last_edited_at: now,
+  };
+  if let Err(err) = collab_storage
+    .record_collab_contributions(workspace_id, object_id, vec![contribution])
+    .await
+  {
+    warn!(
+      "Failed to record the edit of collab {} by user {}: {}",
+      object_id, uid, err
+    );
+  }
+}
@@ -0,0 +1,148 @@
+use std::ops::DerefMut;
+use std::sync::Arc;
+
+use access_control::act::Action;
+use access_control::collab::CollabAccessControl;
+use anyhow::{anyhow, Context};
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab::entity::EncodedCollab;
+use collab::preclude::Collab;
+use database::collab::{select_blob_for_update, CollabStorage, GetCollabOrigin};
+use database_entity::dto::CollabParams;
+use serde_json::json;
+use shared_entity::dto::workspace_dto::{
+  CollabDeltaSyncParams, CollabDeltaSyncResponse, WebhookEvent,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+use yrs::updates::decoder::Decode;
+use yrs::updates::encoder::Encode;
+use yrs::{ReadTxn, StateVector, Update};
+
+use crate::biz::workspace::publish_dup::{broadcast_update, collab_from_doc_state, collab_to_bin};
+use crate::biz::workspace::webhook::enqueue_webhook_event_or_log;
+
+use super::contributor::record_collab_edit_or_log;
+use super::ops::get_latest_collab_encoded;
+
+/// Sync the collab in a single request, for the clients that can't keep the realtime connection
+/// open. The update of the client, if any, is applied first and sent to the connected clients once
+/// it is stored. Then the changes missing from the client's copy, given its state vector, are
+/// returned.
+#[allow(clippy::too_many_arguments)]
+pub async fn delta_sync_collab(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  collab_access_control: &impl CollabAccessControl,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  object_id: &str,
+  params: CollabDeltaSyncParams,
+) -> Result<CollabDeltaSyncResponse, AppError> {
+  let state_vector = StateVector::decode_v1(&params.state_vector)
+    .map_err(|err| AppError::InvalidRequest(format!("Invalid state vector: {}", err)))?;
+  let client_update = match params.update {
+    Some(encoded_update) => {
+      let update = Update::decode_v1(&encoded_update)
+        .map_err(|err| AppError::InvalidRequest(format!("Invalid update: {}", err)))?;
+      Some((encoded_update, update))
+    },
+    None => None,
+  };
+
+  let workspace_id_str = workspace_id.to_string();
+  if client_update.is_some()
+    && !collab_access_control
+      .enforce_action(&workspace_id_str, &uid, object_id, Action::Write)
+      .await?
+  {
+    return Err(AppError::NotEnoughPermissions {
+      user: uid.to_string(),
+      action: format!("update collab:{}", object_id),
+    });
+  }
+
+  let current = get_latest_collab_encoded(
+    collab_storage.clone(),
+    GetCollabOrigin::User { uid },
+    &workspace_id_str,
+    object_id,
+    params.collab_type.clone(),
+  )
+  .await?;
+  let mut collab = collab_from_doc_state(current.doc_state.to_vec(), object_id)?;
+  let (encoded_update, update) = match client_update {
+    Some(client_update) => client_update,
+    None => return Ok(delta_sync_response(&collab, &state_vector)),
+  };
+
+  // The row of the collab stays locked until the update is written, so that concurrent syncs
+  // apply their updates one after the other. The stored state is merged in, as the state read
+  // above may have been served from before the sync that held the lock last.
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to sync collab")?;
+  if let Some(blob) = select_blob_for_update(&mut txn, object_id, &params.collab_type).await? {
+    let stored = EncodedCollab::decode_from_bytes(&blob)
+      .map_err(|err| AppError::Internal(anyhow!("Failed to decode collab: {:?}", err)))?;
+    let stored_update = Update::decode_v1(&stored.doc_state)
+      .map_err(|err| AppError::Internal(anyhow!("Failed to decode collab: {}", err)))?;
+    collab
+      .context
+      .transact_mut()
+      .apply_update(stored_update)
+      .map_err(|err| AppError::Internal(anyhow!("Failed to merge the stored collab: {}", err)))?;
+  }
+  {
+    let mut collab_txn = collab.context.transact_mut();
+    collab_txn
+      .apply_update(update)
+      .map_err(|err| AppError::InvalidRequest(format!("Failed to apply the update: {}", err)))?;
+    // The update builds on changes the server never received, the client has to send them too
+    if collab_txn.store().pending_update().is_some() {
+      return Err(AppError::InvalidRequest(
+        "The update depends on changes missing from the server".to_string(),
+      ));
+    }
+  }
+  let response = delta_sync_response(&collab, &state_vector);
+
+  let encoded_collab = collab_to_bin(collab, params.collab_type.clone()).await?;
+  collab_storage
+    .insert_new_collab_with_transaction(
+      &workspace_id_str,
+      &uid,
+      CollabParams {
+        object_id: object_id.to_string(),
+        encoded_collab_v1: encoded_collab.into(),
+        collab_type: params.collab_type.clone(),
+        embeddings: None,
+      },
+      txn.deref_mut(),
+    )
+    .await?;
+  txn
+    .commit()
+    .await
+    .context("fail to commit the transaction to sync collab")?;
+  broadcast_update(&collab_storage, object_id, encoded_update).await?;
+  record_collab_edit_or_log(&collab_storage, &workspace_id_str, object_id, uid).await;
+  enqueue_webhook_event_or_log(
+    pg_pool,
+    workspace_id,
+    WebhookEvent::CollabUpdated,
+    json!({ "object_id": object_id, "collab_type": params.collab_type, "uid": uid }),
+  )
+  .await;
+  Ok(response)
+}
+
+fn delta_sync_response(collab: &Collab, state_vector: &StateVector) -> CollabDeltaSyncResponse {
+  let txn = collab.transact();
+  CollabDeltaSyncResponse {
+    missing_update: txn.encode_state_as_update_v1(state_vector),
+    state_vector: txn.state_vector().encode_v1(),
+  }
+}
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use database::collab::{select_access_levels_for_user, GetCollabOrigin};
+use database::workspace::select_workspace_member;
+use database::workspace_group::select_collab_group_access_levels_for_user;
+use database_entity::dto::{AFAccessLevel, AccessDecisionStep, AccessSource, EffectiveAccess};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::object_token::resolve_object_token_row;
+use super::ops::get_latest_collab_folder;
+use super::sharing::view_ancestor_ids;
+
+/// Resolve the access level `uid` holds on `object_id`.
+///
+/// A membership on the object itself takes precedence over the memberships on its ancestors, and
+/// among the ancestors the nearest membership wins. The role of the user in the workspace, the
+/// grants on the object to the groups the user belongs to and the object token, when given, all
+/// grant their level on top of that, so the highest level wins, and on a tie the source evaluated
+/// first. A suspended membership grants no access, as if the user was not a member.
+///
+/// With `explain`, every source that was considered is returned in evaluation order, together
+/// with the level it grants and whether it won.
+pub async fn resolve_effective_access(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  uid: i64,
+  object_id: &str,
+  object_token: Option<&str>,
+  explain: bool,
+) -> Result<EffectiveAccess, AppError> {
+  let workspace_id_str = workspace_id.to_string();
+  let folder =
+    get_latest_collab_folder(collab_storage, GetCollabOrigin::Server, &workspace_id_str).await?;
+  let ancestor_ids = view_ancestor_ids(&folder, &workspace_id_str, object_id);
+
+  let mut object_ids = Vec::with_capacity(ancestor_ids.len() + 1);
+  object_ids.push(object_id.to_string());
+  object_ids.extend(ancestor_ids.iter().cloned());
+  let levels = select_access_levels_for_user(uid, &object_ids, pg_pool).await?;
+
+  let mut steps = Vec::with_capacity(object_ids.len() + 2);
+  steps.push(AccessDecisionStep {
+    source: AccessSource::Direct,
+    access_level: levels.get(object_id).copied(),
+    won: false,
+  });
+  for ancestor_id in ancestor_ids {
+    steps.push(AccessDecisionStep {
+      access_level: levels.get(&ancestor_id).copied(),
+      source: AccessSource::Inherited {
+        from_object_id: ancestor_id,
+      },
+      won: false,
+    });
+  }
+  // Only the nearest membership counts, the ones further up are shadowed by it
+  let membership_step = steps.iter().position(|step| step.access_level.is_some());
+  let mut candidates: Vec<usize> = membership_step.into_iter().collect();
+
+  match select_workspace_member(pg_pool, &uid, workspace_id).await {
+    Ok(member) => {
+      candidates.push(steps.len());
+      steps.push(AccessDecisionStep {
+        access_level: Some(AFAccessLevel::from(&member.role)),
+        source: AccessSource::WorkspaceRole { role: member.role },
+        won: false,
+      });
+    },
+    Err(AppError::RecordNotFound(_)) => {},
+    Err(err) => return Err(err),
+  }
+
+  for (group_id, access_level) in
+    select_collab_group_access_levels_for_user(pg_pool, workspace_id, uid, object_id).await?
+  {
+    candidates.push(steps.len());
+    steps.push(AccessDecisionStep {
+      source: AccessSource::Group { group_id },
+      access_level: Some(access_level),
+      won: false,
+    });
+  }
+
+  if let Some(token) = object_token {
+    let (token_id, access_level) = match resolve_object_token_row(pg_pool, token).await {
+      Ok((row, level)) => (Some(row.id), (row.oid == object_id).then_some(level)),
+      Err(AppError::UserUnAuthorized(_)) => (None, None),
+      Err(err) => return Err(err),
+    };
+    if access_level.is_some() {
+      candidates.push(steps.len());
+    }
+    steps.push(AccessDecisionStep {
+      source: AccessSource::ObjectToken { token_id },
+      access_level,
+      won: false,
+    });
+  }
+
+  let winner = candidates
+    .into_iter()
+    .fold(None, |winner: Option<usize>, i| match winner {
+      Some(w) if steps[w].access_level >= steps[i].access_level => Some(w),
+      _ => Some(i),
+    });
+  let access_level = winner.and_then(|i| steps[i].access_level);
+  if let Some(i) = winner {
+    steps[i].won = true;
+  }
+
+  Ok(EffectiveAccess {
+    object_id: object_id.to_string(),
+    uid,
+    access_level,
+    explanation: explain.then_some(steps),
+  })
+}
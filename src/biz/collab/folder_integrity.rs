@@ -0,0 +1,181 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::DerefMut;
+use std::sync::Arc;
+
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab_entity::CollabType;
+use collab_folder::View;
+use database::collab::{CollabStorage, GetCollabOrigin};
+use database::workspace::select_workspace;
+use database_entity::dto::CollabParams;
+use shared_entity::dto::workspace_dto::{FolderIntegrityIssue, FolderIntegrityReport};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::biz::workspace::publish_dup::{broadcast_update, collab_to_bin};
+
+use super::ops::get_latest_collab_folder;
+
+/// Scan the folder of the workspace for orphaned views, views listed more than once as a child,
+/// and cycles. The folder is not modified.
+pub async fn check_folder_integrity(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  workspace_id: &Uuid,
+) -> Result<FolderIntegrityReport, AppError> {
+  let workspace_id = workspace_id.to_string();
+  let folder =
+    get_latest_collab_folder(collab_storage, GetCollabOrigin::Server, &workspace_id).await?;
+  let views = {
+    let txn = folder.collab.transact();
+    folder.body.views.get_all_views(&txn)
+  };
+  let issues = folder_integrity_issues(&workspace_id, &views);
+  Ok(FolderIntegrityReport {
+    workspace_id,
+    issues,
+  })
+}
+
+/// Repair the folder of the workspace: orphaned views are moved under the workspace root, and
+/// every cycle is broken by moving its first view under the workspace root. Views listed more
+/// than once as a child are only reported.
+///
+/// The repair rewrites the folder of a live workspace, so it is refused unless `confirm` is set.
+/// Returns the issues that were found before the repair.
+pub async fn repair_folder_integrity(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  confirm: bool,
+) -> Result<FolderIntegrityReport, AppError> {
+  if !confirm {
+    return Err(AppError::InvalidRequest(
+      "Repairing the folder must be confirmed explicitly".to_string(),
+    ));
+  }
+
+  let owner_uid = select_workspace(pg_pool, workspace_id)
+    .await?
+    .owner_uid
+    .ok_or_else(|| AppError::RecordNotFound(format!("owner of workspace {}", workspace_id)))?;
+  let workspace_id = workspace_id.to_string();
+  let mut folder = get_latest_collab_folder(
+    collab_storage.clone(),
+    GetCollabOrigin::Server,
+    &workspace_id,
+  )
+  .await?;
+  let views = {
+    let txn = folder.collab.transact();
+    folder.body.views.get_all_views(&txn)
+  };
+  let issues = folder_integrity_issues(&workspace_id, &views);
+
+  let moved_view_ids: Vec<&str> = issues
+    .iter()
+    .filter_map(|issue| match issue {
+      FolderIntegrityIssue::Orphan { view_id, .. } => Some(view_id.as_str()),
+      FolderIntegrityIssue::Cycle { view_ids } => view_ids.first().map(String::as_str),
+      FolderIntegrityIssue::DuplicateId { .. } => None,
+    })
+    .collect();
+  if moved_view_ids.is_empty() {
+    return Ok(FolderIntegrityReport {
+      workspace_id,
+      issues,
+    });
+  }
+
+  let encoded_update = {
+    let mut folder_txn = folder.collab.transact_mut();
+    for view_id in moved_view_ids {
+      folder
+        .body
+        .move_nested_view(&mut folder_txn, view_id, &workspace_id, None);
+    }
+    folder_txn.encode_update_v1()
+  };
+  let encoded_folder = collab_to_bin(folder.collab, CollabType::Folder).await?;
+
+  let mut txn = pg_pool.begin().await?;
+  collab_storage
+    .insert_new_collab_with_transaction(
+      &workspace_id,
+      &owner_uid,
+      CollabParams {
+        object_id: workspace_id.clone(),
+        encoded_collab_v1: encoded_folder.into(),
+        collab_type: CollabType::Folder,
+        embeddings: None,
+      },
+      txn.deref_mut(),
+    )
+    .await?;
+  broadcast_update(&collab_storage, &workspace_id, encoded_update).await?;
+  txn.commit().await?;
+
+  Ok(FolderIntegrityReport {
+    workspace_id,
+    issues,
+  })
+}
+
+/// Find the structural issues among the views of a folder. A view is reachable when following
+/// its parents ends at `root_view_id`. A view that is its own parent is standalone, such as the
+/// document of a database row, and is not an issue.
+fn folder_integrity_issues(root_view_id: &str, views: &[Arc<View>]) -> Vec<FolderIntegrityIssue> {
+  let mut parents: BTreeMap<&str, &str> = BTreeMap::new();
+  let mut listed_by: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+  for view in views {
+    parents.insert(view.id.as_str(), view.parent_view_id.as_str());
+    for child in view.children.iter() {
+      listed_by
+        .entry(child.id.as_str())
+        .or_default()
+        .push(view.id.clone());
+    }
+  }
+
+  let mut issues = vec![];
+  let mut resolved: HashSet<&str> = HashSet::new();
+  for &view_id in parents.keys() {
+    let mut path: Vec<&str> = vec![];
+    let mut position_in_path: HashMap<&str, usize> = HashMap::new();
+    let mut current = view_id;
+    while current != root_view_id && !resolved.contains(current) {
+      if let Some(&start) = position_in_path.get(current) {
+        issues.push(FolderIntegrityIssue::Cycle {
+          view_ids: path[start..].iter().map(|id| id.to_string()).collect(),
+        });
+        break;
+      }
+      position_in_path.insert(current, path.len());
+      path.push(current);
+
+      let parent_view_id = parents[current];
+      if parent_view_id == current || parent_view_id == root_view_id {
+        break;
+      }
+      if !parents.contains_key(parent_view_id) {
+        issues.push(FolderIntegrityIssue::Orphan {
+          view_id: current.to_string(),
+          parent_view_id: parent_view_id.to_string(),
+        });
+        break;
+      }
+      current = parent_view_id;
+    }
+    resolved.extend(path);
+  }
+
+  for (view_id, parent_view_ids) in listed_by {
+    if parent_view_ids.len() > 1 {
+      issues.push(FolderIntegrityIssue::DuplicateId {
+        view_id: view_id.to_string(),
+        parent_view_ids,
+      });
+    }
+  }
+  issues
+}
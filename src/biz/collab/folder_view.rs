@@ -1,9 +1,86 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use app_error::AppError;
 use chrono::DateTime;
 use collab_folder::{Folder, SectionItem, ViewLayout as CollabFolderViewLayout};
-use shared_entity::dto::workspace_dto::{FolderView, ViewLayout};
+use shared_entity::dto::workspace_dto::{
+  FolderPageInfo, FolderView, FolderViewAncestor, FolderViewConnection, FolderViewEdge,
+  FolderViewPathItem, FolderViewSearchResult, QueryWorkspaceFolder, ViewLayout, ViewPermissions,
+};
+
+use super::sharing::view_ancestor_ids;
+
+/// Restricts the views returned by [collab_folder_to_folder_view]. The default filter keeps every
+/// view the user can see.
+#[derive(Debug, Clone)]
+pub struct FolderViewFilter {
+  /// Only the views with one of these layouts are kept, along with the views leading to them
+  /// within the requested depth.
+  pub layouts: Option<Vec<ViewLayout>>,
+  /// Only this space is kept among the children of the root view.
+  pub space_id: Option<String>,
+  /// Whether the private spaces of the user, and their views, are kept.
+  pub include_private: bool,
+}
+
+impl Default for FolderViewFilter {
+  fn default() -> Self {
+    Self {
+      layouts: None,
+      space_id: None,
+      include_private: true,
+    }
+  }
+}
+
+impl FolderViewFilter {
+  /// Builds the filter from the query parameters of the folder, where the layouts are given as
+  /// comma separated [ViewLayout] values.
+  pub fn from_query(query: &QueryWorkspaceFolder) -> Result<Self, AppError> {
+    Self::new(
+      query.layouts.as_deref(),
+      query.space_id.clone(),
+      query.include_private,
+    )
+  }
+
+  pub fn new(
+    layouts: Option<&str>,
+    space_id: Option<String>,
+    include_private: Option<bool>,
+  ) -> Result<Self, AppError> {
+    let layouts = match layouts {
+      None => None,
+      Some(layouts) => Some(
+        layouts
+          .split(',')
+          .map(|layout| {
+            layout
+              .trim()
+              .parse::<u8>()
+              .map_err(|err| err.to_string())
+              .and_then(ViewLayout::try_from)
+              .map_err(|err| {
+                AppError::InvalidRequest(format!("Invalid layout {}: {}", layout, err))
+              })
+          })
+          .collect::<Result<Vec<_>, _>>()?,
+      ),
+    };
+    Ok(Self {
+      layouts,
+      space_id,
+      include_private: include_private.unwrap_or(true),
+    })
+  }
+
+  fn keeps_layout(&self, layout: &ViewLayout) -> bool {
+    match &self.layouts {
+      None => true,
+      Some(layouts) => layouts.contains(layout),
+    }
+  }
+}
 
 /// Return all folders belonging to a workspace, excluding private sections which the user does not have access to.
 pub fn collab_folder_to_folder_view(
@@ -11,21 +88,9 @@ pub fn collab_folder_to_folder_view(
   folder: &Folder,
   max_depth: u32,
   pubished_view_ids: &HashSet<String>,
+  filter: &FolderViewFilter,
 ) -> Result<FolderView, AppError> {
-  let mut unviewable = HashSet::new();
-  for private_section in folder.get_all_private_sections() {
-    unviewable.insert(private_section.id);
-  }
-  for trash_view in folder.get_all_trash_sections() {
-    unviewable.insert(trash_view.id);
-  }
-
-  let mut private_view_ids = HashSet::new();
-  for private_section in folder.get_my_private_sections() {
-    unviewable.remove(&private_section.id);
-    private_view_ids.insert(private_section.id);
-  }
-
+  let (unviewable, private_view_ids) = unviewable_view_ids(folder);
   to_folder_view(
     "",
     root_view_id,
@@ -33,6 +98,7 @@ pub fn collab_folder_to_folder_view(
     &unviewable,
     &private_view_ids,
     pubished_view_ids,
+    filter,
     false,
     0,
     max_depth,
@@ -43,6 +109,60 @@ pub fn collab_folder_to_folder_view(
   )))
 }
 
+/// Returns the ids of the views hidden from the user, the trashed views and the private spaces of
+/// the other members, and the ids of the user's own private spaces.
+fn unviewable_view_ids(folder: &Folder) -> (HashSet<String>, HashSet<String>) {
+  let mut unviewable = HashSet::new();
+  for private_section in folder.get_all_private_sections() {
+    unviewable.insert(private_section.id);
+  }
+  for trash_view in folder.get_all_trash_sections() {
+    unviewable.insert(trash_view.id);
+  }
+
+  let mut private_view_ids = HashSet::new();
+  for private_section in folder.get_my_private_sections() {
+    unviewable.remove(&private_section.id);
+    private_view_ids.insert(private_section.id);
+  }
+  (unviewable, private_view_ids)
+}
+
+/// Returns the ancestors of the view, from the top-most space down to its direct parent, as shown
+/// in a breadcrumb. The view is not found when it is hidden from the user, that is when it or one
+/// of its ancestors is trashed or in the private space of another member.
+pub fn folder_view_ancestors(
+  folder: &Folder,
+  workspace_id: &str,
+  view_id: &str,
+) -> Result<Vec<FolderViewAncestor>, AppError> {
+  let not_found = || AppError::RecordNotFound(format!("view {} not found in the folder", view_id));
+  if folder.get_view(view_id).is_none() {
+    return Err(not_found());
+  }
+  let (unviewable, _) = unviewable_view_ids(folder);
+  let ancestor_ids = view_ancestor_ids(folder, workspace_id, view_id);
+  if unviewable.contains(view_id) || ancestor_ids.iter().any(|id| unviewable.contains(id)) {
+    return Err(not_found());
+  }
+  let ancestors = ancestor_ids
+    .iter()
+    .rev()
+    .filter_map(|ancestor_id| folder.get_view(ancestor_id))
+    .map(|view| FolderViewAncestor {
+      view_id: view.id.clone(),
+      name: view.name.clone(),
+      icon: view
+        .icon
+        .as_ref()
+        .map(|icon| to_dto_view_icon(icon.clone())),
+      is_space: view_is_space(&view),
+      layout: to_view_layout(&view.layout),
+    })
+    .collect();
+  Ok(ancestors)
+}
+
 #[allow(clippy::too_many_arguments)]
 fn to_folder_view(
   parent_view_id: &str,
@@ -51,6 +171,7 @@ fn to_folder_view(
   unviewable: &HashSet<String>,
   private_view_ids: &HashSet<String>,
   published_view_ids: &HashSet<String>,
+  filter: &FolderViewFilter,
   parent_is_private: bool,
   depth: u32,
   max_depth: u32,
@@ -58,6 +179,14 @@ fn to_folder_view(
   if depth > max_depth || unviewable.contains(view_id) {
     return None;
   }
+  if depth == 1
+    && filter
+      .space_id
+      .as_ref()
+      .is_some_and(|space_id| space_id != view_id)
+  {
+    return None;
+  }
 
   let view = match folder.get_view(view_id) {
     Some(view) => view,
@@ -73,6 +202,9 @@ fn to_folder_view(
 
   let is_private =
     parent_is_private || (view_is_space(&view) && private_view_ids.contains(view_id));
+  if is_private && !filter.include_private {
+    return None;
+  }
   let extra = view.extra.as_deref().map(|extra| {
     serde_json::from_str::<serde_json::Value>(extra).unwrap_or_else(|e| {
       tracing::warn!("failed to parse extra field({}): {}", extra, e);
@@ -90,12 +222,17 @@ fn to_folder_view(
         unviewable,
         private_view_ids,
         published_view_ids,
+        filter,
         is_private,
         depth + 1,
         max_depth,
       )
     })
     .collect();
+  let layout = to_view_layout(&view.layout);
+  if depth > 0 && children.is_empty() && !filter.keeps_layout(&layout) {
+    return None;
+  }
   Some(FolderView {
     view_id: view_id.to_string(),
     name: view.name.clone(),
@@ -106,11 +243,13 @@ fn to_folder_view(
     is_space: view_is_space(&view),
     is_private,
     is_published: published_view_ids.contains(view_id),
-    layout: to_view_layout(&view.layout),
+    layout,
     created_at: DateTime::from_timestamp(view.created_at, 0).unwrap_or_default(),
     last_edited_time: DateTime::from_timestamp(view.last_edited_time, 0).unwrap_or_default(),
     extra,
     children,
+    server_version: None,
+    permissions: None,
   })
 }
 
@@ -135,6 +274,8 @@ pub fn section_items_to_folder_view(
         layout: to_view_layout(&v.layout),
         extra: v.extra.as_ref().map(|e| parse_extra_field_as_json(e)),
         children: vec![],
+        server_version: None,
+        permissions: None,
       })
     })
     .collect()
@@ -193,3 +334,195 @@ pub fn to_view_layout(collab_folder_view_layout: &CollabFolderViewLayout) -> Vie
     CollabFolderViewLayout::Chat => ViewLayout::Chat,
   }
 }
+
+/// Returns the ids of all the views in the tree that have children.
+pub fn folder_view_parent_ids(folder_view: &FolderView) -> Vec<String> {
+  let mut parent_ids = vec![];
+  let mut stack = vec![folder_view];
+  while let Some(view) = stack.pop() {
+    if !view.children.is_empty() {
+      parent_ids.push(view.view_id.clone());
+      stack.extend(view.children.iter());
+    }
+  }
+  parent_ids
+}
+
+/// Take the view with the given id out of the tree, together with its subtree.
+pub fn take_folder_view(root: FolderView, view_id: &str) -> Option<FolderView> {
+  let mut stack = vec![root];
+  while let Some(view) = stack.pop() {
+    if view.view_id == view_id {
+      return Some(view);
+    }
+    stack.extend(view.children);
+  }
+  None
+}
+
+/// Returns the page of `children` that follows the child whose view id is `after`, with at most
+/// `first` edges. The view id of a child is its cursor.
+pub fn folder_children_connection(
+  children: Vec<FolderView>,
+  first: usize,
+  after: Option<&str>,
+) -> Result<FolderViewConnection, AppError> {
+  let start = match after {
+    None => 0,
+    Some(cursor) => {
+      children
+        .iter()
+        .position(|child| child.view_id == cursor)
+        .ok_or_else(|| AppError::InvalidRequest(format!("Unknown cursor: {}", cursor)))?
+        + 1
+    },
+  };
+  let total = children.len();
+  let edges: Vec<FolderViewEdge> = children
+    .into_iter()
+    .skip(start)
+    .take(first)
+    .map(|mut node| {
+      let has_children = !node.children.is_empty();
+      node.children.clear();
+      FolderViewEdge {
+        cursor: node.view_id.clone(),
+        node,
+        has_children,
+      }
+    })
+    .collect();
+  let page_info = FolderPageInfo {
+    has_next_page: start + edges.len() < total,
+    end_cursor: edges.last().map(|edge| edge.cursor.clone()),
+  };
+  Ok(FolderViewConnection { edges, page_info })
+}
+
+/// Returns the ids of all the views in the tree, including the root.
+pub fn folder_view_ids(folder_view: &FolderView) -> Vec<String> {
+  let mut view_ids = vec![];
+  let mut stack = vec![folder_view];
+  while let Some(view) = stack.pop() {
+    view_ids.push(view.view_id.clone());
+    stack.extend(view.children.iter());
+  }
+  view_ids
+}
+
+/// Set the permissions of every view in the tree. Views missing from the map get no permission.
+pub fn apply_view_permissions(
+  folder_view: &mut FolderView,
+  permissions: &HashMap<String, ViewPermissions>,
+) {
+  folder_view.permissions = Some(
+    permissions
+      .get(&folder_view.view_id)
+      .copied()
+      .unwrap_or_default(),
+  );
+  for child in folder_view.children.iter_mut() {
+    apply_view_permissions(child, permissions);
+  }
+}
+
+/// Reorder the children of each view according to the user's custom order, if any.
+/// Children listed in the custom order come first, in that order. Children that are not listed,
+/// such as views created after the order was saved, follow in the folder's order.
+pub fn apply_user_view_order(
+  folder_view: &mut FolderView,
+  user_view_orders: &HashMap<String, Vec<String>>,
+) {
+  if let Some(ordered_child_ids) = user_view_orders.get(&folder_view.view_id) {
+    let position: HashMap<&str, usize> = ordered_child_ids
+      .iter()
+      .enumerate()
+      .map(|(i, id)| (id.as_str(), i))
+      .collect();
+    // sort_by_key is stable, so unlisted children keep their relative folder order
+    folder_view.children.sort_by_key(|child| {
+      position
+        .get(child.view_id.as_str())
+        .copied()
+        .unwrap_or(usize::MAX)
+    });
+  }
+  for child in folder_view.children.iter_mut() {
+    apply_user_view_order(child, user_view_orders);
+  }
+}
+
+/// Returns the views below the root whose name contains the query, ignoring case, in the folder's
+/// order. With `include_ancestors`, the ancestors of every match are returned as well, right before
+/// their first matching descendant. The root itself is never returned.
+pub fn search_folder_view(
+  root: &FolderView,
+  query: &str,
+  include_ancestors: bool,
+) -> Vec<FolderViewSearchResult> {
+  let query = query.to_lowercase();
+  let mut results = vec![];
+  let mut returned_ids = HashSet::new();
+  let mut path = vec![];
+  for child in &root.children {
+    search_folder_view_recursive(
+      child,
+      &query,
+      include_ancestors,
+      &mut path,
+      &mut returned_ids,
+      &mut results,
+    );
+  }
+  results
+}
+
+fn search_folder_view_recursive<'a>(
+  view: &'a FolderView,
+  query: &str,
+  include_ancestors: bool,
+  path: &mut Vec<&'a FolderView>,
+  returned_ids: &mut HashSet<String>,
+  results: &mut Vec<FolderViewSearchResult>,
+) {
+  if view.name.to_lowercase().contains(query) {
+    if include_ancestors {
+      for (depth, ancestor) in path.iter().enumerate() {
+        if returned_ids.insert(ancestor.view_id.clone()) {
+          results.push(to_search_result(ancestor, &path[..depth], false));
+        }
+      }
+    }
+    returned_ids.insert(view.view_id.clone());
+    results.push(to_search_result(view, path, true));
+  }
+
+  path.push(view);
+  for child in &view.children {
+    search_folder_view_recursive(child, query, include_ancestors, path, returned_ids, results);
+  }
+  path.pop();
+}
+
+fn to_search_result(
+  view: &FolderView,
+  path: &[&FolderView],
+  is_match: bool,
+) -> FolderViewSearchResult {
+  FolderViewSearchResult {
+    view_id: view.view_id.clone(),
+    name: view.name.clone(),
+    icon: view.icon.clone(),
+    is_space: view.is_space,
+    is_private: view.is_private,
+    layout: view.layout.clone(),
+    path: path
+      .iter()
+      .map(|ancestor| FolderViewPathItem {
+        view_id: ancestor.view_id.clone(),
+        name: ancestor.name.clone(),
+      })
+      .collect(),
+    is_match,
+  }
+}
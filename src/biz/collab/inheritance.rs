@@ -0,0 +1,37 @@
+use access_control::access::AccessControl;
+use app_error::AppError;
+use database::collab::{delete_collab_inheritance_break, insert_collab_inheritance_break};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Stop the object, and the views below it, from inheriting the access granted on the views
+/// above it. The members of the object keep their access, and the views below it still inherit
+/// from the object. Only has an effect when the workspace inherits view permissions.
+pub async fn break_collab_inheritance(
+  pg_pool: &PgPool,
+  access_control: &AccessControl,
+  uid: i64,
+  workspace_id: &Uuid,
+  object_id: &str,
+) -> Result<(), AppError> {
+  insert_collab_inheritance_break(pg_pool, workspace_id, object_id, uid).await?;
+  access_control
+    .invalidate_collab_hierarchy(&workspace_id.to_string())
+    .await;
+  Ok(())
+}
+
+/// Let the object inherit the access granted on the views above it again.
+pub async fn restore_collab_inheritance(
+  pg_pool: &PgPool,
+  access_control: &AccessControl,
+  workspace_id: &Uuid,
+  object_id: &str,
+) -> Result<(), AppError> {
+  if delete_collab_inheritance_break(pg_pool, workspace_id, object_id).await? {
+    access_control
+      .invalidate_collab_hierarchy(&workspace_id.to_string())
+      .await;
+  }
+  Ok(())
+}
@@ -1,4 +1,18 @@
 pub mod access_control;
+pub mod comment;
+pub mod compaction;
+pub mod contributor;
+pub mod delta_sync;
+pub mod effective_access;
+pub mod folder_integrity;
 pub mod folder_view;
+pub mod inheritance;
+pub mod object_token;
 pub mod ops;
+pub mod presence;
 pub mod publish_outline;
+pub mod realtime_health;
+pub mod share_link;
+pub mod sharing;
+pub mod version;
+pub mod version_diff;
@@ -0,0 +1,136 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use database::collab::{
+  insert_collab_object_token, revoke_collab_object_token, select_collab_member,
+  select_collab_object_token,
+};
+use database::pg_row::AFCollabObjectTokenRow;
+use database_entity::dto::{AFAccessLevel, CollabObjectToken};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const OBJECT_TOKEN_LEN: usize = 48;
+
+/// Create a token that grants access to `object_id` without a user session.
+/// The granted access level is capped at the access level the creator holds on the object.
+pub async fn create_object_token(
+  pg_pool: &PgPool,
+  creator_uid: i64,
+  workspace_id: &Uuid,
+  object_id: &str,
+  access_level: AFAccessLevel,
+  expires_at: Option<DateTime<Utc>>,
+) -> Result<CollabObjectToken, AppError> {
+  if let Some(expires_at) = expires_at {
+    if expires_at <= Utc::now() {
+      return Err(AppError::InvalidRequest(
+        "The expiration time of the token must be in the future".to_string(),
+      ));
+    }
+  }
+
+  let creator_level = match select_collab_member(&creator_uid, object_id, pg_pool).await {
+    Ok(member) => member.permission.access_level,
+    Err(err) if err.is_record_not_found() => {
+      return Err(AppError::NotEnoughPermissions {
+        user: creator_uid.to_string(),
+        action: format!("create a token for collab:{}", object_id),
+      })
+    },
+    Err(err) => return Err(err),
+  };
+  let access_level = access_level.min(creator_level);
+
+  let token: String = rand::thread_rng()
+    .sample_iter(&Alphanumeric)
+    .take(OBJECT_TOKEN_LEN)
+    .map(char::from)
+    .collect();
+  let id = insert_collab_object_token(
+    pg_pool,
+    &token,
+    workspace_id,
+    object_id,
+    access_level,
+    creator_uid,
+    expires_at,
+  )
+  .await?;
+
+  Ok(CollabObjectToken {
+    id,
+    token,
+    object_id: object_id.to_string(),
+    access_level,
+    expires_at,
+  })
+}
+
+/// Resolve the token to the object it grants access to and the access level it grants.
+/// The access level is capped at the level the creator currently holds, so a token stops granting
+/// more than its creator once the creator is downgraded or removed from the object.
+pub async fn resolve_object_token(
+  pg_pool: &PgPool,
+  token: &str,
+) -> Result<(String, AFAccessLevel), AppError> {
+  let (row, access_level) = resolve_object_token_row(pg_pool, token).await?;
+  Ok((row.oid, access_level))
+}
+
+/// Check that the token grants at least `access_level` on `object_id`.
+/// Returns the workspace id the object belongs to.
+pub async fn enforce_object_token(
+  pg_pool: &PgPool,
+  token: &str,
+  object_id: &str,
+  access_level: AFAccessLevel,
+) -> Result<Uuid, AppError> {
+  let (row, granted_level) = resolve_object_token_row(pg_pool, token).await?;
+  if row.oid != object_id || granted_level < access_level {
+    return Err(AppError::NotEnoughPermissions {
+      user: format!("object token {}", row.id),
+      action: format!("access collab:{} with {:?}", object_id, access_level),
+    });
+  }
+  Ok(row.workspace_id)
+}
+
+pub async fn revoke_object_token(
+  pg_pool: &PgPool,
+  object_id: &str,
+  token_id: &Uuid,
+) -> Result<(), AppError> {
+  if !revoke_collab_object_token(pg_pool, token_id, object_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "active token {} not found for object {}",
+      token_id, object_id
+    )));
+  }
+  Ok(())
+}
+
+pub(crate) async fn resolve_object_token_row(
+  pg_pool: &PgPool,
+  token: &str,
+) -> Result<(AFCollabObjectTokenRow, AFAccessLevel), AppError> {
+  let invalid_token = || AppError::UserUnAuthorized("Invalid object token".to_string());
+  let row = select_collab_object_token(pg_pool, token)
+    .await?
+    .ok_or_else(invalid_token)?;
+  if row.revoked_at.is_some() {
+    return Err(invalid_token());
+  }
+  if matches!(row.expires_at, Some(expires_at) if expires_at <= Utc::now()) {
+    return Err(invalid_token());
+  }
+
+  let creator_level = match select_collab_member(&row.created_by, &row.oid, pg_pool).await {
+    Ok(member) => member.permission.access_level,
+    Err(err) if err.is_record_not_found() => return Err(invalid_token()),
+    Err(err) => return Err(err),
+  };
+  let access_level = AFAccessLevel::from(row.access_level).min(creator_level);
+  Ok((row, access_level))
+}
@@ -1,50 +1,164 @@
 use std::sync::Arc;
 
 use app_error::AppError;
+use appflowy_collaborate::collab::access_control::CollabAccessControlImpl;
 use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use appflowy_collaborate::indexer::DocumentDataExt;
+use collab_document::document::Document;
 use collab_entity::CollabType;
 use collab_entity::EncodedCollab;
 use collab_folder::SectionItem;
-use collab_folder::{CollabOrigin, Folder};
-use database::collab::{CollabStorage, GetCollabOrigin};
+use collab_folder::{CollabOrigin, Folder, ViewLayout as CollabFolderViewLayout};
+use database::collab::{select_workspace_id_of_collabs, CollabStorage, GetCollabOrigin};
+use database::publish::select_nav_view_ids_for_workspace;
+use database::publish::select_published_data_for_view_id;
 use database::publish::select_published_view_ids_for_workspace;
 use database::publish::select_workspace_id_for_publish_namespace;
-use database_entity::dto::{QueryCollab, QueryCollabParams};
-use sqlx::PgPool;
+use database::workspace::{
+  delete_user_view_order, select_user_view_orders, upsert_user_view_order,
+};
+use database_entity::dto::{CollabParams, QueryCollab, QueryCollabParams, QueryCollabResult};
+use sqlx::{PgConnection, PgPool};
 use std::ops::DerefMut;
 
 use anyhow::Context;
-use shared_entity::dto::workspace_dto::{FolderView, PublishedView};
+use futures_util::future::join_all;
+use shared_entity::dto::workspace_dto::{
+  DeletedObjectSummary, FolderView, FolderViewAncestor, FolderViewConnection,
+  FolderViewSearchResult, PublishedView, PublishedViewCover, PublishedViewPreview, ViewPermissions,
+  WebhookEvent,
+};
 use sqlx::types::Uuid;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use tracing::{event, trace};
+use tracing::{event, trace, warn};
 use validator::Validate;
+use yrs::updates::decoder::Decode;
+use yrs::StateVector;
 
+use access_control::act::Action;
 use access_control::collab::CollabAccessControl;
+use access_control::workspace::WorkspaceAccessControl;
 use database_entity::dto::{
-  AFCollabMember, CollabMemberIdentify, InsertCollabMemberParams, QueryCollabMembers,
+  AFAccessLevel, AFCollabMember, AFCollabMemberPage, BatchCreateCollabMemberResult,
+  CollabMemberIdentify, CollabMembersExport, CollabMembersExportItem, CreateCollabMemberResult,
+  EnsureCollabMemberOutcome, InsertCollabMemberParams, MergedCollabMembers, QueryCollabMembers,
   UpdateCollabMemberParams,
 };
 
-use super::folder_view::collab_folder_to_folder_view;
+use crate::biz::workspace::group::upsert_collab_group_access;
+use crate::biz::workspace::publish_dup::{broadcast_update, collab_from_doc_state, collab_to_bin};
+use crate::biz::workspace::webhook::enqueue_webhook_event;
+
 use super::folder_view::section_items_to_folder_view;
+use super::folder_view::{
+  apply_user_view_order, apply_view_permissions, folder_children_connection, folder_view_ids,
+  folder_view_parent_ids, search_folder_view, take_folder_view,
+};
+use super::folder_view::{collab_folder_to_folder_view, folder_view_ancestors, FolderViewFilter};
+use super::publish_outline::collab_folder_to_published_nav;
 use super::publish_outline::collab_folder_to_published_outline;
 
+/// Lock or unlock the membership of the collab. While locked, members can not be added, changed
+/// or removed, unless the caller explicitly bypasses the lock.
+pub async fn set_membership_locked(
+  pg_pool: &PgPool,
+  object_id: &str,
+  locked: bool,
+  locked_by: Option<i64>,
+  reason: Option<&str>,
+) -> Result<(), AppError> {
+  if locked {
+    database::collab::insert_collab_membership_lock(object_id, locked_by, reason, pg_pool).await
+  } else {
+    database::collab::delete_collab_membership_lock(object_id, pg_pool).await
+  }
+}
+
+/// Return [AppError::Locked] if the membership of the collab is locked.
+/// `bypass_membership_lock` is meant for server side maintenance operations only.
+pub(crate) async fn check_membership_unlocked<
+  'a,
+  E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+>(
+  object_id: &str,
+  bypass_membership_lock: bool,
+  executor: E,
+) -> Result<(), AppError> {
+  if bypass_membership_lock {
+    return Ok(());
+  }
+  if database::collab::is_collab_membership_locked(object_id, executor).await? {
+    return Err(AppError::Locked(format!(
+      "the membership of collab {} is locked",
+      object_id
+    )));
+  }
+  Ok(())
+}
+
+/// Return [AppError::TooManyRequests] if the access level of the member would change within
+/// `cooldown` of its last change. Setting the level the member already holds is not a change.
+async fn check_access_level_change_cooldown<
+  'a,
+  E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+>(
+  params: &UpdateCollabMemberParams,
+  cooldown: chrono::Duration,
+  executor: E,
+) -> Result<(), AppError> {
+  let current = database::collab::select_collab_member_access_level_updated_at(
+    params.uid,
+    &params.object_id,
+    executor,
+  )
+  .await?;
+  if let Some((access_level, Some(updated_at))) = current {
+    if access_level != params.access_level && chrono::Utc::now() - updated_at < cooldown {
+      return Err(AppError::TooManyRequests(format!(
+        "the access level of user {} on collab {} was changed less than {} seconds ago",
+        params.uid,
+        params.object_id,
+        cooldown.num_seconds()
+      )));
+    }
+  }
+  Ok(())
+}
+
 /// Create a new collab member
 /// If the collab member already exists, return [AppError::RecordAlreadyExists]
 /// If the collab member does not exist, create a new one
+/// With a `group_id`, the access is granted to the group instead, see [upsert_collab_group_access].
 pub async fn create_collab_member(
   pg_pool: &PgPool,
   params: &InsertCollabMemberParams,
   collab_access_control: &impl CollabAccessControl,
+  bypass_membership_lock: bool,
 ) -> Result<(), AppError> {
   params.validate()?;
+  check_member_expiry(params)?;
+  if let Some(group_id) = params.group_id {
+    return grant_collab_group_access(
+      pg_pool,
+      params,
+      &group_id,
+      collab_access_control,
+      bypass_membership_lock,
+    )
+    .await;
+  }
 
   let mut transaction = pg_pool
     .begin()
     .await
     .context("acquire transaction to insert collab member")?;
+  check_membership_unlocked(
+    &params.object_id,
+    bypass_membership_lock,
+    transaction.deref_mut(),
+  )
+  .await?;
 
   if database::collab::is_collab_member_exists(
     params.uid,
@@ -67,6 +181,15 @@ pub async fn create_collab_member(
     &mut transaction,
   )
   .await?;
+  if params.expires_at.is_some() {
+    database::collab::update_collab_member_expires_at(
+      params.uid,
+      &params.object_id,
+      params.expires_at,
+      &mut transaction,
+    )
+    .await?;
+  }
 
   collab_access_control
     .update_access_level_policy(&params.uid, &params.object_id, params.access_level)
@@ -79,21 +202,183 @@ pub async fn create_collab_member(
   Ok(())
 }
 
+/// The maximum number of members that can be added with one call of [create_collab_members_batch].
+const MAX_BATCH_COLLAB_MEMBERS: usize = 500;
+
+/// Add several members to the same collab in one transaction.
+///
+/// The whole set is validated first: every item must target the same collab, and a user can only
+/// appear once. A user that is already a member is reported as failed, without changing its access
+/// level, and the other members are still added.
+///
+/// The user adding the members can not grant an access level higher than its own.
+pub async fn create_collab_members_batch(
+  pg_pool: &PgPool,
+  uid: i64,
+  params: &[InsertCollabMemberParams],
+  collab_access_control: &impl CollabAccessControl,
+  bypass_membership_lock: bool,
+) -> Result<BatchCreateCollabMemberResult, AppError> {
+  let first = match params.first() {
+    Some(first) => first,
+    None => return Ok(BatchCreateCollabMemberResult(HashMap::new())),
+  };
+  if params.len() > MAX_BATCH_COLLAB_MEMBERS {
+    return Err(AppError::InvalidRequest(format!(
+      "Can not add more than {} members at once",
+      MAX_BATCH_COLLAB_MEMBERS
+    )));
+  }
+  let mut uids = HashSet::with_capacity(params.len());
+  for item in params {
+    item.validate()?;
+    check_member_expiry(item)?;
+    if item.group_id.is_some() {
+      return Err(AppError::InvalidRequest(
+        "Groups can not be added in a batch".to_string(),
+      ));
+    }
+    if item.workspace_id != first.workspace_id || item.object_id != first.object_id {
+      return Err(AppError::InvalidRequest(
+        "All the members must be added to the same collab".to_string(),
+      ));
+    }
+    if !uids.insert(item.uid) {
+      return Err(AppError::InvalidRequest(format!(
+        "User {} appears more than once",
+        item.uid
+      )));
+    }
+  }
+
+  let object_id = &first.object_id;
+  if let Some(access_level) = params.iter().map(|item| item.access_level).max() {
+    let can_grant = collab_access_control
+      .enforce_access_level(&first.workspace_id, &uid, object_id, access_level)
+      .await?;
+    if !can_grant {
+      return Err(AppError::NotEnoughPermissions {
+        user: uid.to_string(),
+        action: format!("grant {:?} on collab:{}", access_level, object_id),
+      });
+    }
+  }
+  let mut transaction = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to insert collab members")?;
+  check_membership_unlocked(object_id, bypass_membership_lock, transaction.deref_mut()).await?;
+
+  let existing_uids: HashSet<i64> =
+    database::collab::select_collab_members(object_id, transaction.deref_mut())
+      .await?
+      .into_iter()
+      .map(|member| member.uid)
+      .collect();
+  let mut results = HashMap::with_capacity(params.len());
+  let mut created = vec![];
+  for item in params {
+    if existing_uids.contains(&item.uid) {
+      results.insert(
+        item.uid,
+        CreateCollabMemberResult::Failed {
+          error: format!(
+            "Collab member with uid {} and object_id {} already exists",
+            item.uid, object_id
+          ),
+        },
+      );
+      continue;
+    }
+    database::collab::insert_collab_member(
+      item.uid,
+      object_id,
+      &item.access_level,
+      &mut transaction,
+    )
+    .await?;
+    if item.expires_at.is_some() {
+      database::collab::update_collab_member_expires_at(
+        item.uid,
+        object_id,
+        item.expires_at,
+        &mut transaction,
+      )
+      .await?;
+    }
+    results.insert(item.uid, CreateCollabMemberResult::Created);
+    created.push(item);
+  }
+
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to insert collab members")?;
+
+  trace!(
+    "Inserting {} collab members into {}",
+    created.len(),
+    object_id
+  );
+  for item in created {
+    collab_access_control
+      .update_access_level_policy(&item.uid, object_id, item.access_level)
+      .await?;
+  }
+  Ok(BatchCreateCollabMemberResult(results))
+}
+
+/// Add the member or change its access level.
+///
+/// With `access_level_change_cooldown`, changing the access level of an existing member within
+/// the cooldown of its last change is rejected with [AppError::TooManyRequests]. Server side
+/// operations pass `None` to skip the cooldown.
 pub async fn upsert_collab_member(
   pg_pool: &PgPool,
   _user_uuid: &Uuid,
   params: &UpdateCollabMemberParams,
   collab_access_control: &impl CollabAccessControl,
+  bypass_membership_lock: bool,
+  access_level_change_cooldown: Option<chrono::Duration>,
 ) -> Result<(), AppError> {
   params.validate()?;
+  check_member_expiry(params)?;
+  if let Some(group_id) = params.group_id {
+    return grant_collab_group_access(
+      pg_pool,
+      params,
+      &group_id,
+      collab_access_control,
+      bypass_membership_lock,
+    )
+    .await;
+  }
   let mut transaction = pg_pool
     .begin()
     .await
     .context("acquire transaction to upsert collab member")?;
+  check_membership_unlocked(
+    &params.object_id,
+    bypass_membership_lock,
+    transaction.deref_mut(),
+  )
+  .await?;
+  if let Some(cooldown) = access_level_change_cooldown {
+    check_access_level_change_cooldown(params, cooldown, transaction.deref_mut()).await?;
+  }
 
-  collab_access_control
-    .update_access_level_policy(&params.uid, &params.object_id, params.access_level)
-    .await?;
+  // A suspended member keeps the new level in the database, but gets no policy until unsuspended
+  let suspended = database::collab::is_collab_member_suspended(
+    params.uid,
+    &params.object_id,
+    transaction.deref_mut(),
+  )
+  .await?;
+  if !suspended {
+    collab_access_control
+      .update_access_level_policy(&params.uid, &params.object_id, params.access_level)
+      .await?;
+  }
 
   database::collab::insert_collab_member(
     params.uid,
@@ -102,6 +387,13 @@ pub async fn upsert_collab_member(
     &mut transaction,
   )
   .await?;
+  database::collab::update_collab_member_expires_at(
+    params.uid,
+    &params.object_id,
+    params.expires_at,
+    &mut transaction,
+  )
+  .await?;
 
   transaction
     .commit()
@@ -110,6 +402,100 @@ pub async fn upsert_collab_member(
   Ok(())
 }
 
+/// Make sure the user is a member of the collab with exactly the given access level.
+///
+/// Unlike [create_collab_member], calling this again with the same params is not an error, so it is
+/// safe to use from consumers that may receive the same event more than once. An `expires_at` of
+/// `None` keeps the expiry time of an existing member. Groups are not supported.
+pub async fn ensure_collab_member(
+  pg_pool: &PgPool,
+  params: &InsertCollabMemberParams,
+  collab_access_control: &impl CollabAccessControl,
+  bypass_membership_lock: bool,
+) -> Result<EnsureCollabMemberOutcome, AppError> {
+  params.validate()?;
+  check_member_expiry(params)?;
+  if params.group_id.is_some() {
+    return Err(AppError::InvalidRequest(
+      "The membership of a group can not be ensured".to_string(),
+    ));
+  }
+
+  let mut transaction = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to ensure collab member")?;
+  let current_member = match database::collab::select_collab_member(
+    &params.uid,
+    &params.object_id,
+    transaction.deref_mut(),
+  )
+  .await
+  {
+    Ok(member) => Some(member),
+    Err(AppError::RecordNotFound(_)) => None,
+    Err(err) => return Err(err),
+  };
+  let suspended = current_member
+    .as_ref()
+    .map_or(false, |member| member.suspended);
+
+  // The database keeps microseconds only, so compare the expiry times at that precision
+  let expiry_unchanged = |member: &AFCollabMember| {
+    params.expires_at.map_or(true, |expires_at| {
+      member.expires_at.map(|time| time.timestamp_micros()) == Some(expires_at.timestamp_micros())
+    })
+  };
+  let outcome = match current_member {
+    None => EnsureCollabMemberOutcome::Created,
+    Some(member)
+      if member.permission.access_level == params.access_level && expiry_unchanged(&member) =>
+    {
+      EnsureCollabMemberOutcome::Unchanged
+    },
+    Some(_) => EnsureCollabMemberOutcome::Updated,
+  };
+
+  if outcome != EnsureCollabMemberOutcome::Unchanged {
+    check_membership_unlocked(
+      &params.object_id,
+      bypass_membership_lock,
+      transaction.deref_mut(),
+    )
+    .await?;
+    database::collab::insert_collab_member(
+      params.uid,
+      &params.object_id,
+      &params.access_level,
+      &mut transaction,
+    )
+    .await?;
+    if params.expires_at.is_some() {
+      database::collab::update_collab_member_expires_at(
+        params.uid,
+        &params.object_id,
+        params.expires_at,
+        &mut transaction,
+      )
+      .await?;
+    }
+  }
+
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to ensure collab member")?;
+
+  // Always refresh the policy, so that a retry also repairs a policy that failed to be written.
+  // A suspended member gets no policy until unsuspended.
+  if !suspended {
+    collab_access_control
+      .update_access_level_policy(&params.uid, &params.object_id, params.access_level)
+      .await?;
+  }
+  Ok(outcome)
+}
+
 pub async fn get_collab_member(
   pg_pool: &PgPool,
   params: &CollabMemberIdentify,
@@ -124,12 +510,19 @@ pub async fn delete_collab_member(
   pg_pool: &PgPool,
   params: &CollabMemberIdentify,
   collab_access_control: &impl CollabAccessControl,
+  bypass_membership_lock: bool,
 ) -> Result<(), AppError> {
   params.validate()?;
   let mut transaction = pg_pool
     .begin()
     .await
     .context("acquire transaction to remove collab member")?;
+  check_membership_unlocked(
+    &params.object_id,
+    bypass_membership_lock,
+    transaction.deref_mut(),
+  )
+  .await?;
   event!(
     tracing::Level::DEBUG,
     "Deleting member:{} from {}",
@@ -149,13 +542,567 @@ pub async fn delete_collab_member(
   Ok(())
 }
 
+/// Delete the object and everything that refers to it: its published record, its entry in the
+/// workspace folder, its members and their access policies, and finally the collab itself. The
+/// child views of the object take its place under its parent, in the same order.
+///
+/// The published record, the members and the folder are updated in one transaction. The access
+/// policies and the collab are removed once it is committed, so a failure there can leave a
+/// policy without a member, or a collab no view refers to. With the S3 publish backend, the
+/// published blob is left in the bucket, unreachable.
+pub async fn delete_object(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  workspace_id: &Uuid,
+  object_id: &str,
+) -> Result<DeletedObjectSummary, AppError> {
+  if object_id == workspace_id.to_string() {
+    return Err(AppError::InvalidRequest(
+      "The folder of a workspace can not be deleted as an object".to_string(),
+    ));
+  }
+  let owner_uid = database::workspace::select_workspace(pg_pool, workspace_id)
+    .await?
+    .owner_uid
+    .ok_or_else(|| AppError::RecordNotFound(format!("owner of workspace {}", workspace_id)))?;
+  let workspace_id_str = workspace_id.to_string();
+  let mut summary = DeletedObjectSummary {
+    object_id: object_id.to_string(),
+    ..Default::default()
+  };
+
+  let mut folder = get_latest_collab_folder(
+    collab_storage.clone(),
+    GetCollabOrigin::Server,
+    &workspace_id_str,
+  )
+  .await?;
+  let folder_update = match folder.get_view(object_id) {
+    None => None,
+    Some(view) => {
+      let encoded_update = {
+        let mut folder_txn = folder.collab.transact_mut();
+        let mut prev_view_id = object_id.to_string();
+        for child in view.children.iter() {
+          folder.body.move_nested_view(
+            &mut folder_txn,
+            &child.id,
+            &view.parent_view_id,
+            Some(&prev_view_id),
+          );
+          prev_view_id = child.id.clone();
+        }
+        summary.reparented_view_ids = view.children.iter().map(|child| child.id.clone()).collect();
+        folder
+          .body
+          .views
+          .dissociate_parent_child(&mut folder_txn, &view.parent_view_id, object_id);
+        folder
+          .body
+          .views
+          .delete_views(&mut folder_txn, vec![object_id]);
+        folder_txn.encode_update_v1()
+      };
+      let encoded_folder = collab_to_bin(folder.collab, CollabType::Folder).await?;
+      Some((encoded_update, encoded_folder))
+    },
+  };
+
+  let mut transaction = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to delete object")?;
+  if let Ok(view_id) = Uuid::parse_str(object_id) {
+    summary.unpublished = database::publish::delete_published_collab_if_exists(
+      transaction.deref_mut(),
+      workspace_id,
+      &view_id,
+    )
+    .await?;
+    if summary.unpublished {
+      enqueue_webhook_event(
+        transaction.deref_mut(),
+        workspace_id,
+        WebhookEvent::ViewUnpublished,
+        serde_json::json!({ "view_id": view_id }),
+      )
+      .await?;
+    }
+  }
+  summary.removed_member_uids =
+    database::collab::delete_collab_members(object_id, &mut transaction).await?;
+  let removed_group_ids =
+    database::workspace_group::delete_collab_group_members(transaction.deref_mut(), object_id)
+      .await?;
+  database::collab::delete_collab_share_links(transaction.deref_mut(), object_id).await?;
+  database::collab::delete_collab_object_tokens(transaction.deref_mut(), object_id).await?;
+  if let Some((_, encoded_folder)) = &folder_update {
+    collab_storage
+      .insert_new_collab_with_transaction(
+        &workspace_id_str,
+        &owner_uid,
+        CollabParams {
+          object_id: workspace_id_str.clone(),
+          encoded_collab_v1: encoded_folder.clone().into(),
+          collab_type: CollabType::Folder,
+          embeddings: None,
+        },
+        &mut transaction,
+      )
+      .await?;
+    summary.removed_from_folder = true;
+  }
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to delete object")?;
+
+  if let Some((encoded_update, _)) = folder_update {
+    broadcast_update(&collab_storage, &workspace_id_str, encoded_update).await?;
+  }
+  for uid in &summary.removed_member_uids {
+    collab_access_control
+      .remove_access_level(uid, object_id)
+      .await?;
+  }
+  for group_id in &removed_group_ids {
+    collab_access_control
+      .remove_group_access_level(&group_id.to_string(), object_id)
+      .await?;
+  }
+  collab_storage
+    .delete_collab(&workspace_id_str, &owner_uid, object_id)
+    .await?;
+
+  Ok(summary)
+}
+
+/// Suspend the membership of the user. The member keeps its access level, but has no access to
+/// the collab until [unsuspend_collab_member] is called. Suspension does not add, change or remove
+/// a member, so it is allowed while the membership of the collab is locked.
+///
+/// The user suspending the member must hold at least the access level of the member.
+pub async fn suspend_collab_member(
+  pg_pool: &PgPool,
+  uid: i64,
+  params: &CollabMemberIdentify,
+  collab_access_control: &impl CollabAccessControl,
+) -> Result<(), AppError> {
+  params.validate()?;
+  let mut transaction = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to suspend collab member")?;
+  enforce_member_access_level(
+    collab_access_control,
+    uid,
+    params,
+    "suspend",
+    transaction.deref_mut(),
+  )
+  .await?;
+  database::collab::update_collab_member_suspended(
+    params.uid,
+    &params.object_id,
+    true,
+    transaction.deref_mut(),
+  )
+  .await?;
+
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to suspend collab member")?;
+
+  collab_access_control
+    .remove_access_level(&params.uid, &params.object_id)
+    .await?;
+  Ok(())
+}
+
+/// Lift the suspension of the member, which restores the access level it held when suspended.
+/// The user lifting the suspension must hold at least that access level.
+pub async fn unsuspend_collab_member(
+  pg_pool: &PgPool,
+  uid: i64,
+  params: &CollabMemberIdentify,
+  collab_access_control: &impl CollabAccessControl,
+) -> Result<(), AppError> {
+  params.validate()?;
+  let mut transaction = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to unsuspend collab member")?;
+  enforce_member_access_level(
+    collab_access_control,
+    uid,
+    params,
+    "unsuspend",
+    transaction.deref_mut(),
+  )
+  .await?;
+  let access_level = database::collab::update_collab_member_suspended(
+    params.uid,
+    &params.object_id,
+    false,
+    transaction.deref_mut(),
+  )
+  .await?;
+
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to unsuspend collab member")?;
+
+  collab_access_control
+    .update_access_level_policy(&params.uid, &params.object_id, access_level)
+    .await?;
+  Ok(())
+}
+
+/// Check that the user holds at least the access level of the member on the collab, so that a
+/// member can not be acted upon by a user with less access.
+async fn enforce_member_access_level(
+  collab_access_control: &impl CollabAccessControl,
+  uid: i64,
+  params: &CollabMemberIdentify,
+  action: &str,
+  conn: &mut PgConnection,
+) -> Result<(), AppError> {
+  let member = database::collab::select_collab_member(&params.uid, &params.object_id, conn).await?;
+  let has_access_level = collab_access_control
+    .enforce_access_level(
+      &params.workspace_id,
+      &uid,
+      &params.object_id,
+      member.permission.access_level,
+    )
+    .await?;
+  if !has_access_level {
+    return Err(AppError::NotEnoughPermissions {
+      user: uid.to_string(),
+      action: format!(
+        "{} member {} of collab:{}",
+        action, params.uid, params.object_id
+      ),
+    });
+  }
+  Ok(())
+}
+
+/// Merge the members of `from_object` into `into_object`.
+///
+/// The members of the target collab become the union of both member lists. When a user is a member
+/// of both collabs, the higher access level wins. Members of the target collab are never downgraded.
+/// Suspended members of `from_object` are not merged, and a suspended member of the target collab
+/// stays suspended. The members of `from_object` are left untouched.
+///
+/// The user merging the members must be able to read `from_object`, and can not grant an access
+/// level higher than its own on `into_object`.
+pub async fn merge_collab_members(
+  pg_pool: &PgPool,
+  workspace_id: &str,
+  uid: i64,
+  from_object: &str,
+  into_object: &str,
+  collab_access_control: &impl CollabAccessControl,
+  bypass_membership_lock: bool,
+) -> Result<MergedCollabMembers, AppError> {
+  if from_object == into_object {
+    return Err(AppError::InvalidRequest(
+      "Can not merge the members of a collab into itself".to_string(),
+    ));
+  }
+  if !collab_access_control
+    .enforce_action(workspace_id, &uid, from_object, Action::Read)
+    .await?
+  {
+    return Err(AppError::NotEnoughPermissions {
+      user: uid.to_string(),
+      action: format!("read the members of collab:{}", from_object),
+    });
+  }
+
+  let mut transaction = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to merge collab members")?;
+  check_membership_unlocked(into_object, bypass_membership_lock, transaction.deref_mut()).await?;
+
+  let source_members =
+    database::collab::select_collab_members(from_object, transaction.deref_mut()).await?;
+  let target_members: HashMap<i64, AFCollabMember> =
+    database::collab::select_collab_members(into_object, transaction.deref_mut())
+      .await?
+      .into_iter()
+      .map(|member| (member.uid, member))
+      .collect();
+
+  let mut merged = MergedCollabMembers::default();
+  let mut changes = vec![];
+  for member in source_members {
+    if member.suspended {
+      continue;
+    }
+    let access_level = member.permission.access_level;
+    let target_suspended = match target_members.get(&member.uid) {
+      None => {
+        merged.added.push(member.uid);
+        false
+      },
+      Some(target) if target.permission.access_level < access_level => {
+        merged.upgraded.push(member.uid);
+        target.suspended
+      },
+      Some(_) => continue,
+    };
+    changes.push((member.uid, access_level, target_suspended));
+  }
+
+  if let Some(access_level) = changes
+    .iter()
+    .map(|(_, access_level, _)| *access_level)
+    .max()
+  {
+    let can_grant = collab_access_control
+      .enforce_access_level(workspace_id, &uid, into_object, access_level)
+      .await?;
+    if !can_grant {
+      return Err(AppError::NotEnoughPermissions {
+        user: uid.to_string(),
+        action: format!("grant {:?} on collab:{}", access_level, into_object),
+      });
+    }
+  }
+
+  trace!(
+    "Merging collab members from {} into {}: {:?}",
+    from_object,
+    into_object,
+    merged
+  );
+  for (uid, access_level, _) in &changes {
+    database::collab::insert_collab_member(*uid, into_object, access_level, &mut transaction)
+      .await?;
+  }
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to merge collab members")?;
+
+  for (uid, access_level, _) in changes.into_iter().filter(|(_, _, suspended)| !suspended) {
+    collab_access_control
+      .update_access_level_policy(&uid, into_object, access_level)
+      .await?;
+  }
+  Ok(merged)
+}
+
+/// Returns the access policies of the collab straight from the access control backend, which can
+/// be compared against the collab members stored in the database.
+pub async fn get_access_policies(
+  collab_access_control: &impl CollabAccessControl,
+  object_id: &str,
+) -> Result<Vec<(i64, AFAccessLevel)>, AppError> {
+  collab_access_control.get_access_policies(object_id).await
+}
+
+const MAX_COLLAB_MEMBER_PAGE_SIZE: u32 = 100;
+
 pub async fn get_collab_member_list(
   pg_pool: &PgPool,
   params: &QueryCollabMembers,
-) -> Result<Vec<AFCollabMember>, AppError> {
+) -> Result<AFCollabMemberPage, AppError> {
   params.validate()?;
-  let collab_member = database::collab::select_collab_members(&params.object_id, pg_pool).await?;
-  Ok(collab_member)
+  if let Some(limit) = params.limit {
+    if !(1..=MAX_COLLAB_MEMBER_PAGE_SIZE).contains(&limit) {
+      return Err(AppError::InvalidRequest(format!(
+        "limit must be between 1 and {}",
+        MAX_COLLAB_MEMBER_PAGE_SIZE
+      )));
+    }
+  }
+  let search = params
+    .search
+    .as_deref()
+    .map(str::trim)
+    .filter(|search| !search.is_empty());
+  // Fetch one extra member to know whether there is a next page
+  let mut members = database::collab::select_collab_members_page(
+    &params.object_id,
+    params.access_level,
+    search,
+    params.after,
+    params.limit.map(|limit| limit as i64 + 1),
+    pg_pool,
+  )
+  .await?;
+  let next_cursor = match params.limit {
+    Some(limit) if members.len() > limit as usize => {
+      members.truncate(limit as usize);
+      members.last().map(|member| member.uid)
+    },
+    _ => None,
+  };
+  Ok(AFCollabMemberPage {
+    members,
+    next_cursor,
+  })
+}
+
+/// Returns the user's direct access level for each of the objects, or `None` where the user is not
+/// a member. Access inherited from a workspace role or a parent view is not taken into account.
+pub async fn check_membership(
+  pg_pool: &PgPool,
+  uid: i64,
+  object_ids: &[String],
+) -> Result<HashMap<String, Option<AFAccessLevel>>, AppError> {
+  if object_ids.len() > MAX_MEMBERSHIP_CHECK_OBJECTS {
+    return Err(AppError::InvalidRequest(format!(
+      "Can not check more than {} objects at once",
+      MAX_MEMBERSHIP_CHECK_OBJECTS
+    )));
+  }
+
+  let mut levels =
+    database::collab::select_access_levels_for_user(uid, object_ids, pg_pool).await?;
+  Ok(
+    object_ids
+      .iter()
+      .map(|object_id| (object_id.clone(), levels.remove(object_id)))
+      .collect(),
+  )
+}
+
+const MAX_MEMBERSHIP_CHECK_OBJECTS: usize = 1000;
+
+/// Exports the members of every collab in the workspace, grouped per collab. With an
+/// `access_level_filter`, only members at or above that level are included, and collabs left
+/// without any matching member are omitted.
+pub async fn export_workspace_collab_members(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  access_level_filter: Option<AFAccessLevel>,
+) -> Result<CollabMembersExport, AppError> {
+  let members =
+    database::collab::select_workspace_collab_members(workspace_id, access_level_filter, pg_pool)
+      .await?;
+
+  // Members are ordered by object id, so the members of a collab are adjacent
+  let mut objects: Vec<CollabMembersExportItem> = vec![];
+  for member in members {
+    match objects.last_mut() {
+      Some(item) if item.object_id == member.oid => item.members.push(member),
+      _ => objects.push(CollabMembersExportItem {
+        object_id: member.oid.clone(),
+        members: vec![member],
+      }),
+    }
+  }
+
+  Ok(CollabMembersExport {
+    workspace_id: *workspace_id,
+    access_level_filter,
+    objects,
+  })
+}
+
+/// Returns the collab members whose access lapses within the given window, so that the owners can
+/// be reminded before the access expires.
+pub async fn get_expiring_members(
+  pg_pool: &PgPool,
+  within: chrono::Duration,
+) -> Result<Vec<AFCollabMember>, AppError> {
+  if within <= chrono::Duration::zero() {
+    return Err(AppError::InvalidRequest(
+      "The expiry window must be positive".to_string(),
+    ));
+  }
+  database::collab::select_expiring_collab_members(pg_pool, within).await
+}
+
+/// Remove the members whose membership has expired, together with their access policies.
+/// Returns the (uid, object id) pairs of the removed members.
+///
+/// The rows are only deleted once all the policies are removed, so a failure leaves them in place
+/// for the next run.
+pub async fn revoke_expired_collab_members(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+) -> Result<Vec<(i64, String)>, AppError> {
+  let mut transaction = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to revoke expired collab members")?;
+  let expired = database::collab::delete_expired_collab_members(transaction.deref_mut()).await?;
+  for (uid, oid) in &expired {
+    collab_access_control.remove_access_level(uid, oid).await?;
+  }
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to revoke expired collab members")?;
+  if !expired.is_empty() {
+    trace!("Revoked {} expired collab members", expired.len());
+  }
+  Ok(expired)
+}
+
+/// Periodically revoke the expired collab members, see [revoke_expired_collab_members].
+/// A zero `period` disables the task.
+pub fn spawn_revoke_expired_collab_members(
+  pg_pool: PgPool,
+  collab_access_control: impl CollabAccessControl,
+  period: std::time::Duration,
+) {
+  if period.is_zero() {
+    return;
+  }
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(period);
+    loop {
+      interval.tick().await;
+      if let Err(err) = revoke_expired_collab_members(&pg_pool, &collab_access_control).await {
+        warn!("Failed to revoke expired collab members: {:?}", err);
+      }
+    }
+  });
+}
+
+async fn grant_collab_group_access(
+  pg_pool: &PgPool,
+  params: &InsertCollabMemberParams,
+  group_id: &Uuid,
+  collab_access_control: &impl CollabAccessControl,
+  bypass_membership_lock: bool,
+) -> Result<(), AppError> {
+  if params.expires_at.is_some() {
+    return Err(AppError::InvalidRequest(
+      "The access of a group can not expire".to_string(),
+    ));
+  }
+  let workspace_id = Uuid::parse_str(&params.workspace_id)
+    .map_err(|err| AppError::InvalidRequest(format!("invalid workspace id: {}", err)))?;
+  upsert_collab_group_access(
+    pg_pool,
+    collab_access_control,
+    &workspace_id,
+    group_id,
+    &params.object_id,
+    params.access_level,
+    bypass_membership_lock,
+  )
+  .await
+}
+
+fn check_member_expiry(params: &InsertCollabMemberParams) -> Result<(), AppError> {
+  if matches!(params.expires_at, Some(expires_at) if expires_at <= chrono::Utc::now()) {
+    return Err(AppError::InvalidRequest(
+      "The expiry time must be in the future".to_string(),
+    ));
+  }
+  Ok(())
 }
 
 pub async fn get_user_favorite_folder_views(
@@ -245,6 +1192,7 @@ pub async fn get_user_trash_folder_views(
   ))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn get_user_workspace_structure(
   collab_storage: Arc<CollabAccessControlStorage>,
   pg_pool: &PgPool,
@@ -252,6 +1200,8 @@ pub async fn get_user_workspace_structure(
   workspace_id: Uuid,
   depth: u32,
   root_view_id: &str,
+  filter: &FolderViewFilter,
+  collab_access_control: Option<&impl CollabAccessControl>,
 ) -> Result<FolderView, AppError> {
   let depth_limit = 10;
   if depth > depth_limit {
@@ -260,18 +1210,261 @@ pub async fn get_user_workspace_structure(
       depth, depth_limit
     )));
   }
-  let folder = get_latest_collab_folder(
+  let encoded_collab = get_latest_collab_encoded(
     collab_storage,
     GetCollabOrigin::User { uid },
     &workspace_id.to_string(),
+    &workspace_id.to_string(),
+    CollabType::Folder,
   )
   .await?;
+  let server_version = collab_update_counter(&encoded_collab);
+  let folder = collab_folder_from_encoded(uid, &workspace_id.to_string(), encoded_collab)?;
   let publish_view_ids = select_published_view_ids_for_workspace(pg_pool, workspace_id).await?;
   let publish_view_ids: HashSet<String> = publish_view_ids
     .into_iter()
     .map(|id| id.to_string())
     .collect();
-  collab_folder_to_folder_view(root_view_id, &folder, depth, &publish_view_ids)
+  let mut folder_view =
+    collab_folder_to_folder_view(root_view_id, &folder, depth, &publish_view_ids, filter)?;
+  let user_view_orders = select_user_view_orders(
+    pg_pool,
+    uid,
+    &workspace_id,
+    &folder_view_parent_ids(&folder_view),
+  )
+  .await?;
+  apply_user_view_order(&mut folder_view, &user_view_orders);
+  if let Some(collab_access_control) = collab_access_control {
+    let mut permissions = HashMap::new();
+    for view_id in folder_view_ids(&folder_view) {
+      let access_level = get_view_access_level(
+        collab_access_control,
+        &workspace_id.to_string(),
+        uid,
+        &view_id,
+      )
+      .await?;
+      permissions.insert(view_id, ViewPermissions::from_access_level(access_level));
+    }
+    apply_view_permissions(&mut folder_view, &permissions);
+  }
+  folder_view.server_version = server_version;
+  Ok(folder_view)
+}
+
+/// Returns the structure of each workspace, keyed by workspace id, as [get_user_workspace_structure]
+/// does for one workspace. The folders are fetched and decoded concurrently, and every workspace
+/// is decoded once even if its id is given more than once. A workspace that fails does not fail
+/// the others, its error is returned in its place.
+pub async fn get_workspace_structures(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_ids: &[Uuid],
+  depth: u32,
+) -> HashMap<String, Result<FolderView, AppError>> {
+  let workspace_ids: HashSet<Uuid> = workspace_ids.iter().copied().collect();
+  let structures = join_all(workspace_ids.into_iter().map(|workspace_id| {
+    let collab_storage = collab_storage.clone();
+    async move {
+      let root_view_id = workspace_id.to_string();
+      let structure = get_user_workspace_structure(
+        collab_storage,
+        pg_pool,
+        uid,
+        workspace_id,
+        depth,
+        &root_view_id,
+        &FolderViewFilter::default(),
+        None::<&CollabAccessControlImpl>,
+      )
+      .await;
+      (root_view_id, structure)
+    }
+  }))
+  .await;
+  structures.into_iter().collect()
+}
+
+/// Returns the highest access level the access control grants the user on the view, taking the
+/// workspace role into account, or `None` if the user can not even read it.
+async fn get_view_access_level(
+  collab_access_control: &impl CollabAccessControl,
+  workspace_id: &str,
+  uid: i64,
+  view_id: &str,
+) -> Result<Option<AFAccessLevel>, AppError> {
+  for access_level in [
+    AFAccessLevel::FullAccess,
+    AFAccessLevel::ReadAndWrite,
+    AFAccessLevel::ReadAndComment,
+    AFAccessLevel::ReadOnly,
+  ] {
+    if collab_access_control
+      .enforce_access_level(workspace_id, &uid, view_id, access_level)
+      .await?
+    {
+      return Ok(Some(access_level));
+    }
+  }
+  Ok(None)
+}
+
+/// Search the views of the workspace folder visible to the user by name. See [search_folder_view].
+pub async fn search_folder_views(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  uid: i64,
+  workspace_id: Uuid,
+  query: &str,
+  include_ancestors: bool,
+) -> Result<Vec<FolderViewSearchResult>, AppError> {
+  let query = query.trim();
+  if query.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "The search query must not be empty".to_string(),
+    ));
+  }
+
+  let encoded_collab = get_latest_collab_encoded(
+    collab_storage,
+    GetCollabOrigin::User { uid },
+    &workspace_id.to_string(),
+    &workspace_id.to_string(),
+    CollabType::Folder,
+  )
+  .await?;
+  let folder = collab_folder_from_encoded(uid, &workspace_id.to_string(), encoded_collab)?;
+  let folder_view = collab_folder_to_folder_view(
+    &workspace_id.to_string(),
+    &folder,
+    u32::MAX,
+    &HashSet::new(),
+    &FolderViewFilter::default(),
+  )?;
+  Ok(search_folder_view(&folder_view, query, include_ancestors))
+}
+
+/// Returns the breadcrumb of the view, see [folder_view_ancestors].
+pub async fn get_folder_view_ancestors(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  uid: i64,
+  workspace_id: Uuid,
+  view_id: &str,
+) -> Result<Vec<FolderViewAncestor>, AppError> {
+  let workspace_id = workspace_id.to_string();
+  let folder =
+    get_latest_collab_folder(collab_storage, GetCollabOrigin::User { uid }, &workspace_id).await?;
+  folder_view_ancestors(&folder, &workspace_id, view_id)
+}
+
+/// The maximum number of children in a page of [get_folder_children_connection].
+const MAX_FOLDER_CHILDREN_PAGE_SIZE: u32 = 100;
+
+/// Returns a page of at most `first` children of `parent_view_id`, after the child whose cursor is
+/// `after`. The children are ordered as in [get_user_workspace_structure], and only the views
+/// the user can see in the workspace structure are returned.
+#[allow(clippy::too_many_arguments)]
+pub async fn get_folder_children_connection(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: Uuid,
+  parent_view_id: &str,
+  first: u32,
+  after: Option<&str>,
+  filter: &FolderViewFilter,
+) -> Result<FolderViewConnection, AppError> {
+  if first == 0 || first > MAX_FOLDER_CHILDREN_PAGE_SIZE {
+    return Err(AppError::InvalidRequest(format!(
+      "The page size must be between 1 and {}",
+      MAX_FOLDER_CHILDREN_PAGE_SIZE
+    )));
+  }
+
+  let encoded_collab = get_latest_collab_encoded(
+    collab_storage,
+    GetCollabOrigin::User { uid },
+    &workspace_id.to_string(),
+    &workspace_id.to_string(),
+    CollabType::Folder,
+  )
+  .await?;
+  let folder = collab_folder_from_encoded(uid, &workspace_id.to_string(), encoded_collab)?;
+  let publish_view_ids: HashSet<String> =
+    select_published_view_ids_for_workspace(pg_pool, workspace_id)
+      .await?
+      .into_iter()
+      .map(|id| id.to_string())
+      .collect();
+  // Build the tree from the workspace root, so that views in sections the user can not see are
+  // never reachable, whatever the parent is.
+  let folder_view = collab_folder_to_folder_view(
+    &workspace_id.to_string(),
+    &folder,
+    u32::MAX,
+    &publish_view_ids,
+    filter,
+  )?;
+  let mut parent = take_folder_view(folder_view, parent_view_id).ok_or_else(|| {
+    AppError::RecordNotFound(format!("view {} not found in the folder", parent_view_id))
+  })?;
+  let user_view_orders =
+    select_user_view_orders(pg_pool, uid, &workspace_id, &[parent.view_id.clone()]).await?;
+  apply_user_view_order(&mut parent, &user_view_orders);
+  folder_children_connection(parent.children, first as usize, after)
+}
+
+const MAX_USER_VIEW_ORDER_LEN: usize = 1000;
+
+/// Save the user's own order of the children of `parent_view_id`. It only affects the folder
+/// returned to this user, the order in the shared folder is not changed. An empty list resets
+/// the children to the folder's order.
+pub async fn set_user_view_order(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  parent_view_id: &str,
+  ordered_child_ids: &[String],
+) -> Result<(), AppError> {
+  if ordered_child_ids.is_empty() {
+    return delete_user_view_order(pg_pool, uid, workspace_id, parent_view_id).await;
+  }
+  if ordered_child_ids.len() > MAX_USER_VIEW_ORDER_LEN {
+    return Err(AppError::InvalidRequest(format!(
+      "Can not order more than {} views",
+      MAX_USER_VIEW_ORDER_LEN
+    )));
+  }
+
+  let mut seen = HashSet::with_capacity(ordered_child_ids.len());
+  if let Some(duplicate) = ordered_child_ids.iter().find(|id| !seen.insert(*id)) {
+    return Err(AppError::InvalidRequest(format!(
+      "View {} appears more than once in the order",
+      duplicate
+    )));
+  }
+  upsert_user_view_order(
+    pg_pool,
+    uid,
+    workspace_id,
+    parent_view_id,
+    ordered_child_ids,
+  )
+  .await
+}
+
+/// Returns the update counter of the collab, which is the sum of the clocks of all the clients in
+/// its state vector. The counter only grows as updates are applied, so it can be used by clients to
+/// detect whether their local copy is behind the server.
+pub fn collab_update_counter(encoded_collab: &EncodedCollab) -> Option<u64> {
+  match StateVector::decode_v1(&encoded_collab.state_vector) {
+    Ok(state_vector) => Some(state_vector.iter().map(|(_, clock)| *clock as u64).sum()),
+    Err(err) => {
+      tracing::warn!("failed to decode state vector of the collab: {}", err);
+      None
+    },
+  }
 }
 
 pub async fn get_latest_collab_folder(
@@ -293,6 +1486,14 @@ pub async fn get_latest_collab_folder(
     CollabType::Folder,
   )
   .await?;
+  collab_folder_from_encoded(folder_uid, workspace_id, encoded_collab)
+}
+
+fn collab_folder_from_encoded(
+  folder_uid: i64,
+  workspace_id: &str,
+  encoded_collab: EncodedCollab,
+) -> Result<Folder, AppError> {
   let folder = Folder::from_collab_doc_state(
     folder_uid,
     CollabOrigin::Server,
@@ -304,6 +1505,101 @@ pub async fn get_latest_collab_folder(
   Ok(folder)
 }
 
+/// Returns the collabs of the workspace the user asked for, keyed by object id. A collab that can
+/// not be read, or fetched, gets its error in its place without failing the others.
+///
+/// The read access is checked once for the workspace, as its members can read all of its collabs.
+/// The other users, such as the guests, are checked collab by collab. A collab stored in another
+/// workspace is never returned.
+pub async fn batch_get_collab_for_user(
+  collab_storage: &Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  workspace_access_control: &impl WorkspaceAccessControl,
+  collab_access_control: &impl CollabAccessControl,
+  uid: i64,
+  workspace_id: &Uuid,
+  queries: Vec<QueryCollab>,
+) -> Result<HashMap<String, QueryCollabResult>, AppError> {
+  let workspace_id_str = workspace_id.to_string();
+  let can_read_workspace = workspace_access_control
+    .enforce_action(&uid, &workspace_id_str, Action::Read)
+    .await?;
+  let oids = queries
+    .iter()
+    .map(|query| query.object_id.clone())
+    .collect::<Vec<_>>();
+  let workspace_id_by_oid = select_workspace_id_of_collabs(pg_pool, &oids).await?;
+
+  // The collabs that are not stored are not found, the storage would otherwise return the collab
+  // being edited, whichever workspace it belongs to
+  let mut results = HashMap::new();
+  let (stored_queries, missing_queries): (Vec<_>, Vec<_>) = queries
+    .into_iter()
+    .partition(|query| workspace_id_by_oid.contains_key(&query.object_id));
+  for query in missing_queries {
+    results.insert(
+      query.object_id,
+      QueryCollabResult::Failed {
+        error: "Record not found".to_string(),
+      },
+    );
+  }
+
+  let access_results = join_all(stored_queries.into_iter().map(|query| {
+    let workspace_id_str = &workspace_id_str;
+    let workspace_id_by_oid = &workspace_id_by_oid;
+    async move {
+      let result = match workspace_id_by_oid.get(&query.object_id) {
+        Some(collab_workspace_id) if collab_workspace_id != workspace_id => {
+          Err(AppError::RecordNotFound(format!(
+            "collab {} is not in workspace {}",
+            query.object_id, workspace_id_str
+          )))
+        },
+        _ if can_read_workspace => Ok(true),
+        _ => {
+          collab_access_control
+            .enforce_action(workspace_id_str, &uid, &query.object_id, Action::Read)
+            .await
+        },
+      };
+      (query, result)
+    }
+  }))
+  .await;
+
+  let mut readable_queries = vec![];
+  for (query, result) in access_results {
+    let result = result.and_then(|can_read| {
+      if can_read {
+        Ok(())
+      } else {
+        Err(AppError::NotEnoughPermissions {
+          user: uid.to_string(),
+          action: format!("read collab:{}", query.object_id),
+        })
+      }
+    });
+    match result {
+      Ok(()) => readable_queries.push(query),
+      Err(err) => {
+        results.insert(
+          query.object_id,
+          QueryCollabResult::Failed {
+            error: err.to_string(),
+          },
+        );
+      },
+    }
+  }
+  results.extend(
+    collab_storage
+      .batch_get_collab(&uid, readable_queries)
+      .await,
+  );
+  Ok(results)
+}
+
 pub async fn get_latest_collab_encoded(
   collab_storage: Arc<CollabAccessControlStorage>,
   collab_origin: GetCollabOrigin,
@@ -347,3 +1643,110 @@ pub async fn get_published_view(
     collab_folder_to_published_outline(&workspace_id.to_string(), &folder, &publish_view_ids)?;
   Ok(published_view)
 }
+
+/// Returns the navigation menu of the published site for the given namespace. Only the published
+/// views that are flagged to show in nav are included, nested and ordered as in the folder.
+pub async fn get_published_nav(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  publish_namespace: &str,
+) -> Result<Vec<PublishedView>, AppError> {
+  let workspace_id = select_workspace_id_for_publish_namespace(pg_pool, publish_namespace).await?;
+  let folder = get_latest_collab_folder(
+    collab_storage,
+    GetCollabOrigin::Server,
+    &workspace_id.to_string(),
+  )
+  .await?;
+  let nav_view_ids: HashSet<String> = select_nav_view_ids_for_workspace(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .map(|id| id.to_string())
+    .collect();
+  Ok(collab_folder_to_published_nav(
+    &workspace_id.to_string(),
+    &folder,
+    &nav_view_ids,
+  ))
+}
+
+/// The maximum number of characters of a document used as the description of a link preview.
+const PUBLISHED_VIEW_PREVIEW_DESCRIPTION_LEN: usize = 200;
+
+/// Returns the title, description and cover of a published view, which is all a link preview
+/// needs. Only the view itself is read from the folder and, for a document, the text is taken from
+/// the published collab, so the published outline is never built.
+pub async fn get_published_view_preview(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  publish_namespace: &str,
+  view_id: &Uuid,
+  pg_pool: &PgPool,
+) -> Result<PublishedViewPreview, AppError> {
+  let workspace_id = select_workspace_id_for_publish_namespace(pg_pool, publish_namespace).await?;
+  let (_, published_blob) = select_published_data_for_view_id(pg_pool, view_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("published view {} not found", view_id)))?;
+  let folder = get_latest_collab_folder(
+    collab_storage,
+    GetCollabOrigin::Server,
+    &workspace_id.to_string(),
+  )
+  .await?;
+  // The folder only contains the view when it belongs to the workspace of the namespace
+  let view = folder.get_view(&view_id.to_string()).ok_or_else(|| {
+    AppError::RecordNotFound(format!(
+      "published view {} not found in namespace {}",
+      view_id, publish_namespace
+    ))
+  })?;
+
+  let description = if matches!(view.layout, CollabFolderViewLayout::Document) {
+    let object_id = view.id.clone();
+    tokio::task::spawn_blocking(move || published_document_description(published_blob, &object_id))
+      .await?
+  } else {
+    None
+  };
+
+  Ok(PublishedViewPreview {
+    view_id: view.id.clone(),
+    title: view.name.clone(),
+    description,
+    cover: view.extra.as_deref().and_then(published_view_cover),
+  })
+}
+
+/// A preview is best effort, so a document that can not be decoded has no description.
+fn published_document_description(doc_state: Vec<u8>, object_id: &str) -> Option<String> {
+  let document_data = collab_from_doc_state(doc_state, object_id)
+    .and_then(|collab| Document::open(collab).map_err(|e| AppError::Unhandled(e.to_string())))
+    .and_then(|document| {
+      document
+        .get_document_data()
+        .map_err(|e| AppError::Unhandled(e.to_string()))
+    });
+  match document_data {
+    Ok(document_data) => {
+      let description: String = document_data
+        .to_plain_text()
+        .trim()
+        .chars()
+        .take(PUBLISHED_VIEW_PREVIEW_DESCRIPTION_LEN)
+        .collect();
+      (!description.is_empty()).then_some(description)
+    },
+    Err(err) => {
+      tracing::warn!("failed to decode published document {}: {}", object_id, err);
+      None
+    },
+  }
+}
+
+fn published_view_cover(extra: &str) -> Option<PublishedViewCover> {
+  let extra = serde_json::from_str::<serde_json::Value>(extra).ok()?;
+  let cover = extra.get("cover")?;
+  Some(PublishedViewCover {
+    cover_type: cover.get("type")?.as_str()?.to_string(),
+    value: cover.get("value")?.as_str()?.to_string(),
+  })
+}
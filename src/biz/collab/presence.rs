@@ -0,0 +1,30 @@
+use access_control::act::Action;
+use access_control::collab::CollabAccessControl;
+use app_error::AppError;
+use appflowy_collaborate::shared_state::RealtimeSharedState;
+use database_entity::dto::CollabPresence;
+use uuid::Uuid;
+
+/// Returns the users that currently have the collab open, most recently active first. Only the
+/// users that can read the collab can see who is on it.
+pub async fn get_collab_presences(
+  realtime_shared_state: &RealtimeSharedState,
+  collab_access_control: &impl CollabAccessControl,
+  uid: i64,
+  workspace_id: &Uuid,
+  object_id: &str,
+) -> Result<Vec<CollabPresence>, AppError> {
+  if !collab_access_control
+    .enforce_action(&workspace_id.to_string(), &uid, object_id, Action::Read)
+    .await?
+  {
+    return Err(AppError::NotEnoughPermissions {
+      user: uid.to_string(),
+      action: format!("read the presences of collab:{}", object_id),
+    });
+  }
+  realtime_shared_state
+    .get_collab_presences(object_id)
+    .await
+    .map_err(|err| AppError::Internal(err.into()))
+}
@@ -98,3 +98,102 @@ fn to_publish_view(
     None
   }
 }
+
+/// Returns the navigation menu of the published site, in folder order.
+/// Only views that are flagged to show in nav are included. When a flagged view is nested
+/// under views that are not flagged, it is lifted up to its nearest flagged ancestor, or
+/// to the top level if there is none. Views in the trash are excluded.
+pub fn collab_folder_to_published_nav(
+  root_view_id: &str,
+  folder: &Folder,
+  nav_view_ids: &HashSet<String>,
+) -> Vec<PublishedView> {
+  let mut unviewable = HashSet::new();
+  for trash_view in folder.get_all_trash_sections() {
+    unviewable.insert(trash_view.id);
+  }
+
+  let max_depth = 10;
+  let root_view = match folder.get_view(root_view_id) {
+    Some(view) => view,
+    None => return vec![],
+  };
+  root_view
+    .children
+    .iter()
+    .flat_map(|child_view_id| {
+      to_nav_items(
+        root_view_id,
+        &child_view_id.id,
+        folder,
+        &unviewable,
+        nav_view_ids,
+        1,
+        max_depth,
+      )
+    })
+    .collect()
+}
+
+fn to_nav_items(
+  parent_view_id: &str,
+  view_id: &str,
+  folder: &Folder,
+  unviewable: &HashSet<String>,
+  nav_view_ids: &HashSet<String>,
+  depth: u32,
+  max_depth: u32,
+) -> Vec<PublishedView> {
+  if depth > max_depth || unviewable.contains(view_id) {
+    return vec![];
+  }
+
+  let view = match folder.get_view(view_id) {
+    Some(view) => view,
+    None => return vec![],
+  };
+
+  // There is currently a bug, in which the parent_view_id is not always set correctly
+  if view.parent_view_id != parent_view_id {
+    return vec![];
+  }
+
+  let children: Vec<PublishedView> = view
+    .children
+    .iter()
+    .flat_map(|child_view_id| {
+      to_nav_items(
+        view_id,
+        &child_view_id.id,
+        folder,
+        unviewable,
+        nav_view_ids,
+        depth + 1,
+        max_depth,
+      )
+    })
+    .collect();
+
+  if !nav_view_ids.contains(view_id) {
+    return children;
+  }
+
+  let extra = view.extra.as_deref().map(|extra| {
+    serde_json::from_str::<serde_json::Value>(extra).unwrap_or_else(|e| {
+      tracing::warn!("failed to parse extra field({}): {}", extra, e);
+      serde_json::Value::Null
+    })
+  });
+  vec![PublishedView {
+    view_id: view.id.clone(),
+    name: view.name.clone(),
+    icon: view
+      .icon
+      .as_ref()
+      .map(|icon| to_dto_view_icon(icon.clone())),
+    is_published: true,
+    layout: to_view_layout(&view.layout),
+    extra,
+    children,
+  }]
+}
@@ -0,0 +1,75 @@
+use std::collections::{HashMap, HashSet};
+
+use appflowy_collaborate::{CollabRealtimeMetrics, SLIDING_WINDOW_SECS};
+use shared_entity::dto::admin_dto::{
+  CollabRealtimeHealth, QueryRealtimeHealth, RealtimeHealth, WorkspaceRealtimeHealth,
+};
+
+const DEFAULT_REALTIME_HEALTH_LIMIT: usize = 100;
+const MAX_REALTIME_HEALTH_LIMIT: usize = 1000;
+
+/// Returns the state of the realtime collaboration on this server, from the counters kept by the
+/// collaboration server.
+pub fn get_realtime_health(
+  metrics: &CollabRealtimeMetrics,
+  query: QueryRealtimeHealth,
+) -> RealtimeHealth {
+  let health = metrics.health();
+  let limit = query
+    .limit
+    .unwrap_or(DEFAULT_REALTIME_HEALTH_LIMIT)
+    .min(MAX_REALTIME_HEALTH_LIMIT);
+
+  let mut users_by_workspace: HashMap<&str, (HashSet<i64>, usize)> = HashMap::new();
+  for (_, sessions) in &health.collab_sessions {
+    let (users, session_count) = users_by_workspace
+      .entry(sessions.workspace_id.as_str())
+      .or_default();
+    users.extend(sessions.uids.iter().copied());
+    *session_count += sessions.uids.len();
+  }
+  let mut workspaces: Vec<WorkspaceRealtimeHealth> = users_by_workspace
+    .into_iter()
+    .filter(|(_, (_, sessions))| *sessions > 0)
+    .map(
+      |(workspace_id, (users, sessions))| WorkspaceRealtimeHealth {
+        workspace_id: workspace_id.to_string(),
+        connected_users: users.len(),
+        sessions,
+      },
+    )
+    .collect();
+  workspaces.sort_by(|a, b| b.connected_users.cmp(&a.connected_users));
+  workspaces.truncate(limit);
+
+  let mut collabs: Vec<CollabRealtimeHealth> = health
+    .collab_sessions
+    .iter()
+    .map(|(object_id, sessions)| CollabRealtimeHealth {
+      object_id: object_id.clone(),
+      workspace_id: sessions.workspace_id.clone(),
+      collab_type: sessions.collab_type.clone(),
+      sessions: sessions.uids.len(),
+    })
+    .collect();
+  collabs.sort_by(|a, b| b.sessions.cmp(&a.sessions));
+  collabs.truncate(limit);
+
+  let lag = health.recent_broadcast_lag;
+  RealtimeHealth {
+    connected_users: health.connected_users,
+    editing_users: health.editing_users,
+    open_collabs: health.open_collabs,
+    applied_updates: health.applied_updates,
+    failed_updates: health.failed_updates,
+    updates_per_second: health.recent_updates.count as f64 / SLIDING_WINDOW_SECS as f64,
+    avg_broadcast_lag_ms: if lag.count > 0 {
+      lag.sum as f64 / lag.count as f64
+    } else {
+      0.0
+    },
+    max_broadcast_lag_ms: lag.max,
+    workspaces,
+    collabs,
+  }
+}
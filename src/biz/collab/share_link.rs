@@ -0,0 +1,376 @@
+use std::ops::DerefMut;
+
+use access_control::collab::CollabAccessControl;
+use anyhow::Context;
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use database::collab::{
+  delete_collab_share_link, insert_collab_member, insert_collab_share_link,
+  insert_collab_share_link_member, select_collab_member, select_collab_share_link_by_token,
+  select_collab_share_link_member_uids, select_collab_share_links,
+  select_live_workspace_collab_share_links, update_collab_member_expires_at,
+  update_collab_share_link, update_collab_share_link_token, use_collab_share_link,
+};
+use database::pg_row::AFCollabShareLinkRow;
+use database_entity::dto::{
+  AFAccessLevel, CollabShareLink, CreateShareLinkParams, UpdateShareLinkParams,
+};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use super::ops::check_membership_unlocked;
+
+const SHARE_LINK_TOKEN_LEN: usize = 32;
+
+/// Create a link that grants `params.permission` on the object to anyone who opens it.
+pub async fn create_share_link(
+  pg_pool: &PgPool,
+  creator_uid: i64,
+  workspace_id: &Uuid,
+  object_id: &str,
+  params: CreateShareLinkParams,
+) -> Result<CollabShareLink, AppError> {
+  check_link_limits(params.expires_at, params.max_uses)?;
+  let token = generate_share_link_token();
+  let row = insert_collab_share_link(
+    pg_pool,
+    &token,
+    workspace_id,
+    object_id,
+    params.permission.into(),
+    creator_uid,
+    params.expires_at,
+    params.max_uses,
+  )
+  .await?;
+  Ok(share_link_from_row(row))
+}
+
+pub async fn list_share_links(
+  pg_pool: &PgPool,
+  object_id: &str,
+) -> Result<Vec<CollabShareLink>, AppError> {
+  let links = select_collab_share_links(pg_pool, object_id)
+    .await?
+    .into_iter()
+    .map(share_link_from_row)
+    .collect();
+  Ok(links)
+}
+
+/// Returns the links of the workspace that can still be used, most recently used first.
+pub async fn list_live_workspace_share_links(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<CollabShareLink>, AppError> {
+  let links = select_live_workspace_collab_share_links(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .map(share_link_from_row)
+    .collect();
+  Ok(links)
+}
+
+/// Change the permission, the expiration time and the maximum number of uses of the link. The users that joined the object
+/// with the link get the new permission, unless their access level was changed in the meantime.
+pub async fn update_share_link(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  object_id: &str,
+  link_id: &Uuid,
+  params: UpdateShareLinkParams,
+) -> Result<CollabShareLink, AppError> {
+  check_link_limits(params.expires_at, params.max_uses)?;
+  let access_level = AFAccessLevel::from(params.permission);
+  let mut transaction = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to update share link")?;
+  let old_row = update_collab_share_link(
+    transaction.deref_mut(),
+    link_id,
+    object_id,
+    access_level,
+    params.expires_at,
+    params.max_uses,
+  )
+  .await?
+  .ok_or_else(|| share_link_not_found(object_id, link_id))?;
+  let joined_uids = select_collab_share_link_member_uids(transaction.deref_mut(), link_id).await?;
+  let uids = members_at_level(
+    &mut transaction,
+    object_id,
+    joined_uids,
+    AFAccessLevel::from(old_row.access_level),
+  )
+  .await?;
+  for uid in &uids {
+    insert_collab_member(*uid, object_id, &access_level, &mut transaction).await?;
+    update_collab_member_expires_at(*uid, object_id, params.expires_at, &mut transaction).await?;
+  }
+  for uid in &uids {
+    collab_access_control
+      .update_access_level_policy(uid, object_id, access_level)
+      .await?;
+  }
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to update share link")?;
+
+  Ok(share_link_from_row(AFCollabShareLinkRow {
+    access_level: access_level as i32,
+    expires_at: params.expires_at,
+    max_uses: params.max_uses,
+    ..old_row
+  }))
+}
+
+/// Replace the token of the link with a new one. The old token stops resolving as soon as the new
+/// one is issued, while the link keeps its permission, expiration time and uses, and the users that
+/// joined the object with it keep their access.
+pub async fn rotate_share_link(
+  pg_pool: &PgPool,
+  object_id: &str,
+  link_id: &Uuid,
+) -> Result<CollabShareLink, AppError> {
+  let token = generate_share_link_token();
+  let row = update_collab_share_link_token(pg_pool, link_id, object_id, &token)
+    .await?
+    .ok_or_else(|| share_link_not_found(object_id, link_id))?;
+  Ok(share_link_from_row(row))
+}
+
+/// Delete the link. The users that joined the object with the link lose their access, unless
+/// their access level was changed in the meantime.
+pub async fn delete_share_link(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  object_id: &str,
+  link_id: &Uuid,
+) -> Result<(), AppError> {
+  let mut transaction = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to delete share link")?;
+  let removed_uids = delete_link_with_members(&mut transaction, object_id, link_id).await?;
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to delete share link")?;
+  remove_access_policies(collab_access_control, object_id, &removed_uids).await
+}
+
+/// Delete every link of the object, as [delete_share_link] does for a single one. Returns the
+/// number of deleted links.
+pub async fn delete_all_share_links(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  object_id: &str,
+) -> Result<usize, AppError> {
+  let mut transaction = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to delete all share links")?;
+  let links = select_collab_share_links(transaction.deref_mut(), object_id).await?;
+  let mut removed_uids = vec![];
+  for link in &links {
+    removed_uids.extend(delete_link_with_members(&mut transaction, object_id, &link.id).await?);
+  }
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to delete all share links")?;
+  remove_access_policies(collab_access_control, object_id, &removed_uids).await?;
+  Ok(links.len())
+}
+
+/// Resolve the link to the object it grants access to, and the access level it grants. Every link
+/// grants at least [AFAccessLevel::ReadOnly]. Reading the object through the link does not count as
+/// a use of it, only [redeem_share_link] does.
+///
+/// The access level is capped at the level the creator of the link currently holds, so a link
+/// stops granting more than its creator once the creator is downgraded or removed from the object.
+pub async fn resolve_share_link(
+  pg_pool: &PgPool,
+  token: &str,
+) -> Result<(AFCollabShareLinkRow, AFAccessLevel), AppError> {
+  let row = select_collab_share_link_by_token(pg_pool, token)
+    .await?
+    .filter(|row| {
+      row
+        .expires_at
+        .map_or(true, |expires_at| expires_at > Utc::now())
+        && row
+          .max_uses
+          .map_or(true, |max_uses| row.use_count < max_uses)
+    })
+    .ok_or_else(invalid_share_link)?;
+  let access_level = share_link_access_level(pg_pool, &row).await?;
+  Ok((row, access_level))
+}
+
+/// Make the user a member of the object of the link, with the access level the link grants, until
+/// the link expires. A user that is already a member keeps its own access level, so opening a link
+/// never downgrades a member, and deleting the link never removes one. Only the users that become
+/// members count as a use of the link.
+pub async fn redeem_share_link(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  uid: i64,
+  token: &str,
+) -> Result<(), AppError> {
+  let row = select_collab_share_link_by_token(pg_pool, token)
+    .await?
+    .ok_or_else(invalid_share_link)?;
+  let mut transaction = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to redeem share link")?;
+  match select_collab_member(&uid, &row.oid, transaction.deref_mut()).await {
+    Ok(_) => return Ok(()),
+    Err(err) if err.is_record_not_found() => {},
+    Err(err) => return Err(err),
+  }
+  let access_level = share_link_access_level(transaction.deref_mut(), &row).await?;
+  // The link is checked again when it is used, so that concurrent uses cannot exceed its maximum
+  // number of uses.
+  let row = use_collab_share_link(transaction.deref_mut(), token)
+    .await?
+    .ok_or_else(invalid_share_link)?;
+  check_membership_unlocked(&row.oid, false, transaction.deref_mut()).await?;
+  insert_collab_member(uid, &row.oid, &access_level, &mut transaction).await?;
+  update_collab_member_expires_at(uid, &row.oid, row.expires_at, &mut transaction).await?;
+  insert_collab_share_link_member(transaction.deref_mut(), &row.id, uid).await?;
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to redeem share link")?;
+  collab_access_control
+    .update_access_level_policy(&uid, &row.oid, access_level)
+    .await?;
+  Ok(())
+}
+
+/// Returns the access level the link grants, capped at the level its creator holds.
+async fn share_link_access_level<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  row: &AFCollabShareLinkRow,
+) -> Result<AFAccessLevel, AppError> {
+  let creator_level = match select_collab_member(&row.created_by, &row.oid, executor).await {
+    Ok(member) => member.permission.access_level,
+    Err(err) if err.is_record_not_found() => return Err(invalid_share_link()),
+    Err(err) => return Err(err),
+  };
+  Ok(AFAccessLevel::from(row.access_level).min(creator_level))
+}
+
+fn invalid_share_link() -> AppError {
+  AppError::UserUnAuthorized("Invalid share link".to_string())
+}
+
+/// Delete the link, and the membership of the users that joined the object with it and still hold
+/// the access level it grants. Returns the uids of the removed members.
+async fn delete_link_with_members(
+  transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  object_id: &str,
+  link_id: &Uuid,
+) -> Result<Vec<i64>, AppError> {
+  // The record of the users that joined with the link is deleted along with the link
+  let joined_uids = select_collab_share_link_member_uids(transaction.deref_mut(), link_id).await?;
+  let row = delete_collab_share_link(transaction.deref_mut(), link_id, object_id)
+    .await?
+    .ok_or_else(|| share_link_not_found(object_id, link_id))?;
+  let uids = members_at_level(
+    transaction,
+    object_id,
+    joined_uids,
+    AFAccessLevel::from(row.access_level),
+  )
+  .await?;
+  for uid in &uids {
+    database::collab::delete_collab_member(*uid, object_id, transaction).await?;
+  }
+  Ok(uids)
+}
+
+async fn remove_access_policies(
+  collab_access_control: &impl CollabAccessControl,
+  object_id: &str,
+  uids: &[i64],
+) -> Result<(), AppError> {
+  for uid in uids {
+    collab_access_control
+      .remove_access_level(uid, object_id)
+      .await?;
+  }
+  Ok(())
+}
+
+/// Returns the users among `uids` that still hold `access_level` on the object.
+async fn members_at_level(
+  transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  object_id: &str,
+  uids: Vec<i64>,
+  access_level: AFAccessLevel,
+) -> Result<Vec<i64>, AppError> {
+  let mut members = vec![];
+  for uid in uids {
+    match select_collab_member(&uid, object_id, transaction.deref_mut()).await {
+      Ok(member) if member.permission.access_level == access_level => members.push(uid),
+      Ok(_) => {},
+      Err(err) if err.is_record_not_found() => {},
+      Err(err) => return Err(err),
+    }
+  }
+  Ok(members)
+}
+
+fn check_link_limits(
+  expires_at: Option<DateTime<Utc>>,
+  max_uses: Option<i32>,
+) -> Result<(), AppError> {
+  if matches!(expires_at, Some(expires_at) if expires_at <= Utc::now()) {
+    return Err(AppError::InvalidRequest(
+      "The expiration time of the link must be in the future".to_string(),
+    ));
+  }
+  if matches!(max_uses, Some(max_uses) if max_uses < 1) {
+    return Err(AppError::InvalidRequest(
+      "The maximum number of uses of the link must be at least 1".to_string(),
+    ));
+  }
+  Ok(())
+}
+
+fn share_link_not_found(object_id: &str, link_id: &Uuid) -> AppError {
+  AppError::RecordNotFound(format!(
+    "share link {} not found for object {}",
+    link_id, object_id
+  ))
+}
+
+fn share_link_from_row(row: AFCollabShareLinkRow) -> CollabShareLink {
+  CollabShareLink {
+    id: row.id,
+    token: row.token,
+    object_id: row.oid,
+    permission: AFAccessLevel::from(row.access_level).into(),
+    created_by: row.created_by,
+    created_at: row.created_at,
+    expires_at: row.expires_at,
+    max_uses: row.max_uses,
+    use_count: row.use_count,
+    last_used_at: row.last_used_at,
+  }
+}
+
+fn generate_share_link_token() -> String {
+  rand::thread_rng()
+    .sample_iter(&Alphanumeric)
+    .take(SHARE_LINK_TOKEN_LEN)
+    .map(char::from)
+    .collect()
+}
@@ -0,0 +1,261 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use access_control::act::Action;
+use access_control::collab::CollabAccessControl;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab_folder::Folder;
+use database::collab::{
+  select_access_levels_for_user, select_active_collab_object_tokens,
+  select_collab_members_for_objects, GetCollabOrigin,
+};
+use database::publish::select_published_collab_info;
+use database::user::select_web_users_from_uids;
+use database_entity::dto::{AFAccessLevel, ShareImpact, SharingLink, SharingMember, SharingState};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::ops::get_latest_collab_folder;
+
+/// Returns the ancestors of the view, nearest first. The workspace itself is not included.
+pub fn view_ancestor_ids(folder: &Folder, workspace_id: &str, view_id: &str) -> Vec<String> {
+  let mut ancestors = vec![];
+  let mut visited = HashSet::from([view_id.to_string()]);
+  let mut current = folder.get_view(view_id);
+  while let Some(view) = current {
+    let parent_id = view.parent_view_id.clone();
+    // A cycle means the folder is corrupted, stop instead of looping forever
+    if parent_id.is_empty() || parent_id == workspace_id || !visited.insert(parent_id.clone()) {
+      break;
+    }
+    current = folder.get_view(&parent_id);
+    ancestors.push(parent_id);
+  }
+  ancestors
+}
+
+/// Returns everything the sharing dialog of an object needs: the direct members, the members
+/// inherited from ancestor views, the active object tokens, the publish status and the access
+/// level of the caller.
+///
+/// An inherited member gets the access level it holds on the nearest ancestor it is a member of.
+pub async fn get_sharing_state(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  caller_uid: i64,
+  workspace_id: &Uuid,
+  object_id: &str,
+) -> Result<SharingState, AppError> {
+  let workspace_id = workspace_id.to_string();
+  if !collab_access_control
+    .enforce_action(&workspace_id, &caller_uid, object_id, Action::Read)
+    .await?
+  {
+    return Err(AppError::NotEnoughPermissions {
+      user: caller_uid.to_string(),
+      action: format!("read the sharing state of collab:{}", object_id),
+    });
+  }
+
+  let folder =
+    get_latest_collab_folder(collab_storage, GetCollabOrigin::Server, &workspace_id).await?;
+  let ancestor_ids = view_ancestor_ids(&folder, &workspace_id, object_id);
+
+  let mut object_ids = Vec::with_capacity(ancestor_ids.len() + 1);
+  object_ids.push(object_id.to_string());
+  object_ids.extend(ancestor_ids.iter().cloned());
+  let mut members_by_object: HashMap<String, Vec<_>> = HashMap::new();
+  for member in select_collab_members_for_objects(&object_ids, pg_pool).await? {
+    members_by_object
+      .entry(member.oid.clone())
+      .or_default()
+      .push(member);
+  }
+
+  let direct_members = members_by_object.remove(object_id).unwrap_or_default();
+  let mut seen_uids: HashSet<i64> = direct_members.iter().map(|m| m.uid).collect();
+  let mut inherited = vec![];
+  for ancestor_id in &ancestor_ids {
+    for member in members_by_object.remove(ancestor_id).unwrap_or_default() {
+      if seen_uids.insert(member.uid) {
+        inherited.push((ancestor_id.clone(), member));
+      }
+    }
+  }
+
+  let uids: Vec<i64> = seen_uids.into_iter().collect();
+  let mut users = select_web_users_from_uids(pg_pool, &uids).await?;
+
+  let members: Vec<SharingMember> = direct_members
+    .into_iter()
+    .map(|member| SharingMember {
+      user: users.remove(&member.uid),
+      uid: member.uid,
+      access_level: member.permission.access_level,
+      expires_at: member.expires_at,
+      inherited_from: None,
+    })
+    .collect();
+  let inherited_members: Vec<SharingMember> = inherited
+    .into_iter()
+    .map(|(ancestor_id, member)| SharingMember {
+      user: users.remove(&member.uid),
+      uid: member.uid,
+      access_level: member.permission.access_level,
+      expires_at: member.expires_at,
+      inherited_from: Some(ancestor_id),
+    })
+    .collect();
+
+  let links = select_active_collab_object_tokens(pg_pool, object_id)
+    .await?
+    .into_iter()
+    .map(|row| SharingLink {
+      id: row.id,
+      access_level: AFAccessLevel::from(row.access_level),
+      created_by: row.created_by,
+      expires_at: row.expires_at,
+    })
+    .collect();
+
+  let publish_info = match Uuid::parse_str(object_id) {
+    Ok(view_id) => match select_published_collab_info(pg_pool, &view_id).await {
+      Ok(info) => Some(info),
+      Err(err) if err.is_record_not_found() => None,
+      Err(err) => return Err(err),
+    },
+    Err(_) => None,
+  };
+
+  let caller_access_level = members
+    .iter()
+    .chain(inherited_members.iter())
+    .find(|member| member.uid == caller_uid)
+    .map(|member| member.access_level);
+
+  Ok(SharingState {
+    object_id: object_id.to_string(),
+    members,
+    inherited_members,
+    links,
+    publish_info,
+    caller_access_level,
+  })
+}
+
+/// Preview which objects `target_uid` would gain access to if `folder_view_id` were shared with
+/// them at `access_level`. Nothing is changed.
+///
+/// The shared view and every view below it are considered, except views in the trash. An object is
+/// only affected when the level it inherits from the share is higher than what the user already
+/// holds on it. A direct membership on a descendant, or on a view between it and the shared view,
+/// takes precedence over the share, as the nearest membership always wins.
+///
+/// The preview reveals the access of the target, so only the users with full access to the view
+/// can preview the share with someone else.
+#[allow(clippy::too_many_arguments)]
+pub async fn preview_share_impact(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  collab_access_control: &impl CollabAccessControl,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  folder_view_id: &str,
+  target_uid: i64,
+  access_level: AFAccessLevel,
+) -> Result<Vec<ShareImpact>, AppError> {
+  let workspace_id = workspace_id.to_string();
+  if target_uid != uid
+    && !collab_access_control
+      .enforce_access_level(
+        &workspace_id,
+        &uid,
+        folder_view_id,
+        AFAccessLevel::FullAccess,
+      )
+      .await?
+  {
+    return Err(AppError::NotEnoughPermissions {
+      user: uid.to_string(),
+      action: format!("preview the share of collab:{}", folder_view_id),
+    });
+  }
+  let folder =
+    get_latest_collab_folder(collab_storage, GetCollabOrigin::Server, &workspace_id).await?;
+  if folder.get_view(folder_view_id).is_none() {
+    return Err(AppError::RecordNotFound(format!(
+      "view {} not found in workspace {}",
+      folder_view_id, workspace_id
+    )));
+  }
+
+  let trash: HashSet<String> = folder
+    .get_all_trash_sections()
+    .into_iter()
+    .map(|item| item.id)
+    .collect();
+  let ancestor_ids = view_ancestor_ids(&folder, &workspace_id, folder_view_id);
+  let subtree_ids = view_subtree_ids(&folder, folder_view_id, &trash);
+
+  let object_ids: Vec<String> = ancestor_ids
+    .iter()
+    .chain(subtree_ids.iter())
+    .cloned()
+    .collect();
+  let levels = select_access_levels_for_user(target_uid, &object_ids, pg_pool).await?;
+
+  // The level the user currently inherits from above the shared view
+  let inherited_level = ancestor_ids.iter().find_map(|id| levels.get(id).copied());
+  let mut impacts = vec![];
+  let mut stack = vec![(folder_view_id.to_string(), inherited_level, None)];
+  let mut visited = HashSet::new();
+  while let Some((view_id, parent_current, parent_new)) = stack.pop() {
+    if !visited.insert(view_id.clone()) {
+      continue;
+    }
+    let direct = levels.get(&view_id).copied();
+    let current = direct.or(parent_current);
+    let new = if view_id == folder_view_id {
+      Some(access_level)
+    } else {
+      direct.or(parent_new)
+    };
+
+    if let Some(view) = folder.get_view(&view_id) {
+      if new > current {
+        impacts.push(ShareImpact {
+          object_id: view_id.clone(),
+          name: view.name.clone(),
+          access_level: new.unwrap_or(access_level),
+          current_access_level: current,
+        });
+      }
+      for child in view.children.iter().rev() {
+        if !trash.contains(&child.id) {
+          stack.push((child.id.clone(), current, new));
+        }
+      }
+    }
+  }
+  Ok(impacts)
+}
+
+/// Returns the view and all the views below it, skipping the views in `excluded` and their
+/// descendants.
+fn view_subtree_ids(folder: &Folder, view_id: &str, excluded: &HashSet<String>) -> Vec<String> {
+  let mut ids = vec![];
+  let mut visited = HashSet::new();
+  let mut stack = vec![view_id.to_string()];
+  while let Some(id) = stack.pop() {
+    if excluded.contains(&id) || !visited.insert(id.clone()) {
+      continue;
+    }
+    if let Some(view) = folder.get_view(&id) {
+      stack.extend(view.children.iter().map(|child| child.id.clone()));
+    }
+    ids.push(id);
+  }
+  ids
+}
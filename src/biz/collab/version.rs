@@ -0,0 +1,233 @@
+use std::ops::DerefMut;
+use std::sync::Arc;
+
+use anyhow::Context;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab_entity::{CollabType, EncodedCollab};
+use database::collab::{
+  select_collab_versions, select_snapshot, update_collab_snapshot_created_by, CollabStorage,
+  GetCollabOrigin,
+};
+use database_entity::dto::{AFCollabVersion, AFSnapshotMeta, CollabParams, InsertSnapshotParams};
+use serde_json::json;
+use shared_entity::dto::workspace_dto::WebhookEvent;
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+use yrs::types::text::YChange;
+use yrs::{
+  Any, Array, ArrayPrelim, ArrayRef, Map, MapPrelim, MapRef, Out, ReadTxn, Text, TextPrelim,
+  TextRef, TransactionMut,
+};
+
+use crate::biz::workspace::publish_dup::{broadcast_update, collab_from_doc_state, collab_to_bin};
+use crate::biz::workspace::webhook::enqueue_webhook_event_or_log;
+
+use super::ops::get_latest_collab_encoded;
+
+/// Returns the versions of the collab, newest first.
+pub async fn list_collab_versions(
+  pg_pool: &PgPool,
+  object_id: &str,
+) -> Result<Vec<AFCollabVersion>, AppError> {
+  select_collab_versions(pg_pool, object_id).await
+}
+
+/// Returns the state of the collab at the given version.
+pub async fn get_collab_version(
+  pg_pool: &PgPool,
+  workspace_id: &str,
+  object_id: &str,
+  snapshot_id: i64,
+) -> Result<EncodedCollab, AppError> {
+  let blob = select_version_blob(pg_pool, workspace_id, object_id, snapshot_id).await?;
+  EncodedCollab::decode_from_bytes(&blob).map_err(|err| {
+    AppError::Internal(anyhow::anyhow!(
+      "Failed to decode version {} of collab {}: {}",
+      snapshot_id,
+      object_id,
+      err
+    ))
+  })
+}
+
+/// Restore the collab to the given version. The restore is written as a new update on top of the
+/// current state, so the history of the collab is kept and connected clients receive the change.
+/// The state before the restore is saved as a new version, which is returned.
+#[allow(clippy::too_many_arguments)]
+pub async fn restore_collab_version(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &str,
+  object_id: &str,
+  collab_type: CollabType,
+  snapshot_id: i64,
+) -> Result<AFSnapshotMeta, AppError> {
+  let version = get_collab_version(pg_pool, workspace_id, object_id, snapshot_id).await?;
+  let version_collab = collab_from_doc_state(version.doc_state.to_vec(), object_id)?;
+
+  let current = get_latest_collab_encoded(
+    collab_storage.clone(),
+    GetCollabOrigin::Server,
+    workspace_id,
+    object_id,
+    collab_type.clone(),
+  )
+  .await?;
+  let backup = collab_storage
+    .create_snapshot(InsertSnapshotParams {
+      object_id: object_id.to_string(),
+      encoded_collab_v1: current.encode_to_bytes()?,
+      workspace_id: workspace_id.to_string(),
+      collab_type: collab_type.clone(),
+    })
+    .await?;
+  update_collab_snapshot_created_by(pg_pool, backup.snapshot_id, uid).await?;
+
+  let mut collab = collab_from_doc_state(current.doc_state.to_vec(), object_id)?;
+  let encoded_update = {
+    let version_txn = version_collab.transact();
+    let mut txn = collab.context.transact_mut();
+    copy_map(&version_txn, &version_collab.data, &mut txn, &collab.data);
+    txn.encode_update_v1()
+  };
+  let encoded_collab = collab_to_bin(collab, collab_type.clone()).await?;
+
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to restore collab version")?;
+  collab_storage
+    .insert_new_collab_with_transaction(
+      workspace_id,
+      &uid,
+      CollabParams {
+        object_id: object_id.to_string(),
+        encoded_collab_v1: encoded_collab.into(),
+        collab_type: collab_type.clone(),
+        embeddings: None,
+      },
+      txn.deref_mut(),
+    )
+    .await?;
+  txn
+    .commit()
+    .await
+    .context("fail to commit the transaction to restore collab version")?;
+  broadcast_update(&collab_storage, object_id, encoded_update).await?;
+  if let Ok(workspace_id) = Uuid::parse_str(workspace_id) {
+    enqueue_webhook_event_or_log(
+      pg_pool,
+      &workspace_id,
+      WebhookEvent::CollabUpdated,
+      json!({ "object_id": object_id, "collab_type": collab_type, "uid": uid }),
+    )
+    .await;
+  }
+  Ok(backup)
+}
+
+/// Returns the encoded collab stored for the version, making sure the version belongs to the
+/// collab.
+async fn select_version_blob(
+  pg_pool: &PgPool,
+  workspace_id: &str,
+  object_id: &str,
+  snapshot_id: i64,
+) -> Result<Vec<u8>, AppError> {
+  match select_snapshot(pg_pool, &snapshot_id).await? {
+    Some(row) if row.oid == object_id && row.workspace_id.to_string() == workspace_id => {
+      Ok(row.blob)
+    },
+    _ => Err(AppError::RecordNotFound(format!(
+      "Collab {} has no version {}",
+      object_id, snapshot_id
+    ))),
+  }
+}
+
+/// Make `dst` hold the same entries as `src`. Plain values that are already equal are left
+/// untouched, but maps, arrays and texts are always replaced by a copy of the ones of `src`.
+fn copy_map<T: ReadTxn>(src_txn: &T, src: &MapRef, dst_txn: &mut TransactionMut, dst: &MapRef) {
+  let entries: Vec<(String, Out)> = src
+    .iter(src_txn)
+    .map(|(key, value)| (key.to_string(), value))
+    .collect();
+  let stale_keys: Vec<String> = dst
+    .keys(&*dst_txn)
+    .filter(|key| !entries.iter().any(|(k, _)| k == key))
+    .map(|key| key.to_string())
+    .collect();
+  for key in stale_keys {
+    dst.remove(dst_txn, &key);
+  }
+
+  for (key, value) in entries {
+    match value {
+      Out::Any(any) => {
+        if !matches!(dst.get(&*dst_txn, &key), Some(Out::Any(current)) if current == any) {
+          dst.insert(dst_txn, key, any);
+        }
+      },
+      Out::YMap(map) => {
+        let new_map = dst.insert(dst_txn, key, MapPrelim::default());
+        copy_map(src_txn, &map, dst_txn, &new_map);
+      },
+      Out::YArray(array) => {
+        let new_array = dst.insert(dst_txn, key, ArrayPrelim::default());
+        copy_array(src_txn, &array, dst_txn, &new_array);
+      },
+      Out::YText(text) => {
+        let new_text = dst.insert(dst_txn, key, TextPrelim::new(""));
+        copy_text(src_txn, &text, dst_txn, &new_text);
+      },
+      other => warn!("skip restoring unsupported value of {}: {:?}", key, other),
+    }
+  }
+}
+
+fn copy_array<T: ReadTxn>(
+  src_txn: &T,
+  src: &ArrayRef,
+  dst_txn: &mut TransactionMut,
+  dst: &ArrayRef,
+) {
+  let values: Vec<Out> = src.iter(src_txn).collect();
+  for value in values {
+    match value {
+      Out::Any(any) => {
+        dst.push_back(dst_txn, any);
+      },
+      Out::YMap(map) => {
+        let new_map = dst.push_back(dst_txn, MapPrelim::default());
+        copy_map(src_txn, &map, dst_txn, &new_map);
+      },
+      Out::YArray(array) => {
+        let new_array = dst.push_back(dst_txn, ArrayPrelim::default());
+        copy_array(src_txn, &array, dst_txn, &new_array);
+      },
+      Out::YText(text) => {
+        let new_text = dst.push_back(dst_txn, TextPrelim::new(""));
+        copy_text(src_txn, &text, dst_txn, &new_text);
+      },
+      other => warn!("skip restoring unsupported array value: {:?}", other),
+    }
+  }
+}
+
+/// Copy the text chunk by chunk, keeping the formatting of each chunk.
+fn copy_text<T: ReadTxn>(src_txn: &T, src: &TextRef, dst_txn: &mut TransactionMut, dst: &TextRef) {
+  for chunk in src.diff(src_txn, YChange::identity) {
+    let attrs = chunk.attributes.map(|attrs| *attrs).unwrap_or_default();
+    let index = dst.len(&*dst_txn);
+    match chunk.insert {
+      Out::Any(Any::String(s)) => dst.insert_with_attributes(dst_txn, index, &s, attrs),
+      Out::Any(embed) => {
+        dst.insert_embed_with_attributes(dst_txn, index, embed, attrs);
+      },
+      other => warn!("skip restoring unsupported text embed: {:?}", other),
+    }
+  }
+}
@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use chrono::{DateTime, Utc};
+use collab_document::blocks::{Block, DocumentData};
+use collab_document::document::Document;
+use collab_entity::CollabType;
+use database::collab::{select_collab_version_at, GetCollabOrigin};
+use database_entity::dto::{
+  DocumentBlockChange, DocumentBlockDiff, DocumentVersionDiff, QueryCollabVersionDiff, TextChange,
+  TextChangeKind,
+};
+use sqlx::PgPool;
+
+use crate::biz::workspace::publish_dup::collab_from_doc_state;
+use crate::biz::workspace::publish_render::{block_text, children};
+
+use super::ops::get_latest_collab_encoded;
+use super::version::get_collab_version;
+
+/// The texts are compared word by word as long as the table of the comparison has at most this
+/// many cells. Longer texts are compared as a single replaced part.
+const MAX_TEXT_DIFF_CELLS: usize = 250_000;
+
+/// Compare two versions of a document, or a version with the current state of the document.
+pub async fn diff_document_versions(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &str,
+  object_id: &str,
+  query: QueryCollabVersionDiff,
+) -> Result<DocumentVersionDiff, AppError> {
+  let from_snapshot_id = resolve_version(
+    pg_pool,
+    object_id,
+    query.from_snapshot_id,
+    query.from_time,
+    "from",
+  )
+  .await?
+  .ok_or_else(|| {
+    AppError::InvalidRequest("Either from_snapshot_id or from_time must be given".to_string())
+  })?;
+  let to_snapshot_id = resolve_version(
+    pg_pool,
+    object_id,
+    query.to_snapshot_id,
+    query.to_time,
+    "to",
+  )
+  .await?;
+
+  let from = get_collab_version(pg_pool, workspace_id, object_id, from_snapshot_id).await?;
+  let to = match to_snapshot_id {
+    Some(to_snapshot_id) => {
+      get_collab_version(pg_pool, workspace_id, object_id, to_snapshot_id).await?
+    },
+    None => {
+      get_latest_collab_encoded(
+        collab_storage,
+        GetCollabOrigin::User { uid },
+        workspace_id,
+        object_id,
+        CollabType::Document,
+      )
+      .await?
+    },
+  };
+
+  let object_id = object_id.to_string();
+  tokio::task::spawn_blocking(move || {
+    let from = document_data(from.doc_state.to_vec(), &object_id)?;
+    let to = document_data(to.doc_state.to_vec(), &object_id)?;
+    let (inserted_blocks, removed_blocks, changed_blocks) = diff_documents(&from, &to);
+    Ok(DocumentVersionDiff {
+      object_id,
+      from_snapshot_id,
+      to_snapshot_id,
+      inserted_blocks,
+      removed_blocks,
+      changed_blocks,
+    })
+  })
+  .await?
+}
+
+/// Returns the version given by its id, or the latest version at the given time.
+async fn resolve_version(
+  pg_pool: &PgPool,
+  object_id: &str,
+  snapshot_id: Option<i64>,
+  time: Option<DateTime<Utc>>,
+  side: &str,
+) -> Result<Option<i64>, AppError> {
+  match (snapshot_id, time) {
+    (Some(_), Some(_)) => Err(AppError::InvalidRequest(format!(
+      "Only one of {}_snapshot_id and {}_time can be given",
+      side, side
+    ))),
+    (Some(snapshot_id), None) => Ok(Some(snapshot_id)),
+    (None, Some(time)) => select_collab_version_at(pg_pool, object_id, time)
+      .await?
+      .map(Some)
+      .ok_or_else(|| {
+        AppError::RecordNotFound(format!(
+          "Collab {} has no version created at or before {}",
+          object_id, time
+        ))
+      }),
+    (None, None) => Ok(None),
+  }
+}
+
+fn document_data(doc_state: Vec<u8>, object_id: &str) -> Result<DocumentData, AppError> {
+  collab_from_doc_state(doc_state, object_id)
+    .and_then(|collab| Document::open(collab).map_err(|e| AppError::Unhandled(e.to_string())))
+    .and_then(|document| {
+      document
+        .get_document_data()
+        .map_err(|e| AppError::Unhandled(e.to_string()))
+    })
+    .map_err(|_| AppError::InvalidRequest(format!("collab {} is not a document", object_id)))
+}
+
+/// The blocks of the document below its page, in the order they are displayed.
+fn ordered_blocks(document: &DocumentData) -> Vec<&Block> {
+  let mut blocks = vec![];
+  let mut stack = match document.blocks.get(&document.page_id) {
+    Some(page) => children(document, page),
+    None => return blocks,
+  };
+  stack.reverse();
+  while let Some(block) = stack.pop() {
+    blocks.push(block);
+    stack.extend(children(document, block).into_iter().rev());
+  }
+  blocks
+}
+
+/// Returns the inserted, the removed and the changed blocks.
+fn diff_documents(
+  from: &DocumentData,
+  to: &DocumentData,
+) -> (
+  Vec<DocumentBlockDiff>,
+  Vec<DocumentBlockDiff>,
+  Vec<DocumentBlockChange>,
+) {
+  let from_blocks = ordered_blocks(from);
+  let to_blocks = ordered_blocks(to);
+  let from_by_id: HashMap<&str, &Block> = from_blocks
+    .iter()
+    .map(|block| (block.id.as_str(), *block))
+    .collect();
+  let to_by_id: HashMap<&str, &Block> = to_blocks
+    .iter()
+    .map(|block| (block.id.as_str(), *block))
+    .collect();
+
+  let mut inserted_blocks = vec![];
+  let mut changed_blocks = vec![];
+  for block in &to_blocks {
+    let text = block_text(to, block);
+    let previous = match from_by_id.get(block.id.as_str()) {
+      Some(previous) => previous,
+      None => {
+        inserted_blocks.push(block_diff(block, text));
+        continue;
+      },
+    };
+    let previous_text = block_text(from, previous);
+    let previous_ty = (previous.ty != block.ty).then(|| previous.ty.clone());
+    let moved = previous.parent != block.parent;
+    let data_changed = data_without_text(previous) != data_without_text(block);
+    let text_changes = if previous_text == text {
+      vec![]
+    } else {
+      text_changes(&previous_text, &text)
+    };
+    if previous_ty.is_some() || moved || data_changed || !text_changes.is_empty() {
+      changed_blocks.push(DocumentBlockChange {
+        block_id: block.id.clone(),
+        ty: block.ty.clone(),
+        previous_ty,
+        moved,
+        data_changed,
+        text_changes,
+      });
+    }
+  }
+  let removed_blocks = from_blocks
+    .iter()
+    .filter(|block| !to_by_id.contains_key(block.id.as_str()))
+    .map(|block| block_diff(block, block_text(from, block)))
+    .collect();
+  (inserted_blocks, removed_blocks, changed_blocks)
+}
+
+fn block_diff(block: &Block, text: String) -> DocumentBlockDiff {
+  DocumentBlockDiff {
+    block_id: block.id.clone(),
+    ty: block.ty.clone(),
+    parent_id: block.parent.clone(),
+    text,
+  }
+}
+
+fn data_without_text(block: &Block) -> Vec<(&String, &serde_json::Value)> {
+  let mut data: Vec<_> = block
+    .data
+    .iter()
+    .filter(|(key, _)| key.as_str() != "delta")
+    .collect();
+  data.sort_by(|a, b| a.0.cmp(b.0));
+  data
+}
+
+/// Split the new text into the parts kept from the old text and the inserted parts, with the
+/// deleted parts of the old text in between. The texts are compared word by word.
+fn text_changes(old: &str, new: &str) -> Vec<TextChange> {
+  let old_words = words(old);
+  let new_words = words(new);
+  let prefix = old_words
+    .iter()
+    .zip(&new_words)
+    .take_while(|(a, b)| a == b)
+    .count();
+  let suffix = old_words[prefix..]
+    .iter()
+    .rev()
+    .zip(new_words[prefix..].iter().rev())
+    .take_while(|(a, b)| a == b)
+    .count();
+  let old_middle = &old_words[prefix..old_words.len() - suffix];
+  let new_middle = &new_words[prefix..new_words.len() - suffix];
+
+  let mut changes = vec![];
+  push_change(&mut changes, TextChangeKind::Equal, &old_words[..prefix]);
+  if old_middle.len() * new_middle.len() > MAX_TEXT_DIFF_CELLS {
+    push_change(&mut changes, TextChangeKind::Delete, old_middle);
+    push_change(&mut changes, TextChangeKind::Insert, new_middle);
+  } else {
+    for (kind, word) in diff_words(old_middle, new_middle) {
+      push_change(&mut changes, kind, &[word]);
+    }
+  }
+  push_change(
+    &mut changes,
+    TextChangeKind::Equal,
+    &old_words[old_words.len() - suffix..],
+  );
+  changes
+}
+
+/// The longest common subsequence of the words, as the words to keep, delete and insert.
+fn diff_words<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(TextChangeKind, &'a str)> {
+  // lengths[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+  let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+  for i in (0..old.len()).rev() {
+    for j in (0..new.len()).rev() {
+      lengths[i][j] = if old[i] == new[j] {
+        lengths[i + 1][j + 1] + 1
+      } else {
+        lengths[i + 1][j].max(lengths[i][j + 1])
+      };
+    }
+  }
+
+  let mut words = vec![];
+  let (mut i, mut j) = (0, 0);
+  while i < old.len() && j < new.len() {
+    if old[i] == new[j] {
+      words.push((TextChangeKind::Equal, old[i]));
+      i += 1;
+      j += 1;
+    } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+      words.push((TextChangeKind::Delete, old[i]));
+      i += 1;
+    } else {
+      words.push((TextChangeKind::Insert, new[j]));
+      j += 1;
+    }
+  }
+  words.extend(old[i..].iter().map(|word| (TextChangeKind::Delete, *word)));
+  words.extend(new[j..].iter().map(|word| (TextChangeKind::Insert, *word)));
+  words
+}
+
+/// Append the words to the last change when it is of the same kind.
+fn push_change(changes: &mut Vec<TextChange>, kind: TextChangeKind, words: &[&str]) {
+  if words.is_empty() {
+    return;
+  }
+  match changes.last_mut() {
+    Some(last) if last.kind == kind => last.text.push_str(&words.concat()),
+    _ => changes.push(TextChange {
+      kind,
+      text: words.concat(),
+    }),
+  }
+}
+
+/// Split the text into words and the characters between them, so that the parts put back
+/// together are the text.
+fn words(text: &str) -> Vec<&str> {
+  let mut words = vec![];
+  let mut start = None;
+  for (index, c) in text.char_indices() {
+    if c.is_alphanumeric() {
+      start.get_or_insert(index);
+      continue;
+    }
+    if let Some(start) = start.take() {
+      words.push(&text[start..index]);
+    }
+    words.push(&text[index..index + c.len_utf8()]);
+  }
+  if let Some(start) = start {
+    words.push(&text[start..]);
+  }
+  words
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn change(kind: TextChangeKind, text: &str) -> TextChange {
+    TextChange {
+      kind,
+      text: text.to_string(),
+    }
+  }
+
+  #[test]
+  fn words_test() {
+    assert_eq!(
+      words("Hello, wörld!"),
+      vec!["Hello", ",", " ", "wörld", "!"]
+    );
+    assert_eq!(words(""), Vec::<&str>::new());
+  }
+
+  #[test]
+  fn text_changes_test() {
+    assert_eq!(
+      text_changes("The quick brown fox", "The slow brown fox jumps"),
+      vec![
+        change(TextChangeKind::Equal, "The "),
+        change(TextChangeKind::Delete, "quick"),
+        change(TextChangeKind::Insert, "slow"),
+        change(TextChangeKind::Equal, " brown fox"),
+        change(TextChangeKind::Insert, " jumps"),
+      ]
+    );
+    assert_eq!(
+      text_changes("", "new"),
+      vec![change(TextChangeKind::Insert, "new")]
+    );
+    assert_eq!(
+      text_changes("a b c", "c b a"),
+      vec![
+        change(TextChangeKind::Delete, "a"),
+        change(TextChangeKind::Insert, "c"),
+        change(TextChangeKind::Equal, " b "),
+        change(TextChangeKind::Delete, "c"),
+        change(TextChangeKind::Insert, "a"),
+      ]
+    );
+  }
+}
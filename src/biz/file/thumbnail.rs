@@ -0,0 +1,235 @@
+use std::io::Cursor;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use database::file::s3_client_impl::{AwsS3BucketClientImpl, S3BucketStorage};
+use database::file::{BlobKey, BucketClient, ResponseBlob};
+use image::io::Reader;
+use image::{DynamicImage, GenericImageView, ImageError, ImageOutputFormat};
+use once_cell::sync::Lazy;
+use tokio::sync::Semaphore;
+use tracing::{trace, warn};
+
+/// The directory of each workspace the thumbnails are cached in. The uploads can't use it as
+/// their parent directory.
+pub const THUMBNAIL_DIR: &str = ".thumbnail";
+
+/// The sizes the thumbnails are generated at. A requested size is rounded up to one of them, so
+/// that at most a few thumbnails are cached for an image.
+pub const THUMBNAIL_SIZES: [u32; 5] = [64, 128, 256, 512, 1024];
+
+pub const DEFAULT_THUMBNAIL_SIZE: u32 = 256;
+
+/// The images larger than this are not decoded, as the decoding takes memory proportional to the
+/// number of pixels.
+const MAX_SOURCE_IMAGE_SIZE: usize = 20 * 1024 * 1024;
+
+/// The images with more pixels than this are not decoded. A small compressed image can declare
+/// huge dimensions, and the decoded image takes memory proportional to them.
+const MAX_SOURCE_IMAGE_PIXELS: u64 = 40_000_000;
+
+const THUMBNAIL_JPEG_QUALITY: u8 = 85;
+
+/// Limits the number of thumbnails generated at the same time.
+static THUMBNAIL_PERMITS: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(4));
+
+pub struct Thumbnail {
+  pub data: Vec<u8>,
+  pub content_type: String,
+}
+
+/// Returns the size in [THUMBNAIL_SIZES] a thumbnail of the requested size is generated at.
+pub fn thumbnail_size(requested: u32) -> u32 {
+  THUMBNAIL_SIZES
+    .iter()
+    .copied()
+    .find(|size| *size >= requested)
+    .unwrap_or(THUMBNAIL_SIZES[THUMBNAIL_SIZES.len() - 1])
+}
+
+fn thumbnail_object_key(key: &impl BlobKey, size: u32) -> String {
+  format!(
+    "{}/{}/{}/{}",
+    key.workspace_id(),
+    THUMBNAIL_DIR,
+    size,
+    key.meta_key()
+  )
+}
+
+/// Returns the thumbnail of the image, which fits in a square of the given size. The thumbnail is
+/// generated the first time it is requested and cached in the bucket next to the image.
+pub async fn get_or_create_thumbnail(
+  bucket_storage: &S3BucketStorage,
+  bucket_client: &AwsS3BucketClientImpl,
+  key: &impl BlobKey,
+  size: u32,
+) -> Result<Thumbnail, AppError> {
+  let size = thumbnail_size(size);
+  let thumbnail_key = thumbnail_object_key(key, size);
+  match bucket_client.get_blob(&thumbnail_key).await {
+    Ok(cached) => {
+      let content_type = cached
+        .content_type()
+        .unwrap_or_else(|| mime::IMAGE_PNG.to_string());
+      return Ok(Thumbnail {
+        data: cached.to_blob(),
+        content_type,
+      });
+    },
+    Err(err) if err.is_record_not_found() => {},
+    Err(err) => return Err(err),
+  }
+
+  let metadata = bucket_storage
+    .get_blob_metadata(key.workspace_id(), &key.meta_key())
+    .await?;
+  if !metadata.file_type.starts_with("image/") {
+    return Err(AppError::InvalidRequest(format!(
+      "{} is not an image",
+      metadata.file_type
+    )));
+  }
+  if metadata.file_size as usize > MAX_SOURCE_IMAGE_SIZE {
+    return Err(AppError::PayloadTooLarge(format!(
+      "The image is larger than {} bytes",
+      MAX_SOURCE_IMAGE_SIZE
+    )));
+  }
+
+  let image = bucket_storage.get_blob(key).await?;
+  let _permit = THUMBNAIL_PERMITS
+    .acquire()
+    .await
+    .map_err(|err| AppError::Internal(err.into()))?;
+  trace!(
+    "generating thumbnail of size {} for {}",
+    size,
+    key.object_key()
+  );
+  let thumbnail = tokio::task::spawn_blocking(move || generate_thumbnail(&image, size))
+    .await
+    .map_err(|err| AppError::Internal(err.into()))??;
+
+  if let Err(err) = bucket_client
+    .put_blob_as_content_type(&thumbnail_key, &thumbnail.data, &thumbnail.content_type)
+    .await
+  {
+    warn!("Failed to cache the thumbnail {}: {}", thumbnail_key, err);
+  }
+  Ok(thumbnail)
+}
+
+/// Deletes the cached thumbnails of the image.
+pub async fn delete_thumbnails(
+  bucket_client: &AwsS3BucketClientImpl,
+  key: &impl BlobKey,
+) -> Result<(), AppError> {
+  let thumbnail_keys = THUMBNAIL_SIZES
+    .iter()
+    .map(|size| thumbnail_object_key(key, *size))
+    .collect();
+  bucket_client.delete_blobs(thumbnail_keys).await?;
+  Ok(())
+}
+
+/// Scales the image down to fit in a square of the given size, keeping its aspect ratio. The
+/// images with transparency are encoded as PNG, the others as JPEG.
+fn generate_thumbnail(data: &[u8], size: u32) -> Result<Thumbnail, AppError> {
+  let unsupported =
+    |err: ImageError| AppError::InvalidRequest(format!("Unsupported image: {}", err));
+  let reader = || {
+    Reader::new(Cursor::new(data))
+      .with_guessed_format()
+      .map_err(|err| unsupported(err.into()))
+  };
+  // The dimensions are read from the header, before any pixel is decoded
+  let (width, height) = reader()?.into_dimensions().map_err(unsupported)?;
+  if width as u64 * height as u64 > MAX_SOURCE_IMAGE_PIXELS {
+    return Err(AppError::PayloadTooLarge(format!(
+      "The image has more than {} pixels",
+      MAX_SOURCE_IMAGE_PIXELS
+    )));
+  }
+  let image = reader()?.decode().map_err(unsupported)?;
+  let (width, height) = image.dimensions();
+  let thumbnail = if width <= size && height <= size {
+    image
+  } else {
+    image.thumbnail(size, size)
+  };
+
+  let mut buf = Cursor::new(Vec::new());
+  let content_type = if thumbnail.color().has_alpha() {
+    DynamicImage::ImageRgba8(thumbnail.to_rgba8())
+      .write_to(&mut buf, ImageOutputFormat::Png)
+      .map_err(|err| AppError::Internal(anyhow!("Failed to encode thumbnail: {}", err)))?;
+    mime::IMAGE_PNG
+  } else {
+    DynamicImage::ImageRgb8(thumbnail.to_rgb8())
+      .write_to(&mut buf, ImageOutputFormat::Jpeg(THUMBNAIL_JPEG_QUALITY))
+      .map_err(|err| AppError::Internal(anyhow!("Failed to encode thumbnail: {}", err)))?;
+    mime::IMAGE_JPEG
+  };
+  Ok(Thumbnail {
+    data: buf.into_inner(),
+    content_type: content_type.to_string(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+  fn encode_png(image: DynamicImage) -> Vec<u8> {
+    let mut buf = Cursor::new(Vec::new());
+    image.write_to(&mut buf, ImageOutputFormat::Png).unwrap();
+    buf.into_inner()
+  }
+
+  #[test]
+  fn thumbnail_size_test() {
+    assert_eq!(thumbnail_size(0), 64);
+    assert_eq!(thumbnail_size(64), 64);
+    assert_eq!(thumbnail_size(100), 128);
+    assert_eq!(thumbnail_size(1024), 1024);
+    assert_eq!(thumbnail_size(5000), 1024);
+  }
+
+  #[test]
+  fn generate_thumbnail_test() {
+    let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(800, 400, Rgb([10, 20, 30])));
+    let thumbnail = generate_thumbnail(&encode_png(image), 256).unwrap();
+    assert_eq!(thumbnail.content_type, mime::IMAGE_JPEG.to_string());
+    let decoded = image::load_from_memory(&thumbnail.data).unwrap();
+    assert_eq!(decoded.dimensions(), (256, 128));
+
+    // The transparency is kept and the small images are not scaled up
+    let image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(100, 50, Rgba([1, 2, 3, 0])));
+    let thumbnail = generate_thumbnail(&encode_png(image), 256).unwrap();
+    assert_eq!(thumbnail.content_type, mime::IMAGE_PNG.to_string());
+    let decoded = image::load_from_memory(&thumbnail.data).unwrap();
+    assert_eq!(decoded.dimensions(), (100, 50));
+  }
+
+  #[test]
+  fn generate_thumbnail_of_huge_image_test() {
+    // A PNG that declares a 7000x7000 grayscale image, followed by empty pixel data
+    let mut data = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+    data.extend_from_slice(&7000u32.to_be_bytes());
+    data.extend_from_slice(&7000u32.to_be_bytes());
+    data.extend_from_slice(&[8, 0, 0, 0, 0]);
+    data.extend_from_slice(&0xbcf0e453u32.to_be_bytes());
+    data.extend_from_slice(b"\x00\x00\x00\x00IDAT");
+    data.extend_from_slice(&0x35af061eu32.to_be_bytes());
+    let err = generate_thumbnail(&data, 256).unwrap_err();
+    assert!(matches!(err, AppError::PayloadTooLarge(_)));
+  }
+
+  #[test]
+  fn generate_thumbnail_of_invalid_image_test() {
+    let err = generate_thumbnail(b"not an image", 256).unwrap_err();
+    assert!(matches!(err, AppError::InvalidRequest(_)));
+  }
+}
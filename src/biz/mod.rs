@@ -1,6 +1,10 @@
+pub mod ai;
 pub mod chat;
 pub mod collab;
+pub mod file;
+pub mod notification;
 pub mod pg_listener;
+pub mod pg_read_pool;
 pub mod search;
 pub mod template;
 pub mod user;
@@ -0,0 +1,335 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use app_error::AppError;
+use chrono::Utc;
+use database::notification::{
+  insert_notifications, select_notification_email_delivery, select_notifications,
+  select_or_insert_unsubscribe_token, select_pending_email_notifications,
+  select_pending_email_recipients, update_email_delivery_by_unsubscribe_token,
+  update_notification_last_emailed_at, update_notifications_email_handled,
+  upsert_notification_email_delivery,
+};
+use database::pg_row::{AFNotificationRecipientRow, AFNotificationRow};
+use database::user::select_name_from_uuid;
+use serde_json::json;
+use shared_entity::dto::notification_dto::{
+  EmailDelivery, Notification, NotificationKind, NotificationSettings,
+};
+use sqlx::PgPool;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::config::config::NotificationSetting;
+use crate::mailer::{Mailer, NotificationMailerItem, NotificationMailerParam};
+
+const NOTIFICATION_LIST_LIMIT: i64 = 100;
+const NOTIFICATION_EMAIL_BATCH_SIZE: i64 = 50;
+/// The number of characters of a comment quoted in a notification.
+const MAX_QUOTED_CONTENT_LEN: usize = 200;
+
+/// Record the notification for every user in `uids`. Apart from workspace invitations, whose
+/// invitation email is sent when the invitation is created, the notifications are emailed in the
+/// background by [spawn_notification_mailer].
+pub async fn record_notification<'a, E: sqlx::Executor<'a, Database = sqlx::Postgres>>(
+  executor: E,
+  uids: &[i64],
+  workspace_id: Option<&Uuid>,
+  kind: NotificationKind,
+  data: serde_json::Value,
+) -> Result<(), AppError> {
+  if uids.is_empty() {
+    return Ok(());
+  }
+  let email = kind != NotificationKind::WorkspaceInvite;
+  insert_notifications(executor, uids, workspace_id, kind.as_str(), &data, email).await
+}
+
+/// Like [record_notification], for events of operations that have already succeeded. A failure
+/// to record the notification is logged rather than returned.
+pub async fn record_notification_or_log(
+  pg_pool: &PgPool,
+  uids: &[i64],
+  workspace_id: Option<&Uuid>,
+  kind: NotificationKind,
+  data: serde_json::Value,
+) {
+  if let Err(err) = record_notification(pg_pool, uids, workspace_id, kind, data).await {
+    error!(
+      "Failed to record {} notification for {:?}: {}",
+      kind.as_str(),
+      uids,
+      err
+    );
+  }
+}
+
+/// Notify the users that `actor` added them as members of the collab. Failures are logged.
+pub async fn notify_shared_with_you(
+  pg_pool: &PgPool,
+  actor: &Uuid,
+  workspace_id: &str,
+  object_id: &str,
+  uids: &[i64],
+) {
+  if uids.is_empty() {
+    return;
+  }
+  let workspace_id = Uuid::parse_str(workspace_id).ok();
+  let actor_name = select_name_from_uuid(pg_pool, actor)
+    .await
+    .unwrap_or_default();
+  record_notification_or_log(
+    pg_pool,
+    uids,
+    workspace_id.as_ref(),
+    NotificationKind::SharedWithYou,
+    json!({ "actor_name": actor_name, "object_id": object_id }),
+  )
+  .await;
+}
+
+/// Returns the latest notifications of the user, newest first.
+pub async fn get_notifications(pg_pool: &PgPool, uid: i64) -> Result<Vec<Notification>, AppError> {
+  let rows = select_notifications(pg_pool, uid, NOTIFICATION_LIST_LIMIT).await?;
+  Ok(rows.into_iter().filter_map(notification_from_row).collect())
+}
+
+pub async fn get_notification_settings(
+  pg_pool: &PgPool,
+  uid: i64,
+) -> Result<NotificationSettings, AppError> {
+  let email_delivery = select_notification_email_delivery(pg_pool, uid).await?;
+  Ok(NotificationSettings {
+    email_delivery: EmailDelivery::from(email_delivery),
+  })
+}
+
+pub async fn update_notification_settings(
+  pg_pool: &PgPool,
+  uid: i64,
+  settings: NotificationSettings,
+) -> Result<NotificationSettings, AppError> {
+  upsert_notification_email_delivery(pg_pool, uid, settings.email_delivery as i16).await?;
+  Ok(settings)
+}
+
+/// Stop emailing the user the token belongs to. The token is carried by the unsubscribe link of
+/// every notification email, so no sign in is needed.
+pub async fn unsubscribe_notification_emails(
+  pg_pool: &PgPool,
+  token: &Uuid,
+) -> Result<(), AppError> {
+  let updated =
+    update_email_delivery_by_unsubscribe_token(pg_pool, token, EmailDelivery::Off as i16).await?;
+  if !updated {
+    return Err(AppError::RecordNotFound(
+      "The unsubscribe link is invalid".to_string(),
+    ));
+  }
+  Ok(())
+}
+
+/// Periodically email the pending notifications, see [email_pending_notifications].
+/// A zero `email_interval_secs` disables the emails.
+pub fn spawn_notification_mailer(
+  pg_pool: PgPool,
+  mailer: Mailer,
+  setting: NotificationSetting,
+  appflowy_web_url: Option<String>,
+) {
+  if setting.email_interval_secs == 0 {
+    return;
+  }
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(Duration::from_secs(setting.email_interval_secs));
+    loop {
+      interval.tick().await;
+      if let Err(err) =
+        email_pending_notifications(&pg_pool, &mailer, &setting, appflowy_web_url.as_deref()).await
+      {
+        warn!("Failed to email notifications: {:?}", err);
+      }
+    }
+  });
+}
+
+/// Email the notifications that have not been emailed yet. Users that receive a daily digest get
+/// all their pending notifications in one email, at most once per digest interval. The
+/// notifications of users that turned emails off are skipped.
+pub async fn email_pending_notifications(
+  pg_pool: &PgPool,
+  mailer: &Mailer,
+  setting: &NotificationSetting,
+  appflowy_web_url: Option<&str>,
+) -> Result<(), AppError> {
+  let digest_cutoff = Utc::now() - chrono::Duration::seconds(setting.digest_interval_secs as i64);
+  let recipients =
+    select_pending_email_recipients(pg_pool, digest_cutoff, NOTIFICATION_EMAIL_BATCH_SIZE).await?;
+  for recipient in recipients {
+    let uid = recipient.uid;
+    if let Err(err) =
+      email_recipient_notifications(pg_pool, mailer, setting, appflowy_web_url, recipient).await
+    {
+      // The notifications stay pending, and are emailed on the next run
+      warn!(
+        "Failed to email the notifications of user {}: {:?}",
+        uid, err
+      );
+    }
+  }
+  Ok(())
+}
+
+async fn email_recipient_notifications(
+  pg_pool: &PgPool,
+  mailer: &Mailer,
+  setting: &NotificationSetting,
+  appflowy_web_url: Option<&str>,
+  recipient: AFNotificationRecipientRow,
+) -> Result<(), AppError> {
+  let rows = select_pending_email_notifications(pg_pool, recipient.uid).await?;
+  let ids: Vec<i64> = rows.iter().map(|row| row.id).collect();
+  let email_delivery = EmailDelivery::from(recipient.email_delivery);
+  let email = match recipient.email {
+    Some(email) if !email.is_empty() && email_delivery != EmailDelivery::Off => email,
+    _ => return update_notifications_email_handled(pg_pool, &ids).await,
+  };
+
+  let notifications: Vec<Notification> =
+    rows.into_iter().filter_map(notification_from_row).collect();
+  let items: Vec<NotificationMailerItem> = notifications
+    .iter()
+    .map(|notification| NotificationMailerItem {
+      message: notification_message(notification),
+      url: notification_url(notification, appflowy_web_url),
+    })
+    .collect();
+  let title = match (email_delivery, items.as_slice()) {
+    (EmailDelivery::Immediate, [item]) => item.message.clone(),
+    _ => format!("You have {} new notifications in AppFlowy", items.len()),
+  };
+  if !items.is_empty() {
+    let token = select_or_insert_unsubscribe_token(pg_pool, recipient.uid).await?;
+    mailer
+      .send_notification(
+        email,
+        NotificationMailerParam {
+          username: recipient.name.unwrap_or_default(),
+          title,
+          items,
+          unsubscribe_url: format!(
+            "{}/api/notification/unsubscribe?token={}",
+            setting.public_url.trim_end_matches('/'),
+            token
+          ),
+        },
+      )
+      .await
+      .map_err(AppError::Internal)?;
+  }
+  update_notifications_email_handled(pg_pool, &ids).await?;
+  update_notification_last_emailed_at(pg_pool, recipient.uid).await?;
+  Ok(())
+}
+
+fn notification_from_row(row: AFNotificationRow) -> Option<Notification> {
+  let kind = match NotificationKind::from_str(&row.kind) {
+    Ok(kind) => kind,
+    Err(err) => {
+      warn!("Skip notification {}: {}", row.id, err);
+      return None;
+    },
+  };
+  Some(Notification {
+    id: row.id,
+    workspace_id: row.workspace_id,
+    kind,
+    data: row.data,
+    created_at: row.created_at,
+  })
+}
+
+fn notification_message(notification: &Notification) -> String {
+  let field = |name: &str| {
+    notification
+      .data
+      .get(name)
+      .and_then(|value| value.as_str())
+      .unwrap_or_default()
+      .to_string()
+  };
+  let actor = match field("actor_name") {
+    name if name.is_empty() => "Someone".to_string(),
+    name => name,
+  };
+  let quoted_content = || {
+    let content = field("content");
+    let mut quoted: String = content.chars().take(MAX_QUOTED_CONTENT_LEN).collect();
+    if quoted.len() < content.len() {
+      quoted.push('…');
+    }
+    quoted
+  };
+  match notification.kind {
+    NotificationKind::Mention => {
+      format!(
+        "{} mentioned you in a comment: \"{}\"",
+        actor,
+        quoted_content()
+      )
+    },
+    NotificationKind::CommentReply => {
+      format!(
+        "{} replied to your comment: \"{}\"",
+        actor,
+        quoted_content()
+      )
+    },
+    NotificationKind::SharedWithYou => format!("{} shared a page with you", actor),
+    NotificationKind::WorkspaceInvite => {
+      format!("{} invited you to {}", actor, field("workspace_name"))
+    },
+  }
+}
+
+/// Returns the link to the object of the notification in AppFlowy Web, if any.
+fn notification_url(notification: &Notification, appflowy_web_url: Option<&str>) -> Option<String> {
+  let web_url = appflowy_web_url?.trim_end_matches('/');
+  let workspace_id = notification.workspace_id?;
+  let object_id = notification.data.get("object_id")?.as_str()?;
+  Some(format!("{}/app/{}/{}", web_url, workspace_id, object_id))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn notification_message_test() {
+    let workspace_id = Uuid::new_v4();
+    let mut notification = Notification {
+      id: 1,
+      workspace_id: Some(workspace_id),
+      kind: NotificationKind::Mention,
+      data: json!({ "actor_name": "Lucas", "object_id": "doc", "content": "a".repeat(300) }),
+      created_at: Utc::now(),
+    };
+    assert_eq!(
+      notification_message(&notification),
+      format!("Lucas mentioned you in a comment: \"{}…\"", "a".repeat(200))
+    );
+    assert_eq!(
+      notification_url(&notification, Some("https://appflowy.com/")),
+      Some(format!("https://appflowy.com/app/{}/doc", workspace_id))
+    );
+    assert_eq!(notification_url(&notification, None), None);
+
+    notification.kind = NotificationKind::SharedWithYou;
+    notification.data = json!({});
+    assert_eq!(
+      notification_message(&notification),
+      "Someone shared a page with you"
+    );
+  }
+}
@@ -1,5 +1,7 @@
 use anyhow::Error;
-use appflowy_collaborate::collab::notification::CollabMemberNotification;
+use appflowy_collaborate::collab::notification::{
+  CollabInheritanceNotification, CollabMemberNotification,
+};
 use database::listener::PostgresDBListener;
 use database::pg_row::AFUserNotification;
 use sqlx::PgPool;
@@ -10,6 +12,7 @@ pub struct PgListeners {
   user_listener: UserListener,
   workspace_member_listener: WorkspaceMemberListener,
   collab_member_listener: CollabMemberListener,
+  collab_inheritance_listener: CollabInheritanceListener,
 }
 
 impl PgListeners {
@@ -22,10 +25,14 @@ impl PgListeners {
     let collab_member_listener =
       CollabMemberListener::new(pg_pool, "af_collab_member_channel").await?;
 
+    let collab_inheritance_listener =
+      CollabInheritanceListener::new(pg_pool, "af_collab_inheritance_channel").await?;
+
     Ok(Self {
       user_listener,
       workspace_member_listener,
       collab_member_listener,
+      collab_inheritance_listener,
     })
   }
 
@@ -39,6 +46,12 @@ impl PgListeners {
     self.collab_member_listener.notify.subscribe()
   }
 
+  pub fn subscribe_collab_inheritance_change(
+    &self,
+  ) -> broadcast::Receiver<CollabInheritanceNotification> {
+    self.collab_inheritance_listener.notify.subscribe()
+  }
+
   pub fn subscribe_user_change(&self, uid: i64) -> tokio::sync::mpsc::Receiver<AFUserNotification> {
     let (tx, rx) = tokio::sync::mpsc::channel(100);
     let mut user_notify = self.user_listener.notify.subscribe();
@@ -56,5 +69,6 @@ impl PgListeners {
 }
 
 pub type CollabMemberListener = PostgresDBListener<CollabMemberNotification>;
+pub type CollabInheritanceListener = PostgresDBListener<CollabInheritanceNotification>;
 pub type UserListener = PostgresDBListener<AFUserNotification>;
 pub type WorkspaceMemberListener = PostgresDBListener<WorkspaceMemberNotification>;
@@ -0,0 +1,79 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+/// How long the health check of the read replica waits for it before considering it unavailable.
+const READ_REPLICA_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The pool used by the read-only queries. The queries go to the read replica when one is
+/// configured and available, and to the primary otherwise. As the replica may lag behind the
+/// primary, only the reads that tolerate slightly stale data should use this pool. The reads that
+/// usually follow a write of the same client, like the member list just after a member is added,
+/// must use the primary.
+#[derive(Clone)]
+pub struct PgReadPool {
+  primary: PgPool,
+  replica: Option<PgPool>,
+  is_replica_available: Arc<AtomicBool>,
+}
+
+impl PgReadPool {
+  pub fn new(primary: PgPool, replica: Option<PgPool>) -> Self {
+    Self {
+      primary,
+      replica,
+      is_replica_available: Arc::new(AtomicBool::new(false)),
+    }
+  }
+
+  pub fn get(&self) -> &PgPool {
+    match &self.replica {
+      Some(replica) if self.is_replica_available.load(Ordering::Relaxed) => replica,
+      _ => &self.primary,
+    }
+  }
+
+  /// Checks whether the read replica answers, and routes the reads to it only if it does.
+  pub async fn check_replica(&self) {
+    let replica = match &self.replica {
+      Some(replica) => replica,
+      None => return,
+    };
+    let is_available = matches!(
+      tokio::time::timeout(
+        READ_REPLICA_CHECK_TIMEOUT,
+        sqlx::query("SELECT 1").execute(replica)
+      )
+      .await,
+      Ok(Ok(_))
+    );
+    let was_available = self
+      .is_replica_available
+      .swap(is_available, Ordering::Relaxed);
+    if was_available != is_available {
+      if is_available {
+        info!("Read replica is available, routing the read-only queries to it");
+      } else {
+        warn!("Read replica is unavailable, falling back to the primary");
+      }
+    }
+  }
+}
+
+/// Periodically checks the read replica, see [PgReadPool::check_replica]. Nothing is spawned when
+/// no read replica is configured.
+pub fn spawn_check_read_replica(read_pool: PgReadPool, period: Duration) {
+  if read_pool.replica.is_none() || period.is_zero() {
+    return;
+  }
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(period);
+    loop {
+      interval.tick().await;
+      read_pool.check_replica().await;
+    }
+  });
+}
@@ -0,0 +1,136 @@
+use access_control::act::Action;
+use access_control::collab::CollabAccessControl;
+use app_error::AppError;
+use collab_entity::CollabType;
+use database::index::{
+  search_collab_content, FullTextSearchParams, HIGHLIGHT_START_SEL, HIGHLIGHT_STOP_SEL,
+};
+use shared_entity::dto::search_dto::{
+  HighlightRange, WorkspaceSearchQuery, WorkspaceSearchResultItem,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const DEFAULT_SEARCH_LIMIT: u32 = 10;
+const MAX_SEARCH_LIMIT: u32 = 100;
+const DEFAULT_PREVIEW_WORDS: u32 = 30;
+const MAX_PREVIEW_WORDS: u32 = 100;
+/// Number of matches read at a time, before the ones the caller can't read are filtered out.
+const SEARCH_PAGE_SIZE: i64 = 100;
+/// Upper bound of the matches read for a single search, so that a caller who can read few of
+/// the matching collabs doesn't scan the whole index.
+const MAX_SEARCH_CANDIDATES: i64 = 1000;
+
+/// Full-text search over the documents and databases of the workspace. Returns the best matches
+/// the user is allowed to read.
+pub async fn search_workspace_content(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  uid: i64,
+  workspace_id: Uuid,
+  query: WorkspaceSearchQuery,
+) -> Result<Vec<WorkspaceSearchResultItem>, AppError> {
+  let text = query.query.trim();
+  if text.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "The search query must not be empty".to_string(),
+    ));
+  }
+  let limit = query
+    .limit
+    .unwrap_or(DEFAULT_SEARCH_LIMIT)
+    .clamp(1, MAX_SEARCH_LIMIT) as usize;
+  let preview_words = query
+    .preview_words
+    .unwrap_or(DEFAULT_PREVIEW_WORDS)
+    .clamp(1, MAX_PREVIEW_WORDS) as i32;
+
+  let workspace_id_str = workspace_id.to_string();
+  let mut results = Vec::with_capacity(limit);
+  let mut offset = 0;
+  while results.len() < limit && offset < MAX_SEARCH_CANDIDATES {
+    let items = search_collab_content(
+      pg_pool,
+      &FullTextSearchParams {
+        workspace_id: &workspace_id,
+        query: text,
+        preview_words,
+        limit: SEARCH_PAGE_SIZE,
+        offset,
+      },
+    )
+    .await?;
+    let is_last_page = (items.len() as i64) < SEARCH_PAGE_SIZE;
+    for item in items {
+      if results.len() == limit {
+        break;
+      }
+      let can_read = collab_access_control
+        .enforce_action(&workspace_id_str, &uid, &item.object_id, Action::Read)
+        .await?;
+      if can_read {
+        let (highlight, highlight_ranges) = split_highlight(&item.highlight);
+        results.push(WorkspaceSearchResultItem {
+          object_id: item.object_id,
+          collab_type: CollabType::from(item.collab_type),
+          score: item.score,
+          highlight,
+          highlight_ranges,
+          indexed_at: item.indexed_at,
+        });
+      }
+    }
+    if is_last_page {
+      break;
+    }
+    offset += SEARCH_PAGE_SIZE;
+  }
+  Ok(results)
+}
+
+/// Split the fragment returned by the index into its plain text and the ranges of the matching
+/// words, which the index wraps in markers rather than markup.
+fn split_highlight(marked: &str) -> (String, Vec<HighlightRange>) {
+  let mut text = String::with_capacity(marked.len());
+  let mut ranges = vec![];
+  let mut len = 0;
+  let mut start = None;
+  for c in marked.chars() {
+    match c {
+      HIGHLIGHT_START_SEL => start = Some(len),
+      HIGHLIGHT_STOP_SEL => {
+        if let Some(start) = start.take() {
+          ranges.push(HighlightRange { start, end: len });
+        }
+      },
+      c => {
+        text.push(c);
+        len += 1;
+      },
+    }
+  }
+  (text, ranges)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn split_highlight_test() {
+    let marked = format!(
+      "<b>é</b> {}macOS{} & {}x{}",
+      HIGHLIGHT_START_SEL, HIGHLIGHT_STOP_SEL, HIGHLIGHT_START_SEL, HIGHLIGHT_STOP_SEL
+    );
+    let (text, ranges) = split_highlight(&marked);
+    assert_eq!(text, "<b>é</b> macOS & x");
+    assert_eq!(
+      ranges,
+      vec![
+        HighlightRange { start: 9, end: 14 },
+        HighlightRange { start: 17, end: 18 },
+      ]
+    );
+    assert_eq!(split_highlight("plain"), ("plain".to_string(), vec![]));
+  }
+}
@@ -1,3 +1,5 @@
+mod full_text;
 mod ops;
 
+pub use self::full_text::*;
 pub use self::ops::*;
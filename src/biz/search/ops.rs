@@ -1,9 +1,8 @@
 use crate::api::metrics::RequestMetrics;
+use access_control::act::Action;
+use access_control::collab::CollabAccessControl;
 use app_error::ErrorCode;
-use appflowy_ai_client::client::AppFlowyAIClient;
-use appflowy_ai_client::dto::{
-  EmbeddingEncodingFormat, EmbeddingInput, EmbeddingOutput, EmbeddingRequest, EmbeddingsModel,
-};
+use appflowy_collaborate::indexer::EmbeddingProvider;
 
 use database::index::{search_documents, SearchDocumentParams};
 use shared_entity::dto::search_dto::{
@@ -14,45 +13,40 @@ use sqlx::PgPool;
 
 use uuid::Uuid;
 
+/// How many more candidates than requested are read, to make up for the results the user is not
+/// allowed to read.
+const SEARCH_CANDIDATES_FACTOR: u32 = 4;
+const MAX_SEARCH_CANDIDATES: u32 = 200;
+
+/// Semantic search over the indexed collabs of the workspace. Only the collabs the user is
+/// allowed to read are returned.
 pub async fn search_document(
   pg_pool: &PgPool,
-  ai_client: &AppFlowyAIClient,
+  collab_access_control: &impl CollabAccessControl,
+  embedder: &dyn EmbeddingProvider,
   uid: i64,
   workspace_id: Uuid,
   request: SearchDocumentRequest,
   metrics: &RequestMetrics,
 ) -> Result<Vec<SearchDocumentResponseItem>, AppResponseError> {
-  let embeddings = ai_client
-    .embeddings(EmbeddingRequest {
-      input: EmbeddingInput::String(request.query.clone()),
-      model: EmbeddingsModel::TextEmbedding3Small.to_string(),
-      chunk_size: 500,
-      encoding_format: EmbeddingEncodingFormat::Float,
-      dimensions: 1536,
-    })
-    .await
-    .map_err(|e| AppResponseError::new(ErrorCode::Internal, e.to_string()))?;
-  let total_tokens = embeddings.total_tokens as u32;
+  let embeddings = embedder.embed(vec![request.query.clone()]).await?;
+  let total_tokens = embeddings.total_tokens;
   metrics.record_search_tokens_used(&workspace_id, total_tokens);
   tracing::info!(
-    "workspace {} OpenAI API search tokens used: {}",
+    "workspace {} embedding search tokens used: {}",
     workspace_id,
     total_tokens
   );
 
-  let embedding = embeddings
-    .data
-    .first()
-    .ok_or_else(|| AppResponseError::new(ErrorCode::Internal, "OpenAI returned no embeddings"))?;
-  let embedding = match &embedding.embedding {
-    EmbeddingOutput::Float(vector) => vector.iter().map(|&v| v as f32).collect(),
-    EmbeddingOutput::Base64(_) => {
-      return Err(AppResponseError::new(
-        ErrorCode::Internal,
-        "OpenAI returned embeddings in unsupported format",
-      ))
-    },
-  };
+  let embedding = embeddings.vectors.into_iter().next().ok_or_else(|| {
+    AppResponseError::new(ErrorCode::Internal, "No embedding returned for the query")
+  })?;
+
+  let limit = request.limit.unwrap_or(10);
+  let candidates = limit
+    .saturating_mul(SEARCH_CANDIDATES_FACTOR)
+    .min(MAX_SEARCH_CANDIDATES)
+    .max(limit);
 
   let mut tx = pg_pool
     .begin()
@@ -63,7 +57,7 @@ pub async fn search_document(
     SearchDocumentParams {
       user_id: uid,
       workspace_id,
-      limit: request.limit.unwrap_or(10) as i32,
+      limit: candidates as i32,
       preview: request.preview_size.unwrap_or(180) as i32,
       embedding,
     },
@@ -71,17 +65,18 @@ pub async fn search_document(
   )
   .await?;
   tx.commit().await?;
-  tracing::trace!(
-    "user {} search request in workspace {} returned {} results for query: `{}`",
-    uid,
-    workspace_id,
-    results.len(),
-    request.query
-  );
-  Ok(
-    results
-      .into_iter()
-      .map(|item| SearchDocumentResponseItem {
+
+  let workspace_id_str = workspace_id.to_string();
+  let mut items = Vec::with_capacity(limit as usize);
+  for item in results {
+    if items.len() == limit as usize {
+      break;
+    }
+    let can_read = collab_access_control
+      .enforce_action(&workspace_id_str, &uid, &item.object_id, Action::Read)
+      .await?;
+    if can_read {
+      items.push(SearchDocumentResponseItem {
         object_id: item.object_id,
         workspace_id: item.workspace_id.to_string(),
         score: item.score,
@@ -89,7 +84,15 @@ pub async fn search_document(
         preview: item.content_preview,
         created_by: item.created_by,
         created_at: item.created_at,
-      })
-      .collect(),
-  )
+      });
+    }
+  }
+  tracing::trace!(
+    "user {} search request in workspace {} returned {} results for query: `{}`",
+    uid,
+    workspace_id,
+    items.len(),
+    request.query
+  );
+  Ok(items)
 }
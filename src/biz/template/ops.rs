@@ -1,8 +1,9 @@
-use std::{ops::DerefMut, path::Path};
+use std::{ops::DerefMut, path::Path, sync::Arc};
 
 use actix_multipart::form::bytes::Bytes as MPBytes;
 use anyhow::Context;
-use app_error::ErrorCode;
+use app_error::{AppError, ErrorCode};
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
 use database::{
   file::{s3_client_impl::AwsS3BucketClientImpl, BucketClient, ResponseBlob},
   template::*,
@@ -15,6 +16,10 @@ use shared_entity::response::AppResponseError;
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::biz::collab::ops::get_latest_collab_folder;
+use crate::biz::workspace::publish_dup::duplicate_published_collab_to_workspace;
+use database::collab::GetCollabOrigin;
+
 pub async fn create_new_template_category(
   pg_pool: &PgPool,
   name: &str,
@@ -256,6 +261,56 @@ pub async fn delete_template(pg_pool: &PgPool, view_id: Uuid) -> Result<(), AppR
   Ok(())
 }
 
+/// Deep copy the template, along with the published views it refers to, into the workspace. The
+/// copy is created under `dest_view_id`, or at the top level of the workspace. Returns the id of
+/// the view the template was copied as.
+#[allow(clippy::too_many_arguments)]
+pub async fn instantiate_template(
+  pg_pool: &PgPool,
+  bucket_client: AwsS3BucketClientImpl,
+  collab_storage: Arc<CollabAccessControlStorage>,
+  uid: i64,
+  workspace_id: Uuid,
+  view_id: Uuid,
+  dest_view_id: Option<String>,
+) -> Result<String, AppResponseError> {
+  // Only views of the template center can be instantiated
+  select_template_view_by_id(pg_pool, view_id).await?;
+  let workspace_id = workspace_id.to_string();
+  let dest_view_id = match dest_view_id {
+    Some(dest_view_id) => {
+      let folder = get_latest_collab_folder(
+        collab_storage.clone(),
+        GetCollabOrigin::User { uid },
+        &workspace_id,
+      )
+      .await?;
+      if folder.get_view(&dest_view_id).is_none() {
+        return Err(
+          AppError::RecordNotFound(format!(
+            "view {} not found in workspace {}",
+            dest_view_id, workspace_id
+          ))
+          .into(),
+        );
+      }
+      dest_view_id
+    },
+    None => workspace_id.clone(),
+  };
+  let new_view_id = duplicate_published_collab_to_workspace(
+    pg_pool,
+    bucket_client,
+    collab_storage,
+    uid,
+    view_id.to_string(),
+    workspace_id,
+    dest_view_id,
+  )
+  .await?;
+  Ok(new_view_id)
+}
+
 const DEFAULT_HOMEPAGE_CATEGORY_COUNT: i64 = 10;
 
 pub async fn get_template_homepage(
@@ -1,4 +1,6 @@
 pub mod user_delete;
+pub mod user_deletion;
+pub mod user_export;
 pub mod user_info;
 pub mod user_init;
 pub mod user_verify;
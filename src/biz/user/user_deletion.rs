@@ -0,0 +1,309 @@
+use access_control::collab::CollabAccessControl;
+use access_control::workspace::WorkspaceAccessControl;
+use anyhow::anyhow;
+use app_error::AppError;
+use chrono::Utc;
+use database::file::BucketClient;
+use database::pg_row::AFUserDeletionRow;
+use database::user::{is_user_exist, select_uid_from_email};
+use database::user_deletion::{
+  delete_af_user, delete_collab_memberships_of_user, delete_group_memberships_of_user,
+  delete_user_data_export_tasks_of_user, delete_user_deletion,
+  delete_workspace_export_tasks_of_user, delete_workspace_memberships_of_user,
+  select_due_user_deletions, select_user_deletion, select_workspace_successor,
+  update_user_deletion_failed, upsert_user_deletion,
+};
+use database::workspace::{select_user_owned_workspaces_id, select_workspace_member};
+use database::workspace_ownership_transfer::update_workspace_owner_and_demote;
+use database_entity::dto::AFRole;
+use gotrue::params::AdminDeleteUserParams;
+use serde_json::json;
+use shared_entity::dto::auth_dto::{
+  AccountDeletion, RequestAccountDeletionParams, WorkspaceOwnershipTransfer,
+};
+use shared_entity::dto::workspace_dto::AuditAction;
+use shared_entity::response::AppResponseError;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::biz::workspace::audit_log::record_audit_log;
+use crate::biz::workspace::ops::delete_workspace_for_user;
+use crate::state::AppState;
+
+/// The accounts deleted at each run of [spawn_purge_deleted_accounts].
+const PURGE_BATCH_SIZE: i64 = 20;
+
+/// Mark the account of the user for deletion. The account can be restored with
+/// [cancel_account_deletion] until the grace period ends, then it is deleted for good by
+/// [spawn_purge_deleted_accounts].
+pub async fn request_account_deletion(
+  state: &AppState,
+  uid: i64,
+  user_uuid: &Uuid,
+  params: RequestAccountDeletionParams,
+) -> Result<AccountDeletion, AppError> {
+  let owned_workspace_ids = select_user_owned_workspaces_id(&state.pg_pool, user_uuid).await?;
+  for transfer in &params.workspace_transfers {
+    if !owned_workspace_ids.contains(&transfer.workspace_id) {
+      return Err(AppError::InvalidRequest(format!(
+        "Only the workspaces the user owns can be transferred, not {}",
+        transfer.workspace_id
+      )));
+    }
+    let new_owner_uid = new_owner_uid(state, transfer).await?;
+    if new_owner_uid == Some(uid) || new_owner_uid.is_none() {
+      return Err(AppError::InvalidRequest(format!(
+        "{} is not another member of the workspace {}",
+        transfer.new_owner_email, transfer.workspace_id
+      )));
+    }
+  }
+
+  let workspace_transfers = serde_json::to_value(&params.workspace_transfers)?;
+  let purge_after = Utc::now() + state.config.account_deletion.grace_period();
+  let row = upsert_user_deletion(
+    &state.pg_pool,
+    uid,
+    user_uuid,
+    &workspace_transfers,
+    purge_after,
+  )
+  .await?;
+  record_audit_log(
+    &state.pg_pool,
+    None,
+    Some(uid),
+    AuditAction::UserDeletionRequested,
+    None,
+    json!({ "purge_after": row.purge_after, "workspace_transfers": row.workspace_transfers }),
+  )
+  .await;
+  account_deletion_from_row(row)
+}
+
+pub async fn get_account_deletion(state: &AppState, uid: i64) -> Result<AccountDeletion, AppError> {
+  let row = select_user_deletion(&state.pg_pool, uid)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound("The account is not marked for deletion".to_string())
+    })?;
+  account_deletion_from_row(row)
+}
+
+/// Restore the account marked for deletion, as long as its grace period has not ended.
+pub async fn cancel_account_deletion(state: &AppState, uid: i64) -> Result<(), AppError> {
+  if delete_user_deletion(&state.pg_pool, uid).await? {
+    record_audit_log(
+      &state.pg_pool,
+      None,
+      Some(uid),
+      AuditAction::UserDeletionCancelled,
+      None,
+      json!({}),
+    )
+    .await;
+  }
+  Ok(())
+}
+
+/// Periodically delete for good the accounts whose grace period has ended, see
+/// [purge_deleted_account]. A zero `period` disables the task.
+pub fn spawn_purge_deleted_accounts(state: AppState, period: std::time::Duration) {
+  if period.is_zero() {
+    return;
+  }
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(period);
+    loop {
+      interval.tick().await;
+      let rows = match select_due_user_deletions(&state.pg_pool, Utc::now(), PURGE_BATCH_SIZE).await
+      {
+        Ok(rows) => rows,
+        Err(err) => {
+          warn!("Failed to select the accounts to delete: {:?}", err);
+          continue;
+        },
+      };
+      for row in rows {
+        let uid = row.uid;
+        if let Err(err) = purge_deleted_account(&state, row).await {
+          warn!("Failed to delete account {}: {}", uid, err);
+          if let Err(err) = update_user_deletion_failed(&state.pg_pool, uid, &err.to_string()).await
+          {
+            warn!("Failed to record the failed deletion of {}: {:?}", uid, err);
+          }
+        }
+      }
+    }
+  });
+}
+
+/// Delete the account and all of its data:
+/// - the workspaces the user owns are handed over to the chosen member, or to the member that
+///   joined first when the chosen one left, and deleted with their files only when the user is
+///   their last member;
+/// - the user is removed from the other workspaces, collabs and groups, along with its policies;
+/// - the archives of the exports the user started are deleted;
+/// - the GoTrue user is deleted, which deletes the user.
+///
+/// Every step can run again, so that a deletion that failed halfway is resumed by the next run.
+async fn purge_deleted_account(
+  state: &AppState,
+  row: AFUserDeletionRow,
+) -> Result<(), AppResponseError> {
+  let uid = row.uid;
+  let workspace_transfers: Vec<WorkspaceOwnershipTransfer> =
+    serde_json::from_value(row.workspace_transfers).unwrap_or_default();
+
+  if is_user_exist(&state.pg_pool, &row.uuid).await? {
+    for workspace_id in select_user_owned_workspaces_id(&state.pg_pool, &row.uuid).await? {
+      let transfer = workspace_transfers
+        .iter()
+        .find(|transfer| transfer.workspace_id == workspace_id);
+      let nominated_owner_uid = match transfer {
+        Some(transfer) => new_owner_uid(state, transfer)
+          .await?
+          .filter(|new_owner_uid| *new_owner_uid != uid),
+        None => None,
+      };
+      let new_owner_uid = match nominated_owner_uid {
+        Some(new_owner_uid) => Some(new_owner_uid),
+        None => select_workspace_successor(&state.pg_pool, &workspace_id, uid).await?,
+      };
+      match new_owner_uid {
+        Some(new_owner_uid) => {
+          // The new owner left in the meantime, the next run picks another one
+          if !update_workspace_owner_and_demote(&state.pg_pool, &workspace_id, uid, new_owner_uid)
+            .await?
+          {
+            return Err(
+              AppError::Internal(anyhow!(
+                "{} left the workspace {} before taking it over",
+                new_owner_uid,
+                workspace_id
+              ))
+              .into(),
+            );
+          }
+          state
+            .workspace_access_control
+            .replace_role(&new_owner_uid, &workspace_id, AFRole::Owner)
+            .await?;
+          record_audit_log(
+            &state.pg_pool,
+            Some(&workspace_id),
+            Some(uid),
+            AuditAction::WorkspaceOwnershipTransferred,
+            None,
+            json!({ "previous_owner_uid": uid, "new_owner_uid": new_owner_uid }),
+          )
+          .await;
+        },
+        None => {
+          info!(
+            "delete workspace {} of deleted account {}, it has no other member",
+            workspace_id, uid
+          );
+          delete_workspace_for_user(
+            state.pg_pool.clone(),
+            workspace_id,
+            state.bucket_storage.clone(),
+          )
+          .await?;
+        },
+      }
+    }
+  }
+
+  for workspace_id in delete_workspace_memberships_of_user(&state.pg_pool, uid).await? {
+    state
+      .workspace_access_control
+      .remove_user_from_workspace(&uid, &workspace_id)
+      .await?;
+    record_audit_log(
+      &state.pg_pool,
+      Some(&workspace_id),
+      Some(uid),
+      AuditAction::MemberRemoved,
+      None,
+      json!({ "uid": uid, "reason": "account_deleted" }),
+    )
+    .await;
+  }
+  for oid in delete_collab_memberships_of_user(&state.pg_pool, uid).await? {
+    state
+      .collab_access_control
+      .remove_access_level(&uid, &oid)
+      .await?;
+  }
+  for group_id in delete_group_memberships_of_user(&state.pg_pool, uid).await? {
+    state
+      .collab_access_control
+      .remove_group_member(&uid, &group_id.to_string())
+      .await?;
+  }
+
+  let mut object_keys = delete_workspace_export_tasks_of_user(&state.pg_pool, uid).await?;
+  object_keys.extend(delete_user_data_export_tasks_of_user(&state.pg_pool, uid).await?);
+  if !object_keys.is_empty() {
+    if let Err(err) = state.bucket_client.delete_blobs(object_keys).await {
+      warn!("Failed to delete the exports of account {}: {}", uid, err);
+    }
+  }
+
+  let admin_token = state.gotrue_admin.token().await?;
+  let deleted = state
+    .gotrue_client
+    .admin_delete_user(
+      &admin_token,
+      &row.uuid.to_string(),
+      &AdminDeleteUserParams {
+        should_soft_delete: false,
+      },
+    )
+    .await;
+  // The GoTrue user is already gone if a previous run deleted it
+  if let Err(err) = deleted {
+    if is_user_exist(&state.pg_pool, &row.uuid).await? {
+      return Err(AppError::from(err).into());
+    }
+  }
+  delete_af_user(&state.pg_pool, uid).await?;
+  delete_user_deletion(&state.pg_pool, uid).await?;
+  record_audit_log(
+    &state.pg_pool,
+    None,
+    Some(uid),
+    AuditAction::UserDeleted,
+    None,
+    json!({ "requested_at": row.requested_at }),
+  )
+  .await;
+  info!("deleted account {}", uid);
+  Ok(())
+}
+
+/// Returns the uid of the new owner of the transfer, if it is a member of the workspace.
+async fn new_owner_uid(
+  state: &AppState,
+  transfer: &WorkspaceOwnershipTransfer,
+) -> Result<Option<i64>, AppError> {
+  let uid = match select_uid_from_email(&state.pg_pool, &transfer.new_owner_email).await {
+    Ok(uid) => uid,
+    Err(err) if err.is_record_not_found() => return Ok(None),
+    Err(err) => return Err(err),
+  };
+  match select_workspace_member(&state.pg_pool, &uid, &transfer.workspace_id).await {
+    Ok(_) => Ok(Some(uid)),
+    Err(err) if err.is_record_not_found() => Ok(None),
+    Err(err) => Err(err),
+  }
+}
+
+fn account_deletion_from_row(row: AFUserDeletionRow) -> Result<AccountDeletion, AppError> {
+  Ok(AccountDeletion {
+    workspace_transfers: serde_json::from_value(row.workspace_transfers)?,
+    requested_at: row.requested_at,
+    purge_after: row.purge_after,
+  })
+}
@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use chrono::{DateTime, Duration, Utc};
+use collab_entity::{CollabType, EncodedCollab};
+use database::collab::CollabStorage;
+use database::file::s3_client_impl::AwsS3BucketClientImpl;
+use database::file::{BucketClient, ResponseBlob};
+use database::pg_row::AFUserDataExportTaskRow;
+use database::user_data_export::{
+  delete_user_data_export_tasks_before, insert_user_data_export_task, select_user_comments,
+  select_user_data_export_task, select_user_data_export_task_count, select_user_owned_collabs,
+  select_user_workspace_memberships, update_user_data_export_task_completed,
+  update_user_data_export_task_failed,
+};
+use database_entity::dto::{AFRole, QueryCollab, QueryCollabResult};
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use serde_json::json;
+use shared_entity::dto::workspace_dto::{
+  AuditAction, UserDataExportDownloadQuery, UserDataExportTask, WorkspaceExportStatus,
+};
+use sqlx::PgPool;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::biz::user::user_info::get_profile;
+use crate::biz::workspace::audit_log::record_audit_log;
+use crate::biz::workspace::export::{
+  acquire_export_permit, document_markdown, upload_archive, ArchiveWriter, WorkspaceArchive,
+};
+use crate::biz::workspace::publish_dup::collab_from_doc_state;
+
+/// Archives of user data exports can be downloaded for this many days.
+const EXPORT_RETENTION_DAYS: i64 = 7;
+/// The download links of an archive stop working after this many hours.
+const DOWNLOAD_LINK_TTL_HOURS: i64 = 24;
+/// The owned collabs are fetched from the storage this many at a time.
+const COLLAB_BATCH_SIZE: usize = 100;
+/// How many exports a user can start in a day.
+const MAX_EXPORTS_PER_DAY: i64 = 3;
+
+/// Start exporting everything tied to the user in the background: the profile, the workspace
+/// memberships, the collabs the user owns and the comments the user wrote. The archive can be
+/// downloaded from the signed link of the task once the export has completed.
+pub async fn start_user_data_export(
+  pg_pool: &PgPool,
+  collab_storage: Arc<CollabAccessControlStorage>,
+  bucket_client: AwsS3BucketClientImpl,
+  secret: &str,
+  uid: i64,
+  user_uuid: Uuid,
+) -> Result<UserDataExportTask, AppError> {
+  let expired_object_keys = delete_user_data_export_tasks_before(
+    pg_pool,
+    uid,
+    Utc::now() - Duration::days(EXPORT_RETENTION_DAYS),
+  )
+  .await?;
+  if !expired_object_keys.is_empty() {
+    if let Err(err) = bucket_client.delete_blobs(expired_object_keys).await {
+      warn!("Failed to delete expired user data exports: {}", err);
+    }
+  }
+
+  let (exports, running_exports) =
+    select_user_data_export_task_count(pg_pool, uid, Utc::now() - Duration::days(1)).await?;
+  if running_exports > 0 {
+    return Err(AppError::TooManyRequests(
+      "An export of your data is already running".to_string(),
+    ));
+  }
+  if exports >= MAX_EXPORTS_PER_DAY {
+    return Err(AppError::TooManyRequests(format!(
+      "Your data can be exported {} times a day, try again later",
+      MAX_EXPORTS_PER_DAY
+    )));
+  }
+
+  let row = insert_user_data_export_task(pg_pool, uid).await?;
+  let task_id = row.task_id;
+  record_audit_log(
+    pg_pool,
+    None,
+    Some(uid),
+    AuditAction::UserDataExported,
+    Some(&task_id.to_string()),
+    json!({}),
+  )
+  .await;
+  let pg_pool = pg_pool.clone();
+  tokio::spawn(async move {
+    let result = async {
+      let _permit = acquire_export_permit().await?;
+      let (file, size) = write_user_data_archive(&pg_pool, collab_storage, uid, &user_uuid).await?;
+      let object_key = export_object_key(uid, &task_id);
+      upload_archive(&bucket_client, &object_key, file, size).await?;
+      Ok::<_, AppError>((object_key, size as i64))
+    }
+    .await;
+    let updated = match result {
+      Ok((object_key, file_size)) => {
+        update_user_data_export_task_completed(&pg_pool, &task_id, &object_key, file_size).await
+      },
+      Err(err) => {
+        warn!("Failed to export the data of user {}: {}", uid, err);
+        update_user_data_export_task_failed(&pg_pool, &task_id, &err.to_string()).await
+      },
+    };
+    if let Err(err) = updated {
+      error!(
+        "Failed to update user data export task {}: {}",
+        task_id, err
+      );
+    }
+  });
+  export_task_from_row(secret, row)
+}
+
+/// Returns the export task of the user, with a new download link if the export has completed.
+pub async fn get_user_data_export_task(
+  pg_pool: &PgPool,
+  secret: &str,
+  uid: i64,
+  task_id: &Uuid,
+) -> Result<UserDataExportTask, AppError> {
+  let row = select_export_task(pg_pool, task_id)
+    .await?
+    .filter(|row| row.uid == uid)
+    .ok_or_else(|| AppError::RecordNotFound(format!("user data export {} not found", task_id)))?;
+  export_task_from_row(secret, row)
+}
+
+/// Returns the archive of an export that has completed, if the download link is valid.
+pub async fn get_user_data_export_archive(
+  pg_pool: &PgPool,
+  bucket_client: &AwsS3BucketClientImpl,
+  secret: &str,
+  task_id: &Uuid,
+  query: &UserDataExportDownloadQuery,
+) -> Result<WorkspaceArchive, AppError> {
+  let invalid_link = || AppError::UserUnAuthorized("Invalid or expired download link".to_string());
+  if query.expires <= Utc::now().timestamp() {
+    return Err(invalid_link());
+  }
+  let row = select_export_task(pg_pool, task_id)
+    .await?
+    .ok_or_else(invalid_link)?;
+  let expected = sign_download_link(secret, task_id, row.uid, query.expires)?;
+  if !constant_time_eq(expected.as_bytes(), query.signature.as_bytes()) {
+    return Err(invalid_link());
+  }
+  let object_key = match (WorkspaceExportStatus::from(row.status), row.object_key) {
+    (WorkspaceExportStatus::Completed, Some(object_key)) => object_key,
+    _ => {
+      return Err(AppError::InvalidRequest(format!(
+        "user data export {} has not completed",
+        task_id
+      )))
+    },
+  };
+  let data = bucket_client.get_blob(&object_key).await?.to_blob();
+  Ok(WorkspaceArchive::from_data(
+    format!("user-data-export-{}.zip", task_id),
+    data,
+  ))
+}
+
+async fn select_export_task(
+  pg_pool: &PgPool,
+  task_id: &Uuid,
+) -> Result<Option<AFUserDataExportTaskRow>, AppError> {
+  Ok(
+    select_user_data_export_task(pg_pool, task_id)
+      .await?
+      .filter(|row| row.created_at > Utc::now() - Duration::days(EXPORT_RETENTION_DAYS)),
+  )
+}
+
+fn export_object_key(uid: i64, task_id: &Uuid) -> String {
+  format!("user-export/{}/{}.zip", uid, task_id)
+}
+
+fn export_task_from_row(
+  secret: &str,
+  row: AFUserDataExportTaskRow,
+) -> Result<UserDataExportTask, AppError> {
+  let status = WorkspaceExportStatus::from(row.status);
+  let (download_url, download_url_expires_at) = if status == WorkspaceExportStatus::Completed {
+    // The link never outlives the archive
+    let expires_at = (Utc::now() + Duration::hours(DOWNLOAD_LINK_TTL_HOURS))
+      .min(row.created_at + Duration::days(EXPORT_RETENTION_DAYS));
+    let expires = expires_at.timestamp();
+    let signature = sign_download_link(secret, &row.task_id, row.uid, expires)?;
+    let url = format!(
+      "/api/user/export/{}/download?expires={}&signature={}",
+      row.task_id, expires, signature
+    );
+    (Some(url), DateTime::from_timestamp(expires, 0))
+  } else {
+    (None, None)
+  };
+  Ok(UserDataExportTask {
+    task_id: row.task_id,
+    status,
+    download_url,
+    download_url_expires_at,
+    file_size: row.file_size,
+    error: row.error,
+    created_at: row.created_at,
+    completed_at: row.completed_at,
+  })
+}
+
+fn sign_download_link(
+  secret: &str,
+  task_id: &Uuid,
+  uid: i64,
+  expires: i64,
+) -> Result<String, AppError> {
+  let message = format!("user-data-export.{}.{}.{}", task_id, uid, expires);
+  let sign = || -> Result<Vec<u8>, openssl::error::ErrorStack> {
+    let key = PKey::hmac(secret.as_bytes())?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(message.as_bytes())?;
+    signer.sign_to_vec()
+  };
+  let signature = sign().map_err(|err| AppError::Internal(err.into()))?;
+  Ok(signature.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+  a.len() == b.len() && openssl::memcmp::eq(a, b)
+}
+
+/// Write the archive of the data of the user to a temporary file. The profile, the memberships and
+/// the comments are exported as JSON. Each owned collab is exported as JSON under
+/// `collabs/<workspace id>/`, and documents are also exported as Markdown.
+async fn write_user_data_archive(
+  pg_pool: &PgPool,
+  collab_storage: Arc<CollabAccessControlStorage>,
+  uid: i64,
+  user_uuid: &Uuid,
+) -> Result<(tokio::fs::File, u64), AppError> {
+  let profile = get_profile(pg_pool, user_uuid).await?;
+  let memberships: Vec<_> = select_user_workspace_memberships(pg_pool, uid)
+    .await?
+    .into_iter()
+    .map(|row| {
+      json!({
+        "workspace_id": row.workspace_id,
+        "workspace_name": row.workspace_name,
+        "role": AFRole::from(row.role_id),
+        "is_owner": row.is_owner,
+        "joined_at": row.joined_at,
+      })
+    })
+    .collect();
+  let comments = select_user_comments(pg_pool, uid).await?;
+
+  let files = vec![
+    (
+      "profile.json".to_string(),
+      serde_json::to_vec_pretty(&profile)?,
+    ),
+    (
+      "workspaces.json".to_string(),
+      serde_json::to_vec_pretty(&memberships)?,
+    ),
+    (
+      "comments.json".to_string(),
+      serde_json::to_vec_pretty(&comments)?,
+    ),
+  ];
+  let mut archive = ArchiveWriter::new()?;
+  archive.add_files(move || files).await?;
+
+  let owned_collabs = select_user_owned_collabs(pg_pool, uid).await?;
+  for chunk in owned_collabs.chunks(COLLAB_BATCH_SIZE) {
+    let paths: HashMap<String, (String, CollabType)> = chunk
+      .iter()
+      .map(|row| {
+        (
+          row.oid.clone(),
+          (
+            format!("collabs/{}/{}", row.workspace_id, row.oid),
+            CollabType::from(row.partition_key),
+          ),
+        )
+      })
+      .collect();
+    let queries = chunk
+      .iter()
+      .map(|row| QueryCollab {
+        object_id: row.oid.clone(),
+        collab_type: CollabType::from(row.partition_key),
+      })
+      .collect();
+    let mut encoded_collabs = Vec::with_capacity(chunk.len());
+    for (object_id, result) in collab_storage.batch_get_collab(&uid, queries).await {
+      let encoded = match result {
+        QueryCollabResult::Success { encode_collab_v1 } => encode_collab_v1,
+        QueryCollabResult::Failed { error } => {
+          warn!("Skip exporting collab {}: {}", object_id, error);
+          continue;
+        },
+      };
+      if let Some((path, collab_type)) = paths.get(&object_id) {
+        encoded_collabs.push((object_id, encoded, path.clone(), collab_type.clone()));
+      }
+    }
+    archive
+      .add_files(move || {
+        let mut files = vec![];
+        for (object_id, encoded, path, collab_type) in encoded_collabs {
+          match collab_files(encoded, &object_id, &path, &collab_type) {
+            Ok(collab_files) => files.extend(collab_files),
+            Err(err) => warn!("Skip exporting collab {}: {}", object_id, err),
+          }
+        }
+        files
+      })
+      .await?;
+  }
+  archive.finish().await
+}
+
+fn collab_files(
+  encoded: Vec<u8>,
+  object_id: &str,
+  path: &str,
+  collab_type: &CollabType,
+) -> Result<Vec<(String, Vec<u8>)>, AppError> {
+  let doc_state = EncodedCollab::decode_from_bytes(&encoded)
+    .map_err(|err| AppError::Internal(anyhow!(err)))?
+    .doc_state
+    .to_vec();
+  let collab = collab_from_doc_state(doc_state.clone(), object_id)?;
+  let content = json!({
+    "object_id": object_id,
+    "collab_type": collab_type,
+    "data": collab.to_json_value(),
+  });
+  let mut files = vec![(
+    format!("{}.json", path),
+    serde_json::to_vec_pretty(&content)?,
+  )];
+  if *collab_type == CollabType::Document {
+    let markdown = document_markdown(doc_state, object_id)?;
+    files.push((format!("{}.md", path), markdown.into_bytes()));
+  }
+  Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn user_data_export_download_link_test() {
+    let task_id = Uuid::new_v4();
+    let signature = sign_download_link("secret", &task_id, 1, 100).unwrap();
+    assert_eq!(
+      signature,
+      sign_download_link("secret", &task_id, 1, 100).unwrap()
+    );
+    assert_ne!(
+      signature,
+      sign_download_link("secret", &task_id, 1, 101).unwrap()
+    );
+    assert_ne!(
+      signature,
+      sign_download_link("secret", &task_id, 2, 100).unwrap()
+    );
+    assert_ne!(
+      signature,
+      sign_download_link("other", &task_id, 1, 100).unwrap()
+    );
+  }
+}
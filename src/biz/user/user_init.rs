@@ -111,7 +111,7 @@ where
   Ok(())
 }
 
-async fn create_user_awareness(
+pub(crate) async fn create_user_awareness(
   uid: &i64,
   user_uuid: &Uuid,
   workspace_id: &str,
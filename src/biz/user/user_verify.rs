@@ -9,9 +9,16 @@ use app_error::AppError;
 use database::user::{create_user, is_user_exist};
 use database::workspace::select_workspace;
 use database_entity::dto::AFRole;
+use gotrue_entity::gotrue_jwt::GoTrueJWTClaims;
+use secrecy::ExposeSecret;
+use serde_json::json;
+use shared_entity::dto::workspace_dto::AuditAction;
 use workspace_template::document::getting_started::GettingStartedTemplate;
 
 use crate::biz::user::user_init::initialize_workspace_for_user;
+use crate::biz::workspace::audit_log::record_audit_log;
+use crate::biz::workspace::invitation::accept_pending_invitations_of_new_user;
+use crate::biz::workspace::sso::provision_sso_user;
 use crate::state::AppState;
 
 /// Verify the token from the gotrue server and create the user if it is a new user
@@ -19,22 +26,77 @@ use crate::state::AppState;
 ///
 #[instrument(skip_all, err)]
 pub async fn verify_token(access_token: &str, state: &AppState) -> Result<bool, AppError> {
+  let (_, _, is_new) = verify_token_of_user(access_token, state).await?;
+  Ok(is_new)
+}
+
+/// Verify the token the user just signed in with, like [verify_token], and record the sign in in
+/// the audit log.
+#[instrument(skip_all, err)]
+pub async fn verify_sign_in(access_token: &str, state: &AppState) -> Result<bool, AppError> {
+  let (user_uuid, claims, is_new) = verify_token_of_user(access_token, state).await?;
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let methods = claims
+    .amr
+    .iter()
+    .flatten()
+    .map(|amr| amr.method.as_str())
+    .collect::<Vec<_>>();
+  record_audit_log(
+    &state.pg_pool,
+    None,
+    Some(uid),
+    AuditAction::UserLogin,
+    None,
+    json!({ "is_new": is_new, "methods": methods }),
+  )
+  .await;
+  Ok(is_new)
+}
+
+async fn verify_token_of_user(
+  access_token: &str,
+  state: &AppState,
+) -> Result<(uuid::Uuid, GoTrueJWTClaims, bool), AppError> {
   let user = state.gotrue_client.user_info(access_token).await?;
   let user_uuid = uuid::Uuid::parse_str(&user.id)?;
   let name = name_from_user_metadata(&user.user_metadata);
+  let is_new = create_user_if_not_exists(state, &user_uuid, &user.email, &name).await?;
+  if is_new {
+    // The user may have been invited to workspaces before having an account
+    let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+    accept_pending_invitations_of_new_user(state, uid, &user_uuid, &user.email).await?;
+  }
 
+  // The users that signed in through the identity provider of a workspace join that workspace
+  let claims = GoTrueJWTClaims::decode(
+    access_token,
+    state.config.gotrue.jwt_secret.expose_secret().as_bytes(),
+  )
+  .map_err(|err| AppError::UserUnAuthorized(format!("fail to decode token: {}", err)))?;
+  provision_sso_user(state, &user_uuid, &claims).await?;
+  Ok((user_uuid, claims, is_new))
+}
+
+/// Create the user of the gotrue user, with its own workspace, if it does not exist yet.
+/// Return true if the user is a new user
+pub async fn create_user_if_not_exists(
+  state: &AppState,
+  user_uuid: &uuid::Uuid,
+  email: &str,
+  name: &str,
+) -> Result<bool, AppError> {
   let mut txn = state
     .pg_pool
     .begin()
     .await
     .context("acquire transaction to verify token")?;
 
-  let is_new = !is_user_exist(txn.deref_mut(), &user_uuid).await?;
+  let is_new = !is_user_exist(txn.deref_mut(), user_uuid).await?;
   if is_new {
     let new_uid = state.id_gen.write().await.next_id();
     event!(tracing::Level::INFO, "create new user:{}", new_uid);
-    let workspace_id =
-      create_user(txn.deref_mut(), new_uid, &user_uuid, &user.email, &name).await?;
+    let workspace_id = create_user(txn.deref_mut(), new_uid, user_uuid, email, name).await?;
     let workspace_row = select_workspace(txn.deref_mut(), &workspace_id).await?;
 
     // It's essential to cache the user's role because subsequent actions will rely on this cached information.
@@ -46,7 +108,7 @@ pub async fn verify_token(access_token: &str, state: &AppState) -> Result<bool,
     // Create a workspace with the GetStarted template
     initialize_workspace_for_user(
       new_uid,
-      &user_uuid,
+      user_uuid,
       &workspace_row,
       &mut txn,
       vec![GettingStartedTemplate],
@@ -54,7 +116,7 @@ pub async fn verify_token(access_token: &str, state: &AppState) -> Result<bool,
     )
     .await?;
   } else {
-    trace!("user already exists:{},{}", user.id, user.email);
+    trace!("user already exists:{},{}", user_uuid, email);
   }
   txn
     .commit()
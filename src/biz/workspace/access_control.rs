@@ -17,11 +17,13 @@ use access_control::act::Action;
 use access_control::workspace::WorkspaceAccessControl;
 use app_error::AppError;
 use database::user::select_uid_from_uuid;
-use database_entity::dto::AFRole;
+use database_entity::dto::{AFRole, WorkspaceCapability};
 
 use crate::api::workspace::{
-  WORKSPACE_INVITE_PATTERN, WORKSPACE_MEMBER_PATTERN, WORKSPACE_PATTERN,
-  WORKSPACE_PUBLISH_NAMESPACE_PATTERN, WORKSPACE_PUBLISH_PATTERN,
+  WORKSPACE_AI_USAGE_PATTERN, WORKSPACE_FOLDER_INTEGRITY_PATTERN, WORKSPACE_INVITE_PATTERN,
+  WORKSPACE_MEMBER_PATTERN, WORKSPACE_OBJECT_PATTERN, WORKSPACE_OWNERSHIP_TRANSFER_PATTERN,
+  WORKSPACE_PATTERN, WORKSPACE_PUBLISH_ANALYTICS_PATTERN, WORKSPACE_PUBLISH_NAMESPACE_PATTERN,
+  WORKSPACE_PUBLISH_PATTERN, WORKSPACE_TRASH_PATTERN,
 };
 use crate::middleware::access_control_mw::{AccessResource, MiddlewareAccessControl};
 use crate::state::UserCache;
@@ -32,6 +34,7 @@ pub struct WorkspaceMiddlewareAccessControl<AC: WorkspaceAccessControl> {
   pub access_control: Arc<AC>,
   skip_resources: Vec<(Method, ResourceDef)>,
   require_role_rules: Vec<(ResourceDef, HashMap<Method, AFRole>)>,
+  require_capability_rules: Vec<(ResourceDef, HashMap<Method, WorkspaceCapability>)>,
 }
 
 impl<AC> WorkspaceMiddlewareAccessControl<AC>
@@ -48,10 +51,6 @@ where
       ],
       // Require role for given resources
       require_role_rules: vec![
-        (
-          ResourceDef::new(WORKSPACE_PUBLISH_PATTERN),
-          [(Method::DELETE, AFRole::Member)].into(),
-        ),
         // Only the Owner can manager the workspace members
         (
           ResourceDef::new(WORKSPACE_MEMBER_PATTERN),
@@ -63,11 +62,6 @@ where
           ]
           .into(),
         ),
-        (
-          // Only the Owner can invite a user to the workspace
-          ResourceDef::new(WORKSPACE_INVITE_PATTERN),
-          [(Method::POST, AFRole::Owner)].into(),
-        ),
         (
           ResourceDef::new(WORKSPACE_PUBLISH_NAMESPACE_PATTERN),
           [
@@ -76,6 +70,57 @@ where
           ]
           .into(),
         ),
+        (
+          ResourceDef::new(WORKSPACE_OWNERSHIP_TRANSFER_PATTERN),
+          [
+            (Method::POST, AFRole::Owner), // Only the Owner can hand the workspace over
+            // The member asked to become the owner can see and decline the transfer
+            (Method::GET, AFRole::Member),
+            (Method::DELETE, AFRole::Member),
+          ]
+          .into(),
+        ),
+        (
+          // Only the Owner can check and repair the structure of the folder
+          ResourceDef::new(WORKSPACE_FOLDER_INTEGRITY_PATTERN),
+          [(Method::GET, AFRole::Owner), (Method::POST, AFRole::Owner)].into(),
+        ),
+        (
+          // Deleting an object removes it for every member, along with its members and publication
+          ResourceDef::new(WORKSPACE_OBJECT_PATTERN),
+          [(Method::DELETE, AFRole::Owner)].into(),
+        ),
+      ],
+      // Require capability for given resources. The roles grant their default capabilities, and
+      // a custom role can grant more.
+      require_capability_rules: vec![
+        (
+          ResourceDef::new(WORKSPACE_PUBLISH_PATTERN),
+          [
+            (Method::POST, WorkspaceCapability::Publish),
+            (Method::DELETE, WorkspaceCapability::Publish),
+          ]
+          .into(),
+        ),
+        (
+          // The reads of the published views are only shown to those who can publish them
+          ResourceDef::new(WORKSPACE_PUBLISH_ANALYTICS_PATTERN),
+          [(Method::GET, WorkspaceCapability::Publish)].into(),
+        ),
+        (
+          ResourceDef::new(WORKSPACE_INVITE_PATTERN),
+          [(Method::POST, WorkspaceCapability::Invite)].into(),
+        ),
+        (
+          // Moving views to the trash
+          ResourceDef::new(WORKSPACE_TRASH_PATTERN),
+          [(Method::POST, WorkspaceCapability::DeleteView)].into(),
+        ),
+        (
+          // The AI usage is billed to the workspace
+          ResourceDef::new(WORKSPACE_AI_USAGE_PATTERN),
+          [(Method::GET, WorkspaceCapability::ManageBilling)].into(),
+        ),
       ],
       access_control,
     }
@@ -90,6 +135,19 @@ where
     })
   }
 
+  fn require_capability(&self, method: &Method, path: &Path<Url>) -> Option<WorkspaceCapability> {
+    self
+      .require_capability_rules
+      .iter()
+      .find_map(|(r, capabilities)| {
+        if r.is_match(path.as_str()) {
+          capabilities.get(method).cloned()
+        } else {
+          None
+        }
+      })
+  }
+
   fn require_role(&self, method: &Method, path: &Path<Url>) -> Option<AFRole> {
     self.require_role_rules.iter().find_map(|(r, roles)| {
       if r.is_match(path.as_str()) {
@@ -128,14 +186,21 @@ where
     // For example, Both AFRole::Owner and AFRole::Member have the write permission to the workspace,
     // but only the Owner can manage the workspace members.
     let require_role = self.require_role(&method, path);
-    let result = match require_role {
-      Some(role) => {
+    let require_capability = self.require_capability(&method, path);
+    let result = match (require_capability, require_role) {
+      (Some(capability), _) => {
+        self
+          .access_control
+          .enforce_capability(uid, resource_id, capability)
+          .await
+      },
+      (None, Some(role)) => {
         self
           .access_control
           .enforce_role(uid, resource_id, role)
           .await
       },
-      None => {
+      (None, None) => {
         // If the request doesn't match any specific resources, we enforce the action.
         let action = Action::from(&method);
         self
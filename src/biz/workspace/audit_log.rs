@@ -0,0 +1,100 @@
+use std::str::FromStr;
+
+use app_error::AppError;
+use database::audit_log::{insert_audit_log, select_audit_logs};
+use shared_entity::dto::workspace_dto::{AuditAction, AuditLogEntry, AuditLogPage, QueryAuditLog};
+use sqlx::PgPool;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+const DEFAULT_AUDIT_LOG_PAGE_SIZE: u32 = 50;
+const MAX_AUDIT_LOG_PAGE_SIZE: u32 = 200;
+
+/// Append an entry to the audit log, for an action that has already succeeded. A failure to
+/// record the entry is logged rather than returned.
+pub async fn record_audit_log(
+  pg_pool: &PgPool,
+  workspace_id: Option<&Uuid>,
+  actor_uid: Option<i64>,
+  action: AuditAction,
+  target: Option<&str>,
+  metadata: serde_json::Value,
+) {
+  if let Err(err) = insert_audit_log(
+    pg_pool,
+    workspace_id,
+    actor_uid,
+    action.as_str(),
+    target,
+    &metadata,
+  )
+  .await
+  {
+    error!(
+      "Failed to record audit log {} of user {:?}: {}",
+      action.as_str(),
+      actor_uid,
+      err
+    );
+  }
+}
+
+pub async fn list_audit_logs(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  query: QueryAuditLog,
+) -> Result<AuditLogPage, AppError> {
+  let before_id = query
+    .after
+    .as_deref()
+    .map(|cursor| {
+      i64::from_str(cursor)
+        .map_err(|_| AppError::InvalidRequest(format!("Invalid cursor: {}", cursor)))
+    })
+    .transpose()?;
+  let limit = query
+    .first
+    .unwrap_or(DEFAULT_AUDIT_LOG_PAGE_SIZE)
+    .clamp(1, MAX_AUDIT_LOG_PAGE_SIZE) as usize;
+
+  // One more entry than the page tells whether there is a next page
+  let mut rows = select_audit_logs(
+    pg_pool,
+    workspace_id,
+    query.actor_uid,
+    query.action.map(|action| action.as_str()),
+    query.since,
+    query.until,
+    before_id,
+    limit as i64 + 1,
+  )
+  .await?;
+  let has_next_page = rows.len() > limit;
+  rows.truncate(limit);
+  let end_cursor = rows.last().map(|row| row.id.to_string());
+
+  let entries = rows
+    .into_iter()
+    .filter_map(|row| match AuditAction::from_str(&row.action) {
+      Ok(action) => Some(AuditLogEntry {
+        id: row.id,
+        workspace_id: row.workspace_id,
+        actor_uid: row.actor_uid,
+        actor_email: row.actor_email,
+        action,
+        target: row.target,
+        metadata: row.metadata,
+        created_at: row.created_at,
+      }),
+      Err(err) => {
+        warn!("Skip audit log {}: {}", row.id, err);
+        None
+      },
+    })
+    .collect();
+  Ok(AuditLogPage {
+    entries,
+    has_next_page,
+    end_cursor,
+  })
+}
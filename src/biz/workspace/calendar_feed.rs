@@ -0,0 +1,473 @@
+use std::sync::Arc;
+
+use access_control::act::Action;
+use access_control::collab::CollabAccessControl;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use chrono::{DateTime, Duration, Utc};
+use collab_database::entity::FieldType;
+use collab_database::fields::Field;
+use collab_database::rows::{Cell, RowDetail};
+use collab_database::template::entity::CELL_DATA;
+use collab_database::views::DatabaseLayout;
+use database::calendar_feed::{
+  delete_database_calendar_feed as delete_database_calendar_feed_row,
+  insert_database_calendar_feed, select_database_calendar_feed_by_token,
+};
+use database::collab::{select_collabs_fingerprint, GetCollabOrigin};
+use database::pg_row::AFDatabaseCalendarFeedRow;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use shared_entity::dto::workspace_dto::DatabaseCalendarFeed;
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+use yrs::Any;
+
+use crate::state::RedisConnectionManager;
+
+use super::database_row::{get_row_details, open_view_database, ViewDatabase};
+use super::export::cell_text;
+
+const CALENDAR_FEED_TOKEN_LEN: usize = 32;
+/// Feeds are rendered again once the database or one of its rows is saved, or after a day.
+const CALENDAR_FEED_CACHE_TTL_SECS: u64 = 60 * 60 * 24;
+/// How long an event with a time but without an end lasts.
+const DEFAULT_EVENT_DURATION_MINUTES: i64 = 60;
+/// The lines of an iCalendar file are folded after this many bytes.
+const ICAL_LINE_LEN: usize = 75;
+
+/// Returns the iCal feed of the database view for the user, creating it if the user has none.
+/// The view must have a date field to show the rows in a calendar.
+pub async fn create_database_calendar_feed(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  public_url: &str,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &str,
+) -> Result<DatabaseCalendarFeed, AppError> {
+  let database = open_view_database(
+    collab_storage,
+    pg_pool,
+    GetCollabOrigin::User { uid },
+    workspace_id,
+    view_id,
+  )
+  .await?;
+  calendar_view(&database, &database.fields()?)?;
+  let row = insert_database_calendar_feed(
+    pg_pool,
+    workspace_id,
+    view_id,
+    &generate_calendar_feed_token(),
+    uid,
+  )
+  .await?;
+  Ok(calendar_feed_from_row(public_url, row))
+}
+
+/// Revoke the feed of the database view of the user. The calendar apps subscribed to it no longer
+/// receive the events, and a feed created afterwards has a new url.
+pub async fn delete_database_calendar_feed(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &str,
+) -> Result<(), AppError> {
+  if !delete_database_calendar_feed_row(pg_pool, workspace_id, view_id, uid).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "no calendar feed of database view {}",
+      view_id
+    )));
+  }
+  Ok(())
+}
+
+/// The rendered feed, with the collabs it was rendered from and their fingerprint at that time.
+#[derive(Serialize, Deserialize)]
+struct CachedCalendarFeed {
+  database_id: String,
+  object_ids: Vec<String>,
+  fingerprint: String,
+  rendered: String,
+}
+
+/// Render the rows of the database view of the feed that have a date as an iCalendar file. The
+/// rows are read as the user that created the feed, and the rendered file is cached until the
+/// database or one of its rows changes. The cache is checked before the database is opened, so
+/// that polling an unchanged feed only reads the fingerprint of its collabs.
+pub async fn get_database_calendar_feed(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  collab_access_control: &impl CollabAccessControl,
+  pg_pool: &PgPool,
+  redis_client: &RedisConnectionManager,
+  token: &str,
+) -> Result<String, AppError> {
+  let feed = select_database_calendar_feed_by_token(pg_pool, token)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound("calendar feed not found".to_string()))?;
+  let cache_key = format!("af_calendar_feed:{}", token);
+  let mut redis_client = redis_client.clone();
+  let cached = match redis_client.get::<_, Option<String>>(&cache_key).await {
+    Ok(cached) => cached.and_then(|value| serde_json::from_str::<CachedCalendarFeed>(&value).ok()),
+    Err(err) => {
+      warn!("failed to read calendar feed {}: {}", feed.view_id, err);
+      None
+    },
+  };
+  if let Some(cached) = cached {
+    // Otherwise the database is opened below, which fails with the reason the access is denied
+    let can_read = collab_access_control
+      .enforce_action(
+        &feed.workspace_id.to_string(),
+        &feed.created_by,
+        &cached.database_id,
+        Action::Read,
+      )
+      .await?;
+    if can_read
+      && select_collabs_fingerprint(pg_pool, &cached.object_ids).await? == cached.fingerprint
+    {
+      return Ok(cached.rendered);
+    }
+  }
+
+  let database = open_view_database(
+    collab_storage.clone(),
+    pg_pool,
+    GetCollabOrigin::User {
+      uid: feed.created_by,
+    },
+    &feed.workspace_id,
+    &feed.view_id,
+  )
+  .await?;
+  let row_ids = database.row_ids();
+  let mut object_ids = row_ids.clone();
+  object_ids.push(database.database_id.clone());
+  let fingerprint = select_collabs_fingerprint(pg_pool, &object_ids).await?;
+
+  let fields = database.fields()?;
+  let (calendar_name, date_field) = calendar_view(&database, &fields)?;
+  let mut row_details = get_row_details(collab_storage, feed.created_by, &row_ids).await;
+  let rows: Vec<RowDetail> = row_ids
+    .iter()
+    .filter_map(|row_id| row_details.remove(row_id))
+    .collect();
+  let rendered = render_calendar(&calendar_name, &fields, &date_field, &rows);
+
+  let cached = CachedCalendarFeed {
+    database_id: database.database_id.clone(),
+    object_ids,
+    fingerprint,
+    rendered,
+  };
+  match serde_json::to_string(&cached) {
+    Ok(value) => {
+      if let Err(err) = redis_client
+        .set_ex::<_, _, ()>(&cache_key, value, CALENDAR_FEED_CACHE_TTL_SECS)
+        .await
+      {
+        warn!("failed to cache calendar feed {}: {}", feed.view_id, err);
+      }
+    },
+    Err(err) => warn!("failed to cache calendar feed {}: {}", feed.view_id, err),
+  }
+  Ok(cached.rendered)
+}
+
+/// Returns the name of the view and the date field its rows are shown by: the field of the
+/// calendar layout, or else the first date field.
+fn calendar_view(database: &ViewDatabase, fields: &[Field]) -> Result<(String, Field), AppError> {
+  let txn = database.collab.transact();
+  let view = database
+    .body
+    .views
+    .get_all_views(&txn)
+    .into_iter()
+    .find(|view| view.id == database.view_id)
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!("database view {} not found", database.view_id))
+    })?;
+  let is_date_field = |field: &&Field| field.field_type == FieldType::DateTime as i64;
+  let layout_field_id = view
+    .layout_settings
+    .get(&DatabaseLayout::Calendar)
+    .and_then(|setting| match setting.get("field_id") {
+      Some(Any::String(field_id)) => Some(field_id.to_string()),
+      _ => None,
+    });
+  let field = layout_field_id
+    .and_then(|field_id| {
+      fields
+        .iter()
+        .filter(is_date_field)
+        .find(|field| field.id == field_id)
+    })
+    .or_else(|| fields.iter().find(is_date_field))
+    .ok_or_else(|| {
+      AppError::InvalidRequest(format!(
+        "The database view {} has no date field",
+        database.view_id
+      ))
+    })?;
+  Ok((view.name, field.clone()))
+}
+
+/// When an event takes place, in seconds. The end of an all-day event is the last day it lasts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EventTime {
+  start: i64,
+  end: Option<i64>,
+  include_time: bool,
+}
+
+fn event_time(cell: &Cell) -> Option<EventTime> {
+  let timestamp = |value: &Any| match value {
+    Any::String(s) => s.parse::<i64>().ok(),
+    Any::BigInt(n) => Some(*n),
+    Any::Number(n) => Some(*n as i64),
+    _ => None,
+  };
+  let start = timestamp(cell.get(CELL_DATA)?)?;
+  let end = if matches!(cell.get("is_range"), Some(Any::Bool(true))) {
+    cell.get("end_timestamp").and_then(timestamp)
+  } else {
+    None
+  };
+  Some(EventTime {
+    start,
+    end: end.filter(|end| *end >= start),
+    include_time: matches!(cell.get("include_time"), Some(Any::Bool(true))),
+  })
+}
+
+fn render_calendar(name: &str, fields: &[Field], date_field: &Field, rows: &[RowDetail]) -> String {
+  let mut lines = vec![
+    "BEGIN:VCALENDAR".to_string(),
+    "VERSION:2.0".to_string(),
+    "PRODID:-//AppFlowy//AppFlowy Cloud//EN".to_string(),
+    "CALSCALE:GREGORIAN".to_string(),
+    "METHOD:PUBLISH".to_string(),
+    format!("X-WR-CALNAME:{}", escape_text(name)),
+  ];
+  for row_detail in rows {
+    let row = &row_detail.row;
+    let Some(time) = row.cells.get(&date_field.id).and_then(event_time) else {
+      continue;
+    };
+    let mut summary = String::new();
+    let mut description = vec![];
+    for field in fields {
+      if field.id == date_field.id {
+        continue;
+      }
+      let Some(text) = row.cells.get(&field.id).map(|cell| cell_text(field, cell)) else {
+        continue;
+      };
+      if text.is_empty() {
+        continue;
+      }
+      if field.is_primary {
+        summary = text;
+      } else {
+        description.push(format!("{}: {}", field.name, text));
+      }
+    }
+    lines.extend(event_lines(
+      &row.id.to_string(),
+      row.modified_at,
+      &time,
+      &summary,
+      &description.join("\n"),
+    ));
+  }
+  lines.push("END:VCALENDAR".to_string());
+
+  let mut calendar = String::new();
+  for line in lines {
+    calendar.push_str(&fold_line(&line));
+    calendar.push_str("\r\n");
+  }
+  calendar
+}
+
+fn event_lines(
+  row_id: &str,
+  modified_at: i64,
+  time: &EventTime,
+  summary: &str,
+  description: &str,
+) -> Vec<String> {
+  let date_time = |timestamp: i64| DateTime::from_timestamp(timestamp, 0).unwrap_or_default();
+  let mut lines = vec![
+    "BEGIN:VEVENT".to_string(),
+    format!("UID:{}@appflowy", row_id),
+    format!(
+      "DTSTAMP:{}",
+      date_time(modified_at).format("%Y%m%dT%H%M%SZ")
+    ),
+  ];
+  let start = date_time(time.start);
+  if time.include_time {
+    let end = time
+      .end
+      .map(date_time)
+      .unwrap_or_else(|| start + Duration::minutes(DEFAULT_EVENT_DURATION_MINUTES));
+    lines.push(format!("DTSTART:{}", start.format("%Y%m%dT%H%M%SZ")));
+    lines.push(format!("DTEND:{}", end.format("%Y%m%dT%H%M%SZ")));
+  } else {
+    // The end date of an all-day event is exclusive
+    let end = time.end.map(date_time).unwrap_or(start) + Duration::days(1);
+    lines.push(format!("DTSTART;VALUE=DATE:{}", start.format("%Y%m%d")));
+    lines.push(format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+  }
+  let summary = if summary.is_empty() {
+    "Untitled"
+  } else {
+    summary
+  };
+  lines.push(format!("SUMMARY:{}", escape_text(summary)));
+  if !description.is_empty() {
+    lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+  }
+  lines.push("END:VEVENT".to_string());
+  lines
+}
+
+/// Escape a TEXT value of the iCalendar format.
+fn escape_text(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '\\' => escaped.push_str("\\\\"),
+      ';' => escaped.push_str("\\;"),
+      ',' => escaped.push_str("\\,"),
+      '\n' => escaped.push_str("\\n"),
+      '\r' => {},
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+/// Split the line so that no line is longer than [ICAL_LINE_LEN] bytes, without splitting a
+/// character. The continuation lines start with a space.
+fn fold_line(line: &str) -> String {
+  let mut folded = String::with_capacity(line.len());
+  let mut line_len = 0;
+  for c in line.chars() {
+    if line_len + c.len_utf8() > ICAL_LINE_LEN {
+      folded.push_str("\r\n ");
+      line_len = 1;
+    }
+    folded.push(c);
+    line_len += c.len_utf8();
+  }
+  folded
+}
+
+fn generate_calendar_feed_token() -> String {
+  rand::thread_rng()
+    .sample_iter(&Alphanumeric)
+    .take(CALENDAR_FEED_TOKEN_LEN)
+    .map(char::from)
+    .collect()
+}
+
+fn calendar_feed_from_row(
+  public_url: &str,
+  row: AFDatabaseCalendarFeedRow,
+) -> DatabaseCalendarFeed {
+  DatabaseCalendarFeed {
+    url: format!(
+      "{}/api/workspace/calendar-feed/{}",
+      public_url.trim_end_matches('/'),
+      row.token
+    ),
+    token: row.token,
+    created_at: row.created_at,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn escape_text_test() {
+    assert_eq!(escape_text("a, b; c\\d"), "a\\, b\\; c\\\\d");
+    assert_eq!(escape_text("line\r\nnext"), "line\\nnext");
+  }
+
+  #[test]
+  fn fold_line_test() {
+    assert_eq!(fold_line("SUMMARY:short"), "SUMMARY:short");
+    let line = format!("SUMMARY:{}", "a".repeat(100));
+    let folded = fold_line(&line);
+    let parts: Vec<&str> = folded.split("\r\n").collect();
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[0].len(), 75);
+    assert!(parts[1].starts_with(' '));
+    assert_eq!(parts.concat().replacen(' ', "", 1), line);
+    // multi-byte characters are not split
+    let folded = fold_line(&"é".repeat(50));
+    assert!(folded.split("\r\n").all(|part| part.len() <= 75));
+  }
+
+  #[test]
+  fn event_time_test() {
+    let cell = Cell::from([
+      (CELL_DATA.to_string(), Any::String("1730419200".into())),
+      ("include_time".to_string(), Any::Bool(true)),
+    ]);
+    assert_eq!(
+      event_time(&cell),
+      Some(EventTime {
+        start: 1730419200,
+        end: None,
+        include_time: true,
+      })
+    );
+    let range = Cell::from([
+      (CELL_DATA.to_string(), Any::String("1730419200".into())),
+      (
+        "end_timestamp".to_string(),
+        Any::String("1730592000".into()),
+      ),
+      ("is_range".to_string(), Any::Bool(true)),
+    ]);
+    assert_eq!(event_time(&range).unwrap().end, Some(1730592000));
+    let empty = Cell::from([(CELL_DATA.to_string(), Any::String("".into()))]);
+    assert_eq!(event_time(&empty), None);
+  }
+
+  #[test]
+  fn event_lines_test() {
+    let all_day = EventTime {
+      start: 1730419200,
+      end: Some(1730592000),
+      include_time: false,
+    };
+    let lines = event_lines("row", 1730419200, &all_day, "Launch, v1", "Status: Done");
+    assert!(lines.contains(&"UID:row@appflowy".to_string()));
+    assert!(lines.contains(&"DTSTART;VALUE=DATE:20241101".to_string()));
+    assert!(lines.contains(&"DTEND;VALUE=DATE:20241104".to_string()));
+    assert!(lines.contains(&"SUMMARY:Launch\\, v1".to_string()));
+    assert!(lines.contains(&"DESCRIPTION:Status: Done".to_string()));
+
+    let timed = EventTime {
+      start: 1730455200,
+      end: None,
+      include_time: true,
+    };
+    let lines = event_lines("row", 1730419200, &timed, "", "");
+    assert!(lines.contains(&"DTSTART:20241101T100000Z".to_string()));
+    assert!(lines.contains(&"DTEND:20241101T110000Z".to_string()));
+    assert!(lines.contains(&"SUMMARY:Untitled".to_string()));
+    assert!(!lines.iter().any(|line| line.starts_with("DESCRIPTION")));
+  }
+}
@@ -0,0 +1,326 @@
+use std::collections::HashSet;
+use std::io::{Cursor, Write};
+use std::sync::Arc;
+
+use access_control::act::Action;
+use access_control::collab::CollabAccessControl;
+use anyhow::anyhow;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use bytes::Bytes;
+use collab_database::entity::FieldType;
+use collab_database::fields::Field;
+use collab_database::rows::RowDetail;
+use database::collab::GetCollabOrigin;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use shared_entity::dto::workspace_dto::DatabaseExportFormat;
+use sqlx::PgPool;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::database_query::{any_i64, view_row_details};
+use super::database_row::open_view_database;
+use super::export::{cell_text, file_name, write_csv_record};
+
+pub const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+pub const XLSX_CONTENT_TYPE: &str =
+  "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+/// The CSV is sent this many rows at a time.
+const CSV_CHUNK_ROWS: usize = 100;
+/// The visibility of the fields that are hidden in the view.
+const FIELD_ALWAYS_HIDDEN: i64 = 2;
+/// Excel doesn't open workbooks with longer sheet names.
+const MAX_SHEET_NAME_LEN: usize = 31;
+
+const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#;
+const SPREADSHEET_NAMESPACE: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+const XLSX_CONTENT_TYPES: &str = r#"<Types
+  xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="rels"
+    ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+  <Default Extension="xml" ContentType="application/xml"/>
+  <Override PartName="/xl/workbook.xml"
+    ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>
+  <Override PartName="/xl/worksheets/sheet1.xml"
+    ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>
+</Types>"#;
+const XLSX_PACKAGE_RELS: &str = r#"<Relationships
+  xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1"
+    Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument"
+    Target="xl/workbook.xml"/>
+</Relationships>"#;
+const XLSX_WORKBOOK_RELS: &str = r#"<Relationships
+  xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1"
+    Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet"
+    Target="worksheets/sheet1.xml"/>
+</Relationships>"#;
+
+/// The rows of a database view, rendered as a file and streamed to the client.
+pub struct DatabaseViewExport {
+  pub file_name: String,
+  pub content_type: &'static str,
+  pub body: BoxStream<'static, Result<Bytes, AppError>>,
+}
+
+/// Export the rows of the database view that match its filters, in the order of its sorts, with
+/// the fields that are not hidden in the view. The user must be able to read the database.
+pub async fn export_database_view(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  collab_access_control: &impl CollabAccessControl,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &str,
+  format: DatabaseExportFormat,
+) -> Result<DatabaseViewExport, AppError> {
+  let database = open_view_database(
+    collab_storage.clone(),
+    pg_pool,
+    GetCollabOrigin::User { uid },
+    workspace_id,
+    view_id,
+  )
+  .await?;
+  let can_read = collab_access_control
+    .enforce_action(
+      &workspace_id.to_string(),
+      &uid,
+      &database.database_id,
+      Action::Read,
+    )
+    .await?;
+  if !can_read {
+    return Err(AppError::NotEnoughPermissions {
+      user: uid.to_string(),
+      action: format!("export database:{}", database.database_id),
+    });
+  }
+
+  let (name, hidden_field_ids) = {
+    let txn = database.collab.transact();
+    let view = database
+      .body
+      .views
+      .get_all_views(&txn)
+      .into_iter()
+      .find(|view| view.id == view_id)
+      .ok_or_else(|| AppError::RecordNotFound(format!("database view {} not found", view_id)))?;
+    let hidden_field_ids: HashSet<String> = view
+      .field_settings
+      .iter()
+      .filter(|(_, settings)| {
+        settings.get("visibility").and_then(any_i64) == Some(FIELD_ALWAYS_HIDDEN)
+      })
+      .map(|(field_id, _)| field_id.clone())
+      .collect();
+    (view.name, hidden_field_ids)
+  };
+  let mut fields = database.fields()?;
+  let rows = view_row_details(collab_storage, uid, database, &fields, None, None).await?;
+  fields.retain(|field| !hidden_field_ids.contains(&field.id));
+
+  let name = file_name(&name);
+  let export = match format {
+    DatabaseExportFormat::Csv => DatabaseViewExport {
+      file_name: format!("{}.csv", name),
+      content_type: CSV_CONTENT_TYPE,
+      body: csv_stream(fields, rows),
+    },
+    DatabaseExportFormat::Xlsx => {
+      let xlsx_file_name = format!("{}.xlsx", name);
+      let workbook =
+        tokio::task::spawn_blocking(move || database_xlsx(&name, &fields, &rows)).await??;
+      DatabaseViewExport {
+        file_name: xlsx_file_name,
+        content_type: XLSX_CONTENT_TYPE,
+        body: stream::once(async move { Ok(Bytes::from(workbook)) }).boxed(),
+      }
+    },
+  };
+  Ok(export)
+}
+
+/// The header, then the rows a chunk at a time, so that the rows are rendered as they are sent.
+fn csv_stream(
+  fields: Vec<Field>,
+  rows: Vec<RowDetail>,
+) -> BoxStream<'static, Result<Bytes, AppError>> {
+  let mut header = String::new();
+  let names: Vec<String> = fields.iter().map(|field| field.name.clone()).collect();
+  write_csv_record(&mut header, &names);
+  let rows = stream::iter(rows).chunks(CSV_CHUNK_ROWS).map(move |chunk| {
+    let mut csv = String::new();
+    for row_detail in &chunk {
+      write_csv_record(&mut csv, &row_texts(&fields, row_detail));
+    }
+    Ok(Bytes::from(csv))
+  });
+  stream::once(async move { Ok(Bytes::from(header)) })
+    .chain(rows)
+    .boxed()
+}
+
+fn row_texts(fields: &[Field], row_detail: &RowDetail) -> Vec<String> {
+  fields
+    .iter()
+    .map(|field| {
+      row_detail
+        .row
+        .cells
+        .get(&field.id)
+        .map(|cell| cell_text(field, cell))
+        .unwrap_or_default()
+    })
+    .collect()
+}
+
+/// A workbook with a single sheet holding the header and the rows. The cells of number fields are
+/// written as numbers, all the others as text.
+fn database_xlsx(name: &str, fields: &[Field], rows: &[RowDetail]) -> Result<Vec<u8>, AppError> {
+  let mut sheet = format!(
+    r#"{}<worksheet xmlns="{}"><sheetData>"#,
+    XML_DECLARATION, SPREADSHEET_NAMESPACE
+  );
+  let header: Vec<String> = fields.iter().map(|field| field.name.clone()).collect();
+  write_xlsx_row(&mut sheet, 1, None, &header);
+  for (index, row_detail) in rows.iter().enumerate() {
+    write_xlsx_row(
+      &mut sheet,
+      index + 2,
+      Some(fields),
+      &row_texts(fields, row_detail),
+    );
+  }
+  sheet.push_str("</sheetData></worksheet>");
+  let workbook = format!(
+    r#"{}<workbook
+  xmlns="{}"
+  xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+  <sheets><sheet name="{}" sheetId="1" r:id="rId1"/></sheets>
+</workbook>"#,
+    XML_DECLARATION,
+    SPREADSHEET_NAMESPACE,
+    escape_xml(&sheet_name(name))
+  );
+  let content_types = format!("{}{}", XML_DECLARATION, XLSX_CONTENT_TYPES);
+  let package_rels = format!("{}{}", XML_DECLARATION, XLSX_PACKAGE_RELS);
+  let workbook_rels = format!("{}{}", XML_DECLARATION, XLSX_WORKBOOK_RELS);
+  let parts = [
+    ("[Content_Types].xml", content_types),
+    ("_rels/.rels", package_rels),
+    ("xl/_rels/workbook.xml.rels", workbook_rels),
+    ("xl/workbook.xml", workbook),
+    ("xl/worksheets/sheet1.xml", sheet),
+  ];
+
+  let zip_error = |err: zip::result::ZipError| AppError::Internal(anyhow!(err));
+  let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+  let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+  for (path, data) in parts {
+    zip.start_file(path, options).map_err(zip_error)?;
+    zip.write_all(data.as_bytes())?;
+  }
+  Ok(zip.finish().map_err(zip_error)?.into_inner())
+}
+
+/// Write a row of the sheet. The cells are written as text, unless they are given with their
+/// fields and are cells of number fields.
+fn write_xlsx_row(sheet: &mut String, row: usize, fields: Option<&[Field]>, texts: &[String]) {
+  sheet.push_str(&format!(r#"<row r="{}">"#, row));
+  for (index, text) in texts.iter().enumerate() {
+    if text.is_empty() {
+      continue;
+    }
+    let reference = format!("{}{}", column_name(index), row);
+    let is_number = fields
+      .and_then(|fields| fields.get(index))
+      .map_or(false, |field| field.field_type == FieldType::Number as i64)
+      && text.parse::<f64>().map_or(false, f64::is_finite);
+    if is_number {
+      sheet.push_str(&format!(r#"<c r="{}"><v>{}</v></c>"#, reference, text));
+    } else {
+      sheet.push_str(&format!(
+        r#"<c r="{}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+        reference,
+        escape_xml(text)
+      ));
+    }
+  }
+  sheet.push_str("</row>");
+}
+
+/// The name of the column in the A1 notation: A to Z, then AA, AB...
+fn column_name(mut index: usize) -> String {
+  let mut name = vec![];
+  loop {
+    name.push(b'A' + (index % 26) as u8);
+    if index < 26 {
+      break;
+    }
+    index = index / 26 - 1;
+  }
+  name.reverse();
+  String::from_utf8(name).unwrap_or_default()
+}
+
+fn sheet_name(name: &str) -> String {
+  name
+    .chars()
+    .map(|c| match c {
+      '[' | ']' | ':' | '*' | '?' | '/' | '\\' => '_',
+      c => c,
+    })
+    .take(MAX_SHEET_NAME_LEN)
+    .collect()
+}
+
+/// Escape the text for XML, leaving out the control characters XML doesn't allow.
+fn escape_xml(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\t' | '\n' | '\r' => escaped.push(c),
+      c if c.is_control() => {},
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn column_name_test() {
+    assert_eq!(column_name(0), "A");
+    assert_eq!(column_name(25), "Z");
+    assert_eq!(column_name(26), "AA");
+    assert_eq!(column_name(27), "AB");
+    assert_eq!(column_name(701), "ZZ");
+    assert_eq!(column_name(702), "AAA");
+  }
+
+  #[test]
+  fn sheet_name_test() {
+    assert_eq!(
+      sheet_name("Q1 [draft]: tasks / notes for the whole team"),
+      "Q1 _draft__ tasks _ notes for t"
+    );
+  }
+
+  #[test]
+  fn escape_xml_test() {
+    assert_eq!(
+      escape_xml("a < b & \"c\"\u{1}\n"),
+      "a &lt; b &amp; &quot;c&quot;\n"
+    );
+  }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use access_control::collab::CollabAccessControl;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab_database::entity::FieldType;
+use collab_database::fields::Field;
+use collab_database::views::{FieldOrder, OrderObjectPosition};
+use collab_entity::CollabType;
+use database::collab::GetCollabOrigin;
+use database::database_import::{
+  insert_database_import_task, select_database_import_task, update_database_import_task_completed,
+  update_database_import_task_failed, update_database_import_task_progress,
+};
+use database::pg_row::AFDatabaseImportTaskRow;
+use database_entity::dto::CollabParams;
+use serde_json::{json, Value};
+use shared_entity::dto::workspace_dto::{
+  DatabaseImportRowError, DatabaseImportTask, WorkspaceImportStatus,
+};
+use sqlx::PgPool;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use super::database_row::{
+  enforce_database_write, find_field, insert_database_rows, open_view_database, resolve_cells,
+  save_database_collabs, ViewDatabase,
+};
+use super::import::parse_csv;
+use super::publish_dup::{broadcast_update, collab_to_bin};
+
+/// The largest CSV file that can be imported into a database, in bytes.
+pub const MAX_DATABASE_IMPORT_CSV_SIZE: usize = 10 * 1024 * 1024;
+/// The most rows a CSV file can contain, not counting the header.
+const MAX_DATABASE_IMPORT_ROWS: usize = 10_000;
+/// The rows are inserted, and the progress of the import is updated, this many rows at a time.
+const DATABASE_IMPORT_BATCH_SIZE: usize = 100;
+/// The most row errors kept for an import.
+const MAX_DATABASE_IMPORT_ROW_ERRORS: usize = 100;
+
+/// Start importing the rows of a CSV file into the database the view belongs to, in the
+/// background. The first line of the file is the header: each column is put in the field with
+/// the same id or name, and a text field is created for the columns that match no field. The
+/// rows whose values don't fit their fields are skipped, and reported in the import task.
+#[allow(clippy::too_many_arguments)]
+pub async fn start_database_csv_import(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  collab_access_control: &impl CollabAccessControl,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: Uuid,
+  view_id: String,
+  csv: Vec<u8>,
+) -> Result<DatabaseImportTask, AppError> {
+  let csv = String::from_utf8(csv)
+    .map_err(|_| AppError::InvalidRequest("The CSV file must be encoded in UTF-8".to_string()))?;
+  let mut records = parse_csv(&csv).into_iter();
+  let header = records.next().unwrap_or_default();
+  let records: Vec<Vec<String>> = records.collect();
+  if records.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "The CSV file must have a header and at least one row".to_string(),
+    ));
+  }
+  if records.len() > MAX_DATABASE_IMPORT_ROWS {
+    return Err(AppError::InvalidRequest(format!(
+      "The CSV file contains more than {} rows",
+      MAX_DATABASE_IMPORT_ROWS
+    )));
+  }
+
+  let database = open_view_database(
+    collab_storage.clone(),
+    pg_pool,
+    GetCollabOrigin::User { uid },
+    &workspace_id,
+    &view_id,
+  )
+  .await?;
+  enforce_database_write(
+    collab_access_control,
+    &workspace_id,
+    uid,
+    &database.database_id,
+  )
+  .await?;
+  let field_ids = map_columns(
+    collab_storage.clone(),
+    pg_pool,
+    uid,
+    &workspace_id,
+    database,
+    &header,
+  )
+  .await?;
+
+  let row =
+    insert_database_import_task(pg_pool, &workspace_id, uid, &view_id, records.len() as i32)
+      .await?;
+  let task_id = row.task_id;
+  let pg_pool = pg_pool.clone();
+  tokio::spawn(async move {
+    let result = import_rows(
+      collab_storage,
+      &pg_pool,
+      task_id,
+      uid,
+      &workspace_id,
+      &view_id,
+      &field_ids,
+      records,
+    )
+    .await;
+    let updated = match result {
+      Ok(()) => update_database_import_task_completed(&pg_pool, &task_id).await,
+      Err(err) => {
+        warn!(
+          "Failed to import into database view {} of workspace {}: {}",
+          view_id, workspace_id, err
+        );
+        update_database_import_task_failed(&pg_pool, &task_id, &err.to_string()).await
+      },
+    };
+    if let Err(err) = updated {
+      error!("Failed to update database import task {}: {}", task_id, err);
+    }
+  });
+  Ok(import_task_from_row(row))
+}
+
+pub async fn get_database_import_task(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &str,
+  task_id: &Uuid,
+) -> Result<DatabaseImportTask, AppError> {
+  let row = select_database_import_task(pg_pool, workspace_id, uid, task_id)
+    .await?
+    .filter(|row| row.view_id == view_id)
+    .ok_or_else(|| AppError::RecordNotFound(format!("database import {} not found", task_id)))?;
+  Ok(import_task_from_row(row))
+}
+
+fn import_task_from_row(row: AFDatabaseImportTaskRow) -> DatabaseImportTask {
+  DatabaseImportTask {
+    task_id: row.task_id,
+    status: WorkspaceImportStatus::from(row.status),
+    view_id: row.view_id,
+    total_rows: row.total_rows,
+    imported_rows: row.imported_rows,
+    failed_rows: row.failed_rows,
+    row_errors: serde_json::from_value(row.row_errors).unwrap_or_default(),
+    error: row.error,
+    created_at: row.created_at,
+    completed_at: row.completed_at,
+  }
+}
+
+/// Returns the id of the field each column of the CSV file goes to, creating a text field at the
+/// end of every view for the columns that match no field. The columns without a name are skipped.
+async fn map_columns(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  database: ViewDatabase,
+  header: &[String],
+) -> Result<Vec<Option<String>>, AppError> {
+  let fields = database.fields()?;
+  let mut new_fields: Vec<Field> = vec![];
+  let field_ids = header
+    .iter()
+    .map(|name| {
+      let name = name.trim();
+      if name.is_empty() {
+        return None;
+      }
+      if let Ok(field) = find_field(&fields, name).or_else(|_| find_field(&new_fields, name)) {
+        return Some(field.id.clone());
+      }
+      let field = Field::new(
+        Uuid::new_v4().to_string(),
+        name.to_string(),
+        FieldType::RichText as i64,
+        false,
+      );
+      let field_id = field.id.clone();
+      new_fields.push(field);
+      Some(field_id)
+    })
+    .collect();
+  if new_fields.is_empty() {
+    return Ok(field_ids);
+  }
+
+  let ViewDatabase {
+    database_id,
+    collab: mut db_collab,
+    body: db_body,
+    ..
+  } = database;
+  let encoded_update = {
+    let mut txn = db_collab.context.transact_mut();
+    for field in &new_fields {
+      db_body.fields.insert_field(&mut txn, field.clone());
+    }
+    for view in db_body.views.get_all_views(&txn) {
+      db_body
+        .views
+        .update_database_view(&mut txn, &view.id, |mut update| {
+          for field in &new_fields {
+            update = update.insert_field_order(
+              &FieldOrder::new(field.id.clone()),
+              &OrderObjectPosition::End,
+            );
+          }
+        });
+    }
+    txn.encode_update_v1()
+  };
+  let params = CollabParams {
+    object_id: database_id.clone(),
+    encoded_collab_v1: collab_to_bin(db_collab, CollabType::Database).await?.into(),
+    collab_type: CollabType::Database,
+    embeddings: None,
+  };
+  save_database_collabs(
+    collab_storage.clone(),
+    pg_pool,
+    uid,
+    workspace_id,
+    vec![params],
+  )
+  .await?;
+  broadcast_update(&collab_storage, &database_id, encoded_update).await?;
+  Ok(field_ids)
+}
+
+/// Insert the rows batch by batch, updating the progress of the import after each batch.
+#[allow(clippy::too_many_arguments)]
+async fn import_rows(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  task_id: Uuid,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &str,
+  field_ids: &[Option<String>],
+  records: Vec<Vec<String>>,
+) -> Result<(), AppError> {
+  let mut kept_row_errors = 0;
+  for (batch_index, batch) in records.chunks(DATABASE_IMPORT_BATCH_SIZE).enumerate() {
+    // The database is opened again for each batch, so that the rows are added to its latest state
+    let database = open_view_database(
+      collab_storage.clone(),
+      pg_pool,
+      GetCollabOrigin::Server,
+      workspace_id,
+      view_id,
+    )
+    .await?;
+    let fields = database.fields()?;
+    let mut rows = Vec::with_capacity(batch.len());
+    let mut row_errors = vec![];
+    let mut failed_rows = 0;
+    for (index, record) in batch.iter().enumerate() {
+      let values = field_ids
+        .iter()
+        .zip(record)
+        .filter(|(_, value)| !value.trim().is_empty())
+        .filter_map(|(field_id, value)| Some((field_id.clone()?, Value::String(value.clone()))))
+        .collect::<HashMap<_, _>>();
+      match resolve_cells(&fields, values) {
+        Ok(cells) => rows.push(
+          cells
+            .into_iter()
+            .filter_map(|(field_id, cell)| cell.map(|cell| (field_id, cell)))
+            .collect(),
+        ),
+        Err(err) => {
+          failed_rows += 1;
+          if kept_row_errors < MAX_DATABASE_IMPORT_ROW_ERRORS {
+            kept_row_errors += 1;
+            row_errors.push(DatabaseImportRowError {
+              row: (batch_index * DATABASE_IMPORT_BATCH_SIZE + index + 1) as i32,
+              error: match err {
+                AppError::InvalidRequest(message) => message,
+                err => err.to_string(),
+              },
+            });
+          }
+        },
+      }
+    }
+    let imported_rows = rows.len() as i32;
+    if !rows.is_empty() {
+      insert_database_rows(
+        collab_storage.clone(),
+        pg_pool,
+        uid,
+        workspace_id,
+        database,
+        rows,
+      )
+      .await?;
+    }
+    update_database_import_task_progress(
+      pg_pool,
+      &task_id,
+      imported_rows,
+      failed_rows,
+      json!(row_errors),
+    )
+    .await?;
+  }
+  Ok(())
+}
@@ -0,0 +1,707 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab_database::entity::FieldType;
+use collab_database::fields::Field;
+use collab_database::rows::RowDetail;
+use collab_database::template::entity::CELL_DATA;
+use database::collab::GetCollabOrigin;
+use serde_json::Value;
+use shared_entity::dto::workspace_dto::{
+  DatabaseFilterOp, DatabaseRowFilter, DatabaseRowSort, DatabaseRows, QueryDatabaseViewParams,
+};
+use sqlx::PgPool;
+use tracing::debug;
+use uuid::Uuid;
+use yrs::Any;
+
+use super::database_row::{
+  database_field, database_row, find_field, get_row_details, open_view_database, parse_date,
+  ViewDatabase,
+};
+use super::export::select_options;
+
+const DEFAULT_DATABASE_ROWS_LIMIT: usize = 100;
+const MAX_DATABASE_ROWS_LIMIT: usize = 1000;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// A filter of the rows, with its fields and values resolved.
+#[derive(Debug)]
+enum RowFilter {
+  And(Vec<RowFilter>),
+  Or(Vec<RowFilter>),
+  Condition {
+    field: Field,
+    op: DatabaseFilterOp,
+    value: Option<CellValue>,
+  },
+}
+
+#[derive(Debug)]
+struct RowSort {
+  field: Field,
+  descending: bool,
+}
+
+/// The value of a cell, as it is compared by the filters and sorts.
+#[derive(Debug, Clone, PartialEq)]
+enum CellValue {
+  Text(String),
+  Number(f64),
+  Checkbox(bool),
+  /// The ids of the selected options.
+  Options(Vec<String>),
+  /// A timestamp in seconds.
+  Date(i64),
+}
+
+/// Returns the rows of the database view that match its filters, in the order of its sorts. The
+/// filter and sorts of the params, when given, are used instead of the ones of the view.
+pub async fn query_database_view(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &str,
+  params: QueryDatabaseViewParams,
+) -> Result<DatabaseRows, AppError> {
+  let database = open_view_database(
+    collab_storage.clone(),
+    pg_pool,
+    GetCollabOrigin::User { uid },
+    workspace_id,
+    view_id,
+  )
+  .await?;
+  let fields = database.fields()?;
+  let rows = view_row_details(
+    collab_storage,
+    uid,
+    database,
+    &fields,
+    params.filter,
+    params.sorts,
+  )
+  .await?;
+
+  let total = rows.len();
+  let limit = params
+    .limit
+    .unwrap_or(DEFAULT_DATABASE_ROWS_LIMIT)
+    .min(MAX_DATABASE_ROWS_LIMIT);
+  let rows = rows
+    .iter()
+    .skip(params.offset.unwrap_or(0))
+    .take(limit)
+    .map(|row_detail| database_row(&fields, row_detail))
+    .collect();
+  Ok(DatabaseRows {
+    fields: fields.iter().map(database_field).collect(),
+    rows,
+    total,
+  })
+}
+
+/// Returns the rows of the database view that match the filter, in the order of the sorts. The
+/// filter and sorts of the view are used when none is given.
+pub(crate) async fn view_row_details(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  uid: i64,
+  database: ViewDatabase,
+  fields: &[Field],
+  filter: Option<DatabaseRowFilter>,
+  sorts: Option<Vec<DatabaseRowSort>>,
+) -> Result<Vec<RowDetail>, AppError> {
+  let (view_filters, view_sorts) = {
+    let txn = database.collab.transact();
+    let view = database
+      .body
+      .views
+      .get_all_views(&txn)
+      .into_iter()
+      .find(|view| view.id == database.view_id)
+      .ok_or_else(|| {
+        AppError::RecordNotFound(format!("database view {} not found", database.view_id))
+      })?;
+    (view.filters, view.sorts)
+  };
+  let filter = match filter {
+    Some(filter) => Some(resolve_filter(fields, filter)?),
+    None => {
+      let filters: Vec<RowFilter> = view_filters
+        .iter()
+        .filter_map(|filter| view_filter(fields, filter))
+        .collect();
+      (!filters.is_empty()).then_some(RowFilter::And(filters))
+    },
+  };
+  let sorts = match sorts {
+    Some(sorts) => sorts
+      .into_iter()
+      .map(|sort| resolve_sort(fields, sort))
+      .collect::<Result<Vec<_>, _>>()?,
+    None => view_sorts
+      .iter()
+      .filter_map(|sort| view_sort(fields, sort))
+      .collect(),
+  };
+
+  let row_ids = database.row_ids();
+  let mut row_details = get_row_details(collab_storage, uid, &row_ids).await;
+  let mut rows: Vec<RowDetail> = row_ids
+    .iter()
+    .filter_map(|row_id| row_details.remove(row_id))
+    .filter(|row_detail| {
+      filter
+        .as_ref()
+        .map_or(true, |filter| row_matches(filter, row_detail))
+    })
+    .collect();
+  if !sorts.is_empty() {
+    // the sort is stable, so the rows that compare equal keep the order of the view
+    rows.sort_by(|a, b| {
+      sorts
+        .iter()
+        .map(|sort| {
+          compare_cells(
+            &sort.field,
+            cell_value(&sort.field, a).as_ref(),
+            cell_value(&sort.field, b).as_ref(),
+            sort.descending,
+          )
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+    });
+  }
+  Ok(rows)
+}
+
+fn resolve_filter(fields: &[Field], filter: DatabaseRowFilter) -> Result<RowFilter, AppError> {
+  let resolve_all = |filters: Vec<DatabaseRowFilter>| {
+    filters
+      .into_iter()
+      .map(|filter| resolve_filter(fields, filter))
+      .collect::<Result<Vec<_>, _>>()
+  };
+  match filter {
+    DatabaseRowFilter::And(filters) => Ok(RowFilter::And(resolve_all(filters)?)),
+    DatabaseRowFilter::Or(filters) => Ok(RowFilter::Or(resolve_all(filters)?)),
+    DatabaseRowFilter::Condition(condition) => {
+      let field = find_field(fields, &condition.field)?;
+      let invalid = || {
+        AppError::InvalidRequest(format!(
+          "Invalid filter {:?} {} on the field {}",
+          condition.op, condition.value, field.name
+        ))
+      };
+      if !op_applies(field, condition.op) {
+        return Err(invalid());
+      }
+      let value = match condition.op {
+        DatabaseFilterOp::IsEmpty | DatabaseFilterOp::IsNotEmpty => None,
+        _ => Some(filter_value(field, &condition.value).ok_or_else(invalid)?),
+      };
+      Ok(RowFilter::Condition {
+        field: field.clone(),
+        op: condition.op,
+        value,
+      })
+    },
+  }
+}
+
+fn resolve_sort(fields: &[Field], sort: DatabaseRowSort) -> Result<RowSort, AppError> {
+  Ok(RowSort {
+    field: find_field(fields, &sort.field)?.clone(),
+    descending: sort.descending,
+  })
+}
+
+fn op_applies(field: &Field, op: DatabaseFilterOp) -> bool {
+  use DatabaseFilterOp::*;
+  match FieldType::from(field.field_type) {
+    FieldType::Number | FieldType::DateTime => matches!(
+      op,
+      Is | IsNot
+        | IsEmpty
+        | IsNotEmpty
+        | GreaterThan
+        | LessThan
+        | GreaterThanOrEqual
+        | LessThanOrEqual
+    ),
+    FieldType::Checkbox => matches!(op, Is | IsNot),
+    FieldType::SingleSelect | FieldType::MultiSelect => {
+      matches!(
+        op,
+        Is | IsNot | Contains | DoesNotContain | IsEmpty | IsNotEmpty
+      )
+    },
+    _ => matches!(
+      op,
+      Is | IsNot | Contains | DoesNotContain | StartsWith | EndsWith | IsEmpty | IsNotEmpty
+    ),
+  }
+}
+
+/// The value a cell of the field is compared with, `None` if it is not valid for the field.
+fn filter_value(field: &Field, value: &Value) -> Option<CellValue> {
+  let text = match value {
+    Value::String(text) => Some(text.trim().to_string()),
+    Value::Number(number) => Some(number.to_string()),
+    Value::Bool(checked) => Some(checked.to_string()),
+    _ => None,
+  };
+  match FieldType::from(field.field_type) {
+    FieldType::Number => text?.parse().ok().map(CellValue::Number),
+    FieldType::Checkbox => match value {
+      Value::Bool(checked) => Some(CellValue::Checkbox(*checked)),
+      _ => None,
+    },
+    FieldType::DateTime => match value {
+      Value::Number(number) => number.as_i64().map(CellValue::Date),
+      Value::String(_) => parse_date(&text?).map(|(timestamp, _)| CellValue::Date(timestamp)),
+      _ => None,
+    },
+    FieldType::SingleSelect | FieldType::MultiSelect => {
+      let options = select_options(field).unwrap_or_default();
+      let names: Vec<String> = match value {
+        Value::Array(names) => names
+          .iter()
+          .map(|name| name.as_str().map(|name| name.trim().to_string()))
+          .collect::<Option<_>>()?,
+        _ => text?
+          .split(',')
+          .map(|name| name.trim().to_string())
+          .collect(),
+      };
+      names
+        .iter()
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+          options
+            .iter()
+            .find(|option| option.id == *name)
+            .or_else(|| options.iter().find(|option| option.name == *name))
+            .map(|option| option.id.clone())
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(CellValue::Options)
+    },
+    _ => text.map(|text| CellValue::Text(text.to_lowercase())),
+  }
+}
+
+/// The value of the cell of the field in the row, `None` if the cell is empty. Unchecked
+/// checkboxes are not empty.
+fn cell_value(field: &Field, row_detail: &RowDetail) -> Option<CellValue> {
+  let data = row_detail
+    .row
+    .cells
+    .get(&field.id)
+    .and_then(|cell| cell.get(CELL_DATA))
+    .map(|data| data.to_string())
+    .unwrap_or_default();
+  let data = data.trim();
+  match FieldType::from(field.field_type) {
+    FieldType::Checkbox => Some(CellValue::Checkbox(data == "Yes")),
+    _ if data.is_empty() => None,
+    FieldType::Number => data.parse().ok().map(CellValue::Number),
+    FieldType::DateTime => data.parse().ok().map(CellValue::Date),
+    FieldType::SingleSelect | FieldType::MultiSelect => Some(CellValue::Options(
+      data
+        .split(',')
+        .filter(|id| !id.is_empty())
+        .map(|id| id.to_string())
+        .collect(),
+    )),
+    _ => Some(CellValue::Text(data.to_lowercase())),
+  }
+}
+
+fn row_matches(filter: &RowFilter, row_detail: &RowDetail) -> bool {
+  match filter {
+    RowFilter::And(filters) => filters.iter().all(|filter| row_matches(filter, row_detail)),
+    RowFilter::Or(filters) => filters.iter().any(|filter| row_matches(filter, row_detail)),
+    RowFilter::Condition { field, op, value } => {
+      condition_matches(*op, cell_value(field, row_detail).as_ref(), value.as_ref())
+    },
+  }
+}
+
+fn condition_matches(
+  op: DatabaseFilterOp,
+  cell: Option<&CellValue>,
+  value: Option<&CellValue>,
+) -> bool {
+  use DatabaseFilterOp::*;
+  match op {
+    IsEmpty => return cell.is_none(),
+    IsNotEmpty => return cell.is_some(),
+    IsNot | DoesNotContain => {
+      let op = if op == IsNot { Is } else { Contains };
+      return !condition_matches(op, cell, value);
+    },
+    _ => {},
+  }
+  let (Some(cell), Some(value)) = (cell, value) else {
+    return false;
+  };
+  match (cell, value) {
+    (CellValue::Text(cell), CellValue::Text(value)) => match op {
+      Is => cell == value,
+      Contains => cell.contains(value.as_str()),
+      StartsWith => cell.starts_with(value.as_str()),
+      EndsWith => cell.ends_with(value.as_str()),
+      _ => false,
+    },
+    (CellValue::Checkbox(cell), CellValue::Checkbox(value)) => op == Is && cell == value,
+    (CellValue::Options(cell), CellValue::Options(value)) => {
+      matches!(op, Is | Contains) && cell.iter().any(|id| value.contains(id))
+    },
+    (CellValue::Number(cell), CellValue::Number(value)) => {
+      compare_matches(op, cell.partial_cmp(value))
+    },
+    (CellValue::Date(cell), CellValue::Date(value)) => compare_matches(
+      op,
+      Some(
+        cell
+          .div_euclid(SECONDS_PER_DAY)
+          .cmp(&value.div_euclid(SECONDS_PER_DAY)),
+      ),
+    ),
+    _ => false,
+  }
+}
+
+fn compare_matches(op: DatabaseFilterOp, ordering: Option<Ordering>) -> bool {
+  use DatabaseFilterOp::*;
+  let Some(ordering) = ordering else {
+    return false;
+  };
+  match op {
+    Is => ordering.is_eq(),
+    GreaterThan => ordering.is_gt(),
+    LessThan => ordering.is_lt(),
+    GreaterThanOrEqual => ordering.is_ge(),
+    LessThanOrEqual => ordering.is_le(),
+    _ => false,
+  }
+}
+
+/// Compare the cells of two rows. The empty cells come last, whatever the direction.
+fn compare_cells(
+  field: &Field,
+  a: Option<&CellValue>,
+  b: Option<&CellValue>,
+  descending: bool,
+) -> Ordering {
+  let (a, b) = match (a, b) {
+    (None, None) => return Ordering::Equal,
+    (None, Some(_)) => return Ordering::Greater,
+    (Some(_), None) => return Ordering::Less,
+    (Some(a), Some(b)) => (a, b),
+  };
+  let ordering = match (a, b) {
+    (CellValue::Text(a), CellValue::Text(b)) => a.cmp(b),
+    (CellValue::Number(a), CellValue::Number(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+    (CellValue::Checkbox(a), CellValue::Checkbox(b)) => a.cmp(b),
+    (CellValue::Date(a), CellValue::Date(b)) => a.cmp(b),
+    (CellValue::Options(a), CellValue::Options(b)) => {
+      // options are sorted in the order they are defined in the field
+      let options = select_options(field).unwrap_or_default();
+      let position = |ids: &Vec<String>| {
+        ids
+          .iter()
+          .filter_map(|id| options.iter().position(|option| option.id == *id))
+          .collect::<Vec<_>>()
+      };
+      position(a).cmp(&position(b))
+    },
+    _ => Ordering::Equal,
+  };
+  if descending {
+    ordering.reverse()
+  } else {
+    ordering
+  }
+}
+
+/// Convert a filter saved in the database view by the AppFlowy clients. The filters that can't
+/// be evaluated on the server are left out.
+fn view_filter(fields: &[Field], filter: &HashMap<String, Any>) -> Option<RowFilter> {
+  // 0: and, 1: or, 2: a condition on a field. Filters saved by older clients have no type
+  match filter.get("filter_type").and_then(any_i64).unwrap_or(2) {
+    filter_type @ (0 | 1) => {
+      let children: Vec<RowFilter> = match filter.get("children") {
+        Some(Any::Array(children)) => children
+          .iter()
+          .filter_map(|child| match child {
+            Any::Map(child) => view_filter(fields, child),
+            _ => None,
+          })
+          .collect(),
+        _ => vec![],
+      };
+      if children.is_empty() {
+        return None;
+      }
+      if filter_type == 0 {
+        Some(RowFilter::And(children))
+      } else {
+        Some(RowFilter::Or(children))
+      }
+    },
+    _ => {
+      let field_id = any_str(filter.get("field_id")?)?;
+      let field = fields.iter().find(|field| field.id == field_id)?;
+      let condition = filter.get("condition").and_then(any_i64)?;
+      let content = filter.get("content").and_then(any_str).unwrap_or_default();
+      let filter = view_condition(field, condition, &content);
+      if filter.is_none() {
+        debug!(
+          "Skip the filter {} of the field {}, it can't be evaluated on the server",
+          condition, field.name
+        );
+      }
+      filter
+    },
+  }
+}
+
+fn view_condition(field: &Field, condition: i64, content: &str) -> Option<RowFilter> {
+  use DatabaseFilterOp::*;
+  let filter = |op: DatabaseFilterOp, value: Option<CellValue>| {
+    Some(RowFilter::Condition {
+      field: field.clone(),
+      op,
+      value,
+    })
+  };
+  match FieldType::from(field.field_type) {
+    FieldType::Number => {
+      let op = [
+        Is,
+        IsNot,
+        GreaterThan,
+        LessThan,
+        GreaterThanOrEqual,
+        LessThanOrEqual,
+        IsEmpty,
+        IsNotEmpty,
+      ]
+      .get(usize::try_from(condition).ok()?)
+      .copied()?;
+      let value = content.trim().parse().ok().map(CellValue::Number);
+      filter(op, value)
+    },
+    FieldType::Checkbox => match condition {
+      0 => filter(Is, Some(CellValue::Checkbox(true))),
+      1 => filter(Is, Some(CellValue::Checkbox(false))),
+      _ => None,
+    },
+    FieldType::SingleSelect | FieldType::MultiSelect => {
+      let op = [Is, IsNot, Contains, DoesNotContain, IsEmpty, IsNotEmpty]
+        .get(usize::try_from(condition).ok()?)
+        .copied()?;
+      let option_ids = content
+        .split(',')
+        .filter(|id| !id.is_empty())
+        .map(|id| id.to_string())
+        .collect();
+      filter(op, Some(CellValue::Options(option_ids)))
+    },
+    FieldType::DateTime => {
+      let content: Value = serde_json::from_str(content).unwrap_or_default();
+      let timestamp = |key: &str| {
+        content
+          .get(key)
+          .and_then(Value::as_i64)
+          .map(CellValue::Date)
+      };
+      match condition {
+        0..=4 => {
+          let op = [
+            Is,
+            LessThan,
+            GreaterThan,
+            LessThanOrEqual,
+            GreaterThanOrEqual,
+          ][condition as usize];
+          filter(op, timestamp("timestamp"))
+        },
+        5 => Some(RowFilter::And(vec![
+          filter(GreaterThanOrEqual, timestamp("start"))?,
+          filter(LessThanOrEqual, timestamp("end"))?,
+        ])),
+        6 => filter(IsEmpty, None),
+        7 => filter(IsNotEmpty, None),
+        _ => None,
+      }
+    },
+    FieldType::RichText | FieldType::URL => {
+      let op = [
+        Is,
+        IsNot,
+        Contains,
+        DoesNotContain,
+        StartsWith,
+        EndsWith,
+        IsEmpty,
+        IsNotEmpty,
+      ]
+      .get(usize::try_from(condition).ok()?)
+      .copied()?;
+      filter(op, Some(CellValue::Text(content.trim().to_lowercase())))
+    },
+    _ => None,
+  }
+}
+
+/// Convert a sort saved in the database view by the AppFlowy clients.
+fn view_sort(fields: &[Field], sort: &HashMap<String, Any>) -> Option<RowSort> {
+  let field_id = any_str(sort.get("field_id")?)?;
+  let field = fields.iter().find(|field| field.id == field_id)?;
+  // 0: ascending, 1: descending
+  let descending = sort.get("condition").and_then(any_i64) == Some(1);
+  Some(RowSort {
+    field: field.clone(),
+    descending,
+  })
+}
+
+pub(crate) fn any_i64(value: &Any) -> Option<i64> {
+  match value {
+    Any::BigInt(n) => Some(*n),
+    Any::Number(n) => Some(*n as i64),
+    Any::String(s) => s.parse().ok(),
+    _ => None,
+  }
+}
+
+fn any_str(value: &Any) -> Option<String> {
+  match value {
+    Any::String(s) => Some(s.to_string()),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use DatabaseFilterOp::*;
+
+  fn text(value: &str) -> CellValue {
+    CellValue::Text(value.to_string())
+  }
+
+  #[test]
+  fn condition_matches_test() {
+    let cell = text("release notes");
+    assert!(condition_matches(
+      Contains,
+      Some(&cell),
+      Some(&text("notes"))
+    ));
+    assert!(condition_matches(
+      StartsWith,
+      Some(&cell),
+      Some(&text("rel"))
+    ));
+    assert!(condition_matches(
+      DoesNotContain,
+      Some(&cell),
+      Some(&text("draft"))
+    ));
+    assert!(!condition_matches(Is, Some(&cell), Some(&text("release"))));
+    assert!(condition_matches(IsNot, None, Some(&text("release"))));
+    assert!(condition_matches(IsEmpty, None, None));
+    assert!(!condition_matches(IsNotEmpty, None, None));
+
+    let number = CellValue::Number(3.0);
+    assert!(condition_matches(
+      GreaterThan,
+      Some(&number),
+      Some(&CellValue::Number(2.5))
+    ));
+    assert!(condition_matches(
+      LessThanOrEqual,
+      Some(&number),
+      Some(&CellValue::Number(3.0))
+    ));
+    assert!(!condition_matches(
+      LessThan,
+      None,
+      Some(&CellValue::Number(3.0))
+    ));
+
+    // dates are compared by day
+    let date = CellValue::Date(SECONDS_PER_DAY + 3600);
+    assert!(condition_matches(
+      Is,
+      Some(&date),
+      Some(&CellValue::Date(SECONDS_PER_DAY))
+    ));
+    assert!(condition_matches(
+      GreaterThan,
+      Some(&date),
+      Some(&CellValue::Date(0))
+    ));
+
+    let options = CellValue::Options(vec!["a".to_string(), "b".to_string()]);
+    let b = CellValue::Options(vec!["b".to_string()]);
+    let c = CellValue::Options(vec!["c".to_string()]);
+    assert!(condition_matches(Is, Some(&options), Some(&b)));
+    assert!(condition_matches(IsNot, Some(&options), Some(&c)));
+    assert!(!condition_matches(Contains, Some(&options), Some(&c)));
+
+    let checked = CellValue::Checkbox(true);
+    assert!(condition_matches(
+      Is,
+      Some(&checked),
+      Some(&CellValue::Checkbox(true))
+    ));
+    assert!(condition_matches(
+      IsNot,
+      Some(&checked),
+      Some(&CellValue::Checkbox(false))
+    ));
+  }
+
+  #[test]
+  fn compare_cells_test() {
+    let field = Field::new("id".to_string(), "Field".to_string(), 0, false);
+    let a = text("a");
+    let b = text("b");
+    assert_eq!(
+      compare_cells(&field, Some(&a), Some(&b), false),
+      Ordering::Less
+    );
+    assert_eq!(
+      compare_cells(&field, Some(&a), Some(&b), true),
+      Ordering::Greater
+    );
+    // empty cells come last in both directions
+    assert_eq!(
+      compare_cells(&field, None, Some(&a), false),
+      Ordering::Greater
+    );
+    assert_eq!(
+      compare_cells(&field, None, Some(&a), true),
+      Ordering::Greater
+    );
+    assert_eq!(
+      compare_cells(
+        &field,
+        Some(&CellValue::Number(10.0)),
+        Some(&CellValue::Number(9.0)),
+        false
+      ),
+      Ordering::Greater
+    );
+  }
+}
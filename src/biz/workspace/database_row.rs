@@ -0,0 +1,844 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use access_control::act::Action;
+use access_control::collab::CollabAccessControl;
+use anyhow::Context;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use chrono::{DateTime, NaiveDate, Utc};
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
+use collab_database::database::{gen_row_id, DatabaseBody};
+use collab_database::entity::FieldType;
+use collab_database::fields::Field;
+use collab_database::rows::{Cell, DatabaseRowBody, Row, RowDetail, CELL_FIELD_TYPE};
+use collab_database::template::entity::CELL_DATA;
+use collab_database::views::{OrderObjectPosition, RowOrder};
+use collab_database::workspace_database::WorkspaceDatabaseBody;
+use collab_entity::{CollabType, EncodedCollab};
+use database::collab::{select_workspace_database_oid, CollabStorage, GetCollabOrigin};
+use database_entity::dto::{CollabParams, QueryCollab, QueryCollabResult};
+use serde_json::{json, Value};
+use shared_entity::dto::workspace_dto::{
+  CreateDatabaseRowParams, DatabaseField, DatabaseRow, DatabaseRows, QueryDatabaseRows,
+  UpdateDatabaseRowParams, WebhookEvent,
+};
+use sqlx::{PgPool, Postgres, Transaction};
+use tracing::warn;
+use uuid::Uuid;
+use yrs::Any;
+
+use crate::biz::collab::ops::get_latest_collab_encoded;
+use crate::biz::workspace::webhook::enqueue_webhook_event_or_log;
+
+use super::export::{cell_text, select_options};
+use super::publish_dup::{broadcast_update, collab_from_doc_state, collab_to_bin};
+
+const DEFAULT_DATABASE_ROWS_LIMIT: usize = 100;
+const MAX_DATABASE_ROWS_LIMIT: usize = 1000;
+
+/// The database collab a database view belongs to.
+pub(crate) struct ViewDatabase {
+  pub database_id: String,
+  pub view_id: String,
+  pub collab: Collab,
+  pub body: DatabaseBody,
+}
+
+impl ViewDatabase {
+  /// The fields of the database, in the order of the view.
+  pub fn fields(&self) -> Result<Vec<Field>, AppError> {
+    let txn = self.collab.transact();
+    let mut fields = self.body.fields.get_all_fields(&txn);
+    let view = self
+      .body
+      .views
+      .get_all_views(&txn)
+      .into_iter()
+      .find(|view| view.id == self.view_id)
+      .ok_or_else(|| {
+        AppError::RecordNotFound(format!("database view {} not found", self.view_id))
+      })?;
+    let field_positions: HashMap<String, usize> = view
+      .field_orders
+      .iter()
+      .enumerate()
+      .map(|(position, field_order)| (field_order.id.clone(), position))
+      .collect();
+    fields.sort_by_key(|field| {
+      field_positions
+        .get(&field.id)
+        .copied()
+        .unwrap_or(usize::MAX)
+    });
+    Ok(fields)
+  }
+
+  /// The ids of the rows, in the order of the view.
+  pub fn row_ids(&self) -> Vec<String> {
+    let txn = self.collab.transact();
+    self
+      .body
+      .views
+      .get_row_orders(&txn, &self.view_id)
+      .into_iter()
+      .map(|row_order| row_order.id.to_string())
+      .collect()
+  }
+}
+
+/// Open the database the view belongs to, as the user or as the server.
+pub(crate) async fn open_view_database(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  origin: GetCollabOrigin,
+  workspace_id: &Uuid,
+  view_id: &str,
+) -> Result<ViewDatabase, AppError> {
+  let workspace_id_str = workspace_id.to_string();
+  let ws_db_oid = select_workspace_database_oid(pg_pool, workspace_id).await?;
+  let ws_db = get_latest_collab_encoded(
+    collab_storage.clone(),
+    origin,
+    &workspace_id_str,
+    &ws_db_oid,
+    CollabType::WorkspaceDatabase,
+  )
+  .await?;
+  let database_id = {
+    let mut ws_db_collab = collab_from_doc_state(ws_db.doc_state.to_vec(), &ws_db_oid)?;
+    let ws_db_body = WorkspaceDatabaseBody::open(&mut ws_db_collab);
+    let txn = ws_db_collab.transact();
+    ws_db_body
+      .get_database_meta_with_view_id(&txn, view_id)
+      .ok_or_else(|| AppError::RecordNotFound(format!("database view {} not found", view_id)))?
+      .database_id
+  };
+  let db = get_latest_collab_encoded(
+    collab_storage,
+    origin,
+    &workspace_id_str,
+    &database_id,
+    CollabType::Database,
+  )
+  .await?;
+  let collab = collab_from_doc_state(db.doc_state.to_vec(), &database_id)?;
+  let body = DatabaseBody::from_collab(&collab)
+    .ok_or_else(|| AppError::RecordNotFound(format!("database {} not found", database_id)))?;
+  Ok(ViewDatabase {
+    database_id,
+    view_id: view_id.to_string(),
+    collab,
+    body,
+  })
+}
+
+pub async fn list_database_rows(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &str,
+  query: QueryDatabaseRows,
+) -> Result<DatabaseRows, AppError> {
+  let database = open_view_database(
+    collab_storage.clone(),
+    pg_pool,
+    GetCollabOrigin::User { uid },
+    workspace_id,
+    view_id,
+  )
+  .await?;
+  let fields = database.fields()?;
+  let row_ids = database.row_ids();
+  let total = row_ids.len();
+  let limit = query
+    .limit
+    .unwrap_or(DEFAULT_DATABASE_ROWS_LIMIT)
+    .min(MAX_DATABASE_ROWS_LIMIT);
+  let row_ids: Vec<String> = row_ids
+    .into_iter()
+    .skip(query.offset.unwrap_or(0))
+    .take(limit)
+    .collect();
+
+  let mut row_details = get_row_details(collab_storage, uid, &row_ids).await;
+  let rows = row_ids
+    .iter()
+    .filter_map(|row_id| row_details.remove(row_id))
+    .map(|row_detail| database_row(&fields, &row_detail))
+    .collect();
+  Ok(DatabaseRows {
+    fields: fields.iter().map(database_field).collect(),
+    rows,
+    total,
+  })
+}
+
+/// Add a row at the end of every view of the database. The row is created as a new collab and
+/// the update of the database is sent to the clients editing it.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_database_row(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  collab_access_control: &impl CollabAccessControl,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &str,
+  params: CreateDatabaseRowParams,
+) -> Result<DatabaseRow, AppError> {
+  let database = open_view_database(
+    collab_storage.clone(),
+    pg_pool,
+    GetCollabOrigin::User { uid },
+    workspace_id,
+    view_id,
+  )
+  .await?;
+  enforce_database_write(
+    collab_access_control,
+    workspace_id,
+    uid,
+    &database.database_id,
+  )
+  .await?;
+  let fields = database.fields()?;
+  let cells = resolve_cells(&fields, params.cells)?
+    .into_iter()
+    .filter_map(|(field_id, cell)| cell.map(|cell| (field_id, cell)))
+    .collect();
+  let mut row_details = insert_database_rows(
+    collab_storage,
+    pg_pool,
+    uid,
+    workspace_id,
+    database,
+    vec![cells],
+  )
+  .await?;
+  let row_detail = row_details
+    .pop()
+    .ok_or_else(|| AppError::Internal(anyhow::anyhow!("the created row is missing")))?;
+  Ok(database_row(&fields, &row_detail))
+}
+
+/// Create the rows with the cells, by field id, and add them at the end of every view of the
+/// database. All the rows are saved with the database in a single transaction.
+pub(crate) async fn insert_database_rows(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  database: ViewDatabase,
+  rows: Vec<HashMap<String, Cell>>,
+) -> Result<Vec<RowDetail>, AppError> {
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to update database")?;
+  let (row_details, inserted_rows) = insert_database_rows_with_transaction(
+    &collab_storage,
+    uid,
+    workspace_id,
+    database,
+    rows,
+    &mut txn,
+  )
+  .await?;
+  txn
+    .commit()
+    .await
+    .context("fail to commit the transaction to update database")?;
+  inserted_rows
+    .notify(&collab_storage, pg_pool, uid, workspace_id)
+    .await?;
+  Ok(row_details)
+}
+
+/// The update of the database that added rows, to send once the rows are committed.
+pub(crate) struct InsertedDatabaseRows {
+  database_id: String,
+  encoded_update: Vec<u8>,
+}
+
+impl InsertedDatabaseRows {
+  /// Send the update to the clients editing the database, and to the webhooks of the workspace.
+  pub async fn notify(
+    self,
+    collab_storage: &Arc<CollabAccessControlStorage>,
+    pg_pool: &PgPool,
+    uid: i64,
+    workspace_id: &Uuid,
+  ) -> Result<(), AppError> {
+    broadcast_update(collab_storage, &self.database_id, self.encoded_update).await?;
+    enqueue_webhook_event_or_log(
+      pg_pool,
+      workspace_id,
+      WebhookEvent::CollabUpdated,
+      json!({ "object_id": self.database_id, "collab_type": CollabType::Database, "uid": uid }),
+    )
+    .await;
+    Ok(())
+  }
+}
+
+/// Same as [insert_database_rows], within the given transaction. The returned update is sent with
+/// [InsertedDatabaseRows::notify] after the transaction is committed.
+pub(crate) async fn insert_database_rows_with_transaction(
+  collab_storage: &Arc<CollabAccessControlStorage>,
+  uid: i64,
+  workspace_id: &Uuid,
+  database: ViewDatabase,
+  rows: Vec<HashMap<String, Cell>>,
+  txn: &mut Transaction<'_, Postgres>,
+) -> Result<(Vec<RowDetail>, InsertedDatabaseRows), AppError> {
+  let ViewDatabase {
+    database_id,
+    collab: mut db_collab,
+    body: db_body,
+    ..
+  } = database;
+  let mut row_collabs = Vec::with_capacity(rows.len());
+  let mut row_details = Vec::with_capacity(rows.len());
+  let mut row_orders = Vec::with_capacity(rows.len());
+  for cells in rows {
+    let row_id = gen_row_id();
+    let mut row = Row::new(row_id.clone(), &database_id);
+    for (field_id, cell) in cells {
+      row.cells.insert(field_id, cell);
+    }
+    row_orders.push(RowOrder::new(row_id.clone(), row.height));
+    let mut row_collab =
+      Collab::new_with_origin(CollabOrigin::Server, row_id.as_str(), vec![], false);
+    DatabaseRowBody::create(row_id.clone(), &mut row_collab, row);
+    let row_detail = RowDetail::from_collab(&row_collab)
+      .ok_or_else(|| AppError::Internal(anyhow::anyhow!("failed to create row {}", row_id)))?;
+    row_details.push(row_detail);
+    row_collabs.push((row_id.to_string(), row_collab));
+  }
+
+  let encoded_update = {
+    let mut txn = db_collab.context.transact_mut();
+    for view in db_body.views.get_all_views(&txn) {
+      db_body
+        .views
+        .update_database_view(&mut txn, &view.id, |mut update| {
+          for row_order in &row_orders {
+            update = update.insert_row_order(row_order, &OrderObjectPosition::End);
+          }
+        });
+    }
+    txn.encode_update_v1()
+  };
+
+  let mut collabs = Vec::with_capacity(row_collabs.len() + 1);
+  for (row_id, row_collab) in row_collabs {
+    collabs.push(CollabParams {
+      object_id: row_id,
+      encoded_collab_v1: collab_to_bin(row_collab, CollabType::DatabaseRow)
+        .await?
+        .into(),
+      collab_type: CollabType::DatabaseRow,
+      embeddings: None,
+    });
+  }
+  collabs.push(CollabParams {
+    object_id: database_id.clone(),
+    encoded_collab_v1: collab_to_bin(db_collab, CollabType::Database).await?.into(),
+    collab_type: CollabType::Database,
+    embeddings: None,
+  });
+  let workspace_id = workspace_id.to_string();
+  for params in collabs {
+    collab_storage
+      .insert_new_collab_with_transaction(&workspace_id, &uid, params, txn)
+      .await?;
+  }
+  Ok((
+    row_details,
+    InsertedDatabaseRows {
+      database_id,
+      encoded_update,
+    },
+  ))
+}
+
+/// Change the cells of a row of the database view. The update of the row is sent to the clients
+/// editing it.
+#[allow(clippy::too_many_arguments)]
+pub async fn update_database_row(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  collab_access_control: &impl CollabAccessControl,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &str,
+  row_id: &str,
+  params: UpdateDatabaseRowParams,
+) -> Result<DatabaseRow, AppError> {
+  let database = open_view_database(
+    collab_storage.clone(),
+    pg_pool,
+    GetCollabOrigin::User { uid },
+    workspace_id,
+    view_id,
+  )
+  .await?;
+  enforce_database_write(
+    collab_access_control,
+    workspace_id,
+    uid,
+    &database.database_id,
+  )
+  .await?;
+  if !database.row_ids().iter().any(|id| id == row_id) {
+    return Err(AppError::RecordNotFound(format!(
+      "row {} not found in database view {}",
+      row_id, view_id
+    )));
+  }
+  let fields = database.fields()?;
+  let cells = resolve_cells(&fields, params.cells)?;
+
+  let workspace_id_str = workspace_id.to_string();
+  let row = get_latest_collab_encoded(
+    collab_storage.clone(),
+    GetCollabOrigin::User { uid },
+    &workspace_id_str,
+    row_id,
+    CollabType::DatabaseRow,
+  )
+  .await?;
+  let mut row_collab = collab_from_doc_state(row.doc_state.to_vec(), row_id)?;
+  let row_body = DatabaseRowBody::open(row_id.to_string().into(), &mut row_collab)
+    .map_err(|err| AppError::Unhandled(err.to_string()))?;
+  let encoded_update = {
+    let mut txn = row_collab.context.transact_mut();
+    row_body.update(&mut txn, |update| {
+      update
+        .set_last_modified(Utc::now().timestamp())
+        .update_cells(|mut cells_update| {
+          for (field_id, cell) in cells {
+            cells_update = match cell {
+              Some(cell) => cells_update.insert_cell(&field_id, cell),
+              None => cells_update.clear(&field_id),
+            };
+          }
+        });
+    });
+    txn.encode_update_v1()
+  };
+  let row_detail = RowDetail::from_collab(&row_collab)
+    .ok_or_else(|| AppError::RecordNotFound(format!("row {} not found", row_id)))?;
+
+  let collab = CollabParams {
+    object_id: row_id.to_string(),
+    encoded_collab_v1: collab_to_bin(row_collab, CollabType::DatabaseRow)
+      .await?
+      .into(),
+    collab_type: CollabType::DatabaseRow,
+    embeddings: None,
+  };
+  save_database_collabs(
+    collab_storage.clone(),
+    pg_pool,
+    uid,
+    workspace_id,
+    vec![collab],
+  )
+  .await?;
+  broadcast_update(&collab_storage, row_id, encoded_update).await?;
+  enqueue_webhook_event_or_log(
+    pg_pool,
+    workspace_id,
+    WebhookEvent::CollabUpdated,
+    json!({ "object_id": row_id, "collab_type": CollabType::DatabaseRow, "uid": uid }),
+  )
+  .await;
+  Ok(database_row(&fields, &row_detail))
+}
+
+/// Remove the row from every view of the database, then delete the collab of the row.
+pub async fn delete_database_row(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  collab_access_control: &impl CollabAccessControl,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &str,
+  row_id: &str,
+) -> Result<(), AppError> {
+  let database = open_view_database(
+    collab_storage.clone(),
+    pg_pool,
+    GetCollabOrigin::User { uid },
+    workspace_id,
+    view_id,
+  )
+  .await?;
+  enforce_database_write(
+    collab_access_control,
+    workspace_id,
+    uid,
+    &database.database_id,
+  )
+  .await?;
+  if !database.row_ids().iter().any(|id| id == row_id) {
+    return Err(AppError::RecordNotFound(format!(
+      "row {} not found in database view {}",
+      row_id, view_id
+    )));
+  }
+  let ViewDatabase {
+    database_id,
+    collab: mut db_collab,
+    body: db_body,
+    ..
+  } = database;
+  let encoded_update = {
+    let mut txn = db_collab.context.transact_mut();
+    for view in db_body.views.get_all_views(&txn) {
+      db_body
+        .views
+        .update_database_view(&mut txn, &view.id, |update| {
+          update.remove_row_order(row_id);
+        });
+    }
+    txn.encode_update_v1()
+  };
+  let collab = CollabParams {
+    object_id: database_id.clone(),
+    encoded_collab_v1: collab_to_bin(db_collab, CollabType::Database).await?.into(),
+    collab_type: CollabType::Database,
+    embeddings: None,
+  };
+  save_database_collabs(
+    collab_storage.clone(),
+    pg_pool,
+    uid,
+    workspace_id,
+    vec![collab],
+  )
+  .await?;
+  broadcast_update(&collab_storage, &database_id, encoded_update).await?;
+  // The row is no longer part of the database, so it is only left behind if this fails
+  if let Err(err) = collab_storage
+    .delete_collab(&workspace_id.to_string(), &uid, row_id)
+    .await
+  {
+    warn!(
+      "Failed to delete the collab of database row {}: {}",
+      row_id, err
+    );
+  }
+  enqueue_webhook_event_or_log(
+    pg_pool,
+    workspace_id,
+    WebhookEvent::CollabUpdated,
+    json!({ "object_id": database_id, "collab_type": CollabType::Database, "uid": uid }),
+  )
+  .await;
+  Ok(())
+}
+
+pub(crate) async fn enforce_database_write(
+  collab_access_control: &impl CollabAccessControl,
+  workspace_id: &Uuid,
+  uid: i64,
+  database_id: &str,
+) -> Result<(), AppError> {
+  let can_write = collab_access_control
+    .enforce_action(&workspace_id.to_string(), &uid, database_id, Action::Write)
+    .await?;
+  if !can_write {
+    return Err(AppError::NotEnoughPermissions {
+      user: uid.to_string(),
+      action: format!("update database:{}", database_id),
+    });
+  }
+  Ok(())
+}
+
+pub(crate) async fn save_database_collabs(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  collabs: Vec<CollabParams>,
+) -> Result<(), AppError> {
+  let workspace_id = workspace_id.to_string();
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to update database")?;
+  for params in collabs {
+    collab_storage
+      .insert_new_collab_with_transaction(&workspace_id, &uid, params, &mut txn)
+      .await?;
+  }
+  txn
+    .commit()
+    .await
+    .context("fail to commit the transaction to update database")?;
+  Ok(())
+}
+
+/// Returns the rows that could be read, by row id.
+pub(crate) async fn get_row_details(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  uid: i64,
+  row_ids: &[String],
+) -> HashMap<String, RowDetail> {
+  let queries = row_ids
+    .iter()
+    .map(|row_id| QueryCollab {
+      object_id: row_id.clone(),
+      collab_type: CollabType::DatabaseRow,
+    })
+    .collect();
+  collab_storage
+    .batch_get_collab(&uid, queries)
+    .await
+    .into_iter()
+    .filter_map(|(row_id, result)| {
+      let encode_collab_v1 = match result {
+        QueryCollabResult::Success { encode_collab_v1 } => encode_collab_v1,
+        QueryCollabResult::Failed { error } => {
+          warn!("Failed to get database row {}: {}", row_id, error);
+          return None;
+        },
+      };
+      let row_detail = EncodedCollab::decode_from_bytes(&encode_collab_v1)
+        .ok()
+        .and_then(|encoded| collab_from_doc_state(encoded.doc_state.to_vec(), &row_id).ok())
+        .and_then(|row_collab| RowDetail::from_collab(&row_collab));
+      match row_detail {
+        Some(row_detail) => Some((row_id, row_detail)),
+        None => {
+          warn!("Failed to open database row {}", row_id);
+          None
+        },
+      }
+    })
+    .collect()
+}
+
+pub(crate) fn database_field(field: &Field) -> DatabaseField {
+  DatabaseField {
+    id: field.id.clone(),
+    name: field.name.clone(),
+    field_type: field.field_type,
+    is_primary: field.is_primary,
+  }
+}
+
+pub(crate) fn database_row(fields: &[Field], row_detail: &RowDetail) -> DatabaseRow {
+  let cells = fields
+    .iter()
+    .filter_map(|field| {
+      let text = cell_text(field, row_detail.row.cells.get(&field.id)?);
+      (!text.is_empty()).then(|| (field.id.clone(), text))
+    })
+    .collect();
+  DatabaseRow {
+    id: row_detail.row.id.to_string(),
+    cells,
+    created_at: row_detail.row.created_at,
+    last_modified: row_detail.row.modified_at,
+  }
+}
+
+/// Turn the values given by field id or name into cells, by field id. `None` clears the cell.
+pub(crate) fn resolve_cells(
+  fields: &[Field],
+  values: HashMap<String, Value>,
+) -> Result<HashMap<String, Option<Cell>>, AppError> {
+  values
+    .into_iter()
+    .map(|(key, value)| {
+      let field = find_field(fields, &key)?;
+      Ok((field.id.clone(), cell_from_value(field, &value)?))
+    })
+    .collect()
+}
+
+/// Find the field by id, or else by name.
+pub(crate) fn find_field<'a>(fields: &'a [Field], key: &str) -> Result<&'a Field, AppError> {
+  fields
+    .iter()
+    .find(|field| field.id == key)
+    .or_else(|| fields.iter().find(|field| field.name == key))
+    .ok_or_else(|| AppError::InvalidRequest(format!("The database has no field {}", key)))
+}
+
+/// Convert the value given for the field to a cell, `None` if the cell is cleared.
+fn cell_from_value(field: &Field, value: &Value) -> Result<Option<Cell>, AppError> {
+  let invalid = || {
+    AppError::InvalidRequest(format!(
+      "Invalid value {} for the field {}",
+      value, field.name
+    ))
+  };
+  let text = match value {
+    Value::Null => return Ok(None),
+    Value::String(text) => text.trim().to_string(),
+    Value::Number(number) => number.to_string(),
+    Value::Bool(checked) => checked.to_string(),
+    Value::Array(_) | Value::Object(_) => String::new(),
+  };
+  let mut include_time = None;
+  let data = match FieldType::from(field.field_type) {
+    FieldType::RichText | FieldType::URL => match value {
+      Value::Array(_) | Value::Object(_) => return Err(invalid()),
+      _ => text,
+    },
+    FieldType::Number => match value {
+      Value::Number(_) => text,
+      Value::String(_) if text.is_empty() => return Ok(None),
+      Value::String(_) if text.parse::<f64>().is_ok() => text,
+      _ => return Err(invalid()),
+    },
+    FieldType::Checkbox => match text.to_lowercase().as_str() {
+      "true" | "yes" => "Yes".to_string(),
+      "false" | "no" => "No".to_string(),
+      _ => return Err(invalid()),
+    },
+    FieldType::DateTime => {
+      let (timestamp, with_time) = match value {
+        Value::Number(number) => (number.as_i64().ok_or_else(invalid)?, true),
+        Value::String(_) if text.is_empty() => return Ok(None),
+        Value::String(_) => parse_date(&text).ok_or_else(invalid)?,
+        _ => return Err(invalid()),
+      };
+      include_time = Some(with_time);
+      timestamp.to_string()
+    },
+    FieldType::SingleSelect | FieldType::MultiSelect => {
+      let options = select_options(field).unwrap_or_default();
+      let names: Vec<String> = match value {
+        Value::Array(names) => names
+          .iter()
+          .map(|name| name.as_str().map(|name| name.trim().to_string()))
+          .collect::<Option<_>>()
+          .ok_or_else(invalid)?,
+        Value::String(_) => text
+          .split(',')
+          .map(|name| name.trim().to_string())
+          .collect(),
+        _ => return Err(invalid()),
+      };
+      let option_ids = names
+        .iter()
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+          options
+            .iter()
+            .find(|option| option.id == *name)
+            .or_else(|| options.iter().find(|option| option.name == *name))
+            .map(|option| option.id.clone())
+            .ok_or_else(|| {
+              AppError::InvalidRequest(format!("The field {} has no option {}", field.name, name))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+      if option_ids.is_empty() {
+        return Ok(None);
+      }
+      if option_ids.len() > 1 && field.field_type == FieldType::SingleSelect as i64 {
+        return Err(invalid());
+      }
+      option_ids.join(",")
+    },
+    _ => {
+      return Err(AppError::InvalidRequest(format!(
+        "The cells of the field {} can't be set through the API",
+        field.name
+      )))
+    },
+  };
+  let mut cell = Cell::from([
+    (CELL_FIELD_TYPE.to_string(), Any::BigInt(field.field_type)),
+    (CELL_DATA.to_string(), Any::String(data.into())),
+  ]);
+  if let Some(include_time) = include_time {
+    cell.insert("include_time".to_string(), Any::Bool(include_time));
+  }
+  Ok(Some(cell))
+}
+
+/// Parse an RFC 3339 date and time, or a `YYYY-MM-DD` date, into a timestamp in seconds. Returns
+/// whether the time is included.
+pub(crate) fn parse_date(text: &str) -> Option<(i64, bool)> {
+  if let Ok(date_time) = DateTime::parse_from_rfc3339(text) {
+    return Some((date_time.timestamp(), true));
+  }
+  let date = NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?;
+  Some((date.and_hms_opt(0, 0, 0)?.and_utc().timestamp(), false))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn field(field_type: FieldType) -> Field {
+    Field::new(
+      "id".to_string(),
+      "Field".to_string(),
+      field_type as i64,
+      false,
+    )
+  }
+
+  fn cell_data(field: &Field, value: Value) -> Option<String> {
+    cell_from_value(field, &value)
+      .unwrap()
+      .map(|cell| cell.get(CELL_DATA).unwrap().to_string())
+  }
+
+  #[test]
+  fn cell_from_value_test() {
+    let text = field(FieldType::RichText);
+    assert_eq!(
+      cell_data(&text, json!(" hello ")),
+      Some("hello".to_string())
+    );
+    assert_eq!(cell_data(&text, json!(12)), Some("12".to_string()));
+    assert_eq!(cell_data(&text, Value::Null), None);
+    assert!(cell_from_value(&text, &json!(["a"])).is_err());
+
+    let number = field(FieldType::Number);
+    assert_eq!(cell_data(&number, json!(1.5)), Some("1.5".to_string()));
+    assert_eq!(cell_data(&number, json!("42")), Some("42".to_string()));
+    assert!(cell_from_value(&number, &json!("many")).is_err());
+
+    let checkbox = field(FieldType::Checkbox);
+    assert_eq!(cell_data(&checkbox, json!(true)), Some("Yes".to_string()));
+    assert_eq!(cell_data(&checkbox, json!("no")), Some("No".to_string()));
+    assert!(cell_from_value(&checkbox, &json!("maybe")).is_err());
+
+    let date = field(FieldType::DateTime);
+    assert_eq!(
+      cell_data(&date, json!("2024-11-01")),
+      Some("1730419200".to_string())
+    );
+    assert_eq!(
+      cell_data(&date, json!("2024-11-01T10:00:00+02:00")),
+      Some("1730448000".to_string())
+    );
+    assert!(cell_from_value(&date, &json!("yesterday")).is_err());
+
+    let select = field(FieldType::SingleSelect);
+    assert_eq!(cell_data(&select, json!("")), None);
+    assert!(cell_from_value(&select, &json!("Done")).is_err());
+
+    assert!(cell_from_value(&field(FieldType::Checklist), &json!("a")).is_err());
+  }
+
+  #[test]
+  fn parse_date_test() {
+    assert_eq!(parse_date("1970-01-02"), Some((86400, false)));
+    assert_eq!(parse_date("1970-01-01T00:01:00Z"), Some((60, true)));
+    assert_eq!(parse_date("01/02/1970"), None);
+  }
+}
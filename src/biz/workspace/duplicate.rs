@@ -0,0 +1,932 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::DerefMut;
+use std::sync::Arc;
+
+use access_control::workspace::WorkspaceAccessControl;
+use anyhow::{anyhow, Context};
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
+use collab_database::database::DatabaseBody;
+use collab_database::rows::{meta_id_from_row_id, DatabaseRowBody, RowMetaKey};
+use collab_database::workspace_database::WorkspaceDatabaseBody;
+use collab_entity::{CollabType, EncodedCollab};
+use collab_folder::{Folder, ViewLayout};
+use database::collab::{select_workspace_database_oid, CollabStorage, GetCollabOrigin};
+use database::pg_row::{AFWorkspaceDuplicateTaskRow, AFWorkspaceRow};
+use database::workspace::{
+  insert_user_workspace, select_workspace, select_workspace_member_list,
+  upsert_workspace_member_with_txn,
+};
+use database::workspace_duplicate::{
+  insert_workspace_duplicate_task, select_workspace_duplicate_task,
+  update_workspace_duplicate_task_completed, update_workspace_duplicate_task_failed,
+  update_workspace_duplicate_task_progress,
+};
+use database_entity::dto::{AFRole, CollabParams, QueryCollab, QueryCollabResult};
+use once_cell::sync::Lazy;
+use shared_entity::dto::workspace_dto::{
+  DuplicateWorkspaceParams, WorkspaceDuplicateStatus, WorkspaceDuplicateTask,
+};
+use sqlx::{PgPool, Postgres, Transaction};
+use tokio::sync::Semaphore;
+use tracing::{error, warn};
+use uuid::Uuid;
+use workspace_template::gen_view_id;
+use yrs::types::text::YChange;
+use yrs::types::Attrs;
+use yrs::{
+  Any, Array, ArrayPrelim, ArrayRef, Map, MapPrelim, MapRef, Out, ReadTxn, Text, TextPrelim,
+  TextRef, TransactionMut,
+};
+
+use crate::biz::collab::ops::{get_latest_collab_encoded, get_latest_collab_folder};
+use crate::biz::user::user_init::create_user_awareness;
+
+use super::ops::check_workspace_owner;
+use super::publish_dup::{collab_from_doc_state, collab_to_bin};
+
+/// The task progress is saved every time this many more objects have been copied.
+const PROGRESS_UPDATE_INTERVAL: i32 = 20;
+const UUID_LEN: usize = 36;
+
+/// Limits the number of workspaces duplicated at the same time. A duplicate copies every object
+/// of the workspace, so the ones asked for beyond that are refused rather than queued.
+static DUPLICATE_PERMITS: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(4));
+
+/// Start duplicating the workspace into a new workspace owned by the user. The documents,
+/// databases and database rows of the workspace are copied under new ids, and every reference to
+/// them is rewritten to point to the copies. Chats are not copied. Copying the members of the
+/// workspace is only allowed to its owner.
+pub async fn start_workspace_duplicate(
+  pg_pool: &PgPool,
+  collab_storage: Arc<CollabAccessControlStorage>,
+  workspace_access_control: impl WorkspaceAccessControl + 'static,
+  user_uuid: Uuid,
+  uid: i64,
+  workspace_id: Uuid,
+  params: DuplicateWorkspaceParams,
+) -> Result<WorkspaceDuplicateTask, AppError> {
+  if params.include_members {
+    check_workspace_owner(pg_pool, &user_uuid, &workspace_id).await?;
+  }
+  let workspace_name = match params.workspace_name {
+    Some(name) if !name.trim().is_empty() => name,
+    _ => {
+      let name = select_workspace(pg_pool, &workspace_id)
+        .await?
+        .workspace_name
+        .unwrap_or_default();
+      format!("{} (copy)", name)
+    },
+  };
+
+  let permit = DUPLICATE_PERMITS.try_acquire().map_err(|_| {
+    AppError::TooManyRequests(
+      "Too many workspaces are being duplicated, please try again later".to_string(),
+    )
+  })?;
+  let row =
+    insert_workspace_duplicate_task(pg_pool, &workspace_id, uid, params.include_members).await?;
+  let task_id = row.task_id;
+  let pg_pool = pg_pool.clone();
+  tokio::spawn(async move {
+    let _permit = permit;
+    let duplicator = WorkspaceDuplicator {
+      pg_pool: pg_pool.clone(),
+      collab_storage,
+      task_id,
+      uid,
+      source_workspace_id: workspace_id.to_string(),
+      ids: HashMap::new(),
+      total_objects: 0,
+      copied_objects: 0,
+    };
+    let result = duplicator
+      .duplicate(
+        &workspace_access_control,
+        &user_uuid,
+        &workspace_name,
+        params.include_members,
+      )
+      .await;
+    let updated = match result {
+      Ok(new_workspace_id) => {
+        update_workspace_duplicate_task_completed(&pg_pool, &task_id, &new_workspace_id).await
+      },
+      Err(err) => {
+        warn!("Failed to duplicate workspace {}: {}", workspace_id, err);
+        update_workspace_duplicate_task_failed(&pg_pool, &task_id, &err.to_string()).await
+      },
+    };
+    if let Err(err) = updated {
+      error!(
+        "Failed to update workspace duplicate task {}: {}",
+        task_id, err
+      );
+    }
+  });
+  Ok(duplicate_task_from_row(row))
+}
+
+pub async fn get_workspace_duplicate_task(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  task_id: &Uuid,
+) -> Result<WorkspaceDuplicateTask, AppError> {
+  let row = select_workspace_duplicate_task(pg_pool, workspace_id, uid, task_id)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!("workspace duplicate {} not found", task_id))
+    })?;
+  Ok(duplicate_task_from_row(row))
+}
+
+fn duplicate_task_from_row(row: AFWorkspaceDuplicateTaskRow) -> WorkspaceDuplicateTask {
+  WorkspaceDuplicateTask {
+    task_id: row.task_id,
+    status: WorkspaceDuplicateStatus::from(row.status),
+    new_workspace_id: row.new_workspace_id,
+    include_members: row.include_members,
+    total_objects: row.total_objects,
+    copied_objects: row.copied_objects,
+    error: row.error,
+    created_at: row.created_at,
+    completed_at: row.completed_at,
+  }
+}
+
+/// A database of the workspace, along with its views and rows.
+struct DuplicateDatabase {
+  database_id: String,
+  view_ids: Vec<String>,
+  doc_state: Vec<u8>,
+  row_ids: Vec<String>,
+}
+
+struct WorkspaceDuplicator {
+  pg_pool: PgPool,
+  collab_storage: Arc<CollabAccessControlStorage>,
+  task_id: Uuid,
+  uid: i64,
+  source_workspace_id: String,
+  /// The new id of every object of the source workspace.
+  ids: HashMap<String, String>,
+  total_objects: i32,
+  copied_objects: i32,
+}
+
+impl WorkspaceDuplicator {
+  /// Returns the id of the new workspace.
+  async fn duplicate(
+    mut self,
+    workspace_access_control: &impl WorkspaceAccessControl,
+    user_uuid: &Uuid,
+    workspace_name: &str,
+    include_members: bool,
+  ) -> Result<Uuid, AppError> {
+    let source_workspace_id = Uuid::parse_str(&self.source_workspace_id)?;
+    let mut folder = get_latest_collab_folder(
+      self.collab_storage.clone(),
+      GetCollabOrigin::User { uid: self.uid },
+      &self.source_workspace_id,
+    )
+    .await?;
+    let (views, chat_view_ids) = collect_views(&folder, &self.source_workspace_id);
+    let document_view_ids: Vec<String> = views
+      .iter()
+      .filter(|(_, layout)| *layout == ViewLayout::Document)
+      .map(|(view_id, _)| view_id.clone())
+      .collect();
+    let database_view_ids: Vec<String> = views
+      .iter()
+      .filter(|(_, layout)| {
+        matches!(
+          layout,
+          ViewLayout::Grid | ViewLayout::Board | ViewLayout::Calendar
+        )
+      })
+      .map(|(view_id, _)| view_id.clone())
+      .collect();
+    let databases = self
+      .load_databases(&source_workspace_id, &database_view_ids)
+      .await?;
+
+    let mut transaction = self
+      .pg_pool
+      .begin()
+      .await
+      .context("acquire transaction to duplicate workspace")?;
+    let workspace_row = insert_user_workspace(&mut transaction, user_uuid, workspace_name).await?;
+    let new_workspace_id = workspace_row.workspace_id;
+
+    self.ids.insert(
+      self.source_workspace_id.clone(),
+      new_workspace_id.to_string(),
+    );
+    for (view_id, _) in &views {
+      self.ids.insert(view_id.clone(), gen_view_id());
+    }
+    for database in &databases {
+      self
+        .ids
+        .insert(database.database_id.clone(), Uuid::new_v4().to_string());
+      for view_id in &database.view_ids {
+        self.ids.entry(view_id.clone()).or_insert_with(gen_view_id);
+      }
+      for row_id in &database.row_ids {
+        let new_row_id = Uuid::new_v4();
+        if let Ok(row_uuid) = Uuid::parse_str(row_id) {
+          self.ids.insert(
+            meta_id_from_row_id(&row_uuid, RowMetaKey::DocumentId),
+            meta_id_from_row_id(&new_row_id, RowMetaKey::DocumentId),
+          );
+        }
+        self.ids.insert(row_id.clone(), new_row_id.to_string());
+      }
+    }
+    self.total_objects = document_view_ids.len() as i32
+      + databases
+        .iter()
+        .map(|database| 1 + database.row_ids.len() as i32)
+        .sum::<i32>();
+    update_workspace_duplicate_task_progress(&self.pg_pool, &self.task_id, self.total_objects, 0)
+      .await?;
+
+    // Views whose content can not be copied are left out of the new folder
+    let mut skipped_view_ids = chat_view_ids;
+    for view_id in document_view_ids {
+      if let Err(err) = self
+        .copy_document(&mut transaction, &new_workspace_id, &view_id)
+        .await
+      {
+        warn!("Skip duplicating document {}: {}", view_id, err);
+        skipped_view_ids.push(view_id);
+      }
+      self.add_progress(1).await?;
+    }
+    let mut database_records = vec![];
+    for database in &databases {
+      self
+        .copy_database(&mut transaction, &new_workspace_id, database)
+        .await?;
+      database_records.push((
+        self.new_id(&database.database_id),
+        database
+          .view_ids
+          .iter()
+          .map(|view_id| self.new_id(view_id))
+          .collect::<Vec<_>>(),
+      ));
+    }
+    let copied_database_view_ids: HashSet<&String> = databases
+      .iter()
+      .flat_map(|database| database.view_ids.iter())
+      .collect();
+    skipped_view_ids.extend(
+      database_view_ids
+        .iter()
+        .filter(|view_id| !copied_database_view_ids.contains(view_id))
+        .cloned(),
+    );
+
+    let skipped_views: Vec<(String, String)> = skipped_view_ids
+      .into_iter()
+      .filter_map(|view_id| {
+        let parent_view_id = folder.get_view(&view_id)?.parent_view_id.clone();
+        Some((view_id, parent_view_id))
+      })
+      .collect();
+    {
+      let mut folder_txn = folder.collab.transact_mut();
+      for (view_id, parent_view_id) in &skipped_views {
+        folder
+          .body
+          .views
+          .dissociate_parent_child(&mut folder_txn, parent_view_id, view_id);
+      }
+      folder.body.views.delete_views(
+        &mut folder_txn,
+        skipped_views
+          .iter()
+          .map(|(view_id, _)| view_id.as_str())
+          .collect(),
+      );
+    }
+    let new_folder = copy_collab(&folder.collab, &new_workspace_id.to_string(), &self.ids);
+    self
+      .insert_collab(
+        &mut transaction,
+        &new_workspace_id,
+        new_workspace_id.to_string(),
+        new_folder,
+        CollabType::Folder,
+      )
+      .await?;
+    self
+      .create_workspace_database(&mut transaction, &workspace_row, database_records)
+      .await?;
+    if let Err(err) = create_user_awareness(
+      &self.uid,
+      user_uuid,
+      &new_workspace_id.to_string(),
+      &self.collab_storage,
+      &mut transaction,
+    )
+    .await
+    {
+      error!(
+        "Failed to create user awareness for workspace: {}, {}",
+        new_workspace_id, err
+      );
+    }
+    transaction
+      .commit()
+      .await
+      .context("fail to commit the transaction to duplicate workspace")?;
+    // The policy is only granted once the workspace exists
+    workspace_access_control
+      .insert_role(&self.uid, &new_workspace_id, AFRole::Owner)
+      .await?;
+
+    if include_members {
+      self
+        .copy_members(
+          workspace_access_control,
+          &source_workspace_id,
+          &new_workspace_id,
+        )
+        .await?;
+    }
+    Ok(new_workspace_id)
+  }
+
+  /// Returns the databases of the database views, each with all its views and the rows of these
+  /// views.
+  async fn load_databases(
+    &self,
+    workspace_id: &Uuid,
+    database_view_ids: &[String],
+  ) -> Result<Vec<DuplicateDatabase>, AppError> {
+    if database_view_ids.is_empty() {
+      return Ok(vec![]);
+    }
+    let ws_db_oid = select_workspace_database_oid(&self.pg_pool, workspace_id).await?;
+    let ws_db = get_latest_collab_encoded(
+      self.collab_storage.clone(),
+      GetCollabOrigin::User { uid: self.uid },
+      &self.source_workspace_id,
+      &ws_db_oid,
+      CollabType::WorkspaceDatabase,
+    )
+    .await?;
+    let database_metas = {
+      let mut ws_db_collab = collab_from_doc_state(ws_db.doc_state.to_vec(), &ws_db_oid)?;
+      let ws_db_body = WorkspaceDatabaseBody::open(&mut ws_db_collab);
+      let txn = ws_db_collab.transact();
+      let mut database_ids = HashSet::new();
+      database_view_ids
+        .iter()
+        .filter_map(|view_id| ws_db_body.get_database_meta_with_view_id(&txn, view_id))
+        .filter(|meta| database_ids.insert(meta.database_id.clone()))
+        .collect::<Vec<_>>()
+    };
+
+    let mut databases = vec![];
+    for meta in database_metas {
+      let db = get_latest_collab_encoded(
+        self.collab_storage.clone(),
+        GetCollabOrigin::User { uid: self.uid },
+        &self.source_workspace_id,
+        &meta.database_id,
+        CollabType::Database,
+      )
+      .await;
+      let doc_state = match db {
+        Ok(db) => db.doc_state.to_vec(),
+        Err(err) => {
+          warn!("Skip duplicating database {}: {}", meta.database_id, err);
+          continue;
+        },
+      };
+      let row_ids = database_row_ids(doc_state.clone(), &meta.database_id, &meta.linked_views)?;
+      databases.push(DuplicateDatabase {
+        database_id: meta.database_id,
+        view_ids: meta.linked_views,
+        doc_state,
+        row_ids,
+      });
+    }
+    Ok(databases)
+  }
+
+  async fn copy_document(
+    &self,
+    transaction: &mut Transaction<'_, Postgres>,
+    new_workspace_id: &Uuid,
+    object_id: &str,
+  ) -> Result<(), AppError> {
+    let encoded = get_latest_collab_encoded(
+      self.collab_storage.clone(),
+      GetCollabOrigin::User { uid: self.uid },
+      &self.source_workspace_id,
+      object_id,
+      CollabType::Document,
+    )
+    .await?;
+    let collab = collab_from_doc_state(encoded.doc_state.to_vec(), object_id)?;
+    let new_object_id = self.new_id(object_id);
+    let new_collab = copy_collab(&collab, &new_object_id, &self.ids);
+    self
+      .insert_collab(
+        transaction,
+        new_workspace_id,
+        new_object_id,
+        new_collab,
+        CollabType::Document,
+      )
+      .await
+  }
+
+  /// Copy the database along with its rows and the documents of its rows.
+  async fn copy_database(
+    &mut self,
+    transaction: &mut Transaction<'_, Postgres>,
+    new_workspace_id: &Uuid,
+    database: &DuplicateDatabase,
+  ) -> Result<(), AppError> {
+    let collab = collab_from_doc_state(database.doc_state.clone(), &database.database_id)?;
+    let new_database_id = self.new_id(&database.database_id);
+    let new_collab = copy_collab(&collab, &new_database_id, &self.ids);
+    self
+      .insert_collab(
+        transaction,
+        new_workspace_id,
+        new_database_id,
+        new_collab,
+        CollabType::Database,
+      )
+      .await?;
+    self.add_progress(1).await?;
+
+    let queries = database
+      .row_ids
+      .iter()
+      .map(|row_id| QueryCollab {
+        object_id: row_id.clone(),
+        collab_type: CollabType::DatabaseRow,
+      })
+      .collect();
+    let rows = self
+      .collab_storage
+      .batch_get_collab(&self.uid, queries)
+      .await;
+    for (row_id, result) in rows {
+      match result {
+        QueryCollabResult::Success { encode_collab_v1 } => {
+          if let Err(err) = self
+            .copy_database_row(transaction, new_workspace_id, &row_id, encode_collab_v1)
+            .await
+          {
+            warn!("Skip duplicating database row {}: {}", row_id, err);
+          }
+        },
+        QueryCollabResult::Failed { error } => {
+          warn!("Skip duplicating database row {}: {}", row_id, error);
+        },
+      }
+      self.add_progress(1).await?;
+    }
+    Ok(())
+  }
+
+  async fn copy_database_row(
+    &self,
+    transaction: &mut Transaction<'_, Postgres>,
+    new_workspace_id: &Uuid,
+    row_id: &str,
+    encoded_collab_v1: Vec<u8>,
+  ) -> Result<(), AppError> {
+    let encoded = EncodedCollab::decode_from_bytes(&encoded_collab_v1).map_err(|err| {
+      AppError::Internal(anyhow!("Failed to decode database row {}: {}", row_id, err))
+    })?;
+    let mut collab = collab_from_doc_state(encoded.doc_state.to_vec(), row_id)?;
+    let new_row_id = self.new_id(row_id);
+    let row_document_id = {
+      let mut row_body = DatabaseRowBody::open(row_id.to_string().into(), &mut collab)
+        .map_err(|e| AppError::Unhandled(e.to_string()))?;
+      let mut txn = collab.context.transact_mut();
+      let row_document_id = row_body
+        .document_id(&txn)
+        .map_err(|e| AppError::Unhandled(e.to_string()))?;
+      // The keys of the row meta are derived from the row id, so they are rewritten by the row
+      row_body
+        .update_id(&mut txn, new_row_id.clone().into())
+        .map_err(|e| AppError::Unhandled(format!("failed to update row id: {:?}", e)))?;
+      row_document_id
+    };
+    let new_collab = copy_collab(&collab, &new_row_id, &self.ids);
+    self
+      .insert_collab(
+        transaction,
+        new_workspace_id,
+        new_row_id,
+        new_collab,
+        CollabType::DatabaseRow,
+      )
+      .await?;
+
+    if let Some(row_document_id) = row_document_id {
+      if let Err(err) = self
+        .copy_document(transaction, new_workspace_id, &row_document_id)
+        .await
+      {
+        warn!(
+          "Skip duplicating the document of database row {}: {}",
+          row_id, err
+        );
+      }
+    }
+    Ok(())
+  }
+
+  async fn create_workspace_database(
+    &self,
+    transaction: &mut Transaction<'_, Postgres>,
+    workspace_row: &AFWorkspaceRow,
+    database_records: Vec<(String, Vec<String>)>,
+  ) -> Result<(), AppError> {
+    let object_id = workspace_row
+      .database_storage_id
+      .ok_or_else(|| AppError::Internal(anyhow!("Workspace database object id is missing")))?
+      .to_string();
+    let mut collab = Collab::new_with_origin(CollabOrigin::Empty, &object_id, vec![], false);
+    {
+      let ws_db_body = WorkspaceDatabaseBody::create(&mut collab);
+      let mut txn = collab.context.transact_mut();
+      for (database_id, view_ids) in database_records {
+        ws_db_body.add_database(&mut txn, &database_id, view_ids);
+      }
+    }
+    self
+      .insert_collab(
+        transaction,
+        &workspace_row.workspace_id,
+        object_id,
+        collab,
+        CollabType::WorkspaceDatabase,
+      )
+      .await
+  }
+
+  /// Add the members of the source workspace to the new workspace, with the same role. The user
+  /// that duplicated the workspace is the only owner of the new workspace.
+  async fn copy_members(
+    &self,
+    workspace_access_control: &impl WorkspaceAccessControl,
+    source_workspace_id: &Uuid,
+    new_workspace_id: &Uuid,
+  ) -> Result<(), AppError> {
+    let members = select_workspace_member_list(&self.pg_pool, source_workspace_id).await?;
+    let mut transaction = self
+      .pg_pool
+      .begin()
+      .await
+      .context("acquire transaction to copy workspace members")?;
+    let mut roles = Vec::with_capacity(members.len());
+    for member in members {
+      if member.uid == self.uid {
+        continue;
+      }
+      let role = match member.role {
+        AFRole::Owner => AFRole::Member,
+        role => role,
+      };
+      upsert_workspace_member_with_txn(
+        &mut transaction,
+        new_workspace_id,
+        &member.email,
+        role.clone(),
+      )
+      .await?;
+      roles.push((member.uid, role));
+    }
+    transaction
+      .commit()
+      .await
+      .context("fail to commit the transaction to copy workspace members")?;
+    for (uid, role) in roles {
+      workspace_access_control
+        .insert_role(&uid, new_workspace_id, role)
+        .await?;
+    }
+    Ok(())
+  }
+
+  async fn insert_collab(
+    &self,
+    transaction: &mut Transaction<'_, Postgres>,
+    workspace_id: &Uuid,
+    object_id: String,
+    collab: Collab,
+    collab_type: CollabType,
+  ) -> Result<(), AppError> {
+    let encoded_collab_v1 = collab_to_bin(collab, collab_type.clone()).await?;
+    self
+      .collab_storage
+      .insert_new_collab_with_transaction(
+        &workspace_id.to_string(),
+        &self.uid,
+        CollabParams {
+          object_id,
+          encoded_collab_v1: encoded_collab_v1.into(),
+          collab_type,
+          embeddings: None,
+        },
+        transaction.deref_mut(),
+      )
+      .await?;
+    Ok(())
+  }
+
+  async fn add_progress(&mut self, copied: i32) -> Result<(), AppError> {
+    let before = self.copied_objects;
+    self.copied_objects += copied;
+    if before / PROGRESS_UPDATE_INTERVAL != self.copied_objects / PROGRESS_UPDATE_INTERVAL {
+      update_workspace_duplicate_task_progress(
+        &self.pg_pool,
+        &self.task_id,
+        self.total_objects,
+        self.copied_objects,
+      )
+      .await?;
+    }
+    Ok(())
+  }
+
+  fn new_id(&self, id: &str) -> String {
+    self.ids.get(id).cloned().unwrap_or_else(|| id.to_string())
+  }
+}
+
+/// Returns the views under the root of the folder, along with the ids of the chats, which are
+/// not duplicated.
+fn collect_views(folder: &Folder, workspace_id: &str) -> (Vec<(String, ViewLayout)>, Vec<String>) {
+  let mut views = vec![];
+  let mut chat_view_ids = vec![];
+  let mut stack = vec![workspace_id.to_string()];
+  while let Some(parent_id) = stack.pop() {
+    let parent = match folder.get_view(&parent_id) {
+      Some(parent) => parent,
+      None => continue,
+    };
+    for child in parent.children.iter() {
+      if let Some(view) = folder.get_view(&child.id) {
+        if view.layout == ViewLayout::Chat {
+          chat_view_ids.push(view.id.clone());
+          continue;
+        }
+        views.push((view.id.clone(), view.layout.clone()));
+        stack.push(view.id.clone());
+      }
+    }
+  }
+  (views, chat_view_ids)
+}
+
+/// Returns the ids of the rows of the views of the database.
+fn database_row_ids(
+  doc_state: Vec<u8>,
+  database_id: &str,
+  view_ids: &[String],
+) -> Result<Vec<String>, AppError> {
+  let db_collab = collab_from_doc_state(doc_state, database_id)?;
+  let db_body = DatabaseBody::from_collab(&db_collab)
+    .ok_or_else(|| AppError::RecordNotFound(format!("database {} not found", database_id)))?;
+  let txn = db_collab.transact();
+  let mut seen = HashSet::new();
+  Ok(
+    view_ids
+      .iter()
+      .flat_map(|view_id| db_body.views.get_row_orders(&txn, view_id))
+      .map(|row_order| row_order.id.to_string())
+      .filter(|row_id| seen.insert(row_id.clone()))
+      .collect(),
+  )
+}
+
+/// Returns a new collab `object_id` holding the data of `collab`, in which every id of `ids` is
+/// replaced by its new id, wherever it appears: in keys, in values and inside text.
+fn copy_collab(collab: &Collab, object_id: &str, ids: &HashMap<String, String>) -> Collab {
+  let mut new_collab = Collab::new_with_origin(CollabOrigin::Empty, object_id, vec![], false);
+  {
+    let src_txn = collab.transact();
+    let mut dst_txn = new_collab.context.transact_mut();
+    copy_map(&src_txn, &collab.data, &mut dst_txn, &new_collab.data, ids);
+  }
+  new_collab
+}
+
+fn copy_map<T: ReadTxn>(
+  src_txn: &T,
+  src: &MapRef,
+  dst_txn: &mut TransactionMut,
+  dst: &MapRef,
+  ids: &HashMap<String, String>,
+) {
+  for (key, value) in src.iter(src_txn) {
+    let key = replace_ids(key, ids);
+    match value {
+      Out::Any(any) => {
+        dst.insert(dst_txn, key, replace_ids_in_any(any, ids));
+      },
+      Out::YMap(map) => {
+        let new_map = dst.insert(dst_txn, key, MapPrelim::default());
+        copy_map(src_txn, &map, dst_txn, &new_map, ids);
+      },
+      Out::YArray(array) => {
+        let new_array = dst.insert(dst_txn, key, ArrayPrelim::default());
+        copy_array(src_txn, &array, dst_txn, &new_array, ids);
+      },
+      Out::YText(text) => {
+        let new_text = dst.insert(dst_txn, key, TextPrelim::new(""));
+        copy_text(src_txn, &text, dst_txn, &new_text, ids);
+      },
+      other => warn!("skip duplicating unsupported value of {}: {:?}", key, other),
+    }
+  }
+}
+
+fn copy_array<T: ReadTxn>(
+  src_txn: &T,
+  src: &ArrayRef,
+  dst_txn: &mut TransactionMut,
+  dst: &ArrayRef,
+  ids: &HashMap<String, String>,
+) {
+  for value in src.iter(src_txn) {
+    match value {
+      Out::Any(any) => {
+        dst.push_back(dst_txn, replace_ids_in_any(any, ids));
+      },
+      Out::YMap(map) => {
+        let new_map = dst.push_back(dst_txn, MapPrelim::default());
+        copy_map(src_txn, &map, dst_txn, &new_map, ids);
+      },
+      Out::YArray(array) => {
+        let new_array = dst.push_back(dst_txn, ArrayPrelim::default());
+        copy_array(src_txn, &array, dst_txn, &new_array, ids);
+      },
+      Out::YText(text) => {
+        let new_text = dst.push_back(dst_txn, TextPrelim::new(""));
+        copy_text(src_txn, &text, dst_txn, &new_text, ids);
+      },
+      other => warn!("skip duplicating unsupported array value: {:?}", other),
+    }
+  }
+}
+
+/// Copy the text chunk by chunk, keeping the formatting of each chunk. Mentions of pages are
+/// attributes of the chunks, so the attributes are rewritten too.
+fn copy_text<T: ReadTxn>(
+  src_txn: &T,
+  src: &TextRef,
+  dst_txn: &mut TransactionMut,
+  dst: &TextRef,
+  ids: &HashMap<String, String>,
+) {
+  for chunk in src.diff(src_txn, YChange::identity) {
+    let attrs: Attrs = chunk
+      .attributes
+      .map(|attrs| *attrs)
+      .unwrap_or_default()
+      .into_iter()
+      .map(|(key, value)| (key, replace_ids_in_any(value, ids)))
+      .collect();
+    let index = dst.len(&*dst_txn);
+    match chunk.insert {
+      Out::Any(Any::String(s)) => {
+        dst.insert_with_attributes(dst_txn, index, &replace_ids(&s, ids), attrs)
+      },
+      Out::Any(embed) => {
+        dst.insert_embed_with_attributes(dst_txn, index, replace_ids_in_any(embed, ids), attrs);
+      },
+      other => warn!("skip duplicating unsupported text embed: {:?}", other),
+    }
+  }
+}
+
+fn replace_ids_in_any(any: Any, ids: &HashMap<String, String>) -> Any {
+  match any {
+    Any::String(s) => Any::String(replace_ids(&s, ids).into()),
+    Any::Array(values) => Any::Array(
+      values
+        .iter()
+        .map(|value| replace_ids_in_any(value.clone(), ids))
+        .collect::<Vec<_>>()
+        .into(),
+    ),
+    Any::Map(map) => Any::Map(Arc::new(
+      map
+        .iter()
+        .map(|(key, value)| {
+          (
+            replace_ids(key, ids),
+            replace_ids_in_any(value.clone(), ids),
+          )
+        })
+        .collect(),
+    )),
+    other => other,
+  }
+}
+
+/// Replace every id of `ids` that appears in `text`. Ids are uuids, which are found by their
+/// length and the positions of their hyphens, so ids nested in JSON strings, such as the data of
+/// document blocks, are replaced as well. Other ids are only replaced when they are the whole text.
+fn replace_ids(text: &str, ids: &HashMap<String, String>) -> String {
+  if let Some(new_id) = ids.get(text) {
+    return new_id.clone();
+  }
+  let bytes = text.as_bytes();
+  let mut replaced = String::with_capacity(text.len());
+  let mut copied_to = 0;
+  let mut i = 0;
+  while i + UUID_LEN <= bytes.len() {
+    if is_uuid(&bytes[i..i + UUID_LEN]) {
+      // A uuid is ascii, so it starts and ends on char boundaries
+      if let Some(new_id) = ids.get(&text[i..i + UUID_LEN]) {
+        replaced.push_str(&text[copied_to..i]);
+        replaced.push_str(new_id);
+        i += UUID_LEN;
+        copied_to = i;
+        continue;
+      }
+    }
+    i += 1;
+  }
+  replaced.push_str(&text[copied_to..]);
+  replaced
+}
+
+fn is_uuid(bytes: &[u8]) -> bool {
+  bytes.iter().enumerate().all(|(i, b)| match i {
+    8 | 13 | 18 | 23 => *b == b'-',
+    _ => b.is_ascii_hexdigit(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn replace_ids_test() {
+    let old_id = "3c4bd2c5-8f4e-4bd6-a4b0-8c1a4d2d8a11";
+    let new_id = "b1f2a0d4-1d1e-4a55-9d6c-0f5f3c2b7e22";
+    let ids = HashMap::from([
+      (old_id.to_string(), new_id.to_string()),
+      ("row-1".to_string(), "row-2".to_string()),
+    ]);
+    assert_eq!(replace_ids(old_id, &ids), new_id);
+    assert_eq!(replace_ids("row-1", &ids), "row-2");
+    assert_eq!(
+      replace_ids(&format!("{{\"view_id\":\"{}\",\"é\":1}}", old_id), &ids),
+      format!("{{\"view_id\":\"{}\",\"é\":1}}", new_id)
+    );
+    assert_eq!(
+      replace_ids("a row-1 b 00000000-0000-0000-0000-000000000000", &ids),
+      "a row-1 b 00000000-0000-0000-0000-000000000000"
+    );
+  }
+
+  #[test]
+  fn copy_collab_test() {
+    let old_id = "3c4bd2c5-8f4e-4bd6-a4b0-8c1a4d2d8a11";
+    let new_id = "b1f2a0d4-1d1e-4a55-9d6c-0f5f3c2b7e22";
+    let ids = HashMap::from([(old_id.to_string(), new_id.to_string())]);
+    let mut collab = Collab::new_with_origin(CollabOrigin::Empty, "object", vec![], false);
+    {
+      let mut txn = collab.context.transact_mut();
+      let views = collab.data.insert(&mut txn, "views", MapPrelim::default());
+      let view = views.insert(&mut txn, old_id, MapPrelim::default());
+      view.insert(&mut txn, "id", old_id);
+      let children = view.insert(&mut txn, "children", ArrayPrelim::default());
+      children.push_back(&mut txn, Any::from(vec![Any::from(old_id)]));
+    }
+
+    let new_collab = copy_collab(&collab, "new-object", &ids);
+    let txn = new_collab.transact();
+    let views: MapRef = new_collab.data.get(&txn, "views").unwrap().cast().unwrap();
+    assert!(views.get(&txn, old_id).is_none());
+    let view: MapRef = views.get(&txn, new_id).unwrap().cast().unwrap();
+    assert_eq!(view.get(&txn, "id").unwrap().to_string(&txn), new_id);
+    let children: ArrayRef = view.get(&txn, "children").unwrap().cast().unwrap();
+    assert_eq!(
+      children.get(&txn, 0).unwrap().to_json(&txn),
+      Any::from(vec![Any::from(new_id)])
+    );
+  }
+}
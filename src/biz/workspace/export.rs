@@ -0,0 +1,899 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{Seek, SeekFrom, Write};
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use appflowy_collaborate::collab::access_control::CollabAccessControlImpl;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use bytes::Bytes;
+use chrono::{DateTime, Duration, Utc};
+use collab_database::database::DatabaseBody;
+use collab_database::entity::FieldType;
+use collab_database::fields::Field;
+use collab_database::rows::{Cell, RowDetail};
+use collab_database::template::entity::CELL_DATA;
+use collab_database::workspace_database::WorkspaceDatabaseBody;
+use collab_document::document::Document;
+use collab_entity::{CollabType, EncodedCollab};
+use database::collab::{select_workspace_database_oid, CollabStorage, GetCollabOrigin};
+use database::file::s3_client_impl::AwsS3BucketClientImpl;
+use database::file::{BucketClient, ResponseBlob};
+use database::pg_row::AFWorkspaceExportTaskRow;
+use database::workspace_export::{
+  delete_workspace_export_tasks_before, insert_workspace_export_task, select_workspace_export_task,
+  update_workspace_export_task_completed, update_workspace_export_task_failed,
+};
+use database_entity::dto::{QueryCollab, QueryCollabResult};
+use database_entity::file_dto::{
+  CompleteUploadRequest, CompletedPartRequest, CreateUploadRequest, UploadPartData,
+};
+use futures_util::stream::{self, BoxStream};
+use futures_util::{StreamExt, TryStreamExt};
+use once_cell::sync::Lazy;
+use shared_entity::dto::workspace_dto::{
+  FolderView, ViewLayout, WorkspaceExportStatus, WorkspaceExportTask,
+};
+use sqlx::PgPool;
+use tokio::io::AsyncReadExt;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio_util::io::ReaderStream;
+use tracing::{error, warn};
+use uuid::Uuid;
+use yrs::Any;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::biz::collab::folder_view::FolderViewFilter;
+use crate::biz::collab::ops::{get_latest_collab_encoded, get_user_workspace_structure};
+
+use super::publish_dup::collab_from_doc_state;
+use super::publish_render::document_to_markdown;
+
+pub const ZIP_CONTENT_TYPE: &str = "application/zip";
+/// Workspaces with more views than this are exported in the background, see
+/// [start_workspace_export].
+const MAX_DIRECT_EXPORT_VIEWS: usize = 100;
+/// The deepest level of the folder that is exported.
+const MAX_EXPORT_DEPTH: u32 = 10;
+/// Archives of background exports can be downloaded for this many days.
+const EXPORT_RETENTION_DAYS: i64 = 7;
+/// The archives are uploaded to the bucket in parts of this size. The bucket requires the parts,
+/// except the last one, to be at least 5 MiB.
+const UPLOAD_PART_SIZE: usize = 8 * 1024 * 1024;
+
+/// Limits the number of archives built at the same time. The exports that come after wait for
+/// one of them to complete.
+static EXPORT_PERMITS: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(4));
+
+/// A zip archive of the documents and databases of a workspace, streamed to the client.
+pub struct WorkspaceArchive {
+  pub file_name: String,
+  pub body: BoxStream<'static, Result<Bytes, AppError>>,
+}
+
+impl WorkspaceArchive {
+  pub(crate) fn from_data(file_name: String, data: Vec<u8>) -> Self {
+    Self {
+      file_name,
+      body: stream::once(async move { Ok(Bytes::from(data)) }).boxed(),
+    }
+  }
+
+  pub(crate) fn from_file(file_name: String, file: tokio::fs::File) -> Self {
+    Self {
+      file_name,
+      body: ReaderStream::new(file).map_err(AppError::from).boxed(),
+    }
+  }
+}
+
+/// Waits until fewer than the maximum number of archives are being built.
+pub(crate) async fn acquire_export_permit() -> Result<SemaphorePermit<'static>, AppError> {
+  EXPORT_PERMITS
+    .acquire()
+    .await
+    .map_err(|err| AppError::Internal(err.into()))
+}
+
+/// Writes a zip archive to a temporary file, a few entries at a time, so that the archive is
+/// never held in memory. The file is deleted once it is dropped.
+pub(crate) struct ArchiveWriter {
+  zip: Option<ZipWriter<std::fs::File>>,
+}
+
+impl ArchiveWriter {
+  pub(crate) fn new() -> Result<Self, AppError> {
+    let file = tempfile::tempfile()?;
+    Ok(Self {
+      zip: Some(ZipWriter::new(file)),
+    })
+  }
+
+  /// Add the files returned by `files` to the archive. `files` runs on a blocking thread, along
+  /// with the compression, so it can render the files too.
+  pub(crate) async fn add_files<F>(&mut self, files: F) -> Result<(), AppError>
+  where
+    F: FnOnce() -> Vec<(String, Vec<u8>)> + Send + 'static,
+  {
+    let mut zip = self.take_zip()?;
+    let zip = tokio::task::spawn_blocking(move || {
+      let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+      for (path, data) in files() {
+        zip.start_file(path, options).map_err(zip_error)?;
+        zip.write_all(&data)?;
+      }
+      Ok::<_, AppError>(zip)
+    })
+    .await??;
+    self.zip = Some(zip);
+    Ok(())
+  }
+
+  /// Complete the archive. Returns the file, positioned at its start, and its size.
+  pub(crate) async fn finish(mut self) -> Result<(tokio::fs::File, u64), AppError> {
+    let zip = self.take_zip()?;
+    let (file, size) = tokio::task::spawn_blocking(move || {
+      let mut file = zip.finish().map_err(zip_error)?;
+      let size = file.seek(SeekFrom::End(0))?;
+      file.seek(SeekFrom::Start(0))?;
+      Ok::<_, AppError>((file, size))
+    })
+    .await??;
+    Ok((tokio::fs::File::from_std(file), size))
+  }
+
+  fn take_zip(&mut self) -> Result<ZipWriter<std::fs::File>, AppError> {
+    self
+      .zip
+      .take()
+      .ok_or_else(|| AppError::Internal(anyhow!("the archive was left incomplete by an error")))
+  }
+}
+
+fn zip_error(err: zip::result::ZipError) -> AppError {
+  AppError::Internal(anyhow!(err))
+}
+
+/// Upload the archive to the bucket. Archives larger than a part are uploaded a part at a time,
+/// so that only one part is held in memory.
+pub(crate) async fn upload_archive(
+  bucket_client: &AwsS3BucketClientImpl,
+  object_key: &str,
+  mut file: tokio::fs::File,
+  size: u64,
+) -> Result<(), AppError> {
+  if size <= UPLOAD_PART_SIZE as u64 {
+    let mut data = Vec::with_capacity(size as usize);
+    file.read_to_end(&mut data).await?;
+    return bucket_client
+      .put_blob_as_content_type(object_key, &data, ZIP_CONTENT_TYPE)
+      .await;
+  }
+
+  let upload = bucket_client
+    .create_upload(
+      object_key,
+      CreateUploadRequest {
+        file_id: object_key.to_string(),
+        parent_dir: String::new(),
+        content_type: ZIP_CONTENT_TYPE.to_string(),
+      },
+    )
+    .await?;
+  let parts = upload_archive_parts(bucket_client, object_key, &upload.upload_id, file).await;
+  let completed = match parts {
+    Ok(parts) => {
+      bucket_client
+        .complete_upload(
+          object_key,
+          CompleteUploadRequest {
+            file_id: object_key.to_string(),
+            parent_dir: String::new(),
+            upload_id: upload.upload_id.clone(),
+            parts,
+          },
+        )
+        .await
+    },
+    Err(err) => Err(err),
+  };
+  if let Err(err) = completed {
+    if let Err(abort_err) = bucket_client
+      .abort_upload(object_key, &upload.upload_id)
+      .await
+    {
+      warn!(
+        "Failed to abort the upload of {}: {}",
+        object_key, abort_err
+      );
+    }
+    return Err(err);
+  }
+  Ok(())
+}
+
+async fn upload_archive_parts(
+  bucket_client: &AwsS3BucketClientImpl,
+  object_key: &str,
+  upload_id: &str,
+  mut file: tokio::fs::File,
+) -> Result<Vec<CompletedPartRequest>, AppError> {
+  let mut parts = vec![];
+  loop {
+    let mut body = Vec::with_capacity(UPLOAD_PART_SIZE);
+    (&mut file)
+      .take(UPLOAD_PART_SIZE as u64)
+      .read_to_end(&mut body)
+      .await?;
+    if body.is_empty() {
+      return Ok(parts);
+    }
+    let part = bucket_client
+      .upload_part(
+        object_key,
+        UploadPartData {
+          file_id: object_key.to_string(),
+          upload_id: upload_id.to_string(),
+          part_number: parts.len() as i32 + 1,
+          body,
+        },
+      )
+      .await?;
+    parts.push(CompletedPartRequest {
+      e_tag: part.e_tag,
+      part_number: part.part_num,
+    });
+  }
+}
+
+/// Export the workspace as a zip archive right away. The archive mirrors the folder of the
+/// workspace: documents are exported as Markdown, databases as CSV, and the children of a view
+/// are put in a directory named after the view.
+pub async fn export_workspace(
+  pg_pool: &PgPool,
+  collab_storage: Arc<CollabAccessControlStorage>,
+  uid: i64,
+  workspace_id: &Uuid,
+) -> Result<WorkspaceArchive, AppError> {
+  let _permit = acquire_export_permit().await?;
+  let (file_name, file, _) = write_workspace_archive(
+    pg_pool,
+    collab_storage,
+    uid,
+    workspace_id,
+    Some(MAX_DIRECT_EXPORT_VIEWS),
+  )
+  .await?;
+  Ok(WorkspaceArchive::from_file(file_name, file))
+}
+
+/// Start exporting the workspace in the background. The archive can be downloaded with
+/// [get_workspace_export_archive] once the export has completed.
+pub async fn start_workspace_export(
+  pg_pool: &PgPool,
+  collab_storage: Arc<CollabAccessControlStorage>,
+  bucket_client: AwsS3BucketClientImpl,
+  uid: i64,
+  workspace_id: Uuid,
+) -> Result<WorkspaceExportTask, AppError> {
+  let expired_object_keys = delete_workspace_export_tasks_before(
+    pg_pool,
+    &workspace_id,
+    Utc::now() - Duration::days(EXPORT_RETENTION_DAYS),
+  )
+  .await?;
+  if !expired_object_keys.is_empty() {
+    if let Err(err) = bucket_client.delete_blobs(expired_object_keys).await {
+      warn!("Failed to delete expired workspace exports: {}", err);
+    }
+  }
+
+  let row = insert_workspace_export_task(pg_pool, &workspace_id, uid).await?;
+  let task_id = row.task_id;
+  let pg_pool = pg_pool.clone();
+  tokio::spawn(async move {
+    let result = async {
+      let _permit = acquire_export_permit().await?;
+      let (_, file, size) =
+        write_workspace_archive(&pg_pool, collab_storage, uid, &workspace_id, None).await?;
+      let object_key = export_object_key(&workspace_id, &task_id);
+      upload_archive(&bucket_client, &object_key, file, size).await?;
+      Ok::<_, AppError>((object_key, size as i64))
+    }
+    .await;
+    let updated = match result {
+      Ok((object_key, file_size)) => {
+        update_workspace_export_task_completed(&pg_pool, &task_id, &object_key, file_size).await
+      },
+      Err(err) => {
+        warn!("Failed to export workspace {}: {}", workspace_id, err);
+        update_workspace_export_task_failed(&pg_pool, &task_id, &err.to_string()).await
+      },
+    };
+    if let Err(err) = updated {
+      error!(
+        "Failed to update workspace export task {}: {}",
+        task_id, err
+      );
+    }
+  });
+  Ok(export_task_from_row(row))
+}
+
+pub async fn get_workspace_export_task(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  task_id: &Uuid,
+) -> Result<WorkspaceExportTask, AppError> {
+  let row = select_export_task(pg_pool, uid, workspace_id, task_id).await?;
+  Ok(export_task_from_row(row))
+}
+
+/// Returns the archive of a background export that has completed.
+pub async fn get_workspace_export_archive(
+  pg_pool: &PgPool,
+  bucket_client: &AwsS3BucketClientImpl,
+  uid: i64,
+  workspace_id: &Uuid,
+  task_id: &Uuid,
+) -> Result<WorkspaceArchive, AppError> {
+  let row = select_export_task(pg_pool, uid, workspace_id, task_id).await?;
+  let object_key = match (WorkspaceExportStatus::from(row.status), row.object_key) {
+    (WorkspaceExportStatus::Completed, Some(object_key)) => object_key,
+    _ => {
+      return Err(AppError::InvalidRequest(format!(
+        "workspace export {} has not completed",
+        task_id
+      )))
+    },
+  };
+  let data = bucket_client.get_blob(&object_key).await?.to_blob();
+  Ok(WorkspaceArchive::from_data(
+    format!("workspace-export-{}.zip", task_id),
+    data,
+  ))
+}
+
+async fn select_export_task(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  task_id: &Uuid,
+) -> Result<AFWorkspaceExportTaskRow, AppError> {
+  select_workspace_export_task(pg_pool, workspace_id, uid, task_id)
+    .await?
+    .filter(|row| row.created_at > Utc::now() - Duration::days(EXPORT_RETENTION_DAYS))
+    .ok_or_else(|| AppError::RecordNotFound(format!("workspace export {} not found", task_id)))
+}
+
+fn export_object_key(workspace_id: &Uuid, task_id: &Uuid) -> String {
+  format!("export/{}/{}.zip", workspace_id, task_id)
+}
+
+fn export_task_from_row(row: AFWorkspaceExportTaskRow) -> WorkspaceExportTask {
+  let status = WorkspaceExportStatus::from(row.status);
+  WorkspaceExportTask {
+    task_id: row.task_id,
+    status,
+    download_url: (status == WorkspaceExportStatus::Completed).then(|| {
+      format!(
+        "/api/workspace/{}/export/{}/download",
+        row.workspace_id, row.task_id
+      )
+    }),
+    file_size: row.file_size,
+    error: row.error,
+    created_at: row.created_at,
+    completed_at: row.completed_at,
+  }
+}
+
+/// A view to export, at `path` in the archive, without the file extension.
+struct ExportView {
+  view_id: String,
+  layout: ViewLayout,
+  path: String,
+}
+
+/// The collabs a view is exported from.
+enum ExportContent {
+  Document {
+    doc_state: Vec<u8>,
+  },
+  Database {
+    view_id: String,
+    doc_state: Vec<u8>,
+    rows: HashMap<String, Vec<u8>>,
+  },
+}
+
+/// Write the archive of the workspace to a temporary file. Returns the name of the archive, the
+/// file and its size.
+async fn write_workspace_archive(
+  pg_pool: &PgPool,
+  collab_storage: Arc<CollabAccessControlStorage>,
+  uid: i64,
+  workspace_id: &Uuid,
+  max_views: Option<usize>,
+) -> Result<(String, tokio::fs::File, u64), AppError> {
+  let folder_view = get_user_workspace_structure(
+    collab_storage.clone(),
+    pg_pool,
+    uid,
+    *workspace_id,
+    MAX_EXPORT_DEPTH,
+    &workspace_id.to_string(),
+    &FolderViewFilter::default(),
+    None::<&CollabAccessControlImpl>,
+  )
+  .await?;
+  let mut views = vec![];
+  collect_export_views(&folder_view.children, "", &mut views);
+  if let Some(max_views) = max_views {
+    if views.len() > max_views {
+      return Err(AppError::InvalidRequest(format!(
+        "The workspace has more than {} pages, export it in the background instead",
+        max_views
+      )));
+    }
+  }
+
+  let mut archive = ArchiveWriter::new()?;
+  write_export_contents(
+    pg_pool,
+    collab_storage,
+    uid,
+    workspace_id,
+    views,
+    &mut archive,
+  )
+  .await?;
+  let (file, size) = archive.finish().await?;
+  Ok((format!("{}.zip", file_name(&folder_view.name)), file, size))
+}
+
+/// Collect the views of the folder, in folder order. Spaces become directories, and chats are
+/// left out since they have no content to export.
+fn collect_export_views(children: &[FolderView], dir: &str, views: &mut Vec<ExportView>) {
+  let mut names = HashSet::new();
+  for child in children {
+    let name = unique_name(&mut names, &file_name(&child.name));
+    let path = if dir.is_empty() {
+      name
+    } else {
+      format!("{}/{}", dir, name)
+    };
+    if !child.is_space && child.layout != ViewLayout::Chat {
+      views.push(ExportView {
+        view_id: child.view_id.clone(),
+        layout: child.layout.clone(),
+        path: path.clone(),
+      });
+    }
+    collect_export_views(&child.children, &path, views);
+  }
+}
+
+/// Fetch the collabs of the views and add them to the archive, one view at a time. Views the user
+/// can not read, or whose collabs can not be found, are left out of the export.
+async fn write_export_contents(
+  pg_pool: &PgPool,
+  collab_storage: Arc<CollabAccessControlStorage>,
+  uid: i64,
+  workspace_id: &Uuid,
+  views: Vec<ExportView>,
+  archive: &mut ArchiveWriter,
+) -> Result<(), AppError> {
+  let database_view_ids: Vec<&str> = views
+    .iter()
+    .filter(|view| view.layout != ViewLayout::Document)
+    .map(|view| view.view_id.as_str())
+    .collect();
+  let database_ids = if database_view_ids.is_empty() {
+    HashMap::new()
+  } else {
+    let ws_db_oid = select_workspace_database_oid(pg_pool, workspace_id).await?;
+    let ws_db = get_latest_collab_encoded(
+      collab_storage.clone(),
+      GetCollabOrigin::User { uid },
+      &workspace_id.to_string(),
+      &ws_db_oid,
+      CollabType::WorkspaceDatabase,
+    )
+    .await?;
+    database_ids_by_view(ws_db.doc_state.to_vec(), &ws_db_oid, &database_view_ids)?
+  };
+
+  let workspace_id = workspace_id.to_string();
+
+  for view in views {
+    let content = match view.layout {
+      ViewLayout::Document => get_latest_collab_encoded(
+        collab_storage.clone(),
+        GetCollabOrigin::User { uid },
+        &workspace_id,
+        &view.view_id,
+        CollabType::Document,
+      )
+      .await
+      .map(|encoded| ExportContent::Document {
+        doc_state: encoded.doc_state.to_vec(),
+      }),
+      _ => {
+        load_database_content(
+          collab_storage.clone(),
+          uid,
+          &workspace_id,
+          &view.view_id,
+          database_ids.get(&view.view_id),
+        )
+        .await
+      },
+    };
+    match content {
+      Ok(content) => {
+        archive
+          .add_files(move || {
+            export_content_file(view.path, content)
+              .into_iter()
+              .collect()
+          })
+          .await?
+      },
+      Err(err) => warn!("Skip exporting view {}: {}", view.view_id, err),
+    }
+  }
+  Ok(())
+}
+
+async fn load_database_content(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  uid: i64,
+  workspace_id: &str,
+  view_id: &str,
+  database_id: Option<&String>,
+) -> Result<ExportContent, AppError> {
+  let database_id = database_id
+    .ok_or_else(|| AppError::RecordNotFound(format!("database view {} not found", view_id)))?;
+  let db = get_latest_collab_encoded(
+    collab_storage.clone(),
+    GetCollabOrigin::User { uid },
+    workspace_id,
+    database_id,
+    CollabType::Database,
+  )
+  .await?;
+  let doc_state = db.doc_state.to_vec();
+  let queries = database_row_ids(doc_state.clone(), database_id, view_id)?
+    .into_iter()
+    .map(|row_id| QueryCollab {
+      object_id: row_id,
+      collab_type: CollabType::DatabaseRow,
+    })
+    .collect();
+  let rows = collab_storage
+    .batch_get_collab(&uid, queries)
+    .await
+    .into_iter()
+    .filter_map(|(row_id, result)| match result {
+      QueryCollabResult::Success { encode_collab_v1 } => Some((row_id, encode_collab_v1)),
+      QueryCollabResult::Failed { error } => {
+        warn!("Failed to get database row {}: {}", row_id, error);
+        None
+      },
+    })
+    .collect();
+  Ok(ExportContent::Database {
+    view_id: view_id.to_string(),
+    doc_state,
+    rows,
+  })
+}
+
+/// Returns the id of the database of each of the database views.
+fn database_ids_by_view(
+  ws_db_doc_state: Vec<u8>,
+  ws_db_oid: &str,
+  view_ids: &[&str],
+) -> Result<HashMap<String, String>, AppError> {
+  let mut ws_db_collab = collab_from_doc_state(ws_db_doc_state, ws_db_oid)?;
+  let ws_db_body = WorkspaceDatabaseBody::open(&mut ws_db_collab);
+  let txn = ws_db_collab.transact();
+  Ok(
+    view_ids
+      .iter()
+      .filter_map(|view_id| {
+        ws_db_body
+          .get_database_meta_with_view_id(&txn, view_id)
+          .map(|meta| (view_id.to_string(), meta.database_id))
+      })
+      .collect(),
+  )
+}
+
+/// Returns the ids of the rows of the database view, in the order of the view.
+fn database_row_ids(
+  doc_state: Vec<u8>,
+  database_id: &str,
+  view_id: &str,
+) -> Result<Vec<String>, AppError> {
+  let db_collab = collab_from_doc_state(doc_state, database_id)?;
+  let db_body = DatabaseBody::from_collab(&db_collab)
+    .ok_or_else(|| AppError::RecordNotFound(format!("database {} not found", database_id)))?;
+  let txn = db_collab.transact();
+  Ok(
+    db_body
+      .views
+      .get_row_orders(&txn, view_id)
+      .into_iter()
+      .map(|row_order| row_order.id.to_string())
+      .collect(),
+  )
+}
+
+/// Render the content of a view as the file it is exported as, None if it can not be rendered.
+fn export_content_file(path: String, content: ExportContent) -> Option<(String, Vec<u8>)> {
+  match content {
+    ExportContent::Document { doc_state } => match document_markdown(doc_state, &path) {
+      Ok(markdown) => Some((format!("{}.md", path), markdown.into_bytes())),
+      Err(err) => {
+        warn!("Skip exporting document {}: {}", path, err);
+        None
+      },
+    },
+    ExportContent::Database {
+      view_id,
+      doc_state,
+      rows,
+    } => match database_csv(doc_state, &view_id, rows) {
+      Ok(csv) => Some((format!("{}.csv", path), csv.into_bytes())),
+      Err(err) => {
+        warn!("Skip exporting database {}: {}", path, err);
+        None
+      },
+    },
+  }
+}
+
+pub fn document_markdown(doc_state: Vec<u8>, object_id: &str) -> Result<String, AppError> {
+  let collab = collab_from_doc_state(doc_state, object_id)?;
+  let document = Document::open(collab).map_err(|e| AppError::Unhandled(e.to_string()))?;
+  let document_data = document
+    .get_document_data()
+    .map_err(|e| AppError::Unhandled(e.to_string()))?;
+  Ok(document_to_markdown(&document_data))
+}
+
+/// Render the rows of the database view as CSV, with the fields in the order of the view.
+fn database_csv(
+  doc_state: Vec<u8>,
+  view_id: &str,
+  rows: HashMap<String, Vec<u8>>,
+) -> Result<String, AppError> {
+  let db_collab = collab_from_doc_state(doc_state, view_id)?;
+  let db_body = DatabaseBody::from_collab(&db_collab)
+    .ok_or_else(|| AppError::RecordNotFound(format!("database of view {} not found", view_id)))?;
+  let (fields, row_ids) = {
+    let txn = db_collab.transact();
+    let mut fields = db_body.fields.get_all_fields(&txn);
+    let view = db_body
+      .views
+      .get_all_views(&txn)
+      .into_iter()
+      .find(|view| view.id == view_id)
+      .ok_or_else(|| AppError::RecordNotFound(format!("database view {} not found", view_id)))?;
+    let field_positions: HashMap<String, usize> = view
+      .field_orders
+      .iter()
+      .enumerate()
+      .map(|(position, field_order)| (field_order.id.clone(), position))
+      .collect();
+    fields.sort_by_key(|field| {
+      field_positions
+        .get(&field.id)
+        .copied()
+        .unwrap_or(usize::MAX)
+    });
+    let row_ids: Vec<String> = view
+      .row_orders
+      .iter()
+      .map(|row_order| row_order.id.to_string())
+      .collect();
+    (fields, row_ids)
+  };
+
+  let mut csv = String::new();
+  let header: Vec<String> = fields.iter().map(|field| field.name.clone()).collect();
+  write_csv_record(&mut csv, &header);
+  for row_id in row_ids {
+    let row_detail = rows
+      .get(&row_id)
+      .and_then(|encoded| EncodedCollab::decode_from_bytes(encoded).ok())
+      .and_then(|encoded| collab_from_doc_state(encoded.doc_state.to_vec(), &row_id).ok())
+      .and_then(|row_collab| RowDetail::from_collab(&row_collab));
+    let row_detail = match row_detail {
+      Some(row_detail) => row_detail,
+      None => {
+        warn!("Skip exporting database row {}", row_id);
+        continue;
+      },
+    };
+    let record: Vec<String> = fields
+      .iter()
+      .map(|field| {
+        row_detail
+          .row
+          .cells
+          .get(&field.id)
+          .map(|cell| cell_text(field, cell))
+          .unwrap_or_default()
+      })
+      .collect();
+    write_csv_record(&mut csv, &record);
+  }
+  Ok(csv)
+}
+
+#[derive(serde::Deserialize)]
+struct SelectTypeOption {
+  #[serde(default)]
+  options: Vec<SelectOption>,
+}
+
+#[derive(serde::Deserialize)]
+pub(crate) struct SelectOption {
+  pub id: String,
+  pub name: String,
+}
+
+/// The options of a single or multi select field, `None` if the field is of another type.
+pub(crate) fn select_options(field: &Field) -> Option<Vec<SelectOption>> {
+  let select_type = [FieldType::SingleSelect, FieldType::MultiSelect]
+    .into_iter()
+    .find(|field_type| field_type.clone() as i64 == field.field_type)?;
+  let options = field
+    .type_options
+    .get(&select_type.type_id())
+    .and_then(|type_option| type_option.get("content"))
+    .and_then(|content| serde_json::from_str::<SelectTypeOption>(&content.to_string()).ok())
+    .map(|type_option| type_option.options)
+    .unwrap_or_default();
+  Some(options)
+}
+
+/// The text of the cell as shown to the user: select options are shown by name and dates are
+/// formatted, other cells are exported as they are stored.
+pub(crate) fn cell_text(field: &Field, cell: &Cell) -> String {
+  let data = match cell.get(CELL_DATA) {
+    Some(data) => data.to_string(),
+    None => return String::new(),
+  };
+  if let Some(options) = select_options(field) {
+    return data
+      .split(',')
+      .filter(|option_id| !option_id.is_empty())
+      .map(|option_id| {
+        options
+          .iter()
+          .find(|option| option.id == option_id)
+          .map(|option| option.name.as_str())
+          .unwrap_or(option_id)
+      })
+      .collect::<Vec<_>>()
+      .join(", ");
+  }
+  if field.field_type == FieldType::DateTime as i64 {
+    let include_time = matches!(cell.get("include_time"), Some(Any::Bool(true)));
+    if let Some(date) = data
+      .parse::<i64>()
+      .ok()
+      .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+    {
+      let format = if include_time {
+        "%Y-%m-%d %H:%M"
+      } else {
+        "%Y-%m-%d"
+      };
+      return date.format(format).to_string();
+    }
+  }
+  data
+}
+
+pub(crate) fn write_csv_record(csv: &mut String, record: &[String]) {
+  let fields: Vec<String> = record.iter().map(|field| escape_csv(field)).collect();
+  csv.push_str(&fields.join(","));
+  csv.push_str("\r\n");
+}
+
+fn escape_csv(field: &str) -> String {
+  if field.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", field.replace('"', "\"\""))
+  } else {
+    field.to_string()
+  }
+}
+
+/// A name that can be used as a file name on any platform.
+pub(crate) fn file_name(name: &str) -> String {
+  let name: String = name
+    .chars()
+    .map(|c| match c {
+      '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+      c if c.is_control() => '_',
+      c => c,
+    })
+    .collect();
+  let name = name.trim().trim_matches('.').trim();
+  if name.is_empty() {
+    "Untitled".to_string()
+  } else {
+    name.chars().take(100).collect()
+  }
+}
+
+/// Views with the same name in a directory are numbered, so that none is overwritten.
+fn unique_name(names: &mut HashSet<String>, name: &str) -> String {
+  let mut unique = name.to_string();
+  let mut n = 1;
+  while !names.insert(unique.to_lowercase()) {
+    n += 1;
+    unique = format!("{} ({})", name, n);
+  }
+  unique
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn export_file_name_test() {
+    assert_eq!(file_name("a/b: c?"), "a_b_ c_");
+    assert_eq!(file_name("  ..  "), "Untitled");
+    let mut names = HashSet::new();
+    assert_eq!(unique_name(&mut names, "Page"), "Page");
+    assert_eq!(unique_name(&mut names, "page"), "page (2)");
+    assert_eq!(unique_name(&mut names, "Page"), "Page (3)");
+  }
+
+  #[test]
+  fn export_csv_test() {
+    let mut csv = String::new();
+    write_csv_record(
+      &mut csv,
+      &[
+        "plain".to_string(),
+        "a,b".to_string(),
+        "say \"hi\"".to_string(),
+      ],
+    );
+    assert_eq!(csv, "plain,\"a,b\",\"say \"\"hi\"\"\"\r\n");
+  }
+  #[tokio::test]
+  async fn archive_writer_test() {
+    let mut archive = ArchiveWriter::new().unwrap();
+    archive
+      .add_files(|| vec![("a.md".to_string(), b"# A".to_vec())])
+      .await
+      .unwrap();
+    archive
+      .add_files(|| vec![("b/c.csv".to_string(), b"x,y".to_vec())])
+      .await
+      .unwrap();
+    let (mut file, size) = archive.finish().await.unwrap();
+    let mut data = vec![];
+    file.read_to_end(&mut data).await.unwrap();
+    assert_eq!(data.len() as u64, size);
+
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(data)).unwrap();
+    let mut names: Vec<_> = zip.file_names().map(|name| name.to_string()).collect();
+    names.sort();
+    assert_eq!(names, ["a.md", "b/c.csv"]);
+    let mut text = String::new();
+    std::io::Read::read_to_string(&mut zip.by_name("b/c.csv").unwrap(), &mut text).unwrap();
+    assert_eq!(text, "x,y");
+  }
+}
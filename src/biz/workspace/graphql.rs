@@ -0,0 +1,247 @@
+use async_graphql::{
+  Context, EmptyMutation, EmptySubscription, Enum, Object, Result, Schema, SimpleObject,
+};
+use chrono::{DateTime, Utc};
+use gotrue_entity::gotrue_jwt::GoTrueJWTClaims;
+use uuid::Uuid;
+
+use access_control::act::Action;
+use access_control::workspace::WorkspaceAccessControl;
+use app_error::AppError;
+use database::pg_row::AFWorkspaceRow;
+use database::publish::{select_published_names_for_workspace, select_workspace_publish_namespace};
+use database::workspace::{select_workspace, select_workspace_member_list};
+use database_entity::dto::AFRole;
+use shared_entity::dto::workspace_dto::{FolderView, ViewLayout};
+
+use crate::biz::collab::folder_view::FolderViewFilter;
+use crate::biz::collab::ops::get_user_workspace_structure;
+use crate::state::AppState;
+
+/// Queries nested deeper than this are rejected before being executed.
+const MAX_QUERY_DEPTH: usize = 16;
+
+/// A read-only GraphQL schema over a workspace, its folder, its members and its published views,
+/// so that a client can fetch all of them in a single request.
+pub type WorkspaceSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn build_workspace_schema() -> WorkspaceSchema {
+  Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+    .limit_depth(MAX_QUERY_DEPTH)
+    .finish()
+}
+
+/// The requesting user, which must be added to the data of every executed request.
+pub struct GraphQLRequester {
+  pub state: AppState,
+  pub uid: i64,
+  pub user_uuid: Uuid,
+  /// The claims of the session, checked against the single sign-on the workspace enforces.
+  pub claims: GoTrueJWTClaims,
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+  /// The workspace with the given id, which the user must be able to read with a session that
+  /// satisfies the single sign-on of the workspace.
+  async fn workspace(&self, ctx: &Context<'_>, id: Uuid) -> Result<Workspace> {
+    let requester = ctx.data::<GraphQLRequester>()?;
+    requester
+      .state
+      .sso_enforcer
+      .check(&id, &requester.user_uuid, &requester.claims)
+      .await?;
+    let can_read = requester
+      .state
+      .workspace_access_control
+      .enforce_action(&requester.uid, &id.to_string(), Action::Read)
+      .await?;
+    if !can_read {
+      return Err(
+        AppError::NotEnoughPermissions {
+          user: requester.uid.to_string(),
+          action: format!("read workspace:{}", id),
+        }
+        .into(),
+      );
+    }
+    let row = select_workspace(&requester.state.pg_pool, &id).await?;
+    Ok(Workspace { row })
+  }
+}
+
+pub struct Workspace {
+  row: AFWorkspaceRow,
+}
+
+#[Object]
+impl Workspace {
+  async fn id(&self) -> Uuid {
+    self.row.workspace_id
+  }
+
+  async fn name(&self) -> Option<&str> {
+    self.row.workspace_name.as_deref()
+  }
+
+  async fn icon(&self) -> Option<&str> {
+    self.row.icon.as_deref()
+  }
+
+  async fn owner_name(&self) -> Option<&str> {
+    self.row.owner_name.as_deref()
+  }
+
+  async fn created_at(&self) -> Option<DateTime<Utc>> {
+    self.row.created_at
+  }
+
+  async fn members(&self, ctx: &Context<'_>) -> Result<Vec<WorkspaceMember>> {
+    let requester = ctx.data::<GraphQLRequester>()?;
+    let members = select_workspace_member_list(&requester.state.pg_pool, &self.row.workspace_id)
+      .await?
+      .into_iter()
+      .map(|member| WorkspaceMember {
+        uid: member.uid,
+        name: member.name,
+        email: member.email,
+        role: member.role.into(),
+      })
+      .collect();
+    Ok(members)
+  }
+
+  /// The views of the folder that the user can see, starting from `root_view_id`, or from the
+  /// workspace when it is not set.
+  async fn folder(
+    &self,
+    ctx: &Context<'_>,
+    #[graphql(default = 1)] depth: u32,
+    root_view_id: Option<String>,
+  ) -> Result<FolderNode> {
+    let requester = ctx.data::<GraphQLRequester>()?;
+    let workspace_id = self.row.workspace_id;
+    let root_view_id = root_view_id.unwrap_or_else(|| workspace_id.to_string());
+    let folder_view = get_user_workspace_structure(
+      requester.state.collab_access_control_storage.clone(),
+      &requester.state.pg_pool,
+      requester.uid,
+      workspace_id,
+      depth,
+      &root_view_id,
+      &FolderViewFilter::default(),
+      Some(&requester.state.collab_access_control),
+    )
+    .await?;
+    Ok(FolderNode::from(folder_view))
+  }
+
+  async fn publish_namespace(&self, ctx: &Context<'_>) -> Result<String> {
+    let requester = ctx.data::<GraphQLRequester>()?;
+    let namespace =
+      select_workspace_publish_namespace(&requester.state.pg_pool, &self.row.workspace_id).await?;
+    Ok(namespace)
+  }
+
+  /// The views of the workspace that are currently published.
+  async fn published_views(&self, ctx: &Context<'_>) -> Result<Vec<PublishedView>> {
+    let requester = ctx.data::<GraphQLRequester>()?;
+    let views =
+      select_published_names_for_workspace(&requester.state.pg_pool, &self.row.workspace_id)
+        .await?
+        .into_iter()
+        .map(|(view_id, publish_name)| PublishedView {
+          view_id,
+          publish_name,
+        })
+        .collect();
+    Ok(views)
+  }
+}
+
+#[derive(SimpleObject)]
+pub struct WorkspaceMember {
+  uid: i64,
+  name: String,
+  email: String,
+  role: MemberRole,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum MemberRole {
+  Owner,
+  Member,
+  Guest,
+}
+
+impl From<AFRole> for MemberRole {
+  fn from(role: AFRole) -> Self {
+    match role {
+      AFRole::Owner => MemberRole::Owner,
+      AFRole::Member => MemberRole::Member,
+      AFRole::Guest => MemberRole::Guest,
+    }
+  }
+}
+
+#[derive(SimpleObject)]
+pub struct PublishedView {
+  view_id: Uuid,
+  publish_name: String,
+}
+
+#[derive(SimpleObject)]
+pub struct FolderNode {
+  view_id: String,
+  name: String,
+  icon: Option<String>,
+  is_space: bool,
+  is_private: bool,
+  is_published: bool,
+  layout: FolderViewLayout,
+  created_at: DateTime<Utc>,
+  last_edited_time: DateTime<Utc>,
+  can_edit: Option<bool>,
+  children: Vec<FolderNode>,
+}
+
+impl From<FolderView> for FolderNode {
+  fn from(view: FolderView) -> Self {
+    Self {
+      view_id: view.view_id,
+      name: view.name,
+      icon: view.icon.map(|icon| icon.value),
+      is_space: view.is_space,
+      is_private: view.is_private,
+      is_published: view.is_published,
+      layout: view.layout.into(),
+      created_at: view.created_at,
+      last_edited_time: view.last_edited_time,
+      can_edit: view.permissions.map(|permissions| permissions.can_edit),
+      children: view.children.into_iter().map(FolderNode::from).collect(),
+    }
+  }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum FolderViewLayout {
+  Document,
+  Grid,
+  Board,
+  Calendar,
+  Chat,
+}
+
+impl From<ViewLayout> for FolderViewLayout {
+  fn from(layout: ViewLayout) -> Self {
+    match layout {
+      ViewLayout::Document => FolderViewLayout::Document,
+      ViewLayout::Grid => FolderViewLayout::Grid,
+      ViewLayout::Board => FolderViewLayout::Board,
+      ViewLayout::Calendar => FolderViewLayout::Calendar,
+      ViewLayout::Chat => FolderViewLayout::Chat,
+    }
+  }
+}
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::ops::DerefMut;
+
+use access_control::collab::CollabAccessControl;
+use anyhow::Context;
+use app_error::AppError;
+use database::pg_row::AFWorkspaceGroupRow;
+use database::workspace::select_workspace_member;
+use database::workspace_group::{
+  delete_collab_group_member, delete_workspace_group as delete_workspace_group_row,
+  delete_workspace_group_member, insert_workspace_group, insert_workspace_group_member,
+  select_collab_group_member_oids, select_workspace_group, select_workspace_group_member_uids,
+  select_workspace_groups, upsert_collab_group_member,
+};
+use database_entity::dto::AFAccessLevel;
+use shared_entity::dto::workspace_dto::{CreateWorkspaceGroupParams, WorkspaceGroup};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::biz::collab::ops::check_membership_unlocked;
+
+const MAX_GROUP_NAME_LEN: usize = 100;
+
+pub async fn create_workspace_group(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  params: CreateWorkspaceGroupParams,
+) -> Result<WorkspaceGroup, AppError> {
+  let name = params.name.trim();
+  if name.is_empty() || name.chars().count() > MAX_GROUP_NAME_LEN {
+    return Err(AppError::InvalidRequest(format!(
+      "The group name must be between 1 and {} characters",
+      MAX_GROUP_NAME_LEN
+    )));
+  }
+  let row = insert_workspace_group(pg_pool, workspace_id, name)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordAlreadyExists(format!("The workspace already has a group named {}", name))
+    })?;
+  Ok(workspace_group_from_row(row, vec![]))
+}
+
+/// Returns the groups of the workspace with their members, ordered by creation time.
+pub async fn list_workspace_groups(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<WorkspaceGroup>, AppError> {
+  let rows = select_workspace_groups(pg_pool, workspace_id).await?;
+  let group_ids: Vec<Uuid> = rows.iter().map(|row| row.group_id).collect();
+  let mut member_uids: HashMap<Uuid, Vec<i64>> = HashMap::new();
+  for (group_id, uid) in select_workspace_group_member_uids(pg_pool, &group_ids).await? {
+    member_uids.entry(group_id).or_default().push(uid);
+  }
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| {
+        let uids = member_uids.remove(&row.group_id).unwrap_or_default();
+        workspace_group_from_row(row, uids)
+      })
+      .collect(),
+  )
+}
+
+/// Delete the group. Its members lose the access that was granted to the group.
+pub async fn delete_workspace_group(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+) -> Result<(), AppError> {
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to delete workspace group")?;
+  let member_uids = select_workspace_group_member_uids(txn.deref_mut(), &[*group_id]).await?;
+  let oids = select_collab_group_member_oids(txn.deref_mut(), group_id).await?;
+  if !delete_workspace_group_row(txn.deref_mut(), workspace_id, group_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Group {} does not exist in workspace {}",
+      group_id, workspace_id
+    )));
+  }
+  txn
+    .commit()
+    .await
+    .context("fail to commit the transaction to delete workspace group")?;
+
+  let guid = group_id.to_string();
+  for oid in oids {
+    collab_access_control
+      .remove_group_access_level(&guid, &oid)
+      .await?;
+  }
+  for (_, uid) in member_uids {
+    collab_access_control
+      .remove_group_member(&uid, &guid)
+      .await?;
+  }
+  Ok(())
+}
+
+/// Add a member of the workspace to the group.
+pub async fn add_workspace_group_member(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  check_workspace_group_exists(pg_pool, workspace_id, group_id).await?;
+  match select_workspace_member(pg_pool, &uid, workspace_id).await {
+    Ok(_) => {},
+    Err(AppError::RecordNotFound(_)) => {
+      return Err(AppError::InvalidRequest(format!(
+        "User {} is not a member of workspace {}",
+        uid, workspace_id
+      )))
+    },
+    Err(err) => return Err(err),
+  }
+  if !insert_workspace_group_member(pg_pool, group_id, uid).await? {
+    return Err(AppError::RecordAlreadyExists(format!(
+      "User {} is already a member of group {}",
+      uid, group_id
+    )));
+  }
+  collab_access_control
+    .add_group_member(&uid, &group_id.to_string())
+    .await?;
+  Ok(())
+}
+
+pub async fn remove_workspace_group_member(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  check_workspace_group_exists(pg_pool, workspace_id, group_id).await?;
+  if !delete_workspace_group_member(pg_pool, group_id, uid).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "User {} is not a member of group {}",
+      uid, group_id
+    )));
+  }
+  collab_access_control
+    .remove_group_member(&uid, &group_id.to_string())
+    .await?;
+  Ok(())
+}
+
+/// Grant the group the access level on the collab, replacing the level it held before.
+pub async fn upsert_collab_group_access(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+  object_id: &str,
+  access_level: AFAccessLevel,
+  bypass_membership_lock: bool,
+) -> Result<(), AppError> {
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to grant group access")?;
+  check_membership_unlocked(object_id, bypass_membership_lock, txn.deref_mut()).await?;
+  check_workspace_group_exists(txn.deref_mut(), workspace_id, group_id).await?;
+  upsert_collab_group_member(txn.deref_mut(), group_id, object_id, access_level).await?;
+  txn
+    .commit()
+    .await
+    .context("fail to commit the transaction to grant group access")?;
+  collab_access_control
+    .update_group_access_level_policy(&group_id.to_string(), object_id, access_level)
+    .await?;
+  Ok(())
+}
+
+pub async fn remove_collab_group_access(
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+  object_id: &str,
+  bypass_membership_lock: bool,
+) -> Result<(), AppError> {
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to remove group access")?;
+  check_membership_unlocked(object_id, bypass_membership_lock, txn.deref_mut()).await?;
+  check_workspace_group_exists(txn.deref_mut(), workspace_id, group_id).await?;
+  if !delete_collab_group_member(txn.deref_mut(), group_id, object_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Group {} has no access to collab {}",
+      group_id, object_id
+    )));
+  }
+  txn
+    .commit()
+    .await
+    .context("fail to commit the transaction to remove group access")?;
+  collab_access_control
+    .remove_group_access_level(&group_id.to_string(), object_id)
+    .await?;
+  Ok(())
+}
+
+async fn check_workspace_group_exists<'a, E: sqlx::Executor<'a, Database = sqlx::Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+) -> Result<(), AppError> {
+  match select_workspace_group(executor, workspace_id, group_id).await? {
+    Some(_) => Ok(()),
+    None => Err(AppError::RecordNotFound(format!(
+      "Group {} does not exist in workspace {}",
+      group_id, workspace_id
+    ))),
+  }
+}
+
+fn workspace_group_from_row(row: AFWorkspaceGroupRow, member_uids: Vec<i64>) -> WorkspaceGroup {
+  WorkspaceGroup {
+    group_id: row.group_id,
+    workspace_id: row.workspace_id,
+    name: row.name,
+    member_uids,
+    created_at: row.created_at,
+  }
+}
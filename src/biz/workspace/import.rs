@@ -0,0 +1,1360 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::{Cursor, Read};
+use std::sync::Arc;
+
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
+use collab_database::database::{timestamp, DatabaseData};
+use collab_database::entity::CreateDatabaseParams;
+use collab_database::workspace_database::WorkspaceDatabaseBody;
+use collab_document::document::Document;
+use collab_entity::CollabType;
+use collab_folder::{RepeatedViewIdentifier, View, ViewLayout};
+use database::collab::{select_workspace_database_oid, CollabStorage, GetCollabOrigin};
+use database::pg_row::AFWorkspaceImportTaskRow;
+use database::workspace_import::{
+  insert_workspace_import_task, select_workspace_import_task,
+  update_workspace_import_task_completed, update_workspace_import_task_failed,
+  update_workspace_import_task_progress,
+};
+use database_entity::dto::CollabParams;
+use serde_json::{json, Map, Value};
+use shared_entity::dto::workspace_dto::{WorkspaceImportStatus, WorkspaceImportTask};
+use sqlx::PgPool;
+use tracing::{error, warn};
+use uuid::Uuid;
+use workspace_template::database::database_collab::create_database_collab;
+use workspace_template::document::parser::JsonToDocumentParser;
+use workspace_template::gen_view_id;
+use zip::ZipArchive;
+
+use crate::biz::collab::ops::{get_latest_collab_encoded, get_latest_collab_folder};
+
+use super::publish_dup::{broadcast_update, collab_from_doc_state, collab_to_bin};
+use super::trash::{edit_folder, save_folder};
+
+/// The largest archive that can be imported, in bytes.
+pub const MAX_IMPORT_ARCHIVE_SIZE: usize = 100 * 1024 * 1024;
+/// The most bytes the files of an archive, and of the archives nested in it, can decompress to.
+const MAX_IMPORT_UNCOMPRESSED_SIZE: u64 = 500 * 1024 * 1024;
+/// The most pages an archive can contain.
+const MAX_IMPORT_PAGES: usize = 1000;
+const NOTION_IMPORT_VIEW_NAME: &str = "Notion import";
+/// Notion appends a 32 character hex id to the name of each exported page.
+const NOTION_ID_LEN: usize = 32;
+
+/// Start importing a Notion export into the workspace in the background. The archive is read
+/// right away, so that an archive without any page is rejected before the import starts. The
+/// pages are created under a new view, which is put under `parent_view_id`, or at the top level
+/// of the workspace if there is none.
+pub async fn start_notion_import(
+  pg_pool: &PgPool,
+  collab_storage: Arc<CollabAccessControlStorage>,
+  uid: i64,
+  workspace_id: Uuid,
+  parent_view_id: Option<String>,
+  archive: Vec<u8>,
+) -> Result<WorkspaceImportTask, AppError> {
+  let workspace_id_str = workspace_id.to_string();
+  let parent_view_id = parent_view_id.unwrap_or_else(|| workspace_id_str.clone());
+  if parent_view_id != workspace_id_str {
+    let folder = get_latest_collab_folder(
+      collab_storage.clone(),
+      GetCollabOrigin::User { uid },
+      &workspace_id_str,
+    )
+    .await?;
+    if folder.get_view(&parent_view_id).is_none() {
+      return Err(AppError::RecordNotFound(format!(
+        "view {} not found in workspace {}",
+        parent_view_id, workspace_id
+      )));
+    }
+  }
+
+  let pages = tokio::task::spawn_blocking(move || read_notion_archive(&archive)).await??;
+  let total_pages = count_pages(&pages);
+  if total_pages == 0 {
+    return Err(AppError::InvalidRequest(
+      "The archive does not contain any Markdown, HTML or CSV page".to_string(),
+    ));
+  }
+  if total_pages > MAX_IMPORT_PAGES {
+    return Err(AppError::InvalidRequest(format!(
+      "The archive contains more than {} pages",
+      MAX_IMPORT_PAGES
+    )));
+  }
+
+  let view_id = gen_view_id();
+  let row =
+    insert_workspace_import_task(pg_pool, &workspace_id, uid, &view_id, total_pages as i32).await?;
+  let task_id = row.task_id;
+  let pg_pool = pg_pool.clone();
+  tokio::spawn(async move {
+    let importer = NotionImporter {
+      pg_pool: pg_pool.clone(),
+      task_id,
+      uid,
+      ts_now: chrono::Utc::now().timestamp(),
+      imported_pages: 0,
+      collabs: vec![],
+      databases: vec![],
+      views: vec![],
+    };
+    let result = importer
+      .import(
+        collab_storage,
+        workspace_id_str,
+        view_id,
+        parent_view_id,
+        pages,
+      )
+      .await;
+    let updated = match result {
+      Ok(()) => update_workspace_import_task_completed(&pg_pool, &task_id).await,
+      Err(err) => {
+        warn!("Failed to import into workspace {}: {}", workspace_id, err);
+        update_workspace_import_task_failed(&pg_pool, &task_id, &err.to_string()).await
+      },
+    };
+    if let Err(err) = updated {
+      error!(
+        "Failed to update workspace import task {}: {}",
+        task_id, err
+      );
+    }
+  });
+  Ok(import_task_from_row(row))
+}
+
+/// Create a Document view holding the Markdown right after the view `sibling_view_id`, under the
+/// same parent. The name of the new view is made from the sibling view. Returns the id of the new
+/// view.
+pub async fn create_markdown_sibling_view(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &str,
+  sibling_view_id: &str,
+  name: impl FnOnce(&View) -> String,
+  markdown: &str,
+) -> Result<String, AppError> {
+  let folder = get_latest_collab_folder(
+    collab_storage.clone(),
+    GetCollabOrigin::User { uid },
+    workspace_id,
+  )
+  .await?;
+  let sibling_view = folder.get_view(sibling_view_id).ok_or_else(|| {
+    AppError::RecordNotFound(format!(
+      "view {} does not exist in workspace {}",
+      sibling_view_id, workspace_id
+    ))
+  })?;
+  let parent_view_id = sibling_view.parent_view_id.clone();
+  let view_id = gen_view_id();
+  let encoded_collab = document_collab(view_id.clone(), markdown_blocks(markdown)).await?;
+  let ts_now = chrono::Utc::now().timestamp();
+  let view = View {
+    id: view_id.clone(),
+    parent_view_id: parent_view_id.clone(),
+    name: name(&sibling_view),
+    desc: "".to_string(),
+    children: RepeatedViewIdentifier { items: vec![] },
+    created_at: ts_now,
+    is_favorite: false,
+    layout: ViewLayout::Document,
+    icon: None,
+    created_by: Some(uid),
+    last_edited_time: ts_now,
+    last_edited_by: Some(uid),
+    extra: None,
+  };
+  let (encoded_update, encoded_folder) = edit_folder(folder, |folder| {
+    let mut folder_txn = folder.collab.transact_mut();
+    folder.body.views.insert(&mut folder_txn, view, None);
+    folder.body.move_nested_view(
+      &mut folder_txn,
+      &view_id,
+      &parent_view_id,
+      Some(sibling_view_id),
+    );
+  })
+  .await?;
+
+  let mut txn = pg_pool.begin().await?;
+  collab_storage
+    .insert_new_collab_with_transaction(
+      workspace_id,
+      &uid,
+      CollabParams {
+        object_id: view_id.clone(),
+        encoded_collab_v1: encoded_collab.into(),
+        collab_type: CollabType::Document,
+        embeddings: None,
+      },
+      &mut txn,
+    )
+    .await?;
+  save_folder(&collab_storage, &mut txn, workspace_id, uid, encoded_folder).await?;
+  txn.commit().await?;
+  broadcast_update(&collab_storage, workspace_id, encoded_update).await?;
+  Ok(view_id)
+}
+
+pub async fn get_workspace_import_task(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  task_id: &Uuid,
+) -> Result<WorkspaceImportTask, AppError> {
+  let row = select_workspace_import_task(pg_pool, workspace_id, uid, task_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("workspace import {} not found", task_id)))?;
+  Ok(import_task_from_row(row))
+}
+
+fn import_task_from_row(row: AFWorkspaceImportTaskRow) -> WorkspaceImportTask {
+  WorkspaceImportTask {
+    task_id: row.task_id,
+    status: WorkspaceImportStatus::from(row.status),
+    view_id: row.view_id,
+    total_pages: row.total_pages,
+    imported_pages: row.imported_pages,
+    error: row.error,
+    created_at: row.created_at,
+    completed_at: row.completed_at,
+  }
+}
+
+/// A page of a Notion export, with the pages nested in it.
+#[derive(Debug)]
+struct NotionPage {
+  name: String,
+  content: NotionContent,
+  children: Vec<NotionPage>,
+}
+
+#[derive(Debug)]
+enum NotionContent {
+  /// The blocks of a document, in the format read by [JsonToDocumentParser].
+  Document(Vec<Value>),
+  /// The records of a database, the first one being the names of the fields.
+  Database(Vec<Vec<String>>),
+}
+
+fn count_pages(pages: &[NotionPage]) -> usize {
+  pages
+    .iter()
+    .map(|page| 1 + count_pages(&page.children))
+    .sum()
+}
+
+/// Read the pages of a Notion export. Notion exports a page as a Markdown or HTML file, and the
+/// pages nested in it in the directory next to it with the same name. Databases are exported as
+/// CSV files. Other files, such as images, are not imported.
+fn read_notion_archive(data: &[u8]) -> Result<Vec<NotionPage>, AppError> {
+  let mut files = BTreeMap::new();
+  let mut budget = MAX_IMPORT_UNCOMPRESSED_SIZE;
+  read_archive_files(data, true, &mut budget, &mut files)?;
+  Ok(build_page_tree(files))
+}
+
+/// Read the files of the archive by their path without the extension. `budget` is the number of
+/// bytes left to decompress, it is shared by all the entries and the nested archives, so that an
+/// archive that decompresses to much more than its declared sizes is rejected early.
+fn read_archive_files(
+  data: &[u8],
+  read_nested: bool,
+  budget: &mut u64,
+  files: &mut BTreeMap<String, NotionContent>,
+) -> Result<(), AppError> {
+  let invalid = |err: zip::result::ZipError| {
+    AppError::InvalidRequest(format!("The archive is not a valid zip file: {}", err))
+  };
+  let mut archive = ZipArchive::new(Cursor::new(data)).map_err(invalid)?;
+  for i in 0..archive.len() {
+    let mut entry = archive.by_index(i).map_err(invalid)?;
+    if entry.is_dir() {
+      continue;
+    }
+    // Entries whose path points outside of the archive are not imported
+    let path = match entry.enclosed_name() {
+      Some(path) => path.to_string_lossy().replace('\\', "/"),
+      None => continue,
+    };
+    let (stem, extension) = match path.rsplit_once('.') {
+      Some((stem, extension)) if !stem.is_empty() => (stem.to_string(), extension.to_lowercase()),
+      _ => continue,
+    };
+    let too_large = || AppError::InvalidRequest(format!("{} is too large to import", path));
+    if entry.size() > *budget {
+      return Err(too_large());
+    }
+    // Notion splits large exports into several archives nested in the one downloaded
+    if extension == "zip" && read_nested {
+      let nested = read_entry(&mut entry, budget)?.ok_or_else(too_large)?;
+      read_archive_files(&nested, false, budget, files)?;
+      continue;
+    }
+    // Notion exports each database twice, the `_all` file holds the rows hidden by the view too
+    if !matches!(extension.as_str(), "md" | "html" | "csv")
+      || (extension == "csv" && stem.ends_with("_all"))
+    {
+      continue;
+    }
+    let text = match read_entry(&mut entry, budget) {
+      Ok(Some(data)) => String::from_utf8(data).map_err(|err| err.to_string()),
+      Ok(None) => return Err(too_large()),
+      Err(err) => Err(err.to_string()),
+    };
+    let text = match text {
+      Ok(text) => text,
+      Err(err) => {
+        warn!("Skip importing {}: {}", path, err);
+        continue;
+      },
+    };
+    let content = match extension.as_str() {
+      "md" => NotionContent::Document(markdown_blocks(&text)),
+      "html" => NotionContent::Document(html_blocks(&text)),
+      _ => NotionContent::Database(parse_csv(&text)),
+    };
+    files.insert(stem, content);
+  }
+  Ok(())
+}
+
+/// Read an entry of an archive, without trusting the size it declares. Returns None when the entry
+/// decompresses to more than `budget` bytes, and takes the bytes read from the budget otherwise.
+fn read_entry(entry: impl Read, budget: &mut u64) -> std::io::Result<Option<Vec<u8>>> {
+  let mut data = vec![];
+  entry.take(*budget + 1).read_to_end(&mut data)?;
+  let len = data.len() as u64;
+  if len > *budget {
+    return Ok(None);
+  }
+  *budget -= len;
+  Ok(Some(data))
+}
+
+/// Nest each page in the closest page whose directory it is in. Directories without a page of
+/// their own, such as the one Notion puts the whole export in, are left out of the hierarchy.
+fn build_page_tree(mut files: BTreeMap<String, NotionContent>) -> Vec<NotionPage> {
+  let mut children: HashMap<Option<String>, Vec<String>> = HashMap::new();
+  for path in files.keys() {
+    let mut parent = None;
+    let mut dir = path.as_str();
+    while let Some((parent_dir, _)) = dir.rsplit_once('/') {
+      if files.contains_key(parent_dir) {
+        parent = Some(parent_dir.to_string());
+        break;
+      }
+      dir = parent_dir;
+    }
+    children.entry(parent).or_default().push(path.clone());
+  }
+  build_pages(None, &mut children, &mut files)
+}
+
+fn build_pages(
+  parent: Option<String>,
+  children: &mut HashMap<Option<String>, Vec<String>>,
+  files: &mut BTreeMap<String, NotionContent>,
+) -> Vec<NotionPage> {
+  let paths = children.remove(&parent).unwrap_or_default();
+  paths
+    .into_iter()
+    .filter_map(|path| {
+      let name = page_name(&path);
+      let content = match files.remove(&path)? {
+        NotionContent::Document(blocks) => NotionContent::Document(without_title(blocks, &name)),
+        database => database,
+      };
+      Some(NotionPage {
+        name,
+        content,
+        children: build_pages(Some(path), children, files),
+      })
+    })
+    .collect()
+}
+
+/// The name of the page at `path`, without the id Notion appends to it.
+fn page_name(path: &str) -> String {
+  let name = path.rsplit('/').next().unwrap_or(path).trim();
+  let name = match name.len().checked_sub(NOTION_ID_LEN) {
+    Some(id_start)
+      if name.is_char_boundary(id_start)
+        && name[id_start..].chars().all(|c| c.is_ascii_hexdigit()) =>
+    {
+      name[..id_start].trim()
+    },
+    _ => name,
+  };
+  if name.is_empty() {
+    "Untitled".to_string()
+  } else {
+    name.to_string()
+  }
+}
+
+/// Notion starts each page with its name as a heading, which the name of the view already shows.
+fn without_title(mut blocks: Vec<Value>, name: &str) -> Vec<Value> {
+  let is_title = blocks.first().map_or(false, |block| {
+    block["type"] == "heading" && block["data"]["level"] == 1 && delta_text(block) == name
+  });
+  if is_title {
+    blocks.remove(0);
+  }
+  blocks
+}
+
+fn delta_text(block: &Value) -> String {
+  block["data"]["delta"]
+    .as_array()
+    .map(|ops| {
+      ops
+        .iter()
+        .filter_map(|op| op["insert"].as_str())
+        .collect::<String>()
+    })
+    .unwrap_or_default()
+}
+
+fn block(ty: &str, data: Value) -> Value {
+  json!({ "type": ty, "data": data })
+}
+
+fn text_block(ty: &str, delta: Value) -> Value {
+  block(ty, json!({ "delta": delta }))
+}
+
+/// Convert Markdown to blocks. Blocks that are indented are nested in the block above them.
+fn markdown_blocks(markdown: &str) -> Vec<Value> {
+  let mut blocks = vec![];
+  let mut lines = markdown.lines();
+  while let Some(line) = lines.next() {
+    let indent = line.len() - line.trim_start().len();
+    let line = line.trim();
+    if line.is_empty() {
+      continue;
+    }
+    if let Some(language) = line.strip_prefix("```") {
+      let mut code = vec![];
+      for line in lines.by_ref() {
+        if line.trim_start().starts_with("```") {
+          break;
+        }
+        code.push(line);
+      }
+      let data = json!({
+        "language": language.trim(),
+        "delta": [{ "insert": code.join("\n") }],
+      });
+      blocks.push((indent, block("code", data)));
+      continue;
+    }
+    blocks.push((indent, markdown_block(line)));
+  }
+  nest_blocks(blocks)
+}
+
+fn markdown_block(line: &str) -> Value {
+  let level = line.chars().take_while(|c| *c == '#').count();
+  if (1..=6).contains(&level) && line[level..].starts_with(' ') {
+    let data = json!({ "level": level, "delta": markdown_delta(line[level..].trim()) });
+    return block("heading", data);
+  }
+  if matches!(line, "---" | "***" | "___") {
+    return block("divider", json!({}));
+  }
+  for (prefix, checked) in [("- [ ] ", false), ("- [x] ", true), ("- [X] ", true)] {
+    if let Some(text) = line.strip_prefix(prefix) {
+      let data = json!({ "checked": checked, "delta": markdown_delta(text) });
+      return block("todo_list", data);
+    }
+  }
+  for prefix in ["- ", "* ", "+ "] {
+    if let Some(text) = line.strip_prefix(prefix) {
+      return text_block("bulleted_list", markdown_delta(text));
+    }
+  }
+  let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+  if let Some(text) = line[digits..].strip_prefix(". ").filter(|_| digits > 0) {
+    return text_block("numbered_list", markdown_delta(text));
+  }
+  if let Some(text) = line.strip_prefix('>') {
+    return text_block("quote", markdown_delta(text.trim_start()));
+  }
+  text_block("paragraph", markdown_delta(line))
+}
+
+/// Convert inline Markdown to a delta. Bold, italic, strikethrough, inline code and links are
+/// kept, but not when nested in one another.
+fn markdown_delta(text: &str) -> Value {
+  let mut ops = vec![];
+  let mut plain = String::new();
+  let mut rest = text;
+  while let Some(c) = rest.chars().next() {
+    if let Some((len, insert, attributes)) = markdown_span(rest) {
+      if !plain.is_empty() {
+        ops.push(json!({ "insert": std::mem::take(&mut plain) }));
+      }
+      if attributes.is_empty() {
+        ops.push(json!({ "insert": insert }));
+      } else {
+        ops.push(json!({ "insert": insert, "attributes": attributes }));
+      }
+      rest = &rest[len..];
+      continue;
+    }
+    rest = &rest[c.len_utf8()..];
+    match rest.chars().next() {
+      Some(escaped) if c == '\\' && escaped.is_ascii_punctuation() => {
+        plain.push(escaped);
+        rest = &rest[escaped.len_utf8()..];
+      },
+      _ => plain.push(c),
+    }
+  }
+  if !plain.is_empty() {
+    ops.push(json!({ "insert": plain }));
+  }
+  Value::Array(ops)
+}
+
+/// Returns the length, the text and the attributes of the formatted span `text` starts with.
+fn markdown_span(text: &str) -> Option<(usize, String, Map<String, Value>)> {
+  for (marker, attribute) in [
+    ("**", "bold"),
+    ("~~", "strikethrough"),
+    ("`", "code"),
+    ("*", "italic"),
+  ] {
+    let end = text
+      .strip_prefix(marker)
+      .and_then(|inner| inner.find(marker))
+      .filter(|end| *end > 0);
+    if let Some(end) = end {
+      let inner = &text[marker.len()..marker.len() + end];
+      let mut attributes = Map::new();
+      attributes.insert(attribute.to_string(), Value::Bool(true));
+      return Some((end + 2 * marker.len(), inner.to_string(), attributes));
+    }
+  }
+  // Links to other files of the export are kept as plain text
+  let (label, rest) = text.strip_prefix('[')?.split_once("](")?;
+  let end = rest.find(')')?;
+  let href = rest[..end].trim_matches(|c| c == '<' || c == '>');
+  let mut attributes = Map::new();
+  if href.starts_with("http://") || href.starts_with("https://") || href.starts_with("mailto:") {
+    attributes.insert("href".to_string(), Value::String(href.to_string()));
+  }
+  Some((label.len() + end + 4, label.to_string(), attributes))
+}
+
+/// Nest each block in the closest block above it that is indented less.
+fn nest_blocks(blocks: Vec<(usize, Value)>) -> Vec<Value> {
+  fn pop(stack: &mut Vec<(usize, Value)>, nested: &mut Vec<Value>) {
+    if let Some((_, block)) = stack.pop() {
+      match stack.last_mut() {
+        Some((_, parent)) => match parent["children"].as_array_mut() {
+          Some(children) => children.push(block),
+          None => parent["children"] = json!([block]),
+        },
+        None => nested.push(block),
+      }
+    }
+  }
+
+  let mut nested = vec![];
+  let mut stack: Vec<(usize, Value)> = vec![];
+  for (indent, block) in blocks {
+    while stack.last().map_or(false, |(last, _)| *last >= indent) {
+      pop(&mut stack, &mut nested);
+    }
+    stack.push((indent, block));
+  }
+  while !stack.is_empty() {
+    pop(&mut stack, &mut nested);
+  }
+  nested
+}
+
+/// A block of an HTML page whose text is being read.
+struct HtmlBlock {
+  indent: usize,
+  ty: &'static str,
+  data: Value,
+  text: String,
+}
+
+/// Convert the HTML of a page to blocks. The headings, paragraphs, lists, quotes, code and
+/// dividers of the page are kept, with their text unformatted.
+fn html_blocks(html: &str) -> Vec<Value> {
+  let mut blocks = vec![];
+  let mut current: Option<HtmlBlock> = None;
+  let mut lists: Vec<&'static str> = vec![];
+  // The depth of the elements whose content is not imported the reader is in
+  let mut skipped: usize = 0;
+  let mut rest = html;
+
+  let flush = |current: &mut Option<HtmlBlock>, blocks: &mut Vec<(usize, Value)>| {
+    if let Some(html_block) = current.take() {
+      let text = html_block.text.trim();
+      if !text.is_empty() {
+        let mut data = html_block.data;
+        data["delta"] = json!([{ "insert": text }]);
+        blocks.push((html_block.indent, block(html_block.ty, data)));
+      }
+    }
+  };
+
+  loop {
+    let (text, tag_start) = match rest.find('<') {
+      Some(start) => (&rest[..start], Some(start)),
+      None => (rest, None),
+    };
+    if skipped == 0 && (current.is_some() || !text.trim().is_empty()) {
+      let html_block = current.get_or_insert_with(|| HtmlBlock {
+        indent: lists.len(),
+        ty: "paragraph",
+        data: json!({}),
+        text: String::new(),
+      });
+      let text = decode_html_entities(text);
+      if html_block.ty == "code" {
+        html_block.text.push_str(&text);
+      } else {
+        for c in text.chars() {
+          if !c.is_whitespace() {
+            html_block.text.push(c);
+          } else if !html_block.text.is_empty() && !html_block.text.ends_with([' ', '\n']) {
+            html_block.text.push(' ');
+          }
+        }
+      }
+    }
+    let tag_start = match tag_start {
+      Some(tag_start) => tag_start,
+      None => break,
+    };
+    let tag_end = match rest[tag_start..].find('>') {
+      Some(end) => tag_start + end,
+      None => break,
+    };
+    let tag = &rest[tag_start + 1..tag_end];
+    rest = &rest[tag_end + 1..];
+    if tag.starts_with('!') {
+      continue;
+    }
+    let closing = tag.starts_with('/');
+    let name = tag
+      .trim_start_matches('/')
+      .split(|c: char| c.is_whitespace() || c == '/')
+      .next()
+      .unwrap_or_default()
+      .to_lowercase();
+    if matches!(name.as_str(), "head" | "header" | "style" | "script") {
+      if closing {
+        skipped = skipped.saturating_sub(1);
+      } else {
+        skipped += 1;
+      }
+      continue;
+    }
+    if skipped > 0 {
+      continue;
+    }
+
+    let open = |ty: &'static str, data: Value, indent: usize| HtmlBlock {
+      indent,
+      ty,
+      data,
+      text: String::new(),
+    };
+    match name.as_str() {
+      "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+        flush(&mut current, &mut blocks);
+        if !closing {
+          let level = name[1..].parse::<usize>().unwrap_or(1);
+          current = Some(open("heading", json!({ "level": level }), lists.len()));
+        }
+      },
+      "p" | "blockquote" | "pre" => {
+        flush(&mut current, &mut blocks);
+        if !closing {
+          let (ty, data) = match name.as_str() {
+            "blockquote" => ("quote", json!({})),
+            "pre" => ("code", json!({ "language": "" })),
+            _ => ("paragraph", json!({})),
+          };
+          current = Some(open(ty, data, lists.len()));
+        }
+      },
+      "ul" | "ol" => {
+        flush(&mut current, &mut blocks);
+        if closing {
+          lists.pop();
+        } else if tag.contains("to-do-list") {
+          lists.push("todo_list");
+        } else if name == "ol" {
+          lists.push("numbered_list");
+        } else {
+          lists.push("bulleted_list");
+        }
+      },
+      "li" => {
+        flush(&mut current, &mut blocks);
+        if !closing {
+          let ty = lists.last().copied().unwrap_or("bulleted_list");
+          let data = if ty == "todo_list" {
+            json!({ "checked": false })
+          } else {
+            json!({})
+          };
+          current = Some(open(ty, data, lists.len().saturating_sub(1)));
+        }
+      },
+      "hr" => {
+        flush(&mut current, &mut blocks);
+        blocks.push((lists.len(), block("divider", json!({}))));
+      },
+      "br" => {
+        if let Some(html_block) = current.as_mut() {
+          html_block.text.push('\n');
+        }
+      },
+      "div" | "section" | "table" | "tr" | "figure" | "details" | "summary" => {
+        // Only text that is not in a block of its own is split by the elements around it
+        if tag.contains("checkbox-on") {
+          if let Some(html_block) = current.as_mut().filter(|block| block.ty == "todo_list") {
+            html_block.data["checked"] = json!(true);
+          }
+        } else if current
+          .as_ref()
+          .map_or(false, |block| block.ty == "paragraph")
+        {
+          flush(&mut current, &mut blocks);
+        }
+      },
+      _ => {},
+    }
+  }
+  flush(&mut current, &mut blocks);
+  nest_blocks(blocks)
+}
+
+fn decode_html_entities(text: &str) -> String {
+  let mut decoded = String::with_capacity(text.len());
+  let mut rest = text;
+  while let Some(start) = rest.find('&') {
+    decoded.push_str(&rest[..start]);
+    rest = &rest[start..];
+    let entity = rest[1..]
+      .find(';')
+      .filter(|end| *end <= 10)
+      .map(|end| &rest[1..end + 1]);
+    let c = entity.and_then(|entity| match entity {
+      "amp" => Some('&'),
+      "lt" => Some('<'),
+      "gt" => Some('>'),
+      "quot" => Some('"'),
+      "apos" => Some('\''),
+      "nbsp" => Some(' '),
+      _ => entity
+        .strip_prefix("#x")
+        .or_else(|| entity.strip_prefix("#X"))
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+        .and_then(char::from_u32),
+    });
+    match (entity, c) {
+      (Some(entity), Some(c)) => {
+        decoded.push(c);
+        rest = &rest[entity.len() + 2..];
+      },
+      _ => {
+        decoded.push('&');
+        rest = &rest[1..];
+      },
+    }
+  }
+  decoded.push_str(rest);
+  decoded
+}
+
+/// Parse CSV as Notion writes it: fields are separated by commas, and quoted when they contain
+/// commas, quotes or line breaks.
+pub(crate) fn parse_csv(csv: &str) -> Vec<Vec<String>> {
+  let mut records = vec![];
+  let mut record = vec![];
+  let mut field = String::new();
+  let mut in_quotes = false;
+  let mut chars = csv.trim_start_matches('\u{feff}').chars().peekable();
+  while let Some(c) = chars.next() {
+    if in_quotes {
+      match c {
+        '"' if chars.peek() == Some(&'"') => {
+          chars.next();
+          field.push('"');
+        },
+        '"' => in_quotes = false,
+        c => field.push(c),
+      }
+      continue;
+    }
+    match c {
+      '"' => in_quotes = true,
+      ',' => record.push(std::mem::take(&mut field)),
+      '\r' => {},
+      '\n' => {
+        record.push(std::mem::take(&mut field));
+        records.push(std::mem::take(&mut record));
+      },
+      c => field.push(c),
+    }
+  }
+  if !field.is_empty() || !record.is_empty() {
+    record.push(field);
+    records.push(record);
+  }
+  records.retain(|record| record.iter().any(|field| !field.is_empty()));
+  records
+}
+
+/// The data of a grid with a text field for each column of the CSV. The first column is the
+/// primary field, as Notion exports the title of the rows first.
+fn database_data(database_id: &str, view_id: &str, name: &str, records: &[Vec<String>]) -> Value {
+  let now = timestamp();
+  let (header, records) = match records.split_first() {
+    Some((header, records)) => (header.clone(), records),
+    None => (vec![], records),
+  };
+  let header = if header.is_empty() {
+    vec!["Name".to_string()]
+  } else {
+    header
+  };
+  let field_ids: Vec<String> = header.iter().map(|_| Uuid::new_v4().to_string()).collect();
+  let fields: Vec<Value> = header
+    .iter()
+    .enumerate()
+    .map(|(i, field_name)| {
+      let field_name = if field_name.trim().is_empty() {
+        format!("Field {}", i + 1)
+      } else {
+        field_name.trim().to_string()
+      };
+      json!({
+        "id": field_ids[i],
+        "name": field_name,
+        "field_type": 0,
+        "type_options": { "0": { "data": "" } },
+        "is_primary": i == 0,
+      })
+    })
+    .collect();
+  let rows: Vec<Value> = records
+    .iter()
+    .map(|record| {
+      let cells: Map<String, Value> = field_ids
+        .iter()
+        .zip(record)
+        .filter(|(_, data)| !data.is_empty())
+        .map(|(field_id, data)| (field_id.clone(), json!({ "field_type": 0, "data": data })))
+        .collect();
+      json!({
+        "id": Uuid::new_v4().to_string(),
+        "database_id": database_id,
+        "cells": cells,
+        "height": 60,
+        "visibility": true,
+        "created_at": now,
+        "modified_at": now,
+      })
+    })
+    .collect();
+  let row_orders: Vec<Value> = rows
+    .iter()
+    .map(|row| json!({ "id": row["id"], "height": 60 }))
+    .collect();
+  let field_orders: Vec<Value> = field_ids.iter().map(|id| json!({ "id": id })).collect();
+  let field_settings: Map<String, Value> = field_ids
+    .iter()
+    .map(|id| {
+      (
+        id.clone(),
+        json!({ "visibility": 0, "width": 150, "wrap": true }),
+      )
+    })
+    .collect();
+  json!({
+    "database_id": database_id,
+    "inline_view_id": view_id,
+    "views": [{
+      "id": view_id,
+      "database_id": database_id,
+      "name": name,
+      "layout": 0,
+      "layout_settings": {},
+      "filters": [],
+      "group_settings": [],
+      "sorts": [],
+      "row_orders": row_orders,
+      "field_orders": field_orders,
+      "field_settings": field_settings,
+      "created_at": now,
+      "modified_at": now,
+    }],
+    "fields": fields,
+    "rows": rows,
+  })
+}
+
+/// Creates the collabs and views of the pages of an import, and adds them to the workspace once
+/// all of them are created.
+struct NotionImporter {
+  pg_pool: PgPool,
+  task_id: Uuid,
+  uid: i64,
+  ts_now: i64,
+  imported_pages: i32,
+  /// The encoded collabs to insert, by object id.
+  collabs: Vec<(String, CollabType, Vec<u8>)>,
+  /// The databases to add to the workspace database, with their views.
+  databases: Vec<(String, Vec<String>)>,
+  /// The views to add to the folder, each one after its parent.
+  views: Vec<View>,
+}
+
+impl NotionImporter {
+  async fn import(
+    mut self,
+    collab_storage: Arc<CollabAccessControlStorage>,
+    workspace_id: String,
+    view_id: String,
+    parent_view_id: String,
+    pages: Vec<NotionPage>,
+  ) -> Result<(), AppError> {
+    let root_collab = document_collab(view_id.clone(), vec![]).await?;
+    self
+      .collabs
+      .push((view_id.clone(), CollabType::Document, root_collab));
+    let root_view = self.new_view(
+      view_id.clone(),
+      parent_view_id,
+      NOTION_IMPORT_VIEW_NAME.to_string(),
+      ViewLayout::Document,
+    );
+    self.views.push(root_view);
+    self.import_pages(pages, view_id).await?;
+    self.insert(collab_storage, workspace_id).await
+  }
+
+  /// Create the pages level by level, so that the view of a page is added after its parent.
+  async fn import_pages(
+    &mut self,
+    pages: Vec<NotionPage>,
+    root_view_id: String,
+  ) -> Result<(), AppError> {
+    let mut queue: VecDeque<(String, NotionPage)> = pages
+      .into_iter()
+      .map(|page| (root_view_id.clone(), page))
+      .collect();
+    while let Some((parent_view_id, page)) = queue.pop_front() {
+      let view_id = gen_view_id();
+      let layout = match page.content {
+        NotionContent::Document(blocks) => {
+          let encoded_collab = document_collab(view_id.clone(), blocks).await?;
+          self
+            .collabs
+            .push((view_id.clone(), CollabType::Document, encoded_collab));
+          ViewLayout::Document
+        },
+        NotionContent::Database(records) => {
+          self.add_database(&view_id, &page.name, &records).await?;
+          ViewLayout::Grid
+        },
+      };
+      let view = self.new_view(view_id.clone(), parent_view_id, page.name, layout);
+      self.views.push(view);
+      self.imported_pages += 1;
+      update_workspace_import_task_progress(&self.pg_pool, &self.task_id, self.imported_pages)
+        .await?;
+      queue.extend(
+        page
+          .children
+          .into_iter()
+          .map(|child| (view_id.clone(), child)),
+      );
+    }
+    Ok(())
+  }
+
+  async fn add_database(
+    &mut self,
+    view_id: &str,
+    name: &str,
+    records: &[Vec<String>],
+  ) -> Result<(), AppError> {
+    let database_id = Uuid::new_v4().to_string();
+    let database_data: DatabaseData =
+      serde_json::from_value(database_data(&database_id, view_id, name, records))?;
+    let params = CreateDatabaseParams::from_database_data(database_data, Some(view_id.to_string()));
+    let database_id = params.database_id.clone();
+    let encoded_database = create_database_collab(params).await?;
+    self.collabs.push((
+      database_id.clone(),
+      CollabType::Database,
+      encoded_database
+        .encoded_database_collab
+        .encoded_collab
+        .encode_to_bytes()?,
+    ));
+    for row in encoded_database.encoded_row_collabs {
+      self.collabs.push((
+        row.object_id,
+        CollabType::DatabaseRow,
+        row.encoded_collab.encode_to_bytes()?,
+      ));
+    }
+    self
+      .databases
+      .push((database_id, vec![view_id.to_string()]));
+    Ok(())
+  }
+
+  fn new_view(
+    &self,
+    view_id: String,
+    parent_view_id: String,
+    name: String,
+    layout: ViewLayout,
+  ) -> View {
+    View {
+      id: view_id,
+      parent_view_id,
+      name,
+      desc: "".to_string(),
+      children: RepeatedViewIdentifier { items: vec![] },
+      created_at: self.ts_now,
+      is_favorite: false,
+      layout,
+      icon: None,
+      created_by: Some(self.uid),
+      last_edited_time: self.ts_now,
+      last_edited_by: Some(self.uid),
+      extra: None,
+    }
+  }
+
+  /// Insert the collabs, and add the databases to the workspace database and the views to the
+  /// folder, all in one transaction.
+  async fn insert(
+    self,
+    collab_storage: Arc<CollabAccessControlStorage>,
+    workspace_id: String,
+  ) -> Result<(), AppError> {
+    let NotionImporter {
+      pg_pool,
+      uid,
+      collabs,
+      databases,
+      views,
+      ..
+    } = self;
+
+    let mut txn = pg_pool.begin().await?;
+    for (object_id, collab_type, encoded_collab) in collabs {
+      collab_storage
+        .insert_new_collab_with_transaction(
+          &workspace_id,
+          &uid,
+          CollabParams {
+            object_id,
+            encoded_collab_v1: encoded_collab.into(),
+            collab_type,
+            embeddings: None,
+          },
+          &mut txn,
+        )
+        .await?;
+    }
+
+    if !databases.is_empty() {
+      let ws_db_oid = select_workspace_database_oid(&pg_pool, &workspace_id.parse()?).await?;
+      let mut ws_db_collab = {
+        let ws_database_ec = get_latest_collab_encoded(
+          collab_storage.clone(),
+          GetCollabOrigin::User { uid },
+          &workspace_id,
+          &ws_db_oid,
+          CollabType::WorkspaceDatabase,
+        )
+        .await?;
+        collab_from_doc_state(ws_database_ec.doc_state.to_vec(), &ws_db_oid)?
+      };
+      let ws_db_body = WorkspaceDatabaseBody::open(&mut ws_db_collab);
+      let (ws_db_updates, updated_ws_db_collab) = tokio::task::spawn_blocking(move || {
+        let ws_db_updates = {
+          let mut txn_wrapper = ws_db_collab.transact_mut();
+          for (database_id, linked_views) in databases {
+            ws_db_body.add_database(&mut txn_wrapper, &database_id, linked_views);
+          }
+          txn_wrapper.encode_update_v1()
+        };
+        let updated_ws_db_collab = collab_to_bin(ws_db_collab, CollabType::WorkspaceDatabase);
+        (ws_db_updates, updated_ws_db_collab)
+      })
+      .await?;
+      collab_storage
+        .insert_new_collab_with_transaction(
+          &workspace_id,
+          &uid,
+          CollabParams {
+            object_id: ws_db_oid.clone(),
+            encoded_collab_v1: updated_ws_db_collab.await?.into(),
+            collab_type: CollabType::WorkspaceDatabase,
+            embeddings: None,
+          },
+          &mut txn,
+        )
+        .await?;
+      broadcast_update(&collab_storage, &ws_db_oid, ws_db_updates).await?;
+    }
+
+    let mut folder = get_latest_collab_folder(
+      collab_storage.clone(),
+      GetCollabOrigin::User { uid },
+      &workspace_id,
+    )
+    .await?;
+    let (encoded_update, updated_encoded_collab) = tokio::task::spawn_blocking(move || {
+      let encoded_update = {
+        let mut folder_txn = folder.collab.transact_mut();
+        for view in views {
+          folder.body.views.insert(&mut folder_txn, view, None);
+        }
+        folder_txn.encode_update_v1()
+      };
+      let updated_encoded_collab = collab_to_bin(folder.collab, CollabType::Folder);
+      (encoded_update, updated_encoded_collab)
+    })
+    .await?;
+    collab_storage
+      .insert_new_collab_with_transaction(
+        &workspace_id,
+        &uid,
+        CollabParams {
+          object_id: workspace_id.clone(),
+          encoded_collab_v1: updated_encoded_collab.await?.into(),
+          collab_type: CollabType::Folder,
+          embeddings: None,
+        },
+        &mut txn,
+      )
+      .await?;
+    broadcast_update(&collab_storage, &workspace_id, encoded_update).await?;
+
+    txn.commit().await?;
+    Ok(())
+  }
+}
+
+/// Returns the encoded collab of a document made of the blocks. Documents without blocks start
+/// with an empty paragraph, for the cursor to be put in.
+async fn document_collab(object_id: String, mut blocks: Vec<Value>) -> Result<Vec<u8>, AppError> {
+  if blocks.is_empty() {
+    blocks.push(text_block("paragraph", json!([])));
+  }
+  tokio::task::spawn_blocking(move || {
+    let json = json!({ "type": "page", "children": blocks }).to_string();
+    let document_data = JsonToDocumentParser::json_str_to_document(&json)?;
+    let collab = Collab::new_with_origin(CollabOrigin::Empty, &object_id, vec![], false);
+    let document = Document::create_with_data(collab, document_data)
+      .map_err(|e| AppError::Unhandled(e.to_string()))?;
+    let encoded_collab = document
+      .encode_collab()
+      .map_err(|e| AppError::Unhandled(e.to_string()))?;
+    Ok(encoded_collab.encode_to_bytes()?)
+  })
+  .await?
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io::Write;
+
+  use zip::write::SimpleFileOptions;
+  use zip::ZipWriter;
+
+  use super::*;
+
+  #[test]
+  fn notion_page_name_test() {
+    assert_eq!(
+      page_name("Export/Projects 1f2e3d4c5b6a79881f2e3d4c5b6a7988"),
+      "Projects"
+    );
+    assert_eq!(page_name("Export/Notes"), "Notes");
+    assert_eq!(page_name("1f2e3d4c5b6a79881f2e3d4c5b6a7988"), "Untitled");
+  }
+
+  #[test]
+  fn notion_archive_decompressed_size_test() {
+    let mut nested = ZipWriter::new(Cursor::new(Vec::new()));
+    for name in ["a.md", "b.md"] {
+      nested
+        .start_file(name, SimpleFileOptions::default())
+        .unwrap();
+      nested.write_all(&[b'a'; 600]).unwrap();
+    }
+    let nested = nested.finish().unwrap().into_inner();
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip
+      .start_file("Export-1.zip", SimpleFileOptions::default())
+      .unwrap();
+    zip.write_all(&nested).unwrap();
+    let data = zip.finish().unwrap().into_inner();
+
+    // Each file fits the budget, but the nested archive and its files together do not
+    let mut budget = nested.len() as u64 + 1000;
+    let mut files = BTreeMap::new();
+    assert!(read_archive_files(&data, true, &mut budget, &mut files).is_err());
+
+    let mut budget = nested.len() as u64 + 1200;
+    let mut files = BTreeMap::new();
+    read_archive_files(&data, true, &mut budget, &mut files).unwrap();
+    assert_eq!(files.len(), 2);
+    assert_eq!(budget, 0);
+  }
+
+  #[test]
+  fn notion_archive_hierarchy_test() {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let files = [
+      (
+        "Export-1/Projects 11111111111111111111111111111111.md",
+        "# Projects\n\nAll the projects",
+      ),
+      (
+        "Export-1/Projects 11111111111111111111111111111111/Launch 22222222222222222222222222222222.md",
+        "# Launch\n\n- [x] Plan",
+      ),
+      (
+        "Export-1/Projects 11111111111111111111111111111111/Tasks 33333333333333333333333333333333.csv",
+        "Name,Status\nWrite docs,Done\n",
+      ),
+      (
+        "Export-1/Projects 11111111111111111111111111111111/Tasks 33333333333333333333333333333333_all.csv",
+        "Name,Status\nWrite docs,Done\n",
+      ),
+      ("Export-1/image.png", "png"),
+    ];
+    for (path, content) in files {
+      zip.start_file(path, SimpleFileOptions::default()).unwrap();
+      zip.write_all(content.as_bytes()).unwrap();
+    }
+    let data = zip.finish().unwrap().into_inner();
+
+    let pages = read_notion_archive(&data).unwrap();
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].name, "Projects");
+    assert_eq!(count_pages(&pages), 3);
+    match &pages[0].content {
+      NotionContent::Document(blocks) => {
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(delta_text(&blocks[0]), "All the projects");
+      },
+      _ => panic!("expected a document"),
+    }
+    let children: Vec<&str> = pages[0]
+      .children
+      .iter()
+      .map(|page| page.name.as_str())
+      .collect();
+    assert_eq!(children, vec!["Launch", "Tasks"]);
+    assert!(matches!(
+      &pages[0].children[1].content,
+      NotionContent::Database(records) if records.len() == 2
+    ));
+  }
+
+  #[test]
+  fn markdown_blocks_test() {
+    let blocks = markdown_blocks(
+      "## Plan\n\n- **Bold** and [link](https://appflowy.io)\n    1. Nested\n- [ ] Todo\n\n```rust\nfn main() {}\n```\n---\n> Quote",
+    );
+    let types: Vec<&str> = blocks
+      .iter()
+      .map(|block| block["type"].as_str().unwrap())
+      .collect();
+    assert_eq!(
+      types,
+      vec![
+        "heading",
+        "bulleted_list",
+        "todo_list",
+        "code",
+        "divider",
+        "quote"
+      ]
+    );
+    assert_eq!(blocks[0]["data"]["level"], 2);
+    assert_eq!(blocks[1]["data"]["delta"][0]["attributes"]["bold"], true);
+    assert_eq!(
+      blocks[1]["data"]["delta"][2]["attributes"]["href"],
+      "https://appflowy.io"
+    );
+    assert_eq!(blocks[1]["children"][0]["type"], "numbered_list");
+    assert_eq!(blocks[2]["data"]["checked"], false);
+    assert_eq!(delta_text(&blocks[3]), "fn main() {}");
+  }
+
+  #[test]
+  fn html_blocks_test() {
+    let blocks = html_blocks(
+      "<html><head><title>Page</title></head><body><header><h1 class=\"page-title\">Page</h1></header>\
+       <h2>Tom &amp; Jerry</h2><p>Some <strong>bold</strong> text</p>\
+       <ul><li>One<ul><li>Nested</li></ul></li></ul>\
+       <ul class=\"to-do-list\"><li><div class=\"checkbox checkbox-on\"></div> Done</li></ul>\
+       <hr></body></html>",
+    );
+    let types: Vec<&str> = blocks
+      .iter()
+      .map(|block| block["type"].as_str().unwrap())
+      .collect();
+    assert_eq!(
+      types,
+      vec![
+        "heading",
+        "paragraph",
+        "bulleted_list",
+        "todo_list",
+        "divider"
+      ]
+    );
+    assert_eq!(delta_text(&blocks[0]), "Tom & Jerry");
+    assert_eq!(delta_text(&blocks[1]), "Some bold text");
+    assert_eq!(delta_text(&blocks[2]["children"][0]), "Nested");
+    assert_eq!(blocks[3]["data"]["checked"], true);
+  }
+
+  #[test]
+  fn parse_csv_test() {
+    let records = parse_csv("\u{feff}Name,Notes\r\n\"a, b\",\"say \"\"hi\"\"\nagain\"\r\n\r\nc,\n");
+    assert_eq!(
+      records,
+      vec![
+        vec!["Name".to_string(), "Notes".to_string()],
+        vec!["a, b".to_string(), "say \"hi\"\nagain".to_string()],
+        vec!["c".to_string(), "".to_string()],
+      ]
+    );
+  }
+}
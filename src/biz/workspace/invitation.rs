@@ -0,0 +1,146 @@
+use app_error::AppError;
+use chrono::{Duration, Utc};
+use database::pg_row::AFWorkspacePendingInvitationRow;
+use database::user::select_name_from_uuid;
+use database::workspace::{
+  delete_workspace_pending_invitation, select_pending_invitation_ids_for_email,
+  select_workspace_member_count_from_workspace_id, select_workspace_name_from_workspace_id,
+  select_workspace_pending_invitation, select_workspace_pending_invitation_list,
+  update_workspace_invitation_sent,
+};
+use database_entity::dto::AFRole;
+use serde_json::json;
+use shared_entity::dto::workspace_dto::{AuditAction, PendingWorkspaceInvitation};
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::biz::workspace::audit_log::record_audit_log;
+use crate::biz::workspace::ops::{accept_workspace_invite, send_workspace_invitation_email};
+use crate::state::AppState;
+
+/// The email of an invitation is sent again at most once per interval.
+const INVITATION_RESEND_INTERVAL_SECS: i64 = 60;
+
+/// Returns the invitations of the workspace that are still pending, the most recent first.
+pub async fn list_pending_workspace_invitations(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<PendingWorkspaceInvitation>, AppError> {
+  let rows = select_workspace_pending_invitation_list(pg_pool, workspace_id).await?;
+  Ok(rows.into_iter().map(invitation_from_row).collect())
+}
+
+/// Send the email of the pending invitation again, with a new link, on behalf of `inviter`.
+pub async fn resend_workspace_invitation(
+  state: &AppState,
+  inviter: &Uuid,
+  workspace_id: &Uuid,
+  invite_id: &Uuid,
+) -> Result<PendingWorkspaceInvitation, AppError> {
+  let row = select_workspace_pending_invitation(&state.pg_pool, workspace_id, invite_id)
+    .await?
+    .ok_or_else(|| no_pending_invitation(invite_id))?;
+  if Utc::now() - row.last_sent_at < Duration::seconds(INVITATION_RESEND_INTERVAL_SECS) {
+    return Err(AppError::TooManyRequests(format!(
+      "The invitation was sent less than {} seconds ago",
+      INVITATION_RESEND_INTERVAL_SECS
+    )));
+  }
+
+  let inviter_name = select_name_from_uuid(&state.pg_pool, inviter).await?;
+  let workspace_name = select_workspace_name_from_workspace_id(&state.pg_pool, workspace_id)
+    .await?
+    .unwrap_or_default();
+  let workspace_member_count =
+    select_workspace_member_count_from_workspace_id(&state.pg_pool, workspace_id)
+      .await?
+      .unwrap_or_default();
+  let admin_token = state.gotrue_admin.token().await?;
+  send_workspace_invitation_email(
+    &state.mailer,
+    &state.gotrue_client,
+    &admin_token,
+    invite_id,
+    row.invitee_email.clone(),
+    inviter_name,
+    workspace_name,
+    workspace_member_count.to_string(),
+    state.config.appflowy_web_url.as_deref(),
+  )
+  .await?;
+  update_workspace_invitation_sent(&state.pg_pool, invite_id).await?;
+
+  let row = select_workspace_pending_invitation(&state.pg_pool, workspace_id, invite_id)
+    .await?
+    .ok_or_else(|| no_pending_invitation(invite_id))?;
+  Ok(invitation_from_row(row))
+}
+
+/// Revoke the pending invitation, its link no longer adds the invitee to the workspace.
+pub async fn revoke_workspace_invitation(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  invite_id: &Uuid,
+) -> Result<(), AppError> {
+  let email = delete_workspace_pending_invitation(pg_pool, workspace_id, invite_id)
+    .await?
+    .ok_or_else(|| no_pending_invitation(invite_id))?;
+  record_audit_log(
+    pg_pool,
+    Some(workspace_id),
+    Some(uid),
+    AuditAction::MemberInvitationRevoked,
+    Some(&email),
+    json!({ "invite_id": invite_id }),
+  )
+  .await;
+  Ok(())
+}
+
+/// Accept the invitations sent to the email of a user that just signed up. The user was invited
+/// before having an account, and signing up proves the user owns the email. A failure to accept
+/// one of the invitations is logged rather than returned, so that it does not fail the sign up.
+pub async fn accept_pending_invitations_of_new_user(
+  state: &AppState,
+  uid: i64,
+  user_uuid: &Uuid,
+  email: &str,
+) -> Result<(), AppError> {
+  for invite_id in select_pending_invitation_ids_for_email(&state.pg_pool, email).await? {
+    match accept_workspace_invite(
+      &state.pg_pool,
+      &state.workspace_access_control,
+      uid,
+      user_uuid,
+      &invite_id,
+    )
+    .await
+    {
+      Ok(()) => info!("user {} accepted invitation {} on sign up", uid, invite_id),
+      Err(err) => warn!(
+        "Failed to accept invitation {} of new user {}: {}",
+        invite_id, uid, err
+      ),
+    }
+  }
+  Ok(())
+}
+
+fn no_pending_invitation(invite_id: &Uuid) -> AppError {
+  AppError::RecordNotFound(format!("There is no pending invitation {}", invite_id))
+}
+
+fn invitation_from_row(row: AFWorkspacePendingInvitationRow) -> PendingWorkspaceInvitation {
+  PendingWorkspaceInvitation {
+    invite_id: row.invite_id,
+    email: row.invitee_email,
+    role: AFRole::from(row.role_id),
+    inviter_uid: row.inviter_uid,
+    inviter_email: row.inviter_email,
+    created_at: row.created_at,
+    last_sent_at: row.last_sent_at,
+    sent_count: row.sent_count,
+  }
+}
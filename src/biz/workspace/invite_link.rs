@@ -0,0 +1,324 @@
+use std::ops::DerefMut;
+
+use access_control::workspace::WorkspaceAccessControl;
+use anyhow::Context;
+use app_error::AppError;
+use chrono::{DateTime, Duration, Utc};
+use database::pg_row::AFWorkspaceInviteLinkRow;
+use database::user::select_is_email_confirmed;
+use database::workspace::select_user_role;
+use database::workspace_invite_link::{
+  delete_workspace_invite_link as delete_workspace_invite_link_row, insert_workspace_invite_link,
+  insert_workspace_invite_link_join, select_workspace_invite_link_by_token_for_update,
+  select_workspace_invite_link_join_count, select_workspace_invite_links,
+  update_workspace_invite_link as update_workspace_invite_link_row,
+  update_workspace_invite_link_token,
+};
+use database::workspace_sso::insert_workspace_member_if_not_exists;
+use database_entity::dto::AFRole;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde_json::json;
+use shared_entity::dto::workspace_dto::{
+  AuditAction, CreateWorkspaceInviteLinkParams, JoinedWorkspace, UpdateWorkspaceInviteLinkParams,
+  WebhookEvent, WorkspaceInviteLink,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::biz::workspace::audit_log::record_audit_log;
+use crate::biz::workspace::sso::normalize_email_domains;
+use crate::biz::workspace::webhook::enqueue_webhook_event;
+
+const INVITE_LINK_TOKEN_LEN: usize = 32;
+const DEFAULT_INVITE_LINK_JOINS_PER_HOUR: i32 = 50;
+const MAX_INVITE_LINK_JOINS_PER_HOUR: i32 = 1000;
+
+/// Create a link that lets the users, whose email belongs to one of the allowed domains, join the
+/// workspace with the role of the link.
+pub async fn create_workspace_invite_link(
+  pg_pool: &PgPool,
+  creator_uid: i64,
+  workspace_id: &Uuid,
+  params: CreateWorkspaceInviteLinkParams,
+) -> Result<WorkspaceInviteLink, AppError> {
+  let settings = check_invite_link_params(&params)?;
+  let token = generate_invite_link_token();
+  let row = insert_workspace_invite_link(
+    pg_pool,
+    workspace_id,
+    &token,
+    params.role.into(),
+    &settings.allowed_domains,
+    settings.max_joins_per_hour,
+    params.expires_at,
+    creator_uid,
+  )
+  .await?;
+  record_invite_link_audit_log(pg_pool, creator_uid, AuditAction::InviteLinkCreated, &row).await;
+  Ok(invite_link_from_row(row))
+}
+
+/// Returns the invite links of the workspace, expired or not, the most recent first.
+pub async fn list_workspace_invite_links(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<WorkspaceInviteLink>, AppError> {
+  let rows = select_workspace_invite_links(pg_pool, workspace_id).await?;
+  Ok(rows.into_iter().map(invite_link_from_row).collect())
+}
+
+/// Replace the role, the allowed domains and the limits of the link. The users that already
+/// joined through the link keep their role.
+pub async fn update_workspace_invite_link(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  link_id: &Uuid,
+  params: UpdateWorkspaceInviteLinkParams,
+) -> Result<WorkspaceInviteLink, AppError> {
+  let settings = check_invite_link_params(&params)?;
+  let row = update_workspace_invite_link_row(
+    pg_pool,
+    workspace_id,
+    link_id,
+    params.role.into(),
+    &settings.allowed_domains,
+    settings.max_joins_per_hour,
+    params.expires_at,
+  )
+  .await?
+  .ok_or_else(|| invite_link_not_found(workspace_id, link_id))?;
+  record_invite_link_audit_log(pg_pool, uid, AuditAction::InviteLinkUpdated, &row).await;
+  Ok(invite_link_from_row(row))
+}
+
+/// Give the link a new token. The link keeps its settings, but its previous token no longer lets
+/// anyone join the workspace.
+pub async fn rotate_workspace_invite_link(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  link_id: &Uuid,
+) -> Result<WorkspaceInviteLink, AppError> {
+  let token = generate_invite_link_token();
+  let row = update_workspace_invite_link_token(pg_pool, workspace_id, link_id, &token)
+    .await?
+    .ok_or_else(|| invite_link_not_found(workspace_id, link_id))?;
+  record_invite_link_audit_log(pg_pool, uid, AuditAction::InviteLinkRotated, &row).await;
+  Ok(invite_link_from_row(row))
+}
+
+/// Delete the link. The users that joined through it stay in the workspace.
+pub async fn delete_workspace_invite_link(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  link_id: &Uuid,
+) -> Result<(), AppError> {
+  if !delete_workspace_invite_link_row(pg_pool, workspace_id, link_id).await? {
+    return Err(invite_link_not_found(workspace_id, link_id));
+  }
+  record_audit_log(
+    pg_pool,
+    Some(workspace_id),
+    Some(uid),
+    AuditAction::InviteLinkDeleted,
+    None,
+    json!({ "link_id": link_id }),
+  )
+  .await;
+  Ok(())
+}
+
+/// Add the user to the workspace of the link with the role of the link. A user that already is a
+/// member of the workspace keeps its role, and does not count towards the joins per hour of the
+/// link. A link restricted to some email domains only lets in the users that confirmed their
+/// email.
+pub async fn join_workspace_with_invite_link(
+  pg_pool: &PgPool,
+  workspace_access_control: &impl WorkspaceAccessControl,
+  uid: i64,
+  email: &str,
+  token: &str,
+) -> Result<JoinedWorkspace, AppError> {
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to join workspace with invite link")?;
+  let row = select_workspace_invite_link_by_token_for_update(txn.deref_mut(), token)
+    .await?
+    .filter(|row| is_live(row.expires_at))
+    .ok_or_else(|| AppError::UserUnAuthorized("Invalid invite link".to_string()))?;
+  match select_user_role(txn.deref_mut(), &uid, &row.workspace_id).await {
+    Ok(role) => {
+      return Ok(JoinedWorkspace {
+        workspace_id: row.workspace_id,
+        role,
+      })
+    },
+    Err(err) if err.is_record_not_found() => {},
+    Err(err) => return Err(err),
+  }
+  if !row.allowed_domains.is_empty() {
+    // Anyone can sign up with an email of the domain until the email has to be confirmed
+    if !select_is_email_confirmed(pg_pool, uid).await? {
+      return Err(AppError::NotEnoughPermissions {
+        user: email.to_string(),
+        action: format!(
+          "join workspace {} before confirming the email",
+          row.workspace_id
+        ),
+      });
+    }
+    let domain = email
+      .rsplit_once('@')
+      .map(|(_, domain)| domain.trim().to_lowercase())
+      .unwrap_or_default();
+    if !row.allowed_domains.contains(&domain) {
+      return Err(AppError::NotEnoughPermissions {
+        user: email.to_string(),
+        action: format!(
+          "join workspace {} with an email outside of {}",
+          row.workspace_id,
+          row.allowed_domains.join(", ")
+        ),
+      });
+    }
+  }
+  let joins_in_last_hour = select_workspace_invite_link_join_count(
+    txn.deref_mut(),
+    &row.id,
+    Utc::now() - Duration::hours(1),
+  )
+  .await?;
+  if joins_in_last_hour >= row.max_joins_per_hour as i64 {
+    return Err(AppError::TooManyRequests(format!(
+      "The invite link was used by {} users in the last hour, try again later",
+      row.max_joins_per_hour
+    )));
+  }
+
+  let role = AFRole::from(row.role_id);
+  insert_workspace_member_if_not_exists(txn.deref_mut(), &row.workspace_id, uid, row.role_id)
+    .await?;
+  insert_workspace_invite_link_join(txn.deref_mut(), &row.id, uid).await?;
+  enqueue_webhook_event(
+    txn.deref_mut(),
+    &row.workspace_id,
+    WebhookEvent::MemberAdded,
+    json!({ "uid": uid, "role": role }),
+  )
+  .await?;
+  txn
+    .commit()
+    .await
+    .context("fail to commit the transaction to join workspace with invite link")?;
+
+  workspace_access_control
+    .insert_role(&uid, &row.workspace_id, role.clone())
+    .await?;
+  record_audit_log(
+    pg_pool,
+    Some(&row.workspace_id),
+    Some(uid),
+    AuditAction::MemberAdded,
+    Some(email),
+    json!({ "uid": uid, "role": role, "link_id": row.id }),
+  )
+  .await;
+  Ok(JoinedWorkspace {
+    workspace_id: row.workspace_id,
+    role,
+  })
+}
+
+struct InviteLinkSettings {
+  allowed_domains: Vec<String>,
+  max_joins_per_hour: i32,
+}
+
+fn check_invite_link_params(
+  params: &CreateWorkspaceInviteLinkParams,
+) -> Result<InviteLinkSettings, AppError> {
+  if params.role == AFRole::Owner {
+    return Err(AppError::InvalidRequest(
+      "The users that join through an invite link cannot be owners".to_string(),
+    ));
+  }
+  if matches!(params.expires_at, Some(expires_at) if expires_at <= Utc::now()) {
+    return Err(AppError::InvalidRequest(
+      "The expiration time of the link must be in the future".to_string(),
+    ));
+  }
+  let max_joins_per_hour = params
+    .max_joins_per_hour
+    .unwrap_or(DEFAULT_INVITE_LINK_JOINS_PER_HOUR);
+  if !(1..=MAX_INVITE_LINK_JOINS_PER_HOUR).contains(&max_joins_per_hour) {
+    return Err(AppError::InvalidRequest(format!(
+      "The joins per hour of the link must be between 1 and {}",
+      MAX_INVITE_LINK_JOINS_PER_HOUR
+    )));
+  }
+  Ok(InviteLinkSettings {
+    allowed_domains: normalize_email_domains(&params.allowed_domains)?,
+    max_joins_per_hour,
+  })
+}
+
+fn is_live(expires_at: Option<DateTime<Utc>>) -> bool {
+  expires_at.map_or(true, |expires_at| expires_at > Utc::now())
+}
+
+async fn record_invite_link_audit_log(
+  pg_pool: &PgPool,
+  uid: i64,
+  action: AuditAction,
+  row: &AFWorkspaceInviteLinkRow,
+) {
+  record_audit_log(
+    pg_pool,
+    Some(&row.workspace_id),
+    Some(uid),
+    action,
+    None,
+    json!({
+      "link_id": row.id,
+      "role": AFRole::from(row.role_id),
+      "allowed_domains": row.allowed_domains,
+      "max_joins_per_hour": row.max_joins_per_hour,
+      "expires_at": row.expires_at,
+    }),
+  )
+  .await;
+}
+
+fn invite_link_not_found(workspace_id: &Uuid, link_id: &Uuid) -> AppError {
+  AppError::RecordNotFound(format!(
+    "Invite link {} does not exist in workspace {}",
+    link_id, workspace_id
+  ))
+}
+
+fn generate_invite_link_token() -> String {
+  rand::thread_rng()
+    .sample_iter(&Alphanumeric)
+    .take(INVITE_LINK_TOKEN_LEN)
+    .map(char::from)
+    .collect()
+}
+
+fn invite_link_from_row(row: AFWorkspaceInviteLinkRow) -> WorkspaceInviteLink {
+  WorkspaceInviteLink {
+    link_id: row.id,
+    workspace_id: row.workspace_id,
+    token: row.token,
+    role: AFRole::from(row.role_id),
+    allowed_domains: row.allowed_domains,
+    max_joins_per_hour: row.max_joins_per_hour,
+    expires_at: row.expires_at,
+    created_by: row.created_by,
+    created_at: row.created_at,
+    rotated_at: row.rotated_at,
+  }
+}
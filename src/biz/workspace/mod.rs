@@ -1,5 +1,35 @@
 pub mod access_control;
+pub mod audit_log;
+pub mod calendar_feed;
+pub mod database_export;
+pub mod database_import;
+pub mod database_query;
+pub mod database_row;
+pub mod duplicate;
+pub mod export;
+pub mod graphql;
+pub mod group;
+pub mod import;
+pub mod invitation;
+pub mod invite_link;
 pub mod ops;
+pub mod ownership_transfer;
 pub mod page_view;
 pub mod publish;
+pub mod publish_access;
+pub mod publish_analytics;
+pub mod publish_domain;
 pub mod publish_dup;
+pub mod publish_form;
+pub mod publish_render;
+pub mod publish_site;
+pub mod role;
+pub mod scim;
+pub mod section;
+pub mod service_account;
+pub mod snapshot_retention;
+pub mod sso;
+pub mod storage_quota;
+pub mod trash;
+pub mod usage;
+pub mod webhook;
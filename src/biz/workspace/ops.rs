@@ -11,6 +11,7 @@ use sqlx::{types::uuid, PgPool};
 use tracing::instrument;
 use uuid::Uuid;
 
+use access_control::collab::CollabAccessControl;
 use access_control::workspace::WorkspaceAccessControl;
 use app_error::{AppError, ErrorCode};
 use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
@@ -18,20 +19,27 @@ use database::collab::upsert_collab_member_with_txn;
 use database::file::s3_client_impl::S3BucketStorage;
 use database::pg_row::AFWorkspaceMemberRow;
 
-use database::user::select_uid_from_email;
+use database::user::{select_uid_from_email, select_uid_from_uuid};
 use database::workspace::*;
+use database::workspace_group::delete_workspace_group_member_of_workspace;
 use database_entity::dto::{
   AFAccessLevel, AFRole, AFWorkspace, AFWorkspaceInvitation, AFWorkspaceInvitationStatus,
-  AFWorkspaceSettings, GlobalComment, Reaction, WorkspaceUsage,
+  AFWorkspaceSettings, GlobalComment, Reaction,
 };
 use gotrue::params::{GenerateLinkParams, GenerateLinkType};
+use serde_json::json;
+use shared_entity::dto::notification_dto::NotificationKind;
 use shared_entity::dto::workspace_dto::{
-  CreateWorkspaceMember, WorkspaceMemberChangeset, WorkspaceMemberInvitation,
+  AuditAction, CollabTypeFootprint, CreateWorkspaceMember, WebhookEvent, WorkspaceMemberChangeset,
+  WorkspaceMemberInvitation, WorkspaceStorageFootprint,
 };
 use shared_entity::response::AppResponseError;
 use workspace_template::document::getting_started::GettingStartedTemplate;
 
+use crate::biz::notification::ops::record_notification_or_log;
 use crate::biz::user::user_init::initialize_workspace_for_user;
+use crate::biz::workspace::audit_log::record_audit_log;
+use crate::biz::workspace::webhook::enqueue_webhook_event;
 use crate::mailer::{Mailer, WorkspaceInviteMailerParam};
 use crate::state::GoTrueAdmin;
 
@@ -320,10 +328,26 @@ pub async fn accept_workspace_invite(
   let invited_uid = inv
     .invitee_uid
     .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Invitee uid is missing for {:?}", inv)))?;
+  enqueue_webhook_event(
+    txn.deref_mut(),
+    &inv.workspace_id,
+    WebhookEvent::MemberAdded,
+    json!({ "uid": invited_uid, "role": inv.role }),
+  )
+  .await?;
   workspace_access_control
-    .insert_role(&invited_uid, &inv.workspace_id, inv.role)
+    .insert_role(&invited_uid, &inv.workspace_id, inv.role.clone())
     .await?;
   txn.commit().await?;
+  record_audit_log(
+    pg_pool,
+    Some(&inv.workspace_id),
+    Some(user_uid),
+    AuditAction::MemberAdded,
+    None,
+    json!({ "uid": invited_uid, "role": inv.role, "invite_id": invite_id }),
+  )
+  .await;
   Ok(())
 }
 
@@ -373,18 +397,11 @@ pub async fn invite_workspace_members(
     }
   }
 
+  let invited_emails: Vec<String> = invitations
+    .iter()
+    .map(|invitation| invitation.email.clone())
+    .collect();
   for invitation in invitations {
-    let inviter_name = inviter_name.clone();
-    let workspace_name = workspace_name.clone();
-    let workspace_member_count = workspace_member_count.to_string();
-
-    // use default icon until we have workspace icon
-    let workspace_icon_url =
-      "https://miro.medium.com/v2/resize:fit:2400/1*mTPfm7CwU31-tLhtLNkyJw.png".to_string();
-    let user_icon_url =
-      "https://cdn.pixabay.com/photo/2015/10/05/22/37/blank-profile-picture-973460_1280.png"
-        .to_string();
-
     let invite_id = match pending_invitations.get(&invitation.email) {
       None => {
         // user is not invited yet
@@ -402,64 +419,115 @@ pub async fn invite_workspace_members(
       },
       Some(invite_id) => {
         tracing::warn!("User already invited: {}", invitation.email);
+        update_workspace_invitation_sent(txn.deref_mut(), invite_id).await?;
         *invite_id
       },
     };
 
-    // Generate a link such that when clicked, the user is added to the workspace.
-    let accept_url = {
-      match appflowy_web_url {
-        Some(appflowy_web_url) => format!("{}/accept-invitation?invited_id={}", appflowy_web_url, invite_id),
-        None => {
-          gotrue_client
-          .admin_generate_link(
-            &admin_token,
-            &GenerateLinkParams {
-              type_: GenerateLinkType::MagicLink,
-              email: invitation.email.clone(),
-              redirect_to: format!(
-                "/web/login-callback?action=accept_workspace_invite&workspace_invitation_id={}&workspace_name={}&workspace_icon={}&user_name={}&user_icon={}&workspace_member_count={}",
-                invite_id, workspace_name,
-                workspace_icon_url,
-                inviter_name,
-                user_icon_url,
-                workspace_member_count,
-              ),
-              ..Default::default()
-            },
-          )
-          .await?
-          .action_link
-        },
-      }
-    };
-
-    // send email can be slow, so send email in background
-    let cloned_mailer = mailer.clone();
-    tokio::spawn(async move {
-      if let Err(err) = cloned_mailer
-        .send_workspace_invite(
-          invitation.email,
-          WorkspaceInviteMailerParam {
-            user_icon_url,
-            username: inviter_name,
-            workspace_name,
-            workspace_icon_url,
-            workspace_member_count,
-            accept_url,
-          },
-        )
-        .await
-      {
-        tracing::error!("Failed to send workspace invite email: {:?}", err);
-      };
-    });
+    send_workspace_invitation_email(
+      mailer,
+      gotrue_client,
+      &admin_token,
+      &invite_id,
+      invitation.email,
+      inviter_name.clone(),
+      workspace_name.clone(),
+      workspace_member_count.to_string(),
+      appflowy_web_url,
+    )
+    .await?;
   }
 
   txn
     .commit()
     .await
     .context("Commit transaction to invite workspace members")?;
+
+  // Invited users that already have an account also find the invitation in their notifications
+  for email in invited_emails {
+    if let Ok(uid) = select_uid_from_email(pg_pool, &email).await {
+      record_notification_or_log(
+        pg_pool,
+        &[uid],
+        Some(workspace_id),
+        NotificationKind::WorkspaceInvite,
+        json!({ "actor_name": inviter_name, "workspace_name": workspace_name }),
+      )
+      .await;
+    }
+  }
+  Ok(())
+}
+
+/// Send the email of the invitation in the background, with a link that adds the invitee to the
+/// workspace once clicked.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn send_workspace_invitation_email(
+  mailer: &Mailer,
+  gotrue_client: &gotrue::api::Client,
+  admin_token: &str,
+  invite_id: &Uuid,
+  invitee_email: String,
+  inviter_name: String,
+  workspace_name: String,
+  workspace_member_count: String,
+  appflowy_web_url: Option<&str>,
+) -> Result<(), AppError> {
+  // use default icon until we have workspace icon
+  let workspace_icon_url =
+    "https://miro.medium.com/v2/resize:fit:2400/1*mTPfm7CwU31-tLhtLNkyJw.png".to_string();
+  let user_icon_url =
+    "https://cdn.pixabay.com/photo/2015/10/05/22/37/blank-profile-picture-973460_1280.png"
+      .to_string();
+
+  // Generate a link such that when clicked, the user is added to the workspace.
+  let accept_url = {
+    match appflowy_web_url {
+      Some(appflowy_web_url) => format!("{}/accept-invitation?invited_id={}", appflowy_web_url, invite_id),
+      None => {
+        gotrue_client
+        .admin_generate_link(
+          admin_token,
+          &GenerateLinkParams {
+            type_: GenerateLinkType::MagicLink,
+            email: invitee_email.clone(),
+            redirect_to: format!(
+              "/web/login-callback?action=accept_workspace_invite&workspace_invitation_id={}&workspace_name={}&workspace_icon={}&user_name={}&user_icon={}&workspace_member_count={}",
+              invite_id, workspace_name,
+              workspace_icon_url,
+              inviter_name,
+              user_icon_url,
+              workspace_member_count,
+            ),
+            ..Default::default()
+          },
+        )
+        .await?
+        .action_link
+      },
+    }
+  };
+
+  // send email can be slow, so send email in background
+  let cloned_mailer = mailer.clone();
+  tokio::spawn(async move {
+    if let Err(err) = cloned_mailer
+      .send_workspace_invite(
+        invitee_email,
+        WorkspaceInviteMailerParam {
+          user_icon_url,
+          username: inviter_name,
+          workspace_name,
+          workspace_icon_url,
+          workspace_member_count,
+          accept_url,
+        },
+      )
+      .await
+    {
+      tracing::error!("Failed to send workspace invite email: {:?}", err);
+    };
+  });
   Ok(())
 }
 
@@ -528,31 +596,53 @@ pub async fn leave_workspace(
   workspace_id: &Uuid,
   user_uuid: &Uuid,
   workspace_access_control: &impl WorkspaceAccessControl,
+  collab_access_control: &impl CollabAccessControl,
 ) -> Result<(), AppResponseError> {
   let email = database::user::select_email_from_user_uuid(pg_pool, user_uuid).await?;
-  remove_workspace_members(pg_pool, workspace_id, &[email], workspace_access_control).await
+  let uid = select_uid_from_uuid(pg_pool, user_uuid).await?;
+  remove_workspace_members(
+    pg_pool,
+    uid,
+    workspace_id,
+    &[email],
+    workspace_access_control,
+    collab_access_control,
+  )
+  .await
 }
 
+/// Remove the members from the workspace, along with their memberships of the groups of the
+/// workspace.
 pub async fn remove_workspace_members(
   pg_pool: &PgPool,
+  actor_uid: i64,
   workspace_id: &Uuid,
   member_emails: &[String],
   workspace_access_control: &impl WorkspaceAccessControl,
+  collab_access_control: &impl CollabAccessControl,
 ) -> Result<(), AppResponseError> {
   let mut txn = pg_pool
     .begin()
     .await
     .context("Begin transaction to delete workspace members")?;
 
+  let mut removed_members = vec![];
   for email in member_emails {
     delete_workspace_members(&mut txn, workspace_id, email.as_str()).await?;
     if let Ok(uid) = select_uid_from_email(txn.deref_mut(), email)
       .await
       .map_err(AppResponseError::from)
     {
-      workspace_access_control
-        .remove_user_from_workspace(&uid, workspace_id)
-        .await?;
+      enqueue_webhook_event(
+        txn.deref_mut(),
+        workspace_id,
+        WebhookEvent::MemberRemoved,
+        json!({ "uid": uid }),
+      )
+      .await?;
+      let group_ids =
+        delete_workspace_group_member_of_workspace(txn.deref_mut(), workspace_id, uid).await?;
+      removed_members.push((uid, email, group_ids));
     }
   }
 
@@ -560,6 +650,27 @@ pub async fn remove_workspace_members(
     .commit()
     .await
     .context("Commit transaction to delete workspace members")?;
+  for (uid, _, group_ids) in &removed_members {
+    workspace_access_control
+      .remove_user_from_workspace(uid, workspace_id)
+      .await?;
+    for group_id in group_ids {
+      collab_access_control
+        .remove_group_member(uid, &group_id.to_string())
+        .await?;
+    }
+  }
+  for (uid, email, _) in removed_members {
+    record_audit_log(
+      pg_pool,
+      Some(workspace_id),
+      Some(actor_uid),
+      AuditAction::MemberRemoved,
+      Some(email.as_str()),
+      json!({ "uid": uid }),
+    )
+    .await;
+  }
   Ok(())
 }
 
@@ -579,6 +690,7 @@ pub async fn get_workspace_member(
 }
 
 pub async fn update_workspace_member(
+  actor_uid: i64,
   uid: &i64,
   pg_pool: &PgPool,
   workspace_id: &Uuid,
@@ -590,21 +702,41 @@ pub async fn update_workspace_member(
     workspace_access_control
       .insert_role(uid, workspace_id, role.clone())
       .await?;
+    record_audit_log(
+      pg_pool,
+      Some(workspace_id),
+      Some(actor_uid),
+      AuditAction::MemberRoleChanged,
+      Some(&changeset.email),
+      json!({ "uid": uid, "role": role }),
+    )
+    .await;
   }
 
   Ok(())
 }
 
-pub async fn get_workspace_document_total_bytes(
+/// Sums the stored size of every non-deleted collab in the workspace, broken down by collab type.
+/// Only the metadata is read, so this stays cheap for large workspaces.
+pub async fn get_workspace_storage_footprint(
   pg_pool: &PgPool,
-  user_uuid: &Uuid,
   workspace_id: &Uuid,
-) -> Result<WorkspaceUsage, AppError> {
-  check_workspace_owner(pg_pool, user_uuid, workspace_id).await?;
+) -> Result<WorkspaceStorageFootprint, AppError> {
+  let rows = database::collab::select_workspace_collab_footprint(workspace_id, pg_pool).await?;
+  let by_collab_type: Vec<CollabTypeFootprint> = rows
+    .into_iter()
+    .map(|(collab_type, object_count, bytes)| CollabTypeFootprint {
+      collab_type,
+      object_count: object_count.max(0) as u64,
+      bytes: bytes.max(0) as u64,
+    })
+    .collect();
 
-  let byte_count = select_workspace_total_collab_bytes(pg_pool, workspace_id).await?;
-  Ok(WorkspaceUsage {
-    total_document_size: byte_count,
+  Ok(WorkspaceStorageFootprint {
+    workspace_id: *workspace_id,
+    object_count: by_collab_type.iter().map(|f| f.object_count).sum(),
+    total_bytes: by_collab_type.iter().map(|f| f.bytes).sum(),
+    by_collab_type,
   })
 }
 
@@ -659,6 +791,10 @@ pub async fn update_workspace_settings(
     setting.ai_model = ai_model;
   }
 
+  if let Some(inherit_view_permissions) = change.inherit_view_permissions {
+    setting.inherit_view_permissions = inherit_view_permissions;
+  }
+
   // Update the workspace settings in the database
   upsert_workspace_settings(&mut tx, workspace_id, &setting).await?;
   tx.commit().await?;
@@ -0,0 +1,195 @@
+use std::ops::DerefMut;
+
+use access_control::workspace::WorkspaceAccessControl;
+use anyhow::Context;
+use app_error::AppError;
+use chrono::{Duration, Utc};
+use database::pg_row::AFWorkspaceOwnershipTransferRow;
+use database::user::select_uid_from_email;
+use database::workspace::select_user_role;
+use database::workspace_ownership_transfer::{
+  delete_workspace_ownership_transfer, select_workspace_ownership_transfer,
+  update_workspace_owner_and_demote, upsert_workspace_ownership_transfer,
+};
+use database_entity::dto::AFRole;
+use serde_json::json;
+use shared_entity::dto::workspace_dto::{
+  AuditAction, TransferWorkspaceOwnershipParams, WorkspaceOwnershipTransferRequest,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::biz::workspace::audit_log::record_audit_log;
+
+/// How long the new owner has to accept a transfer.
+const OWNERSHIP_TRANSFER_TTL_DAYS: i64 = 7;
+
+/// Ask another member of the workspace to become its owner. Nothing changes until the member
+/// accepts with [accept_workspace_ownership_transfer]. The transfer replaces the one that was
+/// pending.
+pub async fn request_workspace_ownership_transfer(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  params: TransferWorkspaceOwnershipParams,
+) -> Result<WorkspaceOwnershipTransferRequest, AppError> {
+  let not_a_member = || {
+    AppError::InvalidRequest(format!(
+      "{} is not another member of the workspace {}",
+      params.new_owner_email, workspace_id
+    ))
+  };
+  let new_owner_uid = match select_uid_from_email(pg_pool, &params.new_owner_email).await {
+    Ok(new_owner_uid) => new_owner_uid,
+    Err(err) if err.is_record_not_found() => return Err(not_a_member()),
+    Err(err) => return Err(err),
+  };
+  if new_owner_uid == uid {
+    return Err(not_a_member());
+  }
+  match select_user_role(pg_pool, &new_owner_uid, workspace_id).await {
+    Ok(AFRole::Guest) => {
+      return Err(AppError::InvalidRequest(
+        "A guest must become a member of the workspace before owning it".to_string(),
+      ))
+    },
+    Ok(_) => {},
+    Err(err) if err.is_record_not_found() => return Err(not_a_member()),
+    Err(err) => return Err(err),
+  }
+
+  let expires_at = Utc::now() + Duration::days(OWNERSHIP_TRANSFER_TTL_DAYS);
+  let row =
+    upsert_workspace_ownership_transfer(pg_pool, workspace_id, uid, new_owner_uid, expires_at)
+      .await?;
+  record_audit_log(
+    pg_pool,
+    Some(workspace_id),
+    Some(uid),
+    AuditAction::WorkspaceOwnershipTransferRequested,
+    Some(&row.to_email),
+    json!({ "new_owner_uid": new_owner_uid, "expires_at": row.expires_at }),
+  )
+  .await;
+  Ok(transfer_from_row(row))
+}
+
+/// Returns the pending transfer of the workspace. Only the owner that requested it and the member
+/// that is asked to accept it can see it.
+pub async fn get_workspace_ownership_transfer(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+) -> Result<WorkspaceOwnershipTransferRequest, AppError> {
+  select_workspace_ownership_transfer(pg_pool, workspace_id)
+    .await?
+    .filter(|row| row.expires_at > Utc::now() && (row.from_uid == uid || row.to_uid == uid))
+    .map(transfer_from_row)
+    .ok_or_else(no_pending_transfer)
+}
+
+/// Cancel the pending transfer, when called by the owner that requested it, or decline it, when
+/// called by the member that is asked to accept it.
+pub async fn cancel_workspace_ownership_transfer(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to cancel workspace ownership transfer")?;
+  let row = delete_workspace_ownership_transfer(txn.deref_mut(), workspace_id)
+    .await?
+    .filter(|row| row.from_uid == uid || row.to_uid == uid)
+    .ok_or_else(no_pending_transfer)?;
+  txn
+    .commit()
+    .await
+    .context("fail to commit the transaction to cancel workspace ownership transfer")?;
+
+  record_audit_log(
+    pg_pool,
+    Some(workspace_id),
+    Some(uid),
+    AuditAction::WorkspaceOwnershipTransferCancelled,
+    Some(&row.to_email),
+    json!({ "new_owner_uid": row.to_uid, "declined": row.to_uid == uid }),
+  )
+  .await;
+  Ok(())
+}
+
+/// Accept the pending transfer of the workspace to the user. The user becomes the owner of the
+/// workspace and the previous owner one of its members, with the policies of their new roles.
+pub async fn accept_workspace_ownership_transfer(
+  pg_pool: &PgPool,
+  workspace_access_control: &impl WorkspaceAccessControl,
+  uid: i64,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to accept workspace ownership transfer")?;
+  let row = delete_workspace_ownership_transfer(txn.deref_mut(), workspace_id)
+    .await?
+    .filter(|row| row.to_uid == uid && row.expires_at > Utc::now())
+    .ok_or_else(no_pending_transfer)?;
+
+  // The workspace may have changed hands since the transfer was requested
+  match select_user_role(txn.deref_mut(), &row.from_uid, workspace_id).await {
+    Ok(AFRole::Owner) => {},
+    Ok(_) => return Err(stale_transfer()),
+    Err(err) if err.is_record_not_found() => return Err(stale_transfer()),
+    Err(err) => return Err(err),
+  }
+  if !update_workspace_owner_and_demote(txn.deref_mut(), workspace_id, row.from_uid, uid).await? {
+    return Err(stale_transfer());
+  }
+  txn
+    .commit()
+    .await
+    .context("fail to commit the transaction to accept workspace ownership transfer")?;
+
+  workspace_access_control
+    .replace_role(&uid, workspace_id, AFRole::Owner)
+    .await?;
+  workspace_access_control
+    .replace_role(&row.from_uid, workspace_id, AFRole::Member)
+    .await?;
+  record_audit_log(
+    pg_pool,
+    Some(workspace_id),
+    Some(uid),
+    AuditAction::WorkspaceOwnershipTransferred,
+    Some(&row.to_email),
+    json!({ "previous_owner_uid": row.from_uid, "new_owner_uid": uid }),
+  )
+  .await;
+  Ok(())
+}
+
+fn no_pending_transfer() -> AppError {
+  AppError::RecordNotFound("There is no pending ownership transfer for the user".to_string())
+}
+
+fn stale_transfer() -> AppError {
+  AppError::InvalidRequest(
+    "The ownership transfer is no longer valid, the requester does not own the workspace or one \
+     of the users left it"
+      .to_string(),
+  )
+}
+
+fn transfer_from_row(row: AFWorkspaceOwnershipTransferRow) -> WorkspaceOwnershipTransferRequest {
+  WorkspaceOwnershipTransferRequest {
+    workspace_id: row.workspace_id,
+    from_uid: row.from_uid,
+    from_email: row.from_email,
+    to_uid: row.to_uid,
+    to_email: row.to_email,
+    created_at: row.created_at,
+    expires_at: row.expires_at,
+  }
+}
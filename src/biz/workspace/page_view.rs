@@ -76,6 +76,8 @@ pub async fn get_page_view_collab(
     last_edited_time: DateTime::from_timestamp(view.last_edited_time, 0).unwrap_or_default(),
     extra: view.extra.as_ref().map(|e| parse_extra_field_as_json(e)),
     children: vec![],
+    server_version: None,
+    permissions: None,
   };
   let page_collab_data = match view.layout {
     collab_folder::ViewLayout::Document => {
@@ -1,9 +1,21 @@
+use std::collections::HashSet;
+use std::ops::DerefMut;
 use std::sync::Arc;
 
+use access_control::workspace::WorkspaceAccessControl;
 use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
 use async_trait::async_trait;
-use database_entity::dto::{PublishCollabItem, PublishInfo};
-use shared_entity::dto::publish_dto::PublishViewMetaData;
+use chrono::{DateTime, Utc};
+use collab_entity::CollabType;
+use database::collab::GetCollabOrigin;
+use database_entity::dto::{
+  PublishCollabItem, PublishCollabKey, PublishInfo, PublishNamespaceInfo,
+  ReassignedPublishNamespace, WorkspaceCapability,
+};
+use serde_json::json;
+use shared_entity::dto::publish_dto::{PublishOutcome, PublishViewMetaData};
+use shared_entity::dto::workspace_dto::WebhookEvent;
 use sqlx::PgPool;
 use tracing::debug;
 use uuid::Uuid;
@@ -11,21 +23,29 @@ use uuid::Uuid;
 use database::{
   file::{s3_client_impl::AwsS3BucketClientImpl, BucketClient, ResponseBlob},
   publish::{
-    delete_published_collabs, insert_or_replace_publish_collabs, select_publish_collab_meta,
-    select_published_collab_blob, select_published_collab_info,
+    delete_expired_published_collabs, delete_published_collabs, insert_or_replace_publish_collabs,
+    select_all_publish_namespaces, select_publish_collab_meta, select_published_collab_blob,
+    select_published_collab_info, select_published_collab_version,
     select_published_collab_workspace_view_id, select_published_data_for_view_id,
-    select_published_metadata_for_view_id, select_user_is_collab_publisher_for_all_views,
-    select_workspace_publish_namespace, select_workspace_publish_namespace_exists,
-    update_workspace_publish_namespace,
+    select_published_metadata_for_view_id, select_published_names_for_workspace,
+    select_user_is_collab_publisher_for_all_views, select_workspace_publish_namespace,
+    select_workspace_publish_namespace_exists, update_publish_namespace_workspace,
+    update_published_collab_expires_at, update_published_collab_show_in_nav,
+    update_published_collab_version, update_workspace_publish_namespace,
   },
-  workspace::select_user_is_workspace_owner,
+  workspace::{select_user_is_workspace_owner, select_workspace},
 };
 
 use crate::api::metrics::PublishedCollabMetrics;
+use crate::biz::collab::ops::{collab_update_counter, get_latest_collab_encoded};
+use crate::biz::pg_read_pool::PgReadPool;
 
 use super::ops::check_workspace_owner;
+use super::webhook::enqueue_webhook_event_or_log;
+
+const MAX_PUBLISH_NAMESPACE_PAGE_SIZE: i64 = 1000;
 
-async fn check_workspace_owner_or_publisher(
+pub(super) async fn check_workspace_owner_or_publisher(
   pg_pool: &PgPool,
   user_uuid: &Uuid,
   workspace_id: &Uuid,
@@ -65,7 +85,7 @@ fn check_collab_publish_name(publish_name: &str) -> Result<(), AppError> {
   Ok(())
 }
 
-fn get_collab_s3_key(workspace_id: &Uuid, view_id: &Uuid) -> String {
+pub fn get_collab_s3_key(workspace_id: &Uuid, view_id: &Uuid) -> String {
   format!("published-collab/{}/{}", workspace_id, view_id)
 }
 
@@ -93,6 +113,203 @@ pub async fn get_workspace_publish_namespace(
   select_workspace_publish_namespace(pg_pool, workspace_id).await
 }
 
+pub async fn set_published_view_show_in_nav(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  show_in_nav: bool,
+) -> Result<(), AppError> {
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+  update_published_collab_show_in_nav(pg_pool, workspace_id, view_id, show_in_nav).await?;
+  Ok(())
+}
+
+/// Set the time after which the published view is treated as unpublished. `None` removes the
+/// expiry. Expired views are hidden right away, [expire_published_views] only cleans them up.
+pub async fn set_published_view_expiry(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  expires_at: Option<DateTime<Utc>>,
+) -> Result<(), AppError> {
+  if matches!(expires_at, Some(expires_at) if expires_at <= Utc::now()) {
+    return Err(AppError::InvalidRequest(
+      "The expiry time must be in the future".to_string(),
+    ));
+  }
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+  update_published_collab_expires_at(pg_pool, workspace_id, view_id, expires_at).await?;
+  Ok(())
+}
+
+/// Delete the records of the published views that have expired, and return their keys.
+/// Blobs kept in S3 are not touched; the caller can remove them with [get_collab_s3_key].
+pub async fn expire_published_views(pg_pool: &PgPool) -> Result<Vec<PublishCollabKey>, AppError> {
+  let expired = delete_expired_published_collabs(pg_pool).await?;
+  if !expired.is_empty() {
+    debug!("Deleted {} expired published views", expired.len());
+  }
+  for key in &expired {
+    enqueue_webhook_event_or_log(
+      pg_pool,
+      &key.workspace_id,
+      WebhookEvent::ViewUnpublished,
+      json!({ "view_id": key.view_id }),
+    )
+    .await;
+  }
+  Ok(expired)
+}
+
+/// Publish the collab only if it has changed since it was last published.
+/// The current version of the collab is compared against the version recorded at the last publish.
+/// If the version of the collab can not be determined, the collab is always published.
+#[allow(clippy::too_many_arguments)]
+pub async fn publish_if_changed(
+  published_collab_store: &dyn PublishedCollabStore,
+  collab_storage: Arc<CollabAccessControlStorage>,
+  workspace_access_control: &impl WorkspaceAccessControl,
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  uid: i64,
+  user_uuid: &Uuid,
+  collab_type: CollabType,
+  published_item: PublishCollabItem<serde_json::Value, Vec<u8>>,
+) -> Result<PublishOutcome, AppError> {
+  // Check the capability first, so that the outcome does not tell whether the view has changed
+  if !workspace_access_control
+    .enforce_capability(
+      &uid,
+      &workspace_id.to_string(),
+      WorkspaceCapability::Publish,
+    )
+    .await?
+  {
+    return Err(AppError::NotEnoughPermissions {
+      user: uid.to_string(),
+      action: format!("publish in workspace {}", workspace_id),
+    });
+  }
+
+  let view_id = published_item.meta.view_id;
+  let publish_name = published_item.meta.publish_name.clone();
+  let encoded_collab = get_latest_collab_encoded(
+    collab_storage,
+    GetCollabOrigin::Server,
+    &workspace_id.to_string(),
+    &view_id.to_string(),
+    collab_type,
+  )
+  .await?;
+  let current_version = collab_update_counter(&encoded_collab).map(|version| version as i64);
+
+  if let Some(current_version) = current_version {
+    let published_version =
+      select_published_collab_version(pg_pool, workspace_id, &view_id).await?;
+    if published_version == Some(current_version) {
+      debug!(
+        "skip publishing view {}, unchanged since version {}",
+        view_id, current_version
+      );
+      return Ok(PublishOutcome::NoChange);
+    }
+  }
+
+  published_collab_store
+    .publish_collabs(vec![published_item], workspace_id, user_uuid)
+    .await?;
+  if let Some(current_version) = current_version {
+    update_published_collab_version(pg_pool, workspace_id, &view_id, current_version).await?;
+  }
+  enqueue_webhook_event_or_log(
+    pg_pool,
+    workspace_id,
+    WebhookEvent::ViewPublished,
+    json!({ "view_id": view_id, "publish_name": publish_name }),
+  )
+  .await;
+  Ok(PublishOutcome::Published)
+}
+
+/// Returns the publish namespaces of all workspaces, for moderation. The caller is responsible
+/// for making sure only administrators can reach this.
+pub async fn list_all_publish_namespaces(
+  pg_pool: &PgPool,
+  limit: i64,
+  offset: i64,
+) -> Result<Vec<PublishNamespaceInfo>, AppError> {
+  if !(1..=MAX_PUBLISH_NAMESPACE_PAGE_SIZE).contains(&limit) {
+    return Err(AppError::InvalidRequest(format!(
+      "limit must be between 1 and {}",
+      MAX_PUBLISH_NAMESPACE_PAGE_SIZE
+    )));
+  }
+  if offset < 0 {
+    return Err(AppError::InvalidRequest(
+      "offset must not be negative".to_string(),
+    ));
+  }
+  select_all_publish_namespaces(pg_pool, limit, offset).await
+}
+
+/// Move a publish namespace to another workspace, so that its public URLs are served from there.
+/// The previous workspace gets a fresh default namespace. The caller is responsible for making
+/// sure only administrators can reach this.
+///
+/// Public URLs resolve by publish name, so the views published under the namespace before the move
+/// whose publish name is not published in the new workspace are reported as unresolved.
+pub async fn reassign_publish_namespace(
+  pg_pool: &PgPool,
+  namespace: &str,
+  new_workspace_id: &Uuid,
+) -> Result<ReassignedPublishNamespace, AppError> {
+  let mut txn = pg_pool.begin().await?;
+  let new_workspace = select_workspace(txn.deref_mut(), new_workspace_id).await?;
+  if new_workspace.deleted_at.is_some() {
+    return Err(AppError::RecordNotFound(format!(
+      "workspace {} not found",
+      new_workspace_id
+    )));
+  }
+
+  let previous_workspace_id =
+    update_publish_namespace_workspace(&mut txn, namespace, new_workspace_id).await?;
+  if previous_workspace_id == *new_workspace_id {
+    return Err(AppError::InvalidRequest(format!(
+      "publish namespace {} already belongs to workspace {}",
+      namespace, new_workspace_id
+    )));
+  }
+
+  let published_names: HashSet<String> =
+    select_published_names_for_workspace(txn.deref_mut(), new_workspace_id)
+      .await?
+      .into_iter()
+      .map(|(_, publish_name)| publish_name)
+      .collect();
+  let unresolved_views =
+    select_published_names_for_workspace(txn.deref_mut(), &previous_workspace_id)
+      .await?
+      .into_iter()
+      .filter(|(_, publish_name)| !published_names.contains(publish_name))
+      .map(|(view_id, publish_name)| PublishInfo {
+        namespace: Some(namespace.to_string()),
+        publish_name,
+        view_id,
+      })
+      .collect();
+  txn.commit().await?;
+
+  Ok(ReassignedPublishNamespace {
+    namespace: namespace.to_string(),
+    previous_workspace_id,
+    workspace_id: *new_workspace_id,
+    unresolved_views,
+  })
+}
+
 async fn check_workspace_namespace(new_namespace: &str) -> Result<(), AppError> {
   // Check len
   if new_namespace.len() < 8 {
@@ -160,11 +377,16 @@ pub trait PublishedCollabStore: Sync + Send + 'static {
 pub struct PublishedCollabPostgresStore {
   metrics: Arc<PublishedCollabMetrics>,
   pg_pool: PgPool,
+  read_pool: PgReadPool,
 }
 
 impl PublishedCollabPostgresStore {
-  pub fn new(metrics: Arc<PublishedCollabMetrics>, pg_pool: PgPool) -> Self {
-    Self { metrics, pg_pool }
+  pub fn new(metrics: Arc<PublishedCollabMetrics>, pg_pool: PgPool, read_pool: PgReadPool) -> Self {
+    Self {
+      metrics,
+      pg_pool,
+      read_pool,
+    }
   }
 }
 
@@ -201,7 +423,7 @@ impl PublishedCollabStore for PublishedCollabPostgresStore {
     publish_name: &str,
   ) -> Result<serde_json::Value, AppError> {
     let metadata =
-      select_publish_collab_meta(&self.pg_pool, publish_namespace, publish_name).await?;
+      select_publish_collab_meta(self.read_pool.get(), publish_namespace, publish_name).await?;
     Ok(metadata)
   }
 
@@ -233,7 +455,8 @@ impl PublishedCollabStore for PublishedCollabPostgresStore {
     publish_namespace: &str,
     publish_name: &str,
   ) -> Result<Vec<u8>, AppError> {
-    let result = select_published_collab_blob(&self.pg_pool, publish_namespace, publish_name).await;
+    let result =
+      select_published_collab_blob(self.read_pool.get(), publish_namespace, publish_name).await;
     if result.is_err() {
       self.metrics.incr_failure_read_count(1);
     } else {
@@ -257,6 +480,7 @@ impl PublishedCollabStore for PublishedCollabPostgresStore {
 pub struct PublishedCollabS3StoreWithPostgresFallback {
   metrics: Arc<PublishedCollabMetrics>,
   pg_pool: PgPool,
+  read_pool: PgReadPool,
   bucket_client: AwsS3BucketClientImpl,
 }
 
@@ -264,11 +488,13 @@ impl PublishedCollabS3StoreWithPostgresFallback {
   pub fn new(
     metrics: Arc<PublishedCollabMetrics>,
     pg_pool: PgPool,
+    read_pool: PgReadPool,
     bucket_client: AwsS3BucketClientImpl,
   ) -> Self {
     Self {
       metrics,
       pg_pool,
+      read_pool,
       bucket_client,
     }
   }
@@ -325,7 +551,7 @@ impl PublishedCollabStore for PublishedCollabS3StoreWithPostgresFallback {
     publish_name: &str,
   ) -> Result<serde_json::Value, AppError> {
     let metadata =
-      select_publish_collab_meta(&self.pg_pool, publish_namespace, publish_name).await?;
+      select_publish_collab_meta(self.read_pool.get(), publish_namespace, publish_name).await?;
     Ok(metadata)
   }
 
@@ -376,9 +602,12 @@ impl PublishedCollabStore for PublishedCollabS3StoreWithPostgresFallback {
     publish_namespace: &str,
     publish_name: &str,
   ) -> Result<Vec<u8>, AppError> {
-    let collab_key =
-      select_published_collab_workspace_view_id(&self.pg_pool, publish_namespace, publish_name)
-        .await?;
+    let collab_key = select_published_collab_workspace_view_id(
+      self.read_pool.get(),
+      publish_namespace,
+      publish_name,
+    )
+    .await?;
     let object_key = get_collab_s3_key(&collab_key.workspace_id, &collab_key.view_id);
     let resp = self.bucket_client.get_blob(&object_key).await;
     match resp {
@@ -392,7 +621,7 @@ impl PublishedCollabStore for PublishedCollabS3StoreWithPostgresFallback {
           object_key, err
         );
         let result =
-          select_published_collab_blob(&self.pg_pool, publish_namespace, publish_name).await;
+          select_published_collab_blob(self.read_pool.get(), publish_namespace, publish_name).await;
         if result.is_err() {
           self.metrics.incr_failure_read_count(1);
         } else {
@@ -0,0 +1,252 @@
+use app_error::AppError;
+use chrono::{Duration, Utc};
+use database::publish::{
+  select_published_collab_password_hash, select_published_collab_password_hash_by_name,
+  update_published_collab_password_hash,
+};
+use database_entity::dto::PublishedViewAccessToken;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::publish::check_workspace_owner_or_publisher;
+
+const MIN_PASSWORD_LEN: usize = 8;
+const MAX_PASSWORD_LEN: usize = 128;
+const PASSWORD_HASH_SCHEME: &str = "pbkdf2-sha256";
+const PASSWORD_HASH_ITERATIONS: usize = 100_000;
+const PASSWORD_SALT_LEN: usize = 16;
+const PASSWORD_HASH_LEN: usize = 32;
+const ACCESS_TOKEN_TTL_MINUTES: i64 = 60;
+
+/// Set the password required to read the content of the published view. `None` removes the
+/// password. Changing or removing the password invalidates the access tokens issued before.
+pub async fn set_published_view_password(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  password: Option<String>,
+) -> Result<(), AppError> {
+  let password_hash = match password {
+    Some(password) => {
+      let len = password.chars().count();
+      if !(MIN_PASSWORD_LEN..=MAX_PASSWORD_LEN).contains(&len) {
+        return Err(AppError::InvalidRequest(format!(
+          "The password must be between {} and {} characters long",
+          MIN_PASSWORD_LEN, MAX_PASSWORD_LEN
+        )));
+      }
+      Some(tokio::task::spawn_blocking(move || hash_password(&password)).await??)
+    },
+    None => None,
+  };
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+  update_published_collab_password_hash(pg_pool, workspace_id, view_id, password_hash.as_deref())
+    .await?;
+  Ok(())
+}
+
+/// Exchange the password of the published view for a short-lived access token, which is passed
+/// back when reading the content of the view.
+pub async fn create_published_view_access_token(
+  pg_pool: &PgPool,
+  secret: &str,
+  publish_namespace: &str,
+  publish_name: &str,
+  password: &str,
+) -> Result<PublishedViewAccessToken, AppError> {
+  let (view_id, password_hash) =
+    select_published_collab_password_hash_by_name(pg_pool, publish_namespace, publish_name)
+      .await?
+      .ok_or_else(|| {
+        AppError::RecordNotFound(format!(
+          "published view {}/{} not found",
+          publish_namespace, publish_name
+        ))
+      })?;
+  let password_hash = password_hash.ok_or_else(|| {
+    AppError::InvalidRequest("The published view is not password protected".to_string())
+  })?;
+  let is_valid = {
+    let password = password.to_string();
+    let password_hash = password_hash.clone();
+    tokio::task::spawn_blocking(move || verify_password(&password, &password_hash)).await??
+  };
+  if !is_valid {
+    return Err(AppError::UserUnAuthorized("Incorrect password".to_string()));
+  }
+
+  let expires_at = Utc::now() + Duration::minutes(ACCESS_TOKEN_TTL_MINUTES);
+  let signature = sign_access_token(secret, &view_id, expires_at.timestamp(), &password_hash)?;
+  Ok(PublishedViewAccessToken {
+    access_token: format!("{}.{}.{}", view_id, expires_at.timestamp(), signature),
+    expires_at,
+  })
+}
+
+/// Check that the content of the view published under the given name can be read with the
+/// access token. Views without a password can be read without one.
+pub async fn check_published_view_access_by_name(
+  pg_pool: &PgPool,
+  secret: &str,
+  publish_namespace: &str,
+  publish_name: &str,
+  access_token: Option<&str>,
+) -> Result<(), AppError> {
+  if let Some((view_id, Some(password_hash))) =
+    select_published_collab_password_hash_by_name(pg_pool, publish_namespace, publish_name).await?
+  {
+    verify_access_token(secret, &view_id, &password_hash, access_token)?;
+  }
+  Ok(())
+}
+
+/// Check that the content of the published view can be read with the access token. Views without
+/// a password can be read without one.
+pub async fn check_published_view_access(
+  pg_pool: &PgPool,
+  secret: &str,
+  view_id: &Uuid,
+  access_token: Option<&str>,
+) -> Result<(), AppError> {
+  if let Some(password_hash) = select_published_collab_password_hash(pg_pool, view_id).await? {
+    verify_access_token(secret, view_id, &password_hash, access_token)?;
+  }
+  Ok(())
+}
+
+fn verify_access_token(
+  secret: &str,
+  view_id: &Uuid,
+  password_hash: &str,
+  access_token: Option<&str>,
+) -> Result<(), AppError> {
+  let access_token = access_token.ok_or_else(|| {
+    AppError::UserUnAuthorized("The published view is password protected".to_string())
+  })?;
+  let invalid_token = || AppError::UserUnAuthorized("Invalid access token".to_string());
+  let mut parts = access_token.splitn(3, '.');
+  let (token_view_id, expires_at, signature) = match (parts.next(), parts.next(), parts.next()) {
+    (Some(view_id), Some(expires_at), Some(signature)) => (view_id, expires_at, signature),
+    _ => return Err(invalid_token()),
+  };
+  let expires_at: i64 = expires_at.parse().map_err(|_| invalid_token())?;
+  if token_view_id != view_id.to_string() || expires_at <= Utc::now().timestamp() {
+    return Err(invalid_token());
+  }
+  let expected = sign_access_token(secret, view_id, expires_at, password_hash)?;
+  if !constant_time_eq(expected.as_bytes(), signature.as_bytes()) {
+    return Err(invalid_token());
+  }
+  Ok(())
+}
+
+/// The signature covers the password hash, so that the tokens issued for a password stop
+/// working once the password changes.
+fn sign_access_token(
+  secret: &str,
+  view_id: &Uuid,
+  expires_at: i64,
+  password_hash: &str,
+) -> Result<String, AppError> {
+  let message = format!(
+    "published-view-access.{}.{}.{}",
+    view_id, expires_at, password_hash
+  );
+  let sign = || -> Result<Vec<u8>, openssl::error::ErrorStack> {
+    let key = PKey::hmac(secret.as_bytes())?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(message.as_bytes())?;
+    signer.sign_to_vec()
+  };
+  let signature = sign().map_err(|err| AppError::Internal(err.into()))?;
+  Ok(to_hex(&signature))
+}
+
+/// Hash the password as `pbkdf2-sha256$<iterations>$<salt>$<hash>`.
+fn hash_password(password: &str) -> Result<String, AppError> {
+  let salt: String = rand::thread_rng()
+    .sample_iter(&Alphanumeric)
+    .take(PASSWORD_SALT_LEN)
+    .map(char::from)
+    .collect();
+  let hash = derive_password_hash(password, &salt, PASSWORD_HASH_ITERATIONS)?;
+  Ok(format!(
+    "{}${}${}${}",
+    PASSWORD_HASH_SCHEME, PASSWORD_HASH_ITERATIONS, salt, hash
+  ))
+}
+
+fn verify_password(password: &str, password_hash: &str) -> Result<bool, AppError> {
+  let parts: Vec<&str> = password_hash.split('$').collect();
+  let (iterations, salt, expected) = match parts.as_slice() {
+    [PASSWORD_HASH_SCHEME, iterations, salt, hash] => (iterations, salt, hash),
+    _ => {
+      return Err(AppError::Internal(anyhow::anyhow!(
+        "unsupported published view password hash"
+      )))
+    },
+  };
+  let iterations: usize = iterations
+    .parse()
+    .map_err(|err| AppError::Internal(anyhow::anyhow!("invalid password hash: {}", err)))?;
+  let hash = derive_password_hash(password, salt, iterations)?;
+  Ok(constant_time_eq(hash.as_bytes(), expected.as_bytes()))
+}
+
+fn derive_password_hash(password: &str, salt: &str, iterations: usize) -> Result<String, AppError> {
+  let mut hash = [0u8; PASSWORD_HASH_LEN];
+  openssl::pkcs5::pbkdf2_hmac(
+    password.as_bytes(),
+    salt.as_bytes(),
+    iterations,
+    MessageDigest::sha256(),
+    &mut hash,
+  )
+  .map_err(|err| AppError::Internal(err.into()))?;
+  Ok(to_hex(&hash))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+  a.len() == b.len() && openssl::memcmp::eq(a, b)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn published_view_password_hash_test() {
+    let hash = hash_password("correct horse").unwrap();
+    assert!(hash.starts_with(PASSWORD_HASH_SCHEME));
+    assert!(verify_password("correct horse", &hash).unwrap());
+    assert!(!verify_password("wrong horse", &hash).unwrap());
+    assert_ne!(hash, hash_password("correct horse").unwrap());
+  }
+
+  #[test]
+  fn published_view_access_token_test() {
+    let view_id = Uuid::new_v4();
+    let expires_at = Utc::now().timestamp() + 60;
+    let signature = sign_access_token("secret", &view_id, expires_at, "hash").unwrap();
+    let token = format!("{}.{}.{}", view_id, expires_at, signature);
+    assert!(verify_access_token("secret", &view_id, "hash", Some(&token)).is_ok());
+    assert!(verify_access_token("secret", &view_id, "new hash", Some(&token)).is_err());
+    assert!(verify_access_token("secret", &Uuid::new_v4(), "hash", Some(&token)).is_err());
+    assert!(verify_access_token("secret", &view_id, "hash", None).is_err());
+
+    let expired_at = Utc::now().timestamp() - 1;
+    let signature = sign_access_token("secret", &view_id, expired_at, "hash").unwrap();
+    let token = format!("{}.{}.{}", view_id, expired_at, signature);
+    assert!(verify_access_token("secret", &view_id, "hash", Some(&token)).is_err());
+  }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use app_error::AppError;
+use chrono::{Duration, NaiveDate, Utc};
+use database::pg_row::{AFPublishedViewDailyStatRow, AFPublishedViewReferrerStatRow};
+use database::publish::{
+  insert_published_view_hits, select_published_view_daily_stats,
+  select_published_view_referrer_stats,
+};
+use shared_entity::dto::workspace_dto::{
+  PublishAnalytics, PublishedViewAnalytics, PublishedViewDailyViews, PublishedViewReferrer,
+};
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use tracing::warn;
+use url::Url;
+use uuid::Uuid;
+
+const DEFAULT_ANALYTICS_DAYS: u32 = 30;
+const MAX_ANALYTICS_DAYS: u32 = 365;
+const MAX_REFERRERS_PER_VIEW: usize = 10;
+/// The number of referring sites counted per view and day. The reads from any other site are
+/// counted under [OTHER_REFERRER_HOST].
+const MAX_REFERRER_HOSTS_PER_DAY: i64 = 50;
+/// Not a valid host, so it can not be mistaken for a referring site.
+pub const OTHER_REFERRER_HOST: &str = "(other)";
+/// The reads waiting to be counted. Reads beyond that are dropped rather than slowing down the
+/// readers when the database falls behind.
+const HIT_CHANNEL_SIZE: usize = 10_000;
+/// The number of distinct pages and referring sites counted before the counts are written early.
+const MAX_PENDING_HITS: usize = 1_000;
+const HIT_FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+struct PublishedViewHit {
+  publish_namespace: String,
+  publish_name: String,
+  referrer_host: Option<String>,
+}
+
+/// Counts the reads of published views in the background. The reads are summed in memory and
+/// written with a single statement every [HIT_FLUSH_INTERVAL], so the database sees one write per
+/// interval rather than one per read.
+#[derive(Clone)]
+pub struct PublishedViewHitRecorder {
+  tx: mpsc::Sender<PublishedViewHit>,
+}
+
+impl PublishedViewHitRecorder {
+  pub fn new(pg_pool: PgPool) -> Self {
+    let (tx, rx) = mpsc::channel(HIT_CHANNEL_SIZE);
+    tokio::spawn(run_published_view_hit_recorder(pg_pool, rx));
+    Self { tx }
+  }
+
+  /// Count a read of the view published under the given name. Readers are not identified in any
+  /// way: only the host of the referring page is kept, if the reader followed a link from another
+  /// site.
+  pub fn record(
+    &self,
+    publish_namespace: String,
+    publish_name: String,
+    referrer_host: Option<String>,
+  ) {
+    let hit = PublishedViewHit {
+      publish_namespace,
+      publish_name,
+      referrer_host,
+    };
+    if let Err(mpsc::error::TrySendError::Full(hit)) = self.tx.try_send(hit) {
+      warn!(
+        "Dropped the read of published view {}/{}: too many reads waiting to be counted",
+        hit.publish_namespace, hit.publish_name
+      );
+    }
+  }
+}
+
+async fn run_published_view_hit_recorder(
+  pg_pool: PgPool,
+  mut rx: mpsc::Receiver<PublishedViewHit>,
+) {
+  let mut pending: HashMap<(String, String, Option<String>), i64> = HashMap::new();
+  let mut interval = tokio::time::interval(HIT_FLUSH_INTERVAL);
+  loop {
+    tokio::select! {
+      hit = rx.recv() => match hit {
+        Some(hit) => {
+          *pending
+            .entry((hit.publish_namespace, hit.publish_name, hit.referrer_host))
+            .or_default() += 1;
+          if pending.len() >= MAX_PENDING_HITS {
+            flush_published_view_hits(&pg_pool, &mut pending).await;
+          }
+        },
+        None => {
+          flush_published_view_hits(&pg_pool, &mut pending).await;
+          break;
+        },
+      },
+      _ = interval.tick() => flush_published_view_hits(&pg_pool, &mut pending).await,
+    }
+  }
+}
+
+async fn flush_published_view_hits(
+  pg_pool: &PgPool,
+  pending: &mut HashMap<(String, String, Option<String>), i64>,
+) {
+  if pending.is_empty() {
+    return;
+  }
+  let len = pending.len();
+  let mut publish_namespaces = Vec::with_capacity(len);
+  let mut publish_names = Vec::with_capacity(len);
+  let mut referrer_hosts = Vec::with_capacity(len);
+  let mut view_counts = Vec::with_capacity(len);
+  for ((publish_namespace, publish_name, referrer_host), view_count) in pending.drain() {
+    publish_namespaces.push(publish_namespace);
+    publish_names.push(publish_name);
+    referrer_hosts.push(referrer_host);
+    view_counts.push(view_count);
+  }
+  if let Err(err) = insert_published_view_hits(
+    pg_pool,
+    &publish_namespaces,
+    &publish_names,
+    &referrer_hosts,
+    &view_counts,
+    MAX_REFERRER_HOSTS_PER_DAY,
+    OTHER_REFERRER_HOST,
+  )
+  .await
+  {
+    warn!("Failed to record the reads of published views: {}", err);
+  }
+}
+
+/// Returns the host of the referring page, or `None` if there is no referrer or the referrer is
+/// one of the `own_hosts`, which are the hosts the published site itself is served on.
+pub fn referrer_host(referrer: Option<&str>, own_hosts: &[&str]) -> Option<String> {
+  let url = Url::parse(referrer?).ok()?;
+  if url.scheme() != "http" && url.scheme() != "https" {
+    return None;
+  }
+  let host = url.host_str()?.trim_start_matches("www.").to_lowercase();
+  let is_own_host = own_hosts.iter().any(|own_host| {
+    let own_host = own_host.split(':').next().unwrap_or_default();
+    own_host
+      .trim_start_matches("www.")
+      .eq_ignore_ascii_case(&host)
+  });
+  if host.is_empty() || is_own_host {
+    return None;
+  }
+  Some(host)
+}
+
+/// Returns the reads of the views published by the workspace over the last `days` days.
+pub async fn get_publish_analytics(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  days: Option<u32>,
+) -> Result<PublishAnalytics, AppError> {
+  let days = days.unwrap_or(DEFAULT_ANALYTICS_DAYS);
+  if !(1..=MAX_ANALYTICS_DAYS).contains(&days) {
+    return Err(AppError::InvalidRequest(format!(
+      "days must be between 1 and {}",
+      MAX_ANALYTICS_DAYS
+    )));
+  }
+  let since = Utc::now().date_naive() - Duration::days(days as i64 - 1);
+  let daily_stats = select_published_view_daily_stats(pg_pool, workspace_id, since).await?;
+  let referrer_stats = select_published_view_referrer_stats(pg_pool, workspace_id, since).await?;
+  Ok(publish_analytics(since, daily_stats, referrer_stats))
+}
+
+fn publish_analytics(
+  since: NaiveDate,
+  daily_stats: Vec<AFPublishedViewDailyStatRow>,
+  referrer_stats: Vec<AFPublishedViewReferrerStatRow>,
+) -> PublishAnalytics {
+  // The daily stats are ordered by view, so the stats of a view are next to each other
+  let mut views: Vec<PublishedViewAnalytics> = vec![];
+  for stat in daily_stats {
+    let daily_views = PublishedViewDailyViews {
+      day: stat.day,
+      views: stat.view_count,
+    };
+    match views.last_mut() {
+      Some(view) if view.view_id == stat.view_id => {
+        view.total_views += stat.view_count;
+        view.daily_views.push(daily_views);
+      },
+      _ => views.push(PublishedViewAnalytics {
+        view_id: stat.view_id,
+        publish_name: stat.publish_name,
+        total_views: stat.view_count,
+        daily_views: vec![daily_views],
+        referrers: vec![],
+      }),
+    }
+  }
+  for stat in referrer_stats {
+    if let Some(view) = views.iter_mut().find(|view| view.view_id == stat.view_id) {
+      if view.referrers.len() < MAX_REFERRERS_PER_VIEW {
+        view.referrers.push(PublishedViewReferrer {
+          host: stat.referrer_host,
+          views: stat.view_count,
+        });
+      }
+    }
+  }
+  views.sort_by(|a, b| b.total_views.cmp(&a.total_views));
+  PublishAnalytics { since, views }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn referrer_host_test() {
+    let own_hosts = ["appflowy.com", "docs.example.com:8000"];
+    assert_eq!(
+      referrer_host(Some("https://www.Google.com/search?q=x"), &own_hosts),
+      Some("google.com".to_string())
+    );
+    assert_eq!(
+      referrer_host(Some("https://appflowy.com/ns/page"), &own_hosts),
+      None
+    );
+    assert_eq!(
+      referrer_host(Some("http://docs.example.com/page"), &own_hosts),
+      None
+    );
+    assert_eq!(referrer_host(Some("android-app://x"), &own_hosts), None);
+    assert_eq!(referrer_host(Some("not a url"), &own_hosts), None);
+    assert_eq!(referrer_host(None, &own_hosts), None);
+  }
+
+  #[test]
+  fn publish_analytics_test() {
+    let since = NaiveDate::from_ymd_opt(2024, 10, 1).unwrap();
+    let (view_1, view_2) = (Uuid::new_v4(), Uuid::new_v4());
+    let daily = |view_id: Uuid, day: u32, view_count: i64| AFPublishedViewDailyStatRow {
+      view_id,
+      publish_name: Some(view_id.to_string()),
+      day: NaiveDate::from_ymd_opt(2024, 10, day).unwrap(),
+      view_count,
+    };
+    let analytics = publish_analytics(
+      since,
+      vec![
+        daily(view_1, 1, 2),
+        daily(view_2, 1, 1),
+        daily(view_2, 2, 4),
+      ],
+      vec![AFPublishedViewReferrerStatRow {
+        view_id: view_1,
+        referrer_host: "google.com".to_string(),
+        view_count: 1,
+      }],
+    );
+    assert_eq!(analytics.views.len(), 2);
+    assert_eq!(analytics.views[0].view_id, view_2);
+    assert_eq!(analytics.views[0].total_views, 5);
+    assert_eq!(analytics.views[0].daily_views.len(), 2);
+    assert!(analytics.views[0].referrers.is_empty());
+    assert_eq!(analytics.views[1].total_views, 2);
+    assert_eq!(analytics.views[1].referrers[0].host, "google.com");
+  }
+}
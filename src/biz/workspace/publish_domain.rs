@@ -0,0 +1,290 @@
+use std::time::Duration;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use database::pg_row::AFPublishCustomDomainRow;
+use database::publish::{
+  delete_publish_custom_domain, insert_publish_custom_domain, select_publish_custom_domain,
+  select_publish_custom_domain_verified_by_other, select_publish_custom_domains,
+  select_publish_namespace_for_custom_domain, update_publish_custom_domain_verified,
+};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::Deserialize;
+use shared_entity::dto::workspace_dto::PublishCustomDomain;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::ops::check_workspace_owner;
+
+const VERIFICATION_RECORD_PREFIX: &str = "_appflowy-publish";
+const VERIFICATION_VALUE_PREFIX: &str = "appflowy-publish-verification=";
+const VERIFICATION_TOKEN_LEN: usize = 32;
+const DNS_LOOKUP_TIMEOUT_SECS: u64 = 10;
+const DNS_TXT_RECORD_TYPE: u16 = 16;
+const MAX_DOMAIN_LEN: usize = 253;
+const MAX_DOMAIN_LABEL_LEN: usize = 63;
+
+/// Register a domain that serves the publish namespace of the workspace once verified. Registering
+/// a domain again returns the existing registration.
+pub async fn register_publish_custom_domain(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<PublishCustomDomain, AppError> {
+  check_workspace_owner(pg_pool, user_uuid, workspace_id).await?;
+  let domain = normalize_domain(domain)?;
+  if select_publish_custom_domain_verified_by_other(pg_pool, workspace_id, &domain).await? {
+    return Err(domain_in_use(&domain));
+  }
+  let token = generate_verification_token();
+  let row = insert_publish_custom_domain(pg_pool, workspace_id, &domain, &token).await?;
+  Ok(custom_domain_from_row(row))
+}
+
+pub async fn list_publish_custom_domains(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+) -> Result<Vec<PublishCustomDomain>, AppError> {
+  check_workspace_owner(pg_pool, user_uuid, workspace_id).await?;
+  let rows = select_publish_custom_domains(pg_pool, workspace_id).await?;
+  Ok(rows.into_iter().map(custom_domain_from_row).collect())
+}
+
+/// Look up the verification TXT record of the domain, and start serving the publish namespace of
+/// the workspace on the domain if the record holds the verification token.
+pub async fn verify_publish_custom_domain(
+  pg_pool: &PgPool,
+  dns_over_https_url: &str,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<PublishCustomDomain, AppError> {
+  check_workspace_owner(pg_pool, user_uuid, workspace_id).await?;
+  let domain = normalize_domain(domain)?;
+  let row = select_publish_custom_domain(pg_pool, workspace_id, &domain)
+    .await?
+    .ok_or_else(|| custom_domain_not_found(workspace_id, &domain))?;
+  if row.verified_at.is_some() {
+    return Ok(custom_domain_from_row(row));
+  }
+
+  let record_name = verification_record_name(&domain);
+  let expected = verification_record_value(&row.verification_token);
+  let records = lookup_txt_records(dns_over_https_url, &record_name).await?;
+  if !records.iter().any(|record| record.trim() == expected) {
+    return Err(AppError::InvalidRequest(format!(
+      "The TXT record {} of the domain does not contain {}",
+      record_name, expected
+    )));
+  }
+
+  let row = update_publish_custom_domain_verified(pg_pool, workspace_id, &domain)
+    .await?
+    .ok_or_else(|| domain_in_use(&domain))?;
+  Ok(custom_domain_from_row(row))
+}
+
+pub async fn unregister_publish_custom_domain(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<(), AppError> {
+  check_workspace_owner(pg_pool, user_uuid, workspace_id).await?;
+  let domain = normalize_domain(domain)?;
+  if !delete_publish_custom_domain(pg_pool, workspace_id, &domain).await? {
+    return Err(custom_domain_not_found(workspace_id, &domain));
+  }
+  Ok(())
+}
+
+/// Returns the publish namespace served on the host of a request, or `None` if the host is not a
+/// verified custom domain.
+pub async fn publish_namespace_for_host(
+  pg_pool: &PgPool,
+  host: &str,
+) -> Result<Option<String>, AppError> {
+  let domain = match normalize_domain(strip_port(host)) {
+    Ok(domain) => domain,
+    Err(_) => return Ok(None),
+  };
+  select_publish_namespace_for_custom_domain(pg_pool, &domain).await
+}
+
+fn normalize_domain(domain: &str) -> Result<String, AppError> {
+  let domain = domain.trim().trim_end_matches('.').to_lowercase();
+  let invalid = || AppError::InvalidRequest(format!("{} is not a valid domain", domain));
+  if domain.is_empty() || domain.len() > MAX_DOMAIN_LEN || !domain.contains('.') {
+    return Err(invalid());
+  }
+  for label in domain.split('.') {
+    if label.is_empty()
+      || label.len() > MAX_DOMAIN_LABEL_LEN
+      || label.starts_with('-')
+      || label.ends_with('-')
+      || !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+      return Err(invalid());
+    }
+  }
+  // An ip address is not a domain
+  if domain.split('.').all(|label| label.parse::<u8>().is_ok()) {
+    return Err(invalid());
+  }
+  Ok(domain)
+}
+
+fn strip_port(host: &str) -> &str {
+  match host.rsplit_once(':') {
+    Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+    _ => host,
+  }
+}
+
+fn verification_record_name(domain: &str) -> String {
+  format!("{}.{}", VERIFICATION_RECORD_PREFIX, domain)
+}
+
+fn verification_record_value(token: &str) -> String {
+  format!("{}{}", VERIFICATION_VALUE_PREFIX, token)
+}
+
+fn generate_verification_token() -> String {
+  rand::thread_rng()
+    .sample_iter(&Alphanumeric)
+    .take(VERIFICATION_TOKEN_LEN)
+    .map(char::from)
+    .collect()
+}
+
+#[derive(Deserialize)]
+struct DnsJsonResponse {
+  #[serde(rename = "Status")]
+  status: u32,
+  #[serde(rename = "Answer", default)]
+  answer: Vec<DnsJsonAnswer>,
+}
+
+#[derive(Deserialize)]
+struct DnsJsonAnswer {
+  #[serde(rename = "type")]
+  record_type: u16,
+  data: String,
+}
+
+/// Returns the TXT records of `name`, looked up with a DNS-over-HTTPS endpoint that answers in
+/// the JSON format.
+async fn lookup_txt_records(dns_over_https_url: &str, name: &str) -> Result<Vec<String>, AppError> {
+  let http_client = reqwest::Client::builder()
+    .timeout(Duration::from_secs(DNS_LOOKUP_TIMEOUT_SECS))
+    .build()?;
+  let resp = http_client
+    .get(dns_over_https_url)
+    .query(&[("name", name), ("type", "TXT")])
+    .header(reqwest::header::ACCEPT, "application/dns-json")
+    .send()
+    .await?;
+  if !resp.status().is_success() {
+    return Err(AppError::Internal(anyhow!(
+      "DNS lookup of {} failed with status {}",
+      name,
+      resp.status()
+    )));
+  }
+  let resp: DnsJsonResponse = resp.json().await?;
+  // Any status other than NOERROR means there is no record, such as NXDOMAIN for a missing name
+  if resp.status != 0 {
+    return Ok(vec![]);
+  }
+  Ok(
+    resp
+      .answer
+      .into_iter()
+      .filter(|answer| answer.record_type == DNS_TXT_RECORD_TYPE)
+      .map(|answer| txt_record_text(&answer.data))
+      .collect(),
+  )
+}
+
+/// The data of a TXT record is a list of quoted strings, which make up the text of the record
+/// once concatenated.
+fn txt_record_text(data: &str) -> String {
+  let mut text = String::with_capacity(data.len());
+  let mut in_quotes = false;
+  let mut chars = data.chars();
+  let mut has_quotes = false;
+  while let Some(c) = chars.next() {
+    match c {
+      '"' => {
+        in_quotes = !in_quotes;
+        has_quotes = true;
+      },
+      '\\' if in_quotes => {
+        if let Some(escaped) = chars.next() {
+          text.push(escaped);
+        }
+      },
+      c if in_quotes => text.push(c),
+      _ => {},
+    }
+  }
+  if has_quotes {
+    text
+  } else {
+    data.to_string()
+  }
+}
+
+fn domain_in_use(domain: &str) -> AppError {
+  AppError::InvalidRequest(format!(
+    "The domain {} is already in use by another workspace",
+    domain
+  ))
+}
+
+fn custom_domain_not_found(workspace_id: &Uuid, domain: &str) -> AppError {
+  AppError::RecordNotFound(format!(
+    "Domain {} is not registered by workspace {}",
+    domain, workspace_id
+  ))
+}
+
+fn custom_domain_from_row(row: AFPublishCustomDomainRow) -> PublishCustomDomain {
+  PublishCustomDomain {
+    verification_record_name: verification_record_name(&row.domain),
+    verification_record_value: verification_record_value(&row.verification_token),
+    domain: row.domain,
+    verified_at: row.verified_at,
+    created_at: row.created_at,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn normalize_custom_domain_test() {
+    assert_eq!(
+      normalize_domain(" Docs.Example.com. ").unwrap(),
+      "docs.example.com"
+    );
+    assert!(normalize_domain("localhost").is_err());
+    assert!(normalize_domain("127.0.0.1").is_err());
+    assert!(normalize_domain("-docs.example.com").is_err());
+    assert!(normalize_domain("docs..example.com").is_err());
+    assert!(normalize_domain("docs.example.com/path").is_err());
+    assert_eq!(strip_port("docs.example.com:8080"), "docs.example.com");
+  }
+
+  #[test]
+  fn txt_record_text_test() {
+    assert_eq!(txt_record_text("\"abc=123\""), "abc=123");
+    assert_eq!(txt_record_text("\"abc\" \"=123\""), "abc=123");
+    assert_eq!(txt_record_text("\"a\\\"b\""), "a\"b");
+    assert_eq!(txt_record_text("abc=123"), "abc=123");
+  }
+}
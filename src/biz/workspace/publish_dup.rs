@@ -43,6 +43,7 @@ use yrs::{Map, MapRef};
 
 use crate::biz::collab::ops::get_latest_collab_encoded;
 
+/// Returns the id of the view the published view was duplicated as.
 #[allow(clippy::too_many_arguments)]
 pub async fn duplicate_published_collab_to_workspace(
   pg_pool: &PgPool,
@@ -52,7 +53,7 @@ pub async fn duplicate_published_collab_to_workspace(
   publish_view_id: String,
   dest_workspace_id: String,
   dest_view_id: String,
-) -> Result<(), AppError> {
+) -> Result<String, AppError> {
   let copier = PublishCollabDuplicator::new(
     pg_pool.clone(),
     bucket_client,
@@ -63,13 +64,13 @@ pub async fn duplicate_published_collab_to_workspace(
   );
 
   let time_now = chrono::Utc::now().timestamp_millis();
-  copier.duplicate(&publish_view_id).await?;
+  let view_id = copier.duplicate(&publish_view_id).await?;
   let elapsed = chrono::Utc::now().timestamp_millis() - time_now;
   tracing::info!(
     "duplicate_published_collab_to_workspace: elapsed time: {}ms",
     elapsed
   );
-  Ok(())
+  Ok(view_id)
 }
 
 pub struct PublishCollabDuplicator {
@@ -135,7 +136,7 @@ impl PublishCollabDuplicator {
     }
   }
 
-  async fn duplicate(mut self, publish_view_id: &str) -> Result<(), AppError> {
+  async fn duplicate(mut self, publish_view_id: &str) -> Result<String, AppError> {
     // new view after deep copy
     // this is the root of the document/database duplicated
     let mut root_view = match self.deep_copy(gen_view_id(), publish_view_id).await? {
@@ -147,6 +148,7 @@ impl PublishCollabDuplicator {
       },
     };
     root_view.parent_view_id.clone_from(&self.dest_view_id);
+    let root_view_id = root_view.id.clone();
 
     // destructuring self to own inner values, avoids cloning
     let PublishCollabDuplicator {
@@ -325,7 +327,7 @@ impl PublishCollabDuplicator {
     broadcast_update(&collab_storage, &dest_workspace_id, encoded_update).await?;
 
     txn.commit().await?;
-    Ok(())
+    Ok(root_view_id)
   }
 
   /// Deep copy a published collab to the destination workspace.
@@ -1109,7 +1111,7 @@ impl PublishCollabDuplicator {
 }
 
 /// broadcast updates to collab group if exists
-async fn broadcast_update(
+pub(crate) async fn broadcast_update(
   collab_storage: &Arc<CollabAccessControlStorage>,
   oid: &str,
   encoded_update: Vec<u8>,
@@ -1187,7 +1189,10 @@ fn to_folder_view_layout(layout: workspace_dto::ViewLayout) -> collab_folder::Vi
   }
 }
 
-async fn collab_to_bin(collab: Collab, collab_type: CollabType) -> Result<Vec<u8>, AppError> {
+pub(crate) async fn collab_to_bin(
+  collab: Collab,
+  collab_type: CollabType,
+) -> Result<Vec<u8>, AppError> {
   tokio::task::spawn_blocking(move || {
     let bin = collab
       .encode_collab_v1(|collab| collab_type.validate_require_data(collab))
@@ -0,0 +1,361 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::DerefMut;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use anyhow::{anyhow, Context};
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use chrono::{Duration, Utc};
+use collab_database::entity::FieldType;
+use collab_database::fields::Field;
+use database::collab::GetCollabOrigin;
+use database::pg_row::AFPublishedDatabaseFormRow;
+use database::publish::{select_published_collab_info, select_published_collab_workspace_view_id};
+use database::publish_form::{
+  delete_published_database_form as delete_published_database_form_row,
+  insert_published_database_form_submission, select_published_database_form,
+  select_published_database_form_for_update, select_published_database_form_submission_count,
+  upsert_published_database_form,
+};
+use secrecy::ExposeSecret;
+use serde::Deserialize;
+use shared_entity::dto::workspace_dto::{
+  PublishedDatabaseForm, PublishedDatabaseFormField, PublishedDatabaseFormSettings,
+  PublishedDatabaseFormSubmission, SetPublishedDatabaseFormParams, SubmitPublishedDatabaseForm,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::config::PublishedCollabSetting;
+
+use super::database_row::{
+  find_field, insert_database_rows_with_transaction, open_view_database, resolve_cells,
+};
+use super::export::select_options;
+use super::publish::check_workspace_owner_or_publisher;
+
+const DEFAULT_FORM_SUBMISSIONS_PER_HOUR: i32 = 100;
+const MAX_FORM_SUBMISSIONS_PER_HOUR: i32 = 10_000;
+/// How many rows a single ip address can submit through a form in an hour.
+const FORM_SUBMISSIONS_PER_IP_PER_HOUR: i64 = 10;
+const CAPTCHA_VERIFY_TIMEOUT_SECS: u64 = 10;
+
+/// Let the visitors of the published database view add rows to it, filling in the given fields
+/// only. The rows are created on behalf of the user that sets the form up, who must be the owner
+/// of the workspace or the publisher of the view.
+#[allow(clippy::too_many_arguments)]
+pub async fn set_published_database_form(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  setting: &PublishedCollabSetting,
+  user_uuid: &Uuid,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  params: SetPublishedDatabaseFormParams,
+) -> Result<PublishedDatabaseFormSettings, AppError> {
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+  select_published_collab_info(pg_pool, view_id).await?;
+  let max_submissions_per_hour = params
+    .max_submissions_per_hour
+    .unwrap_or(DEFAULT_FORM_SUBMISSIONS_PER_HOUR);
+  if !(1..=MAX_FORM_SUBMISSIONS_PER_HOUR).contains(&max_submissions_per_hour) {
+    return Err(AppError::InvalidRequest(format!(
+      "The submissions per hour of the form must be between 1 and {}",
+      MAX_FORM_SUBMISSIONS_PER_HOUR
+    )));
+  }
+  if params.require_captcha && setting.captcha_secret_key.is_none() {
+    return Err(AppError::InvalidRequest(
+      "No captcha is configured on the server".to_string(),
+    ));
+  }
+
+  let database = open_view_database(
+    collab_storage,
+    pg_pool,
+    GetCollabOrigin::User { uid },
+    workspace_id,
+    &view_id.to_string(),
+  )
+  .await?;
+  let fields = database.fields()?;
+  let mut field_ids = Vec::with_capacity(params.fields.len());
+  for key in &params.fields {
+    let field = find_field(&fields, key)?;
+    if !is_form_field(field) {
+      return Err(AppError::InvalidRequest(format!(
+        "The field {} can't be filled in through a form",
+        field.name
+      )));
+    }
+    if !field_ids.contains(&field.id) {
+      field_ids.push(field.id.clone());
+    }
+  }
+  if field_ids.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "The form must have at least one field".to_string(),
+    ));
+  }
+
+  let row = upsert_published_database_form(
+    pg_pool,
+    workspace_id,
+    view_id,
+    &field_ids,
+    max_submissions_per_hour,
+    params.require_captcha,
+    uid,
+  )
+  .await?;
+  Ok(form_settings_from_row(row))
+}
+
+pub async fn get_published_database_form_settings(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<PublishedDatabaseFormSettings, AppError> {
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+  let row = select_published_database_form(pg_pool, workspace_id, view_id)
+    .await?
+    .ok_or_else(|| form_not_found(view_id))?;
+  Ok(form_settings_from_row(row))
+}
+
+/// Stop accepting submissions. The rows already submitted stay in the database.
+pub async fn delete_published_database_form(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<(), AppError> {
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+  if !delete_published_database_form_row(pg_pool, workspace_id, view_id).await? {
+    return Err(form_not_found(view_id));
+  }
+  Ok(())
+}
+
+/// Returns the fields of the form of the published view, in the order of the view. The caller
+/// checks that the visitor can read the published view.
+pub async fn get_published_database_form(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<PublishedDatabaseForm, AppError> {
+  let key =
+    select_published_collab_workspace_view_id(pg_pool, publish_namespace, publish_name).await?;
+  let form = select_published_database_form(pg_pool, &key.workspace_id, &key.view_id)
+    .await?
+    .ok_or_else(|| form_not_found(&key.view_id))?;
+  let database = open_view_database(
+    collab_storage,
+    pg_pool,
+    GetCollabOrigin::Server,
+    &key.workspace_id,
+    &key.view_id.to_string(),
+  )
+  .await?;
+  let fields = form_fields(database.fields()?, &form)
+    .iter()
+    .map(|field| PublishedDatabaseFormField {
+      id: field.id.clone(),
+      name: field.name.clone(),
+      field_type: field.field_type,
+      options: select_options(field)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|option| option.name)
+        .collect(),
+    })
+    .collect();
+  Ok(PublishedDatabaseForm {
+    fields,
+    require_captcha: form.require_captcha,
+  })
+}
+
+/// Add a row with the submitted cells at the end of the published database. Only the fields of
+/// the form can be set, and a form accepts a bounded number of submissions per hour, from all the
+/// visitors and from each ip address. The caller checks that the visitor can read the published
+/// view.
+pub async fn submit_published_database_form(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  setting: &PublishedCollabSetting,
+  publish_namespace: &str,
+  publish_name: &str,
+  ip: Option<&str>,
+  params: SubmitPublishedDatabaseForm,
+) -> Result<PublishedDatabaseFormSubmission, AppError> {
+  let key =
+    select_published_collab_workspace_view_id(pg_pool, publish_namespace, publish_name).await?;
+  // The captcha is verified before the form is locked, so that the other submissions don't wait
+  // for the captcha service
+  let form = select_published_database_form(pg_pool, &key.workspace_id, &key.view_id)
+    .await?
+    .ok_or_else(|| form_not_found(&key.view_id))?;
+  if form.require_captcha {
+    verify_captcha(setting, params.captcha_token.as_deref(), ip).await?;
+  }
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to submit published form")?;
+  let form =
+    select_published_database_form_for_update(txn.deref_mut(), &key.workspace_id, &key.view_id)
+      .await?
+      .ok_or_else(|| form_not_found(&key.view_id))?;
+  let (submissions, ip_submissions) = select_published_database_form_submission_count(
+    txn.deref_mut(),
+    &key.view_id,
+    ip,
+    Utc::now() - Duration::hours(1),
+  )
+  .await?;
+  if submissions >= form.max_submissions_per_hour as i64 {
+    return Err(AppError::TooManyRequests(format!(
+      "The form received {} submissions in the last hour, try again later",
+      form.max_submissions_per_hour
+    )));
+  }
+  if ip.is_some() && ip_submissions >= FORM_SUBMISSIONS_PER_IP_PER_HOUR {
+    return Err(AppError::TooManyRequests(
+      "Too many submissions of the form in the last hour, try again later".to_string(),
+    ));
+  }
+
+  let database = open_view_database(
+    collab_storage.clone(),
+    pg_pool,
+    GetCollabOrigin::Server,
+    &key.workspace_id,
+    &key.view_id.to_string(),
+  )
+  .await?;
+  let fields = form_fields(database.fields()?, &form);
+  let cells: HashMap<_, _> = resolve_cells(&fields, params.cells)?
+    .into_iter()
+    .filter_map(|(field_id, cell)| cell.map(|cell| (field_id, cell)))
+    .collect();
+  if cells.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "The submission has no value".to_string(),
+    ));
+  }
+  // The row is written in the transaction that holds the form, so that the concurrent submissions
+  // add their rows to the database one after the other and a failed submission is not counted
+  let (mut row_details, inserted_rows) = insert_database_rows_with_transaction(
+    &collab_storage,
+    form.created_by,
+    &key.workspace_id,
+    database,
+    vec![cells],
+    &mut txn,
+  )
+  .await?;
+  let row_id = row_details
+    .pop()
+    .map(|row_detail| row_detail.row.id.to_string())
+    .ok_or_else(|| AppError::Internal(anyhow!("the submitted row is missing")))?;
+  insert_published_database_form_submission(txn.deref_mut(), &key.view_id, &row_id, ip).await?;
+  txn
+    .commit()
+    .await
+    .context("fail to commit the transaction to submit published form")?;
+  inserted_rows
+    .notify(&collab_storage, pg_pool, form.created_by, &key.workspace_id)
+    .await?;
+  Ok(PublishedDatabaseFormSubmission { row_id })
+}
+
+/// The fields of the form that are still in the database, in the order of the view.
+fn form_fields(fields: Vec<Field>, form: &AFPublishedDatabaseFormRow) -> Vec<Field> {
+  let field_ids: HashSet<&String> = form.field_ids.iter().collect();
+  fields
+    .into_iter()
+    .filter(|field| field_ids.contains(&field.id) && is_form_field(field))
+    .collect()
+}
+
+/// The fields whose cells can be set from a value given by the visitor.
+fn is_form_field(field: &Field) -> bool {
+  matches!(
+    FieldType::from(field.field_type),
+    FieldType::RichText
+      | FieldType::URL
+      | FieldType::Number
+      | FieldType::Checkbox
+      | FieldType::DateTime
+      | FieldType::SingleSelect
+      | FieldType::MultiSelect
+  )
+}
+
+#[derive(Deserialize)]
+struct CaptchaVerifyResponse {
+  success: bool,
+}
+
+/// Verify the captcha token with the captcha service, in the format shared by Cloudflare
+/// Turnstile and hCaptcha.
+async fn verify_captcha(
+  setting: &PublishedCollabSetting,
+  token: Option<&str>,
+  ip: Option<&str>,
+) -> Result<(), AppError> {
+  let token = token
+    .filter(|token| !token.is_empty())
+    .ok_or_else(|| AppError::InvalidRequest("The captcha token is missing".to_string()))?;
+  let secret_key = setting
+    .captcha_secret_key
+    .as_ref()
+    .ok_or_else(|| AppError::Internal(anyhow!("the form requires a captcha but none is set up")))?;
+  let mut form = vec![
+    ("secret", secret_key.expose_secret().as_str()),
+    ("response", token),
+  ];
+  if let Some(ip) = ip {
+    form.push(("remoteip", ip));
+  }
+  let http_client = reqwest::Client::builder()
+    .timeout(StdDuration::from_secs(CAPTCHA_VERIFY_TIMEOUT_SECS))
+    .build()?;
+  let resp = http_client
+    .post(&setting.captcha_verify_url)
+    .form(&form)
+    .send()
+    .await?;
+  if !resp.status().is_success() {
+    return Err(AppError::Internal(anyhow!(
+      "captcha verification failed with status {}",
+      resp.status()
+    )));
+  }
+  let resp: CaptchaVerifyResponse = resp.json().await?;
+  if !resp.success {
+    return Err(AppError::UserUnAuthorized(
+      "The captcha is invalid".to_string(),
+    ));
+  }
+  Ok(())
+}
+
+fn form_settings_from_row(row: AFPublishedDatabaseFormRow) -> PublishedDatabaseFormSettings {
+  PublishedDatabaseFormSettings {
+    view_id: row.view_id,
+    field_ids: row.field_ids,
+    max_submissions_per_hour: row.max_submissions_per_hour,
+    require_captcha: row.require_captcha,
+    updated_at: row.updated_at,
+  }
+}
+
+fn form_not_found(view_id: &Uuid) -> AppError {
+  AppError::RecordNotFound(format!("published view {} has no form", view_id))
+}
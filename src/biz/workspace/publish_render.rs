@@ -0,0 +1,654 @@
+use app_error::AppError;
+use collab_document::blocks::{Block, DocumentData};
+use collab_document::document::Document;
+use database::publish::{select_published_collab_blob, select_published_collab_render_version};
+use redis::AsyncCommands;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::state::RedisConnectionManager;
+
+use super::publish_dup::collab_from_doc_state;
+
+/// Rendered documents are cached until they are published again, or for a day at most.
+const RENDER_CACHE_TTL_SECS: u64 = 60 * 60 * 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublishedRenderFormat {
+  Html,
+  Markdown,
+}
+
+impl PublishedRenderFormat {
+  fn as_str(&self) -> &'static str {
+    match self {
+      PublishedRenderFormat::Html => "html",
+      PublishedRenderFormat::Markdown => "markdown",
+    }
+  }
+}
+
+/// Render the document published under the given name. The rendered document is cached for the
+/// version that is published, so it is only rendered again once the document is published again.
+pub async fn render_published_document(
+  pg_pool: &PgPool,
+  redis_client: &RedisConnectionManager,
+  publish_namespace: &str,
+  publish_name: &str,
+  format: PublishedRenderFormat,
+) -> Result<String, AppError> {
+  let (view_id, published_version, updated_at) =
+    select_published_collab_render_version(pg_pool, publish_namespace, publish_name)
+      .await?
+      .ok_or_else(|| {
+        AppError::RecordNotFound(format!(
+          "published view {}/{} not found",
+          publish_namespace, publish_name
+        ))
+      })?;
+  let cache_key = format!(
+    "af_published_render:{}:{}:{}:{}",
+    view_id,
+    format.as_str(),
+    published_version.unwrap_or_default(),
+    updated_at
+      .map(|time| time.timestamp_micros())
+      .unwrap_or_default()
+  );
+  let mut redis_client = redis_client.clone();
+  match redis_client.get::<_, Option<String>>(&cache_key).await {
+    Ok(Some(rendered)) => return Ok(rendered),
+    Ok(None) => {},
+    Err(err) => warn!("failed to read rendered document {}: {}", view_id, err),
+  }
+
+  let blob = select_published_collab_blob(pg_pool, publish_namespace, publish_name).await?;
+  let object_id = view_id.to_string();
+  let rendered = tokio::task::spawn_blocking(move || {
+    let document_data = collab_from_doc_state(blob, &object_id)
+      .and_then(|collab| Document::open(collab).map_err(|e| AppError::Unhandled(e.to_string())))
+      .and_then(|document| {
+        document
+          .get_document_data()
+          .map_err(|e| AppError::Unhandled(e.to_string()))
+      })
+      .map_err(|_| {
+        AppError::InvalidRequest(format!("published view {} is not a document", object_id))
+      })?;
+    Ok::<_, AppError>(match format {
+      PublishedRenderFormat::Html => document_to_html(&document_data),
+      PublishedRenderFormat::Markdown => document_to_markdown(&document_data),
+    })
+  })
+  .await??;
+
+  if let Err(err) = redis_client
+    .set_ex::<_, _, ()>(&cache_key, &rendered, RENDER_CACHE_TTL_SECS)
+    .await
+  {
+    warn!("failed to cache rendered document {}: {}", view_id, err);
+  }
+  Ok(rendered)
+}
+
+/// A run of text of a block, with its formatting.
+struct TextOp {
+  text: String,
+  attributes: Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct InsertOp {
+  insert: String,
+  #[serde(default)]
+  attributes: Option<Map<String, Value>>,
+}
+
+impl TextOp {
+  fn is_set(&self, attribute: &str) -> bool {
+    matches!(self.attributes.get(attribute), Some(Value::Bool(true)))
+  }
+
+  fn str_attribute(&self, attribute: &str) -> Option<&str> {
+    self
+      .attributes
+      .get(attribute)
+      .and_then(|value| value.as_str())
+  }
+
+  /// The text to render. Mentions are left out, since they refer to content that is not part of
+  /// the document, and inline formulas are rendered as their source.
+  fn display_text(&self) -> Option<&str> {
+    if self.attributes.contains_key("mention") {
+      return None;
+    }
+    Some(self.str_attribute("formula").unwrap_or(&self.text))
+  }
+}
+
+fn text_ops(document: &DocumentData, block: &Block) -> Vec<TextOp> {
+  let values = match block.data.get("delta") {
+    Some(Value::Array(values)) => values.clone(),
+    _ => {
+      let json = match (
+        block.external_type.as_deref(),
+        block.external_id.as_ref(),
+        document.meta.text_map.as_ref(),
+      ) {
+        (Some("text"), Some(text_id), Some(text_map)) => text_map.get(text_id),
+        _ => None,
+      };
+      json
+        .and_then(|json| serde_json::from_str::<Vec<Value>>(json).ok())
+        .unwrap_or_default()
+    },
+  };
+  values
+    .into_iter()
+    .filter_map(|value| serde_json::from_value::<InsertOp>(value).ok())
+    .map(|op| TextOp {
+      text: op.insert,
+      attributes: op.attributes.unwrap_or_default(),
+    })
+    .collect()
+}
+
+pub(crate) fn children<'a>(document: &'a DocumentData, block: &Block) -> Vec<&'a Block> {
+  document
+    .meta
+    .children_map
+    .get(&block.children)
+    .map(|ids| {
+      ids
+        .iter()
+        .filter_map(|id| document.blocks.get(id))
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn heading_level(block: &Block) -> usize {
+  block
+    .data
+    .get("level")
+    .and_then(|level| level.as_u64())
+    .unwrap_or(1)
+    .clamp(1, 6) as usize
+}
+
+fn data_str<'a>(block: &'a Block, key: &str) -> Option<&'a str> {
+  block.data.get(key).and_then(|value| value.as_str())
+}
+
+fn is_checked(block: &Block) -> bool {
+  matches!(block.data.get("checked"), Some(Value::Bool(true)))
+}
+
+/// Only links to web pages and email addresses are rendered, so that a published document can
+/// not run scripts through `javascript:` or `data:` urls.
+fn safe_url(url: &str) -> Option<&str> {
+  let url = url.trim();
+  let lowercase = url.to_lowercase();
+  ["http://", "https://", "mailto:"]
+    .iter()
+    .any(|scheme| lowercase.starts_with(scheme))
+    .then_some(url)
+}
+
+fn plain_text(ops: &[TextOp]) -> String {
+  ops.iter().filter_map(|op| op.display_text()).collect()
+}
+
+/// The text of the block as it is rendered, without its formatting.
+pub(crate) fn block_text(document: &DocumentData, block: &Block) -> String {
+  plain_text(&text_ops(document, block))
+}
+
+/// Convert the document into an html fragment. All the text of the document is escaped, so the
+/// fragment only contains the markup generated here.
+pub fn document_to_html(document: &DocumentData) -> String {
+  let mut html = String::new();
+  if let Some(page) = document.blocks.get(&document.page_id) {
+    html_blocks(document, &children(document, page), &mut html);
+  }
+  html
+}
+
+fn html_blocks(document: &DocumentData, blocks: &[&Block], html: &mut String) {
+  let mut i = 0;
+  while i < blocks.len() {
+    let list_tag = match blocks[i].ty.as_str() {
+      "bulleted_list" | "todo_list" => Some("ul"),
+      "numbered_list" => Some("ol"),
+      _ => None,
+    };
+    match list_tag {
+      Some(tag) => {
+        // Consecutive items of the same list type make up a single list
+        let ty = &blocks[i].ty;
+        html.push_str(&format!("<{}>", tag));
+        while i < blocks.len() && &blocks[i].ty == ty {
+          html_list_item(document, blocks[i], html);
+          i += 1;
+        }
+        html.push_str(&format!("</{}>\n", tag));
+      },
+      None => {
+        html_block(document, blocks[i], html);
+        i += 1;
+      },
+    }
+  }
+}
+
+fn html_list_item(document: &DocumentData, block: &Block, html: &mut String) {
+  html.push_str("<li>");
+  if block.ty == "todo_list" {
+    let checked = if is_checked(block) { " checked" } else { "" };
+    html.push_str(&format!("<input type=\"checkbox\" disabled{}> ", checked));
+  }
+  html_inline(&text_ops(document, block), html);
+  html_blocks(document, &children(document, block), html);
+  html.push_str("</li>");
+}
+
+fn html_block(document: &DocumentData, block: &Block, html: &mut String) {
+  let ops = text_ops(document, block);
+  let block_children = children(document, block);
+  match block.ty.as_str() {
+    "heading" => {
+      let level = heading_level(block);
+      html.push_str(&format!("<h{}>", level));
+      html_inline(&ops, html);
+      html.push_str(&format!("</h{}>\n", level));
+      html_blocks(document, &block_children, html);
+    },
+    "quote" => {
+      html.push_str("<blockquote>");
+      html_inline(&ops, html);
+      html_blocks(document, &block_children, html);
+      html.push_str("</blockquote>\n");
+    },
+    "callout" => {
+      html.push_str("<aside>");
+      if let Some(icon) = data_str(block, "icon").filter(|icon| !icon.is_empty()) {
+        html.push_str(&format!("<span>{}</span> ", escape_html(icon)));
+      }
+      html_inline(&ops, html);
+      html_blocks(document, &block_children, html);
+      html.push_str("</aside>\n");
+    },
+    "toggle_list" => {
+      html.push_str("<details><summary>");
+      html_inline(&ops, html);
+      html.push_str("</summary>");
+      html_blocks(document, &block_children, html);
+      html.push_str("</details>\n");
+    },
+    "code" => {
+      match data_str(block, "language").filter(|language| !language.is_empty()) {
+        Some(language) => html.push_str(&format!(
+          "<pre><code class=\"language-{}\">",
+          escape_html(language)
+        )),
+        None => html.push_str("<pre><code>"),
+      }
+      html.push_str(&escape_html(&plain_text(&ops)));
+      html.push_str("</code></pre>\n");
+    },
+    "math_equation" => {
+      if let Some(formula) = data_str(block, "formula") {
+        html.push_str(&format!(
+          "<div class=\"math\">{}</div>\n",
+          escape_html(formula)
+        ));
+      }
+    },
+    "divider" => html.push_str("<hr>\n"),
+    "image" => {
+      if let Some(url) = data_str(block, "url").and_then(safe_url) {
+        html.push_str(&format!("<img src=\"{}\" alt=\"\">\n", escape_html(url)));
+      }
+    },
+    "link_preview" => {
+      if let Some(url) = data_str(block, "url").and_then(safe_url) {
+        let url = escape_html(url);
+        html.push_str(&format!("<p><a href=\"{}\">{}</a></p>\n", url, url));
+      }
+    },
+    // Paragraphs, and the blocks that have no html counterpart, are rendered as their text
+    _ => {
+      if !ops.is_empty() {
+        html.push_str("<p>");
+        html_inline(&ops, html);
+        html.push_str("</p>\n");
+      }
+      html_blocks(document, &block_children, html);
+    },
+  }
+}
+
+fn html_inline(ops: &[TextOp], html: &mut String) {
+  for op in ops {
+    let text = match op.display_text() {
+      Some(text) => text,
+      None => continue,
+    };
+    let mut rendered = escape_html(text);
+    if op.attributes.contains_key("formula") {
+      rendered = format!("<span class=\"math\">{}</span>", rendered);
+    }
+    if op.is_set("code") {
+      rendered = format!("<code>{}</code>", rendered);
+    }
+    if op.is_set("bold") {
+      rendered = format!("<strong>{}</strong>", rendered);
+    }
+    if op.is_set("italic") {
+      rendered = format!("<em>{}</em>", rendered);
+    }
+    if op.is_set("underline") {
+      rendered = format!("<u>{}</u>", rendered);
+    }
+    if op.is_set("strikethrough") {
+      rendered = format!("<s>{}</s>", rendered);
+    }
+    if let Some(href) = op.str_attribute("href").and_then(safe_url) {
+      rendered = format!("<a href=\"{}\">{}</a>", escape_html(href), rendered);
+    }
+    html.push_str(&rendered);
+  }
+}
+
+fn escape_html(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&#39;"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+/// Convert the document into CommonMark.
+pub fn document_to_markdown(document: &DocumentData) -> String {
+  let mut markdown = String::new();
+  if let Some(page) = document.blocks.get(&document.page_id) {
+    markdown_blocks(document, &children(document, page), 0, &mut markdown);
+  }
+  markdown.trim_end().to_string() + "\n"
+}
+
+fn markdown_blocks(document: &DocumentData, blocks: &[&Block], depth: usize, md: &mut String) {
+  for block in blocks {
+    markdown_block(document, block, depth, md);
+  }
+}
+
+fn markdown_block(document: &DocumentData, block: &Block, depth: usize, md: &mut String) {
+  let ops = text_ops(document, block);
+  let block_children = children(document, block);
+  let indent = "  ".repeat(depth);
+  let list_marker = match block.ty.as_str() {
+    "bulleted_list" | "toggle_list" => Some("- ".to_string()),
+    "numbered_list" => Some("1. ".to_string()),
+    "todo_list" => Some(format!(
+      "- [{}] ",
+      if is_checked(block) { "x" } else { " " }
+    )),
+    _ => None,
+  };
+  if let Some(marker) = list_marker {
+    md.push_str(&format!("{}{}{}\n", indent, marker, markdown_inline(&ops)));
+    markdown_blocks(document, &block_children, depth + 1, md);
+    return;
+  }
+
+  ensure_blank_line(md);
+  match block.ty.as_str() {
+    "heading" => {
+      md.push_str(&format!(
+        "{}{} {}\n\n",
+        indent,
+        "#".repeat(heading_level(block)),
+        markdown_inline(&ops)
+      ));
+      markdown_blocks(document, &block_children, depth, md);
+    },
+    "quote" | "callout" => {
+      let mut quoted = String::new();
+      if let Some(icon) = data_str(block, "icon").filter(|icon| !icon.is_empty()) {
+        quoted.push_str(icon);
+        quoted.push(' ');
+      }
+      quoted.push_str(&markdown_inline(&ops));
+      quoted.push_str("\n\n");
+      markdown_blocks(document, &block_children, 0, &mut quoted);
+      for line in quoted.trim_end().lines() {
+        if line.is_empty() {
+          md.push_str(&format!("{}>\n", indent));
+        } else {
+          md.push_str(&format!("{}> {}\n", indent, line));
+        }
+      }
+      md.push('\n');
+    },
+    "code" => {
+      let language = data_str(block, "language").unwrap_or_default();
+      let code = plain_text(&ops);
+      // The fence must be longer than any run of backticks in the code
+      let fence = "`".repeat(longest_backtick_run(&code).max(2) + 1);
+      md.push_str(&format!(
+        "{}{}{}\n{}\n{}{}\n\n",
+        indent, fence, language, code, indent, fence
+      ));
+    },
+    "math_equation" => {
+      if let Some(formula) = data_str(block, "formula") {
+        md.push_str(&format!("{}$$\n{}\n{}$$\n\n", indent, formula, indent));
+      }
+    },
+    "divider" => md.push_str(&format!("{}---\n\n", indent)),
+    "image" => {
+      if let Some(url) = data_str(block, "url").and_then(safe_url) {
+        md.push_str(&format!("{}![](<{}>)\n\n", indent, url));
+      }
+    },
+    "link_preview" => {
+      if let Some(url) = data_str(block, "url").and_then(safe_url) {
+        md.push_str(&format!("{}<{}>\n\n", indent, url));
+      }
+    },
+    _ => {
+      let text = markdown_inline(&ops);
+      if !text.is_empty() {
+        md.push_str(&format!("{}{}\n\n", indent, text));
+      }
+      markdown_blocks(document, &block_children, depth, md);
+    },
+  }
+}
+
+fn markdown_inline(ops: &[TextOp]) -> String {
+  let mut md = String::new();
+  for op in ops {
+    let text = match op.display_text() {
+      Some(text) => text,
+      None => continue,
+    };
+    if text.trim().is_empty() {
+      md.push_str(text);
+      continue;
+    }
+    let mut rendered = if op.attributes.contains_key("formula") {
+      format!("${}$", text)
+    } else if op.is_set("code") {
+      let fence = "`".repeat(longest_backtick_run(text) + 1);
+      format!("{} {} {}", fence, text, fence)
+    } else {
+      escape_markdown(text)
+    };
+    if op.is_set("bold") {
+      rendered = format!("**{}**", rendered);
+    }
+    if op.is_set("italic") {
+      rendered = format!("_{}_", rendered);
+    }
+    if op.is_set("strikethrough") {
+      rendered = format!("~~{}~~", rendered);
+    }
+    if let Some(href) = op.str_attribute("href").and_then(safe_url) {
+      rendered = format!("[{}](<{}>)", rendered, href);
+    }
+    md.push_str(&rendered);
+  }
+  md.replace('\n', "  \n")
+}
+
+fn escape_markdown(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    if matches!(
+      c,
+      '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '~' | '|'
+    ) {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escaped
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+  text
+    .split(|c| c != '`')
+    .map(|run| run.len())
+    .max()
+    .unwrap_or(0)
+}
+
+fn ensure_blank_line(md: &mut String) {
+  if !md.is_empty() && !md.ends_with("\n\n") {
+    md.push('\n');
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use collab_document::blocks::DocumentMeta;
+  use serde_json::json;
+
+  use super::*;
+
+  fn block(id: &str, ty: &str, parent: &str, data: Value) -> Block {
+    Block {
+      id: id.to_string(),
+      ty: ty.to_string(),
+      parent: parent.to_string(),
+      children: format!("{}-children", id),
+      external_id: None,
+      external_type: None,
+      data: serde_json::from_value(data).unwrap(),
+    }
+  }
+
+  fn document(blocks: Vec<Block>) -> DocumentData {
+    let mut children_map: HashMap<String, Vec<String>> = HashMap::new();
+    for block in &blocks {
+      if !block.parent.is_empty() {
+        children_map
+          .entry(format!("{}-children", block.parent))
+          .or_default()
+          .push(block.id.clone());
+      }
+    }
+    DocumentData {
+      page_id: "page".to_string(),
+      blocks: blocks
+        .into_iter()
+        .map(|block| (block.id.clone(), block))
+        .collect(),
+      meta: DocumentMeta {
+        children_map,
+        text_map: Some(HashMap::new()),
+      },
+    }
+  }
+
+  fn sample_document() -> DocumentData {
+    document(vec![
+      block("page", "page", "", json!({})),
+      block(
+        "title",
+        "heading",
+        "page",
+        json!({"level": 2, "delta": [{"insert": "Release <notes>"}]}),
+      ),
+      block(
+        "intro",
+        "paragraph",
+        "page",
+        json!({"delta": [
+          {"insert": "Read the "},
+          {"insert": "docs", "attributes": {"href": "https://appflowy.io", "bold": true}},
+          {"insert": " or ", "attributes": {}},
+          {"insert": "this", "attributes": {"href": "javascript:alert(1)"}}
+        ]}),
+      ),
+      block(
+        "item-1",
+        "bulleted_list",
+        "page",
+        json!({"delta": [{"insert": "first"}]}),
+      ),
+      block(
+        "item-2",
+        "bulleted_list",
+        "page",
+        json!({"delta": [{"insert": "second"}]}),
+      ),
+      block(
+        "todo",
+        "todo_list",
+        "page",
+        json!({"checked": true, "delta": [{"insert": "done"}]}),
+      ),
+      block(
+        "code",
+        "code",
+        "page",
+        json!({"language": "rust", "delta": [{"insert": "fn main() {}"}]}),
+      ),
+    ])
+  }
+
+  #[test]
+  fn published_document_to_html_test() {
+    let html = document_to_html(&sample_document());
+    assert!(html.contains("<h2>Release &lt;notes&gt;</h2>"));
+    assert!(html.contains("<a href=\"https://appflowy.io\"><strong>docs</strong></a>"));
+    assert!(!html.contains("javascript:"));
+    assert!(html.contains("<ul><li>first</li><li>second</li></ul>"));
+    assert!(html.contains("<input type=\"checkbox\" disabled checked> done"));
+    assert!(html.contains("<pre><code class=\"language-rust\">fn main() {}</code></pre>"));
+  }
+
+  #[test]
+  fn published_document_to_markdown_test() {
+    let markdown = document_to_markdown(&sample_document());
+    assert!(markdown.contains("## Release \\<notes\\>\n"));
+    assert!(markdown.contains("Read the [**docs**](<https://appflowy.io>) or this\n"));
+    assert!(markdown.contains("- first\n- second\n"));
+    assert!(markdown.contains("- [x] done\n"));
+    assert!(markdown.contains("```rust\nfn main() {}\n```\n"));
+  }
+}
@@ -0,0 +1,158 @@
+use app_error::AppError;
+use chrono::{DateTime, SecondsFormat, Utc};
+use database::pg_row::AFPublishedSiteEntryRow;
+use database::publish::{select_published_site_entries, select_workspace_id_for_publish_namespace};
+use sqlx::PgPool;
+
+/// The sitemap protocol allows at most 50,000 urls per sitemap.
+const MAX_SITEMAP_URLS: i64 = 50_000;
+const MAX_FEED_ENTRIES: i64 = 50;
+
+/// Returns the sitemap of the published site, which lists the url of every view published without
+/// a password. `site_url` is the url the pages of the site are served under.
+pub async fn get_published_sitemap(
+  pg_pool: &PgPool,
+  publish_namespace: &str,
+  site_url: &str,
+) -> Result<String, AppError> {
+  let workspace_id = select_workspace_id_for_publish_namespace(pg_pool, publish_namespace).await?;
+  let entries = select_published_site_entries(pg_pool, &workspace_id, MAX_SITEMAP_URLS).await?;
+  Ok(sitemap_xml(site_url, &entries))
+}
+
+/// Returns the Atom feed of the views most recently published or updated on the published site.
+pub async fn get_published_feed(
+  pg_pool: &PgPool,
+  publish_namespace: &str,
+  site_url: &str,
+) -> Result<String, AppError> {
+  let workspace_id = select_workspace_id_for_publish_namespace(pg_pool, publish_namespace).await?;
+  let entries = select_published_site_entries(pg_pool, &workspace_id, MAX_FEED_ENTRIES).await?;
+  Ok(atom_feed_xml(publish_namespace, site_url, &entries))
+}
+
+fn sitemap_xml(site_url: &str, entries: &[AFPublishedSiteEntryRow]) -> String {
+  let mut xml = String::from(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+     <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+  );
+  for entry in entries {
+    xml.push_str(&format!(
+      "  <url>\n    <loc>{}</loc>\n    <lastmod>{}</lastmod>\n  </url>\n",
+      escape_xml(&page_url(site_url, &entry.publish_name)),
+      timestamp(&entry.updated_at),
+    ));
+  }
+  xml.push_str("</urlset>\n");
+  xml
+}
+
+fn atom_feed_xml(
+  publish_namespace: &str,
+  site_url: &str,
+  entries: &[AFPublishedSiteEntryRow],
+) -> String {
+  // A feed without entries was last updated when it was generated
+  let updated = entries
+    .iter()
+    .map(|entry| entry.updated_at)
+    .max()
+    .unwrap_or_else(Utc::now);
+  let mut xml = format!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+     <feed xmlns=\"http://www.w3.org/2005/Atom\">\n  \
+     <title>{title}</title>\n  \
+     <id>{id}</id>\n  \
+     <link href=\"{id}\"/>\n  \
+     <updated>{updated}</updated>\n  \
+     <author><name>{title}</name></author>\n",
+    title = escape_xml(publish_namespace),
+    id = escape_xml(site_url),
+    updated = timestamp(&updated),
+  );
+  for entry in entries {
+    let url = escape_xml(&page_url(site_url, &entry.publish_name));
+    let title = entry
+      .title
+      .as_deref()
+      .filter(|title| !title.trim().is_empty())
+      .unwrap_or(&entry.publish_name);
+    xml.push_str(&format!(
+      "  <entry>\n    \
+       <title>{}</title>\n    \
+       <id>{}</id>\n    \
+       <link href=\"{}\"/>\n    \
+       <published>{}</published>\n    \
+       <updated>{}</updated>\n  \
+       </entry>\n",
+      escape_xml(title),
+      url,
+      url,
+      timestamp(&entry.created_at),
+      timestamp(&entry.updated_at),
+    ));
+  }
+  xml.push_str("</feed>\n");
+  xml
+}
+
+fn page_url(site_url: &str, publish_name: &str) -> String {
+  format!("{}/{}", site_url.trim_end_matches('/'), publish_name)
+}
+
+fn timestamp(time: &DateTime<Utc>) -> String {
+  time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn escape_xml(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&apos;"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::TimeZone;
+  use uuid::Uuid;
+
+  fn entry(publish_name: &str, title: Option<&str>) -> AFPublishedSiteEntryRow {
+    AFPublishedSiteEntryRow {
+      view_id: Uuid::new_v4(),
+      publish_name: publish_name.to_string(),
+      title: title.map(|title| title.to_string()),
+      created_at: Utc.with_ymd_and_hms(2024, 9, 1, 8, 0, 0).unwrap(),
+      updated_at: Utc.with_ymd_and_hms(2024, 10, 1, 8, 0, 0).unwrap(),
+    }
+  }
+
+  #[test]
+  fn published_sitemap_test() {
+    let xml = sitemap_xml("https://appflowy.com/ns/", &[entry("page-1", None)]);
+    assert!(xml.contains("<loc>https://appflowy.com/ns/page-1</loc>"));
+    assert!(xml.contains("<lastmod>2024-10-01T08:00:00Z</lastmod>"));
+  }
+
+  #[test]
+  fn published_feed_test() {
+    let xml = atom_feed_xml(
+      "ns",
+      "https://appflowy.com/ns",
+      &[entry("page-1", Some("Q&A <notes>")), entry("page-2", None)],
+    );
+    assert!(xml.contains("<title>Q&amp;A &lt;notes&gt;</title>"));
+    assert!(xml.contains("<title>page-2</title>"));
+    assert!(xml.contains("<id>https://appflowy.com/ns/page-1</id>"));
+    assert!(xml.contains("<published>2024-09-01T08:00:00Z</published>"));
+    assert!(xml.contains("<updated>2024-10-01T08:00:00Z</updated>"));
+  }
+}
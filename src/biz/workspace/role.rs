@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::ops::DerefMut;
+
+use access_control::workspace::WorkspaceAccessControl;
+use anyhow::Context;
+use app_error::AppError;
+use database::pg_row::AFWorkspaceRoleRow;
+use database::workspace::select_workspace_member;
+use database::workspace_role::{
+  delete_workspace_role as delete_workspace_role_row, insert_workspace_role, select_workspace_role,
+  select_workspace_role_member_uids, select_workspace_roles, update_workspace_member_custom_role,
+  update_workspace_role as update_workspace_role_row,
+};
+use database_entity::dto::WorkspaceCapability;
+use shared_entity::dto::workspace_dto::{
+  CreateWorkspaceRoleParams, UpdateWorkspaceRoleParams, WorkspaceRole,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const MAX_ROLE_NAME_LEN: usize = 100;
+
+pub async fn create_workspace_role(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  params: CreateWorkspaceRoleParams,
+) -> Result<WorkspaceRole, AppError> {
+  let name = check_role_name(&params.name)?;
+  let capabilities = capability_names(&params.capabilities);
+  let row = insert_workspace_role(pg_pool, workspace_id, name, &capabilities)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordAlreadyExists(format!("The workspace already has a role named {}", name))
+    })?;
+  Ok(workspace_role_from_row(row, vec![]))
+}
+
+/// Returns the roles of the workspace with the members they are assigned to, ordered by creation
+/// time.
+pub async fn list_workspace_roles(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<WorkspaceRole>, AppError> {
+  let rows = select_workspace_roles(pg_pool, workspace_id).await?;
+  let role_ids: Vec<Uuid> = rows.iter().map(|row| row.role_id).collect();
+  let mut member_uids: HashMap<Uuid, Vec<i64>> = HashMap::new();
+  for (role_id, uid) in select_workspace_role_member_uids(pg_pool, &role_ids).await? {
+    member_uids.entry(role_id).or_default().push(uid);
+  }
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| {
+        let uids = member_uids.remove(&row.role_id).unwrap_or_default();
+        workspace_role_from_row(row, uids)
+      })
+      .collect(),
+  )
+}
+
+/// Rename the role and replace its capabilities. The members the role is assigned to hold the
+/// new capabilities right away.
+pub async fn update_workspace_role(
+  pg_pool: &PgPool,
+  workspace_access_control: &impl WorkspaceAccessControl,
+  workspace_id: &Uuid,
+  role_id: &Uuid,
+  params: UpdateWorkspaceRoleParams,
+) -> Result<WorkspaceRole, AppError> {
+  let name = check_role_name(&params.name)?;
+  let capabilities = capability_names(&params.capabilities);
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to update workspace role")?;
+  check_workspace_role_exists(txn.deref_mut(), workspace_id, role_id).await?;
+  let row = update_workspace_role_row(txn.deref_mut(), workspace_id, role_id, name, &capabilities)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordAlreadyExists(format!("The workspace already has a role named {}", name))
+    })?;
+  let member_uids: Vec<i64> = select_workspace_role_member_uids(txn.deref_mut(), &[*role_id])
+    .await?
+    .into_iter()
+    .map(|(_, uid)| uid)
+    .collect();
+  txn
+    .commit()
+    .await
+    .context("fail to commit the transaction to update workspace role")?;
+
+  let role = workspace_role_from_row(row, member_uids);
+  for uid in &role.member_uids {
+    workspace_access_control
+      .update_capabilities(uid, workspace_id, &role.capabilities)
+      .await?;
+  }
+  Ok(role)
+}
+
+/// Delete the role. The members it was assigned to lose the capabilities it granted.
+pub async fn delete_workspace_role(
+  pg_pool: &PgPool,
+  workspace_access_control: &impl WorkspaceAccessControl,
+  workspace_id: &Uuid,
+  role_id: &Uuid,
+) -> Result<(), AppError> {
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to delete workspace role")?;
+  let member_uids = select_workspace_role_member_uids(txn.deref_mut(), &[*role_id]).await?;
+  if !delete_workspace_role_row(txn.deref_mut(), workspace_id, role_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Role {} does not exist in workspace {}",
+      role_id, workspace_id
+    )));
+  }
+  txn
+    .commit()
+    .await
+    .context("fail to commit the transaction to delete workspace role")?;
+
+  for (_, uid) in member_uids {
+    workspace_access_control
+      .update_capabilities(&uid, workspace_id, &[])
+      .await?;
+  }
+  Ok(())
+}
+
+/// Assign the role to a member of the workspace, replacing the custom role it held before.
+pub async fn assign_workspace_role(
+  pg_pool: &PgPool,
+  workspace_access_control: &impl WorkspaceAccessControl,
+  workspace_id: &Uuid,
+  role_id: &Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  let role = check_workspace_role_exists(pg_pool, workspace_id, role_id).await?;
+  match select_workspace_member(pg_pool, &uid, workspace_id).await {
+    Ok(_) => {},
+    Err(AppError::RecordNotFound(_)) => {
+      return Err(AppError::InvalidRequest(format!(
+        "User {} is not a member of workspace {}",
+        uid, workspace_id
+      )))
+    },
+    Err(err) => return Err(err),
+  }
+  update_workspace_member_custom_role(pg_pool, workspace_id, uid, Some(role_id)).await?;
+  workspace_access_control
+    .update_capabilities(
+      &uid,
+      workspace_id,
+      &capabilities_from_names(&role.capabilities),
+    )
+    .await?;
+  Ok(())
+}
+
+/// Remove the role from the member, which is left with the capabilities of its [AFRole] only.
+///
+/// [AFRole]: database_entity::dto::AFRole
+pub async fn unassign_workspace_role(
+  pg_pool: &PgPool,
+  workspace_access_control: &impl WorkspaceAccessControl,
+  workspace_id: &Uuid,
+  role_id: &Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  check_workspace_role_exists(pg_pool, workspace_id, role_id).await?;
+  let assigned = select_workspace_role_member_uids(pg_pool, &[*role_id])
+    .await?
+    .into_iter()
+    .any(|(_, member_uid)| member_uid == uid);
+  if !assigned {
+    return Err(AppError::RecordNotFound(format!(
+      "Role {} is not assigned to user {}",
+      role_id, uid
+    )));
+  }
+  update_workspace_member_custom_role(pg_pool, workspace_id, uid, None).await?;
+  workspace_access_control
+    .update_capabilities(&uid, workspace_id, &[])
+    .await?;
+  Ok(())
+}
+
+/// Returns the capabilities the user holds in the workspace, through its role or its custom role.
+pub async fn get_member_capabilities(
+  workspace_access_control: &impl WorkspaceAccessControl,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<Vec<WorkspaceCapability>, AppError> {
+  let workspace_id = workspace_id.to_string();
+  let mut capabilities = vec![];
+  for capability in WorkspaceCapability::ALL {
+    if workspace_access_control
+      .enforce_capability(&uid, &workspace_id, capability)
+      .await?
+    {
+      capabilities.push(capability);
+    }
+  }
+  Ok(capabilities)
+}
+
+async fn check_workspace_role_exists<'a, E: sqlx::Executor<'a, Database = sqlx::Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  role_id: &Uuid,
+) -> Result<AFWorkspaceRoleRow, AppError> {
+  select_workspace_role(executor, workspace_id, role_id)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!(
+        "Role {} does not exist in workspace {}",
+        role_id, workspace_id
+      ))
+    })
+}
+
+fn check_role_name(name: &str) -> Result<&str, AppError> {
+  let name = name.trim();
+  if name.is_empty() || name.chars().count() > MAX_ROLE_NAME_LEN {
+    return Err(AppError::InvalidRequest(format!(
+      "The role name must be between 1 and {} characters",
+      MAX_ROLE_NAME_LEN
+    )));
+  }
+  Ok(name)
+}
+
+/// The capabilities are stored by name, each of them once, in the order of
+/// [WorkspaceCapability::ALL].
+fn capability_names(capabilities: &[WorkspaceCapability]) -> Vec<String> {
+  WorkspaceCapability::ALL
+    .iter()
+    .filter(|capability| capabilities.contains(capability))
+    .map(|capability| capability.as_str().to_string())
+    .collect()
+}
+
+fn capabilities_from_names(names: &[String]) -> Vec<WorkspaceCapability> {
+  names
+    .iter()
+    .filter_map(|name| WorkspaceCapability::from_name(name))
+    .collect()
+}
+
+fn workspace_role_from_row(row: AFWorkspaceRoleRow, member_uids: Vec<i64>) -> WorkspaceRole {
+  WorkspaceRole {
+    role_id: row.role_id,
+    workspace_id: row.workspace_id,
+    name: row.name,
+    capabilities: capabilities_from_names(&row.capabilities),
+    member_uids,
+    created_at: row.created_at,
+  }
+}
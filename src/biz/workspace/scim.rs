@@ -0,0 +1,785 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use access_control::workspace::WorkspaceAccessControl;
+use app_error::AppError;
+use database::pg_row::{AFScimGroupRow, AFScimUserRow};
+use database::scim::{
+  delete_scim_group, delete_scim_group_members, delete_scim_user, delete_workspace_member_by_uid,
+  insert_scim_group, insert_scim_group_members, select_scim_group, select_scim_group_member_uids,
+  select_scim_groups, select_scim_uids_by_uuids, select_scim_user, select_scim_user_group_role_ids,
+  select_scim_users, update_scim_group, update_scim_group_role, upsert_scim_user,
+  upsert_workspace_member_role,
+};
+use database::user::{select_uid_from_uuid, update_user};
+use database::workspace::select_workspace;
+use database_entity::dto::AFRole;
+use gotrue::params::AdminUserParams;
+use serde_json::{json, Value};
+use shared_entity::dto::scim_dto::{
+  ScimEmail, ScimGroup, ScimListResponse, ScimMeta, ScimName, ScimPatchRequest, ScimReference,
+  ScimUser, SCIM_GROUP_SCHEMA, SCIM_LIST_RESPONSE_SCHEMA, SCIM_USER_SCHEMA,
+};
+use shared_entity::dto::workspace_dto::{ScimGroupRole, WebhookEvent};
+use uuid::Uuid;
+
+use crate::biz::user::user_verify::create_user_if_not_exists;
+use crate::biz::workspace::webhook::enqueue_webhook_event_or_log;
+use crate::state::AppState;
+
+const DEFAULT_SCIM_PAGE_SIZE: usize = 100;
+
+pub async fn create_scim_user(
+  state: &AppState,
+  workspace_id: &Uuid,
+  user: ScimUser,
+) -> Result<ScimUser, AppError> {
+  let email = scim_user_email(&user)?;
+  if !select_scim_users(&state.pg_pool, workspace_id, Some(&email), None)
+    .await?
+    .is_empty()
+  {
+    return Err(AppError::RecordAlreadyExists(format!(
+      "User {} is already provisioned",
+      email
+    )));
+  }
+  let name = scim_user_display_name(&user).unwrap_or_else(|| email.clone());
+
+  // The user may already have an account, from signing up or from another workspace
+  let admin_token = state.gotrue_admin.token().await?;
+  let existing_user = state
+    .gotrue_client
+    .admin_list_user(&admin_token, Some(&email))
+    .await?
+    .users
+    .into_iter()
+    .find(|existing_user| existing_user.email.eq_ignore_ascii_case(&email));
+  let gotrue_user = match existing_user {
+    Some(existing_user) => existing_user,
+    None => {
+      state
+        .gotrue_client
+        .admin_add_user(
+          &admin_token,
+          &AdminUserParams {
+            email: email.clone(),
+            email_confirm: true,
+            user_metadata: BTreeMap::from([("name".to_string(), json!(name))]),
+            ..Default::default()
+          },
+        )
+        .await?
+    },
+  };
+  let user_uuid = Uuid::from_str(&gotrue_user.id).map_err(|err| AppError::Internal(err.into()))?;
+  create_user_if_not_exists(state, &user_uuid, &gotrue_user.email, &name).await?;
+  let uid = select_uid_from_uuid(&state.pg_pool, &user_uuid).await?;
+
+  upsert_scim_user(
+    &state.pg_pool,
+    workspace_id,
+    uid,
+    user.external_id.as_deref(),
+    user.active,
+  )
+  .await?;
+  sync_scim_member(state, workspace_id, uid, user.active).await?;
+  get_scim_user(state, workspace_id, &user_uuid).await
+}
+
+pub async fn get_scim_user(
+  state: &AppState,
+  workspace_id: &Uuid,
+  user_uuid: &Uuid,
+) -> Result<ScimUser, AppError> {
+  let row = select_scim_user(&state.pg_pool, workspace_id, user_uuid)
+    .await?
+    .ok_or_else(|| scim_user_not_found(user_uuid))?;
+  Ok(scim_user_from_row(row))
+}
+
+pub async fn list_scim_users(
+  state: &AppState,
+  workspace_id: &Uuid,
+  filter: Option<&str>,
+  start_index: Option<usize>,
+  count: Option<usize>,
+) -> Result<ScimListResponse<ScimUser>, AppError> {
+  let (mut email, mut external_id) = (None, None);
+  if let Some(filter) = filter {
+    let (attribute, value) = parse_eq_filter(filter)?;
+    match attribute.to_ascii_lowercase().as_str() {
+      "username" => email = Some(value),
+      "externalid" => external_id = Some(value),
+      _ => {
+        return Err(AppError::InvalidRequest(format!(
+          "Filtering users by {} is not supported",
+          attribute
+        )))
+      },
+    }
+  }
+  let rows = select_scim_users(
+    &state.pg_pool,
+    workspace_id,
+    email.as_deref(),
+    external_id.as_deref(),
+  )
+  .await?;
+  let users = rows.into_iter().map(scim_user_from_row).collect();
+  Ok(scim_list_response(users, start_index, count))
+}
+
+/// Replace the attributes of the user. The user name, which is the email of the user, can not be
+/// changed.
+pub async fn replace_scim_user(
+  state: &AppState,
+  workspace_id: &Uuid,
+  user_uuid: &Uuid,
+  user: ScimUser,
+) -> Result<ScimUser, AppError> {
+  let row = select_scim_user(&state.pg_pool, workspace_id, user_uuid)
+    .await?
+    .ok_or_else(|| scim_user_not_found(user_uuid))?;
+  if let Some(name) = scim_user_display_name(&user) {
+    if name != row.name {
+      update_user(&state.pg_pool, user_uuid, Some(name), None, None).await?;
+    }
+  }
+  upsert_scim_user(
+    &state.pg_pool,
+    workspace_id,
+    row.uid,
+    user.external_id.as_deref(),
+    user.active,
+  )
+  .await?;
+  sync_scim_member(state, workspace_id, row.uid, user.active).await?;
+  get_scim_user(state, workspace_id, user_uuid).await
+}
+
+pub async fn patch_scim_user(
+  state: &AppState,
+  workspace_id: &Uuid,
+  user_uuid: &Uuid,
+  patch: ScimPatchRequest,
+) -> Result<ScimUser, AppError> {
+  let mut user = get_scim_user(state, workspace_id, user_uuid).await?;
+  // The display name is derived from the name parts when only those are patched
+  let (mut display_name, mut name) = (None, ScimName::default());
+  for (op, path, value) in flatten_patch_operations(patch)? {
+    match (op.as_str(), path.to_ascii_lowercase().as_str()) {
+      ("remove", "externalid") => user.external_id = None,
+      (_, "active") => user.active = patch_bool(&value)?,
+      (_, "externalid") => user.external_id = Some(patch_string(&value)?),
+      (_, "displayname") => display_name = Some(patch_string(&value)?),
+      (_, "name.formatted") => name.formatted = Some(patch_string(&value)?),
+      (_, "name.givenname") => name.given_name = Some(patch_string(&value)?),
+      (_, "name.familyname") => name.family_name = Some(patch_string(&value)?),
+      // The user name and the emails are managed by the authentication of the user
+      (_, "username") | (_, "emails") => {},
+      (_, path) => {
+        return Err(AppError::InvalidRequest(format!(
+          "Patching the user attribute {} is not supported",
+          path
+        )))
+      },
+    }
+  }
+  if display_name.is_some()
+    || name.formatted.is_some()
+    || name.given_name.is_some()
+    || name.family_name.is_some()
+  {
+    user.display_name = display_name;
+    user.name = Some(name);
+  }
+  replace_scim_user(state, workspace_id, user_uuid, user).await
+}
+
+/// Remove the user from the workspace. The account of the user is kept.
+pub async fn delete_scim_user_from_workspace(
+  state: &AppState,
+  workspace_id: &Uuid,
+  user_uuid: &Uuid,
+) -> Result<(), AppError> {
+  let row = select_scim_user(&state.pg_pool, workspace_id, user_uuid)
+    .await?
+    .ok_or_else(|| scim_user_not_found(user_uuid))?;
+  sync_scim_member(state, workspace_id, row.uid, false).await?;
+  delete_scim_user(&state.pg_pool, workspace_id, row.uid).await
+}
+
+pub async fn create_scim_group(
+  state: &AppState,
+  workspace_id: &Uuid,
+  group: ScimGroup,
+) -> Result<ScimGroup, AppError> {
+  let display_name = group.display_name.trim();
+  if display_name.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "The display name of a group cannot be empty".to_string(),
+    ));
+  }
+  if !select_scim_groups(&state.pg_pool, workspace_id, Some(display_name))
+    .await?
+    .is_empty()
+  {
+    return Err(AppError::RecordAlreadyExists(format!(
+      "Group {} already exists",
+      display_name
+    )));
+  }
+  let group_id = insert_scim_group(
+    &state.pg_pool,
+    workspace_id,
+    display_name,
+    group.external_id.as_deref(),
+  )
+  .await?;
+  let uids = scim_member_uids(state, workspace_id, &group.members).await?;
+  insert_scim_group_members(&state.pg_pool, &group_id, &uids).await?;
+  sync_scim_members(state, workspace_id, &uids).await?;
+  get_scim_group(state, workspace_id, &group_id).await
+}
+
+pub async fn get_scim_group(
+  state: &AppState,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+) -> Result<ScimGroup, AppError> {
+  let row = select_scim_group(&state.pg_pool, workspace_id, group_id)
+    .await?
+    .ok_or_else(|| scim_group_not_found(group_id))?;
+  Ok(scim_group_from_row(row))
+}
+
+pub async fn list_scim_groups(
+  state: &AppState,
+  workspace_id: &Uuid,
+  filter: Option<&str>,
+  start_index: Option<usize>,
+  count: Option<usize>,
+) -> Result<ScimListResponse<ScimGroup>, AppError> {
+  let display_name = match filter {
+    Some(filter) => {
+      let (attribute, value) = parse_eq_filter(filter)?;
+      if !attribute.eq_ignore_ascii_case("displayName") {
+        return Err(AppError::InvalidRequest(format!(
+          "Filtering groups by {} is not supported",
+          attribute
+        )));
+      }
+      Some(value)
+    },
+    None => None,
+  };
+  let rows = select_scim_groups(&state.pg_pool, workspace_id, display_name.as_deref()).await?;
+  let groups = rows.into_iter().map(scim_group_from_row).collect();
+  Ok(scim_list_response(groups, start_index, count))
+}
+
+pub async fn replace_scim_group(
+  state: &AppState,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+  group: ScimGroup,
+) -> Result<ScimGroup, AppError> {
+  select_scim_group(&state.pg_pool, workspace_id, group_id)
+    .await?
+    .ok_or_else(|| scim_group_not_found(group_id))?;
+  update_scim_group(
+    &state.pg_pool,
+    group_id,
+    group.display_name.trim(),
+    group.external_id.as_deref(),
+  )
+  .await?;
+  let uids = scim_member_uids(state, workspace_id, &group.members).await?;
+  set_scim_group_members(state, workspace_id, group_id, &uids).await?;
+  get_scim_group(state, workspace_id, group_id).await
+}
+
+pub async fn patch_scim_group(
+  state: &AppState,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+  patch: ScimPatchRequest,
+) -> Result<ScimGroup, AppError> {
+  let row = select_scim_group(&state.pg_pool, workspace_id, group_id)
+    .await?
+    .ok_or_else(|| scim_group_not_found(group_id))?;
+  let (mut display_name, mut external_id) = (row.display_name, row.external_id);
+  let mut member_uids = select_scim_group_member_uids(&state.pg_pool, group_id).await?;
+  for (op, path, value) in flatten_patch_operations(patch)? {
+    let lower_path = path.to_ascii_lowercase();
+    match (op.as_str(), lower_path.as_str()) {
+      ("remove", "externalid") => external_id = None,
+      (_, "externalid") => external_id = Some(patch_string(&value)?),
+      (_, "displayname") => display_name = patch_string(&value)?,
+      ("add", "members") => {
+        let references = patch_references(&value)?;
+        for uid in scim_member_uids(state, workspace_id, &references).await? {
+          if !member_uids.contains(&uid) {
+            member_uids.push(uid);
+          }
+        }
+      },
+      ("replace", "members") => {
+        member_uids = scim_member_uids(state, workspace_id, &patch_references(&value)?).await?;
+      },
+      ("remove", "members") => {
+        if value.is_null() {
+          member_uids.clear();
+        } else {
+          let removed = scim_member_uids(state, workspace_id, &patch_references(&value)?).await?;
+          member_uids.retain(|uid| !removed.contains(uid));
+        }
+      },
+      // `members[value eq "<id>"]`
+      ("remove", _) if lower_path.starts_with("members[") => {
+        let filter = path["members[".len()..].trim_end_matches(']');
+        let (_, value) = parse_eq_filter(filter)?;
+        let reference = ScimReference {
+          value,
+          display: None,
+        };
+        let removed = scim_member_uids(state, workspace_id, &[reference]).await?;
+        member_uids.retain(|uid| !removed.contains(uid));
+      },
+      (_, path) => {
+        return Err(AppError::InvalidRequest(format!(
+          "Patching the group attribute {} is not supported",
+          path
+        )))
+      },
+    }
+  }
+  update_scim_group(
+    &state.pg_pool,
+    group_id,
+    display_name.trim(),
+    external_id.as_deref(),
+  )
+  .await?;
+  set_scim_group_members(state, workspace_id, group_id, &member_uids).await?;
+  get_scim_group(state, workspace_id, group_id).await
+}
+
+pub async fn delete_scim_group_from_workspace(
+  state: &AppState,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+) -> Result<(), AppError> {
+  select_scim_group(&state.pg_pool, workspace_id, group_id)
+    .await?
+    .ok_or_else(|| scim_group_not_found(group_id))?;
+  let uids = select_scim_group_member_uids(&state.pg_pool, group_id).await?;
+  delete_scim_group(&state.pg_pool, group_id).await?;
+  sync_scim_members(state, workspace_id, &uids).await
+}
+
+/// Returns the SCIM groups of the workspace with the role they are mapped to.
+pub async fn list_scim_group_roles(
+  state: &AppState,
+  workspace_id: &Uuid,
+) -> Result<Vec<ScimGroupRole>, AppError> {
+  let rows = select_scim_groups(&state.pg_pool, workspace_id, None).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| ScimGroupRole {
+        group_id: row.group_id,
+        display_name: row.display_name,
+        role: row.role_id.map(AFRole::from),
+        member_count: row.member_uuids.len(),
+      })
+      .collect(),
+  )
+}
+
+/// Map the group to the role, and update the role of its members.
+pub async fn update_scim_group_role_of_workspace(
+  state: &AppState,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+  role: Option<AFRole>,
+) -> Result<(), AppError> {
+  let role_id = role.map(i32::from);
+  if !update_scim_group_role(&state.pg_pool, workspace_id, group_id, role_id).await? {
+    return Err(scim_group_not_found(group_id));
+  }
+  let uids = select_scim_group_member_uids(&state.pg_pool, group_id).await?;
+  sync_scim_members(state, workspace_id, &uids).await
+}
+
+async fn set_scim_group_members(
+  state: &AppState,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+  uids: &[i64],
+) -> Result<(), AppError> {
+  let mut affected_uids = select_scim_group_member_uids(&state.pg_pool, group_id).await?;
+  delete_scim_group_members(&state.pg_pool, group_id, None).await?;
+  insert_scim_group_members(&state.pg_pool, group_id, uids).await?;
+  affected_uids.extend_from_slice(uids);
+  affected_uids.sort_unstable();
+  affected_uids.dedup();
+  sync_scim_members(state, workspace_id, &affected_uids).await
+}
+
+/// Update the membership of the provisioned users after their groups changed.
+async fn sync_scim_members(
+  state: &AppState,
+  workspace_id: &Uuid,
+  uids: &[i64],
+) -> Result<(), AppError> {
+  if uids.is_empty() {
+    return Ok(());
+  }
+  let rows = select_scim_users(&state.pg_pool, workspace_id, None, None).await?;
+  for row in rows.into_iter().filter(|row| uids.contains(&row.uid)) {
+    sync_scim_member(state, workspace_id, row.uid, row.active).await?;
+  }
+  Ok(())
+}
+
+/// An active user is a member of the workspace, with the highest role of its mapped groups, or
+/// the Member role. An inactive user is not a member. The owner of the workspace is left alone.
+async fn sync_scim_member(
+  state: &AppState,
+  workspace_id: &Uuid,
+  uid: i64,
+  active: bool,
+) -> Result<(), AppError> {
+  let workspace = select_workspace(&state.pg_pool, workspace_id).await?;
+  if workspace.owner_uid == Some(uid) {
+    return Ok(());
+  }
+
+  if active {
+    let role = select_scim_user_group_role_ids(&state.pg_pool, workspace_id, uid)
+      .await?
+      .into_iter()
+      .map(AFRole::from)
+      .max()
+      .unwrap_or(AFRole::Member);
+    let is_added =
+      upsert_workspace_member_role(&state.pg_pool, workspace_id, uid, role.clone().into()).await?;
+    state
+      .workspace_access_control
+      .insert_role(&uid, workspace_id, role.clone())
+      .await?;
+    if is_added {
+      enqueue_webhook_event_or_log(
+        &state.pg_pool,
+        workspace_id,
+        WebhookEvent::MemberAdded,
+        json!({ "uid": uid, "role": role }),
+      )
+      .await;
+    }
+  } else if delete_workspace_member_by_uid(&state.pg_pool, workspace_id, uid).await? {
+    state
+      .workspace_access_control
+      .remove_user_from_workspace(&uid, workspace_id)
+      .await?;
+    enqueue_webhook_event_or_log(
+      &state.pg_pool,
+      workspace_id,
+      WebhookEvent::MemberRemoved,
+      json!({ "uid": uid }),
+    )
+    .await;
+  }
+  Ok(())
+}
+
+/// Returns the uid of the provisioned users the references point to. The references to unknown
+/// users are rejected.
+async fn scim_member_uids(
+  state: &AppState,
+  workspace_id: &Uuid,
+  references: &[ScimReference],
+) -> Result<Vec<i64>, AppError> {
+  let uuids = references
+    .iter()
+    .map(|reference| {
+      Uuid::from_str(&reference.value)
+        .map_err(|_| AppError::InvalidRequest(format!("Invalid user id: {}", reference.value)))
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+  let uids = select_scim_uids_by_uuids(&state.pg_pool, workspace_id, &uuids).await?;
+  if uids.len() != uuids.len() {
+    return Err(AppError::InvalidRequest(
+      "A member of the group is not a provisioned user".to_string(),
+    ));
+  }
+  Ok(uids)
+}
+
+/// Turn the operations into `(op, path, value)`, with `op` in lower case. An operation without a
+/// path, whose value holds the attributes to change, is turned into one operation per attribute.
+fn flatten_patch_operations(
+  patch: ScimPatchRequest,
+) -> Result<Vec<(String, String, Value)>, AppError> {
+  let mut operations = vec![];
+  for operation in patch.operations {
+    let op = operation.op.to_ascii_lowercase();
+    if !matches!(op.as_str(), "add" | "remove" | "replace") {
+      return Err(AppError::InvalidRequest(format!(
+        "Unknown patch operation: {}",
+        operation.op
+      )));
+    }
+    let value = operation.value.unwrap_or(Value::Null);
+    match operation.path {
+      Some(path) => operations.push((op, path, value)),
+      None => {
+        let attributes = match value {
+          Value::Object(attributes) => attributes,
+          _ => {
+            return Err(AppError::InvalidRequest(
+              "A patch operation without a path must have an object value".to_string(),
+            ))
+          },
+        };
+        for (attribute, value) in attributes {
+          match value {
+            Value::Object(sub_attributes) if attribute == "name" => {
+              for (sub_attribute, value) in sub_attributes {
+                operations.push((op.clone(), format!("name.{}", sub_attribute), value));
+              }
+            },
+            value => operations.push((op.clone(), attribute, value)),
+          }
+        }
+      },
+    }
+  }
+  Ok(operations)
+}
+
+/// Some identity providers send booleans as strings.
+fn patch_bool(value: &Value) -> Result<bool, AppError> {
+  match value {
+    Value::Bool(value) => Ok(*value),
+    Value::String(value) if value.eq_ignore_ascii_case("true") => Ok(true),
+    Value::String(value) if value.eq_ignore_ascii_case("false") => Ok(false),
+    _ => Err(AppError::InvalidRequest(format!(
+      "Expected a boolean, got {}",
+      value
+    ))),
+  }
+}
+
+fn patch_string(value: &Value) -> Result<String, AppError> {
+  value
+    .as_str()
+    .map(str::to_string)
+    .ok_or_else(|| AppError::InvalidRequest(format!("Expected a string, got {}", value)))
+}
+
+fn patch_references(value: &Value) -> Result<Vec<ScimReference>, AppError> {
+  serde_json::from_value(value.clone())
+    .map_err(|err| AppError::InvalidRequest(format!("Invalid members: {}", err)))
+}
+
+/// Parse a filter of the form `<attribute> eq "<value>"`.
+fn parse_eq_filter(filter: &str) -> Result<(String, String), AppError> {
+  let invalid_filter = || {
+    AppError::InvalidRequest(format!(
+      "Unsupported filter: {}, only `<attribute> eq \"<value>\"` is supported",
+      filter
+    ))
+  };
+  let mut parts = filter.trim().splitn(3, ' ');
+  let attribute = parts.next().ok_or_else(invalid_filter)?;
+  let operator = parts.next().ok_or_else(invalid_filter)?;
+  let value = parts.next().ok_or_else(invalid_filter)?.trim();
+  if !operator.eq_ignore_ascii_case("eq") || value.len() < 2 {
+    return Err(invalid_filter());
+  }
+  let value = value
+    .strip_prefix('"')
+    .and_then(|value| value.strip_suffix('"'))
+    .ok_or_else(invalid_filter)?;
+  Ok((attribute.to_string(), value.to_string()))
+}
+
+fn scim_list_response<T>(
+  resources: Vec<T>,
+  start_index: Option<usize>,
+  count: Option<usize>,
+) -> ScimListResponse<T> {
+  let total_results = resources.len();
+  let start_index = start_index.unwrap_or(1).max(1);
+  let count = count.unwrap_or(DEFAULT_SCIM_PAGE_SIZE);
+  let resources: Vec<T> = resources
+    .into_iter()
+    .skip(start_index - 1)
+    .take(count)
+    .collect();
+  ScimListResponse {
+    schemas: vec![SCIM_LIST_RESPONSE_SCHEMA.to_string()],
+    total_results,
+    start_index,
+    items_per_page: resources.len(),
+    resources,
+  }
+}
+
+/// The email of the user is its user name, or its primary email when the user name is not an
+/// email.
+fn scim_user_email(user: &ScimUser) -> Result<String, AppError> {
+  let email = if user.user_name.contains('@') {
+    Some(user.user_name.clone())
+  } else {
+    user
+      .emails
+      .iter()
+      .find(|email| email.primary)
+      .or(user.emails.first())
+      .map(|email| email.value.clone())
+  };
+  email
+    .map(|email| email.trim().to_lowercase())
+    .filter(|email| email.contains('@'))
+    .ok_or_else(|| AppError::InvalidRequest("The user has no email".to_string()))
+}
+
+fn scim_user_display_name(user: &ScimUser) -> Option<String> {
+  let name = user.name.as_ref();
+  user
+    .display_name
+    .clone()
+    .or_else(|| name.and_then(|name| name.formatted.clone()))
+    .or_else(|| {
+      let given_name = name.and_then(|name| name.given_name.as_deref());
+      let family_name = name.and_then(|name| name.family_name.as_deref());
+      let full_name = [given_name, family_name]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+      (!full_name.is_empty()).then_some(full_name)
+    })
+    .filter(|name| !name.trim().is_empty())
+}
+
+fn scim_user_from_row(row: AFScimUserRow) -> ScimUser {
+  ScimUser {
+    schemas: vec![SCIM_USER_SCHEMA.to_string()],
+    id: Some(row.uuid.to_string()),
+    external_id: row.external_id,
+    user_name: row.email.clone(),
+    name: Some(ScimName {
+      formatted: Some(row.name.clone()),
+      ..Default::default()
+    }),
+    display_name: Some(row.name),
+    emails: vec![ScimEmail {
+      value: row.email,
+      primary: true,
+      type_: Some("work".to_string()),
+    }],
+    active: row.active,
+    groups: row
+      .group_ids
+      .into_iter()
+      .map(|group_id| ScimReference {
+        value: group_id.to_string(),
+        display: None,
+      })
+      .collect(),
+    meta: Some(ScimMeta {
+      resource_type: "User".to_string(),
+      created: row.created_at,
+      last_modified: row.updated_at,
+    }),
+  }
+}
+
+fn scim_group_from_row(row: AFScimGroupRow) -> ScimGroup {
+  ScimGroup {
+    schemas: vec![SCIM_GROUP_SCHEMA.to_string()],
+    id: Some(row.group_id.to_string()),
+    external_id: row.external_id,
+    display_name: row.display_name,
+    members: row
+      .member_uuids
+      .into_iter()
+      .map(|uuid| ScimReference {
+        value: uuid.to_string(),
+        display: None,
+      })
+      .collect(),
+    meta: Some(ScimMeta {
+      resource_type: "Group".to_string(),
+      created: row.created_at,
+      last_modified: row.updated_at,
+    }),
+  }
+}
+
+fn scim_user_not_found(user_uuid: &Uuid) -> AppError {
+  AppError::RecordNotFound(format!("User {} is not provisioned", user_uuid))
+}
+
+fn scim_group_not_found(group_id: &Uuid) -> AppError {
+  AppError::RecordNotFound(format!("Group {} does not exist", group_id))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use shared_entity::dto::scim_dto::ScimPatchOperation;
+
+  #[test]
+  fn parse_eq_filter_test() {
+    assert_eq!(
+      parse_eq_filter(r#"userName eq "alice@example.com""#).unwrap(),
+      ("userName".to_string(), "alice@example.com".to_string())
+    );
+    assert_eq!(
+      parse_eq_filter(r#"displayName EQ "Engineering team""#).unwrap(),
+      ("displayName".to_string(), "Engineering team".to_string())
+    );
+    assert!(parse_eq_filter(r#"userName sw "alice""#).is_err());
+    assert!(parse_eq_filter("userName eq alice").is_err());
+  }
+
+  #[test]
+  fn flatten_patch_operations_test() {
+    let patch = ScimPatchRequest {
+      schemas: vec![],
+      operations: vec![
+        ScimPatchOperation {
+          op: "Replace".to_string(),
+          path: None,
+          value: Some(json!({ "active": false, "name": { "givenName": "Alice" } })),
+        },
+        ScimPatchOperation {
+          op: "add".to_string(),
+          path: Some("externalId".to_string()),
+          value: Some(json!("00u1")),
+        },
+      ],
+    };
+    let operations = flatten_patch_operations(patch).unwrap();
+    assert_eq!(
+      operations,
+      vec![
+        ("replace".to_string(), "active".to_string(), json!(false)),
+        (
+          "replace".to_string(),
+          "name.givenName".to_string(),
+          json!("Alice")
+        ),
+        ("add".to_string(), "externalId".to_string(), json!("00u1")),
+      ]
+    );
+    assert!(patch_bool(&json!("False")).is_ok_and(|active| !active));
+  }
+}
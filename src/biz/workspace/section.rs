@@ -0,0 +1,147 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::Context;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab_folder::Folder;
+use database::collab::GetCollabOrigin;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::biz::collab::ops::get_latest_collab_folder;
+
+use super::publish_dup::broadcast_update;
+use super::trash::{edit_folder, save_folder};
+
+/// The number of views kept in the recent section of a user. The views visited the longest time
+/// ago are dropped first.
+const MAX_RECENT_VIEWS: usize = 100;
+
+/// Record a visit of the view by the user, which moves it to the top of the recent section of
+/// the user. The sections are stored in the folder, so they roam across the devices of the user.
+pub async fn record_view_visit(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: String,
+) -> Result<(), AppError> {
+  let folder = user_folder_with_view(&collab_storage, uid, workspace_id, &view_id).await?;
+  let mut recent_ids: Vec<String> = folder
+    .get_my_recent_sections()
+    .into_iter()
+    .map(|item| item.id)
+    .collect();
+  // The view is removed before being added again, so that its visit time is updated
+  let was_recent = recent_ids.contains(&view_id);
+  recent_ids.retain(|id| *id != view_id);
+  // The oldest visits come first
+  let overflow = recent_ids.len().saturating_sub(MAX_RECENT_VIEWS - 1);
+  let mut removed_ids: Vec<String> = recent_ids.into_iter().take(overflow).collect();
+  if was_recent {
+    removed_ids.push(view_id.clone());
+  }
+  update_user_sections(
+    &collab_storage,
+    pg_pool,
+    uid,
+    workspace_id,
+    folder,
+    |folder| {
+      if !removed_ids.is_empty() {
+        folder.delete_recent_view_ids(removed_ids);
+      }
+      folder.add_recent_view_ids(vec![view_id]);
+    },
+  )
+  .await
+}
+
+/// Add the view to, or remove it from, the favorite section of the user.
+pub async fn set_view_favorite(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: String,
+  is_favorite: bool,
+) -> Result<(), AppError> {
+  let folder = user_folder_with_view(&collab_storage, uid, workspace_id, &view_id).await?;
+  let favorite_ids: HashSet<String> = folder
+    .get_my_favorite_sections()
+    .into_iter()
+    .map(|item| item.id)
+    .collect();
+  if favorite_ids.contains(&view_id) == is_favorite {
+    return Ok(());
+  }
+  update_user_sections(
+    &collab_storage,
+    pg_pool,
+    uid,
+    workspace_id,
+    folder,
+    |folder| {
+      if is_favorite {
+        folder.add_favorite_view_ids(vec![view_id]);
+      } else {
+        folder.delete_favorite_view_ids(vec![view_id]);
+      }
+    },
+  )
+  .await
+}
+
+/// Returns the folder opened as the user, whose sections are the ones of the user, once checked
+/// that the view belongs to it.
+async fn user_folder_with_view(
+  collab_storage: &Arc<CollabAccessControlStorage>,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &str,
+) -> Result<Folder, AppError> {
+  let workspace_id_str = workspace_id.to_string();
+  let folder = get_latest_collab_folder(
+    collab_storage.clone(),
+    GetCollabOrigin::User { uid },
+    &workspace_id_str,
+  )
+  .await?;
+  if view_id == workspace_id_str || folder.get_view(view_id).is_none() {
+    return Err(AppError::RecordNotFound(format!(
+      "View {} does not exist in workspace {}",
+      view_id, workspace_id
+    )));
+  }
+  Ok(folder)
+}
+
+async fn update_user_sections(
+  collab_storage: &Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  folder: Folder,
+  edit: impl FnOnce(&mut Folder),
+) -> Result<(), AppError> {
+  let workspace_id_str = workspace_id.to_string();
+  let (encoded_update, encoded_folder) = edit_folder(folder, edit).await?;
+  let mut transaction = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to update the folder sections")?;
+  save_folder(
+    collab_storage,
+    &mut transaction,
+    &workspace_id_str,
+    uid,
+    encoded_folder,
+  )
+  .await?;
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to update the folder sections")?;
+  broadcast_update(collab_storage, &workspace_id_str, encoded_update).await
+}
@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+use std::ops::DerefMut;
+use std::str::FromStr;
+
+use access_control::workspace::WorkspaceAccessControl;
+use anyhow::anyhow;
+use app_error::AppError;
+use authentication::jwt::ApiKeyPrincipal;
+use chrono::{Duration, Utc};
+use database::pg_row::{AFApiKeyRow, AFServiceAccountRow};
+use database::service_account::{
+  insert_api_key, insert_service_account, revoke_api_key, revoke_service_account_api_keys,
+  select_api_key_principal, select_service_account, select_service_account_exists_by_name,
+  select_service_accounts, select_workspace_api_keys, update_api_key_last_used_at,
+};
+use database::user::select_web_user_from_uid;
+use gotrue::params::{AdminDeleteUserParams, AdminUserParams};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use shared_entity::dto::workspace_dto::{
+  ApiKey, ApiKeyScope, CreateServiceAccountParams, ServiceAccount, ServiceAccountApiKey,
+};
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::state::GoTrueAdmin;
+
+/// Every API key starts with this prefix, which tells it apart from a GoTrue token.
+pub const API_KEY_PREFIX: &str = "afk_";
+const API_KEY_SECRET_LEN: usize = 40;
+/// The number of characters of a key that are kept to tell the keys apart.
+const API_KEY_DISPLAY_LEN: usize = 12;
+const SERVICE_ACCOUNT_PASSWORD_LEN: usize = 48;
+/// The users that back the service accounts get an address under this reserved domain, so no
+/// email is ever delivered to them.
+const SERVICE_ACCOUNT_EMAIL_DOMAIN: &str = "service-account.invalid";
+const MAX_SERVICE_ACCOUNTS_PER_WORKSPACE: usize = 20;
+const MAX_SERVICE_ACCOUNT_NAME_LEN: usize = 64;
+
+/// Create the service account, and the first key of the service account.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_service_account(
+  pg_pool: &PgPool,
+  gotrue_client: &gotrue::api::Client,
+  gotrue_admin: &GoTrueAdmin,
+  workspace_access_control: &impl WorkspaceAccessControl,
+  creator_uid: i64,
+  service_account_uid: i64,
+  workspace_id: &Uuid,
+  params: CreateServiceAccountParams,
+) -> Result<ServiceAccountApiKey, AppError> {
+  let name = params.name.trim();
+  if name.is_empty() || name.len() > MAX_SERVICE_ACCOUNT_NAME_LEN {
+    return Err(AppError::InvalidRequest(format!(
+      "The name of a service account must be between 1 and {} characters",
+      MAX_SERVICE_ACCOUNT_NAME_LEN
+    )));
+  }
+  if select_service_account_exists_by_name(pg_pool, workspace_id, name).await? {
+    return Err(AppError::RecordAlreadyExists(format!(
+      "Service account {} already exists in workspace {}",
+      name, workspace_id
+    )));
+  }
+  if select_service_accounts(pg_pool, workspace_id).await?.len()
+    >= MAX_SERVICE_ACCOUNTS_PER_WORKSPACE
+  {
+    return Err(AppError::InvalidRequest(format!(
+      "A workspace can have at most {} service accounts",
+      MAX_SERVICE_ACCOUNTS_PER_WORKSPACE
+    )));
+  }
+
+  // The user of the service account is never signed in, its password is thrown away
+  let admin_token = gotrue_admin.token().await?;
+  let user = gotrue_client
+    .admin_add_user(
+      &admin_token,
+      &AdminUserParams {
+        email: format!("{}@{}", Uuid::new_v4(), SERVICE_ACCOUNT_EMAIL_DOMAIN),
+        password: Some(generate_secret(SERVICE_ACCOUNT_PASSWORD_LEN)),
+        email_confirm: true,
+        ..Default::default()
+      },
+    )
+    .await?;
+  let user_uuid = Uuid::from_str(&user.id).map_err(|err| AppError::Internal(err.into()))?;
+
+  let role = params.scope.role();
+  let result = async {
+    let mut txn = pg_pool.begin().await?;
+    let row = insert_service_account(
+      txn.deref_mut(),
+      workspace_id,
+      service_account_uid,
+      &user_uuid,
+      &user.email,
+      name,
+      params.scope.as_str(),
+      role.clone().into(),
+      creator_uid,
+    )
+    .await?;
+    let (api_key, key_row) = issue_api_key(txn.deref_mut(), &row.service_account_id).await?;
+    txn.commit().await?;
+    workspace_access_control
+      .insert_role(&row.uid, workspace_id, role)
+      .await?;
+    Ok::<_, AppError>((row, api_key, key_row))
+  }
+  .await;
+
+  match result {
+    Ok((row, api_key, key_row)) => {
+      let api_key_id = key_row.api_key_id;
+      Ok(ServiceAccountApiKey {
+        service_account: service_account_from_row(row, vec![key_row])?,
+        api_key_id,
+        api_key,
+      })
+    },
+    Err(err) => {
+      // Without its service account, the user would be left over
+      if let Err(err) = gotrue_client
+        .admin_delete_user(
+          &admin_token,
+          &user.id,
+          &AdminDeleteUserParams {
+            should_soft_delete: false,
+          },
+        )
+        .await
+      {
+        warn!("Failed to delete the user of a service account: {}", err);
+      }
+      Err(err)
+    },
+  }
+}
+
+pub async fn list_service_accounts(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<ServiceAccount>, AppError> {
+  let rows = select_service_accounts(pg_pool, workspace_id).await?;
+  let mut keys_by_account: HashMap<Uuid, Vec<AFApiKeyRow>> = HashMap::new();
+  for key in select_workspace_api_keys(pg_pool, workspace_id).await? {
+    keys_by_account
+      .entry(key.service_account_id)
+      .or_default()
+      .push(key);
+  }
+  rows
+    .into_iter()
+    .map(|row| {
+      let keys = keys_by_account
+        .remove(&row.service_account_id)
+        .unwrap_or_default();
+      service_account_from_row(row, keys)
+    })
+    .collect()
+}
+
+/// Revoke the active keys of the service account and issue a new one.
+pub async fn rotate_service_account_api_key(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  service_account_id: &Uuid,
+) -> Result<ServiceAccountApiKey, AppError> {
+  let mut txn = pg_pool.begin().await?;
+  let row = select_service_account(txn.deref_mut(), workspace_id, service_account_id)
+    .await?
+    .ok_or_else(|| service_account_not_found(workspace_id, service_account_id))?;
+  revoke_service_account_api_keys(txn.deref_mut(), service_account_id).await?;
+  let (api_key, key_row) = issue_api_key(txn.deref_mut(), service_account_id).await?;
+  txn.commit().await?;
+
+  let api_key_id = key_row.api_key_id;
+  let service_account = list_service_accounts(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .find(|account| account.service_account_id == row.service_account_id)
+    .ok_or_else(|| service_account_not_found(workspace_id, service_account_id))?;
+  Ok(ServiceAccountApiKey {
+    service_account,
+    api_key_id,
+    api_key,
+  })
+}
+
+pub async fn revoke_service_account_api_key(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  service_account_id: &Uuid,
+  api_key_id: &Uuid,
+) -> Result<(), AppError> {
+  select_service_account(pg_pool, workspace_id, service_account_id)
+    .await?
+    .ok_or_else(|| service_account_not_found(workspace_id, service_account_id))?;
+  if !revoke_api_key(pg_pool, service_account_id, api_key_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Active api key {} does not exist for service account {}",
+      api_key_id, service_account_id
+    )));
+  }
+  Ok(())
+}
+
+/// Delete the service account together with its keys and the user that backs it.
+pub async fn delete_service_account(
+  pg_pool: &PgPool,
+  gotrue_client: &gotrue::api::Client,
+  gotrue_admin: &GoTrueAdmin,
+  workspace_access_control: &impl WorkspaceAccessControl,
+  workspace_id: &Uuid,
+  service_account_id: &Uuid,
+) -> Result<(), AppError> {
+  let row = select_service_account(pg_pool, workspace_id, service_account_id)
+    .await?
+    .ok_or_else(|| service_account_not_found(workspace_id, service_account_id))?;
+  // Stop the keys from authenticating before the user is gone
+  revoke_service_account_api_keys(pg_pool, service_account_id).await?;
+  workspace_access_control
+    .remove_user_from_workspace(&row.uid, workspace_id)
+    .await?;
+
+  // Deleting the user deletes its af_user row, and with it the service account and its keys
+  let user = select_web_user_from_uid(pg_pool, row.uid).await?;
+  let admin_token = gotrue_admin.token().await?;
+  gotrue_client
+    .admin_delete_user(
+      &admin_token,
+      &user.uuid.to_string(),
+      &AdminDeleteUserParams {
+        should_soft_delete: false,
+      },
+    )
+    .await?;
+  Ok(())
+}
+
+/// Returns the principal the API key authenticates, and the scope of the key.
+pub async fn authenticate_api_key(
+  pg_pool: &PgPool,
+  api_key: &str,
+) -> Result<(ApiKeyPrincipal, ApiKeyScope), AppError> {
+  let invalid_key = || AppError::UserUnAuthorized("Invalid api key".to_string());
+  if !api_key.starts_with(API_KEY_PREFIX) {
+    return Err(invalid_key());
+  }
+  let row = select_api_key_principal(pg_pool, &hash_api_key(api_key))
+    .await?
+    .ok_or_else(invalid_key)?;
+  let scope = ApiKeyScope::from_str(&row.scope).map_err(|err| AppError::Internal(anyhow!(err)))?;
+
+  // Only record the last use once a minute, to not write on every request
+  let is_stale = row.last_used_at.map_or(true, |last_used_at| {
+    Utc::now() - last_used_at > Duration::minutes(1)
+  });
+  if is_stale {
+    if let Err(err) = update_api_key_last_used_at(pg_pool, &row.api_key_id).await {
+      warn!(
+        "Failed to record the use of api key {}: {}",
+        row.api_key_id, err
+      );
+    }
+  }
+
+  Ok((
+    ApiKeyPrincipal {
+      user_uuid: row.user_uuid,
+      workspace_id: row.workspace_id,
+    },
+    scope,
+  ))
+}
+
+/// Returns the new key and its row. Only the hash of the key is stored.
+async fn issue_api_key<'a, E: sqlx::Executor<'a, Database = sqlx::Postgres>>(
+  executor: E,
+  service_account_id: &Uuid,
+) -> Result<(String, AFApiKeyRow), AppError> {
+  let api_key = format!("{}{}", API_KEY_PREFIX, generate_secret(API_KEY_SECRET_LEN));
+  let row = insert_api_key(
+    executor,
+    service_account_id,
+    &hash_api_key(&api_key),
+    &api_key[..API_KEY_DISPLAY_LEN],
+  )
+  .await?;
+  Ok((api_key, row))
+}
+
+fn hash_api_key(api_key: &str) -> String {
+  openssl::sha::sha256(api_key.as_bytes())
+    .iter()
+    .map(|b| format!("{:02x}", b))
+    .collect()
+}
+
+fn generate_secret(len: usize) -> String {
+  rand::thread_rng()
+    .sample_iter(&Alphanumeric)
+    .take(len)
+    .map(char::from)
+    .collect()
+}
+
+fn service_account_from_row(
+  row: AFServiceAccountRow,
+  keys: Vec<AFApiKeyRow>,
+) -> Result<ServiceAccount, AppError> {
+  let scope = ApiKeyScope::from_str(&row.scope).map_err(|err| AppError::Internal(anyhow!(err)))?;
+  Ok(ServiceAccount {
+    service_account_id: row.service_account_id,
+    workspace_id: row.workspace_id,
+    uid: row.uid,
+    name: row.name,
+    scope,
+    created_at: row.created_at,
+    api_keys: keys
+      .into_iter()
+      .map(|key| ApiKey {
+        api_key_id: key.api_key_id,
+        key_prefix: key.key_prefix,
+        created_at: key.created_at,
+        last_used_at: key.last_used_at,
+        revoked_at: key.revoked_at,
+      })
+      .collect(),
+  })
+}
+
+fn service_account_not_found(workspace_id: &Uuid, service_account_id: &Uuid) -> AppError {
+  AppError::RecordNotFound(format!(
+    "Service account {} does not exist in workspace {}",
+    service_account_id, workspace_id
+  ))
+}
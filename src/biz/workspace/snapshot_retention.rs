@@ -0,0 +1,276 @@
+use std::collections::HashSet;
+
+use app_error::AppError;
+use chrono::{DateTime, Datelike, Duration, DurationRound, Utc};
+use database::collab::COLLAB_SNAPSHOT_LIMIT;
+use database::pg_row::AFWorkspaceSnapshotRetentionRow;
+use database::snapshot_retention::{
+  delete_collab_snapshots, delete_workspace_snapshot_retention,
+  select_all_workspace_snapshot_retentions, select_workspace_collab_snapshot_times,
+  select_workspace_snapshot_retention, upsert_workspace_snapshot_retention,
+};
+use shared_entity::dto::workspace_dto::{SnapshotRetentionPolicy, WorkspaceSnapshotRetention};
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// The upper bound of each setting of a policy, so that a typo doesn't keep the snapshots forever.
+const MAX_RETENTION_VALUE: i32 = 3650;
+
+/// The number of snapshots deleted in one statement by the cleanup.
+const DELETE_BATCH_SIZE: usize = 1000;
+
+/// The policy of the workspaces that don't override it, which keeps the most recent snapshots of
+/// each collab up to the limit applied when a snapshot is created.
+pub fn default_snapshot_retention_policy() -> SnapshotRetentionPolicy {
+  SnapshotRetentionPolicy {
+    keep_last: COLLAB_SNAPSHOT_LIMIT as i32,
+    keep_days: 0,
+    hourly_days: 0,
+    daily_days: 0,
+    weekly_weeks: 0,
+  }
+}
+
+pub async fn get_workspace_snapshot_retention(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<WorkspaceSnapshotRetention, AppError> {
+  let retention = match select_workspace_snapshot_retention(pg_pool, workspace_id).await? {
+    Some(row) => snapshot_retention_from_row(row),
+    None => WorkspaceSnapshotRetention {
+      workspace_id: *workspace_id,
+      policy: default_snapshot_retention_policy(),
+      is_default: true,
+      updated_at: None,
+    },
+  };
+  Ok(retention)
+}
+
+pub async fn update_workspace_snapshot_retention(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  uid: i64,
+  policy: SnapshotRetentionPolicy,
+) -> Result<WorkspaceSnapshotRetention, AppError> {
+  if policy.keep_last < 1 {
+    return Err(AppError::InvalidRequest(
+      "At least the most recent snapshot must be kept".to_string(),
+    ));
+  }
+  let values = [
+    policy.keep_last,
+    policy.keep_days,
+    policy.hourly_days,
+    policy.daily_days,
+    policy.weekly_weeks,
+  ];
+  if values
+    .iter()
+    .any(|value| !(0..=MAX_RETENTION_VALUE).contains(value))
+  {
+    return Err(AppError::InvalidRequest(format!(
+      "The settings of the snapshot retention policy must be between 0 and {}",
+      MAX_RETENTION_VALUE
+    )));
+  }
+
+  let row = upsert_workspace_snapshot_retention(
+    pg_pool,
+    workspace_id,
+    policy.keep_last,
+    policy.keep_days,
+    policy.hourly_days,
+    policy.daily_days,
+    policy.weekly_weeks,
+    uid,
+  )
+  .await?;
+  Ok(snapshot_retention_from_row(row))
+}
+
+/// Removes the policy of the workspace, which falls back to the default of the server.
+pub async fn remove_workspace_snapshot_retention(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  delete_workspace_snapshot_retention(pg_pool, workspace_id).await
+}
+
+/// Periodically delete the snapshots that the policies of the workspaces no longer keep, see
+/// [enforce_snapshot_retention]. A zero `period` disables the task.
+pub fn spawn_enforce_snapshot_retention(pg_pool: PgPool, period: std::time::Duration) {
+  if period.is_zero() {
+    return;
+  }
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(period);
+    loop {
+      interval.tick().await;
+      if let Err(err) = enforce_snapshot_retention(&pg_pool).await {
+        warn!(
+          "Failed to enforce the snapshot retention policies: {:?}",
+          err
+        );
+      }
+    }
+  });
+}
+
+/// Deletes the snapshots that the policies of the workspaces no longer keep. The workspaces
+/// without a policy are left out, as their snapshots are limited when created.
+pub async fn enforce_snapshot_retention(pg_pool: &PgPool) -> Result<(), AppError> {
+  let now = Utc::now();
+  for row in select_all_workspace_snapshot_retentions(pg_pool).await? {
+    let workspace_id = row.workspace_id;
+    let policy = snapshot_retention_from_row(row).policy;
+    let snapshots = select_workspace_collab_snapshot_times(pg_pool, &workspace_id).await?;
+    let expired = expired_snapshots(&policy, &snapshots, now);
+    let mut deleted = 0;
+    for chunk in expired.chunks(DELETE_BATCH_SIZE) {
+      deleted += delete_collab_snapshots(pg_pool, chunk).await?;
+    }
+    if deleted > 0 {
+      info!(
+        "Deleted {} snapshots of workspace {} per its retention policy",
+        deleted, workspace_id
+      );
+    }
+  }
+  Ok(())
+}
+
+/// Returns the ids of the snapshots the policy doesn't keep. The snapshots are ordered by object
+/// id, the most recent snapshot of each collab first, so that the first snapshot met in an hour,
+/// a day or a week is the one kept for it.
+fn expired_snapshots(
+  policy: &SnapshotRetentionPolicy,
+  snapshots: &[(i64, String, DateTime<Utc>)],
+  now: DateTime<Utc>,
+) -> Vec<i64> {
+  let mut expired = vec![];
+  let mut current_oid: Option<&str> = None;
+  let mut index = 0;
+  let mut hours = HashSet::new();
+  let mut days = HashSet::new();
+  let mut weeks = HashSet::new();
+  for (snapshot_id, oid, created_at) in snapshots {
+    if current_oid != Some(oid.as_str()) {
+      current_oid = Some(oid.as_str());
+      index = 0;
+      hours.clear();
+      days.clear();
+      weeks.clear();
+    }
+    let age = now - *created_at;
+    let mut keep =
+      index < policy.keep_last as usize || age < Duration::days(policy.keep_days as i64);
+    if age < Duration::days(policy.hourly_days as i64) {
+      let hour = created_at
+        .duration_trunc(Duration::hours(1))
+        .unwrap_or(*created_at);
+      keep |= hours.insert(hour);
+    }
+    if age < Duration::days(policy.daily_days as i64) {
+      keep |= days.insert(created_at.date_naive());
+    }
+    if age < Duration::weeks(policy.weekly_weeks as i64) {
+      let week = created_at.iso_week();
+      keep |= weeks.insert((week.year(), week.week()));
+    }
+    if !keep {
+      expired.push(*snapshot_id);
+    }
+    index += 1;
+  }
+  expired
+}
+
+fn snapshot_retention_from_row(row: AFWorkspaceSnapshotRetentionRow) -> WorkspaceSnapshotRetention {
+  WorkspaceSnapshotRetention {
+    workspace_id: row.workspace_id,
+    policy: SnapshotRetentionPolicy {
+      keep_last: row.keep_last,
+      keep_days: row.keep_days,
+      hourly_days: row.hourly_days,
+      daily_days: row.daily_days,
+      weekly_weeks: row.weekly_weeks,
+    },
+    is_default: false,
+    updated_at: Some(row.updated_at),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::TimeZone;
+
+  fn policy(keep_last: i32) -> SnapshotRetentionPolicy {
+    SnapshotRetentionPolicy {
+      keep_last,
+      keep_days: 0,
+      hourly_days: 0,
+      daily_days: 0,
+      weekly_weeks: 0,
+    }
+  }
+
+  #[test]
+  fn expired_snapshots_keep_last_test() {
+    let now = Utc.with_ymd_and_hms(2024, 10, 20, 12, 0, 0).unwrap();
+    let snapshots = (0..5)
+      .map(|i| (i, "a".to_string(), now - Duration::days(i)))
+      .chain((5..8).map(|i| (i, "b".to_string(), now - Duration::days(i))))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      expired_snapshots(&policy(2), &snapshots, now),
+      vec![2, 3, 4, 7]
+    );
+
+    let keep_days = SnapshotRetentionPolicy {
+      keep_days: 3,
+      ..policy(1)
+    };
+    assert_eq!(
+      expired_snapshots(&keep_days, &snapshots, now),
+      vec![3, 4, 6, 7]
+    );
+  }
+
+  #[test]
+  fn expired_snapshots_tiers_test() {
+    let now = Utc.with_ymd_and_hms(2024, 10, 20, 12, 0, 0).unwrap();
+    // Two snapshots per hour over the last 3 days, the most recent first.
+    let snapshots = (0..144)
+      .map(|i| (i, "a".to_string(), now - Duration::minutes(30 * i)))
+      .collect::<Vec<_>>();
+
+    // One per hour during the last day: 24 hours, plus the snapshot at `now` starting its hour.
+    let hourly = SnapshotRetentionPolicy {
+      hourly_days: 1,
+      ..policy(1)
+    };
+    let expired = expired_snapshots(&hourly, &snapshots, now);
+    assert_eq!(snapshots.len() - expired.len(), 25);
+
+    // One per day during the last 3 days, at most one per calendar day.
+    let daily = SnapshotRetentionPolicy {
+      daily_days: 3,
+      ..policy(1)
+    };
+    let expired = expired_snapshots(&daily, &snapshots, now);
+    let kept = snapshots
+      .iter()
+      .filter(|(id, _, _)| !expired.contains(id))
+      .map(|(_, _, created_at)| created_at.date_naive())
+      .collect::<Vec<_>>();
+    assert_eq!(kept.len(), 4);
+    assert_eq!(
+      kept.iter().collect::<HashSet<_>>().len(),
+      kept.len(),
+      "a single snapshot is kept per day"
+    );
+  }
+}
@@ -0,0 +1,309 @@
+use std::str::FromStr;
+
+use access_control::workspace::WorkspaceAccessControl;
+use app_error::AppError;
+use database::pg_row::AFWorkspaceSsoRow;
+use database::user::select_uid_from_uuid;
+use database::workspace_sso::{
+  delete_workspace_sso, insert_workspace_member_if_not_exists, select_workspace_sso,
+  select_workspace_sso_by_provider, upsert_workspace_sso,
+};
+use database_entity::dto::AFRole;
+use gotrue::params::{CreateSSOProviderParams, SSOParams};
+use gotrue_entity::gotrue_jwt::GoTrueJWTClaims;
+use serde_json::json;
+use shared_entity::dto::auth_dto::{SsoSignInParams, SsoSignInUrl};
+use shared_entity::dto::workspace_dto::{UpdateWorkspaceSsoParams, WebhookEvent, WorkspaceSso};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::biz::workspace::webhook::enqueue_webhook_event_or_log;
+use crate::state::AppState;
+
+/// The method GoTrue records in the `amr` claim of the sessions that come from a SAML assertion.
+const SAML_AMR_METHOD: &str = "sso/saml";
+
+pub async fn get_workspace_sso(
+  state: &AppState,
+  workspace_id: &Uuid,
+) -> Result<WorkspaceSso, AppError> {
+  let row = select_workspace_sso(&state.pg_pool, workspace_id)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!("workspace {} has no single sign-on", workspace_id))
+    })?;
+  Ok(workspace_sso_from_row(row))
+}
+
+/// Register the identity provider of the workspace in GoTrue, or update it, and bind it to the
+/// workspace. GoTrue fetches and validates the metadata of the provider.
+pub async fn update_workspace_sso(
+  state: &AppState,
+  workspace_id: &Uuid,
+  params: UpdateWorkspaceSsoParams,
+) -> Result<WorkspaceSso, AppError> {
+  let metadata_url = params
+    .metadata_url
+    .map(|url| url.trim().to_string())
+    .filter(|url| !url.is_empty());
+  let metadata_xml = params
+    .metadata_xml
+    .map(|xml| xml.trim().to_string())
+    .filter(|xml| !xml.is_empty());
+  if metadata_url.is_some() == metadata_xml.is_some() {
+    return Err(AppError::InvalidRequest(
+      "Either the url or the content of the metadata of the identity provider is required"
+        .to_string(),
+    ));
+  }
+  if let Some(metadata_url) = &metadata_url {
+    if !metadata_url.starts_with("https://") {
+      return Err(AppError::InvalidRequest(
+        "The metadata url must use https".to_string(),
+      ));
+    }
+  }
+  if params.default_role == AFRole::Owner {
+    return Err(AppError::InvalidRequest(
+      "The users provisioned by the identity provider cannot be owners".to_string(),
+    ));
+  }
+  let domains = normalize_email_domains(&params.domains)?;
+
+  let provider_params = CreateSSOProviderParams {
+    type_: "saml".to_string(),
+    metadata_url: metadata_url.clone().unwrap_or_default(),
+    metadata_xml: metadata_xml.unwrap_or_default(),
+    domains: domains.clone(),
+    attribute_mapping: json!({
+      "keys": {
+        "name": { "names": ["displayName", "name", "http://schemas.xmlsoap.org/ws/2005/05/identity/claims/name"] },
+        "email": { "names": ["email", "mail", "http://schemas.xmlsoap.org/ws/2005/05/identity/claims/emailaddress"] },
+      }
+    }),
+  };
+  let admin_token = state.gotrue_admin.token().await?;
+  let existing = select_workspace_sso(&state.pg_pool, workspace_id).await?;
+  let provider = match &existing {
+    Some(existing) => {
+      state
+        .gotrue_client
+        .admin_update_sso_provider(
+          &admin_token,
+          &existing.provider_id.to_string(),
+          &provider_params,
+        )
+        .await?
+    },
+    None => {
+      state
+        .gotrue_client
+        .admin_create_sso_providers(&admin_token, &provider_params)
+        .await?
+    },
+  };
+  let provider_id = Uuid::from_str(&provider.id).map_err(|err| AppError::Internal(err.into()))?;
+
+  let row = upsert_workspace_sso(
+    &state.pg_pool,
+    workspace_id,
+    &provider_id,
+    &provider.saml.entity_id,
+    metadata_url.as_deref(),
+    &domains,
+    params.default_role.into(),
+    params.enforced,
+  )
+  .await;
+  let row = match row {
+    Ok(row) => row,
+    Err(err) => {
+      // Don't leave a provider in GoTrue that no workspace knows of
+      if existing.is_none() {
+        let _ = state
+          .gotrue_client
+          .admin_delete_sso_provider(&admin_token, &provider.id)
+          .await;
+      }
+      return Err(err);
+    },
+  };
+  state.sso_enforcer.invalidate(workspace_id);
+  Ok(workspace_sso_from_row(row))
+}
+
+pub async fn remove_workspace_sso(state: &AppState, workspace_id: &Uuid) -> Result<(), AppError> {
+  let row = match select_workspace_sso(&state.pg_pool, workspace_id).await? {
+    Some(row) => row,
+    None => return Ok(()),
+  };
+  let admin_token = state.gotrue_admin.token().await?;
+  state
+    .gotrue_client
+    .admin_delete_sso_provider(&admin_token, &row.provider_id.to_string())
+    .await?;
+  delete_workspace_sso(&state.pg_pool, workspace_id).await?;
+  state.sso_enforcer.invalidate(workspace_id);
+  Ok(())
+}
+
+/// Lowercase the email domains, with or without their leading `@`, and drop the duplicates.
+pub(crate) fn normalize_email_domains(domains: &[String]) -> Result<Vec<String>, AppError> {
+  let mut domains = domains
+    .iter()
+    .map(|domain| domain.trim().trim_start_matches('@').to_lowercase())
+    .filter(|domain| !domain.is_empty())
+    .collect::<Vec<_>>();
+  domains.sort();
+  domains.dedup();
+  if let Some(domain) = domains
+    .iter()
+    .find(|domain| !domain.contains('.') || domain.contains(char::is_whitespace))
+  {
+    return Err(AppError::InvalidRequest(format!(
+      "Invalid email domain: {}",
+      domain
+    )));
+  }
+  Ok(domains)
+}
+
+/// Returns the url of the identity provider of the workspace, or of the domain of the email, the
+/// user signs in with.
+pub async fn sso_sign_in_url(
+  state: &AppState,
+  params: SsoSignInParams,
+) -> Result<SsoSignInUrl, AppError> {
+  let mut sso_params = SSOParams {
+    redirect_to: params.redirect_to,
+    skip_http_redirect: true,
+    ..Default::default()
+  };
+  match (params.workspace_id, params.email) {
+    (Some(workspace_id), _) => {
+      let row = select_workspace_sso(&state.pg_pool, &workspace_id)
+        .await?
+        .ok_or_else(|| {
+          AppError::RecordNotFound(format!("workspace {} has no single sign-on", workspace_id))
+        })?;
+      sso_params.provider_id = Some(row.provider_id.to_string());
+    },
+    (None, Some(email)) => {
+      let domain = email
+        .rsplit_once('@')
+        .map(|(_, domain)| domain.trim().to_lowercase())
+        .filter(|domain| !domain.is_empty())
+        .ok_or_else(|| AppError::InvalidRequest(format!("Invalid email: {}", email)))?;
+      sso_params.domain = Some(domain);
+    },
+    (None, None) => {
+      return Err(AppError::InvalidRequest(
+        "Either the workspace or the email is required".to_string(),
+      ))
+    },
+  }
+  let url = state.gotrue_client.sso(&sso_params).await?;
+  Ok(SsoSignInUrl { url: url.url })
+}
+
+/// Returns the GoTrue provider of the SAML assertion the session comes from, if any. GoTrue only
+/// issues such a session once it validated the signature, the audience and the validity period
+/// of the assertion against the metadata of the provider.
+pub fn saml_provider_id(claims: &GoTrueJWTClaims) -> Option<Uuid> {
+  claims
+    .amr
+    .as_ref()?
+    .iter()
+    .filter(|amr| amr.method == SAML_AMR_METHOD)
+    .find_map(|amr| amr.provider.as_deref())
+    .and_then(|provider| Uuid::from_str(provider).ok())
+}
+
+/// Add the user, signed in through the identity provider of a workspace, to that workspace with
+/// the default role of its single sign-on. Nothing changes for the users that already are
+/// members.
+pub async fn provision_sso_user(
+  state: &AppState,
+  user_uuid: &Uuid,
+  claims: &GoTrueJWTClaims,
+) -> Result<(), AppError> {
+  let provider_id = match saml_provider_id(claims) {
+    Some(provider_id) => provider_id,
+    None => return Ok(()),
+  };
+  let row = match select_workspace_sso_by_provider(&state.pg_pool, &provider_id).await? {
+    Some(row) => row,
+    None => return Ok(()),
+  };
+  let uid = select_uid_from_uuid(&state.pg_pool, user_uuid).await?;
+  let is_added = insert_workspace_member_if_not_exists(
+    &state.pg_pool,
+    &row.workspace_id,
+    uid,
+    row.default_role_id,
+  )
+  .await?;
+  if is_added {
+    let role = AFRole::from(row.default_role_id);
+    info!(
+      "provision user {} into workspace {} through single sign-on",
+      uid, row.workspace_id
+    );
+    state
+      .workspace_access_control
+      .insert_role(&uid, &row.workspace_id, role.clone())
+      .await?;
+    enqueue_webhook_event_or_log(
+      &state.pg_pool,
+      &row.workspace_id,
+      WebhookEvent::MemberAdded,
+      json!({ "uid": uid, "role": role }),
+    )
+    .await;
+  }
+  Ok(())
+}
+
+fn workspace_sso_from_row(row: AFWorkspaceSsoRow) -> WorkspaceSso {
+  WorkspaceSso {
+    workspace_id: row.workspace_id,
+    provider_id: row.provider_id,
+    entity_id: row.entity_id,
+    metadata_url: row.metadata_url,
+    domains: row.domains,
+    default_role: AFRole::from(row.default_role_id),
+    enforced: row.enforced,
+    created_at: row.created_at,
+    updated_at: row.updated_at,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn claims_with_amr(amr: serde_json::Value) -> GoTrueJWTClaims {
+    serde_json::from_value(json!({
+      "email": "alice@example.com",
+      "phone": "",
+      "app_metadata": {},
+      "user_metadata": {},
+      "role": "authenticated",
+      "amr": amr,
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn saml_provider_id_test() {
+    let provider_id = Uuid::new_v4();
+    let claims = claims_with_amr(json!([{ "method": "password", "timestamp": 0 }]));
+    assert_eq!(saml_provider_id(&claims), None);
+
+    let claims = claims_with_amr(json!([
+      { "method": "password", "timestamp": 0 },
+      { "method": SAML_AMR_METHOD, "timestamp": 1, "provider": provider_id.to_string() },
+    ]));
+    assert_eq!(saml_provider_id(&claims), Some(provider_id));
+  }
+}
@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use app_error::AppError;
+use appflowy_collaborate::collab::storage_quota::{storage_quota, StorageQuotaSetting};
+use database::storage_quota::{refresh_workspace_storage_usage, select_current_workspace_storage};
+use shared_entity::dto::workspace_dto::WorkspaceStorageQuota;
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+/// How often the bytes used by the workspaces are counted again.
+const STORAGE_USAGE_REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Returns the quota of the workspace along with the bytes it uses now, which can be a little off
+/// the usage its writes are checked against until the next refresh.
+pub async fn get_workspace_storage_quota(
+  pg_pool: &PgPool,
+  setting: &StorageQuotaSetting,
+  workspace_id: &Uuid,
+) -> Result<WorkspaceStorageQuota, AppError> {
+  let row = select_current_workspace_storage(pg_pool, workspace_id).await?;
+  let (plan, quota_bytes) = storage_quota(setting, &row);
+  Ok(WorkspaceStorageQuota {
+    workspace_id: *workspace_id,
+    plan,
+    collab_bytes: row.collab_bytes,
+    blob_bytes: row.blob_bytes,
+    used_bytes: row.collab_bytes + row.blob_bytes,
+    quota_bytes,
+  })
+}
+
+/// Periodically count again the bytes used by each workspace, which the storage quota is checked
+/// against. Counting them on each write would serialize the writes of a workspace.
+pub fn spawn_refresh_workspace_storage_usage(pg_pool: PgPool) {
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(STORAGE_USAGE_REFRESH_INTERVAL);
+    loop {
+      interval.tick().await;
+      if let Err(err) = refresh_workspace_storage_usage(&pg_pool).await {
+        warn!(
+          "Failed to refresh the storage usage of the workspaces: {:?}",
+          err
+        );
+      }
+    }
+  });
+}
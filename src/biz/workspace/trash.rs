@@ -0,0 +1,362 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ops::DerefMut;
+use std::sync::Arc;
+
+use access_control::collab::CollabAccessControl;
+use anyhow::Context;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use chrono::Utc;
+use collab_entity::CollabType;
+use collab_folder::Folder;
+use database::collab::{CollabStorage, GetCollabOrigin};
+use database::view_trash::{
+  delete_view_trash, insert_view_trash, select_expired_view_trash, select_view_trash,
+};
+use database_entity::dto::CollabParams;
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+use yrs::ReadTxn;
+
+use crate::biz::collab::ops::{delete_object, get_latest_collab_encoded, get_latest_collab_folder};
+
+use super::publish_dup::{broadcast_update, collab_to_bin};
+
+/// The number of expired views purged in one run of [purge_expired_trash].
+const PURGE_BATCH_SIZE: i64 = 100;
+
+/// Move the views to the trash of the user. The views stay in the folder, along with their
+/// children, until they are restored or purged.
+pub async fn move_views_to_trash(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_ids: Vec<String>,
+) -> Result<(), AppError> {
+  if view_ids.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "No view to move to the trash".to_string(),
+    ));
+  }
+  let workspace_id_str = workspace_id.to_string();
+  let folder = get_latest_collab_folder(
+    collab_storage.clone(),
+    GetCollabOrigin::User { uid },
+    &workspace_id_str,
+  )
+  .await?;
+  for view_id in &view_ids {
+    if *view_id == workspace_id_str || folder.get_view(view_id).is_none() {
+      return Err(AppError::RecordNotFound(format!(
+        "View {} does not exist in workspace {}",
+        view_id, workspace_id
+      )));
+    }
+  }
+  let trash_ids: HashSet<String> = folder
+    .get_my_trash_sections()
+    .into_iter()
+    .map(|item| item.id)
+    .collect();
+  let new_trash_ids: Vec<String> = view_ids
+    .iter()
+    .filter(|view_id| !trash_ids.contains(*view_id))
+    .cloned()
+    .collect();
+
+  let mut transaction = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to move views to trash")?;
+  insert_view_trash(transaction.deref_mut(), workspace_id, &view_ids, uid).await?;
+  let encoded_update = if new_trash_ids.is_empty() {
+    None
+  } else {
+    let (encoded_update, encoded_folder) =
+      edit_folder(folder, |folder| folder.add_trash_view_ids(new_trash_ids)).await?;
+    save_folder(
+      &collab_storage,
+      &mut transaction,
+      &workspace_id_str,
+      uid,
+      encoded_folder,
+    )
+    .await?;
+    Some(encoded_update)
+  };
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to move views to trash")?;
+
+  if let Some(encoded_update) = encoded_update {
+    broadcast_update(&collab_storage, &workspace_id_str, encoded_update).await?;
+  }
+  Ok(())
+}
+
+/// Take the views out of the trash, whichever member moved them there.
+pub async fn restore_views_from_trash(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_ids: Vec<String>,
+) -> Result<(), AppError> {
+  if view_ids.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "No view to restore from the trash".to_string(),
+    ));
+  }
+  // The trash of each member is a separate section of the folder, so the views are taken out of
+  // the trash of the member that moved them there. Untracked views are in the trash of the user.
+  let mut view_ids_by_uid: BTreeMap<i64, Vec<String>> = BTreeMap::new();
+  let rows = select_view_trash(pg_pool, workspace_id, &view_ids).await?;
+  for view_id in &view_ids {
+    let deleted_by = rows
+      .iter()
+      .find(|row| row.view_id == *view_id)
+      .map(|row| row.deleted_by)
+      .unwrap_or(uid);
+    view_ids_by_uid
+      .entry(deleted_by)
+      .or_default()
+      .push(view_id.clone());
+  }
+
+  for (deleted_by, view_ids) in view_ids_by_uid {
+    remove_from_trash_section(&collab_storage, pg_pool, workspace_id, deleted_by, view_ids).await?;
+  }
+  Ok(())
+}
+
+/// Delete, for good, the views that have been in the trash for longer than `retention`, along
+/// with their children. Views that were restored by a client are only forgotten.
+pub async fn purge_expired_trash(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  retention: chrono::Duration,
+) -> Result<(), AppError> {
+  let cutoff = Utc::now() - retention;
+  let rows = select_expired_view_trash(pg_pool, cutoff, PURGE_BATCH_SIZE).await?;
+  for row in rows {
+    let result = purge_trash_view(
+      collab_storage.clone(),
+      pg_pool,
+      collab_access_control,
+      &row.workspace_id,
+      row.deleted_by,
+      &row.view_id,
+    )
+    .await;
+    if let Err(err) = result {
+      warn!(
+        "Failed to purge view {} from the trash of workspace {}: {:?}",
+        row.view_id, row.workspace_id, err
+      );
+    }
+  }
+  Ok(())
+}
+
+/// Periodically purge the expired views in the trash, see [purge_expired_trash].
+/// A zero `period` disables the task.
+pub fn spawn_purge_expired_trash(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: PgPool,
+  collab_access_control: impl CollabAccessControl,
+  period: std::time::Duration,
+  retention: chrono::Duration,
+) {
+  if period.is_zero() {
+    return;
+  }
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(period);
+    loop {
+      interval.tick().await;
+      if let Err(err) = purge_expired_trash(
+        collab_storage.clone(),
+        &pg_pool,
+        &collab_access_control,
+        retention,
+      )
+      .await
+      {
+        warn!("Failed to purge expired trash: {:?}", err);
+      }
+    }
+  });
+}
+
+async fn purge_trash_view(
+  collab_storage: Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  collab_access_control: &impl CollabAccessControl,
+  workspace_id: &Uuid,
+  deleted_by: i64,
+  view_id: &str,
+) -> Result<(), AppError> {
+  let folder = trash_owner_folder(&collab_storage, workspace_id, deleted_by).await?;
+  let in_trash = folder
+    .get_my_trash_sections()
+    .iter()
+    .any(|item| item.id == view_id);
+  if !in_trash || folder.get_view(view_id).is_none() {
+    delete_view_trash(pg_pool, workspace_id, &[view_id.to_string()]).await?;
+    return Ok(());
+  }
+
+  // Children are deleted before their parent, so none is left behind as an orphan
+  let mut view_ids = vec![];
+  let mut stack = vec![view_id.to_string()];
+  while let Some(id) = stack.pop() {
+    if let Some(view) = folder.get_view(&id) {
+      stack.extend(view.children.iter().map(|child| child.id.clone()));
+    }
+    view_ids.push(id);
+  }
+  drop(folder);
+
+  remove_from_trash_section(
+    &collab_storage,
+    pg_pool,
+    workspace_id,
+    deleted_by,
+    vec![view_id.to_string()],
+  )
+  .await?;
+  for id in view_ids.iter().rev() {
+    delete_object(
+      collab_storage.clone(),
+      pg_pool,
+      collab_access_control,
+      workspace_id,
+      id,
+    )
+    .await?;
+  }
+  Ok(())
+}
+
+/// Take the views out of the trash section of `deleted_by`, and stop tracking them.
+async fn remove_from_trash_section(
+  collab_storage: &Arc<CollabAccessControlStorage>,
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  deleted_by: i64,
+  view_ids: Vec<String>,
+) -> Result<(), AppError> {
+  let workspace_id_str = workspace_id.to_string();
+  let folder = trash_owner_folder(collab_storage, workspace_id, deleted_by).await?;
+  let trash_ids: HashSet<String> = folder
+    .get_my_trash_sections()
+    .into_iter()
+    .map(|item| item.id)
+    .collect();
+  let removed_ids: Vec<String> = view_ids
+    .iter()
+    .filter(|view_id| trash_ids.contains(*view_id))
+    .cloned()
+    .collect();
+
+  let mut transaction = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to remove views from trash")?;
+  delete_view_trash(transaction.deref_mut(), workspace_id, &view_ids).await?;
+  let encoded_update = if removed_ids.is_empty() {
+    None
+  } else {
+    let (encoded_update, encoded_folder) =
+      edit_folder(folder, |folder| folder.delete_trash_view_ids(removed_ids)).await?;
+    save_folder(
+      collab_storage,
+      &mut transaction,
+      &workspace_id_str,
+      deleted_by,
+      encoded_folder,
+    )
+    .await?;
+    Some(encoded_update)
+  };
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to remove views from trash")?;
+
+  if let Some(encoded_update) = encoded_update {
+    broadcast_update(collab_storage, &workspace_id_str, encoded_update).await?;
+  }
+  Ok(())
+}
+
+/// Returns the folder opened as `deleted_by`, whose trash section is the one that holds the views
+/// the member moved to the trash. The member may have left the workspace since, so the folder is
+/// read on behalf of the server.
+async fn trash_owner_folder(
+  collab_storage: &Arc<CollabAccessControlStorage>,
+  workspace_id: &Uuid,
+  deleted_by: i64,
+) -> Result<Folder, AppError> {
+  let workspace_id = workspace_id.to_string();
+  let encoded_collab = get_latest_collab_encoded(
+    collab_storage.clone(),
+    GetCollabOrigin::Server,
+    &workspace_id,
+    &workspace_id,
+    CollabType::Folder,
+  )
+  .await?;
+  Folder::from_collab_doc_state(
+    deleted_by,
+    collab_folder::CollabOrigin::Server,
+    encoded_collab.into(),
+    &workspace_id,
+    vec![],
+  )
+  .map_err(|e| AppError::Unhandled(e.to_string()))
+}
+
+/// Apply `edit` to the folder, and return the update it made along with the encoded folder. The
+/// trash methods of the folder open their own transaction, so the update is the difference from
+/// the state of the folder before the edit.
+pub(super) async fn edit_folder(
+  mut folder: Folder,
+  edit: impl FnOnce(&mut Folder),
+) -> Result<(Vec<u8>, Vec<u8>), AppError> {
+  let state_vector = folder.collab.transact().state_vector();
+  edit(&mut folder);
+  let encoded_update = folder
+    .collab
+    .transact()
+    .encode_state_as_update_v1(&state_vector);
+  let encoded_folder = collab_to_bin(folder.collab, CollabType::Folder).await?;
+  Ok((encoded_update, encoded_folder))
+}
+
+pub(super) async fn save_folder(
+  collab_storage: &Arc<CollabAccessControlStorage>,
+  transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  workspace_id: &str,
+  uid: i64,
+  encoded_folder: Vec<u8>,
+) -> Result<(), AppError> {
+  collab_storage
+    .insert_new_collab_with_transaction(
+      workspace_id,
+      &uid,
+      CollabParams {
+        object_id: workspace_id.to_string(),
+        encoded_collab_v1: encoded_folder.into(),
+        collab_type: CollabType::Folder,
+        embeddings: None,
+      },
+      transaction,
+    )
+    .await?;
+  Ok(())
+}
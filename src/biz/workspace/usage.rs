@@ -0,0 +1,72 @@
+use app_error::AppError;
+use chrono::{Duration, Utc};
+use database::workspace_usage::select_workspace_usage;
+use database_entity::dto::WorkspaceUsage;
+use redis::AsyncCommands;
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::biz::workspace::ops::check_workspace_owner;
+use crate::state::RedisConnectionManager;
+
+/// The usage of a workspace is aggregated over all its collabs, so it is cached for a while rather
+/// than computed on each request.
+const USAGE_CACHE_TTL_SECS: u64 = 60 * 5;
+const ACTIVE_EDITOR_DAYS: i64 = 30;
+
+/// Returns the activity of the workspace. Only the owner of the workspace can see it.
+pub async fn get_workspace_usage(
+  pg_pool: &PgPool,
+  redis_client: &RedisConnectionManager,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+) -> Result<WorkspaceUsage, AppError> {
+  check_workspace_owner(pg_pool, user_uuid, workspace_id).await?;
+
+  let cache_key = format!("af_workspace_usage:{}", workspace_id);
+  let mut redis_client = redis_client.clone();
+  match redis_client.get::<_, Option<String>>(&cache_key).await {
+    Ok(Some(cached)) => match serde_json::from_str(&cached) {
+      Ok(usage) => return Ok(usage),
+      Err(err) => warn!(
+        "invalid cached usage of workspace {}: {}",
+        workspace_id, err
+      ),
+    },
+    Ok(None) => {},
+    Err(err) => warn!(
+      "failed to read usage of workspace {}: {}",
+      workspace_id, err
+    ),
+  }
+
+  let active_since = Utc::now() - Duration::days(ACTIVE_EDITOR_DAYS);
+  let row = select_workspace_usage(pg_pool, workspace_id, active_since).await?;
+  let usage = WorkspaceUsage {
+    total_document_size: row.total_document_size,
+    document_count: row.document_count,
+    active_editor_count: row.active_editor_count,
+    storage_bytes: row.storage_bytes,
+    publish_count: row.publish_count,
+  };
+
+  match serde_json::to_string(&usage) {
+    Ok(value) => {
+      if let Err(err) = redis_client
+        .set_ex::<_, _, ()>(&cache_key, value, USAGE_CACHE_TTL_SECS)
+        .await
+      {
+        warn!(
+          "failed to cache usage of workspace {}: {}",
+          workspace_id, err
+        );
+      }
+    },
+    Err(err) => warn!(
+      "failed to serialize usage of workspace {}: {}",
+      workspace_id, err
+    ),
+  }
+  Ok(usage)
+}
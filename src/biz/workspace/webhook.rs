@@ -0,0 +1,438 @@
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use chrono::Utc;
+use database::pg_row::{AFDueWebhookDeliveryRow, AFWebhookDeliveryRow, AFWorkspaceWebhookRow};
+use database::webhook::{
+  claim_due_webhook_deliveries, delete_webhook_delivery, delete_workspace_webhook,
+  insert_webhook_deliveries, insert_workspace_webhook, select_webhook_dead_letters,
+  select_workspace_webhook_exists, select_workspace_webhooks, update_webhook_dead_letter_requeued,
+  update_webhook_delivery_failed,
+};
+use futures_util::future::join_all;
+use hyper::client::connect::dns::Name;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::redirect::Policy;
+use serde_json::json;
+use shared_entity::dto::workspace_dto::{
+  CreateWebhookParams, Webhook, WebhookDeadLetter, WebhookEvent,
+};
+use sqlx::PgPool;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::config::config::WebhookSetting;
+
+pub const WEBHOOK_EVENT_HEADER: &str = "X-AppFlowy-Event";
+pub const WEBHOOK_DELIVERY_HEADER: &str = "X-AppFlowy-Delivery";
+/// Carries `sha256=<hex>`, the HMAC-SHA256 of the body keyed with the secret of the webhook.
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-AppFlowy-Signature";
+
+const MAX_WEBHOOKS_PER_WORKSPACE: usize = 20;
+const MIN_WEBHOOK_SECRET_LEN: usize = 16;
+const WEBHOOK_DISPATCH_BATCH_SIZE: i64 = 50;
+const MAX_WEBHOOK_RETRY_DELAY_SECS: i64 = 60 * 60;
+const MAX_WEBHOOK_ERROR_LEN: usize = 512;
+
+pub async fn create_webhook(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  params: CreateWebhookParams,
+) -> Result<Webhook, AppError> {
+  let url = url::Url::parse(&params.url)
+    .map_err(|err| AppError::InvalidRequest(format!("Invalid webhook url: {}", err)))?;
+  if url.scheme() != "http" && url.scheme() != "https" {
+    return Err(AppError::InvalidRequest(
+      "The webhook url must be an http or https url".to_string(),
+    ));
+  }
+  check_webhook_url_host(&url).map_err(AppError::InvalidRequest)?;
+  if params.secret.len() < MIN_WEBHOOK_SECRET_LEN {
+    return Err(AppError::InvalidRequest(format!(
+      "The webhook secret must be at least {} characters",
+      MIN_WEBHOOK_SECRET_LEN
+    )));
+  }
+  if select_workspace_webhooks(pg_pool, workspace_id)
+    .await?
+    .len()
+    >= MAX_WEBHOOKS_PER_WORKSPACE
+  {
+    return Err(AppError::InvalidRequest(format!(
+      "A workspace can have at most {} webhooks",
+      MAX_WEBHOOKS_PER_WORKSPACE
+    )));
+  }
+
+  let mut events: Vec<String> = params
+    .events
+    .iter()
+    .map(|event| event.as_str().to_string())
+    .collect();
+  events.sort();
+  events.dedup();
+  let row =
+    insert_workspace_webhook(pg_pool, workspace_id, url.as_str(), &params.secret, &events).await?;
+  Ok(webhook_from_row(row))
+}
+
+pub async fn list_webhooks(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<Webhook>, AppError> {
+  let rows = select_workspace_webhooks(pg_pool, workspace_id).await?;
+  Ok(rows.into_iter().map(webhook_from_row).collect())
+}
+
+pub async fn delete_webhook(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  webhook_id: &Uuid,
+) -> Result<(), AppError> {
+  if !delete_workspace_webhook(pg_pool, workspace_id, webhook_id).await? {
+    return Err(webhook_not_found(workspace_id, webhook_id));
+  }
+  Ok(())
+}
+
+pub async fn list_webhook_dead_letters(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  webhook_id: &Uuid,
+) -> Result<Vec<WebhookDeadLetter>, AppError> {
+  if !select_workspace_webhook_exists(pg_pool, workspace_id, webhook_id).await? {
+    return Err(webhook_not_found(workspace_id, webhook_id));
+  }
+  let rows = select_webhook_dead_letters(pg_pool, webhook_id).await?;
+  Ok(rows.into_iter().filter_map(dead_letter_from_row).collect())
+}
+
+/// Queue the dead letter to be delivered again.
+pub async fn retry_webhook_dead_letter(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  webhook_id: &Uuid,
+  delivery_id: i64,
+) -> Result<(), AppError> {
+  if !select_workspace_webhook_exists(pg_pool, workspace_id, webhook_id).await? {
+    return Err(webhook_not_found(workspace_id, webhook_id));
+  }
+  if !update_webhook_dead_letter_requeued(pg_pool, webhook_id, delivery_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Webhook {} has no dead letter {}",
+      webhook_id, delivery_id
+    )));
+  }
+  Ok(())
+}
+
+/// Queue the event for every webhook of the workspace that is subscribed to it. The event is
+/// delivered in the background by [spawn_webhook_dispatcher].
+///
+/// The updates of an object that are waiting to be delivered are merged into a single delivery
+/// carrying the latest data, so that a burst of edits does not queue a delivery per edit.
+pub async fn enqueue_webhook_event<'a, E: sqlx::Executor<'a, Database = sqlx::Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  event: WebhookEvent,
+  data: serde_json::Value,
+) -> Result<(), AppError> {
+  let coalesce_key = match event {
+    WebhookEvent::CollabUpdated => data
+      .get("object_id")
+      .and_then(|object_id| object_id.as_str())
+      .map(|object_id| object_id.to_string()),
+    _ => None,
+  };
+  insert_webhook_deliveries(
+    executor,
+    workspace_id,
+    event.as_str(),
+    &data,
+    coalesce_key.as_deref(),
+  )
+  .await?;
+  Ok(())
+}
+
+/// Like [enqueue_webhook_event], for events of operations that have already succeeded. A failure
+/// to queue the event is logged rather than returned.
+pub async fn enqueue_webhook_event_or_log(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  event: WebhookEvent,
+  data: serde_json::Value,
+) {
+  if let Err(err) = enqueue_webhook_event(pg_pool, workspace_id, event, data).await {
+    error!(
+      "Failed to queue webhook event {} of workspace {}: {}",
+      event.as_str(),
+      workspace_id,
+      err
+    );
+  }
+}
+
+pub fn spawn_webhook_dispatcher(pg_pool: PgPool, setting: WebhookSetting) {
+  if setting.dispatch_interval_secs == 0 {
+    return;
+  }
+  // A redirect could lead the request to an address the resolver refused
+  let http_client = match reqwest::Client::builder()
+    .timeout(Duration::from_secs(setting.request_timeout_secs))
+    .redirect(Policy::none())
+    .dns_resolver(Arc::new(PublicIpResolver))
+    .build()
+  {
+    Ok(http_client) => http_client,
+    Err(err) => {
+      error!("Failed to create the webhook http client: {}", err);
+      return;
+    },
+  };
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(Duration::from_secs(setting.dispatch_interval_secs));
+    loop {
+      interval.tick().await;
+      if let Err(err) = dispatch_due_webhook_deliveries(&pg_pool, &http_client, &setting).await {
+        warn!("Failed to dispatch webhook deliveries: {:?}", err);
+      }
+    }
+  });
+}
+
+async fn dispatch_due_webhook_deliveries(
+  pg_pool: &PgPool,
+  http_client: &reqwest::Client,
+  setting: &WebhookSetting,
+) -> Result<(), AppError> {
+  // A claimed delivery must not be due again before the request to deliver it has timed out.
+  let lease_until =
+    Utc::now() + chrono::Duration::seconds(2 * setting.request_timeout_secs as i64 + 1);
+  let deliveries =
+    claim_due_webhook_deliveries(pg_pool, WEBHOOK_DISPATCH_BATCH_SIZE, lease_until).await?;
+  // Deliveries are sent concurrently, so every request ends before its lease does.
+  let results = join_all(deliveries.into_iter().map(|delivery| async move {
+    let delivery_id = delivery.delivery_id;
+    let attempts = delivery.attempts + 1;
+    (
+      delivery_id,
+      attempts,
+      send_webhook_delivery(http_client, delivery).await,
+    )
+  }))
+  .await;
+  for (delivery_id, attempts, result) in results {
+    match result {
+      Ok(()) => delete_webhook_delivery(pg_pool, delivery_id).await?,
+      Err(err) => {
+        let next_attempt_at = (attempts < setting.max_attempts).then(|| {
+          let delay_secs = 2_i64
+            .saturating_pow(attempts as u32)
+            .min(MAX_WEBHOOK_RETRY_DELAY_SECS);
+          Utc::now() + chrono::Duration::seconds(delay_secs)
+        });
+        let err: String = err
+          .to_string()
+          .chars()
+          .take(MAX_WEBHOOK_ERROR_LEN)
+          .collect();
+        update_webhook_delivery_failed(pg_pool, delivery_id, &err, next_attempt_at).await?;
+      },
+    }
+  }
+  Ok(())
+}
+
+async fn send_webhook_delivery(
+  http_client: &reqwest::Client,
+  delivery: AFDueWebhookDeliveryRow,
+) -> Result<(), AppError> {
+  let body = serde_json::to_vec(&json!({
+    "delivery_id": delivery.delivery_id,
+    "event": delivery.event,
+    "workspace_id": delivery.workspace_id,
+    "created_at": delivery.created_at,
+    "data": delivery.payload,
+  }))?;
+  // The hosts given by name are checked by the [PublicIpResolver] when they are resolved
+  let url = url::Url::parse(&delivery.url)
+    .map_err(|err| AppError::InvalidRequest(format!("Invalid webhook url: {}", err)))?;
+  check_webhook_url_host(&url).map_err(AppError::InvalidRequest)?;
+  let signature = sign_webhook_body(&delivery.secret, &body)?;
+  let resp = http_client
+    .post(&delivery.url)
+    .header(reqwest::header::CONTENT_TYPE, "application/json")
+    .header(WEBHOOK_EVENT_HEADER, &delivery.event)
+    .header(WEBHOOK_DELIVERY_HEADER, delivery.delivery_id.to_string())
+    .header(WEBHOOK_SIGNATURE_HEADER, format!("sha256={}", signature))
+    .body(body)
+    .send()
+    .await
+    .map_err(|err| AppError::Internal(anyhow!("Failed to call the webhook: {}", err)))?;
+  if !resp.status().is_success() {
+    return Err(AppError::Internal(anyhow!(
+      "The webhook responded with status {}",
+      resp.status()
+    )));
+  }
+  Ok(())
+}
+
+/// Resolves the hosts of the webhooks, refusing the ones with an address that is not public, so
+/// that a webhook can not reach the internal network of the server. The addresses are checked
+/// when the request is sent, so a host can not be pointed at another address afterwards.
+struct PublicIpResolver;
+
+impl Resolve for PublicIpResolver {
+  fn resolve(&self, name: Name) -> Resolving {
+    Box::pin(resolve_public_host(name))
+  }
+}
+
+async fn resolve_public_host(
+  name: Name,
+) -> Result<Addrs, Box<dyn std::error::Error + Send + Sync>> {
+  let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+  if let Some(addr) = addrs.iter().find(|addr| !is_public_ip(&addr.ip())) {
+    return Err(
+      format!(
+        "The webhook host {} resolves to the non-public address {}",
+        name.as_str(),
+        addr.ip()
+      )
+      .into(),
+    );
+  }
+  Ok(Box::new(addrs.into_iter()))
+}
+
+/// Rejects the urls whose host is an address that is not public. The hosts given by name are
+/// checked by the [PublicIpResolver].
+fn check_webhook_url_host(url: &url::Url) -> Result<(), String> {
+  let ip = match url.host() {
+    Some(url::Host::Ipv4(ip)) => IpAddr::V4(ip),
+    Some(url::Host::Ipv6(ip)) => IpAddr::V6(ip),
+    Some(url::Host::Domain(_)) => return Ok(()),
+    None => return Err("The webhook url has no host".to_string()),
+  };
+  if !is_public_ip(&ip) {
+    return Err(format!("The webhook url can not target the address {}", ip));
+  }
+  Ok(())
+}
+
+/// Returns false for the loopback, private, link-local, shared and otherwise reserved addresses,
+/// which include the metadata endpoints of the cloud providers.
+fn is_public_ip(ip: &IpAddr) -> bool {
+  match ip {
+    IpAddr::V4(ip) => {
+      let [a, b, ..] = ip.octets();
+      !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // shared address space, 100.64.0.0/10
+        || (a == 100 && (64..128).contains(&b))
+        // reserved, 240.0.0.0/4
+        || a >= 240)
+    },
+    IpAddr::V6(ip) => {
+      if let Some(ip) = ip.to_ipv4_mapped() {
+        return is_public_ip(&IpAddr::V4(ip));
+      }
+      let first_segment = ip.segments()[0];
+      !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // unique local, fc00::/7
+        || (first_segment & 0xfe00) == 0xfc00
+        // link-local, fe80::/10
+        || (first_segment & 0xffc0) == 0xfe80)
+    },
+  }
+}
+
+/// Returns the hex encoded HMAC-SHA256 of the body, keyed with the secret.
+fn sign_webhook_body(secret: &str, body: &[u8]) -> Result<String, AppError> {
+  let sign = || -> Result<Vec<u8>, openssl::error::ErrorStack> {
+    let key = PKey::hmac(secret.as_bytes())?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(body)?;
+    signer.sign_to_vec()
+  };
+  let signature = sign().map_err(|err| AppError::Internal(err.into()))?;
+  Ok(signature.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn webhook_not_found(workspace_id: &Uuid, webhook_id: &Uuid) -> AppError {
+  AppError::RecordNotFound(format!(
+    "Webhook {} does not exist in workspace {}",
+    webhook_id, workspace_id
+  ))
+}
+
+fn webhook_from_row(row: AFWorkspaceWebhookRow) -> Webhook {
+  Webhook {
+    webhook_id: row.webhook_id,
+    workspace_id: row.workspace_id,
+    url: row.url,
+    events: row
+      .events
+      .iter()
+      .filter_map(|event| WebhookEvent::from_str(event).ok())
+      .collect(),
+    created_at: row.created_at,
+  }
+}
+
+fn dead_letter_from_row(row: AFWebhookDeliveryRow) -> Option<WebhookDeadLetter> {
+  Some(WebhookDeadLetter {
+    delivery_id: row.delivery_id,
+    webhook_id: row.webhook_id,
+    event: WebhookEvent::from_str(&row.event).ok()?,
+    payload: row.payload,
+    attempts: row.attempts,
+    last_error: row.last_error,
+    created_at: row.created_at,
+    dead_at: row.dead_at?,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn is_public_ip_test() {
+    for ip in [
+      "127.0.0.1",
+      "10.1.2.3",
+      "172.16.0.1",
+      "192.168.1.1",
+      "169.254.169.254",
+      "100.64.0.1",
+      "0.0.0.0",
+      "::1",
+      "fd00:ec2::254",
+      "fe80::1",
+      "::ffff:127.0.0.1",
+    ] {
+      assert!(!is_public_ip(&ip.parse().unwrap()), "{}", ip);
+    }
+    for ip in ["8.8.8.8", "1.1.1.1", "2606:4700:4700::1111"] {
+      assert!(is_public_ip(&ip.parse().unwrap()), "{}", ip);
+    }
+  }
+}
@@ -1,7 +1,11 @@
 use std::fmt::Display;
+use std::net::IpAddr;
 use std::str::FromStr;
 
 use anyhow::Context;
+use appflowy_ai_client::llm::LLMProvider;
+use appflowy_collaborate::collab::storage_quota::StorageQuotaSetting;
+use collab_stream::connection::RedisSetting;
 use secrecy::{ExposeSecret, Secret};
 use semver::Version;
 use serde::Deserialize;
@@ -16,13 +20,23 @@ pub struct Config {
   pub gotrue: GoTrueSetting,
   pub application: ApplicationSetting,
   pub websocket: WebsocketSetting,
+  /// Only used by the session store, which does not support sentinel or cluster mode.
   pub redis_uri: Secret<String>,
+  pub redis: RedisSetting,
   pub s3: S3Setting,
   pub appflowy_ai: AppFlowyAISetting,
+  pub llm: LLMSetting,
+  pub ai_quota: AIQuotaSetting,
   pub grpc_history: GrpcHistorySetting,
+  pub grpc: GrpcSetting,
   pub collab: CollabSetting,
   pub published_collab: PublishedCollabSetting,
+  pub webhook: WebhookSetting,
   pub mailer: MailerSetting,
+  pub notification: NotificationSetting,
+  pub rate_limit: RateLimitSetting,
+  pub storage_quota: StorageQuotaSetting,
+  pub account_deletion: AccountDeletionSetting,
   pub apple_oauth: AppleOAuthSetting,
   pub appflowy_web_url: Option<String>,
 }
@@ -35,6 +49,45 @@ pub struct MailerSetting {
   pub smtp_password: Secret<String>,
 }
 
+#[derive(Clone, Debug)]
+pub struct NotificationSetting {
+  /// How often, in seconds, the pending notifications are emailed. `0` disables the emails.
+  pub email_interval_secs: u64,
+  /// The minimum time, in seconds, between two digests emailed to the same user.
+  pub digest_interval_secs: u64,
+  /// The public url of the server, which the unsubscribe link of the emails points to.
+  pub public_url: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct AccountDeletionSetting {
+  /// How often, in seconds, the accounts whose grace period has ended are deleted for good. `0`
+  /// disables the deletion.
+  pub purge_interval_secs: u64,
+  /// How long, in days, an account marked for deletion can still be restored.
+  pub grace_period_days: i64,
+}
+
+impl AccountDeletionSetting {
+  pub fn grace_period(&self) -> chrono::Duration {
+    chrono::Duration::days(self.grace_period_days)
+  }
+}
+
+/// The default rate limits. A workspace can override them, see `af_workspace_rate_limit`.
+#[derive(Clone, Debug)]
+pub struct RateLimitSetting {
+  pub enabled: bool,
+  /// The number of requests per minute of each signed in user.
+  pub user_requests_per_minute: u32,
+  /// The number of requests per minute of each ip address, for the requests without a user.
+  pub anonymous_requests_per_minute: u32,
+  /// The number of requests per minute to a workspace, all members together.
+  pub workspace_requests_per_minute: u32,
+  /// The number of concurrent websocket connections of each user.
+  pub ws_connections_per_user: u32,
+}
+
 #[derive(serde::Deserialize, Clone, Debug)]
 pub struct AppleOAuthSetting {
   pub client_id: String,
@@ -54,6 +107,8 @@ pub struct S3Setting {
   pub secret_key: Secret<String>,
   pub bucket: String,
   pub region: String,
+  /// How long the presigned urls of the upload parts are valid.
+  pub presigned_url_expiration_secs: u64,
 }
 
 #[derive(serde::Deserialize, Clone, Debug)]
@@ -81,6 +136,35 @@ impl AppFlowyAISetting {
   }
 }
 
+/// The large language model the server calls itself, rather than through the AppFlowy AI
+/// service, such as for the summary of a document. Those features are unavailable when no
+/// provider is set.
+#[derive(Clone, Debug)]
+pub struct LLMSetting {
+  pub provider: Option<LLMProvider>,
+  /// Defaults to the url of the provider.
+  pub base_url: Option<String>,
+  pub api_key: Secret<String>,
+  pub model: String,
+}
+
+/// The AI quotas of the workspaces in a calendar month, by plan. The workspaces on the AI Local
+/// plan run the models on the devices, so the server gives them the quota of the free plan.
+#[derive(Clone, Debug)]
+pub struct AIQuotaSetting {
+  pub free_plan: AIPlanQuota,
+  pub pro_plan: AIPlanQuota,
+  pub team_plan: AIPlanQuota,
+  pub ai_max_plan: AIPlanQuota,
+}
+
+/// `0` leaves the workspaces unlimited.
+#[derive(Clone, Debug)]
+pub struct AIPlanQuota {
+  pub monthly_requests: i64,
+  pub monthly_tokens: i64,
+}
+
 // We are using 127.0.0.1 as our host in address, we are instructing our
 // application to only accept connections coming from the same machine. However,
 // request from the hose machine which is not seen as local by our Docker image.
@@ -95,6 +179,11 @@ pub struct ApplicationSetting {
   pub host: String,
   pub server_key: Secret<String>,
   pub use_tls: bool,
+  /// The reverse proxies in front of the server, whose `X-Forwarded-For` header is trusted to
+  /// carry the ip address of the client. Without them, the peer address is the client.
+  pub trusted_proxies: Vec<IpAddr>,
+  /// The key the download links of the user data exports are signed with.
+  pub export_signing_secret: Secret<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -106,14 +195,20 @@ pub struct DatabaseSetting {
   /// connections are reserved for system applications.
   /// When we exceed the limit of the database connection, then it shows an error message.
   pub max_connections: u32,
+  /// The read replica that the read-only queries are routed to, `None` to route them to the
+  /// primary.
+  pub read_replica_conn_opts: Option<PgConnectOptions>,
+  /// How often the read replica is checked, the queries fall back to the primary while it is
+  /// unavailable.
+  pub read_replica_check_interval_secs: u64,
 }
 
 impl Display for DatabaseSetting {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     write!(
       f,
-      "DatabaseSetting {{ pg_conn_opts: {:?}, require_ssl: {}, max_connections: {} }}",
-      self.pg_conn_opts, self.require_ssl, self.max_connections
+      "DatabaseSetting {{ pg_conn_opts: {:?}, require_ssl: {}, max_connections: {}, read_replica_conn_opts: {:?} }}",
+      self.pg_conn_opts, self.require_ssl, self.max_connections, self.read_replica_conn_opts
     )
   }
 }
@@ -128,6 +223,18 @@ impl DatabaseSetting {
     let options = self.pg_conn_opts.clone();
     options.ssl_mode(ssl_mode)
   }
+
+  pub fn read_replica_connect_options(&self) -> Option<PgConnectOptions> {
+    let ssl_mode = if self.require_ssl {
+      PgSslMode::Require
+    } else {
+      PgSslMode::Prefer
+    };
+    self
+      .read_replica_conn_opts
+      .clone()
+      .map(|options| options.ssl_mode(ssl_mode))
+  }
 }
 
 #[derive(Clone, Debug)]
@@ -135,11 +242,48 @@ pub struct GrpcHistorySetting {
   pub addrs: String,
 }
 
+#[derive(Clone, Debug)]
+pub struct GrpcSetting {
+  /// The port of the integration gRPC server. The server is not started when it is 0.
+  pub port: u16,
+}
+
 #[derive(Clone, Debug)]
 pub struct CollabSetting {
   pub group_persistence_interval_secs: u64,
   pub edit_state_max_count: u32,
   pub edit_state_max_secs: i64,
+  /// The minimum number of seconds between two changes of the access level of a collab member.
+  /// `0` disables the cooldown.
+  pub access_level_change_cooldown_secs: u64,
+  /// How often, in seconds, the collab members whose membership has expired are revoked.
+  /// `0` disables the revocation.
+  pub member_expiry_check_interval_secs: u64,
+  /// How often, in seconds, the views that have been in the trash for longer than
+  /// `trash_retention_days` are deleted for good. `0` disables the purge.
+  pub trash_purge_interval_secs: u64,
+  pub trash_retention_days: i64,
+  /// How often, in seconds, the encoded state of the large collabs is compacted. `0` disables the
+  /// compaction.
+  pub compaction_interval_secs: u64,
+  /// The size, in bytes, above which the encoded state of a collab is compacted.
+  pub compaction_min_len: i32,
+  /// The maximum number of collabs compacted in a single run.
+  pub compaction_batch_size: i64,
+  /// How often, in seconds, the snapshots that the retention policies of the workspaces no
+  /// longer keep are deleted. `0` disables the cleanup.
+  pub snapshot_retention_interval_secs: u64,
+}
+
+impl CollabSetting {
+  pub fn access_level_change_cooldown(&self) -> Option<chrono::Duration> {
+    (self.access_level_change_cooldown_secs > 0)
+      .then(|| chrono::Duration::seconds(self.access_level_change_cooldown_secs as i64))
+  }
+
+  pub fn trash_retention(&self) -> chrono::Duration {
+    chrono::Duration::days(self.trash_retention_days)
+  }
 }
 
 #[derive(Clone, Debug)]
@@ -151,6 +295,15 @@ pub enum PublishedCollabStorageBackend {
 #[derive(Clone, Debug)]
 pub struct PublishedCollabSetting {
   pub storage_backend: PublishedCollabStorageBackend,
+  /// DNS-over-HTTPS endpoint, answering in the JSON format, that the TXT records of custom
+  /// domains are looked up with.
+  pub dns_over_https_url: String,
+  /// The secret key of the captcha service the submissions of the published forms are verified
+  /// with. Without it, the forms can't require a captcha.
+  pub captcha_secret_key: Option<Secret<String>>,
+  /// The endpoint that verifies the captcha tokens, such as the one of Cloudflare Turnstile or
+  /// hCaptcha.
+  pub captcha_verify_url: String,
 }
 
 impl TryFrom<&str> for PublishedCollabStorageBackend {
@@ -165,6 +318,15 @@ impl TryFrom<&str> for PublishedCollabStorageBackend {
   }
 }
 
+#[derive(Clone, Debug)]
+pub struct WebhookSetting {
+  /// How often, in seconds, the due webhook deliveries are sent. `0` disables the delivery.
+  pub dispatch_interval_secs: u64,
+  /// The number of failed attempts after which a delivery becomes a dead letter.
+  pub max_attempts: i32,
+  pub request_timeout_secs: u64,
+}
+
 // Default values favor local development.
 pub fn get_configuration() -> Result<Config, anyhow::Error> {
   let config = Config {
@@ -182,6 +344,16 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
       max_connections: get_env_var("APPFLOWY_DATABASE_MAX_CONNECTIONS", "40")
         .parse()
         .context("fail to get APPFLOWY_DATABASE_MAX_CONNECTIONS")?,
+      read_replica_conn_opts: Some(get_env_var("APPFLOWY_DATABASE_READ_REPLICA_URL", ""))
+        .filter(|url| !url.is_empty())
+        .map(|url| PgConnectOptions::from_str(&url))
+        .transpose()?,
+      read_replica_check_interval_secs: get_env_var(
+        "APPFLOWY_DATABASE_READ_REPLICA_CHECK_INTERVAL_SECS",
+        "10",
+      )
+      .parse()
+      .context("fail to get APPFLOWY_DATABASE_READ_REPLICA_CHECK_INTERVAL_SECS")?,
     },
     gotrue: GoTrueSetting {
       base_url: get_env_var("APPFLOWY_GOTRUE_BASE_URL", "http://localhost:9999"),
@@ -197,13 +369,29 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
         .parse()
         .context("fail to get APPFLOWY_APPLICATION_USE_TLS")?,
       server_key: get_env_var("APPFLOWY_APPLICATION_SERVER_KEY", "server_key").into(),
+      trusted_proxies: get_env_var("APPFLOWY_TRUSTED_PROXIES", "")
+        .split(',')
+        .map(|ip| ip.trim())
+        .filter(|ip| !ip.is_empty())
+        .map(IpAddr::from_str)
+        .collect::<Result<_, _>>()
+        .context("fail to get APPFLOWY_TRUSTED_PROXIES")?,
+      export_signing_secret: get_env_var("APPFLOWY_EXPORT_SIGNING_SECRET", "export_signing_secret")
+        .into(),
     },
     websocket: WebsocketSetting {
       heartbeat_interval: get_env_var("APPFLOWY_WEBSOCKET_HEARTBEAT_INTERVAL", "6").parse()?,
       client_timeout: get_env_var("APPFLOWY_WEBSOCKET_CLIENT_TIMEOUT", "60").parse()?,
       min_client_version: get_env_var("APPFLOWY_WEBSOCKET_CLIENT_MIN_VERSION", "0.5.0").parse()?,
+      compression: get_env_var("APPFLOWY_WEBSOCKET_COMPRESSION", "true")
+        .parse()
+        .context("fail to get APPFLOWY_WEBSOCKET_COMPRESSION")?,
+      min_protocol_version: get_env_var("APPFLOWY_WEBSOCKET_MIN_PROTOCOL_VERSION", "1")
+        .parse()
+        .context("fail to get APPFLOWY_WEBSOCKET_MIN_PROTOCOL_VERSION")?,
     },
     redis_uri: get_env_var("APPFLOWY_REDIS_URI", "redis://localhost:6379").into(),
+    redis: get_redis_setting()?,
     s3: S3Setting {
       use_minio: get_env_var("APPFLOWY_S3_USE_MINIO", "true")
         .parse()
@@ -213,14 +401,38 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
       secret_key: get_env_var("APPFLOWY_S3_SECRET_KEY", "minioadmin").into(),
       bucket: get_env_var("APPFLOWY_S3_BUCKET", "appflowy"),
       region: get_env_var("APPFLOWY_S3_REGION", ""),
+      presigned_url_expiration_secs: get_env_var(
+        "APPFLOWY_S3_PRESIGNED_URL_EXPIRATION_SECS",
+        "3600",
+      )
+      .parse()?,
     },
     appflowy_ai: AppFlowyAISetting {
       port: get_env_var("APPFLOWY_AI_SERVER_PORT", "5001").into(),
       host: get_env_var("APPFLOWY_AI_SERVER_HOST", "localhost").into(),
     },
+    llm: LLMSetting {
+      provider: get_env_var_opt("APPFLOWY_LLM_PROVIDER")
+        .filter(|provider| !provider.is_empty())
+        .map(|provider| provider.parse())
+        .transpose()
+        .context("fail to get APPFLOWY_LLM_PROVIDER")?,
+      base_url: get_env_var_opt("APPFLOWY_LLM_BASE_URL").filter(|url| !url.is_empty()),
+      api_key: get_env_var("APPFLOWY_LLM_API_KEY", "").into(),
+      model: get_env_var("APPFLOWY_LLM_MODEL", "gpt-4o-mini"),
+    },
+    ai_quota: AIQuotaSetting {
+      free_plan: get_ai_plan_quota("FREE")?,
+      pro_plan: get_ai_plan_quota("PRO")?,
+      team_plan: get_ai_plan_quota("TEAM")?,
+      ai_max_plan: get_ai_plan_quota("AI_MAX")?,
+    },
     grpc_history: GrpcHistorySetting {
       addrs: get_env_var("APPFLOWY_GRPC_HISTORY_ADDRS", "http://localhost:50051"),
     },
+    grpc: GrpcSetting {
+      port: get_env_var("APPFLOWY_GRPC_PORT", "0").parse()?,
+    },
     collab: CollabSetting {
       group_persistence_interval_secs: get_env_var(
         "APPFLOWY_COLLAB_GROUP_PERSISTENCE_INTERVAL",
@@ -229,11 +441,51 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
       .parse()?,
       edit_state_max_count: get_env_var("APPFLOWY_COLLAB_EDIT_STATE_MAX_COUNT", "100").parse()?,
       edit_state_max_secs: get_env_var("APPFLOWY_COLLAB_EDIT_STATE_MAX_SECS", "60").parse()?,
+      access_level_change_cooldown_secs: get_env_var(
+        "APPFLOWY_COLLAB_ACCESS_LEVEL_CHANGE_COOLDOWN_SECS",
+        "0",
+      )
+      .parse()?,
+      member_expiry_check_interval_secs: get_env_var(
+        "APPFLOWY_COLLAB_MEMBER_EXPIRY_CHECK_INTERVAL_SECS",
+        "60",
+      )
+      .parse()?,
+      trash_purge_interval_secs: get_env_var("APPFLOWY_COLLAB_TRASH_PURGE_INTERVAL_SECS", "3600")
+        .parse()?,
+      trash_retention_days: get_env_var("APPFLOWY_COLLAB_TRASH_RETENTION_DAYS", "30").parse()?,
+      compaction_interval_secs: get_env_var("APPFLOWY_COLLAB_COMPACTION_INTERVAL_SECS", "3600")
+        .parse()?,
+      // 1MB
+      compaction_min_len: get_env_var("APPFLOWY_COLLAB_COMPACTION_MIN_LEN", "1048576").parse()?,
+      compaction_batch_size: get_env_var("APPFLOWY_COLLAB_COMPACTION_BATCH_SIZE", "100").parse()?,
+      snapshot_retention_interval_secs: get_env_var(
+        "APPFLOWY_COLLAB_SNAPSHOT_RETENTION_INTERVAL_SECS",
+        "3600",
+      )
+      .parse()?,
     },
     published_collab: PublishedCollabSetting {
       storage_backend: get_env_var("APPFLOWY_PUBLISHED_COLLAB_STORAGE_BACKEND", "postgres")
         .as_str()
         .try_into()?,
+      dns_over_https_url: get_env_var(
+        "APPFLOWY_PUBLISHED_COLLAB_DNS_OVER_HTTPS_URL",
+        "https://cloudflare-dns.com/dns-query",
+      ),
+      captcha_secret_key: get_env_var_opt("APPFLOWY_PUBLISHED_FORM_CAPTCHA_SECRET_KEY")
+        .filter(|key| !key.is_empty())
+        .map(Secret::new),
+      captcha_verify_url: get_env_var(
+        "APPFLOWY_PUBLISHED_FORM_CAPTCHA_VERIFY_URL",
+        "https://challenges.cloudflare.com/turnstile/v0/siteverify",
+      ),
+    },
+    webhook: WebhookSetting {
+      dispatch_interval_secs: get_env_var("APPFLOWY_WEBHOOK_DISPATCH_INTERVAL_SECS", "5")
+        .parse()?,
+      max_attempts: get_env_var("APPFLOWY_WEBHOOK_MAX_ATTEMPTS", "8").parse()?,
+      request_timeout_secs: get_env_var("APPFLOWY_WEBHOOK_REQUEST_TIMEOUT_SECS", "10").parse()?,
     },
     mailer: MailerSetting {
       smtp_host: get_env_var("APPFLOWY_MAILER_SMTP_HOST", "smtp.gmail.com"),
@@ -241,6 +493,43 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
       smtp_username: get_env_var("APPFLOWY_MAILER_SMTP_USERNAME", "sender@example.com"),
       smtp_password: get_env_var("APPFLOWY_MAILER_SMTP_PASSWORD", "password").into(),
     },
+    notification: NotificationSetting {
+      email_interval_secs: get_env_var("APPFLOWY_NOTIFICATION_EMAIL_INTERVAL_SECS", "60")
+        .parse()?,
+      digest_interval_secs: get_env_var("APPFLOWY_NOTIFICATION_DIGEST_INTERVAL_SECS", "86400")
+        .parse()?,
+      public_url: get_env_var("API_EXTERNAL_URL", "http://localhost"),
+    },
+    account_deletion: AccountDeletionSetting {
+      purge_interval_secs: get_env_var("APPFLOWY_ACCOUNT_DELETION_PURGE_INTERVAL_SECS", "3600")
+        .parse()?,
+      grace_period_days: get_env_var("APPFLOWY_ACCOUNT_DELETION_GRACE_PERIOD_DAYS", "30")
+        .parse()?,
+    },
+    rate_limit: RateLimitSetting {
+      enabled: get_env_var("APPFLOWY_RATE_LIMIT_ENABLED", "false")
+        .parse()
+        .context("fail to get APPFLOWY_RATE_LIMIT_ENABLED")?,
+      user_requests_per_minute: get_env_var("APPFLOWY_RATE_LIMIT_USER_REQUESTS_PER_MINUTE", "600")
+        .parse()?,
+      anonymous_requests_per_minute: get_env_var(
+        "APPFLOWY_RATE_LIMIT_ANONYMOUS_REQUESTS_PER_MINUTE",
+        "120",
+      )
+      .parse()?,
+      workspace_requests_per_minute: get_env_var(
+        "APPFLOWY_RATE_LIMIT_WORKSPACE_REQUESTS_PER_MINUTE",
+        "3000",
+      )
+      .parse()?,
+      ws_connections_per_user: get_env_var("APPFLOWY_RATE_LIMIT_WS_CONNECTIONS_PER_USER", "20")
+        .parse()?,
+    },
+    storage_quota: StorageQuotaSetting {
+      free_plan_bytes: get_env_var("APPFLOWY_STORAGE_QUOTA_FREE_PLAN_BYTES", "0").parse()?,
+      pro_plan_bytes: get_env_var("APPFLOWY_STORAGE_QUOTA_PRO_PLAN_BYTES", "0").parse()?,
+      team_plan_bytes: get_env_var("APPFLOWY_STORAGE_QUOTA_TEAM_PLAN_BYTES", "0").parse()?,
+    },
     apple_oauth: AppleOAuthSetting {
       client_id: get_env_var("APPFLOWY_APPLE_OAUTH_CLIENT_ID", ""),
       client_secret: get_env_var("APPFLOWY_APPLE_OAUTH_CLIENT_SECRET", "").into(),
@@ -250,6 +539,38 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
   Ok(config)
 }
 
+fn get_ai_plan_quota(plan: &str) -> Result<AIPlanQuota, anyhow::Error> {
+  Ok(AIPlanQuota {
+    monthly_requests: get_env_var(&format!("APPFLOWY_AI_QUOTA_{}_PLAN_REQUESTS", plan), "0")
+      .parse()?,
+    monthly_tokens: get_env_var(&format!("APPFLOWY_AI_QUOTA_{}_PLAN_TOKENS", plan), "0").parse()?,
+  })
+}
+
+fn get_redis_setting() -> Result<RedisSetting, anyhow::Error> {
+  // Without explicit nodes, the single redis uri is used as the only node.
+  let nodes = get_env_var("APPFLOWY_REDIS_NODES", "");
+  let nodes = if nodes.trim().is_empty() {
+    vec![get_env_var("APPFLOWY_REDIS_URI", "redis://localhost:6379")]
+  } else {
+    nodes
+      .split(',')
+      .map(|node| node.trim().to_string())
+      .filter(|node| !node.is_empty())
+      .collect()
+  };
+  Ok(RedisSetting {
+    mode: get_env_var("APPFLOWY_REDIS_MODE", "standalone")
+      .parse()
+      .map_err(|err: String| anyhow::anyhow!(err))
+      .context("fail to get APPFLOWY_REDIS_MODE")?,
+    nodes,
+    sentinel_master: get_env_var("APPFLOWY_REDIS_SENTINEL_MASTER", "mymaster"),
+    health_check_interval_secs: get_env_var("APPFLOWY_REDIS_HEALTH_CHECK_INTERVAL_SECS", "30")
+      .parse()?,
+  })
+}
+
 /// The possible runtime environment for our application.
 #[derive(Clone, Debug, Deserialize)]
 pub enum Environment {
@@ -286,4 +607,8 @@ pub struct WebsocketSetting {
   pub heartbeat_interval: u8,
   pub client_timeout: u8,
   pub min_client_version: Version,
+  /// Whether the realtime messages are compressed for the clients that accept it.
+  pub compression: bool,
+  /// The oldest version of the realtime protocol accepted, older clients are asked to upgrade.
+  pub min_protocol_version: u32,
 }
@@ -34,11 +34,18 @@ impl Mailer {
     let workspace_invite_template =
       include_str!("../assets/mailer_templates/build_production/workspace_invitation.html");
 
-    HANDLEBARS
+    let notification_template =
+      include_str!("../assets/mailer_templates/build_production/notification.html");
+
+    let mut handlebars = HANDLEBARS
       .write()
-      .map_err(|err| anyhow::anyhow!(format!("Failed to write handlebars: {}", err)))?
+      .map_err(|err| anyhow::anyhow!(format!("Failed to write handlebars: {}", err)))?;
+    handlebars
       .register_template_string("workspace_invite", workspace_invite_template)
       .map_err(|err| anyhow::anyhow!(format!("Failed to register handlebars template: {}", err)))?;
+    handlebars
+      .register_template_string("notification", notification_template)
+      .map_err(|err| anyhow::anyhow!(format!("Failed to register handlebars template: {}", err)))?;
 
     Ok(Self {
       smtp_transport,
@@ -75,6 +82,34 @@ impl Mailer {
     AsyncTransport::send(&self.smtp_transport, email).await?;
     Ok(())
   }
+
+  /// Send one notification, or a digest of several, to the user.
+  pub async fn send_notification(
+    &self,
+    email: String,
+    param: NotificationMailerParam,
+  ) -> Result<(), anyhow::Error> {
+    let rendered = match HANDLEBARS.read() {
+      Ok(registory) => registory.render("notification", &param)?,
+      Err(err) => anyhow::bail!(format!("Failed to render handlebars template: {}", err)),
+    };
+
+    let email = Message::builder()
+      .from(lettre::message::Mailbox::new(
+        Some("AppFlowy Notification".to_string()),
+        self.smtp_username.parse::<Address>()?,
+      ))
+      .to(lettre::message::Mailbox::new(
+        Some(param.username.clone()),
+        email.parse()?,
+      ))
+      .subject(param.title.clone())
+      .header(ContentType::TEXT_HTML)
+      .body(rendered)?;
+
+    AsyncTransport::send(&self.smtp_transport, email).await?;
+    Ok(())
+  }
 }
 
 #[derive(serde::Serialize)]
@@ -86,3 +121,17 @@ pub struct WorkspaceInviteMailerParam {
   pub workspace_member_count: String,
   pub accept_url: String,
 }
+
+#[derive(serde::Serialize)]
+pub struct NotificationMailerParam {
+  pub username: String, // Recipient
+  pub title: String,
+  pub items: Vec<NotificationMailerItem>,
+  pub unsubscribe_url: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct NotificationMailerItem {
+  pub message: String,
+  pub url: Option<String>,
+}
@@ -170,8 +170,10 @@ where
         let services = self.controllers.clone();
 
         Box::pin(async move {
-          // If the workspace_id or collab_object_id is not present, skip the access control
-          if workspace_id.is_none() && object_id.is_none() {
+          // The collab is checked within its workspace, so without a workspace_id the access
+          // control is skipped. Such routes, like the ones opened with an object token, are
+          // authorized by their handlers and may not carry a user session at all.
+          if workspace_id.is_none() {
             return fut.await;
           }
 
@@ -0,0 +1,249 @@
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+use actix_service::{forward_ready, Service, Transform};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::http::Method;
+use actix_web::web::Data;
+use actix_web::{Error, HttpMessage};
+use app_error::AppError;
+use authentication::jwt::ApiKeyPrincipal;
+use futures_util::future::LocalBoxFuture;
+use shared_entity::dto::workspace_dto::ApiKeyScope;
+
+use crate::biz::workspace::service_account::{authenticate_api_key, API_KEY_PREFIX};
+use crate::middleware::rate_limit_mw::workspace_id_from_path;
+use crate::state::AppState;
+
+/// Authenticates the requests that carry an API key, `Authorization: Bearer afk_...`, as the
+/// service account the key belongs to. The other requests are passed through untouched.
+///
+/// Once the key is verified, an [ApiKeyPrincipal] is inserted into the extensions of the request,
+/// which the user extractors, and thus the access control, resolve to the user of the service
+/// account. A key is confined to the routes of its workspace and to its [ApiKeyScope].
+pub struct ApiKeyMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for ApiKeyMiddleware
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type Transform = ApiKeyMiddlewareService<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(ApiKeyMiddlewareService {
+      service: Rc::new(service),
+    }))
+  }
+}
+
+pub struct ApiKeyMiddlewareService<S> {
+  service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiKeyMiddlewareService<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, req: ServiceRequest) -> Self::Future {
+    let api_key = req
+      .headers()
+      .get(AUTHORIZATION)
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| value.strip_prefix("Bearer "))
+      .filter(|token| token.starts_with(API_KEY_PREFIX))
+      .map(|token| token.to_string());
+    let api_key = match api_key {
+      Some(api_key) => api_key,
+      None => return Box::pin(self.service.call(req)),
+    };
+
+    let pg_pool = req
+      .app_data::<Data<AppState>>()
+      .map(|state| state.pg_pool.clone());
+    let service = self.service.clone();
+    Box::pin(async move {
+      let pg_pool = pg_pool.ok_or_else(|| {
+        AppError::Internal(anyhow::anyhow!("AppState is not found in the request"))
+      })?;
+      // The SCIM clients expect the http status of the error
+      let is_scim = req.path().starts_with("/scim/");
+      let (principal, scope) = match authenticate_api_key(&pg_pool, &api_key).await {
+        Ok(authenticated) => authenticated,
+        Err(err) if is_scim => return Err(actix_web::error::ErrorUnauthorized(err.to_string())),
+        Err(err) => return Err(Error::from(err)),
+      };
+      if !is_allowed_by_api_key(&principal, scope, req.method(), req.path()) {
+        if is_scim {
+          return Err(actix_web::error::ErrorForbidden(
+            "The api key is not allowed to provision the workspace",
+          ));
+        }
+        return Err(Error::from(AppError::NotEnoughPermissions {
+          user: principal.user_uuid.to_string(),
+          action: format!(
+            "{} {} with a {} api key",
+            req.method(),
+            req.path(),
+            scope.as_str()
+          ),
+        }));
+      }
+      req.extensions_mut().insert(principal);
+      service.call(req).await
+    })
+  }
+}
+
+/// A key only reaches the routes scoped by the workspace of its service account, see
+/// [workspace_id_from_path]. The other routes, such as the ones of the user or the ones that
+/// create a workspace, are denied. A read-only key only reads, and a publish-only key only writes
+/// to the publish endpoints of the workspace. The SCIM endpoints are only reached by, and only
+/// reach, SCIM keys.
+fn is_allowed_by_api_key(
+  principal: &ApiKeyPrincipal,
+  scope: ApiKeyScope,
+  method: &Method,
+  path: &str,
+) -> bool {
+  let is_scim = path.starts_with("/scim/");
+  if is_scim || scope == ApiKeyScope::Scim {
+    return is_scim && scope == ApiKeyScope::Scim;
+  }
+  if workspace_id_from_path(path) != Some(principal.workspace_id) {
+    return false;
+  }
+  let is_read = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+  match scope {
+    ApiKeyScope::ReadOnly => is_read,
+    ApiKeyScope::PublishOnly => {
+      is_read
+        || path
+          .strip_prefix("/api/workspace/")
+          .and_then(|rest| rest.split('/').nth(1))
+          .map_or(false, |segment| segment.starts_with("publish"))
+    },
+    ApiKeyScope::Full => true,
+    ApiKeyScope::Scim => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use uuid::Uuid;
+
+  #[test]
+  fn is_allowed_by_api_key_test() {
+    let principal = ApiKeyPrincipal {
+      user_uuid: Uuid::new_v4(),
+      workspace_id: Uuid::new_v4(),
+    };
+    let folder = format!("/api/workspace/{}/folder", principal.workspace_id);
+    let publish = format!("/api/workspace/{}/publish", principal.workspace_id);
+    let other = format!("/api/workspace/{}/folder", Uuid::new_v4());
+    let other_file_storage = format!("/api/file_storage/{}/usage", Uuid::new_v4());
+    let chat = format!("/api/chat/{}/abc", principal.workspace_id);
+
+    assert!(is_allowed_by_api_key(
+      &principal,
+      ApiKeyScope::ReadOnly,
+      &Method::GET,
+      &folder
+    ));
+    assert!(!is_allowed_by_api_key(
+      &principal,
+      ApiKeyScope::ReadOnly,
+      &Method::POST,
+      &publish
+    ));
+    assert!(is_allowed_by_api_key(
+      &principal,
+      ApiKeyScope::PublishOnly,
+      &Method::POST,
+      &publish
+    ));
+    assert!(!is_allowed_by_api_key(
+      &principal,
+      ApiKeyScope::PublishOnly,
+      &Method::PUT,
+      &folder
+    ));
+    assert!(is_allowed_by_api_key(
+      &principal,
+      ApiKeyScope::Full,
+      &Method::DELETE,
+      &folder
+    ));
+    assert!(!is_allowed_by_api_key(
+      &principal,
+      ApiKeyScope::Full,
+      &Method::GET,
+      &other
+    ));
+    assert!(!is_allowed_by_api_key(
+      &principal,
+      ApiKeyScope::Full,
+      &Method::GET,
+      &other_file_storage
+    ));
+    assert!(is_allowed_by_api_key(
+      &principal,
+      ApiKeyScope::Full,
+      &Method::POST,
+      &chat
+    ));
+    // The routes that are not scoped by the workspace are denied
+    for path in [
+      "/api/user/profile",
+      "/api/user",
+      "/api/workspace",
+      "/api/graphql",
+    ] {
+      assert!(!is_allowed_by_api_key(
+        &principal,
+        ApiKeyScope::Full,
+        &Method::DELETE,
+        path
+      ));
+    }
+    assert!(!is_allowed_by_api_key(
+      &principal,
+      ApiKeyScope::ReadOnly,
+      &Method::GET,
+      "/api/user/profile"
+    ));
+    assert!(is_allowed_by_api_key(
+      &principal,
+      ApiKeyScope::Scim,
+      &Method::POST,
+      "/scim/v2/Users"
+    ));
+    assert!(!is_allowed_by_api_key(
+      &principal,
+      ApiKeyScope::Scim,
+      &Method::GET,
+      &folder
+    ));
+    assert!(!is_allowed_by_api_key(
+      &principal,
+      ApiKeyScope::Full,
+      &Method::GET,
+      "/scim/v2/Users"
+    ));
+  }
+}
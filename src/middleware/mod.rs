@@ -1,5 +1,8 @@
 pub mod access_control_mw;
+pub mod api_key_mw;
 // pub mod cors_mw;
 pub mod encrypt_mw;
 pub mod metrics_mw;
+pub mod rate_limit_mw;
 pub mod request_id;
+pub mod sso_mw;
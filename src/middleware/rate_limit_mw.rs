@@ -0,0 +1,434 @@
+use std::future::{ready, Ready};
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use actix_service::{forward_ready, Service, Transform};
+use actix_web::body::EitherBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::RETRY_AFTER;
+use actix_web::web::Data;
+use actix_web::{Error, HttpResponse};
+use app_error::ErrorCode;
+use authentication::jwt::UserUuid;
+use chrono::Utc;
+use collab_rt_entity::RealtimeMessage;
+use dashmap::DashMap;
+use database::rate_limit::{select_user_ws_connection_limit, select_workspace_requests_per_minute};
+use futures_util::future::LocalBoxFuture;
+use shared_entity::response::AppResponse;
+use sqlx::PgPool;
+use tokio::sync::mpsc::Sender;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::api::util::client_ip;
+use crate::config::config::RateLimitSetting;
+use crate::state::{AppState, RedisConnectionManager};
+
+const RATE_LIMIT_KEY_PREFIX: &str = "af:rate_limit";
+const WINDOW_SECS: i64 = 60;
+/// How long the limits of a workspace are cached before they are read again.
+const WORKSPACE_LIMIT_CACHE_TTL: Duration = Duration::from_secs(60);
+/// How many times the password of a published view can be tried in a minute from an ip address.
+const PUBLISHED_VIEW_PASSWORD_ATTEMPTS_PER_IP: u32 = 5;
+/// How many times the password of a published view can be tried in a minute, from any address.
+const PUBLISHED_VIEW_PASSWORD_ATTEMPTS: u32 = 60;
+
+/// Limits the number of requests per minute and the number of concurrent websocket connections.
+///
+/// Requests are counted in fixed windows of one minute, shared by every server through Redis.
+/// When Redis can not be reached, the requests are let through.
+#[derive(Clone)]
+pub struct RateLimiter {
+  redis: RedisConnectionManager,
+  pg_pool: PgPool,
+  setting: RateLimitSetting,
+  workspace_limits: Arc<DashMap<Uuid, (u32, Instant)>>,
+}
+
+impl RateLimiter {
+  pub fn new(redis: RedisConnectionManager, pg_pool: PgPool, setting: RateLimitSetting) -> Self {
+    Self {
+      redis,
+      pg_pool,
+      setting,
+      workspace_limits: Arc::new(DashMap::new()),
+    }
+  }
+
+  pub fn is_enabled(&self) -> bool {
+    self.setting.enabled
+  }
+
+  /// Count the request against the limit of the user, or of the ip address when there is no
+  /// user. Returns the number of seconds to wait if the limit is exceeded.
+  pub async fn check_caller(&self, user_uuid: Option<Uuid>, ip: Option<IpAddr>) -> Option<u64> {
+    let (key, limit) = match (user_uuid, ip) {
+      (Some(user_uuid), _) => (
+        format!("user:{}", user_uuid),
+        self.setting.user_requests_per_minute,
+      ),
+      (None, Some(ip)) => (
+        format!("ip:{}", ip),
+        self.setting.anonymous_requests_per_minute,
+      ),
+      (None, None) => return None,
+    };
+    self.hit(&key, limit).await
+  }
+
+  /// Count the request against the limit of the workspace it targets. Returns the number of
+  /// seconds to wait if the limit is exceeded.
+  pub async fn check_workspace(&self, workspace_id: &Uuid) -> Option<u64> {
+    let limit = self.workspace_requests_per_minute(workspace_id).await;
+    let key = format!("workspace:{}", workspace_id);
+    self.hit(&key, limit).await
+  }
+
+  /// Count an attempt at the password of the published view, against the limit of the ip address
+  /// and the limit of the view, so that the password is not guessed from many addresses either.
+  /// The attempts are limited even when the rate limiting is disabled. Returns the number of
+  /// seconds to wait if a limit is exceeded.
+  pub async fn check_published_view_password(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    ip: Option<IpAddr>,
+  ) -> Option<u64> {
+    let key = format!("publish_password:{}/{}", publish_namespace, publish_name);
+    if let Some(ip) = ip {
+      let ip_key = format!("{}:{}", key, ip);
+      if let Some(retry_after) = self
+        .hit(&ip_key, PUBLISHED_VIEW_PASSWORD_ATTEMPTS_PER_IP)
+        .await
+      {
+        return Some(retry_after);
+      }
+    }
+    self.hit(&key, PUBLISHED_VIEW_PASSWORD_ATTEMPTS).await
+  }
+
+  /// Register the websocket connection of the user. Returns the number of seconds to wait if the
+  /// user already has as many connections as allowed.
+  ///
+  /// The connection holds a lease that is renewed until `session` is closed, so the connections
+  /// of a server that stopped abruptly are released once their lease expires.
+  pub async fn acquire_ws_connection(
+    &self,
+    uid: i64,
+    session_id: &str,
+    session: Sender<RealtimeMessage>,
+    lease: Duration,
+  ) -> Result<(), u64> {
+    if !self.is_enabled() {
+      return Ok(());
+    }
+    let limit = match select_user_ws_connection_limit(&self.pg_pool, uid).await {
+      Ok(Some(limit)) if limit >= 0 => limit as u32,
+      Ok(_) => self.setting.ws_connections_per_user,
+      Err(err) => {
+        warn!("Failed to get the websocket limit of user {}: {}", uid, err);
+        self.setting.ws_connections_per_user
+      },
+    };
+    let key = format!("{}:ws:{}", RATE_LIMIT_KEY_PREFIX, uid);
+    let mut redis = self.redis.clone();
+    let now = Utc::now().timestamp();
+    let expires_at = now + lease.as_secs() as i64;
+    let result: redis::RedisResult<(u64,)> = redis::pipe()
+      .atomic()
+      .zrembyscore(&key, "-inf", now)
+      .ignore()
+      .zadd(&key, session_id, expires_at)
+      .ignore()
+      .zcard(&key)
+      .expire(&key, lease.as_secs() as i64)
+      .ignore()
+      .query_async(&mut redis)
+      .await;
+    let count = match result {
+      Ok((count,)) => count,
+      Err(err) => {
+        warn!("Failed to count the websocket connections: {}", err);
+        return Ok(());
+      },
+    };
+    if count > limit as u64 {
+      let _: redis::RedisResult<()> = redis::cmd("ZREM")
+        .arg(&key)
+        .arg(session_id)
+        .query_async(&mut redis)
+        .await;
+      // The first lease to expire frees a connection
+      let oldest: redis::RedisResult<Vec<(String, i64)>> = redis::cmd("ZRANGE")
+        .arg(&key)
+        .arg(0)
+        .arg(0)
+        .arg("WITHSCORES")
+        .query_async(&mut redis)
+        .await;
+      let retry_after = oldest
+        .ok()
+        .and_then(|oldest| oldest.first().map(|(_, expires_at)| *expires_at - now))
+        .unwrap_or(lease.as_secs() as i64);
+      return Err(retry_after.max(1) as u64);
+    }
+
+    let session_id = session_id.to_string();
+    tokio::spawn(async move {
+      let mut interval = tokio::time::interval(lease / 2);
+      interval.tick().await;
+      loop {
+        tokio::select! {
+          _ = session.closed() => break,
+          _ = interval.tick() => {
+            let expires_at = Utc::now().timestamp() + lease.as_secs() as i64;
+            let result: redis::RedisResult<()> = redis::pipe()
+              .zadd(&key, &session_id, expires_at)
+              .ignore()
+              .expire(&key, lease.as_secs() as i64)
+              .ignore()
+              .query_async(&mut redis)
+              .await;
+            if let Err(err) = result {
+              warn!("Failed to renew the websocket connection lease: {}", err);
+            }
+          },
+        }
+      }
+      let result: redis::RedisResult<()> = redis::cmd("ZREM")
+        .arg(&key)
+        .arg(&session_id)
+        .query_async(&mut redis)
+        .await;
+      if let Err(err) = result {
+        warn!("Failed to release the websocket connection: {}", err);
+      }
+    });
+    Ok(())
+  }
+
+  /// Returns the number of seconds to wait if the request exceeds the limit of the key.
+  async fn hit(&self, key: &str, limit: u32) -> Option<u64> {
+    let now = Utc::now().timestamp();
+    let window = now / WINDOW_SECS;
+    let key = format!("{}:{}:{}", RATE_LIMIT_KEY_PREFIX, key, window);
+    let mut redis = self.redis.clone();
+    let result: redis::RedisResult<(u64,)> = redis::pipe()
+      .atomic()
+      .incr(&key, 1)
+      .expire(&key, WINDOW_SECS)
+      .ignore()
+      .query_async(&mut redis)
+      .await;
+    match result {
+      Ok((count,)) if count > limit as u64 => Some((WINDOW_SECS - now % WINDOW_SECS) as u64),
+      Ok(_) => None,
+      Err(err) => {
+        warn!(
+          "Failed to count the request against the rate limit: {}",
+          err
+        );
+        None
+      },
+    }
+  }
+
+  async fn workspace_requests_per_minute(&self, workspace_id: &Uuid) -> u32 {
+    if let Some(entry) = self.workspace_limits.get(workspace_id) {
+      let (limit, fetched_at) = *entry.value();
+      if fetched_at.elapsed() < WORKSPACE_LIMIT_CACHE_TTL {
+        return limit;
+      }
+    }
+    let limit = match select_workspace_requests_per_minute(&self.pg_pool, workspace_id).await {
+      Ok(Some(limit)) if limit >= 0 => limit as u32,
+      Ok(_) => self.setting.workspace_requests_per_minute,
+      Err(err) => {
+        warn!(
+          "Failed to get the rate limit of workspace {}: {}",
+          workspace_id, err
+        );
+        self.setting.workspace_requests_per_minute
+      },
+    };
+    self
+      .workspace_limits
+      .insert(*workspace_id, (limit, Instant::now()));
+    limit
+  }
+}
+
+/// Returns the response to a request that exceeded a rate limit.
+pub fn too_many_requests_response(retry_after: u64) -> HttpResponse {
+  HttpResponse::TooManyRequests()
+    .insert_header((RETRY_AFTER, retry_after.to_string()))
+    .json(AppResponse::<()>::new(
+      ErrorCode::TooManyRequests,
+      format!("Too many requests, retry after {} seconds", retry_after),
+    ))
+}
+
+/// Rejects the requests that exceed the limits of the [RateLimiter] of the [AppState] with a
+/// `429 Too Many Requests`. The websocket connections are limited when they are established,
+/// see [RateLimiter::acquire_ws_connection].
+#[derive(Clone, Copy)]
+pub enum RateLimitMiddleware {
+  /// Counts the requests against the limit of the user, or of the ip address of the anonymous
+  /// requests. Runs before the access control, so that the requests it rejects are limited too.
+  Caller,
+  /// Counts the requests against the limit of the workspace they target. Runs after the access
+  /// control, so that the users that are not members of a workspace don't use up its limit.
+  Workspace,
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimitMiddleware
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<EitherBody<B>>;
+  type Error = Error;
+  type Transform = RateLimitMiddlewareService<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(RateLimitMiddlewareService {
+      service,
+      limit: *self,
+    }))
+  }
+}
+
+pub struct RateLimitMiddlewareService<S> {
+  service: S,
+  limit: RateLimitMiddleware,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddlewareService<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<EitherBody<B>>;
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, mut req: ServiceRequest) -> Self::Future {
+    let state = req
+      .app_data::<Data<AppState>>()
+      .filter(|state| state.rate_limiter.is_enabled() && !skip_rate_limit(req.path()))
+      .cloned();
+    let state = match state {
+      Some(state) => state,
+      None => {
+        let fut = self.service.call(req);
+        return Box::pin(async move { Ok(fut.await?.map_into_left_body()) });
+      },
+    };
+
+    let http_req = req.request().clone();
+    match self.limit {
+      RateLimitMiddleware::Caller => {
+        let user_uuid = req.extract::<UserUuid>();
+        let ip = client_ip(&http_req, &state.config.application.trusted_proxies);
+        let fut = self.service.call(req);
+        Box::pin(async move {
+          let user_uuid = user_uuid.await.ok().map(|user_uuid| *user_uuid);
+          if let Some(retry_after) = state.rate_limiter.check_caller(user_uuid, ip).await {
+            let response = too_many_requests_response(retry_after);
+            return Ok(ServiceResponse::new(http_req, response).map_into_right_body());
+          }
+          Ok(fut.await?.map_into_left_body())
+        })
+      },
+      RateLimitMiddleware::Workspace => {
+        let workspace_id = match workspace_id_from_path(req.path()) {
+          Some(workspace_id) => workspace_id,
+          None => {
+            let fut = self.service.call(req);
+            return Box::pin(async move { Ok(fut.await?.map_into_left_body()) });
+          },
+        };
+        let fut = self.service.call(req);
+        Box::pin(async move {
+          if let Some(retry_after) = state.rate_limiter.check_workspace(&workspace_id).await {
+            let response = too_many_requests_response(retry_after);
+            return Ok(ServiceResponse::new(http_req, response).map_into_right_body());
+          }
+          Ok(fut.await?.map_into_left_body())
+        })
+      },
+    }
+  }
+}
+
+/// The websocket connections are limited when they are established, and the metrics are
+/// scraped by the monitoring.
+fn skip_rate_limit(path: &str) -> bool {
+  path.starts_with("/ws") || path.starts_with("/metrics")
+}
+
+/// The scopes whose routes start with the workspace they target, see [workspace_id_from_path].
+const WORKSPACE_SCOPED_PREFIXES: [&str; 7] = [
+  "/api/workspace/v1/",
+  "/api/workspace/",
+  "/api/file_storage/",
+  "/api/ai/",
+  "/api/chat/",
+  "/api/search/",
+  "/api/history/",
+];
+
+/// Returns the workspace the request targets, for the requests to the routes scoped by a
+/// workspace, such as `/api/workspace/{workspace_id}` or `/api/file_storage/{workspace_id}`.
+pub(crate) fn workspace_id_from_path(path: &str) -> Option<Uuid> {
+  let rest = WORKSPACE_SCOPED_PREFIXES
+    .iter()
+    .find_map(|prefix| path.strip_prefix(prefix))?;
+  let workspace_id = rest.split('/').next()?;
+  Uuid::parse_str(workspace_id).ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn workspace_id_from_path_test() {
+    let workspace_id = Uuid::new_v4();
+    assert_eq!(
+      workspace_id_from_path(&format!("/api/workspace/{}/collab/abc", workspace_id)),
+      Some(workspace_id)
+    );
+    assert_eq!(
+      workspace_id_from_path(&format!("/api/workspace/{}", workspace_id)),
+      Some(workspace_id)
+    );
+    for prefix in [
+      "workspace/v1",
+      "file_storage",
+      "ai",
+      "chat",
+      "search",
+      "history",
+    ] {
+      assert_eq!(
+        workspace_id_from_path(&format!("/api/{}/{}/abc", prefix, workspace_id)),
+        Some(workspace_id)
+      );
+    }
+    assert_eq!(workspace_id_from_path("/api/workspace/list"), None);
+    assert_eq!(
+      workspace_id_from_path(&format!("/api/workspace/published-info/{}", workspace_id)),
+      None
+    );
+    assert_eq!(workspace_id_from_path("/api/user/profile"), None);
+  }
+}
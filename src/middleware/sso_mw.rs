@@ -0,0 +1,252 @@
+use std::collections::HashSet;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use actix_service::{forward_ready, Service, Transform};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::web::Data;
+use actix_web::{Error, HttpMessage};
+use app_error::AppError;
+use appflowy_collaborate::actix_ws::client::rt_client::RealtimeSessionGuard;
+use async_trait::async_trait;
+use authentication::jwt::{ApiKeyPrincipal, Authorization};
+use collab_rt_entity::{ClientCollabMessage, MessageByObjectId};
+use dashmap::DashMap;
+use database::collab::select_workspace_id_of_collabs;
+use database::workspace_sso::{
+  select_enforced_workspace_sso, select_enforced_workspace_sso_of_member,
+  select_is_workspace_member_by_uuid,
+};
+use futures_util::future::LocalBoxFuture;
+use gotrue_entity::gotrue_jwt::GoTrueJWTClaims;
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::biz::workspace::sso::saml_provider_id;
+use crate::middleware::rate_limit_mw::workspace_id_from_path;
+use crate::state::AppState;
+
+/// How long the single sign-on enforcement of a workspace is cached before it is read again.
+const SSO_ENFORCEMENT_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// The provider a workspace enforces, and the owner of the workspace.
+type EnforcedSso = Option<(Uuid, Option<Uuid>)>;
+
+/// Tells whether a user signed in the way the workspace requires.
+#[derive(Clone)]
+pub struct SsoEnforcer {
+  pg_pool: PgPool,
+  workspaces: Arc<DashMap<Uuid, (EnforcedSso, Instant)>>,
+}
+
+impl SsoEnforcer {
+  pub fn new(pg_pool: PgPool) -> Self {
+    Self {
+      pg_pool,
+      workspaces: Arc::new(DashMap::new()),
+    }
+  }
+
+  /// Forget the cached enforcement of the workspace, after its single sign-on changed. The other
+  /// servers pick up the change once their cache expires.
+  pub fn invalidate(&self, workspace_id: &Uuid) {
+    self.workspaces.remove(workspace_id);
+  }
+
+  /// A workspace that enforces single sign-on is only accessed by its members with a session
+  /// that comes from its identity provider. The owner is let through with any session, so a
+  /// broken identity provider does not lock everyone out of the workspace.
+  pub async fn check(
+    &self,
+    workspace_id: &Uuid,
+    user_uuid: &Uuid,
+    claims: &GoTrueJWTClaims,
+  ) -> Result<(), AppError> {
+    let (provider_id, owner_uuid) = match self.enforced_sso(workspace_id).await? {
+      Some(enforced) => enforced,
+      None => return Ok(()),
+    };
+    if saml_provider_id(claims) == Some(provider_id) || owner_uuid.as_ref() == Some(user_uuid) {
+      return Ok(());
+    }
+    // The published pages of the workspace stay open to the users that are not members
+    if !select_is_workspace_member_by_uuid(&self.pg_pool, workspace_id, user_uuid).await? {
+      return Ok(());
+    }
+    Err(AppError::SsoRequired(format!(
+      "workspace {} requires signing in with its identity provider",
+      workspace_id
+    )))
+  }
+
+  /// Returns the workspaces the session must not access, following the rules of
+  /// [SsoEnforcer::check]. The realtime connections are not scoped by a workspace, so they are
+  /// kept from syncing the objects of these workspaces, see [SsoSessionGuard].
+  pub async fn blocked_workspaces(
+    &self,
+    user_uuid: &Uuid,
+    claims: &GoTrueJWTClaims,
+  ) -> Result<HashSet<Uuid>, AppError> {
+    let provider_id = saml_provider_id(claims);
+    let blocked_workspaces = select_enforced_workspace_sso_of_member(&self.pg_pool, user_uuid)
+      .await?
+      .into_iter()
+      .filter(|(_, required_provider_id)| provider_id != Some(*required_provider_id))
+      .map(|(workspace_id, _)| workspace_id)
+      .collect();
+    Ok(blocked_workspaces)
+  }
+
+  async fn enforced_sso(&self, workspace_id: &Uuid) -> Result<EnforcedSso, AppError> {
+    if let Some(entry) = self.workspaces.get(workspace_id) {
+      let (enforced, fetched_at) = *entry.value();
+      if fetched_at.elapsed() < SSO_ENFORCEMENT_CACHE_TTL {
+        return Ok(enforced);
+      }
+    }
+    let enforced = select_enforced_workspace_sso(&self.pg_pool, workspace_id).await?;
+    self
+      .workspaces
+      .insert(*workspace_id, (enforced, Instant::now()));
+    Ok(enforced)
+  }
+}
+
+/// Drops the realtime messages of the objects that belong to the workspaces a connection was
+/// not signed in for, see [SsoEnforcer::blocked_workspaces].
+pub struct SsoSessionGuard {
+  pg_pool: PgPool,
+  blocked_workspaces: HashSet<Uuid>,
+  /// The stored workspace of the objects the connection synced, None for the objects that were
+  /// not stored yet.
+  object_workspaces: DashMap<String, Option<Uuid>>,
+}
+
+impl SsoSessionGuard {
+  pub fn new(pg_pool: PgPool, blocked_workspaces: HashSet<Uuid>) -> Self {
+    Self {
+      pg_pool,
+      blocked_workspaces,
+      object_workspaces: DashMap::new(),
+    }
+  }
+
+  fn is_blocked(&self, object_id: &str, messages: &[ClientCollabMessage]) -> bool {
+    let stored_workspace_id = self
+      .object_workspaces
+      .get(object_id)
+      .and_then(|entry| *entry.value());
+    if let Some(workspace_id) = stored_workspace_id {
+      return self.blocked_workspaces.contains(&workspace_id);
+    }
+    // The objects that are not stored yet are created in the workspace the client names
+    messages.iter().any(|message| match message {
+      ClientCollabMessage::ClientInitSync { data } => Uuid::parse_str(&data.workspace_id)
+        .map(|workspace_id| self.blocked_workspaces.contains(&workspace_id))
+        .unwrap_or(true),
+      _ => false,
+    })
+  }
+}
+
+#[async_trait]
+impl RealtimeSessionGuard for SsoSessionGuard {
+  async fn filter(&self, mut messages: MessageByObjectId) -> MessageByObjectId {
+    let unknown_object_ids: Vec<String> = messages
+      .keys()
+      .filter(|object_id| !self.object_workspaces.contains_key(*object_id))
+      .cloned()
+      .collect();
+    if !unknown_object_ids.is_empty() {
+      match select_workspace_id_of_collabs(&self.pg_pool, &unknown_object_ids).await {
+        Ok(workspace_ids) => {
+          for object_id in unknown_object_ids {
+            let workspace_id = workspace_ids.get(&object_id).copied();
+            self.object_workspaces.insert(object_id, workspace_id);
+          }
+        },
+        Err(err) => {
+          warn!(
+            "failed to look up the workspace of the synced objects: {}",
+            err
+          );
+          messages.clear();
+          return messages;
+        },
+      }
+    }
+    messages.retain(|object_id, messages| !self.is_blocked(object_id, messages));
+    messages
+  }
+}
+
+/// Rejects the requests to a workspace that enforces single sign-on when the user did not sign
+/// in through the identity provider of the workspace, see [SsoEnforcer::check].
+///
+/// The requests authenticated with an API key, and the requests without a valid token, are
+/// passed through, the handlers authenticate them. The GraphQL queries check the workspace they
+/// read, and the realtime connections are restricted by a [SsoSessionGuard].
+pub struct SsoMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for SsoMiddleware
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type Transform = SsoMiddlewareService<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(SsoMiddlewareService {
+      service: Rc::new(service),
+    }))
+  }
+}
+
+pub struct SsoMiddlewareService<S> {
+  service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for SsoMiddlewareService<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, mut req: ServiceRequest) -> Self::Future {
+    let workspace_id = workspace_id_from_path(req.path())
+      .filter(|_| req.extensions().get::<ApiKeyPrincipal>().is_none());
+    let sso_enforcer = req
+      .app_data::<Data<AppState>>()
+      .map(|state| state.sso_enforcer.clone());
+    let (workspace_id, sso_enforcer) = match (workspace_id, sso_enforcer) {
+      (Some(workspace_id), Some(sso_enforcer)) => (workspace_id, sso_enforcer),
+      _ => return Box::pin(self.service.call(req)),
+    };
+
+    let service = self.service.clone();
+    Box::pin(async move {
+      if let Ok(auth) = req.extract::<Authorization>().await {
+        if let Ok(user_uuid) = auth.uuid() {
+          sso_enforcer
+            .check(&workspace_id, &user_uuid, &auth.claims)
+            .await?;
+        }
+      }
+      service.call(req).await
+    })
+  }
+}
@@ -12,11 +12,12 @@ use access_control::access::AccessControl;
 use access_control::metrics::AccessControlMetrics;
 use app_error::AppError;
 use appflowy_ai_client::client::AppFlowyAIClient;
+use appflowy_ai_client::llm::LLMClient;
 use appflowy_collaborate::collab::access_control::CollabAccessControlImpl;
 use appflowy_collaborate::collab::cache::CollabCache;
 use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
 use appflowy_collaborate::indexer::IndexerProvider;
-use appflowy_collaborate::metrics::CollabMetrics;
+use appflowy_collaborate::metrics::{CollabMetrics, CollabStorageMetrics};
 use appflowy_collaborate::shared_state::RealtimeSharedState;
 use appflowy_collaborate::CollabRealtimeMetrics;
 use database::file::s3_client_impl::{AwsS3BucketClientImpl, S3BucketStorage};
@@ -26,16 +27,22 @@ use snowflake::Snowflake;
 use tonic_proto::history::history_client::HistoryClient;
 use workspace_access::WorkspaceAccessControlImpl;
 
-use crate::api::metrics::{PublishedCollabMetrics, RequestMetrics};
+use crate::api::metrics::{CollabCompactionMetrics, PublishedCollabMetrics, RequestMetrics};
 use crate::biz::pg_listener::PgListeners;
+use crate::biz::pg_read_pool::PgReadPool;
 use crate::biz::workspace::publish::PublishedCollabStore;
+use crate::biz::workspace::publish_analytics::PublishedViewHitRecorder;
 use crate::config::config::Config;
 use crate::mailer::Mailer;
+use crate::middleware::rate_limit_mw::RateLimiter;
+use crate::middleware::sso_mw::SsoEnforcer;
 
-pub type RedisConnectionManager = redis::aio::ConnectionManager;
+pub type RedisConnectionManager = collab_stream::connection::RedisConnectionManager;
 #[derive(Clone)]
 pub struct AppState {
   pub pg_pool: PgPool,
+  /// The pool of the read-only queries, see [PgReadPool].
+  pub pg_read_pool: PgReadPool,
   pub config: Arc<Config>,
   pub user_cache: UserCache,
   pub id_gen: Arc<RwLock<Snowflake>>,
@@ -54,9 +61,14 @@ pub struct AppState {
   pub gotrue_admin: GoTrueAdmin,
   pub mailer: Mailer,
   pub ai_client: AppFlowyAIClient,
+  /// `None` when the server is not configured with a large language model of its own.
+  pub llm_client: Option<LLMClient>,
   pub realtime_shared_state: RealtimeSharedState,
   pub grpc_history_client: Arc<Mutex<HistoryClient<tonic::transport::Channel>>>,
   pub indexer_provider: Arc<IndexerProvider>,
+  pub rate_limiter: RateLimiter,
+  pub sso_enforcer: SsoEnforcer,
+  pub published_view_hits: PublishedViewHitRecorder,
 }
 
 impl AppState {
@@ -125,7 +137,9 @@ pub struct AppMetrics {
   pub realtime_metrics: Arc<CollabRealtimeMetrics>,
   pub access_control_metrics: Arc<AccessControlMetrics>,
   pub collab_metrics: Arc<CollabMetrics>,
+  pub collab_storage_metrics: Arc<CollabStorageMetrics>,
   pub published_collab_metrics: Arc<PublishedCollabMetrics>,
+  pub collab_compaction_metrics: Arc<CollabCompactionMetrics>,
 }
 
 impl Default for AppMetrics {
@@ -141,14 +155,18 @@ impl AppMetrics {
     let realtime_metrics = Arc::new(CollabRealtimeMetrics::register(&mut registry));
     let access_control_metrics = Arc::new(AccessControlMetrics::register(&mut registry));
     let collab_metrics = Arc::new(CollabMetrics::register(&mut registry));
+    let collab_storage_metrics = Arc::new(CollabStorageMetrics::register(&mut registry));
     let published_collab_metrics = Arc::new(PublishedCollabMetrics::register(&mut registry));
+    let collab_compaction_metrics = Arc::new(CollabCompactionMetrics::register(&mut registry));
     Self {
       registry: Arc::new(registry),
       request_metrics,
       realtime_metrics,
       access_control_metrics,
       collab_metrics,
+      collab_storage_metrics,
       published_collab_metrics,
+      collab_compaction_metrics,
     }
   }
 }
@@ -0,0 +1,35 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use database_entity::dto::AFRole;
+use shared_entity::dto::billing_dto::SubscriptionPlan;
+
+#[tokio::test]
+async fn workspace_ai_usage_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let usage = owner
+    .api_client
+    .get_workspace_ai_usage(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(usage.workspace_id.to_string(), workspace_id);
+  assert_eq!(usage.plan, SubscriptionPlan::Free);
+  assert_eq!(usage.requests, 0);
+  assert_eq!(usage.tokens_consumed, 0);
+  assert!(usage.period_start < usage.period_end);
+  assert!(usage.daily.iter().all(|day| day.day >= usage.period_start));
+
+  // only the members that can manage the billing monitor the AI usage
+  let error = member
+    .api_client
+    .get_workspace_ai_usage(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+}
@@ -1,5 +1,7 @@
 use appflowy_ai_client::dto::{AIModel, CompletionType};
 use client_api_test::{local_ai_test_enabled, TestClient};
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::Method;
 use shared_entity::dto::ai_dto::CompleteTextParams;
 
 #[tokio::test]
@@ -23,3 +25,36 @@ async fn improve_writing_test() {
     .unwrap();
   assert!(!resp.text.is_empty());
 }
+
+#[tokio::test]
+async fn stream_completion_as_server_sent_events_test() {
+  if !local_ai_test_enabled() {
+    return;
+  }
+  let test_client = TestClient::new_user().await;
+  let workspace_id = test_client.workspace_id().await;
+  let url = format!(
+    "{}/api/ai/{}/complete/stream",
+    test_client.api_client.base_url, workspace_id
+  );
+  let resp = test_client
+    .api_client
+    .http_client_with_auth(Method::POST, &url)
+    .await
+    .unwrap()
+    .header(ACCEPT, "text/event-stream")
+    .json(&CompleteTextParams {
+      text: "I feel hungry".to_string(),
+      completion_type: CompletionType::ImproveWriting,
+    })
+    .send()
+    .await
+    .unwrap();
+  assert_eq!(
+    resp.headers().get(CONTENT_TYPE).unwrap(),
+    "text/event-stream"
+  );
+  let body = resp.text().await.unwrap();
+  assert!(body.starts_with("data: "));
+  assert!(body.ends_with("event: done\ndata: \n\n"));
+}
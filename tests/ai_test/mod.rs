@@ -1,5 +1,9 @@
+mod ai_usage;
 mod chat_test;
 mod complete_text;
 // mod local_ai_test;
+mod summarize_document;
 mod summarize_row;
+mod translate_document;
 mod util;
+mod workspace_chat;
@@ -0,0 +1,68 @@
+use collab::preclude::Collab;
+use collab_document::document::Document;
+use collab_entity::CollabType;
+use database_entity::dto::CreateCollabParams;
+use shared_entity::dto::ai_dto::SummarizeDocumentParams;
+
+use app_error::ErrorCode;
+use client_api_test::{local_ai_test_enabled, TestClient};
+use workspace_template::document::getting_started::getting_started_document_data;
+
+#[tokio::test]
+async fn summarize_document_test() {
+  if !local_ai_test_enabled() {
+    return;
+  }
+  let test_client = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = test_client.workspace_id().await;
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let encoded_collab = {
+    let collab = Collab::new(
+      test_client.uid().await,
+      object_id.clone(),
+      test_client.device_id.clone(),
+      vec![],
+      false,
+    );
+    let document =
+      Document::create_with_data(collab, getting_started_document_data().unwrap()).unwrap();
+    document.encode_collab().unwrap()
+  };
+  test_client
+    .api_client
+    .create_collab(CreateCollabParams {
+      object_id: object_id.clone(),
+      encoded_collab_v1: encoded_collab.encode_to_bytes().unwrap(),
+      collab_type: CollabType::Document,
+      workspace_id: workspace_id.clone(),
+    })
+    .await
+    .unwrap();
+
+  let resp = test_client
+    .api_client
+    .summarize_document(
+      &workspace_id,
+      SummarizeDocumentParams {
+        object_id: object_id.clone(),
+        max_words: Some(50),
+      },
+    )
+    .await
+    .unwrap();
+  assert!(!resp.summary.is_empty());
+  assert!(!resp.truncated);
+
+  let error = test_client
+    .api_client
+    .summarize_document(
+      &workspace_id,
+      SummarizeDocumentParams {
+        object_id,
+        max_words: Some(0),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+}
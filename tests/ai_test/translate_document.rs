@@ -0,0 +1,89 @@
+use app_error::ErrorCode;
+use client_api_test::{local_ai_test_enabled, TestClient};
+use shared_entity::dto::ai_dto::TranslateDocumentParams;
+
+#[tokio::test]
+async fn translate_document_test() {
+  if !local_ai_test_enabled() {
+    return;
+  }
+  let test_client = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = test_client.workspace_id().await;
+  let folder_view = test_client
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let getting_started = general
+    .children
+    .iter()
+    .find(|v| v.name == "Getting started")
+    .unwrap();
+
+  let resp = test_client
+    .api_client
+    .translate_document(
+      &workspace_id,
+      TranslateDocumentParams {
+        object_id: getting_started.view_id.clone(),
+        target_language: "French".to_string(),
+        create_view: false,
+      },
+    )
+    .await
+    .unwrap();
+  assert!(!resp.markdown.is_empty());
+  assert!(resp.view_id.is_none());
+
+  // the translation is put in a new view right after the document
+  let resp = test_client
+    .api_client
+    .translate_document(
+      &workspace_id,
+      TranslateDocumentParams {
+        object_id: getting_started.view_id.clone(),
+        target_language: "French".to_string(),
+        create_view: true,
+      },
+    )
+    .await
+    .unwrap();
+  let view_id = resp.view_id.unwrap();
+  let folder_view = test_client
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let index = general
+    .children
+    .iter()
+    .position(|v| v.view_id == getting_started.view_id)
+    .unwrap();
+  let translation = &general.children[index + 1];
+  assert_eq!(translation.view_id, view_id);
+  assert_eq!(translation.name, "Getting started (French)");
+
+  let error = test_client
+    .api_client
+    .translate_document(
+      &workspace_id,
+      TranslateDocumentParams {
+        object_id: getting_started.view_id.clone(),
+        target_language: " ".to_string(),
+        create_view: false,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+}
@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use client_api::entity::QuestionStreamValue;
+use collab::preclude::Collab;
+use collab_document::document::Document;
+use collab_entity::CollabType;
+use futures_util::StreamExt;
+use shared_entity::dto::ai_dto::{WorkspaceChatParams, WorkspaceChatSource};
+use tokio::time::sleep;
+
+use client_api_test::{local_ai_test_enabled, TestClient};
+use workspace_template::document::getting_started::getting_started_document_data;
+
+#[ignore]
+#[tokio::test]
+async fn workspace_chat_test() {
+  if !local_ai_test_enabled() {
+    return;
+  }
+  let mut test_client = TestClient::new_user().await;
+  let workspace_id = test_client.workspace_id().await;
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let encoded_collab = {
+    let collab = Collab::new(
+      test_client.uid().await,
+      object_id.clone(),
+      test_client.device_id.clone(),
+      vec![],
+      false,
+    );
+    let document =
+      Document::create_with_data(collab, getting_started_document_data().unwrap()).unwrap();
+    document.encode_collab().unwrap()
+  };
+  test_client
+    .create_and_edit_collab_with_data(
+      &object_id,
+      &workspace_id,
+      CollabType::Document,
+      Some(encoded_collab),
+    )
+    .await;
+  test_client
+    .open_collab(&workspace_id, &object_id, CollabType::Document)
+    .await;
+  // wait for the document to be indexed
+  sleep(Duration::from_millis(2000)).await;
+
+  let mut stream = test_client
+    .api_client
+    .stream_workspace_chat(
+      &workspace_id,
+      WorkspaceChatParams {
+        question: "What is AppFlowy?".to_string(),
+      },
+    )
+    .await
+    .unwrap();
+  let mut sources = vec![];
+  let mut answer = String::new();
+  while let Some(value) = stream.next().await {
+    match value.unwrap() {
+      QuestionStreamValue::Metadata { value } => {
+        sources = serde_json::from_value::<Vec<WorkspaceChatSource>>(value).unwrap();
+      },
+      QuestionStreamValue::Answer { value } => answer.push_str(&value),
+    }
+  }
+  assert_eq!(sources.len(), 1);
+  assert_eq!(sources[0].view_id, object_id);
+  assert!(!answer.is_empty());
+
+  // the users outside of the workspace are not given its documents
+  let other_client = TestClient::new_user_without_ws_conn().await;
+  let result = other_client
+    .api_client
+    .stream_workspace_chat(
+      &workspace_id,
+      WorkspaceChatParams {
+        question: "What is AppFlowy?".to_string(),
+      },
+    )
+    .await;
+  if let Ok(mut stream) = result {
+    while let Some(Ok(value)) = stream.next().await {
+      if let QuestionStreamValue::Metadata { value } = value {
+        let sources = serde_json::from_value::<Vec<WorkspaceChatSource>>(value).unwrap();
+        assert!(sources.is_empty());
+      }
+    }
+  }
+
+  // the question is empty
+  let result = test_client
+    .api_client
+    .stream_workspace_chat(
+      &workspace_id,
+      WorkspaceChatParams {
+        question: " ".to_string(),
+      },
+    )
+    .await;
+  assert!(result.is_err());
+}
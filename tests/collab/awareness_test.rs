@@ -1,9 +1,12 @@
 use std::time::Duration;
 
+use app_error::ErrorCode;
 use collab_entity::CollabType;
+use collab_rt_entity::user::{AFCollabPresenceChange, UserMessage};
+use tokio::sync::broadcast::Receiver;
 use tokio::time::sleep;
 
-use client_api_test::TestClient;
+use client_api_test::{generate_unique_registered_user_client, TestClient};
 use database_entity::dto::{AFAccessLevel, AFRole};
 
 #[tokio::test]
@@ -70,6 +73,90 @@ async fn viewing_document_editing_users_test() {
   assert_num_connected_client_within_secs(&owner, &object_id, 2, 30).await;
 }
 
+#[tokio::test]
+async fn collab_presence_test() {
+  let collab_type = CollabType::Unknown;
+  let mut owner = TestClient::new_user().await;
+  let mut guest = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &guest, AFRole::Member)
+    .await
+    .unwrap();
+  let object_id = owner
+    .create_and_edit_collab(&workspace_id, collab_type.clone())
+    .await;
+  owner
+    .add_collab_member(
+      &workspace_id,
+      &object_id,
+      &guest,
+      AFAccessLevel::ReadAndWrite,
+    )
+    .await;
+  let mut user_change_recv = owner.ws_client.subscribe_user_changed();
+
+  // the owner is told when the guest opens the collab
+  guest
+    .open_collab(&workspace_id, &object_id, collab_type)
+    .await;
+  guest.wait_object_sync_complete(&object_id).await.unwrap();
+  let owner_uid = owner.uid().await;
+  let guest_uid = guest.uid().await;
+  let change = recv_presence_change(&mut user_change_recv).await;
+  assert_eq!(change.object_id, object_id);
+  assert_eq!(change.uid, guest_uid);
+  assert!(change.is_present);
+
+  let presences = owner
+    .api_client
+    .get_collab_presences(&workspace_id, &object_id)
+    .await
+    .unwrap()
+    .presences;
+  let mut uids = presences.iter().map(|p| p.uid).collect::<Vec<_>>();
+  uids.sort();
+  let mut expected_uids = vec![owner_uid, guest_uid];
+  expected_uids.sort();
+  assert_eq!(uids, expected_uids);
+
+  // and when the guest leaves it
+  guest.disconnect().await;
+  let change = recv_presence_change(&mut user_change_recv).await;
+  assert_eq!(change.uid, guest_uid);
+  assert!(!change.is_present);
+  let presences = owner
+    .api_client
+    .get_collab_presences(&workspace_id, &object_id)
+    .await
+    .unwrap()
+    .presences;
+  assert_eq!(
+    presences.iter().map(|p| p.uid).collect::<Vec<_>>(),
+    vec![owner_uid]
+  );
+
+  // only the users that can read the collab can see who is on it
+  let (outsider, _) = generate_unique_registered_user_client().await;
+  let err = outsider
+    .get_collab_presences(&workspace_id, &object_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+async fn recv_presence_change(recv: &mut Receiver<UserMessage>) -> AFCollabPresenceChange {
+  tokio::time::timeout(Duration::from_secs(10), async {
+    loop {
+      if let UserMessage::CollabPresenceChange(change) = recv.recv().await.unwrap() {
+        return change;
+      }
+    }
+  })
+  .await
+  .unwrap()
+}
+
 async fn assert_num_connected_client_within_secs(
   client: &TestClient,
   object_id: &str,
@@ -0,0 +1,182 @@
+use crate::collab::util::test_encode_collab_v1;
+use app_error::ErrorCode;
+use client_api_test::{generate_unique_registered_user_client, workspace_id_from_client};
+use collab_entity::CollabType;
+use database_entity::dto::{CreateCollabCommentParams, CreateCollabParams};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn collab_comment_thread_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  let profile = c.get_profile().await.unwrap();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world")
+    .encode_to_bytes()
+    .unwrap();
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    encoded_collab_v1: encode_collab,
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+  })
+  .await
+  .unwrap();
+
+  // Only members of the workspace are recorded as mentioned
+  let (outsider, _) = generate_unique_registered_user_client().await;
+  let outsider_uuid = outsider.get_profile().await.unwrap().uuid;
+  let comment = c
+    .create_collab_comment(
+      &workspace_id,
+      &object_id,
+      &CreateCollabCommentParams {
+        block_id: "block_1".to_string(),
+        content: format!("@{} @{} please check", profile.uuid, outsider_uuid),
+        reply_comment_id: None,
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(comment.mentions.len(), 1);
+  assert_eq!(comment.mentions[0].uuid, profile.uuid);
+  assert_eq!(comment.created_by.as_ref().unwrap().uuid, profile.uuid);
+
+  // A reply to a reply belongs to the same thread
+  let reply = c
+    .create_collab_comment(
+      &workspace_id,
+      &object_id,
+      &CreateCollabCommentParams {
+        block_id: "block_1".to_string(),
+        content: "done".to_string(),
+        reply_comment_id: Some(comment.comment_id),
+      },
+    )
+    .await
+    .unwrap();
+  let nested_reply = c
+    .create_collab_comment(
+      &workspace_id,
+      &object_id,
+      &CreateCollabCommentParams {
+        block_id: "block_1".to_string(),
+        content: "thanks".to_string(),
+        reply_comment_id: Some(reply.comment_id),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(nested_reply.reply_comment_id, Some(comment.comment_id));
+
+  // Replies are anchored to the block of the thread
+  let err = c
+    .create_collab_comment(
+      &workspace_id,
+      &object_id,
+      &CreateCollabCommentParams {
+        block_id: "block_2".to_string(),
+        content: "elsewhere".to_string(),
+        reply_comment_id: Some(comment.comment_id),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  c.create_collab_comment(
+    &workspace_id,
+    &object_id,
+    &CreateCollabCommentParams {
+      block_id: "block_2".to_string(),
+      content: "another thread".to_string(),
+      reply_comment_id: None,
+    },
+  )
+  .await
+  .unwrap();
+  let comments = c
+    .get_collab_comments(&workspace_id, &object_id, Some("block_1".to_string()))
+    .await
+    .unwrap()
+    .comments;
+  assert_eq!(comments.len(), 3);
+  let comments = c
+    .get_collab_comments(&workspace_id, &object_id, None)
+    .await
+    .unwrap()
+    .comments;
+  assert_eq!(comments.len(), 4);
+
+  let resolved = c
+    .resolve_collab_comment(&workspace_id, &object_id, &comment.comment_id, true)
+    .await
+    .unwrap();
+  assert!(resolved.resolved_at.is_some());
+  assert_eq!(resolved.resolved_by.unwrap().uuid, profile.uuid);
+  let err = c
+    .resolve_collab_comment(&workspace_id, &object_id, &reply.comment_id, true)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  let reopened = c
+    .resolve_collab_comment(&workspace_id, &object_id, &comment.comment_id, false)
+    .await
+    .unwrap();
+  assert!(reopened.resolved_at.is_none());
+
+  // Deleted comments are kept without their content, so the thread stays together
+  c.delete_collab_comment(&workspace_id, &object_id, &comment.comment_id)
+    .await
+    .unwrap();
+  let comments = c
+    .get_collab_comments(&workspace_id, &object_id, Some("block_1".to_string()))
+    .await
+    .unwrap()
+    .comments;
+  let deleted = comments
+    .iter()
+    .find(|item| item.comment_id == comment.comment_id)
+    .unwrap();
+  assert!(deleted.is_deleted);
+  assert!(deleted.content.is_empty());
+  assert!(deleted.mentions.is_empty());
+}
+
+#[tokio::test]
+async fn collab_comment_requires_access_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world")
+    .encode_to_bytes()
+    .unwrap();
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    encoded_collab_v1: encode_collab,
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+  })
+  .await
+  .unwrap();
+
+  let (outsider, _) = generate_unique_registered_user_client().await;
+  let err = outsider
+    .get_collab_comments(&workspace_id, &object_id, None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = outsider
+    .create_collab_comment(
+      &workspace_id,
+      &object_id,
+      &CreateCollabCommentParams {
+        block_id: "block_1".to_string(),
+        content: "hello".to_string(),
+        reply_comment_id: None,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
@@ -0,0 +1,81 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use collab_entity::CollabType;
+use database_entity::dto::{AFRole, CreateCollabParams};
+use uuid::Uuid;
+
+use crate::collab::util::test_encode_collab_v1;
+
+fn update_params(workspace_id: &str, object_id: &str, title: &str) -> CreateCollabParams {
+  CreateCollabParams {
+    object_id: object_id.to_string(),
+    encoded_collab_v1: test_encode_collab_v1(object_id, "title", title)
+      .encode_to_bytes()
+      .unwrap(),
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.to_string(),
+  }
+}
+
+#[tokio::test]
+async fn collab_contributors_test() {
+  let owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let object_id = Uuid::new_v4().to_string();
+  owner
+    .api_client
+    .create_collab(update_params(&workspace_id, &object_id, "first"))
+    .await
+    .unwrap();
+  let contributors = owner
+    .api_client
+    .get_collab_contributors(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  assert!(contributors.last_edited_by.is_none());
+  assert!(contributors.contributors.is_empty());
+
+  for title in ["second", "third"] {
+    owner
+      .api_client
+      .update_collab(update_params(&workspace_id, &object_id, title))
+      .await
+      .unwrap();
+  }
+  member
+    .api_client
+    .update_collab(update_params(&workspace_id, &object_id, "fourth"))
+    .await
+    .unwrap();
+
+  let contributors = member
+    .api_client
+    .get_collab_contributors(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  let member_uid = member.uid().await;
+  let owner_uid = owner.uid().await;
+  assert_eq!(contributors.last_edited_by.unwrap().uid, member_uid);
+  assert_eq!(contributors.contributors.len(), 2);
+  assert_eq!(contributors.contributors[0].uid, member_uid);
+  assert_eq!(contributors.contributors[0].edit_count, 1);
+  assert_eq!(contributors.contributors[1].uid, owner_uid);
+  assert_eq!(contributors.contributors[1].edit_count, 2);
+  assert!(
+    contributors.contributors[1].first_edited_at <= contributors.contributors[1].last_edited_at
+  );
+
+  // Only the users that can read the collab can see who edited it
+  let outsider = TestClient::new_user().await;
+  let err = outsider
+    .api_client
+    .get_collab_contributors(&workspace_id, &object_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
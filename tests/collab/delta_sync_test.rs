@@ -0,0 +1,239 @@
+use collab::core::collab::DataSource;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
+use collab_entity::CollabType;
+use database_entity::dto::{CreateCollabParams, QueryCollabParams};
+use serde_json::json;
+use shared_entity::dto::workspace_dto::CollabDeltaSyncParams;
+use sqlx::types::Uuid;
+use yrs::updates::decoder::Decode;
+use yrs::updates::encoder::Encode;
+use yrs::{ReadTxn, StateVector, Update};
+
+use app_error::ErrorCode;
+use client_api_test::*;
+
+use crate::collab::util::test_encode_collab_v1;
+
+fn local_collab(object_id: &str, doc_state: Vec<u8>) -> Collab {
+  Collab::new_with_source(
+    CollabOrigin::Empty,
+    object_id,
+    DataSource::DocStateV1(doc_state),
+    vec![],
+    false,
+  )
+  .unwrap()
+}
+
+fn state_vector(collab: &Collab) -> StateVector {
+  collab.transact().state_vector()
+}
+
+#[tokio::test]
+async fn delta_sync_collab_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: test_encode_collab_v1(&object_id, "title", "first")
+      .encode_to_bytes()
+      .unwrap(),
+  })
+  .await
+  .unwrap();
+  let doc_state = c
+    .get_collab(QueryCollabParams::new(
+      &object_id,
+      CollabType::Unknown,
+      &workspace_id,
+    ))
+    .await
+    .unwrap()
+    .encode_collab
+    .doc_state
+    .to_vec();
+
+  // two devices edit their own copy of the collab while offline
+  let mut device_a = local_collab(&object_id, doc_state.clone());
+  let mut device_b = local_collab(&object_id, doc_state);
+  let synced_a = state_vector(&device_a);
+  let synced_b = state_vector(&device_b);
+  device_a.insert("a", "1");
+  device_b.insert("b", "2");
+
+  let update_b = device_b.transact().encode_state_as_update_v1(&synced_b);
+  c.delta_sync_collab(
+    &workspace_id,
+    &object_id,
+    CollabDeltaSyncParams {
+      collab_type: CollabType::Unknown,
+      state_vector: synced_b.encode_v1(),
+      update: Some(update_b),
+    },
+  )
+  .await
+  .unwrap();
+
+  // the second device to sync receives the changes of the first one
+  let update_a = device_a.transact().encode_state_as_update_v1(&synced_a);
+  let response = c
+    .delta_sync_collab(
+      &workspace_id,
+      &object_id,
+      CollabDeltaSyncParams {
+        collab_type: CollabType::Unknown,
+        state_vector: synced_a.encode_v1(),
+        update: Some(update_a),
+      },
+    )
+    .await
+    .unwrap();
+  {
+    let mut txn = device_a.context.transact_mut();
+    txn
+      .apply_update(Update::decode_v1(&response.missing_update).unwrap())
+      .unwrap();
+  }
+  let expected = json!({"title": "first", "a": "1", "b": "2"});
+  assert_eq!(device_a.to_json_value(), expected);
+  assert_eq!(
+    StateVector::decode_v1(&response.state_vector).unwrap(),
+    state_vector(&device_a)
+  );
+
+  let doc_state = c
+    .get_collab(QueryCollabParams::new(
+      &object_id,
+      CollabType::Unknown,
+      &workspace_id,
+    ))
+    .await
+    .unwrap()
+    .encode_collab
+    .doc_state
+    .to_vec();
+  assert_eq!(
+    local_collab(&object_id, doc_state).to_json_value(),
+    expected
+  );
+
+  // nothing is missing once in sync
+  let response = c
+    .delta_sync_collab(
+      &workspace_id,
+      &object_id,
+      CollabDeltaSyncParams {
+        collab_type: CollabType::Unknown,
+        state_vector: response.state_vector,
+        update: None,
+      },
+    )
+    .await
+    .unwrap();
+  let update = Update::decode_v1(&response.missing_update).unwrap();
+  assert!(update.state_vector().is_empty());
+}
+
+#[tokio::test]
+async fn delta_sync_collab_with_invalid_state_vector_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: test_encode_collab_v1(&object_id, "title", "first")
+      .encode_to_bytes()
+      .unwrap(),
+  })
+  .await
+  .unwrap();
+
+  let err = c
+    .delta_sync_collab(
+      &workspace_id,
+      &object_id,
+      CollabDeltaSyncParams {
+        collab_type: CollabType::Unknown,
+        state_vector: vec![255, 255, 255],
+        update: None,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn concurrent_delta_sync_collab_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: test_encode_collab_v1(&object_id, "title", "first")
+      .encode_to_bytes()
+      .unwrap(),
+  })
+  .await
+  .unwrap();
+  let doc_state = c
+    .get_collab(QueryCollabParams::new(
+      &object_id,
+      CollabType::Unknown,
+      &workspace_id,
+    ))
+    .await
+    .unwrap()
+    .encode_collab
+    .doc_state
+    .to_vec();
+
+  // every device syncs its own edit at the same time, none of them is lost
+  let mut expected = json!({"title": "first"});
+  let mut params = vec![];
+  for i in 0..5 {
+    let mut device = local_collab(&object_id, doc_state.clone());
+    let synced = state_vector(&device);
+    let key = format!("key_{}", i);
+    device.insert(&key, i.to_string());
+    expected[key] = json!(i.to_string());
+    params.push(CollabDeltaSyncParams {
+      collab_type: CollabType::Unknown,
+      state_vector: synced.encode_v1(),
+      update: Some(device.transact().encode_state_as_update_v1(&synced)),
+    });
+  }
+  let results = futures::future::join_all(
+    params
+      .into_iter()
+      .map(|params| c.delta_sync_collab(&workspace_id, &object_id, params)),
+  )
+  .await;
+  for result in results {
+    result.unwrap();
+  }
+
+  let doc_state = c
+    .get_collab(QueryCollabParams::new(
+      &object_id,
+      CollabType::Unknown,
+      &workspace_id,
+    ))
+    .await
+    .unwrap()
+    .encode_collab
+    .doc_state
+    .to_vec();
+  assert_eq!(
+    local_collab(&object_id, doc_state).to_json_value(),
+    expected
+  );
+}
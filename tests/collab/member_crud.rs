@@ -1,10 +1,14 @@
 use crate::collab::util::test_encode_collab_v1;
-use client_api_test::{generate_unique_registered_user_client, workspace_id_from_client};
+use app_error::ErrorCode;
+use client_api_test::{
+  generate_unique_registered_user_client, localhost_client, workspace_id_from_client, TestClient,
+};
 
 use collab_entity::CollabType;
 use database_entity::dto::{
-  AFAccessLevel, CollabMemberIdentify, CreateCollabParams, InsertCollabMemberParams,
-  QueryCollabMembers, UpdateCollabMemberParams,
+  AFAccessLevel, AFRole, AFWorkspaceSettingsChange, AccessSource, CollabAccessPolicy,
+  CollabMemberIdentify, CreateCollabMemberResult, CreateCollabParams, EnsureCollabMemberOutcome,
+  InsertCollabMemberParams, QueryCollabMembers, SharePermission, UpdateCollabMemberParams,
 };
 use uuid::Uuid;
 
@@ -63,6 +67,8 @@ async fn update_collab_member_permission_test() {
     workspace_id: workspace_id.clone(),
     object_id: object_id.clone(),
     access_level: AFAccessLevel::ReadOnly,
+    expires_at: None,
+    group_id: None,
   })
   .await
   .unwrap();
@@ -106,6 +112,8 @@ async fn add_collab_member_test() {
       workspace_id: workspace_id.clone(),
       object_id: object_id.clone(),
       access_level: AFAccessLevel::ReadAndComment,
+      expires_at: None,
+      group_id: None,
     })
     .await
     .unwrap();
@@ -152,6 +160,8 @@ async fn add_collab_member_then_remove_test() {
       workspace_id: workspace_id.clone(),
       object_id: object_id.clone(),
       access_level: AFAccessLevel::ReadAndComment,
+      expires_at: None,
+      group_id: None,
     })
     .await
     .unwrap();
@@ -159,10 +169,11 @@ async fn add_collab_member_then_remove_test() {
     .get_collab_members(QueryCollabMembers {
       workspace_id: workspace_id.clone(),
       object_id: object_id.clone(),
+      ..Default::default()
     })
     .await
     .unwrap()
-    .0;
+    .members;
   assert_eq!(members.len(), 2);
 
   // Delete the member
@@ -178,9 +189,1293 @@ async fn add_collab_member_then_remove_test() {
     .get_collab_members(QueryCollabMembers {
       workspace_id,
       object_id,
+      ..Default::default()
     })
     .await
     .unwrap()
-    .0;
+    .members;
   assert_eq!(members.len(), 1);
 }
+
+#[tokio::test]
+async fn collab_object_token_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world")
+    .encode_to_bytes()
+    .unwrap();
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    encoded_collab_v1: encode_collab,
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+  })
+  .await
+  .unwrap();
+
+  let token = c
+    .create_collab_object_token(&workspace_id, &object_id, AFAccessLevel::ReadOnly, None)
+    .await
+    .unwrap();
+  assert_eq!(token.access_level, AFAccessLevel::ReadOnly);
+
+  let guest_client = localhost_client();
+  let collab = guest_client
+    .get_collab_with_object_token(&token.token, &object_id, CollabType::Unknown)
+    .await
+    .unwrap();
+  assert_eq!(collab.object_id, object_id);
+
+  // a token is only valid for its own object
+  let err = guest_client
+    .get_collab_with_object_token(
+      &token.token,
+      &Uuid::new_v4().to_string(),
+      CollabType::Unknown,
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  c.revoke_collab_object_token(&workspace_id, &object_id, &token.id)
+    .await
+    .unwrap();
+  let err = guest_client
+    .get_collab_with_object_token(&token.token, &object_id, CollabType::Unknown)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+}
+
+#[tokio::test]
+async fn collab_share_link_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let (other, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world")
+    .encode_to_bytes()
+    .unwrap();
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    encoded_collab_v1: encode_collab,
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+  })
+  .await
+  .unwrap();
+
+  let link = c
+    .create_collab_share_link(&workspace_id, &object_id, SharePermission::View, None, None)
+    .await
+    .unwrap();
+  assert_eq!(link.permission, SharePermission::View);
+  assert_eq!(link.object_id, object_id);
+
+  let guest_client = localhost_client();
+  let collab = guest_client
+    .get_collab_with_share_link(&link.token, CollabType::Unknown)
+    .await
+    .unwrap();
+  assert_eq!(collab.object_id, object_id);
+
+  let link = c
+    .update_collab_share_link(
+      &workspace_id,
+      &object_id,
+      &link.id,
+      SharePermission::Edit,
+      None,
+      None,
+    )
+    .await
+    .unwrap();
+  assert_eq!(link.permission, SharePermission::Edit);
+  let links = c
+    .list_collab_share_links(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  assert_eq!(links.len(), 1);
+  assert_eq!(links[0].permission, SharePermission::Edit);
+
+  // only the users with full access to the collab can manage its links
+  let err = other
+    .create_collab_share_link(&workspace_id, &object_id, SharePermission::Edit, None, None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  let err = c
+    .create_collab_share_link(
+      &workspace_id,
+      &object_id,
+      SharePermission::View,
+      Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+      None,
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  c.delete_collab_share_link(&workspace_id, &object_id, &link.id)
+    .await
+    .unwrap();
+  let err = guest_client
+    .get_collab_with_share_link(&link.token, CollabType::Unknown)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+}
+
+#[tokio::test]
+async fn rotate_collab_share_link_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let (other, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world")
+    .encode_to_bytes()
+    .unwrap();
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    encoded_collab_v1: encode_collab,
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+  })
+  .await
+  .unwrap();
+
+  let expires_at = chrono::Utc::now() + chrono::Duration::days(1);
+  let old_link = c
+    .create_collab_share_link(
+      &workspace_id,
+      &object_id,
+      SharePermission::View,
+      Some(expires_at),
+      Some(5),
+    )
+    .await
+    .unwrap();
+  let new_link = c
+    .rotate_collab_share_link(&workspace_id, &object_id, &old_link.id)
+    .await
+    .unwrap();
+  assert_eq!(new_link.id, old_link.id);
+  assert_ne!(new_link.token, old_link.token);
+  assert_eq!(new_link.object_id, object_id);
+  assert_eq!(new_link.permission, SharePermission::View);
+  assert_eq!(new_link.expires_at, old_link.expires_at);
+  assert_eq!(new_link.max_uses, Some(5));
+
+  let guest_client = localhost_client();
+  let err = guest_client
+    .get_collab_with_share_link(&old_link.token, CollabType::Unknown)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+  let collab = guest_client
+    .get_collab_with_share_link(&new_link.token, CollabType::Unknown)
+    .await
+    .unwrap();
+  assert_eq!(collab.object_id, object_id);
+
+  // only the users with full access to the collab can rotate its links
+  let err = other
+    .rotate_collab_share_link(&workspace_id, &object_id, &old_link.id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn collab_share_link_usage_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world")
+    .encode_to_bytes()
+    .unwrap();
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    encoded_collab_v1: encode_collab,
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+  })
+  .await
+  .unwrap();
+
+  let limited_link = c
+    .create_collab_share_link(
+      &workspace_id,
+      &object_id,
+      SharePermission::View,
+      None,
+      Some(1),
+    )
+    .await
+    .unwrap();
+  let expiring_link = c
+    .create_collab_share_link(
+      &workspace_id,
+      &object_id,
+      SharePermission::Comment,
+      Some(chrono::Utc::now() + chrono::Duration::days(1)),
+      None,
+    )
+    .await
+    .unwrap();
+
+  // reading the object does not count as a use of the link, only joining the object does
+  let guest_client = localhost_client();
+  for _ in 0..2 {
+    guest_client
+      .get_collab_with_share_link(&limited_link.token, CollabType::Unknown)
+      .await
+      .unwrap();
+  }
+  let live_links = c.list_workspace_share_links(&workspace_id).await.unwrap();
+  assert_eq!(live_links.len(), 2);
+  assert!(live_links.iter().all(|link| link.use_count == 0));
+  assert!(live_links.iter().all(|link| link.last_used_at.is_none()));
+
+  c.delete_all_collab_share_links(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  let links = c
+    .list_collab_share_links(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  assert!(links.is_empty());
+  let err = guest_client
+    .get_collab_with_share_link(&expiring_link.token, CollabType::Unknown)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+}
+
+#[tokio::test]
+async fn collab_sharing_state_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  let uid = c.get_profile().await.unwrap().uid;
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world")
+    .encode_to_bytes()
+    .unwrap();
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    encoded_collab_v1: encode_collab,
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+  })
+  .await
+  .unwrap();
+  c.create_collab_object_token(&workspace_id, &object_id, AFAccessLevel::ReadOnly, None)
+    .await
+    .unwrap();
+
+  let sharing_state = c
+    .get_collab_sharing_state(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  assert_eq!(sharing_state.members.len(), 1);
+  assert_eq!(sharing_state.members[0].uid, uid);
+  assert!(sharing_state.members[0].user.is_some());
+  assert!(sharing_state.inherited_members.is_empty());
+  assert_eq!(sharing_state.links.len(), 1);
+  assert!(sharing_state.publish_info.is_none());
+  assert_eq!(
+    sharing_state.caller_access_level,
+    Some(AFAccessLevel::FullAccess)
+  );
+}
+
+#[tokio::test]
+async fn export_collab_members_by_access_level_test() {
+  let (c_1, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c_1).await;
+  let uid_1 = c_1.get_profile().await.unwrap().uid;
+  let (c_2, _user) = generate_unique_registered_user_client().await;
+  let uid_2 = c_2.get_profile().await.unwrap().uid;
+
+  let shared_object_id = Uuid::new_v4().to_string();
+  let private_object_id = Uuid::new_v4().to_string();
+  for object_id in [&shared_object_id, &private_object_id] {
+    let encode_collab = test_encode_collab_v1(object_id, "title", "hello world");
+    c_1
+      .create_collab(CreateCollabParams {
+        object_id: object_id.clone(),
+        encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+        collab_type: CollabType::Unknown,
+        workspace_id: workspace_id.clone(),
+      })
+      .await
+      .unwrap();
+  }
+  c_1
+    .add_collab_member(InsertCollabMemberParams {
+      uid: uid_2,
+      workspace_id: workspace_id.clone(),
+      object_id: shared_object_id.clone(),
+      access_level: AFAccessLevel::ReadAndComment,
+      expires_at: None,
+      group_id: None,
+    })
+    .await
+    .unwrap();
+
+  let export = c_1
+    .export_workspace_collab_members(&workspace_id, None)
+    .await
+    .unwrap();
+  let shared = export
+    .objects
+    .iter()
+    .find(|item| item.object_id == shared_object_id)
+    .unwrap();
+  assert_eq!(shared.members.len(), 2);
+
+  // only the owner holds full access, on both objects
+  let export = c_1
+    .export_workspace_collab_members(&workspace_id, Some(AFAccessLevel::FullAccess))
+    .await
+    .unwrap();
+  for object_id in [&shared_object_id, &private_object_id] {
+    let item = export
+      .objects
+      .iter()
+      .find(|item| &item.object_id == object_id)
+      .unwrap();
+    assert_eq!(item.members.len(), 1);
+    assert_eq!(item.members[0].uid, uid_1);
+  }
+
+  // only the workspace owner can export the members
+  let result = c_2
+    .export_workspace_collab_members(&workspace_id, None)
+    .await;
+  assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn check_membership_test() {
+  let (c_1, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c_1).await;
+
+  let first_object_id = Uuid::new_v4().to_string();
+  let second_object_id = Uuid::new_v4().to_string();
+  for object_id in [&first_object_id, &second_object_id] {
+    let encode_collab = test_encode_collab_v1(object_id, "title", "hello world");
+    c_1
+      .create_collab(CreateCollabParams {
+        object_id: object_id.clone(),
+        encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+        collab_type: CollabType::Unknown,
+        workspace_id: workspace_id.clone(),
+      })
+      .await
+      .unwrap();
+  }
+
+  let levels = c_1
+    .check_membership(
+      &workspace_id,
+      vec![first_object_id.clone(), second_object_id.clone()],
+    )
+    .await
+    .unwrap();
+  assert_eq!(levels[&first_object_id], Some(AFAccessLevel::FullAccess));
+  assert_eq!(levels[&second_object_id], Some(AFAccessLevel::FullAccess));
+
+  let unknown_object_id = Uuid::new_v4().to_string();
+  let levels = c_1
+    .check_membership(&workspace_id, vec![unknown_object_id.clone()])
+    .await
+    .unwrap();
+  assert_eq!(levels.len(), 1);
+  assert_eq!(levels[&unknown_object_id], None);
+}
+
+#[tokio::test]
+async fn explain_effective_access_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let uid = c.get_profile().await.unwrap().uid;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world");
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+  })
+  .await
+  .unwrap();
+
+  let access = c
+    .get_effective_access(&workspace_id, &object_id, None, None, false)
+    .await
+    .unwrap();
+  assert_eq!(access.uid, uid);
+  assert_eq!(access.access_level, Some(AFAccessLevel::FullAccess));
+  assert!(access.explanation.is_none());
+
+  // the direct membership grants more than the token, so it wins
+  let token = c
+    .create_collab_object_token(&workspace_id, &object_id, AFAccessLevel::ReadOnly, None)
+    .await
+    .unwrap();
+  let access = c
+    .get_effective_access(&workspace_id, &object_id, None, Some(&token.token), true)
+    .await
+    .unwrap();
+  let steps = access.explanation.unwrap();
+  assert_eq!(steps.len(), 3);
+  assert_eq!(steps[0].source, AccessSource::Direct);
+  assert_eq!(steps[0].access_level, Some(AFAccessLevel::FullAccess));
+  assert!(steps[0].won);
+  // the workspace role grants as much, but the membership was evaluated first
+  assert_eq!(
+    steps[1].source,
+    AccessSource::WorkspaceRole {
+      role: AFRole::Owner
+    }
+  );
+  assert_eq!(steps[1].access_level, Some(AFAccessLevel::FullAccess));
+  assert!(!steps[1].won);
+  assert_eq!(
+    steps[2].source,
+    AccessSource::ObjectToken {
+      token_id: Some(token.id)
+    }
+  );
+  assert_eq!(steps[2].access_level, Some(AFAccessLevel::ReadOnly));
+  assert!(!steps[2].won);
+}
+
+#[tokio::test]
+async fn effective_access_includes_workspace_role_and_group_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let member_uid = member.uid().await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world");
+  owner
+    .api_client
+    .create_collab(CreateCollabParams {
+      object_id: object_id.clone(),
+      encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+      collab_type: CollabType::Unknown,
+      workspace_id: workspace_id.clone(),
+    })
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .add_collab_member(InsertCollabMemberParams {
+      uid: member_uid,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      access_level: AFAccessLevel::ReadOnly,
+      expires_at: None,
+      group_id: None,
+    })
+    .await
+    .unwrap();
+
+  // the role of a member grants more than its read only membership
+  let access = member
+    .api_client
+    .get_effective_access(&workspace_id, &object_id, None, None, true)
+    .await
+    .unwrap();
+  assert_eq!(access.access_level, Some(AFAccessLevel::ReadAndWrite));
+  let steps = access.explanation.unwrap();
+  assert_eq!(steps[0].source, AccessSource::Direct);
+  assert_eq!(steps[0].access_level, Some(AFAccessLevel::ReadOnly));
+  assert!(!steps[0].won);
+  let role_step = steps
+    .iter()
+    .find(|step| matches!(step.source, AccessSource::WorkspaceRole { .. }))
+    .unwrap();
+  assert_eq!(
+    role_step.source,
+    AccessSource::WorkspaceRole {
+      role: AFRole::Member
+    }
+  );
+  assert!(role_step.won);
+
+  // a group the member belongs to is granted full access on the object
+  let group = owner
+    .api_client
+    .create_workspace_group(&workspace_id, "editors")
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .add_workspace_group_member(&workspace_id, &group.group_id.to_string(), member_uid)
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .add_collab_member(InsertCollabMemberParams {
+      uid: 0,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      access_level: AFAccessLevel::FullAccess,
+      expires_at: None,
+      group_id: Some(group.group_id),
+    })
+    .await
+    .unwrap();
+  let access = member
+    .api_client
+    .get_effective_access(&workspace_id, &object_id, None, None, true)
+    .await
+    .unwrap();
+  assert_eq!(access.access_level, Some(AFAccessLevel::FullAccess));
+  let winners: Vec<_> = access
+    .explanation
+    .unwrap()
+    .into_iter()
+    .filter(|step| step.won)
+    .collect();
+  assert_eq!(winners.len(), 1);
+  assert_eq!(
+    winners[0].source,
+    AccessSource::Group {
+      group_id: group.group_id
+    }
+  );
+}
+
+#[tokio::test]
+async fn suspend_collab_member_test() {
+  let (c_1, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c_1).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world");
+  c_1
+    .create_collab(CreateCollabParams {
+      object_id: object_id.clone(),
+      encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+      collab_type: CollabType::Unknown,
+      workspace_id: workspace_id.clone(),
+    })
+    .await
+    .unwrap();
+
+  let (c_2, _user) = generate_unique_registered_user_client().await;
+  let uid_2 = c_2.get_profile().await.unwrap().uid;
+  c_1
+    .add_collab_member(InsertCollabMemberParams {
+      uid: uid_2,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      access_level: AFAccessLevel::ReadAndWrite,
+      expires_at: None,
+      group_id: None,
+    })
+    .await
+    .unwrap();
+
+  let identify = CollabMemberIdentify {
+    uid: uid_2,
+    workspace_id: workspace_id.clone(),
+    object_id: object_id.clone(),
+  };
+  // a member can not suspend a member with more access than itself
+  let uid_1 = c_1.get_profile().await.unwrap().uid;
+  let err = c_2
+    .suspend_collab_member(CollabMemberIdentify {
+      uid: uid_1,
+      ..identify.clone()
+    })
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  c_1.suspend_collab_member(identify.clone()).await.unwrap();
+
+  // the stored level is kept, but grants no access while suspended
+  let member = c_1.get_collab_member(identify.clone()).await.unwrap();
+  assert!(member.suspended);
+  assert_eq!(member.permission.access_level, AFAccessLevel::ReadAndWrite);
+  let access = c_1
+    .get_effective_access(&workspace_id, &object_id, Some(uid_2), None, false)
+    .await
+    .unwrap();
+  assert_eq!(access.access_level, None);
+
+  c_1.unsuspend_collab_member(identify.clone()).await.unwrap();
+  let member = c_1.get_collab_member(identify).await.unwrap();
+  assert!(!member.suspended);
+  let access = c_1
+    .get_effective_access(&workspace_id, &object_id, Some(uid_2), None, false)
+    .await
+    .unwrap();
+  assert_eq!(access.access_level, Some(AFAccessLevel::ReadAndWrite));
+}
+
+#[tokio::test]
+async fn add_collab_members_batch_test() {
+  let (c_1, _user) = generate_unique_registered_user_client().await;
+  let uid_1 = c_1.get_profile().await.unwrap().uid;
+  let workspace_id = workspace_id_from_client(&c_1).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world");
+  c_1
+    .create_collab(CreateCollabParams {
+      object_id: object_id.clone(),
+      encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+      collab_type: CollabType::Unknown,
+      workspace_id: workspace_id.clone(),
+    })
+    .await
+    .unwrap();
+
+  let (c_2, _user) = generate_unique_registered_user_client().await;
+  let uid_2 = c_2.get_profile().await.unwrap().uid;
+  let (c_3, _user) = generate_unique_registered_user_client().await;
+  let uid_3 = c_3.get_profile().await.unwrap().uid;
+
+  // the owner is already a member, the others are added
+  let params = [uid_1, uid_2, uid_3]
+    .into_iter()
+    .map(|uid| InsertCollabMemberParams {
+      uid,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      access_level: AFAccessLevel::ReadOnly,
+      expires_at: None,
+      group_id: None,
+    })
+    .collect();
+  let result = c_1
+    .add_collab_members_batch(&workspace_id, &object_id, params)
+    .await
+    .unwrap()
+    .0;
+  assert_eq!(result.len(), 3);
+  assert!(matches!(
+    result[&uid_1],
+    CreateCollabMemberResult::Failed { .. }
+  ));
+  assert_eq!(result[&uid_2], CreateCollabMemberResult::Created);
+  assert_eq!(result[&uid_3], CreateCollabMemberResult::Created);
+
+  let members = c_1
+    .get_collab_members(QueryCollabMembers {
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      ..Default::default()
+    })
+    .await
+    .unwrap()
+    .members;
+  assert_eq!(members.len(), 3);
+  let owner = members.iter().find(|member| member.uid == uid_1).unwrap();
+  assert_eq!(owner.permission.access_level, AFAccessLevel::FullAccess);
+
+  // a user can only appear once in a batch
+  let duplicated = vec![
+    InsertCollabMemberParams {
+      uid: uid_2,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      access_level: AFAccessLevel::ReadOnly,
+      expires_at: None,
+      group_id: None,
+    };
+    2
+  ];
+  let err = c_1
+    .add_collab_members_batch(&workspace_id, &object_id, duplicated)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  // the workspace of every member must match the one of the path
+  let (c_4, _user) = generate_unique_registered_user_client().await;
+  let uid_4 = c_4.get_profile().await.unwrap().uid;
+  let other_workspace = vec![InsertCollabMemberParams {
+    uid: uid_4,
+    workspace_id: Uuid::new_v4().to_string(),
+    object_id: object_id.clone(),
+    access_level: AFAccessLevel::ReadOnly,
+    expires_at: None,
+    group_id: None,
+  }];
+  let err = c_1
+    .add_collab_members_batch(&workspace_id, &object_id, other_workspace)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  // a member can not grant a higher access level than its own
+  let escalated = vec![InsertCollabMemberParams {
+    uid: uid_4,
+    workspace_id: workspace_id.clone(),
+    object_id: object_id.clone(),
+    access_level: AFAccessLevel::FullAccess,
+    expires_at: None,
+    group_id: None,
+  }];
+  let err = c_2
+    .add_collab_members_batch(&workspace_id, &object_id, escalated)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn merge_collab_members_test() {
+  let (c_1, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c_1).await;
+  let source_id = Uuid::new_v4().to_string();
+  let target_id = Uuid::new_v4().to_string();
+  for object_id in [&source_id, &target_id] {
+    let encode_collab = test_encode_collab_v1(object_id, "title", "hello world");
+    c_1
+      .create_collab(CreateCollabParams {
+        object_id: object_id.clone(),
+        encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+        collab_type: CollabType::Unknown,
+        workspace_id: workspace_id.clone(),
+      })
+      .await
+      .unwrap();
+  }
+
+  let (c_2, _user) = generate_unique_registered_user_client().await;
+  let uid_2 = c_2.get_profile().await.unwrap().uid;
+  let (c_3, _user) = generate_unique_registered_user_client().await;
+  let uid_3 = c_3.get_profile().await.unwrap().uid;
+  for (uid, object_id, access_level) in [
+    (uid_2, &source_id, AFAccessLevel::FullAccess),
+    (uid_3, &source_id, AFAccessLevel::ReadAndComment),
+    (uid_2, &target_id, AFAccessLevel::ReadOnly),
+  ] {
+    c_1
+      .add_collab_member(InsertCollabMemberParams {
+        uid,
+        workspace_id: workspace_id.clone(),
+        object_id: object_id.clone(),
+        access_level,
+        expires_at: None,
+        group_id: None,
+      })
+      .await
+      .unwrap();
+  }
+
+  // a read only member of the target can not grant the full access it holds on the source
+  let err = c_2
+    .merge_collab_members(&workspace_id, &target_id, &source_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  let merged = c_1
+    .merge_collab_members(&workspace_id, &target_id, &source_id)
+    .await
+    .unwrap();
+  assert_eq!(merged.added, vec![uid_3]);
+  assert_eq!(merged.upgraded, vec![uid_2]);
+  let members = c_1
+    .get_collab_members(QueryCollabMembers {
+      workspace_id: workspace_id.clone(),
+      object_id: target_id.clone(),
+      ..Default::default()
+    })
+    .await
+    .unwrap()
+    .members;
+  assert_eq!(members.len(), 3);
+  let access_level = |uid: i64| {
+    members
+      .iter()
+      .find(|member| member.uid == uid)
+      .unwrap()
+      .permission
+      .access_level
+  };
+  assert_eq!(access_level(uid_2), AFAccessLevel::FullAccess);
+  assert_eq!(access_level(uid_3), AFAccessLevel::ReadAndComment);
+
+  let err = c_1
+    .merge_collab_members(&workspace_id, &target_id, &target_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn query_collab_members_page_test() {
+  let (c_1, _user) = generate_unique_registered_user_client().await;
+  let uid_1 = c_1.get_profile().await.unwrap().uid;
+  let workspace_id = workspace_id_from_client(&c_1).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world");
+  c_1
+    .create_collab(CreateCollabParams {
+      object_id: object_id.clone(),
+      encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+      collab_type: CollabType::Unknown,
+      workspace_id: workspace_id.clone(),
+    })
+    .await
+    .unwrap();
+
+  let (c_2, user_2) = generate_unique_registered_user_client().await;
+  let uid_2 = c_2.get_profile().await.unwrap().uid;
+  let (c_3, _user) = generate_unique_registered_user_client().await;
+  let uid_3 = c_3.get_profile().await.unwrap().uid;
+  for (uid, access_level) in [
+    (uid_2, AFAccessLevel::ReadOnly),
+    (uid_3, AFAccessLevel::ReadAndWrite),
+  ] {
+    c_1
+      .add_collab_member(InsertCollabMemberParams {
+        uid,
+        workspace_id: workspace_id.clone(),
+        object_id: object_id.clone(),
+        access_level,
+        expires_at: None,
+        group_id: None,
+      })
+      .await
+      .unwrap();
+  }
+
+  // filter by access level
+  let members = c_1
+    .get_collab_members(QueryCollabMembers {
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      access_level: Some(AFAccessLevel::ReadOnly),
+      ..Default::default()
+    })
+    .await
+    .unwrap()
+    .members;
+  assert_eq!(members.len(), 1);
+  assert_eq!(members[0].uid, uid_2);
+
+  // search by email, ignoring case
+  let members = c_1
+    .get_collab_members(QueryCollabMembers {
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      search: Some(user_2.email.to_uppercase()),
+      ..Default::default()
+    })
+    .await
+    .unwrap()
+    .members;
+  assert_eq!(members.len(), 1);
+  assert_eq!(members[0].uid, uid_2);
+
+  // without a limit, all the members are returned
+  let all = c_1
+    .get_collab_members(QueryCollabMembers {
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+  assert_eq!(all.members.len(), 3);
+  assert_eq!(all.next_cursor, None);
+
+  // walk through the members two at a time
+  let first_page = c_1
+    .get_collab_members(QueryCollabMembers {
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      limit: Some(2),
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+  assert_eq!(first_page.members.len(), 2);
+  assert_eq!(first_page.members[0].uid, uid_1);
+  assert_eq!(first_page.next_cursor, Some(first_page.members[1].uid));
+  let second_page = c_1
+    .get_collab_members(QueryCollabMembers {
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      after: first_page.next_cursor,
+      limit: Some(2),
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+  assert_eq!(second_page.members.len(), 1);
+  assert_eq!(second_page.next_cursor, None);
+  let mut uids: Vec<i64> = first_page
+    .members
+    .iter()
+    .chain(second_page.members.iter())
+    .map(|member| member.uid)
+    .collect();
+  uids.sort();
+  let mut expected = vec![uid_1, uid_2, uid_3];
+  expected.sort();
+  assert_eq!(uids, expected);
+
+  let error = c_1
+    .get_collab_members(QueryCollabMembers {
+      workspace_id,
+      object_id,
+      limit: Some(0),
+      ..Default::default()
+    })
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn add_collab_member_with_expiry_test() {
+  let (c_1, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c_1).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world");
+  c_1
+    .create_collab(CreateCollabParams {
+      object_id: object_id.clone(),
+      encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+      collab_type: CollabType::Unknown,
+      workspace_id: workspace_id.clone(),
+    })
+    .await
+    .unwrap();
+
+  let (c_2, _user) = generate_unique_registered_user_client().await;
+  let uid_2 = c_2.get_profile().await.unwrap().uid;
+
+  // the expiry must be in the future
+  let error = c_1
+    .add_collab_member(InsertCollabMemberParams {
+      uid: uid_2,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      access_level: AFAccessLevel::ReadAndWrite,
+      expires_at: Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+      group_id: None,
+    })
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+
+  let expires_at = chrono::Utc::now() + chrono::Duration::days(14);
+  c_1
+    .add_collab_member(InsertCollabMemberParams {
+      uid: uid_2,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      access_level: AFAccessLevel::ReadAndWrite,
+      expires_at: Some(expires_at),
+      group_id: None,
+    })
+    .await
+    .unwrap();
+  let member = c_1
+    .get_collab_member(CollabMemberIdentify {
+      uid: uid_2,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+    })
+    .await
+    .unwrap();
+  assert_eq!(
+    member.expires_at.map(|time| time.timestamp()),
+    Some(expires_at.timestamp())
+  );
+
+  // updating without an expiry keeps the current one
+  c_1
+    .update_collab_member(UpdateCollabMemberParams {
+      uid: uid_2,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      access_level: AFAccessLevel::ReadOnly,
+      expires_at: None,
+      group_id: None,
+    })
+    .await
+    .unwrap();
+  let member = c_1
+    .get_collab_member(CollabMemberIdentify {
+      uid: uid_2,
+      workspace_id,
+      object_id,
+    })
+    .await
+    .unwrap();
+  assert_eq!(member.permission.access_level, AFAccessLevel::ReadOnly);
+  assert_eq!(
+    member.expires_at.map(|time| time.timestamp()),
+    Some(expires_at.timestamp())
+  );
+}
+
+#[tokio::test]
+async fn inherit_collab_access_from_parent_view_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let guest = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &guest, AFRole::Guest)
+    .await
+    .unwrap();
+  let folder_view = owner
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let space = &folder_view.children[0];
+  let child_id = space.children[0].view_id.clone();
+  owner
+    .add_collab_member(
+      &workspace_id,
+      &space.view_id,
+      &guest,
+      AFAccessLevel::FullAccess,
+    )
+    .await;
+
+  // a guest can only manage the views it holds full access to
+  let err = guest
+    .api_client
+    .break_collab_inheritance(&workspace_id, &child_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  // once the workspace inherits view permissions, the access to the space applies to its views
+  owner
+    .api_client
+    .update_workspace_settings(
+      &workspace_id,
+      &AFWorkspaceSettingsChange::new().inherit_view_permissions(true),
+    )
+    .await
+    .unwrap();
+  guest
+    .api_client
+    .break_collab_inheritance(&workspace_id, &child_id)
+    .await
+    .unwrap();
+
+  // the inheritance is now broken on the view, so the guest can not restore it
+  let err = guest
+    .api_client
+    .restore_collab_inheritance(&workspace_id, &child_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  owner
+    .api_client
+    .restore_collab_inheritance(&workspace_id, &child_id)
+    .await
+    .unwrap();
+  guest
+    .api_client
+    .break_collab_inheritance(&workspace_id, &child_id)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn get_collab_access_policies_test() {
+  let (c_1, _user) = generate_unique_registered_user_client().await;
+  let uid_1 = c_1.get_profile().await.unwrap().uid;
+  let workspace_id = workspace_id_from_client(&c_1).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world");
+  c_1
+    .create_collab(CreateCollabParams {
+      object_id: object_id.clone(),
+      encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+      collab_type: CollabType::Unknown,
+      workspace_id: workspace_id.clone(),
+    })
+    .await
+    .unwrap();
+
+  let (c_2, _user) = generate_unique_registered_user_client().await;
+  let uid_2 = c_2.get_profile().await.unwrap().uid;
+  c_1
+    .add_collab_member(InsertCollabMemberParams {
+      uid: uid_2,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      access_level: AFAccessLevel::ReadAndComment,
+      expires_at: None,
+      group_id: None,
+    })
+    .await
+    .unwrap();
+
+  let policies = c_1
+    .get_collab_access_policies(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  assert!(policies.contains(&CollabAccessPolicy {
+    uid: uid_1,
+    access_level: AFAccessLevel::FullAccess,
+  }));
+  assert!(policies.contains(&CollabAccessPolicy {
+    uid: uid_2,
+    access_level: AFAccessLevel::ReadAndComment,
+  }));
+
+  // the policy of a removed member is removed as well
+  c_1
+    .remove_collab_member(CollabMemberIdentify {
+      uid: uid_2,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+    })
+    .await
+    .unwrap();
+  let policies = c_1
+    .get_collab_access_policies(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  assert!(policies.iter().all(|policy| policy.uid != uid_2));
+
+  // only the owner of the workspace can list the policies
+  let err = c_2
+    .get_collab_access_policies(&workspace_id, &object_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn ensure_collab_member_test() {
+  let (c_1, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c_1).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world");
+  c_1
+    .create_collab(CreateCollabParams {
+      object_id: object_id.clone(),
+      encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+      collab_type: CollabType::Unknown,
+      workspace_id: workspace_id.clone(),
+    })
+    .await
+    .unwrap();
+
+  let (c_2, _user) = generate_unique_registered_user_client().await;
+  let uid_2 = c_2.get_profile().await.unwrap().uid;
+  let params = |access_level| InsertCollabMemberParams {
+    uid: uid_2,
+    workspace_id: workspace_id.clone(),
+    object_id: object_id.clone(),
+    access_level,
+    expires_at: None,
+    group_id: None,
+  };
+  let access_level = || {
+    let (c_1, identify) = (
+      &c_1,
+      CollabMemberIdentify {
+        uid: uid_2,
+        workspace_id: workspace_id.clone(),
+        object_id: object_id.clone(),
+      },
+    );
+    async move {
+      c_1
+        .get_collab_member(identify)
+        .await
+        .unwrap()
+        .permission
+        .access_level
+    }
+  };
+
+  let outcome = c_1
+    .ensure_collab_member(params(AFAccessLevel::ReadOnly))
+    .await
+    .unwrap();
+  assert_eq!(outcome, EnsureCollabMemberOutcome::Created);
+  assert_eq!(access_level().await, AFAccessLevel::ReadOnly);
+
+  // delivering the same event again changes nothing
+  let outcome = c_1
+    .ensure_collab_member(params(AFAccessLevel::ReadOnly))
+    .await
+    .unwrap();
+  assert_eq!(outcome, EnsureCollabMemberOutcome::Unchanged);
+  assert_eq!(access_level().await, AFAccessLevel::ReadOnly);
+
+  let outcome = c_1
+    .ensure_collab_member(params(AFAccessLevel::ReadAndWrite))
+    .await
+    .unwrap();
+  assert_eq!(outcome, EnsureCollabMemberOutcome::Updated);
+  assert_eq!(access_level().await, AFAccessLevel::ReadAndWrite);
+
+  // the policy follows the database
+  let policies = c_1
+    .get_collab_access_policies(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  assert!(policies.contains(&CollabAccessPolicy {
+    uid: uid_2,
+    access_level: AFAccessLevel::ReadAndWrite,
+  }));
+
+  // the expiry time is set once, and ensuring it again changes nothing
+  let expires_at = chrono::Utc::now() + chrono::Duration::days(1);
+  let with_expiry = InsertCollabMemberParams {
+    expires_at: Some(expires_at),
+    ..params(AFAccessLevel::ReadAndWrite)
+  };
+  let outcome = c_1.ensure_collab_member(with_expiry.clone()).await.unwrap();
+  assert_eq!(outcome, EnsureCollabMemberOutcome::Updated);
+  let outcome = c_1.ensure_collab_member(with_expiry).await.unwrap();
+  assert_eq!(outcome, EnsureCollabMemberOutcome::Unchanged);
+  let member = c_1
+    .get_collab_member(CollabMemberIdentify {
+      uid: uid_2,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+    })
+    .await
+    .unwrap();
+  assert_eq!(
+    member.expires_at.map(|time| time.timestamp()),
+    Some(expires_at.timestamp())
+  );
+
+  let error = c_1
+    .ensure_collab_member(InsertCollabMemberParams {
+      expires_at: Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+      ..params(AFAccessLevel::ReadAndWrite)
+    })
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+  let error = c_1
+    .ensure_collab_member(InsertCollabMemberParams {
+      group_id: Some(Uuid::new_v4()),
+      ..params(AFAccessLevel::ReadAndWrite)
+    })
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+}
@@ -1,9 +1,13 @@
 mod awareness_test;
 mod collab_curd_test;
+mod comment_test;
+mod contributor_test;
+mod delta_sync_test;
 mod member_crud;
 mod missing_update_test;
 mod multi_devices_edit;
 mod permission_test;
 mod single_device_edit;
 mod storage_test;
+mod version_test;
 pub mod util;
@@ -7,6 +7,7 @@ use collab::entity::EncodedCollab;
 use collab::lock::Mutex;
 use collab::preclude::{Doc, Transact};
 use collab_entity::CollabType;
+use futures::TryStreamExt;
 use sqlx::types::Uuid;
 use sqlx::PgPool;
 use tokio::time::sleep;
@@ -16,6 +17,7 @@ use appflowy_collaborate::collab::cache::CollabCache;
 use appflowy_collaborate::collab::mem_cache::CollabMemCache;
 use appflowy_collaborate::collab::queue::StorageQueue;
 use appflowy_collaborate::collab::WritePriority;
+use appflowy_collaborate::metrics::CollabStorageMetrics;
 use client_api_test::*;
 use database::collab::CollabMetadata;
 use database_entity::dto::{
@@ -25,7 +27,9 @@ use database_entity::dto::{
 use workspace_template::document::getting_started::GettingStartedTemplate;
 use workspace_template::WorkspaceTemplateBuilder;
 
-use crate::collab::util::{generate_random_bytes, redis_connection_manager, test_encode_collab_v1};
+use crate::collab::util::{
+  generate_random_bytes, generate_random_string, redis_connection_manager, test_encode_collab_v1,
+};
 use crate::sql_test::util::{setup_db, test_create_user};
 
 #[tokio::test]
@@ -165,6 +169,112 @@ async fn success_part_batch_get_collab_test() {
   }
 }
 
+#[tokio::test]
+async fn batch_get_collab_access_test() {
+  let (owner, _) = generate_unique_registered_user_client().await;
+  let owner_workspace_id = workspace_id_from_client(&owner).await;
+  let (other, _) = generate_unique_registered_user_client().await;
+  let other_workspace_id = workspace_id_from_client(&other).await;
+
+  let owner_object_id = Uuid::new_v4().to_string();
+  let other_object_id = Uuid::new_v4().to_string();
+  for (c, workspace_id, object_id) in [
+    (&owner, &owner_workspace_id, &owner_object_id),
+    (&other, &other_workspace_id, &other_object_id),
+  ] {
+    c.create_collab(CreateCollabParams {
+      object_id: object_id.clone(),
+      encoded_collab_v1: test_encode_collab_v1(object_id, "title", "hello world")
+        .encode_to_bytes()
+        .unwrap(),
+      collab_type: CollabType::Unknown,
+      workspace_id: workspace_id.clone(),
+    })
+    .await
+    .unwrap();
+  }
+  let queries = vec![
+    QueryCollab {
+      object_id: owner_object_id.clone(),
+      collab_type: CollabType::Unknown,
+    },
+    QueryCollab {
+      object_id: other_object_id.clone(),
+      collab_type: CollabType::Unknown,
+    },
+  ];
+
+  // A collab of another workspace is not returned through the owner's workspace.
+  let results = owner
+    .batch_get_collab(&owner_workspace_id, queries.clone())
+    .await
+    .unwrap()
+    .0;
+  assert!(matches!(
+    results.get(&owner_object_id).unwrap(),
+    QueryCollabResult::Success { .. }
+  ));
+  assert!(matches!(
+    results.get(&other_object_id).unwrap(),
+    QueryCollabResult::Failed { .. }
+  ));
+
+  // A user outside of the workspace can not read any of its collabs.
+  let results = other
+    .batch_get_collab(&owner_workspace_id, queries)
+    .await
+    .unwrap()
+    .0;
+  assert!(results
+    .values()
+    .all(|result| matches!(result, QueryCollabResult::Failed { .. })));
+}
+
+#[tokio::test]
+async fn get_large_collab_stream_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  // Larger than a single chunk read from the database.
+  let content = generate_random_string(2 * 1024 * 1024);
+  let encode_collab = test_encode_collab_v1(&object_id, "title", &content);
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+  })
+  .await
+  .unwrap();
+
+  let data = c
+    .get_collab_stream(QueryCollabParams::new(
+      &object_id,
+      CollabType::Unknown,
+      &workspace_id,
+    ))
+    .await
+    .unwrap()
+    .try_concat()
+    .await
+    .unwrap();
+  let streamed_collab = EncodedCollab::decode_from_bytes(&data).unwrap();
+  assert_eq!(streamed_collab.doc_state, encode_collab.doc_state);
+
+  let err = match c
+    .get_collab_stream(QueryCollabParams::new(
+      &Uuid::new_v4().to_string(),
+      CollabType::Unknown,
+      &workspace_id,
+    ))
+    .await
+  {
+    Ok(_) => panic!("a collab that doesn't exist should not be streamed"),
+    Err(err) => err,
+  };
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
 #[tokio::test]
 async fn success_delete_collab_test() {
   let (c, _user) = generate_unique_registered_user_client().await;
@@ -450,7 +560,11 @@ async fn simulate_small_data_set_write(pool: PgPool) {
     .await
     .unwrap();
 
-  let collab_cache = CollabCache::new(conn.clone(), pool);
+  let collab_cache = CollabCache::new(
+    conn.clone(),
+    pool,
+    Arc::new(CollabStorageMetrics::default()),
+  );
   let queue_name = uuid::Uuid::new_v4().to_string();
   let storage_queue = StorageQueue::new(collab_cache.clone(), conn, &queue_name);
 
@@ -535,7 +649,11 @@ async fn simulate_large_data_set_write(pool: PgPool) {
     .await
     .unwrap();
 
-  let collab_cache = CollabCache::new(conn.clone(), pool);
+  let collab_cache = CollabCache::new(
+    conn.clone(),
+    pool,
+    Arc::new(CollabStorageMetrics::default()),
+  );
   let queue_name = uuid::Uuid::new_v4().to_string();
   let storage_queue = StorageQueue::new(collab_cache.clone(), conn, &queue_name);
 
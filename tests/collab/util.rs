@@ -4,9 +4,9 @@ use anyhow::Context;
 use collab::core::origin::CollabOrigin;
 use collab::entity::EncodedCollab;
 use collab::preclude::Collab;
+use collab_stream::connection::RedisConnectionManager;
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
-use redis::aio::ConnectionManager;
 use tokio::time::sleep;
 
 #[allow(dead_code)]
@@ -54,12 +54,12 @@ pub async fn redis_client() -> redis::Client {
     .unwrap()
 }
 
-pub async fn redis_connection_manager() -> ConnectionManager {
+pub async fn redis_connection_manager() -> RedisConnectionManager {
   let mut attempt = 0;
   let max_attempts = 5;
   let mut wait_time = 500;
   loop {
-    match redis_client().await.get_connection_manager().await {
+    match RedisConnectionManager::new_standalone(redis_client().await).await {
       Ok(manager) => return manager,
       Err(err) => {
         if attempt >= max_attempts {
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+
+use collab::core::collab::DataSource;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
+use collab_document::blocks::{Block, DocumentData, DocumentMeta};
+use collab_document::document::Document;
+use collab_entity::CollabType;
+use database_entity::dto::{
+  CreateCollabParams, QueryCollabParams, QueryCollabVersionDiff, TextChange, TextChangeKind,
+};
+use serde_json::json;
+use sqlx::types::Uuid;
+
+use app_error::ErrorCode;
+use client_api_test::*;
+
+use crate::collab::util::test_encode_collab_v1;
+
+fn collab_json(object_id: &str, doc_state: Vec<u8>) -> serde_json::Value {
+  Collab::new_with_source(
+    CollabOrigin::Empty,
+    object_id,
+    DataSource::DocStateV1(doc_state),
+    vec![],
+    false,
+  )
+  .unwrap()
+  .to_json_value()
+}
+
+#[tokio::test]
+async fn restore_collab_version_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let uid = c.get_profile().await.unwrap().uid;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: test_encode_collab_v1(&object_id, "title", "first")
+      .encode_to_bytes()
+      .unwrap(),
+  })
+  .await
+  .unwrap();
+  let version = c
+    .create_snapshot(&workspace_id, &object_id, CollabType::Unknown)
+    .await
+    .unwrap();
+
+  c.update_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: test_encode_collab_v1(&object_id, "title", "second")
+      .encode_to_bytes()
+      .unwrap(),
+  })
+  .await
+  .unwrap();
+
+  let versions = c
+    .list_collab_versions(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  assert_eq!(versions.len(), 1);
+  assert_eq!(versions[0].snapshot_id, version.snapshot_id);
+  assert_eq!(versions[0].created_by, Some(uid));
+
+  let encoded_collab = c
+    .get_collab_version(&workspace_id, &object_id, version.snapshot_id)
+    .await
+    .unwrap();
+  assert_eq!(
+    collab_json(&object_id, encoded_collab.doc_state.to_vec()),
+    json!({"title": "first"})
+  );
+
+  let backup = c
+    .restore_collab_version(
+      &workspace_id,
+      &object_id,
+      version.snapshot_id,
+      CollabType::Unknown,
+    )
+    .await
+    .unwrap();
+  let doc_state = c
+    .get_collab(QueryCollabParams::new(
+      &object_id,
+      CollabType::Unknown,
+      &workspace_id,
+    ))
+    .await
+    .unwrap()
+    .encode_collab
+    .doc_state;
+  assert_eq!(
+    collab_json(&object_id, doc_state.to_vec()),
+    json!({"title": "first"})
+  );
+
+  // The state before the restore is kept as a new version.
+  let versions = c
+    .list_collab_versions(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  assert_eq!(versions.len(), 2);
+  assert_eq!(versions[0].snapshot_id, backup.snapshot_id);
+  assert_eq!(versions[0].created_by, Some(uid));
+  let encoded_collab = c
+    .get_collab_version(&workspace_id, &object_id, backup.snapshot_id)
+    .await
+    .unwrap();
+  assert_eq!(
+    collab_json(&object_id, encoded_collab.doc_state.to_vec()),
+    json!({"title": "second"})
+  );
+}
+
+#[tokio::test]
+async fn get_version_of_other_collab_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: test_encode_collab_v1(&object_id, "title", "hello")
+      .encode_to_bytes()
+      .unwrap(),
+  })
+  .await
+  .unwrap();
+  let version = c
+    .create_snapshot(&workspace_id, &object_id, CollabType::Unknown)
+    .await
+    .unwrap();
+
+  let other_object_id = Uuid::new_v4().to_string();
+  c.create_collab(CreateCollabParams {
+    object_id: other_object_id.clone(),
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: test_encode_collab_v1(&other_object_id, "title", "hello")
+      .encode_to_bytes()
+      .unwrap(),
+  })
+  .await
+  .unwrap();
+  let err = c
+    .get_collab_version(&workspace_id, &other_object_id, version.snapshot_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+/// A document with a paragraph for each of the blocks, given as their ids and texts.
+fn encoded_document(object_id: &str, paragraphs: &[(&str, &str)]) -> Vec<u8> {
+  let mut blocks = HashMap::new();
+  blocks.insert(
+    "page".to_string(),
+    Block {
+      id: "page".to_string(),
+      ty: "page".to_string(),
+      parent: "".to_string(),
+      children: "page-children".to_string(),
+      external_id: None,
+      external_type: None,
+      data: HashMap::new(),
+    },
+  );
+  for (id, text) in paragraphs {
+    blocks.insert(
+      id.to_string(),
+      Block {
+        id: id.to_string(),
+        ty: "paragraph".to_string(),
+        parent: "page".to_string(),
+        children: format!("{}-children", id),
+        external_id: None,
+        external_type: None,
+        data: HashMap::from([("delta".to_string(), json!([{ "insert": text }]))]),
+      },
+    );
+  }
+  let mut children_map = HashMap::new();
+  children_map.insert(
+    "page-children".to_string(),
+    paragraphs.iter().map(|(id, _)| id.to_string()).collect(),
+  );
+  let data = DocumentData {
+    page_id: "page".to_string(),
+    blocks,
+    meta: DocumentMeta {
+      children_map,
+      text_map: Some(HashMap::new()),
+    },
+  };
+  let collab = Collab::new_with_origin(CollabOrigin::Empty, object_id, vec![], false);
+  Document::create_with_data(collab, data)
+    .unwrap()
+    .encode_collab()
+    .unwrap()
+    .encode_to_bytes()
+    .unwrap()
+}
+
+#[tokio::test]
+async fn diff_document_versions_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Document,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: encoded_document(&object_id, &[("a", "Hello world"), ("b", "Remove me")]),
+  })
+  .await
+  .unwrap();
+  let version = c
+    .create_snapshot(&workspace_id, &object_id, CollabType::Document)
+    .await
+    .unwrap();
+  c.update_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Document,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: encoded_document(
+      &object_id,
+      &[("a", "Hello brave world"), ("c", "New block")],
+    ),
+  })
+  .await
+  .unwrap();
+
+  let diff = c
+    .diff_collab_versions(
+      &workspace_id,
+      &object_id,
+      &QueryCollabVersionDiff {
+        from_snapshot_id: Some(version.snapshot_id),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(diff.from_snapshot_id, version.snapshot_id);
+  assert_eq!(diff.to_snapshot_id, None);
+  assert_eq!(diff.inserted_blocks.len(), 1);
+  assert_eq!(diff.inserted_blocks[0].block_id, "c");
+  assert_eq!(diff.inserted_blocks[0].text, "New block");
+  assert_eq!(diff.removed_blocks.len(), 1);
+  assert_eq!(diff.removed_blocks[0].block_id, "b");
+  assert_eq!(diff.removed_blocks[0].text, "Remove me");
+  assert_eq!(diff.changed_blocks.len(), 1);
+  assert_eq!(diff.changed_blocks[0].block_id, "a");
+  assert_eq!(
+    diff.changed_blocks[0].text_changes,
+    vec![
+      TextChange {
+        kind: TextChangeKind::Equal,
+        text: "Hello ".to_string(),
+      },
+      TextChange {
+        kind: TextChangeKind::Insert,
+        text: "brave ".to_string(),
+      },
+      TextChange {
+        kind: TextChangeKind::Equal,
+        text: "world".to_string(),
+      },
+    ]
+  );
+
+  // The version can also be found by the time it was created at.
+  let to = c
+    .create_snapshot(&workspace_id, &object_id, CollabType::Document)
+    .await
+    .unwrap();
+  let diff = c
+    .diff_collab_versions(
+      &workspace_id,
+      &object_id,
+      &QueryCollabVersionDiff {
+        from_time: Some(version.created_at),
+        to_snapshot_id: Some(to.snapshot_id),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(diff.from_snapshot_id, version.snapshot_id);
+  assert_eq!(diff.to_snapshot_id, Some(to.snapshot_id));
+  assert_eq!(diff.inserted_blocks.len(), 1);
+  assert_eq!(diff.removed_blocks.len(), 1);
+
+  let err = c
+    .diff_collab_versions(
+      &workspace_id,
+      &object_id,
+      &QueryCollabVersionDiff {
+        from_time: Some(version.created_at - chrono::Duration::days(1)),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
@@ -4,6 +4,7 @@ use std::ops::Deref;
 mod delete_dir_test;
 mod multiple_part_test;
 mod put_and_get;
+mod thumbnail_test;
 mod usage;
 
 use appflowy_cloud::application::get_aws_s3_client;
@@ -43,6 +44,7 @@ impl TestBucket {
       secret_key: Secret::new(LOCALHOST_MINIO_SECRET_KEY.to_string()),
       bucket: LOCALHOST_MINIO_BUCKET_NAME.to_string(),
       region: "".to_string(),
+      presigned_url_expiration_secs: 3600,
     };
     let client = AwsS3BucketClientImpl::new(
       get_aws_s3_client(&setting).await.unwrap(),
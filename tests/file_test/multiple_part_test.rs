@@ -420,3 +420,223 @@ async fn multiple_level_dir_upload_file_test() {
   let blob_text = String::from_utf8(blob.to_vec()).unwrap();
   assert_eq!(blob_text, text);
 }
+
+#[tokio::test]
+async fn resume_upload_with_part_url_test() {
+  let (c1, _user1) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c1).await;
+  let parent_dir = workspace_id.clone();
+  let text = generate_random_string(8 * 1024 * 1024);
+  let file_id = Uuid::new_v4().to_string();
+
+  let upload = c1
+    .create_upload(
+      &workspace_id,
+      CreateUploadRequest {
+        file_id: file_id.clone(),
+        parent_dir: parent_dir.clone(),
+        content_type: mime::TEXT_PLAIN_UTF_8.to_string(),
+      },
+    )
+    .await
+    .unwrap();
+  let mut chunked_bytes = ChunkedBytes::from_bytes(Bytes::from(text.clone())).unwrap();
+  chunked_bytes.set_chunk_size(5 * 1024 * 1024).unwrap();
+  let chunks = chunked_bytes
+    .iter()
+    .map(|chunk| chunk.to_vec())
+    .collect::<Vec<_>>();
+  assert_eq!(chunks.len(), 2);
+
+  // The first part is uploaded before the connection is lost
+  c1.upload_part(
+    &workspace_id,
+    &parent_dir,
+    &file_id,
+    &upload.upload_id,
+    1,
+    chunks[0].clone(),
+  )
+  .await
+  .unwrap();
+
+  // Resume the upload with the missing part, uploaded to its presigned url
+  let uploaded = c1
+    .list_upload_parts(&workspace_id, &parent_dir, &file_id, &upload.upload_id)
+    .await
+    .unwrap();
+  assert_eq!(uploaded.parts.len(), 1);
+  assert_eq!(uploaded.parts[0].part_num, 1);
+  assert_eq!(uploaded.parts[0].size, chunks[0].len() as i64);
+
+  let part_url = c1
+    .get_upload_part_url(&workspace_id, &parent_dir, &file_id, &upload.upload_id, 2)
+    .await
+    .unwrap();
+  assert_eq!(part_url.part_num, 2);
+  c1.upload_part_to_url(&part_url.url, 2, chunks[1].clone())
+    .await
+    .unwrap();
+
+  let uploaded = c1
+    .list_upload_parts(&workspace_id, &parent_dir, &file_id, &upload.upload_id)
+    .await
+    .unwrap();
+  assert_eq!(uploaded.parts.len(), 2);
+
+  let req = || CompleteUploadRequest {
+    file_id: file_id.clone(),
+    parent_dir: parent_dir.clone(),
+    upload_id: upload.upload_id.clone(),
+    parts: uploaded
+      .parts
+      .iter()
+      .map(|part| CompletedPartRequest {
+        e_tag: part.e_tag.clone(),
+        part_number: part.part_num,
+      })
+      .collect(),
+  };
+  c1.complete_upload(&workspace_id, req()).await.unwrap();
+  // Retrying the completion of a completed upload succeeds
+  c1.complete_upload(&workspace_id, req()).await.unwrap();
+
+  let blob = c1
+    .get_blob_v1(&workspace_id, &parent_dir, &file_id)
+    .await
+    .unwrap()
+    .1;
+  assert_eq!(String::from_utf8(blob.to_vec()).unwrap(), text);
+}
+
+#[tokio::test]
+async fn abort_upload_test() {
+  let (c1, _user1) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c1).await;
+  let file_id = Uuid::new_v4().to_string();
+
+  let upload = c1
+    .create_upload(
+      &workspace_id,
+      CreateUploadRequest {
+        file_id: file_id.clone(),
+        parent_dir: workspace_id.clone(),
+        content_type: mime::TEXT_PLAIN_UTF_8.to_string(),
+      },
+    )
+    .await
+    .unwrap();
+  c1.upload_part(
+    &workspace_id,
+    &workspace_id,
+    &file_id,
+    &upload.upload_id,
+    1,
+    generate_random_bytes(1024),
+  )
+  .await
+  .unwrap();
+
+  c1.abort_upload(&workspace_id, &workspace_id, &file_id, &upload.upload_id)
+    .await
+    .unwrap();
+  let err = c1
+    .list_upload_parts(&workspace_id, &workspace_id, &file_id, &upload.upload_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn upload_to_other_workspace_test() {
+  let (c1, _user1) = generate_unique_registered_user_client().await;
+  let (c2, _user2) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c1).await;
+  let file_id = Uuid::new_v4().to_string();
+
+  let upload = c1
+    .create_upload(
+      &workspace_id,
+      CreateUploadRequest {
+        file_id: file_id.clone(),
+        parent_dir: workspace_id.clone(),
+        content_type: mime::TEXT_PLAIN_UTF_8.to_string(),
+      },
+    )
+    .await
+    .unwrap();
+
+  let err = c2
+    .create_upload(
+      &workspace_id,
+      CreateUploadRequest {
+        file_id: Uuid::new_v4().to_string(),
+        parent_dir: workspace_id.clone(),
+        content_type: mime::TEXT_PLAIN_UTF_8.to_string(),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  // The part urls of the upload are only issued to the members of the workspace
+  let err = c2
+    .get_upload_part_url(&workspace_id, &workspace_id, &file_id, &upload.upload_id, 1)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = c2
+    .list_upload_parts(&workspace_id, &workspace_id, &file_id, &upload.upload_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn upload_outside_workspace_dir_test() {
+  let (c1, _user1) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c1).await;
+
+  for (parent_dir, file_id) in [
+    ("..".to_string(), Uuid::new_v4().to_string()),
+    ("file/../..".to_string(), Uuid::new_v4().to_string()),
+    ("/file".to_string(), Uuid::new_v4().to_string()),
+    (workspace_id.clone(), "../file".to_string()),
+  ] {
+    let err = c1
+      .create_upload(
+        &workspace_id,
+        CreateUploadRequest {
+          file_id,
+          parent_dir,
+          content_type: mime::TEXT_PLAIN_UTF_8.to_string(),
+        },
+      )
+      .await
+      .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidRequest);
+  }
+
+  let upload = c1
+    .create_upload(
+      &workspace_id,
+      CreateUploadRequest {
+        file_id: Uuid::new_v4().to_string(),
+        parent_dir: workspace_id.clone(),
+        content_type: mime::TEXT_PLAIN_UTF_8.to_string(),
+      },
+    )
+    .await
+    .unwrap();
+  let err = c1
+    .get_upload_part_url(
+      &workspace_id,
+      &workspace_id,
+      &upload.file_id,
+      &upload.upload_id,
+      10001,
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
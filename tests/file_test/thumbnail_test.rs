@@ -0,0 +1,120 @@
+use std::io::Cursor;
+
+use app_error::ErrorCode;
+use client_api::Client;
+use client_api_test::{generate_unique_registered_user_client, workspace_id_from_client};
+use database_entity::file_dto::{CompleteUploadRequest, CompletedPartRequest, CreateUploadRequest};
+use image::{DynamicImage, GenericImageView, ImageOutputFormat, Rgb, RgbImage};
+use uuid::Uuid;
+
+async fn upload_file(
+  client: &Client,
+  workspace_id: &str,
+  parent_dir: &str,
+  content_type: &str,
+  data: Vec<u8>,
+) -> String {
+  let file_id = Uuid::new_v4().to_string();
+  let upload = client
+    .create_upload(
+      workspace_id,
+      CreateUploadRequest {
+        file_id: file_id.clone(),
+        parent_dir: parent_dir.to_string(),
+        content_type: content_type.to_string(),
+      },
+    )
+    .await
+    .unwrap();
+  let part = client
+    .upload_part(
+      workspace_id,
+      parent_dir,
+      &file_id,
+      &upload.upload_id,
+      1,
+      data,
+    )
+    .await
+    .unwrap();
+  client
+    .complete_upload(
+      workspace_id,
+      CompleteUploadRequest {
+        file_id: file_id.clone(),
+        parent_dir: parent_dir.to_string(),
+        upload_id: upload.upload_id,
+        parts: vec![CompletedPartRequest {
+          e_tag: part.e_tag,
+          part_number: part.part_num,
+        }],
+      },
+    )
+    .await
+    .unwrap();
+  file_id
+}
+
+#[tokio::test]
+async fn get_image_thumbnail_test() {
+  let (c1, _user1) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c1).await;
+  let parent_dir = workspace_id.clone();
+
+  let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(800, 400, Rgb([200, 100, 50])));
+  let mut png = Cursor::new(Vec::new());
+  image.write_to(&mut png, ImageOutputFormat::Png).unwrap();
+  let file_id = upload_file(
+    &c1,
+    &workspace_id,
+    &parent_dir,
+    mime::IMAGE_PNG.as_ref(),
+    png.into_inner(),
+  )
+  .await;
+
+  // The requested size is rounded up to 256
+  let (mime, thumbnail) = c1
+    .get_thumbnail_v1(&workspace_id, &parent_dir, &file_id, 200)
+    .await
+    .unwrap();
+  assert_eq!(mime, mime::IMAGE_JPEG);
+  let decoded = image::load_from_memory(&thumbnail).unwrap();
+  assert_eq!(decoded.dimensions(), (256, 128));
+
+  // The second request is served from the cache
+  let (_, cached) = c1
+    .get_thumbnail_v1(&workspace_id, &parent_dir, &file_id, 256)
+    .await
+    .unwrap();
+  assert_eq!(cached, thumbnail);
+
+  c1.delete_blob_v1(&workspace_id, &parent_dir, &file_id)
+    .await
+    .unwrap();
+  let err = c1
+    .get_thumbnail_v1(&workspace_id, &parent_dir, &file_id, 256)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn get_thumbnail_of_non_image_test() {
+  let (c1, _user1) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c1).await;
+  let file_id = upload_file(
+    &c1,
+    &workspace_id,
+    &workspace_id,
+    mime::TEXT_PLAIN_UTF_8.as_ref(),
+    b"hello world".to_vec(),
+  )
+  .await;
+
+  let err = c1
+    .get_thumbnail_v1(&workspace_id, &workspace_id, &file_id, 256)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
@@ -1,4 +1,5 @@
 use client_api_test::TestClient;
+use shared_entity::dto::billing_dto::SubscriptionPlan;
 
 #[tokio::test]
 async fn workspace_usage_put_blob_test() {
@@ -34,3 +35,35 @@ async fn workspace_usage_put_and_then_delete_blob_test() {
   let usage = client.get_workspace_usage().await;
   assert_eq!(usage.consumed_capacity, 0);
 }
+
+#[tokio::test]
+async fn workspace_storage_quota_test() {
+  let client = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = client.workspace_id().await;
+  let before = client
+    .api_client
+    .get_workspace_storage_quota(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(before.plan, SubscriptionPlan::Free);
+  assert_eq!(before.used_bytes, before.collab_bytes + before.blob_bytes);
+
+  let mime = mime::TEXT_PLAIN_UTF_8;
+  let file_id = uuid::Uuid::new_v4().to_string();
+  client.upload_blob(&file_id, "123", &mime).await;
+  let after = client
+    .api_client
+    .get_workspace_storage_quota(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(after.blob_bytes, before.blob_bytes + 3);
+  assert_eq!(after.used_bytes, after.collab_bytes + after.blob_bytes);
+
+  client.delete_file(&file_id).await;
+  let after_delete = client
+    .api_client
+    .get_workspace_storage_quota(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(after_delete.blob_bytes, before.blob_bytes);
+}
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use collab::preclude::Collab;
+use collab_document::document::Document;
+use collab_entity::CollabType;
+use database_entity::dto::CreateCollabParams;
+use shared_entity::dto::search_dto::WorkspaceSearchQuery;
+use tokio::time::sleep;
+
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use workspace_template::document::getting_started::getting_started_document_data;
+
+fn search_query(query: &str) -> WorkspaceSearchQuery {
+  WorkspaceSearchQuery {
+    query: query.to_string(),
+    limit: None,
+    preview_words: None,
+  }
+}
+
+#[tokio::test]
+async fn full_text_search_document_test() {
+  let test_client = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = test_client.workspace_id().await;
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let encoded_collab = {
+    let collab = Collab::new(
+      test_client.uid().await,
+      object_id.clone(),
+      test_client.device_id.clone(),
+      vec![],
+      false,
+    );
+    let document =
+      Document::create_with_data(collab, getting_started_document_data().unwrap()).unwrap();
+    document.encode_collab().unwrap()
+  };
+  test_client
+    .api_client
+    .create_collab(CreateCollabParams {
+      object_id: object_id.clone(),
+      encoded_collab_v1: encoded_collab.encode_to_bytes().unwrap(),
+      collab_type: CollabType::Document,
+      workspace_id: workspace_id.clone(),
+    })
+    .await
+    .unwrap();
+
+  // the document is indexed when it is written to the database
+  let mut results = vec![];
+  for _ in 0..10 {
+    results = test_client
+      .api_client
+      .search_workspace(&workspace_id, search_query("download macos"))
+      .await
+      .unwrap();
+    if !results.is_empty() {
+      break;
+    }
+    sleep(Duration::from_secs(1)).await;
+  }
+  assert_eq!(results.len(), 1);
+  assert_eq!(results[0].object_id, object_id);
+  assert_eq!(results[0].collab_type, CollabType::Document);
+  // the matching words are returned as ranges of the plain text, never as markup
+  assert!(!results[0].highlight.contains("<b>"));
+  let matches: Vec<String> = results[0]
+    .highlight_ranges
+    .iter()
+    .map(|range| {
+      results[0]
+        .highlight
+        .chars()
+        .skip(range.start)
+        .take(range.end - range.start)
+        .collect()
+    })
+    .collect();
+  assert!(matches.iter().any(|word| word == "macOS"));
+
+  let results = test_client
+    .api_client
+    .search_workspace(&workspace_id, search_query("download -macos"))
+    .await
+    .unwrap();
+  assert!(results.is_empty());
+
+  let error = test_client
+    .api_client
+    .search_workspace(&workspace_id, search_query("  "))
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+}
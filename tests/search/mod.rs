@@ -1 +1,2 @@
 mod document_search;
+mod full_text_search;
@@ -0,0 +1,80 @@
+use crate::sql_test::util::{setup_db, test_create_user};
+
+use appflowy_cloud::biz::collab::compaction::compact_collab;
+use appflowy_cloud::biz::workspace::publish_dup::collab_from_doc_state;
+use collab::core::origin::CollabOrigin;
+use collab::entity::EncodedCollab;
+use collab::preclude::Collab;
+use collab_entity::CollabType;
+use database::collab::{
+  insert_into_af_collab, select_blob_from_af_collab, select_collabs_to_compact,
+};
+use database_entity::dto::CollabParams;
+use sqlx::PgPool;
+
+#[sqlx::test(migrations = false)]
+async fn compact_collab_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+  let user_uuid = uuid::Uuid::new_v4();
+  let name = user_uuid.to_string();
+  let email = format!("{}@appflowy.io", name);
+  let user = test_create_user(&pool, user_uuid, &email, &name)
+    .await
+    .unwrap();
+
+  // Overwriting the same key keeps the previous values in the state, as the garbage collection
+  // is disabled.
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let mut collab = Collab::new_with_origin(CollabOrigin::Empty, &object_id, vec![], true);
+  for i in 0..100 {
+    collab.insert("title", format!("{}{}", "a".repeat(1024), i));
+  }
+  let expected = collab.to_json_value();
+  let encoded_collab_v1 = collab
+    .encode_collab_v1(|_| Ok::<(), anyhow::Error>(()))
+    .unwrap()
+    .encode_to_bytes()
+    .unwrap();
+  let len = encoded_collab_v1.len() as i32;
+  let params = CollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Unknown,
+    encoded_collab_v1: encoded_collab_v1.into(),
+    embeddings: None,
+  };
+  let mut txn = pool.begin().await.unwrap();
+  insert_into_af_collab(&mut txn, &user.uid, &user.workspace_id, &params)
+    .await
+    .unwrap();
+  txn.commit().await.unwrap();
+
+  // only the collabs above the size threshold are compacted
+  let is_selected =
+    |collabs: Vec<(String, CollabType)>| collabs.iter().any(|(oid, _)| oid == &object_id);
+  assert!(!is_selected(
+    select_collabs_to_compact(&pool, len + 1, 1000)
+      .await
+      .unwrap()
+  ));
+  assert!(is_selected(
+    select_collabs_to_compact(&pool, len, 1000).await.unwrap()
+  ));
+
+  let reclaimed = compact_collab(&pool, &object_id, &CollabType::Unknown)
+    .await
+    .unwrap();
+  assert!(reclaimed > 0);
+  let blob = select_blob_from_af_collab(&pool, &CollabType::Unknown, &object_id)
+    .await
+    .unwrap();
+  assert_eq!(blob.len() as i64, len as i64 - reclaimed);
+  let encoded_collab = EncodedCollab::decode_from_bytes(&blob).unwrap();
+  let compacted_collab =
+    collab_from_doc_state(encoded_collab.doc_state.to_vec(), &object_id).unwrap();
+  assert_eq!(compacted_collab.to_json_value(), expected);
+
+  // a compacted collab is not compacted again until it is written
+  assert!(!is_selected(
+    select_collabs_to_compact(&pool, 0, 1000).await.unwrap()
+  ));
+}
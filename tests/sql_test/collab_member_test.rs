@@ -0,0 +1,181 @@
+use crate::sql_test::util::{setup_db, test_create_user};
+
+use database::collab::{
+  delete_collab_membership_lock, delete_expired_collab_members, insert_collab_member,
+  insert_collab_membership_lock, is_collab_member_exists, is_collab_membership_locked,
+  select_collab_member_access_level_updated_at, select_expiring_collab_members,
+  update_collab_member_expires_at,
+};
+use database_entity::dto::AFAccessLevel;
+use sqlx::PgPool;
+
+#[sqlx::test(migrations = false)]
+async fn select_expiring_collab_members_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+
+  let user_uuid = uuid::Uuid::new_v4();
+  let name = user_uuid.to_string();
+  let email = format!("{}@appflowy.io", name);
+  let user = test_create_user(&pool, user_uuid, &email, &name)
+    .await
+    .unwrap();
+
+  let expiring_oid = uuid::Uuid::new_v4().to_string();
+  let later_oid = uuid::Uuid::new_v4().to_string();
+  let permanent_oid = uuid::Uuid::new_v4().to_string();
+  let mut txn = pool.begin().await.unwrap();
+  for oid in [&expiring_oid, &later_oid, &permanent_oid] {
+    insert_collab_member(user.uid, oid, &AFAccessLevel::ReadAndWrite, &mut txn)
+      .await
+      .unwrap();
+  }
+  txn.commit().await.unwrap();
+
+  for (oid, expires_in) in [
+    (&expiring_oid, chrono::Duration::hours(1)),
+    (&later_oid, chrono::Duration::days(10)),
+  ] {
+    sqlx::query("UPDATE af_collab_member SET expires_at = NOW() + $1 WHERE uid = $2 AND oid = $3")
+      .bind(expires_in)
+      .bind(user.uid)
+      .bind(oid)
+      .execute(&pool)
+      .await
+      .unwrap();
+  }
+
+  let members = select_expiring_collab_members(&pool, chrono::Duration::days(1))
+    .await
+    .unwrap();
+  assert_eq!(members.len(), 1);
+  assert_eq!(members[0].oid, expiring_oid);
+  assert!(members[0].expires_at.is_some());
+}
+
+#[sqlx::test(migrations = false)]
+async fn collab_membership_lock_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+
+  let user_uuid = uuid::Uuid::new_v4();
+  let name = user_uuid.to_string();
+  let email = format!("{}@appflowy.io", name);
+  let user = test_create_user(&pool, user_uuid, &email, &name)
+    .await
+    .unwrap();
+
+  let oid = uuid::Uuid::new_v4().to_string();
+  assert!(!is_collab_membership_locked(&oid, &pool).await.unwrap());
+
+  insert_collab_membership_lock(&oid, Some(user.uid), Some("legal hold"), &pool)
+    .await
+    .unwrap();
+  // locking twice keeps the lock
+  insert_collab_membership_lock(&oid, None, None, &pool)
+    .await
+    .unwrap();
+  assert!(is_collab_membership_locked(&oid, &pool).await.unwrap());
+
+  delete_collab_membership_lock(&oid, &pool).await.unwrap();
+  assert!(!is_collab_membership_locked(&oid, &pool).await.unwrap());
+}
+
+#[sqlx::test(migrations = false)]
+async fn collab_member_access_level_updated_at_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+
+  let user_uuid = uuid::Uuid::new_v4();
+  let name = user_uuid.to_string();
+  let email = format!("{}@appflowy.io", name);
+  let user = test_create_user(&pool, user_uuid, &email, &name)
+    .await
+    .unwrap();
+
+  let oid = uuid::Uuid::new_v4().to_string();
+  assert!(
+    select_collab_member_access_level_updated_at(user.uid, &oid, &pool)
+      .await
+      .unwrap()
+      .is_none()
+  );
+
+  let mut txn = pool.begin().await.unwrap();
+  insert_collab_member(user.uid, &oid, &AFAccessLevel::ReadOnly, &mut txn)
+    .await
+    .unwrap();
+  // setting the same level again is not a change
+  insert_collab_member(user.uid, &oid, &AFAccessLevel::ReadOnly, &mut txn)
+    .await
+    .unwrap();
+  txn.commit().await.unwrap();
+  let (access_level, updated_at) =
+    select_collab_member_access_level_updated_at(user.uid, &oid, &pool)
+      .await
+      .unwrap()
+      .unwrap();
+  assert_eq!(access_level, AFAccessLevel::ReadOnly);
+  assert!(updated_at.is_none());
+
+  let mut txn = pool.begin().await.unwrap();
+  insert_collab_member(user.uid, &oid, &AFAccessLevel::ReadAndWrite, &mut txn)
+    .await
+    .unwrap();
+  txn.commit().await.unwrap();
+  let (access_level, updated_at) =
+    select_collab_member_access_level_updated_at(user.uid, &oid, &pool)
+      .await
+      .unwrap()
+      .unwrap();
+  assert_eq!(access_level, AFAccessLevel::ReadAndWrite);
+  assert!(updated_at.is_some());
+}
+
+#[sqlx::test(migrations = false)]
+async fn delete_expired_collab_members_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+
+  let user_uuid = uuid::Uuid::new_v4();
+  let name = user_uuid.to_string();
+  let email = format!("{}@appflowy.io", name);
+  let user = test_create_user(&pool, user_uuid, &email, &name)
+    .await
+    .unwrap();
+
+  let expired_oid = uuid::Uuid::new_v4().to_string();
+  let active_oid = uuid::Uuid::new_v4().to_string();
+  let permanent_oid = uuid::Uuid::new_v4().to_string();
+  let mut txn = pool.begin().await.unwrap();
+  for oid in [&expired_oid, &active_oid, &permanent_oid] {
+    insert_collab_member(user.uid, oid, &AFAccessLevel::ReadAndWrite, &mut txn)
+      .await
+      .unwrap();
+  }
+  update_collab_member_expires_at(
+    user.uid,
+    &expired_oid,
+    Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+    &mut txn,
+  )
+  .await
+  .unwrap();
+  update_collab_member_expires_at(
+    user.uid,
+    &active_oid,
+    Some(chrono::Utc::now() + chrono::Duration::days(14)),
+    &mut txn,
+  )
+  .await
+  .unwrap();
+  txn.commit().await.unwrap();
+
+  let expired = delete_expired_collab_members(&pool).await.unwrap();
+  assert_eq!(expired, vec![(user.uid, expired_oid.clone())]);
+  assert!(!is_collab_member_exists(user.uid, &expired_oid, &pool)
+    .await
+    .unwrap());
+  assert!(is_collab_member_exists(user.uid, &active_oid, &pool)
+    .await
+    .unwrap());
+  assert!(is_collab_member_exists(user.uid, &permanent_oid, &pool)
+    .await
+    .unwrap());
+}
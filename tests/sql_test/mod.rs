@@ -1,4 +1,10 @@
 mod chat_test;
+mod collab_compaction_test;
+mod collab_member_test;
 mod history_test;
+mod publish_test;
+mod read_pool_test;
+mod user_deletion_test;
 pub(crate) mod util;
+mod workspace_sso_test;
 mod workspace_test;
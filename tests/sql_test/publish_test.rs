@@ -0,0 +1,327 @@
+use crate::sql_test::util::{setup_db, test_create_user};
+
+use database::publish::{
+  delete_expired_published_collabs, insert_or_replace_publish_collabs,
+  insert_publish_custom_domain, insert_published_view_hits, select_all_publish_namespaces,
+  select_publish_custom_domain_verified_by_other, select_publish_namespace_for_custom_domain,
+  select_published_collab_info, select_published_names_for_workspace,
+  select_published_view_daily_stats, select_published_view_referrer_stats,
+  select_workspace_publish_namespace, update_publish_custom_domain_verified,
+  update_publish_namespace_workspace, update_published_collab_expires_at,
+};
+use database_entity::dto::{PublishCollabItem, PublishCollabMetadata};
+use sqlx::PgPool;
+
+#[sqlx::test(migrations = false)]
+async fn select_all_publish_namespaces_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+
+  let mut workspace_ids = vec![];
+  for _ in 0..3 {
+    let user_uuid = uuid::Uuid::new_v4();
+    let name = user_uuid.to_string();
+    let email = format!("{}@appflowy.io", name);
+    let user = test_create_user(&pool, user_uuid, &email, &name)
+      .await
+      .unwrap();
+    workspace_ids.push(user.workspace_id);
+  }
+
+  let first_page = select_all_publish_namespaces(&pool, 2, 0).await.unwrap();
+  let second_page = select_all_publish_namespaces(&pool, 2, 2).await.unwrap();
+  assert_eq!(first_page.len(), 2);
+  assert_eq!(second_page.len(), 1);
+
+  let listed: Vec<String> = first_page
+    .iter()
+    .chain(second_page.iter())
+    .map(|info| info.workspace_id.to_string())
+    .collect();
+  assert_eq!(listed, workspace_ids);
+  assert!(first_page.iter().all(|info| !info.namespace.is_empty()));
+}
+
+#[sqlx::test(migrations = false)]
+async fn expired_published_collab_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+
+  let user_uuid = uuid::Uuid::new_v4();
+  let name = user_uuid.to_string();
+  let email = format!("{}@appflowy.io", name);
+  let user = test_create_user(&pool, user_uuid, &email, &name)
+    .await
+    .unwrap();
+  let workspace_id = uuid::Uuid::parse_str(&user.workspace_id).unwrap();
+
+  let view_ids = [uuid::Uuid::new_v4(), uuid::Uuid::new_v4()];
+  let items = view_ids
+    .iter()
+    .enumerate()
+    .map(|(i, view_id)| PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id: *view_id,
+        publish_name: format!("publish-name-{}", i),
+        metadata: serde_json::json!({}),
+      },
+      data: vec![1, 2, 3],
+    })
+    .collect();
+  insert_or_replace_publish_collabs(&pool, &workspace_id, &user_uuid, items)
+    .await
+    .unwrap();
+
+  let past = chrono::Utc::now() - chrono::Duration::minutes(1);
+  update_published_collab_expires_at(&pool, &workspace_id, &view_ids[0], Some(past))
+    .await
+    .unwrap();
+
+  // the expired view is hidden before the cleanup has run
+  let err = select_published_collab_info(&pool, &view_ids[0])
+    .await
+    .unwrap_err();
+  assert!(err.is_record_not_found());
+  select_published_collab_info(&pool, &view_ids[1])
+    .await
+    .unwrap();
+
+  let expired = delete_expired_published_collabs(&pool).await.unwrap();
+  assert_eq!(expired.len(), 1);
+  assert_eq!(expired[0].view_id, view_ids[0]);
+  assert!(delete_expired_published_collabs(&pool)
+    .await
+    .unwrap()
+    .is_empty());
+}
+
+#[sqlx::test(migrations = false)]
+async fn reassign_publish_namespace_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+
+  let mut users = vec![];
+  for _ in 0..2 {
+    let user_uuid = uuid::Uuid::new_v4();
+    let name = user_uuid.to_string();
+    let email = format!("{}@appflowy.io", name);
+    let user = test_create_user(&pool, user_uuid, &email, &name)
+      .await
+      .unwrap();
+    let workspace_id = uuid::Uuid::parse_str(&user.workspace_id).unwrap();
+    users.push((user_uuid, workspace_id));
+  }
+  let (from_user, from_workspace) = users[0];
+  let (to_user, to_workspace) = users[1];
+
+  let publish = |publish_name: &str| PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id: uuid::Uuid::new_v4(),
+      publish_name: publish_name.to_string(),
+      metadata: serde_json::json!({}),
+    },
+    data: vec![1, 2, 3],
+  };
+  insert_or_replace_publish_collabs(
+    &pool,
+    &from_workspace,
+    &from_user,
+    vec![publish("name-1"), publish("name-2")],
+  )
+  .await
+  .unwrap();
+  insert_or_replace_publish_collabs(&pool, &to_workspace, &to_user, vec![publish("name-1")])
+    .await
+    .unwrap();
+
+  let namespace = select_workspace_publish_namespace(&pool, &from_workspace)
+    .await
+    .unwrap();
+  let mut txn = pool.begin().await.unwrap();
+  let previous = update_publish_namespace_workspace(&mut txn, &namespace, &to_workspace)
+    .await
+    .unwrap();
+  txn.commit().await.unwrap();
+  assert_eq!(previous, from_workspace);
+
+  assert_eq!(
+    select_workspace_publish_namespace(&pool, &to_workspace)
+      .await
+      .unwrap(),
+    namespace
+  );
+  let fresh_namespace = select_workspace_publish_namespace(&pool, &from_workspace)
+    .await
+    .unwrap();
+  assert_ne!(fresh_namespace, namespace);
+
+  let names: Vec<String> = select_published_names_for_workspace(&pool, &to_workspace)
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|(_, name)| name)
+    .collect();
+  assert_eq!(names, vec!["name-1".to_string()]);
+}
+
+#[sqlx::test(migrations = false)]
+async fn publish_custom_domain_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+
+  let mut workspace_ids = vec![];
+  for _ in 0..2 {
+    let user_uuid = uuid::Uuid::new_v4();
+    let name = user_uuid.to_string();
+    let email = format!("{}@appflowy.io", name);
+    let user = test_create_user(&pool, user_uuid, &email, &name)
+      .await
+      .unwrap();
+    workspace_ids.push(uuid::Uuid::parse_str(&user.workspace_id).unwrap());
+  }
+  let (workspace_id, other_workspace_id) = (workspace_ids[0], workspace_ids[1]);
+  let domain = "docs.example.com";
+
+  // both workspaces can register the domain, and it is not served until verified
+  insert_publish_custom_domain(&pool, &workspace_id, domain, "token-1")
+    .await
+    .unwrap();
+  insert_publish_custom_domain(&pool, &other_workspace_id, domain, "token-2")
+    .await
+    .unwrap();
+  assert!(select_publish_namespace_for_custom_domain(&pool, domain)
+    .await
+    .unwrap()
+    .is_none());
+
+  let verified = update_publish_custom_domain_verified(&pool, &workspace_id, domain)
+    .await
+    .unwrap()
+    .unwrap();
+  assert!(verified.verified_at.is_some());
+  let namespace = select_workspace_publish_namespace(&pool, &workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(
+    select_publish_namespace_for_custom_domain(&pool, domain)
+      .await
+      .unwrap(),
+    Some(namespace)
+  );
+
+  // only one workspace can verify the domain
+  assert!(
+    select_publish_custom_domain_verified_by_other(&pool, &other_workspace_id, domain)
+      .await
+      .unwrap()
+  );
+  assert!(
+    update_publish_custom_domain_verified(&pool, &other_workspace_id, domain)
+      .await
+      .unwrap()
+      .is_none()
+  );
+}
+
+#[sqlx::test(migrations = false)]
+async fn published_view_stats_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+
+  let user_uuid = uuid::Uuid::new_v4();
+  let name = user_uuid.to_string();
+  let email = format!("{}@appflowy.io", name);
+  let user = test_create_user(&pool, user_uuid, &email, &name)
+    .await
+    .unwrap();
+  let workspace_id = uuid::Uuid::parse_str(&user.workspace_id).unwrap();
+  let namespace = select_workspace_publish_namespace(&pool, &workspace_id)
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  let item = PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: "publish-name".to_string(),
+      metadata: serde_json::json!({}),
+    },
+    data: vec![1, 2, 3],
+  };
+  insert_or_replace_publish_collabs(&pool, &workspace_id, &user_uuid, vec![item])
+    .await
+    .unwrap();
+
+  let record_hits = |publish_name: &str, referrer_host: Option<&str>, view_count: i64| {
+    let pool = &pool;
+    let publish_namespaces = vec![namespace.clone()];
+    let publish_names = vec![publish_name.to_string()];
+    let referrer_hosts = vec![referrer_host.map(|host| host.to_string())];
+    async move {
+      insert_published_view_hits(
+        pool,
+        &publish_namespaces,
+        &publish_names,
+        &referrer_hosts,
+        &[view_count],
+        2,
+        "(other)",
+      )
+      .await
+    }
+  };
+  record_hits("publish-name", None, 1).await.unwrap();
+  record_hits("publish-name", Some("google.com"), 2)
+    .await
+    .unwrap();
+  // reads of views that are not published are not counted
+  record_hits("unknown-name", None, 1).await.unwrap();
+
+  let today = chrono::Utc::now().date_naive();
+  let daily_stats = select_published_view_daily_stats(&pool, &workspace_id, today)
+    .await
+    .unwrap();
+  assert_eq!(daily_stats.len(), 1);
+  assert_eq!(daily_stats[0].view_id, view_id);
+  assert_eq!(daily_stats[0].publish_name.as_deref(), Some("publish-name"));
+  assert_eq!(daily_stats[0].view_count, 3);
+
+  let referrer_stats = select_published_view_referrer_stats(&pool, &workspace_id, today)
+    .await
+    .unwrap();
+  assert_eq!(referrer_stats.len(), 1);
+  assert_eq!(referrer_stats[0].referrer_host, "google.com");
+  assert_eq!(referrer_stats[0].view_count, 2);
+
+  // once a view counts as many referring sites as allowed for the day, the reads from new sites
+  // are counted together
+  insert_published_view_hits(
+    &pool,
+    &vec![namespace.clone(); 3],
+    &vec!["publish-name".to_string(); 3],
+    &[
+      Some("bing.com".to_string()),
+      Some("duckduckgo.com".to_string()),
+      Some("google.com".to_string()),
+    ],
+    &[3, 1, 1],
+    2,
+    "(other)",
+  )
+  .await
+  .unwrap();
+  let referrer_stats = select_published_view_referrer_stats(&pool, &workspace_id, today)
+    .await
+    .unwrap();
+  let referrers: Vec<(&str, i64)> = referrer_stats
+    .iter()
+    .map(|stat| (stat.referrer_host.as_str(), stat.view_count))
+    .collect();
+  assert_eq!(
+    referrers,
+    vec![("bing.com", 3), ("google.com", 3), ("(other)", 1)]
+  );
+
+  let tomorrow = today + chrono::Duration::days(1);
+  assert!(
+    select_published_view_daily_stats(&pool, &workspace_id, tomorrow)
+      .await
+      .unwrap()
+      .is_empty()
+  );
+}
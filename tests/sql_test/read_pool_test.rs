@@ -0,0 +1,47 @@
+use appflowy_cloud::biz::pg_read_pool::PgReadPool;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+
+fn replica_pool(pool: &PgPool, port: Option<u16>) -> PgPool {
+  let mut options = (*pool.connect_options())
+    .clone()
+    .application_name("read_replica");
+  if let Some(port) = port {
+    options = options.port(port);
+  }
+  PgPoolOptions::new().connect_lazy_with(options)
+}
+
+fn is_replica(pool: &PgPool) -> bool {
+  pool.connect_options().get_application_name() == Some("read_replica")
+}
+
+#[sqlx::test(migrations = false)]
+async fn read_pool_routes_to_available_replica_sql_test(pool: PgPool) {
+  let read_pool = PgReadPool::new(pool.clone(), Some(replica_pool(&pool, None)));
+  // the replica is used only once it answered
+  assert!(!is_replica(read_pool.get()));
+
+  read_pool.check_replica().await;
+  assert!(is_replica(read_pool.get()));
+  sqlx::query("SELECT 1")
+    .execute(read_pool.get())
+    .await
+    .unwrap();
+}
+
+#[sqlx::test(migrations = false)]
+async fn read_pool_falls_back_to_primary_sql_test(pool: PgPool) {
+  // nothing listens on port 1
+  let read_pool = PgReadPool::new(pool.clone(), Some(replica_pool(&pool, Some(1))));
+  read_pool.check_replica().await;
+  assert!(!is_replica(read_pool.get()));
+  sqlx::query("SELECT 1")
+    .execute(read_pool.get())
+    .await
+    .unwrap();
+
+  let read_pool = PgReadPool::new(pool.clone(), None);
+  read_pool.check_replica().await;
+  assert!(!is_replica(read_pool.get()));
+}
@@ -0,0 +1,102 @@
+use crate::sql_test::util::{setup_db, test_create_user};
+
+use chrono::{Duration, Utc};
+use database::user_deletion::{
+  delete_user_deletion, delete_workspace_memberships_of_user, select_due_user_deletions,
+  update_user_deletion_failed, update_workspace_owner, upsert_user_deletion,
+};
+use database::workspace::{select_user_owned_workspaces_id, select_workspace_member};
+use database::workspace_sso::insert_workspace_member_if_not_exists;
+use database_entity::dto::AFRole;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[sqlx::test(migrations = false)]
+async fn user_deletion_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+  let owner_uuid = Uuid::new_v4();
+  let owner = test_create_user(
+    &pool,
+    owner_uuid,
+    &format!("{}@appflowy.io", owner_uuid),
+    "owner",
+  )
+  .await
+  .unwrap();
+  let member_uuid = Uuid::new_v4();
+  let member = test_create_user(
+    &pool,
+    member_uuid,
+    &format!("{}@appflowy.io", member_uuid),
+    "member",
+  )
+  .await
+  .unwrap();
+  let workspace_id = Uuid::parse_str(&owner.workspace_id).unwrap();
+
+  // marking the account again keeps the grace period running
+  let purge_after = Utc::now() + Duration::days(30);
+  let row = upsert_user_deletion(&pool, owner.uid, &owner_uuid, &json!([]), purge_after)
+    .await
+    .unwrap();
+  let transfers = json!([{ "workspace_id": workspace_id, "new_owner_email": "a@appflowy.io" }]);
+  let updated = upsert_user_deletion(
+    &pool,
+    owner.uid,
+    &owner_uuid,
+    &transfers,
+    purge_after + Duration::days(1),
+  )
+  .await
+  .unwrap();
+  assert_eq!(updated.purge_after, row.purge_after);
+  assert_eq!(updated.workspace_transfers, transfers);
+
+  // only the accounts whose grace period has ended are due
+  assert!(select_due_user_deletions(&pool, Utc::now(), 10)
+    .await
+    .unwrap()
+    .iter()
+    .all(|row| row.uid != owner.uid));
+  let due = select_due_user_deletions(&pool, purge_after + Duration::seconds(1), 10)
+    .await
+    .unwrap();
+  assert!(due.iter().any(|row| row.uid == owner.uid));
+  update_user_deletion_failed(&pool, owner.uid, "boom")
+    .await
+    .unwrap();
+  let due = select_due_user_deletions(&pool, purge_after + Duration::seconds(1), 10)
+    .await
+    .unwrap();
+  let row = due.iter().find(|row| row.uid == owner.uid).unwrap();
+  assert_eq!(row.attempts, 1);
+  assert_eq!(row.last_error.as_deref(), Some("boom"));
+
+  // the ownership of the workspace goes to a member
+  insert_workspace_member_if_not_exists(&pool, &workspace_id, member.uid, AFRole::Member.into())
+    .await
+    .unwrap();
+  update_workspace_owner(&pool, &workspace_id, member.uid)
+    .await
+    .unwrap();
+  let owned = select_user_owned_workspaces_id(&pool, &member_uuid)
+    .await
+    .unwrap();
+  assert!(owned.contains(&workspace_id));
+  let new_owner = select_workspace_member(&pool, &member.uid, &workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(new_owner.role, AFRole::Owner);
+
+  let workspace_ids = delete_workspace_memberships_of_user(&pool, owner.uid)
+    .await
+    .unwrap();
+  assert!(workspace_ids.contains(&workspace_id));
+  assert!(select_workspace_member(&pool, &owner.uid, &workspace_id)
+    .await
+    .is_err());
+
+  assert!(delete_user_deletion(&pool, owner.uid).await.unwrap());
+  assert!(!delete_user_deletion(&pool, owner.uid).await.unwrap());
+}
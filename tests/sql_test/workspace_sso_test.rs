@@ -0,0 +1,103 @@
+use crate::sql_test::util::{setup_db, test_create_user};
+
+use database::workspace_sso::{
+  insert_workspace_member_if_not_exists, select_enforced_workspace_sso,
+  select_is_workspace_member_by_uuid, select_workspace_sso_by_provider, upsert_workspace_sso,
+};
+use database_entity::dto::AFRole;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[sqlx::test(migrations = false)]
+async fn workspace_sso_enforcement_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+  let owner_uuid = Uuid::new_v4();
+  let owner = test_create_user(
+    &pool,
+    owner_uuid,
+    &format!("{}@appflowy.io", owner_uuid),
+    "owner",
+  )
+  .await
+  .unwrap();
+  let member_uuid = Uuid::new_v4();
+  let member = test_create_user(
+    &pool,
+    member_uuid,
+    &format!("{}@appflowy.io", member_uuid),
+    "member",
+  )
+  .await
+  .unwrap();
+  let workspace_id = Uuid::parse_str(&owner.workspace_id).unwrap();
+  let provider_id = Uuid::new_v4();
+
+  let row = upsert_workspace_sso(
+    &pool,
+    &workspace_id,
+    &provider_id,
+    "https://idp.example.com/metadata",
+    None,
+    &["example.com".to_string()],
+    AFRole::Guest.into(),
+    false,
+  )
+  .await
+  .unwrap();
+  assert!(!row.enforced);
+  assert!(select_enforced_workspace_sso(&pool, &workspace_id)
+    .await
+    .unwrap()
+    .is_none());
+
+  upsert_workspace_sso(
+    &pool,
+    &workspace_id,
+    &provider_id,
+    "https://idp.example.com/metadata",
+    None,
+    &["example.com".to_string()],
+    AFRole::Guest.into(),
+    true,
+  )
+  .await
+  .unwrap();
+  assert_eq!(
+    select_enforced_workspace_sso(&pool, &workspace_id)
+      .await
+      .unwrap(),
+    Some((provider_id, Some(owner_uuid)))
+  );
+
+  // the first sign-in through the provider adds the user, the next ones keep its role
+  let row = select_workspace_sso_by_provider(&pool, &provider_id)
+    .await
+    .unwrap()
+    .unwrap();
+  assert!(
+    !select_is_workspace_member_by_uuid(&pool, &workspace_id, &member_uuid)
+      .await
+      .unwrap()
+  );
+  assert!(insert_workspace_member_if_not_exists(
+    &pool,
+    &workspace_id,
+    member.uid,
+    row.default_role_id
+  )
+  .await
+  .unwrap());
+  assert!(!insert_workspace_member_if_not_exists(
+    &pool,
+    &workspace_id,
+    member.uid,
+    AFRole::Member.into()
+  )
+  .await
+  .unwrap());
+  assert!(
+    select_is_workspace_member_by_uuid(&pool, &workspace_id, &member_uuid)
+      .await
+      .unwrap()
+  );
+}
@@ -0,0 +1,97 @@
+use std::io::{Cursor, Read};
+use std::time::Duration;
+
+use app_error::ErrorCode;
+use client_api::Client;
+use client_api_test::generate_unique_registered_user_client;
+use shared_entity::dto::workspace_dto::{UserDataExportTask, WorkspaceExportStatus};
+
+fn read_archive(data: &[u8]) -> Vec<(String, String)> {
+  let mut archive = zip::ZipArchive::new(Cursor::new(data)).unwrap();
+  (0..archive.len())
+    .map(|i| {
+      let mut file = archive.by_index(i).unwrap();
+      let mut content = String::new();
+      file.read_to_string(&mut content).unwrap();
+      (file.name().to_string(), content)
+    })
+    .collect()
+}
+
+async fn wait_for_export(c: &Client, task: UserDataExportTask) -> UserDataExportTask {
+  let mut task = c.get_user_data_export_task(&task.task_id).await.unwrap();
+  for _ in 0..30 {
+    if task.status != WorkspaceExportStatus::Pending {
+      break;
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    task = c.get_user_data_export_task(&task.task_id).await.unwrap();
+  }
+  task
+}
+
+#[tokio::test]
+async fn user_data_export_test() {
+  let (c, user) = generate_unique_registered_user_client().await;
+  let task = c.start_user_data_export().await.unwrap();
+  let task = wait_for_export(&c, task).await;
+  assert_eq!(task.status, WorkspaceExportStatus::Completed);
+  assert!(task.download_url_expires_at.is_some());
+  let download_url = task.download_url.clone().unwrap();
+
+  // the signed link works without authentication
+  let (anonymous, _) = generate_unique_registered_user_client().await;
+  let data = anonymous
+    .download_user_data_export(&download_url)
+    .await
+    .unwrap();
+  assert_eq!(task.file_size, Some(data.len() as i64));
+  let files = read_archive(&data);
+  let profile = files
+    .iter()
+    .find(|(name, _)| name == "profile.json")
+    .map(|(_, content)| content)
+    .unwrap();
+  assert!(profile.contains(&user.email));
+  assert!(files.iter().any(|(name, _)| name == "workspaces.json"));
+  assert!(files.iter().any(|(name, _)| name == "comments.json"));
+  assert!(files
+    .iter()
+    .any(|(name, _)| name.starts_with("collabs/") && name.ends_with(".md")));
+
+  // a tampered link is rejected
+  let tampered_url = download_url.replace("expires=", "expires=1");
+  let error = c
+    .download_user_data_export(&tampered_url)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::UserUnAuthorized);
+
+  // tasks are only visible to the user that started them
+  assert!(anonymous
+    .get_user_data_export_task(&task.task_id)
+    .await
+    .is_err());
+}
+
+#[tokio::test]
+async fn user_data_export_limit_test() {
+  let (c, _) = generate_unique_registered_user_client().await;
+  let task = c.start_user_data_export().await.unwrap();
+
+  // a single export runs at a time
+  let error = c.start_user_data_export().await.unwrap_err();
+  assert_eq!(error.code, ErrorCode::TooManyRequests);
+
+  let task = wait_for_export(&c, task).await;
+  assert_eq!(task.status, WorkspaceExportStatus::Completed);
+  for _ in 0..2 {
+    let task = c.start_user_data_export().await.unwrap();
+    let task = wait_for_export(&c, task).await;
+    assert_eq!(task.status, WorkspaceExportStatus::Completed);
+  }
+
+  // the exports of a day are limited
+  let error = c.start_user_data_export().await.unwrap_err();
+  assert_eq!(error.code, ErrorCode::TooManyRequests);
+}
@@ -1,6 +1,8 @@
+use app_error::ErrorCode;
 use client_api::entity::AFRole;
 use client_api_test::*;
 use gotrue::params::{AdminDeleteUserParams, AdminUserParams};
+use shared_entity::dto::auth_dto::{RequestAccountDeletionParams, WorkspaceOwnershipTransfer};
 
 #[tokio::test]
 async fn user_delete_self() {
@@ -104,3 +106,50 @@ async fn admin_delete_create_same_user_hard() {
   assert_ne!(user_uuid, recreated_user_uuid);
   assert_ne!(workspace_id, recreated_workspace_uuid);
 }
+
+#[tokio::test]
+async fn user_request_and_cancel_account_deletion() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let workspace_uuid = uuid::Uuid::parse_str(&workspace_id).unwrap();
+
+  // the ownership can only go to another member of the workspace
+  let (_, stranger) = generate_unique_registered_user_client().await;
+  let error = owner
+    .api_client
+    .request_account_deletion(RequestAccountDeletionParams {
+      workspace_transfers: vec![WorkspaceOwnershipTransfer {
+        workspace_id: workspace_uuid,
+        new_owner_email: stranger.email,
+      }],
+    })
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+
+  let transfers = vec![WorkspaceOwnershipTransfer {
+    workspace_id: workspace_uuid,
+    new_owner_email: member.email().await,
+  }];
+  let deletion = owner
+    .api_client
+    .request_account_deletion(RequestAccountDeletionParams {
+      workspace_transfers: transfers.clone(),
+    })
+    .await
+    .unwrap();
+  assert!(deletion.purge_after > deletion.requested_at);
+  let deletion = owner.api_client.get_account_deletion().await.unwrap();
+  assert_eq!(deletion.workspace_transfers, transfers);
+
+  // the account is untouched during the grace period, and can be restored
+  owner.api_client.get_workspaces().await.unwrap();
+  owner.api_client.cancel_account_deletion().await.unwrap();
+  let error = owner.api_client.get_account_deletion().await.unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+}
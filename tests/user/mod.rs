@@ -1,4 +1,6 @@
+mod data_export;
 mod delete;
+mod notification_test;
 mod refresh;
 mod sign_in;
 mod sign_out;
@@ -0,0 +1,69 @@
+use client_api_test::*;
+use collab_entity::CollabType;
+use database_entity::dto::{AFAccessLevel, CreateCollabParams, InsertCollabMemberParams};
+use shared_entity::dto::notification_dto::{EmailDelivery, NotificationKind, NotificationSettings};
+use uuid::Uuid;
+
+use crate::collab::util::test_encode_collab_v1;
+
+#[tokio::test]
+async fn notification_settings_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let settings = c.get_notification_settings().await.unwrap();
+  assert_eq!(settings.email_delivery, EmailDelivery::Immediate);
+
+  c.update_notification_settings(&NotificationSettings {
+    email_delivery: EmailDelivery::DailyDigest,
+  })
+  .await
+  .unwrap();
+  let settings = c.get_notification_settings().await.unwrap();
+  assert_eq!(settings.email_delivery, EmailDelivery::DailyDigest);
+}
+
+#[tokio::test]
+async fn shared_with_you_notification_test() {
+  let (c_1, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c_1).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world");
+  c_1
+    .create_collab(CreateCollabParams {
+      object_id: object_id.clone(),
+      encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+      collab_type: CollabType::Unknown,
+      workspace_id: workspace_id.clone(),
+    })
+    .await
+    .unwrap();
+
+  let (c_2, _user) = generate_unique_registered_user_client().await;
+  let uid_2 = c_2.get_profile().await.unwrap().uid;
+  assert!(c_2
+    .get_notifications()
+    .await
+    .unwrap()
+    .notifications
+    .is_empty());
+
+  c_1
+    .add_collab_member(InsertCollabMemberParams {
+      uid: uid_2,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      access_level: AFAccessLevel::ReadAndComment,
+      expires_at: None,
+      group_id: None,
+    })
+    .await
+    .unwrap();
+
+  let notifications = c_2.get_notifications().await.unwrap().notifications;
+  assert_eq!(notifications.len(), 1);
+  assert_eq!(notifications[0].kind, NotificationKind::SharedWithYou);
+  assert_eq!(notifications[0].data["object_id"], object_id);
+  assert_eq!(
+    notifications[0].workspace_id.unwrap().to_string(),
+    workspace_id
+  );
+}
@@ -0,0 +1,40 @@
+use collab_entity::CollabType;
+use serde_json::json;
+
+use client_api_test::*;
+
+#[tokio::test]
+async fn sync_large_update_with_compression_test() {
+  let mut client_1 = TestClient::new_user().await;
+  let workspace_id = client_1.workspace_id().await;
+  let object_id = client_1
+    .create_and_edit_collab(&workspace_id, CollabType::Unknown)
+    .await;
+
+  // Large enough for the messages of both sides to be compressed
+  let text = "AppFlowy ".repeat(2000);
+  client_1
+    .insert_into(&object_id, "text", text.as_str())
+    .await;
+  client_1
+    .wait_object_sync_complete(&object_id)
+    .await
+    .unwrap();
+  let expected = json!({ "text": text });
+  assert_server_collab(
+    &workspace_id,
+    &mut client_1.api_client,
+    &object_id,
+    &CollabType::Unknown,
+    10,
+    expected.clone(),
+  )
+  .await
+  .unwrap();
+
+  let mut client_2 = TestClient::new(client_1.user.clone(), true).await;
+  client_2
+    .open_collab(&workspace_id, &object_id, CollabType::Unknown)
+    .await;
+  assert_client_collab_within_secs(&mut client_2, &object_id, "text", expected, 60).await;
+}
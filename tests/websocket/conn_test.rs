@@ -1,8 +1,9 @@
 use std::time::{Duration, SystemTime};
-use tokio::time::timeout;
+use tokio::time::{sleep, timeout};
 
 use client_api::ws::{ConnectState, WSClient, WSClientConfig};
 use client_api_test::generate_unique_registered_user_client;
+use collab_rt_entity::RealtimeProtocolVersion;
 
 #[tokio::test]
 async fn realtime_connect_test() {
@@ -80,3 +81,22 @@ async fn realtime_disconnect_test() {
     }
   }
 }
+
+#[tokio::test]
+async fn realtime_protocol_version_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let ws_client = WSClient::new(WSClientConfig::default(), c.clone(), c.clone());
+  assert_eq!(ws_client.protocol_version(), RealtimeProtocolVersion::V1);
+  ws_client.connect().await.unwrap();
+
+  // The server confirms the version right after the connection is opened
+  let confirmed = async {
+    while ws_client.protocol_version() != RealtimeProtocolVersion::CURRENT {
+      sleep(Duration::from_millis(100)).await;
+    }
+  };
+  match timeout(Duration::from_secs(10), confirmed).await {
+    Ok(_) => {},
+    Err(_) => panic!("Protocol version not confirmed."),
+  }
+}
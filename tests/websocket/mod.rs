@@ -1,2 +1,4 @@
 mod actor_test;
+mod compression_test;
 mod conn_test;
+mod realtime_health_test;
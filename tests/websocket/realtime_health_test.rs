@@ -0,0 +1,47 @@
+use app_error::ErrorCode;
+use collab_entity::CollabType;
+
+use client_api_test::*;
+
+#[tokio::test]
+async fn realtime_health_test() {
+  let mut test_client = TestClient::new_user().await;
+  let workspace_id = test_client.workspace_id().await;
+  let object_id = test_client
+    .create_and_edit_collab(&workspace_id, CollabType::Unknown)
+    .await;
+  test_client.insert_into(&object_id, "1", "a").await;
+  test_client
+    .wait_object_sync_complete(&object_id)
+    .await
+    .unwrap();
+
+  let health = admin_user_client()
+    .await
+    .get_realtime_health(Some(1000))
+    .await
+    .unwrap();
+  assert!(health.connected_users >= 1);
+  assert!(health.applied_updates >= 1);
+  let collab = health
+    .collabs
+    .iter()
+    .find(|collab| collab.object_id == object_id)
+    .unwrap();
+  assert_eq!(collab.workspace_id, workspace_id);
+  assert_eq!(collab.sessions, 1);
+  let workspace = health
+    .workspaces
+    .iter()
+    .find(|workspace| workspace.workspace_id == workspace_id)
+    .unwrap();
+  assert_eq!(workspace.connected_users, 1);
+
+  // only the administrator of the server can read it
+  let err = test_client
+    .api_client
+    .get_realtime_health(None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
@@ -0,0 +1,91 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use database_entity::dto::AFRole;
+use shared_entity::dto::workspace_dto::{AuditAction, QueryAuditLog};
+
+#[tokio::test]
+async fn audit_log_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let member_uid = member.uid().await;
+
+  // only the owner reads the audit log
+  let error = member
+    .api_client
+    .get_workspace_audit_log(&workspace_id, &QueryAuditLog::default())
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::UserUnAuthorized);
+
+  let page = owner
+    .api_client
+    .get_workspace_audit_log(
+      &workspace_id,
+      &QueryAuditLog {
+        action: Some(AuditAction::MemberAdded),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  let entry = page
+    .entries
+    .iter()
+    .find(|entry| entry.metadata["uid"] == member_uid)
+    .unwrap();
+  assert_eq!(entry.action, AuditAction::MemberAdded);
+  assert_eq!(entry.actor_uid, Some(member_uid));
+
+  // the sign in of a member is part of the audit log of the workspace
+  let page = owner
+    .api_client
+    .get_workspace_audit_log(
+      &workspace_id,
+      &QueryAuditLog {
+        actor_uid: Some(member_uid),
+        action: Some(AuditAction::UserLogin),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert!(!page.entries.is_empty());
+  assert!(page
+    .entries
+    .iter()
+    .all(|entry| entry.workspace_id.is_none()));
+
+  // the entries are paginated, the most recent first
+  let first_page = owner
+    .api_client
+    .get_workspace_audit_log(
+      &workspace_id,
+      &QueryAuditLog {
+        first: Some(1),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(first_page.entries.len(), 1);
+  assert!(first_page.has_next_page);
+  let second_page = owner
+    .api_client
+    .get_workspace_audit_log(
+      &workspace_id,
+      &QueryAuditLog {
+        first: Some(1),
+        after: first_page.end_cursor.clone(),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(second_page.entries.len(), 1);
+  assert!(second_page.entries[0].id < first_page.entries[0].id);
+}
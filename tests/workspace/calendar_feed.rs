@@ -0,0 +1,43 @@
+use app_error::ErrorCode;
+use client_api_test::{generate_unique_registered_user_client, localhost_client};
+
+#[tokio::test]
+async fn database_calendar_feed_requires_date_field_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let view_id = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .children
+    .iter()
+    .find(|v| v.name == "To-dos")
+    .unwrap()
+    .view_id
+    .clone();
+
+  // the To-dos board has no date field to show its rows in a calendar
+  let err = c
+    .create_database_calendar_feed(&workspace_id, &view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  let err = c
+    .delete_database_calendar_feed(&workspace_id, &view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  let err = localhost_client()
+    .get_database_calendar_feed("not-a-feed-token")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
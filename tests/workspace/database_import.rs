@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use app_error::ErrorCode;
+use client_api_test::generate_unique_registered_user_client;
+use shared_entity::dto::workspace_dto::{QueryDatabaseRows, WorkspaceImportStatus};
+
+#[tokio::test]
+async fn import_csv_into_database_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let view_id = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .children
+    .iter()
+    .find(|v| v.name == "To-dos")
+    .unwrap()
+    .view_id
+    .clone();
+
+  let error = c
+    .import_database_csv(&workspace_id, &view_id, b"Description\n".to_vec())
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+
+  // the Notes column matches no field, so a text field is created for it
+  let csv = "Description,Status,Notes\nImported,,From a CSV file\nBroken,Not an option,Skipped\n";
+  let task = c
+    .import_database_csv(&workspace_id, &view_id, csv.as_bytes().to_vec())
+    .await
+    .unwrap();
+  assert_eq!(task.total_rows, 2);
+  let mut task = c
+    .get_database_import_task(&workspace_id, &view_id, &task.task_id)
+    .await
+    .unwrap();
+  for _ in 0..30 {
+    if task.status != WorkspaceImportStatus::Pending {
+      break;
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    task = c
+      .get_database_import_task(&workspace_id, &view_id, &task.task_id)
+      .await
+      .unwrap();
+  }
+  assert_eq!(task.status, WorkspaceImportStatus::Completed);
+  assert_eq!(task.imported_rows, 1);
+  assert_eq!(task.failed_rows, 1);
+  assert_eq!(task.row_errors.len(), 1);
+  assert_eq!(task.row_errors[0].row, 2);
+
+  let rows = c
+    .list_database_rows(&workspace_id, &view_id, &QueryDatabaseRows::default())
+    .await
+    .unwrap();
+  assert_eq!(rows.total, 6);
+  let notes = rows.fields.iter().find(|f| f.name == "Notes").unwrap();
+  let primary_field = rows.fields.iter().find(|f| f.is_primary).unwrap();
+  let row = rows.rows.last().unwrap();
+  assert_eq!(
+    row.cells.get(&primary_field.id).map(String::as_str),
+    Some("Imported")
+  );
+  assert_eq!(
+    row.cells.get(&notes.id).map(String::as_str),
+    Some("From a CSV file")
+  );
+}
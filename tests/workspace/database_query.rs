@@ -0,0 +1,127 @@
+use app_error::ErrorCode;
+use client_api_test::generate_unique_registered_user_client;
+use serde_json::json;
+use shared_entity::dto::workspace_dto::{
+  CreateDatabaseRowParams, DatabaseFilterOp, DatabaseRowCondition, DatabaseRowFilter,
+  DatabaseRowSort, QueryDatabaseRows, QueryDatabaseViewParams,
+};
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn query_database_view_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let view_id = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .children
+    .iter()
+    .find(|v| v.name == "To-dos")
+    .unwrap()
+    .view_id
+    .clone();
+  let rows = c
+    .list_database_rows(&workspace_id, &view_id, &QueryDatabaseRows::default())
+    .await
+    .unwrap();
+  let primary_field = rows.fields.iter().find(|f| f.is_primary).unwrap().clone();
+
+  for name in ["query b", "query a", "query c"] {
+    c.create_database_row(
+      &workspace_id,
+      &view_id,
+      &CreateDatabaseRowParams {
+        cells: HashMap::from([(primary_field.name.clone(), json!(name))]),
+      },
+    )
+    .await
+    .unwrap();
+  }
+
+  // the view has no filter, so every row is returned
+  let all = c
+    .query_database_view(&workspace_id, &view_id, &QueryDatabaseViewParams::default())
+    .await
+    .unwrap();
+  assert_eq!(all.total, rows.total + 3);
+
+  let contains = |value: &str| {
+    DatabaseRowFilter::Condition(DatabaseRowCondition {
+      field: primary_field.name.clone(),
+      op: DatabaseFilterOp::Contains,
+      value: json!(value),
+    })
+  };
+  let result = c
+    .query_database_view(
+      &workspace_id,
+      &view_id,
+      &QueryDatabaseViewParams {
+        filter: Some(DatabaseRowFilter::And(vec![
+          contains("QUERY"),
+          DatabaseRowFilter::Condition(DatabaseRowCondition {
+            field: primary_field.id.clone(),
+            op: DatabaseFilterOp::IsNot,
+            value: json!("query c"),
+          }),
+        ])),
+        sorts: Some(vec![DatabaseRowSort {
+          field: primary_field.name.clone(),
+          descending: true,
+        }]),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  let names: Vec<&str> = result
+    .rows
+    .iter()
+    .map(|row| row.cells[&primary_field.id].as_str())
+    .collect();
+  assert_eq!(result.total, 2);
+  assert_eq!(names, vec!["query b", "query a"]);
+
+  let result = c
+    .query_database_view(
+      &workspace_id,
+      &view_id,
+      &QueryDatabaseViewParams {
+        filter: Some(DatabaseRowFilter::Or(vec![
+          contains("query a"),
+          contains("query c"),
+        ])),
+        limit: Some(1),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(result.total, 2);
+  assert_eq!(result.rows.len(), 1);
+
+  let error = c
+    .query_database_view(
+      &workspace_id,
+      &view_id,
+      &QueryDatabaseViewParams {
+        filter: Some(DatabaseRowFilter::Condition(DatabaseRowCondition {
+          field: primary_field.name.clone(),
+          op: DatabaseFilterOp::GreaterThan,
+          value: json!(1),
+        })),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+}
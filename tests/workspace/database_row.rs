@@ -0,0 +1,147 @@
+use app_error::ErrorCode;
+use client_api_test::generate_unique_registered_user_client;
+use serde_json::json;
+use shared_entity::dto::workspace_dto::{
+  CreateDatabaseRowParams, QueryDatabaseRows, UpdateDatabaseRowParams,
+};
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn database_row_crud_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id.to_string();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let todos = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .children
+    .iter()
+    .find(|v| v.name == "To-dos")
+    .unwrap();
+  let view_id = todos.view_id.clone();
+
+  let rows = c
+    .list_database_rows(&workspace_id, &view_id, &QueryDatabaseRows::default())
+    .await
+    .unwrap();
+  assert_eq!(rows.total, 5);
+  assert_eq!(rows.rows.len(), 5);
+  let primary_field = rows.fields.iter().find(|f| f.is_primary).unwrap().clone();
+
+  // cells can be given by field name
+  let row = c
+    .create_database_row(
+      &workspace_id,
+      &view_id,
+      &CreateDatabaseRowParams {
+        cells: HashMap::from([(primary_field.name.clone(), json!("Sent by an integration"))]),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(
+    row.cells.get(&primary_field.id).map(String::as_str),
+    Some("Sent by an integration")
+  );
+  let rows = c
+    .list_database_rows(&workspace_id, &view_id, &QueryDatabaseRows::default())
+    .await
+    .unwrap();
+  assert_eq!(rows.total, 6);
+  assert_eq!(rows.rows.last().unwrap().id, row.id);
+
+  // and by field id
+  let updated = c
+    .update_database_row(
+      &workspace_id,
+      &view_id,
+      &row.id,
+      &UpdateDatabaseRowParams {
+        cells: HashMap::from([(primary_field.id.clone(), json!("Edited"))]),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(
+    updated.cells.get(&primary_field.id).map(String::as_str),
+    Some("Edited")
+  );
+
+  let error = c
+    .update_database_row(
+      &workspace_id,
+      &view_id,
+      &row.id,
+      &UpdateDatabaseRowParams {
+        cells: HashMap::from([("no such field".to_string(), json!("value"))]),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+
+  let page = c
+    .list_database_rows(
+      &workspace_id,
+      &view_id,
+      &QueryDatabaseRows {
+        offset: Some(5),
+        limit: Some(10),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(page.rows.len(), 1);
+  assert_eq!(
+    page.rows[0]
+      .cells
+      .get(&primary_field.id)
+      .map(String::as_str),
+    Some("Edited")
+  );
+
+  c.delete_database_row(&workspace_id, &view_id, &row.id)
+    .await
+    .unwrap();
+  let rows = c
+    .list_database_rows(&workspace_id, &view_id, &QueryDatabaseRows::default())
+    .await
+    .unwrap();
+  assert_eq!(rows.total, 5);
+  assert!(rows.rows.iter().all(|r| r.id != row.id));
+}
+
+#[tokio::test]
+async fn database_row_requires_write_access_test() {
+  let (owner, _) = generate_unique_registered_user_client().await;
+  let (other, _) = generate_unique_registered_user_client().await;
+  let workspace_id = owner.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let folder_view = owner
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let view_id = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .children
+    .iter()
+    .find(|v| v.name == "To-dos")
+    .unwrap()
+    .view_id
+    .clone();
+
+  let result = other
+    .create_database_row(&workspace_id, &view_id, &CreateDatabaseRowParams::default())
+    .await;
+  assert!(result.is_err());
+}
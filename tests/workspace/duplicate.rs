@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use client_api_test::generate_unique_registered_user_client;
+use shared_entity::dto::workspace_dto::{
+  DuplicateWorkspaceParams, FolderView, WorkspaceDuplicateStatus,
+};
+
+fn view_names(folder_view: &FolderView) -> Vec<String> {
+  let mut names = vec![];
+  for child in &folder_view.children {
+    names.push(child.name.clone());
+    names.extend(view_names(child));
+  }
+  names
+}
+
+fn view_ids(folder_view: &FolderView) -> Vec<String> {
+  let mut ids = vec![];
+  for child in &folder_view.children {
+    ids.push(child.view_id.clone());
+    ids.extend(view_ids(child));
+  }
+  ids
+}
+
+#[tokio::test]
+async fn duplicate_workspace() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+
+  let task = c
+    .duplicate_workspace(
+      &workspace_id,
+      &DuplicateWorkspaceParams {
+        workspace_name: Some("Project copy".to_string()),
+        include_members: false,
+      },
+    )
+    .await
+    .unwrap();
+  let mut task = c
+    .get_workspace_duplicate_task(&workspace_id, &task.task_id)
+    .await
+    .unwrap();
+  for _ in 0..30 {
+    if task.status != WorkspaceDuplicateStatus::Pending {
+      break;
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    task = c
+      .get_workspace_duplicate_task(&workspace_id, &task.task_id)
+      .await
+      .unwrap();
+  }
+  assert_eq!(task.status, WorkspaceDuplicateStatus::Completed);
+  assert_eq!(task.copied_objects, task.total_objects);
+  let new_workspace_id = task.new_workspace_id.unwrap().to_string();
+
+  let workspaces = c.get_workspaces().await.unwrap();
+  let new_workspace = workspaces
+    .iter()
+    .find(|workspace| workspace.workspace_id.to_string() == new_workspace_id)
+    .unwrap();
+  assert_eq!(new_workspace.workspace_name, "Project copy");
+
+  let folder = c
+    .get_workspace_folder(&workspace_id, Some(10), None)
+    .await
+    .unwrap();
+  let new_folder = c
+    .get_workspace_folder(&new_workspace_id, Some(10), None)
+    .await
+    .unwrap();
+  assert_eq!(view_names(&folder), view_names(&new_folder));
+  let ids = view_ids(&folder);
+  assert!(view_ids(&new_folder).iter().all(|id| !ids.contains(id)));
+}
+
+#[tokio::test]
+async fn duplicate_workspace_with_members_requires_owner() {
+  let (owner, _) = generate_unique_registered_user_client().await;
+  let (other, _) = generate_unique_registered_user_client().await;
+  let workspace_id = owner.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let other_workspace_id = other.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+
+  let result = other
+    .duplicate_workspace(
+      &workspace_id,
+      &DuplicateWorkspaceParams {
+        workspace_name: None,
+        include_members: true,
+      },
+    )
+    .await;
+  assert!(result.is_err());
+  let task = other
+    .duplicate_workspace(
+      &other_workspace_id,
+      &DuplicateWorkspaceParams {
+        workspace_name: None,
+        include_members: true,
+      },
+    )
+    .await
+    .unwrap();
+  assert!(task.include_members);
+}
@@ -0,0 +1,132 @@
+use std::io::{Cursor, Read};
+use std::time::Duration;
+
+use client_api_test::generate_unique_registered_user_client;
+use shared_entity::dto::workspace_dto::{DatabaseExportFormat, WorkspaceExportStatus};
+
+fn read_archive(data: &[u8]) -> Vec<(String, String)> {
+  let mut archive = zip::ZipArchive::new(Cursor::new(data)).unwrap();
+  (0..archive.len())
+    .map(|i| {
+      let mut file = archive.by_index(i).unwrap();
+      let mut content = String::new();
+      file.read_to_string(&mut content).unwrap();
+      (file.name().to_string(), content)
+    })
+    .collect()
+}
+
+#[tokio::test]
+async fn export_workspace_as_zip() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+
+  let data = c.export_workspace(&workspace_id).await.unwrap();
+  let files = read_archive(&data);
+  let (_, getting_started) = files
+    .iter()
+    .find(|(name, _)| name == "General/Getting started.md")
+    .unwrap();
+  assert!(!getting_started.is_empty());
+  let (_, todos) = files
+    .iter()
+    .find(|(name, _)| name == "General/To-dos.csv")
+    .unwrap();
+  // the header and the 5 rows of the database
+  assert_eq!(todos.lines().count(), 6);
+}
+
+#[tokio::test]
+async fn export_database_view_as_csv_and_xlsx() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let view_id = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .children
+    .iter()
+    .find(|v| v.name == "To-dos")
+    .unwrap()
+    .view_id
+    .clone();
+
+  let csv = c
+    .export_database_view(&workspace_id, &view_id, DatabaseExportFormat::Csv)
+    .await
+    .unwrap();
+  let csv = String::from_utf8(csv.to_vec()).unwrap();
+  // the header and the 5 rows of the database
+  assert_eq!(csv.lines().count(), 6);
+
+  let xlsx = c
+    .export_database_view(&workspace_id, &view_id, DatabaseExportFormat::Xlsx)
+    .await
+    .unwrap();
+  let files = read_archive(&xlsx);
+  let (_, sheet) = files
+    .iter()
+    .find(|(name, _)| name == "xl/worksheets/sheet1.xml")
+    .unwrap();
+  assert_eq!(sheet.matches("<row ").count(), 6);
+  assert!(files.iter().any(|(name, _)| name == "[Content_Types].xml"));
+
+  // the database can't be exported by someone who can't read it
+  let (other, _user) = generate_unique_registered_user_client().await;
+  let result = other
+    .export_database_view(&workspace_id, &view_id, DatabaseExportFormat::Csv)
+    .await;
+  assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn export_workspace_in_background() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+
+  let task = c.start_workspace_export(&workspace_id).await.unwrap();
+  let mut task = c
+    .get_workspace_export_task(&workspace_id, &task.task_id)
+    .await
+    .unwrap();
+  for _ in 0..30 {
+    if task.status != WorkspaceExportStatus::Pending {
+      break;
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    task = c
+      .get_workspace_export_task(&workspace_id, &task.task_id)
+      .await
+      .unwrap();
+  }
+  assert_eq!(task.status, WorkspaceExportStatus::Completed);
+  assert!(task.download_url.is_some());
+
+  let data = c
+    .download_workspace_export(&workspace_id, &task.task_id)
+    .await
+    .unwrap();
+  assert_eq!(task.file_size, Some(data.len() as i64));
+  let files = read_archive(&data);
+  assert!(files
+    .iter()
+    .any(|(name, _)| name == "General/Getting started.md"));
+
+  // tasks are only visible to the user that started them
+  let (other_client, _) = generate_unique_registered_user_client().await;
+  assert!(other_client
+    .get_workspace_export_task(&workspace_id, &task.task_id)
+    .await
+    .is_err());
+}
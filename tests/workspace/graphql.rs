@@ -0,0 +1,55 @@
+use client_api_test::generate_unique_registered_user_client;
+use serde_json::json;
+
+const WORKSPACE_QUERY: &str = r#"
+  query Workspace($id: UUID!) {
+    workspace(id: $id) {
+      name
+      members { email role }
+      folder(depth: 2) { name children { name layout children { name } } }
+      publishedViews { viewId }
+    }
+  }
+"#;
+
+#[tokio::test]
+async fn query_workspace_structure_with_graphql() {
+  let (c, user) = generate_unique_registered_user_client().await;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id.to_string();
+
+  let resp = c
+    .query_workspace_graphql(WORKSPACE_QUERY, json!({ "id": workspace_id }))
+    .await
+    .unwrap();
+  assert!(resp.get("errors").is_none(), "{}", resp);
+  let workspace = &resp["data"]["workspace"];
+  assert_eq!(workspace["members"][0]["email"], json!(user.email));
+  assert_eq!(workspace["members"][0]["role"], json!("OWNER"));
+  assert_eq!(workspace["folder"]["name"], json!("Workspace"));
+  assert_eq!(workspace["folder"]["children"][0]["name"], json!("General"));
+  assert_eq!(
+    workspace["folder"]["children"][0]["children"]
+      .as_array()
+      .unwrap()
+      .len(),
+    2
+  );
+  assert_eq!(workspace["publishedViews"], json!([]));
+}
+
+#[tokio::test]
+async fn query_workspace_of_other_user_with_graphql() {
+  let (owner, _) = generate_unique_registered_user_client().await;
+  let workspace_id = owner.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let (other, _) = generate_unique_registered_user_client().await;
+
+  let resp = other
+    .query_workspace_graphql(WORKSPACE_QUERY, json!({ "id": workspace_id }))
+    .await
+    .unwrap();
+  assert!(resp["data"].is_null(), "{}", resp);
+  assert_eq!(resp["errors"].as_array().unwrap().len(), 1);
+}
@@ -0,0 +1,157 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use database_entity::dto::{AFAccessLevel, AFRole, InsertCollabMemberParams};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn workspace_group_crud_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let outsider = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let group = owner
+    .api_client
+    .create_workspace_group(&workspace_id, "design")
+    .await
+    .unwrap();
+  assert_eq!(group.name, "design");
+  assert!(group.member_uids.is_empty());
+
+  // the name of a group is unique within the workspace
+  let error = owner
+    .api_client
+    .create_workspace_group(&workspace_id, "design")
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordAlreadyExists);
+
+  // only the owner manages the groups
+  let error = member
+    .api_client
+    .create_workspace_group(&workspace_id, "marketing")
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::UserUnAuthorized);
+
+  let group_id = group.group_id.to_string();
+  let member_uid = member.uid().await;
+  owner
+    .api_client
+    .add_workspace_group_member(&workspace_id, &group_id, member_uid)
+    .await
+    .unwrap();
+  // only the members of the workspace can join its groups
+  let error = owner
+    .api_client
+    .add_workspace_group_member(&workspace_id, &group_id, outsider.uid().await)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+
+  let groups = owner
+    .api_client
+    .list_workspace_groups(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(groups.len(), 1);
+  assert_eq!(groups[0].member_uids, vec![member_uid]);
+
+  // grant the group access to a collab
+  owner
+    .api_client
+    .add_collab_member(InsertCollabMemberParams {
+      uid: 0,
+      workspace_id: workspace_id.clone(),
+      object_id: workspace_id.clone(),
+      access_level: AFAccessLevel::ReadAndWrite,
+      expires_at: None,
+      group_id: Some(group.group_id),
+    })
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .remove_collab_group_access(&workspace_id, &workspace_id, &group_id)
+    .await
+    .unwrap();
+  let error = owner
+    .api_client
+    .remove_collab_group_access(&workspace_id, &workspace_id, &group_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+
+  // a group of another workspace can not be granted access
+  let error = owner
+    .api_client
+    .add_collab_member(InsertCollabMemberParams {
+      uid: 0,
+      workspace_id: workspace_id.clone(),
+      object_id: workspace_id.clone(),
+      access_level: AFAccessLevel::ReadOnly,
+      expires_at: None,
+      group_id: Some(Uuid::new_v4()),
+    })
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+
+  owner
+    .api_client
+    .remove_workspace_group_member(&workspace_id, &group_id, member_uid)
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .delete_workspace_group(&workspace_id, &group_id)
+    .await
+    .unwrap();
+  let groups = owner
+    .api_client
+    .list_workspace_groups(&workspace_id)
+    .await
+    .unwrap();
+  assert!(groups.is_empty());
+}
+
+#[tokio::test]
+async fn workspace_group_member_removed_with_workspace_member_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let group = owner
+    .api_client
+    .create_workspace_group(&workspace_id, "design")
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .add_workspace_group_member(
+      &workspace_id,
+      &group.group_id.to_string(),
+      member.uid().await,
+    )
+    .await
+    .unwrap();
+
+  owner
+    .api_client
+    .remove_workspace_members(&workspace_id, vec![member.email().await])
+    .await
+    .unwrap();
+  let groups = owner
+    .api_client
+    .list_workspace_groups(&workspace_id)
+    .await
+    .unwrap();
+  assert!(groups[0].member_uids.is_empty());
+}
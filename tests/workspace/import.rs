@@ -0,0 +1,102 @@
+use std::io::{Cursor, Write};
+use std::time::Duration;
+
+use client_api_test::generate_unique_registered_user_client;
+use shared_entity::dto::workspace_dto::WorkspaceImportStatus;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+fn notion_archive(files: &[(&str, &str)]) -> Vec<u8> {
+  let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+  for (path, content) in files {
+    zip.start_file(*path, SimpleFileOptions::default()).unwrap();
+    zip.write_all(content.as_bytes()).unwrap();
+  }
+  zip.finish().unwrap().into_inner()
+}
+
+#[tokio::test]
+async fn import_notion_archive() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let general_view_id = c
+    .get_workspace_folder(&workspace_id, Some(1), None)
+    .await
+    .unwrap()
+    .children[0]
+    .view_id
+    .clone();
+
+  let archive = notion_archive(&[
+    (
+      "Export/Projects 0123456789abcdef0123456789abcdef.md",
+      "# Projects\n\nAll the **projects**\n\n- [ ] Plan the launch",
+    ),
+    (
+      "Export/Projects 0123456789abcdef0123456789abcdef/Launch fedcba9876543210fedcba9876543210.html",
+      "<html><body><h1>Launch</h1><p>Launch day</p></body></html>",
+    ),
+    (
+      "Export/Projects 0123456789abcdef0123456789abcdef/Tasks 00112233445566778899aabbccddeeff.csv",
+      "Name,Status\nWrite docs,Done\nShip,In progress\n",
+    ),
+  ]);
+  let task = c
+    .import_notion_archive(&workspace_id, Some(general_view_id), archive)
+    .await
+    .unwrap();
+  assert_eq!(task.total_pages, 3);
+  let mut task = c
+    .get_workspace_import_task(&workspace_id, &task.task_id)
+    .await
+    .unwrap();
+  for _ in 0..30 {
+    if task.status != WorkspaceImportStatus::Pending {
+      break;
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    task = c
+      .get_workspace_import_task(&workspace_id, &task.task_id)
+      .await
+      .unwrap();
+  }
+  assert_eq!(task.status, WorkspaceImportStatus::Completed);
+  assert_eq!(task.imported_pages, 3);
+
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), Some(task.view_id.clone()))
+    .await
+    .unwrap();
+  assert_eq!(folder_view.name, "Notion import");
+  assert_eq!(folder_view.children.len(), 1);
+  let projects = &folder_view.children[0];
+  assert_eq!(projects.name, "Projects");
+  let children: Vec<&str> = projects
+    .children
+    .iter()
+    .map(|view| view.name.as_str())
+    .collect();
+  assert_eq!(children, vec!["Launch", "Tasks"]);
+}
+
+#[tokio::test]
+async fn import_invalid_notion_archive() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+
+  // not a zip file
+  assert!(c
+    .import_notion_archive(&workspace_id, None, b"not a zip".to_vec())
+    .await
+    .is_err());
+  // a zip file without any page
+  let archive = notion_archive(&[("Export/image.png", "png")]);
+  assert!(c
+    .import_notion_archive(&workspace_id, None, archive)
+    .await
+    .is_err());
+}
@@ -1,5 +1,7 @@
 use app_error::ErrorCode;
-use client_api_test::generate_unique_registered_user_client;
+use client_api_test::{
+  api_client_with_email, generate_unique_email, generate_unique_registered_user_client, TestClient,
+};
 use database_entity::dto::{AFRole, AFWorkspaceInvitationStatus};
 use shared_entity::dto::workspace_dto::{QueryWorkspaceParam, WorkspaceMemberInvitation};
 
@@ -107,3 +109,76 @@ async fn invite_workspace_crud() {
     .unwrap();
   assert_eq!(member_count, 2);
 }
+
+#[tokio::test]
+async fn manage_pending_workspace_invitations_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let email = generate_unique_email();
+  owner
+    .api_client
+    .invite_workspace_members(
+      &workspace_id,
+      vec![WorkspaceMemberInvitation {
+        email: email.clone(),
+        role: AFRole::Guest,
+      }],
+    )
+    .await
+    .unwrap();
+
+  // only the owner manages the pending invitations
+  let error = member
+    .api_client
+    .list_pending_workspace_invitations(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::UserUnAuthorized);
+
+  let invitations = owner
+    .api_client
+    .list_pending_workspace_invitations(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(invitations.len(), 1);
+  assert_eq!(invitations[0].email, email);
+  assert_eq!(invitations[0].role, AFRole::Guest);
+  assert_eq!(invitations[0].sent_count, 1);
+  let invite_id = invitations[0].invite_id.to_string();
+
+  // the email was just sent
+  let error = owner
+    .api_client
+    .resend_workspace_invitation(&workspace_id, &invite_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::TooManyRequests);
+
+  owner
+    .api_client
+    .revoke_workspace_invitation(&workspace_id, &invite_id)
+    .await
+    .unwrap();
+  assert!(owner
+    .api_client
+    .list_pending_workspace_invitations(&workspace_id)
+    .await
+    .unwrap()
+    .is_empty());
+  let error = owner
+    .api_client
+    .revoke_workspace_invitation(&workspace_id, &invite_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+
+  // the revoked invitation is not accepted when the invitee signs up
+  let invited_client = api_client_with_email(&email).await;
+  let workspaces = invited_client.get_workspaces().await.unwrap();
+  assert_eq!(workspaces.len(), 1);
+}
@@ -24,10 +24,11 @@ async fn get_workspace_owner_after_sign_up_test() {
     .get_collab_members(QueryCollabMembers {
       workspace_id: workspace_id.clone(),
       object_id: workspace_id.clone(),
+      ..Default::default()
     })
     .await
     .unwrap()
-    .0;
+    .members;
   assert_eq!(collab_members.len(), 1);
   assert_eq!(
     collab_members[0].permission.access_level,
@@ -136,21 +137,23 @@ async fn add_not_exist_workspace_members() {
     .await
     .unwrap();
 
+  // the invitation is accepted when the invitee signs up
   let invited_client = api_client_with_email(&email).await;
-  let invite_id = invited_client
+  let invitations = invited_client
     .list_workspace_invitations(None)
     .await
-    .unwrap()
-    .first()
-    .unwrap()
-    .invite_id;
-  invited_client
-    .accept_workspace_invitation(invite_id.to_string().as_str())
-    .await
     .unwrap();
+  assert_eq!(invitations.len(), 1);
+  assert_eq!(invitations[0].status, AFWorkspaceInvitationStatus::Accepted);
 
   let workspaces = invited_client.get_workspaces().await.unwrap();
   assert_eq!(workspaces.len(), 2);
+  assert!(c1
+    .api_client
+    .list_pending_workspace_invitations(&workspace_id)
+    .await
+    .unwrap()
+    .is_empty());
 }
 
 #[tokio::test]
@@ -258,10 +261,11 @@ async fn workspace_add_member() {
     .get_collab_members(QueryCollabMembers {
       workspace_id: workspace_id.clone(),
       object_id: workspace_id.clone(),
+      ..Default::default()
     })
     .await
     .unwrap()
-    .0;
+    .members;
 
   assert_eq!(collab_members.len(), 4);
 
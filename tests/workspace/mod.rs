@@ -1,11 +1,32 @@
+mod audit_log;
+mod calendar_feed;
+mod database_import;
+mod database_query;
+mod database_row;
 mod default_user_workspace;
+mod duplicate;
 mod edit_workspace;
+mod export;
+mod graphql;
+mod group;
+mod import;
 mod invitation_crud;
+mod invite_link;
 mod member_crud;
+mod openapi;
+mod ownership_transfer;
 mod page_view;
 mod publish;
+mod publish_form;
 mod published_data;
+mod rate_limit;
+mod role;
+mod scim;
+mod service_account;
+mod snapshot_retention;
+mod sso;
 mod template;
+mod webhook;
 mod workspace_crud;
 mod workspace_folder;
 mod workspace_settings;
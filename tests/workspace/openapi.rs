@@ -0,0 +1,48 @@
+use client_api_test::TestClient;
+use reqwest::StatusCode;
+
+#[tokio::test]
+async fn openapi_document_test() {
+  let client = TestClient::new_user_without_ws_conn().await;
+  let base_url = client.api_client.base_url.clone();
+  let resp = reqwest::get(format!("{}/api/openapi.json", base_url))
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::OK);
+  let document: serde_json::Value = resp.json().await.unwrap();
+  assert!(document["openapi"].as_str().unwrap().starts_with("3."));
+  let paths = document["paths"].as_object().unwrap();
+  assert!(paths["/api/workspace"]["get"].is_object());
+
+  // a documented route is served as documented
+  let folder_path = "/api/workspace/{workspace_id}/folder";
+  assert!(paths[folder_path]["get"].is_object());
+  let workspace_id = client.workspace_id().await;
+  let resp = reqwest::Client::new()
+    .get(format!(
+      "{}{}",
+      base_url,
+      folder_path.replace("{workspace_id}", &workspace_id)
+    ))
+    .bearer_auth(client.api_client.access_token().unwrap())
+    .send()
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::OK);
+  let body: serde_json::Value = resp.json().await.unwrap();
+  assert_eq!(body["data"]["name"], "Workspace");
+}
+
+#[tokio::test]
+async fn swagger_ui_test() {
+  let client = TestClient::new_user_without_ws_conn().await;
+  let resp = reqwest::get(format!(
+    "{}/swagger-ui/index.html",
+    client.api_client.base_url
+  ))
+  .await
+  .unwrap();
+  assert_eq!(resp.status(), StatusCode::OK);
+  let body = resp.text().await.unwrap();
+  assert!(body.contains("swagger-ui"));
+}
@@ -0,0 +1,175 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use database_entity::dto::{AFRole, WorkspaceCapability};
+use shared_entity::dto::workspace_dto::{AuditAction, QueryAuditLog};
+
+#[tokio::test]
+async fn transfer_workspace_ownership_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let owner_uid = owner.uid().await;
+  let member_uid = member.uid().await;
+  let member_email = member.email().await;
+
+  // only the owner hands the workspace over
+  let error = member
+    .api_client
+    .request_workspace_ownership_transfer(&workspace_id, &owner.email().await)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+
+  let transfer = owner
+    .api_client
+    .request_workspace_ownership_transfer(&workspace_id, &member_email)
+    .await
+    .unwrap();
+  assert_eq!(transfer.from_uid, owner_uid);
+  assert_eq!(transfer.to_uid, member_uid);
+  let pending = member
+    .api_client
+    .get_workspace_ownership_transfer(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(pending, transfer);
+
+  // nothing changes until the member accepts
+  let capabilities = member
+    .api_client
+    .get_workspace_capabilities(&workspace_id)
+    .await
+    .unwrap();
+  assert!(!capabilities.contains(&WorkspaceCapability::ManageBilling));
+
+  member
+    .api_client
+    .accept_workspace_ownership_transfer(&workspace_id)
+    .await
+    .unwrap();
+  let members = member
+    .api_client
+    .get_workspace_members(&workspace_id)
+    .await
+    .unwrap();
+  let role_of = |email: &str| {
+    members
+      .iter()
+      .find(|member| member.email == email)
+      .unwrap()
+      .role
+      .clone()
+  };
+  assert_eq!(role_of(&member_email), AFRole::Owner);
+  assert_eq!(role_of(&owner.email().await), AFRole::Member);
+
+  // the previous owner lost the policies only the owner holds
+  let capabilities = owner
+    .api_client
+    .get_workspace_capabilities(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(
+    capabilities,
+    vec![
+      WorkspaceCapability::Publish,
+      WorkspaceCapability::DeleteView
+    ]
+  );
+  let error = owner
+    .api_client
+    .get_workspace_audit_log(&workspace_id, &QueryAuditLog::default())
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::UserUnAuthorized);
+
+  // the transfer is recorded in the audit log
+  let page = member
+    .api_client
+    .get_workspace_audit_log(
+      &workspace_id,
+      &QueryAuditLog {
+        action: Some(AuditAction::WorkspaceOwnershipTransferred),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(page.entries.len(), 1);
+  assert_eq!(page.entries[0].actor_uid, Some(member_uid));
+  assert_eq!(page.entries[0].metadata["previous_owner_uid"], owner_uid);
+
+  // the transfer has been consumed
+  let error = member
+    .api_client
+    .accept_workspace_ownership_transfer(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn decline_workspace_ownership_transfer_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let other_member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &other_member, AFRole::Member)
+    .await
+    .unwrap();
+
+  // the workspace can only be handed to one of its members
+  let outsider = TestClient::new_user_without_ws_conn().await;
+  let error = owner
+    .api_client
+    .request_workspace_ownership_transfer(&workspace_id, &outsider.email().await)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+
+  owner
+    .api_client
+    .request_workspace_ownership_transfer(&workspace_id, &member.email().await)
+    .await
+    .unwrap();
+
+  // only the member asked to become the owner can accept the transfer
+  let error = other_member
+    .api_client
+    .accept_workspace_ownership_transfer(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+
+  member
+    .api_client
+    .cancel_workspace_ownership_transfer(&workspace_id)
+    .await
+    .unwrap();
+  let error = member
+    .api_client
+    .accept_workspace_ownership_transfer(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+  let members = owner
+    .api_client
+    .get_workspace_members(&workspace_id)
+    .await
+    .unwrap();
+  let owners: Vec<_> = members
+    .iter()
+    .filter(|member| member.role == AFRole::Owner)
+    .collect();
+  assert_eq!(owners.len(), 1);
+  assert_eq!(owners[0].email, owner.email().await);
+}
@@ -1,5 +1,5 @@
 use app_error::ErrorCode;
-use appflowy_cloud::biz::collab::folder_view::collab_folder_to_folder_view;
+use appflowy_cloud::biz::collab::folder_view::{collab_folder_to_folder_view, FolderViewFilter};
 use appflowy_cloud::biz::workspace::publish_dup::collab_from_doc_state;
 use client_api::entity::{AFRole, GlobalComment, PublishCollabItem, PublishCollabMetadata};
 use client_api_test::TestClient;
@@ -14,8 +14,8 @@ use collab_document::document::Document;
 use collab_entity::CollabType;
 use collab_folder::{CollabOrigin, Folder, UserId};
 use itertools::Itertools;
-use shared_entity::dto::publish_dto::PublishDatabaseData;
-use shared_entity::dto::workspace_dto::PublishedDuplicate;
+use shared_entity::dto::publish_dto::{PublishDatabaseData, PublishOutcome};
+use shared_entity::dto::workspace_dto::{PublishedDuplicate, ViewLayout};
 use std::collections::{HashMap, HashSet};
 use std::thread::sleep;
 use std::time::Duration;
@@ -671,6 +671,622 @@ async fn workspace_member_publish_unpublish() {
     .unwrap();
 }
 
+#[tokio::test]
+async fn publish_collab_only_if_changed() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let guest = TestClient::new_user_without_ws_conn().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &guest, AFRole::Guest)
+    .await
+    .unwrap();
+
+  let folder_view = owner
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let doc_view = folder_view.children[0]
+    .children
+    .iter()
+    .find(|view| view.layout == ViewLayout::Document)
+    .unwrap();
+  let view_id = uuid::Uuid::parse_str(&doc_view.view_id).unwrap();
+  let item = |publish_name: &str| PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: publish_name.to_string(),
+      metadata: MyCustomMetadata {
+        title: "my_title".to_string(),
+      },
+    },
+    data: "yrs_encoded_data".as_bytes(),
+  };
+
+  let outcome = owner
+    .api_client
+    .publish_collab_if_changed(&workspace_id, CollabType::Document, item("publish-name"))
+    .await
+    .unwrap();
+  assert_eq!(outcome, PublishOutcome::Published);
+
+  // the document has not been edited since
+  let outcome = owner
+    .api_client
+    .publish_collab_if_changed(&workspace_id, CollabType::Document, item("publish-name"))
+    .await
+    .unwrap();
+  assert_eq!(outcome, PublishOutcome::NoChange);
+
+  // a member who can not publish learns nothing about the document
+  let err = guest
+    .api_client
+    .publish_collab_if_changed(&workspace_id, CollabType::Document, item("publish-name"))
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn published_nav_only_contains_flagged_views() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view.children[0];
+  let view_ids: Vec<uuid::Uuid> = general_space
+    .children
+    .iter()
+    .map(|v| uuid::Uuid::parse_str(&v.view_id).unwrap())
+    .collect();
+  assert_eq!(view_ids.len(), 2);
+
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    view_ids
+      .iter()
+      .enumerate()
+      .map(|(i, view_id)| PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id: *view_id,
+          publish_name: format!("publish-name-{}", i),
+          metadata: MyCustomMetadata {
+            title: format!("my_title_{}", i),
+          },
+        },
+        data: "yrs_encoded_data".as_bytes(),
+      })
+      .collect(),
+  )
+  .await
+  .unwrap();
+
+  let guest_client = localhost_client();
+  let nav = guest_client.get_published_nav(&my_namespace).await.unwrap();
+  assert!(nav.is_empty());
+
+  c.set_published_view_show_in_nav(&workspace_id, &view_ids[1], true)
+    .await
+    .unwrap();
+  let nav = guest_client.get_published_nav(&my_namespace).await.unwrap();
+  assert_eq!(nav.len(), 1);
+  assert_eq!(nav[0].view_id, view_ids[1].to_string());
+  assert!(nav[0].children.is_empty());
+
+  // only published views can be shown in nav
+  let err = c
+    .set_published_view_show_in_nav(&workspace_id, &uuid::Uuid::new_v4(), true)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn expired_published_view_is_not_found() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  let publish_name = "expiring-view";
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: publish_name.to_string(),
+        metadata: MyCustomMetadata {
+          title: "my_title".to_string(),
+        },
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+
+  // an expiry in the past is rejected
+  let err = c
+    .set_published_view_expiry(
+      &workspace_id,
+      &view_id,
+      Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  c.set_published_view_expiry(
+    &workspace_id,
+    &view_id,
+    Some(chrono::Utc::now() + chrono::Duration::seconds(2)),
+  )
+  .await
+  .unwrap();
+  let guest_client = localhost_client();
+  guest_client
+    .get_published_collab::<MyCustomMetadata>(&my_namespace, publish_name)
+    .await
+    .unwrap();
+
+  tokio::time::sleep(Duration::from_secs(3)).await;
+  let err = guest_client
+    .get_published_collab::<MyCustomMetadata>(&my_namespace, publish_name)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+  let err = guest_client
+    .get_published_collab_info(&view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn password_protected_published_view_requires_access_token() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  let publish_name = "protected-view";
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: publish_name.to_string(),
+        metadata: MyCustomMetadata {
+          title: "my_title".to_string(),
+        },
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+
+  // a password that is too short is rejected
+  let err = c
+    .set_published_view_password(&workspace_id, &view_id, Some("sesame"))
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  c.set_published_view_password(&workspace_id, &view_id, Some("open sesame"))
+    .await
+    .unwrap();
+  let guest_client = localhost_client();
+  let err = guest_client
+    .get_published_collab::<MyCustomMetadata>(&my_namespace, publish_name)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+  let err = guest_client
+    .get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+
+  let err = guest_client
+    .get_published_view_access_token(&my_namespace, publish_name, "wrong password")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+
+  let token = guest_client
+    .get_published_view_access_token(&my_namespace, publish_name, "open sesame")
+    .await
+    .unwrap();
+  assert!(token.expires_at > chrono::Utc::now());
+  let metadata = guest_client
+    .get_published_collab_with_access_token::<MyCustomMetadata>(
+      &my_namespace,
+      publish_name,
+      Some(&token.access_token),
+    )
+    .await
+    .unwrap();
+  assert_eq!(metadata.title, "my_title");
+  let blob = guest_client
+    .get_published_collab_blob_with_access_token(
+      &my_namespace,
+      publish_name,
+      Some(&token.access_token),
+    )
+    .await
+    .unwrap();
+  assert_eq!(blob, "yrs_encoded_data".as_bytes());
+
+  // the comments are protected by the password too
+  let err = c
+    .create_comment_on_published_view(&view_id, "protected comment", &None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+  c.create_comment_on_published_view_with_access_token(
+    &view_id,
+    "protected comment",
+    &None,
+    Some(&token.access_token),
+  )
+  .await
+  .unwrap();
+  let err = guest_client
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+  let comments = guest_client
+    .get_published_view_comments_with_access_token(&view_id, Some(&token.access_token))
+    .await
+    .unwrap()
+    .comments;
+  assert_eq!(comments.len(), 1);
+  let err = guest_client
+    .get_published_view_reactions(&view_id, &None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+
+  // the attempts at the password are limited
+  let mut error_codes = vec![];
+  for _ in 0..6 {
+    let err = guest_client
+      .get_published_view_access_token(&my_namespace, publish_name, "wrong password")
+      .await
+      .unwrap_err();
+    error_codes.push(err.code);
+  }
+  assert!(error_codes.contains(&ErrorCode::TooManyRequests));
+
+  // changing the password invalidates the tokens issued for the old one
+  c.set_published_view_password(&workspace_id, &view_id, Some("new password"))
+    .await
+    .unwrap();
+  let err = guest_client
+    .get_published_collab_with_access_token::<MyCustomMetadata>(
+      &my_namespace,
+      publish_name,
+      Some(&token.access_token),
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+
+  // removing the password makes the view public again
+  c.set_published_view_password(&workspace_id, &view_id, None)
+    .await
+    .unwrap();
+  guest_client
+    .get_published_collab::<MyCustomMetadata>(&my_namespace, publish_name)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn register_publish_custom_domain() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let domain = format!("docs.{}.example.com", uuid::Uuid::new_v4().simple());
+
+  let registered = c
+    .register_publish_custom_domain(&workspace_id, &domain.to_uppercase())
+    .await
+    .unwrap();
+  assert_eq!(registered.domain, domain);
+  assert_eq!(
+    registered.verification_record_name,
+    format!("_appflowy-publish.{}", domain)
+  );
+  assert!(registered.verified_at.is_none());
+
+  // registering the domain again keeps the verification token
+  let registered_again = c
+    .register_publish_custom_domain(&workspace_id, &domain)
+    .await
+    .unwrap();
+  assert_eq!(registered_again, registered);
+
+  let err = c
+    .register_publish_custom_domain(&workspace_id, "localhost")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  // only the owner of the workspace can manage its domains
+  let (other_client, _) = generate_unique_registered_user_client().await;
+  let err = other_client
+    .register_publish_custom_domain(&workspace_id, &domain)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+
+  let domains = c.list_publish_custom_domains(&workspace_id).await.unwrap();
+  assert_eq!(domains, vec![registered]);
+
+  c.unregister_publish_custom_domain(&workspace_id, &domain)
+    .await
+    .unwrap();
+  assert!(c
+    .list_publish_custom_domains(&workspace_id)
+    .await
+    .unwrap()
+    .is_empty());
+  let err = c
+    .unregister_publish_custom_domain(&workspace_id, &domain)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn published_sitemap_and_feed() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let public_view_id = uuid::Uuid::new_v4();
+  let protected_view_id = uuid::Uuid::new_v4();
+  let publish_item = |view_id: uuid::Uuid, publish_name: &str| PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: publish_name.to_string(),
+      metadata: MyCustomMetadata {
+        title: publish_name.to_string(),
+      },
+    },
+    data: "yrs_encoded_data".as_bytes(),
+  };
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    vec![
+      publish_item(public_view_id, "public-view"),
+      publish_item(protected_view_id, "protected-view"),
+    ],
+  )
+  .await
+  .unwrap();
+  c.set_published_view_password(&workspace_id, &protected_view_id, Some("open sesame"))
+    .await
+    .unwrap();
+
+  // password protected views are left out of the sitemap and feed
+  let guest_client = localhost_client();
+  let sitemap = guest_client
+    .get_published_sitemap(&my_namespace)
+    .await
+    .unwrap();
+  assert!(sitemap.contains("<urlset"));
+  assert!(sitemap.contains(&format!("/{}/public-view</loc>", my_namespace)));
+  assert!(!sitemap.contains("protected-view"));
+
+  let feed = guest_client
+    .get_published_feed(&my_namespace)
+    .await
+    .unwrap();
+  assert!(feed.contains("<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+  assert!(feed.contains(&format!("/{}/public-view</id>", my_namespace)));
+  assert!(!feed.contains("protected-view"));
+
+  let err = guest_client
+    .get_published_sitemap(&uuid::Uuid::new_v4().to_string())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn render_published_document_as_html_and_markdown() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let doc_view_id = uuid::Uuid::new_v4();
+  let other_view_id = uuid::Uuid::new_v4();
+  c.publish_collabs::<MyCustomMetadata, Vec<u8>>(
+    &workspace_id,
+    vec![
+      PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id: doc_view_id,
+          publish_name: "doc".to_string(),
+          metadata: MyCustomMetadata {
+            title: "doc".to_string(),
+          },
+        },
+        data: hex::decode(published_data::DOC_1_DOC_STATE_HEX).unwrap(),
+      },
+      PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id: other_view_id,
+          publish_name: "not-a-doc".to_string(),
+          metadata: MyCustomMetadata {
+            title: "not-a-doc".to_string(),
+          },
+        },
+        data: "yrs_encoded_data".as_bytes().to_vec(),
+      },
+    ],
+  )
+  .await
+  .unwrap();
+
+  let guest_client = localhost_client();
+  let html = guest_client
+    .get_published_view_html(&my_namespace, "doc", None)
+    .await
+    .unwrap();
+  assert!(html.contains("<p>"));
+  assert!(html.contains("stuffs"));
+  assert!(!html.contains("<script"));
+
+  // rendered again from the cache
+  let cached_html = guest_client
+    .get_published_view_html(&my_namespace, "doc", None)
+    .await
+    .unwrap();
+  assert_eq!(html, cached_html);
+
+  let markdown = guest_client
+    .get_published_view_markdown(&my_namespace, "doc", None)
+    .await
+    .unwrap();
+  assert!(markdown.contains("stuffs"));
+  assert!(!markdown.contains("<p>"));
+
+  let err = guest_client
+    .get_published_view_html(&my_namespace, "not-a-doc", None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let err = guest_client
+    .get_published_view_html(&my_namespace, "unknown", None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  // password protected views are only rendered with an access token
+  c.set_published_view_password(&workspace_id, &doc_view_id, Some("open sesame"))
+    .await
+    .unwrap();
+  let err = guest_client
+    .get_published_view_html(&my_namespace, "doc", None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+  let access_token = guest_client
+    .get_published_view_access_token(&my_namespace, "doc", "open sesame")
+    .await
+    .unwrap();
+  let protected_html = guest_client
+    .get_published_view_html(&my_namespace, "doc", Some(&access_token.access_token))
+    .await
+    .unwrap();
+  assert_eq!(html, protected_html);
+}
+
+#[tokio::test]
+async fn published_view_analytics() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: "read-view".to_string(),
+        metadata: MyCustomMetadata {
+          title: "read-view".to_string(),
+        },
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+
+  let guest_client = localhost_client();
+  for _ in 0..3 {
+    guest_client
+      .get_published_collab::<serde_json::Value>(&my_namespace, "read-view")
+      .await
+      .unwrap();
+  }
+
+  // reads are counted in the background, and written in batches
+  let mut analytics = c.get_publish_analytics(&workspace_id, None).await.unwrap();
+  for _ in 0..30 {
+    if analytics.views.first().map(|view| view.total_views) == Some(3) {
+      break;
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    analytics = c.get_publish_analytics(&workspace_id, None).await.unwrap();
+  }
+  assert_eq!(analytics.views.len(), 1);
+  let view = &analytics.views[0];
+  assert_eq!(view.view_id, view_id);
+  assert_eq!(view.publish_name.as_deref(), Some("read-view"));
+  assert_eq!(view.total_views, 3);
+  assert_eq!(view.daily_views.len(), 1);
+  assert_eq!(view.daily_views[0].views, 3);
+
+  let err = c
+    .get_publish_analytics(&workspace_id, Some(0))
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn published_view_analytics_require_publish_capability() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let guest = TestClient::new_user_without_ws_conn().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &guest, AFRole::Guest)
+    .await
+    .unwrap();
+
+  owner
+    .api_client
+    .get_publish_analytics(&workspace_id, None)
+    .await
+    .unwrap();
+  let err = guest
+    .api_client
+    .get_publish_analytics(&workspace_id, None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 struct MyCustomMetadata {
   title: String,
@@ -865,8 +1481,14 @@ async fn duplicate_to_workspace_doc_inline_database() {
     )
     .unwrap();
 
-    let folder_view =
-      collab_folder_to_folder_view(&workspace_id_2, &folder, 5, &HashSet::default()).unwrap();
+    let folder_view = collab_folder_to_folder_view(
+      &workspace_id_2,
+      &folder,
+      5,
+      &HashSet::default(),
+      &FolderViewFilter::default(),
+    )
+    .unwrap();
     let doc_3_fv = folder_view
       .children
       .into_iter()
@@ -1385,3 +2007,57 @@ fn get_database_id_and_row_ids(published_db_blob: &[u8]) -> (String, HashSet<Str
   let row_ids: HashSet<String> = pub_db_data.database_row_collabs.into_keys().collect();
   (pub_db_id, row_ids)
 }
+
+#[tokio::test]
+async fn published_view_preview_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let getting_started = folder_view.children[0]
+    .children
+    .iter()
+    .find(|v| v.name == "Getting started")
+    .unwrap();
+  let view_id = uuid::Uuid::parse_str(&getting_started.view_id).unwrap();
+
+  let guest_client = localhost_client();
+  let err = guest_client
+    .get_published_view_preview(&my_namespace, &view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: "getting-started".to_string(),
+        metadata: MyCustomMetadata {
+          title: "my_title".to_string(),
+        },
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+
+  let preview = guest_client
+    .get_published_view_preview(&my_namespace, &view_id)
+    .await
+    .unwrap();
+  assert_eq!(preview.view_id, getting_started.view_id);
+  assert_eq!(preview.title, "Getting started");
+  assert_eq!(preview.cover.unwrap().cover_type, "gradient");
+  // the published data is not a document, so there is nothing to describe the view with
+  assert!(preview.description.is_none());
+}
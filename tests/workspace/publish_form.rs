@@ -0,0 +1,177 @@
+use app_error::ErrorCode;
+use client_api::entity::{PublishCollabItem, PublishCollabMetadata};
+use client_api_test::{generate_unique_registered_user_client, localhost_client};
+use serde_json::json;
+use shared_entity::dto::workspace_dto::{
+  QueryDatabaseRows, SetPublishedDatabaseFormParams, SubmitPublishedDatabaseForm,
+};
+use std::collections::HashMap;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct FormMetadata {
+  title: String,
+}
+
+#[tokio::test]
+async fn submit_published_database_form_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id.to_string();
+  let namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &namespace)
+    .await
+    .unwrap();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let todos = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .children
+    .iter()
+    .find(|v| v.name == "To-dos")
+    .unwrap();
+  let view_id = todos.view_id.clone();
+  let view_uuid = uuid::Uuid::parse_str(&view_id).unwrap();
+  let publish_name = "todo-form";
+  c.publish_collabs::<FormMetadata, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id: view_uuid,
+        publish_name: publish_name.to_string(),
+        metadata: FormMetadata {
+          title: "To-dos".to_string(),
+        },
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+
+  let rows = c
+    .list_database_rows(&workspace_id, &view_id, &QueryDatabaseRows::default())
+    .await
+    .unwrap();
+  let primary_field = rows.fields.iter().find(|f| f.is_primary).unwrap().clone();
+  let other_field = rows.fields.iter().find(|f| !f.is_primary).unwrap().clone();
+
+  // the form can only be submitted once it is set up
+  let guest_client = localhost_client();
+  let err = guest_client
+    .get_published_database_form(&namespace, publish_name)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  // no captcha is configured on the test server
+  let err = c
+    .set_published_database_form(
+      &workspace_id,
+      &view_uuid,
+      &SetPublishedDatabaseFormParams {
+        fields: vec![primary_field.name.clone()],
+        max_submissions_per_hour: None,
+        require_captcha: true,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let settings = c
+    .set_published_database_form(
+      &workspace_id,
+      &view_uuid,
+      &SetPublishedDatabaseFormParams {
+        fields: vec![primary_field.name.clone()],
+        max_submissions_per_hour: Some(2),
+        require_captcha: false,
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(settings.field_ids, vec![primary_field.id.clone()]);
+
+  let form = guest_client
+    .get_published_database_form(&namespace, publish_name)
+    .await
+    .unwrap();
+  assert!(!form.require_captcha);
+  assert_eq!(form.fields.len(), 1);
+  assert_eq!(form.fields[0].id, primary_field.id);
+
+  // only the fields of the form can be filled in
+  let err = guest_client
+    .submit_published_database_form(
+      &namespace,
+      publish_name,
+      &SubmitPublishedDatabaseForm {
+        cells: HashMap::from([(other_field.id.clone(), json!("Hidden"))]),
+        captcha_token: None,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let submission = guest_client
+    .submit_published_database_form(
+      &namespace,
+      publish_name,
+      &SubmitPublishedDatabaseForm {
+        cells: HashMap::from([(primary_field.name.clone(), json!("Sent through the form"))]),
+        captcha_token: None,
+      },
+    )
+    .await
+    .unwrap();
+  let rows = c
+    .list_database_rows(&workspace_id, &view_id, &QueryDatabaseRows::default())
+    .await
+    .unwrap();
+  let row = rows.rows.last().unwrap();
+  assert_eq!(row.id, submission.row_id);
+  assert_eq!(
+    row.cells.get(&primary_field.id).map(String::as_str),
+    Some("Sent through the form")
+  );
+
+  // the form accepts two submissions per hour
+  guest_client
+    .submit_published_database_form(
+      &namespace,
+      publish_name,
+      &SubmitPublishedDatabaseForm {
+        cells: HashMap::from([(primary_field.id.clone(), json!("Second"))]),
+        captcha_token: None,
+      },
+    )
+    .await
+    .unwrap();
+  let err = guest_client
+    .submit_published_database_form(
+      &namespace,
+      publish_name,
+      &SubmitPublishedDatabaseForm {
+        cells: HashMap::from([(primary_field.id.clone(), json!("Third"))]),
+        captcha_token: None,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::TooManyRequests);
+
+  c.delete_published_database_form(&workspace_id, &view_uuid)
+    .await
+    .unwrap();
+  let err = guest_client
+    .get_published_database_form(&namespace, publish_name)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
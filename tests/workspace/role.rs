@@ -0,0 +1,235 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use database_entity::dto::{AFRole, WorkspaceCapability};
+use shared_entity::dto::workspace_dto::{CreateWorkspaceRoleParams, WorkspaceMemberInvitation};
+
+#[tokio::test]
+async fn workspace_role_crud_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let params = CreateWorkspaceRoleParams {
+    name: "recruiter".to_string(),
+    capabilities: vec![WorkspaceCapability::Invite],
+  };
+  let role = owner
+    .api_client
+    .create_workspace_role(&workspace_id, &params)
+    .await
+    .unwrap();
+  assert_eq!(role.name, "recruiter");
+  assert_eq!(role.capabilities, vec![WorkspaceCapability::Invite]);
+
+  // the name of a role is unique within the workspace
+  let error = owner
+    .api_client
+    .create_workspace_role(&workspace_id, &params)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordAlreadyExists);
+
+  // only the owner manages the roles
+  let error = member
+    .api_client
+    .create_workspace_role(
+      &workspace_id,
+      &CreateWorkspaceRoleParams {
+        name: "biller".to_string(),
+        capabilities: vec![WorkspaceCapability::ManageBilling],
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::UserUnAuthorized);
+
+  let role_id = role.role_id.to_string();
+  let member_uid = member.uid().await;
+  owner
+    .api_client
+    .assign_workspace_role(&workspace_id, &role_id, member_uid)
+    .await
+    .unwrap();
+  let roles = owner
+    .api_client
+    .list_workspace_roles(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(roles.len(), 1);
+  assert_eq!(roles[0].member_uids, vec![member_uid]);
+
+  let capabilities = owner
+    .api_client
+    .get_workspace_capabilities(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(capabilities, WorkspaceCapability::ALL.to_vec());
+
+  let role = owner
+    .api_client
+    .update_workspace_role(
+      &workspace_id,
+      &role_id,
+      &CreateWorkspaceRoleParams {
+        name: "hiring manager".to_string(),
+        capabilities: vec![
+          WorkspaceCapability::Invite,
+          WorkspaceCapability::ManageBilling,
+        ],
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(role.name, "hiring manager");
+  assert_eq!(role.member_uids, vec![member_uid]);
+
+  owner
+    .api_client
+    .delete_workspace_role(&workspace_id, &role_id)
+    .await
+    .unwrap();
+  let roles = owner
+    .api_client
+    .list_workspace_roles(&workspace_id)
+    .await
+    .unwrap();
+  assert!(roles.is_empty());
+}
+
+#[tokio::test]
+async fn custom_role_grants_capabilities_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let invitee = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  // a member can publish and delete views, but not invite
+  let capabilities = member
+    .api_client
+    .get_workspace_capabilities(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(
+    capabilities,
+    vec![
+      WorkspaceCapability::Publish,
+      WorkspaceCapability::DeleteView
+    ]
+  );
+  let error = member
+    .api_client
+    .invite_workspace_members(
+      &workspace_id,
+      vec![WorkspaceMemberInvitation {
+        email: invitee.email().await,
+        role: AFRole::Member,
+      }],
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+
+  let role = owner
+    .api_client
+    .create_workspace_role(
+      &workspace_id,
+      &CreateWorkspaceRoleParams {
+        name: "recruiter".to_string(),
+        capabilities: vec![WorkspaceCapability::Invite],
+      },
+    )
+    .await
+    .unwrap();
+  let role_id = role.role_id.to_string();
+  let member_uid = member.uid().await;
+  owner
+    .api_client
+    .assign_workspace_role(&workspace_id, &role_id, member_uid)
+    .await
+    .unwrap();
+  member
+    .api_client
+    .invite_workspace_members(
+      &workspace_id,
+      vec![WorkspaceMemberInvitation {
+        email: invitee.email().await,
+        role: AFRole::Member,
+      }],
+    )
+    .await
+    .unwrap();
+
+  // the invite capability does not let the member make other users owners
+  let error = member
+    .api_client
+    .invite_workspace_members(
+      &workspace_id,
+      vec![WorkspaceMemberInvitation {
+        email: invitee.email().await,
+        role: AFRole::Owner,
+      }],
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::UserUnAuthorized);
+
+  owner
+    .api_client
+    .unassign_workspace_role(&workspace_id, &role_id, member_uid)
+    .await
+    .unwrap();
+  let capabilities = member
+    .api_client
+    .get_workspace_capabilities(&workspace_id)
+    .await
+    .unwrap();
+  assert!(!capabilities.contains(&WorkspaceCapability::Invite));
+}
+
+#[tokio::test]
+async fn custom_role_grants_billing_capability_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let error = member
+    .api_client
+    .get_workspace_ai_usage(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+
+  let role = owner
+    .api_client
+    .create_workspace_role(
+      &workspace_id,
+      &CreateWorkspaceRoleParams {
+        name: "biller".to_string(),
+        capabilities: vec![WorkspaceCapability::ManageBilling],
+      },
+    )
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .assign_workspace_role(&workspace_id, &role.role_id.to_string(), member.uid().await)
+    .await
+    .unwrap();
+  member
+    .api_client
+    .get_workspace_ai_usage(&workspace_id)
+    .await
+    .unwrap();
+}
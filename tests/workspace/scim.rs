@@ -0,0 +1,196 @@
+use client_api_test::{generate_unique_email, TestClient};
+use database_entity::dto::AFRole;
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+use shared_entity::dto::workspace_dto::{
+  ApiKeyScope, CreateServiceAccountParams, UpdateScimGroupRoleParams,
+};
+
+/// Send the request as the identity provider the SCIM api key belongs to.
+async fn scim_request(
+  client: &TestClient,
+  api_key: &str,
+  method: Method,
+  path: &str,
+  body: Option<Value>,
+) -> (StatusCode, Value) {
+  let url = format!("{}/scim/v2{}", client.api_client.base_url, path);
+  let mut req = reqwest::Client::new()
+    .request(method, &url)
+    .bearer_auth(api_key);
+  if let Some(body) = body {
+    req = req.json(&body);
+  }
+  let resp = req.send().await.unwrap();
+  let status = resp.status();
+  let body = resp.json::<Value>().await.unwrap_or(Value::Null);
+  (status, body)
+}
+
+async fn member_role(owner: &TestClient, workspace_id: &str, email: &str) -> Option<AFRole> {
+  owner
+    .api_client
+    .get_workspace_members(workspace_id)
+    .await
+    .unwrap()
+    .into_iter()
+    .find(|member| member.email == email)
+    .map(|member| member.role)
+}
+
+#[tokio::test]
+async fn scim_provision_user_and_group_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let scim_key = owner
+    .api_client
+    .create_service_account(
+      &workspace_id,
+      CreateServiceAccountParams {
+        name: "okta".to_string(),
+        scope: ApiKeyScope::Scim,
+      },
+    )
+    .await
+    .unwrap()
+    .api_key;
+  let full_key = owner
+    .api_client
+    .create_service_account(
+      &workspace_id,
+      CreateServiceAccountParams {
+        name: "automation".to_string(),
+        scope: ApiKeyScope::Full,
+      },
+    )
+    .await
+    .unwrap()
+    .api_key;
+
+  // only a SCIM key reaches the SCIM endpoints
+  let (status, _) = scim_request(&owner, &full_key, Method::GET, "/Users", None).await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+
+  let email = generate_unique_email();
+  let user = json!({
+    "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+    "userName": email,
+    "externalId": "00u1",
+    "name": { "givenName": "Alice", "familyName": "Smith" },
+    "active": true
+  });
+  let (status, created) = scim_request(
+    &owner,
+    &scim_key,
+    Method::POST,
+    "/Users",
+    Some(user.clone()),
+  )
+  .await;
+  assert_eq!(status, StatusCode::CREATED);
+  let user_id = created["id"].as_str().unwrap().to_string();
+  assert_eq!(
+    member_role(&owner, &workspace_id, &email).await,
+    Some(AFRole::Member)
+  );
+
+  let (status, error) = scim_request(&owner, &scim_key, Method::POST, "/Users", Some(user)).await;
+  assert_eq!(status, StatusCode::CONFLICT);
+  assert_eq!(error["scimType"], "uniqueness");
+
+  let filter = format!("/Users?filter=userName%20eq%20%22{}%22", email);
+  let (status, list) = scim_request(&owner, &scim_key, Method::GET, &filter, None).await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(list["totalResults"], 1);
+  assert_eq!(list["Resources"][0]["id"], user_id.as_str());
+
+  // a group mapped to the Guest role downgrades its members
+  let (status, group) = scim_request(
+    &owner,
+    &scim_key,
+    Method::POST,
+    "/Groups",
+    Some(json!({
+      "displayName": "Contractors",
+      "members": [{ "value": user_id }]
+    })),
+  )
+  .await;
+  assert_eq!(status, StatusCode::CREATED);
+  let group_id = group["id"].as_str().unwrap().to_string();
+  owner
+    .api_client
+    .update_scim_group_role(
+      &workspace_id,
+      &group_id,
+      UpdateScimGroupRoleParams {
+        role: Some(AFRole::Guest),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(
+    member_role(&owner, &workspace_id, &email).await,
+    Some(AFRole::Guest)
+  );
+  let groups = owner
+    .api_client
+    .list_scim_groups(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(groups.len(), 1);
+  assert_eq!(groups[0].member_count, 1);
+
+  // leaving the group restores the default role
+  let (status, _) = scim_request(
+    &owner,
+    &scim_key,
+    Method::PATCH,
+    &format!("/Groups/{}", group_id),
+    Some(json!({
+      "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+      "Operations": [{ "op": "remove", "path": format!("members[value eq \"{}\"]", user_id) }]
+    })),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(
+    member_role(&owner, &workspace_id, &email).await,
+    Some(AFRole::Member)
+  );
+
+  // deactivating the user removes it from the workspace
+  let (status, patched) = scim_request(
+    &owner,
+    &scim_key,
+    Method::PATCH,
+    &format!("/Users/{}", user_id),
+    Some(json!({
+      "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+      "Operations": [{ "op": "Replace", "path": "active", "value": "False" }]
+    })),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(patched["active"], false);
+  assert_eq!(member_role(&owner, &workspace_id, &email).await, None);
+
+  let (status, _) = scim_request(
+    &owner,
+    &scim_key,
+    Method::DELETE,
+    &format!("/Users/{}", user_id),
+    None,
+  )
+  .await;
+  assert_eq!(status, StatusCode::NO_CONTENT);
+  let (status, _) = scim_request(
+    &owner,
+    &scim_key,
+    Method::GET,
+    &format!("/Users/{}", user_id),
+    None,
+  )
+  .await;
+  assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,211 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use database_entity::dto::AFRole;
+use reqwest::Method;
+use shared_entity::dto::workspace_dto::{ApiKeyScope, CreateServiceAccountParams};
+use shared_entity::response::{AppResponse, AppResponseError};
+
+/// Send the request as the service account the api key belongs to.
+async fn request_with_api_key(
+  client: &TestClient,
+  api_key: &str,
+  method: Method,
+  path: &str,
+) -> Result<serde_json::Value, AppResponseError> {
+  let url = format!("{}{}", client.api_client.base_url, path);
+  let resp = reqwest::Client::new()
+    .request(method, &url)
+    .bearer_auth(api_key)
+    .json(&serde_json::json!({}))
+    .send()
+    .await
+    .unwrap();
+  AppResponse::<serde_json::Value>::from_response(resp)
+    .await
+    .unwrap()
+    .into_data()
+}
+
+#[tokio::test]
+async fn service_account_api_key_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  // only the owner manages the service accounts
+  let error = member
+    .api_client
+    .create_service_account(
+      &workspace_id,
+      CreateServiceAccountParams {
+        name: "backup".to_string(),
+        scope: ApiKeyScope::ReadOnly,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::UserUnAuthorized);
+
+  let created = owner
+    .api_client
+    .create_service_account(
+      &workspace_id,
+      CreateServiceAccountParams {
+        name: "backup".to_string(),
+        scope: ApiKeyScope::ReadOnly,
+      },
+    )
+    .await
+    .unwrap();
+  assert!(created.api_key.starts_with("afk_"));
+  assert_eq!(created.service_account.scope, ApiKeyScope::ReadOnly);
+  let service_account_id = created.service_account.service_account_id.to_string();
+
+  let error = owner
+    .api_client
+    .create_service_account(
+      &workspace_id,
+      CreateServiceAccountParams {
+        name: "backup".to_string(),
+        scope: ApiKeyScope::Full,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordAlreadyExists);
+
+  // a read-only key reads the workspace, but does not write to it
+  let members_path = format!("/api/workspace/{}/member", workspace_id);
+  request_with_api_key(&owner, &created.api_key, Method::GET, &members_path)
+    .await
+    .unwrap();
+  let publish_path = format!("/api/workspace/{}/publish", workspace_id);
+  let error = request_with_api_key(&owner, &created.api_key, Method::POST, &publish_path)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+
+  // a key does not reach the other workspaces
+  let other_members_path = format!("/api/workspace/{}/member", member.workspace_id().await);
+  let error = request_with_api_key(&owner, &created.api_key, Method::GET, &other_members_path)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+
+  // rotating the key revokes the old one
+  let rotated = owner
+    .api_client
+    .rotate_service_account_api_key(&workspace_id, &service_account_id)
+    .await
+    .unwrap();
+  let error = request_with_api_key(&owner, &created.api_key, Method::GET, &members_path)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::UserUnAuthorized);
+  request_with_api_key(&owner, &rotated.api_key, Method::GET, &members_path)
+    .await
+    .unwrap();
+
+  let service_accounts = owner
+    .api_client
+    .list_service_accounts(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(service_accounts.len(), 1);
+  let api_keys = &service_accounts[0].api_keys;
+  assert_eq!(api_keys.len(), 2);
+  assert!(api_keys
+    .iter()
+    .any(|key| key.api_key_id == created.api_key_id && key.revoked_at.is_some()));
+  assert!(api_keys
+    .iter()
+    .any(|key| key.api_key_id == rotated.api_key_id && key.revoked_at.is_none()));
+
+  owner
+    .api_client
+    .revoke_service_account_api_key(
+      &workspace_id,
+      &service_account_id,
+      &rotated.api_key_id.to_string(),
+    )
+    .await
+    .unwrap();
+  let error = request_with_api_key(&owner, &rotated.api_key, Method::GET, &members_path)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::UserUnAuthorized);
+
+  owner
+    .api_client
+    .delete_service_account(&workspace_id, &service_account_id)
+    .await
+    .unwrap();
+  let service_accounts = owner
+    .api_client
+    .list_service_accounts(&workspace_id)
+    .await
+    .unwrap();
+  assert!(service_accounts.is_empty());
+}
+
+#[tokio::test]
+async fn service_account_api_key_confinement_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let other = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let other_workspace_id = other.workspace_id().await;
+  let created = owner
+    .api_client
+    .create_service_account(
+      &workspace_id,
+      CreateServiceAccountParams {
+        name: "sync".to_string(),
+        scope: ApiKeyScope::Full,
+      },
+    )
+    .await
+    .unwrap();
+
+  request_with_api_key(
+    &owner,
+    &created.api_key,
+    Method::GET,
+    &format!("/api/file_storage/{}/usage", workspace_id),
+  )
+  .await
+  .unwrap();
+
+  // the routes scoped by another workspace are denied, whatever their scope
+  for path in [
+    format!("/api/file_storage/{}/usage", other_workspace_id),
+    format!(
+      "/api/workspace/v1/{}/collab/{}",
+      other_workspace_id, other_workspace_id
+    ),
+    format!("/api/search/{}", other_workspace_id),
+  ] {
+    let error = request_with_api_key(&owner, &created.api_key, Method::GET, &path)
+      .await
+      .unwrap_err();
+    assert_eq!(error.code, ErrorCode::NotEnoughPermissions, "{}", path);
+  }
+
+  // a key does not act as a user: it neither creates workspaces nor manages the account
+  for (method, path) in [
+    (Method::POST, "/api/workspace"),
+    (Method::GET, "/api/user/profile"),
+    (Method::POST, "/api/user/export"),
+    (Method::DELETE, "/api/user"),
+  ] {
+    let error = request_with_api_key(&owner, &created.api_key, method, path)
+      .await
+      .unwrap_err();
+    assert_eq!(error.code, ErrorCode::NotEnoughPermissions, "{}", path);
+  }
+  let workspaces = owner.api_client.get_workspaces().await.unwrap();
+  assert_eq!(workspaces.len(), 1);
+}
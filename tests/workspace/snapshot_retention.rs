@@ -0,0 +1,91 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use database_entity::dto::AFRole;
+use shared_entity::dto::workspace_dto::SnapshotRetentionPolicy;
+
+#[tokio::test]
+async fn workspace_snapshot_retention_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  // without an override, the workspace follows the default of the server
+  let retention = owner
+    .api_client
+    .get_workspace_snapshot_retention(&workspace_id)
+    .await
+    .unwrap();
+  assert!(retention.is_default);
+  assert!(retention.updated_at.is_none());
+
+  let policy = SnapshotRetentionPolicy {
+    keep_last: 5,
+    keep_days: 1,
+    hourly_days: 2,
+    daily_days: 14,
+    weekly_weeks: 8,
+  };
+
+  // only the owner manages the policy
+  let error = member
+    .api_client
+    .update_workspace_snapshot_retention(&workspace_id, policy.clone())
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::UserUnAuthorized);
+  let error = member
+    .api_client
+    .get_workspace_snapshot_retention(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::UserUnAuthorized);
+
+  for invalid_policy in [
+    SnapshotRetentionPolicy {
+      keep_last: 0,
+      ..policy.clone()
+    },
+    SnapshotRetentionPolicy {
+      daily_days: -1,
+      ..policy.clone()
+    },
+  ] {
+    let error = owner
+      .api_client
+      .update_workspace_snapshot_retention(&workspace_id, invalid_policy)
+      .await
+      .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidRequest);
+  }
+
+  let retention = owner
+    .api_client
+    .update_workspace_snapshot_retention(&workspace_id, policy.clone())
+    .await
+    .unwrap();
+  assert!(!retention.is_default);
+  assert_eq!(retention.policy, policy);
+  let retention = owner
+    .api_client
+    .get_workspace_snapshot_retention(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(retention.policy, policy);
+
+  // removing the override falls back to the default
+  owner
+    .api_client
+    .delete_workspace_snapshot_retention(&workspace_id)
+    .await
+    .unwrap();
+  let retention = owner
+    .api_client
+    .get_workspace_snapshot_retention(&workspace_id)
+    .await
+    .unwrap();
+  assert!(retention.is_default);
+}
@@ -418,5 +418,45 @@ async fn test_template_crud() {
   assert_eq!(resp.unwrap_err().code, ErrorCode::RecordNotFound);
 }
 
+#[tokio::test]
+async fn test_instantiate_template_requires_template() {
+  let (authorized_client, _) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&authorized_client).await;
+  authorized_client
+    .set_workspace_publish_namespace(&workspace_id, &Uuid::new_v4().to_string())
+    .await
+    .unwrap();
+  let view_id = Uuid::new_v4();
+  authorized_client
+    .publish_collabs::<TemplateMetadata, &[u8]>(
+      &workspace_id,
+      vec![PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id,
+          publish_name: view_id.to_string(),
+          metadata: TemplateMetadata {},
+        },
+        data: "yrs_encoded_data_1".as_bytes(),
+      }],
+    )
+    .await
+    .unwrap();
+
+  // A published view that is not a template cannot be instantiated
+  let err = authorized_client
+    .instantiate_template(&workspace_id, view_id, None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  // Users outside of the workspace cannot instantiate anything into it
+  let (other_client, _) = generate_unique_registered_user_client().await;
+  let err = other_client
+    .instantiate_template(&workspace_id, view_id, None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 struct TemplateMetadata {}
@@ -0,0 +1,125 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use database_entity::dto::AFRole;
+use shared_entity::dto::workspace_dto::{CreateWebhookParams, WebhookEvent};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn workspace_webhook_crud_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let webhook = owner
+    .api_client
+    .create_webhook(
+      &workspace_id,
+      CreateWebhookParams {
+        url: "https://example.com/appflowy".to_string(),
+        secret: "0123456789abcdef".to_string(),
+        events: vec![
+          WebhookEvent::CollabUpdated,
+          WebhookEvent::MemberAdded,
+          WebhookEvent::CollabUpdated,
+        ],
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(webhook.url, "https://example.com/appflowy");
+  assert_eq!(
+    webhook.events,
+    vec![WebhookEvent::CollabUpdated, WebhookEvent::MemberAdded]
+  );
+
+  // the url must be http(s) and the secret long enough to sign with
+  let error = owner
+    .api_client
+    .create_webhook(
+      &workspace_id,
+      CreateWebhookParams {
+        url: "ftp://example.com".to_string(),
+        secret: "0123456789abcdef".to_string(),
+        events: vec![],
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+  let error = owner
+    .api_client
+    .create_webhook(
+      &workspace_id,
+      CreateWebhookParams {
+        url: "https://example.com/appflowy".to_string(),
+        secret: "short".to_string(),
+        events: vec![],
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+  // nor can it target the internal network of the server
+  for url in [
+    "http://127.0.0.1:8000/hook",
+    "http://169.254.169.254/latest/meta-data",
+  ] {
+    let error = owner
+      .api_client
+      .create_webhook(
+        &workspace_id,
+        CreateWebhookParams {
+          url: url.to_string(),
+          secret: "0123456789abcdef".to_string(),
+          events: vec![],
+        },
+      )
+      .await
+      .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidRequest);
+  }
+
+  // only the owner manages the webhooks
+  let error = member
+    .api_client
+    .list_webhooks(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::UserUnAuthorized);
+
+  let webhooks = owner.api_client.list_webhooks(&workspace_id).await.unwrap();
+  assert_eq!(webhooks.len(), 1);
+  assert_eq!(webhooks[0].webhook_id, webhook.webhook_id);
+
+  let webhook_id = webhook.webhook_id.to_string();
+  let dead_letters = owner
+    .api_client
+    .list_webhook_dead_letters(&workspace_id, &webhook_id)
+    .await
+    .unwrap();
+  assert!(dead_letters.is_empty());
+  let error = owner
+    .api_client
+    .retry_webhook_dead_letter(&workspace_id, &webhook_id, 1)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+  let error = owner
+    .api_client
+    .list_webhook_dead_letters(&workspace_id, &Uuid::new_v4().to_string())
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+
+  owner
+    .api_client
+    .delete_webhook(&workspace_id, &webhook_id)
+    .await
+    .unwrap();
+  let webhooks = owner.api_client.list_webhooks(&workspace_id).await.unwrap();
+  assert!(webhooks.is_empty());
+}
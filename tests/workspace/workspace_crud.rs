@@ -114,3 +114,46 @@ async fn test_workspace_rename_and_icon_change() {
     assert_eq!(name, "new_name456");
   }
 }
+
+#[tokio::test]
+async fn workspace_storage_footprint_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let footprint = c
+    .get_workspace_storage_footprint(&workspace_id)
+    .await
+    .unwrap();
+
+  // A new workspace contains at least its folder and the getting started document
+  assert!(footprint.object_count > 0);
+  assert!(footprint
+    .by_collab_type
+    .iter()
+    .any(|f| f.collab_type == CollabType::Folder && f.object_count == 1));
+  assert_eq!(
+    footprint.total_bytes,
+    footprint
+      .by_collab_type
+      .iter()
+      .map(|f| f.bytes)
+      .sum::<u64>()
+  );
+}
+
+#[tokio::test]
+async fn workspace_usage_metrics_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let usage = c.get_workspace_usage_metrics(&workspace_id).await.unwrap();
+
+  // A new workspace contains the getting started document, written by its owner
+  assert!(usage.document_count > 0);
+  assert!(usage.total_document_size > 0);
+  assert_eq!(usage.active_editor_count, 1);
+  assert!(usage.storage_bytes >= usage.total_document_size);
+  assert_eq!(usage.publish_count, 0);
+}
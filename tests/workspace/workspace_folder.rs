@@ -1,7 +1,15 @@
-use client_api::entity::{CreateCollabParams, QueryCollabParams};
-use client_api_test::generate_unique_registered_user_client;
+use app_error::ErrorCode;
+use client_api::entity::{
+  AFAccessLevel, AFRole, CreateCollabParams, InsertCollabMemberParams, QueryCollabParams,
+  SharePermission,
+};
+use client_api_test::{generate_unique_registered_user_client, TestClient};
 use collab::core::origin::CollabClient;
-use collab_folder::{CollabOrigin, Folder};
+use collab_folder::{CollabOrigin, Folder, RepeatedViewIdentifier, View};
+use shared_entity::dto::workspace_dto::{
+  FolderIntegrityIssue, FolderView, QueryFolderChildrenConnection, QueryWorkspaceFolder,
+  ViewLayout, WorkspaceFolderResult,
+};
 
 #[tokio::test]
 async fn get_workpace_folder() {
@@ -17,6 +25,8 @@ async fn get_workpace_folder() {
   assert_eq!(folder_view.name, "Workspace");
   assert_eq!(folder_view.children[0].name, "General");
   assert_eq!(folder_view.children[0].children.len(), 0);
+  assert!(folder_view.server_version.is_some());
+  assert!(folder_view.children[0].server_version.is_none());
   let folder_view = c
     .get_workspace_folder(&workspace_id, Some(2), None)
     .await
@@ -35,6 +45,183 @@ async fn get_workpace_folder() {
   assert_eq!(folder_view.children.len(), 2);
 }
 
+#[tokio::test]
+async fn get_workspace_folders_test() {
+  let (c_1, _user) = generate_unique_registered_user_client().await;
+  let workspace_id_1 = c_1.get_workspaces().await.unwrap()[0].workspace_id;
+  let (c_2, _user) = generate_unique_registered_user_client().await;
+  let workspace_id_2 = c_2.get_workspaces().await.unwrap()[0].workspace_id;
+
+  // the workspace given twice is returned once, and the workspace of another user fails alone
+  let folders = c_1
+    .get_workspace_folders(
+      vec![workspace_id_1, workspace_id_2, workspace_id_1],
+      Some(2),
+    )
+    .await
+    .unwrap();
+  assert_eq!(folders.len(), 2);
+  match &folders[&workspace_id_1.to_string()] {
+    WorkspaceFolderResult::Folder(folder_view) => {
+      assert_eq!(folder_view.name, "Workspace");
+      assert_eq!(folder_view.children[0].name, "General");
+      assert_eq!(folder_view.children[0].children.len(), 2);
+    },
+    WorkspaceFolderResult::Failed { error } => panic!("unexpected error: {}", error),
+  }
+  assert!(matches!(
+    folders[&workspace_id_2.to_string()],
+    WorkspaceFolderResult::Failed { .. }
+  ));
+
+  // the depth limit applies to every tree
+  let folders = c_1
+    .get_workspace_folders(vec![workspace_id_1], Some(1))
+    .await
+    .unwrap();
+  match &folders[&workspace_id_1.to_string()] {
+    WorkspaceFolderResult::Folder(folder_view) => {
+      assert!(folder_view.children[0].children.is_empty());
+    },
+    WorkspaceFolderResult::Failed { error } => panic!("unexpected error: {}", error),
+  }
+}
+
+#[tokio::test]
+async fn user_view_order_is_applied_to_folder() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id.to_string();
+
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view.children[0];
+  let original_order: Vec<String> = general_space
+    .children
+    .iter()
+    .map(|v| v.view_id.clone())
+    .collect();
+  assert_eq!(original_order.len(), 2);
+  let reversed_order: Vec<String> = original_order.iter().rev().cloned().collect();
+
+  c.set_user_view_order(
+    &workspace_id,
+    &general_space.view_id,
+    reversed_order.clone(),
+  )
+  .await
+  .unwrap();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let order: Vec<String> = folder_view.children[0]
+    .children
+    .iter()
+    .map(|v| v.view_id.clone())
+    .collect();
+  assert_eq!(order, reversed_order);
+
+  // an empty order resets to the folder's order
+  c.set_user_view_order(&workspace_id, &general_space.view_id, vec![])
+    .await
+    .unwrap();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let order: Vec<String> = folder_view.children[0]
+    .children
+    .iter()
+    .map(|v| v.view_id.clone())
+    .collect();
+  assert_eq!(order, original_order);
+
+  let too_many: Vec<String> = (0..1001).map(|i| format!("view-{}", i)).collect();
+  let err = c
+    .set_user_view_order(&workspace_id, &general_space.view_id, too_many)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn get_workspace_folder_with_filter() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let general_space_id = c
+    .get_workspace_folder(&workspace_id, Some(1), None)
+    .await
+    .unwrap()
+    .children[0]
+    .view_id
+    .clone();
+
+  let folder_view = c
+    .get_workspace_folder_with_query(
+      &workspace_id,
+      &QueryWorkspaceFolder {
+        depth: Some(3),
+        layouts: Some("0".to_string()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  fn assert_leaves_are_documents(view: &FolderView) {
+    for child in &view.children {
+      if child.children.is_empty() {
+        assert_eq!(child.layout, ViewLayout::Document, "{}", child.name);
+      }
+      assert_leaves_are_documents(child);
+    }
+  }
+  assert_leaves_are_documents(&folder_view);
+
+  let folder_view = c
+    .get_workspace_folder_with_query(
+      &workspace_id,
+      &QueryWorkspaceFolder {
+        depth: Some(1),
+        space_id: Some(general_space_id.clone()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(folder_view.children.len(), 1);
+  assert_eq!(folder_view.children[0].view_id, general_space_id);
+
+  let folder_view = c
+    .get_workspace_folder_with_query(
+      &workspace_id,
+      &QueryWorkspaceFolder {
+        depth: Some(2),
+        include_private: Some(false),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert!(folder_view.children.iter().all(|view| !view.is_private));
+
+  let err = c
+    .get_workspace_folder_with_query(
+      &workspace_id,
+      &QueryWorkspaceFolder {
+        layouts: Some("9".to_string()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
 #[tokio::test]
 async fn get_section_items() {
   let (c, _user) = generate_unique_registered_user_client().await;
@@ -96,3 +283,604 @@ async fn get_section_items() {
   assert_eq!(recent_section_items.views.len(), 1);
   assert_eq!(recent_section_items.views[0].view_id, recent_id);
 }
+
+#[tokio::test]
+async fn move_view_to_trash_and_restore() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id.to_string();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let view_id = folder_view.children[0].children[0].view_id.clone();
+
+  c.move_views_to_trash(&workspace_id, vec![view_id.clone()])
+    .await
+    .unwrap();
+  let trash = c.get_workspace_trash(&workspace_id).await.unwrap();
+  assert_eq!(trash.views.len(), 1);
+  assert_eq!(trash.views[0].view_id, view_id);
+
+  c.restore_views_from_trash(&workspace_id, vec![view_id.clone()])
+    .await
+    .unwrap();
+  let trash = c.get_workspace_trash(&workspace_id).await.unwrap();
+  assert!(trash.views.is_empty());
+
+  let err = c
+    .move_views_to_trash(&workspace_id, vec!["not-a-view".to_string()])
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn record_visits_and_toggle_favorites() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id.to_string();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let first_view = &folder_view.children[0].children[0];
+  let second_view = &folder_view.children[0].children[1];
+
+  c.record_view_visit(&workspace_id, &first_view.view_id)
+    .await
+    .unwrap();
+  c.record_view_visit(&workspace_id, &second_view.view_id)
+    .await
+    .unwrap();
+  c.record_view_visit(&workspace_id, &first_view.view_id)
+    .await
+    .unwrap();
+  let recent = c.get_workspace_recent(&workspace_id).await.unwrap();
+  let recent_ids: Vec<&str> = recent.views.iter().map(|v| v.view_id.as_str()).collect();
+  assert_eq!(
+    recent_ids,
+    vec![second_view.view_id.as_str(), first_view.view_id.as_str()]
+  );
+  assert_eq!(recent.views[1].name, first_view.name);
+
+  c.set_view_favorite(&workspace_id, &second_view.view_id, true)
+    .await
+    .unwrap();
+  let favorite = c.get_workspace_favorite(&workspace_id).await.unwrap();
+  assert_eq!(favorite.views.len(), 1);
+  assert_eq!(favorite.views[0].view_id, second_view.view_id);
+  assert_eq!(favorite.views[0].name, second_view.name);
+
+  c.set_view_favorite(&workspace_id, &second_view.view_id, false)
+    .await
+    .unwrap();
+  let favorite = c.get_workspace_favorite(&workspace_id).await.unwrap();
+  assert!(favorite.views.is_empty());
+
+  let err = c
+    .set_view_favorite(&workspace_id, "not-a-view", true)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn preview_share_impact_of_space() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let (other, _) = generate_unique_registered_user_client().await;
+  let other_uid = other.get_profile().await.unwrap().uid;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id.to_string();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view.children[0];
+
+  let impacts = c
+    .preview_share_impact(
+      &workspace_id,
+      &general_space.view_id,
+      other_uid,
+      AFAccessLevel::ReadOnly,
+    )
+    .await
+    .unwrap();
+  let impacted_ids: Vec<String> = impacts.iter().map(|i| i.object_id.clone()).collect();
+  assert_eq!(impacted_ids[0], general_space.view_id);
+  for child in &general_space.children {
+    assert!(impacted_ids.contains(&child.view_id));
+  }
+  assert!(impacts
+    .iter()
+    .all(|i| i.access_level == AFAccessLevel::ReadOnly && i.current_access_level.is_none()));
+}
+
+#[tokio::test]
+async fn preview_share_impact_requires_full_access() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let folder_view = owner
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view.children[0];
+
+  // a member without full access can only preview what it would gain itself
+  member
+    .api_client
+    .preview_share_impact(
+      &workspace_id,
+      &general_space.view_id,
+      member.uid().await,
+      AFAccessLevel::FullAccess,
+    )
+    .await
+    .unwrap();
+  let err = member
+    .api_client
+    .preview_share_impact(
+      &workspace_id,
+      &general_space.view_id,
+      owner.uid().await,
+      AFAccessLevel::ReadOnly,
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn search_workspace_folder_by_name() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id.to_string();
+
+  let results = c
+    .search_folder_views(&workspace_id, "GETTING", false)
+    .await
+    .unwrap();
+  assert_eq!(results.len(), 1);
+  assert_eq!(results[0].name, "Getting started");
+  assert!(results[0].is_match);
+  assert_eq!(results[0].path.len(), 1);
+  assert_eq!(results[0].path[0].name, "General");
+
+  // ancestors are returned once, before their matching descendants
+  let results = c
+    .search_folder_views(&workspace_id, "guide", true)
+    .await
+    .unwrap();
+  let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+  assert_eq!(
+    names,
+    vec![
+      "General",
+      "Getting started",
+      "Desktop guide",
+      "Mobile guide"
+    ]
+  );
+  assert!(!results[0].is_match);
+  assert!(!results[1].is_match);
+  assert!(results[2].is_match && results[3].is_match);
+  assert_eq!(results[3].path.len(), 2);
+
+  let results = c
+    .search_folder_views(&workspace_id, "no view has this name", true)
+    .await
+    .unwrap();
+  assert!(results.is_empty());
+}
+
+#[tokio::test]
+async fn get_workspace_folder_with_permissions() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id.to_string();
+
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  assert!(folder_view.permissions.is_none());
+
+  // the owner of the workspace can do everything on every view
+  let folder_view = c
+    .get_workspace_folder_with_permissions(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let mut stack = vec![&folder_view];
+  while let Some(view) = stack.pop() {
+    let permissions = view.permissions.unwrap();
+    assert!(permissions.can_read && permissions.can_comment && permissions.can_edit);
+    assert!(permissions.can_share && permissions.can_delete);
+    stack.extend(view.children.iter());
+  }
+}
+
+#[tokio::test]
+async fn paginate_folder_children_connection() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id.to_string();
+
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view.children[0];
+  let child_ids: Vec<String> = general_space
+    .children
+    .iter()
+    .map(|v| v.view_id.clone())
+    .collect();
+  assert_eq!(child_ids.len(), 2);
+
+  let first_page = c
+    .get_folder_children_connection(&workspace_id, &general_space.view_id, Some(1), None)
+    .await
+    .unwrap();
+  assert_eq!(first_page.edges.len(), 1);
+  assert_eq!(first_page.edges[0].node.view_id, child_ids[0]);
+  assert!(first_page.edges[0].node.children.is_empty());
+  assert!(first_page.page_info.has_next_page);
+
+  let second_page = c
+    .get_folder_children_connection(
+      &workspace_id,
+      &general_space.view_id,
+      Some(1),
+      first_page.page_info.end_cursor,
+    )
+    .await
+    .unwrap();
+  assert_eq!(second_page.edges.len(), 1);
+  assert_eq!(second_page.edges[0].node.view_id, child_ids[1]);
+  assert!(!second_page.page_info.has_next_page);
+
+  let err = c
+    .get_folder_children_connection(
+      &workspace_id,
+      &general_space.view_id,
+      Some(1),
+      Some("unknown cursor".to_string()),
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, app_error::ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn filter_folder_children_connection_by_layout() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view.children[0];
+  let layout = general_space.children[0].layout.clone();
+
+  let page = c
+    .get_folder_children_connection_with_query(
+      &workspace_id,
+      &general_space.view_id,
+      &QueryFolderChildrenConnection {
+        first: Some(50),
+        layouts: Some((layout.clone() as u8).to_string()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert!(!page.edges.is_empty());
+  for edge in page.edges {
+    assert!(edge.node.layout == layout || edge.has_children);
+  }
+}
+
+#[tokio::test]
+async fn get_folder_view_ancestors() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view.children[0];
+  let child = &general_space.children[0];
+
+  let ancestors = c
+    .get_folder_view_ancestors(&workspace_id, &child.view_id)
+    .await
+    .unwrap();
+  assert_eq!(ancestors.len(), 1);
+  assert_eq!(ancestors[0].view_id, general_space.view_id);
+  assert_eq!(ancestors[0].name, general_space.name);
+  assert!(ancestors[0].is_space);
+
+  let ancestors = c
+    .get_folder_view_ancestors(&workspace_id, &general_space.view_id)
+    .await
+    .unwrap();
+  assert!(ancestors.is_empty());
+
+  let err = c
+    .get_folder_view_ancestors(&workspace_id, "unknown view")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+fn folder_test_view(view_id: &str, parent_view_id: &str) -> View {
+  View {
+    id: view_id.to_string(),
+    parent_view_id: parent_view_id.to_string(),
+    name: view_id.to_string(),
+    desc: "".to_string(),
+    children: RepeatedViewIdentifier { items: vec![] },
+    created_at: 0,
+    is_favorite: false,
+    layout: collab_folder::ViewLayout::Document,
+    icon: None,
+    created_by: None,
+    last_edited_time: 0,
+    last_edited_by: None,
+    extra: None,
+  }
+}
+
+#[tokio::test]
+async fn check_and_repair_folder_integrity() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let report = owner
+    .api_client
+    .check_folder_integrity(&workspace_id)
+    .await
+    .unwrap();
+  assert!(report.is_healthy());
+
+  // break the folder the way a bad migration would: a view whose parent does not exist, and two
+  // views that are each other's parent
+  let folder_collab = owner
+    .api_client
+    .get_collab(QueryCollabParams::new(
+      workspace_id.clone(),
+      collab_entity::CollabType::Folder,
+      workspace_id.clone(),
+    ))
+    .await
+    .unwrap()
+    .encode_collab;
+  let uid = owner.uid().await;
+  let mut folder = Folder::from_collab_doc_state(
+    uid,
+    CollabOrigin::Client(CollabClient::new(uid, owner.api_client.device_id.clone())),
+    folder_collab.into(),
+    &workspace_id,
+    vec![],
+  )
+  .unwrap();
+  folder.insert_view(folder_test_view("orphan", "missing-parent"), None);
+  folder.insert_view(folder_test_view("cycle-a", "cycle-b"), None);
+  folder.insert_view(folder_test_view("cycle-b", "cycle-a"), None);
+  let collab_type = collab_entity::CollabType::Folder;
+  owner
+    .api_client
+    .update_collab(CreateCollabParams {
+      workspace_id: workspace_id.clone(),
+      collab_type: collab_type.clone(),
+      object_id: workspace_id.clone(),
+      encoded_collab_v1: folder
+        .encode_collab_v1(|collab| collab_type.validate_require_data(collab))
+        .unwrap()
+        .encode_to_bytes()
+        .unwrap(),
+    })
+    .await
+    .unwrap();
+
+  let report = owner
+    .api_client
+    .check_folder_integrity(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(report.issues.len(), 2);
+  assert!(report.issues.contains(&FolderIntegrityIssue::Orphan {
+    view_id: "orphan".to_string(),
+    parent_view_id: "missing-parent".to_string(),
+  }));
+  assert!(report.issues.iter().any(|issue| matches!(
+    issue,
+    FolderIntegrityIssue::Cycle { view_ids } if view_ids.len() == 2
+  )));
+
+  // only the owner can look at or repair the folder
+  let err = member
+    .api_client
+    .repair_folder_integrity(&workspace_id, true)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  // the repair must be confirmed
+  let err = owner
+    .api_client
+    .repair_folder_integrity(&workspace_id, false)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  let report = owner
+    .api_client
+    .check_folder_integrity(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(report.issues.len(), 2);
+
+  let report = owner
+    .api_client
+    .repair_folder_integrity(&workspace_id, true)
+    .await
+    .unwrap();
+  assert_eq!(report.issues.len(), 2);
+  let report = owner
+    .api_client
+    .check_folder_integrity(&workspace_id)
+    .await
+    .unwrap();
+  assert!(report.is_healthy());
+  let folder_view = owner
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(1), None)
+    .await
+    .unwrap();
+  assert!(folder_view
+    .children
+    .iter()
+    .any(|view| view.view_id == "orphan"));
+}
+
+#[tokio::test]
+async fn delete_object_moves_its_children_to_its_parent() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let folder_view = owner
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(3), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view.children[0];
+  let view = &general_space.children[0];
+  let child_ids: Vec<String> = view
+    .children
+    .iter()
+    .map(|child| child.view_id.clone())
+    .collect();
+  assert!(!child_ids.is_empty());
+
+  let err = member
+    .api_client
+    .delete_object(&workspace_id, &view.view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  // the share links, object tokens and group access of the view go with it
+  owner
+    .api_client
+    .create_collab_share_link(
+      &workspace_id,
+      &view.view_id,
+      SharePermission::View,
+      None,
+      None,
+    )
+    .await
+    .unwrap();
+  let object_token = owner
+    .api_client
+    .create_collab_object_token(&workspace_id, &view.view_id, AFAccessLevel::ReadOnly, None)
+    .await
+    .unwrap();
+  let group = owner
+    .api_client
+    .create_workspace_group(&workspace_id, "readers")
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .add_collab_member(InsertCollabMemberParams {
+      uid: 0,
+      workspace_id: workspace_id.clone(),
+      object_id: view.view_id.clone(),
+      access_level: AFAccessLevel::ReadOnly,
+      expires_at: None,
+      group_id: Some(group.group_id),
+    })
+    .await
+    .unwrap();
+
+  let summary = owner
+    .api_client
+    .delete_object(&workspace_id, &view.view_id)
+    .await
+    .unwrap();
+  assert!(summary.removed_from_folder);
+  assert_eq!(summary.reparented_view_ids, child_ids);
+
+  // the children take the place of the deleted view, in the same order
+  let folder_view = owner
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let sibling_ids: Vec<String> = folder_view.children[0]
+    .children
+    .iter()
+    .map(|view| view.view_id.clone())
+    .collect();
+  assert!(!sibling_ids.contains(&view.view_id));
+  assert_eq!(sibling_ids[..child_ids.len()], child_ids[..]);
+  let report = owner
+    .api_client
+    .check_folder_integrity(&workspace_id)
+    .await
+    .unwrap();
+  assert!(report.is_healthy());
+
+  let result = owner
+    .api_client
+    .get_collab(QueryCollabParams::new(
+      view.view_id.clone(),
+      collab_entity::CollabType::Document,
+      workspace_id.clone(),
+    ))
+    .await;
+  assert!(result.is_err());
+  let share_links = owner
+    .api_client
+    .list_collab_share_links(&workspace_id, &view.view_id)
+    .await
+    .unwrap();
+  assert!(share_links.is_empty());
+  let result = owner
+    .api_client
+    .get_collab_with_object_token(
+      &object_token.token,
+      &view.view_id,
+      collab_entity::CollabType::Document,
+    )
+    .await;
+  assert!(result.is_err());
+  let err = owner
+    .api_client
+    .remove_collab_group_access(&workspace_id, &view.view_id, &group.group_id.to_string())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}